@@ -0,0 +1,116 @@
+//! Indexing the files of a project
+
+use crate::options::Config;
+use crate::project::{Project, ProjectOptions};
+use crate::search::SearchClient;
+use crate::watcher::WatcherBuilder;
+use anyhow::Result;
+use semantiq_index::AutoIndexer;
+use semantiq_retrieval::{BOOTSTRAP_SAMPLE_SIZE, bootstrap_thresholds};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Configures an [`Indexer`]
+#[derive(Debug, Clone)]
+pub struct IndexerBuilder {
+    options: ProjectOptions,
+}
+
+impl IndexerBuilder {
+    /// Database of the index, `.semantiq.db` at the project root by default
+    pub fn database(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.database = Some(path.into());
+        self
+    }
+
+    /// Configuration used instead of the `semantiq.toml` of the project
+    pub fn config(mut self, config: Config) -> Self {
+        self.options.config = Some(config.into_inner());
+        self
+    }
+
+    /// Open the index, creating it if needed, and load the embedding model
+    /// of the index (downloading it on first use)
+    pub fn build(self) -> Result<Indexer> {
+        let project = self.options.open()?;
+        // An index built by another parser version is rebuilt by the next pass
+        project.store.check_and_prepare_for_reindex()?;
+
+        let indexer = AutoIndexer::from_config(
+            Arc::clone(&project.store),
+            project.root.clone(),
+            &project.config,
+        )?;
+
+        Ok(Indexer {
+            project,
+            indexer: Arc::new(indexer),
+        })
+    }
+}
+
+/// Indexes the files of a project. Files are watched from the creation of
+/// the indexer; a [`Watcher`](crate::Watcher) applies their changes.
+pub struct Indexer {
+    project: Project,
+    indexer: Arc<AutoIndexer>,
+}
+
+/// Outcome of [`Indexer::index`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IndexSummary {
+    /// Files of the project considered
+    pub scanned: usize,
+    /// Files indexed because they were new or changed
+    pub indexed: usize,
+    /// Files already up to date
+    pub skipped: usize,
+    /// Files that failed to index
+    pub errors: usize,
+}
+
+impl Indexer {
+    pub fn builder(project_root: impl Into<PathBuf>) -> IndexerBuilder {
+        IndexerBuilder {
+            options: ProjectOptions::new(project_root),
+        }
+    }
+
+    /// Root of the project
+    pub fn project_root(&self) -> &std::path::Path {
+        &self.project.root
+    }
+
+    /// Index the files of the project that are new or changed since the
+    /// last pass, and remove the deleted ones. Waits while another process,
+    /// such as `semantiq serve`, writes the index.
+    pub fn index(&self) -> Result<IndexSummary> {
+        let result = self.indexer.initial_index()?;
+
+        // Seed semantic thresholds until searches calibrate them
+        if result.indexed > 0
+            && let Err(e) = bootstrap_thresholds(&self.project.store, BOOTSTRAP_SAMPLE_SIZE)
+        {
+            warn!("Threshold bootstrap failed: {}", e);
+        }
+
+        Ok(IndexSummary {
+            scanned: result.scanned,
+            indexed: result.indexed,
+            skipped: result.skipped,
+            errors: result.errors,
+        })
+    }
+
+    /// Configure a watcher applying file changes to the index
+    pub fn watcher(&self) -> WatcherBuilder {
+        WatcherBuilder::new(Arc::clone(&self.indexer))
+    }
+
+    /// Open a search client on the index
+    pub fn search_client(&self) -> Result<SearchClient> {
+        SearchClient::open(self.project.clone())
+    }
+}
@@ -0,0 +1,133 @@
+//! Stable Rust API to index and search a project with Semantiq, for tools
+//! embedding it without running the `semantiq` CLI.
+//!
+//! - [`Indexer`] indexes the files of a project into its database
+//! - [`Watcher`] keeps the index up to date as files change
+//! - [`SearchClient`] searches the index
+//!
+//! Each is configured with a builder. By default the database is
+//! `.semantiq.db` at the project root (as for the CLI) and the configuration
+//! is read from its `semantiq.toml`:
+//!
+//! ```no_run
+//! use semantiq_core::{Indexer, SearchOptions};
+//! use std::time::Duration;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let indexer = Indexer::builder("/path/to/project").build()?;
+//! let summary = indexer.index()?;
+//! println!("{} files indexed", summary.indexed);
+//!
+//! let _watcher = indexer
+//!     .watcher()
+//!     .poll_interval(Duration::from_secs(1))
+//!     .on_update(|update| println!("{} files reindexed", update.indexed))
+//!     .start()?;
+//!
+//! let client = indexer.search_client()?;
+//! let results = client.search("retry failed uploads", 10, SearchOptions::default())?;
+//! for result in &results.results {
+//!     println!("{}:{} {:.2}", result.file_path, result.start_line, result.score);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Stability
+//!
+//! This crate has its own version, apart from the `semantiq` release it
+//! ships with, and follows semver: breaking changes to the types it defines
+//! bump its major version (its minor version while it is 0.x). CI runs
+//! `cargo semver-checks` on it against the base of every change. The other
+//! `semantiq-*` crates, the database schema and the layout of the index are
+//! internal and may change in any release; none of their types appear in
+//! this API, and options and results are converted at the boundary.
+//! Options, results and summaries are `#[non_exhaustive]`, so new fields and
+//! variants are not breaking changes, and options are set with their
+//! `with_*` methods.
+
+mod indexer;
+mod options;
+mod project;
+mod results;
+mod search;
+mod watcher;
+
+pub use indexer::{IndexSummary, Indexer, IndexerBuilder};
+pub use options::{Config, FindRefsOptions, RefKind, SearchOptions};
+pub use results::{
+    DependencyInfo, SearchResult, SearchResultKind, SearchResults, SymbolDefinition,
+    SymbolExplanation,
+};
+pub use search::{SearchClient, SearchClientBuilder};
+pub use watcher::{WatchUpdate, Watcher, WatcherBuilder};
+
+/// Name of the database created at the project root when no other is set
+pub const DEFAULT_DATABASE_NAME: &str = ".semantiq.db";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_index_watch_and_search() {
+        // Events under hidden directories such as `.tmpXXXX` are ignored
+        let dir = tempfile::Builder::new()
+            .prefix("semantiq-core")
+            .tempdir()
+            .unwrap();
+        std::fs::write(
+            dir.path().join("config.rs"),
+            "pub fn parse_config(text: &str) -> usize {\n    text.len()\n}\n",
+        )
+        .unwrap();
+
+        let indexer = Indexer::builder(dir.path()).build().unwrap();
+        let summary = indexer.index().unwrap();
+        assert_eq!(summary.indexed, 1);
+        assert!(dir.path().join(DEFAULT_DATABASE_NAME).exists());
+
+        let client = indexer.search_client().unwrap();
+        let results = client
+            .search("parse_config", 10, SearchOptions::default())
+            .unwrap();
+        assert!(results.results.iter().any(|r| r.file_path == "config.rs"));
+        let explanation = client.explain_symbol("parse_config").unwrap();
+        assert!(explanation.found);
+        assert_eq!(explanation.definitions[0].file_path, "config.rs");
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = indexer
+            .watcher()
+            .poll_interval(Duration::from_millis(50))
+            .on_update(move |update| {
+                let _ = tx.send(update.indexed);
+            })
+            .start()
+            .unwrap();
+        std::fs::write(
+            dir.path().join("upload.rs"),
+            "pub fn retry_upload(attempts: u32) -> bool {\n    attempts < 3\n}\n",
+        )
+        .unwrap();
+        assert!(rx.recv_timeout(Duration::from_secs(10)).unwrap() >= 1);
+
+        // A client opened on its own sees the files of the watcher, once the
+        // events of the whole write are applied
+        let client = SearchClient::builder(dir.path()).build().unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        loop {
+            let refs = client
+                .find_references("retry_upload", &FindRefsOptions::default())
+                .unwrap();
+            if refs.results.iter().any(|r| r.file_path == "upload.rs") {
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "file not indexed");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        watcher.stop();
+    }
+}
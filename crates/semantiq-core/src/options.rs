@@ -0,0 +1,261 @@
+//! Configuration and search options, converted to those of the internal
+//! crates when used
+
+use anyhow::Result;
+use semantiq_index::SemantiqConfig;
+use semantiq_retrieval as retrieval;
+use std::path::Path;
+use std::time::Duration;
+
+/// Configuration of a project, as read from its `semantiq.toml`
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    inner: SemantiqConfig,
+}
+
+impl Config {
+    /// Load `semantiq.toml` from the project root, or the defaults if it
+    /// does not exist
+    pub fn load(project_root: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            inner: SemantiqConfig::load(project_root.as_ref())?,
+        })
+    }
+
+    /// Parse the content of a `semantiq.toml`
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(Self {
+            inner: SemantiqConfig::parse(content)?,
+        })
+    }
+
+    pub(crate) fn into_inner(self) -> SemantiqConfig {
+        self.inner
+    }
+}
+
+/// Options of [`SearchClient::search`](crate::SearchClient::search)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct SearchOptions {
+    /// Minimum score (0.0-1.0), the engine default when `None`
+    pub min_score: Option<f32>,
+    /// File extensions searched (e.g. `rs`), all source files when `None`
+    pub file_types: Option<Vec<String>>,
+    /// Symbol kinds returned (e.g. `function`), all when `None`
+    pub symbol_kinds: Option<Vec<String>>,
+    /// Workspace packages searched, all when `None`
+    pub packages: Option<Vec<String>>,
+    /// Search the indexed sources of external dependencies too
+    pub include_external: bool,
+    /// Time budget of the search, `search.timeout_ms` when `None`
+    pub timeout: Option<Duration>,
+    /// Lines of the file added above and below the content of each result
+    pub context_lines: usize,
+    /// Expand the content of each result to the whole symbol enclosing it
+    pub expand_to_symbol: bool,
+    /// Search generated files too
+    pub include_generated: bool,
+    /// Leave out text matches inside comments
+    pub exclude_comments: bool,
+    /// Leave out text matches inside string literals
+    pub exclude_strings: bool,
+}
+
+impl SearchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    pub fn with_file_types(mut self, file_types: Vec<String>) -> Self {
+        self.file_types = Some(file_types);
+        self
+    }
+
+    pub fn with_symbol_kinds(mut self, symbol_kinds: Vec<String>) -> Self {
+        self.symbol_kinds = Some(symbol_kinds);
+        self
+    }
+
+    pub fn with_packages(mut self, packages: Vec<String>) -> Self {
+        self.packages = Some(packages);
+        self
+    }
+
+    pub fn with_include_external(mut self, include: bool) -> Self {
+        self.include_external = include;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_context_lines(mut self, lines: usize) -> Self {
+        self.context_lines = lines;
+        self
+    }
+
+    pub fn with_expand_to_symbol(mut self, expand: bool) -> Self {
+        self.expand_to_symbol = expand;
+        self
+    }
+
+    pub fn with_include_generated(mut self, include: bool) -> Self {
+        self.include_generated = include;
+        self
+    }
+
+    pub fn with_exclude_comments(mut self, exclude: bool) -> Self {
+        self.exclude_comments = exclude;
+        self
+    }
+
+    pub fn with_exclude_strings(mut self, exclude: bool) -> Self {
+        self.exclude_strings = exclude;
+        self
+    }
+
+    pub(crate) fn to_engine(&self) -> retrieval::SearchOptions {
+        let mut options = retrieval::SearchOptions::new()
+            .with_context_lines(self.context_lines)
+            .with_expand_to_symbol(self.expand_to_symbol)
+            .with_include_generated(self.include_generated)
+            .with_exclude_comments(self.exclude_comments)
+            .with_exclude_strings(self.exclude_strings);
+        if let Some(min_score) = self.min_score {
+            options = options.with_min_score(min_score);
+        }
+        if let Some(ref file_types) = self.file_types {
+            options = options.with_file_types(file_types.clone());
+        }
+        if let Some(ref symbol_kinds) = self.symbol_kinds {
+            options = options.with_symbol_kinds(symbol_kinds.clone());
+        }
+        if let Some(ref packages) = self.packages {
+            options = options.with_packages(packages.clone());
+        }
+        if self.include_external {
+            options = options.with_scope(retrieval::SearchScope::All);
+        }
+        if let Some(timeout) = self.timeout {
+            options = options.with_deadline(retrieval::Deadline::new().with_timeout(timeout));
+        }
+        options
+    }
+}
+
+/// Which references [`SearchClient::find_references`](crate::SearchClient::find_references)
+/// returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RefKind {
+    /// Symbol definitions only
+    Definition,
+    /// Usages only
+    Usage,
+    /// Both definitions and usages
+    #[default]
+    All,
+}
+
+/// Options of [`SearchClient::find_references`](crate::SearchClient::find_references)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FindRefsOptions {
+    /// Maximum number of results
+    pub limit: usize,
+    /// Definitions, usages or both
+    pub kind: RefKind,
+    /// Directory or file, relative to the project root, the references are
+    /// found under
+    pub path_prefix: Option<String>,
+    /// Leave out test files and directories
+    pub exclude_tests: bool,
+    /// Leave out usages inside comments
+    pub exclude_comments: bool,
+    /// Leave out usages inside string literals
+    pub exclude_strings: bool,
+    /// Also find the references of the symbols bound to this one in other
+    /// languages (napi-rs, wasm-bindgen and PyO3 bindings)
+    pub cross_language: bool,
+}
+
+impl Default for FindRefsOptions {
+    fn default() -> Self {
+        Self {
+            limit: retrieval::FindRefsOptions::DEFAULT_LIMIT,
+            kind: RefKind::All,
+            path_prefix: None,
+            exclude_tests: false,
+            exclude_comments: false,
+            exclude_strings: false,
+            cross_language: false,
+        }
+    }
+}
+
+impl FindRefsOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: RefKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_path_prefix(mut self, prefix: &str) -> Self {
+        self.path_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn with_exclude_tests(mut self, exclude: bool) -> Self {
+        self.exclude_tests = exclude;
+        self
+    }
+
+    pub fn with_exclude_comments(mut self, exclude: bool) -> Self {
+        self.exclude_comments = exclude;
+        self
+    }
+
+    pub fn with_exclude_strings(mut self, exclude: bool) -> Self {
+        self.exclude_strings = exclude;
+        self
+    }
+
+    pub fn with_cross_language(mut self, follow: bool) -> Self {
+        self.cross_language = follow;
+        self
+    }
+
+    pub(crate) fn to_engine(&self) -> retrieval::FindRefsOptions {
+        let mut options = retrieval::FindRefsOptions::new()
+            .with_limit(self.limit)
+            .with_kind(match self.kind {
+                RefKind::Definition => retrieval::RefKind::Definition,
+                RefKind::Usage => retrieval::RefKind::Usage,
+                RefKind::All => retrieval::RefKind::All,
+            })
+            .excluding_tests(self.exclude_tests)
+            .excluding_comments(self.exclude_comments)
+            .excluding_strings(self.exclude_strings)
+            .following_cross_language(self.cross_language);
+        if let Some(ref prefix) = self.path_prefix {
+            options = options.with_path_prefix(prefix);
+        }
+        options
+    }
+}
@@ -0,0 +1,69 @@
+//! Opening the index of a project, shared by the builders
+
+use crate::DEFAULT_DATABASE_NAME;
+use anyhow::{Context, Result};
+use semantiq_index::{IndexStore, SemantiqConfig};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Project root, database and configuration set on a builder
+#[derive(Debug, Clone)]
+pub(crate) struct ProjectOptions {
+    pub root: PathBuf,
+    pub database: Option<PathBuf>,
+    pub config: Option<SemantiqConfig>,
+}
+
+impl ProjectOptions {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            database: None,
+            config: None,
+        }
+    }
+
+    /// Open the database of the project, applying its configuration
+    pub fn open(self) -> Result<Project> {
+        let root = self
+            .root
+            .canonicalize()
+            .with_context(|| format!("Project root {:?} not found", self.root))?;
+        let config = match self.config {
+            Some(config) => config,
+            None => SemantiqConfig::load(&root)?,
+        };
+        if let Err(e) = config.register_languages(&root) {
+            warn!("Custom languages or symbol kinds disabled: {:#}", e);
+        }
+
+        let database = self
+            .database
+            .unwrap_or_else(|| root.join(DEFAULT_DATABASE_NAME));
+        let store = Arc::new(IndexStore::open(&database)?);
+        store.apply_config(&config)?;
+
+        Ok(Project {
+            root,
+            store,
+            config,
+        })
+    }
+}
+
+/// Open index of a project
+#[derive(Clone)]
+pub(crate) struct Project {
+    pub root: PathBuf,
+    pub store: Arc<IndexStore>,
+    pub config: SemantiqConfig,
+}
+
+impl Project {
+    pub fn root_str(&self) -> Result<&str> {
+        self.root
+            .to_str()
+            .context("Project root path contains invalid UTF-8")
+    }
+}
@@ -0,0 +1,191 @@
+//! Results of a [`SearchClient`](crate::SearchClient), converted from those
+//! of the internal crates
+
+use semantiq_retrieval as retrieval;
+use std::time::Duration;
+
+/// What a search result matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SearchResultKind {
+    /// A symbol definition
+    Symbol,
+    /// Text of a file
+    TextMatch,
+    /// A chunk of code close in meaning to the query
+    SemanticMatch,
+    /// A usage of a symbol
+    Reference,
+}
+
+impl SearchResultKind {
+    pub(crate) fn from_engine(kind: retrieval::SearchResultKind) -> Self {
+        match kind {
+            retrieval::SearchResultKind::Symbol => SearchResultKind::Symbol,
+            retrieval::SearchResultKind::TextMatch => SearchResultKind::TextMatch,
+            retrieval::SearchResultKind::SemanticMatch => SearchResultKind::SemanticMatch,
+            retrieval::SearchResultKind::Reference => SearchResultKind::Reference,
+        }
+    }
+}
+
+/// A piece of code found in the index
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SearchResult {
+    pub kind: SearchResultKind,
+    /// Path relative to the project root
+    pub file_path: String,
+    /// First line of `content`, 1-based
+    pub start_line: usize,
+    /// Last line of `content`, inclusive
+    pub end_line: usize,
+    pub content: String,
+    pub score: f32,
+    /// Symbol matched or enclosing the match
+    pub symbol_name: Option<String>,
+    pub symbol_kind: Option<String>,
+    pub signature: Option<String>,
+    /// The file changed since it was indexed; `content` was re-read from
+    /// disk but its lines may have shifted
+    pub stale: bool,
+}
+
+impl SearchResult {
+    pub(crate) fn from_engine(result: retrieval::SearchResult) -> Self {
+        Self {
+            kind: SearchResultKind::from_engine(result.kind),
+            file_path: result.file_path,
+            start_line: result.start_line,
+            end_line: result.end_line,
+            content: result.content,
+            score: result.score,
+            symbol_name: result.metadata.symbol_name,
+            symbol_kind: result.metadata.symbol_kind,
+            signature: result.metadata.signature,
+            stale: result.stale,
+        }
+    }
+}
+
+/// Results of a search or of a reference lookup, best first
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SearchResults {
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    /// Results found before the limit applied
+    pub total_count: usize,
+    pub elapsed: Duration,
+    /// Files whose results were stale or deleted and that should be
+    /// reindexed
+    pub stale_files: Vec<String>,
+    /// The search ran out of time and only holds the results found until
+    /// then
+    pub truncated: bool,
+}
+
+impl SearchResults {
+    pub(crate) fn from_engine(results: retrieval::SearchResults) -> Self {
+        Self {
+            query: results.query,
+            results: results
+                .results
+                .into_iter()
+                .map(SearchResult::from_engine)
+                .collect(),
+            total_count: results.total_count,
+            elapsed: Duration::from_millis(results.search_time_ms),
+            stale_files: results.stale_files,
+            truncated: results.truncated_by_timeout,
+        }
+    }
+}
+
+/// A definition of an explained symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SymbolDefinition {
+    pub file_path: String,
+    pub kind: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+}
+
+impl SymbolDefinition {
+    pub(crate) fn from_engine(definition: retrieval::SymbolDefinition) -> Self {
+        Self {
+            file_path: definition.file_path,
+            kind: definition.kind,
+            start_line: definition.start_line,
+            end_line: definition.end_line,
+            signature: definition.signature,
+            doc_comment: definition.doc_comment,
+        }
+    }
+}
+
+/// Definitions and usages of a symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SymbolExplanation {
+    pub name: String,
+    /// The symbol has at least one definition in the index
+    pub found: bool,
+    pub definitions: Vec<SymbolDefinition>,
+    /// Occurrences of the name outside its definitions
+    pub usage_count: usize,
+    /// Files with at least one of these occurrences
+    pub usage_files: usize,
+    /// Symbols defined next to it
+    pub related_symbols: Vec<String>,
+}
+
+impl SymbolExplanation {
+    pub(crate) fn from_engine(explanation: retrieval::SymbolExplanation) -> Self {
+        Self {
+            name: explanation.name,
+            found: explanation.found,
+            definitions: explanation
+                .definitions
+                .into_iter()
+                .map(SymbolDefinition::from_engine)
+                .collect(),
+            usage_count: explanation.usage_count,
+            usage_files: explanation.usage_files,
+            related_symbols: explanation.related_symbols,
+        }
+    }
+}
+
+/// An import between two files
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DependencyInfo {
+    /// Imported module or file; the importing file for dependents
+    pub target_path: String,
+    pub import_name: Option<String>,
+    pub kind: String,
+    /// Names imported, empty for whole-module imports
+    pub symbols: Vec<String>,
+    /// Workspace package of the imported code, unknown for third-party
+    /// libraries
+    pub package: Option<String>,
+    /// Indexed file a C or C++ include resolves to
+    pub resolved_path: Option<String>,
+}
+
+impl DependencyInfo {
+    pub(crate) fn from_engine(dependency: retrieval::DependencyInfo) -> Self {
+        Self {
+            target_path: dependency.target_path,
+            import_name: dependency.import_name,
+            kind: dependency.kind,
+            symbols: dependency.symbols.into_iter().map(|s| s.name).collect(),
+            package: dependency.package,
+            resolved_path: dependency.resolved_path,
+        }
+    }
+}
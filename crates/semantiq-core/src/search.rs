@@ -0,0 +1,107 @@
+//! Searching the index of a project
+
+use crate::options::{Config, FindRefsOptions, SearchOptions};
+use crate::project::{Project, ProjectOptions};
+use crate::results::{DependencyInfo, SearchResults, SymbolExplanation};
+use anyhow::Result;
+use semantiq_retrieval::RetrievalEngine;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Configures a [`SearchClient`]
+#[derive(Debug, Clone)]
+pub struct SearchClientBuilder {
+    options: ProjectOptions,
+}
+
+impl SearchClientBuilder {
+    /// Database of the index, `.semantiq.db` at the project root by default
+    pub fn database(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.database = Some(path.into());
+        self
+    }
+
+    /// Configuration used instead of the `semantiq.toml` of the project
+    pub fn config(mut self, config: Config) -> Self {
+        self.options.config = Some(config.into_inner());
+        self
+    }
+
+    /// Open the index, built by an [`Indexer`](crate::Indexer) or
+    /// `semantiq index`
+    pub fn build(self) -> Result<SearchClient> {
+        SearchClient::open(self.options.open()?)
+    }
+}
+
+/// Searches the index of a project. Cloning a client shares its query cache.
+#[derive(Clone)]
+pub struct SearchClient {
+    engine: Arc<RetrievalEngine>,
+}
+
+impl SearchClient {
+    pub fn builder(project_root: impl Into<PathBuf>) -> SearchClientBuilder {
+        SearchClientBuilder {
+            options: ProjectOptions::new(project_root),
+        }
+    }
+
+    pub(crate) fn open(project: Project) -> Result<Self> {
+        let engine = RetrievalEngine::from_config(
+            Arc::clone(&project.store),
+            project.root_str()?,
+            &project.config,
+        );
+        Ok(Self {
+            engine: Arc::new(engine),
+        })
+    }
+
+    /// Search code semantically and by symbol and text, best results first
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        options: SearchOptions,
+    ) -> Result<SearchResults> {
+        let results = self
+            .engine
+            .search(query, limit, Some(options.to_engine()))?;
+        Ok(SearchResults::from_engine(results))
+    }
+
+    /// Definitions and usages of a symbol
+    pub fn find_references(
+        &self,
+        symbol: &str,
+        options: &FindRefsOptions,
+    ) -> Result<SearchResults> {
+        let results = self.engine.find_references(symbol, &options.to_engine())?;
+        Ok(SearchResults::from_engine(results))
+    }
+
+    /// Definitions, documentation and usage count of a symbol
+    pub fn explain_symbol(&self, symbol: &str) -> Result<SymbolExplanation> {
+        let explanation = self.engine.explain_symbol(symbol)?;
+        Ok(SymbolExplanation::from_engine(explanation))
+    }
+
+    /// Imports of a file, given relative to the project root
+    pub fn dependencies(&self, file_path: &str) -> Result<Vec<DependencyInfo>> {
+        let dependencies = self.engine.get_dependencies(file_path)?;
+        Ok(dependencies
+            .into_iter()
+            .map(DependencyInfo::from_engine)
+            .collect())
+    }
+
+    /// Files importing a file, given relative to the project root
+    pub fn dependents(&self, file_path: &str) -> Result<Vec<DependencyInfo>> {
+        let dependencies = self.engine.get_dependents(file_path)?;
+        Ok(dependencies
+            .into_iter()
+            .map(DependencyInfo::from_engine)
+            .collect())
+    }
+}
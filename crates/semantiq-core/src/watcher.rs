@@ -0,0 +1,131 @@
+//! Applying file changes to the index in the background
+
+use anyhow::{Context, Result};
+use semantiq_index::{AutoIndexer, MigrationStep};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::error;
+
+type UpdateCallback = Box<dyn Fn(&WatchUpdate) + Send>;
+
+/// Configures a [`Watcher`], from [`Indexer::watcher`](crate::Indexer::watcher)
+pub struct WatcherBuilder {
+    indexer: Arc<AutoIndexer>,
+    poll_interval: Duration,
+    on_update: Option<UpdateCallback>,
+}
+
+impl WatcherBuilder {
+    /// Time between two polls of file events by default
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    pub(crate) fn new(indexer: Arc<AutoIndexer>) -> Self {
+        Self {
+            indexer,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            on_update: None,
+        }
+    }
+
+    /// Time between two polls of file events
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Called from the watcher thread after each poll that changed the index
+    pub fn on_update(mut self, callback: impl Fn(&WatchUpdate) + Send + 'static) -> Self {
+        self.on_update = Some(Box::new(callback));
+        self
+    }
+
+    /// Start watching in a background thread
+    pub fn start(self) -> Result<Watcher> {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let handle = std::thread::Builder::new()
+            .name("semantiq-watcher".to_string())
+            .spawn(move || {
+                // Re-embed the index for a new embedding model between polls
+                let mut migrating = true;
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(self.poll_interval)
+                {
+                    match self.indexer.process_events() {
+                        Ok(result) => {
+                            let update = WatchUpdate {
+                                indexed: result.indexed,
+                                removed: result.removed,
+                                errors: result.errors,
+                            };
+                            if update.indexed + update.removed + update.errors > 0
+                                && let Some(callback) = &self.on_update
+                            {
+                                callback(&update);
+                            }
+                        }
+                        Err(e) => error!("Watcher error: {}", e),
+                    }
+
+                    if migrating {
+                        match self.indexer.migrate_embeddings() {
+                            Ok(MigrationStep::Done) => migrating = false,
+                            Ok(_) => {}
+                            Err(e) => {
+                                error!("Embedding migration stopped: {}", e);
+                                migrating = false;
+                            }
+                        }
+                    }
+                }
+            })
+            .context("Failed to start the watcher thread")?;
+
+        Ok(Watcher {
+            stop: Some(stop),
+            handle: Some(handle),
+        })
+    }
+}
+
+/// Changes applied by a poll of a [`Watcher`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WatchUpdate {
+    /// Files indexed because they were created or changed
+    pub indexed: usize,
+    /// Files removed from the index
+    pub removed: usize,
+    /// Files that failed to index
+    pub errors: usize,
+}
+
+/// Applies the changes of the files of a project to its index until it is
+/// stopped or dropped
+pub struct Watcher {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Stop watching, waiting for the poll in progress
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        // Disconnecting the channel ends the thread at its next wait
+        self.stop.take();
+        if let Some(handle) = self.handle.take()
+            && handle.join().is_err()
+        {
+            error!("Watcher thread panicked");
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
@@ -1,9 +1,18 @@
 pub mod model;
+pub mod prompts;
+pub mod service;
+pub mod window;
 
-pub use model::{EmbeddingConfig, EmbeddingModel, StubEmbeddingModel, create_embedding_model};
+pub use model::{
+    EmbeddingConfig, EmbeddingModel, EmbeddingModelKind, StubEmbeddingModel,
+    create_embedding_model, create_embedding_model_for,
+};
+pub use prompts::EmbeddingPrompts;
+pub use service::EmbeddingService;
+pub use window::{ChunkEmbedding, TokenFit};
 
 #[cfg(feature = "onnx")]
-pub use model::ensure_models_downloaded;
+pub use model::{ensure_model_downloaded, ensure_models_downloaded};
 
-/// Dimension of MiniLM embeddings
+/// Dimension of the embeddings of every supported model
 pub const EMBEDDING_DIM: usize = 384;
@@ -1,3 +1,8 @@
+use crate::prompts::EmbeddingPrompts;
+use crate::service::EmbeddingService;
+use crate::window::{
+    ChunkEmbedding, TokenFit, TokenWindow, estimate_tokens, pool_embeddings, split_windows,
+};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "onnx")]
@@ -8,11 +13,90 @@ use std::fs;
 use std::io::Write;
 #[cfg(feature = "onnx")]
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 #[cfg(feature = "onnx")]
 use tracing::{info, warn};
 
+/// Embedding models available for semantic search.
+///
+/// Both models produce 384-dimensional vectors, but their vector spaces are
+/// unrelated: an index must be embedded and queried with the same model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EmbeddingModelKind {
+    /// English sentence-transformers/all-MiniLM-L6-v2
+    #[default]
+    #[serde(rename = "minilm")]
+    MiniLm,
+    /// sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2, trained on 50+
+    /// languages, for code bases with non-English comments and queries
+    #[serde(rename = "multilingual-minilm")]
+    MultilingualMiniLm,
+}
+
+impl EmbeddingModelKind {
+    /// Parse a model name as used in configuration files
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "minilm" | "all-minilm-l6-v2" => Some(Self::MiniLm),
+            "multilingual-minilm" | "multilingual" | "paraphrase-multilingual-minilm-l12-v2" => {
+                Some(Self::MultilingualMiniLm)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MiniLm => "minilm",
+            Self::MultilingualMiniLm => "multilingual-minilm",
+        }
+    }
+
+    /// Hugging Face repository the model is downloaded from
+    pub fn repository(&self) -> &'static str {
+        match self {
+            Self::MiniLm => "sentence-transformers/all-MiniLM-L6-v2",
+            Self::MultilingualMiniLm => {
+                "sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2"
+            }
+        }
+    }
+
+    /// Local file names of the ONNX model and its tokenizer
+    fn file_names(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::MiniLm => ("minilm.onnx", "tokenizer.json"),
+            Self::MultilingualMiniLm => (
+                "multilingual-minilm.onnx",
+                "multilingual-minilm-tokenizer.json",
+            ),
+        }
+    }
+
+    pub fn dimension(&self) -> usize {
+        crate::EMBEDDING_DIM
+    }
+
+    /// Prompts the model was trained with. The sentence-transformers MiniLM
+    /// models embed queries and passages alike.
+    pub fn default_prompts(&self) -> EmbeddingPrompts {
+        match self {
+            Self::MiniLm | Self::MultilingualMiniLm => EmbeddingPrompts::default(),
+        }
+    }
+}
+
+impl std::fmt::Display for EmbeddingModelKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
+    /// Model the files below belong to
+    #[serde(default)]
+    pub model: EmbeddingModelKind,
     pub model_path: String,
     pub tokenizer_path: String,
     pub max_length: usize,
@@ -20,10 +104,22 @@ pub struct EmbeddingConfig {
     /// Number of threads for ONNX intra-op parallelism.
     /// Defaults to number of CPU cores, capped at 8.
     pub num_threads: usize,
+    /// Prompts of the query and passage sides of the model
+    #[serde(default)]
+    pub prompts: EmbeddingPrompts,
 }
 
 impl Default for EmbeddingConfig {
     fn default() -> Self {
+        Self::for_model(EmbeddingModelKind::default())
+    }
+}
+
+impl EmbeddingConfig {
+    /// Configuration of a model stored in the default models directory
+    pub fn for_model(model: EmbeddingModelKind) -> Self {
+        let (model_file, tokenizer_file) = model.file_names();
+
         // Get number of threads from environment or use sensible default
         let num_threads = std::env::var("SEMANTIQ_ONNX_THREADS")
             .ok()
@@ -39,24 +135,28 @@ impl Default for EmbeddingConfig {
         {
             let models_dir = get_models_dir();
             Self {
-                model_path: models_dir.join("minilm.onnx").to_string_lossy().to_string(),
+                model,
+                model_path: models_dir.join(model_file).to_string_lossy().to_string(),
                 tokenizer_path: models_dir
-                    .join("tokenizer.json")
+                    .join(tokenizer_file)
                     .to_string_lossy()
                     .to_string(),
                 max_length: 512,
                 batch_size: 32,
                 num_threads,
+                prompts: model.default_prompts(),
             }
         }
         #[cfg(not(feature = "onnx"))]
         {
             Self {
-                model_path: "models/minilm.onnx".to_string(),
-                tokenizer_path: "models/tokenizer.json".to_string(),
+                model,
+                model_path: format!("models/{}", model_file),
+                tokenizer_path: format!("models/{}", tokenizer_file),
                 max_length: 512,
                 batch_size: 32,
                 num_threads,
+                prompts: model.default_prompts(),
             }
         }
     }
@@ -70,12 +170,15 @@ fn get_models_dir() -> PathBuf {
         .join("models")
 }
 
+/// Download URLs of the ONNX model and tokenizer of a model
 #[cfg(feature = "onnx")]
-const MODEL_URL: &str =
-    "https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main/onnx/model.onnx";
-#[cfg(feature = "onnx")]
-const TOKENIZER_URL: &str =
-    "https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main/tokenizer.json";
+fn model_urls(model: EmbeddingModelKind) -> (String, String) {
+    let base = format!("https://huggingface.co/{}/resolve/main", model.repository());
+    (
+        format!("{}/onnx/model.onnx", base),
+        format!("{}/tokenizer.json", base),
+    )
+}
 
 /// Compute SHA-256 hash of a byte slice
 #[cfg(feature = "onnx")]
@@ -134,7 +237,7 @@ fn download_file(url: &str, path: &Path) -> Result<()> {
         fs::create_dir_all(parent)?;
     }
 
-    // Use an agent with no body size limit (model is ~90MB)
+    // Use an agent with no body size limit (models are ~90MB to ~470MB)
     let agent = ureq::Agent::new_with_config(
         ureq::config::Config::builder()
             .http_status_as_error(true)
@@ -146,7 +249,7 @@ fn download_file(url: &str, path: &Path) -> Result<()> {
     let bytes = response
         .into_body()
         .with_config()
-        .limit(200 * 1024 * 1024)
+        .limit(600 * 1024 * 1024)
         .read_to_vec()?;
 
     // Compute checksum of downloaded data
@@ -208,12 +311,19 @@ fn ensure_file_downloaded(url: &str, path: &Path, name: &str) -> Result<()> {
 
 #[cfg(feature = "onnx")]
 pub fn ensure_models_downloaded() -> Result<EmbeddingConfig> {
-    let config = EmbeddingConfig::default();
+    ensure_model_downloaded(EmbeddingModelKind::default())
+}
+
+/// Download the files of `model` if needed and return its configuration
+#[cfg(feature = "onnx")]
+pub fn ensure_model_downloaded(model: EmbeddingModelKind) -> Result<EmbeddingConfig> {
+    let config = EmbeddingConfig::for_model(model);
     let model_path = Path::new(&config.model_path);
     let tokenizer_path = Path::new(&config.tokenizer_path);
+    let (model_url, tokenizer_url) = model_urls(model);
 
-    ensure_file_downloaded(MODEL_URL, model_path, "Model")?;
-    ensure_file_downloaded(TOKENIZER_URL, tokenizer_path, "Tokenizer")?;
+    ensure_file_downloaded(&model_url, model_path, "Model")?;
+    ensure_file_downloaded(&tokenizer_url, tokenizer_path, "Tokenizer")?;
 
     Ok(config)
 }
@@ -223,6 +333,78 @@ pub trait EmbeddingModel: Send + Sync {
     fn embed(&self, text: &str) -> Result<Vec<f32>>;
     fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
     fn dimension(&self) -> usize;
+
+    /// The model returns placeholder embeddings instead of computing them,
+    /// which are not worth caching
+    fn is_placeholder(&self) -> bool {
+        false
+    }
+
+    /// Tokens the model embeds at once, without special tokens
+    fn max_tokens(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Tokens of `text`, without special tokens
+    fn count_tokens(&self, text: &str) -> usize {
+        estimate_tokens(text)
+    }
+
+    /// Embed chunks, splitting those over [`Self::max_tokens`] into windows
+    /// whose embeddings are pooled (see [`crate::window`])
+    fn embed_chunks(&self, texts: &[String]) -> Result<Vec<ChunkEmbedding>> {
+        let max_tokens = self.max_tokens();
+        let mut fits = Vec::with_capacity(texts.len());
+        let mut windows: Vec<TokenWindow<'_>> = Vec::with_capacity(texts.len());
+        for text in texts {
+            let tokens = self.count_tokens(text);
+            let split = if tokens > max_tokens {
+                split_windows(text, max_tokens, |line| self.count_tokens(line))
+            } else {
+                vec![TokenWindow { text, tokens }]
+            };
+            fits.push(TokenFit {
+                tokens,
+                windows: split.len(),
+                truncated: split.iter().any(|w| w.tokens > max_tokens),
+            });
+            windows.extend(split);
+        }
+
+        let window_texts: Vec<String> = windows.iter().map(|w| w.text.to_string()).collect();
+        let embeddings = self.embed_batch(&window_texts)?;
+        if embeddings.len() != windows.len() {
+            anyhow::bail!(
+                "Expected {} embeddings, got {}",
+                windows.len(),
+                embeddings.len()
+            );
+        }
+
+        let mut offset = 0;
+        Ok(fits
+            .into_iter()
+            .map(|fit| {
+                let range = offset..offset + fit.windows;
+                offset = range.end;
+                let embedding = if fit.windows == 1 {
+                    embeddings[range.start].clone()
+                } else {
+                    let weights: Vec<usize> =
+                        windows[range.clone()].iter().map(|w| w.tokens).collect();
+                    pool_embeddings(&embeddings[range], &weights)
+                };
+                ChunkEmbedding { embedding, fit }
+            })
+            .collect())
+    }
+
+    /// Embed one chunk, see [`Self::embed_chunks`]
+    fn embed_chunk(&self, text: &str) -> Result<ChunkEmbedding> {
+        self.embed_chunks(&[text.to_string()])?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("No embedding returned"))
+    }
 }
 
 /// Stub embedding model for when ONNX is not available
@@ -255,6 +437,10 @@ impl EmbeddingModel for StubEmbeddingModel {
     fn dimension(&self) -> usize {
         self.dimension
     }
+
+    fn is_placeholder(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(feature = "onnx")]
@@ -276,8 +462,8 @@ pub mod onnx {
     impl OnnxEmbeddingModel {
         pub fn load(config: EmbeddingConfig) -> Result<Self> {
             info!(
-                "Loading ONNX model from {} (threads: {})",
-                config.model_path, config.num_threads
+                "Loading ONNX model {} from {} (threads: {})",
+                config.model, config.model_path, config.num_threads
             );
 
             let session = Session::builder()?
@@ -285,8 +471,14 @@ pub mod onnx {
                 .with_intra_threads(config.num_threads)?
                 .commit_from_file(&config.model_path)?;
 
-            let tokenizer = Tokenizer::from_file(&config.tokenizer_path)
+            let mut tokenizer = Tokenizer::from_file(&config.tokenizer_path)
                 .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+            // Lengths are enforced by `max_length`, not silently by the
+            // tokenizer's own configuration
+            tokenizer
+                .with_truncation(None)
+                .map_err(|e| anyhow::anyhow!("Failed to configure tokenizer: {}", e))?;
+            tokenizer.with_padding(None);
 
             Ok(Self {
                 session: Mutex::new(session),
@@ -405,11 +597,43 @@ pub mod onnx {
         }
 
         fn dimension(&self) -> usize {
-            384 // MiniLM dimension
+            self.config.model.dimension()
+        }
+
+        fn max_tokens(&self) -> usize {
+            // Room for the [CLS] and [SEP] tokens
+            self.config.max_length.saturating_sub(2).max(1)
+        }
+
+        fn count_tokens(&self, text: &str) -> usize {
+            match self.tokenizer.encode(text, false) {
+                Ok(encoding) => encoding.get_ids().len(),
+                Err(_) => estimate_tokens(text),
+            }
         }
     }
 }
 
+/// The embedding model `model`, downloaded if needed and loaded once per
+/// process (see [`crate::EmbeddingService`])
+pub fn create_embedding_model_for(model: EmbeddingModelKind) -> Result<Arc<dyn EmbeddingModel>> {
+    EmbeddingService::global().model(model)
+}
+
+/// Load a new instance of the embedding model `model`, downloading it if
+/// needed
+pub(crate) fn load_embedding_model(model: EmbeddingModelKind) -> Result<Box<dyn EmbeddingModel>> {
+    #[cfg(feature = "onnx")]
+    let config = Some(ensure_model_downloaded(model)?);
+    #[cfg(not(feature = "onnx"))]
+    let config = {
+        tracing::debug!("ONNX support disabled, using stub for {}", model);
+        None
+    };
+
+    create_embedding_model(config)
+}
+
 /// Create an embedding model based on available features
 pub fn create_embedding_model(
     #[allow(unused_variables)] config: Option<EmbeddingConfig>,
@@ -446,4 +670,86 @@ mod tests {
         let embedding = model.embed("test").unwrap();
         assert_eq!(embedding.len(), 384);
     }
+
+    /// Model with a 4-token limit, embedding texts by their first letter
+    struct WindowedModel;
+
+    impl EmbeddingModel for WindowedModel {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(if text.starts_with('a') {
+                vec![1.0, 0.0]
+            } else {
+                vec![0.0, 1.0]
+            })
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            texts.iter().map(|t| self.embed(t)).collect()
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+
+        fn max_tokens(&self) -> usize {
+            4
+        }
+    }
+
+    #[test]
+    fn test_embed_chunks_pools_windows_of_long_chunks() {
+        let texts = ["a b".to_string(), "a b c\nd e f\nz z z z z z".to_string()];
+        let embedded = WindowedModel.embed_chunks(&texts).unwrap();
+
+        assert_eq!(embedded[0].embedding, vec![1.0, 0.0]);
+        assert_eq!(
+            embedded[0].fit,
+            TokenFit {
+                tokens: 2,
+                windows: 1,
+                truncated: false
+            }
+        );
+
+        // Windows of 3, 3 and 6 tokens, the last one over the limit
+        assert_eq!(
+            embedded[1].fit,
+            TokenFit {
+                tokens: 12,
+                windows: 3,
+                truncated: true
+            }
+        );
+        let norm = (3.0f32 * 3.0 + 9.0 * 9.0).sqrt();
+        assert!((embedded[1].embedding[0] - 3.0 / norm).abs() < 1e-6);
+        assert!((embedded[1].embedding[1] - 9.0 / norm).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_model_kind_parse() {
+        assert_eq!(
+            EmbeddingModelKind::parse("multilingual"),
+            Some(EmbeddingModelKind::MultilingualMiniLm)
+        );
+        assert_eq!(
+            EmbeddingModelKind::parse(" MiniLM "),
+            Some(EmbeddingModelKind::MiniLm)
+        );
+        assert_eq!(EmbeddingModelKind::parse("bert"), None);
+        for kind in [
+            EmbeddingModelKind::MiniLm,
+            EmbeddingModelKind::MultilingualMiniLm,
+        ] {
+            assert_eq!(EmbeddingModelKind::parse(kind.as_str()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn test_models_use_separate_files() {
+        let english = EmbeddingConfig::for_model(EmbeddingModelKind::MiniLm);
+        let multilingual = EmbeddingConfig::for_model(EmbeddingModelKind::MultilingualMiniLm);
+        assert_eq!(multilingual.model, EmbeddingModelKind::MultilingualMiniLm);
+        assert_ne!(english.model_path, multilingual.model_path);
+        assert_ne!(english.tokenizer_path, multilingual.tokenizer_path);
+    }
 }
@@ -0,0 +1,80 @@
+//! Instruction prompts of embedding models
+//!
+//! Models such as E5 and BGE are trained to embed a search query and the
+//! passages it should find differently, e.g. `query: how are uploads
+//! retried` against `passage: fn retry_upload(...)`. Embedding both sides
+//! the same way hurts their retrieval. Each side has a template, holding
+//! `{text}` where the text goes, or else prefixed to the text; an empty
+//! template embeds the text as it is.
+
+use serde::{Deserialize, Serialize};
+
+/// Where the text goes in a prompt template
+pub const TEXT_PLACEHOLDER: &str = "{text}";
+
+/// Prompt templates of the query and passage sides of a model
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingPrompts {
+    /// Template of search queries, e.g. `query: `
+    pub query: String,
+    /// Template of indexed chunks and file summaries, e.g. `passage: `
+    pub passage: String,
+}
+
+impl EmbeddingPrompts {
+    pub fn new(query: &str, passage: &str) -> Self {
+        Self {
+            query: query.to_string(),
+            passage: passage.to_string(),
+        }
+    }
+
+    /// Neither side is prompted
+    pub fn is_empty(&self) -> bool {
+        self.query.is_empty() && self.passage.is_empty()
+    }
+
+    /// Text to embed for a search query
+    pub fn query_text(&self, query: &str) -> String {
+        apply(&self.query, query)
+    }
+
+    /// Text to embed for an indexed passage
+    pub fn passage_text(&self, passage: &str) -> String {
+        apply(&self.passage, passage)
+    }
+}
+
+fn apply(template: &str, text: &str) -> String {
+    if template.contains(TEXT_PLACEHOLDER) {
+        template.replacen(TEXT_PLACEHOLDER, text, 1)
+    } else {
+        format!("{}{}", template, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_templates() {
+        let prompts = EmbeddingPrompts::new("query: ", "Represent this code: {text} [end]");
+        assert_eq!(prompts.query_text("retry upload"), "query: retry upload");
+        assert_eq!(
+            prompts.passage_text("fn retry() {}"),
+            "Represent this code: fn retry() {} [end]"
+        );
+
+        // Placeholders in the text are left alone
+        assert_eq!(
+            prompts.passage_text("format!(\"{text}\")"),
+            "Represent this code: format!(\"{text}\") [end]"
+        );
+
+        let none = EmbeddingPrompts::default();
+        assert!(none.is_empty());
+        assert_eq!(none.query_text("retry upload"), "retry upload");
+    }
+}
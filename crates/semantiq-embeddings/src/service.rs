@@ -0,0 +1,167 @@
+//! Embedding models shared by every component of the process.
+//!
+//! `semantiq serve` embeds text from the retrieval engine, the auto-indexer
+//! and embedding migrations, and `semantiq index` from the indexer. Each
+//! used to load its own copy of the model, holding it twice in memory and
+//! loading it twice at startup. Models are now loaded once per process, on
+//! first use, and handed out behind an `Arc`.
+//!
+//! A loaded model runs one inference before it is shared: the first run of
+//! an ONNX session allocates its buffers and is several times slower than
+//! the next ones, which would otherwise fall on the first search.
+
+use crate::model::{EmbeddingModel, EmbeddingModelKind};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// Text embedded to warm up a loaded model
+const WARMUP_TEXT: &str = "fn warm_up() -> Result<()>";
+
+/// Loaded embedding models, one per model kind
+#[derive(Default)]
+pub struct EmbeddingService {
+    models: Mutex<HashMap<EmbeddingModelKind, Arc<dyn EmbeddingModel>>>,
+}
+
+impl EmbeddingService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The service shared by the whole process
+    pub fn global() -> &'static EmbeddingService {
+        static SERVICE: OnceLock<EmbeddingService> = OnceLock::new();
+        SERVICE.get_or_init(EmbeddingService::new)
+    }
+
+    /// The model `kind`, downloaded if needed, loaded and warmed up on first
+    /// use. A failed load is not remembered: the next call tries again.
+    pub fn model(&self, kind: EmbeddingModelKind) -> Result<Arc<dyn EmbeddingModel>> {
+        self.model_with(kind, crate::model::load_embedding_model)
+    }
+
+    /// Whether the model `kind` is loaded
+    pub fn is_loaded(&self, kind: EmbeddingModelKind) -> bool {
+        let models = match self.models.lock() {
+            Ok(models) => models,
+            Err(e) => e.into_inner(),
+        };
+        models.contains_key(&kind)
+    }
+
+    fn model_with(
+        &self,
+        kind: EmbeddingModelKind,
+        load: impl FnOnce(EmbeddingModelKind) -> Result<Box<dyn EmbeddingModel>>,
+    ) -> Result<Arc<dyn EmbeddingModel>> {
+        // Held while loading, so that concurrent callers wait for the model
+        // instead of loading it again
+        let mut models = match self.models.lock() {
+            Ok(models) => models,
+            Err(e) => e.into_inner(),
+        };
+        if let Some(model) = models.get(&kind) {
+            return Ok(Arc::clone(model));
+        }
+
+        let model: Arc<dyn EmbeddingModel> = Arc::from(load(kind)?);
+        warm_up(kind, model.as_ref());
+        models.insert(kind, Arc::clone(&model));
+        Ok(model)
+    }
+}
+
+/// Run a first inference with `model`, unless it is a placeholder
+fn warm_up(kind: EmbeddingModelKind, model: &dyn EmbeddingModel) {
+    if model.is_placeholder() {
+        return;
+    }
+    let start = Instant::now();
+    match model.embed(WARMUP_TEXT) {
+        Ok(_) => debug!(
+            "Embedding model {} warmed up in {:?}",
+            kind,
+            start.elapsed()
+        ),
+        Err(e) => warn!("Failed to warm up embedding model {}: {}", kind, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Model counting its inferences
+    #[derive(Default)]
+    struct CountingModel {
+        embedded: Arc<AtomicUsize>,
+    }
+
+    impl EmbeddingModel for CountingModel {
+        fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            self.embedded.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![1.0])
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            texts.iter().map(|t| self.embed(t)).collect()
+        }
+
+        fn dimension(&self) -> usize {
+            1
+        }
+    }
+
+    #[test]
+    fn test_models_are_loaded_once_and_warmed_up() {
+        let service = EmbeddingService::new();
+        let loads = AtomicUsize::new(0);
+        let embedded = Arc::new(AtomicUsize::new(0));
+        let load = |_| -> Result<Box<dyn EmbeddingModel>> {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(CountingModel {
+                embedded: Arc::clone(&embedded),
+            }))
+        };
+
+        assert!(!service.is_loaded(EmbeddingModelKind::MiniLm));
+        let first = service
+            .model_with(EmbeddingModelKind::MiniLm, load)
+            .unwrap();
+        let second = service
+            .model_with(EmbeddingModelKind::MiniLm, load)
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(service.is_loaded(EmbeddingModelKind::MiniLm));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        // The warmup inference
+        assert_eq!(embedded.load(Ordering::SeqCst), 1);
+
+        // Each kind has its own model
+        service
+            .model_with(EmbeddingModelKind::MultilingualMiniLm, load)
+            .unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_failed_loads_are_retried() {
+        let service = EmbeddingService::new();
+        let failed = service.model_with(EmbeddingModelKind::MiniLm, |_| {
+            anyhow::bail!("model download failed")
+        });
+        assert!(failed.is_err());
+        assert!(!service.is_loaded(EmbeddingModelKind::MiniLm));
+
+        let model = service
+            .model_with(EmbeddingModelKind::MiniLm, |_| {
+                Ok(Box::new(CountingModel::default()))
+            })
+            .unwrap();
+        assert_eq!(model.dimension(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+//! Fitting texts into the token limit of an embedding model.
+//!
+//! A text over the limit is split at line boundaries into windows that each
+//! fit, and its embedding is the mean of the window embeddings weighted by
+//! their token counts, so the end of a long function is not lost. Only a
+//! single line over the limit is still truncated.
+
+use serde::{Deserialize, Serialize};
+
+/// How a text was fitted into the model's token limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenFit {
+    /// Tokens of the text, without special tokens
+    pub tokens: usize,
+    /// Windows embedded separately and pooled, 1 when the text fits
+    pub windows: usize,
+    /// Part of the text was cut off by the tokenizer
+    pub truncated: bool,
+}
+
+/// Embedding of a chunk and how it was fitted into the token limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkEmbedding {
+    pub embedding: Vec<f32>,
+    pub fit: TokenFit,
+}
+
+/// Part of a text embedded on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenWindow<'a> {
+    pub text: &'a str,
+    pub tokens: usize,
+}
+
+/// Split `text` into windows of at most `max_tokens` tokens, cutting between
+/// lines. A line over the limit makes a window of its own.
+pub fn split_windows<'a>(
+    text: &'a str,
+    max_tokens: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> Vec<TokenWindow<'a>> {
+    let mut windows = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_tokens = count_tokens(line);
+        if tokens > 0 && tokens + line_tokens > max_tokens {
+            windows.push(TokenWindow {
+                text: &text[start..offset],
+                tokens,
+            });
+            start = offset;
+            tokens = 0;
+        }
+        tokens += line_tokens;
+        offset += line.len();
+    }
+    if offset > start {
+        windows.push(TokenWindow {
+            text: &text[start..],
+            tokens,
+        });
+    }
+    windows
+}
+
+/// Mean of `embeddings` weighted by `weights`, L2-normalized
+pub fn pool_embeddings(embeddings: &[Vec<f32>], weights: &[usize]) -> Vec<f32> {
+    let dimension = embeddings.first().map_or(0, Vec::len);
+    let mut pooled = vec![0.0f32; dimension];
+    for (embedding, weight) in embeddings.iter().zip(weights) {
+        let weight = (*weight).max(1) as f32;
+        for (sum, value) in pooled.iter_mut().zip(embedding) {
+            *sum += value * weight;
+        }
+    }
+
+    let norm: f32 = pooled.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut pooled {
+            *value /= norm;
+        }
+    }
+    pooled
+}
+
+/// Rough token count of a text for models without a tokenizer: words and
+/// punctuation marks, the way WordPiece splits code at the least
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            if !in_word {
+                tokens += 1;
+            }
+            in_word = true;
+        } else {
+            in_word = false;
+            if !c.is_whitespace() {
+                tokens += 1;
+            }
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_windows_at_line_boundaries() {
+        let text = "fn a() {\n    one();\n    two();\n}\n";
+        let windows = split_windows(text, 6, estimate_tokens);
+
+        let joined: String = windows.iter().map(|w| w.text).collect();
+        assert_eq!(joined, text);
+        assert!(windows.len() > 1);
+        assert!(windows.iter().all(|w| w.tokens <= 6));
+        assert!(windows.iter().all(|w| w.text.ends_with('\n')));
+    }
+
+    #[test]
+    fn test_split_windows_keeps_long_line_whole() {
+        let text = "short\na b c d e f g h\nend";
+        let windows = split_windows(text, 3, estimate_tokens);
+        assert_eq!(
+            windows,
+            vec![
+                TokenWindow {
+                    text: "short\n",
+                    tokens: 1
+                },
+                TokenWindow {
+                    text: "a b c d e f g h\n",
+                    tokens: 8
+                },
+                TokenWindow {
+                    text: "end",
+                    tokens: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pool_embeddings_weights_windows() {
+        let pooled = pool_embeddings(&[vec![1.0, 0.0], vec![0.0, 1.0]], &[3, 1]);
+        assert!(pooled[0] > pooled[1]);
+        let norm: f32 = pooled.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens("fn parse_config(path: &str) {}"), 10);
+        assert_eq!(estimate_tokens(""), 0);
+    }
+}
@@ -0,0 +1,167 @@
+//! Asynchronous access to the index
+//!
+//! [`IndexStore`] runs SQLite queries synchronously, which blocks the thread
+//! calling it. An [`AsyncIndexStore`] runs them on the blocking thread pool
+//! of the tokio runtime instead, so that a large query of one tool call does
+//! not stall the reactor serving the others. Operations go through a bounded
+//! queue: at most `queue_size` run at the same time, the others waiting for a
+//! slot without holding a thread, so a burst of calls cannot exhaust the
+//! blocking pool while they all wait on the database connection anyway.
+
+use crate::store::{IndexStats, IndexStore};
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::error;
+
+/// Operations running at the same time by default (`[limits] index_queue`
+/// in `semantiq.toml`)
+pub const DEFAULT_INDEX_QUEUE: usize = 8;
+
+/// An [`IndexStore`] whose operations run off the async runtime, through a
+/// bounded queue. Clones share the store and the queue.
+#[derive(Clone)]
+pub struct AsyncIndexStore {
+    store: Arc<IndexStore>,
+    queue: Arc<Semaphore>,
+    queue_size: usize,
+}
+
+impl AsyncIndexStore {
+    pub fn new(store: Arc<IndexStore>) -> Self {
+        Self {
+            store,
+            queue: Arc::new(Semaphore::new(DEFAULT_INDEX_QUEUE)),
+            queue_size: DEFAULT_INDEX_QUEUE,
+        }
+    }
+
+    /// Run at most `size` operations at the same time (at least one).
+    pub fn with_queue_size(mut self, size: usize) -> Self {
+        let size = size.max(1);
+        self.queue = Arc::new(Semaphore::new(size));
+        self.queue_size = size;
+        self
+    }
+
+    /// The underlying store, for synchronous callers
+    pub fn store(&self) -> &Arc<IndexStore> {
+        &self.store
+    }
+
+    /// Operations allowed to run at the same time
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+    }
+
+    /// Operations running now
+    pub fn in_flight(&self) -> usize {
+        self.queue_size - self.queue.available_permits()
+    }
+
+    /// Run `operation` on the store, on the blocking thread pool once a slot
+    /// of the queue is free.
+    pub async fn run<T, F>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce(&IndexStore) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let store = Arc::clone(&self.store);
+        self.spawn_blocking(move || operation(&store)).await?
+    }
+
+    /// Run blocking work reading or writing the index (a search of the
+    /// retrieval engine, a tool call) through the same queue as the
+    /// operations of [`AsyncIndexStore::run`]. Fails if the work panicked.
+    pub async fn spawn_blocking<T, F>(&self, work: F) -> Result<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let _slot = Arc::clone(&self.queue)
+            .acquire_owned()
+            .await
+            .map_err(|_| anyhow!("Index queue closed"))?;
+        tokio::task::spawn_blocking(work).await.map_err(|e| {
+            error!("Index operation failed: {}", e);
+            anyhow!("Index operation failed: {}", e)
+        })
+    }
+
+    /// Statistics of the index, see [`IndexStore::get_stats`]
+    pub async fn get_stats(&self) -> Result<IndexStats> {
+        self.run(|store| store.get_stats()).await
+    }
+
+    /// Checkpoint the write-ahead log, see [`IndexStore::checkpoint_wal`]
+    pub async fn checkpoint_wal(&self) -> Result<()> {
+        self.run(|store| store.checkpoint_wal()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn async_store(queue_size: usize) -> AsyncIndexStore {
+        let store = Arc::new(IndexStore::open_in_memory().unwrap());
+        AsyncIndexStore::new(store).with_queue_size(queue_size)
+    }
+
+    #[tokio::test]
+    async fn test_run_on_blocking_pool() {
+        let store = async_store(2);
+        store
+            .run(|store| store.insert_file("src/lib.rs", Some("rust"), "", 0, 0))
+            .await
+            .unwrap();
+        assert_eq!(store.get_stats().await.unwrap().file_count, 1);
+        assert_eq!(store.in_flight(), 0);
+
+        let result = store.run(|_| -> Result<()> { Err(anyhow!("no such file")) });
+        assert_eq!(result.await.unwrap_err().to_string(), "no such file");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_queue_bounds_operations_in_flight() {
+        let store = async_store(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                let running = Arc::clone(&running);
+                let most = Arc::clone(&most);
+                tokio::spawn(async move {
+                    store
+                        .spawn_blocking(move || {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            most.fetch_max(now, Ordering::SeqCst);
+                            std::thread::sleep(Duration::from_millis(20));
+                            running.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(most.load(Ordering::SeqCst), 2);
+        assert_eq!(store.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_panics_become_errors() {
+        let store = async_store(1);
+        let result = store.spawn_blocking(|| panic!("boom")).await;
+        assert!(result.is_err());
+        // The slot is released
+        assert_eq!(store.spawn_blocking(|| 42).await.unwrap(), 42);
+        assert_eq!(AsyncIndexStore::new(store.store().clone()).queue_size(), 8);
+        assert_eq!(async_store(0).queue_size(), 1);
+    }
+}
@@ -1,25 +1,64 @@
-use crate::IndexStore;
-use crate::exclusions::{should_exclude, should_exclude_entry};
+use crate::encoding::read_source;
+use crate::exclusions::{MAX_OUTLINE_FILE_SIZE, should_exclude_entry, should_exclude_path};
+use crate::external::{self, EXTERNAL_PREFIX, is_external_path};
+use crate::extraction::ExtractedFile;
+use crate::generated::GeneratedDetector;
+use crate::includes::{IncludeResolver, compile_commands_path};
+use crate::issues::{IndexIssue, IndexIssueKind, IndexReport};
+use crate::migration::{EmbeddingMigrator, MIGRATION_BATCH_SIZE, MigrationStep};
+use crate::outline::is_outline_size;
+use crate::paths::{normalize_path, relative_path, resolve_path};
+use crate::priority::IndexQueue;
+use crate::progress::{FileOutcome, ProgressTracker};
+use crate::summary::file_summary;
 use crate::watcher::{FileEvent, FileWatcher};
+use crate::workspace::{MANIFEST_FILES, discover_workspace_packages};
+use crate::{
+    ChunkingConfig, EmbeddingsConfig, FileIndexData, IncludesConfig, IndexLockAttempt,
+    IndexLockGuard, IndexStore, QuotaConfig, SemantiqConfig,
+};
 use anyhow::Result;
 use ignore::WalkBuilder;
-use semantiq_embeddings::{EmbeddingModel, create_embedding_model};
-use semantiq_parser::{
-    ChunkExtractor, ImportExtractor, Language, LanguageSupport, SymbolExtractor,
-};
+use ignore::overrides::{Override, OverrideBuilder};
+use semantiq_embeddings::{ChunkEmbedding, EmbeddingModel, create_embedding_model_for};
+use semantiq_parser::{ChunkExtractor, CodeChunk, Language, LanguageSupport, Symbol};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::UNIX_EPOCH;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, UNIX_EPOCH};
 use tracing::{debug, error, info, warn};
 
+/// Owner recorded in the indexing lock while the auto-indexer writes
+const LOCK_OWNER: &str = "semantiq serve";
+
 pub struct AutoIndexer {
     store: Arc<IndexStore>,
     watcher: Mutex<FileWatcher>,
+    /// File events polled while another process held the indexing lock
+    deferred_events: Mutex<Vec<FileEvent>>,
     project_root: PathBuf,
     language_support: Mutex<LanguageSupport>,
     chunk_extractor: ChunkExtractor,
-    embedding_model: Box<dyn EmbeddingModel>,
+    /// Marks generated files, from the `.gitattributes` read at startup
+    generated: GeneratedDetector,
+    /// Model of the index, replaced when a migration switches to a new one
+    embedding_model: RwLock<Arc<dyn EmbeddingModel>>,
+    /// Re-embeds the index when its embedding model changed
+    migrator: Mutex<Option<EmbeddingMigrator>>,
+    progress: ProgressTracker,
+    /// Index the sources of declared dependencies after the initial pass
+    index_external: bool,
+    /// Share of re-embedded chunks before searches switch to a new model
+    switch_coverage: f64,
+    /// Limits of the index of a very large project
+    quota: QuotaConfig,
+    /// Lookup of C and C++ includes (`[includes]` in `semantiq.toml`)
+    includes: IncludesConfig,
+    /// Classifies C and C++ includes, from the compilation database read
+    /// by the last initial pass or change to it
+    include_resolver: RwLock<Arc<IncludeResolver>>,
 }
 
 impl AutoIndexer {
@@ -30,25 +69,138 @@ impl AutoIndexer {
         let language_support = LanguageSupport::new()?;
         let chunk_extractor = ChunkExtractor::new();
 
-        // Initialize embedding model (downloads if needed)
-        let embedding_model = create_embedding_model(None)?;
+        // Initialize the model the index is embedded with (downloads if needed)
+        let model_kind = store.embedding_model()?;
+        let embedding_model = create_embedding_model_for(model_kind)?;
         info!(
-            "Embedding model initialized (dim={})",
+            "Embedding model {} initialized (dim={})",
+            model_kind,
             embedding_model.dimension()
         );
 
+        let generated = GeneratedDetector::load(&project_root);
         info!("AutoIndexer initialized for {:?}", project_root);
 
         Ok(Self {
             store,
             watcher: Mutex::new(watcher),
+            deferred_events: Mutex::new(Vec::new()),
             project_root,
             language_support: Mutex::new(language_support),
             chunk_extractor,
-            embedding_model,
+            generated,
+            embedding_model: RwLock::new(embedding_model),
+            migrator: Mutex::new(None),
+            progress: ProgressTracker::new(),
+            index_external: false,
+            switch_coverage: EmbeddingsConfig::default().switch_coverage,
+            quota: QuotaConfig::default(),
+            includes: IncludesConfig::default(),
+            include_resolver: RwLock::default(),
         })
     }
 
+    /// Create an auto-indexer configured by the `semantiq.toml` of the
+    /// project: external dependencies, embedding migrations, quota, chunking
+    /// and C and C++ includes
+    pub fn from_config(
+        store: Arc<IndexStore>,
+        project_root: PathBuf,
+        config: &SemantiqConfig,
+    ) -> Result<Self> {
+        Ok(Self::new(store, project_root)?
+            .with_external_dependencies(config.external.enabled)
+            .with_switch_coverage(config.embeddings.switch_coverage)
+            .with_quota(config.quota.clone())
+            .with_chunking(&config.chunking)
+            .with_includes(&config.includes))
+    }
+
+    /// Enable or disable indexing of external dependency sources
+    pub fn with_external_dependencies(mut self, enabled: bool) -> Self {
+        self.index_external = enabled;
+        self
+    }
+
+    /// Share of the chunks re-embedded before searches switch to a new model
+    pub fn with_switch_coverage(mut self, coverage: f64) -> Self {
+        self.switch_coverage = coverage;
+        self
+    }
+
+    /// Limits of the number of files and of the size of the index
+    pub fn with_quota(mut self, quota: QuotaConfig) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// How files are split into chunks
+    pub fn with_chunking(mut self, chunking: &ChunkingConfig) -> Self {
+        self.chunk_extractor = chunking.chunk_extractor();
+        self
+    }
+
+    /// How C and C++ includes are looked up; the compilation database is
+    /// read by the initial pass
+    pub fn with_includes(mut self, includes: &IncludesConfig) -> Self {
+        self.includes = includes.clone();
+        self
+    }
+
+    fn include_resolver(&self) -> Arc<IncludeResolver> {
+        match self.include_resolver.read() {
+            Ok(resolver) => Arc::clone(&resolver),
+            Err(e) => Arc::clone(&e.into_inner()),
+        }
+    }
+
+    fn embedding_model(&self) -> Arc<dyn EmbeddingModel> {
+        match self.embedding_model.read() {
+            Ok(model) => Arc::clone(&model),
+            Err(e) => Arc::clone(&e.into_inner()),
+        }
+    }
+
+    /// Re-embed the next batch of chunks when the embedding model of the
+    /// index changed. Files indexed meanwhile are embedded with the current
+    /// model until the switch, and with the new one after it.
+    pub fn migrate_embeddings(&self) -> Result<MigrationStep> {
+        let mut migrator = self
+            .migrator
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Migrator lock poisoned: {}", e))?;
+        if migrator.is_none() {
+            *migrator = EmbeddingMigrator::for_store(&self.store, self.switch_coverage)?;
+        }
+        let Some(active) = migrator.as_ref() else {
+            return Ok(MigrationStep::Done);
+        };
+
+        let _lock = match self.store.try_lock_index(LOCK_OWNER)? {
+            IndexLockAttempt::Acquired(guard) => guard,
+            IndexLockAttempt::Held(_) => return Ok(MigrationStep::Paused),
+        };
+
+        let step = active.step(&self.store, MIGRATION_BATCH_SIZE)?;
+        match step {
+            MigrationStep::Switched(model) => {
+                match self.embedding_model.write() {
+                    Ok(mut current) => *current = active.model(),
+                    Err(e) => *e.into_inner() = active.model(),
+                }
+                info!("Switched the index to embedding model {}", model);
+            }
+            MigrationStep::Done => *migrator = None,
+            _ => {}
+        }
+        Ok(step)
+    }
+
+    /// Get a handle to the progress tracker for the initial indexing pass
+    pub fn progress(&self) -> ProgressTracker {
+        self.progress.clone()
+    }
+
     /// Perform initial indexing of all files in the project
     /// Only indexes files that are new or have changed since last index
     pub fn initial_index(&self) -> Result<InitialIndexResult> {
@@ -56,6 +208,230 @@ impl AutoIndexer {
 
         let mut result = InitialIndexResult::default();
 
+        // Another process (e.g. `semantiq index`) may be writing the index
+        let lock = self.wait_for_lock()?;
+
+        // Files left in the indexing journal by an interrupted run are removed
+        // from the index, so the scan below indexes them again
+        match self.store.recover_interrupted_files() {
+            Ok(paths) => result.recovered = paths.len(),
+            Err(e) => error!("Failed to recover interrupted indexing: {}", e),
+        }
+
+        // Files are tagged with their package as they are indexed
+        if let Err(e) = self.sync_workspace_packages() {
+            error!("Failed to record workspace packages: {}", e);
+        }
+        if let Err(e) = self.sync_translation_units() {
+            error!("Failed to record translation units: {}", e);
+        }
+
+        self.progress.begin_scan();
+
+        let candidates = self.collect_candidates(|| {
+            result.scanned += 1;
+            self.progress.file_scanned();
+        });
+
+        self.progress.begin_indexing(candidates.len());
+
+        // Files being worked on first, so that searches cover them early
+        let mut queue = IndexQueue::new(&self.project_root, candidates, &self.quota);
+        if self.quota.max_files > 0 {
+            result.over_quota = self.drop_over_quota(&mut queue);
+        }
+        if let Err(e) = self.store.set_quota_skipped_files(result.over_quota) {
+            debug!("Failed to record the files over the quota: {}", e);
+        }
+        loop {
+            self.promote_edited_files(&mut queue);
+            let Some(path) = queue.pop() else {
+                break;
+            };
+
+            if !lock.refresh()? {
+                warn!("Index lock was taken over by another process, stopping initial index");
+                break;
+            }
+
+            let Some(rel_path) = relative_path(&self.project_root, &path) else {
+                continue;
+            };
+
+            self.progress.start_file(&rel_path);
+            let (outcome, issues) = self.initial_index_file(&path, &rel_path);
+            // Files indexed as decoded from a guessed encoding
+            if outcome == FileOutcome::Indexed
+                && issues.iter().any(|i| i.kind == IndexIssueKind::Encoding)
+            {
+                result.warnings += 1;
+                self.progress.file_warning();
+            }
+            result.issues.extend(issues);
+            match outcome {
+                FileOutcome::Indexed => result.indexed += 1,
+                FileOutcome::Skipped => result.skipped += 1,
+                FileOutcome::Error => result.errors += 1,
+            }
+            self.progress.finish_file(outcome);
+        }
+
+        if let Err(e) = self.sync_external_dependencies() {
+            error!("Failed to index external dependencies: {}", e);
+        }
+
+        // Directory summaries for semantiq_overview; later changes are
+        // summarized again when an overview finds them stale
+        if let Err(e) = self.store.refresh_dir_summaries() {
+            error!("Failed to summarize directories: {}", e);
+        }
+        // Weights of boilerplate chunks; chunks written later stay neutral
+        // until the next full pass
+        if let Err(e) = self.store.refresh_chunk_distinctiveness() {
+            error!("Failed to weigh chunk distinctiveness: {}", e);
+        }
+        if let Err(e) = self.store.enforce_size_quota(&self.quota) {
+            error!("Failed to enforce the index size quota: {}", e);
+        }
+        // Issues of files removed from the project meanwhile
+        if let Err(e) = self
+            .store
+            .retain_index_issues(|path| resolve_path(&self.project_root, path).is_file())
+        {
+            error!("Failed to drop the issues of removed files: {}", e);
+        }
+
+        self.progress.complete();
+
+        info!(
+            "Initial index complete: {} scanned, {} indexed, {} skipped, {} errors, {} warnings",
+            result.scanned, result.indexed, result.skipped, result.errors, result.warnings
+        );
+        if !result.issues.is_empty() {
+            warn!("{} (see `semantiq stats`)", result.issues);
+        }
+
+        Ok(result)
+    }
+
+    /// Leave the files of lowest priority past `quota.max_files` out of the
+    /// initial pass, removing those indexed earlier. Returns their number.
+    fn drop_over_quota(&self, queue: &mut IndexQueue) -> usize {
+        let dropped = queue.limit(self.quota.max_files);
+        for path in &dropped {
+            if let Some(rel_path) = relative_path(&self.project_root, path)
+                && let Err(e) = self.store.delete_file(&rel_path)
+            {
+                debug!("Failed to remove {} over the file quota: {}", rel_path, e);
+            }
+        }
+        if !dropped.is_empty() {
+            info!(
+                "Leaving out {} files of lowest priority (quota.max_files = {})",
+                dropped.len(),
+                self.quota.max_files
+            );
+        }
+        dropped.len()
+    }
+
+    /// Move the files the watcher reports as edited to the front of the
+    /// initial pass. Their events are kept for [`Self::process_events`],
+    /// which picks up any later change once the pass is over.
+    fn promote_edited_files(&self, queue: &mut IndexQueue) {
+        let (Ok(mut deferred), Ok(watcher)) = (self.deferred_events.lock(), self.watcher.lock())
+        else {
+            return;
+        };
+        for event in watcher.poll_events() {
+            if let FileEvent::Created(ref path) | FileEvent::Modified(ref path) = event
+                && queue.promote(path)
+            {
+                debug!("Indexing {:?} next: edited during the initial pass", path);
+            }
+            deferred.push(event);
+        }
+    }
+
+    /// Take the indexing lock, waiting for as long as another process holds it
+    fn wait_for_lock(&self) -> Result<IndexLockGuard<'_>> {
+        loop {
+            match self.store.lock_index(LOCK_OWNER, Duration::from_secs(30))? {
+                IndexLockAttempt::Acquired(guard) => return Ok(guard),
+                IndexLockAttempt::Held(holder) => {
+                    info!("Waiting for the index lock held by {}", holder)
+                }
+            }
+        }
+    }
+
+    /// Take the indexing lock, failing if another process holds it
+    fn try_lock(&self) -> Result<IndexLockGuard<'_>> {
+        match self.store.try_lock_index(LOCK_OWNER)? {
+            IndexLockAttempt::Acquired(guard) => Ok(guard),
+            IndexLockAttempt::Held(holder) => anyhow::bail!(
+                "The index is being written by {}, try again once it finishes",
+                holder
+            ),
+        }
+    }
+
+    /// Index the sources of declared dependencies when enabled, or remove
+    /// previously indexed ones when disabled
+    fn sync_external_dependencies(&self) -> Result<()> {
+        if !self.index_external {
+            let removed = self.store.delete_files_with_prefix(EXTERNAL_PREFIX)?;
+            if removed > 0 {
+                info!(
+                    "Removed {} external dependency files from the index",
+                    removed
+                );
+            }
+            return Ok(());
+        }
+
+        let packages = external::discover_packages(&self.project_root);
+        let mut language_support = self
+            .language_support
+            .lock()
+            .map_err(|e| anyhow::anyhow!("LanguageSupport lock poisoned: {}", e))?;
+        external::index_external_packages(
+            &self.store,
+            &packages,
+            &mut language_support,
+            &self.chunk_extractor,
+            Some(self.embedding_model().as_ref()),
+        )?;
+        Ok(())
+    }
+
+    /// Record the packages declared by the manifests of the project
+    fn sync_workspace_packages(&self) -> Result<()> {
+        let packages = discover_workspace_packages(&self.project_root);
+        if self.store.set_packages(&packages)? {
+            info!("Found {} workspace packages", packages.len());
+        }
+        Ok(())
+    }
+
+    /// Read the compilation database again, recording its translation
+    /// units and classifying includes by them from now on
+    fn sync_translation_units(&self) -> Result<()> {
+        let resolver = IncludeResolver::load(&self.project_root, &self.includes);
+        let units = resolver.units();
+        if self.store.set_translation_units(&units)? {
+            info!("Found {} translation units", units.len());
+        }
+        match self.include_resolver.write() {
+            Ok(mut current) => *current = Arc::new(resolver),
+            Err(e) => *e.into_inner() = Arc::new(resolver),
+        }
+        Ok(())
+    }
+
+    /// Walk the project respecting .gitignore and exclusions, returning files
+    /// in a supported language. `on_scanned` is called for every file seen.
+    fn collect_candidates(&self, mut on_scanned: impl FnMut()) -> Vec<PathBuf> {
         // Use ignore crate to walk directory respecting .gitignore
         let walker = WalkBuilder::new(&self.project_root)
             .hidden(true) // Skip hidden files by default
@@ -72,88 +448,132 @@ impl AutoIndexer {
             })
             .build();
 
+        let mut candidates = Vec::new();
         for entry in walker.flatten() {
             // Skip directories
             if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(true) {
                 continue;
             }
 
-            let path = entry.path();
-            result.scanned += 1;
+            on_scanned();
 
             // Skip if not a supported language
-            if Language::from_path(path).is_none() {
+            if Language::detect(entry.path()).is_none() {
                 continue;
             }
 
-            // Get relative path
-            let rel_path = path
-                .strip_prefix(&self.project_root)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .to_string();
+            candidates.push(entry.into_path());
+        }
 
-            // Read file content to check if needs reindex
-            let content = match fs::read_to_string(path) {
-                Ok(c) => c,
-                Err(e) => {
-                    debug!("Skipping {}: {}", rel_path, e);
-                    continue;
-                }
-            };
+        candidates
+    }
 
-            // Check if file needs to be reindexed
-            match self.store.needs_reindex(&rel_path, &content) {
-                Ok(true) => {
-                    // File is new or changed, index it
-                    if let Err(e) = self.index_file(path) {
-                        error!("Failed to index {}: {}", rel_path, e);
-                        result.errors += 1;
-                    } else {
-                        result.indexed += 1;
-                    }
-                }
-                Ok(false) => {
-                    // File already indexed and unchanged
-                    result.skipped += 1;
-                }
-                Err(e) => {
-                    debug!("Error checking reindex for {}: {}", rel_path, e);
-                    // Try to index anyway
-                    if let Err(e) = self.index_file(path) {
-                        error!("Failed to index {}: {}", rel_path, e);
-                        result.errors += 1;
-                    } else {
-                        result.indexed += 1;
-                    }
+    /// Index a single candidate file during the initial pass if it changed,
+    /// along with the issues met
+    fn initial_index_file(&self, path: &Path, rel_path: &str) -> (FileOutcome, Vec<IndexIssue>) {
+        // Read file content to check if needs reindex
+        let source = match read_source(path) {
+            Ok(source) => source,
+            Err(e) => {
+                warn!("Skipping {}: {}", rel_path, e);
+                let issue = IndexIssue::new(rel_path, IndexIssueKind::ReadError, e);
+                return (FileOutcome::Skipped, self.left_out(issue));
+            }
+        };
+
+        // Check if file needs to be reindexed
+        match self.store.needs_reindex(rel_path, &source.content) {
+            // File already indexed and unchanged, `.gitattributes` may have changed
+            Ok(false) => {
+                let generated = self.generated.is_generated(rel_path, &source.content);
+                if let Err(e) = self.store.set_file_generated(rel_path, generated) {
+                    debug!("Failed to mark {} as generated: {}", rel_path, e);
                 }
+                return (FileOutcome::Skipped, Vec::new());
+            }
+            Ok(true) => {}
+            Err(e) => {
+                // Try to index anyway
+                debug!("Error checking reindex for {}: {}", rel_path, e);
             }
         }
 
-        info!(
-            "Initial index complete: {} scanned, {} indexed, {} skipped, {} errors",
-            result.scanned, result.indexed, result.skipped, result.errors
-        );
+        // File is new or changed, index it
+        match self.index_file(path) {
+            Ok(issues) => (FileOutcome::Indexed, issues),
+            Err(e) => {
+                error!("Failed to index {}: {}", rel_path, e);
+                (FileOutcome::Error, Vec::new())
+            }
+        }
+    }
 
-        Ok(result)
+    /// Record the issue of a file left out of the index, returning it
+    fn left_out(&self, issue: IndexIssue) -> Vec<IndexIssue> {
+        let issues = vec![issue];
+        if let Err(e) = self.store.set_index_issues(&issues[0].path, &issues) {
+            debug!("Failed to record the issue of {}: {}", issues[0].path, e);
+        }
+        issues
     }
 
     /// Process pending file events and reindex changed files
+    /// (deferred while another process holds the indexing lock)
     pub fn process_events(&self) -> Result<ProcessResult> {
-        let events = {
+        let mut deferred = self
+            .deferred_events
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Deferred events lock poisoned: {}", e))?;
+        {
             let watcher = self
                 .watcher
                 .lock()
                 .map_err(|e| anyhow::anyhow!("FileWatcher lock poisoned: {}", e))?;
-            watcher.poll_events()
-        };
+            deferred.extend(watcher.poll_events());
+        }
 
-        if events.is_empty() {
+        if deferred.is_empty() {
             return Ok(ProcessResult::default());
         }
 
+        let _lock = match self.store.try_lock_index(LOCK_OWNER)? {
+            IndexLockAttempt::Acquired(guard) => guard,
+            IndexLockAttempt::Held(holder) => {
+                debug!(
+                    "Deferring {} file events, index locked by {}",
+                    deferred.len(),
+                    holder
+                );
+                return Ok(ProcessResult::default());
+            }
+        };
+        let events = std::mem::take(&mut *deferred);
+        drop(deferred);
+
         let mut result = ProcessResult::default();
 
+        let manifest_changed = events.iter().any(|event| {
+            let (FileEvent::Created(path) | FileEvent::Modified(path) | FileEvent::Deleted(path)) =
+                event;
+            path.file_name()
+                .is_some_and(|name| MANIFEST_FILES.iter().any(|m| name == *m))
+        });
+        if manifest_changed && let Err(e) = self.sync_workspace_packages() {
+            error!("Failed to record workspace packages: {}", e);
+        }
+        let compile_commands = compile_commands_path(&self.project_root, &self.includes);
+        let compile_commands_changed = compile_commands.is_some_and(|database| {
+            events.iter().any(|event| {
+                let (FileEvent::Created(path)
+                | FileEvent::Modified(path)
+                | FileEvent::Deleted(path)) = event;
+                *path == database
+            })
+        });
+        if compile_commands_changed && let Err(e) = self.sync_translation_units() {
+            error!("Failed to record translation units: {}", e);
+        }
+
         for event in events {
             match event {
                 FileEvent::Created(path) | FileEvent::Modified(path) => {
@@ -180,46 +600,202 @@ impl AutoIndexer {
                 "Auto-indexed: {} files updated, {} files removed, {} errors",
                 result.indexed, result.removed, result.errors
             );
+            if let Err(e) = self.store.enforce_size_quota(&self.quota) {
+                error!("Failed to enforce the index size quota: {}", e);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Reindex specific files on demand, given paths relative to the project root.
+    /// Files that no longer exist are removed from the index.
+    pub fn reindex_paths(&self, rel_paths: &[String]) -> Result<ProcessResult> {
+        let _lock = self.try_lock()?;
+        let mut result = ProcessResult::default();
+
+        for rel_path in rel_paths {
+            let path = resolve_path(&self.project_root, rel_path);
+            if path.is_file() {
+                if let Err(e) = self.index_file(&path) {
+                    error!("Failed to reindex {}: {}", rel_path, e);
+                    result.errors += 1;
+                } else {
+                    result.indexed += 1;
+                }
+            } else if let Err(e) = self.remove_file(&path) {
+                error!("Failed to remove {}: {}", rel_path, e);
+                result.errors += 1;
+            } else {
+                result.removed += 1;
+            }
         }
 
+        debug!(
+            "On-demand reindex: {} files updated, {} files removed, {} errors",
+            result.indexed, result.removed, result.errors
+        );
+
         Ok(result)
     }
 
-    /// Index a single file
-    fn index_file(&self, path: &Path) -> Result<()> {
-        // Skip excluded paths (hidden dirs, node_modules, large files, etc.)
-        if should_exclude(path) {
+    /// Synchronously reindex the files matching `pattern`, up to `max_files`.
+    ///
+    /// The pattern is either a path relative to the project root (a file or a
+    /// directory) or a gitignore-style glob such as `src/**/*.rs`; `None`
+    /// matches the whole project. Only files whose content changed since they
+    /// were indexed are reindexed, and indexed files that no longer exist are
+    /// removed. Changes beyond `max_files` are counted in `deferred` and left
+    /// to the watcher loop.
+    pub fn reindex_matching(
+        &self,
+        pattern: Option<&str>,
+        max_files: usize,
+    ) -> Result<ReindexReport> {
+        let filter = PathFilter::new(&self.project_root, pattern)?;
+        let _lock = self.try_lock()?;
+        let mut report = ReindexReport::default();
+
+        let candidates = self.collect_candidates(|| {});
+        let mut on_disk = HashSet::new();
+
+        for path in &candidates {
+            let Some(rel_path) = relative_path(&self.project_root, path) else {
+                continue;
+            };
+            if !filter.matches(&rel_path) {
+                continue;
+            }
+            on_disk.insert(rel_path.clone());
+            report.matched += 1;
+
+            let source = match read_source(path) {
+                Ok(source) => source,
+                Err(e) => {
+                    debug!("Skipping {}: {}", rel_path, e);
+                    continue;
+                }
+            };
+            if !self
+                .store
+                .needs_reindex(&rel_path, &source.content)
+                .unwrap_or(true)
+            {
+                report.unchanged += 1;
+                continue;
+            }
+            if report.changed() >= max_files {
+                report.deferred += 1;
+                continue;
+            }
+            match self.index_file(path) {
+                Ok(_) => report.indexed.push(rel_path),
+                Err(e) => {
+                    error!("Failed to reindex {}: {}", rel_path, e);
+                    report.errors.push(rel_path);
+                }
+            }
+        }
+
+        // Indexed files that match but are gone from disk (or now excluded)
+        for rel_path in self.store.get_all_file_paths()? {
+            if is_external_path(&rel_path)
+                || !filter.matches(&rel_path)
+                || on_disk.contains(&rel_path)
+            {
+                continue;
+            }
+            report.matched += 1;
+            if report.changed() >= max_files {
+                report.deferred += 1;
+                continue;
+            }
+            match self.store.delete_file(&rel_path) {
+                Ok(()) => report.removed.push(rel_path),
+                Err(e) => {
+                    error!("Failed to remove {}: {}", rel_path, e);
+                    report.errors.push(rel_path);
+                }
+            }
+        }
+
+        info!(
+            "On-demand reindex ({}): {} matched, {} updated, {} removed, {} unchanged, {} deferred",
+            pattern.unwrap_or("*"),
+            report.matched,
+            report.indexed.len(),
+            report.removed.len(),
+            report.unchanged,
+            report.deferred
+        );
+
+        Ok(report)
+    }
+
+    /// Index a single file, returning the issues met, which are recorded
+    /// with it. Files that fail to parse are indexed without their symbols.
+    fn index_file(&self, path: &Path) -> Result<Vec<IndexIssue>> {
+        // Skip excluded paths (hidden dirs, node_modules, etc.)
+        if should_exclude_path(path) {
             debug!("Skipping excluded path: {:?}", path);
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         // Check if this is a supported language
-        let language = match Language::from_path(path) {
+        let language = match Language::detect(path) {
             Some(lang) => lang,
             None => {
                 debug!("Skipping unsupported file: {:?}", path);
-                return Ok(());
+                return Ok(Vec::new());
             }
         };
 
-        // Get relative path
-        let rel_path = path
-            .strip_prefix(&self.project_root)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .to_string();
+        let Some(rel_path) = relative_path(&self.project_root, path) else {
+            debug!("Skipping path outside the project: {:?}", path);
+            return Ok(Vec::new());
+        };
+
+        // Files too large even for an outline of their top-level symbols
+        let metadata = fs::metadata(path)?;
+        if metadata.len() > MAX_OUTLINE_FILE_SIZE {
+            warn!(
+                "Skipping {}: too large ({} bytes)",
+                rel_path,
+                metadata.len()
+            );
+            return Ok(self.left_out(IndexIssue::too_large(&rel_path, metadata.len())));
+        }
 
-        // Read file content
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
+        // New files past `quota.max_files` wait for the next initial pass,
+        // which keeps the files of highest priority
+        if self.quota.max_files > 0
+            && self.store.get_file_by_path(&rel_path)?.is_none()
+            && self.store.project_file_count()? >= self.quota.max_files
+        {
+            debug!("Skipping {}: quota.max_files reached", rel_path);
+            return Ok(Vec::new());
+        }
+
+        // Read file content, decoding it to UTF-8 if needed
+        let source = match read_source(path) {
+            Ok(source) => source,
             Err(e) => {
-                debug!("Skipping {}: {}", rel_path, e);
-                return Ok(());
+                warn!("Skipping {}: {}", rel_path, e);
+                let issue = IndexIssue::new(&rel_path, IndexIssueKind::ReadError, e);
+                return Ok(self.left_out(issue));
             }
         };
+        let mut issues = Vec::new();
+        if let Some(warning) = &source.warning {
+            warn!("{}: {}", rel_path, warning);
+            issues.push(IndexIssue::new(
+                &rel_path,
+                IndexIssueKind::Encoding,
+                warning,
+            ));
+        }
+        let content = source.content;
 
-        // Get file metadata
-        let metadata = fs::metadata(path)?;
         let size = metadata.len() as i64;
         let last_modified = metadata
             .modified()
@@ -227,112 +803,144 @@ impl AutoIndexer {
             .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0);
+        // Files too large to chunk only get their top-level symbols
+        let outline = is_outline_size(metadata.len());
 
-        // Insert file record
-        let file_id = self.store.insert_file(
-            &rel_path,
-            Some(language.name()),
-            &content,
-            size,
-            last_modified,
+        let extracted = {
+            let mut language_support = self
+                .language_support
+                .lock()
+                .map_err(|e| anyhow::anyhow!("LanguageSupport lock poisoned: {}", e))?;
+            ExtractedFile::extract(
+                &mut language_support,
+                &self.chunk_extractor,
+                language,
+                path,
+                &rel_path,
+                &content,
+                outline,
+            )
+        };
+        let mut extracted = extracted.unwrap_or_else(|e| {
+            warn!("Failed to parse {}: {}", rel_path, e);
+            issues.push(IndexIssue::new(&rel_path, IndexIssueKind::ParseFailure, e));
+            ExtractedFile::default()
+        });
+        self.include_resolver()
+            .classify(&rel_path, &mut extracted.imports);
+        // Directories evicted by `quota.max_db_mb` stay without embeddings
+        let embeddings = if self.store.embeddings_evicted(&rel_path)? {
+            Vec::new()
+        } else {
+            self.embed_chunks(&extracted.chunks)
+        };
+        let summary_embedding = if outline {
+            None
+        } else {
+            self.embed_summary(&rel_path, &extracted.symbols, &extracted.chunks)
+        };
+
+        // Write everything in one transaction so a crash never leaves the file half-indexed
+        self.store.write_file_index(
+            &FileIndexData {
+                encoding: source.encoding,
+                generated: self.generated.is_generated(&rel_path, &content),
+                outline,
+                embeddings: &embeddings,
+                issues: &issues,
+                summary_embedding: summary_embedding.as_deref(),
+                ..FileIndexData::new(
+                    &rel_path,
+                    Some(language.name()),
+                    &content,
+                    size,
+                    last_modified,
+                )
+            }
+            .with_extracted(&extracted),
         )?;
 
-        // Parse and extract symbols
-        let mut language_support = self
-            .language_support
-            .lock()
-            .map_err(|e| anyhow::anyhow!("LanguageSupport lock poisoned: {}", e))?;
-        match language_support.parse(language, &content) {
-            Ok(tree) => {
-                // Extract symbols
-                let symbols = SymbolExtractor::extract(&tree, &content, language)?;
-                self.store.insert_symbols(file_id, &symbols)?;
-
-                // Extract chunks and generate embeddings
-                let chunks = self.chunk_extractor.extract(&tree, &content, language)?;
-                self.store.insert_chunks(file_id, &chunks)?;
-
-                // Generate embeddings for chunks in batch to reduce ONNX overhead
-                let chunks_to_embed = self.store.get_chunks_by_file(file_id)?;
-                if !chunks_to_embed.is_empty() {
-                    let texts: Vec<String> =
-                        chunks_to_embed.iter().map(|c| c.content.clone()).collect();
-                    match self.embedding_model.embed_batch(&texts) {
-                        Ok(embeddings) => {
-                            for (chunk, embedding) in chunks_to_embed.iter().zip(embeddings.iter())
-                            {
-                                if let Err(e) =
-                                    self.store.update_chunk_embedding(chunk.id, embedding)
-                                {
-                                    debug!(
-                                        "Failed to store embedding for chunk {}: {}",
-                                        chunk.id, e
-                                    );
-                                }
-                            }
-                        }
+        debug!(
+            "Auto-indexed {}: {} symbols, {} chunks, {} deps",
+            rel_path,
+            extracted.symbols.len(),
+            extracted.chunks.len(),
+            extracted.imports.len()
+        );
+
+        Ok(issues)
+    }
+
+    /// Generate embeddings for chunks in batch to reduce ONNX overhead,
+    /// falling back to one chunk at a time if the batch fails
+    fn embed_chunks(&self, chunks: &[CodeChunk]) -> Vec<Option<ChunkEmbedding>> {
+        if chunks.is_empty() {
+            return Vec::new();
+        }
+
+        let embedding_model = self.embedding_model();
+        let texts: Vec<String> = chunks
+            .iter()
+            .map(|c| self.store.embedding_text(&c.content))
+            .collect();
+        match embedding_model.embed_chunks(&texts) {
+            Ok(embeddings) => embeddings.into_iter().map(Some).collect(),
+            Err(e) => {
+                debug!("Batch embedding failed, falling back to individual: {}", e);
+                chunks
+                    .iter()
+                    .zip(&texts)
+                    .map(|(chunk, text)| match embedding_model.embed_chunk(text) {
+                        Ok(embedding) => Some(embedding),
                         Err(e) => {
-                            debug!("Batch embedding failed, falling back to individual: {}", e);
-                            // Fallback to individual embedding on batch failure
-                            for chunk in &chunks_to_embed {
-                                match self.embedding_model.embed(&chunk.content) {
-                                    Ok(embedding) => {
-                                        if let Err(e) =
-                                            self.store.update_chunk_embedding(chunk.id, &embedding)
-                                        {
-                                            debug!(
-                                                "Failed to store embedding for chunk {}: {}",
-                                                chunk.id, e
-                                            );
-                                        }
-                                    }
-                                    Err(e) => {
-                                        debug!(
-                                            "Failed to generate embedding for chunk {}: {}",
-                                            chunk.id, e
-                                        );
-                                    }
-                                }
-                            }
+                            debug!(
+                                "Failed to generate embedding for chunk at line {}: {}",
+                                chunk.start_line, e
+                            );
+                            None
                         }
-                    }
-                }
-
-                // Extract imports and store as dependencies
-                let imports = ImportExtractor::extract(&tree, &content, language)?;
-                self.store.delete_dependencies(file_id)?;
-                for import in &imports {
-                    self.store.insert_dependency(
-                        file_id,
-                        &import.path,
-                        import.name.as_deref(),
-                        import.kind.as_str(),
-                    )?;
-                }
+                    })
+                    .collect()
+            }
+        }
+    }
 
+    /// Generate the embedding of a file summary, `None` for a file without
+    /// symbols or chunks or if embedding fails
+    fn embed_summary(
+        &self,
+        rel_path: &str,
+        symbols: &[Symbol],
+        chunks: &[CodeChunk],
+    ) -> Option<Vec<f32>> {
+        let summary = file_summary(
+            rel_path,
+            symbols
+                .iter()
+                .map(|s| (s.name.as_str(), s.doc_comment.as_deref())),
+            chunks.iter().map(|c| (c.content.as_str(), c.symbols.len())),
+        )?;
+        match self
+            .embedding_model()
+            .embed(&self.store.passage_text(&summary))
+        {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
                 debug!(
-                    "Auto-indexed {}: {} symbols, {} chunks, {} deps",
-                    rel_path,
-                    symbols.len(),
-                    chunks.len(),
-                    imports.len()
+                    "Failed to generate summary embedding for {}: {}",
+                    rel_path, e
                 );
-            }
-            Err(e) => {
-                warn!("Failed to parse {}: {}", rel_path, e);
+                None
             }
         }
-
-        Ok(())
     }
 
     /// Remove a file from the index
     fn remove_file(&self, path: &Path) -> Result<()> {
-        let rel_path = path
-            .strip_prefix(&self.project_root)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .to_string();
+        let Some(rel_path) = relative_path(&self.project_root, path) else {
+            debug!("Skipping path outside the project: {:?}", path);
+            return Ok(());
+        };
 
         self.store.delete_file(&rel_path)?;
         debug!("Removed from index: {}", rel_path);
@@ -348,10 +956,180 @@ pub struct ProcessResult {
     pub errors: usize,
 }
 
+/// Outcome of [`AutoIndexer::reindex_matching`]
+#[derive(Default, Debug, Serialize)]
+pub struct ReindexReport {
+    /// Files matching the pattern, on disk or in the index
+    pub matched: usize,
+    /// Relative paths of files reindexed because their content changed
+    pub indexed: Vec<String>,
+    /// Relative paths of files removed from the index
+    pub removed: Vec<String>,
+    /// Matching files already up to date
+    pub unchanged: usize,
+    /// Changed files left for the watcher because the limit was reached
+    pub deferred: usize,
+    /// Relative paths of files that failed to reindex
+    pub errors: Vec<String>,
+}
+
+impl ReindexReport {
+    /// Number of files processed (updated, removed or failed)
+    pub fn changed(&self) -> usize {
+        self.indexed.len() + self.removed.len() + self.errors.len()
+    }
+}
+
+/// Selects the files targeted by an on-demand reindex
+enum PathFilter {
+    All,
+    /// A file path or directory prefix relative to the project root
+    Path(String),
+    Glob(Override),
+}
+
+impl PathFilter {
+    fn new(project_root: &Path, pattern: Option<&str>) -> Result<Self> {
+        let pattern = match pattern.map(|p| p.trim()) {
+            None | Some("") | Some(".") => return Ok(PathFilter::All),
+            Some(p) => p,
+        };
+
+        if pattern.contains(['*', '?', '[']) {
+            let mut builder = OverrideBuilder::new(project_root);
+            builder.add(pattern.trim_start_matches("./"))?;
+            Ok(PathFilter::Glob(builder.build()?))
+        } else {
+            match normalize_path(pattern) {
+                p if p.is_empty() => Ok(PathFilter::All),
+                p => Ok(PathFilter::Path(p)),
+            }
+        }
+    }
+
+    fn matches(&self, rel_path: &str) -> bool {
+        match self {
+            PathFilter::All => true,
+            PathFilter::Path(prefix) => {
+                rel_path == prefix
+                    || rel_path
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }
+            PathFilter::Glob(glob) => glob.matched(rel_path, false).is_whitelist(),
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct InitialIndexResult {
     pub scanned: usize,
     pub indexed: usize,
     pub skipped: usize,
     pub errors: usize,
+    /// Indexed files whose encoding was guessed or that had invalid bytes replaced
+    pub warnings: usize,
+    /// Files re-queued because a previous run was interrupted while indexing them
+    pub recovered: usize,
+    /// Files of lowest priority left out by `quota.max_files`
+    pub over_quota: usize,
+    /// Issues met by the files indexed or left out
+    pub issues: IndexReport,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exclusions::MAX_FILE_SIZE;
+
+    fn filter(pattern: Option<&str>) -> PathFilter {
+        PathFilter::new(Path::new("/project"), pattern).unwrap()
+    }
+
+    #[test]
+    fn test_path_filter_all() {
+        for pattern in [None, Some(""), Some("."), Some("./")] {
+            assert!(filter(pattern).matches("src/main.rs"));
+        }
+    }
+
+    #[test]
+    fn test_path_filter_file_and_directory() {
+        let file = filter(Some("src/main.rs"));
+        assert!(file.matches("src/main.rs"));
+        assert!(!file.matches("src/main.rs.bak"));
+
+        let dir = filter(Some("./src/"));
+        assert!(dir.matches("src/main.rs"));
+        assert!(dir.matches("src/nested/lib.rs"));
+        assert!(!dir.matches("srcs/main.rs"));
+        assert!(!dir.matches("tests/main.rs"));
+    }
+
+    #[test]
+    fn test_path_filter_glob() {
+        let glob = filter(Some("src/**/*.rs"));
+        assert!(glob.matches("src/main.rs"));
+        assert!(glob.matches("src/a/b.rs"));
+        assert!(!glob.matches("src/main.ts"));
+        assert!(!glob.matches("tests/main.rs"));
+
+        let ext = filter(Some("*.py"));
+        assert!(ext.matches("app.py"));
+        assert!(ext.matches("pkg/mod.py"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_path_filter_windows_separators() {
+        let dir = filter(Some(r".\src\nested\"));
+        assert!(dir.matches("src/nested/lib.rs"));
+        assert!(!dir.matches("src/main.rs"));
+    }
+
+    #[test]
+    fn test_oversized_files_are_indexed_as_outlines() {
+        // Not the default `.tmp` prefix, which would mark the files hidden
+        let dir = tempfile::Builder::new()
+            .prefix("project")
+            .tempdir()
+            .unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        let line = format!("// {}\n", "generated ".repeat(25));
+        let padding = line.repeat(MAX_FILE_SIZE as usize / line.len() + 1);
+        fs::write(
+            src.join("big.rs"),
+            format!(
+                "pub struct Table;\n\nimpl Table {{\n    pub fn row(&self) -> usize {{ 0 }}\n}}\n{}pub fn lookup(key: u32) -> u32 {{ key }}\n",
+                padding
+            ),
+        )
+        .unwrap();
+        fs::write(src.join("small.rs"), "pub fn small() {}\n").unwrap();
+
+        let store = Arc::new(IndexStore::open_in_memory().unwrap());
+        let indexer = AutoIndexer::new(Arc::clone(&store), dir.path().to_path_buf()).unwrap();
+        assert_eq!(indexer.initial_index().unwrap().errors, 0);
+
+        let big = store.get_file_by_path("src/big.rs").unwrap().unwrap();
+        let mut names: Vec<String> = store
+            .get_symbols_by_file(big.id)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["Table", "Table", "lookup"]);
+        assert!(store.get_chunks_by_file(big.id).unwrap().is_empty());
+
+        let small = store.get_file_by_path("src/small.rs").unwrap().unwrap();
+        assert!(!store.get_chunks_by_file(small.id).unwrap().is_empty());
+        assert_eq!(
+            store
+                .get_outline_files(&["src/big.rs", "src/small.rs"])
+                .unwrap(),
+            HashSet::from(["src/big.rs".to_string()])
+        );
+    }
 }
@@ -0,0 +1,20 @@
+//! Current time as stored in the index: Unix seconds, or milliseconds for
+//! the query cache
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current Unix time in seconds, 0 if the clock is before the epoch
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Current Unix time in milliseconds, 0 if the clock is before the epoch
+pub fn unix_now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,227 @@
+//! zstd compression of chunk content
+//!
+//! A compressed chunk stores its content as a BLOB holding one zstd frame
+//! instead of TEXT, so compressed and plain chunks coexist in the same column
+//! and reads decompress transparently. Code chunks are short and compress
+//! poorly on their own; `semantiq compact` trains a dictionary on the
+//! project's chunks (shared identifiers, imports, boilerplate) and frames
+//! written afterwards reference it by its zstd dictionary id.
+
+use anyhow::{Context, Result, anyhow};
+use rusqlite::types::{Value, ValueRef};
+use std::io::Read;
+use std::sync::Arc;
+use tracing::warn;
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+use zstd::zstd_safe;
+
+/// zstd compression level used for chunk content
+pub const COMPRESSION_LEVEL: i32 = 3;
+
+/// Maximum size of a trained dictionary
+pub const MAX_DICTIONARY_SIZE: usize = 112 * 1024;
+
+/// Minimum number of chunks needed to train a dictionary
+pub const MIN_TRAINING_SAMPLES: usize = 64;
+
+/// A zstd dictionary trained on the chunks of a project
+pub struct ChunkDictionary {
+    id: u32,
+    bytes: Vec<u8>,
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl ChunkDictionary {
+    /// Load a dictionary from its serialized form
+    pub fn new(bytes: Vec<u8>) -> Result<Self> {
+        let id = zstd_safe::get_dict_id(&bytes)
+            .ok_or_else(|| anyhow!("Invalid zstd dictionary: missing dictionary id"))?
+            .get();
+        Ok(Self {
+            id,
+            encoder: EncoderDictionary::copy(&bytes, COMPRESSION_LEVEL),
+            decoder: DecoderDictionary::copy(&bytes),
+            bytes,
+        })
+    }
+
+    /// Train a dictionary on chunk contents.
+    ///
+    /// Returns `None` when there are too few samples or training fails, in
+    /// which case chunks are compressed without a dictionary.
+    pub fn train<S: AsRef<[u8]>>(samples: &[S]) -> Option<Self> {
+        if samples.len() < MIN_TRAINING_SAMPLES {
+            return None;
+        }
+
+        match zstd::dict::from_samples(samples, MAX_DICTIONARY_SIZE)
+            .map_err(anyhow::Error::from)
+            .and_then(Self::new)
+        {
+            Ok(dictionary) => Some(dictionary),
+            Err(e) => {
+                warn!("Failed to train a chunk dictionary: {}", e);
+                None
+            }
+        }
+    }
+
+    /// zstd dictionary id, recorded in every frame compressed with it
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Encodes chunk content for storage and decodes it back
+#[derive(Clone, Default)]
+pub struct ChunkCodec {
+    compress: bool,
+    dictionary: Option<Arc<ChunkDictionary>>,
+}
+
+impl ChunkCodec {
+    pub fn new(compress: bool, dictionary: Option<Arc<ChunkDictionary>>) -> Self {
+        Self {
+            compress,
+            dictionary,
+        }
+    }
+
+    /// Whether new chunks are stored compressed
+    pub fn compresses(&self) -> bool {
+        self.compress
+    }
+
+    pub fn dictionary(&self) -> Option<&Arc<ChunkDictionary>> {
+        self.dictionary.as_ref()
+    }
+
+    /// Value stored in `chunks.content`: a zstd frame when compression is
+    /// enabled and saves space, the text itself otherwise.
+    pub fn encode(&self, content: &str) -> Result<Value> {
+        if !self.compress {
+            return Ok(Value::Text(content.to_string()));
+        }
+
+        let compressed = self.compress(content)?;
+        if compressed.len() < content.len() {
+            Ok(Value::Blob(compressed))
+        } else {
+            Ok(Value::Text(content.to_string()))
+        }
+    }
+
+    /// Compress content into a zstd frame, with the dictionary if there is one
+    pub fn compress(&self, content: &str) -> Result<Vec<u8>> {
+        let compressed = match self.dictionary {
+            Some(ref dictionary) => {
+                zstd::bulk::Compressor::with_prepared_dictionary(&dictionary.encoder)?
+                    .compress(content.as_bytes())?
+            }
+            None => zstd::bulk::compress(content.as_bytes(), COMPRESSION_LEVEL)?,
+        };
+        Ok(compressed)
+    }
+
+    /// Read `chunks.content`, decompressing it if it is stored as a BLOB
+    pub fn decode(&self, value: ValueRef<'_>) -> Result<String> {
+        match value {
+            ValueRef::Text(text) => Ok(String::from_utf8(text.to_vec())?),
+            ValueRef::Blob(frame) => self.decompress(frame),
+            other => Err(anyhow!(
+                "Unexpected chunk content type: {}",
+                other.data_type()
+            )),
+        }
+    }
+
+    fn decompress(&self, frame: &[u8]) -> Result<String> {
+        let mut content = String::new();
+        match zstd_safe::get_dict_id_from_frame(frame) {
+            Some(id) => {
+                let dictionary = self
+                    .dictionary
+                    .as_ref()
+                    .filter(|d| d.id == id.get())
+                    .ok_or_else(|| anyhow!("Chunk compressed with unknown dictionary {}", id))?;
+                zstd::stream::read::Decoder::with_prepared_dictionary(frame, &dictionary.decoder)?
+                    .read_to_string(&mut content)
+            }
+            None => zstd::stream::read::Decoder::with_buffer(frame)?.read_to_string(&mut content),
+        }
+        .context("Failed to decompress chunk content")?;
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<String> {
+        (0..200)
+            .map(|i| {
+                format!(
+                    "pub fn handler_{i}(request: &Request) -> Result<Response> {{\n    let user = request.user()?;\n    info!(\"handling request {i}\");\n    Ok(Response::new(user.id + {i}))\n}}\n"
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_codec_stores_text() {
+        let codec = ChunkCodec::default();
+        let value = codec.encode("fn main() {}").unwrap();
+        assert_eq!(value, Value::Text("fn main() {}".to_string()));
+    }
+
+    #[test]
+    fn test_round_trip_without_dictionary() {
+        let codec = ChunkCodec::new(true, None);
+        let content = "let x = 1;\n".repeat(50);
+
+        let Value::Blob(frame) = codec.encode(&content).unwrap() else {
+            panic!("expected compressed content");
+        };
+        assert!(frame.len() < content.len());
+        assert_eq!(codec.decode(ValueRef::Blob(&frame)).unwrap(), content);
+        assert_eq!(codec.decode(ValueRef::Text(b"plain")).unwrap(), "plain");
+
+        // Content that does not shrink stays text
+        assert!(matches!(codec.encode("x").unwrap(), Value::Text(_)));
+    }
+
+    #[test]
+    fn test_round_trip_with_trained_dictionary() {
+        let samples = samples();
+        let dictionary = Arc::new(ChunkDictionary::train(&samples).expect("dictionary"));
+        let codec = ChunkCodec::new(true, Some(dictionary.clone()));
+
+        let content = &samples[42];
+        let frame = codec.compress(content).unwrap();
+        let plain = ChunkCodec::new(true, None).compress(content).unwrap();
+        assert!(frame.len() < plain.len());
+        assert_eq!(codec.decode(ValueRef::Blob(&frame)).unwrap(), *content);
+
+        // Reloading the dictionary from its bytes keeps its id
+        let reloaded = ChunkDictionary::new(dictionary.as_bytes().to_vec()).unwrap();
+        assert_eq!(reloaded.id(), dictionary.id());
+
+        // A frame needing a dictionary cannot be read without it
+        assert!(
+            ChunkCodec::default()
+                .decode(ValueRef::Blob(&frame))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_too_few_samples_skip_training() {
+        assert!(ChunkDictionary::train(&samples()[..10]).is_none());
+    }
+}
@@ -0,0 +1,837 @@
+//! Project configuration loaded from `semantiq.toml`
+//!
+//! The file is optional and lives at the project root. Missing sections and
+//! keys fall back to their defaults, e.g.:
+//!
+//! ```toml
+//! [embeddings]
+//! model = "multilingual-minilm"
+//! quantization = "int8"
+//! switch_coverage = 0.9
+//! preprocess = ["strip_license_headers", "collapse_whitespace"]
+//!
+//! [embeddings.prompts.multilingual-minilm]
+//! query = "query: "
+//! passage = "passage: "
+//!
+//! [external]
+//! enabled = true
+//!
+//! [cache]
+//! capacity = 512
+//! persist = true
+//! query_embeddings_capacity = 2000
+//! query_embeddings_ttl_days = 7
+//!
+//! [storage]
+//! compress_chunks = true
+//! file_contents = true
+//! scan_memory_mb = 128
+//!
+//! [chunking]
+//! config_key_depth = 2
+//!
+//! [search]
+//! coarse_min_chunks = 20000
+//! coarse_files = 100
+//! snippet_tokens = 120
+//! timeout_ms = 5000
+//!
+//! [includes]
+//! paths = ["include", "third_party/zlib"]
+//! compile_commands = "out/compile_commands.json"
+//!
+//! [git]
+//! enabled = true
+//! history_commits = 1000
+//!
+//! [usage]
+//! enabled = false
+//!
+//! [base]
+//! path = ".semantiq/base.db"
+//!
+//! [mcp]
+//! structured_content = false
+//!
+//! [quota]
+//! max_files = 200000
+//! max_db_mb = 4096
+//! path_weights = { "services/billing" = 50, "legacy" = -100 }
+//!
+//! [recalibration]
+//! observation_delta = 1000
+//! max_age_days = 14
+//!
+//! [history]
+//! max_generations = 50000
+//!
+//! [limits]
+//! requests_per_second = 5.0
+//! max_results = 200
+//! index_queue = 4
+//!
+//! [limits.tools.semantiq_search]
+//! burst = 5
+//! max_concurrent = 2
+//!
+//! [[languages]]
+//! name = "mydsl"
+//! grammar = "grammars/libtree-sitter-mydsl.so"
+//! extensions = ["dsl"]
+//! symbols = { rule_definition = "function" }
+//!
+//! [symbol_kinds.ruby]
+//! constant = "type"
+//! ```
+
+use crate::preprocess::PreprocessStep;
+use crate::quantization::EmbeddingFormat;
+use anyhow::{Context, Result};
+use semantiq_embeddings::{EmbeddingConfig, EmbeddingModelKind, EmbeddingPrompts};
+use semantiq_parser::{ChunkExtractor, LanguagePlugin, SymbolKindOverrides};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file at the project root
+pub const CONFIG_FILE_NAME: &str = "semantiq.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SemantiqConfig {
+    pub embeddings: EmbeddingsConfig,
+    pub external: ExternalConfig,
+    pub cache: QueryCacheConfig,
+    pub storage: StorageConfig,
+    pub chunking: ChunkingConfig,
+    pub search: SearchConfig,
+    pub limits: LimitsConfig,
+    pub git: GitConfig,
+    pub includes: IncludesConfig,
+    pub usage: UsageConfig,
+    pub base: BaseIndexConfig,
+    pub mcp: McpConfig,
+    pub quota: QuotaConfig,
+    pub recalibration: RecalibrationConfig,
+    pub history: HistoryConfig,
+    /// Custom languages parsed with compiled tree-sitter grammars
+    pub languages: Vec<LanguagePlugin>,
+    /// Symbol kinds of node kinds overriding the built-in mappings, by
+    /// language (see `semantiq_parser::symbol_kinds`)
+    pub symbol_kinds: SymbolKindOverrides,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    /// Model used to embed code and queries ("minilm" or "multilingual-minilm")
+    pub model: EmbeddingModelKind,
+    /// Storage format of chunk embeddings ("float32" or "int8")
+    pub quantization: EmbeddingFormat,
+    /// Share of the chunks embedded again with a new `model` before searches
+    /// switch to it; the remaining chunks are embedded right after the switch
+    pub switch_coverage: f64,
+    /// Steps applied, in order, to the text of chunks before embedding;
+    /// changing them re-embeds the chunks like a model change
+    pub preprocess: Vec<PreprocessStep>,
+    /// Prompt templates of the query and passage sides, by model name,
+    /// replacing those the model was trained with; changing the passage
+    /// side re-embeds the chunks like a model change
+    pub prompts: BTreeMap<String, EmbeddingPrompts>,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            model: EmbeddingModelKind::default(),
+            quantization: EmbeddingFormat::default(),
+            switch_coverage: 0.95,
+            preprocess: Vec::new(),
+            prompts: BTreeMap::new(),
+        }
+    }
+}
+
+impl EmbeddingsConfig {
+    /// Prompts of `model`: the configured ones, or else those it was
+    /// trained with
+    pub fn prompts_for(&self, model: EmbeddingModelKind) -> EmbeddingPrompts {
+        self.prompts
+            .get(model.as_str())
+            .cloned()
+            .unwrap_or_else(|| EmbeddingConfig::for_model(model).prompts)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExternalConfig {
+    /// Index the sources of declared dependencies (Cargo registry, node_modules type declarations)
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryCacheConfig {
+    /// Cache search results until the index changes
+    pub enabled: bool,
+    /// Maximum number of cached queries
+    pub capacity: usize,
+    /// Also store cached results in the database so they survive restarts
+    pub persist: bool,
+    /// Keep the embeddings of recent queries in the database, so that a
+    /// repeated query is not embedded again, also after a restart
+    pub query_embeddings: bool,
+    /// Maximum number of cached query embeddings
+    pub query_embeddings_capacity: usize,
+    /// Days a cached query embedding is used for
+    pub query_embeddings_ttl_days: u64,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: 256,
+            persist: false,
+            query_embeddings: true,
+            query_embeddings_capacity: 1000,
+            query_embeddings_ttl_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Store chunk content compressed with zstd (see `semantiq compact`)
+    pub compress_chunks: bool,
+    /// Store the compressed content of indexed files, so searches and line
+    /// reads work without the project's files
+    pub file_contents: bool,
+    /// Memory taken by a page of chunks read by a pass over the whole index,
+    /// such as a change of `embeddings.quantization`, in MB
+    pub scan_memory_mb: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            compress_chunks: false,
+            file_contents: false,
+            scan_memory_mb: crate::store::DEFAULT_SCAN_MEMORY_MB,
+        }
+    }
+}
+
+impl StorageConfig {
+    /// `scan_memory_mb` in bytes, at least 1 MB
+    pub fn scan_memory_bytes(&self) -> usize {
+        self.scan_memory_mb.max(1) as usize * 1024 * 1024
+    }
+}
+
+/// How files are split into chunks; changes apply to files indexed again
+/// (see `semantiq index --force`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkingConfig {
+    /// Depth of the keys of JSON, YAML and TOML files chunked on their own:
+    /// 1 for one chunk per top-level key, 2 to also split their keys, and so
+    /// on. Keys larger than a chunk are split further regardless.
+    pub config_key_depth: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            config_key_depth: 1,
+        }
+    }
+}
+
+impl ChunkingConfig {
+    /// Chunk extractor splitting files as configured
+    pub fn chunk_extractor(&self) -> ChunkExtractor {
+        ChunkExtractor::new().with_config_key_depth(self.config_key_depth)
+    }
+}
+
+/// Semantic search strategy and result snippets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Chunks in the index from which semantic searches first pick the closest
+    /// files by their summary, then rank the chunks of those files; 0 to
+    /// always compare the query with every chunk
+    pub coarse_min_chunks: usize,
+    /// Files whose chunks are ranked after the coarse step
+    pub coarse_files: usize,
+    /// Approximate tokens of the snippet shown for each result, centered on
+    /// the query terms; 0 to show the whole result
+    pub snippet_tokens: usize,
+    /// Time after which a search returns the results found so far, in
+    /// milliseconds; 0 for no limit
+    pub timeout_ms: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            coarse_min_chunks: 50_000,
+            coarse_files: 200,
+            snippet_tokens: 80,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+/// Use of the project's git history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Read the history of the project's git repository, e.g. to suggest
+    /// files that changed together in `semantiq_related`
+    pub enabled: bool,
+    /// Most recent commits read from the history of a file
+    pub history_commits: usize,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            history_commits: 500,
+        }
+    }
+}
+
+/// Resolution of the `#include` directives of C and C++ files
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncludesConfig {
+    /// Directories searched for included files after the directory of the
+    /// including file, relative to the project root
+    pub paths: Vec<PathBuf>,
+    /// Compilation database whose `-I`, `-iquote` and `-isystem` directories
+    /// are searched too, relative to the project root (default:
+    /// `compile_commands.json` at the root or in `build/`)
+    pub compile_commands: Option<PathBuf>,
+}
+
+/// Usage statistics of the tools, kept in the index database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Count tool calls, query categories and calls without results for
+    /// `semantiq stats --usage`. Nothing is ever sent anywhere.
+    pub enabled: bool,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Read-only index searched along with the local one, e.g. built nightly
+/// in CI and downloaded, so that the whole project is searchable before
+/// the local index is complete
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BaseIndexConfig {
+    /// Database of the base index, relative to the project root
+    pub path: Option<PathBuf>,
+}
+
+impl BaseIndexConfig {
+    /// Path of the base index database, if one is configured
+    pub fn resolve(&self, project_root: &Path) -> Option<PathBuf> {
+        self.path.as_ref().map(|path| project_root.join(path))
+    }
+}
+
+/// Responses of the MCP tools
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Follow the text of tool responses with their JSON and an embedded
+    /// resource per result, for clients that render results natively
+    pub structured_content: bool,
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            structured_content: true,
+        }
+    }
+}
+
+/// Guards on tool calls from MCP clients and the `/api` endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Throttle tool calls; `max_results` applies either way
+    pub enabled: bool,
+    /// Calls per second each tool sustains, 0 to not throttle
+    pub requests_per_second: f64,
+    /// Calls of a tool accepted at once before throttling starts
+    pub burst: u32,
+    /// Calls of a tool running at the same time, 0 for no cap
+    pub max_concurrent: usize,
+    /// Upper bound of the `limit` parameter of the tools
+    pub max_results: usize,
+    /// Index queries running at the same time on the blocking threads, the
+    /// others waiting in a queue (at least 1)
+    pub index_queue: usize,
+    /// Overrides for individual tools, keyed by tool name
+    pub tools: BTreeMap<String, ToolLimitsConfig>,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            requests_per_second: 10.0,
+            burst: 20,
+            max_concurrent: 4,
+            max_results: 1000,
+            index_queue: crate::async_store::DEFAULT_INDEX_QUEUE,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// Limits of one tool, falling back to those of [`LimitsConfig`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolLimitsConfig {
+    pub requests_per_second: Option<f64>,
+    pub burst: Option<u32>,
+    pub max_concurrent: Option<usize>,
+}
+
+impl LimitsConfig {
+    /// Effective limits of `tool`: (calls per second, burst, concurrent calls)
+    pub fn for_tool(&self, tool: &str) -> (f64, u32, usize) {
+        let overrides = self.tools.get(tool);
+        (
+            overrides
+                .and_then(|t| t.requests_per_second)
+                .unwrap_or(self.requests_per_second),
+            overrides.and_then(|t| t.burst).unwrap_or(self.burst),
+            overrides
+                .and_then(|t| t.max_concurrent)
+                .unwrap_or(self.max_concurrent),
+        )
+    }
+}
+
+/// Limits of the index of a project too large to index in full
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    /// Files indexed at most, those of highest priority (see
+    /// [`crate::priority`]); 0 for no limit
+    pub max_files: usize,
+    /// Size of the database in megabytes past which the embeddings of the
+    /// least recently searched directories are dropped, their symbols kept;
+    /// 0 for no limit
+    pub max_db_mb: u64,
+    /// Priority added to the files under a path, e.g. 50 to index a team's
+    /// services first or -100 to leave legacy code out first. The longest
+    /// matching path applies.
+    pub path_weights: BTreeMap<String, i32>,
+}
+
+impl QuotaConfig {
+    /// Priority added to a file by `path_weights`
+    pub fn path_weight(&self, rel_path: &str) -> i32 {
+        self.path_weights
+            .iter()
+            .filter(|(path, _)| {
+                let path = path.trim_end_matches('/');
+                rel_path == path
+                    || rel_path
+                        .strip_prefix(path)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(path, _)| path.len())
+            .map_or(0, |(_, weight)| *weight)
+    }
+
+    /// Size of the database past which embeddings are dropped, if limited
+    pub fn max_db_bytes(&self) -> Option<u64> {
+        (self.max_db_mb > 0).then(|| self.max_db_mb * 1024 * 1024)
+    }
+}
+
+/// Calibration of the semantic search thresholds again as live searches
+/// record the distances of their results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecalibrationConfig {
+    /// Recalibrate in the background of `semantiq serve`
+    pub enabled: bool,
+    /// Observations recorded beyond the samples of the last calibration
+    /// after which the thresholds are calibrated again
+    pub observation_delta: usize,
+    /// Days after which thresholds are calibrated again and observations
+    /// are dropped; 0 to keep both
+    pub max_age_days: u64,
+    /// Seconds between two checks of the observations
+    pub check_interval_secs: u64,
+}
+
+impl Default for RecalibrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            observation_delta: 500,
+            max_age_days: 30,
+            check_interval_secs: 600,
+        }
+    }
+}
+
+impl RecalibrationConfig {
+    /// Age in seconds after which thresholds and observations expire, if any
+    pub fn max_age_secs(&self) -> Option<i64> {
+        (self.max_age_days > 0).then(|| self.max_age_days as i64 * 24 * 60 * 60)
+    }
+}
+
+/// Retention of the symbol history (see `store::history`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Index generations back from the current one that the history
+    /// answers for; older symbol versions and occurrence counts are
+    /// pruned. 0 to keep them all
+    pub max_generations: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_generations: 10_000,
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Generations kept, if limited
+    pub fn retained_generations(&self) -> Option<u64> {
+        (self.max_generations > 0).then_some(self.max_generations)
+    }
+}
+
+impl SemantiqConfig {
+    /// Load `semantiq.toml` from the project root, or the defaults if it does not exist
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("Invalid configuration in {:?}", path))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Load the grammars of the custom languages, so that their files are
+    /// detected and parsed, and register the symbol kind overrides
+    pub fn register_languages(&self, project_root: &Path) -> Result<()> {
+        semantiq_parser::register_languages(&self.languages, project_root)?;
+        semantiq_parser::register_symbol_kinds(&self.symbol_kinds)
+            .context("Invalid [symbol_kinds]")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quantization() {
+        let config = SemantiqConfig::parse("[embeddings]\nquantization = \"int8\"\n").unwrap();
+        assert_eq!(config.embeddings.quantization, EmbeddingFormat::Int8);
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = SemantiqConfig::parse("").unwrap();
+        assert_eq!(config, SemantiqConfig::default());
+        assert_eq!(config.embeddings.quantization, EmbeddingFormat::Float32);
+        assert!(!config.external.enabled);
+    }
+
+    #[test]
+    fn test_parse_model() {
+        let config =
+            SemantiqConfig::parse("[embeddings]\nmodel = \"multilingual-minilm\"\n").unwrap();
+        assert_eq!(
+            config.embeddings.model,
+            EmbeddingModelKind::MultilingualMiniLm
+        );
+        assert!(SemantiqConfig::parse("[embeddings]\nmodel = \"bert\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_switch_coverage() {
+        let config = SemantiqConfig::parse("[embeddings]\nswitch_coverage = 0.8\n").unwrap();
+        assert_eq!(config.embeddings.switch_coverage, 0.8);
+        assert_eq!(SemantiqConfig::default().embeddings.switch_coverage, 0.95);
+    }
+
+    #[test]
+    fn test_parse_preprocess() {
+        let config = SemantiqConfig::parse(
+            "[embeddings]\npreprocess = [\"strip_license_headers\", \"collapse_whitespace\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.embeddings.preprocess,
+            vec![
+                PreprocessStep::StripLicenseHeaders,
+                PreprocessStep::CollapseWhitespace
+            ]
+        );
+        assert!(SemantiqConfig::parse("[embeddings]\npreprocess = [\"minify\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_external() {
+        let config = SemantiqConfig::parse("[external]\nenabled = true\n").unwrap();
+        assert!(config.external.enabled);
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        let config = SemantiqConfig::parse("[cache]\ncapacity = 32\npersist = true\n").unwrap();
+        assert!(config.cache.enabled);
+        assert_eq!(config.cache.capacity, 32);
+        assert!(config.cache.persist);
+        assert!(config.cache.query_embeddings);
+
+        let config = SemantiqConfig::parse(
+            "[cache]
+enabled = false
+query_embeddings_ttl_days = 7
+",
+        )
+        .unwrap();
+        assert!(!config.cache.enabled);
+        assert!(config.cache.query_embeddings);
+        assert_eq!(config.cache.query_embeddings_ttl_days, 7);
+        assert_eq!(config.cache.query_embeddings_capacity, 1000);
+    }
+
+    #[test]
+    fn test_parse_storage() {
+        let config = SemantiqConfig::parse("[storage]\ncompress_chunks = true\n").unwrap();
+        assert!(config.storage.compress_chunks);
+        assert!(!SemantiqConfig::default().storage.compress_chunks);
+
+        let config = SemantiqConfig::parse("[storage]\nfile_contents = true\n").unwrap();
+        assert!(config.storage.file_contents);
+        assert!(!config.storage.compress_chunks);
+        assert_eq!(config.storage.scan_memory_mb, 64);
+
+        let config = SemantiqConfig::parse("[storage]\nscan_memory_mb = 0\n").unwrap();
+        assert_eq!(config.storage.scan_memory_bytes(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_parse_chunking() {
+        let config = SemantiqConfig::parse("[chunking]\nconfig_key_depth = 3\n").unwrap();
+        assert_eq!(config.chunking.config_key_depth, 3);
+        assert_eq!(SemantiqConfig::default().chunking.config_key_depth, 1);
+    }
+
+    #[test]
+    fn test_parse_languages() {
+        let config = SemantiqConfig::parse(
+            "[[languages]]\nname = \"mydsl\"\ngrammar = \"grammars/mydsl.so\"\nextensions = [\"dsl\"]\n\n[languages.symbols]\nrule_definition = \"function\"\n",
+        )
+        .unwrap();
+        let language = &config.languages[0];
+        assert_eq!(language.name, "mydsl");
+        assert_eq!(language.name_field, "name");
+        assert_eq!(language.entry_point, None);
+        assert_eq!(
+            language.symbols["rule_definition"],
+            semantiq_parser::SymbolKind::Function
+        );
+        assert!(SemantiqConfig::parse("[[languages]]\nname = \"mydsl\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_symbol_kinds() {
+        let config = SemantiqConfig::parse(
+            "[symbol_kinds.ruby]\nconstant = \"type\"\n\n[symbol_kinds.html]\nelement = \"none\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.symbol_kinds["ruby"]["constant"], "type");
+        assert_eq!(config.symbol_kinds["html"]["element"], "none");
+        assert!(SemantiqConfig::parse("[symbol_kinds]\nruby = \"type\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_search() {
+        let config =
+            SemantiqConfig::parse("[search]\ncoarse_min_chunks = 0\nsnippet_tokens = 40\n")
+                .unwrap();
+        assert_eq!(config.search.coarse_min_chunks, 0);
+        assert_eq!(config.search.coarse_files, 200);
+        assert_eq!(config.search.snippet_tokens, 40);
+        assert_eq!(config.search.timeout_ms, 10_000);
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let config = SemantiqConfig::parse(
+            "[limits]\nrequests_per_second = 2.5\nmax_results = 50\nindex_queue = 2\n\n[limits.tools.semantiq_search]\nburst = 3\n",
+        )
+        .unwrap();
+        assert!(config.limits.enabled);
+        assert_eq!(config.limits.max_results, 50);
+        assert_eq!(config.limits.index_queue, 2);
+        assert_eq!(SemantiqConfig::default().limits.index_queue, 8);
+        assert_eq!(config.limits.for_tool("semantiq_search"), (2.5, 3, 4));
+        assert_eq!(config.limits.for_tool("semantiq_deps"), (2.5, 20, 4));
+    }
+
+    #[test]
+    fn test_parse_git() {
+        assert!(SemantiqConfig::default().git.enabled);
+        let config = SemantiqConfig::parse("[git]\nenabled = false\n").unwrap();
+        assert!(!config.git.enabled);
+        assert_eq!(config.git.history_commits, 500);
+    }
+
+    #[test]
+    fn test_parse_includes() {
+        assert!(SemantiqConfig::default().includes.paths.is_empty());
+        let config = SemantiqConfig::parse(
+            "[includes]\npaths = [\"include\"]\ncompile_commands = \"out/compile_commands.json\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.includes.paths, [PathBuf::from("include")]);
+        assert_eq!(
+            config.includes.compile_commands,
+            Some(PathBuf::from("out/compile_commands.json"))
+        );
+    }
+
+    #[test]
+    fn test_parse_usage() {
+        assert!(SemantiqConfig::default().usage.enabled);
+        let config = SemantiqConfig::parse("[usage]\nenabled = false\n").unwrap();
+        assert!(!config.usage.enabled);
+    }
+
+    #[test]
+    fn test_parse_base() {
+        assert_eq!(
+            SemantiqConfig::default().base.resolve(Path::new("/p")),
+            None
+        );
+        let config = SemantiqConfig::parse("[base]\npath = \".semantiq/base.db\"\n").unwrap();
+        assert_eq!(
+            config.base.resolve(Path::new("/p")),
+            Some(PathBuf::from("/p/.semantiq/base.db"))
+        );
+    }
+
+    #[test]
+    fn test_parse_mcp() {
+        assert!(SemantiqConfig::default().mcp.structured_content);
+        let config = SemantiqConfig::parse("[mcp]\nstructured_content = false\n").unwrap();
+        assert!(!config.mcp.structured_content);
+    }
+
+    #[test]
+    fn test_parse_quota() {
+        assert_eq!(SemantiqConfig::default().quota.max_db_bytes(), None);
+        let config = SemantiqConfig::parse(
+            "[quota]\nmax_files = 1000\nmax_db_mb = 2\n\n[quota.path_weights]\n\"services\" = 10\n\"services/billing/\" = 50\n",
+        )
+        .unwrap();
+        assert_eq!(config.quota.max_files, 1000);
+        assert_eq!(config.quota.max_db_bytes(), Some(2 * 1024 * 1024));
+        assert_eq!(config.quota.path_weight("services/billing/api.rs"), 50);
+        assert_eq!(config.quota.path_weight("services/auth/api.rs"), 10);
+        assert_eq!(config.quota.path_weight("services_old/api.rs"), 0);
+    }
+
+    #[test]
+    fn test_parse_prompts() {
+        let config = SemantiqConfig::parse(
+            "[embeddings]\nmodel = \"multilingual-minilm\"\n\n[embeddings.prompts.multilingual-minilm]\nquery = \"query: \"\npassage = \"passage: \"\n",
+        )
+        .unwrap();
+        let prompts = config
+            .embeddings
+            .prompts_for(EmbeddingModelKind::MultilingualMiniLm);
+        assert_eq!(prompts.query_text("retry"), "query: retry");
+        assert_eq!(
+            prompts.passage_text("fn retry() {}"),
+            "passage: fn retry() {}"
+        );
+        assert!(
+            config
+                .embeddings
+                .prompts_for(EmbeddingModelKind::MiniLm)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_recalibration() {
+        let config =
+            SemantiqConfig::parse("[recalibration]\nobservation_delta = 1000\nmax_age_days = 0\n")
+                .unwrap();
+        assert!(config.recalibration.enabled);
+        assert_eq!(config.recalibration.observation_delta, 1000);
+        assert_eq!(config.recalibration.max_age_secs(), None);
+        assert_eq!(
+            SemantiqConfig::default().recalibration.max_age_secs(),
+            Some(30 * 24 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn test_parse_history() {
+        assert_eq!(
+            SemantiqConfig::default().history.retained_generations(),
+            Some(10_000)
+        );
+        let config = SemantiqConfig::parse(
+            "[history]
+max_generations = 0
+",
+        )
+        .unwrap();
+        assert_eq!(config.history.retained_generations(), None);
+    }
+
+    #[test]
+    fn test_invalid_quantization_is_rejected() {
+        assert!(SemantiqConfig::parse("[embeddings]\nquantization = \"int4\"\n").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = std::env::temp_dir().join("semantiq-config-missing-test");
+        let config = SemantiqConfig::load(&dir).unwrap();
+        assert_eq!(config, SemantiqConfig::default());
+    }
+}
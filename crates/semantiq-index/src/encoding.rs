@@ -0,0 +1,202 @@
+//! Decoding of source files that are not UTF-8.
+//!
+//! Files are indexed as UTF-8 text. A file with a UTF-16 byte order mark is
+//! decoded from UTF-16, and one that is not valid UTF-8 from the legacy
+//! encoding its bytes read best in: Windows-1252 (Latin-1 text), Shift_JIS,
+//! EUC-JP or GBK. Bytes that are invalid in that encoding are replaced with
+//! U+FFFD rather than skipping the file. Byte offsets and lines of the index
+//! refer to the decoded text, so indexed files are read back through
+//! [`read_source`] too.
+
+use encoding_rs::{EUC_JP, Encoding, GBK, SHIFT_JIS, UTF_8, WINDOWS_1252};
+use std::io;
+use std::path::Path;
+
+/// Legacy encodings tried for files that are not UTF-8, preferred in this
+/// order when they read equally well
+const LEGACY_ENCODINGS: [&Encoding; 4] = [WINDOWS_1252, GBK, SHIFT_JIS, EUC_JP];
+
+/// Text of a source file, decoded to UTF-8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceText {
+    pub content: String,
+    /// Encoding the file was decoded from, `None` for UTF-8
+    pub encoding: Option<&'static str>,
+    /// Set when the encoding was guessed or invalid bytes were replaced,
+    /// so the indexed text may differ from what the author wrote
+    pub warning: Option<String>,
+}
+
+impl SourceText {
+    fn utf8(content: String) -> Self {
+        Self {
+            content,
+            encoding: None,
+            warning: None,
+        }
+    }
+}
+
+/// Read a source file and decode it to UTF-8.
+///
+/// Fails with [`io::ErrorKind::InvalidData`] for binary files, recognized by
+/// a NUL byte in a file that is neither UTF-8 nor UTF-16.
+pub fn read_source(path: &Path) -> io::Result<SourceText> {
+    let bytes = std::fs::read(path)?;
+    decode_source(bytes).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "binary file"))
+}
+
+/// Decode the content of a source file to UTF-8, `None` for binary content.
+pub fn decode_source(bytes: Vec<u8>) -> Option<SourceText> {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(content) => return Some(SourceText::utf8(content)),
+        Err(e) => e.into_bytes(),
+    };
+
+    if let Some((encoding, bom_length)) = Encoding::for_bom(&bytes)
+        && encoding != UTF_8
+    {
+        let (content, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return Some(SourceText {
+            content: content.into_owned(),
+            encoding: Some(encoding.name()),
+            warning: had_errors.then(|| format!("invalid {} bytes replaced", encoding.name())),
+        });
+    }
+
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    // UTF-8 with a few stray bytes, e.g. a Latin-1 comment pasted in
+    if is_mostly_utf8(&bytes) {
+        return Some(SourceText {
+            content: String::from_utf8_lossy(&bytes).into_owned(),
+            encoding: None,
+            warning: Some("invalid UTF-8 bytes replaced".to_string()),
+        });
+    }
+
+    let encoding = guess_legacy_encoding(&bytes);
+    let (content, had_errors) = encoding.decode_without_bom_handling(&bytes);
+    Some(SourceText {
+        content: content.into_owned(),
+        encoding: Some(encoding.name()),
+        warning: Some(if had_errors {
+            format!("decoded as {} with invalid bytes replaced", encoding.name())
+        } else {
+            format!("decoded as {}", encoding.name())
+        }),
+    })
+}
+
+/// More non-ASCII characters are valid UTF-8 than bytes are invalid
+fn is_mostly_utf8(bytes: &[u8]) -> bool {
+    let mut valid = 0;
+    let mut invalid = 0;
+    for chunk in bytes.utf8_chunks() {
+        valid += chunk.valid().chars().filter(|c| !c.is_ascii()).count();
+        invalid += chunk.invalid().len();
+    }
+    valid > invalid
+}
+
+/// The legacy encoding in which `bytes` decode without errors to the most
+/// plausible text
+fn guess_legacy_encoding(bytes: &[u8]) -> &'static Encoding {
+    let mut best = (WINDOWS_1252, i64::MIN);
+    for encoding in LEGACY_ENCODINGS {
+        let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) else {
+            continue;
+        };
+        let score = text.chars().map(|c| char_score(encoding, c)).sum();
+        if score > best.1 {
+            best = (encoding, score);
+        }
+    }
+    best.0
+}
+
+/// How typical `c` is of text written in `encoding`: letters of its script
+/// count for, symbols and letters of other scripts against.
+fn char_score(encoding: &'static Encoding, c: char) -> i64 {
+    let japanese = encoding == SHIFT_JIS || encoding == EUC_JP;
+    match c {
+        _ if c.is_ascii() => 0,
+        // CJK punctuation and fullwidth forms
+        '\u{3000}'..='\u{303F}' | '\u{FF01}'..='\u{FF60}' => 0,
+        // Kana: frequent in Japanese text, absent from Chinese text
+        '\u{3040}'..='\u{30FF}' if japanese => 2,
+        '\u{4E00}'..='\u{9FFF}' if encoding != WINDOWS_1252 => 1,
+        // Latin letters with diacritics (and × ÷)
+        '\u{00C0}'..='\u{00FF}' if encoding == WINDOWS_1252 => 1,
+        _ => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(encoding: &'static Encoding, text: &str) -> Vec<u8> {
+        let (bytes, _, had_errors) = encoding.encode(text);
+        assert!(!had_errors);
+        bytes.into_owned()
+    }
+
+    #[test]
+    fn test_utf8_is_kept() {
+        let source = decode_source("// café\nfn main() {}\n".into()).unwrap();
+        assert_eq!(source, SourceText::utf8("// café\nfn main() {}\n".into()));
+    }
+
+    #[test]
+    fn test_legacy_encodings_are_detected() {
+        let texts = [
+            (
+                WINDOWS_1252,
+                "// Déjà vu: résumé naïve façade\nfn main() {}\n",
+            ),
+            (SHIFT_JIS, "// 設定ファイルを読み込む\nfn load() {}\n"),
+            (EUC_JP, "// 設定ファイルを読み込む\nfn load() {}\n"),
+            (GBK, "// 读取配置文件并返回结果\nfn load() {}\n"),
+        ];
+        for (encoding, text) in texts {
+            let source = decode_source(encode(encoding, text)).unwrap();
+            assert_eq!(source.content, text, "{}", encoding.name());
+            assert_eq!(source.encoding, Some(encoding.name()));
+            assert_eq!(
+                source.warning,
+                Some(format!("decoded as {}", encoding.name()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_utf16_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("fn main() {}\n".encode_utf16().flat_map(u16::to_le_bytes));
+        let source = decode_source(bytes).unwrap();
+        assert_eq!(source.content, "fn main() {}\n");
+        assert_eq!(source.encoding, Some("UTF-16LE"));
+        assert_eq!(source.warning, None);
+    }
+
+    #[test]
+    fn test_stray_bytes_in_utf8_are_replaced() {
+        let mut bytes = "// naïve — ünïcode\n// caf".as_bytes().to_vec();
+        bytes.extend([0xE9, b'\n']);
+        let source = decode_source(bytes).unwrap();
+        assert_eq!(source.content, "// naïve — ünïcode\n// caf\u{FFFD}\n");
+        assert_eq!(source.encoding, None);
+        assert_eq!(
+            source.warning.as_deref(),
+            Some("invalid UTF-8 bytes replaced")
+        );
+    }
+
+    #[test]
+    fn test_binary_content_is_rejected() {
+        assert_eq!(decode_source(vec![0x7F, b'E', b'L', b'F', 0, 0xFF]), None);
+    }
+}
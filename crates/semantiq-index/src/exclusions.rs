@@ -8,6 +8,10 @@ use std::path::Path;
 /// Maximum file size in bytes (1MB)
 pub const MAX_FILE_SIZE: u64 = 1024 * 1024;
 
+/// Maximum size in bytes of a file indexed as an outline (16MB), see
+/// [`crate::outline`]
+pub const MAX_OUTLINE_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
 /// Directories to exclude from indexing
 pub const EXCLUDED_DIRS: &[&str] = &[
     "node_modules",
@@ -54,10 +58,11 @@ pub fn should_exclude_path(path: &Path) -> bool {
     false
 }
 
-/// Check if a file should be excluded based on its size
+/// Check if a file should be excluded based on its size, even from outline
+/// indexing
 pub fn is_file_too_large(path: &Path) -> bool {
     if let Ok(metadata) = std::fs::metadata(path) {
-        return metadata.len() > MAX_FILE_SIZE;
+        return metadata.len() > MAX_OUTLINE_FILE_SIZE;
     }
     false
 }
@@ -0,0 +1,942 @@
+//! Indexing of external dependency sources
+//!
+//! When enabled in `semantiq.toml`, the sources of declared dependencies are
+//! indexed next to the project so agents can look up third-party APIs:
+//!
+//! - Rust: crates listed in `Cargo.toml`, read from the Cargo registry sources
+//!   (`$CARGO_HOME/registry/src`) at the version pinned in `Cargo.lock`
+//! - TypeScript/JavaScript: type declaration files (`.d.ts`) of packages listed
+//!   in `package.json`, read from `node_modules` (falling back to `@types/*`)
+//!
+//! External files are stored under the `external://` namespace, e.g.
+//! `external://cargo/serde@1.0.200/src/de/mod.rs`, which keeps them apart from
+//! project paths so searches can include or exclude them.
+
+use crate::encoding::read_source;
+use crate::exclusions::MAX_FILE_SIZE;
+use crate::generated::looks_generated;
+use crate::paths::to_index_path;
+use crate::summary::file_summary;
+use crate::{ExternalPackageMarker, FileIndexData, IndexStore};
+use anyhow::Result;
+use semantiq_embeddings::{ChunkEmbedding, EmbeddingModel};
+use semantiq_parser::{ChunkExtractor, Language, LanguageSupport, SymbolExtractor};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, info, warn};
+
+/// Path prefix of all indexed external files
+pub const EXTERNAL_PREFIX: &str = "external://";
+
+/// Maximum number of files indexed for a single package
+const MAX_FILES_PER_PACKAGE: usize = 1000;
+
+/// Directories of a crate that do not contain its public API
+const SKIPPED_CRATE_DIRS: &[&str] = &["tests", "benches", "examples", "target"];
+
+/// Check if an indexed path belongs to the external namespace
+pub fn is_external_path(path: &str) -> bool {
+    path.starts_with(EXTERNAL_PREFIX)
+}
+
+/// Index path prefix of the package an external path belongs to
+/// (`external://npm/@types/node@20.1.0/index.d.ts` → `external://npm/@types/node@20.1.0`)
+pub(crate) fn package_namespace(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix(EXTERNAL_PREFIX)?;
+    let (_ecosystem, package_path) = rest.split_once('/')?;
+
+    // The version follows the first `@` that does not start a component (npm scopes do)
+    let mut offset = path.len() - package_path.len();
+    for component in package_path.split('/') {
+        offset += component.len();
+        if component.find('@').is_some_and(|i| i > 0) {
+            return Some(&path[..offset]);
+        }
+        offset += 1;
+    }
+    None
+}
+
+/// Package manager an external package comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+}
+
+impl Ecosystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Npm => "npm",
+        }
+    }
+}
+
+/// A dependency whose sources are available on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalPackage {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    /// Directory holding the package sources
+    pub root: PathBuf,
+}
+
+impl ExternalPackage {
+    /// Index path prefix of the package, e.g. `external://cargo/serde@1.0.200`
+    pub fn namespace(&self) -> String {
+        format!(
+            "{}{}/{}@{}",
+            EXTERNAL_PREFIX,
+            self.ecosystem.as_str(),
+            self.name,
+            self.version
+        )
+    }
+
+    /// Key of the package in the index metadata, e.g. `cargo/serde`
+    pub fn marker_key(&self) -> String {
+        format!("{}/{}", self.ecosystem.as_str(), self.name)
+    }
+
+    /// Index path of a file given relative to the package root
+    pub fn index_path(&self, rel_path: &str) -> String {
+        format!("{}/{}", self.namespace(), rel_path)
+    }
+
+    /// Source files to index with their path relative to the package root, sorted
+    pub fn source_files(&self) -> Vec<(PathBuf, String)> {
+        let mut files = Vec::new();
+        match self.ecosystem {
+            Ecosystem::Cargo => collect_files(
+                &self.root,
+                &self.root,
+                SKIPPED_CRATE_DIRS,
+                &|name| name.ends_with(".rs"),
+                &mut files,
+            ),
+            Ecosystem::Npm => collect_files(
+                &self.root,
+                &self.root,
+                &["node_modules"],
+                &is_declaration_file,
+                &mut files,
+            ),
+        }
+        files
+    }
+}
+
+/// Find the declared dependencies of a project whose sources are on disk.
+pub fn discover_packages(project_root: &Path) -> Vec<ExternalPackage> {
+    let mut packages = Vec::new();
+    if let Some(cargo_home) = cargo_home() {
+        packages.extend(discover_cargo_packages(project_root, &cargo_home));
+    }
+    packages.extend(discover_npm_packages(project_root));
+    packages
+}
+
+/// Find crates declared in `Cargo.toml` in the registry sources of `cargo_home`.
+///
+/// Versions are taken from `Cargo.lock` when present, otherwise the highest
+/// version found in the registry is used. Path and git dependencies are skipped.
+pub fn discover_cargo_packages(project_root: &Path, cargo_home: &Path) -> Vec<ExternalPackage> {
+    let manifest = match read_toml(&project_root.join("Cargo.toml")) {
+        Some(m) => m,
+        None => return Vec::new(),
+    };
+
+    let mut names = BTreeSet::new();
+    let tables = [
+        manifest.get("dependencies"),
+        manifest
+            .get("workspace")
+            .and_then(|w| w.get("dependencies")),
+    ];
+    for table in tables.into_iter().flatten() {
+        let Some(table) = table.as_table() else {
+            continue;
+        };
+        for (key, spec) in table {
+            if spec.get("path").is_some() || spec.get("git").is_some() {
+                continue;
+            }
+            let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+            names.insert(name.to_string());
+        }
+    }
+
+    let locked = read_toml(&project_root.join("Cargo.lock"))
+        .map(|lock| locked_versions(&lock))
+        .unwrap_or_default();
+
+    let registries: Vec<PathBuf> = fs::read_dir(cargo_home.join("registry").join("src"))
+        .map(|entries| {
+            let mut dirs: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect();
+            dirs.sort();
+            dirs
+        })
+        .unwrap_or_default();
+
+    let mut packages = Vec::new();
+    for name in names {
+        let found = match locked.get(&name) {
+            Some(version) => registries
+                .iter()
+                .map(|r| r.join(format!("{}-{}", name, version)))
+                .find(|dir| dir.is_dir())
+                .map(|dir| (version.clone(), dir)),
+            None => highest_registry_version(&registries, &name),
+        };
+
+        match found {
+            Some((version, root)) => packages.push(ExternalPackage {
+                ecosystem: Ecosystem::Cargo,
+                name,
+                version,
+                root,
+            }),
+            None => debug!("Sources of crate {} not found in the Cargo registry", name),
+        }
+    }
+    packages
+}
+
+/// Find packages declared in `package.json` that ship type declarations in `node_modules`.
+///
+/// Packages without their own declarations fall back to the matching
+/// `@types/*` package when it is installed.
+pub fn discover_npm_packages(project_root: &Path) -> Vec<ExternalPackage> {
+    let manifest: serde_json::Value = match fs::read_to_string(project_root.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+    {
+        Some(m) => m,
+        None => return Vec::new(),
+    };
+
+    let mut names = BTreeSet::new();
+    for key in ["dependencies", "devDependencies"] {
+        if let Some(deps) = manifest.get(key).and_then(|d| d.as_object()) {
+            names.extend(deps.keys().cloned());
+        }
+    }
+
+    let node_modules = project_root.join("node_modules");
+    let mut packages: BTreeMap<String, ExternalPackage> = BTreeMap::new();
+    for name in names {
+        let mut candidates = vec![name.clone()];
+        if !name.starts_with("@types/") {
+            candidates.push(types_package_name(&name));
+        }
+
+        for candidate in candidates {
+            let root = node_modules.join(&candidate);
+            if packages.contains_key(&candidate) || !has_declaration_files(&root) {
+                continue;
+            }
+            let version = fs::read_to_string(root.join("package.json"))
+                .ok()
+                .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+                .and_then(|p| p.get("version").and_then(|v| v.as_str()).map(String::from))
+                .unwrap_or_else(|| "0.0.0".to_string());
+            packages.insert(
+                candidate.clone(),
+                ExternalPackage {
+                    ecosystem: Ecosystem::Npm,
+                    name: candidate,
+                    version,
+                    root,
+                },
+            );
+            break;
+        }
+    }
+    packages.into_values().collect()
+}
+
+/// Result of indexing external packages
+#[derive(Debug, Default)]
+pub struct ExternalIndexResult {
+    /// Packages indexed during this run
+    pub packages_indexed: usize,
+    /// Packages already indexed at the same version
+    pub packages_up_to_date: usize,
+    pub files_indexed: usize,
+    pub symbols: usize,
+    pub chunks: usize,
+    /// Files of packages that are no longer dependencies (or changed version),
+    /// and files left by an interrupted indexing of a package
+    pub files_removed: usize,
+}
+
+/// Index the sources of external packages into the `external://` namespace.
+///
+/// Registry sources are immutable for a given version, so packages whose
+/// marker records a complete indexing of the same version are skipped. Other
+/// packages have their leftover files removed and are indexed again. Files
+/// and markers of packages that are no longer in `packages` are removed.
+pub fn index_external_packages(
+    store: &IndexStore,
+    packages: &[ExternalPackage],
+    language_support: &mut LanguageSupport,
+    chunk_extractor: &ChunkExtractor,
+    embedding_model: Option<&dyn EmbeddingModel>,
+) -> Result<ExternalIndexResult> {
+    let mut result = ExternalIndexResult::default();
+
+    let namespaces: Vec<String> = packages.iter().map(|p| p.namespace() + "/").collect();
+    for path in store.get_file_paths_with_prefix(EXTERNAL_PREFIX)? {
+        if !namespaces.iter().any(|ns| path.starts_with(ns.as_str())) {
+            store.delete_file(&path)?;
+            result.files_removed += 1;
+        }
+    }
+    let keys: Vec<String> = packages.iter().map(ExternalPackage::marker_key).collect();
+    store.retain_external_package_markers(&keys)?;
+
+    for ((package, namespace), key) in packages.iter().zip(&namespaces).zip(&keys) {
+        let marker = store.get_external_package_marker(key)?;
+        if marker.is_some_and(|m| m.complete && m.version == package.version) {
+            result.packages_up_to_date += 1;
+            continue;
+        }
+
+        // Files of an interrupted run are indexed again from scratch
+        result.files_removed += store.delete_files_with_prefix(namespace)?;
+        let mut marker = ExternalPackageMarker {
+            version: package.version.clone(),
+            complete: false,
+        };
+        store.set_external_package_marker(key, &marker)?;
+
+        let files = package.source_files();
+        if files.len() >= MAX_FILES_PER_PACKAGE {
+            warn!(
+                "{} has more than {} source files, indexing the first {}",
+                package.namespace(),
+                MAX_FILES_PER_PACKAGE,
+                MAX_FILES_PER_PACKAGE
+            );
+        }
+
+        for (path, rel_path) in files {
+            let index_path = package.index_path(&rel_path);
+            match index_external_file(
+                store,
+                &path,
+                &index_path,
+                language_support,
+                chunk_extractor,
+                embedding_model,
+            ) {
+                Ok(Some((symbols, chunks))) => {
+                    result.files_indexed += 1;
+                    result.symbols += symbols;
+                    result.chunks += chunks;
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to index {}: {}", index_path, e),
+            }
+        }
+
+        marker.complete = true;
+        store.set_external_package_marker(key, &marker)?;
+        debug!("Indexed external package {}", package.namespace());
+        result.packages_indexed += 1;
+    }
+
+    info!(
+        "External dependencies: {} packages indexed ({} files), {} up to date, {} files removed",
+        result.packages_indexed,
+        result.files_indexed,
+        result.packages_up_to_date,
+        result.files_removed
+    );
+
+    Ok(result)
+}
+
+/// Index one external file, returning its symbol and chunk counts, or `None` if skipped
+fn index_external_file(
+    store: &IndexStore,
+    path: &Path,
+    index_path: &str,
+    language_support: &mut LanguageSupport,
+    chunk_extractor: &ChunkExtractor,
+    embedding_model: Option<&dyn EmbeddingModel>,
+) -> Result<Option<(usize, usize)>> {
+    let language = match external_language(path) {
+        Some(l) => l,
+        None => return Ok(None),
+    };
+
+    let metadata = fs::metadata(path)?;
+    if metadata.len() > MAX_FILE_SIZE {
+        debug!(
+            "Skipping {} (too large: {} bytes)",
+            index_path,
+            metadata.len()
+        );
+        return Ok(None);
+    }
+    let source = read_source(path)?;
+    let content = source.content;
+    let last_modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let tree = language_support.parse_file(language, path, &content)?;
+    let symbols = SymbolExtractor::extract(&tree, &content, language)?;
+    let chunks = chunk_extractor.extract(&tree, &content, language)?;
+
+    let embeddings: Vec<Option<ChunkEmbedding>> = match embedding_model {
+        Some(model) => chunks
+            .iter()
+            .map(
+                |chunk| match model.embed_chunk(&store.embedding_text(&chunk.content)) {
+                    Ok(embedding) => Some(embedding),
+                    Err(e) => {
+                        debug!(
+                            "Failed to embed chunk at {}:{}: {}",
+                            index_path, chunk.start_line, e
+                        );
+                        None
+                    }
+                },
+            )
+            .collect(),
+        None => Vec::new(),
+    };
+    let summary_embedding = embedding_model.and_then(|model| {
+        let summary = file_summary(
+            index_path,
+            symbols
+                .iter()
+                .map(|s| (s.name.as_str(), s.doc_comment.as_deref())),
+            chunks.iter().map(|c| (c.content.as_str(), c.symbols.len())),
+        )?;
+        model
+            .embed(&store.passage_text(&summary))
+            .map_err(|e| debug!("Failed to embed summary of {}: {}", index_path, e))
+            .ok()
+    });
+
+    store.write_file_index(&FileIndexData {
+        encoding: source.encoding,
+        generated: looks_generated(index_path, &content),
+        symbols: &symbols,
+        chunks: &chunks,
+        embeddings: &embeddings,
+        summary_embedding: summary_embedding.as_deref(),
+        ..FileIndexData::new(
+            index_path,
+            Some(language.name()),
+            &content,
+            metadata.len() as i64,
+            last_modified,
+        )
+    })?;
+
+    Ok(Some((symbols.len(), chunks.len())))
+}
+
+/// Language of an external source file (`.d.mts`/`.d.cts` declarations are TypeScript)
+fn external_language(path: &Path) -> Option<Language> {
+    let name = path.file_name()?.to_str()?;
+    if is_declaration_file(name) {
+        return Some(Language::TypeScript);
+    }
+    Language::from_path(path)
+}
+
+fn is_declaration_file(name: &str) -> bool {
+    name.ends_with(".d.ts") || name.ends_with(".d.mts") || name.ends_with(".d.cts")
+}
+
+fn has_declaration_files(root: &Path) -> bool {
+    let mut files = Vec::new();
+    collect_files(
+        root,
+        root,
+        &["node_modules"],
+        &is_declaration_file,
+        &mut files,
+    );
+    !files.is_empty()
+}
+
+/// `@types` package providing declarations for `name` (`@scope/pkg` -> `@types/scope__pkg`)
+fn types_package_name(name: &str) -> String {
+    match name.strip_prefix('@') {
+        Some(scoped) => format!("@types/{}", scoped.replace('/', "__")),
+        None => format!("@types/{}", name),
+    }
+}
+
+/// Recursively collect files accepted by `accept`, skipping hidden and `skipped_dirs`
+/// directories, up to `MAX_FILES_PER_PACKAGE` files. Entries are visited in sorted order.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    skipped_dirs: &[&str],
+    accept: &dyn Fn(&str) -> bool,
+    files: &mut Vec<(PathBuf, String)>,
+) {
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+        Err(_) => return,
+    };
+    entries.sort();
+
+    for path in entries {
+        if files.len() >= MAX_FILES_PER_PACKAGE {
+            return;
+        }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+
+        // Do not follow symlinks out of the package
+        let Ok(file_type) = fs::symlink_metadata(&path).map(|m| m.file_type()) else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !skipped_dirs.contains(&name) {
+                collect_files(root, &path, skipped_dirs, accept, files);
+            }
+        } else if file_type.is_file() && accept(name) {
+            let rel_path = to_index_path(path.strip_prefix(root).unwrap_or(&path));
+            files.push((path, rel_path));
+        }
+    }
+}
+
+/// Cargo home directory (`$CARGO_HOME`, or `.cargo` in the home directory)
+fn cargo_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("CARGO_HOME") {
+        return Some(PathBuf::from(home));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".cargo"))
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    match toml::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Failed to parse {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Highest locked version of each registry package in a `Cargo.lock`
+fn locked_versions(lock: &toml::Value) -> BTreeMap<String, String> {
+    let mut versions: BTreeMap<String, String> = BTreeMap::new();
+    let Some(packages) = lock.get("package").and_then(|p| p.as_array()) else {
+        return versions;
+    };
+
+    for package in packages {
+        let from_registry = package
+            .get("source")
+            .and_then(|s| s.as_str())
+            .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"));
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(|n| n.as_str()),
+            package.get("version").and_then(|v| v.as_str()),
+        ) else {
+            continue;
+        };
+        if !from_registry {
+            continue;
+        }
+
+        let newer = versions
+            .get(name)
+            .is_none_or(|current| version_key(version) > version_key(current));
+        if newer {
+            versions.insert(name.to_string(), version.to_string());
+        }
+    }
+    versions
+}
+
+/// Highest version of a crate extracted in any of the registry source directories
+fn highest_registry_version(registries: &[PathBuf], name: &str) -> Option<(String, PathBuf)> {
+    let prefix = format!("{}-", name);
+    let mut best: Option<(String, PathBuf)> = None;
+
+    for registry in registries {
+        let Ok(entries) = fs::read_dir(registry) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            let Some(version) = dir_name.strip_prefix(&prefix) else {
+                continue;
+            };
+            // `foo-bar-1.0` must not match crate `foo`
+            if !version.starts_with(|c: char| c.is_ascii_digit()) || !entry.path().is_dir() {
+                continue;
+            }
+            let newer = best
+                .as_ref()
+                .is_none_or(|(current, _)| version_key(version) > version_key(current));
+            if newer {
+                best = Some((version.to_string(), entry.path()));
+            }
+        }
+    }
+    best
+}
+
+/// Numeric components of a version for ordering (pre-release and build metadata ignored)
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh scratch directory under the system temp dir
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("semantiq-external-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_version_key_ordering() {
+        assert!(version_key("1.10.0") > version_key("1.9.3"));
+        assert!(version_key("2.0.0-alpha.1") > version_key("1.99.0"));
+        assert_eq!(version_key("1.0.0+build"), vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn test_types_package_name() {
+        assert_eq!(types_package_name("lodash"), "@types/lodash");
+        assert_eq!(types_package_name("@babel/core"), "@types/babel__core");
+    }
+
+    #[test]
+    fn test_package_paths() {
+        let package = ExternalPackage {
+            ecosystem: Ecosystem::Cargo,
+            name: "serde".to_string(),
+            version: "1.0.200".to_string(),
+            root: PathBuf::from("/tmp/serde-1.0.200"),
+        };
+        assert_eq!(package.namespace(), "external://cargo/serde@1.0.200");
+        assert_eq!(
+            package.index_path("src/lib.rs"),
+            "external://cargo/serde@1.0.200/src/lib.rs"
+        );
+        assert!(is_external_path(&package.index_path("src/lib.rs")));
+        assert!(!is_external_path("src/lib.rs"));
+        assert_eq!(
+            package_namespace(&package.index_path("src/lib.rs")),
+            Some("external://cargo/serde@1.0.200")
+        );
+        assert_eq!(
+            package_namespace("external://npm/@types/node@20.1.0/fs/promises.d.ts"),
+            Some("external://npm/@types/node@20.1.0")
+        );
+        assert_eq!(package_namespace("src/lib.rs"), None);
+    }
+
+    #[test]
+    fn test_discover_cargo_packages() {
+        let dir = scratch_dir("cargo");
+        let project = dir.join("project");
+        let registry = dir.join("cargo-home/registry/src/index.crates.io-1234");
+
+        write(
+            &project.join("Cargo.toml"),
+            r#"
+[package]
+name = "app"
+
+[dependencies]
+serde = "1"
+json = { version = "0.12", package = "serde_json" }
+local = { path = "../local" }
+remote = { git = "https://example.com/remote" }
+missing = "1"
+
+[workspace.dependencies]
+anyhow = "1"
+"#,
+        );
+        write(
+            &project.join("Cargo.lock"),
+            r#"
+[[package]]
+name = "serde"
+version = "1.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde_json"
+version = "1.0.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+        );
+        write(
+            &registry.join("serde-1.0.100/src/lib.rs"),
+            "pub trait Serialize {}",
+        );
+        write(
+            &registry.join("serde-1.0.200/src/lib.rs"),
+            "pub trait Serialize {}",
+        );
+        write(
+            &registry.join("serde_json-1.0.5/src/lib.rs"),
+            "pub fn to_string() {}",
+        );
+        write(
+            &registry.join("anyhow-1.0.9/src/lib.rs"),
+            "pub struct Error;",
+        );
+        write(
+            &registry.join("anyhow-1.0.10/src/lib.rs"),
+            "pub struct Error;",
+        );
+
+        let packages = discover_cargo_packages(&project, &dir.join("cargo-home"));
+        let found: Vec<(&str, &str)> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str()))
+            .collect();
+        // Locked versions win; unlocked crates use the highest version available
+        assert_eq!(
+            found,
+            vec![
+                ("anyhow", "1.0.10"),
+                ("serde", "1.0.100"),
+                ("serde_json", "1.0.5")
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_discover_npm_packages() {
+        let dir = scratch_dir("npm");
+        let modules = dir.join("node_modules");
+
+        write(
+            &dir.join("package.json"),
+            r#"{
+                "dependencies": { "typed": "^1.0.0", "untyped": "^2.0.0", "absent": "1" },
+                "devDependencies": { "@scope/tool": "3" }
+            }"#,
+        );
+        write(
+            &modules.join("typed/package.json"),
+            r#"{"version": "1.2.3"}"#,
+        );
+        write(
+            &modules.join("typed/index.d.ts"),
+            "export declare function run(): void;",
+        );
+        write(&modules.join("typed/index.js"), "exports.run = () => {};");
+        write(&modules.join("untyped/index.js"), "module.exports = {};");
+        write(
+            &modules.join("@types/untyped/package.json"),
+            r#"{"version": "2.0.1"}"#,
+        );
+        write(
+            &modules.join("@types/untyped/index.d.ts"),
+            "export declare const value: number;",
+        );
+        write(&modules.join("@types/scope__tool/index.d.ts"), "export {};");
+
+        let packages = discover_npm_packages(&dir);
+        let found: Vec<(&str, &str)> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("@types/scope__tool", "0.0.0"),
+                ("@types/untyped", "2.0.1"),
+                ("typed", "1.2.3")
+            ]
+        );
+
+        let typed = packages.iter().find(|p| p.name == "typed").unwrap();
+        let files: Vec<String> = typed.source_files().into_iter().map(|(_, r)| r).collect();
+        assert_eq!(files, vec!["index.d.ts"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_index_external_packages() {
+        let dir = scratch_dir("index");
+        write(
+            &dir.join("src/lib.rs"),
+            "/// Parse input\npub fn parse_input(s: &str) -> usize { s.len() }\n",
+        );
+        write(&dir.join("tests/it.rs"), "fn test_parse() {}\n");
+
+        let store = IndexStore::open_in_memory().unwrap();
+        store
+            .insert_file(
+                "external://cargo/old@0.1.0/src/lib.rs",
+                Some("rust"),
+                "fn old() {}",
+                11,
+                0,
+            )
+            .unwrap();
+
+        let package = ExternalPackage {
+            ecosystem: Ecosystem::Cargo,
+            name: "parser".to_string(),
+            version: "0.3.0".to_string(),
+            root: dir.clone(),
+        };
+        let mut language_support = LanguageSupport::new().unwrap();
+        let chunk_extractor = ChunkExtractor::new();
+
+        let result = index_external_packages(
+            &store,
+            std::slice::from_ref(&package),
+            &mut language_support,
+            &chunk_extractor,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.packages_indexed, 1);
+        assert_eq!(result.files_indexed, 1);
+        assert_eq!(result.files_removed, 1);
+        assert_eq!(
+            store.get_file_paths_with_prefix(EXTERNAL_PREFIX).unwrap(),
+            vec!["external://cargo/parser@0.3.0/src/lib.rs"]
+        );
+        assert!(!store.find_symbol_by_name("parse_input").unwrap().is_empty());
+
+        // Same version again: nothing to do
+        let result = index_external_packages(
+            &store,
+            &[package],
+            &mut language_support,
+            &chunk_extractor,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.packages_indexed, 0);
+        assert_eq!(result.packages_up_to_date, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_index_external_packages_after_interruption() {
+        let dir = scratch_dir("interrupted");
+        write(&dir.join("src/lib.rs"), "pub fn parse_input() {}\n");
+
+        // A file of the package made it in before the previous run stopped
+        let store = IndexStore::open_in_memory().unwrap();
+        store
+            .insert_file(
+                "external://cargo/parser@0.3.0/src/partial.rs",
+                Some("rust"),
+                "fn partial() {}",
+                15,
+                0,
+            )
+            .unwrap();
+        store
+            .set_external_package_marker(
+                "cargo/parser",
+                &ExternalPackageMarker {
+                    version: "0.3.0".to_string(),
+                    complete: false,
+                },
+            )
+            .unwrap();
+        store
+            .set_external_package_marker(
+                "cargo/gone",
+                &ExternalPackageMarker {
+                    version: "1.0.0".to_string(),
+                    complete: true,
+                },
+            )
+            .unwrap();
+
+        let package = ExternalPackage {
+            ecosystem: Ecosystem::Cargo,
+            name: "parser".to_string(),
+            version: "0.3.0".to_string(),
+            root: dir.clone(),
+        };
+        let mut language_support = LanguageSupport::new().unwrap();
+        let chunk_extractor = ChunkExtractor::new();
+
+        let result = index_external_packages(
+            &store,
+            &[package],
+            &mut language_support,
+            &chunk_extractor,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.packages_indexed, 1);
+        assert_eq!(result.packages_up_to_date, 0);
+        assert_eq!(result.files_removed, 1);
+        assert_eq!(
+            store.get_file_paths_with_prefix(EXTERNAL_PREFIX).unwrap(),
+            vec!["external://cargo/parser@0.3.0/src/lib.rs"]
+        );
+        assert_eq!(
+            store.get_external_package_marker("cargo/parser").unwrap(),
+            Some(ExternalPackageMarker {
+                version: "0.3.0".to_string(),
+                complete: true,
+            })
+        );
+        assert_eq!(
+            store.get_external_package_marker("cargo/gone").unwrap(),
+            None
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,117 @@
+//! Everything the indexers extract from the syntax tree of a file
+//!
+//! The CLI `index` command and the auto-indexer of `semantiq serve` parse
+//! files the same way; both go through [`ExtractedFile::extract`] so a new
+//! extractor is added in one place.
+
+use crate::outline::outline_symbols;
+use anyhow::Result;
+use semantiq_parser::{
+    Binding, BindingExtractor, ChunkExtractor, CodeChunk, ConfigRef, ConfigRefExtractor,
+    Entrypoint, EntrypointExtractor, Import, ImportExtractor, Keyword, KeywordExtractor, Language,
+    LanguageSupport, Occurrence, OccurrenceExtractor, RelationExtractor, Symbol, SymbolExtractor,
+    TextMask, TextMaskExtractor, TypeRelation,
+};
+use std::path::Path;
+
+/// Symbols, chunks, imports, type relations, occurrences, keywords, comment
+/// and string ranges, entry points, environment and configuration reads and
+/// writes and bindings to other languages of a file
+///
+/// The default is empty, which is how a file that failed to parse is
+/// indexed.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedFile {
+    pub symbols: Vec<Symbol>,
+    pub chunks: Vec<CodeChunk>,
+    pub imports: Vec<Import>,
+    pub relations: Vec<TypeRelation>,
+    pub occurrences: Vec<Occurrence>,
+    pub keywords: Vec<Keyword>,
+    pub text_mask: TextMask,
+    pub entrypoints: Vec<Entrypoint>,
+    pub config_refs: Vec<ConfigRef>,
+    pub bindings: Vec<Binding>,
+}
+
+impl ExtractedFile {
+    /// Outline of a file too large to chunk: its top-level symbols only
+    pub fn outline(symbols: Vec<Symbol>) -> Self {
+        Self {
+            symbols: outline_symbols(symbols),
+            ..Self::default()
+        }
+    }
+
+    /// Parse a file and extract everything indexed from it, or only its
+    /// outline when `outline` is set
+    pub fn extract(
+        language_support: &mut LanguageSupport,
+        chunk_extractor: &ChunkExtractor,
+        language: Language,
+        path: &Path,
+        rel_path: &str,
+        content: &str,
+        outline: bool,
+    ) -> Result<Self> {
+        let tree = language_support.parse_file(language, path, content)?;
+        let symbols = SymbolExtractor::extract(&tree, content, language)?;
+        if outline {
+            return Ok(Self::outline(symbols));
+        }
+        Ok(Self {
+            symbols,
+            chunks: chunk_extractor.extract(&tree, content, language)?,
+            imports: ImportExtractor::extract(&tree, content, language)?,
+            relations: RelationExtractor::extract(&tree, content, language)?,
+            occurrences: OccurrenceExtractor::extract(&tree, content, language)?,
+            keywords: KeywordExtractor::extract(&tree, content, language)?,
+            text_mask: TextMaskExtractor::extract(&tree, language)?,
+            entrypoints: EntrypointExtractor::extract(&tree, content, language, rel_path)?,
+            config_refs: ConfigRefExtractor::extract(&tree, content, language)?,
+            bindings: BindingExtractor::extract(&tree, content, language)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_full_and_outline() {
+        let source = "use std::fmt;\n\npub struct Point { x: i32 }\n\nimpl Point {\n    fn norm(&self) -> i32 { self.x }\n}\n";
+        let mut language_support = LanguageSupport::new().unwrap();
+        let chunk_extractor = ChunkExtractor::new();
+        let path = Path::new("src/point.rs");
+
+        let full = ExtractedFile::extract(
+            &mut language_support,
+            &chunk_extractor,
+            Language::Rust,
+            path,
+            "src/point.rs",
+            source,
+            false,
+        )
+        .unwrap();
+        assert!(full.symbols.iter().any(|s| s.name == "norm"));
+        assert!(!full.chunks.is_empty());
+        assert_eq!(full.imports.len(), 1);
+
+        let outline = ExtractedFile::extract(
+            &mut language_support,
+            &chunk_extractor,
+            Language::Rust,
+            path,
+            "src/point.rs",
+            source,
+            true,
+        )
+        .unwrap();
+        assert!(outline.symbols.iter().all(|s| s.parent.is_none()));
+        assert!(outline.symbols.iter().any(|s| s.name == "Point"));
+        assert!(outline.chunks.is_empty());
+        assert!(outline.imports.is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+//! Detection of generated files
+//!
+//! Protobuf output, minified bundles and other generated code crowd search
+//! results with code nobody edits. A file is marked generated when:
+//!
+//! - the `.gitattributes` at the project root sets `linguist-generated` on
+//!   it (`linguist-generated=false` or `-linguist-generated` marks it as
+//!   hand-written, whatever the heuristics below say)
+//! - its name is one of generated code, e.g. `app.min.js` or `user.pb.go`
+//! - one of its first lines carries a marker such as `@generated` or
+//!   `Code generated by protoc-gen-go. DO NOT EDIT.`
+//! - its lines are very long on average, as in minified code
+//!
+//! Generated files are indexed as usual but left out of searches unless
+//! asked for (see `SearchOptions::include_generated` in semantiq-retrieval).
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+/// Attribute marking generated files in `.gitattributes`, as used by GitHub
+const LINGUIST_GENERATED: &str = "linguist-generated";
+
+/// Name endings of generated files
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js",
+    ".min.mjs",
+    ".min.css",
+    ".pb.go",
+    ".pb.cc",
+    ".pb.h",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    ".pb.ts",
+    "_pb.js",
+    "_pb.d.ts",
+    ".g.dart",
+    ".freezed.dart",
+    ".designer.cs",
+    ".generated.ts",
+];
+
+/// Lines searched for a generated marker
+const MARKER_LINES: usize = 10;
+
+/// Mean line length (in bytes) above which a file is taken for minified
+const MINIFIED_MEAN_LINE_LENGTH: usize = 500;
+
+/// Files shorter than this are never taken for minified
+const MINIFIED_MIN_SIZE: usize = 1024;
+
+/// Tells generated files from hand-written ones
+#[derive(Default)]
+pub struct GeneratedDetector {
+    /// `linguist-generated` patterns of `.gitattributes` and whether they
+    /// mark files as generated, the last matching one applying
+    rules: Vec<(Gitignore, bool)>,
+}
+
+impl GeneratedDetector {
+    /// Detector honoring the `.gitattributes` of `project_root`, if any
+    pub fn load(project_root: &Path) -> Self {
+        match fs::read_to_string(project_root.join(".gitattributes")) {
+            Ok(text) => Self::from_gitattributes(project_root, &text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Detector honoring the `linguist-generated` attributes of `text`, a
+    /// `.gitattributes` file at `root`
+    pub fn from_gitattributes(root: &Path, text: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let Some(generated) = fields.filter_map(linguist_generated).next_back() else {
+                continue;
+            };
+
+            let mut builder = GitignoreBuilder::new(root);
+            if let Err(e) = builder.add_line(None, pattern) {
+                debug!("Ignoring .gitattributes pattern {}: {}", pattern, e);
+                continue;
+            }
+            match builder.build() {
+                Ok(matcher) => rules.push((matcher, generated)),
+                Err(e) => debug!("Ignoring .gitattributes pattern {}: {}", pattern, e),
+            }
+        }
+        Self { rules }
+    }
+
+    /// Whether the file at `path`, relative to the project root, is generated
+    pub fn is_generated(&self, path: &str, content: &str) -> bool {
+        match self.attribute(path) {
+            Some(generated) => generated,
+            None => looks_generated(path, content),
+        }
+    }
+
+    /// `linguist-generated` value of `path` in `.gitattributes`, if set
+    fn attribute(&self, path: &str) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| !matcher.matched_path_or_any_parents(path, false).is_none())
+            .map(|(_, generated)| *generated)
+    }
+}
+
+/// Value of a `linguist-generated` attribute, `None` for other attributes
+fn linguist_generated(attribute: &str) -> Option<bool> {
+    match attribute {
+        LINGUIST_GENERATED => Some(true),
+        _ if attribute.strip_prefix('-') == Some(LINGUIST_GENERATED) => Some(false),
+        _ => match attribute
+            .strip_prefix(LINGUIST_GENERATED)?
+            .strip_prefix('=')?
+        {
+            "true" | "set" => Some(true),
+            "false" | "unset" => Some(false),
+            _ => None,
+        },
+    }
+}
+
+/// Whether the name or content of a file marks it as generated
+pub fn looks_generated(path: &str, content: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    if GENERATED_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        return true;
+    }
+
+    if content.lines().take(MARKER_LINES).any(has_generated_marker) {
+        return true;
+    }
+
+    let lines = content.lines().count().max(1);
+    content.len() >= MINIFIED_MIN_SIZE && content.len() / lines > MINIFIED_MEAN_LINE_LENGTH
+}
+
+/// `@generated`, `<auto-generated>`, or a line both saying the code is
+/// generated and not to be edited
+fn has_generated_marker(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    lower.contains("@generated")
+        || lower.contains("<auto-generated")
+        || (lower.contains("generated") && lower.contains("do not edit"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_names_and_markers() {
+        assert!(looks_generated("web/static/app.min.js", "var a=1;"));
+        assert!(looks_generated("api/user.pb.go", "package api"));
+        assert!(looks_generated("proto/user_pb2.py", "import sys"));
+        assert!(looks_generated(
+            "api/client.go",
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\n\npackage api\n"
+        ));
+        assert!(looks_generated(
+            "src/schema.ts",
+            "/**\n * @generated SignedSource<<abc>>\n */\nexport type A = string;\n"
+        ));
+        assert!(!looks_generated(
+            "src/app.js",
+            "// Builds the app\nexport function build() {}\n"
+        ));
+        // A marker far down the file is not a banner
+        let late = format!("{}// do not edit: generated\n", "let a = 1;\n".repeat(20));
+        assert!(!looks_generated("src/app.js", &late));
+    }
+
+    #[test]
+    fn test_minified_content() {
+        let minified = format!("{}\n", "a=1;".repeat(2000));
+        assert!(looks_generated("web/bundle.js", &minified));
+        let code = "function add(a, b) {\n  return a + b;\n}\n".repeat(100);
+        assert!(!looks_generated("web/add.js", &code));
+    }
+
+    #[test]
+    fn test_gitattributes() {
+        let detector = GeneratedDetector::from_gitattributes(
+            Path::new("/project"),
+            "# Generated sources\n\
+             gen/** linguist-generated\n\
+             *.snap linguist-generated=true\n\
+             gen/keep.go -linguist-generated\n\
+             vendor/jquery.min.js linguist-generated=false\n\
+             *.md text eol=lf\n",
+        );
+
+        assert!(detector.is_generated("gen/api/client.go", "package api"));
+        assert!(detector.is_generated("src/__snapshots__/a.snap", ""));
+        assert!(!detector.is_generated("gen/keep.go", "package gen"));
+        // An explicit `false` overrides the heuristics
+        assert!(!detector.is_generated("vendor/jquery.min.js", ""));
+        assert!(detector.is_generated("web/app.min.js", ""));
+        assert!(!detector.is_generated("README.md", "# Project"));
+    }
+}
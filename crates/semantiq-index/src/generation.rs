@@ -0,0 +1,95 @@
+//! Change notifications for the index generation
+//!
+//! A [`GenerationWatcher`] polls the index generation of a store (see
+//! [`IndexStore::index_generation`]) and publishes it through a
+//! `tokio::sync::watch` channel, so servers can tell their clients when
+//! results they hold may be stale. Polling the database rather than hooking
+//! writes also catches the writes of other processes, e.g. `semantiq index`
+//! run while a server is up.
+
+use crate::store::IndexStore;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Publishes the index generation of a store as it changes
+#[derive(Debug)]
+pub struct GenerationWatcher {
+    sender: Arc<watch::Sender<u64>>,
+}
+
+impl GenerationWatcher {
+    /// Start polling the generation of `store` every `interval` on a
+    /// background thread, which stops once the watcher is dropped.
+    pub fn spawn(store: Arc<IndexStore>, interval: Duration) -> Self {
+        let initial = store.index_generation().unwrap_or_else(|e| {
+            warn!("Failed to read the index generation: {}", e);
+            0
+        });
+        let sender = Arc::new(watch::Sender::new(initial));
+        let weak = Arc::downgrade(&sender);
+
+        let spawned = std::thread::Builder::new()
+            .name("semantiq-generation".into())
+            .spawn(move || poll(store, weak, interval));
+        if let Err(e) = spawned {
+            warn!("Failed to start the index generation watcher: {}", e);
+        }
+
+        Self { sender }
+    }
+
+    /// Latest index generation seen
+    pub fn current(&self) -> u64 {
+        *self.sender.borrow()
+    }
+
+    /// Receiver notified each time the index generation changes
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.sender.subscribe()
+    }
+}
+
+fn poll(store: Arc<IndexStore>, sender: Weak<watch::Sender<u64>>, interval: Duration) {
+    loop {
+        std::thread::sleep(interval);
+        let Some(sender) = sender.upgrade() else {
+            return;
+        };
+        match store.index_generation() {
+            Ok(generation) => {
+                sender.send_if_modified(|current| {
+                    let changed = *current != generation;
+                    *current = generation;
+                    changed
+                });
+            }
+            Err(e) => warn!("Failed to read the index generation: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watcher_publishes_generation_changes() {
+        let store = Arc::new(IndexStore::open_in_memory().unwrap());
+        let watcher = GenerationWatcher::spawn(Arc::clone(&store), Duration::from_millis(10));
+        assert_eq!(watcher.current(), 0);
+
+        let mut rx = watcher.subscribe();
+        store
+            .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), rx.changed())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(*rx.borrow(), store.index_generation().unwrap());
+        assert!(watcher.current() > 0);
+    }
+}
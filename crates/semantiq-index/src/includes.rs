@@ -0,0 +1,676 @@
+//! Resolution of the `#include` directives of C and C++ files
+//!
+//! An include names a header by a path the compiler looks up in the
+//! directory of the including file (for `"quoted"` includes) and then in
+//! its include directories. These come from `[includes]` in `semantiq.toml`
+//! and from the `-I`, `-iquote` and `-isystem` flags of the compilation
+//! database (`compile_commands.json`), when the project has one.
+//!
+//! Files include headers, never the sources implementing them, so a source
+//! file has no dependents of its own. A header and the source of the same
+//! name next to it, or in the matching `src` directory of an `include`
+//! directory, are a pair (see [`paired_files`]): the files including the
+//! header depend on its source.
+
+use crate::config::IncludesConfig;
+use crate::paths::{relative_path, resolve_path};
+use anyhow::{Context, Result};
+use semantiq_parser::{Import, ImportKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Extensions of C and C++ headers
+pub const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx"];
+
+/// Extensions of C and C++ sources
+pub const SOURCE_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx"];
+
+/// Compilation databases looked for when none is configured
+const DEFAULT_COMPILE_COMMANDS: &[&str] = &["compile_commands.json", "build/compile_commands.json"];
+
+/// Directory names holding headers, and those holding their sources
+const HEADER_DIRS: &[&str] = &["include", "inc"];
+const SOURCE_DIRS: &[&str] = &["src", "source", "lib"];
+
+/// Whether `path` is a C or C++ header
+pub fn is_c_header(path: &str) -> bool {
+    extension(path).is_some_and(|ext| HEADER_EXTENSIONS.contains(&ext))
+}
+
+/// Whether `path` is a C or C++ source
+pub fn is_c_source(path: &str) -> bool {
+    extension(path).is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+}
+
+/// Whether `path` is a C or C++ header or source
+pub fn is_c_family(path: &str) -> bool {
+    is_c_header(path) || is_c_source(path)
+}
+
+fn extension(path: &str) -> Option<&str> {
+    Path::new(path).extension().and_then(|ext| ext.to_str())
+}
+
+/// Directories searched for included files, relative to the project root
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncludePaths {
+    dirs: Vec<String>,
+}
+
+impl IncludePaths {
+    /// Include directories in search order, duplicates left out
+    pub fn new(dirs: impl IntoIterator<Item = String>) -> Self {
+        let mut unique: Vec<String> = Vec::new();
+        for dir in dirs {
+            if !unique.contains(&dir) {
+                unique.push(dir);
+            }
+        }
+        Self { dirs: unique }
+    }
+
+    /// Include directories of the project at `root`: the configured ones,
+    /// then those of its compilation database inside the project. A
+    /// compilation database that cannot be read is left out with a warning.
+    pub fn load(root: &Path, config: &IncludesConfig) -> Self {
+        let units = compile_commands_path(root, config)
+            .map(|path| read_compile_commands(root, &path))
+            .transpose()
+            .unwrap_or_else(|e| {
+                warn!("Compilation database ignored: {:#}", e);
+                None
+            })
+            .unwrap_or_default();
+        Self::from_config(config).with_units(&units)
+    }
+
+    /// The include directories configured
+    pub fn from_config(config: &IncludesConfig) -> Self {
+        Self::new(
+            config
+                .paths
+                .iter()
+                .filter_map(|path| join_path("", &path.to_string_lossy())),
+        )
+    }
+
+    /// These directories, then those of `units` inside the project
+    pub fn with_units(&self, units: &[TranslationUnit]) -> Self {
+        Self::new(
+            self.dirs.iter().cloned().chain(
+                units
+                    .iter()
+                    .flat_map(|unit| unit.project_include_dirs().map(String::from)),
+            ),
+        )
+    }
+
+    /// The directories of `unit` inside the project, then these ones: the
+    /// include directories of the file compiled as `unit`
+    pub fn for_unit(&self, unit: &TranslationUnit) -> Self {
+        Self::new(
+            unit.project_include_dirs()
+                .map(String::from)
+                .chain(self.dirs.iter().cloned()),
+        )
+    }
+
+    pub fn dirs(&self) -> &[String] {
+        &self.dirs
+    }
+
+    /// Indexed file included as `include` by the file at `including`: for
+    /// a `quoted` include, next to the including file first, then in the
+    /// include directories in order. `None` when no candidate is indexed.
+    pub fn resolve(
+        &self,
+        including: &str,
+        include: &str,
+        quoted: bool,
+        is_indexed: impl Fn(&str) -> bool,
+    ) -> Option<String> {
+        let including_dir = including.rsplit_once('/').map_or("", |(dir, _)| dir);
+        quoted
+            .then_some(including_dir)
+            .into_iter()
+            .chain(self.dirs.iter().map(String::as_str))
+            .filter_map(|dir| join_path(dir, include))
+            .find(|candidate| is_indexed(candidate))
+    }
+}
+
+/// Classifies the includes of C and C++ files as they are indexed, looking
+/// them up in the include directories of their translation unit
+#[derive(Debug, Clone, Default)]
+pub struct IncludeResolver {
+    root: PathBuf,
+    paths: IncludePaths,
+    units: HashMap<String, TranslationUnit>,
+    /// Include directories outside the project of every unit, for headers
+    external_dirs: Vec<PathBuf>,
+}
+
+impl IncludeResolver {
+    pub fn new(root: &Path, paths: IncludePaths, units: Vec<TranslationUnit>) -> Self {
+        let mut external_dirs: Vec<PathBuf> = Vec::new();
+        for dir in units
+            .iter()
+            .flat_map(TranslationUnit::external_include_dirs)
+        {
+            if !external_dirs.iter().any(|known| known == dir) {
+                external_dirs.push(dir.to_path_buf());
+            }
+        }
+        Self {
+            root: root.to_path_buf(),
+            paths: paths.with_units(&units),
+            units: units
+                .into_iter()
+                .map(|unit| (unit.path.clone(), unit))
+                .collect(),
+            external_dirs,
+        }
+    }
+
+    /// Resolver of the project at `root`, from its configuration and its
+    /// compilation database. A compilation database that cannot be read is
+    /// left out with a warning.
+    pub fn load(root: &Path, config: &IncludesConfig) -> Self {
+        let units = match compile_commands_path(root, config) {
+            Some(path) => read_compile_commands(root, &path).unwrap_or_else(|e| {
+                warn!("Compilation database ignored: {:#}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        Self::new(root, IncludePaths::from_config(config), units)
+    }
+
+    /// Translation units of the compilation database, by path
+    pub fn units(&self) -> Vec<TranslationUnit> {
+        let mut units: Vec<TranslationUnit> = self.units.values().cloned().collect();
+        units.sort();
+        units
+    }
+
+    /// Set the kind of each include of the C or C++ file at `path`: `local`
+    /// when it is found in the project, `external` in an include directory
+    /// outside of it, `std` otherwise. A quoted include found nowhere, e.g.
+    /// a generated header, stays `local`.
+    pub fn classify(&self, path: &str, imports: &mut [Import]) {
+        if !is_c_family(path) {
+            return;
+        }
+        let unit = self.units.get(path);
+        let paths = unit.map(|unit| self.paths.for_unit(unit));
+        let paths = paths.as_ref().unwrap_or(&self.paths);
+        let external_dirs: Vec<&Path> = match unit {
+            Some(unit) => unit.external_include_dirs().collect(),
+            None => self.external_dirs.iter().map(PathBuf::as_path).collect(),
+        };
+
+        for import in imports
+            .iter_mut()
+            .filter(|i| matches!(i.kind, ImportKind::Local | ImportKind::Std))
+        {
+            let quoted = import.kind == ImportKind::Local;
+            let in_project = paths
+                .resolve(path, &import.path, quoted, |candidate| {
+                    resolve_path(&self.root, candidate).is_file()
+                })
+                .is_some();
+            import.kind = if in_project {
+                ImportKind::Local
+            } else if external_dirs
+                .iter()
+                .any(|dir| dir.join(&import.path).is_file())
+            {
+                ImportKind::External
+            } else if quoted {
+                ImportKind::Local
+            } else {
+                ImportKind::Std
+            };
+        }
+    }
+}
+
+/// Indexed files paired with the header or source at `path`: the sources
+/// of a header, or the headers of a source, of the same name, next to it
+/// or in the matching directory (`include/` and `src/`, `inc/` and
+/// `source/`...). Headers under `include/<project>/` are paired with
+/// sources directly in `src/`.
+pub fn paired_files(path: &str, is_indexed: impl Fn(&str) -> bool) -> Vec<String> {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let Some((stem, _)) = file.rsplit_once('.') else {
+        return Vec::new();
+    };
+    let (extensions, from_dirs, to_dirs) = if is_c_header(path) {
+        (SOURCE_EXTENSIONS, HEADER_DIRS, SOURCE_DIRS)
+    } else if is_c_source(path) {
+        (HEADER_EXTENSIONS, SOURCE_DIRS, HEADER_DIRS)
+    } else {
+        return Vec::new();
+    };
+
+    let segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    let mut dirs = vec![dir.to_string()];
+    for (i, segment) in segments.iter().enumerate() {
+        if !from_dirs.contains(segment) {
+            continue;
+        }
+        for to_dir in to_dirs {
+            let mut swapped = segments.clone();
+            swapped[i] = to_dir;
+            dirs.push(swapped.join("/"));
+            if is_c_header(path) && i + 1 < segments.len() {
+                dirs.push(swapped[..=i].join("/"));
+            }
+        }
+    }
+
+    let mut paired = Vec::new();
+    for dir in &dirs {
+        for extension in extensions {
+            let candidate = match dir.as_str() {
+                "" => format!("{}.{}", stem, extension),
+                dir => format!("{}/{}.{}", dir, stem, extension),
+            };
+            if candidate != path && !paired.contains(&candidate) && is_indexed(&candidate) {
+                paired.push(candidate);
+            }
+        }
+    }
+    paired
+}
+
+/// Path `rel` from the directory `dir`, both relative to the project root,
+/// with `.` and `..` segments resolved. `None` when it leaves the project
+/// or `rel` is absolute.
+fn join_path(dir: &str, rel: &str) -> Option<String> {
+    if rel.starts_with('/') || Path::new(rel).is_absolute() {
+        return None;
+    }
+    let mut parts: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for part in rel.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// A source file of the compilation database, compiled on its own
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TranslationUnit {
+    /// Path of the source relative to the project root
+    pub path: String,
+    /// Include directories in search order: relative to the project root,
+    /// or absolute for those outside of it (system and third-party headers)
+    pub include_dirs: Vec<String>,
+    /// Macros defined on the command line, `NAME` or `NAME=value`
+    pub defines: Vec<String>,
+    /// Build target compiling the file, when its object file is named after
+    /// it (`CMakeFiles/<target>.dir/`)
+    pub target: Option<String>,
+}
+
+impl TranslationUnit {
+    /// Include directories inside the project, relative to its root
+    pub fn project_include_dirs(&self) -> impl Iterator<Item = &str> {
+        self.include_dirs
+            .iter()
+            .map(String::as_str)
+            .filter(|dir| !Path::new(dir).is_absolute())
+    }
+
+    /// Include directories outside the project
+    pub fn external_include_dirs(&self) -> impl Iterator<Item = &Path> {
+        self.include_dirs
+            .iter()
+            .map(Path::new)
+            .filter(|dir| dir.is_absolute())
+    }
+}
+
+/// Compilation database of the project at `root`: the configured one, or
+/// else `compile_commands.json` at the root or in `build/` if there is one
+pub fn compile_commands_path(root: &Path, config: &IncludesConfig) -> Option<PathBuf> {
+    match config.compile_commands {
+        Some(ref path) => Some(root.join(path)),
+        None => DEFAULT_COMPILE_COMMANDS
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.is_file()),
+    }
+}
+
+/// Entry of a compilation database
+#[derive(Deserialize)]
+struct CompileCommand {
+    directory: PathBuf,
+    file: PathBuf,
+    #[serde(default)]
+    arguments: Vec<String>,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    output: Option<String>,
+}
+
+/// Translation units of the compilation database at `path`, by path. Files
+/// outside the project at `root` are left out; of a file compiled several
+/// times, the first entry is kept.
+pub fn read_compile_commands(root: &Path, path: &Path) -> Result<Vec<TranslationUnit>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let commands: Vec<CompileCommand> = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid compilation database {}", path.display()))?;
+
+    let mut units: Vec<TranslationUnit> = Vec::new();
+    for command in commands {
+        let Some(file) = project_path(root, &command.directory.join(&command.file)) else {
+            continue;
+        };
+        if units.iter().any(|unit| unit.path == file) {
+            continue;
+        }
+        let arguments = match command.command {
+            Some(ref line) if command.arguments.is_empty() => split_command(line),
+            _ => command.arguments,
+        };
+
+        let mut include_dirs: Vec<String> = Vec::new();
+        for dir in flag_values(&arguments, INCLUDE_FLAGS) {
+            let dir = command.directory.join(dir);
+            let dir = project_path(root, &dir).unwrap_or_else(|| dir.to_string_lossy().into());
+            if !include_dirs.contains(&dir) {
+                include_dirs.push(dir);
+            }
+        }
+        let output = command
+            .output
+            .as_deref()
+            .or_else(|| flag_values(&arguments, &["-o"]).into_iter().next());
+        units.push(TranslationUnit {
+            path: file,
+            include_dirs,
+            defines: flag_values(&arguments, &["-D", "/D"])
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            target: output.and_then(build_target),
+        });
+    }
+    units.sort();
+    Ok(units)
+}
+
+/// Path of `path` relative to the project root with `..` segments
+/// resolved, `None` outside the project
+fn project_path(root: &Path, path: &Path) -> Option<String> {
+    relative_path(root, path).and_then(|rel| join_path("", &rel))
+}
+
+/// Target named by the path of an object file built by CMake
+fn build_target(output: &str) -> Option<String> {
+    let output = output.replace('\\', "/");
+    let (_, rest) = output.split_once("CMakeFiles/")?;
+    let (target, _) = rest.split_once(".dir/")?;
+    Some(target.to_string())
+}
+
+/// Flags of include directories
+const INCLUDE_FLAGS: &[&str] = &["-I", "/I", "-iquote", "-isystem", "-idirafter"];
+
+/// Values of the `flags`, given as one argument (`-Iinclude`) or two
+/// (`-I include`)
+fn flag_values<'a>(arguments: &'a [String], flags: &[&str]) -> Vec<&'a str> {
+    let mut values = Vec::new();
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        let Some(flag) = flags.iter().find(|flag| argument.starts_with(*flag)) else {
+            continue;
+        };
+        match &argument[flag.len()..] {
+            "" => values.extend(arguments.next().map(String::as_str)),
+            value => values.push(value),
+        }
+    }
+    values
+}
+
+/// Arguments of a command line, split on whitespace outside quotes
+fn split_command(line: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_argument = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_argument = true;
+            }
+            (Some(q), c) if c == q => quote = None,
+            (_, '\\') => {
+                current.extend(chars.next());
+                in_argument = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            (_, c) => {
+                current.push(c);
+                in_argument = true;
+            }
+        }
+    }
+    if in_argument {
+        arguments.push(current);
+    }
+    arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paired_files() {
+        let indexed = [
+            "src/parser.c",
+            "src/parser.h",
+            "include/lexer.h",
+            "src/lexer.cpp",
+            "include/proj/ast.hpp",
+            "src/ast.cc",
+        ];
+        let is_indexed = |path: &str| indexed.contains(&path);
+
+        assert_eq!(paired_files("src/parser.c", is_indexed), ["src/parser.h"]);
+        assert_eq!(paired_files("src/parser.h", is_indexed), ["src/parser.c"]);
+        assert_eq!(
+            paired_files("include/lexer.h", is_indexed),
+            ["src/lexer.cpp"]
+        );
+        assert_eq!(
+            paired_files("src/lexer.cpp", is_indexed),
+            ["include/lexer.h"]
+        );
+        assert_eq!(
+            paired_files("include/proj/ast.hpp", is_indexed),
+            ["src/ast.cc"]
+        );
+        assert!(paired_files("src/main.rs", is_indexed).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_include() {
+        let includes = IncludePaths::new(["include".to_string(), "vendor/zlib".to_string()]);
+        let indexed = ["src/util.h", "include/proj/api.h", "vendor/zlib/zlib.h"];
+        let is_indexed = |path: &str| indexed.contains(&path);
+
+        assert_eq!(
+            includes.resolve("src/main.c", "util.h", true, is_indexed),
+            Some("src/util.h".to_string())
+        );
+        assert_eq!(
+            includes.resolve("src/net/io.c", "../util.h", true, is_indexed),
+            Some("src/util.h".to_string())
+        );
+        assert_eq!(
+            includes.resolve("src/main.c", "proj/api.h", true, is_indexed),
+            Some("include/proj/api.h".to_string())
+        );
+        assert_eq!(
+            includes.resolve("src/main.c", "zlib.h", false, is_indexed),
+            Some("vendor/zlib/zlib.h".to_string())
+        );
+        // Angle includes are not looked up next to the including file
+        assert_eq!(
+            includes.resolve("src/main.c", "util.h", false, is_indexed),
+            None
+        );
+        assert_eq!(
+            includes.resolve("src/main.c", "stdio.h", false, is_indexed),
+            None
+        );
+    }
+
+    #[test]
+    fn test_load_compile_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("build")).unwrap();
+        let build = root.join("build");
+        let database = serde_json::json!([
+            {
+                "directory": build,
+                "command": "cc -I../include -iquote \"../gen dir\" -isystem /usr/include -c ../src/a.c",
+                "file": "../src/a.c",
+            },
+            {
+                "directory": root,
+                "arguments": ["c++", "-I", "include", "-Ivendor/zlib", "-c", "src/b.cpp"],
+                "file": "src/b.cpp",
+            },
+        ]);
+        fs::write(
+            root.join("build/compile_commands.json"),
+            database.to_string(),
+        )
+        .unwrap();
+
+        let config = IncludesConfig {
+            paths: vec![PathBuf::from("third_party")],
+            compile_commands: None,
+        };
+        assert_eq!(
+            IncludePaths::load(root, &config).dirs(),
+            ["third_party", "include", "gen dir", "vendor/zlib"]
+        );
+    }
+
+    #[test]
+    fn test_read_compile_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let database = serde_json::json!([
+            {
+                "directory": root.join("build"),
+                "command": "cc -DDEBUG -D VERSION=2 -I../include -isystem /opt/ssl/include -o CMakeFiles/app.dir/src/a.c.o -c ../src/a.c",
+                "file": "../src/a.c",
+            },
+            {
+                "directory": root,
+                "arguments": ["cc", "-Iinclude", "-c", "src/a.c"],
+                "file": "src/a.c",
+            },
+            {
+                "directory": "/elsewhere",
+                "arguments": ["cc", "-c", "x.c"],
+                "file": "x.c",
+            },
+        ]);
+        let path = root.join("compile_commands.json");
+        fs::write(&path, database.to_string()).unwrap();
+
+        // The file outside the project and the second entry of `src/a.c`
+        // are left out
+        assert_eq!(
+            read_compile_commands(root, &path).unwrap(),
+            [TranslationUnit {
+                path: "src/a.c".to_string(),
+                include_dirs: vec!["include".to_string(), "/opt/ssl/include".to_string()],
+                defines: vec!["DEBUG".to_string(), "VERSION=2".to_string()],
+                target: Some("app".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_classify_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        let external = dir.path().join("ssl");
+        for file in ["include/api.h", "src/util.h", "src/a.c", "src/b.c"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::create_dir_all(external.join("openssl")).unwrap();
+        fs::write(external.join("openssl/ssl.h"), "").unwrap();
+
+        let unit = TranslationUnit {
+            path: "src/a.c".to_string(),
+            include_dirs: vec!["include".to_string(), external.to_string_lossy().into()],
+            defines: Vec::new(),
+            target: None,
+        };
+        let resolver = IncludeResolver::new(&root, IncludePaths::default(), vec![unit]);
+        let include = |path: &str, kind: ImportKind| Import {
+            path: path.to_string(),
+            name: None,
+            kind,
+            symbols: Vec::new(),
+            start_line: 1,
+            end_line: 1,
+        };
+        let kinds = |path: &str| {
+            let mut imports = vec![
+                include("api.h", ImportKind::Std),
+                include("openssl/ssl.h", ImportKind::Std),
+                include("stdio.h", ImportKind::Std),
+                include("util.h", ImportKind::Local),
+                include("generated.h", ImportKind::Local),
+            ];
+            resolver.classify(path, &mut imports);
+            imports.into_iter().map(|i| i.kind).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds("src/a.c"),
+            [
+                ImportKind::Local,
+                ImportKind::External,
+                ImportKind::Std,
+                ImportKind::Local,
+                ImportKind::Local,
+            ]
+        );
+        // Without a translation unit, the directories of every unit are used
+        assert_eq!(kinds("src/b.c")[..3], kinds("src/a.c")[..3]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Problems met while indexing files.
+//!
+//! A file that cannot be read, is too large, had its encoding guessed or
+//! fails to parse is not worth stopping an indexing pass for: it is indexed
+//! as well as it can be, or left out, and the pass goes on. Each problem is
+//! an [`IndexIssue`] of the file, written to the `index_issues` table in
+//! place of the issues of its previous indexing, so that they outlive the
+//! log of the pass: `semantiq stats` counts them and `semantiq_index_issues`
+//! lists them. The issues met by one pass make up its [`IndexReport`].
+
+use crate::exclusions::MAX_OUTLINE_FILE_SIZE;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Kind of problem met while indexing a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexIssueKind {
+    /// The file failed to parse or its symbols failed to extract; it is
+    /// indexed without symbols or chunks
+    ParseFailure,
+    /// The file could not be read, or is binary; it is left out
+    ReadError,
+    /// The file is too large even for an outline; it is left out
+    TooLarge,
+    /// The encoding of the file was guessed or invalid bytes were replaced
+    /// (see [`crate::encoding`]); it is indexed as decoded
+    Encoding,
+}
+
+impl IndexIssueKind {
+    pub const ALL: [IndexIssueKind; 4] = [
+        IndexIssueKind::ParseFailure,
+        IndexIssueKind::ReadError,
+        IndexIssueKind::TooLarge,
+        IndexIssueKind::Encoding,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IndexIssueKind::ParseFailure => "parse_failure",
+            IndexIssueKind::ReadError => "read_error",
+            IndexIssueKind::TooLarge => "too_large",
+            IndexIssueKind::Encoding => "encoding",
+        }
+    }
+
+    /// Kind named `name`, as written by [`IndexIssueKind::as_str`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+
+    /// Description of `count` issues of this kind, e.g. "2 parse failures"
+    pub fn describe(&self, count: usize) -> String {
+        let (one, many) = match self {
+            IndexIssueKind::ParseFailure => ("parse failure", "parse failures"),
+            IndexIssueKind::ReadError => ("read error", "read errors"),
+            IndexIssueKind::TooLarge => ("file too large", "files too large"),
+            IndexIssueKind::Encoding => ("encoding warning", "encoding warnings"),
+        };
+        format!("{} {}", count, if count == 1 { one } else { many })
+    }
+}
+
+impl fmt::Display for IndexIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem met while indexing the file at `path`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexIssue {
+    /// Path relative to the project root
+    pub path: String,
+    pub kind: IndexIssueKind,
+    pub message: String,
+}
+
+impl IndexIssue {
+    pub fn new(path: &str, kind: IndexIssueKind, message: impl fmt::Display) -> Self {
+        Self {
+            path: path.to_string(),
+            kind,
+            message: message.to_string(),
+        }
+    }
+
+    /// Issue of a file of `size` bytes, too large to be indexed
+    pub fn too_large(path: &str, size: u64) -> Self {
+        Self::new(
+            path,
+            IndexIssueKind::TooLarge,
+            format!(
+                "{} bytes, over the limit of {} bytes",
+                size, MAX_OUTLINE_FILE_SIZE
+            ),
+        )
+    }
+}
+
+/// Issues met by an indexing pass, in the order of the files
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexReport {
+    pub issues: Vec<IndexIssue>,
+}
+
+impl IndexReport {
+    pub fn extend(&mut self, issues: impl IntoIterator<Item = IndexIssue>) {
+        self.issues.extend(issues);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues of each kind met, in the order of
+    /// [`IndexIssueKind::ALL`]
+    pub fn counts(&self) -> Vec<(IndexIssueKind, usize)> {
+        IndexIssueKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.issues.iter().filter(|i| i.kind == kind).count()))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    /// Number of files with at least one issue
+    pub fn file_count(&self) -> usize {
+        let mut paths: Vec<&str> = self.issues.iter().map(|i| i.path.as_str()).collect();
+        paths.sort_unstable();
+        paths.dedup();
+        paths.len()
+    }
+}
+
+impl fmt::Display for IndexReport {
+    /// One line: "3 files with issues (2 parse failures, 1 read error)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files = self.file_count();
+        let counts: Vec<String> = self
+            .counts()
+            .into_iter()
+            .map(|(kind, count)| kind.describe(count))
+            .collect();
+        write!(
+            f,
+            "{} file{} with issues ({})",
+            files,
+            if files == 1 { "" } else { "s" },
+            counts.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_names_round_trip() {
+        for kind in IndexIssueKind::ALL {
+            assert_eq!(IndexIssueKind::from_name(kind.as_str()), Some(kind));
+        }
+        assert_eq!(IndexIssueKind::from_name("crash"), None);
+    }
+
+    #[test]
+    fn test_report_summary() {
+        let mut report = IndexReport::default();
+        assert!(report.is_empty());
+        report.extend([
+            IndexIssue::new("a.rs", IndexIssueKind::ParseFailure, "unbalanced braces"),
+            IndexIssue::new("a.rs", IndexIssueKind::Encoding, "guessed Shift_JIS"),
+            IndexIssue::new("b.rs", IndexIssueKind::ParseFailure, "timeout"),
+            IndexIssue::new("c.bin.rs", IndexIssueKind::ReadError, "binary file"),
+        ]);
+        assert_eq!(report.file_count(), 3);
+        assert_eq!(
+            report.to_string(),
+            "3 files with issues (2 parse failures, 1 read error, 1 encoding warning)"
+        );
+    }
+}
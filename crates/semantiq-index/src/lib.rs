@@ -1,13 +1,75 @@
+pub mod async_store;
 pub mod auto_indexer;
+pub mod clock;
+pub mod compression;
+pub mod config;
+pub mod encoding;
 pub mod exclusions;
+pub mod external;
+pub mod extraction;
+pub mod generated;
+pub mod generation;
+pub mod includes;
+pub mod issues;
+pub mod migration;
+pub mod outline;
+pub mod paths;
+pub mod preprocess;
+pub mod priority;
+pub mod progress;
+pub mod quantization;
 pub mod schema;
 pub mod store;
+pub mod summary;
+pub mod titles;
 pub mod watcher;
+pub mod workspace;
 
-pub use auto_indexer::{AutoIndexer, InitialIndexResult, ProcessResult};
+pub use async_store::{AsyncIndexStore, DEFAULT_INDEX_QUEUE};
+pub use auto_indexer::{AutoIndexer, InitialIndexResult, ProcessResult, ReindexReport};
+pub use clock::{unix_now, unix_now_millis};
+pub use compression::{ChunkCodec, ChunkDictionary};
+pub use config::{
+    BaseIndexConfig, CONFIG_FILE_NAME, ChunkingConfig, EmbeddingsConfig, ExternalConfig, GitConfig,
+    HistoryConfig, IncludesConfig, LimitsConfig, QueryCacheConfig, QuotaConfig,
+    RecalibrationConfig, SearchConfig, SemantiqConfig, StorageConfig, ToolLimitsConfig,
+    UsageConfig,
+};
+pub use encoding::{SourceText, decode_source, read_source};
 pub use exclusions::{
-    EXCLUDED_DIRS, MAX_FILE_SIZE, should_exclude, should_exclude_entry, should_exclude_path,
+    EXCLUDED_DIRS, MAX_FILE_SIZE, MAX_OUTLINE_FILE_SIZE, should_exclude, should_exclude_entry,
+    should_exclude_path,
+};
+pub use external::{
+    EXTERNAL_PREFIX, Ecosystem, ExternalIndexResult, ExternalPackage, is_external_path,
+};
+pub use extraction::ExtractedFile;
+pub use generated::GeneratedDetector;
+pub use generation::GenerationWatcher;
+pub use includes::{
+    IncludePaths, IncludeResolver, TranslationUnit, is_c_family, is_c_header, is_c_source,
+    paired_files,
+};
+pub use issues::{IndexIssue, IndexIssueKind, IndexReport};
+pub use migration::{EmbeddingMigrator, MIGRATION_BATCH_SIZE, MigrationStep};
+pub use outline::{is_outline_size, outline_symbols};
+pub use paths::{normalize_path, relative_path, resolve_path, to_index_path};
+pub use preprocess::{EmbeddingPreprocessor, PreprocessStep};
+pub use progress::{FileOutcome, IndexPhase, IndexProgress, ProgressTracker};
+pub use quantization::EmbeddingFormat;
+pub use schema::{
+    BindingRecord, ChunkRecord, ConfigRefRecord, DependencyRecord, EntrypointRecord, FileRecord,
+    IndexIssueRecord, RouteRecord, SymbolRecord, SymbolVersionRecord, TypeRelationRecord,
+};
+pub use store::{
+    CalibrationData, CalibrationRecord, ChunkEmbeddings, CompactionReport, DirectoryStats,
+    DirectorySummary, EmbeddingMigration, EvictedDirectory, EvictionReport, ExternalPackageMarker,
+    FileIndexData, FileTag, INDEX_LOCK_STALE_SECS, IndexLockAttempt, IndexLockGuard, IndexLockInfo,
+    IndexStats, IndexStore, LanguageShare, LanguageStats, QuotaStats, SavedQuery, SharedSymbol,
+    SymbolEdge, SymbolField, SymbolMatch, SymbolUsage, ToolUsage, ToolUsageEvent, TopSymbol,
+    UnreferencedSymbol,
 };
-pub use schema::{ChunkRecord, DependencyRecord, FileRecord, SymbolRecord};
-pub use store::{CalibrationData, CalibrationRecord, IndexStats, IndexStore};
+pub use summary::file_summary;
+pub use titles::chunk_title;
 pub use watcher::FileWatcher;
+pub use workspace::{PackageEcosystem, WorkspacePackage};
@@ -0,0 +1,326 @@
+//! Switch of an indexed project to another embedding model
+//!
+//! When `embeddings.model` changes, [`IndexStore::set_embedding_model`] keeps
+//! the parsed files and starts a migration. An [`EmbeddingMigrator`] then
+//! embeds the chunks again in batches, in the background under
+//! `semantiq serve` or at the end of `semantiq index`:
+//!
+//! 1. Chunks are embedded with the new model into a second vector table,
+//!    while searches keep using the current one.
+//! 2. Once `embeddings.switch_coverage` of the chunks are embedded, searches
+//!    switch to the new vectors in one transaction.
+//! 3. Chunks left without a vector, e.g. those written during the migration,
+//!    are embedded with the new model, then the file summaries.
+
+use crate::IndexStore;
+use crate::schema::EMBEDDING_DIMENSION;
+use crate::store::EmbeddingMigration;
+use anyhow::{Result, bail};
+use semantiq_embeddings::{
+    ChunkEmbedding, EmbeddingModel, EmbeddingModelKind, create_embedding_model_for,
+};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Chunks embedded per migration step
+pub const MIGRATION_BATCH_SIZE: usize = 64;
+
+/// Outcome of one [`EmbeddingMigrator::step`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MigrationStep {
+    /// A batch was embedded with the new model, searches still use the old one
+    Migrating(EmbeddingMigration),
+    /// Searches now use the new model
+    Switched(EmbeddingModelKind),
+    /// Chunks or file summaries left without a vector by the switch were embedded
+    Backfilled(usize),
+    /// Another process holds the index lock, try again later
+    Paused,
+    /// Nothing left to embed
+    Done,
+}
+
+/// Embeds the chunks of an index again with the model of its pending migration
+pub struct EmbeddingMigrator {
+    target: EmbeddingModelKind,
+    model: Arc<dyn EmbeddingModel>,
+    switch_coverage: f64,
+}
+
+impl EmbeddingMigrator {
+    pub fn new(
+        target: EmbeddingModelKind,
+        model: Arc<dyn EmbeddingModel>,
+        switch_coverage: f64,
+    ) -> Self {
+        Self {
+            target,
+            model,
+            switch_coverage: switch_coverage.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Migrator for the pending migration of `store`, loading (and
+    /// downloading if needed) its model. `None` when no migration is pending.
+    pub fn for_store(store: &IndexStore, switch_coverage: f64) -> Result<Option<Self>> {
+        let Some(migration) = store.embedding_migration()? else {
+            return Ok(None);
+        };
+        let model = create_embedding_model_for(migration.to)?;
+        info!(
+            "Re-embedding {} chunks with {} ({} done)",
+            migration.total, migration.to, migration.embedded
+        );
+        Ok(Some(Self::new(migration.to, model, switch_coverage)))
+    }
+
+    /// Model the chunks are embedded with
+    pub fn target(&self) -> EmbeddingModelKind {
+        self.target
+    }
+
+    pub fn model(&self) -> Arc<dyn EmbeddingModel> {
+        Arc::clone(&self.model)
+    }
+
+    /// Embed the next batch of at most `batch_size` chunks, switching
+    /// searches to the new model once enough chunks are embedded. The caller
+    /// holds the index lock.
+    pub fn step(&self, store: &IndexStore, batch_size: usize) -> Result<MigrationStep> {
+        match store.embedding_migration()? {
+            Some(migration) if migration.to == self.target => {
+                let chunks = store.chunks_to_migrate(batch_size)?;
+                if let Some(last) = chunks.last() {
+                    let texts: Vec<String> = chunks
+                        .iter()
+                        .map(|c| store.embedding_text(&c.content))
+                        .collect();
+                    let ids: Vec<i64> = chunks.iter().map(|c| c.id).collect();
+                    let embeddings: Vec<(i64, Vec<f32>)> = self
+                        .embed(&ids, &texts)?
+                        .into_iter()
+                        .map(|(id, embedded)| (id, embedded.embedding))
+                        .collect();
+                    store.store_migrated_embeddings(last.id, &embeddings)?;
+
+                    let progress = store.embedding_migration()?.unwrap_or(migration);
+                    debug!(
+                        "Re-embedded {}/{} chunks with {}",
+                        progress.embedded, progress.total, self.target
+                    );
+                    if progress.coverage() < self.switch_coverage {
+                        return Ok(MigrationStep::Migrating(progress));
+                    }
+                }
+                // Enough chunks embedded, or none left to embed
+                store.complete_embedding_migration()?;
+                Ok(MigrationStep::Switched(self.target))
+            }
+            // The migration was cancelled or replaced by another one
+            Some(_) => Ok(MigrationStep::Done),
+            None if store.embedding_model()? == self.target => {
+                let chunks = store.get_chunks_without_embeddings(batch_size)?;
+                if chunks.is_empty() {
+                    return self.backfill_file_summaries(store, batch_size);
+                }
+                let texts: Vec<String> = chunks
+                    .iter()
+                    .map(|c| store.embedding_text(&c.content))
+                    .collect();
+                let ids: Vec<i64> = chunks.iter().map(|c| c.id).collect();
+                let embeddings = match self.embed(&ids, &texts) {
+                    Ok(embeddings) => embeddings,
+                    // Those chunks cannot be embedded, leave them
+                    Err(e) => {
+                        debug!("Stopping backfill: {}", e);
+                        return Ok(MigrationStep::Done);
+                    }
+                };
+                for (chunk_id, embedded) in &embeddings {
+                    store.update_chunk_embedding(*chunk_id, &embedded.embedding)?;
+                    store.update_chunk_token_fit(*chunk_id, &embedded.fit)?;
+                }
+                Ok(MigrationStep::Backfilled(embeddings.len()))
+            }
+            None => Ok(MigrationStep::Done),
+        }
+    }
+
+    /// Run steps until the migration and the backfill are done, calling
+    /// `on_step` after each one.
+    pub fn run(
+        &self,
+        store: &IndexStore,
+        batch_size: usize,
+        mut on_step: impl FnMut(&MigrationStep),
+    ) -> Result<()> {
+        loop {
+            let step = self.step(store, batch_size)?;
+            on_step(&step);
+            if step == MigrationStep::Done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Embed the summaries of files that have none, after the chunks.
+    fn backfill_file_summaries(
+        &self,
+        store: &IndexStore,
+        batch_size: usize,
+    ) -> Result<MigrationStep> {
+        let summaries = store.get_files_without_summary(batch_size)?;
+        if summaries.is_empty() {
+            return Ok(MigrationStep::Done);
+        }
+        let (ids, texts): (Vec<i64>, Vec<String>) = summaries
+            .into_iter()
+            .map(|(id, summary)| (id, store.passage_text(&summary)))
+            .unzip();
+        let embeddings = match self.embed(&ids, &texts) {
+            Ok(embeddings) => embeddings,
+            Err(e) => {
+                debug!("Stopping file summary backfill: {}", e);
+                return Ok(MigrationStep::Done);
+            }
+        };
+        for (file_id, embedded) in &embeddings {
+            store.update_file_summary_embedding(*file_id, &embedded.embedding)?;
+        }
+        Ok(MigrationStep::Backfilled(embeddings.len()))
+    }
+
+    /// Embed texts in one batch, falling back to one at a time if the batch
+    /// fails, and pair each embedding with the id of its text. Texts over the
+    /// model's token limit are embedded in windows. Fails when no text could
+    /// be embedded.
+    fn embed(&self, ids: &[i64], texts: &[String]) -> Result<Vec<(i64, ChunkEmbedding)>> {
+        let embeddings: Vec<Option<ChunkEmbedding>> = match self.model.embed_chunks(texts) {
+            Ok(embeddings) => embeddings.into_iter().map(Some).collect(),
+            Err(e) => {
+                debug!("Batch embedding failed, falling back to individual: {}", e);
+                texts
+                    .iter()
+                    .map(|text| self.model.embed_chunk(text).ok())
+                    .collect()
+            }
+        };
+
+        let embedded: Vec<(i64, ChunkEmbedding)> = ids
+            .iter()
+            .zip(embeddings)
+            .filter_map(|(id, embedding)| Some((*id, embedding?)))
+            .filter(|(_, embedded)| embedded.embedding.len() == EMBEDDING_DIMENSION)
+            .collect();
+        if embedded.is_empty() {
+            bail!("Failed to embed {} texts with {}", texts.len(), self.target);
+        }
+        Ok(embedded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_embeddings::StubEmbeddingModel;
+    use semantiq_parser::CodeChunk;
+
+    fn chunk(content: &str) -> CodeChunk {
+        CodeChunk {
+            content: content.to_string(),
+            start_line: 1,
+            end_line: 1,
+            start_byte: 0,
+            end_byte: content.len(),
+            symbols: Vec::new(),
+        }
+    }
+
+    fn embedded_store(chunks: usize) -> IndexStore {
+        let store = IndexStore::open_in_memory().unwrap();
+        let file_id = store
+            .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+            .unwrap();
+        let chunks: Vec<CodeChunk> = (0..chunks)
+            .map(|i| chunk(&format!("fn f{i}() {{}}")))
+            .collect();
+        store.insert_chunks(file_id, &chunks).unwrap();
+        for record in store.get_chunks_by_file(file_id).unwrap() {
+            store
+                .update_chunk_embedding(record.id, &vec![0.5; EMBEDDING_DIMENSION])
+                .unwrap();
+        }
+        store
+    }
+
+    fn migrator(switch_coverage: f64) -> EmbeddingMigrator {
+        EmbeddingMigrator::new(
+            EmbeddingModelKind::MultilingualMiniLm,
+            Arc::new(StubEmbeddingModel::new()),
+            switch_coverage,
+        )
+    }
+
+    #[test]
+    fn test_migration_switches_at_coverage() {
+        let store = embedded_store(4);
+        assert!(
+            store
+                .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+                .unwrap()
+        );
+        let migrator = migrator(0.75);
+
+        let step = migrator.step(&store, 2).unwrap();
+        let MigrationStep::Migrating(progress) = step else {
+            panic!("expected a migrating step, got {:?}", step);
+        };
+        assert_eq!((progress.embedded, progress.total), (2, 4));
+        assert_eq!(store.embedding_model().unwrap(), EmbeddingModelKind::MiniLm);
+
+        assert_eq!(
+            migrator.step(&store, 2).unwrap(),
+            MigrationStep::Switched(EmbeddingModelKind::MultilingualMiniLm)
+        );
+        assert_eq!(
+            store.embedding_model().unwrap(),
+            EmbeddingModelKind::MultilingualMiniLm
+        );
+        assert_eq!(store.get_embedding_coverage().unwrap(), (4, 4));
+        // Then the summary of the file
+        assert_eq!(
+            migrator.step(&store, 2).unwrap(),
+            MigrationStep::Backfilled(1)
+        );
+        assert_eq!(migrator.step(&store, 2).unwrap(), MigrationStep::Done);
+    }
+
+    #[test]
+    fn test_chunks_written_during_migration_are_backfilled() {
+        let store = embedded_store(2);
+        store
+            .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+            .unwrap();
+        let migrator = migrator(0.5);
+        assert!(matches!(
+            migrator.step(&store, 1).unwrap(),
+            MigrationStep::Switched(_)
+        ));
+        // The second chunk was not embedded with the new model before the switch
+        assert_eq!(store.get_embedding_coverage().unwrap(), (1, 2));
+
+        let mut steps = Vec::new();
+        migrator.run(&store, 10, |step| steps.push(*step)).unwrap();
+        // The chunk, then the file summary dropped by the switch
+        assert_eq!(
+            steps,
+            vec![
+                MigrationStep::Backfilled(1),
+                MigrationStep::Backfilled(1),
+                MigrationStep::Done
+            ]
+        );
+        assert_eq!(store.get_embedding_coverage().unwrap(), (2, 2));
+        assert_eq!(store.get_file_summary_coverage().unwrap(), (1, 1));
+    }
+}
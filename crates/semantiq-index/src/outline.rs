@@ -0,0 +1,65 @@
+//! Outline indexing of oversized files
+//!
+//! Files above [`MAX_FILE_SIZE`] are too large to chunk and embed, but a
+//! 2MB generated module is still worth navigating. Up to
+//! [`MAX_OUTLINE_FILE_SIZE`], they are parsed and indexed as an outline:
+//! their top-level symbols, with names, signatures and lines, and no chunks,
+//! embeddings, imports, relations, occurrences or keywords. Only symbol
+//! search finds them, and their results are marked as coming from an
+//! outline.
+
+use crate::exclusions::{MAX_FILE_SIZE, MAX_OUTLINE_FILE_SIZE};
+use semantiq_parser::Symbol;
+
+/// Whether a file of `size` bytes is indexed as an outline
+pub fn is_outline_size(size: u64) -> bool {
+    size > MAX_FILE_SIZE && size <= MAX_OUTLINE_FILE_SIZE
+}
+
+/// Top-level symbols of a file, without their doc comments
+pub fn outline_symbols(symbols: Vec<Symbol>) -> Vec<Symbol> {
+    symbols
+        .into_iter()
+        .filter(|symbol| symbol.parent.is_none())
+        .map(|symbol| Symbol {
+            doc_comment: None,
+            ..symbol
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_parser::{Language, LanguageSupport, SymbolExtractor};
+
+    #[test]
+    fn test_outline_sizes() {
+        assert!(!is_outline_size(MAX_FILE_SIZE));
+        assert!(is_outline_size(MAX_FILE_SIZE + 1));
+        assert!(is_outline_size(MAX_OUTLINE_FILE_SIZE));
+        assert!(!is_outline_size(MAX_OUTLINE_FILE_SIZE + 1));
+    }
+
+    #[test]
+    fn test_outline_keeps_top_level_symbols() {
+        let source = "/// A point\npub struct Point { x: i32 }\n\nimpl Point {\n    fn norm(&self) -> i32 { self.x }\n}\n\npub fn origin() -> Point { Point { x: 0 } }\n";
+        let tree = LanguageSupport::new()
+            .unwrap()
+            .parse(Language::Rust, source)
+            .unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, Language::Rust).unwrap();
+
+        let outline = outline_symbols(symbols);
+        let names: Vec<&str> = outline.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Point", "Point", "origin"]);
+        assert!(outline.iter().all(|s| s.doc_comment.is_none()));
+        assert!(
+            outline
+                .iter()
+                .find(|s| s.name == "origin")
+                .and_then(|s| s.signature.as_deref())
+                .is_some_and(|sig| sig.starts_with("pub fn origin()"))
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Paths of indexed files.
+//!
+//! Files are indexed under their path relative to the project root with `/`
+//! separators on every platform, so that an index is portable between
+//! operating systems and stored paths can be matched against import paths
+//! and glob patterns, which use `/` as well. Paths coming from the filesystem
+//! go through [`relative_path`]; paths given by users or tools go through
+//! [`normalize_path`] before being looked up.
+
+use crate::external::EXTERNAL_PREFIX;
+use std::path::{Component, Path, PathBuf};
+
+/// Path of `path` relative to `root`, as stored in the index.
+///
+/// Returns `None` when `path` is not under `root`. Both are also compared in
+/// their canonical form, since watchers may report paths through a different
+/// spelling of the root (a `\\?\` verbatim prefix on Windows, a resolved
+/// symlink such as `/private/var` on macOS).
+pub fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    if let Ok(rel) = path.strip_prefix(root) {
+        return Some(to_index_path(rel));
+    }
+
+    let canonical_root = root.canonicalize().ok()?;
+    if let Ok(rel) = path.strip_prefix(&canonical_root) {
+        return Some(to_index_path(rel));
+    }
+    // A deleted file cannot be canonicalized, but its parent may still exist
+    let canonical_path = match path.canonicalize() {
+        Ok(p) => p,
+        Err(_) => path.parent()?.canonicalize().ok()?.join(path.file_name()?),
+    };
+    canonical_path
+        .strip_prefix(&canonical_root)
+        .ok()
+        .map(to_index_path)
+}
+
+/// Join the components of a relative path with `/`.
+///
+/// Root and prefix components are dropped, so this is only meaningful for
+/// relative paths.
+pub fn to_index_path(path: &Path) -> String {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            Component::ParentDir => parts.push("..".into()),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    parts.join("/")
+}
+
+/// Normalize a relative path given as text, e.g. a tool argument, to its
+/// indexed form: `\` separators become `/` on Windows, and `.` segments,
+/// repeated and trailing separators are removed. External paths keep their
+/// `external://` prefix.
+pub fn normalize_path(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix(EXTERNAL_PREFIX) {
+        return format!("{}{}", EXTERNAL_PREFIX, normalize_path(rest));
+    }
+    let path = if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    };
+    path.split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Location on disk of an indexed file.
+pub fn resolve_path(root: &Path, path: &str) -> PathBuf {
+    let mut full_path = root.to_path_buf();
+    full_path.extend(
+        normalize_path(path)
+            .split('/')
+            .filter(|part| !part.is_empty()),
+    );
+    full_path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("/project");
+        assert_eq!(
+            relative_path(root, Path::new("/project/src/lib.rs")).as_deref(),
+            Some("src/lib.rs")
+        );
+        assert_eq!(relative_path(root, Path::new("/elsewhere/lib.rs")), None);
+    }
+
+    #[test]
+    fn test_relative_path_through_canonical_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let root = dir.path().join("src").join("..");
+
+        let canonical = dir.path().canonicalize().unwrap();
+        std::fs::write(canonical.join("src/lib.rs"), "").unwrap();
+        assert_eq!(
+            relative_path(&root, &canonical.join("src/lib.rs")).as_deref(),
+            Some("src/lib.rs")
+        );
+        // Deleted files too
+        assert_eq!(
+            relative_path(&root, &canonical.join("src/gone.rs")).as_deref(),
+            Some("src/gone.rs")
+        );
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("src/lib.rs"), "src/lib.rs");
+        assert_eq!(normalize_path("./src//engine/"), "src/engine");
+        assert_eq!(normalize_path("."), "");
+        assert_eq!(
+            normalize_path("external://cargo/serde@1.0.0/src/lib.rs"),
+            "external://cargo/serde@1.0.0/src/lib.rs"
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(
+            resolve_path(Path::new("/project"), "src/engine/mod.rs"),
+            Path::new("/project")
+                .join("src")
+                .join("engine")
+                .join("mod.rs")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_paths_use_forward_slashes() {
+        let root = Path::new(r"C:\project");
+        assert_eq!(
+            relative_path(root, Path::new(r"C:\project\src\engine\mod.rs")).as_deref(),
+            Some("src/engine/mod.rs")
+        );
+        assert_eq!(
+            to_index_path(Path::new(r"src\engine\mod.rs")),
+            "src/engine/mod.rs"
+        );
+        assert_eq!(normalize_path(r".\src\engine\mod.rs"), "src/engine/mod.rs");
+        assert_eq!(
+            resolve_path(root, "src/engine/mod.rs"),
+            PathBuf::from(r"C:\project\src\engine\mod.rs")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_verbatim_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let verbatim = dir.path().canonicalize().unwrap();
+        assert!(verbatim.to_string_lossy().starts_with(r"\\?\"));
+        assert_eq!(
+            relative_path(dir.path(), &verbatim.join("src").join("lib.rs")).as_deref(),
+            Some("src/lib.rs")
+        );
+    }
+}
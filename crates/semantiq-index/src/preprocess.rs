@@ -0,0 +1,301 @@
+//! Preprocessing of chunk text before it is embedded
+//!
+//! Boilerplate such as license headers and generated-code banners says
+//! nothing about what a chunk does, yet it takes part of the model's token
+//! budget and pulls unrelated files together. `embeddings.preprocess` lists
+//! the steps applied, in order, to the text of each chunk before embedding.
+//! The stored chunk content is left as it is.
+//!
+//! The index records the [`EmbeddingPreprocessor::version`] its chunks were
+//! embedded with. Changing the pipeline re-embeds the chunks in the
+//! background like a model change (see [`crate::migration`]), without
+//! parsing the files again.
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the preprocessing steps' behavior, part of
+/// [`EmbeddingPreprocessor::version`]. Bump it when a step changes what it
+/// produces, so that indexes embedded with the old behavior are re-embedded.
+const PREPROCESS_VERSION: u32 = 1;
+
+/// Words of a comment marking a license or copyright header
+const LICENSE_MARKERS: &[&str] = &[
+    "license",
+    "licence",
+    "copyright",
+    "spdx-license-identifier",
+    "all rights reserved",
+];
+
+/// Words of a comment marking generated code
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "code generated",
+    "do not edit",
+    "auto-generated",
+    "autogenerated",
+    "generated by",
+];
+
+/// A step of the embedding preprocessing pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessStep {
+    /// Drop a leading comment block that mentions a license or copyright
+    StripLicenseHeaders,
+    /// Drop comment lines marking generated code, e.g. `// Code generated
+    /// by protoc. DO NOT EDIT.` or `# @generated`
+    StripGeneratedBanners,
+    /// Trim lines, collapse runs of spaces and drop blank lines
+    CollapseWhitespace,
+    /// Lowercase identifiers, splitting camel case words with `_` so that
+    /// `parseConfig` and `parse_config` read the same
+    LowercaseIdentifiers,
+}
+
+impl PreprocessStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreprocessStep::StripLicenseHeaders => "strip_license_headers",
+            PreprocessStep::StripGeneratedBanners => "strip_generated_banners",
+            PreprocessStep::CollapseWhitespace => "collapse_whitespace",
+            PreprocessStep::LowercaseIdentifiers => "lowercase_identifiers",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "strip_license_headers" => Some(PreprocessStep::StripLicenseHeaders),
+            "strip_generated_banners" => Some(PreprocessStep::StripGeneratedBanners),
+            "collapse_whitespace" => Some(PreprocessStep::CollapseWhitespace),
+            "lowercase_identifiers" => Some(PreprocessStep::LowercaseIdentifiers),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        match self {
+            PreprocessStep::StripLicenseHeaders => strip_license_header(text),
+            PreprocessStep::StripGeneratedBanners => strip_generated_banners(text),
+            PreprocessStep::CollapseWhitespace => collapse_whitespace(text),
+            PreprocessStep::LowercaseIdentifiers => lowercase_identifiers(text),
+        }
+    }
+}
+
+/// Ordered preprocessing steps applied to chunk text before embedding
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddingPreprocessor {
+    steps: Vec<PreprocessStep>,
+}
+
+impl EmbeddingPreprocessor {
+    pub fn new(steps: &[PreprocessStep]) -> Self {
+        Self {
+            steps: steps.to_vec(),
+        }
+    }
+
+    pub fn steps(&self) -> &[PreprocessStep] {
+        &self.steps
+    }
+
+    /// Identifier of the pipeline recorded with the embeddings, e.g.
+    /// `v1:strip_license_headers,collapse_whitespace`. Empty for no steps.
+    pub fn version(&self) -> String {
+        if self.steps.is_empty() {
+            return String::new();
+        }
+        let steps: Vec<&str> = self.steps.iter().map(PreprocessStep::as_str).collect();
+        format!("v{}:{}", PREPROCESS_VERSION, steps.join(","))
+    }
+
+    /// Text to embed for `text`. Text that the steps would empty is
+    /// embedded as it is.
+    pub fn apply(&self, text: &str) -> String {
+        let processed = self
+            .steps
+            .iter()
+            .fold(text.to_string(), |text, step| step.apply(&text));
+        if processed.trim().is_empty() {
+            text.to_string()
+        } else {
+            processed
+        }
+    }
+}
+
+/// The text of a single-line comment, `None` for other lines and for doc
+/// comments, which document the code that follows
+fn line_comment(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with("///") || line.starts_with("//!") {
+        return None;
+    }
+    ["//", "--", ";;"]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .or_else(|| {
+            // `#` comments, but not `#include`, `#[derive]` or `#!` lines
+            let rest = line.strip_prefix('#')?;
+            (rest.is_empty() || rest.starts_with([' ', '#'])).then_some(rest)
+        })
+}
+
+fn mentions_any(text: &str, markers: &[&str]) -> bool {
+    let lower = text.to_lowercase();
+    markers.iter().any(|marker| lower.contains(marker))
+}
+
+/// Drop the first comment block of `text`, after blank lines, when it
+/// mentions a license or copyright
+fn strip_license_header(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let Some(first) = lines.get(start).map(|line| line.trim()) else {
+        return text.to_string();
+    };
+
+    let block = [("/*", "*/"), ("<!--", "-->"), ("\"\"\"", "\"\"\"")]
+        .into_iter()
+        .find(|(open, _)| first.starts_with(open));
+    let end = if let Some((open, close)) = block {
+        // Block comment, up to the line closing it
+        if first[open.len()..].contains(close) {
+            start + 1
+        } else {
+            match lines[start + 1..].iter().position(|l| l.contains(close)) {
+                Some(offset) => start + offset + 2,
+                None => return text.to_string(),
+            }
+        }
+    } else if line_comment(first).is_some() {
+        start
+            + lines[start..]
+                .iter()
+                .take_while(|line| line_comment(line).is_some())
+                .count()
+    } else {
+        return text.to_string();
+    };
+
+    if !mentions_any(&lines[start..end].join("\n"), LICENSE_MARKERS) {
+        return text.to_string();
+    }
+    lines[end..]
+        .iter()
+        .skip_while(|line| line.trim().is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drop the comment lines that mark generated code
+fn strip_generated_banners(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line_comment(line).is_some_and(|c| mentions_any(c, GENERATED_MARKERS)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Lowercase `text`, inserting `_` at the camel case boundaries of words:
+/// `HTTPServer.parseConfig` becomes `http_server.parse_config`
+fn lowercase_identifiers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len() + text.len() / 8);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                output.push('_');
+            }
+        }
+        output.extend(c.to_lowercase());
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_license_headers() {
+        let rust = "// Copyright 2024 Acme\n// Licensed under MIT\n\nfn main() {}\n";
+        assert_eq!(strip_license_header(rust), "fn main() {}");
+
+        let block = "/*\n * SPDX-License-Identifier: Apache-2.0\n */\npackage main";
+        assert_eq!(strip_license_header(block), "package main");
+
+        let python = "# Copyright (c) Acme. All rights reserved.\nimport os";
+        assert_eq!(strip_license_header(python), "import os");
+
+        // Other comments and later license mentions are kept
+        let doc = "/// Parse the license file\nfn parse() {}";
+        assert_eq!(strip_license_header(doc), doc);
+        let attribute = "#[derive(Debug)]\n// license\nstruct A;";
+        assert_eq!(strip_license_header(attribute), attribute);
+    }
+
+    #[test]
+    fn test_strip_generated_banners() {
+        let go = "// Code generated by protoc-gen-go. DO NOT EDIT.\n\npackage pb\n// Handles requests\nfunc A() {}";
+        assert_eq!(
+            strip_generated_banners(go),
+            "\npackage pb\n// Handles requests\nfunc A() {}"
+        );
+        // Code mentioning the markers is kept
+        let code = "let banner = \"do not edit\";";
+        assert_eq!(strip_generated_banners(code), code);
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        assert_eq!(
+            collapse_whitespace("fn a() {\n\n\n    let  x =\t1;   \n}\n"),
+            "fn a() {\nlet x = 1;\n}"
+        );
+    }
+
+    #[test]
+    fn test_lowercase_identifiers() {
+        assert_eq!(
+            lowercase_identifiers("HTTPServer.parseConfig(MAX_SIZE, utf8Len)"),
+            "http_server.parse_config(max_size, utf8_len)"
+        );
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let preprocessor = EmbeddingPreprocessor::new(&[
+            PreprocessStep::StripLicenseHeaders,
+            PreprocessStep::CollapseWhitespace,
+        ]);
+        assert_eq!(
+            preprocessor.version(),
+            "v1:strip_license_headers,collapse_whitespace"
+        );
+        assert_eq!(
+            preprocessor.apply("// MIT License\n\nfn  main() {}\n"),
+            "fn main() {}"
+        );
+        // A chunk made only of the header is embedded as it is
+        assert_eq!(preprocessor.apply("// MIT License"), "// MIT License");
+
+        let none = EmbeddingPreprocessor::default();
+        assert_eq!(none.version(), "");
+        assert_eq!(none.apply("fn  main() {}"), "fn  main() {}");
+    }
+}
@@ -0,0 +1,313 @@
+//! Order of the files of the initial indexing pass
+//!
+//! On a large project the initial pass takes minutes, and searches only
+//! cover what it has indexed so far. Files are indexed by priority so that
+//! the code being worked on is searchable first: files changed recently
+//! come first, source directories before examples, docs and fixtures, and
+//! files the watcher reports as edited during the pass jump the queue.
+//!
+//! `quota.path_weights` adds to the priority of the files under a path, and
+//! with `quota.max_files` the files of lowest priority are not indexed.
+
+use crate::config::QuotaConfig;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Score of a file by how long ago it was modified
+const RECENCY_SCORES: &[(Duration, i32)] = &[
+    (HOUR, 40),
+    (DAY, 30),
+    (Duration::from_secs(7 * 24 * 60 * 60), 20),
+    (Duration::from_secs(30 * 24 * 60 * 60), 10),
+];
+
+/// Directories of the code a project is made of
+const SOURCE_DIRS: &[&str] = &[
+    "src", "lib", "app", "apps", "cmd", "pkg", "internal", "crates", "packages",
+];
+
+/// Directories rarely edited, or not part of the project's own code
+const PERIPHERAL_DIRS: &[&str] = &[
+    "examples",
+    "example",
+    "samples",
+    "sample",
+    "demo",
+    "demos",
+    "docs",
+    "doc",
+    "benches",
+    "benchmarks",
+    "fixtures",
+    "testdata",
+    "third_party",
+    "third-party",
+    "vendored",
+    "generated",
+    "migrations",
+];
+
+/// Directories of tests
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec", "specs"];
+
+/// Priority of a file in the initial pass, higher first: recently modified
+/// files score up to 40, then source directories add 5, test directories
+/// subtract 5 and peripheral directories subtract 20.
+pub fn file_priority(rel_path: &str, modified: Option<SystemTime>, now: SystemTime) -> i32 {
+    let recency = modified
+        .and_then(|modified| now.duration_since(modified).ok())
+        .and_then(|age| {
+            RECENCY_SCORES
+                .iter()
+                .find(|(max_age, _)| age <= *max_age)
+                .map(|(_, score)| *score)
+        })
+        .unwrap_or(0);
+
+    let dirs: Vec<String> = rel_path
+        .split('/')
+        .rev()
+        .skip(1)
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let in_any = |names: &[&str]| dirs.iter().any(|dir| names.contains(&dir.as_str()));
+    let location = if in_any(PERIPHERAL_DIRS) {
+        -20
+    } else if in_any(TEST_DIRS) {
+        -5
+    } else if in_any(SOURCE_DIRS) {
+        5
+    } else {
+        0
+    };
+
+    recency + location
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct QueuedFile {
+    priority: i32,
+    modified: Option<SystemTime>,
+    path: PathBuf,
+}
+
+impl Ord for QueuedFile {
+    /// Highest priority first, then most recently modified, then by path
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| self.modified.cmp(&other.modified))
+            .then_with(|| other.path.cmp(&self.path))
+    }
+}
+
+impl PartialOrd for QueuedFile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Files left to index in the initial pass, in priority order
+#[derive(Debug, Default)]
+pub struct IndexQueue {
+    queued: BinaryHeap<QueuedFile>,
+    /// Files reported as edited during the pass, indexed next
+    promoted: VecDeque<PathBuf>,
+    /// Files not indexed yet
+    pending: HashSet<PathBuf>,
+}
+
+impl IndexQueue {
+    /// Queue the candidate files of a project, reading their modification
+    /// time, weighted by the `path_weights` of `quota`
+    pub fn new(project_root: &Path, candidates: Vec<PathBuf>, quota: &QuotaConfig) -> Self {
+        let now = SystemTime::now();
+        let mut queue = Self::default();
+        for path in candidates {
+            let modified = path.metadata().and_then(|m| m.modified()).ok();
+            let rel_path = path
+                .strip_prefix(project_root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let priority = file_priority(&rel_path, modified, now) + quota.path_weight(&rel_path);
+            queue.push(path, priority, modified);
+        }
+        queue
+    }
+
+    fn push(&mut self, path: PathBuf, priority: i32, modified: Option<SystemTime>) {
+        if self.pending.insert(path.clone()) {
+            self.queued.push(QueuedFile {
+                priority,
+                modified,
+                path,
+            });
+        }
+    }
+
+    /// Index `path` next if it is still queued. Returns `false` otherwise.
+    pub fn promote(&mut self, path: &Path) -> bool {
+        if !self.pending.contains(path) || self.promoted.iter().any(|p| p == path) {
+            return false;
+        }
+        self.promoted.push_back(path.to_path_buf());
+        true
+    }
+
+    /// Keep the `max_files` queued files of highest priority, returning the
+    /// others, lowest priority first
+    pub fn limit(&mut self, max_files: usize) -> Vec<PathBuf> {
+        if self.pending.len() <= max_files {
+            return Vec::new();
+        }
+        let mut dropped = std::mem::take(&mut self.queued).into_sorted_vec();
+        dropped.retain(|file| self.pending.contains(&file.path));
+        let kept = dropped.split_off(dropped.len() - max_files);
+        self.queued = kept.into();
+        self.promoted
+            .retain(|path| !dropped.iter().any(|file| &file.path == path));
+        dropped
+            .into_iter()
+            .map(|file| {
+                self.pending.remove(&file.path);
+                file.path
+            })
+            .collect()
+    }
+
+    /// Take the next file to index
+    pub fn pop(&mut self) -> Option<PathBuf> {
+        let next = self.promoted.pop_front().or_else(|| {
+            loop {
+                let file = self.queued.pop()?;
+                if self.pending.contains(&file.path) {
+                    break Some(file.path);
+                }
+            }
+        })?;
+        self.pending.remove(&next);
+        Some(next)
+    }
+
+    /// Number of files left to index
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_priority() {
+        let now = SystemTime::now();
+        let ago = |secs| Some(now - Duration::from_secs(secs));
+
+        assert_eq!(file_priority("src/main.rs", ago(60), now), 45);
+        assert_eq!(file_priority("src/main.rs", ago(2 * 86400), now), 25);
+        assert_eq!(file_priority("src/main.rs", ago(365 * 86400), now), 5);
+        assert_eq!(file_priority("build.rs", None, now), 0);
+        assert_eq!(file_priority("crates/app/tests/api.rs", None, now), -5);
+        assert_eq!(file_priority("examples/src/demo.rs", ago(60), now), 20);
+        assert_eq!(file_priority("Docs/guide.md", None, now), -20);
+        // File names are not directories
+        assert_eq!(file_priority("lib/examples.rs", None, now), 5);
+    }
+
+    #[test]
+    fn test_queue_order_and_promotion() {
+        let now = SystemTime::now();
+        let mut queue = IndexQueue::default();
+        let old = Some(now - Duration::from_secs(365 * 86400));
+        queue.push(PathBuf::from("examples/a.rs"), -20, old);
+        queue.push(PathBuf::from("src/old.rs"), 5, old);
+        queue.push(PathBuf::from("src/new.rs"), 45, Some(now));
+        queue.push(PathBuf::from("src/b.rs"), 5, old);
+        queue.push(PathBuf::from("src/new.rs"), 45, Some(now));
+        assert_eq!(queue.len(), 4);
+
+        assert_eq!(queue.pop(), Some(PathBuf::from("src/new.rs")));
+        assert!(queue.promote(Path::new("examples/a.rs")));
+        assert!(!queue.promote(Path::new("examples/a.rs")));
+        assert!(!queue.promote(Path::new("src/new.rs")));
+        assert!(!queue.promote(Path::new("README.md")));
+
+        assert_eq!(queue.pop(), Some(PathBuf::from("examples/a.rs")));
+        assert_eq!(queue.pop(), Some(PathBuf::from("src/b.rs")));
+        assert_eq!(queue.pop(), Some(PathBuf::from("src/old.rs")));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_queue_limit_drops_lowest_priority() {
+        let mut queue = IndexQueue::default();
+        queue.push(PathBuf::from("examples/a.rs"), -20, None);
+        queue.push(PathBuf::from("src/a.rs"), 5, None);
+        queue.push(PathBuf::from("src/b.rs"), 45, None);
+        queue.push(PathBuf::from("tests/a.rs"), -5, None);
+        assert!(queue.promote(Path::new("examples/a.rs")));
+
+        assert_eq!(
+            queue.limit(2),
+            vec![PathBuf::from("examples/a.rs"), PathBuf::from("tests/a.rs")]
+        );
+        assert!(queue.limit(2).is_empty());
+        assert_eq!(queue.pop(), Some(PathBuf::from("src/b.rs")));
+        assert_eq!(queue.pop(), Some(PathBuf::from("src/a.rs")));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_queue_reads_modification_times() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("examples")).unwrap();
+        std::fs::write(root.join("examples/demo.rs"), "").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("notes.rs"), "").unwrap();
+
+        let mut queue = IndexQueue::new(
+            root,
+            vec![
+                root.join("examples/demo.rs"),
+                root.join("notes.rs"),
+                root.join("src/lib.rs"),
+            ],
+            &QuotaConfig::default(),
+        );
+        let order: Vec<PathBuf> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(
+            order,
+            vec![
+                root.join("src/lib.rs"),
+                root.join("notes.rs"),
+                root.join("examples/demo.rs")
+            ]
+        );
+
+        // A path weight outranks the location of a file
+        let quota = QuotaConfig {
+            path_weights: [("examples".to_string(), 50)].into(),
+            ..QuotaConfig::default()
+        };
+        let mut queue = IndexQueue::new(
+            root,
+            vec![root.join("examples/demo.rs"), root.join("src/lib.rs")],
+            &quota,
+        );
+        assert_eq!(queue.pop(), Some(root.join("examples/demo.rs")));
+    }
+}
@@ -0,0 +1,295 @@
+//! Progress reporting for long-running indexing passes
+//!
+//! A [`ProgressTracker`] is updated by the indexer as it walks the project and
+//! publishes [`IndexProgress`] snapshots through a `tokio::sync::watch` channel,
+//! so any number of consumers (CLI progress bar, MCP notifications, HTTP API)
+//! can observe the latest state without slowing indexing down.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::watch;
+
+/// Current stage of an indexing pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexPhase {
+    /// No indexing pass has started yet
+    #[default]
+    Idle,
+    /// Walking the project to discover candidate files
+    Scanning,
+    /// Parsing, chunking and embedding candidate files
+    Indexing,
+    /// The pass has finished
+    Complete,
+}
+
+impl IndexPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IndexPhase::Idle => "idle",
+            IndexPhase::Scanning => "scanning",
+            IndexPhase::Indexing => "indexing",
+            IndexPhase::Complete => "complete",
+        }
+    }
+}
+
+/// Point-in-time snapshot of an indexing pass
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub phase: IndexPhase,
+    /// Files seen while walking the project (including unsupported ones)
+    pub scanned: usize,
+    /// Supported files that will be checked during this pass
+    pub total: usize,
+    /// Files checked so far (indexed + skipped + errors)
+    pub processed: usize,
+    pub indexed: usize,
+    pub skipped: usize,
+    pub errors: usize,
+    /// Indexed files whose encoding was guessed or that had invalid bytes
+    /// replaced (see [`crate::encoding`])
+    pub warnings: usize,
+    /// Relative path of the file currently being processed
+    pub current_file: Option<String>,
+    pub elapsed_ms: u64,
+    /// Estimated time until completion, once at least one file has been processed
+    pub eta_ms: Option<u64>,
+}
+
+impl IndexProgress {
+    /// Number of candidate files not processed yet
+    pub fn remaining(&self) -> usize {
+        self.total.saturating_sub(self.processed)
+    }
+
+    /// Completion percentage in the range 0.0..=100.0
+    pub fn percent(&self) -> f32 {
+        match self.phase {
+            IndexPhase::Complete => 100.0,
+            _ if self.total == 0 => 0.0,
+            _ => (self.processed as f32 / self.total as f32 * 100.0).min(100.0),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.phase == IndexPhase::Complete
+    }
+}
+
+/// Outcome of processing a single file, used to update the counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOutcome {
+    Indexed,
+    Skipped,
+    Error,
+}
+
+/// Shared, cloneable handle used to publish indexing progress
+#[derive(Clone)]
+pub struct ProgressTracker {
+    sender: Arc<watch::Sender<IndexProgress>>,
+    started_at: Arc<Mutex<Instant>>,
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(IndexProgress::default());
+        Self {
+            sender: Arc::new(sender),
+            started_at: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Subscribe to progress updates. The receiver always holds the latest snapshot.
+    pub fn subscribe(&self) -> watch::Receiver<IndexProgress> {
+        self.sender.subscribe()
+    }
+
+    /// Get the latest progress snapshot
+    pub fn snapshot(&self) -> IndexProgress {
+        self.sender.borrow().clone()
+    }
+
+    /// Reset counters and enter the scanning phase
+    pub fn begin_scan(&self) {
+        if let Ok(mut started_at) = self.started_at.lock() {
+            *started_at = Instant::now();
+        }
+        self.sender.send_replace(IndexProgress {
+            phase: IndexPhase::Scanning,
+            ..Default::default()
+        });
+    }
+
+    /// Record a file seen during the scan
+    pub fn file_scanned(&self) {
+        self.sender.send_modify(|p| p.scanned += 1);
+    }
+
+    /// Enter the indexing phase with the number of candidate files
+    pub fn begin_indexing(&self, total: usize) {
+        let elapsed_ms = self.elapsed_ms();
+        self.sender.send_modify(|p| {
+            p.phase = IndexPhase::Indexing;
+            p.total = total;
+            p.elapsed_ms = elapsed_ms;
+        });
+    }
+
+    /// Mark a file as the one currently being processed
+    pub fn start_file(&self, rel_path: &str) {
+        self.sender
+            .send_modify(|p| p.current_file = Some(rel_path.to_string()));
+    }
+
+    /// Record the outcome of the current file and refresh the ETA
+    pub fn finish_file(&self, outcome: FileOutcome) {
+        let elapsed_ms = self.elapsed_ms();
+        self.sender.send_modify(|p| {
+            match outcome {
+                FileOutcome::Indexed => p.indexed += 1,
+                FileOutcome::Skipped => p.skipped += 1,
+                FileOutcome::Error => p.errors += 1,
+            }
+            p.processed += 1;
+            p.elapsed_ms = elapsed_ms;
+            p.eta_ms = estimate_eta_ms(elapsed_ms, p.processed, p.remaining());
+        });
+    }
+
+    /// Record a warning about the current file
+    pub fn file_warning(&self) {
+        self.sender.send_modify(|p| p.warnings += 1);
+    }
+
+    /// Mark the pass as complete
+    pub fn complete(&self) {
+        let elapsed_ms = self.elapsed_ms();
+        self.sender.send_modify(|p| {
+            p.phase = IndexPhase::Complete;
+            p.current_file = None;
+            p.elapsed_ms = elapsed_ms;
+            p.eta_ms = Some(0);
+        });
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started_at
+            .lock()
+            .map(|s| s.elapsed().as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Extrapolate the remaining time from the average time per processed file
+fn estimate_eta_ms(elapsed_ms: u64, processed: usize, remaining: usize) -> Option<u64> {
+    if processed == 0 {
+        return None;
+    }
+    Some(elapsed_ms / processed as u64 * remaining as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_counts_outcomes() {
+        let tracker = ProgressTracker::new();
+        tracker.begin_scan();
+        tracker.file_scanned();
+        tracker.file_scanned();
+        tracker.file_scanned();
+        tracker.begin_indexing(3);
+
+        tracker.start_file("src/main.rs");
+        assert_eq!(
+            tracker.snapshot().current_file.as_deref(),
+            Some("src/main.rs")
+        );
+        tracker.file_warning();
+        tracker.finish_file(FileOutcome::Indexed);
+        tracker.start_file("src/lib.rs");
+        tracker.finish_file(FileOutcome::Skipped);
+
+        let progress = tracker.snapshot();
+        assert_eq!(progress.phase, IndexPhase::Indexing);
+        assert_eq!(progress.scanned, 3);
+        assert_eq!(progress.total, 3);
+        assert_eq!(progress.processed, 2);
+        assert_eq!(progress.indexed, 1);
+        assert_eq!(progress.skipped, 1);
+        assert_eq!(progress.warnings, 1);
+        assert_eq!(progress.remaining(), 1);
+        assert!(progress.eta_ms.is_some());
+
+        tracker.start_file("src/bad.rs");
+        tracker.finish_file(FileOutcome::Error);
+        tracker.complete();
+
+        let progress = tracker.snapshot();
+        assert!(progress.is_complete());
+        assert_eq!(progress.errors, 1);
+        assert_eq!(progress.remaining(), 0);
+        assert_eq!(progress.current_file, None);
+        assert_eq!(progress.percent(), 100.0);
+    }
+
+    #[test]
+    fn test_subscriber_sees_latest_snapshot() {
+        let tracker = ProgressTracker::new();
+        let mut rx = tracker.subscribe();
+        assert_eq!(rx.borrow().phase, IndexPhase::Idle);
+
+        tracker.begin_scan();
+        tracker.begin_indexing(10);
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(rx.borrow_and_update().total, 10);
+    }
+
+    #[test]
+    fn test_begin_scan_resets_counters() {
+        let tracker = ProgressTracker::new();
+        tracker.begin_scan();
+        tracker.begin_indexing(1);
+        tracker.finish_file(FileOutcome::Indexed);
+        tracker.complete();
+
+        tracker.begin_scan();
+        let progress = tracker.snapshot();
+        assert_eq!(progress.phase, IndexPhase::Scanning);
+        assert_eq!(progress.processed, 0);
+        assert_eq!(progress.indexed, 0);
+    }
+
+    #[test]
+    fn test_percent_and_eta() {
+        let progress = IndexProgress {
+            phase: IndexPhase::Indexing,
+            total: 4,
+            processed: 1,
+            ..Default::default()
+        };
+        assert_eq!(progress.percent(), 25.0);
+        assert_eq!(IndexProgress::default().percent(), 0.0);
+
+        assert_eq!(estimate_eta_ms(1000, 0, 10), None);
+        assert_eq!(estimate_eta_ms(1000, 2, 4), Some(2000));
+    }
+
+    #[test]
+    fn test_phase_serialization() {
+        let json = serde_json::to_string(&IndexPhase::Indexing).unwrap();
+        assert_eq!(json, "\"indexing\"");
+        assert_eq!(IndexPhase::Complete.as_str(), "complete");
+    }
+}
@@ -0,0 +1,158 @@
+//! Scalar quantization of chunk embeddings
+//!
+//! Embeddings produced by the model are L2-normalized, so every component lies
+//! in `[-1.0, 1.0]`. Int8 quantization maps each component to `round(x * 127)`
+//! with a fixed scale shared by all vectors: this keeps L2 distances comparable
+//! across vectors (int8 distance ≈ float distance × 127) so calibrated
+//! thresholds stay valid, while storing 1 byte per dimension instead of 4.
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Fixed scale between float components and their int8 representation
+pub const INT8_SCALE: f32 = 127.0;
+
+/// Storage format of chunk embeddings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingFormat {
+    /// 4 bytes per dimension, exact
+    #[default]
+    Float32,
+    /// 1 byte per dimension, scalar-quantized
+    Int8,
+}
+
+impl EmbeddingFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingFormat::Float32 => "float32",
+            EmbeddingFormat::Int8 => "int8",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "float32" | "f32" | "float" => Some(EmbeddingFormat::Float32),
+            "int8" | "i8" => Some(EmbeddingFormat::Int8),
+            _ => None,
+        }
+    }
+
+    /// Element type used for the `chunks_vec` vec0 column
+    pub(crate) fn vec_column_type(&self) -> &'static str {
+        match self {
+            EmbeddingFormat::Float32 => "float",
+            EmbeddingFormat::Int8 => "int8",
+        }
+    }
+
+    /// Serialize an embedding to the byte layout stored for this format
+    pub fn encode(&self, embedding: &[f32]) -> Vec<u8> {
+        match self {
+            EmbeddingFormat::Float32 => embedding.iter().flat_map(|f| f.to_le_bytes()).collect(),
+            EmbeddingFormat::Int8 => quantize_int8(embedding)
+                .into_iter()
+                .map(|v| v as u8)
+                .collect(),
+        }
+    }
+
+    /// Deserialize stored bytes back to a float embedding
+    pub fn decode(&self, bytes: &[u8]) -> Vec<f32> {
+        match self {
+            EmbeddingFormat::Float32 => {
+                if !bytes.len().is_multiple_of(4) {
+                    warn!(
+                        "Invalid embedding bytes length: {} (not divisible by 4)",
+                        bytes.len()
+                    );
+                    return Vec::new();
+                }
+                bytes
+                    .chunks_exact(4)
+                    .map(|chunk| {
+                        let bytes: [u8; 4] =
+                            chunk.try_into().expect("chunks_exact guarantees 4 bytes");
+                        f32::from_le_bytes(bytes)
+                    })
+                    .collect()
+            }
+            EmbeddingFormat::Int8 => {
+                let values: Vec<i8> = bytes.iter().map(|&b| b as i8).collect();
+                dequantize_int8(&values)
+            }
+        }
+    }
+
+    /// Convert a distance computed by vec0 in this format back to float scale
+    pub(crate) fn normalize_distance(&self, distance: f32) -> f32 {
+        match self {
+            EmbeddingFormat::Float32 => distance,
+            EmbeddingFormat::Int8 => distance / INT8_SCALE,
+        }
+    }
+}
+
+/// Quantize a normalized embedding to int8, clamping out-of-range components
+pub fn quantize_int8(embedding: &[f32]) -> Vec<i8> {
+    embedding
+        .iter()
+        .map(|&x| (x * INT8_SCALE).round().clamp(-INT8_SCALE, INT8_SCALE) as i8)
+        .collect()
+}
+
+/// Map int8 components back to approximate float values
+pub fn dequantize_int8(values: &[i8]) -> Vec<f32> {
+    values.iter().map(|&v| v as f32 / INT8_SCALE).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_round_trip() {
+        let embedding = vec![0.0, 0.5, -0.5, 1.0, -1.0, 0.123];
+        let quantized = quantize_int8(&embedding);
+        assert_eq!(&quantized[..5], &[0, 64, -64, 127, -127]);
+
+        let restored = dequantize_int8(&quantized);
+        for (a, b) in embedding.iter().zip(&restored) {
+            assert!((a - b).abs() <= 0.5 / INT8_SCALE + f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_quantize_clamps_out_of_range() {
+        assert_eq!(quantize_int8(&[2.0, -3.0]), vec![127, -127]);
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let embedding = vec![0.25, -0.75, 0.0];
+
+        let bytes = EmbeddingFormat::Float32.encode(&embedding);
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(EmbeddingFormat::Float32.decode(&bytes), embedding);
+
+        let bytes = EmbeddingFormat::Int8.encode(&embedding);
+        assert_eq!(bytes.len(), 3);
+        let decoded = EmbeddingFormat::Int8.decode(&bytes);
+        assert!((decoded[1] + 0.75).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_format_parse_and_serde() {
+        assert_eq!(EmbeddingFormat::parse("INT8"), Some(EmbeddingFormat::Int8));
+        assert_eq!(
+            EmbeddingFormat::parse("float32"),
+            Some(EmbeddingFormat::Float32)
+        );
+        assert_eq!(EmbeddingFormat::parse("int4"), None);
+        assert_eq!(
+            serde_json::to_string(&EmbeddingFormat::Int8).unwrap(),
+            "\"int8\""
+        );
+    }
+}
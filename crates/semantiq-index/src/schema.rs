@@ -1,7 +1,10 @@
-use rusqlite::{Connection, Result as SqliteResult};
+use crate::titles::fill_chunk_titles;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult};
+use semantiq_embeddings::TokenFit;
+use semantiq_parser::{ImportedSymbol, SymbolQualifiers};
 use serde::{Deserialize, Serialize};
 
-pub const SCHEMA_VERSION: i32 = 3;
+pub const SCHEMA_VERSION: i32 = 27;
 
 /// Embedding dimension (MiniLM-L6-v2 produces 384-dimensional vectors)
 pub const EMBEDDING_DIMENSION: usize = 384;
@@ -23,7 +26,17 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
             hash TEXT NOT NULL,
             size INTEGER NOT NULL,
             last_modified INTEGER NOT NULL,
-            indexed_at INTEGER NOT NULL
+            indexed_at INTEGER NOT NULL,
+            -- Encoding the file was decoded from, NULL for UTF-8
+            encoding TEXT,
+            -- Workspace package owning the file, NULL outside of any package
+            package TEXT,
+            -- 1 for generated files (see crate::generated)
+            generated INTEGER NOT NULL DEFAULT 0,
+            -- 1 for oversized files indexed as an outline (see crate::outline)
+            outline INTEGER NOT NULL DEFAULT 0,
+            -- 1 for files whose embeddings were dropped (see `store::quota`)
+            embeddings_evicted INTEGER NOT NULL DEFAULT 0
         );
 
         -- Symbols table
@@ -36,9 +49,18 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
             end_line INTEGER NOT NULL,
             start_byte INTEGER NOT NULL,
             end_byte INTEGER NOT NULL,
+            start_column INTEGER NOT NULL DEFAULT 0,
+            end_column INTEGER NOT NULL DEFAULT 0,
+            cfg_context TEXT,
             signature TEXT,
             doc_comment TEXT,
             parent TEXT,
+            -- Qualifiers (see semantiq_parser::SymbolQualifiers)
+            is_public INTEGER NOT NULL DEFAULT 0,
+            is_async INTEGER NOT NULL DEFAULT 0,
+            is_static INTEGER NOT NULL DEFAULT 0,
+            is_abstract INTEGER NOT NULL DEFAULT 0,
+            generic_params INTEGER NOT NULL DEFAULT 0,
             FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
         );
 
@@ -53,6 +75,18 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
             end_byte INTEGER NOT NULL,
             symbols_json TEXT,
             embedding BLOB,
+            -- Symbol overlapping most of the chunk (see `link_chunk_symbols_impl`)
+            symbol_id INTEGER REFERENCES symbols(id) ON DELETE SET NULL,
+            -- How the chunk fitted into the model's token limit, once embedded
+            token_count INTEGER,
+            embedding_windows INTEGER,
+            embedding_truncated INTEGER NOT NULL DEFAULT 0,
+            -- Hash of the content with collapsed whitespace, to count duplicates
+            content_hash TEXT,
+            -- Weight of semantic scores (see `store::distinctiveness`)
+            distinctiveness REAL NOT NULL DEFAULT 1.0,
+            -- One-line heading of the chunk in results (see `titles`)
+            title TEXT,
             FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
         );
 
@@ -66,6 +100,168 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
             FOREIGN KEY (source_file_id) REFERENCES files(id) ON DELETE CASCADE
         );
 
+        -- Symbols named by each import (`import { A, B as C } from './x'`)
+        CREATE TABLE IF NOT EXISTS dependency_symbols (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            dependency_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            alias TEXT,
+            FOREIGN KEY (dependency_id) REFERENCES dependencies(id) ON DELETE CASCADE
+        );
+
+        -- Implements/extends relations between types, and Go method sets
+        CREATE TABLE IF NOT EXISTS type_relations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            type_name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            target TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- Main functions, HTTP routes, CLI subcommands and library exports,
+        -- flagged by kind
+        CREATE TABLE IF NOT EXISTS entrypoints (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            name TEXT NOT NULL,
+            handler TEXT,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- HTTP routes of the route entry points, method NULL for every method
+        CREATE TABLE IF NOT EXISTS routes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            method TEXT,
+            path TEXT NOT NULL,
+            handler TEXT,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- Environment variables (source 'env') and configuration keys
+        -- (source 'config') read or written by each file
+        CREATE TABLE IF NOT EXISTS config_refs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            source TEXT NOT NULL,
+            access TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- Rust symbols exported to another language by napi-rs, wasm-bindgen
+        -- or PyO3, with the name the other language calls them by
+        CREATE TABLE IF NOT EXISTS bindings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            symbol TEXT NOT NULL,
+            exported_name TEXT NOT NULL,
+            framework TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- Every version of every symbol, valid from the index generation it
+        -- was written at until the one it was changed or removed at (NULL
+        -- while current). Kept by path so that it outlives the file. The
+        -- lines are those the version was written with; later moves are
+        -- kept in symbol_spans.
+        CREATE TABLE IF NOT EXISTS symbol_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            signature TEXT,
+            doc_comment TEXT,
+            parent TEXT,
+            valid_from INTEGER NOT NULL,
+            valid_to INTEGER
+        );
+
+        -- Lines of a symbol version from the index generation it moved to
+        -- them at, until the one it moved again at (NULL while current)
+        CREATE TABLE IF NOT EXISTS symbol_spans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            version_id INTEGER NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            valid_from INTEGER NOT NULL,
+            valid_to INTEGER,
+            FOREIGN KEY (version_id) REFERENCES symbol_history(id) ON DELETE CASCADE
+        );
+
+        -- Number of times each identifier appeared in each file, by path,
+        -- valid over index generations like symbol_history
+        CREATE TABLE IF NOT EXISTS occurrence_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            valid_from INTEGER NOT NULL,
+            valid_to INTEGER
+        );
+
+        -- Number of times each identifier appears in each file, definitions included
+        CREATE TABLE IF NOT EXISTS occurrences (
+            name TEXT NOT NULL,
+            file_id INTEGER NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY (name, file_id),
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        ) WITHOUT ROWID;
+
+        -- Names referenced by each symbol, resolved to symbols when queried
+        -- (see `store::symbol_edges`)
+        CREATE TABLE IF NOT EXISTS symbol_edges (
+            source_symbol_id INTEGER NOT NULL,
+            target_name TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY (source_symbol_id, target_name),
+            FOREIGN KEY (source_symbol_id) REFERENCES symbols(id) ON DELETE CASCADE
+        ) WITHOUT ROWID;
+
+        -- Keywords of each file and how often they appear in it
+        CREATE TABLE IF NOT EXISTS file_tags (
+            file_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY (file_id, tag),
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        ) WITHOUT ROWID;
+
+        -- Byte ranges of the comments and string literals of each file,
+        -- varint-encoded (see `store::text_masks`)
+        CREATE TABLE IF NOT EXISTS text_masks (
+            file_id INTEGER PRIMARY KEY,
+            comments BLOB NOT NULL,
+            strings BLOB NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- When results were last found in each directory, and directories
+        -- whose embeddings were dropped by `quota.max_db_mb` (see `store::quota`)
+        CREATE TABLE IF NOT EXISTS searched_dirs (
+            dir TEXT PRIMARY KEY,
+            last_searched INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS evicted_dirs (
+            dir TEXT PRIMARY KEY,
+            evicted_at INTEGER NOT NULL,
+            chunks INTEGER NOT NULL
+        );
+
         -- Indexes for performance
         CREATE INDEX IF NOT EXISTS idx_files_path ON files(path);
         CREATE INDEX IF NOT EXISTS idx_symbols_name ON symbols(name);
@@ -74,33 +270,26 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
         CREATE INDEX IF NOT EXISTS idx_chunks_file_id ON chunks(file_id);
         CREATE INDEX IF NOT EXISTS idx_deps_source ON dependencies(source_file_id);
         CREATE INDEX IF NOT EXISTS idx_deps_target ON dependencies(target_path);
-
-        -- FTS5 for full-text search on symbols
-        CREATE VIRTUAL TABLE IF NOT EXISTS symbols_fts USING fts5(
-            name,
-            signature,
-            doc_comment,
-            content='symbols',
-            content_rowid='id'
-        );
-
-        -- Triggers to keep FTS in sync
-        CREATE TRIGGER IF NOT EXISTS symbols_ai AFTER INSERT ON symbols BEGIN
-            INSERT INTO symbols_fts(rowid, name, signature, doc_comment)
-            VALUES (new.id, new.name, new.signature, new.doc_comment);
-        END;
-
-        CREATE TRIGGER IF NOT EXISTS symbols_ad AFTER DELETE ON symbols BEGIN
-            INSERT INTO symbols_fts(symbols_fts, rowid, name, signature, doc_comment)
-            VALUES ('delete', old.id, old.name, old.signature, old.doc_comment);
-        END;
-
-        CREATE TRIGGER IF NOT EXISTS symbols_au AFTER UPDATE ON symbols BEGIN
-            INSERT INTO symbols_fts(symbols_fts, rowid, name, signature, doc_comment)
-            VALUES ('delete', old.id, old.name, old.signature, old.doc_comment);
-            INSERT INTO symbols_fts(rowid, name, signature, doc_comment)
-            VALUES (new.id, new.name, new.signature, new.doc_comment);
-        END;
+        CREATE INDEX IF NOT EXISTS idx_dep_symbols_dependency ON dependency_symbols(dependency_id);
+        CREATE INDEX IF NOT EXISTS idx_type_relations_target ON type_relations(target, kind);
+        CREATE INDEX IF NOT EXISTS idx_type_relations_type ON type_relations(type_name, kind);
+        CREATE INDEX IF NOT EXISTS idx_type_relations_file_id ON type_relations(file_id);
+        CREATE INDEX IF NOT EXISTS idx_entrypoints_kind ON entrypoints(kind);
+        CREATE INDEX IF NOT EXISTS idx_entrypoints_file_id ON entrypoints(file_id);
+        CREATE INDEX IF NOT EXISTS idx_routes_file_id ON routes(file_id);
+        CREATE INDEX IF NOT EXISTS idx_config_refs_name ON config_refs(name);
+        CREATE INDEX IF NOT EXISTS idx_config_refs_file_id ON config_refs(file_id);
+        CREATE INDEX IF NOT EXISTS idx_bindings_symbol ON bindings(symbol);
+        CREATE INDEX IF NOT EXISTS idx_bindings_exported_name ON bindings(exported_name);
+        CREATE INDEX IF NOT EXISTS idx_bindings_file_id ON bindings(file_id);
+        CREATE INDEX IF NOT EXISTS idx_symbol_history_name ON symbol_history(name, valid_from);
+        CREATE INDEX IF NOT EXISTS idx_symbol_history_path ON symbol_history(path, valid_to);
+        CREATE INDEX IF NOT EXISTS idx_symbol_spans_version ON symbol_spans(version_id, valid_from);
+        CREATE INDEX IF NOT EXISTS idx_occurrence_history_name ON occurrence_history(name, valid_from);
+        CREATE INDEX IF NOT EXISTS idx_occurrence_history_path ON occurrence_history(path, valid_to);
+        CREATE INDEX IF NOT EXISTS idx_occurrences_file_id ON occurrences(file_id);
+        CREATE INDEX IF NOT EXISTS idx_symbol_edges_target ON symbol_edges(target_name);
+        CREATE INDEX IF NOT EXISTS idx_file_tags_tag ON file_tags(tag);
 
         -- Distance observations for threshold calibration
         -- Records distances observed during semantic search for ML-based calibration
@@ -131,9 +320,142 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
             std_distance REAL,
             calibrated_at INTEGER NOT NULL
         );
+
+        -- Files whose indexing transaction has started but not committed
+        CREATE TABLE IF NOT EXISTS index_journal (
+            path TEXT PRIMARY KEY,
+            started_at INTEGER NOT NULL
+        );
+
+        -- Problems met by the last indexing of each file (see `crate::issues`),
+        -- kept by path since files that could not be read are not indexed
+        CREATE TABLE IF NOT EXISTS index_issues (
+            path TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            message TEXT NOT NULL,
+            recorded_at INTEGER NOT NULL,
+            PRIMARY KEY (path, kind)
+        ) WITHOUT ROWID;
+
+        -- Tool calls per day, tool and query category ('' for tools without one),
+        -- recorded for `semantiq stats --usage` unless `usage.enabled` is off
+        CREATE TABLE IF NOT EXISTS tool_usage (
+            day INTEGER NOT NULL,
+            tool TEXT NOT NULL,
+            category TEXT NOT NULL,
+            calls INTEGER NOT NULL,
+            errors INTEGER NOT NULL,
+            zero_results INTEGER NOT NULL,
+            total_ms INTEGER NOT NULL,
+            PRIMARY KEY (day, tool, category)
+        );
+
+        -- Persisted query results, valid for the index generation they were computed at
+        CREATE TABLE IF NOT EXISTS query_cache (
+            key TEXT PRIMARY KEY,
+            generation INTEGER NOT NULL,
+            payload TEXT NOT NULL,
+            stored_at INTEGER NOT NULL
+        );
+
+        -- Embeddings of recent queries per embedding model, independent of
+        -- the index generation
+        CREATE TABLE IF NOT EXISTS query_embeddings (
+            model TEXT NOT NULL,
+            query TEXT NOT NULL,
+            embedding BLOB NOT NULL,
+            stored_at INTEGER NOT NULL,
+            -- Increasing counter recording entry use, for LRU eviction
+            last_used INTEGER NOT NULL,
+            PRIMARY KEY (model, query)
+        );
+
+        -- Advisory lock held by the process currently writing the index
+        CREATE TABLE IF NOT EXISTS index_lock (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            owner TEXT NOT NULL,
+            pid INTEGER NOT NULL,
+            acquired_at INTEGER NOT NULL,
+            heartbeat_at INTEGER NOT NULL
+        );
+
+        -- zstd dictionary used by compressed chunk content (a BLOB in chunks.content)
+        CREATE TABLE IF NOT EXISTS chunk_dictionary (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            dictionary_id INTEGER NOT NULL,
+            dictionary BLOB NOT NULL,
+            trained_at INTEGER NOT NULL
+        );
+
+        -- Aggregates of the files under each directory, rebuilt from the
+        -- other tables (see `store::dir_summaries`); lists are JSON arrays
+        CREATE TABLE IF NOT EXISTS dir_summaries (
+            path TEXT PRIMARY KEY,
+            parent TEXT,
+            file_count INTEGER NOT NULL,
+            line_count INTEGER NOT NULL,
+            languages TEXT NOT NULL,
+            top_symbols TEXT NOT NULL,
+            entry_files TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_dir_summaries_parent ON dir_summaries(parent);
+
+        -- Packages of the project's workspaces (see `crate::workspace`),
+        -- `root` being '' for a package at the project root
+        CREATE TABLE IF NOT EXISTS packages (
+            root TEXT NOT NULL,
+            ecosystem TEXT NOT NULL,
+            name TEXT NOT NULL,
+            PRIMARY KEY (root, ecosystem)
+        ) WITHOUT ROWID;
+
+        -- Source files of the compilation database (see `crate::includes`), with
+        -- their include directories and defines as JSON arrays
+        CREATE TABLE IF NOT EXISTS translation_units (
+            path TEXT PRIMARY KEY,
+            target TEXT,
+            include_dirs TEXT NOT NULL,
+            defines TEXT NOT NULL
+        ) WITHOUT ROWID;
+        CREATE INDEX IF NOT EXISTS idx_translation_units_target ON translation_units(target);
+
+        -- Searches saved by name (see `store::saved_queries`); filters are
+        -- a JSON object of search parameters
+        CREATE TABLE IF NOT EXISTS saved_queries (
+            name TEXT PRIMARY KEY,
+            query TEXT NOT NULL,
+            filters TEXT NOT NULL,
+            description TEXT,
+            saved_at INTEGER NOT NULL
+        );
+
+        -- zstd-compressed content of indexed files, with `storage.file_contents`
+        CREATE TABLE IF NOT EXISTS file_contents (
+            file_id INTEGER PRIMARY KEY,
+            content BLOB NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
         "#,
     )?;
 
+    init_symbols_fts(conn)?;
+    init_chunks_fts(conn)?;
+    init_chunk_symbols(conn)?;
+    init_chunk_token_fit(conn)?;
+    init_chunk_distinctiveness(conn)?;
+    init_chunk_title(conn)?;
+    init_file_encoding(conn)?;
+    init_file_package(conn)?;
+    init_file_generated(conn)?;
+    init_file_outline(conn)?;
+    init_file_embeddings_evicted(conn)?;
+    init_symbol_qualifiers(conn)?;
+    init_symbol_columns(conn)?;
+    init_symbol_cfg_context(conn)?;
+    if cfg!(windows) {
+        init_forward_slash_paths(conn)?;
+    }
+
     // Create sqlite-vec virtual table for vector similarity search
     // This table stores chunk embeddings for semantic search
     conn.execute_batch(&format!(
@@ -154,6 +476,305 @@ pub fn init_schema(conn: &Connection) -> SqliteResult<()> {
     Ok(())
 }
 
+/// Create the FTS5 table of symbols, replacing the one of schema 3 and
+/// earlier (kept in sync by triggers from the `symbols` table, without
+/// stemming or identifier words).
+fn init_symbols_fts(conn: &Connection) -> SqliteResult<()> {
+    let stored_version: Option<i32> = conn
+        .query_row(
+            "SELECT value FROM metadata WHERE key = 'schema_version'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?
+        .and_then(|v| v.parse().ok());
+    if stored_version.is_some_and(|v| v < 4) {
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS symbols_ai;
+             DROP TRIGGER IF EXISTS symbols_ad;
+             DROP TRIGGER IF EXISTS symbols_au;
+             DROP TABLE IF EXISTS symbols_fts;",
+        )?;
+    }
+
+    // Rows are written with their symbol (see `insert_symbols_impl`): `words`
+    // holds the name split into words ("getUserById" gives "get user by id")
+    // and the porter stemmer matches "indexes" with "index"
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS symbols_fts USING fts5(
+            name,
+            words,
+            signature,
+            doc_comment,
+            content='',
+            contentless_delete=1,
+            tokenize='porter unicode61'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS symbols_fts_ad AFTER DELETE ON symbols BEGIN
+            DELETE FROM symbols_fts WHERE rowid = old.id;
+        END;
+        "#,
+    )
+}
+
+/// Create the FTS5 table of chunk contents of schema 10, ranking chunks
+/// with BM25 when no embedding model is available. Chunks indexed by
+/// earlier schemas are added when their file is reindexed for the parser
+/// version of schema 10.
+fn init_chunks_fts(conn: &Connection) -> SqliteResult<()> {
+    // Rows are written with their chunk (see `insert_chunks_impl`): `words`
+    // holds the words of the chunk's mixed-case identifiers, which the
+    // tokenizer keeps whole ("getUserById" gives "get user by id")
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
+            content,
+            words,
+            content='',
+            contentless_delete=1,
+            tokenize='porter unicode61'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS chunks_fts_ad AFTER DELETE ON chunks BEGIN
+            DELETE FROM chunks_fts WHERE rowid = old.id;
+        END;
+        "#,
+    )
+}
+
+/// Link the chunks of schema 4 and earlier to their symbol, adding the
+/// `symbol_id` column these did not have.
+fn init_chunk_symbols(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('chunks') WHERE name = 'symbol_id'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch(&format!(
+            "ALTER TABLE chunks ADD COLUMN symbol_id INTEGER REFERENCES symbols(id) ON DELETE SET NULL;
+             UPDATE chunks SET symbol_id = ({OWNING_SYMBOL_SQL});"
+        ))?;
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_chunks_symbol_id ON chunks(symbol_id);")
+}
+
+/// Add the token fit columns of schema 6 to the chunks of earlier schemas,
+/// filled in when each chunk is embedded again.
+fn init_chunk_token_fit(conn: &Connection) -> SqliteResult<()> {
+    let has_columns: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('chunks') WHERE name = 'token_count'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_columns {
+        conn.execute_batch(
+            "ALTER TABLE chunks ADD COLUMN token_count INTEGER;
+             ALTER TABLE chunks ADD COLUMN embedding_windows INTEGER;
+             ALTER TABLE chunks ADD COLUMN embedding_truncated INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    Ok(())
+}
+
+/// Add the distinctiveness columns of schema 11 to the chunks of earlier
+/// schemas. Content hashes are filled in by the next distinctiveness pass.
+fn init_chunk_distinctiveness(conn: &Connection) -> SqliteResult<()> {
+    let has_columns: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('chunks') WHERE name = 'content_hash'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_columns {
+        conn.execute_batch(
+            "ALTER TABLE chunks ADD COLUMN content_hash TEXT;
+             ALTER TABLE chunks ADD COLUMN distinctiveness REAL NOT NULL DEFAULT 1.0;",
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_chunks_content_hash ON chunks(content_hash);",
+    )
+}
+
+/// Add the `title` column of schema 18 to the chunks of earlier schemas,
+/// titled from the symbols they are already linked to.
+fn init_chunk_title(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('chunks') WHERE name = 'title'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch("ALTER TABLE chunks ADD COLUMN title TEXT;")?;
+        fill_chunk_titles(conn, None)?;
+    }
+    Ok(())
+}
+
+/// Add the `encoding` column of schema 7 to the files of earlier schemas,
+/// all of which were read as UTF-8.
+fn init_file_encoding(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = 'encoding'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN encoding TEXT;")?;
+    }
+    Ok(())
+}
+
+/// Add the `package` column of schema 8 to the files of earlier schemas,
+/// filled in when the packages of the project are next recorded.
+fn init_file_package(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = 'package'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN package TEXT;")?;
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_files_package ON files(package);")
+}
+
+/// Add the `generated` column of schema 14 to the files of earlier schemas,
+/// filled in when the project is next indexed.
+fn init_file_generated(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = 'generated'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN generated INTEGER NOT NULL DEFAULT 0;")?;
+    }
+    Ok(())
+}
+
+/// Add the `outline` column of schema 16 to the files of earlier schemas,
+/// which never hold outlines.
+fn init_file_outline(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = 'outline'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN outline INTEGER NOT NULL DEFAULT 0;")?;
+    }
+    Ok(())
+}
+
+/// Add the `embeddings_evicted` column of schema 19 to the files of earlier
+/// schemas, which never had their embeddings dropped.
+fn init_file_embeddings_evicted(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = 'embeddings_evicted'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute_batch(
+            "ALTER TABLE files ADD COLUMN embeddings_evicted INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    Ok(())
+}
+
+/// Add the qualifier columns of schema 9 to the symbols of earlier schemas,
+/// filled in when the files are reindexed for the new parser version.
+fn init_symbol_qualifiers(conn: &Connection) -> SqliteResult<()> {
+    let has_columns: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('symbols') WHERE name = 'is_public'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_columns {
+        conn.execute_batch(
+            "ALTER TABLE symbols ADD COLUMN is_public INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE symbols ADD COLUMN is_async INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE symbols ADD COLUMN is_static INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE symbols ADD COLUMN is_abstract INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE symbols ADD COLUMN generic_params INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    Ok(())
+}
+
+/// Add the column positions of schema 12 to the symbols of earlier
+/// schemas, filled in when the files are reindexed for the new parser
+/// version.
+fn init_symbol_columns(conn: &Connection) -> SqliteResult<()> {
+    let has_columns: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('symbols') WHERE name = 'start_column'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_columns {
+        conn.execute_batch(
+            "ALTER TABLE symbols ADD COLUMN start_column INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE symbols ADD COLUMN end_column INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    Ok(())
+}
+
+/// Add the conditional compilation context of schema 13 to the symbols of
+/// earlier schemas, filled in when the files are reindexed for the new
+/// parser version.
+fn init_symbol_cfg_context(conn: &Connection) -> SqliteResult<()> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('symbols') WHERE name = 'cfg_context'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_column {
+        conn.execute("ALTER TABLE symbols ADD COLUMN cfg_context TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// Replace the `\` separators of paths indexed on Windows before paths were
+/// normalized (see [`crate::paths`]). A path already indexed in both forms
+/// keeps its `/` record; the other is removed when the file is next indexed.
+fn init_forward_slash_paths(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "UPDATE OR IGNORE files SET path = replace(path, '\\', '/') WHERE instr(path, '\\') > 0",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Id of the symbol of the chunk's file overlapping most of the chunk, the
+/// smallest one on ties, used in `UPDATE chunks`
+pub(crate) const OWNING_SYMBOL_SQL: &str = "SELECT id FROM (
+         SELECT s.id,
+                MIN(s.end_byte, chunks.end_byte) - MAX(s.start_byte, chunks.start_byte) AS overlap,
+                s.end_byte - s.start_byte AS span
+         FROM symbols s
+         WHERE s.file_id = chunks.file_id
+           AND s.start_byte < chunks.end_byte AND s.end_byte > chunks.start_byte
+     )
+     ORDER BY overlap DESC, span
+     LIMIT 1";
+
+/// Name of the package owning the file at `path`, an SQL expression: the
+/// package with the deepest root containing it, as in
+/// [`crate::workspace::owning_package`]. External files belong to none.
+pub(crate) fn owning_package_sql(path: &str) -> String {
+    format!(
+        "(SELECT name FROM packages
+          WHERE substr({path}, 1, 11) <> 'external://'
+            AND (root = '' OR substr({path}, 1, length(root) + 1) = root || '/')
+          ORDER BY length(root) DESC, ecosystem
+          LIMIT 1)"
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileRecord {
     pub id: i64,
@@ -163,6 +784,12 @@ pub struct FileRecord {
     pub size: i64,
     pub last_modified: i64,
     pub indexed_at: i64,
+    /// Encoding the file was decoded from (see [`crate::encoding`]), `None` for UTF-8
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Workspace package owning the file (see [`crate::workspace`])
+    #[serde(default)]
+    pub package: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -175,9 +802,19 @@ pub struct SymbolRecord {
     pub end_line: i64,
     pub start_byte: i64,
     pub end_byte: i64,
+    /// 0-based byte columns of the start and end lines
+    #[serde(default)]
+    pub start_column: i64,
+    #[serde(default)]
+    pub end_column: i64,
     pub signature: Option<String>,
     pub doc_comment: Option<String>,
     pub parent: Option<String>,
+    #[serde(default)]
+    pub qualifiers: SymbolQualifiers,
+    /// Conditions under which the symbol is compiled, e.g. `test`
+    #[serde(default)]
+    pub cfg_context: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -191,6 +828,18 @@ pub struct ChunkRecord {
     pub end_byte: i64,
     pub symbols: Vec<String>,
     pub embedding: Option<Vec<f32>>,
+    /// Symbol overlapping most of the chunk, if any
+    pub symbol_id: Option<i64>,
+    /// How the chunk fitted into the embedding model's token limit, `None`
+    /// until it is embedded
+    pub token_fit: Option<TokenFit>,
+    /// Weight of the chunk's semantic scores, below 1.0 for duplicated or
+    /// boilerplate code (see [`crate::IndexStore::refresh_chunk_distinctiveness`])
+    pub distinctiveness: f32,
+    /// One-line heading of the chunk, from the signature and doc comment of
+    /// its symbol (see [`crate::titles`])
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -200,6 +849,116 @@ pub struct DependencyRecord {
     pub target_path: String,
     pub import_name: Option<String>,
     pub kind: String,
+    /// Symbols named by the import, in source order
+    pub symbols: Vec<ImportedSymbol>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeRelationRecord {
+    pub id: i64,
+    pub file_id: i64,
+    /// Path of the file declaring the relation
+    pub file_path: String,
+    pub language: Option<String>,
+    pub type_name: String,
+    /// `implements`, `extends`, `has_method` or `requires_method`
+    pub kind: String,
+    pub target: String,
+    pub start_line: i64,
+    pub end_line: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntrypointRecord {
+    pub id: i64,
+    pub file_id: i64,
+    /// Path of the file declaring the entry point
+    pub file_path: String,
+    pub language: Option<String>,
+    /// `main`, `route`, `command` or `export`
+    pub kind: String,
+    pub name: String,
+    pub handler: Option<String>,
+    pub start_line: i64,
+    pub end_line: i64,
+}
+
+/// An HTTP route, with the file registering it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteRecord {
+    pub id: i64,
+    pub file_id: i64,
+    pub file_path: String,
+    pub language: Option<String>,
+    /// Uppercase method, `None` for routes handling every method
+    pub method: Option<String>,
+    pub path: String,
+    /// Function handling the route, when it is named
+    pub handler: Option<String>,
+    pub start_line: i64,
+    pub end_line: i64,
+}
+
+/// A read or write of an environment variable or configuration key, with
+/// the file making it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigRefRecord {
+    pub id: i64,
+    pub file_id: i64,
+    pub file_path: String,
+    pub language: Option<String>,
+    pub name: String,
+    /// `env` or `config`
+    pub source: String,
+    /// `read` or `write`
+    pub access: String,
+    pub line: i64,
+}
+
+/// A problem met by the last indexing of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexIssueRecord {
+    pub path: String,
+    /// Kind of issue (see [`crate::IndexIssueKind`])
+    pub kind: String,
+    pub message: String,
+    /// Unix time the issue was met at
+    pub recorded_at: i64,
+}
+
+/// A Rust symbol exported to another language, with the file exporting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BindingRecord {
+    pub id: i64,
+    pub file_id: i64,
+    pub file_path: String,
+    /// Name of the symbol in Rust
+    pub symbol: String,
+    /// Name of the symbol in the other language
+    pub exported_name: String,
+    /// `napi`, `wasm_bindgen` or `pyo3`
+    pub framework: String,
+    pub line: i64,
+}
+
+/// A version of a symbol, with the index generations it was valid at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolVersionRecord {
+    pub id: i64,
+    pub file_path: String,
+    pub name: String,
+    pub kind: String,
+    /// Lines the symbol had at the generation the version was read at
+    pub start_line: i64,
+    pub end_line: i64,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub parent: Option<String>,
+    /// First index generation the version is part of
+    pub valid_from: i64,
+    /// Index generation the version was changed or removed at, `None` for
+    /// the current version
+    pub valid_to: Option<i64>,
 }
 
 #[cfg(test)]
@@ -0,0 +1,95 @@
+//! Read-only base index for IndexStore.
+//!
+//! A base index is an index database built elsewhere, e.g. nightly in CI,
+//! and downloaded next to the project (`[base]` in `semantiq.toml`). It is
+//! searched along with the local index, so that the whole project is
+//! searchable before the local index is complete. It is opened read-only and
+//! only used when it was built with the same schema, parser and embedding
+//! model as the local index.
+
+use super::{IndexStore, STATEMENT_CACHE_CAPACITY, init_sqlite_vec};
+use super::{
+    compression, embedding_format, embedding_model, embedding_preprocessing, embedding_prompts,
+};
+use crate::compression::ChunkCodec;
+use crate::config::StorageConfig;
+use crate::schema::SCHEMA_VERSION;
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{Arc, Mutex, RwLock};
+
+impl IndexStore {
+    /// Open the base index at `path` read-only, checking that its results
+    /// can be merged with those of `local`: it must have been built by the
+    /// same schema and parser versions, and embedded with the same model.
+    pub fn open_base(path: &Path, local: &IndexStore) -> Result<Self> {
+        if !path.is_file() {
+            bail!("Base index not found: {:?}", path);
+        }
+        init_sqlite_vec();
+
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("Failed to open base index at {:?}", path))?;
+        conn.execute_batch(
+            "PRAGMA query_only=ON;
+             PRAGMA busy_timeout=5000;",
+        )?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+        let schema_version: Option<String> = conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .with_context(|| format!("{:?} is not a semantiq index", path))?;
+        if schema_version.as_deref() != Some(SCHEMA_VERSION.to_string().as_str()) {
+            bail!(
+                "Base index {:?} has schema version {}, expected {}: it was built by another version of semantiq",
+                path,
+                schema_version.as_deref().unwrap_or("none"),
+                SCHEMA_VERSION
+            );
+        }
+        if Self::needs_full_reindex_impl(&conn)? {
+            bail!(
+                "Base index {:?} was built by another parser version of semantiq",
+                path
+            );
+        }
+        let model = embedding_model::load_embedding_model(&conn)?.unwrap_or_default();
+        let local_model = local.embedding_model()?;
+        if model != local_model {
+            bail!(
+                "Base index {:?} is embedded with {}, the local index with {}",
+                path,
+                model,
+                local_model
+            );
+        }
+
+        let embedding_format = embedding_format::load_embedding_format(&conn)?;
+        let chunk_dictionary = compression::load_chunk_dictionary(&conn)?;
+        let embedding_preprocessor = embedding_preprocessing::load_embedding_preprocessor(&conn)?;
+        let embedding_prompts = embedding_prompts::load_embedding_prompts(&conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            db_path: path.to_path_buf(),
+            embedding_format: RwLock::new(embedding_format),
+            embedding_preprocessor: RwLock::new(embedding_preprocessor),
+            embedding_prompts: RwLock::new(embedding_prompts),
+            chunk_codec: RwLock::new(ChunkCodec::new(false, chunk_dictionary)),
+            file_contents: AtomicBool::new(false),
+            usage_stats: AtomicBool::new(false),
+            search_tracking: AtomicBool::new(false),
+            scan_memory: AtomicUsize::new(StorageConfig::default().scan_memory_bytes()),
+        })
+    }
+}
@@ -0,0 +1,84 @@
+//! Cross-language binding operations for IndexStore.
+//!
+//! The Rust symbols each file exports to another language through napi-rs,
+//! wasm-bindgen or PyO3 (see `semantiq_parser::bindings`), one row per
+//! symbol.
+
+use super::IndexStore;
+use crate::schema::BindingRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row, params};
+use semantiq_parser::Binding;
+use std::collections::HashSet;
+
+impl IndexStore {
+    /// Replace the bindings exported by a file.
+    pub fn insert_bindings(&self, file_id: i64, bindings: &[Binding]) -> Result<()> {
+        self.with_conn(|conn| {
+            Self::insert_bindings_impl(conn, file_id, bindings)?;
+            Self::bump_generation_impl(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_bindings_impl(
+        conn: &Connection,
+        file_id: i64,
+        bindings: &[Binding],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM bindings WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO bindings (file_id, symbol, exported_name, framework, line)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for b in bindings {
+            stmt.execute(params![
+                file_id,
+                b.symbol,
+                b.exported_name,
+                b.framework.as_str(),
+                b.line as i64,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Get the bindings whose Rust or exported name is one of `names`, by
+    /// file and line.
+    pub fn get_bindings(&self, names: &[String]) -> Result<Vec<BindingRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT b.id, b.file_id, f.path, b.symbol, b.exported_name, b.framework, b.line
+                 FROM bindings b JOIN files f ON f.id = b.file_id
+                 WHERE b.symbol = ?1 OR b.exported_name = ?1",
+            )?;
+            let mut seen = HashSet::new();
+            let mut records = Vec::new();
+            for name in names {
+                for record in stmt.query_map([name], binding_from_row)? {
+                    let record = record?;
+                    if seen.insert(record.id) {
+                        records.push(record);
+                    }
+                }
+            }
+            records.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+            Ok(records)
+        })
+    }
+}
+
+fn binding_from_row(row: &Row<'_>) -> rusqlite::Result<BindingRecord> {
+    Ok(BindingRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_path: row.get(2)?,
+        symbol: row.get(3)?,
+        exported_name: row.get(4)?,
+        framework: row.get(5)?,
+        line: row.get(6)?,
+    })
+}
@@ -1,6 +1,7 @@
 //! Threshold calibration storage operations.
 
 use super::IndexStore;
+use crate::clock::unix_now;
 use anyhow::Result;
 use rusqlite::{OptionalExtension, params};
 use tracing::debug;
@@ -39,10 +40,7 @@ pub struct CalibrationData {
 impl IndexStore {
     /// Save calibrated thresholds for a language.
     pub fn save_calibration(&self, data: &CalibrationData) -> Result<()> {
-        let calibrated_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs() as i64)
-            .unwrap_or(0);
+        let calibrated_at = unix_now();
 
         self.with_conn(|conn| {
             conn.execute(
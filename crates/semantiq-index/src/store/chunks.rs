@@ -1,16 +1,24 @@
 //! Chunk operations for IndexStore.
 
 use super::IndexStore;
-use crate::schema::ChunkRecord;
+use super::compression::read_content;
+use super::distinctiveness::chunk_content_hash;
+use super::embedding_format::{vec_insert_sql, vec_search_sql};
+use crate::compression::ChunkCodec;
+use crate::external::EXTERNAL_PREFIX;
+use crate::quantization::EmbeddingFormat;
+use crate::schema::{ChunkRecord, OWNING_SYMBOL_SQL};
+use crate::titles::fill_chunk_titles;
 use anyhow::{Result, anyhow};
 use rusqlite::Connection;
 use rusqlite::{OptionalExtension, params};
-use semantiq_parser::CodeChunk;
+use semantiq_embeddings::TokenFit;
+use semantiq_parser::{CodeChunk, split_words};
 use std::sync::{MutexGuard, PoisonError};
 use tracing::{debug, warn};
 
 /// Parse symbols JSON with logging on error.
-fn parse_symbols_json(json: &str) -> Vec<String> {
+pub(super) fn parse_symbols_json(json: &str) -> Vec<String> {
     serde_json::from_str(json).unwrap_or_else(|e| {
         if !json.is_empty() && json != "[]" {
             warn!("Failed to parse symbols JSON: {} (json: {})", e, json);
@@ -19,22 +27,68 @@ fn parse_symbols_json(json: &str) -> Vec<String> {
     })
 }
 
-/// Convert embedding bytes to f32 vector with validation.
-fn parse_embedding_bytes(bytes: &[u8]) -> Vec<f32> {
-    if !bytes.len().is_multiple_of(4) {
-        warn!(
-            "Invalid embedding bytes length: {} (not divisible by 4)",
-            bytes.len()
-        );
-        return Vec::new();
+/// Token fit stored in the 3 columns from `idx`, `None` for a chunk not
+/// embedded since they were added.
+pub(super) fn read_token_fit(
+    row: &rusqlite::Row,
+    idx: usize,
+) -> rusqlite::Result<Option<TokenFit>> {
+    let tokens: Option<i64> = row.get(idx)?;
+    let windows: Option<i64> = row.get(idx + 1)?;
+    let truncated: bool = row.get(idx + 2)?;
+    Ok(tokens.zip(windows).map(|(tokens, windows)| TokenFit {
+        tokens: tokens as usize,
+        windows: windows as usize,
+        truncated,
+    }))
+}
+
+/// Words of the identifiers of `content` made of several words, lowercased
+/// and separated by spaces (`let userId = getUserById(id)` gives
+/// `user id get user by id`)
+fn compound_words(content: &str) -> String {
+    content
+        .split(|c: char| !c.is_alphanumeric())
+        .map(split_words)
+        .filter(|words| words.len() > 1)
+        .flatten()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// FTS5 query matching any word of `terms`, and the words of their
+/// mixed-case identifiers, for BM25 ranking. `None` without any word.
+fn bm25_query(terms: &[&str]) -> Option<String> {
+    const MAX_WORDS: usize = 32;
+
+    let mut words: Vec<String> = Vec::new();
+    for token in terms
+        .iter()
+        .flat_map(|term| term.split(|c: char| !c.is_alphanumeric()))
+    {
+        let parts = split_words(token);
+        let token_words = std::iter::once(token.to_string()).chain(if parts.len() > 1 {
+            parts
+        } else {
+            Vec::new()
+        });
+        for word in token_words {
+            let word = word.to_lowercase();
+            if !word.is_empty() && !words.contains(&word) {
+                words.push(word);
+            }
+        }
     }
-    bytes
-        .chunks_exact(4)
-        .map(|chunk| {
-            let bytes: [u8; 4] = chunk.try_into().expect("chunks_exact guarantees 4 bytes");
-            f32::from_le_bytes(bytes)
-        })
-        .collect()
+    words.truncate(MAX_WORDS);
+
+    (!words.is_empty()).then(|| {
+        words
+            .iter()
+            .map(|word| format!("\"{}\"", word))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    })
 }
 
 impl IndexStore {
@@ -47,30 +101,15 @@ impl IndexStore {
                 anyhow!("Database lock poisoned: {}", e)
             })?;
 
+        let codec = self.chunk_codec_for(&conn)?;
+
         // Use a transaction for atomicity
         conn.execute("BEGIN IMMEDIATE", [])?;
 
         let result = (|| -> Result<()> {
-            // Delete existing chunks for this file
-            conn.execute("DELETE FROM chunks WHERE file_id = ?1", [file_id])?;
-
-            let mut stmt = conn.prepare(
-                "INSERT INTO chunks (file_id, content, start_line, end_line, start_byte, end_byte, symbols_json)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            )?;
-
-            for chunk in chunks {
-                let symbols_json = serde_json::to_string(&chunk.symbols)?;
-                stmt.execute(params![
-                    file_id,
-                    chunk.content,
-                    chunk.start_line as i64,
-                    chunk.end_line as i64,
-                    chunk.start_byte as i64,
-                    chunk.end_byte as i64,
-                    symbols_json,
-                ])?;
-            }
+            Self::insert_chunks_impl(&conn, &codec, file_id, chunks)?;
+            Self::link_chunk_symbols_impl(&conn, file_id)?;
+            Self::bump_generation_impl(&conn)?;
             Ok(())
         })();
 
@@ -87,28 +126,113 @@ impl IndexStore {
         }
     }
 
-    /// Update the embedding for a chunk.
-    pub fn update_chunk_embedding(&self, chunk_id: i64, embedding: &[f32]) -> Result<()> {
-        self.with_conn(|conn| {
-            // Convert f32 slice to bytes for the chunks table
-            let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+    /// Internal implementation for use within a transaction, returns the ids
+    /// of the inserted chunks in order. Content is encoded with `codec`.
+    pub(crate) fn insert_chunks_impl(
+        conn: &Connection,
+        codec: &ChunkCodec,
+        file_id: i64,
+        chunks: &[CodeChunk],
+    ) -> Result<Vec<i64>> {
+        // Delete existing chunks for this file
+        conn.prepare_cached("DELETE FROM chunks WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO chunks (file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, content_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+
+        let mut fts_stmt = conn
+            .prepare_cached("INSERT INTO chunks_fts (rowid, content, words) VALUES (?1, ?2, ?3)")?;
+
+        let mut ids = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let symbols_json = serde_json::to_string(&chunk.symbols)?;
+            stmt.execute(params![
+                file_id,
+                codec.encode(&chunk.content)?,
+                chunk.start_line as i64,
+                chunk.end_line as i64,
+                chunk.start_byte as i64,
+                chunk.end_byte as i64,
+                symbols_json,
+                chunk_content_hash(&chunk.content),
+            ])?;
+            let id = conn.last_insert_rowid();
+            fts_stmt.execute(params![id, chunk.content, compound_words(&chunk.content)])?;
+            ids.push(id);
+        }
+        Self::forget_migrated_chunks_impl(conn, &ids)?;
+        Ok(ids)
+    }
 
-            // Update the chunks table (for backward compatibility)
-            conn.execute(
-                "UPDATE chunks SET embedding = ?1 WHERE id = ?2",
-                params![embedding_bytes, chunk_id],
-            )?;
+    /// Point each chunk of a file to the symbol overlapping most of it, once
+    /// both are written, and title the chunk after that symbol.
+    pub(crate) fn link_chunk_symbols_impl(conn: &Connection, file_id: i64) -> Result<()> {
+        conn.prepare_cached(&format!(
+            "UPDATE chunks SET symbol_id = ({OWNING_SYMBOL_SQL}) WHERE file_id = ?1"
+        ))?
+        .execute([file_id])?;
+        fill_chunk_titles(conn, Some(file_id))?;
+        Ok(())
+    }
 
-            // Insert/replace into the vec0 virtual table for vector search
-            conn.execute(
-                "INSERT OR REPLACE INTO chunks_vec(chunk_id, embedding) VALUES (?1, ?2)",
-                params![chunk_id, embedding_bytes],
-            )?;
+    /// Record how a chunk fitted into the embedding model's token limit.
+    pub fn update_chunk_token_fit(&self, chunk_id: i64, fit: &TokenFit) -> Result<()> {
+        self.with_conn(|conn| Self::update_chunk_token_fit_impl(conn, chunk_id, fit))
+    }
 
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn update_chunk_token_fit_impl(
+        conn: &Connection,
+        chunk_id: i64,
+        fit: &TokenFit,
+    ) -> Result<()> {
+        conn.prepare_cached(
+            "UPDATE chunks SET token_count = ?1, embedding_windows = ?2, embedding_truncated = ?3
+             WHERE id = ?4",
+        )?
+        .execute(params![
+            fit.tokens as i64,
+            fit.windows as i64,
+            fit.truncated,
+            chunk_id
+        ])?;
+        Ok(())
+    }
+
+    /// Update the embedding for a chunk.
+    ///
+    /// The embedding is stored in the index's embedding format (see
+    /// [`IndexStore::embedding_format`]).
+    pub fn update_chunk_embedding(&self, chunk_id: i64, embedding: &[f32]) -> Result<()> {
+        self.with_conn(|conn| {
+            Self::update_chunk_embedding_impl(conn, self.embedding_format(), chunk_id, embedding)?;
+            Self::bump_generation_impl(conn)?;
             Ok(())
         })
     }
 
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn update_chunk_embedding_impl(
+        conn: &Connection,
+        format: EmbeddingFormat,
+        chunk_id: i64,
+        embedding: &[f32],
+    ) -> Result<()> {
+        let embedding_bytes = format.encode(embedding);
+
+        // Update the chunks table (for backward compatibility)
+        conn.prepare_cached("UPDATE chunks SET embedding = ?1 WHERE id = ?2")?
+            .execute(params![embedding_bytes, chunk_id])?;
+
+        // Insert/replace into the vec0 virtual table for vector search
+        conn.prepare_cached(vec_insert_sql(format))?
+            .execute(params![chunk_id, embedding_bytes])?;
+        Ok(())
+    }
+
     /// Search for similar chunks using vector similarity (sqlite-vec).
     /// Returns chunk IDs with their distances, ordered by similarity (closest first).
     pub fn search_similar_chunks(
@@ -117,22 +241,18 @@ impl IndexStore {
         limit: usize,
     ) -> Result<Vec<(i64, f32)>> {
         self.with_conn(|conn| {
-            let embedding_bytes: Vec<u8> = query_embedding
-                .iter()
-                .flat_map(|f| f.to_le_bytes())
-                .collect();
+            let format = self.embedding_format();
+            let embedding_bytes = format.encode(query_embedding);
 
-            let mut stmt = conn.prepare(
-                "SELECT chunk_id, distance
-                 FROM chunks_vec
-                 WHERE embedding MATCH ?1
-                 ORDER BY distance
-                 LIMIT ?2",
-            )?;
+            let mut stmt = conn.prepare(vec_search_sql(format))?;
 
+            // Quantized distances are scaled back so thresholds stay comparable
             let results = stmt
                 .query_map(params![embedding_bytes, limit as i64], |row| {
-                    Ok((row.get::<_, i64>(0)?, row.get::<_, f32>(1)?))
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        format.normalize_distance(row.get::<_, f32>(1)?),
+                    ))
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
 
@@ -140,6 +260,33 @@ impl IndexStore {
         })
     }
 
+    /// Search chunk contents with the FTS5 BM25 ranking, matching any word of
+    /// `terms`. Returns chunk IDs with their relevance (higher is better),
+    /// most relevant first.
+    pub fn search_chunks_bm25(&self, terms: &[&str], limit: usize) -> Result<Vec<(i64, f32)>> {
+        let Some(fts_query) = bm25_query(terms) else {
+            return Ok(Vec::new());
+        };
+
+        self.with_conn(|conn| {
+            // bm25() is negative, lower for better matches; identifier words
+            // weigh half as much as the content they are taken from
+            let mut stmt = conn.prepare_cached(
+                "SELECT rowid, -bm25(chunks_fts, 1.0, 0.5) AS relevance
+                 FROM chunks_fts
+                 WHERE chunks_fts MATCH ?1
+                 ORDER BY relevance DESC
+                 LIMIT ?2",
+            )?;
+            let results = stmt
+                .query_map(params![fts_query, limit as i64], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)? as f32))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(results)
+        })
+    }
+
     /// Get chunk records by IDs (useful after vector search).
     ///
     /// If more than 900 IDs are provided, the query is split into batches
@@ -161,9 +308,12 @@ impl IndexStore {
         }
 
         self.with_conn(|conn| {
+            let codec = self.chunk_codec_for(conn)?;
+            let format = self.embedding_format();
             let placeholders: String = chunk_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
             let query = format!(
-                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, embedding
+                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, embedding, symbol_id,
+                        token_count, embedding_windows, embedding_truncated, distinctiveness, title
                  FROM chunks WHERE id IN ({})",
                 placeholders
             );
@@ -176,18 +326,22 @@ impl IndexStore {
                     let symbols_json: String = row.get(7)?;
                     let symbols = parse_symbols_json(&symbols_json);
                     let embedding_bytes: Option<Vec<u8>> = row.get(8)?;
-                    let embedding = embedding_bytes.map(|b| parse_embedding_bytes(&b));
+                    let embedding = embedding_bytes.map(|b| format.decode(&b));
 
                     Ok(ChunkRecord {
                         id: row.get(0)?,
                         file_id: row.get(1)?,
-                        content: row.get(2)?,
+                        content: read_content(&codec, row, 2)?,
                         start_line: row.get(3)?,
                         end_line: row.get(4)?,
                         start_byte: row.get(5)?,
                         end_byte: row.get(6)?,
                         symbols,
                         embedding,
+                        symbol_id: row.get(9)?,
+                        token_fit: read_token_fit(row, 10)?,
+                        distinctiveness: row.get(13)?,
+                        title: row.get(14)?,
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -196,12 +350,16 @@ impl IndexStore {
         })
     }
 
-    /// Get chunks that don't have embeddings yet.
+    /// Get chunks that don't have embeddings yet, leaving out those whose
+    /// embeddings were dropped by `quota.max_db_mb`.
     pub fn get_chunks_without_embeddings(&self, limit: usize) -> Result<Vec<ChunkRecord>> {
         self.with_conn(|conn| {
+            let codec = self.chunk_codec_for(conn)?;
             let mut stmt = conn.prepare(
-                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json
+                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, symbol_id,
+                        token_count, embedding_windows, embedding_truncated, distinctiveness, title
                  FROM chunks WHERE embedding IS NULL
+                     AND file_id NOT IN (SELECT id FROM files WHERE embeddings_evicted = 1)
                  LIMIT ?1",
             )?;
 
@@ -213,13 +371,17 @@ impl IndexStore {
                     Ok(ChunkRecord {
                         id: row.get(0)?,
                         file_id: row.get(1)?,
-                        content: row.get(2)?,
+                        content: read_content(&codec, row, 2)?,
                         start_line: row.get(3)?,
                         end_line: row.get(4)?,
                         start_byte: row.get(5)?,
                         end_byte: row.get(6)?,
                         symbols,
                         embedding: None,
+                        symbol_id: row.get(8)?,
+                        token_fit: read_token_fit(row, 9)?,
+                        distinctiveness: row.get(12)?,
+                        title: row.get(13)?,
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -231,8 +393,10 @@ impl IndexStore {
     /// Get all chunks for a file.
     pub fn get_chunks_by_file(&self, file_id: i64) -> Result<Vec<ChunkRecord>> {
         self.with_conn(|conn| {
+            let codec = self.chunk_codec_for(conn)?;
             let mut stmt = conn.prepare(
-                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json
+                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, symbol_id,
+                        token_count, embedding_windows, embedding_truncated, distinctiveness, title
                  FROM chunks WHERE file_id = ?1",
             )?;
 
@@ -244,13 +408,17 @@ impl IndexStore {
                     Ok(ChunkRecord {
                         id: row.get(0)?,
                         file_id: row.get(1)?,
-                        content: row.get(2)?,
+                        content: read_content(&codec, row, 2)?,
                         start_line: row.get(3)?,
                         end_line: row.get(4)?,
                         start_byte: row.get(5)?,
                         end_byte: row.get(6)?,
                         symbols,
                         embedding: None,
+                        symbol_id: row.get(8)?,
+                        token_fit: read_token_fit(row, 9)?,
+                        distinctiveness: row.get(12)?,
+                        title: row.get(13)?,
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -259,41 +427,25 @@ impl IndexStore {
         })
     }
 
-    /// Get all chunks that have embeddings.
-    pub fn get_chunks_with_embeddings(&self) -> Result<Vec<(ChunkRecord, Vec<f32>)>> {
+    /// Get up to `limit` random chunks with embeddings from project files
+    /// (external dependency sources are skipped), as `(chunk_id, embedding)`.
+    pub fn sample_chunk_embeddings(&self, limit: usize) -> Result<Vec<(i64, Vec<f32>)>> {
         self.with_conn(|conn| {
+            let format = self.embedding_format();
             let mut stmt = conn.prepare(
-                "SELECT c.id, c.file_id, c.content, c.start_line, c.end_line, c.start_byte, c.end_byte, c.symbols_json, c.embedding, f.path
-                 FROM chunks c
+                "SELECT c.id, c.embedding FROM chunks c
                  JOIN files f ON c.file_id = f.id
-                 WHERE c.embedding IS NOT NULL",
+                 WHERE c.embedding IS NOT NULL AND substr(f.path, 1, length(?1)) != ?1
+                 ORDER BY RANDOM()
+                 LIMIT ?2",
             )?;
 
             let results = stmt
-                .query_map([], |row| {
-                    let symbols_json: String = row.get(7)?;
-                    let symbols = parse_symbols_json(&symbols_json);
-                    let embedding_bytes: Vec<u8> = row.get(8)?;
-                    let embedding = parse_embedding_bytes(&embedding_bytes);
-
-                    let chunk = ChunkRecord {
-                        id: row.get(0)?,
-                        file_id: row.get(1)?,
-                        content: row.get(2)?,
-                        start_line: row.get(3)?,
-                        end_line: row.get(4)?,
-                        start_byte: row.get(5)?,
-                        end_byte: row.get(6)?,
-                        symbols,
-                        embedding: Some(embedding.clone()),
-                    };
-
-                    Ok((chunk, embedding))
+                .query_map(params![EXTERNAL_PREFIX, limit as i64], |row| {
+                    let embedding_bytes: Vec<u8> = row.get(1)?;
+                    Ok((row.get::<_, i64>(0)?, format.decode(&embedding_bytes)))
                 })?
-                .filter_map(|r| {
-                    r.map_err(|e| warn!("Failed to load chunk with embedding: {}", e)).ok()
-                })
-                .collect();
+                .collect::<Result<Vec<_>, _>>()?;
 
             Ok(results)
         })
@@ -0,0 +1,224 @@
+//! Chunk content compression for IndexStore.
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use crate::compression::{ChunkCodec, ChunkDictionary};
+use anyhow::{Result, anyhow};
+use rusqlite::types::Type;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Serialize;
+use std::sync::{Arc, MutexGuard, PoisonError};
+use tracing::{info, warn};
+
+/// Number of chunks sampled to train the dictionary
+const TRAINING_SAMPLES: usize = 4000;
+
+/// Number of chunks rewritten per query during compaction
+const COMPACT_BATCH_SIZE: i64 = 500;
+
+/// Outcome of [`IndexStore::compact_chunks`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CompactionReport {
+    /// Chunks rewritten
+    pub chunks: usize,
+    /// Chunks stored compressed (the others did not shrink)
+    pub compressed: usize,
+    /// Size of the chunk content once decompressed
+    pub content_bytes: u64,
+    /// Bytes stored for chunk content before compaction
+    pub stored_bytes_before: u64,
+    /// Bytes stored for chunk content after compaction
+    pub stored_bytes_after: u64,
+    /// Size of the trained dictionary, 0 when there were too few chunks to train one
+    pub dictionary_bytes: usize,
+}
+
+/// Read the persisted chunk dictionary, if one was trained.
+pub(crate) fn load_chunk_dictionary(conn: &Connection) -> Result<Option<Arc<ChunkDictionary>>> {
+    let bytes: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT dictionary FROM chunk_dictionary WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    bytes
+        .map(|b| ChunkDictionary::new(b).map(Arc::new))
+        .transpose()
+}
+
+/// Read the chunk content at column `idx`, decompressing it if needed.
+pub(crate) fn read_content(
+    codec: &ChunkCodec,
+    row: &Row<'_>,
+    idx: usize,
+) -> rusqlite::Result<String> {
+    codec
+        .decode(row.get_ref(idx)?)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Blob, e.into()))
+}
+
+impl IndexStore {
+    /// Get the encoding of chunk content.
+    pub fn chunk_codec(&self) -> ChunkCodec {
+        self.chunk_codec
+            .read()
+            .map(|c| c.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Get the encoding of chunk content, reloading the dictionary if another
+    /// process (e.g. `semantiq compact`) replaced it. Called with the
+    /// connection locked.
+    pub(crate) fn chunk_codec_for(&self, conn: &Connection) -> Result<ChunkCodec> {
+        let cached = self.chunk_codec();
+        let persisted: Option<u32> = conn
+            .query_row(
+                "SELECT dictionary_id FROM chunk_dictionary WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if persisted == cached.dictionary().map(|d| d.id()) {
+            return Ok(cached);
+        }
+
+        let codec = ChunkCodec::new(cached.compresses(), load_chunk_dictionary(conn)?);
+        match self.chunk_codec.write() {
+            Ok(mut c) => *c = codec.clone(),
+            Err(e) => *e.into_inner() = codec.clone(),
+        }
+        Ok(codec)
+    }
+
+    /// Compress the content of chunks written from now on. Existing chunks
+    /// keep their encoding until [`IndexStore::compact_chunks`] rewrites them.
+    pub fn set_chunk_compression(&self, enabled: bool) {
+        let mut codec = match self.chunk_codec.write() {
+            Ok(c) => c,
+            Err(e) => e.into_inner(),
+        };
+        *codec = ChunkCodec::new(enabled, codec.dictionary().cloned());
+    }
+
+    /// Compress the content of every chunk with zstd.
+    ///
+    /// Trains a dictionary on a sample of the indexed chunks, rewrites all
+    /// chunks with it, then vacuums the database to release the freed pages.
+    /// Chunks that do not shrink are kept as text. Reads are unaffected:
+    /// content is decompressed transparently.
+    pub fn compact_chunks(&self) -> Result<CompactionReport> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+        let current = match self.chunk_codec_for(&conn) {
+            Ok(codec) => codec,
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                return Err(e);
+            }
+        };
+
+        let result = (|| -> Result<(CompactionReport, Option<Arc<ChunkDictionary>>)> {
+            let samples: Vec<String> = {
+                let mut stmt = conn.prepare(
+                    "SELECT content FROM chunks
+                     WHERE id IN (SELECT id FROM chunks ORDER BY RANDOM() LIMIT ?1)",
+                )?;
+                stmt.query_map([TRAINING_SAMPLES as i64], |row| {
+                    read_content(&current, row, 0)
+                })?
+                .collect::<Result<Vec<_>, _>>()?
+            };
+            let dictionary = ChunkDictionary::train(&samples).map(Arc::new);
+            let target = ChunkCodec::new(true, dictionary.clone());
+
+            let mut report = CompactionReport {
+                dictionary_bytes: dictionary.as_ref().map_or(0, |d| d.as_bytes().len()),
+                ..Default::default()
+            };
+
+            let mut select = conn.prepare(
+                "SELECT id, length(CAST(content AS BLOB)), content FROM chunks
+                 WHERE id > ?1 ORDER BY id LIMIT ?2",
+            )?;
+            let mut update = conn.prepare("UPDATE chunks SET content = ?1 WHERE id = ?2")?;
+            let mut last_id = 0i64;
+
+            loop {
+                let batch: Vec<(i64, i64, String)> = select
+                    .query_map(params![last_id, COMPACT_BATCH_SIZE], |row| {
+                        Ok((row.get(0)?, row.get(1)?, read_content(&current, row, 2)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let Some(&(id, _, _)) = batch.last() else {
+                    break;
+                };
+                last_id = id;
+
+                for (chunk_id, stored_len, content) in &batch {
+                    let value = target.encode(content)?;
+                    if let rusqlite::types::Value::Blob(ref frame) = value {
+                        report.compressed += 1;
+                        report.stored_bytes_after += frame.len() as u64;
+                    } else {
+                        report.stored_bytes_after += content.len() as u64;
+                    }
+                    report.chunks += 1;
+                    report.content_bytes += content.len() as u64;
+                    report.stored_bytes_before += *stored_len as u64;
+                    update.execute(params![value, chunk_id])?;
+                }
+            }
+
+            match dictionary {
+                Some(ref dictionary) => {
+                    let trained_at = unix_now();
+                    conn.execute(
+                        "INSERT OR REPLACE INTO chunk_dictionary (id, dictionary_id, dictionary, trained_at)
+                         VALUES (1, ?1, ?2, ?3)",
+                        params![dictionary.id(), dictionary.as_bytes(), trained_at],
+                    )?;
+                }
+                None => {
+                    conn.execute("DELETE FROM chunk_dictionary", [])?;
+                }
+            }
+
+            Ok((report, dictionary))
+        })();
+
+        match result {
+            Ok((report, dictionary)) => {
+                conn.execute("COMMIT", [])?;
+                match self.chunk_codec.write() {
+                    Ok(mut c) => *c = ChunkCodec::new(current.compresses(), dictionary),
+                    Err(e) => *e.into_inner() = ChunkCodec::new(current.compresses(), dictionary),
+                }
+                info!(
+                    "Compacted {} chunks: {} -> {} bytes ({} byte dictionary)",
+                    report.chunks,
+                    report.stored_bytes_before,
+                    report.stored_bytes_after,
+                    report.dictionary_bytes
+                );
+
+                // Release the pages freed by the smaller chunks
+                if let Err(e) = conn.execute_batch("VACUUM") {
+                    warn!("Failed to vacuum database after compaction: {}", e);
+                }
+                Ok(report)
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Environment variable and configuration key operations for IndexStore.
+//!
+//! The variables and keys each file reads or writes (see
+//! `semantiq_parser::config_refs`), one row per name, access and line.
+
+use super::IndexStore;
+use crate::schema::ConfigRefRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row, params};
+use semantiq_parser::{ConfigAccess, ConfigRef, ConfigSource};
+
+impl IndexStore {
+    /// Replace the environment and configuration reads and writes of a file.
+    pub fn insert_config_refs(&self, file_id: i64, refs: &[ConfigRef]) -> Result<()> {
+        self.with_conn(|conn| {
+            Self::insert_config_refs_impl(conn, file_id, refs)?;
+            Self::bump_generation_impl(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_config_refs_impl(
+        conn: &Connection,
+        file_id: i64,
+        refs: &[ConfigRef],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM config_refs WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO config_refs (file_id, name, source, access, line)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for r in refs {
+            stmt.execute(params![
+                file_id,
+                r.name,
+                r.source.as_str(),
+                r.access.as_str(),
+                r.line as i64,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Get the reads and writes of the variables or keys matching `pattern`,
+    /// a name or a glob (`DATABASE_*`), of the given source and access or
+    /// of any. Writes come first for each name, then files and lines in
+    /// order.
+    pub fn get_config_refs(
+        &self,
+        pattern: &str,
+        source: Option<ConfigSource>,
+        access: Option<ConfigAccess>,
+    ) -> Result<Vec<ConfigRefRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT c.id, c.file_id, f.path, f.language, c.name, c.source, c.access, c.line
+                 FROM config_refs c JOIN files f ON f.id = c.file_id
+                 WHERE c.name GLOB ?1
+                   AND (?2 IS NULL OR c.source = ?2)
+                   AND (?3 IS NULL OR c.access = ?3)
+                 ORDER BY c.name, c.access = 'read', f.path, c.line, c.id",
+            )?;
+            let records = stmt
+                .query_map(
+                    params![
+                        pattern,
+                        source.map(|s| s.as_str()),
+                        access.map(|a| a.as_str())
+                    ],
+                    config_ref_from_row,
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+
+    /// Get the distinct names of the variables and keys of the given source,
+    /// or of any, in order.
+    pub fn get_config_names(&self, source: Option<ConfigSource>) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT DISTINCT name FROM config_refs
+                 WHERE ?1 IS NULL OR source = ?1
+                 ORDER BY name",
+            )?;
+            let names = stmt
+                .query_map(params![source.map(|s| s.as_str())], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(names)
+        })
+    }
+}
+
+fn config_ref_from_row(row: &Row<'_>) -> rusqlite::Result<ConfigRefRecord> {
+    Ok(ConfigRefRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_path: row.get(2)?,
+        language: row.get(3)?,
+        name: row.get(4)?,
+        source: row.get(5)?,
+        access: row.get(6)?,
+        line: row.get(7)?,
+    })
+}
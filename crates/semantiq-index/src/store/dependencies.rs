@@ -1,31 +1,143 @@
 //! Dependency operations for IndexStore.
 
 use super::IndexStore;
+use crate::paths::normalize_path;
 use crate::schema::DependencyRecord;
-use anyhow::Result;
-use rusqlite::params;
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, params};
+use semantiq_parser::{Import, ImportedSymbol};
 use std::collections::HashSet;
+use std::sync::{MutexGuard, PoisonError};
+use tracing::debug;
 
 impl IndexStore {
-    /// Insert a dependency record.
+    /// Insert a dependency record and return its id.
     pub fn insert_dependency(
         &self,
         source_file_id: i64,
         target_path: &str,
         import_name: Option<&str>,
         kind: &str,
-    ) -> Result<()> {
+    ) -> Result<i64> {
         self.with_conn(|conn| {
-            conn.execute(
-                "INSERT INTO dependencies (source_file_id, target_path, import_name, kind)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![source_file_id, target_path, import_name, kind],
+            let dependency_id =
+                Self::insert_dependency_impl(conn, source_file_id, target_path, import_name, kind)?;
+            Self::bump_generation_impl(conn)?;
+
+            Ok(dependency_id)
+        })
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_dependency_impl(
+        conn: &Connection,
+        source_file_id: i64,
+        target_path: &str,
+        import_name: Option<&str>,
+        kind: &str,
+    ) -> Result<i64> {
+        conn.prepare_cached(
+            "INSERT INTO dependencies (source_file_id, target_path, import_name, kind)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![source_file_id, target_path, import_name, kind])?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Replace the dependencies of a file with its imports and the symbols
+    /// they name, in a single transaction. Returns the dependency ids in
+    /// the order of `imports`.
+    pub fn insert_dependencies(&self, source_file_id: i64, imports: &[Import]) -> Result<Vec<i64>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<Vec<i64>> {
+            let ids = Self::insert_dependencies_impl(&conn, source_file_id, imports)?;
+            Self::bump_generation_impl(&conn)?;
+            Ok(ids)
+        })();
+
+        match result {
+            Ok(ids) => {
+                conn.execute("COMMIT", [])?;
+                debug!(
+                    "Inserted {} dependencies for file_id {}",
+                    imports.len(),
+                    source_file_id
+                );
+                Ok(ids)
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_dependencies_impl(
+        conn: &Connection,
+        source_file_id: i64,
+        imports: &[Import],
+    ) -> Result<Vec<i64>> {
+        conn.prepare_cached("DELETE FROM dependencies WHERE source_file_id = ?1")?
+            .execute([source_file_id])?;
+
+        let mut ids = Vec::with_capacity(imports.len());
+        for import in imports {
+            let dependency_id = Self::insert_dependency_impl(
+                conn,
+                source_file_id,
+                &import.path,
+                import.name.as_deref(),
+                import.kind.as_str(),
             )?;
+            Self::insert_dependency_symbols_impl(conn, dependency_id, &import.symbols)?;
+            ids.push(dependency_id);
+        }
+        Ok(ids)
+    }
+
+    /// Record the symbols named by a dependency's import statement.
+    pub fn insert_dependency_symbols(
+        &self,
+        dependency_id: i64,
+        symbols: &[ImportedSymbol],
+    ) -> Result<()> {
+        if symbols.is_empty() {
+            return Ok(());
+        }
+
+        self.with_conn(|conn| {
+            Self::insert_dependency_symbols_impl(conn, dependency_id, symbols)?;
+            Self::bump_generation_impl(conn)?;
 
             Ok(())
         })
     }
 
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_dependency_symbols_impl(
+        conn: &Connection,
+        dependency_id: i64,
+        symbols: &[ImportedSymbol],
+    ) -> Result<()> {
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO dependency_symbols (dependency_id, name, alias)
+             VALUES (?1, ?2, ?3)",
+        )?;
+        for symbol in symbols {
+            stmt.execute(params![dependency_id, symbol.name, symbol.alias])?;
+        }
+        Ok(())
+    }
+
     /// Delete all dependencies for a file.
     pub fn delete_dependencies(&self, file_id: i64) -> Result<()> {
         self.with_conn(|conn| {
@@ -33,6 +145,7 @@ impl IndexStore {
                 "DELETE FROM dependencies WHERE source_file_id = ?1",
                 [file_id],
             )?;
+            Self::bump_generation_impl(conn)?;
             Ok(())
         })
     }
@@ -53,11 +166,12 @@ impl IndexStore {
                         target_path: row.get(2)?,
                         import_name: row.get(3)?,
                         kind: row.get(4)?,
+                        symbols: Vec::new(),
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
 
-            Ok(results)
+            load_dependency_symbols(conn, results)
         })
     }
 
@@ -77,6 +191,8 @@ impl IndexStore {
     ///    via `escape_like()` before being used as bind values, preventing
     ///    unintended wildcard matching.
     pub fn get_dependents(&self, target_path: &str) -> Result<Vec<DependencyRecord>> {
+        let target_path = normalize_path(target_path);
+        let target_path = target_path.as_str();
         self.with_conn(|conn| {
             let patterns = Self::build_dependent_patterns(target_path);
 
@@ -116,6 +232,7 @@ impl IndexStore {
                         target_path: row.get(2)?,
                         import_name: row.get(3)?,
                         kind: row.get(4)?,
+                        symbols: Vec::new(),
                     })
                 })?
                 .filter_map(|r| r.ok())
@@ -135,7 +252,7 @@ impl IndexStore {
                 .filter(|r| seen_ids.insert(r.id))
                 .collect();
 
-            Ok(all_results)
+            load_dependency_symbols(conn, all_results)
         })
     }
 
@@ -176,6 +293,27 @@ impl IndexStore {
     }
 }
 
+/// Attach the imported symbols to each dependency record.
+fn load_dependency_symbols(
+    conn: &Connection,
+    mut records: Vec<DependencyRecord>,
+) -> Result<Vec<DependencyRecord>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT name, alias FROM dependency_symbols WHERE dependency_id = ?1 ORDER BY id",
+    )?;
+    for record in &mut records {
+        record.symbols = stmt
+            .query_map([record.id], |row| {
+                Ok(ImportedSymbol {
+                    name: row.get(0)?,
+                    alias: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    Ok(records)
+}
+
 /// Escape special LIKE characters so they are matched literally.
 fn escape_like(s: &str) -> String {
     s.replace('\\', "\\\\")
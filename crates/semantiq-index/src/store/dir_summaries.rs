@@ -0,0 +1,431 @@
+//! Directory summaries for IndexStore.
+//!
+//! A summarization pass aggregates, for each directory of the project, the
+//! files under it: their languages, total lines, most referenced top-level
+//! symbols and the files to read first. Summaries are rebuilt as a whole and
+//! tagged with the index generation they were computed at, so readers can
+//! tell when they fell behind the index.
+
+use super::IndexStore;
+use crate::external::EXTERNAL_PREFIX;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tracing::debug;
+
+/// Metadata key holding the index generation the summaries were computed at
+const SUMMARIES_GENERATION_KEY: &str = "dir_summaries_generation";
+
+/// Top-level symbols listed per directory
+const MAX_TOP_SYMBOLS: usize = 10;
+
+/// Entry files listed per directory
+const MAX_ENTRY_FILES: usize = 5;
+
+/// Symbol kinds that can be listed among the top symbols of a directory
+const TOP_SYMBOL_KINDS: &str =
+    "'function', 'class', 'struct', 'enum', 'interface', 'trait', 'type', 'table'";
+
+/// File names (without extension) of conventional entry points
+const ENTRY_FILE_STEMS: [&str; 9] = [
+    "main", "lib", "index", "mod", "__init__", "__main__", "app", "server", "cli",
+];
+
+/// Number of indexed files of a language under a directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageShare {
+    pub language: String,
+    pub file_count: usize,
+}
+
+/// A top-level symbol and how often it is referenced across the index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopSymbol {
+    pub name: String,
+    pub kind: String,
+    pub file_path: String,
+    /// Occurrences of the name that are not definitions
+    pub references: usize,
+}
+
+/// Aggregates of the indexed files under a directory, subdirectories included
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectorySummary {
+    /// Directory path relative to the project root ("." for the root)
+    pub path: String,
+    pub file_count: usize,
+    /// Lines of the files, up to the last line of their chunks
+    pub line_count: usize,
+    /// Files per language, most files first
+    pub languages: Vec<LanguageShare>,
+    /// Most referenced top-level symbols defined under the directory
+    pub top_symbols: Vec<TopSymbol>,
+    /// Files to read first: conventional entry points closest to the
+    /// directory, then the files defining the most referenced symbols
+    pub entry_files: Vec<String>,
+}
+
+/// An indexed file, as seen by the summarization pass
+struct SummarizedFile {
+    path: String,
+    language: String,
+    line_count: usize,
+    /// Top-level symbols, most referenced first
+    symbols: Vec<TopSymbol>,
+    /// References to all of the top-level symbols
+    references: usize,
+}
+
+impl IndexStore {
+    /// Rebuild the summaries of every directory from the index. Returns the
+    /// number of directories summarized.
+    ///
+    /// Summaries are derived data: rebuilding them leaves the index
+    /// generation alone.
+    pub fn refresh_dir_summaries(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            conn.execute("BEGIN IMMEDIATE", [])?;
+
+            let result = (|| -> Result<usize> {
+                let generation = Self::index_generation_impl(conn)?;
+                let files = summarized_files(conn)?;
+                let summaries = summarize_directories(&files);
+
+                conn.execute("DELETE FROM dir_summaries", [])?;
+                let mut stmt = conn.prepare_cached(
+                    "INSERT INTO dir_summaries
+                     (path, parent, file_count, line_count, languages, top_symbols, entry_files)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?;
+                for summary in &summaries {
+                    stmt.execute(params![
+                        summary.path,
+                        parent_directory(&summary.path),
+                        summary.file_count as i64,
+                        summary.line_count as i64,
+                        serde_json::to_string(&summary.languages)?,
+                        serde_json::to_string(&summary.top_symbols)?,
+                        serde_json::to_string(&summary.entry_files)?,
+                    ])?;
+                }
+
+                conn.execute(
+                    "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                    params![SUMMARIES_GENERATION_KEY, generation.to_string()],
+                )?;
+                Ok(summaries.len())
+            })();
+
+            match result {
+                Ok(count) => {
+                    conn.execute("COMMIT", [])?;
+                    debug!("Summarized {} directories", count);
+                    Ok(count)
+                }
+                Err(e) => {
+                    let _ = conn.execute("ROLLBACK", []);
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    /// Whether the directory summaries were computed at the current index
+    /// generation.
+    pub fn dir_summaries_current(&self) -> Result<bool> {
+        self.with_conn(|conn| {
+            let summarized: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM metadata WHERE key = ?1",
+                    [SUMMARIES_GENERATION_KEY],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let generation = Self::index_generation_impl(conn)?;
+            Ok(summarized.and_then(|v| v.parse().ok()) == Some(generation))
+        })
+    }
+
+    /// Get the summary of a directory ("." for the project root), `None` if
+    /// no indexed file is under it.
+    pub fn get_dir_summary(&self, path: &str) -> Result<Option<DirectorySummary>> {
+        self.with_conn(|conn| {
+            let row = conn
+                .query_row(
+                    "SELECT path, file_count, line_count, languages, top_symbols, entry_files
+                     FROM dir_summaries WHERE path = ?1",
+                    [path],
+                    summary_columns,
+                )
+                .optional()?;
+            row.map(summary_from_columns).transpose()
+        })
+    }
+
+    /// Get the summaries of the direct subdirectories of a directory, most
+    /// files first.
+    pub fn get_subdirectory_summaries(&self, path: &str) -> Result<Vec<DirectorySummary>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT path, file_count, line_count, languages, top_symbols, entry_files
+                 FROM dir_summaries WHERE parent = ?1
+                 ORDER BY file_count DESC, path ASC",
+            )?;
+            let rows = stmt
+                .query_map([path], summary_columns)?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter().map(summary_from_columns).collect()
+        })
+    }
+}
+
+/// Columns of a `dir_summaries` row, with the lists still in JSON
+type SummaryColumns = (String, i64, i64, String, String, String);
+
+fn summary_columns(row: &rusqlite::Row) -> rusqlite::Result<SummaryColumns> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+fn summary_from_columns(columns: SummaryColumns) -> Result<DirectorySummary> {
+    let (path, file_count, line_count, languages, top_symbols, entry_files) = columns;
+    Ok(DirectorySummary {
+        path,
+        file_count: file_count as usize,
+        line_count: line_count as usize,
+        languages: serde_json::from_str(&languages)?,
+        top_symbols: serde_json::from_str(&top_symbols)?,
+        entry_files: serde_json::from_str(&entry_files)?,
+    })
+}
+
+/// Read the project files of the index with their top-level symbols
+fn summarized_files(conn: &Connection) -> Result<Vec<SummarizedFile>> {
+    // References of a name are its occurrences minus its definitions
+    let mut references: HashMap<String, i64> = HashMap::new();
+    {
+        let mut stmt = conn.prepare("SELECT name, SUM(count) FROM occurrences GROUP BY name")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (name, count): (String, i64) = row?;
+            references.insert(name, count);
+        }
+        let mut stmt = conn.prepare("SELECT name, COUNT(*) FROM symbols GROUP BY name")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (name, definitions): (String, i64) = row?;
+            if let Some(count) = references.get_mut(&name) {
+                *count -= definitions;
+            }
+        }
+    }
+
+    let mut files: BTreeMap<i64, SummarizedFile> = BTreeMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.path, COALESCE(f.language, 'unknown'),
+                    (SELECT MAX(end_line) FROM chunks c WHERE c.file_id = f.id)
+             FROM files f",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, path, language, lines) = row?;
+            if path.starts_with(EXTERNAL_PREFIX) {
+                continue;
+            }
+            files.insert(
+                id,
+                SummarizedFile {
+                    path,
+                    language,
+                    line_count: lines.unwrap_or(0).max(0) as usize,
+                    symbols: Vec::new(),
+                    references: 0,
+                },
+            );
+        }
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT file_id, name, kind FROM symbols
+         WHERE parent IS NULL AND kind IN ({TOP_SYMBOL_KINDS})"
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (file_id, name, kind) = row?;
+        let Some(file) = files.get_mut(&file_id) else {
+            continue;
+        };
+        let count = references.get(&name).copied().unwrap_or(0).max(0) as usize;
+        file.references += count;
+        file.symbols.push(TopSymbol {
+            name,
+            kind,
+            file_path: file.path.clone(),
+            references: count,
+        });
+    }
+
+    let mut files: Vec<SummarizedFile> = files.into_values().collect();
+    for file in &mut files {
+        rank_symbols(&mut file.symbols);
+    }
+    Ok(files)
+}
+
+/// Sort symbols most referenced first and keep the top ones
+fn rank_symbols(symbols: &mut Vec<TopSymbol>) {
+    symbols.sort_by(|a, b| {
+        b.references
+            .cmp(&a.references)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    symbols.truncate(MAX_TOP_SYMBOLS);
+}
+
+/// Summaries of every directory holding one of `files`, ordered by path
+fn summarize_directories(files: &[SummarizedFile]) -> Vec<DirectorySummary> {
+    let mut by_dir: BTreeMap<String, Vec<&SummarizedFile>> = BTreeMap::new();
+    for file in files {
+        for directory in ancestor_directories(&file.path) {
+            by_dir.entry(directory).or_default().push(file);
+        }
+    }
+
+    by_dir
+        .into_iter()
+        .map(|(path, files)| summarize_directory(path, &files))
+        .collect()
+}
+
+fn summarize_directory(path: String, files: &[&SummarizedFile]) -> DirectorySummary {
+    let mut languages: BTreeMap<&str, usize> = BTreeMap::new();
+    for file in files {
+        *languages.entry(&file.language).or_default() += 1;
+    }
+    let mut languages: Vec<LanguageShare> = languages
+        .into_iter()
+        .map(|(language, file_count)| LanguageShare {
+            language: language.to_string(),
+            file_count,
+        })
+        .collect();
+    languages.sort_by_key(|l| std::cmp::Reverse(l.file_count));
+
+    let mut top_symbols: Vec<TopSymbol> = files
+        .iter()
+        .flat_map(|file| file.symbols.iter().cloned())
+        .collect();
+    rank_symbols(&mut top_symbols);
+
+    DirectorySummary {
+        file_count: files.len(),
+        line_count: files.iter().map(|file| file.line_count).sum(),
+        languages,
+        top_symbols,
+        entry_files: entry_files(files),
+        path,
+    }
+}
+
+/// Conventional entry points, shallowest first, then the files whose
+/// symbols are referenced the most
+fn entry_files(files: &[&SummarizedFile]) -> Vec<String> {
+    let mut entry_points: Vec<&str> = files
+        .iter()
+        .map(|file| file.path.as_str())
+        .filter(|path| is_entry_point(path))
+        .collect();
+    entry_points.sort_by_key(|path| (path.matches('/').count(), *path));
+
+    let mut referenced: Vec<&SummarizedFile> = files
+        .iter()
+        .copied()
+        .filter(|file| file.references > 0 && !is_entry_point(&file.path))
+        .collect();
+    referenced.sort_by(|a, b| {
+        b.references
+            .cmp(&a.references)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    entry_points
+        .into_iter()
+        .chain(referenced.into_iter().map(|file| file.path.as_str()))
+        .take(MAX_ENTRY_FILES)
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_entry_point(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    ENTRY_FILE_STEMS.contains(&stem)
+}
+
+/// "." and every directory containing the relative file `path`
+fn ancestor_directories(path: &str) -> Vec<String> {
+    let mut directories = vec![".".to_string()];
+    let mut end = 0;
+    while let Some(offset) = path[end..].find('/') {
+        end += offset;
+        directories.push(path[..end].to_string());
+        end += 1;
+    }
+    directories
+}
+
+/// Directory containing `path`, `None` for the project root
+fn parent_directory(path: &str) -> Option<&str> {
+    if path == "." {
+        return None;
+    }
+    Some(path.rsplit_once('/').map_or(".", |(parent, _)| parent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_paths() {
+        assert_eq!(
+            ancestor_directories("src/http/api.rs"),
+            vec![".", "src", "src/http"]
+        );
+        assert_eq!(ancestor_directories("build.rs"), vec!["."]);
+        assert_eq!(parent_directory("src/http"), Some("src"));
+        assert_eq!(parent_directory("src"), Some("."));
+        assert_eq!(parent_directory("."), None);
+    }
+
+    #[test]
+    fn test_entry_points() {
+        assert!(is_entry_point("src/main.rs"));
+        assert!(is_entry_point("pkg/__init__.py"));
+        assert!(is_entry_point("web/index.ts"));
+        assert!(!is_entry_point("web/index.test.ts"));
+        assert!(!is_entry_point("src/domain.rs"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Chunk distinctiveness for IndexStore.
+//!
+//! Generated code and repeated boilerplate (license headers, derive blocks,
+//! re-exports) are close to many queries, so they crowd unique code out of
+//! semantic results. A pass after indexing gives each chunk a weight, like
+//! an IDF, that semantic scores are multiplied by:
+//!
+//! - chunks whose content, whitespace aside, appears several times in the
+//!   index are weighted down by the log of their duplicate count
+//! - chunks made of words found in most chunks are weighted down by the
+//!   mean inverse document frequency of their words, read from the full-text
+//!   index of chunks
+//!
+//! Weights are derived data, rebuilt as a whole like directory summaries.
+//! Chunks written since the last pass keep the neutral weight of 1.0.
+
+use super::IndexStore;
+use super::compression::read_content;
+use super::scan::read_page;
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Lowest weight of a chunk
+const MIN_DISTINCTIVENESS: f32 = 0.3;
+
+/// Weight lost per natural log of the duplicate count: a chunk found twice
+/// weighs 0.74, ten times 0.46
+const DUPLICATE_PENALTY: f32 = 0.5;
+
+/// Weight of the chunks made of the most common words, those of the most
+/// distinctive chunks being 1.0
+const COMMON_WORDS_WEIGHT: f32 = 0.7;
+
+/// Chunks from which word rarity is measured; below it, only duplicates are
+/// weighted down
+const MIN_RARITY_CHUNKS: usize = 20;
+
+/// Quantile of the mean word rarity of chunks taken as fully distinctive
+const RARITY_REFERENCE_QUANTILE: f64 = 0.9;
+
+/// Hash of a chunk's content with runs of whitespace collapsed, so that
+/// duplicates indented differently are counted together.
+pub(super) fn chunk_content_hash(content: &str) -> String {
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    IndexStore::hash_content(&normalized)
+}
+
+/// Weight of a chunk found `duplicates` times in the index.
+fn duplicate_factor(duplicates: usize) -> f32 {
+    1.0 / (1.0 + DUPLICATE_PENALTY * (duplicates.max(1) as f32).ln())
+}
+
+/// Weight of a chunk from the mean rarity of its words, relative to the
+/// `reference` rarity of distinctive chunks.
+fn rarity_factor(rarity: f64, reference: f64) -> f32 {
+    if reference <= 0.0 {
+        return 1.0;
+    }
+    let relative = (rarity / reference).min(1.0) as f32;
+    COMMON_WORDS_WEIGHT + (1.0 - COMMON_WORDS_WEIGHT) * relative
+}
+
+impl IndexStore {
+    /// Recompute the distinctiveness of every chunk from its duplicate count
+    /// and the rarity of its words across the index. Returns the number of
+    /// chunks weighted below 1.0.
+    ///
+    /// Weights are derived data: computing them leaves the index generation
+    /// alone.
+    pub fn refresh_chunk_distinctiveness(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            let codec = self.chunk_codec_for(conn)?;
+            conn.execute("BEGIN IMMEDIATE", [])?;
+
+            let result = (|| -> Result<usize> {
+                // Chunks written before schema 11 have no hash yet
+                let mut update_hash =
+                    conn.prepare_cached("UPDATE chunks SET content_hash = ?2 WHERE id = ?1")?;
+                let mut after = 0;
+                loop {
+                    let missing: Vec<(i64, String)> = read_page(
+                        conn,
+                        "SELECT id, content FROM chunks
+                         WHERE content_hash IS NULL AND id > ?1 ORDER BY id",
+                        after,
+                        self.scan_memory(),
+                        |row| Ok((row.get(0)?, read_content(&codec, row, 1)?)),
+                        |(_, content)| content.len(),
+                    )?;
+                    let Some(&(last, _)) = missing.last() else {
+                        break;
+                    };
+                    after = last;
+                    for (id, content) in &missing {
+                        update_hash.execute(params![id, chunk_content_hash(content)])?;
+                    }
+                }
+
+                let duplicates = chunk_duplicates(conn)?;
+                let rarity = if duplicates.len() >= MIN_RARITY_CHUNKS {
+                    chunk_word_rarity(conn, duplicates.len())?
+                } else {
+                    HashMap::new()
+                };
+                let reference = rarity_reference(&rarity);
+
+                let mut update =
+                    conn.prepare_cached("UPDATE chunks SET distinctiveness = ?2 WHERE id = ?1")?;
+                let mut weighted = 0;
+                for (&id, &(count, current)) in &duplicates {
+                    let mut weight = duplicate_factor(count);
+                    if let Some(&rarity) = rarity.get(&id) {
+                        weight *= rarity_factor(rarity, reference);
+                    }
+                    let weight = weight.max(MIN_DISTINCTIVENESS);
+                    if weight < 1.0 {
+                        weighted += 1;
+                    }
+                    if (weight - current).abs() > 1e-3 {
+                        update.execute(params![id, weight])?;
+                    }
+                }
+                Ok(weighted)
+            })();
+
+            match result {
+                Ok(weighted) => {
+                    conn.execute("COMMIT", [])?;
+                    debug!("Weighted down {} chunks", weighted);
+                    Ok(weighted)
+                }
+                Err(e) => {
+                    let _ = conn.execute("ROLLBACK", []);
+                    Err(e)
+                }
+            }
+        })
+    }
+}
+
+/// Duplicate count and current weight of every chunk, by chunk id.
+fn chunk_duplicates(conn: &Connection) -> Result<HashMap<i64, (usize, f32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, d.count, c.distinctiveness
+         FROM chunks c
+         JOIN (SELECT content_hash, COUNT(*) AS count FROM chunks GROUP BY content_hash) d
+           ON d.content_hash = c.content_hash",
+    )?;
+    let duplicates = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                (row.get::<_, i64>(1)? as usize, row.get::<_, f64>(2)? as f32),
+            ))
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(duplicates)
+}
+
+/// Mean inverse document frequency of the distinct words of each chunk,
+/// among `chunk_count` chunks, read from the vocabulary of the full-text
+/// index of chunk contents.
+fn chunk_word_rarity(conn: &Connection, chunk_count: usize) -> Result<HashMap<i64, f64>> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS temp.chunks_fts_terms
+             USING fts5vocab(main, chunks_fts, row);
+         CREATE VIRTUAL TABLE IF NOT EXISTS temp.chunks_fts_instances
+             USING fts5vocab(main, chunks_fts, instance);",
+    )?;
+
+    let total = chunk_count as f64;
+    let idf: HashMap<String, f64> = conn
+        .prepare("SELECT term, doc FROM temp.chunks_fts_terms")?
+        .query_map([], |row| {
+            let documents: i64 = row.get(1)?;
+            Ok((row.get(0)?, (total / documents.max(1) as f64).ln().max(0.0)))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    // Instances come ordered by term, then chunk: repeated words of a chunk
+    // are next to each other
+    let mut stmt =
+        conn.prepare("SELECT term, doc FROM temp.chunks_fts_instances WHERE col = 'content'")?;
+    let mut rows = stmt.query([])?;
+    let mut sums: HashMap<i64, (f64, usize)> = HashMap::new();
+    let mut previous: Option<(String, i64)> = None;
+    while let Some(row) = rows.next()? {
+        let term: String = row.get(0)?;
+        let chunk: i64 = row.get(1)?;
+        if previous
+            .as_ref()
+            .is_some_and(|(t, c)| *c == chunk && *t == term)
+        {
+            continue;
+        }
+        let entry = sums.entry(chunk).or_default();
+        entry.0 += idf.get(&term).copied().unwrap_or(0.0);
+        entry.1 += 1;
+        previous = Some((term, chunk));
+    }
+
+    Ok(sums
+        .into_iter()
+        .map(|(chunk, (sum, words))| (chunk, sum / words as f64))
+        .collect())
+}
+
+/// Mean word rarity taken as fully distinctive: the
+/// [`RARITY_REFERENCE_QUANTILE`] of the chunks, so that a few chunks of
+/// unique words do not weigh every other chunk down.
+fn rarity_reference(rarity: &HashMap<i64, f64>) -> f64 {
+    if rarity.is_empty() {
+        return 0.0;
+    }
+    let mut values: Vec<f64> = rarity.values().copied().collect();
+    values.sort_by(f64::total_cmp);
+    let index = ((values.len() - 1) as f64 * RARITY_REFERENCE_QUANTILE).round() as usize;
+    values[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_content_hash_ignores_whitespace() {
+        assert_eq!(
+            chunk_content_hash("#[derive(Debug)]\nstruct A;"),
+            chunk_content_hash("  #[derive(Debug)]\n    struct  A;\n")
+        );
+        assert_ne!(
+            chunk_content_hash("struct A;"),
+            chunk_content_hash("struct B;")
+        );
+    }
+
+    #[test]
+    fn test_weight_factors() {
+        assert_eq!(duplicate_factor(1), 1.0);
+        assert!((duplicate_factor(2) - 0.743).abs() < 1e-3);
+        assert!(duplicate_factor(10) < duplicate_factor(2));
+
+        assert_eq!(rarity_factor(3.0, 2.0), 1.0);
+        assert_eq!(rarity_factor(0.0, 2.0), COMMON_WORDS_WEIGHT);
+        assert_eq!(rarity_factor(1.0, 0.0), 1.0);
+    }
+}
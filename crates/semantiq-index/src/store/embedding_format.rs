@@ -0,0 +1,188 @@
+//! Embedding storage format (float32 or int8) for IndexStore.
+
+use super::IndexStore;
+use super::embedding_migration::load_migration_target;
+use super::scan::read_page;
+use crate::quantization::EmbeddingFormat;
+use crate::schema::EMBEDDING_DIMENSION;
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::sync::{MutexGuard, PoisonError};
+use tracing::{info, warn};
+
+/// Metadata key holding the embedding storage format
+const EMBEDDING_FORMAT_KEY: &str = "embedding_format";
+
+/// Read the persisted embedding format, defaulting to float32 for existing databases.
+pub(crate) fn load_embedding_format(conn: &Connection) -> Result<EmbeddingFormat> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM metadata WHERE key = ?1",
+            [EMBEDDING_FORMAT_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match value {
+        None => Ok(EmbeddingFormat::Float32),
+        Some(v) => EmbeddingFormat::parse(&v)
+            .ok_or_else(|| anyhow!("Unknown embedding format in database: {}", v)),
+    }
+}
+
+/// SQL inserting a chunk embedding into `chunks_vec` for the given format.
+pub(crate) fn vec_insert_sql(format: EmbeddingFormat) -> &'static str {
+    match format {
+        EmbeddingFormat::Float32 => {
+            "INSERT OR REPLACE INTO chunks_vec(chunk_id, embedding) VALUES (?1, ?2)"
+        }
+        EmbeddingFormat::Int8 => {
+            "INSERT OR REPLACE INTO chunks_vec(chunk_id, embedding) VALUES (?1, vec_int8(?2))"
+        }
+    }
+}
+
+/// SQL running a KNN query against `chunks_vec` for the given format.
+pub(crate) fn vec_search_sql(format: EmbeddingFormat) -> &'static str {
+    match format {
+        EmbeddingFormat::Float32 => {
+            "SELECT chunk_id, distance
+             FROM chunks_vec
+             WHERE embedding MATCH ?1
+             ORDER BY distance
+             LIMIT ?2"
+        }
+        EmbeddingFormat::Int8 => {
+            "SELECT chunk_id, distance
+             FROM chunks_vec
+             WHERE embedding MATCH vec_int8(?1)
+             ORDER BY distance
+             LIMIT ?2"
+        }
+    }
+}
+
+impl IndexStore {
+    /// Get the storage format of chunk embeddings.
+    pub fn embedding_format(&self) -> EmbeddingFormat {
+        self.embedding_format
+            .read()
+            .map(|f| *f)
+            .unwrap_or_else(|e| *e.into_inner())
+    }
+
+    /// Convert all stored embeddings to the given format.
+    ///
+    /// Rebuilds the `chunks_vec` table with the matching element type and
+    /// re-encodes the embeddings kept in `chunks`, then vacuums the database to
+    /// release the freed pages. Converting int8 back to float32 keeps the
+    /// quantization error. A pending model migration starts over. Returns the
+    /// number of converted embeddings.
+    pub fn set_embedding_format(&self, format: EmbeddingFormat) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        let current = self.embedding_format();
+        if current == format {
+            return Ok(0);
+        }
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<usize> {
+            conn.execute_batch(&format!(
+                "DROP TABLE IF EXISTS chunks_vec;
+                 CREATE VIRTUAL TABLE chunks_vec USING vec0(
+                     chunk_id INTEGER PRIMARY KEY,
+                     embedding {}[{EMBEDDING_DIMENSION}]
+                 );",
+                format.vec_column_type()
+            ))?;
+
+            let mut update_stmt = conn.prepare("UPDATE chunks SET embedding = ?1 WHERE id = ?2")?;
+            let mut insert_stmt = conn.prepare(vec_insert_sql(format))?;
+
+            // Converted a page at a time: rows already rewritten are behind
+            // the last chunk id read
+            let mut converted = 0;
+            let mut after = 0;
+            loop {
+                let page: Vec<(i64, Vec<u8>)> = read_page(
+                    &conn,
+                    "SELECT id, embedding FROM chunks
+                     WHERE embedding IS NOT NULL AND id > ?1 ORDER BY id",
+                    after,
+                    self.scan_memory(),
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                    |(_, bytes): &(i64, Vec<u8>)| bytes.len(),
+                )?;
+                let Some(&(last, _)) = page.last() else {
+                    break;
+                };
+                after = last;
+                converted += page.len();
+
+                for (chunk_id, bytes) in &page {
+                    let embedding = current.decode(bytes);
+                    if embedding.len() != EMBEDDING_DIMENSION {
+                        warn!(
+                            "Dropping embedding of chunk {} with unexpected dimension {}",
+                            chunk_id,
+                            embedding.len()
+                        );
+                        update_stmt.execute(params![Option::<Vec<u8>>::None, chunk_id])?;
+                        continue;
+                    }
+                    let encoded = format.encode(&embedding);
+                    update_stmt.execute(params![encoded, chunk_id])?;
+                    insert_stmt.execute(params![chunk_id, encoded])?;
+                }
+            }
+
+            Self::convert_file_summaries_impl(&conn, current, format)?;
+
+            // Vectors of a pending model migration are computed again
+            if let Some(target) = load_migration_target(&conn)? {
+                Self::start_embedding_migration_impl(&conn, format, target)?;
+            }
+
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                [EMBEDDING_FORMAT_KEY, format.as_str()],
+            )?;
+            Self::bump_generation_impl(&conn)?;
+
+            Ok(converted)
+        })();
+
+        match result {
+            Ok(count) => {
+                conn.execute("COMMIT", [])?;
+                match self.embedding_format.write() {
+                    Ok(mut f) => *f = format,
+                    Err(e) => *e.into_inner() = format,
+                }
+                info!(
+                    "Converted {} embeddings from {} to {}",
+                    count,
+                    current.as_str(),
+                    format.as_str()
+                );
+
+                // Release the pages freed by the smaller vectors
+                if let Err(e) = conn.execute_batch("VACUUM") {
+                    warn!("Failed to vacuum database after conversion: {}", e);
+                }
+                Ok(count)
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+}
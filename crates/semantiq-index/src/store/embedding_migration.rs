@@ -0,0 +1,348 @@
+//! Re-embedding of the index with a new embedding model for IndexStore.
+//!
+//! Files, symbols and chunks do not depend on the embedding model, so a model
+//! change only embeds the chunks again. The new vectors are written to
+//! `chunks_vec_next`, next to the `chunks_vec` table searches keep using, and
+//! [`IndexStore::complete_embedding_migration`] replaces the old vectors with
+//! them in one transaction.
+
+use super::IndexStore;
+use super::chunks::{parse_symbols_json, read_token_fit};
+use super::compression::read_content;
+use super::embedding_format::vec_insert_sql;
+use super::embedding_model::{EMBEDDING_MODEL_KEY, load_embedding_model};
+use super::scan::read_page;
+use crate::quantization::EmbeddingFormat;
+use crate::schema::{ChunkRecord, EMBEDDING_DIMENSION};
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, OptionalExtension, params};
+use semantiq_embeddings::EmbeddingModelKind;
+use serde::Serialize;
+use std::sync::{MutexGuard, PoisonError};
+use tracing::info;
+
+/// Metadata key holding the model the index is being re-embedded with
+const MIGRATION_TARGET_KEY: &str = "embedding_migration";
+
+/// Metadata key holding the id of the last chunk re-embedded
+const MIGRATION_CURSOR_KEY: &str = "embedding_migration_cursor";
+
+/// Progress of a switch to another embedding model
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EmbeddingMigration {
+    /// Model searches use until the switch
+    pub from: EmbeddingModelKind,
+    /// Model the chunks are embedded with again
+    pub to: EmbeddingModelKind,
+    /// Chunks embedded with the new model
+    pub embedded: usize,
+    /// Chunks in the index
+    pub total: usize,
+}
+
+impl EmbeddingMigration {
+    /// Share of the chunks embedded with the new model, between 0 and 1
+    pub fn coverage(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.embedded as f64 / self.total as f64
+        }
+    }
+}
+
+/// SQL inserting a chunk embedding into `chunks_vec_next` for the given format.
+fn vec_next_insert_sql(format: EmbeddingFormat) -> &'static str {
+    match format {
+        EmbeddingFormat::Float32 => {
+            "INSERT OR REPLACE INTO chunks_vec_next(chunk_id, embedding) VALUES (?1, ?2)"
+        }
+        EmbeddingFormat::Int8 => {
+            "INSERT OR REPLACE INTO chunks_vec_next(chunk_id, embedding) VALUES (?1, vec_int8(?2))"
+        }
+    }
+}
+
+/// Read the model of the pending migration, if any.
+pub(super) fn load_migration_target(conn: &Connection) -> Result<Option<EmbeddingModelKind>> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM metadata WHERE key = ?1",
+            [MIGRATION_TARGET_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    value
+        .map(|v| {
+            EmbeddingModelKind::parse(&v)
+                .ok_or_else(|| anyhow!("Unknown embedding model in migration: {}", v))
+        })
+        .transpose()
+}
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction: create an empty
+    /// `chunks_vec_next` table and record `target` as the pending migration.
+    pub(crate) fn start_embedding_migration_impl(
+        conn: &Connection,
+        format: EmbeddingFormat,
+        target: EmbeddingModelKind,
+    ) -> Result<()> {
+        conn.execute_batch(&format!(
+            "DROP TABLE IF EXISTS chunks_vec_next;
+             CREATE VIRTUAL TABLE chunks_vec_next USING vec0(
+                 chunk_id INTEGER PRIMARY KEY,
+                 embedding {}[{EMBEDDING_DIMENSION}]
+             );",
+            format.vec_column_type()
+        ))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2), (?3, '0')",
+            [MIGRATION_TARGET_KEY, target.as_str(), MIGRATION_CURSOR_KEY],
+        )?;
+        Ok(())
+    }
+
+    /// Internal implementation for use within a transaction: drop the pending
+    /// migration and the vectors it computed.
+    pub(crate) fn cancel_embedding_migration_impl(conn: &Connection) -> Result<()> {
+        conn.execute_batch("DROP TABLE IF EXISTS chunks_vec_next;")?;
+        conn.execute(
+            "DELETE FROM metadata WHERE key IN (?1, ?2)",
+            [MIGRATION_TARGET_KEY, MIGRATION_CURSOR_KEY],
+        )?;
+        Ok(())
+    }
+
+    /// Internal implementation for use within a transaction, after the index
+    /// was cleared: nothing is left to re-embed, so the pending migration's
+    /// model is recorded right away and files are embedded with it.
+    pub(crate) fn adopt_migration_target_impl(conn: &Connection) -> Result<()> {
+        if let Some(target) = load_migration_target(conn)? {
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                [EMBEDDING_MODEL_KEY, target.as_str()],
+            )?;
+            Self::cancel_embedding_migration_impl(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Internal implementation for use within a transaction: forget the
+    /// re-embedded vectors of newly inserted chunks, whose ids may have been
+    /// used by deleted chunks. They are embedded again later.
+    pub(crate) fn forget_migrated_chunks_impl(conn: &Connection, chunk_ids: &[i64]) -> Result<()> {
+        if chunk_ids.is_empty() || load_migration_target(conn)?.is_none() {
+            return Ok(());
+        }
+        let mut stmt = conn.prepare_cached("DELETE FROM chunks_vec_next WHERE chunk_id = ?1")?;
+        for chunk_id in chunk_ids {
+            stmt.execute([chunk_id])?;
+        }
+        Ok(())
+    }
+
+    /// Get the progress of the pending switch to another embedding model.
+    pub fn embedding_migration(&self) -> Result<Option<EmbeddingMigration>> {
+        self.with_conn(|conn| {
+            let Some(to) = load_migration_target(conn)? else {
+                return Ok(None);
+            };
+            let from = load_embedding_model(conn)?.unwrap_or_default();
+            let (embedded, total): (i64, i64) = conn.query_row(
+                "SELECT
+                    (SELECT COUNT(*) FROM chunks_vec_next
+                     WHERE chunk_id IN (SELECT id FROM chunks)),
+                    (SELECT COUNT(*) FROM chunks
+                     WHERE file_id NOT IN (SELECT id FROM files WHERE embeddings_evicted = 1))",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            Ok(Some(EmbeddingMigration {
+                from,
+                to,
+                embedded: embedded as usize,
+                total: total as usize,
+            }))
+        })
+    }
+
+    /// Get up to `limit` chunks not re-embedded yet by the pending migration,
+    /// in id order. Empty when no migration is pending.
+    pub fn chunks_to_migrate(&self, limit: usize) -> Result<Vec<ChunkRecord>> {
+        self.with_conn(|conn| {
+            if load_migration_target(conn)?.is_none() {
+                return Ok(Vec::new());
+            }
+            let codec = self.chunk_codec_for(conn)?;
+            let mut stmt = conn.prepare(
+                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, symbol_id,
+                        token_count, embedding_windows, embedding_truncated, distinctiveness, title
+                 FROM chunks
+                 WHERE id > (SELECT CAST(value AS INTEGER) FROM metadata WHERE key = ?1)
+                     AND file_id NOT IN (SELECT id FROM files WHERE embeddings_evicted = 1)
+                 ORDER BY id
+                 LIMIT ?2",
+            )?;
+
+            let results = stmt
+                .query_map(params![MIGRATION_CURSOR_KEY, limit as i64], |row| {
+                    let symbols_json: String = row.get(7)?;
+                    Ok(ChunkRecord {
+                        id: row.get(0)?,
+                        file_id: row.get(1)?,
+                        content: read_content(&codec, row, 2)?,
+                        start_line: row.get(3)?,
+                        end_line: row.get(4)?,
+                        start_byte: row.get(5)?,
+                        end_byte: row.get(6)?,
+                        symbols: parse_symbols_json(&symbols_json),
+                        embedding: None,
+                        symbol_id: row.get(8)?,
+                        token_fit: read_token_fit(row, 9)?,
+                        distinctiveness: row.get(12)?,
+                        title: row.get(13)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(results)
+        })
+    }
+
+    /// Store embeddings computed with the new model, and mark every chunk up
+    /// to `last_chunk_id` as processed, including those that failed to embed.
+    pub fn store_migrated_embeddings(
+        &self,
+        last_chunk_id: i64,
+        embeddings: &[(i64, Vec<f32>)],
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        if load_migration_target(&conn)?.is_none() {
+            return Err(anyhow!("No embedding migration is pending"));
+        }
+        let format = self.embedding_format();
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<()> {
+            let mut stmt = conn.prepare(vec_next_insert_sql(format))?;
+            for (chunk_id, embedding) in embeddings {
+                stmt.execute(params![chunk_id, format.encode(embedding)])?;
+            }
+            conn.execute(
+                "UPDATE metadata SET value = MAX(CAST(value AS INTEGER), ?2) WHERE key = ?1",
+                params![MIGRATION_CURSOR_KEY, last_chunk_id],
+            )?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])?;
+                Ok(())
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+
+    /// Switch searches to the vectors of the pending migration.
+    ///
+    /// In one transaction, `chunks_vec` is rebuilt from `chunks_vec_next`,
+    /// read a page of at most [`Self::scan_memory`] bytes at a time, and
+    /// the new model is recorded. Chunks without a new vector, and all file
+    /// summaries, are left without an embedding until they are embedded
+    /// again. Returns the new model, or
+    /// `None` when no migration is pending.
+    pub fn complete_embedding_migration(&self) -> Result<Option<EmbeddingModelKind>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        let Some(target) = load_migration_target(&conn)? else {
+            return Ok(None);
+        };
+        let format = self.embedding_format();
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<usize> {
+            conn.execute_batch(&format!(
+                "UPDATE chunks SET embedding = NULL;
+                 DROP TABLE IF EXISTS chunks_vec;
+                 CREATE VIRTUAL TABLE chunks_vec USING vec0(
+                     chunk_id INTEGER PRIMARY KEY,
+                     embedding {}[{EMBEDDING_DIMENSION}]
+                 );",
+                format.vec_column_type()
+            ))?;
+
+            let mut update_stmt = conn.prepare("UPDATE chunks SET embedding = ?1 WHERE id = ?2")?;
+            let mut insert_stmt = conn.prepare(vec_insert_sql(format))?;
+
+            // Copied a page at a time, in order of chunk id
+            let mut switched = 0;
+            let mut after = 0;
+            loop {
+                let page: Vec<(i64, Vec<u8>)> = read_page(
+                    &conn,
+                    "SELECT c.id, v.embedding FROM chunks c
+                     JOIN chunks_vec_next v ON v.chunk_id = c.id
+                     WHERE c.id > ?1 ORDER BY c.id",
+                    after,
+                    self.scan_memory(),
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                    |(_, bytes): &(i64, Vec<u8>)| bytes.len(),
+                )?;
+                let Some(&(last, _)) = page.last() else {
+                    break;
+                };
+                after = last;
+                switched += page.len();
+
+                for (chunk_id, bytes) in &page {
+                    update_stmt.execute(params![bytes, chunk_id])?;
+                    insert_stmt.execute(params![chunk_id, bytes])?;
+                }
+            }
+
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                [EMBEDDING_MODEL_KEY, target.as_str()],
+            )?;
+            // File summaries are embedded again with the new model afterwards
+            Self::clear_file_summaries_impl(&conn, format)?;
+            Self::cancel_embedding_migration_impl(&conn)?;
+            Self::bump_generation_impl(&conn)?;
+            Ok(switched)
+        })();
+
+        match result {
+            Ok(count) => {
+                conn.execute("COMMIT", [])?;
+                info!(
+                    "Switched to embedding model {} ({} chunks re-embedded)",
+                    target, count
+                );
+                Ok(Some(target))
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Embedding model recorded for IndexStore.
+//!
+//! Vectors from different models live in unrelated spaces, so the model used
+//! to embed the index is recorded and queries must be embedded with it. A new
+//! model is adopted through a migration (see `embedding_migration`).
+
+use super::IndexStore;
+use super::embedding_migration::load_migration_target;
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, OptionalExtension};
+use semantiq_embeddings::EmbeddingModelKind;
+use std::sync::{MutexGuard, PoisonError};
+use tracing::info;
+
+/// Metadata key holding the embedding model
+pub(super) const EMBEDDING_MODEL_KEY: &str = "embedding_model";
+
+/// Read the recorded embedding model, `None` for databases that predate the record.
+pub(super) fn load_embedding_model(conn: &Connection) -> Result<Option<EmbeddingModelKind>> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM metadata WHERE key = ?1",
+            [EMBEDDING_MODEL_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    value
+        .map(|v| {
+            EmbeddingModelKind::parse(&v)
+                .ok_or_else(|| anyhow!("Unknown embedding model in database: {}", v))
+        })
+        .transpose()
+}
+
+impl IndexStore {
+    /// Get the model the index is embedded with.
+    ///
+    /// Databases created before the model was recorded use the default model.
+    pub fn embedding_model(&self) -> Result<EmbeddingModelKind> {
+        self.with_conn(|conn| Ok(load_embedding_model(conn)?.unwrap_or_default()))
+    }
+
+    /// Switch the index to another embedding model.
+    ///
+    /// An index without embeddings records the model right away. Otherwise
+    /// files, symbols and chunks are kept and a migration starts: chunks are
+    /// embedded again with the new model into a second vector table while
+    /// searches keep using the current model, until
+    /// [`IndexStore::complete_embedding_migration`] switches them. Asking for
+    /// the recorded model cancels a pending migration. Returns true if a
+    /// migration was started.
+    pub fn set_embedding_model(&self, model: EmbeddingModelKind) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        let current = load_embedding_model(&conn)?.unwrap_or_default();
+        let pending = load_migration_target(&conn)?;
+        if pending == Some(model) {
+            return Ok(false);
+        }
+        let has_embeddings: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM chunks WHERE embedding IS NOT NULL)",
+            [],
+            |row| row.get(0),
+        )?;
+        let migrate = current != model && has_embeddings;
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<()> {
+            if migrate {
+                Self::start_embedding_migration_impl(&conn, self.embedding_format(), model)?;
+            } else {
+                if pending.is_some() {
+                    Self::cancel_embedding_migration_impl(&conn)?;
+                }
+                conn.execute(
+                    "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                    [EMBEDDING_MODEL_KEY, model.as_str()],
+                )?;
+            }
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])?;
+                if migrate {
+                    info!(
+                        "Embedding model changed ({} -> {}) - re-embedding chunks",
+                        current, model
+                    );
+                } else if let Some(pending) = pending {
+                    info!("Cancelled the switch to embedding model {}", pending);
+                }
+                Ok(migrate)
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+}
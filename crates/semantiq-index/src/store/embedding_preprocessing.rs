@@ -0,0 +1,151 @@
+//! Embedding preprocessing recorded for IndexStore.
+//!
+//! Chunk text goes through the `embeddings.preprocess` pipeline before it is
+//! embedded (see `crate::preprocess`), so the pipeline is recorded with the
+//! index. Changing it re-embeds the chunks through a migration to the
+//! current model; files, symbols and chunks are kept.
+
+use super::IndexStore;
+use super::embedding_migration::load_migration_target;
+use super::embedding_model::load_embedding_model;
+use crate::preprocess::{EmbeddingPreprocessor, PreprocessStep};
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::{MutexGuard, PoisonError};
+use tracing::{info, warn};
+
+/// Metadata key holding the version of the preprocessing pipeline
+const EMBEDDING_PREPROCESSING_KEY: &str = "embedding_preprocessing";
+
+/// Read a recorded metadata value, empty if there is none.
+pub(super) fn load_metadata_value(conn: &Connection, key: &str) -> Result<String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM metadata WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    Ok(value.unwrap_or_default())
+}
+
+/// Read the recorded pipeline, so that chunks written before the
+/// configuration is applied are embedded like the others. Unknown steps,
+/// from a newer version, are left out.
+pub(super) fn load_embedding_preprocessor(conn: &Connection) -> Result<EmbeddingPreprocessor> {
+    let version = load_metadata_value(conn, EMBEDDING_PREPROCESSING_KEY)?;
+    let Some((_, steps)) = version.split_once(':') else {
+        return Ok(EmbeddingPreprocessor::default());
+    };
+    let steps: Vec<PreprocessStep> = steps
+        .split(',')
+        .filter_map(|step| {
+            let parsed = PreprocessStep::parse(step);
+            if parsed.is_none() {
+                warn!("Unknown embedding preprocessing step in database: {}", step);
+            }
+            parsed
+        })
+        .collect();
+    Ok(EmbeddingPreprocessor::new(&steps))
+}
+
+impl IndexStore {
+    /// Get the pipeline chunk text goes through before embedding.
+    pub fn embedding_preprocessor(&self) -> EmbeddingPreprocessor {
+        self.embedding_preprocessor
+            .read()
+            .map(|p| p.clone())
+            .unwrap_or_default()
+    }
+
+    /// Text to embed for the content of a chunk: preprocessed, then put in
+    /// the passage prompt.
+    pub fn embedding_text(&self, content: &str) -> String {
+        let text = match self.embedding_preprocessor.read() {
+            Ok(preprocessor) => preprocessor.apply(content),
+            Err(_) => content.to_string(),
+        };
+        self.passage_text(&text)
+    }
+
+    /// Preprocess chunk text with another pipeline from now on.
+    ///
+    /// An index without embeddings records the pipeline right away.
+    /// Otherwise a migration to the current model starts (or restarts, if
+    /// one is pending, to its model): chunks are embedded again with the new
+    /// pipeline while searches keep using the current vectors (see
+    /// [`IndexStore::set_embedding_model`]). Returns true if a migration was
+    /// started.
+    pub fn set_embedding_preprocessor(&self, preprocessor: &EmbeddingPreprocessor) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        if let Ok(mut current) = self.embedding_preprocessor.write() {
+            *current = preprocessor.clone();
+        }
+        let version = preprocessor.version();
+        let Some(recorded) =
+            self.record_embedding_input(&conn, EMBEDDING_PREPROCESSING_KEY, &version)?
+        else {
+            return Ok(false);
+        };
+        info!(
+            "Embedding preprocessing changed ('{}' -> '{}') - re-embedding chunks",
+            recorded, version
+        );
+        Ok(true)
+    }
+
+    /// Record `value`, something the text of embedded chunks depends on,
+    /// under the metadata `key`. When it changed and chunks are embedded, a
+    /// migration to the current model re-embeds them (see
+    /// [`IndexStore::set_embedding_preprocessor`]); the previous value is
+    /// returned then.
+    pub(super) fn record_embedding_input(
+        &self,
+        conn: &Connection,
+        key: &str,
+        value: &str,
+    ) -> Result<Option<String>> {
+        let recorded = load_metadata_value(conn, key)?;
+        if recorded == value {
+            return Ok(None);
+        }
+        let has_embeddings: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM chunks WHERE embedding IS NOT NULL)",
+            [],
+            |row| row.get(0),
+        )?;
+        let target = match load_migration_target(conn)? {
+            Some(target) => target,
+            None => load_embedding_model(conn)?.unwrap_or_default(),
+        };
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<()> {
+            if has_embeddings {
+                Self::start_embedding_migration_impl(conn, self.embedding_format(), target)?;
+            }
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                [key, value],
+            )?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])?;
+                Ok(has_embeddings.then_some(recorded))
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+}
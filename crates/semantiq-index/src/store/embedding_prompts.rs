@@ -0,0 +1,95 @@
+//! Embedding prompts recorded for IndexStore.
+//!
+//! Chunks and file summaries are embedded in the passage prompt of the
+//! model, search queries in its query prompt (see
+//! `semantiq_embeddings::prompts`). The prompts are recorded with the index,
+//! so that queries are embedded like the passages they are compared with and
+//! so that changing them re-embeds the chunks, like the preprocessing
+//! pipeline.
+
+use super::IndexStore;
+use super::embedding_preprocessing::load_metadata_value;
+use anyhow::{Result, anyhow};
+use rusqlite::Connection;
+use semantiq_embeddings::EmbeddingPrompts;
+use std::sync::{MutexGuard, PoisonError};
+use tracing::{info, warn};
+
+/// Metadata key holding the prompts as JSON, absent for no prompts
+const EMBEDDING_PROMPTS_KEY: &str = "embedding_prompts";
+
+/// Recorded value of `prompts`, empty for none
+fn prompts_value(prompts: &EmbeddingPrompts) -> Result<String> {
+    if prompts.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(serde_json::to_string(prompts)?)
+}
+
+/// Read the recorded prompts, so that chunks written before the
+/// configuration is applied are embedded like the others.
+pub(super) fn load_embedding_prompts(conn: &Connection) -> Result<EmbeddingPrompts> {
+    let value = load_metadata_value(conn, EMBEDDING_PROMPTS_KEY)?;
+    if value.is_empty() {
+        return Ok(EmbeddingPrompts::default());
+    }
+    Ok(serde_json::from_str(&value).unwrap_or_else(|e| {
+        warn!("Invalid embedding prompts in database: {}", e);
+        EmbeddingPrompts::default()
+    }))
+}
+
+impl IndexStore {
+    /// Get the prompts queries and passages are embedded in.
+    pub fn embedding_prompts(&self) -> EmbeddingPrompts {
+        self.embedding_prompts
+            .read()
+            .map(|p| p.clone())
+            .unwrap_or_default()
+    }
+
+    /// Text to embed for a passage, such as a file summary, in the passage
+    /// prompt.
+    pub fn passage_text(&self, text: &str) -> String {
+        match self.embedding_prompts.read() {
+            Ok(prompts) => prompts.passage_text(text),
+            Err(_) => text.to_string(),
+        }
+    }
+
+    /// Text to embed for a search query, in the query prompt.
+    pub fn query_text(&self, query: &str) -> String {
+        match self.embedding_prompts.read() {
+            Ok(prompts) => prompts.query_text(query),
+            Err(_) => query.to_string(),
+        }
+    }
+
+    /// Embed queries and passages in other prompts from now on.
+    ///
+    /// Like [`IndexStore::set_embedding_preprocessor`], chunks already
+    /// embedded are embedded again in the new prompts by a migration to the
+    /// current model. Returns true if a migration was started.
+    pub fn set_embedding_prompts(&self, prompts: &EmbeddingPrompts) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        if let Ok(mut current) = self.embedding_prompts.write() {
+            *current = prompts.clone();
+        }
+        let value = prompts_value(prompts)?;
+        let Some(recorded) = self.record_embedding_input(&conn, EMBEDDING_PROMPTS_KEY, &value)?
+        else {
+            return Ok(false);
+        };
+        info!(
+            "Embedding prompts changed ('{}' -> '{}') - re-embedding chunks",
+            recorded, value
+        );
+        Ok(true)
+    }
+}
@@ -0,0 +1,95 @@
+//! Entry point operations for IndexStore.
+//!
+//! The main functions, HTTP routes, CLI subcommands and library exports of
+//! each file (see `semantiq_parser::entrypoints`), flagged by their kind.
+//! The routes are also recorded with their method and path (see
+//! `store::routes`).
+
+use super::IndexStore;
+use crate::schema::EntrypointRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row, params};
+use semantiq_parser::{Entrypoint, EntrypointKind};
+
+const ENTRYPOINT_COLUMNS: &str =
+    "e.id, e.file_id, f.path, f.language, e.kind, e.name, e.handler, e.start_line, e.end_line";
+
+impl IndexStore {
+    /// Replace the entry points of a file, and its routes.
+    pub fn insert_entrypoints(&self, file_id: i64, entrypoints: &[Entrypoint]) -> Result<()> {
+        self.with_conn(|conn| {
+            Self::insert_entrypoints_impl(conn, file_id, entrypoints)?;
+            Self::bump_generation_impl(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_entrypoints_impl(
+        conn: &Connection,
+        file_id: i64,
+        entrypoints: &[Entrypoint],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM entrypoints WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO entrypoints (file_id, kind, name, handler, start_line, end_line)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for entrypoint in entrypoints {
+            stmt.execute(params![
+                file_id,
+                entrypoint.kind.as_str(),
+                entrypoint.name,
+                entrypoint.handler,
+                entrypoint.start_line as i64,
+                entrypoint.end_line as i64,
+            ])?;
+        }
+        Self::insert_routes_impl(conn, file_id, entrypoints)
+    }
+
+    /// Get the entry points of the given kind, or of every kind, in the
+    /// files under `directory` ("." or `None` for the whole project),
+    /// ordered by kind, file and line.
+    pub fn get_entrypoints(
+        &self,
+        kind: Option<EntrypointKind>,
+        directory: Option<&str>,
+    ) -> Result<Vec<EntrypointRecord>> {
+        let directory = directory.filter(|d| !d.is_empty() && *d != ".");
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {ENTRYPOINT_COLUMNS} FROM entrypoints e JOIN files f ON f.id = e.file_id
+                 WHERE (?1 IS NULL OR e.kind = ?1)
+                   AND (?2 IS NULL OR f.path = ?2
+                        OR substr(f.path, 1, length(?2) + 1) = ?2 || '/')
+                 ORDER BY CASE e.kind
+                     WHEN 'main' THEN 0 WHEN 'command' THEN 1 WHEN 'route' THEN 2 ELSE 3
+                 END, f.path, e.start_line, e.id"
+            ))?;
+            let records = stmt
+                .query_map(
+                    params![kind.map(|k| k.as_str()), directory],
+                    entrypoint_from_row,
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+}
+
+fn entrypoint_from_row(row: &Row<'_>) -> rusqlite::Result<EntrypointRecord> {
+    Ok(EntrypointRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_path: row.get(2)?,
+        language: row.get(3)?,
+        kind: row.get(4)?,
+        name: row.get(5)?,
+        handler: row.get(6)?,
+        start_line: row.get(7)?,
+        end_line: row.get(8)?,
+    })
+}
@@ -0,0 +1,82 @@
+//! Indexing markers of external packages for IndexStore.
+//!
+//! Each external package gets a metadata entry holding the version indexed
+//! and whether its files were all indexed. The marker is written incomplete
+//! before the first file and completed after the last one, so a package whose
+//! indexing was interrupted is indexed again instead of being taken as up to
+//! date from the files that made it in.
+
+use super::IndexStore;
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the metadata keys of package markers, followed by
+/// `<ecosystem>/<name>`
+const MARKER_KEY_PREFIX: &str = "external_package:";
+
+/// Indexing state of an external package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalPackageMarker {
+    pub version: String,
+    /// All the files of the package were indexed
+    pub complete: bool,
+}
+
+impl IndexStore {
+    /// Marker of the package `<ecosystem>/<name>`, `None` if it was never
+    /// indexed.
+    pub fn get_external_package_marker(
+        &self,
+        package: &str,
+    ) -> Result<Option<ExternalPackageMarker>> {
+        self.with_conn(|conn| {
+            let value: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM metadata WHERE key = ?1",
+                    [format!("{}{}", MARKER_KEY_PREFIX, package)],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+        })
+    }
+
+    /// Record the indexing state of the package `<ecosystem>/<name>`.
+    pub fn set_external_package_marker(
+        &self,
+        package: &str,
+        marker: &ExternalPackageMarker,
+    ) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                params![
+                    format!("{}{}", MARKER_KEY_PREFIX, package),
+                    serde_json::to_string(marker)?
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Delete the markers of the packages not in `keep`, given as
+    /// `<ecosystem>/<name>`, returning how many were removed.
+    pub fn retain_external_package_markers(&self, keep: &[String]) -> Result<usize> {
+        self.with_conn(|conn| {
+            let keys: Vec<String> = conn
+                .prepare("SELECT key FROM metadata WHERE substr(key, 1, length(?1)) = ?1")?
+                .query_map([MARKER_KEY_PREFIX], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut removed = 0;
+            for key in keys {
+                let package = &key[MARKER_KEY_PREFIX.len()..];
+                if !keep.iter().any(|p| p == package) {
+                    removed += conn.execute("DELETE FROM metadata WHERE key = ?1", [&key])?;
+                }
+            }
+            Ok(removed)
+        })
+    }
+}
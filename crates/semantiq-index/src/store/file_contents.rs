@@ -0,0 +1,128 @@
+//! Stored file content for IndexStore.
+//!
+//! With `storage.file_contents` enabled, the content of each indexed file is
+//! stored compressed with zstd, so text search and line reads work from the
+//! database alone: against an index copied to another machine, or a checkout
+//! at another revision.
+
+use super::IndexStore;
+use crate::compression::COMPRESSION_LEVEL;
+use crate::external::EXTERNAL_PREFIX;
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::sync::atomic::Ordering;
+
+impl IndexStore {
+    /// Store the content of files written from now on.
+    pub fn set_file_contents(&self, enabled: bool) {
+        self.file_contents.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether the content of written files is stored
+    pub fn stores_file_contents(&self) -> bool {
+        self.file_contents.load(Ordering::Relaxed)
+    }
+
+    /// Internal implementation for use within a transaction: store the
+    /// content of a file when enabled, drop the previous one otherwise.
+    pub(crate) fn write_file_content_impl(
+        &self,
+        conn: &Connection,
+        file_id: i64,
+        content: &str,
+    ) -> Result<()> {
+        if self.stores_file_contents() {
+            conn.prepare_cached(
+                "INSERT OR REPLACE INTO file_contents (file_id, content) VALUES (?1, ?2)",
+            )?
+            .execute(params![file_id, compress(content)?])?;
+        } else {
+            conn.prepare_cached("DELETE FROM file_contents WHERE file_id = ?1")?
+                .execute([file_id])?;
+        }
+        Ok(())
+    }
+
+    /// Store the content of an indexed file that has none, e.g. one indexed
+    /// before `storage.file_contents` was enabled. Returns whether it was
+    /// stored.
+    pub fn backfill_file_content(&self, path: &str, content: &str) -> Result<bool> {
+        let compressed = compress(content)?;
+        self.with_conn(|conn| {
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO file_contents (file_id, content)
+                 SELECT id, ?2 FROM files WHERE path = ?1",
+                params![path, compressed],
+            )?;
+            Ok(inserted > 0)
+        })
+    }
+
+    /// Whether any file content is stored
+    pub fn has_file_contents(&self) -> Result<bool> {
+        self.with_conn(|conn| {
+            Ok(
+                conn.query_row("SELECT EXISTS(SELECT 1 FROM file_contents)", [], |row| {
+                    row.get(0)
+                })?,
+            )
+        })
+    }
+
+    /// Whether the content of an indexed file is stored
+    pub fn has_file_content(&self, path: &str) -> Result<bool> {
+        self.with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT EXISTS(
+                     SELECT 1 FROM file_contents fc
+                     JOIN files f ON f.id = fc.file_id
+                     WHERE f.path = ?1
+                 )",
+                [path],
+                |row| row.get(0),
+            )?)
+        })
+    }
+
+    /// Get the stored content of an indexed file.
+    pub fn get_file_content(&self, path: &str) -> Result<Option<String>> {
+        let compressed: Option<Vec<u8>> = self.with_conn(|conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT fc.content FROM file_contents fc
+                     JOIN files f ON f.id = fc.file_id
+                     WHERE f.path = ?1",
+                    [path],
+                    |row| row.get(0),
+                )
+                .optional()?)
+        })?;
+        compressed
+            .map(|bytes| decompress(&bytes).with_context(|| format!("Corrupt content of {}", path)))
+            .transpose()
+    }
+
+    /// Get the paths of project files with stored content, sorted.
+    pub fn get_file_content_paths(&self) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT f.path FROM file_contents fc
+                 JOIN files f ON f.id = fc.file_id
+                 WHERE f.path NOT LIKE ?1 || '%'
+                 ORDER BY f.path",
+            )?;
+            let paths = stmt
+                .query_map([EXTERNAL_PREFIX], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(paths)
+        })
+    }
+}
+
+fn compress(content: &str) -> Result<Vec<u8>> {
+    Ok(zstd::encode_all(content.as_bytes(), COMPRESSION_LEVEL)?)
+}
+
+fn decompress(bytes: &[u8]) -> Result<String> {
+    Ok(String::from_utf8(zstd::decode_all(bytes)?)?)
+}
@@ -0,0 +1,291 @@
+//! File summary embeddings for IndexStore.
+//!
+//! Each file with symbols or chunks has one embedding of its summary (see
+//! [`crate::summary`]) in the `files_vec` table. Searches on large indexes
+//! first pick the files closest to the query, then compare the query with
+//! the chunks of those files only.
+
+use super::IndexStore;
+use super::compression::read_content;
+use crate::quantization::EmbeddingFormat;
+use crate::schema::EMBEDDING_DIMENSION;
+use crate::summary::file_summary;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, params};
+
+/// Create the `files_vec` table with the element type of `format`, unless it
+/// exists. Unlike `chunks_vec` it is created after the embedding format is
+/// known, as databases in int8 predate it.
+pub(super) fn init_files_vec(conn: &Connection, format: EmbeddingFormat) -> Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS files_vec USING vec0(
+             file_id INTEGER PRIMARY KEY,
+             embedding {}[{EMBEDDING_DIMENSION}]
+         );",
+        format.vec_column_type()
+    ))?;
+    Ok(())
+}
+
+/// SQL inserting a file summary embedding into `files_vec` for the given format.
+fn files_vec_insert_sql(format: EmbeddingFormat) -> &'static str {
+    match format {
+        EmbeddingFormat::Float32 => {
+            "INSERT OR REPLACE INTO files_vec(file_id, embedding) VALUES (?1, ?2)"
+        }
+        EmbeddingFormat::Int8 => {
+            "INSERT OR REPLACE INTO files_vec(file_id, embedding) VALUES (?1, vec_int8(?2))"
+        }
+    }
+}
+
+/// SQL running a KNN query against `files_vec` for the given format.
+fn files_vec_search_sql(format: EmbeddingFormat) -> &'static str {
+    match format {
+        EmbeddingFormat::Float32 => {
+            "SELECT file_id, distance
+             FROM files_vec
+             WHERE embedding MATCH ?1
+             ORDER BY distance
+             LIMIT ?2"
+        }
+        EmbeddingFormat::Int8 => {
+            "SELECT file_id, distance
+             FROM files_vec
+             WHERE embedding MATCH vec_int8(?1)
+             ORDER BY distance
+             LIMIT ?2"
+        }
+    }
+}
+
+/// Distance between the stored embedding of chunk `c` and the query `?1`
+fn chunk_distance_sql(format: EmbeddingFormat) -> &'static str {
+    match format {
+        EmbeddingFormat::Float32 => "vec_distance_l2(c.embedding, ?1)",
+        EmbeddingFormat::Int8 => "vec_distance_l2(vec_int8(c.embedding), vec_int8(?1))",
+    }
+}
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction: store the
+    /// summary embedding of a file, or remove it when `None`.
+    pub(crate) fn update_file_summary_embedding_impl(
+        conn: &Connection,
+        format: EmbeddingFormat,
+        file_id: i64,
+        embedding: Option<&[f32]>,
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM files_vec WHERE file_id = ?1")?
+            .execute([file_id])?;
+        if let Some(embedding) = embedding {
+            conn.prepare_cached(files_vec_insert_sql(format))?
+                .execute(params![file_id, format.encode(embedding)])?;
+        }
+        Ok(())
+    }
+
+    /// Internal implementation for use within a transaction: rebuild
+    /// `files_vec` with the element type of `format`, converting the
+    /// embeddings stored in format `current`.
+    pub(crate) fn convert_file_summaries_impl(
+        conn: &Connection,
+        current: EmbeddingFormat,
+        format: EmbeddingFormat,
+    ) -> Result<usize> {
+        let embeddings: Vec<(i64, Vec<u8>)> = {
+            let mut stmt = conn.prepare("SELECT file_id, embedding FROM files_vec")?;
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        Self::clear_file_summaries_impl(conn, format)?;
+        let mut insert_stmt = conn.prepare(files_vec_insert_sql(format))?;
+        for (file_id, bytes) in &embeddings {
+            let embedding = current.decode(bytes);
+            if embedding.len() == EMBEDDING_DIMENSION {
+                insert_stmt.execute(params![file_id, format.encode(&embedding)])?;
+            }
+        }
+        Ok(embeddings.len())
+    }
+
+    /// Internal implementation for use within a transaction: drop every file
+    /// summary embedding, e.g. when they were computed with another model.
+    pub(crate) fn clear_file_summaries_impl(
+        conn: &Connection,
+        format: EmbeddingFormat,
+    ) -> Result<()> {
+        conn.execute_batch("DROP TABLE IF EXISTS files_vec;")?;
+        init_files_vec(conn, format)
+    }
+
+    /// Store the summary embedding of a file.
+    pub fn update_file_summary_embedding(&self, file_id: i64, embedding: &[f32]) -> Result<()> {
+        self.with_conn(|conn| {
+            Self::update_file_summary_embedding_impl(
+                conn,
+                self.embedding_format(),
+                file_id,
+                Some(embedding),
+            )?;
+            Self::bump_generation_impl(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Get the summary embedding of a file, `None` if it has none.
+    pub fn get_file_summary_embedding(&self, file_id: i64) -> Result<Option<Vec<f32>>> {
+        self.with_conn(|conn| {
+            let bytes: Option<Vec<u8>> = conn
+                .query_row(
+                    "SELECT embedding FROM files_vec WHERE file_id = ?1",
+                    [file_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(bytes.map(|bytes| self.embedding_format().decode(&bytes)))
+        })
+    }
+
+    /// Count the files with a summary embedding, out of the files with
+    /// chunks.
+    pub fn get_file_summary_coverage(&self) -> Result<(usize, usize)> {
+        self.with_conn(|conn| {
+            let (summarized, total): (i64, i64) = conn.query_row(
+                "SELECT
+                    (SELECT COUNT(*) FROM files_vec
+                     WHERE file_id IN (SELECT file_id FROM chunks)),
+                    (SELECT COUNT(DISTINCT file_id) FROM chunks)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            Ok((summarized as usize, total as usize))
+        })
+    }
+
+    /// Get up to `limit` files with chunks but no summary embedding, with
+    /// the summary to embed for each.
+    pub fn get_files_without_summary(&self, limit: usize) -> Result<Vec<(i64, String)>> {
+        self.with_conn(|conn| {
+            let codec = self.chunk_codec_for(conn)?;
+            let files: Vec<(i64, String)> = {
+                let mut stmt = conn.prepare(
+                    "SELECT f.id, f.path FROM files f
+                     WHERE EXISTS (SELECT 1 FROM chunks c WHERE c.file_id = f.id)
+                       AND f.id NOT IN (SELECT file_id FROM files_vec)
+                     ORDER BY f.id
+                     LIMIT ?1",
+                )?;
+                stmt.query_map([limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+
+            let mut symbols_stmt = conn.prepare_cached(
+                "SELECT name, doc_comment FROM symbols WHERE file_id = ?1 ORDER BY start_line",
+            )?;
+            let mut chunks_stmt = conn.prepare_cached(
+                "SELECT content, json_array_length(symbols_json) FROM chunks
+                 WHERE file_id = ?1 ORDER BY start_line",
+            )?;
+
+            let mut summaries = Vec::with_capacity(files.len());
+            for (file_id, path) in files {
+                let symbols = symbols_stmt
+                    .query_map([file_id], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let chunks = chunks_stmt
+                    .query_map([file_id], |row| {
+                        Ok((
+                            read_content(&codec, row, 0)?,
+                            row.get::<_, i64>(1)? as usize,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let summary = file_summary(
+                    &path,
+                    symbols
+                        .iter()
+                        .map(|(name, doc)| (name.as_str(), doc.as_deref())),
+                    chunks
+                        .iter()
+                        .map(|(content, count)| (content.as_str(), *count)),
+                );
+                if let Some(summary) = summary {
+                    summaries.push((file_id, summary));
+                }
+            }
+            Ok(summaries)
+        })
+    }
+
+    /// Search for the files whose summary is closest to the query.
+    /// Returns file IDs with their distances, closest first.
+    pub fn search_similar_files(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>> {
+        self.with_conn(|conn| {
+            let format = self.embedding_format();
+            let mut stmt = conn.prepare(files_vec_search_sql(format))?;
+            let results = stmt
+                .query_map(
+                    params![format.encode(query_embedding), limit as i64],
+                    |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            format.normalize_distance(row.get::<_, f32>(1)?),
+                        ))
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(results)
+        })
+    }
+
+    /// Search for similar chunks within the given files only. Returns chunk
+    /// IDs with their distances, closest first, like
+    /// [`IndexStore::search_similar_chunks`].
+    pub fn search_similar_chunks_in_files(
+        &self,
+        query_embedding: &[f32],
+        file_ids: &[i64],
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>> {
+        if file_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.with_conn(|conn| {
+            let format = self.embedding_format();
+            let embedding_bytes = format.encode(query_embedding);
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT c.id, {} FROM chunks c
+                 WHERE c.file_id = ?2 AND c.embedding IS NOT NULL",
+                chunk_distance_sql(format)
+            ))?;
+
+            // One query per file, each reading the chunks of the file by index
+            let mut results: Vec<(i64, f32)> = Vec::new();
+            for file_id in file_ids {
+                let rows = stmt.query_map(params![embedding_bytes, file_id], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        format.normalize_distance(row.get::<_, f32>(1)?),
+                    ))
+                })?;
+                for row in rows {
+                    results.push(row?);
+                }
+            }
+
+            results.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+}
@@ -1,13 +1,16 @@
 //! File operations for IndexStore.
 
 use super::IndexStore;
-use crate::schema::FileRecord;
-use anyhow::{Context, Result, anyhow};
+use crate::clock::unix_now;
+use crate::paths::normalize_path;
+use crate::schema::{FileRecord, owning_package_sql};
+use anyhow::{Result, anyhow};
 use rusqlite::Connection;
 use rusqlite::{OptionalExtension, params};
-use semantiq_parser::PARSER_VERSION;
+use semantiq_parser::{PARSER_VERSION, symbol_kinds};
+use std::collections::HashSet;
 use std::sync::{MutexGuard, PoisonError};
-use std::time::{SystemTime, UNIX_EPOCH};
+
 use tracing::{debug, info, warn};
 
 impl IndexStore {
@@ -20,35 +23,73 @@ impl IndexStore {
         size: i64,
         last_modified: i64,
     ) -> Result<i64> {
-        let hash = Self::hash_content(content);
-        let indexed_at = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .context("System time before UNIX epoch")?
-            .as_secs() as i64;
-
         self.with_conn(|conn| {
-            conn.execute(
-                "INSERT OR REPLACE INTO files (path, language, hash, size, last_modified, indexed_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![path, language, hash, size, last_modified, indexed_at],
+            let id = Self::insert_file_impl(
+                conn,
+                path,
+                language,
+                None,
+                false,
+                false,
+                content,
+                size,
+                last_modified,
             )?;
-
-            let id = conn.last_insert_rowid();
-            debug!("Inserted file {} with id {}", path, id);
+            Self::bump_generation_impl(conn)?;
             Ok(id)
         })
     }
 
+    /// Internal implementation for use within a transaction.
+    #[allow(clippy::too_many_arguments)] // one argument per stored column
+    pub(crate) fn insert_file_impl(
+        conn: &Connection,
+        path: &str,
+        language: Option<&str>,
+        encoding: Option<&str>,
+        generated: bool,
+        outline: bool,
+        content: &str,
+        size: i64,
+        last_modified: i64,
+    ) -> Result<i64> {
+        let path = &normalize_path(path);
+        let hash = Self::hash_content(content);
+        let indexed_at = unix_now();
+
+        conn.prepare_cached(&format!(
+            "INSERT OR REPLACE INTO files (path, language, hash, size, last_modified, indexed_at, encoding, generated, outline, package)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, {})",
+            owning_package_sql("?1")
+        ))?
+        .execute(params![
+            path,
+            language,
+            hash,
+            size,
+            last_modified,
+            indexed_at,
+            encoding,
+            generated,
+            outline
+        ])?;
+
+        let id = conn.last_insert_rowid();
+        debug!("Inserted file {} with id {}", path, id);
+        Ok(id)
+    }
+
     /// Get a file record by its path.
     pub fn get_file_by_path(&self, path: &str) -> Result<Option<FileRecord>> {
+        let path = normalize_path(path);
         self.with_conn(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, path, language, hash, size, last_modified, indexed_at
+                "SELECT id, path, language, hash, size, last_modified, indexed_at, encoding, package
                  FROM files WHERE path = ?1",
             )?;
 
             let result = stmt
-                .query_row([path], |row| {
+                .query_row([&path], |row| {
                     Ok(FileRecord {
                         id: row.get(0)?,
                         path: row.get(1)?,
@@ -57,6 +98,8 @@ impl IndexStore {
                         size: row.get(4)?,
                         last_modified: row.get(5)?,
                         indexed_at: row.get(6)?,
+                        encoding: row.get(7)?,
+                        package: row.get(8)?,
                     })
                 })
                 .optional()?;
@@ -65,6 +108,64 @@ impl IndexStore {
         })
     }
 
+    /// Mark an indexed file as generated or not (see [`crate::generated`]),
+    /// e.g. after `.gitattributes` changed. Returns whether the mark changed.
+    pub fn set_file_generated(&self, path: &str, generated: bool) -> Result<bool> {
+        let path = normalize_path(path);
+        self.with_conn(|conn| {
+            let changed = conn
+                .prepare_cached(
+                    "UPDATE files SET generated = ?2 WHERE path = ?1 AND generated != ?2",
+                )?
+                .execute(params![path, generated])?;
+            if changed > 0 {
+                Self::bump_generation_impl(conn)?;
+            }
+            Ok(changed > 0)
+        })
+    }
+
+    /// Get the paths among `paths` of files marked as generated.
+    pub fn get_generated_files(&self, paths: &[&str]) -> Result<HashSet<String>> {
+        if paths.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT 1 FROM files WHERE path = ?1 AND generated = 1")?;
+
+            let mut generated = HashSet::new();
+            for &path in paths {
+                if stmt.exists([path])? {
+                    generated.insert(path.to_string());
+                }
+            }
+            Ok(generated)
+        })
+    }
+
+    /// Get the paths among `paths` of files indexed as an outline (see
+    /// [`crate::outline`]).
+    pub fn get_outline_files(&self, paths: &[&str]) -> Result<HashSet<String>> {
+        if paths.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT 1 FROM files WHERE path = ?1 AND outline = 1")?;
+
+            let mut outlines = HashSet::new();
+            for &path in paths {
+                if stmt.exists([path])? {
+                    outlines.insert(path.to_string());
+                }
+            }
+            Ok(outlines)
+        })
+    }
+
     /// Check if a file needs to be re-indexed based on content hash.
     pub fn needs_reindex(&self, path: &str, content: &str) -> Result<bool> {
         if let Some(file) = self.get_file_by_path(path)? {
@@ -77,12 +178,84 @@ impl IndexStore {
 
     /// Delete a file and its associated data (cascades to symbols, chunks, deps).
     pub fn delete_file(&self, path: &str) -> Result<()> {
+        let path = normalize_path(path);
         self.with_conn(|conn| {
-            conn.execute("DELETE FROM files WHERE path = ?1", [path])?;
+            // vec0 tables have no foreign keys to cascade from
+            conn.execute(
+                "DELETE FROM files_vec WHERE file_id IN (SELECT id FROM files WHERE path = ?1)",
+                [&path],
+            )?;
+            // Files left out of the index may have issues too
+            conn.execute("DELETE FROM index_issues WHERE path = ?1", [&path])?;
+            if conn.execute("DELETE FROM files WHERE path = ?1", [&path])? > 0 {
+                Self::close_symbol_history_impl(conn, Some(&path))?;
+                Self::bump_generation_impl(conn)?;
+            }
             Ok(())
         })
     }
 
+    /// Get the paths of all indexed files, sorted.
+    pub fn get_all_file_paths(&self) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT path FROM files ORDER BY path")?;
+            let paths = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(paths)
+        })
+    }
+
+    /// Get the paths of indexed files starting with `prefix`, sorted.
+    pub fn get_file_paths_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT path FROM files WHERE substr(path, 1, length(?1)) = ?1 ORDER BY path",
+            )?;
+            let paths = stmt
+                .query_map([prefix], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(paths)
+        })
+    }
+
+    /// Get the time the most recently indexed file whose path starts with
+    /// `prefix` was indexed at, `None` if there is none.
+    pub fn get_last_indexed_with_prefix(&self, prefix: &str) -> Result<Option<i64>> {
+        self.with_conn(|conn| {
+            let indexed_at = conn.query_row(
+                "SELECT MAX(indexed_at) FROM files WHERE substr(path, 1, length(?1)) = ?1",
+                [prefix],
+                |row| row.get(0),
+            )?;
+            Ok(indexed_at)
+        })
+    }
+
+    /// Delete all files whose path starts with `prefix`, returning how many were removed.
+    pub fn delete_files_with_prefix(&self, prefix: &str) -> Result<usize> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM files_vec WHERE file_id IN
+                     (SELECT id FROM files WHERE substr(path, 1, length(?1)) = ?1)",
+                [prefix],
+            )?;
+            conn.execute(
+                "DELETE FROM index_issues WHERE substr(path, 1, length(?1)) = ?1",
+                [prefix],
+            )?;
+            let deleted = conn.execute(
+                "DELETE FROM files WHERE substr(path, 1, length(?1)) = ?1",
+                [prefix],
+            )?;
+            if deleted > 0 {
+                Self::close_symbol_history_impl(conn, None)?;
+                Self::bump_generation_impl(conn)?;
+            }
+            Ok(deleted)
+        })
+    }
+
     /// Get a file path by its ID.
     pub fn get_file_path_by_id(&self, file_id: i64) -> Result<Option<String>> {
         self.with_conn(|conn| {
@@ -111,7 +284,8 @@ impl IndexStore {
 
     // Parser version management
 
-    /// Check if a full re-index is needed (parser version changed).
+    /// Check if a full re-index is needed (parser version or symbol kind
+    /// overrides changed).
     pub fn needs_full_reindex(&self) -> Result<bool> {
         self.with_conn(Self::needs_full_reindex_impl)
     }
@@ -126,6 +300,17 @@ impl IndexStore {
             )
             .optional()?;
 
+        let stored_symbol_kinds: Option<String> = conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = 'symbol_kinds'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if stored_symbol_kinds.unwrap_or_default() != symbol_kinds::fingerprint() {
+            return Ok(true);
+        }
+
         match stored_version {
             Some(v) => {
                 let stored: u32 = match v.parse() {
@@ -155,6 +340,10 @@ impl IndexStore {
             "INSERT OR REPLACE INTO metadata (key, value) VALUES ('parser_version', ?1)",
             [PARSER_VERSION.to_string()],
         )?;
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('symbol_kinds', ?1)",
+            [symbol_kinds::fingerprint()],
+        )?;
         Ok(())
     }
 
@@ -167,12 +356,25 @@ impl IndexStore {
     pub(crate) fn clear_all_data_impl(conn: &Connection) -> Result<()> {
         conn.execute_batch(
             "BEGIN IMMEDIATE;
+             DELETE FROM occurrences;
+             DELETE FROM file_tags;
+             DELETE FROM files_vec;
+             DELETE FROM type_relations;
+             DELETE FROM entrypoints;
+             DELETE FROM routes;
+             DELETE FROM config_refs;
+             DELETE FROM bindings;
+             DELETE FROM dependency_symbols;
              DELETE FROM dependencies;
              DELETE FROM chunks;
              DELETE FROM symbols;
              DELETE FROM files;
+             DELETE FROM evicted_dirs;
+             DELETE FROM index_issues;
              COMMIT;",
         )?;
+        Self::close_symbol_history_impl(conn, None)?;
+        Self::bump_generation_impl(conn)?;
         debug!("Cleared all indexed data");
         Ok(())
     }
@@ -196,19 +398,33 @@ impl IndexStore {
 
         let result = (|| -> Result<()> {
             conn.execute_batch(
-                "DELETE FROM dependencies;
+                "DELETE FROM occurrences;
+                 DELETE FROM file_tags;
+                 DELETE FROM files_vec;
+                 DELETE FROM type_relations;
+                 DELETE FROM entrypoints;
+                 DELETE FROM routes;
+                 DELETE FROM config_refs;
+                 DELETE FROM bindings;
+                 DELETE FROM dependency_symbols;
+                 DELETE FROM dependencies;
                  DELETE FROM chunks;
                  DELETE FROM symbols;
-                 DELETE FROM files;",
+                 DELETE FROM files;
+                 DELETE FROM evicted_dirs;
+                 DELETE FROM index_issues;",
             )?;
+            Self::close_symbol_history_impl(&conn, None)?;
             Self::set_parser_version_impl(&conn)?;
+            Self::adopt_migration_target_impl(&conn)?;
+            Self::bump_generation_impl(&conn)?;
             Ok(())
         })();
 
         match result {
             Ok(()) => {
                 conn.execute("COMMIT", [])?;
-                info!("Parser version changed - index cleared for full reindex");
+                info!("Parser version or symbol kinds changed - index cleared for full reindex");
                 Ok(true)
             }
             Err(e) => {
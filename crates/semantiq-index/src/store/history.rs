@@ -0,0 +1,556 @@
+//! Symbol and occurrence history operations for IndexStore.
+//!
+//! Each write of the symbols of a file is compared with the versions of
+//! its symbols still valid: unchanged symbols keep their version, changed
+//! and removed ones are closed at the index generation the write produces,
+//! and new ones open a version at it. The history thus only grows with
+//! changes, and answers what the symbols of the index were at any earlier
+//! generation.
+//!
+//! Line spans are not part of what identifies a version, or every edit
+//! above a symbol would start a new one: a move instead opens a span of the
+//! version at the generation it moved at, so that the lines of a symbol are
+//! those it had at the generation asked for. The occurrence counts of each
+//! file are kept the same way, a count that changed closing the previous
+//! one. Rows closed before the generations kept by
+//! `[history] max_generations` are pruned as files are written.
+
+use super::IndexStore;
+use crate::schema::SymbolVersionRecord;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use semantiq_parser::{Occurrence, Symbol};
+use std::collections::HashMap;
+
+/// Metadata key holding the generation the whole index was last cleared
+/// at, see [`IndexStore::close_symbol_history_impl`]
+const HISTORY_CLEARED_KEY: &str = "symbol_history_cleared";
+
+/// Metadata key holding the number of generations the history is kept
+/// for, absent to keep it all
+const HISTORY_RETENTION_KEY: &str = "symbol_history_max_generations";
+
+/// Columns read by [`version_from_row`], the lines being those of the span
+/// joined by [`VERSION_SPANS`] if any
+const VERSION_COLUMNS: &str = "h.id, h.path, h.name, h.kind,
+    coalesce(s.start_line, h.start_line) AS line, coalesce(s.end_line, h.end_line),
+    h.signature, h.doc_comment, h.parent, h.valid_from, h.valid_to";
+
+/// Versions `h` with the span `s` they had at generation `?2`, none while
+/// they had the lines they were written with
+const VERSION_SPANS: &str = "FROM symbol_history h
+    LEFT JOIN symbol_spans s ON s.version_id = h.id
+        AND s.valid_from <= ?2 AND (s.valid_to IS NULL OR s.valid_to > ?2)";
+
+/// What identifies a version of a symbol: any change but a move starts a
+/// new version
+type VersionKey = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+fn version_key(symbol: &Symbol) -> VersionKey {
+    (
+        symbol.name.clone(),
+        symbol.kind.as_str().to_string(),
+        symbol.signature.clone(),
+        symbol.doc_comment.clone(),
+        symbol.parent.clone(),
+    )
+}
+
+/// An open version of a symbol
+struct OpenVersion {
+    id: i64,
+    start_line: i64,
+    end_line: i64,
+    /// Open span holding the lines, with the generation it was opened at,
+    /// `None` while the version has the lines it was written with
+    span: Option<(i64, i64)>,
+}
+
+impl IndexStore {
+    /// Internal implementation for use within a write, after the symbols
+    /// of `file_id` were replaced by `symbols`.
+    ///
+    /// A symbol coming back unchanged after the whole index was cleared
+    /// (a full reindex) reopens the version closed by the clear rather than
+    /// starting a new one.
+    pub(crate) fn record_symbol_history_impl(
+        conn: &Connection,
+        file_id: i64,
+        symbols: &[Symbol],
+    ) -> Result<()> {
+        let Some(path) = conn
+            .prepare_cached("SELECT path FROM files WHERE id = ?1")?
+            .query_row([file_id], |row| row.get::<_, String>(0))
+            .optional()?
+        else {
+            return Ok(());
+        };
+        let generation = Self::index_generation_impl(conn)? as i64 + 1;
+
+        let mut open: HashMap<VersionKey, Vec<OpenVersion>> = HashMap::new();
+        {
+            let mut stmt = conn.prepare_cached(
+                "SELECT h.id, coalesce(s.start_line, h.start_line) AS line,
+                        coalesce(s.end_line, h.end_line), s.id, s.valid_from,
+                        h.name, h.kind, h.signature, h.doc_comment, h.parent
+                 FROM symbol_history h
+                 LEFT JOIN symbol_spans s ON s.version_id = h.id AND s.valid_to IS NULL
+                 WHERE h.path = ?1 AND h.valid_to IS NULL
+                 ORDER BY line DESC",
+            )?;
+            let rows = stmt.query_map([&path], |row| {
+                let span = match row.get::<_, Option<i64>>(3)? {
+                    Some(span_id) => Some((span_id, row.get(4)?)),
+                    None => None,
+                };
+                Ok((
+                    OpenVersion {
+                        id: row.get(0)?,
+                        start_line: row.get(1)?,
+                        end_line: row.get(2)?,
+                        span,
+                    },
+                    (
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                        row.get(8)?,
+                        row.get(9)?,
+                    ),
+                ))
+            })?;
+            for row in rows {
+                let (version, key) = row?;
+                open.entry(key).or_default().push(version);
+            }
+        }
+
+        let mut cleared_at: Option<Option<i64>> = None;
+        for symbol in symbols {
+            let key = version_key(symbol);
+            let (start_line, end_line) = (symbol.start_line as i64, symbol.end_line as i64);
+            // Versions are popped from the first line, so that symbols
+            // sharing a key keep theirs in order
+            if let Some(version) = open.get_mut(&key).and_then(Vec::pop) {
+                if (version.start_line, version.end_line) != (start_line, end_line) {
+                    if let Some((span_id, _)) = version.span {
+                        Self::close_history_row_impl(conn, "symbol_spans", span_id, generation)?;
+                    }
+                    Self::open_symbol_span_impl(
+                        conn, version.id, start_line, end_line, generation,
+                    )?;
+                }
+                continue;
+            }
+
+            let cleared = match cleared_at {
+                Some(cleared) => cleared,
+                None => *cleared_at.insert(Self::history_cleared_at_impl(conn)?),
+            };
+            let (name, kind, signature, doc_comment, parent) = key;
+            let reopened = match cleared {
+                Some(cleared) => conn
+                    .prepare_cached(
+                        "UPDATE symbol_history SET valid_to = NULL
+                         WHERE id = (
+                             SELECT id FROM symbol_history
+                             WHERE path = ?1 AND name = ?2 AND kind = ?3 AND signature IS ?5
+                               AND doc_comment IS ?6 AND parent IS ?7 AND valid_to = ?8
+                             ORDER BY abs(start_line - ?4)
+                             LIMIT 1)
+                         RETURNING id",
+                    )?
+                    .query_row(
+                        params![
+                            path,
+                            name,
+                            kind,
+                            start_line,
+                            signature,
+                            doc_comment,
+                            parent,
+                            cleared
+                        ],
+                        |row| row.get::<_, i64>(0),
+                    )
+                    .optional()?,
+                None => None,
+            };
+            if let Some(id) = reopened {
+                // The spans the version had were closed with it
+                Self::open_symbol_span_impl(conn, id, start_line, end_line, generation)?;
+            } else {
+                conn.prepare_cached(
+                    "INSERT INTO symbol_history (path, name, kind, start_line, end_line, signature,
+                                                 doc_comment, parent, valid_from)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )?
+                .execute(params![
+                    path,
+                    name,
+                    kind,
+                    start_line,
+                    end_line,
+                    signature,
+                    doc_comment,
+                    parent,
+                    generation
+                ])?;
+            }
+        }
+
+        for version in open.into_values().flatten() {
+            Self::close_symbol_version_impl(conn, version.id, generation)?;
+        }
+        Self::prune_symbol_history_impl(conn, Some(&path), generation)
+    }
+
+    /// Generation the whole index was last cleared at, if it ever was
+    fn history_cleared_at_impl(conn: &Connection) -> Result<Option<i64>> {
+        Ok(conn
+            .prepare_cached("SELECT value FROM metadata WHERE key = ?1")?
+            .query_row([HISTORY_CLEARED_KEY], |row| row.get::<_, String>(0))
+            .optional()?
+            .and_then(|v| v.parse().ok()))
+    }
+
+    /// Give version `version_id` the lines `start_line..=end_line` from
+    /// `generation` on.
+    fn open_symbol_span_impl(
+        conn: &Connection,
+        version_id: i64,
+        start_line: i64,
+        end_line: i64,
+        generation: i64,
+    ) -> Result<()> {
+        conn.prepare_cached(
+            "INSERT INTO symbol_spans (version_id, start_line, end_line, valid_from)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![version_id, start_line, end_line, generation])?;
+        Ok(())
+    }
+
+    /// End a row of a history table at `generation`, or drop it when it
+    /// was opened at the same generation and so was never part of the index.
+    fn close_history_row_impl(
+        conn: &Connection,
+        table: &str,
+        id: i64,
+        generation: i64,
+    ) -> Result<()> {
+        conn.prepare_cached(&format!(
+            "DELETE FROM {table} WHERE id = ?1 AND valid_from = ?2"
+        ))?
+        .execute(params![id, generation])?;
+        conn.prepare_cached(&format!("UPDATE {table} SET valid_to = ?2 WHERE id = ?1"))?
+            .execute(params![id, generation])?;
+        Ok(())
+    }
+
+    /// End a version and its open span at `generation`.
+    fn close_symbol_version_impl(conn: &Connection, id: i64, generation: i64) -> Result<()> {
+        let span = conn
+            .prepare_cached(
+                "SELECT id FROM symbol_spans WHERE version_id = ?1 AND valid_to IS NULL",
+            )?
+            .query_row([id], |row| row.get::<_, i64>(0))
+            .optional()?;
+        if let Some(span_id) = span {
+            Self::close_history_row_impl(conn, "symbol_spans", span_id, generation)?;
+        }
+        Self::close_history_row_impl(conn, "symbol_history", id, generation)
+    }
+
+    /// Internal implementation for use within a write, after the
+    /// occurrences of `file_id` were replaced by `occurrences`: unchanged
+    /// counts are kept, changed and removed ones closed at the generation
+    /// the write produces. Like symbols, a count coming back unchanged after
+    /// the whole index was cleared reopens the row closed by the clear.
+    pub(crate) fn record_occurrence_history_impl(
+        conn: &Connection,
+        file_id: i64,
+        occurrences: &[Occurrence],
+    ) -> Result<()> {
+        let Some(path) = conn
+            .prepare_cached("SELECT path FROM files WHERE id = ?1")?
+            .query_row([file_id], |row| row.get::<_, String>(0))
+            .optional()?
+        else {
+            return Ok(());
+        };
+        let generation = Self::index_generation_impl(conn)? as i64 + 1;
+
+        let mut open: HashMap<String, (i64, i64)> = conn
+            .prepare_cached(
+                "SELECT name, id, count FROM occurrence_history
+                 WHERE path = ?1 AND valid_to IS NULL",
+            )?
+            .query_map([&path], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+            .collect::<Result<_, _>>()?;
+
+        let mut cleared_at: Option<Option<i64>> = None;
+        for occurrence in occurrences {
+            let count = occurrence.count as i64;
+            match open.remove(&occurrence.name) {
+                Some((_, open_count)) if open_count == count => continue,
+                Some((id, _)) => {
+                    Self::close_history_row_impl(conn, "occurrence_history", id, generation)?
+                }
+                None => {}
+            }
+
+            let cleared = match cleared_at {
+                Some(cleared) => cleared,
+                None => *cleared_at.insert(Self::history_cleared_at_impl(conn)?),
+            };
+            let reopened = match cleared {
+                Some(cleared) => conn
+                    .prepare_cached(
+                        "UPDATE occurrence_history SET valid_to = NULL
+                         WHERE path = ?1 AND name = ?2 AND count = ?3 AND valid_to = ?4",
+                    )?
+                    .execute(params![path, occurrence.name, count, cleared])?,
+                None => 0,
+            };
+            if reopened == 0 {
+                conn.prepare_cached(
+                    "INSERT INTO occurrence_history (path, name, count, valid_from)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?
+                .execute(params![path, occurrence.name, count, generation])?;
+            }
+        }
+
+        for (id, _) in open.into_values() {
+            Self::close_history_row_impl(conn, "occurrence_history", id, generation)?;
+        }
+        Ok(())
+    }
+
+    /// Internal implementation for use within a write, after files were
+    /// deleted: close the versions and occurrence counts of `path`, or of
+    /// every path no longer indexed when `None`.
+    ///
+    /// With `None` and no file left, the whole index was cleared: the
+    /// generation is recorded so that symbols written again unchanged reopen
+    /// their version (see [`IndexStore::record_symbol_history_impl`]).
+    pub(crate) fn close_symbol_history_impl(conn: &Connection, path: Option<&str>) -> Result<()> {
+        let generation = Self::index_generation_impl(conn)? as i64 + 1;
+        let ids = match path {
+            Some(path) => conn
+                .prepare_cached(
+                    "SELECT id FROM symbol_history
+                     WHERE path = ?1 AND valid_to IS NULL
+                       AND NOT EXISTS (SELECT 1 FROM files WHERE path = ?1)",
+                )?
+                .query_map([path], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?,
+            None => conn
+                .prepare_cached(
+                    "SELECT id FROM symbol_history
+                     WHERE valid_to IS NULL AND path NOT IN (SELECT path FROM files)",
+                )?
+                .query_map([], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        for id in ids {
+            Self::close_symbol_version_impl(conn, id, generation)?;
+        }
+        let ids = match path {
+            Some(path) => conn
+                .prepare_cached(
+                    "SELECT id FROM occurrence_history
+                     WHERE path = ?1 AND valid_to IS NULL
+                       AND NOT EXISTS (SELECT 1 FROM files WHERE path = ?1)",
+                )?
+                .query_map([path], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?,
+            None => conn
+                .prepare_cached(
+                    "SELECT id FROM occurrence_history
+                     WHERE valid_to IS NULL AND path NOT IN (SELECT path FROM files)",
+                )?
+                .query_map([], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        for id in ids {
+            Self::close_history_row_impl(conn, "occurrence_history", id, generation)?;
+        }
+
+        if path.is_none() {
+            let empty: bool =
+                conn.query_row("SELECT NOT EXISTS (SELECT 1 FROM files)", [], |row| {
+                    row.get(0)
+                })?;
+            if empty {
+                conn.prepare_cached(
+                    "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                )?
+                .execute(params![HISTORY_CLEARED_KEY, generation.to_string()])?;
+            }
+        }
+        Self::prune_symbol_history_impl(conn, path, generation)
+    }
+
+    /// Oldest generation the history answers for at `generation`, 0 when
+    /// it is kept in full
+    fn oldest_history_generation_impl(conn: &Connection, generation: i64) -> Result<i64> {
+        let retained: Option<i64> = conn
+            .prepare_cached("SELECT value FROM metadata WHERE key = ?1")?
+            .query_row([HISTORY_RETENTION_KEY], |row| row.get::<_, String>(0))
+            .optional()?
+            .and_then(|v| v.parse().ok());
+        Ok(retained.map_or(0, |retained| (generation - retained + 1).max(0)))
+    }
+
+    /// Delete the versions, spans and occurrence counts of `path`, or of
+    /// every path when `None`, that were closed before the oldest
+    /// generation kept at `generation`.
+    fn prune_symbol_history_impl(
+        conn: &Connection,
+        path: Option<&str>,
+        generation: i64,
+    ) -> Result<()> {
+        let oldest = Self::oldest_history_generation_impl(conn, generation)?;
+        if oldest == 0 {
+            return Ok(());
+        }
+        // Spans of deleted versions go with them
+        match path {
+            Some(path) => {
+                conn.prepare_cached(
+                    "DELETE FROM symbol_spans WHERE valid_to <= ?2
+                       AND version_id IN (SELECT id FROM symbol_history WHERE path = ?1)",
+                )?
+                .execute(params![path, oldest])?;
+                for table in ["symbol_history", "occurrence_history"] {
+                    conn.prepare_cached(&format!(
+                        "DELETE FROM {table} WHERE path = ?1 AND valid_to <= ?2"
+                    ))?
+                    .execute(params![path, oldest])?;
+                }
+            }
+            None => {
+                for table in ["symbol_spans", "symbol_history", "occurrence_history"] {
+                    conn.prepare_cached(&format!("DELETE FROM {table} WHERE valid_to <= ?1"))?
+                        .execute([oldest])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the symbol history for the last `max_generations` index
+    /// generations, or in full when `None`, pruning what falls out of them.
+    pub fn set_symbol_history_retention(&self, max_generations: Option<u64>) -> Result<()> {
+        self.with_conn(|conn| {
+            match max_generations {
+                Some(max_generations) => conn.execute(
+                    "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                    params![HISTORY_RETENTION_KEY, max_generations.to_string()],
+                )?,
+                None => conn.execute(
+                    "DELETE FROM metadata WHERE key = ?1",
+                    [HISTORY_RETENTION_KEY],
+                )?,
+            };
+            let generation = Self::index_generation_impl(conn)? as i64;
+            Self::prune_symbol_history_impl(conn, None, generation)
+        })
+    }
+
+    /// Oldest index generation the symbol history answers for, 0 when it is
+    /// kept in full.
+    pub fn oldest_history_generation(&self) -> Result<u64> {
+        self.with_conn(|conn| {
+            let generation = Self::index_generation_impl(conn)? as i64;
+            Ok(Self::oldest_history_generation_impl(conn, generation)? as u64)
+        })
+    }
+
+    /// Get the versions of the symbols named `name` valid at index
+    /// generation `generation`, with the lines they had then, by path and
+    /// line.
+    pub fn get_symbol_versions(
+        &self,
+        name: &str,
+        generation: u64,
+    ) -> Result<Vec<SymbolVersionRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {VERSION_COLUMNS} {VERSION_SPANS}
+                 WHERE h.name = ?1 AND h.valid_from <= ?2 AND (h.valid_to IS NULL OR h.valid_to > ?2)
+                 ORDER BY h.path, line"
+            ))?;
+            let versions = stmt
+                .query_map(params![name, generation as i64], version_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(versions)
+        })
+    }
+
+    /// Get the versions of the symbols of the file at `path` valid at index
+    /// generation `generation`, with the lines they had then, by line.
+    pub fn get_file_symbol_versions(
+        &self,
+        path: &str,
+        generation: u64,
+    ) -> Result<Vec<SymbolVersionRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {VERSION_COLUMNS} {VERSION_SPANS}
+                 WHERE h.path = ?1 AND h.valid_from <= ?2 AND (h.valid_to IS NULL OR h.valid_to > ?2)
+                 ORDER BY line"
+            ))?;
+            let versions = stmt
+                .query_map(params![path, generation as i64], version_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(versions)
+        })
+    }
+
+    /// Get the number of occurrences of `name` in each file at index
+    /// generation `generation`, definitions included, by path.
+    pub fn get_occurrences_as_of(
+        &self,
+        name: &str,
+        generation: u64,
+    ) -> Result<Vec<(String, usize)>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT path, count FROM occurrence_history
+                 WHERE name = ?1 AND valid_from <= ?2 AND (valid_to IS NULL OR valid_to > ?2)
+                 ORDER BY path",
+            )?;
+            let occurrences = stmt
+                .query_map(params![name, generation as i64], |row| {
+                    Ok((row.get(0)?, row.get::<_, i64>(1)? as usize))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(occurrences)
+        })
+    }
+}
+
+fn version_from_row(row: &Row<'_>) -> rusqlite::Result<SymbolVersionRecord> {
+    Ok(SymbolVersionRecord {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        name: row.get(2)?,
+        kind: row.get(3)?,
+        start_line: row.get(4)?,
+        end_line: row.get(5)?,
+        signature: row.get(6)?,
+        doc_comment: row.get(7)?,
+        parent: row.get(8)?,
+        valid_from: row.get(9)?,
+        valid_to: row.get(10)?,
+    })
+}
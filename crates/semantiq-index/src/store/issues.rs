@@ -0,0 +1,132 @@
+//! Indexing issue operations for IndexStore.
+//!
+//! The issues of a file are replaced each time it is indexed, or met again
+//! while left out (see `crate::issues`), and removed with the file. Files
+//! that could not be read have no record in `files`, so issues are kept by
+//! path and those of files gone from the project are dropped after a full
+//! pass by [`IndexStore::retain_index_issues`].
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use crate::issues::{IndexIssue, IndexIssueKind};
+use crate::schema::IndexIssueRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row, params};
+use std::collections::BTreeMap;
+
+impl IndexStore {
+    /// Internal implementation for use within a write: replace the issues
+    /// of `path` by `issues`.
+    pub(crate) fn replace_index_issues_impl(
+        conn: &Connection,
+        path: &str,
+        issues: &[IndexIssue],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM index_issues WHERE path = ?1")?
+            .execute([path])?;
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let mut stmt = conn.prepare_cached(
+            "INSERT OR REPLACE INTO index_issues (path, kind, message, recorded_at)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for issue in issues {
+            stmt.execute(params![path, issue.kind.as_str(), issue.message, now])?;
+        }
+        Ok(())
+    }
+
+    /// Replace the issues of the file at `path`, for a file left out of the
+    /// index (files written to it carry theirs in [`crate::FileIndexData`]).
+    pub fn set_index_issues(&self, path: &str, issues: &[IndexIssue]) -> Result<()> {
+        self.with_conn(|conn| Self::replace_index_issues_impl(conn, path, issues))
+    }
+
+    /// Get the issues of the given kind, or of any, by path and kind. At
+    /// most `limit` are returned.
+    pub fn get_index_issues(
+        &self,
+        kind: Option<IndexIssueKind>,
+        limit: usize,
+    ) -> Result<Vec<IndexIssueRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT path, kind, message, recorded_at FROM index_issues
+                 WHERE ?1 IS NULL OR kind = ?1
+                 ORDER BY path, kind
+                 LIMIT ?2",
+            )?;
+            let issues = stmt
+                .query_map(
+                    params![kind.map(|k| k.as_str()), limit as i64],
+                    issue_from_row,
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(issues)
+        })
+    }
+
+    /// Get the issues of the file at `path`, by kind.
+    pub fn get_file_index_issues(&self, path: &str) -> Result<Vec<IndexIssueRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT path, kind, message, recorded_at FROM index_issues
+                 WHERE path = ?1
+                 ORDER BY kind",
+            )?;
+            let issues = stmt
+                .query_map([path], issue_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(issues)
+        })
+    }
+
+    /// Count the issues of each kind
+    pub fn count_index_issues(&self) -> Result<BTreeMap<IndexIssueKind, usize>> {
+        self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT kind, COUNT(*) FROM index_issues GROUP BY kind")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            let mut counts = BTreeMap::new();
+            for row in rows {
+                let (kind, count) = row?;
+                if let Some(kind) = IndexIssueKind::from_name(&kind) {
+                    counts.insert(kind, count as usize);
+                }
+            }
+            Ok(counts)
+        })
+    }
+
+    /// Drop the issues of the paths `keep` rejects, e.g. of files no longer
+    /// in the project. Returns the number of paths whose issues were dropped.
+    pub fn retain_index_issues(&self, keep: impl Fn(&str) -> bool) -> Result<usize> {
+        self.with_conn(|conn| {
+            let paths = conn
+                .prepare_cached("SELECT DISTINCT path FROM index_issues")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut dropped = 0;
+            for path in paths.iter().filter(|path| !keep(path)) {
+                conn.prepare_cached("DELETE FROM index_issues WHERE path = ?1")?
+                    .execute([path])?;
+                dropped += 1;
+            }
+            Ok(dropped)
+        })
+    }
+}
+
+fn issue_from_row(row: &Row<'_>) -> rusqlite::Result<IndexIssueRecord> {
+    Ok(IndexIssueRecord {
+        path: row.get(0)?,
+        kind: row.get(1)?,
+        message: row.get(2)?,
+        recorded_at: row.get(3)?,
+    })
+}
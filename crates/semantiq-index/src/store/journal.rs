@@ -0,0 +1,313 @@
+//! Indexing journal for IndexStore.
+//!
+//! A file is written to the index in a single transaction covering its
+//! record and content, symbols, chunks, embeddings, summary embedding, dependencies,
+//! type relations, identifier occurrences, symbol edges, keywords, comment
+//! and string ranges, entry points, environment and configuration reads and
+//! the issues met while indexing it.
+//! The file is entered in the journal before that transaction starts and
+//! removed from it by the same commit, so an entry left behind marks a file
+//! whose indexing was interrupted (crash, kill, failed write) and must be
+//! indexed again.
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use crate::compression::ChunkCodec;
+use crate::external::package_namespace;
+use crate::extraction::ExtractedFile;
+use crate::issues::IndexIssue;
+use crate::quantization::EmbeddingFormat;
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, params};
+use semantiq_embeddings::ChunkEmbedding;
+use semantiq_parser::{
+    Binding, CodeChunk, ConfigRef, Entrypoint, Import, Keyword, Occurrence, Symbol, TextMask,
+    TypeRelation,
+};
+use std::sync::{MutexGuard, PoisonError};
+use tracing::{debug, info};
+
+/// Everything extracted from one file, written by [`IndexStore::write_file_index`].
+#[derive(Debug, Clone, Copy)]
+pub struct FileIndexData<'a> {
+    pub path: &'a str,
+    pub language: Option<&'a str>,
+    /// Encoding the file was decoded from (see [`crate::encoding`]), `None` for UTF-8
+    pub encoding: Option<&'a str>,
+    /// The file is generated (see [`crate::generated`])
+    pub generated: bool,
+    /// Only the top-level symbols of the file are indexed (see [`crate::outline`])
+    pub outline: bool,
+    pub content: &'a str,
+    pub size: i64,
+    pub last_modified: i64,
+    pub symbols: &'a [Symbol],
+    pub chunks: &'a [CodeChunk],
+    /// Embedding of each chunk and how it fitted into the model's token
+    /// limit, in the same order, `None` where embedding failed
+    pub embeddings: &'a [Option<ChunkEmbedding>],
+    /// Embedding of the file summary (see [`crate::summary`])
+    pub summary_embedding: Option<&'a [f32]>,
+    pub imports: &'a [Import],
+    pub relations: &'a [TypeRelation],
+    pub occurrences: &'a [Occurrence],
+    pub keywords: &'a [Keyword],
+    /// Comments and string literals of the file, `None` for none
+    pub text_mask: Option<&'a TextMask>,
+    pub entrypoints: &'a [Entrypoint],
+    pub config_refs: &'a [ConfigRef],
+    pub bindings: &'a [Binding],
+    /// Problems met while indexing the file, replacing those of its
+    /// previous indexing
+    pub issues: &'a [IndexIssue],
+}
+
+impl<'a> FileIndexData<'a> {
+    /// Data for a file with no symbols, chunks or imports (e.g. one that failed to parse).
+    pub fn new(
+        path: &'a str,
+        language: Option<&'a str>,
+        content: &'a str,
+        size: i64,
+        last_modified: i64,
+    ) -> Self {
+        Self {
+            path,
+            language,
+            encoding: None,
+            generated: false,
+            outline: false,
+            content,
+            size,
+            last_modified,
+            symbols: &[],
+            chunks: &[],
+            embeddings: &[],
+            summary_embedding: None,
+            imports: &[],
+            relations: &[],
+            occurrences: &[],
+            keywords: &[],
+            text_mask: None,
+            entrypoints: &[],
+            config_refs: &[],
+            bindings: &[],
+            issues: &[],
+        }
+    }
+
+    /// The same data with the symbols, chunks, imports and the rest
+    /// extracted from the file
+    pub fn with_extracted(self, extracted: &'a ExtractedFile) -> Self {
+        Self {
+            symbols: &extracted.symbols,
+            chunks: &extracted.chunks,
+            imports: &extracted.imports,
+            relations: &extracted.relations,
+            occurrences: &extracted.occurrences,
+            keywords: &extracted.keywords,
+            text_mask: Some(&extracted.text_mask),
+            entrypoints: &extracted.entrypoints,
+            config_refs: &extracted.config_refs,
+            bindings: &extracted.bindings,
+            ..self
+        }
+    }
+}
+
+impl IndexStore {
+    /// Write a file and everything extracted from it in a single transaction.
+    ///
+    /// Replaces any previous data of the file and returns its new id. If the
+    /// write does not commit, the file stays in the journal and is picked up
+    /// by [`IndexStore::recover_interrupted_files`].
+    pub fn write_file_index(&self, data: &FileIndexData<'_>) -> Result<i64> {
+        let ids = self.write_file_indexes(std::slice::from_ref(data))?;
+        Ok(ids[0])
+    }
+
+    /// Write a batch of files in a single transaction, returning their new
+    /// ids in order.
+    ///
+    /// Same as [`IndexStore::write_file_index`] for each file, but the batch
+    /// is committed once: a full index writes many small files, and one
+    /// commit per file is most of the time it spends in SQLite.
+    pub fn write_file_indexes(&self, files: &[FileIndexData<'_>]) -> Result<Vec<i64>> {
+        if files.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        let started_at = unix_now();
+        {
+            let mut stmt = conn.prepare_cached(
+                "INSERT OR REPLACE INTO index_journal (path, started_at) VALUES (?1, ?2)",
+            )?;
+            for data in files {
+                stmt.execute(params![data.path, started_at])?;
+            }
+        }
+
+        let codec = self.chunk_codec_for(&conn)?;
+        let format = self.embedding_format();
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<Vec<i64>> {
+            let mut ids = Vec::with_capacity(files.len());
+            for data in files {
+                ids.push(self.write_file_index_impl(&conn, &codec, format, data)?);
+            }
+            Self::bump_generation_impl(&conn)?;
+            Ok(ids)
+        })();
+
+        match result {
+            Ok(ids) => {
+                conn.execute("COMMIT", [])?;
+                for data in files {
+                    debug!(
+                        "Wrote {}: {} symbols, {} chunks, {} deps",
+                        data.path,
+                        data.symbols.len(),
+                        data.chunks.len(),
+                        data.imports.len()
+                    );
+                }
+                Ok(ids)
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+
+    /// Internal implementation for use within a transaction: write one file
+    /// and remove it from the journal.
+    fn write_file_index_impl(
+        &self,
+        conn: &Connection,
+        codec: &ChunkCodec,
+        format: EmbeddingFormat,
+        data: &FileIndexData<'_>,
+    ) -> Result<i64> {
+        let file_id = Self::insert_file_impl(
+            conn,
+            data.path,
+            data.language,
+            data.encoding,
+            data.generated,
+            data.outline,
+            data.content,
+            data.size,
+            data.last_modified,
+        )?;
+        self.write_file_content_impl(conn, file_id, data.content)?;
+        Self::insert_symbols_impl(conn, file_id, data.symbols)?;
+
+        let chunk_ids = Self::insert_chunks_impl(conn, codec, file_id, data.chunks)?;
+        Self::link_chunk_symbols_impl(conn, file_id)?;
+        // Directories evicted by `quota.max_db_mb` stay without embeddings
+        if Self::embeddings_evicted_impl(conn, data.path)? {
+            conn.prepare_cached("UPDATE files SET embeddings_evicted = 1 WHERE id = ?1")?
+                .execute([file_id])?;
+        } else {
+            for (chunk_id, embedding) in chunk_ids.iter().zip(data.embeddings) {
+                if let Some(embedding) = embedding {
+                    Self::update_chunk_embedding_impl(
+                        conn,
+                        format,
+                        *chunk_id,
+                        &embedding.embedding,
+                    )?;
+                    Self::update_chunk_token_fit_impl(conn, *chunk_id, &embedding.fit)?;
+                }
+            }
+        }
+        Self::update_file_summary_embedding_impl(conn, format, file_id, data.summary_embedding)?;
+
+        Self::insert_dependencies_impl(conn, file_id, data.imports)?;
+        Self::insert_type_relations_impl(conn, file_id, data.relations)?;
+        Self::insert_occurrences_impl(conn, file_id, data.occurrences)?;
+        Self::insert_symbol_edges_impl(conn, file_id, data.occurrences)?;
+        Self::insert_file_tags_impl(conn, file_id, data.keywords)?;
+        Self::insert_text_mask_impl(conn, file_id, data.text_mask)?;
+        Self::insert_entrypoints_impl(conn, file_id, data.entrypoints)?;
+        Self::insert_config_refs_impl(conn, file_id, data.config_refs)?;
+        Self::insert_bindings_impl(conn, file_id, data.bindings)?;
+        Self::replace_index_issues_impl(conn, data.path, data.issues)?;
+
+        conn.prepare_cached("DELETE FROM index_journal WHERE path = ?1")?
+            .execute([data.path])?;
+        Ok(file_id)
+    }
+
+    /// Get the paths whose indexing was interrupted, sorted.
+    pub fn interrupted_files(&self) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT path FROM index_journal ORDER BY path")?;
+            let paths = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(paths)
+        })
+    }
+
+    /// Re-queue files whose indexing was interrupted and return their paths.
+    ///
+    /// Their indexed data is removed so that the next scan sees them as new
+    /// files and indexes them again. For external dependency files the whole
+    /// package is removed, since packages are only indexed when absent.
+    pub fn recover_interrupted_files(&self) -> Result<Vec<String>> {
+        let paths = self.interrupted_files()?;
+        if paths.is_empty() {
+            return Ok(paths);
+        }
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<()> {
+            for path in &paths {
+                match package_namespace(path) {
+                    Some(namespace) => conn.execute(
+                        "DELETE FROM files WHERE substr(path, 1, length(?1)) = ?1",
+                        [format!("{}/", namespace)],
+                    )?,
+                    None => conn.execute("DELETE FROM files WHERE path = ?1", [path])?,
+                };
+            }
+            conn.execute("DELETE FROM index_journal", [])?;
+            Self::close_symbol_history_impl(&conn, None)?;
+            Self::bump_generation_impl(&conn)?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])?;
+                info!(
+                    "Re-queued {} files whose indexing was interrupted",
+                    paths.len()
+                );
+                Ok(paths)
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Cross-process indexing lock for IndexStore.
+//!
+//! Only one process should write the index at a time: running `semantiq index`
+//! while the server's auto-indexer is busy leads to `SQLITE_BUSY` errors and
+//! duplicated work. The lock is a single row in `index_lock` recording the
+//! owner, its pid and a heartbeat. A lock whose heartbeat is older than
+//! [`INDEX_LOCK_STALE_SECS`] belongs to a process that died and is taken over.
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::fmt;
+use std::sync::{MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Seconds without a heartbeat after which a lock is considered abandoned.
+pub const INDEX_LOCK_STALE_SECS: i64 = 120;
+
+/// Interval between two attempts while waiting for the lock.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Owner of the indexing lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexLockInfo {
+    /// What holds the lock (e.g. `semantiq index`, `semantiq serve`)
+    pub owner: String,
+    pub pid: u32,
+    /// Unix timestamp the lock was acquired at
+    pub acquired_at: i64,
+    /// Unix timestamp of the last heartbeat
+    pub heartbeat_at: i64,
+}
+
+impl IndexLockInfo {
+    /// Seconds the lock has been held at `now`.
+    pub fn held_for(&self, now: i64) -> i64 {
+        (now - self.acquired_at).max(0)
+    }
+
+    fn is_stale(&self, now: i64) -> bool {
+        now - self.heartbeat_at > INDEX_LOCK_STALE_SECS
+    }
+}
+
+impl fmt::Display for IndexLockInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (pid {}, held for {}s)",
+            self.owner,
+            self.pid,
+            self.held_for(unix_now())
+        )
+    }
+}
+
+/// Result of an attempt to take the indexing lock.
+pub enum IndexLockAttempt<'a> {
+    Acquired(IndexLockGuard<'a>),
+    /// Another live process holds the lock
+    Held(IndexLockInfo),
+}
+
+/// Holds the indexing lock until dropped.
+pub struct IndexLockGuard<'a> {
+    store: &'a IndexStore,
+}
+
+impl IndexLockGuard<'_> {
+    /// Record a heartbeat so the lock is not considered stale.
+    ///
+    /// Returns `false` if another process has taken the lock over (see
+    /// [`IndexStore::steal_index_lock`]); the caller should stop writing.
+    pub fn refresh(&self) -> Result<bool> {
+        self.store.with_conn(|conn| {
+            let updated = conn.execute(
+                "UPDATE index_lock SET heartbeat_at = ?1 WHERE id = 1 AND pid = ?2",
+                params![unix_now(), std::process::id()],
+            )?;
+            Ok(updated == 1)
+        })
+    }
+}
+
+impl Drop for IndexLockGuard<'_> {
+    fn drop(&mut self) {
+        let released = self.store.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM index_lock WHERE id = 1 AND pid = ?1",
+                [std::process::id()],
+            )?;
+            Ok(())
+        });
+        match released {
+            Ok(()) => debug!("Released index lock"),
+            Err(e) => warn!("Failed to release index lock: {}", e),
+        }
+    }
+}
+
+impl IndexStore {
+    /// Get the current holder of the indexing lock, if any (stale or not).
+    pub fn index_lock_holder(&self) -> Result<Option<IndexLockInfo>> {
+        self.with_conn(load_lock)
+    }
+
+    /// Take the indexing lock if it is free, abandoned or already ours.
+    pub fn try_lock_index(&self, owner: &str) -> Result<IndexLockAttempt<'_>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e: PoisonError<MutexGuard<Connection>>| {
+                anyhow!("Database lock poisoned: {}", e)
+            })?;
+
+        conn.execute("BEGIN IMMEDIATE", [])?;
+
+        let result = (|| -> Result<Option<IndexLockInfo>> {
+            let now = unix_now();
+            if let Some(holder) = load_lock(&conn)?
+                && holder.pid != std::process::id()
+            {
+                if !holder.is_stale(now) {
+                    return Ok(Some(holder));
+                }
+                warn!("Taking over abandoned index lock from {}", holder);
+            }
+            write_lock(&conn, owner, now)?;
+            Ok(None)
+        })();
+
+        match result {
+            Ok(holder) => {
+                conn.execute("COMMIT", [])?;
+                drop(conn);
+                Ok(match holder {
+                    Some(holder) => IndexLockAttempt::Held(holder),
+                    None => {
+                        debug!("Acquired index lock for {}", owner);
+                        IndexLockAttempt::Acquired(IndexLockGuard { store: self })
+                    }
+                })
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+
+    /// Take the indexing lock, waiting up to `timeout` while another process holds it.
+    pub fn lock_index(&self, owner: &str, timeout: Duration) -> Result<IndexLockAttempt<'_>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.try_lock_index(owner)? {
+                IndexLockAttempt::Held(holder) if Instant::now() < deadline => {
+                    debug!("Waiting for index lock held by {}", holder);
+                    std::thread::sleep(LOCK_POLL_INTERVAL);
+                }
+                attempt => return Ok(attempt),
+            }
+        }
+    }
+
+    /// Take the indexing lock regardless of its holder, returning the previous one.
+    ///
+    /// The previous holder notices on its next [`IndexLockGuard::refresh`].
+    pub fn steal_index_lock(
+        &self,
+        owner: &str,
+    ) -> Result<(IndexLockGuard<'_>, Option<IndexLockInfo>)> {
+        let previous = self.with_conn(|conn| {
+            let previous = load_lock(conn)?.filter(|holder| holder.pid != std::process::id());
+            write_lock(conn, owner, unix_now())?;
+            Ok(previous)
+        })?;
+        if let Some(ref holder) = previous {
+            warn!("Took the index lock over from {}", holder);
+        }
+        Ok((IndexLockGuard { store: self }, previous))
+    }
+}
+
+fn load_lock(conn: &Connection) -> Result<Option<IndexLockInfo>> {
+    let holder = conn
+        .query_row(
+            "SELECT owner, pid, acquired_at, heartbeat_at FROM index_lock WHERE id = 1",
+            [],
+            |row| {
+                Ok(IndexLockInfo {
+                    owner: row.get(0)?,
+                    pid: row.get(1)?,
+                    acquired_at: row.get(2)?,
+                    heartbeat_at: row.get(3)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(holder)
+}
+
+fn write_lock(conn: &Connection, owner: &str, now: i64) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO index_lock (id, owner, pid, acquired_at, heartbeat_at)
+         VALUES (1, ?1, ?2, ?3, ?3)",
+        params![owner, std::process::id(), now],
+    )?;
+    Ok(())
+}
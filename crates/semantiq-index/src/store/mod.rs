@@ -3,25 +3,79 @@
 //! This module provides the `IndexStore` type for storing and querying
 //! indexed code data including files, symbols, chunks, and dependencies.
 
+mod base_index;
+mod bindings;
 mod calibrations;
 mod chunks;
+mod compression;
+mod config_refs;
 mod dependencies;
+mod dir_summaries;
+mod distinctiveness;
+mod embedding_format;
+mod embedding_migration;
+mod embedding_model;
+mod embedding_preprocessing;
+mod embedding_prompts;
+mod entrypoints;
+mod external_packages;
+mod file_contents;
+mod file_summaries;
 mod files;
+mod history;
+mod issues;
+mod journal;
+mod lock;
 mod observations;
+mod occurrences;
+mod packages;
+mod query_cache;
+mod quota;
+mod relations;
+mod routes;
+mod saved_queries;
+mod scan;
+mod stats;
+mod symbol_edges;
 mod symbols;
+mod tags;
+mod text_masks;
+mod translation_units;
+mod usage;
 
+use crate::compression::ChunkCodec;
+use crate::config::{SemantiqConfig, StorageConfig};
+use crate::preprocess::EmbeddingPreprocessor;
+use crate::quantization::EmbeddingFormat;
 use crate::schema::init_schema;
 use anyhow::{Context, Result, anyhow};
 use rusqlite::{Connection, ffi::sqlite3_auto_extension};
+use semantiq_embeddings::EmbeddingPrompts;
 use sqlite_vec::sqlite3_vec_init;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::Once;
-use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
 
 // Re-export types
 pub use calibrations::{CalibrationData, CalibrationRecord};
+pub use compression::CompactionReport;
+pub use dir_summaries::{DirectorySummary, LanguageShare, TopSymbol};
+pub use embedding_migration::EmbeddingMigration;
+pub use external_packages::ExternalPackageMarker;
+pub use journal::FileIndexData;
+pub use lock::{INDEX_LOCK_STALE_SECS, IndexLockAttempt, IndexLockGuard, IndexLockInfo};
+pub use occurrences::{SharedSymbol, SymbolUsage, UnreferencedSymbol};
+pub use quota::{EvictedDirectory, EvictionReport, QuotaStats, quota_directory};
+pub use saved_queries::SavedQuery;
+pub use scan::{ChunkEmbeddings, DEFAULT_SCAN_MEMORY_MB};
+pub use stats::{DirectoryStats, LanguageStats};
+pub use symbol_edges::SymbolEdge;
+pub use symbols::{SymbolField, SymbolMatch};
+pub use tags::FileTag;
+pub use usage::{ToolUsage, ToolUsageEvent};
 
 /// Global initializer for sqlite-vec extension.
 ///
@@ -80,10 +134,35 @@ fn init_sqlite_vec() {
     });
 }
 
+/// Prepared statements kept per connection: enough for every statement of
+/// [`IndexStore::write_file_index`] to be prepared once per connection.
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
 /// The main storage interface for the code index.
 pub struct IndexStore {
     pub(crate) conn: Arc<Mutex<Connection>>,
     db_path: PathBuf,
+    /// Storage format of embeddings, persisted in the metadata table.
+    /// Only read or changed while holding the connection lock.
+    embedding_format: RwLock<EmbeddingFormat>,
+    /// Pipeline chunk text goes through before embedding, persisted in the
+    /// metadata table
+    embedding_preprocessor: RwLock<EmbeddingPreprocessor>,
+    /// Prompts queries and passages are embedded in, persisted in the
+    /// metadata table
+    embedding_prompts: RwLock<EmbeddingPrompts>,
+    /// Encoding of chunk content, with the dictionary persisted in the
+    /// `chunk_dictionary` table
+    chunk_codec: RwLock<ChunkCodec>,
+    /// Store the content of written files (`storage.file_contents`)
+    file_contents: AtomicBool,
+    /// Record the usage statistics of the tools (`usage.enabled`)
+    usage_stats: AtomicBool,
+    /// Record the directories searches find results in (`quota.max_db_mb`)
+    search_tracking: AtomicBool,
+    /// Memory taken by a page of a scan over every chunk
+    /// (`storage.scan_memory_mb`)
+    scan_memory: AtomicUsize,
 }
 
 impl IndexStore {
@@ -102,11 +181,25 @@ impl IndexStore {
              PRAGMA busy_timeout=5000;",
         )?;
 
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         init_schema(&conn)?;
+        let embedding_format = embedding_format::load_embedding_format(&conn)?;
+        file_summaries::init_files_vec(&conn, embedding_format)?;
+        let chunk_dictionary = compression::load_chunk_dictionary(&conn)?;
+        let embedding_preprocessor = embedding_preprocessing::load_embedding_preprocessor(&conn)?;
+        let embedding_prompts = embedding_prompts::load_embedding_prompts(&conn)?;
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
             db_path: path.to_path_buf(),
+            embedding_format: RwLock::new(embedding_format),
+            embedding_preprocessor: RwLock::new(embedding_preprocessor),
+            embedding_prompts: RwLock::new(embedding_prompts),
+            chunk_codec: RwLock::new(ChunkCodec::new(false, chunk_dictionary)),
+            file_contents: AtomicBool::new(false),
+            usage_stats: AtomicBool::new(true),
+            search_tracking: AtomicBool::new(false),
+            scan_memory: AtomicUsize::new(StorageConfig::default().scan_memory_bytes()),
         })
     }
 
@@ -115,11 +208,25 @@ impl IndexStore {
         init_sqlite_vec();
 
         let conn = Connection::open_in_memory()?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         init_schema(&conn)?;
+        let embedding_format = embedding_format::load_embedding_format(&conn)?;
+        file_summaries::init_files_vec(&conn, embedding_format)?;
+        let chunk_dictionary = compression::load_chunk_dictionary(&conn)?;
+        let embedding_preprocessor = embedding_preprocessing::load_embedding_preprocessor(&conn)?;
+        let embedding_prompts = embedding_prompts::load_embedding_prompts(&conn)?;
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
             db_path: PathBuf::from(":memory:"),
+            embedding_format: RwLock::new(embedding_format),
+            embedding_preprocessor: RwLock::new(embedding_preprocessor),
+            embedding_prompts: RwLock::new(embedding_prompts),
+            chunk_codec: RwLock::new(ChunkCodec::new(false, chunk_dictionary)),
+            file_contents: AtomicBool::new(false),
+            usage_stats: AtomicBool::new(true),
+            search_tracking: AtomicBool::new(false),
+            scan_memory: AtomicUsize::new(StorageConfig::default().scan_memory_bytes()),
         })
     }
 
@@ -128,6 +235,27 @@ impl IndexStore {
         &self.db_path
     }
 
+    /// Check that the database answers queries.
+    pub fn ping(&self) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.query_row("SELECT 1", [], |_| Ok(()))?;
+            Ok(())
+        })
+    }
+
+    /// Copy the write-ahead log into the database file and truncate it, so
+    /// that the file is complete on its own once the process exits.
+    pub fn checkpoint_wal(&self) -> Result<()> {
+        self.with_conn(|conn| {
+            let busy: i64 =
+                conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+            if busy != 0 {
+                anyhow::bail!("Database busy, write-ahead log not checkpointed");
+            }
+            Ok(())
+        })
+    }
+
     /// Helper function to safely acquire the connection lock with proper error handling.
     pub(crate) fn with_conn<F, T>(&self, f: F) -> Result<T>
     where
@@ -142,6 +270,44 @@ impl IndexStore {
         f(&conn)
     }
 
+    /// Apply project configuration to the database.
+    ///
+    /// Starts re-embedding the index when `embeddings.model` differs from the
+    /// recorded model (see [`IndexStore::set_embedding_model`]),
+    /// `embeddings.preprocess` from the recorded pipeline or the prompts of
+    /// the model from the recorded ones, and converts
+    /// stored embeddings when `embeddings.quantization` differs from the
+    /// current storage format. `storage.compress_chunks` applies to chunks
+    /// written from now on; existing chunks are rewritten by
+    /// [`IndexStore::compact_chunks`]. `storage.file_contents` applies to
+    /// files written from now on. With `quota.max_db_mb`, the directories
+    /// searches find results in are recorded for [`IndexStore::enforce_size_quota`].
+    /// `storage.scan_memory_mb` bounds the pages of passes over every chunk.
+    pub fn apply_config(&self, config: &SemantiqConfig) -> Result<()> {
+        self.set_embedding_model(config.embeddings.model)?;
+        self.set_embedding_preprocessor(&EmbeddingPreprocessor::new(
+            &config.embeddings.preprocess,
+        ))?;
+        self.set_embedding_prompts(&config.embeddings.prompts_for(config.embeddings.model))?;
+        self.set_chunk_compression(config.storage.compress_chunks);
+        self.set_file_contents(config.storage.file_contents);
+        self.set_scan_memory(config.storage.scan_memory_bytes());
+        self.set_usage_stats(config.usage.enabled);
+        self.set_search_tracking(config.quota.max_db_bytes().is_some());
+        self.set_symbol_history_retention(config.history.retained_generations())?;
+
+        let target = config.embeddings.quantization;
+        if self.embedding_format() != target {
+            tracing::info!(
+                "Embedding format changed in configuration: {} -> {}",
+                self.embedding_format().as_str(),
+                target.as_str()
+            );
+            self.set_embedding_format(target)?;
+        }
+        Ok(())
+    }
+
     /// Get index statistics.
     pub fn get_stats(&self) -> Result<IndexStats> {
         self.with_conn(|conn| {
@@ -150,7 +316,8 @@ impl IndexStore {
                     (SELECT COUNT(*) FROM files) as file_count,
                     (SELECT COUNT(*) FROM symbols) as symbol_count,
                     (SELECT COUNT(*) FROM chunks) as chunk_count,
-                    (SELECT COUNT(*) FROM dependencies) as dep_count",
+                    (SELECT COUNT(*) FROM dependencies) as dep_count,
+                    (SELECT value FROM metadata WHERE key = 'index_generation') as generation",
                 [],
                 |row| {
                     Ok(IndexStats {
@@ -158,6 +325,10 @@ impl IndexStore {
                         symbol_count: row.get::<_, i64>(1)? as usize,
                         chunk_count: row.get::<_, i64>(2)? as usize,
                         dependency_count: row.get::<_, i64>(3)? as usize,
+                        generation: row
+                            .get::<_, Option<String>>(4)?
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(0),
                     })
                 },
             )
@@ -177,12 +348,18 @@ impl IndexStore {
     /// - `(`, `)`: Grouping
     ///
     /// This function wraps the query in double quotes for literal matching,
-    /// then appends `*` for prefix search.
+    /// then appends `*` for prefix search. A query made of several identifier
+    /// words (`UserById`) also matches them in the `words` column, as a phrase.
     pub(crate) fn escape_fts5_query(query: &str) -> String {
         // Strip null bytes and control characters that could cause unexpected FTS5 behavior
         let cleaned: String = query.chars().filter(|c| !c.is_control()).collect();
         let escaped = cleaned.replace('"', "\"\"");
-        format!("\"{}\"*", escaped)
+        let words = symbols::identifier_words(&cleaned);
+        if words.contains(' ') && words != cleaned.to_lowercase() {
+            format!("\"{}\"* OR words : \"{}\"*", escaped, words)
+        } else {
+            format!("\"{}\"*", escaped)
+        }
     }
 
     /// Computes a hash of file content for change detection.
@@ -203,6 +380,8 @@ pub struct IndexStats {
     pub symbol_count: usize,
     pub chunk_count: usize,
     pub dependency_count: usize,
+    /// Index generation, see [`IndexStore::index_generation`]
+    pub generation: u64,
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 //! Distance observation operations for ML calibration.
 
 use super::IndexStore;
+use crate::clock::unix_now;
 use anyhow::{Result, anyhow};
 use rusqlite::Connection;
 use rusqlite::params;
@@ -133,11 +134,7 @@ impl IndexStore {
     /// Returns the number of observations deleted.
     pub fn cleanup_old_observations(&self, max_age_secs: i64) -> Result<usize> {
         self.with_conn(|conn| {
-            let cutoff = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-                - max_age_secs;
+            let cutoff = unix_now() - max_age_secs;
 
             let rows = conn.execute(
                 "DELETE FROM distance_observations WHERE timestamp < ?1",
@@ -0,0 +1,246 @@
+//! Identifier occurrence operations for IndexStore.
+//!
+//! Occurrences count every identifier of a file, including the names of the
+//! symbols it defines. References to a name are its occurrences minus the
+//! definitions of symbols with that name in the same file.
+
+use super::IndexStore;
+use super::symbols::qualifiers_from_row;
+use crate::external::EXTERNAL_PREFIX;
+use crate::schema::SymbolRecord;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use semantiq_parser::Occurrence;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Occurrences of `o.name` in file `o.file_id` that are not definitions
+const REFERENCES_IN_FILE: &str = "o.count - (SELECT COUNT(*) FROM symbols d
+                                  WHERE d.file_id = o.file_id AND d.name = o.name)";
+
+/// How often a name is referenced across the index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SymbolUsage {
+    /// Occurrences of the name that are not definitions
+    pub references: usize,
+    /// Files with at least one reference
+    pub files: usize,
+}
+
+/// A symbol whose name is referenced in no file other than its own
+#[derive(Debug, Clone)]
+pub struct UnreferencedSymbol {
+    pub symbol: SymbolRecord,
+    pub file_path: String,
+    pub language: Option<String>,
+    /// References in the defining file itself
+    pub local_references: usize,
+}
+
+/// A name defined in one file and referenced in another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedSymbol {
+    /// The other file
+    pub file_path: String,
+    pub name: String,
+    /// Defined in the given file and referenced in `file_path`, rather than
+    /// the reverse
+    pub defined_here: bool,
+}
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_occurrences_impl(
+        conn: &Connection,
+        file_id: i64,
+        occurrences: &[Occurrence],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM occurrences WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT OR REPLACE INTO occurrences (name, file_id, count) VALUES (?1, ?2, ?3)",
+        )?;
+        for occurrence in occurrences {
+            stmt.execute(params![occurrence.name, file_id, occurrence.count as i64])?;
+        }
+        Self::record_occurrence_history_impl(conn, file_id, occurrences)?;
+        Ok(())
+    }
+
+    /// Get the identifier occurrences of a file, sorted by name, without
+    /// their lines (kept per symbol, see [`super::symbol_edges`]).
+    pub fn get_occurrences_by_file(&self, file_id: i64) -> Result<Vec<Occurrence>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT name, count FROM occurrences WHERE file_id = ?1 ORDER BY name",
+            )?;
+            let occurrences = stmt
+                .query_map([file_id], |row| {
+                    Ok(Occurrence {
+                        name: row.get(0)?,
+                        count: row.get::<_, i64>(1)? as usize,
+                        lines: Vec::new(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(occurrences)
+        })
+    }
+
+    /// Count the references to `name` and the files they are in.
+    pub fn symbol_usage(&self, name: &str) -> Result<SymbolUsage> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {REFERENCES_IN_FILE} FROM occurrences o WHERE o.name = ?1"
+            ))?;
+            let per_file = stmt
+                .query_map([name], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(per_file
+                .into_iter()
+                .filter(|&references| references > 0)
+                .fold(SymbolUsage::default(), |usage, references| SymbolUsage {
+                    references: usage.references + references as usize,
+                    files: usage.files + 1,
+                }))
+        })
+    }
+
+    /// Get the content hash of the files among `paths` and of those with an
+    /// occurrence of `name`, by path: they change when one of these files is
+    /// indexed again, or when another file starts using the name.
+    pub fn get_name_file_hashes(
+        &self,
+        name: &str,
+        paths: &[&str],
+    ) -> Result<BTreeMap<String, String>> {
+        self.with_conn(|conn| {
+            let mut hashes: BTreeMap<String, String> = conn
+                .prepare_cached(
+                    "SELECT path, hash FROM files
+                     WHERE id IN (SELECT file_id FROM occurrences WHERE name = ?1)",
+                )?
+                .query_map([name], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            let mut stmt = conn.prepare_cached("SELECT hash FROM files WHERE path = ?1")?;
+            for path in paths {
+                if hashes.contains_key(*path) {
+                    continue;
+                }
+                if let Some(hash) = stmt.query_row([path], |row| row.get(0)).optional()? {
+                    hashes.insert(path.to_string(), hash);
+                }
+            }
+            Ok(hashes)
+        })
+    }
+
+    /// Get the names shared between a file and other project files: symbols
+    /// it defines that they reference, and symbols they define that it
+    /// references. Names defined in more than `max_definitions` files (e.g.
+    /// `new`) say little about how two files relate and are left out.
+    /// Ordered by file and name.
+    pub fn find_shared_symbols(
+        &self,
+        file_id: i64,
+        max_definitions: usize,
+    ) -> Result<Vec<SharedSymbol>> {
+        self.with_conn(|conn| {
+            let definitions =
+                "(SELECT COUNT(DISTINCT d.file_id) FROM symbols d WHERE d.name = s.name)";
+            let project_file = format!(
+                "substr(f.path, 1, {}) != '{EXTERNAL_PREFIX}'",
+                EXTERNAL_PREFIX.len()
+            );
+            let mut stmt = conn.prepare(&format!(
+                "SELECT f.path, s.name, 1 FROM symbols s
+                 JOIN occurrences o ON o.name = s.name AND o.file_id != s.file_id
+                 JOIN files f ON f.id = o.file_id
+                 WHERE s.file_id = ?1 AND {REFERENCES_IN_FILE} > 0
+                   AND {definitions} <= ?2 AND {project_file}
+                 UNION
+                 SELECT f.path, s.name, 0 FROM occurrences o
+                 JOIN symbols s ON s.name = o.name AND s.file_id != o.file_id
+                 JOIN files f ON f.id = s.file_id
+                 WHERE o.file_id = ?1 AND {REFERENCES_IN_FILE} > 0
+                   AND {definitions} <= ?2 AND {project_file}
+                 ORDER BY 1, 2"
+            ))?;
+
+            let shared = stmt
+                .query_map(params![file_id, max_definitions as i64], |row| {
+                    Ok(SharedSymbol {
+                        file_path: row.get(0)?,
+                        name: row.get(1)?,
+                        defined_here: row.get(2)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(shared)
+        })
+    }
+
+    /// Get the project symbols of the given kinds whose name is referenced
+    /// in no other file, ordered by file and line. External dependencies
+    /// are left out.
+    pub fn find_unreferenced_symbols(&self, kinds: &[&str]) -> Result<Vec<UnreferencedSymbol>> {
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.with_conn(|conn| {
+            // Safety: only the placeholder count depends on the input
+            let placeholders = vec!["?"; kinds.len()].join(", ");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT s.id, s.file_id, s.name, s.kind, s.start_line, s.end_line,
+                        s.start_byte, s.end_byte, s.signature, s.doc_comment, s.parent,
+                        f.path, f.language,
+                        (SELECT {REFERENCES_IN_FILE} FROM occurrences o
+                         WHERE o.name = s.name AND o.file_id = s.file_id),
+                        s.is_public, s.is_async, s.is_static, s.is_abstract, s.generic_params,
+                        s.start_column, s.end_column, s.cfg_context
+                 FROM symbols s JOIN files f ON f.id = s.file_id
+                 WHERE s.kind IN ({placeholders})
+                   AND substr(f.path, 1, {}) != '{EXTERNAL_PREFIX}'
+                   AND NOT EXISTS (
+                       SELECT 1 FROM occurrences o
+                       WHERE o.name = s.name AND o.file_id != s.file_id
+                         AND {REFERENCES_IN_FILE} > 0
+                   )
+                 ORDER BY f.path, s.start_line",
+                EXTERNAL_PREFIX.len()
+            ))?;
+
+            let symbols = stmt
+                .query_map(params_from_iter(kinds), |row| {
+                    Ok(UnreferencedSymbol {
+                        symbol: SymbolRecord {
+                            id: row.get(0)?,
+                            file_id: row.get(1)?,
+                            name: row.get(2)?,
+                            kind: row.get(3)?,
+                            start_line: row.get(4)?,
+                            end_line: row.get(5)?,
+                            start_byte: row.get(6)?,
+                            end_byte: row.get(7)?,
+                            signature: row.get(8)?,
+                            doc_comment: row.get(9)?,
+                            parent: row.get(10)?,
+                            qualifiers: qualifiers_from_row(row, 14)?,
+                            start_column: row.get(19)?,
+                            end_column: row.get(20)?,
+                            cfg_context: row.get(21)?,
+                        },
+                        file_path: row.get(11)?,
+                        language: row.get(12)?,
+                        local_references: row.get::<_, Option<i64>>(13)?.unwrap_or(0).max(0)
+                            as usize,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(symbols)
+        })
+    }
+}
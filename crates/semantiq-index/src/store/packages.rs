@@ -0,0 +1,126 @@
+//! Workspace package operations for IndexStore.
+//!
+//! The packages found by [`crate::workspace`] are recorded as a whole, and
+//! each project file is tagged with the package owning it when it is
+//! indexed. Recording a different set of packages tags all files again.
+
+use super::IndexStore;
+use crate::schema::owning_package_sql;
+use crate::workspace::{PackageEcosystem, WorkspacePackage};
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::collections::HashMap;
+use tracing::debug;
+
+impl IndexStore {
+    /// Record the packages of the project, tagging every indexed file with
+    /// the one owning it.
+    ///
+    /// Returns `false`, without writing, when the packages are already the
+    /// recorded ones.
+    pub fn set_packages(&self, packages: &[WorkspacePackage]) -> Result<bool> {
+        let mut packages = packages.to_vec();
+        packages.sort();
+        packages.dedup();
+
+        self.with_conn(|conn| {
+            if Self::get_packages_impl(conn)? == packages {
+                return Ok(false);
+            }
+
+            conn.execute("BEGIN IMMEDIATE", [])?;
+
+            let result = (|| -> Result<usize> {
+                conn.execute("DELETE FROM packages", [])?;
+                let mut stmt = conn.prepare_cached(
+                    "INSERT OR REPLACE INTO packages (root, ecosystem, name) VALUES (?1, ?2, ?3)",
+                )?;
+                for package in &packages {
+                    stmt.execute(params![
+                        package.root,
+                        package.ecosystem.as_str(),
+                        package.name
+                    ])?;
+                }
+
+                let tagged = conn.execute(
+                    &format!(
+                        "UPDATE files SET package = {}",
+                        owning_package_sql("files.path")
+                    ),
+                    [],
+                )?;
+                Self::bump_generation_impl(conn)?;
+                Ok(tagged)
+            })();
+
+            match result {
+                Ok(tagged) => {
+                    conn.execute("COMMIT", [])?;
+                    debug!(
+                        "Recorded {} workspace packages, tagged {} files",
+                        packages.len(),
+                        tagged
+                    );
+                    Ok(true)
+                }
+                Err(e) => {
+                    let _ = conn.execute("ROLLBACK", []);
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    /// Get the recorded packages of the project, sorted by root.
+    pub fn get_packages(&self) -> Result<Vec<WorkspacePackage>> {
+        self.with_conn(Self::get_packages_impl)
+    }
+
+    fn get_packages_impl(conn: &Connection) -> Result<Vec<WorkspacePackage>> {
+        let mut stmt = conn.prepare_cached("SELECT root, ecosystem, name FROM packages")?;
+        let mut packages = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter_map(|(root, ecosystem, name)| {
+                Some(WorkspacePackage {
+                    root,
+                    ecosystem: PackageEcosystem::parse(&ecosystem)?,
+                    name,
+                })
+            })
+            .collect::<Vec<_>>();
+        packages.sort();
+        Ok(packages)
+    }
+
+    /// Get the package of each of the given files. Files outside of any
+    /// package, or not indexed, are left out of the map.
+    pub fn get_file_packages(&self, paths: &[&str]) -> Result<HashMap<String, String>> {
+        if paths.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT package FROM files WHERE path = ?1 AND package IS NOT NULL",
+            )?;
+
+            let mut packages = HashMap::new();
+            for &path in paths {
+                let mut rows = stmt.query([path])?;
+                if let Some(row) = rows.next()? {
+                    packages.insert(path.to_string(), row.get(0)?);
+                }
+            }
+            Ok(packages)
+        })
+    }
+}
@@ -0,0 +1,160 @@
+//! Index generation, persisted query-result cache and query-embedding cache
+//! for IndexStore.
+//!
+//! The index generation is a counter stored in the metadata table and bumped
+//! by every write to indexed data. Cached query results are tagged with the
+//! generation they were computed at and ignored once it moves on. Cached
+//! query embeddings only depend on the embedding model and expire with age.
+
+use super::IndexStore;
+use crate::clock::unix_now_millis;
+use crate::quantization::EmbeddingFormat;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::time::Duration;
+
+/// Metadata key holding the index generation
+const GENERATION_KEY: &str = "index_generation";
+
+impl IndexStore {
+    /// Current index generation (0 for a database that was never written to).
+    pub fn index_generation(&self) -> Result<u64> {
+        self.with_conn(Self::index_generation_impl)
+    }
+
+    /// Internal implementation for use with a held connection.
+    pub(crate) fn index_generation_impl(conn: &Connection) -> Result<u64> {
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = ?1",
+                [GENERATION_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value.and_then(|v| v.parse().ok()).unwrap_or(0))
+    }
+
+    /// Internal implementation for use within a write, bumps the index generation.
+    pub(crate) fn bump_generation_impl(conn: &Connection) -> Result<()> {
+        conn.prepare_cached(
+            "INSERT INTO metadata (key, value) VALUES (?1, '1')
+             ON CONFLICT(key) DO UPDATE SET value = CAST(value AS INTEGER) + 1",
+        )?
+        .execute([GENERATION_KEY])?;
+        Ok(())
+    }
+
+    /// Get a persisted query result, if it was stored at `generation`.
+    pub fn get_cached_query(&self, key: &str, generation: u64) -> Result<Option<String>> {
+        self.with_conn(|conn| {
+            let payload = conn
+                .query_row(
+                    "SELECT payload FROM query_cache WHERE key = ?1 AND generation = ?2",
+                    params![key, generation as i64],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(payload)
+        })
+    }
+
+    /// Persist a query result computed at `generation`.
+    ///
+    /// Entries from other generations are dropped, and only the `max_entries`
+    /// most recently stored entries are kept.
+    pub fn put_cached_query(
+        &self,
+        key: &str,
+        generation: u64,
+        payload: &str,
+        max_entries: usize,
+    ) -> Result<()> {
+        let stored_at = unix_now_millis();
+
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM query_cache WHERE generation != ?1",
+                [generation as i64],
+            )?;
+            conn.execute(
+                "INSERT OR REPLACE INTO query_cache (key, generation, payload, stored_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![key, generation as i64, payload, stored_at],
+            )?;
+            conn.execute(
+                "DELETE FROM query_cache WHERE key NOT IN (
+                     SELECT key FROM query_cache ORDER BY stored_at DESC, rowid DESC LIMIT ?1
+                 )",
+                [max_entries as i64],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Remove all persisted query results, returning how many were removed.
+    pub fn clear_query_cache(&self) -> Result<usize> {
+        self.with_conn(|conn| Ok(conn.execute("DELETE FROM query_cache", [])?))
+    }
+
+    /// Get the cached embedding of `query` by `model`, unless it was stored
+    /// more than `ttl` ago.
+    pub fn get_query_embedding(
+        &self,
+        model: &str,
+        query: &str,
+        ttl: Duration,
+    ) -> Result<Option<Vec<f32>>> {
+        let oldest = unix_now_millis().saturating_sub(ttl.as_millis() as i64);
+        self.with_conn(|conn| {
+            let bytes: Option<Vec<u8>> = conn
+                .query_row(
+                    "UPDATE query_embeddings SET last_used = (SELECT MAX(last_used) + 1 FROM query_embeddings)
+                     WHERE model = ?1 AND query = ?2 AND stored_at >= ?3
+                     RETURNING embedding",
+                    params![model, query, oldest],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(bytes.map(|bytes| EmbeddingFormat::Float32.decode(&bytes)))
+        })
+    }
+
+    /// Cache the embedding of `query` by `model`.
+    ///
+    /// Entries stored more than `ttl` ago are dropped, and only the
+    /// `max_entries` most recently used entries are kept.
+    pub fn put_query_embedding(
+        &self,
+        model: &str,
+        query: &str,
+        embedding: &[f32],
+        ttl: Duration,
+        max_entries: usize,
+    ) -> Result<()> {
+        let now = unix_now_millis();
+        let oldest = now.saturating_sub(ttl.as_millis() as i64);
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM query_embeddings WHERE stored_at < ?1",
+                [oldest],
+            )?;
+            conn.execute(
+                "INSERT OR REPLACE INTO query_embeddings (model, query, embedding, stored_at, last_used)
+                 VALUES (?1, ?2, ?3, ?4, (SELECT COALESCE(MAX(last_used), 0) + 1 FROM query_embeddings))",
+                params![model, query, EmbeddingFormat::Float32.encode(embedding), now],
+            )?;
+            conn.execute(
+                "DELETE FROM query_embeddings WHERE rowid NOT IN (
+                     SELECT rowid FROM query_embeddings ORDER BY last_used DESC LIMIT ?1
+                 )",
+                [max_entries as i64],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Remove all cached query embeddings, returning how many were removed.
+    pub fn clear_query_embeddings(&self) -> Result<usize> {
+        self.with_conn(|conn| Ok(conn.execute("DELETE FROM query_embeddings", [])?))
+    }
+}
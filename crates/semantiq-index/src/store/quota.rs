@@ -0,0 +1,346 @@
+//! Index size quotas for IndexStore.
+//!
+//! On a project too large to index in full, `quota.max_files` caps the files
+//! indexed (see [`crate::priority::IndexQueue::limit`]) and `quota.max_db_mb`
+//! the size of the database: past it, the chunk embeddings of whole
+//! directories are dropped, least recently searched first, while their
+//! symbols, chunks and text stay searchable. Files written to an evicted
+//! directory get no embeddings until the size quota is removed.
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use crate::config::QuotaConfig;
+use crate::external::EXTERNAL_PREFIX;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+use tracing::{info, warn};
+
+/// Metadata key of the number of files left out by `quota.max_files`
+const SKIPPED_FILES_KEY: &str = "quota_skipped_files";
+
+/// Directories whose embeddings were dropped by [`IndexStore::enforce_size_quota`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EvictionReport {
+    pub directories: Vec<String>,
+    /// Chunks whose embedding was dropped
+    pub chunks: usize,
+    /// Size of the database before and after, in bytes
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// A directory whose embeddings were dropped
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvictedDirectory {
+    pub directory: String,
+    pub evicted_at: i64,
+    /// Chunks whose embedding was dropped
+    pub chunks: usize,
+    /// When results were last found in the directory, if ever
+    pub last_searched: Option<i64>,
+}
+
+/// State of the index quotas, for `semantiq stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QuotaStats {
+    /// Indexed files of the project, external dependencies aside
+    pub project_files: usize,
+    /// Files left out by `quota.max_files` in the last initial pass
+    pub skipped_files: usize,
+    /// Directories whose embeddings were dropped, most recent first
+    pub evicted: Vec<EvictedDirectory>,
+}
+
+/// Directory a file counts in for quotas: its parent, "." at the root
+pub fn quota_directory(path: &str) -> &str {
+    path.rsplit_once('/').map_or(".", |(dir, _)| dir)
+}
+
+/// Embedded chunks of a directory, candidates for eviction
+struct EmbeddedDirectory {
+    directory: String,
+    file_ids: Vec<i64>,
+    chunks: usize,
+    bytes: u64,
+}
+
+impl IndexStore {
+    /// Record the directories searches find results in from now on, which
+    /// `quota.max_db_mb` evicts last.
+    pub fn set_search_tracking(&self, enabled: bool) {
+        self.search_tracking.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Record that results were found in the directories of `paths`. Does
+    /// nothing unless search tracking is enabled.
+    pub fn record_searched_dirs<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        if !self.search_tracking.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut dirs: Vec<&str> = paths.into_iter().map(quota_directory).collect();
+        dirs.sort_unstable();
+        dirs.dedup();
+        if dirs.is_empty() {
+            return Ok(());
+        }
+
+        let now = unix_now();
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "INSERT INTO searched_dirs (dir, last_searched) VALUES (?1, ?2)
+                 ON CONFLICT(dir) DO UPDATE SET last_searched = excluded.last_searched",
+            )?;
+            for dir in dirs {
+                stmt.execute(params![dir, now])?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Number of indexed files of the project, external dependencies aside.
+    pub fn project_file_count(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM files WHERE substr(path, 1, length(?1)) != ?1",
+                [EXTERNAL_PREFIX],
+                |row| row.get(0),
+            )?;
+            Ok(count as usize)
+        })
+    }
+
+    /// Record the number of files the last initial pass left out by
+    /// `quota.max_files`.
+    pub fn set_quota_skipped_files(&self, count: usize) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                params![SKIPPED_FILES_KEY, count.to_string()],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Whether the embeddings of the directory of `path` were dropped, so
+    /// that its chunks are written without embeddings.
+    pub fn embeddings_evicted(&self, path: &str) -> Result<bool> {
+        self.with_conn(|conn| Self::embeddings_evicted_impl(conn, path))
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn embeddings_evicted_impl(conn: &Connection, path: &str) -> Result<bool> {
+        Ok(conn
+            .prepare_cached("SELECT 1 FROM evicted_dirs WHERE dir = ?1")?
+            .query_row([quota_directory(path)], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    /// Drop the chunk embeddings of the least recently searched directories
+    /// until the database fits in `quota.max_db_mb`. Directories never
+    /// searched go first, then those of lowest `quota.path_weights`, then the
+    /// largest. Without a size quota, directories evicted earlier get their
+    /// embeddings back instead (see [`Self::restore_evicted_embeddings`]).
+    pub fn enforce_size_quota(&self, quota: &QuotaConfig) -> Result<EvictionReport> {
+        let size_before = self.get_database_size()?;
+        let mut report = EvictionReport {
+            size_before,
+            size_after: size_before,
+            ..EvictionReport::default()
+        };
+        let Some(max_bytes) = quota.max_db_bytes() else {
+            self.restore_evicted_embeddings()?;
+            return Ok(report);
+        };
+        if size_before <= max_bytes {
+            return Ok(report);
+        }
+
+        let mut candidates = self.embedded_directories()?;
+        let last_searched = self.last_searched_dirs()?;
+        candidates.sort_by_key(|dir| {
+            (
+                last_searched.get(&dir.directory).copied().unwrap_or(0),
+                quota.path_weight(&dir.directory),
+                std::cmp::Reverse(dir.bytes),
+            )
+        });
+
+        // Embeddings are stored in `chunks` and in `chunks_vec`
+        let excess = size_before - max_bytes;
+        let mut freed = 0;
+        let evicted: Vec<EmbeddedDirectory> = candidates
+            .into_iter()
+            .take_while(|dir| {
+                let more = freed < excess;
+                freed += dir.bytes * 2;
+                more
+            })
+            .collect();
+        if evicted.is_empty() {
+            return Ok(report);
+        }
+
+        self.with_conn(|conn| {
+            conn.execute("BEGIN IMMEDIATE", [])?;
+            let result = Self::evict_directories_impl(conn, &evicted);
+            match result {
+                Ok(()) => conn.execute("COMMIT", [])?,
+                Err(e) => {
+                    let _ = conn.execute("ROLLBACK", []);
+                    return Err(e);
+                }
+            };
+            // Release the pages of the dropped embeddings
+            if let Err(e) = conn.execute_batch("VACUUM") {
+                warn!("Failed to vacuum database after eviction: {}", e);
+            }
+            Ok(())
+        })?;
+
+        report.chunks = evicted.iter().map(|dir| dir.chunks).sum();
+        report.directories = evicted.into_iter().map(|dir| dir.directory).collect();
+        report.size_after = self.get_database_size()?;
+        info!(
+            "Dropped the embeddings of {} chunks in {} directories ({} -> {} bytes)",
+            report.chunks,
+            report.directories.len(),
+            report.size_before,
+            report.size_after
+        );
+        Ok(report)
+    }
+
+    fn evict_directories_impl(conn: &Connection, evicted: &[EmbeddedDirectory]) -> Result<()> {
+        let now = unix_now();
+        for dir in evicted {
+            for file_id in &dir.file_ids {
+                // vec0 tables have no foreign keys to cascade from
+                conn.prepare_cached(
+                    "DELETE FROM chunks_vec WHERE chunk_id IN
+                         (SELECT id FROM chunks WHERE file_id = ?1)",
+                )?
+                .execute([file_id])?;
+                conn.prepare_cached(
+                    "UPDATE chunks SET embedding = NULL, token_count = NULL,
+                         embedding_windows = NULL, embedding_truncated = 0
+                     WHERE file_id = ?1",
+                )?
+                .execute([file_id])?;
+                conn.prepare_cached("UPDATE files SET embeddings_evicted = 1 WHERE id = ?1")?
+                    .execute([file_id])?;
+            }
+            conn.prepare_cached(
+                "INSERT INTO evicted_dirs (dir, evicted_at, chunks) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(dir) DO UPDATE SET
+                     evicted_at = excluded.evicted_at,
+                     chunks = chunks + excluded.chunks",
+            )?
+            .execute(params![dir.directory, now, dir.chunks as i64])?;
+        }
+        Self::bump_generation_impl(conn)
+    }
+
+    /// Directories with embedded chunks, with the bytes of their embeddings
+    fn embedded_directories(&self) -> Result<Vec<EmbeddedDirectory>> {
+        let rows: Vec<(i64, String, i64, i64)> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT f.id, f.path, COUNT(*), SUM(length(c.embedding))
+                 FROM files f JOIN chunks c ON c.file_id = f.id
+                 WHERE c.embedding IS NOT NULL
+                 GROUP BY f.id",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        let mut by_dir: BTreeMap<String, EmbeddedDirectory> = BTreeMap::new();
+        for (file_id, path, chunks, bytes) in rows {
+            let directory = quota_directory(&path).to_string();
+            let entry = by_dir
+                .entry(directory.clone())
+                .or_insert_with(|| EmbeddedDirectory {
+                    directory,
+                    file_ids: Vec::new(),
+                    chunks: 0,
+                    bytes: 0,
+                });
+            entry.file_ids.push(file_id);
+            entry.chunks += chunks as usize;
+            entry.bytes += bytes as u64;
+        }
+        Ok(by_dir.into_values().collect())
+    }
+
+    fn last_searched_dirs(&self) -> Result<HashMap<String, i64>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT dir, last_searched FROM searched_dirs")?;
+            let dirs = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            Ok(dirs)
+        })
+    }
+
+    /// Give the evicted directories their embeddings back: their files are
+    /// indexed again, and embedded, by the next indexing pass. Returns the
+    /// number of files.
+    pub fn restore_evicted_embeddings(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            let restored = conn.execute(
+                "UPDATE files SET hash = '', embeddings_evicted = 0 WHERE embeddings_evicted = 1",
+                [],
+            )?;
+            let dirs = conn.execute("DELETE FROM evicted_dirs", [])?;
+            if restored > 0 || dirs > 0 {
+                info!(
+                    "Restoring the embeddings of {} files in {} directories",
+                    restored, dirs
+                );
+            }
+            Ok(restored)
+        })
+    }
+
+    /// Get the state of the index quotas.
+    pub fn get_quota_stats(&self) -> Result<QuotaStats> {
+        let project_files = self.project_file_count()?;
+        self.with_conn(|conn| {
+            let skipped_files: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM metadata WHERE key = ?1",
+                    [SKIPPED_FILES_KEY],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let mut stmt = conn.prepare(
+                "SELECT e.dir, e.evicted_at, e.chunks, s.last_searched
+                 FROM evicted_dirs e LEFT JOIN searched_dirs s ON s.dir = e.dir
+                 ORDER BY e.evicted_at DESC, e.dir",
+            )?;
+            let evicted = stmt
+                .query_map([], |row| {
+                    Ok(EvictedDirectory {
+                        directory: row.get(0)?,
+                        evicted_at: row.get(1)?,
+                        chunks: row.get::<_, i64>(2)? as usize,
+                        last_searched: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(QuotaStats {
+                project_files,
+                skipped_files: skipped_files.and_then(|v| v.parse().ok()).unwrap_or(0),
+                evicted,
+            })
+        })
+    }
+}
@@ -0,0 +1,136 @@
+//! Type relation operations for IndexStore.
+
+use super::IndexStore;
+use crate::schema::TypeRelationRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row, params, params_from_iter};
+use semantiq_parser::{RelationKind, TypeRelation};
+
+const RELATION_COLUMNS: &str =
+    "r.id, r.file_id, f.path, f.language, r.type_name, r.kind, r.target, r.start_line, r.end_line";
+
+impl IndexStore {
+    /// Replace the type relations of a file.
+    pub fn insert_type_relations(&self, file_id: i64, relations: &[TypeRelation]) -> Result<()> {
+        self.with_conn(|conn| {
+            Self::insert_type_relations_impl(conn, file_id, relations)?;
+            Self::bump_generation_impl(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_type_relations_impl(
+        conn: &Connection,
+        file_id: i64,
+        relations: &[TypeRelation],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM type_relations WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO type_relations (file_id, type_name, kind, target, start_line, end_line)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for relation in relations {
+            stmt.execute(params![
+                file_id,
+                relation.type_name,
+                relation.kind.as_str(),
+                relation.target,
+                relation.start_line as i64,
+                relation.end_line as i64,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Get the type relations declared in a file.
+    pub fn get_type_relations(&self, file_id: i64) -> Result<Vec<TypeRelationRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {RELATION_COLUMNS} FROM type_relations r JOIN files f ON f.id = r.file_id
+                 WHERE r.file_id = ?1 ORDER BY r.start_line, r.id"
+            ))?;
+            let records = stmt
+                .query_map([file_id], relation_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+
+    /// Get the relations of the given kind whose target is `target`
+    /// (e.g. every type implementing a trait), ordered by file and line.
+    pub fn find_relations_to(
+        &self,
+        target: &str,
+        kind: RelationKind,
+    ) -> Result<Vec<TypeRelationRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {RELATION_COLUMNS} FROM type_relations r JOIN files f ON f.id = r.file_id
+                 WHERE r.target = ?1 AND r.kind = ?2 ORDER BY f.path, r.start_line"
+            ))?;
+            let records = stmt
+                .query_map(params![target, kind.as_str()], relation_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+
+    /// Get the relations of the given kind declared by `type_name`
+    /// (e.g. the methods required by a Go interface).
+    pub fn find_relations_from(
+        &self,
+        type_name: &str,
+        kind: RelationKind,
+    ) -> Result<Vec<TypeRelationRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {RELATION_COLUMNS} FROM type_relations r JOIN files f ON f.id = r.file_id
+                 WHERE r.type_name = ?1 AND r.kind = ?2 ORDER BY f.path, r.start_line"
+            ))?;
+            let records = stmt
+                .query_map(params![type_name, kind.as_str()], relation_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+
+    /// Get the `has_method` relations naming any of `methods`, ordered by
+    /// type, file and line.
+    pub fn find_method_declarations(&self, methods: &[String]) -> Result<Vec<TypeRelationRecord>> {
+        if methods.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.with_conn(|conn| {
+            // Safety: only the placeholder count depends on the input
+            let placeholders = vec!["?"; methods.len()].join(", ");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {RELATION_COLUMNS} FROM type_relations r JOIN files f ON f.id = r.file_id
+                 WHERE r.kind = '{}' AND r.target IN ({placeholders})
+                 ORDER BY r.type_name, f.path, r.start_line",
+                RelationKind::HasMethod.as_str()
+            ))?;
+            let records = stmt
+                .query_map(params_from_iter(methods), relation_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+}
+
+fn relation_from_row(row: &Row<'_>) -> rusqlite::Result<TypeRelationRecord> {
+    Ok(TypeRelationRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_path: row.get(2)?,
+        language: row.get(3)?,
+        type_name: row.get(4)?,
+        kind: row.get(5)?,
+        target: row.get(6)?,
+        start_line: row.get(7)?,
+        end_line: row.get(8)?,
+    })
+}
@@ -0,0 +1,77 @@
+//! HTTP route operations for IndexStore.
+//!
+//! The method and path of each route entry point (see
+//! `semantiq_parser::routes`), written with the entry points of its file.
+//! Paths keep the syntax of their framework, so requests are matched against
+//! them by the retrieval engine rather than by SQL.
+
+use super::IndexStore;
+use crate::schema::RouteRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row, params};
+use semantiq_parser::{Entrypoint, HttpRoute};
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction: replace the
+    /// routes of a file with those of its route entry points.
+    pub(crate) fn insert_routes_impl(
+        conn: &Connection,
+        file_id: i64,
+        entrypoints: &[Entrypoint],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM routes WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO routes (file_id, method, path, handler, start_line, end_line)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for entrypoint in entrypoints {
+            let Some(route) = HttpRoute::of_entrypoint(entrypoint) else {
+                continue;
+            };
+            stmt.execute(params![
+                file_id,
+                route.method,
+                route.path,
+                entrypoint.handler,
+                entrypoint.start_line as i64,
+                entrypoint.end_line as i64,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Get the routes handling `method` (routes of every method included),
+    /// or all routes, ordered by path, method and file.
+    pub fn get_routes(&self, method: Option<&str>) -> Result<Vec<RouteRecord>> {
+        let method = method.map(str::to_uppercase);
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT r.id, r.file_id, f.path, f.language, r.method, r.path, r.handler,
+                        r.start_line, r.end_line
+                 FROM routes r JOIN files f ON f.id = r.file_id
+                 WHERE ?1 IS NULL OR r.method IS NULL OR r.method = ?1
+                 ORDER BY r.path, r.method, f.path, r.start_line, r.id",
+            )?;
+            let records = stmt
+                .query_map(params![method], route_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(records)
+        })
+    }
+}
+
+fn route_from_row(row: &Row<'_>) -> rusqlite::Result<RouteRecord> {
+    Ok(RouteRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_path: row.get(2)?,
+        language: row.get(3)?,
+        method: row.get(4)?,
+        path: row.get(5)?,
+        handler: row.get(6)?,
+        start_line: row.get(7)?,
+        end_line: row.get(8)?,
+    })
+}
@@ -0,0 +1,234 @@
+//! Saved query operations for IndexStore.
+//!
+//! Teams save recurring searches under a name (`semantiq query save`) and
+//! run them again by name from the CLI or the `semantiq_saved_search` tool.
+//! The text and filters of a saved query may hold `{parameter}` placeholders,
+//! filled in with the values given when it is run. Saved queries are kept
+//! across reindexing.
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use anyhow::{Result, bail};
+use rusqlite::{OptionalExtension, Row, params};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Longest name of a saved query
+const MAX_NAME_LENGTH: usize = 64;
+
+/// A search saved under a name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub query: String,
+    /// Search filters by parameter name of `semantiq_search`, e.g.
+    /// `file_type` → `rs`
+    pub filters: BTreeMap<String, String>,
+    pub description: Option<String>,
+    /// Unix time of the last save, set by [`IndexStore::save_query`]
+    pub saved_at: i64,
+}
+
+impl SavedQuery {
+    pub fn new(name: &str, query: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_filter(mut self, name: &str, value: &str) -> Self {
+        self.filters.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Names of the `{parameter}` placeholders of the query text and
+    /// filters, in order of first appearance
+    pub fn parameters(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for text in std::iter::once(&self.query).chain(self.filters.values()) {
+            for name in placeholders(text) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names
+    }
+
+    /// The query with its placeholders replaced by `values`. Fails when a
+    /// parameter has no value or a value matches no parameter.
+    pub fn bind(&self, values: &HashMap<String, String>) -> Result<SavedQuery> {
+        let parameters = self.parameters();
+        if let Some(missing) = parameters.iter().find(|p| !values.contains_key(*p)) {
+            bail!(
+                "Missing value for parameter '{}' of saved query '{}'",
+                missing,
+                self.name
+            );
+        }
+        let mut unknown: Vec<&String> = values
+            .keys()
+            .filter(|name| !parameters.contains(name))
+            .collect();
+        unknown.sort();
+        if let Some(unknown) = unknown.first() {
+            let expected = if parameters.is_empty() {
+                "it has no parameters".to_string()
+            } else {
+                format!("parameters: {}", parameters.join(", "))
+            };
+            bail!(
+                "Unknown parameter '{}' of saved query '{}' ({})",
+                unknown,
+                self.name,
+                expected
+            );
+        }
+
+        Ok(SavedQuery {
+            query: substitute(&self.query, values),
+            filters: self
+                .filters
+                .iter()
+                .map(|(name, value)| (name.clone(), substitute(value, values)))
+                .collect(),
+            ..self.clone()
+        })
+    }
+}
+
+/// Names of the `{name}` placeholders of a text, names being made of
+/// letters, digits, `_` and `-`. Other braces are kept as they are.
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{').skip(1).filter_map(|rest| {
+        let (name, _) = rest.split_once('}')?;
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        valid.then_some(name)
+    })
+}
+
+fn substitute(text: &str, values: &HashMap<String, String>) -> String {
+    let mut output = text.to_string();
+    for name in placeholders(text) {
+        if let Some(value) = values.get(name) {
+            output = output.replace(&format!("{{{}}}", name), value);
+        }
+    }
+    output
+}
+
+/// Check that a saved query name is 1 to 64 letters, digits, `_`, `-` or `.`
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        bail!(
+            "Invalid saved query name '{}': expected 1 to {} characters",
+            name,
+            MAX_NAME_LENGTH
+        );
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        bail!(
+            "Invalid saved query name '{}': use letters, digits, '_', '-' and '.'",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn saved_query_from_row(row: &Row<'_>) -> rusqlite::Result<SavedQuery> {
+    let filters: String = row.get(2)?;
+    Ok(SavedQuery {
+        name: row.get(0)?,
+        query: row.get(1)?,
+        filters: serde_json::from_str(&filters).unwrap_or_default(),
+        description: row.get(3)?,
+        saved_at: row.get(4)?,
+    })
+}
+
+impl IndexStore {
+    /// Save a query under its name, replacing any query of that name.
+    ///
+    /// Returns `true` when a query was replaced.
+    pub fn save_query(&self, query: &SavedQuery) -> Result<bool> {
+        validate_name(&query.name)?;
+        if query.query.trim().is_empty() {
+            bail!("The text of saved query '{}' cannot be empty", query.name);
+        }
+        let filters = serde_json::to_string(&query.filters)?;
+        let saved_at = unix_now();
+
+        self.with_conn(|conn| {
+            let replaced = conn
+                .query_row(
+                    "SELECT 1 FROM saved_queries WHERE name = ?1",
+                    [&query.name],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            conn.execute(
+                "INSERT OR REPLACE INTO saved_queries (name, query, filters, description, saved_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    query.name,
+                    query.query.trim(),
+                    filters,
+                    query.description,
+                    saved_at
+                ],
+            )?;
+            Ok(replaced)
+        })
+    }
+
+    /// Get the query saved under `name`.
+    pub fn get_saved_query(&self, name: &str) -> Result<Option<SavedQuery>> {
+        self.with_conn(|conn| {
+            let query = conn
+                .query_row(
+                    "SELECT name, query, filters, description, saved_at
+                     FROM saved_queries WHERE name = ?1",
+                    [name],
+                    saved_query_from_row,
+                )
+                .optional()?;
+            Ok(query)
+        })
+    }
+
+    /// Get every saved query, sorted by name.
+    pub fn list_saved_queries(&self) -> Result<Vec<SavedQuery>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT name, query, filters, description, saved_at
+                 FROM saved_queries ORDER BY name",
+            )?;
+            let queries = stmt
+                .query_map([], saved_query_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(queries)
+        })
+    }
+
+    /// Delete the query saved under `name`. Returns `false` if there is none.
+    pub fn delete_saved_query(&self, name: &str) -> Result<bool> {
+        self.with_conn(|conn| {
+            let deleted = conn.execute("DELETE FROM saved_queries WHERE name = ?1", [name])?;
+            Ok(deleted > 0)
+        })
+    }
+}
@@ -0,0 +1,177 @@
+//! Paged scans of chunks for IndexStore.
+//!
+//! A pass over every chunk, such as converting the embedding format or
+//! switching to the vectors of another model, reads it in pages keyed by
+//! chunk id, each taking at most `storage.scan_memory_mb` of memory, instead
+//! of loading the whole table: with 384 floats per embedding, a large index
+//! takes gigabytes at once. [`ChunkEmbeddings`] releases the connection
+//! between its pages, so indexing and searches go on during a scan.
+
+use super::IndexStore;
+use super::chunks::{parse_symbols_json, read_token_fit};
+use super::compression::read_content;
+use crate::schema::ChunkRecord;
+use anyhow::Result;
+use rusqlite::{Connection, Row};
+use std::sync::atomic::Ordering;
+
+/// Memory taken by a page of a scan by default (`[storage] scan_memory_mb`
+/// in `semantiq.toml`)
+pub const DEFAULT_SCAN_MEMORY_MB: u64 = 64;
+
+/// Rows of the query `sql`, taking its chunk id after which to start as
+/// `?1` and ordered by it, until their `size` adds up to `budget` bytes.
+/// The page holds at least one row, unless the query has none left.
+pub(super) fn read_page<T>(
+    conn: &Connection,
+    sql: &str,
+    after: i64,
+    budget: usize,
+    mut read: impl FnMut(&Row<'_>) -> rusqlite::Result<T>,
+    size: impl Fn(&T) -> usize,
+) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut rows = stmt.query([after])?;
+    let mut page = Vec::new();
+    let mut used = 0;
+    while let Some(row) = rows.next()? {
+        let item = read(row)?;
+        used += size(&item);
+        page.push(item);
+        if used >= budget {
+            break;
+        }
+    }
+    Ok(page)
+}
+
+/// Chunks with embeddings, read a page at a time (see
+/// [`IndexStore::chunks_with_embeddings`])
+pub struct ChunkEmbeddings<'a> {
+    store: &'a IndexStore,
+    /// Id of the last chunk read
+    after: i64,
+    page: std::vec::IntoIter<ChunkRecord>,
+    done: bool,
+}
+
+impl Iterator for ChunkEmbeddings<'_> {
+    type Item = Result<ChunkRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(chunk) = self.page.next() {
+            return Some(Ok(chunk));
+        }
+        if self.done {
+            return None;
+        }
+        match self.store.get_chunks_with_embeddings_page(self.after) {
+            Ok(page) => {
+                let Some(last) = page.last() else {
+                    self.done = true;
+                    return None;
+                };
+                self.after = last.id;
+                self.page = page.into_iter();
+                self.page.next().map(Ok)
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl IndexStore {
+    /// Take at most `bytes` of memory per page of a scan over every chunk.
+    pub fn set_scan_memory(&self, bytes: usize) {
+        self.scan_memory.store(bytes.max(1), Ordering::Relaxed);
+    }
+
+    /// Memory taken by a page of a scan over every chunk, in bytes
+    pub fn scan_memory(&self) -> usize {
+        self.scan_memory.load(Ordering::Relaxed)
+    }
+
+    /// Iterate over the chunks that have embeddings, in order of id, with
+    /// their embedding.
+    ///
+    /// Chunks are read a page at a time of at most [`Self::scan_memory`]
+    /// bytes; chunks written or deleted during the scan may be missed or
+    /// seen.
+    pub fn chunks_with_embeddings(&self) -> ChunkEmbeddings<'_> {
+        ChunkEmbeddings {
+            store: self,
+            after: 0,
+            page: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+
+    /// Get all chunks that have embeddings, with their embedding.
+    ///
+    /// Holds every embedding of the index at once; iterate over
+    /// [`Self::chunks_with_embeddings`] instead.
+    #[deprecated(note = "loads every embedding at once, use `chunks_with_embeddings`")]
+    pub fn get_chunks_with_embeddings(&self) -> Result<Vec<(ChunkRecord, Vec<f32>)>> {
+        self.chunks_with_embeddings()
+            .map(|chunk| {
+                let chunk = chunk?;
+                let embedding = chunk.embedding.clone().unwrap_or_default();
+                Ok((chunk, embedding))
+            })
+            .collect()
+    }
+
+    /// Get the chunks that have embeddings and an id above `after`, in
+    /// order of id, up to [`Self::scan_memory`] bytes of content and
+    /// embeddings. Empty once every chunk has been read.
+    pub fn get_chunks_with_embeddings_page(&self, after: i64) -> Result<Vec<ChunkRecord>> {
+        self.with_conn(|conn| {
+            let codec = self.chunk_codec_for(conn)?;
+            let format = self.embedding_format();
+            read_page(
+                conn,
+                "SELECT id, file_id, content, start_line, end_line, start_byte, end_byte, symbols_json, embedding, symbol_id,
+                        token_count, embedding_windows, embedding_truncated, distinctiveness, title
+                 FROM chunks
+                 WHERE embedding IS NOT NULL AND id > ?1
+                 ORDER BY id",
+                after,
+                self.scan_memory(),
+                |row| {
+                    let symbols_json: String = row.get(7)?;
+                    let embedding_bytes: Vec<u8> = row.get(8)?;
+                    Ok(ChunkRecord {
+                        id: row.get(0)?,
+                        file_id: row.get(1)?,
+                        content: read_content(&codec, row, 2)?,
+                        start_line: row.get(3)?,
+                        end_line: row.get(4)?,
+                        start_byte: row.get(5)?,
+                        end_byte: row.get(6)?,
+                        symbols: parse_symbols_json(&symbols_json),
+                        embedding: Some(format.decode(&embedding_bytes)),
+                        symbol_id: row.get(9)?,
+                        token_fit: read_token_fit(row, 10)?,
+                        distinctiveness: row.get(13)?,
+                        title: row.get(14)?,
+                    })
+                },
+                chunk_size,
+            )
+        })
+    }
+}
+
+/// Memory taken by a chunk read with its embedding, roughly
+fn chunk_size(chunk: &ChunkRecord) -> usize {
+    std::mem::size_of::<ChunkRecord>()
+        + chunk.content.len()
+        + chunk.symbols.iter().map(String::len).sum::<usize>()
+        + chunk
+            .embedding
+            .as_ref()
+            .map_or(0, |e| e.len() * std::mem::size_of::<f32>())
+}
@@ -0,0 +1,196 @@
+//! Aggregate statistics queries for detailed index reports.
+
+use super::IndexStore;
+use crate::schema::FileRecord;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// File, symbol and chunk counts for a single language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageStats {
+    pub language: String,
+    pub file_count: usize,
+    pub symbol_count: usize,
+    pub chunk_count: usize,
+    /// Chunks that have an embedding stored
+    pub embedded_chunk_count: usize,
+}
+
+impl LanguageStats {
+    /// Percentage of chunks with an embedding (0.0 when there are no chunks)
+    pub fn embedding_coverage(&self) -> f32 {
+        if self.chunk_count == 0 {
+            0.0
+        } else {
+            self.embedded_chunk_count as f32 / self.chunk_count as f32 * 100.0
+        }
+    }
+}
+
+/// File counts and freshness for a directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryStats {
+    /// Directory path relative to the project root ("." for root-level files)
+    pub directory: String,
+    pub file_count: usize,
+    pub total_size: i64,
+    /// Most recent `indexed_at` timestamp among the directory's files
+    pub last_indexed_at: i64,
+}
+
+impl IndexStore {
+    /// Get per-language file, symbol and chunk counts, ordered by file count.
+    ///
+    /// Files without a detected language are grouped under "unknown".
+    pub fn get_language_stats(&self) -> Result<Vec<LanguageStats>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT
+                    COALESCE(f.language, 'unknown') AS lang,
+                    COUNT(*) AS file_count,
+                    COALESCE(SUM(s.symbol_count), 0),
+                    COALESCE(SUM(c.chunk_count), 0),
+                    COALESCE(SUM(c.embedded_count), 0)
+                 FROM files f
+                 LEFT JOIN (
+                    SELECT file_id, COUNT(*) AS symbol_count FROM symbols GROUP BY file_id
+                 ) s ON s.file_id = f.id
+                 LEFT JOIN (
+                    SELECT file_id,
+                           COUNT(*) AS chunk_count,
+                           SUM(CASE WHEN embedding IS NOT NULL THEN 1 ELSE 0 END) AS embedded_count
+                    FROM chunks GROUP BY file_id
+                 ) c ON c.file_id = f.id
+                 GROUP BY lang
+                 ORDER BY file_count DESC, lang ASC",
+            )?;
+
+            let stats = stmt
+                .query_map([], |row| {
+                    Ok(LanguageStats {
+                        language: row.get(0)?,
+                        file_count: row.get::<_, i64>(1)? as usize,
+                        symbol_count: row.get::<_, i64>(2)? as usize,
+                        chunk_count: row.get::<_, i64>(3)? as usize,
+                        embedded_chunk_count: row.get::<_, i64>(4)? as usize,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(stats)
+        })
+    }
+
+    /// Get the number of chunks with an embedding and the total number of chunks.
+    pub fn get_embedding_coverage(&self) -> Result<(usize, usize)> {
+        self.with_conn(|conn| {
+            let (embedded, total): (i64, i64) = conn.query_row(
+                "SELECT
+                    COALESCE(SUM(CASE WHEN embedding IS NOT NULL THEN 1 ELSE 0 END), 0),
+                    COUNT(*)
+                 FROM chunks",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            Ok((embedded as usize, total as usize))
+        })
+    }
+
+    /// Get the number of chunks over the embedding model's token limit,
+    /// embedded in several windows, and of those still truncated.
+    pub fn get_token_fit_stats(&self) -> Result<(usize, usize)> {
+        self.with_conn(|conn| {
+            let (windowed, truncated): (i64, i64) = conn.query_row(
+                "SELECT
+                    COALESCE(SUM(CASE WHEN embedding_windows > 1 THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(embedding_truncated), 0)
+                 FROM chunks WHERE embedding IS NOT NULL",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            Ok((windowed as usize, truncated as usize))
+        })
+    }
+
+    /// Get the largest indexed files, biggest first.
+    pub fn get_largest_files(&self, limit: usize) -> Result<Vec<FileRecord>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, path, language, hash, size, last_modified, indexed_at, encoding, package
+                 FROM files ORDER BY size DESC, path ASC LIMIT ?1",
+            )?;
+
+            let files = stmt
+                .query_map([limit as i64], |row| {
+                    Ok(FileRecord {
+                        id: row.get(0)?,
+                        path: row.get(1)?,
+                        language: row.get(2)?,
+                        hash: row.get(3)?,
+                        size: row.get(4)?,
+                        last_modified: row.get(5)?,
+                        indexed_at: row.get(6)?,
+                        encoding: row.get(7)?,
+                        package: row.get(8)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(files)
+        })
+    }
+
+    /// Get file counts and last index time grouped by directory.
+    ///
+    /// Paths are grouped by their first `depth` directory components, so a
+    /// depth of 1 reports top-level directories only.
+    pub fn get_directory_stats(&self, depth: usize) -> Result<Vec<DirectoryStats>> {
+        let rows: Vec<(String, i64, i64)> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT path, size, indexed_at FROM files")?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        let mut by_dir: BTreeMap<String, DirectoryStats> = BTreeMap::new();
+        for (path, size, indexed_at) in rows {
+            let directory = directory_prefix(&path, depth.max(1));
+            let entry = by_dir
+                .entry(directory.clone())
+                .or_insert_with(|| DirectoryStats {
+                    directory,
+                    file_count: 0,
+                    total_size: 0,
+                    last_indexed_at: 0,
+                });
+            entry.file_count += 1;
+            entry.total_size += size;
+            entry.last_indexed_at = entry.last_indexed_at.max(indexed_at);
+        }
+
+        Ok(by_dir.into_values().collect())
+    }
+
+    /// Get the size of the database in bytes (allocated pages).
+    pub fn get_database_size(&self) -> Result<u64> {
+        self.with_conn(|conn| {
+            let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+            let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+            Ok((page_count * page_size) as u64)
+        })
+    }
+}
+
+/// Directory made of the first `depth` components of a relative file path.
+fn directory_prefix(path: &str, depth: usize) -> String {
+    let normalized = path.replace('\\', "/");
+    let components: Vec<&str> = normalized.split('/').collect();
+    // The last component is the file name
+    let dirs = &components[..components.len().saturating_sub(1)];
+    if dirs.is_empty() {
+        ".".to_string()
+    } else {
+        dirs[..dirs.len().min(depth)].join("/")
+    }
+}
@@ -0,0 +1,426 @@
+//! Symbol dependency edges for IndexStore.
+//!
+//! File dependencies tell that `server.rs` imports `config.rs`; symbol edges
+//! tell that `handle_request` calls `load_config`. When a file is written,
+//! each line an identifier appears on is attributed to the innermost symbol
+//! enclosing it, and stored as an edge from that symbol to the name. Names
+//! are resolved to the symbols they designate when queried, so that edges
+//! follow the files defining them as they change. A name referenced in a
+//! symbol of file F designates:
+//!
+//! 1. the symbols of that name defined in F, if any
+//! 2. otherwise those defined in the files F imports (import paths are
+//!    matched to files by their last segments), in the other files of its
+//!    directory for Go, whose packages are directories, or, failing those,
+//!    anywhere when a local import of F names it (`use crate::{load_config}`)
+//! 3. otherwise nothing: the name is a local variable, a field, or comes
+//!    from the standard library or an external package
+
+use super::IndexStore;
+use super::symbols::qualifiers_from_row;
+use crate::paths::normalize_path;
+use crate::schema::SymbolRecord;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use semantiq_parser::{ImportedSymbol, Occurrence};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Columns read by [`symbol_from_row`], followed by the path of the file
+const SYMBOL_COLUMNS: &str = "s.id, s.file_id, s.name, s.kind, s.start_line, s.end_line,
+    s.start_byte, s.end_byte, s.signature, s.doc_comment, s.parent,
+    s.is_public, s.is_async, s.is_static, s.is_abstract, s.generic_params,
+    s.start_column, s.end_column, s.cfg_context, f.path";
+
+/// File stems standing for their directory in import paths
+const DIRECTORY_STEMS: &[&str] = &["mod", "index", "__init__", "lib", "main"];
+
+/// A symbol using, or used by, the queried one
+#[derive(Debug, Clone)]
+pub struct SymbolEdge {
+    /// The other symbol
+    pub symbol: SymbolRecord,
+    pub file_path: String,
+    /// First line referencing the used symbol, in the file of the using one
+    pub line: usize,
+    /// Lines referencing the used symbol
+    pub count: usize,
+}
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction: record the
+    /// names referenced by each symbol of a file, from its occurrences. The
+    /// symbols must be inserted first; replacing them removes their edges.
+    pub(crate) fn insert_symbol_edges_impl(
+        conn: &Connection,
+        file_id: i64,
+        occurrences: &[Occurrence],
+    ) -> Result<()> {
+        let mut symbols: Vec<(i64, String, usize, usize)> = conn
+            .prepare_cached(
+                "SELECT id, name, start_line, end_line FROM symbols WHERE file_id = ?1",
+            )?
+            .query_map([file_id], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get::<_, i64>(2)?.max(0) as usize,
+                    row.get::<_, i64>(3)?.max(0) as usize,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        if symbols.is_empty() {
+            return Ok(());
+        }
+
+        // Innermost symbol of each line: larger symbols are laid first and
+        // overwritten by the ones nested in them
+        symbols.sort_by_key(|(_, _, start, end)| std::cmp::Reverse(end.saturating_sub(*start)));
+        let last_line = symbols.iter().map(|(_, _, _, end)| *end).max().unwrap_or(0);
+        let mut owners: Vec<Option<usize>> = vec![None; last_line + 1];
+        for (index, (_, _, start, end)) in symbols.iter().enumerate() {
+            for owner in &mut owners[*start..=*end] {
+                *owner = Some(index);
+            }
+        }
+
+        let mut edges: BTreeMap<(i64, &str), (usize, usize)> = BTreeMap::new();
+        for occurrence in occurrences {
+            for &line in &occurrence.lines {
+                let Some(&Some(owner)) = owners.get(line) else {
+                    continue;
+                };
+                let (symbol_id, name, _, _) = &symbols[owner];
+                // A symbol's own name is its definition, or a recursive call
+                if *name == occurrence.name {
+                    continue;
+                }
+                let (first_line, count) = edges
+                    .entry((*symbol_id, occurrence.name.as_str()))
+                    .or_insert((line, 0));
+                *first_line = (*first_line).min(line);
+                *count += 1;
+            }
+        }
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT OR REPLACE INTO symbol_edges (source_symbol_id, target_name, line, count)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for ((symbol_id, name), (line, count)) in edges {
+            stmt.execute(params![symbol_id, name, line as i64, count as i64])?;
+        }
+        Ok(())
+    }
+
+    /// Get the symbols used by the symbols named `name` in the file at
+    /// `file_path`, ordered by file and line.
+    pub fn get_symbol_dependencies(&self, file_path: &str, name: &str) -> Result<Vec<SymbolEdge>> {
+        let file_path = normalize_path(file_path);
+        self.with_conn(|conn| {
+            let Some(scope) = ImportScope::load(conn, &file_path)? else {
+                return Ok(Vec::new());
+            };
+
+            let mut stmt = conn.prepare_cached(
+                "SELECT e.target_name, MIN(e.line), SUM(e.count) FROM symbol_edges e
+                 JOIN symbols s ON s.id = e.source_symbol_id
+                 WHERE s.file_id = ?1 AND s.name = ?2
+                 GROUP BY e.target_name",
+            )?;
+            let references = stmt
+                .query_map(params![scope.file_id, name], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?.max(0) as usize,
+                        row.get::<_, i64>(2)?.max(0) as usize,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut edges = Vec::new();
+            for (target, line, count) in references {
+                let candidates = definitions(conn, &target)?;
+                let local = candidates
+                    .iter()
+                    .any(|(symbol, _)| symbol.file_id == scope.file_id);
+                let designated = if local {
+                    candidates
+                        .iter()
+                        .map(|(symbol, _)| symbol.file_id == scope.file_id)
+                        .collect()
+                } else {
+                    let paths: Vec<&str> = candidates.iter().map(|(_, p)| p.as_str()).collect();
+                    scope.designated(&target, &paths)
+                };
+                edges.extend(
+                    candidates
+                        .into_iter()
+                        .zip(designated)
+                        .filter(|(_, designated)| *designated)
+                        .map(|((symbol, file_path), _)| SymbolEdge {
+                            symbol,
+                            file_path,
+                            line,
+                            count,
+                        }),
+                );
+            }
+            edges.sort_by(|a, b| {
+                (&a.file_path, a.symbol.start_line).cmp(&(&b.file_path, b.symbol.start_line))
+            });
+            Ok(edges)
+        })
+    }
+
+    /// Get the symbols using the symbols named `name` in the file at
+    /// `file_path`, ordered by file and line.
+    pub fn get_symbol_dependents(&self, file_path: &str, name: &str) -> Result<Vec<SymbolEdge>> {
+        let file_path = normalize_path(file_path);
+        self.with_conn(|conn| {
+            let Some(file_id) = file_id(conn, &file_path)? else {
+                return Ok(Vec::new());
+            };
+            if !defines(conn, file_id, name)? {
+                return Ok(Vec::new());
+            }
+
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {SYMBOL_COLUMNS}, e.line, e.count FROM symbol_edges e
+                 JOIN symbols s ON s.id = e.source_symbol_id
+                 JOIN files f ON f.id = s.file_id
+                 WHERE e.target_name = ?1
+                 ORDER BY f.path, e.line"
+            ))?;
+            let references = stmt
+                .query_map([name], |row| {
+                    let (symbol, file_path) = symbol_from_row(row)?;
+                    Ok(SymbolEdge {
+                        symbol,
+                        file_path,
+                        line: row.get::<_, i64>(20)?.max(0) as usize,
+                        count: row.get::<_, i64>(21)?.max(0) as usize,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // Files defining the name, among which those using it pick
+            let defining: Vec<String> = definitions(conn, name)?
+                .into_iter()
+                .map(|(_, path)| path)
+                .collect();
+
+            // Whether each file using the name designates the queried file by it
+            let mut designates: HashMap<i64, bool> = HashMap::new();
+            let mut edges = Vec::new();
+            for edge in references {
+                let source_file = edge.symbol.file_id;
+                let designated = match designates.get(&source_file) {
+                    Some(&designated) => designated,
+                    None => {
+                        let designated = if source_file == file_id {
+                            true
+                        } else if defining.contains(&edge.file_path) {
+                            // Its own definition wins
+                            false
+                        } else {
+                            let paths: Vec<&str> = defining.iter().map(String::as_str).collect();
+                            ImportScope::load(conn, &edge.file_path)?.is_some_and(|scope| {
+                                scope
+                                    .designated(name, &paths)
+                                    .into_iter()
+                                    .zip(&paths)
+                                    .any(|(designated, path)| designated && *path == file_path)
+                            })
+                        };
+                        designates.insert(source_file, designated);
+                        designated
+                    }
+                };
+                if designated {
+                    edges.push(edge);
+                }
+            }
+            Ok(edges)
+        })
+    }
+}
+
+/// Imports of a file, telling where the names it references are defined
+struct ImportScope {
+    file_id: i64,
+    path: String,
+    /// Go files see the symbols of the other files of their package
+    go: bool,
+    /// Import paths, their kind and the symbols they name
+    imports: Vec<(String, String, Vec<ImportedSymbol>)>,
+}
+
+impl ImportScope {
+    fn load(conn: &Connection, path: &str) -> Result<Option<Self>> {
+        let file = conn
+            .prepare_cached("SELECT id, language FROM files WHERE path = ?1")?
+            .query_row([path], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .optional()?;
+        let Some((file_id, language)) = file else {
+            return Ok(None);
+        };
+
+        let mut imports: Vec<(String, String, Vec<ImportedSymbol>)> = conn
+            .prepare_cached("SELECT target_path, kind FROM dependencies WHERE source_file_id = ?1")?
+            .query_map([file_id], |row| Ok((row.get(0)?, row.get(1)?, Vec::new())))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut symbols = conn.prepare_cached(
+            "SELECT d.target_path, ds.name, ds.alias FROM dependency_symbols ds
+             JOIN dependencies d ON d.id = ds.dependency_id
+             WHERE d.source_file_id = ?1
+             ORDER BY ds.id",
+        )?;
+        for row in symbols.query_map([file_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ImportedSymbol {
+                    name: row.get(1)?,
+                    alias: row.get(2)?,
+                },
+            ))
+        })? {
+            let (target, symbol) = row?;
+            if let Some((_, _, named)) = imports.iter_mut().find(|(t, _, _)| *t == target) {
+                named.push(symbol);
+            }
+        }
+
+        Ok(Some(Self {
+            file_id,
+            path: path.to_string(),
+            go: language.as_deref() == Some("go"),
+            imports,
+        }))
+    }
+
+    /// Which of the files at `paths`, defining `name` elsewhere, the name
+    /// designates when referenced in the file: those its imports lead to,
+    /// or all of them when none does but a local import names it
+    fn designated(&self, name: &str, paths: &[&str]) -> Vec<bool> {
+        let imported: Vec<bool> = paths.iter().map(|path| self.imports_file(path)).collect();
+        if imported.contains(&true) {
+            return imported;
+        }
+        let named = self.imports.iter().any(|(_, kind, symbols)| {
+            kind == "local" && symbols.iter().any(|s| s.alias.is_none() && s.name == name)
+        });
+        vec![named; paths.len()]
+    }
+
+    /// Whether the file imports the file at `path`, or shares its Go package
+    fn imports_file(&self, path: &str) -> bool {
+        (self.go && Path::new(&self.path).parent() == Path::new(path).parent())
+            || self
+                .imports
+                .iter()
+                .any(|(target, _, _)| import_matches(target, path))
+    }
+}
+
+/// Whether the import path `target` can designate the file at `path`: one
+/// of its last two segments is the file stem (`./utils`,
+/// `crate::config::load_config`, `shop.pricing`), or its last segment is
+/// the directory of the file (Go packages, `mod.rs`, `index.ts`...)
+fn import_matches(target: &str, path: &str) -> bool {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(str::to_lowercase);
+    let directory = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .map(str::to_lowercase);
+
+    let segments: Vec<String> = target
+        .split(['/', '\\', ':', '.'])
+        .filter(|s| !s.is_empty() && !matches!(*s, "crate" | "self" | "super"))
+        .map(str::to_lowercase)
+        .collect();
+    let last = segments.len().saturating_sub(2);
+    let stem_matches = stem.is_some_and(|stem| {
+        !DIRECTORY_STEMS.contains(&stem.as_str()) && segments[last..].contains(&stem)
+    });
+    stem_matches || (directory.is_some() && segments.last() == directory.as_ref())
+}
+
+/// Symbols named `name` and the paths of their files
+fn definitions(conn: &Connection, name: &str) -> Result<Vec<(SymbolRecord, String)>> {
+    Ok(conn
+        .prepare_cached(&format!(
+            "SELECT {SYMBOL_COLUMNS} FROM symbols s JOIN files f ON f.id = s.file_id
+             WHERE s.name = ?1"
+        ))?
+        .query_map([name], symbol_from_row)?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn file_id(conn: &Connection, path: &str) -> Result<Option<i64>> {
+    Ok(conn
+        .prepare_cached("SELECT id FROM files WHERE path = ?1")?
+        .query_row([path], |row| row.get(0))
+        .optional()?)
+}
+
+/// Whether the file defines a symbol named `name`
+fn defines(conn: &Connection, file_id: i64, name: &str) -> Result<bool> {
+    Ok(conn
+        .prepare_cached("SELECT 1 FROM symbols WHERE file_id = ?1 AND name = ?2")?
+        .exists(params![file_id, name])?)
+}
+
+/// Read the [`SYMBOL_COLUMNS`] of a row
+fn symbol_from_row(row: &Row) -> rusqlite::Result<(SymbolRecord, String)> {
+    Ok((
+        SymbolRecord {
+            id: row.get(0)?,
+            file_id: row.get(1)?,
+            name: row.get(2)?,
+            kind: row.get(3)?,
+            start_line: row.get(4)?,
+            end_line: row.get(5)?,
+            start_byte: row.get(6)?,
+            end_byte: row.get(7)?,
+            signature: row.get(8)?,
+            doc_comment: row.get(9)?,
+            parent: row.get(10)?,
+            qualifiers: qualifiers_from_row(row, 11)?,
+            start_column: row.get(16)?,
+            end_column: row.get(17)?,
+            cfg_context: row.get(18)?,
+        },
+        row.get(19)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_matches() {
+        assert!(import_matches("./utils", "src/utils.ts"));
+        assert!(import_matches("../lib/utils.js", "src/lib/utils.ts"));
+        assert!(import_matches(
+            "crate::config::load_config",
+            "src/config.rs"
+        ));
+        assert!(import_matches("crate::config", "src/config/mod.rs"));
+        assert!(import_matches("shop.pricing", "shop/pricing.py"));
+        assert!(import_matches("example.com/kv/store", "store/snapshot.go"));
+        assert!(import_matches("./components", "src/components/index.ts"));
+
+        assert!(!import_matches("./utils", "src/helpers.ts"));
+        assert!(!import_matches("crate::config", "src/lib.rs"));
+        assert!(!import_matches("std::collections::HashMap", "src/cache.rs"));
+    }
+}
@@ -5,15 +5,75 @@ use crate::schema::SymbolRecord;
 use anyhow::{Result, anyhow};
 use rusqlite::Connection;
 use rusqlite::params;
-use semantiq_parser::Symbol;
+use semantiq_parser::{Symbol, SymbolQualifiers, split_words};
+use serde::{Deserialize, Serialize};
 use std::sync::{MutexGuard, PoisonError};
 use tracing::debug;
 
+/// BM25 weights of the `symbols_fts` columns: name, name words, signature
+/// and doc comment. A match in the name outranks one in the documentation.
+const SYMBOL_FTS_WEIGHTS: &str = "10.0, 5.0, 2.0, 1.0";
+
+/// Part of a symbol a full-text search matched, the name first when several
+/// did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolField {
+    /// The name, or the words of the name
+    Name,
+    Signature,
+    /// The doc comment
+    Docs,
+}
+
+impl SymbolField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolField::Name => "name",
+            SymbolField::Signature => "signature",
+            SymbolField::Docs => "docs",
+        }
+    }
+}
+
+/// A symbol found by a full-text search
+#[derive(Debug, Clone)]
+pub struct SymbolMatch {
+    pub symbol: SymbolRecord,
+    pub matched_in: SymbolField,
+}
+
+/// Words of an identifier, lowercased and separated by spaces
+/// (`getUserById` gives `get user by id`)
+pub(crate) fn identifier_words(name: &str) -> String {
+    split_words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Read the qualifier columns of a symbol, starting at column `start`
+/// (`is_public, is_async, is_static, is_abstract, generic_params`)
+pub(crate) fn qualifiers_from_row(
+    row: &rusqlite::Row,
+    start: usize,
+) -> rusqlite::Result<SymbolQualifiers> {
+    Ok(SymbolQualifiers {
+        is_public: row.get(start)?,
+        is_async: row.get(start + 1)?,
+        is_static: row.get(start + 2)?,
+        is_abstract: row.get(start + 3)?,
+        generic_params: row.get::<_, i64>(start + 4)?.max(0) as usize,
+    })
+}
+
 impl IndexStore {
     /// Maximum limit for symbol search results to prevent excessive memory usage.
     const MAX_SYMBOL_SEARCH_LIMIT: usize = 10000;
 
-    /// Insert symbols for a file (replaces existing symbols for that file).
+    /// Insert symbols for a file (replaces existing symbols for that file),
+    /// recording the changes in the symbol history.
     pub fn insert_symbols(&self, file_id: i64, symbols: &[Symbol]) -> Result<()> {
         let conn = self
             .conn
@@ -26,28 +86,9 @@ impl IndexStore {
         conn.execute("BEGIN IMMEDIATE", [])?;
 
         let result = (|| -> Result<()> {
-            // Delete existing symbols for this file
-            conn.execute("DELETE FROM symbols WHERE file_id = ?1", [file_id])?;
-
-            let mut stmt = conn.prepare(
-                "INSERT INTO symbols (file_id, name, kind, start_line, end_line, start_byte, end_byte, signature, doc_comment, parent)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            )?;
-
-            for symbol in symbols {
-                stmt.execute(params![
-                    file_id,
-                    symbol.name,
-                    symbol.kind.as_str(),
-                    symbol.start_line as i64,
-                    symbol.end_line as i64,
-                    symbol.start_byte as i64,
-                    symbol.end_byte as i64,
-                    symbol.signature,
-                    symbol.doc_comment,
-                    symbol.parent,
-                ])?;
-            }
+            Self::insert_symbols_impl(&conn, file_id, symbols)?;
+            Self::link_chunk_symbols_impl(&conn, file_id)?;
+            Self::bump_generation_impl(&conn)?;
             Ok(())
         })();
 
@@ -64,22 +105,158 @@ impl IndexStore {
         }
     }
 
-    /// Search symbols using FTS5 full-text search.
-    pub fn search_symbols(&self, query: &str, limit: usize) -> Result<Vec<SymbolRecord>> {
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_symbols_impl(
+        conn: &Connection,
+        file_id: i64,
+        symbols: &[Symbol],
+    ) -> Result<()> {
+        // Delete existing symbols for this file
+        conn.prepare_cached("DELETE FROM symbols WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT INTO symbols (file_id, name, kind, start_line, end_line, start_byte, end_byte, signature, doc_comment, parent,
+                                  is_public, is_async, is_static, is_abstract, generic_params, start_column, end_column,
+                                  cfg_context)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        )?;
+
+        let mut fts_stmt = conn.prepare_cached(
+            "INSERT INTO symbols_fts (rowid, name, words, signature, doc_comment)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+
+        for symbol in symbols {
+            stmt.execute(params![
+                file_id,
+                symbol.name,
+                symbol.kind.as_str(),
+                symbol.start_line as i64,
+                symbol.end_line as i64,
+                symbol.start_byte as i64,
+                symbol.end_byte as i64,
+                symbol.signature,
+                symbol.doc_comment,
+                symbol.parent,
+                symbol.qualifiers.is_public,
+                symbol.qualifiers.is_async,
+                symbol.qualifiers.is_static,
+                symbol.qualifiers.is_abstract,
+                symbol.qualifiers.generic_params as i64,
+                symbol.start_column as i64,
+                symbol.end_column as i64,
+                symbol.cfg_context,
+            ])?;
+            fts_stmt.execute(params![
+                conn.last_insert_rowid(),
+                symbol.name,
+                identifier_words(&symbol.name),
+                symbol.signature,
+                symbol.doc_comment,
+            ])?;
+        }
+        Self::record_symbol_history_impl(conn, file_id, symbols)?;
+        Ok(())
+    }
+
+    /// Search symbols using FTS5 full-text search, best matches first.
+    ///
+    /// The name, signature and doc comment of symbols are searched, ranked
+    /// with BM25 weighting matches in the name the most.
+    pub fn search_symbols(&self, query: &str, limit: usize) -> Result<Vec<SymbolMatch>> {
         // Cap limit to prevent excessive memory usage
         let safe_limit = limit.min(Self::MAX_SYMBOL_SEARCH_LIMIT);
+        let fts_query = Self::escape_fts5_query(query);
 
         self.with_conn(|conn| {
-            let mut stmt = conn.prepare(
+            let mut stmt = conn.prepare(&format!(
                 "SELECT s.id, s.file_id, s.name, s.kind, s.start_line, s.end_line,
-                        s.start_byte, s.end_byte, s.signature, s.doc_comment, s.parent
+                        s.start_byte, s.end_byte, s.signature, s.doc_comment, s.parent,
+                        s.is_public, s.is_async, s.is_static, s.is_abstract, s.generic_params,
+                        CASE
+                            WHEN s.id IN (SELECT rowid FROM symbols_fts WHERE symbols_fts MATCH ?2) THEN 0
+                            WHEN s.id IN (SELECT rowid FROM symbols_fts WHERE symbols_fts MATCH ?3) THEN 1
+                            ELSE 2
+                        END,
+                        s.start_column, s.end_column, s.cfg_context
                  FROM symbols s
                  JOIN symbols_fts ON s.id = symbols_fts.rowid
                  WHERE symbols_fts MATCH ?1
-                 LIMIT ?2",
-            )?;
+                 ORDER BY bm25(symbols_fts, {SYMBOL_FTS_WEIGHTS})
+                 LIMIT ?4"
+            ))?;
+
+            let results = stmt
+                .query_map(
+                    params![
+                        fts_query,
+                        format!("{{name words}} : ({})", fts_query),
+                        format!("signature : ({})", fts_query),
+                        safe_limit as i64
+                    ],
+                    |row| {
+                        Ok(SymbolMatch {
+                            symbol: SymbolRecord {
+                                id: row.get(0)?,
+                                file_id: row.get(1)?,
+                                name: row.get(2)?,
+                                kind: row.get(3)?,
+                                start_line: row.get(4)?,
+                                end_line: row.get(5)?,
+                                start_byte: row.get(6)?,
+                                end_byte: row.get(7)?,
+                                signature: row.get(8)?,
+                                doc_comment: row.get(9)?,
+                                parent: row.get(10)?,
+                                qualifiers: qualifiers_from_row(row, 11)?,
+                                start_column: row.get(17)?,
+                                end_column: row.get(18)?,
+                                cfg_context: row.get(19)?,
+                            },
+                            matched_in: match row.get::<_, i64>(16)? {
+                                0 => SymbolField::Name,
+                                1 => SymbolField::Signature,
+                                _ => SymbolField::Docs,
+                            },
+                        })
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(results)
+        })
+    }
+
+    /// Search the doc comments of symbols for any of `words`, best matches
+    /// first: documentation holding more of the words, and rarer ones, ranks
+    /// higher.
+    pub fn search_symbol_docs(&self, words: &[&str], limit: usize) -> Result<Vec<SymbolRecord>> {
+        let terms: Vec<String> = words
+            .iter()
+            .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect())
+            .filter(|word: &String| !word.is_empty())
+            .map(|word| format!("\"{}\"*", word))
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let safe_limit = limit.min(Self::MAX_SYMBOL_SEARCH_LIMIT);
+        let fts_query = format!("doc_comment : ({})", terms.join(" OR "));
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT s.id, s.file_id, s.name, s.kind, s.start_line, s.end_line,
+                        s.start_byte, s.end_byte, s.signature, s.doc_comment, s.parent,
+                        s.is_public, s.is_async, s.is_static, s.is_abstract, s.generic_params,
+                        s.start_column, s.end_column, s.cfg_context
+                 FROM symbols s
+                 JOIN symbols_fts ON s.id = symbols_fts.rowid
+                 WHERE symbols_fts MATCH ?1
+                 ORDER BY bm25(symbols_fts, {SYMBOL_FTS_WEIGHTS})
+                 LIMIT ?2"
+            ))?;
 
-            let fts_query = Self::escape_fts5_query(query);
             let results = stmt
                 .query_map(params![fts_query, safe_limit as i64], |row| {
                     Ok(SymbolRecord {
@@ -94,6 +271,10 @@ impl IndexStore {
                         signature: row.get(8)?,
                         doc_comment: row.get(9)?,
                         parent: row.get(10)?,
+                        qualifiers: qualifiers_from_row(row, 11)?,
+                        start_column: row.get(16)?,
+                        end_column: row.get(17)?,
+                        cfg_context: row.get(18)?,
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -107,7 +288,9 @@ impl IndexStore {
         self.with_conn(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, file_id, name, kind, start_line, end_line,
-                        start_byte, end_byte, signature, doc_comment, parent
+                        start_byte, end_byte, signature, doc_comment, parent,
+                        is_public, is_async, is_static, is_abstract, generic_params,
+                        start_column, end_column, cfg_context
                  FROM symbols WHERE name = ?1",
             )?;
 
@@ -125,6 +308,10 @@ impl IndexStore {
                         signature: row.get(8)?,
                         doc_comment: row.get(9)?,
                         parent: row.get(10)?,
+                        qualifiers: qualifiers_from_row(row, 11)?,
+                        start_column: row.get(16)?,
+                        end_column: row.get(17)?,
+                        cfg_context: row.get(18)?,
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -133,12 +320,39 @@ impl IndexStore {
         })
     }
 
+    /// Distinct symbol names starting with `prefix` (case-insensitive), for
+    /// spelling suggestions. Shortest names first.
+    pub fn symbol_names_with_prefix(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        let prefix = prefix.to_lowercase();
+        let safe_limit = limit.min(Self::MAX_SYMBOL_SEARCH_LIMIT);
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT name FROM symbols
+                 WHERE lower(substr(name, 1, ?2)) = ?1
+                 ORDER BY length(name), name
+                 LIMIT ?3",
+            )?;
+
+            let names = stmt
+                .query_map(
+                    params![prefix, prefix.chars().count() as i64, safe_limit as i64],
+                    |row| row.get(0),
+                )?
+                .collect::<Result<Vec<String>, _>>()?;
+
+            Ok(names)
+        })
+    }
+
     /// Get all symbols for a file, ordered by start line.
     pub fn get_symbols_by_file(&self, file_id: i64) -> Result<Vec<SymbolRecord>> {
         self.with_conn(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, file_id, name, kind, start_line, end_line,
-                        start_byte, end_byte, signature, doc_comment, parent
+                        start_byte, end_byte, signature, doc_comment, parent,
+                        is_public, is_async, is_static, is_abstract, generic_params,
+                        start_column, end_column, cfg_context
                  FROM symbols WHERE file_id = ?1
                  ORDER BY start_line",
             )?;
@@ -157,6 +371,64 @@ impl IndexStore {
                         signature: row.get(8)?,
                         doc_comment: row.get(9)?,
                         parent: row.get(10)?,
+                        qualifiers: qualifiers_from_row(row, 11)?,
+                        start_column: row.get(16)?,
+                        end_column: row.get(17)?,
+                        cfg_context: row.get(18)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(results)
+        })
+    }
+
+    /// Get symbols by ID, e.g. the symbols chunks belong to. Missing IDs are
+    /// skipped.
+    pub fn get_symbols_by_ids(&self, symbol_ids: &[i64]) -> Result<Vec<SymbolRecord>> {
+        if symbol_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Stay within SQLite's SQLITE_MAX_VARIABLE_NUMBER (default 999)
+        const BATCH_SIZE: usize = 900;
+        if symbol_ids.len() > BATCH_SIZE {
+            let mut all_results = Vec::new();
+            for batch in symbol_ids.chunks(BATCH_SIZE) {
+                all_results.extend(self.get_symbols_by_ids(batch)?);
+            }
+            return Ok(all_results);
+        }
+
+        self.with_conn(|conn| {
+            let placeholders = vec!["?"; symbol_ids.len()].join(",");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, file_id, name, kind, start_line, end_line,
+                        start_byte, end_byte, signature, doc_comment, parent,
+                        is_public, is_async, is_static, is_abstract, generic_params,
+                        start_column, end_column, cfg_context
+                 FROM symbols WHERE id IN ({})",
+                placeholders
+            ))?;
+
+            let results = stmt
+                .query_map(rusqlite::params_from_iter(symbol_ids), |row| {
+                    Ok(SymbolRecord {
+                        id: row.get(0)?,
+                        file_id: row.get(1)?,
+                        name: row.get(2)?,
+                        kind: row.get(3)?,
+                        start_line: row.get(4)?,
+                        end_line: row.get(5)?,
+                        start_byte: row.get(6)?,
+                        end_byte: row.get(7)?,
+                        signature: row.get(8)?,
+                        doc_comment: row.get(9)?,
+                        parent: row.get(10)?,
+                        qualifiers: qualifiers_from_row(row, 11)?,
+                        start_column: row.get(16)?,
+                        end_column: row.get(17)?,
+                        cfg_context: row.get(18)?,
                     })
                 })?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -0,0 +1,121 @@
+//! File tag operations for IndexStore.
+//!
+//! The keywords of each file are stored with their count in the file. Tags
+//! are the keywords that set a file apart, ranked by TF-IDF at query time so
+//! that they follow the rest of the index as it changes.
+
+use super::IndexStore;
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use semantiq_parser::Keyword;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// A keyword of a file with its TF-IDF weight
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileTag {
+    pub tag: String,
+    pub score: f64,
+}
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_file_tags_impl(
+        conn: &Connection,
+        file_id: i64,
+        keywords: &[Keyword],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM file_tags WHERE file_id = ?1")?
+            .execute([file_id])?;
+
+        let mut stmt = conn.prepare_cached(
+            "INSERT OR REPLACE INTO file_tags (file_id, tag, count) VALUES (?1, ?2, ?3)",
+        )?;
+        for keyword in keywords {
+            stmt.execute(params![file_id, keyword.term, keyword.count as i64])?;
+        }
+        Ok(())
+    }
+
+    /// Get up to `per_file` tags of each of the given files, best first.
+    ///
+    /// A keyword scores `tf * ln(N / df)`: its share of the file's keyword
+    /// occurrences, weighted by how rare it is across the N tagged files.
+    /// Keywords found in every file score 0 and are never tags. Files
+    /// without tags are left out of the map.
+    pub fn get_file_tags(
+        &self,
+        paths: &[&str],
+        per_file: usize,
+    ) -> Result<HashMap<String, Vec<FileTag>>> {
+        if paths.is_empty() || per_file == 0 {
+            return Ok(HashMap::new());
+        }
+
+        self.with_conn(|conn| {
+            let tagged_files: i64 =
+                conn.query_row("SELECT COUNT(DISTINCT file_id) FROM file_tags", [], |row| {
+                    row.get(0)
+                })?;
+            let mut stmt = conn.prepare_cached(
+                "SELECT t.tag, t.count,
+                        (SELECT SUM(a.count) FROM file_tags a WHERE a.file_id = t.file_id),
+                        (SELECT COUNT(*) FROM file_tags d WHERE d.tag = t.tag)
+                 FROM file_tags t JOIN files f ON f.id = t.file_id
+                 WHERE f.path = ?1",
+            )?;
+
+            let mut tags_by_path = HashMap::new();
+            for &path in paths {
+                let mut tags = stmt
+                    .query_map([path], |row| {
+                        let count: i64 = row.get(1)?;
+                        let total: i64 = row.get(2)?;
+                        let files_with_tag: i64 = row.get(3)?;
+                        let tf = count as f64 / total.max(1) as f64;
+                        let idf = (tagged_files as f64 / files_with_tag.max(1) as f64).ln();
+                        Ok(FileTag {
+                            tag: row.get(0)?,
+                            score: tf * idf,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                tags.retain(|t| t.score > 0.0);
+                if tags.is_empty() {
+                    continue;
+                }
+                tags.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tag.cmp(&b.tag)));
+                tags.truncate(per_file);
+                tags_by_path.insert(path.to_string(), tags);
+            }
+            Ok(tags_by_path)
+        })
+    }
+
+    /// Get the given files that have every one of `tags` among their
+    /// keywords.
+    pub fn files_with_tags(&self, paths: &[&str], tags: &[String]) -> Result<HashSet<String>> {
+        if tags.is_empty() {
+            return Ok(paths.iter().map(|p| p.to_string()).collect());
+        }
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT COUNT(*) FROM file_tags t JOIN files f ON f.id = t.file_id
+                 WHERE f.path = ?1 AND t.tag = ?2",
+            )?;
+
+            let mut matching = HashSet::new();
+            'paths: for &path in paths {
+                for tag in tags {
+                    let found: i64 = stmt.query_row(params![path, tag], |row| row.get(0))?;
+                    if found == 0 {
+                        continue 'paths;
+                    }
+                }
+                matching.insert(path.to_string());
+            }
+            Ok(matching)
+        })
+    }
+}
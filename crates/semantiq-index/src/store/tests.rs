@@ -1,7 +1,14 @@
 //! Tests for IndexStore.
 
 use super::*;
-use semantiq_parser::{CodeChunk, Symbol, SymbolKind};
+use crate::config::QuotaConfig;
+use crate::schema::{BindingRecord, EMBEDDING_DIMENSION};
+use semantiq_embeddings::{ChunkEmbedding, TokenFit};
+use semantiq_parser::{
+    Binding, BindingFramework, CodeChunk, ConfigAccess, ConfigRef, ConfigSource, Entrypoint,
+    EntrypointKind, Import, ImportKind, ImportedSymbol, Keyword, Occurrence, RelationKind, Symbol,
+    SymbolKind, SymbolQualifiers, TextMask, TypeRelation,
+};
 
 #[test]
 fn test_insert_and_get_file() {
@@ -32,9 +39,13 @@ fn test_insert_and_search_symbols() {
         end_line: 1,
         start_byte: 0,
         end_byte: 13,
+        start_column: 0,
+        end_column: 0,
         signature: Some("fn hello()".to_string()),
         doc_comment: None,
         parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
     }];
 
     store.insert_symbols(file_id, &symbols).unwrap();
@@ -72,6 +83,63 @@ fn test_needs_full_reindex_different_version() {
     assert!(store.needs_full_reindex().unwrap());
 }
 
+#[test]
+fn test_schema_upgrade_replaces_symbols_fts() {
+    let dir = std::env::temp_dir().join(format!("semantiq-fts-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("index.db");
+    {
+        let store = IndexStore::open(&db_path).unwrap();
+        store
+            .with_conn(|conn| {
+                // Schema 3 kept an FTS table without stemming in sync by triggers
+                conn.execute_batch(
+                    "DROP TRIGGER symbols_fts_ad;
+                     DROP TABLE symbols_fts;
+                     CREATE VIRTUAL TABLE symbols_fts USING fts5(
+                         name, signature, doc_comment, content='symbols', content_rowid='id'
+                     );
+                     CREATE TRIGGER symbols_ai AFTER INSERT ON symbols BEGIN
+                         INSERT INTO symbols_fts(rowid, name, signature, doc_comment)
+                         VALUES (new.id, new.name, new.signature, new.doc_comment);
+                     END;
+                     UPDATE metadata SET value = '3' WHERE key = 'schema_version';",
+                )?;
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    let store = IndexStore::open(&db_path).unwrap();
+    let file_id = store
+        .insert_file("test.rs", Some("rust"), "", 0, 1000)
+        .unwrap();
+    store
+        .insert_symbols(
+            file_id,
+            &[Symbol {
+                name: "loadIndexes".to_string(),
+                kind: SymbolKind::Function,
+                start_line: 1,
+                end_line: 1,
+                start_byte: 0,
+                end_byte: 0,
+                start_column: 0,
+                end_column: 0,
+                signature: None,
+                doc_comment: None,
+                parent: None,
+                qualifiers: SymbolQualifiers::default(),
+                cfg_context: None,
+            }],
+        )
+        .unwrap();
+    assert_eq!(store.search_symbols("index", 10).unwrap().len(), 1);
+
+    drop(store);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn test_needs_full_reindex_corrupted_version() {
     let store = IndexStore::open_in_memory().unwrap();
@@ -102,9 +170,13 @@ fn test_clear_all_data() {
         end_line: 1,
         start_byte: 0,
         end_byte: 12,
+        start_column: 0,
+        end_column: 0,
         signature: None,
         doc_comment: None,
         parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
     }];
     store.insert_symbols(file_id, &symbols).unwrap();
 
@@ -178,6 +250,233 @@ fn test_insert_and_get_chunks() {
     assert_eq!(retrieved[1].content, "fn foo() {}");
 }
 
+#[test]
+fn test_search_chunks_bm25() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("cache.rs", Some("rust"), "", 0, 1000)
+        .unwrap();
+    let chunk = |content: &str, start_line| CodeChunk {
+        content: content.to_string(),
+        start_line,
+        end_line: start_line,
+        start_byte: 0,
+        end_byte: content.len(),
+        symbols: Vec::new(),
+    };
+    store
+        .insert_chunks(
+            file_id,
+            &[
+                chunk("fn evict_entry(cache: &mut Cache) { cache.evict() }", 1),
+                chunk("fn render(view: View) {}", 2),
+                chunk("// the cache is cleared on reload", 3),
+            ],
+        )
+        .unwrap();
+
+    // Identifier parts are searchable, and the chunk mentioning both terms
+    // ranks first
+    let ranked = store.search_chunks_bm25(&["cache", "entry"], 10).unwrap();
+    assert_eq!(ranked.len(), 2);
+    assert!(ranked[0].1 > ranked[1].1);
+    let chunks = store.get_chunks_by_file(file_id).unwrap();
+    assert_eq!(ranked[0].0, chunks[0].id);
+
+    assert!(
+        store
+            .search_chunks_bm25(&["nothing"], 10)
+            .unwrap()
+            .is_empty()
+    );
+    assert!(store.search_chunks_bm25(&[], 10).unwrap().is_empty());
+
+    // Deleted chunks leave the full-text index
+    store.delete_file("cache.rs").unwrap();
+    assert!(store.search_chunks_bm25(&["cache"], 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_refresh_chunk_distinctiveness() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let chunk = |content: String| CodeChunk {
+        end_byte: content.len(),
+        content,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        symbols: Vec::new(),
+    };
+    let license = "// Licensed under the MIT license. See LICENSE in the project root.";
+    for i in 0..3 {
+        let file_id = store
+            .insert_file(&format!("gen{i}.rs"), Some("rust"), "", 0, 1000)
+            .unwrap();
+        // Indentation aside, the header is the same in every file
+        let header = format!("{}{}", " ".repeat(i), license);
+        store.insert_chunks(file_id, &[chunk(header)]).unwrap();
+    }
+    let file_id = store
+        .insert_file("lib.rs", Some("rust"), "", 0, 1000)
+        .unwrap();
+    let mut chunks: Vec<CodeChunk> = (0..30)
+        .map(|i| {
+            chunk(format!(
+                "fn handler_{i}(request_{i}: Payload{i}) -> Reply{i} {{}}"
+            ))
+        })
+        .collect();
+    // Found once, but only made of words found everywhere
+    chunks.push(chunk("fn handler(request) {}".to_string()));
+    store.insert_chunks(file_id, &chunks).unwrap();
+
+    // Chunks are neutral until the pass runs, which leaves the generation alone
+    let generation = store.index_generation().unwrap();
+    assert!(
+        store
+            .get_chunks_by_file(file_id)
+            .unwrap()
+            .iter()
+            .all(|c| c.distinctiveness == 1.0)
+    );
+    assert!(store.refresh_chunk_distinctiveness().unwrap() > 0);
+    assert_eq!(store.index_generation().unwrap(), generation);
+
+    let header = store.get_chunks_by_file(1).unwrap()[0].distinctiveness;
+    let mut chunks = store.get_chunks_by_file(file_id).unwrap();
+    let common = chunks.pop().unwrap().distinctiveness;
+    let best = chunks.iter().map(|c| c.distinctiveness).fold(0.0, f32::max);
+    assert_eq!(best, 1.0);
+    assert!(chunks.iter().all(|c| c.distinctiveness > common));
+    assert!(
+        (0.7..0.8).contains(&common),
+        "common chunk weighs {}",
+        common
+    );
+    assert!(header < 0.7, "header weighs {}", header);
+}
+
+/// Symbols `main` (bytes 0..12) and `foo` (13..24), with a chunk of each
+/// and a chunk spanning `foo` and the end of `main`
+fn insert_linked_file(store: &IndexStore) -> i64 {
+    let file_id = store
+        .insert_file(
+            "test.rs",
+            Some("rust"),
+            "fn main() {}\nfn foo() {}",
+            25,
+            1000,
+        )
+        .unwrap();
+    let symbol = |name: &str, start_byte, end_byte| Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte,
+        end_byte,
+        start_column: 0,
+        end_column: 0,
+        signature: Some(format!("fn {}()", name)),
+        doc_comment: None,
+        parent: Some("app".to_string()),
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    let chunk = |start_byte, end_byte| CodeChunk {
+        content: "fn".to_string(),
+        start_line: 1,
+        end_line: 2,
+        start_byte,
+        end_byte,
+        symbols: Vec::new(),
+    };
+    store
+        .insert_symbols(file_id, &[symbol("main", 0, 12), symbol("foo", 13, 24)])
+        .unwrap();
+    store
+        .insert_chunks(file_id, &[chunk(0, 12), chunk(13, 24), chunk(8, 24)])
+        .unwrap();
+    file_id
+}
+
+#[test]
+fn test_chunks_link_to_owning_symbol() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = insert_linked_file(&store);
+
+    let chunks = store.get_chunks_by_file(file_id).unwrap();
+    let ids: Vec<i64> = chunks.iter().map(|c| c.symbol_id.unwrap()).collect();
+    let symbols = store.get_symbols_by_ids(&ids).unwrap();
+    let owner = |id: i64| symbols.iter().find(|s| s.id == id).unwrap().name.as_str();
+    assert_eq!(owner(ids[0]), "main");
+    assert_eq!(owner(ids[1]), "foo");
+    // The chunk overlaps 4 bytes of main and all of foo
+    assert_eq!(owner(ids[2]), "foo");
+    assert_eq!(symbols[0].parent.as_deref(), Some("app"));
+    // Chunks are titled after their symbol
+    let titles: Vec<&str> = chunks.iter().filter_map(|c| c.title.as_deref()).collect();
+    assert_eq!(titles, ["fn main()", "fn foo()", "fn foo()"]);
+
+    // Symbols written again are linked again
+    store.insert_symbols(file_id, &[]).unwrap();
+    let chunks = store.get_chunks_by_file(file_id).unwrap();
+    assert!(
+        chunks
+            .iter()
+            .all(|c| c.symbol_id.is_none() && c.title.is_none())
+    );
+}
+
+#[test]
+fn test_schema_upgrade_links_chunks_to_symbols() {
+    let dir = std::env::temp_dir().join(format!("semantiq-chunk-symbols-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("index.db");
+    {
+        let store = IndexStore::open(&db_path).unwrap();
+        insert_linked_file(&store);
+        store
+            .with_conn(|conn| {
+                // Schema 4 chunks had no symbol_id column
+                conn.execute_batch(
+                    "CREATE TABLE chunks_v4 (
+                         id INTEGER PRIMARY KEY AUTOINCREMENT,
+                         file_id INTEGER NOT NULL,
+                         content TEXT NOT NULL,
+                         start_line INTEGER NOT NULL,
+                         end_line INTEGER NOT NULL,
+                         start_byte INTEGER NOT NULL,
+                         end_byte INTEGER NOT NULL,
+                         symbols_json TEXT,
+                         embedding BLOB,
+                         FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+                     );
+                     INSERT INTO chunks_v4 SELECT id, file_id, content, start_line, end_line,
+                         start_byte, end_byte, symbols_json, embedding FROM chunks;
+                     DROP TABLE chunks;
+                     ALTER TABLE chunks_v4 RENAME TO chunks;
+                     UPDATE metadata SET value = '4' WHERE key = 'schema_version';",
+                )?;
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    let store = IndexStore::open(&db_path).unwrap();
+    let file = store.get_file_by_path("test.rs").unwrap().unwrap();
+    let chunks = store.get_chunks_by_file(file.id).unwrap();
+    assert_eq!(chunks.len(), 3);
+    assert!(
+        chunks
+            .iter()
+            .all(|c| c.symbol_id.is_some() && c.title.is_some())
+    );
+
+    drop(store);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn test_chunks_without_embeddings() {
     let store = IndexStore::open_in_memory().unwrap();
@@ -331,6 +630,37 @@ fn test_get_dependents() {
     assert_eq!(dependents[0].source_file_id, file_id);
 }
 
+#[test]
+fn test_file_paths_are_normalized() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store
+        .insert_file("./src//main.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+
+    assert_eq!(store.get_all_file_paths().unwrap(), vec!["src/main.rs"]);
+    assert!(store.get_file_by_path("./src/main.rs").unwrap().is_some());
+    store.delete_file("src/main.rs/").unwrap();
+    assert!(store.get_all_file_paths().unwrap().is_empty());
+}
+
+#[cfg(windows)]
+#[test]
+fn test_windows_paths_match_dependents() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file(r"src\main.rs", Some("rust"), "use crate::utils;", 17, 1000)
+        .unwrap();
+    store
+        .insert_dependency(file_id, "./utils", Some("utils"), "local")
+        .unwrap();
+
+    assert_eq!(store.get_all_file_paths().unwrap(), vec!["src/main.rs"]);
+    assert!(store.get_file_by_path(r"src\main.rs").unwrap().is_some());
+    let dependents = store.get_dependents(r"src\utils.rs").unwrap();
+    assert_eq!(dependents.len(), 1);
+    assert_eq!(dependents[0].source_file_id, file_id);
+}
+
 #[test]
 fn test_get_dependents_deduplicates() {
     let store = IndexStore::open_in_memory().unwrap();
@@ -409,6 +739,62 @@ fn test_get_dependents_no_false_positives() {
     assert_eq!(dependents.len(), 0);
 }
 
+#[test]
+fn test_dependency_symbols() {
+    let store = IndexStore::open_in_memory().unwrap();
+
+    let file_id = store
+        .insert_file(
+            "src/app.ts",
+            Some("typescript"),
+            "import { A } from './x';",
+            24,
+            1000,
+        )
+        .unwrap();
+
+    let dependency_id = store
+        .insert_dependency(file_id, "./x", Some("x"), "local")
+        .unwrap();
+    let symbols = vec![
+        ImportedSymbol::new("A", None),
+        ImportedSymbol::new("B", Some("C".to_string())),
+    ];
+    store
+        .insert_dependency_symbols(dependency_id, &symbols)
+        .unwrap();
+    store
+        .insert_dependency(file_id, "./side-effect", Some("side-effect"), "local")
+        .unwrap();
+
+    let deps = store.get_dependencies(file_id).unwrap();
+    let x = deps.iter().find(|d| d.target_path == "./x").unwrap();
+    assert_eq!(x.id, dependency_id);
+    assert_eq!(x.symbols, symbols);
+    let side_effect = deps
+        .iter()
+        .find(|d| d.target_path == "./side-effect")
+        .unwrap();
+    assert!(side_effect.symbols.is_empty());
+
+    let dependents = store.get_dependents("src/x.ts").unwrap();
+    assert_eq!(dependents.len(), 1);
+    assert_eq!(dependents[0].symbols, symbols);
+
+    // Symbols go away with their dependency
+    store.delete_dependencies(file_id).unwrap();
+    let remaining: i64 = store
+        .with_conn(|conn| {
+            Ok(
+                conn.query_row("SELECT COUNT(*) FROM dependency_symbols", [], |row| {
+                    row.get(0)
+                })?,
+            )
+        })
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
+
 #[test]
 fn test_delete_dependencies() {
     let store = IndexStore::open_in_memory().unwrap();
@@ -445,6 +831,44 @@ fn test_delete_file() {
     assert!(store.get_file_by_path("test.rs").unwrap().is_none());
 }
 
+#[test]
+fn test_file_paths_with_prefix() {
+    let store = IndexStore::open_in_memory().unwrap();
+
+    for path in [
+        "src/main.rs",
+        "external://cargo/serde@1.0.0/src/lib.rs",
+        "external://cargo/serde@1.0.0/src/de.rs",
+        "external://npm/left_pad@1.0.0/index.d.ts",
+    ] {
+        store
+            .insert_file(path, Some("rust"), path, path.len() as i64, 1000)
+            .unwrap();
+    }
+
+    let serde = store
+        .get_file_paths_with_prefix("external://cargo/serde@1.0.0/")
+        .unwrap();
+    assert_eq!(
+        serde,
+        vec![
+            "external://cargo/serde@1.0.0/src/de.rs",
+            "external://cargo/serde@1.0.0/src/lib.rs"
+        ]
+    );
+
+    // LIKE wildcards in the prefix are matched literally
+    assert!(
+        store
+            .get_file_paths_with_prefix("external://npm/left%")
+            .unwrap()
+            .is_empty()
+    );
+
+    assert_eq!(store.delete_files_with_prefix("external://").unwrap(), 3);
+    assert_eq!(store.get_all_file_paths().unwrap(), vec!["src/main.rs"]);
+}
+
 #[test]
 fn test_needs_reindex_same_content() {
     let store = IndexStore::open_in_memory().unwrap();
@@ -500,9 +924,13 @@ fn test_get_symbols_by_file() {
             end_line: 1,
             start_byte: 0,
             end_byte: 13,
+            start_column: 0,
+            end_column: 0,
             signature: Some("fn hello()".to_string()),
             doc_comment: None,
             parent: None,
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
         },
         Symbol {
             name: "world".to_string(),
@@ -511,9 +939,13 @@ fn test_get_symbols_by_file() {
             end_line: 2,
             start_byte: 14,
             end_byte: 27,
+            start_column: 0,
+            end_column: 0,
             signature: Some("fn world()".to_string()),
             doc_comment: None,
             parent: None,
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
         },
     ];
 
@@ -540,16 +972,174 @@ fn test_search_symbols_fts() {
         end_line: 1,
         start_byte: 0,
         end_byte: 23,
+        start_column: 0,
+        end_column: 0,
         signature: Some("fn calculate_total()".to_string()),
         doc_comment: None,
         parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
     }];
 
     store.insert_symbols(file_id, &symbols).unwrap();
 
     let results = store.search_symbols("calculate", 10).unwrap();
     assert_eq!(results.len(), 1);
-    assert_eq!(results[0].name, "calculate_total");
+    assert_eq!(results[0].symbol.name, "calculate_total");
+    assert_eq!(results[0].matched_in, SymbolField::Name);
+}
+
+#[test]
+fn test_search_symbols_stems_and_splits_identifiers() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("test.ts", Some("typescript"), "", 0, 1000)
+        .unwrap();
+    let symbol = |name: &str, doc: Option<&str>| Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: None,
+        doc_comment: doc.map(str::to_string),
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    store
+        .insert_symbols(
+            file_id,
+            &[
+                symbol("getUserById", None),
+                symbol("rebuild", Some("Rebuild the search index")),
+            ],
+        )
+        .unwrap();
+
+    let names = |query: &str| -> Vec<String> {
+        store
+            .search_symbols(query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.symbol.name)
+            .collect()
+    };
+    // camelCase identifiers are indexed word by word
+    assert_eq!(names("user by id"), vec!["getUserById"]);
+    assert_eq!(names("UserById"), vec!["getUserById"]);
+    assert_eq!(names("getUser"), vec!["getUserById"]);
+    // Porter stemming matches inflected forms
+    assert_eq!(names("indexes"), vec!["rebuild"]);
+    assert!(names("account").is_empty());
+
+    // Rows leave the FTS table with their symbol
+    store.insert_symbols(file_id, &[]).unwrap();
+    assert!(names("user").is_empty());
+}
+
+#[test]
+fn test_search_symbols_reports_matched_field() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("upload.rs", Some("rust"), "", 0, 1000)
+        .unwrap();
+    let symbol = |name: &str, signature: Option<&str>, doc: Option<&str>| Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: signature.map(str::to_string),
+        doc_comment: doc.map(str::to_string),
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    store
+        .insert_symbols(
+            file_id,
+            &[
+                symbol(
+                    "send_chunk",
+                    None,
+                    Some("Retry a failed upload with backoff"),
+                ),
+                symbol("start", Some("fn start(upload: Upload)"), None),
+                symbol("upload", None, None),
+            ],
+        )
+        .unwrap();
+
+    let found: Vec<(String, SymbolField)> = store
+        .search_symbols("upload", 10)
+        .unwrap()
+        .into_iter()
+        .map(|m| (m.symbol.name, m.matched_in))
+        .collect();
+    // Name matches rank first, documentation last
+    assert_eq!(
+        found,
+        vec![
+            ("upload".to_string(), SymbolField::Name),
+            ("start".to_string(), SymbolField::Signature),
+            ("send_chunk".to_string(), SymbolField::Docs),
+        ]
+    );
+
+    let docs: Vec<String> = store
+        .search_symbol_docs(&["retries", "uploads", "\"quoted\""], 10)
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(docs, vec!["send_chunk"]);
+    assert!(store.search_symbol_docs(&["*"], 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_symbol_names_with_prefix() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("test.rs", Some("rust"), "", 0, 1000)
+        .unwrap();
+
+    let symbols: Vec<Symbol> = ["parse_config", "Parser", "parse", "parse", "load"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| Symbol {
+            name: name.to_string(),
+            kind: SymbolKind::Function,
+            start_line: i + 1,
+            end_line: i + 1,
+            start_byte: 0,
+            end_byte: 0,
+            start_column: 0,
+            end_column: 0,
+            signature: None,
+            doc_comment: None,
+            parent: None,
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
+        })
+        .collect();
+    store.insert_symbols(file_id, &symbols).unwrap();
+
+    assert_eq!(
+        store.symbol_names_with_prefix("Pa", 10).unwrap(),
+        vec!["parse", "Parser", "parse_config"]
+    );
+    assert_eq!(
+        store.symbol_names_with_prefix("pa", 1).unwrap(),
+        vec!["parse"]
+    );
+    assert!(store.symbol_names_with_prefix("x", 10).unwrap().is_empty());
 }
 
 #[test]
@@ -573,9 +1163,13 @@ fn test_get_stats() {
         end_line: 1,
         start_byte: 0,
         end_byte: 12,
+        start_column: 0,
+        end_column: 0,
         signature: None,
         doc_comment: None,
         parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
     }];
     store.insert_symbols(file_id, &symbols).unwrap();
 
@@ -590,6 +1184,23 @@ fn test_db_path() {
     assert_eq!(store.db_path().to_string_lossy(), ":memory:");
 }
 
+#[test]
+fn test_checkpoint_wal() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index.db");
+    let store = IndexStore::open(&path).unwrap();
+    store.ping().unwrap();
+    store
+        .insert_file("src/lib.rs", Some("rust"), "fn main() {}", 12, 1000)
+        .unwrap();
+
+    let wal = dir.path().join("index.db-wal");
+    assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+    store.checkpoint_wal().unwrap();
+    assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+    assert!(store.get_file_by_path("src/lib.rs").unwrap().is_some());
+}
+
 // Distance observations and calibration tests
 
 #[test]
@@ -824,6 +1435,42 @@ fn test_get_file_language() {
     assert!(no_lang.is_none());
 }
 
+#[test]
+fn test_sample_chunk_embeddings_skips_external_files() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let chunk = CodeChunk {
+        content: "fn main() {}".to_string(),
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 12,
+        symbols: vec!["main".to_string()],
+    };
+    let embedding: Vec<f32> = (0..EMBEDDING_DIMENSION).map(|i| i as f32 * 0.001).collect();
+
+    for path in [
+        "src/main.rs",
+        "src/lib.rs",
+        "external://cargo/foo@1.0.0/src/lib.rs",
+    ] {
+        let file_id = store
+            .insert_file(path, Some("rust"), "fn main() {}", 12, 1000)
+            .unwrap();
+        store
+            .insert_chunks(file_id, std::slice::from_ref(&chunk))
+            .unwrap();
+        let chunk_id = store.get_chunks_by_file(file_id).unwrap()[0].id;
+        if path != "src/lib.rs" {
+            store.update_chunk_embedding(chunk_id, &embedding).unwrap();
+        }
+    }
+
+    let sampled = store.sample_chunk_embeddings(10).unwrap();
+    assert_eq!(sampled.len(), 1);
+    assert_eq!(sampled[0].1.len(), EMBEDDING_DIMENSION);
+    assert!(store.sample_chunk_embeddings(0).unwrap().is_empty());
+}
+
 #[test]
 fn test_get_chunk_language() {
     let store = IndexStore::open_in_memory().unwrap();
@@ -849,3 +1496,2876 @@ fn test_get_chunk_language() {
     let language = store.get_chunk_language(chunk_id).unwrap();
     assert_eq!(language, Some("python".to_string()));
 }
+
+#[test]
+fn test_language_stats_and_embedding_coverage() {
+    let store = IndexStore::open_in_memory().unwrap();
+
+    let rust_id = store
+        .insert_file("src/main.rs", Some("rust"), "fn main() {}", 12, 1000)
+        .unwrap();
+    store
+        .insert_file("src/lib.rs", Some("rust"), "fn lib() {}", 11, 1000)
+        .unwrap();
+    store
+        .insert_file("app.py", Some("python"), "def main(): pass", 16, 1000)
+        .unwrap();
+
+    store
+        .insert_symbols(
+            rust_id,
+            &[Symbol {
+                name: "main".to_string(),
+                kind: SymbolKind::Function,
+                start_line: 1,
+                end_line: 1,
+                start_byte: 0,
+                end_byte: 12,
+                start_column: 0,
+                end_column: 0,
+                signature: None,
+                doc_comment: None,
+                parent: None,
+                qualifiers: SymbolQualifiers::default(),
+                cfg_context: None,
+            }],
+        )
+        .unwrap();
+
+    let chunk = |start_line: usize| CodeChunk {
+        content: "fn main() {}".to_string(),
+        start_line,
+        end_line: start_line,
+        start_byte: 0,
+        end_byte: 12,
+        symbols: vec![],
+    };
+    store.insert_chunks(rust_id, &[chunk(1), chunk(2)]).unwrap();
+    let chunk_id = store.get_chunks_by_file(rust_id).unwrap()[0].id;
+    let embedding: Vec<f32> = (0..384).map(|i| i as f32 * 0.001).collect();
+    store.update_chunk_embedding(chunk_id, &embedding).unwrap();
+
+    let stats = store.get_language_stats().unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].language, "rust");
+    assert_eq!(stats[0].file_count, 2);
+    assert_eq!(stats[0].symbol_count, 1);
+    assert_eq!(stats[0].chunk_count, 2);
+    assert_eq!(stats[0].embedded_chunk_count, 1);
+    assert_eq!(stats[0].embedding_coverage(), 50.0);
+    assert_eq!(stats[1].language, "python");
+    assert_eq!(stats[1].chunk_count, 0);
+    assert_eq!(stats[1].embedding_coverage(), 0.0);
+
+    assert_eq!(store.get_embedding_coverage().unwrap(), (1, 2));
+}
+
+#[test]
+fn test_largest_files_and_directory_stats() {
+    let store = IndexStore::open_in_memory().unwrap();
+
+    store
+        .insert_file("README.md", Some("markdown"), "# readme", 8, 1000)
+        .unwrap();
+    store
+        .insert_file("src/main.rs", Some("rust"), "fn main() {}", 500, 1000)
+        .unwrap();
+    store
+        .insert_file(
+            "src/index/store.rs",
+            Some("rust"),
+            "fn store() {}",
+            2000,
+            1000,
+        )
+        .unwrap();
+
+    let largest = store.get_largest_files(2).unwrap();
+    assert_eq!(largest.len(), 2);
+    assert_eq!(largest[0].path, "src/index/store.rs");
+    assert_eq!(largest[1].path, "src/main.rs");
+
+    let dirs = store.get_directory_stats(1).unwrap();
+    assert_eq!(dirs.len(), 2);
+    assert_eq!(dirs[0].directory, ".");
+    assert_eq!(dirs[0].file_count, 1);
+    assert_eq!(dirs[1].directory, "src");
+    assert_eq!(dirs[1].file_count, 2);
+    assert_eq!(dirs[1].total_size, 2500);
+    assert!(dirs[1].last_indexed_at > 0);
+
+    let nested = store.get_directory_stats(2).unwrap();
+    let names: Vec<&str> = nested.iter().map(|d| d.directory.as_str()).collect();
+    assert_eq!(names, vec![".", "src", "src/index"]);
+
+    assert!(store.get_database_size().unwrap() > 0);
+}
+
+/// L2-normalized test embedding whose direction depends on `seed`
+fn normalized_embedding(seed: f32) -> Vec<f32> {
+    let raw: Vec<f32> = (0..384).map(|i| ((i as f32) * seed).sin()).collect();
+    let norm = raw.iter().map(|x| x * x).sum::<f32>().sqrt();
+    raw.iter().map(|x| x / norm).collect()
+}
+
+fn insert_embedded_chunks(store: &IndexStore, count: usize) -> Vec<i64> {
+    let file_id = store
+        .insert_file("src/main.rs", Some("rust"), "fn main() {}", 12, 1000)
+        .unwrap();
+    let chunks: Vec<CodeChunk> = (0..count)
+        .map(|i| CodeChunk {
+            content: format!("fn f{}() {{}}", i),
+            start_line: i + 1,
+            end_line: i + 1,
+            start_byte: 0,
+            end_byte: 10,
+            symbols: vec![],
+        })
+        .collect();
+    store.insert_chunks(file_id, &chunks).unwrap();
+
+    let ids: Vec<i64> = store
+        .get_chunks_by_file(file_id)
+        .unwrap()
+        .iter()
+        .map(|c| c.id)
+        .collect();
+    for (i, id) in ids.iter().enumerate() {
+        store
+            .update_chunk_embedding(*id, &normalized_embedding(0.1 + i as f32))
+            .unwrap();
+    }
+    ids
+}
+
+#[test]
+fn test_int8_embedding_search() {
+    let store = IndexStore::open_in_memory().unwrap();
+    assert_eq!(store.embedding_format(), EmbeddingFormat::Float32);
+    assert_eq!(
+        store.set_embedding_format(EmbeddingFormat::Int8).unwrap(),
+        0
+    );
+    assert_eq!(store.embedding_format(), EmbeddingFormat::Int8);
+
+    let ids = insert_embedded_chunks(&store, 3);
+
+    let results = store
+        .search_similar_chunks(&normalized_embedding(1.1), 3)
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, ids[1]);
+    // Distances are scaled back to float range (normalized vectors: 0..=2)
+    assert!(results[0].1 < 0.1);
+    assert!(results.iter().all(|(_, d)| *d <= 2.0));
+
+    let chunks = store.get_chunks_by_ids(&[ids[0]]).unwrap();
+    let restored = chunks[0].embedding.as_ref().unwrap();
+    let original = normalized_embedding(0.1);
+    assert_eq!(restored.len(), 384);
+    assert!(
+        restored
+            .iter()
+            .zip(&original)
+            .all(|(a, b)| (a - b).abs() < 0.01)
+    );
+}
+
+#[test]
+fn test_convert_existing_embeddings_to_int8() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let ids = insert_embedded_chunks(&store, 3);
+
+    let query = normalized_embedding(2.1);
+    let float_results = store.search_similar_chunks(&query, 3).unwrap();
+
+    assert_eq!(
+        store.set_embedding_format(EmbeddingFormat::Int8).unwrap(),
+        3
+    );
+    // Converting to the current format is a no-op
+    assert_eq!(
+        store.set_embedding_format(EmbeddingFormat::Int8).unwrap(),
+        0
+    );
+
+    let int8_results = store.search_similar_chunks(&query, 3).unwrap();
+    assert_eq!(int8_results[0].0, ids[2]);
+    // Quantization keeps distances close to their float values
+    for (f, q) in float_results.iter().zip(&int8_results) {
+        assert!((f.1 - q.1).abs() < 0.02);
+    }
+
+    // The format is persisted in the metadata table
+    let format = store
+        .with_conn(embedding_format::load_embedding_format)
+        .unwrap();
+    assert_eq!(format, EmbeddingFormat::Int8);
+
+    assert_eq!(
+        store
+            .set_embedding_format(EmbeddingFormat::Float32)
+            .unwrap(),
+        3
+    );
+    let restored = store.search_similar_chunks(&query, 1).unwrap();
+    assert_eq!(restored[0].0, ids[2]);
+}
+
+#[test]
+fn test_chunk_scans_read_pages_within_the_memory_budget() {
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let store = IndexStore::open_in_memory().unwrap();
+    let ids = insert_embedded_chunks(&store, 3);
+    // Each page holds a single chunk
+    store.set_scan_memory(1);
+
+    let page = store.get_chunks_with_embeddings_page(0).unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].id, ids[0]);
+    assert_eq!(page[0].embedding.as_ref().map(Vec::len), Some(384));
+    assert!(
+        store
+            .get_chunks_with_embeddings_page(ids[2])
+            .unwrap()
+            .is_empty()
+    );
+
+    let scanned: Vec<i64> = store
+        .chunks_with_embeddings()
+        .map(|chunk| chunk.unwrap().id)
+        .collect();
+    assert_eq!(scanned, ids);
+    #[allow(deprecated)]
+    let all = store.get_chunks_with_embeddings().unwrap();
+    assert_eq!(
+        all.iter().map(|(chunk, _)| chunk.id).collect::<Vec<_>>(),
+        ids
+    );
+    assert!(all.iter().all(|(_, embedding)| embedding.len() == 384));
+
+    // Conversions go through every page
+    assert_eq!(
+        store.set_embedding_format(EmbeddingFormat::Int8).unwrap(),
+        3
+    );
+    let query = normalized_embedding(2.1);
+    assert_eq!(store.search_similar_chunks(&query, 3).unwrap().len(), 3);
+
+    // So does the switch to the vectors of another model
+    assert!(
+        store
+            .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+            .unwrap()
+    );
+    let mut new = vec![0.0; EMBEDDING_DIMENSION];
+    new[0] = 1.0;
+    for &id in &ids {
+        store
+            .store_migrated_embeddings(id, &[(id, new.clone())])
+            .unwrap();
+    }
+    store.complete_embedding_migration().unwrap();
+    assert_eq!(store.get_embedding_coverage().unwrap(), (3, 3));
+    assert_eq!(store.search_similar_chunks(&new, 3).unwrap().len(), 3);
+}
+
+#[test]
+fn test_index_generation_bumps_on_writes() {
+    let store = IndexStore::open_in_memory().unwrap();
+    assert_eq!(store.index_generation().unwrap(), 0);
+    assert_eq!(store.get_stats().unwrap().generation, 0);
+
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    let after_insert = store.index_generation().unwrap();
+    assert!(after_insert > 0);
+    assert_eq!(store.get_stats().unwrap().generation, after_insert);
+
+    store
+        .insert_dependency(file_id, "std", None, "std")
+        .unwrap();
+    assert!(store.index_generation().unwrap() > after_insert);
+
+    // Reads and no-op deletes leave the generation alone
+    let generation = store.index_generation().unwrap();
+    store.get_file_by_path("src/lib.rs").unwrap();
+    store.delete_file("src/missing.rs").unwrap();
+    assert_eq!(store.index_generation().unwrap(), generation);
+
+    store.delete_file("src/lib.rs").unwrap();
+    assert!(store.index_generation().unwrap() > generation);
+}
+
+#[test]
+fn test_query_cache_is_scoped_to_generation() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store.put_cached_query("q1", 3, "{\"a\":1}", 10).unwrap();
+    assert_eq!(
+        store.get_cached_query("q1", 3).unwrap().as_deref(),
+        Some("{\"a\":1}")
+    );
+    assert!(store.get_cached_query("q1", 4).unwrap().is_none());
+
+    // Storing at a newer generation drops older entries
+    store.put_cached_query("q2", 4, "{}", 10).unwrap();
+    assert!(store.get_cached_query("q1", 3).unwrap().is_none());
+
+    // Only the most recent entries are kept
+    store.put_cached_query("q3", 4, "{}", 1).unwrap();
+    assert!(store.get_cached_query("q2", 4).unwrap().is_none());
+    assert!(store.get_cached_query("q3", 4).unwrap().is_some());
+
+    assert_eq!(store.clear_query_cache().unwrap(), 1);
+}
+
+#[test]
+fn test_query_embeddings_are_cached_per_model() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let ttl = std::time::Duration::from_secs(3600);
+    store
+        .put_query_embedding("minilm", "parse config", &[0.5, -1.0], ttl, 10)
+        .unwrap();
+    assert_eq!(
+        store
+            .get_query_embedding("minilm", "parse config", ttl)
+            .unwrap(),
+        Some(vec![0.5, -1.0])
+    );
+    assert!(
+        store
+            .get_query_embedding("multilingual-minilm", "parse config", ttl)
+            .unwrap()
+            .is_none()
+    );
+
+    // Only the most recently used entries are kept
+    store
+        .put_query_embedding("minilm", "load file", &[1.0], ttl, 10)
+        .unwrap();
+    store
+        .get_query_embedding("minilm", "parse config", ttl)
+        .unwrap();
+    store
+        .put_query_embedding("minilm", "retry upload", &[1.0], ttl, 2)
+        .unwrap();
+    assert!(
+        store
+            .get_query_embedding("minilm", "load file", ttl)
+            .unwrap()
+            .is_none()
+    );
+
+    // Expired entries are ignored
+    store
+        .with_conn(|conn| Ok(conn.execute("UPDATE query_embeddings SET stored_at = 0", [])?))
+        .unwrap();
+    assert!(
+        store
+            .get_query_embedding("minilm", "parse config", ttl)
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(store.clear_query_embeddings().unwrap(), 2);
+}
+
+#[test]
+fn test_embedding_model_change_without_embeddings_is_recorded() {
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let store = IndexStore::open_in_memory().unwrap();
+    assert_eq!(store.embedding_model().unwrap(), EmbeddingModelKind::MiniLm);
+
+    store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    assert!(
+        !store
+            .set_embedding_model(EmbeddingModelKind::MiniLm)
+            .unwrap()
+    );
+
+    // Nothing to embed again: the model is recorded and the files are kept
+    assert!(
+        !store
+            .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+            .unwrap()
+    );
+    assert_eq!(store.get_stats().unwrap().file_count, 1);
+    assert_eq!(
+        store.embedding_model().unwrap(),
+        EmbeddingModelKind::MultilingualMiniLm
+    );
+    assert!(store.embedding_migration().unwrap().is_none());
+}
+
+#[test]
+fn test_embedding_model_change_migrates_embeddings() {
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    store
+        .insert_chunks(
+            file_id,
+            &[
+                CodeChunk {
+                    content: "fn a() {}".to_string(),
+                    start_line: 1,
+                    end_line: 1,
+                    start_byte: 0,
+                    end_byte: 9,
+                    symbols: vec!["a".to_string()],
+                },
+                CodeChunk {
+                    content: "fn b() {}".to_string(),
+                    start_line: 2,
+                    end_line: 2,
+                    start_byte: 10,
+                    end_byte: 19,
+                    symbols: vec!["b".to_string()],
+                },
+            ],
+        )
+        .unwrap();
+    let chunk_ids: Vec<i64> = store
+        .get_chunks_by_file(file_id)
+        .unwrap()
+        .iter()
+        .map(|c| c.id)
+        .collect();
+    let old = vec![1.0; EMBEDDING_DIMENSION];
+    let mut new = vec![0.0; EMBEDDING_DIMENSION];
+    new[0] = 1.0;
+    for &id in &chunk_ids {
+        store.update_chunk_embedding(id, &old).unwrap();
+    }
+
+    assert!(
+        store
+            .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+            .unwrap()
+    );
+    // Searches keep the current model and vectors until the switch
+    assert_eq!(store.embedding_model().unwrap(), EmbeddingModelKind::MiniLm);
+    assert_eq!(store.get_stats().unwrap().chunk_count, 2);
+    let migration = store.embedding_migration().unwrap().unwrap();
+    assert_eq!(migration.to, EmbeddingModelKind::MultilingualMiniLm);
+    assert_eq!((migration.embedded, migration.total), (0, 2));
+
+    let batch = store.chunks_to_migrate(1).unwrap();
+    assert_eq!(batch.len(), 1);
+    store
+        .store_migrated_embeddings(batch[0].id, &[(batch[0].id, new.clone())])
+        .unwrap();
+    assert_eq!(store.embedding_migration().unwrap().unwrap().embedded, 1);
+    assert_eq!(store.chunks_to_migrate(10).unwrap().len(), 1);
+    assert_eq!(store.search_similar_chunks(&old, 1).unwrap()[0].1, 0.0);
+
+    let generation = store.index_generation().unwrap();
+    assert_eq!(
+        store.complete_embedding_migration().unwrap(),
+        Some(EmbeddingModelKind::MultilingualMiniLm)
+    );
+    assert_eq!(
+        store.embedding_model().unwrap(),
+        EmbeddingModelKind::MultilingualMiniLm
+    );
+    assert!(store.embedding_migration().unwrap().is_none());
+    assert!(store.index_generation().unwrap() > generation);
+
+    // The chunk without a new vector waits to be embedded again
+    assert_eq!(store.get_embedding_coverage().unwrap(), (1, 2));
+    let results = store.search_similar_chunks(&new, 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, batch[0].id);
+    assert!(results[0].1 < 1e-6);
+    assert_eq!(
+        store.get_chunks_by_ids(&[batch[0].id]).unwrap()[0].embedding,
+        Some(new)
+    );
+}
+
+#[test]
+fn test_embedding_migration_cancel_and_restart() {
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    let chunk = CodeChunk {
+        content: "fn a() {}".to_string(),
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 9,
+        symbols: vec![],
+    };
+    store
+        .insert_chunks(file_id, std::slice::from_ref(&chunk))
+        .unwrap();
+    let id = store.get_chunks_by_file(file_id).unwrap()[0].id;
+    store
+        .update_chunk_embedding(id, &vec![1.0; EMBEDDING_DIMENSION])
+        .unwrap();
+
+    store
+        .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+        .unwrap();
+    store
+        .store_migrated_embeddings(id, &[(id, vec![0.5; EMBEDDING_DIMENSION])])
+        .unwrap();
+    assert_eq!(store.embedding_migration().unwrap().unwrap().embedded, 1);
+
+    // Rewritten chunks are embedded again, even when they reuse an id
+    store.insert_chunks(file_id, &[chunk]).unwrap();
+    assert_eq!(store.embedding_migration().unwrap().unwrap().embedded, 0);
+
+    // Going back to the recorded model cancels the migration
+    assert!(
+        !store
+            .set_embedding_model(EmbeddingModelKind::MiniLm)
+            .unwrap()
+    );
+    assert!(store.embedding_migration().unwrap().is_none());
+    assert!(store.chunks_to_migrate(10).unwrap().is_empty());
+    assert_eq!(store.complete_embedding_migration().unwrap(), None);
+}
+
+#[test]
+fn test_embedding_preprocessing_change_migrates_embeddings() {
+    use crate::preprocess::{EmbeddingPreprocessor, PreprocessStep};
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("index.db");
+    let store = IndexStore::open(&db_path).unwrap();
+    let pipeline = EmbeddingPreprocessor::new(&[PreprocessStep::CollapseWhitespace]);
+
+    // Without embeddings, the pipeline is recorded right away
+    assert!(!store.set_embedding_preprocessor(&pipeline).unwrap());
+    assert!(store.embedding_migration().unwrap().is_none());
+    assert_eq!(store.embedding_text("fn  a() {}\n\n"), "fn a() {}");
+
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    let chunk = CodeChunk {
+        content: "fn a() {}".to_string(),
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 9,
+        symbols: vec![],
+    };
+    store.insert_chunks(file_id, &[chunk]).unwrap();
+    let id = store.get_chunks_by_file(file_id).unwrap()[0].id;
+    store
+        .update_chunk_embedding(id, &vec![1.0; EMBEDDING_DIMENSION])
+        .unwrap();
+    assert!(!store.set_embedding_preprocessor(&pipeline).unwrap());
+
+    // Another pipeline re-embeds the chunks with the current model
+    let lowercase = EmbeddingPreprocessor::new(&[
+        PreprocessStep::CollapseWhitespace,
+        PreprocessStep::LowercaseIdentifiers,
+    ]);
+    assert!(store.set_embedding_preprocessor(&lowercase).unwrap());
+    let migration = store.embedding_migration().unwrap().unwrap();
+    assert_eq!(migration.to, EmbeddingModelKind::MiniLm);
+    assert_eq!((migration.embedded, migration.total), (0, 1));
+    assert_eq!(store.get_stats().unwrap().chunk_count, 1);
+    // Applying the same model keeps the migration
+    assert!(
+        !store
+            .set_embedding_model(EmbeddingModelKind::MiniLm)
+            .unwrap()
+    );
+    assert!(store.embedding_migration().unwrap().is_some());
+
+    // The recorded pipeline is used when the database is opened again
+    drop(store);
+    let store = IndexStore::open(&db_path).unwrap();
+    assert_eq!(store.embedding_preprocessor(), lowercase);
+    assert_eq!(store.embedding_text("parseConfig()"), "parse_config()");
+}
+
+#[test]
+fn test_embedding_prompts_change_migrates_embeddings() {
+    use semantiq_embeddings::EmbeddingPrompts;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("index.db");
+    let store = IndexStore::open(&db_path).unwrap();
+    let e5 = EmbeddingPrompts::new("query: ", "passage: ");
+
+    // Without embeddings, the prompts are recorded right away
+    assert!(!store.set_embedding_prompts(&e5).unwrap());
+    assert!(store.embedding_migration().unwrap().is_none());
+    assert_eq!(store.embedding_text("fn a() {}"), "passage: fn a() {}");
+    assert_eq!(store.query_text("config"), "query: config");
+
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    let chunk = CodeChunk {
+        content: "fn a() {}".to_string(),
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 9,
+        symbols: vec![],
+    };
+    store.insert_chunks(file_id, &[chunk]).unwrap();
+    let id = store.get_chunks_by_file(file_id).unwrap()[0].id;
+    store
+        .update_chunk_embedding(id, &vec![1.0; EMBEDDING_DIMENSION])
+        .unwrap();
+    assert!(!store.set_embedding_prompts(&e5).unwrap());
+
+    // Other prompts re-embed the chunks
+    let bge = EmbeddingPrompts::new("Represent this sentence for searching code: ", "");
+    assert!(store.set_embedding_prompts(&bge).unwrap());
+    let migration = store.embedding_migration().unwrap().unwrap();
+    assert_eq!((migration.embedded, migration.total), (0, 1));
+
+    // The recorded prompts are used when the database is opened again
+    drop(store);
+    let store = IndexStore::open(&db_path).unwrap();
+    assert_eq!(store.embedding_prompts(), bge);
+    assert_eq!(store.embedding_text("fn a() {}"), "fn a() {}");
+
+    // Dropping the prompts re-embeds the chunks too
+    assert!(
+        store
+            .set_embedding_prompts(&EmbeddingPrompts::default())
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_embedding_migration_keeps_int8_format() {
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+    store
+        .insert_chunks(
+            file_id,
+            &[CodeChunk {
+                content: "fn a() {}".to_string(),
+                start_line: 1,
+                end_line: 1,
+                start_byte: 0,
+                end_byte: 9,
+                symbols: vec![],
+            }],
+        )
+        .unwrap();
+    let id = store.get_chunks_by_file(file_id).unwrap()[0].id;
+    store
+        .update_chunk_embedding(id, &vec![0.5; EMBEDDING_DIMENSION])
+        .unwrap();
+    store.set_embedding_format(EmbeddingFormat::Int8).unwrap();
+
+    store
+        .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+        .unwrap();
+    let mut new = vec![0.0; EMBEDDING_DIMENSION];
+    new[0] = 1.0;
+    store
+        .store_migrated_embeddings(id, &[(id, new.clone())])
+        .unwrap();
+    store.complete_embedding_migration().unwrap();
+
+    let results = store.search_similar_chunks(&new, 1).unwrap();
+    assert_eq!(results[0].0, id);
+    assert!(results[0].1 < 0.05);
+}
+
+/// A chunk embedding split into two windows
+fn embedded(embedding: Vec<f32>) -> ChunkEmbedding {
+    ChunkEmbedding {
+        embedding,
+        fit: TokenFit {
+            tokens: 700,
+            windows: 2,
+            truncated: false,
+        },
+    }
+}
+
+fn journal_test_data() -> (Vec<Symbol>, Vec<CodeChunk>, Vec<Import>) {
+    let symbols = vec![Symbol {
+        name: "main".to_string(),
+        kind: SymbolKind::Function,
+        start_line: 2,
+        end_line: 2,
+        start_byte: 19,
+        end_byte: 31,
+        start_column: 0,
+        end_column: 0,
+        signature: Some("fn main()".to_string()),
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    }];
+    let chunks = vec![CodeChunk {
+        content: "fn main() {}".to_string(),
+        start_line: 2,
+        end_line: 2,
+        start_byte: 19,
+        end_byte: 31,
+        symbols: vec!["main".to_string()],
+    }];
+    let imports = vec![Import {
+        path: "std::io".to_string(),
+        name: Some("io".to_string()),
+        kind: ImportKind::Std,
+        symbols: vec![ImportedSymbol::new("io", None)],
+        start_line: 1,
+        end_line: 1,
+    }];
+    (symbols, chunks, imports)
+}
+
+#[test]
+fn test_write_file_index() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let (symbols, chunks, imports) = journal_test_data();
+    let embeddings = vec![Some(embedded(vec![0.1; EMBEDDING_DIMENSION]))];
+    let content = "use std::io;\nfn main() {}";
+
+    let file_id = store
+        .write_file_index(&FileIndexData {
+            symbols: &symbols,
+            chunks: &chunks,
+            embeddings: &embeddings,
+            imports: &imports,
+            ..FileIndexData::new("src/main.rs", Some("rust"), content, 25, 1000)
+        })
+        .unwrap();
+
+    assert_eq!(
+        store.get_file_by_path("src/main.rs").unwrap().unwrap().id,
+        file_id
+    );
+    assert_eq!(store.get_symbols_by_file(file_id).unwrap().len(), 1);
+    let stored_chunks = store.get_chunks_by_file(file_id).unwrap();
+    assert_eq!(stored_chunks.len(), 1);
+    assert_eq!(stored_chunks[0].token_fit, Some(embedded(Vec::new()).fit));
+    assert_eq!(store.get_token_fit_stats().unwrap(), (1, 0));
+    assert_eq!(store.chunks_with_embeddings().count(), 1);
+    let deps = store.get_dependencies(file_id).unwrap();
+    assert_eq!(deps.len(), 1);
+    assert_eq!(deps[0].symbols, vec![ImportedSymbol::new("io", None)]);
+    assert!(store.interrupted_files().unwrap().is_empty());
+}
+
+#[test]
+fn test_write_file_indexes() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let (symbols, chunks, imports) = journal_test_data();
+    let content = "use std::io;\nfn main() {}";
+    let generation = store.index_generation().unwrap();
+
+    let files: Vec<FileIndexData<'_>> = ["src/a.rs", "src/b.rs", "src/c.rs"]
+        .into_iter()
+        .map(|path| FileIndexData {
+            symbols: &symbols,
+            chunks: &chunks,
+            imports: &imports,
+            ..FileIndexData::new(path, Some("rust"), content, 25, 1000)
+        })
+        .collect();
+    let ids = store.write_file_indexes(&files).unwrap();
+
+    assert_eq!(ids.len(), 3);
+    for (path, file_id) in ["src/a.rs", "src/b.rs", "src/c.rs"].iter().zip(&ids) {
+        assert_eq!(store.get_file_by_path(path).unwrap().unwrap().id, *file_id);
+        assert_eq!(store.get_symbols_by_file(*file_id).unwrap().len(), 1);
+        assert_eq!(store.get_chunks_by_file(*file_id).unwrap().len(), 1);
+        assert_eq!(store.get_dependencies(*file_id).unwrap().len(), 1);
+    }
+    // One write for the whole batch
+    assert_eq!(store.index_generation().unwrap(), generation + 1);
+    assert!(store.interrupted_files().unwrap().is_empty());
+    assert!(store.write_file_indexes(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_insert_dependencies() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/main.rs", Some("rust"), "use std::io;", 12, 1000)
+        .unwrap();
+    store
+        .insert_dependency(file_id, "crate::old", None, "local")
+        .unwrap();
+
+    let imports = vec![
+        Import {
+            path: "std::io".to_string(),
+            name: Some("io".to_string()),
+            kind: ImportKind::Std,
+            symbols: vec![ImportedSymbol::new("Read", None)],
+            start_line: 1,
+            end_line: 1,
+        },
+        Import {
+            path: "crate::utils".to_string(),
+            name: None,
+            kind: ImportKind::Local,
+            symbols: Vec::new(),
+            start_line: 2,
+            end_line: 2,
+        },
+    ];
+    let ids = store.insert_dependencies(file_id, &imports).unwrap();
+    assert_eq!(ids.len(), 2);
+
+    let deps = store.get_dependencies(file_id).unwrap();
+    let mut targets: Vec<&str> = deps.iter().map(|d| d.target_path.as_str()).collect();
+    targets.sort();
+    assert_eq!(targets, vec!["crate::utils", "std::io"]);
+    let io = deps.iter().find(|d| d.target_path == "std::io").unwrap();
+    assert_eq!(io.id, ids[0]);
+    assert_eq!(io.symbols, vec![ImportedSymbol::new("Read", None)]);
+}
+
+#[test]
+fn test_write_file_index_records_encoding() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store
+        .write_file_index(&FileIndexData {
+            encoding: Some("Shift_JIS"),
+            ..FileIndexData::new("src/legacy.c", Some("c"), "int x;", 6, 1000)
+        })
+        .unwrap();
+    store
+        .write_file_index(&FileIndexData::new(
+            "src/main.c",
+            Some("c"),
+            "int y;",
+            6,
+            1000,
+        ))
+        .unwrap();
+
+    let legacy = store.get_file_by_path("src/legacy.c").unwrap().unwrap();
+    assert_eq!(legacy.encoding.as_deref(), Some("Shift_JIS"));
+    let main = store.get_file_by_path("src/main.c").unwrap().unwrap();
+    assert_eq!(main.encoding, None);
+}
+
+#[test]
+fn test_failed_write_is_rolled_back_and_recovered() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let (symbols, chunks, imports) = journal_test_data();
+
+    store
+        .write_file_index(&FileIndexData::new(
+            "src/main.rs",
+            Some("rust"),
+            "v1",
+            2,
+            1000,
+        ))
+        .unwrap();
+    let generation = store.index_generation().unwrap();
+
+    // An embedding of the wrong dimension makes the write fail after the
+    // file, symbols and chunks were written
+    let bad_embeddings = vec![Some(embedded(vec![0.1; 3]))];
+    let result = store.write_file_index(&FileIndexData {
+        symbols: &symbols,
+        chunks: &chunks,
+        embeddings: &bad_embeddings,
+        imports: &imports,
+        ..FileIndexData::new("src/main.rs", Some("rust"), "v2", 2, 2000)
+    });
+    assert!(result.is_err());
+
+    // Nothing of the failed write is visible
+    let file = store.get_file_by_path("src/main.rs").unwrap().unwrap();
+    assert!(!store.needs_reindex("src/main.rs", "v1").unwrap());
+    assert!(store.get_symbols_by_file(file.id).unwrap().is_empty());
+    assert_eq!(store.index_generation().unwrap(), generation);
+    assert_eq!(store.interrupted_files().unwrap(), vec!["src/main.rs"]);
+
+    // Recovery drops the file so the next scan indexes it again
+    let recovered = store.recover_interrupted_files().unwrap();
+    assert_eq!(recovered, vec!["src/main.rs"]);
+    assert!(store.get_file_by_path("src/main.rs").unwrap().is_none());
+    assert!(store.interrupted_files().unwrap().is_empty());
+    assert!(store.recover_interrupted_files().unwrap().is_empty());
+}
+
+#[test]
+fn test_recover_interrupted_external_file_removes_package() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let namespace = "external://cargo/serde@1.0.200";
+
+    store
+        .write_file_index(&FileIndexData::new(
+            &format!("{}/src/lib.rs", namespace),
+            Some("rust"),
+            "pub mod de;",
+            11,
+            1000,
+        ))
+        .unwrap();
+    store
+        .write_file_index(&FileIndexData::new(
+            "external://cargo/serde_json@1.0.0/src/lib.rs",
+            Some("rust"),
+            "pub mod value;",
+            14,
+            1000,
+        ))
+        .unwrap();
+
+    let (_, chunks, _) = journal_test_data();
+    let bad_embeddings = vec![Some(embedded(vec![0.1; 3]))];
+    let result = store.write_file_index(&FileIndexData {
+        chunks: &chunks,
+        embeddings: &bad_embeddings,
+        ..FileIndexData::new(
+            &format!("{}/src/de/mod.rs", namespace),
+            Some("rust"),
+            "fn main() {}",
+            12,
+            1000,
+        )
+    });
+    assert!(result.is_err());
+
+    store.recover_interrupted_files().unwrap();
+    assert!(
+        store
+            .get_file_paths_with_prefix(namespace)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        store
+            .get_file_paths_with_prefix("external://cargo/serde_json@")
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+// ==================== Index lock tests ====================
+
+/// Record the lock as held by another process, with its last heartbeat `age` seconds ago.
+fn hold_lock_elsewhere(store: &IndexStore, age: i64) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    store
+        .with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO index_lock (id, owner, pid, acquired_at, heartbeat_at)
+                 VALUES (1, 'semantiq serve', ?1, ?2, ?2)",
+                rusqlite::params![std::process::id().wrapping_add(1), now - age],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_index_lock_acquire_and_release() {
+    let store = IndexStore::open_in_memory().unwrap();
+    assert!(store.index_lock_holder().unwrap().is_none());
+
+    let IndexLockAttempt::Acquired(guard) = store.try_lock_index("semantiq index").unwrap() else {
+        panic!("lock should be free");
+    };
+    let holder = store.index_lock_holder().unwrap().unwrap();
+    assert_eq!(holder.owner, "semantiq index");
+    assert_eq!(holder.pid, std::process::id());
+    assert!(guard.refresh().unwrap());
+
+    drop(guard);
+    assert!(store.index_lock_holder().unwrap().is_none());
+}
+
+#[test]
+fn test_index_lock_held_by_another_process() {
+    let store = IndexStore::open_in_memory().unwrap();
+    hold_lock_elsewhere(&store, 0);
+
+    let attempt = store
+        .lock_index("semantiq index", std::time::Duration::ZERO)
+        .unwrap();
+    let IndexLockAttempt::Held(holder) = attempt else {
+        panic!("lock should be held");
+    };
+    assert_eq!(holder.owner, "semantiq serve");
+    assert!(holder.to_string().starts_with("semantiq serve (pid "));
+}
+
+#[test]
+fn test_stale_index_lock_is_taken_over() {
+    let store = IndexStore::open_in_memory().unwrap();
+    hold_lock_elsewhere(&store, INDEX_LOCK_STALE_SECS + 10);
+
+    assert!(matches!(
+        store.try_lock_index("semantiq index").unwrap(),
+        IndexLockAttempt::Acquired(_)
+    ));
+}
+
+#[test]
+fn test_steal_index_lock() {
+    let store = IndexStore::open_in_memory().unwrap();
+    hold_lock_elsewhere(&store, 0);
+
+    let (guard, previous) = store.steal_index_lock("semantiq index").unwrap();
+    assert_eq!(previous.unwrap().owner, "semantiq serve");
+    assert_eq!(
+        store.index_lock_holder().unwrap().unwrap().pid,
+        std::process::id()
+    );
+
+    // The thief is robbed in turn: its guard notices and leaves the lock alone
+    hold_lock_elsewhere(&store, 0);
+    assert!(!guard.refresh().unwrap());
+    drop(guard);
+    assert_eq!(
+        store.index_lock_holder().unwrap().unwrap().owner,
+        "semantiq serve"
+    );
+}
+
+// ==================== Type relation tests ====================
+
+fn relation(type_name: &str, kind: RelationKind, target: &str, line: usize) -> TypeRelation {
+    TypeRelation {
+        type_name: type_name.to_string(),
+        kind,
+        target: target.to_string(),
+        start_line: line,
+        end_line: line,
+    }
+}
+
+#[test]
+fn test_type_relations_round_trip() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let relations = vec![
+        relation("Circle", RelationKind::Implements, "Shape", 3),
+        relation("Solid", RelationKind::Extends, "Shape", 8),
+    ];
+    let file_id = store
+        .write_file_index(&FileIndexData {
+            relations: &relations,
+            ..FileIndexData::new("src/shape.rs", Some("rust"), "", 0, 0)
+        })
+        .unwrap();
+
+    let stored = store.get_type_relations(file_id).unwrap();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[0].file_path, "src/shape.rs");
+    assert_eq!(stored[0].kind, "implements");
+
+    let implementors = store
+        .find_relations_to("Shape", RelationKind::Implements)
+        .unwrap();
+    assert_eq!(implementors.len(), 1);
+    assert_eq!(implementors[0].type_name, "Circle");
+    assert_eq!(implementors[0].language.as_deref(), Some("rust"));
+
+    // Reindexing the file replaces its relations
+    let file_id = store
+        .write_file_index(&FileIndexData::new("src/shape.rs", Some("rust"), "", 0, 0))
+        .unwrap();
+    assert!(store.get_type_relations(file_id).unwrap().is_empty());
+    assert!(
+        store
+            .find_relations_to("Shape", RelationKind::Extends)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_entrypoints_round_trip() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let entrypoints = vec![
+        Entrypoint {
+            kind: EntrypointKind::Route,
+            name: "GET /users".to_string(),
+            handler: Some("list_users".to_string()),
+            start_line: 4,
+            end_line: 6,
+        },
+        Entrypoint {
+            kind: EntrypointKind::Main,
+            name: "main".to_string(),
+            handler: None,
+            start_line: 10,
+            end_line: 12,
+        },
+    ];
+    store
+        .write_file_index(&FileIndexData {
+            entrypoints: &entrypoints,
+            ..FileIndexData::new("src/main.rs", Some("rust"), "", 0, 0)
+        })
+        .unwrap();
+
+    // Main functions come first, whatever their line
+    let stored = store.get_entrypoints(None, None).unwrap();
+    let names: Vec<&str> = stored.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["main", "GET /users"]);
+    assert_eq!(stored[1].handler.as_deref(), Some("list_users"));
+    assert_eq!(stored[1].language.as_deref(), Some("rust"));
+
+    let routes = store
+        .get_entrypoints(Some(EntrypointKind::Route), Some("src"))
+        .unwrap();
+    assert_eq!(routes.len(), 1);
+    // Directories match whole path components
+    assert!(store.get_entrypoints(None, Some("sr")).unwrap().is_empty());
+
+    // Deleting the file deletes its entry points
+    store.delete_file("src/main.rs").unwrap();
+    assert!(store.get_entrypoints(None, None).unwrap().is_empty());
+}
+
+#[test]
+fn test_routes_of_route_entrypoints() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let route = |name: &str, handler: &str, line: usize| Entrypoint {
+        kind: EntrypointKind::Route,
+        name: name.to_string(),
+        handler: Some(handler.to_string()),
+        start_line: line,
+        end_line: line,
+    };
+    let entrypoints = vec![
+        route("POST /users", "create_user", 3),
+        route("/users/{id}", "user", 8),
+        route("GET /users", "list_users", 5),
+        Entrypoint {
+            kind: EntrypointKind::Command,
+            name: "serve".to_string(),
+            handler: None,
+            start_line: 12,
+            end_line: 12,
+        },
+    ];
+    store
+        .write_file_index(&FileIndexData {
+            entrypoints: &entrypoints,
+            ..FileIndexData::new("src/api.rs", Some("rust"), "", 0, 0)
+        })
+        .unwrap();
+
+    let routes = store.get_routes(None).unwrap();
+    let names: Vec<(Option<&str>, &str)> = routes
+        .iter()
+        .map(|r| (r.method.as_deref(), r.path.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            (Some("GET"), "/users"),
+            (Some("POST"), "/users"),
+            (None, "/users/{id}")
+        ]
+    );
+    assert_eq!(routes[0].handler.as_deref(), Some("list_users"));
+
+    // Routes of every method handle any method
+    let posts = store.get_routes(Some("post")).unwrap();
+    let handlers: Vec<&str> = posts.iter().filter_map(|r| r.handler.as_deref()).collect();
+    assert_eq!(handlers, ["create_user", "user"]);
+
+    store.delete_file("src/api.rs").unwrap();
+    assert!(store.get_routes(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_config_refs_by_name_and_glob() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let config_ref =
+        |name: &str, source: ConfigSource, access: ConfigAccess, line: usize| ConfigRef {
+            name: name.to_string(),
+            source,
+            access,
+            line,
+        };
+    let refs = vec![
+        config_ref("DATABASE_URL", ConfigSource::Env, ConfigAccess::Read, 3),
+        config_ref("DATABASE_POOL", ConfigSource::Env, ConfigAccess::Read, 4),
+        config_ref("DATABASE_URL", ConfigSource::Env, ConfigAccess::Write, 9),
+        config_ref("db.url", ConfigSource::Config, ConfigAccess::Read, 12),
+    ];
+    store
+        .write_file_index(&FileIndexData {
+            config_refs: &refs,
+            ..FileIndexData::new("src/db.rs", Some("rust"), "", 0, 0)
+        })
+        .unwrap();
+
+    // Writes come first
+    let url = store.get_config_refs("DATABASE_URL", None, None).unwrap();
+    let sites: Vec<(&str, i64)> = url.iter().map(|r| (r.access.as_str(), r.line)).collect();
+    assert_eq!(sites, [("write", 9), ("read", 3)]);
+    assert_eq!(url[0].file_path, "src/db.rs");
+
+    let database = store
+        .get_config_refs(
+            "DATABASE_*",
+            Some(ConfigSource::Env),
+            Some(ConfigAccess::Read),
+        )
+        .unwrap();
+    let names: Vec<&str> = database.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["DATABASE_POOL", "DATABASE_URL"]);
+    assert!(
+        store
+            .get_config_refs("db.url", Some(ConfigSource::Env), None)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        store.get_config_names(Some(ConfigSource::Config)).unwrap(),
+        ["db.url"]
+    );
+
+    store.delete_file("src/db.rs").unwrap();
+    assert!(store.get_config_names(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_bindings_by_rust_or_exported_name() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let bindings = vec![
+        Binding {
+            symbol: "get_user".to_string(),
+            exported_name: "getUser".to_string(),
+            framework: BindingFramework::Napi,
+            line: 12,
+        },
+        Binding {
+            symbol: "Parser".to_string(),
+            exported_name: "Parser".to_string(),
+            framework: BindingFramework::WasmBindgen,
+            line: 3,
+        },
+    ];
+    store
+        .write_file_index(&FileIndexData {
+            bindings: &bindings,
+            ..FileIndexData::new("core/src/lib.rs", Some("rust"), "", 0, 0)
+        })
+        .unwrap();
+
+    let names = |records: Vec<BindingRecord>| -> Vec<(String, String)> {
+        records
+            .into_iter()
+            .map(|r| (r.symbol, r.framework))
+            .collect()
+    };
+    let by_exported = store.get_bindings(&["getUser".to_string()]).unwrap();
+    assert_eq!(by_exported[0].file_path, "core/src/lib.rs");
+    assert_eq!(by_exported[0].line, 12);
+    assert_eq!(
+        names(by_exported),
+        [("get_user".to_string(), "napi".to_string())]
+    );
+    // Each binding once, by line
+    let both = store
+        .get_bindings(&[
+            "get_user".to_string(),
+            "getUser".to_string(),
+            "Parser".to_string(),
+        ])
+        .unwrap();
+    assert_eq!(both.len(), 2);
+    assert_eq!(both[0].symbol, "Parser");
+
+    store.delete_file("core/src/lib.rs").unwrap();
+    assert!(
+        store
+            .get_bindings(&["Parser".to_string()])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_symbol_history_as_of_generation() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let symbol = |name: &str, line: usize, signature: &str| Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: line,
+        end_line: line,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: Some(signature.to_string()),
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    let write = |symbols: &[Symbol]| {
+        store
+            .write_file_index(&FileIndexData {
+                symbols,
+                ..FileIndexData::new("src/lib.rs", Some("rust"), "", 0, 0)
+            })
+            .unwrap();
+        store.index_generation().unwrap()
+    };
+    let signatures = |name: &str, generation| -> Vec<String> {
+        store
+            .get_symbol_versions(name, generation)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.signature.unwrap_or_default())
+            .collect()
+    };
+
+    let first = write(&[
+        symbol("load", 1, "fn load()"),
+        symbol("save", 3, "fn save()"),
+    ]);
+    // Unchanged symbols keep their version
+    let second = write(&[
+        symbol("load", 1, "fn load(path: &str)"),
+        symbol("save", 3, "fn save()"),
+    ]);
+    assert_eq!(
+        store
+            .get_file_symbol_versions("src/lib.rs", first)
+            .unwrap()
+            .len(),
+        2
+    );
+    let save = store.get_symbol_versions("save", second).unwrap();
+    assert_eq!((save[0].valid_from, save[0].valid_to), (first as i64, None));
+
+    // A symbol moving keeps its version, with the lines of each generation
+    let moved = write(&[
+        symbol("load", 1, "fn load(path: &str)"),
+        symbol("save", 5, "fn save()"),
+    ]);
+    let save = store.get_symbol_versions("save", moved).unwrap();
+    assert_eq!((save[0].valid_from, save[0].start_line), (first as i64, 5));
+    let save = store.get_symbol_versions("save", second).unwrap();
+    assert_eq!((save[0].valid_from, save[0].start_line), (first as i64, 3));
+    let moved_back = write(&[
+        symbol("load", 1, "fn load(path: &str)"),
+        symbol("save", 7, "fn save()"),
+    ]);
+    let lines = |generation| {
+        store
+            .get_file_symbol_versions("src/lib.rs", generation)
+            .unwrap()
+    };
+    assert_eq!(lines(moved)[1].start_line, 5);
+    assert_eq!(lines(moved_back)[1].start_line, 7);
+
+    store.delete_file("src/lib.rs").unwrap();
+    let deleted = store.index_generation().unwrap();
+
+    assert!(signatures("load", first - 1).is_empty());
+    assert_eq!(signatures("load", first), ["fn load()"]);
+    assert_eq!(signatures("load", second), ["fn load(path: &str)"]);
+    assert!(signatures("load", deleted).is_empty());
+    assert!(
+        store
+            .get_file_symbol_versions("src/lib.rs", deleted)
+            .unwrap()
+            .is_empty()
+    );
+
+    // A full reindex writing the symbols again unchanged reopens them
+    let restored = write(&[symbol("save", 3, "fn save()")]);
+    store.clear_all_data().unwrap();
+    let cleared = store.index_generation().unwrap();
+    assert!(signatures("save", cleared).is_empty());
+    write(&[symbol("save", 3, "fn save()")]);
+    let save = store.get_symbol_versions("save", cleared).unwrap();
+    assert_eq!(save.len(), 1);
+    assert_eq!(
+        (save[0].valid_from, save[0].valid_to),
+        (restored as i64, None)
+    );
+}
+
+#[test]
+fn test_occurrence_history_as_of_generation() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let write = |path: &str, counts: &[(&str, usize)]| {
+        store
+            .write_file_index(&FileIndexData {
+                occurrences: &occurrences(counts),
+                ..FileIndexData::new(path, Some("rust"), "", 0, 0)
+            })
+            .unwrap();
+        store.index_generation().unwrap()
+    };
+
+    let first = write("src/a.rs", &[("load", 2), ("save", 1)]);
+    let second = write("src/b.rs", &[("load", 1)]);
+    let third = write("src/a.rs", &[("load", 3)]);
+    store.delete_file("src/b.rs").unwrap();
+    let deleted = store.index_generation().unwrap();
+
+    let load = |generation| store.get_occurrences_as_of("load", generation).unwrap();
+    assert_eq!(load(first), [("src/a.rs".to_string(), 2)]);
+    assert_eq!(
+        load(second),
+        [("src/a.rs".to_string(), 2), ("src/b.rs".to_string(), 1)]
+    );
+    assert_eq!(
+        load(third),
+        [("src/a.rs".to_string(), 3), ("src/b.rs".to_string(), 1)]
+    );
+    assert_eq!(load(deleted), [("src/a.rs".to_string(), 3)]);
+    assert_eq!(store.get_occurrences_as_of("save", first).unwrap().len(), 1);
+    assert!(
+        store
+            .get_occurrences_as_of("save", third)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_symbol_history_retention() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store.set_symbol_history_retention(Some(2)).unwrap();
+    let write = |signature: &str| {
+        let symbol = Symbol {
+            name: "load".to_string(),
+            kind: SymbolKind::Function,
+            start_line: 1,
+            end_line: 1,
+            start_byte: 0,
+            end_byte: 0,
+            start_column: 0,
+            end_column: 0,
+            signature: Some(signature.to_string()),
+            doc_comment: None,
+            parent: None,
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
+        };
+        store
+            .write_file_index(&FileIndexData {
+                symbols: &[symbol],
+                ..FileIndexData::new("src/lib.rs", Some("rust"), "", 0, 0)
+            })
+            .unwrap();
+        store.index_generation().unwrap()
+    };
+    let versions = |generation| store.get_symbol_versions("load", generation).unwrap();
+
+    let first = write("fn load()");
+    write("fn load(path: &str)");
+    let third = write("fn load(path: &Path)");
+    let fourth = write("fn load(path: &Path) -> Config");
+
+    // Only the versions valid at the last two generations are kept
+    assert_eq!(store.oldest_history_generation().unwrap(), third);
+    assert!(versions(first).is_empty());
+    assert_eq!(
+        versions(third)[0].signature.as_deref(),
+        Some("fn load(path: &Path)")
+    );
+    assert_eq!(versions(fourth).len(), 1);
+
+    store.set_symbol_history_retention(None).unwrap();
+    assert_eq!(store.oldest_history_generation().unwrap(), 0);
+}
+
+#[test]
+fn test_find_method_declarations() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("store/file.go", Some("go"), "", 0, 0)
+        .unwrap();
+    store
+        .insert_type_relations(
+            file_id,
+            &[
+                relation("Reader", RelationKind::RequiresMethod, "Read", 2),
+                relation("File", RelationKind::HasMethod, "Read", 5),
+                relation("File", RelationKind::HasMethod, "Close", 6),
+                relation("Cache", RelationKind::HasMethod, "Get", 7),
+            ],
+        )
+        .unwrap();
+
+    let required = store
+        .find_relations_from("Reader", RelationKind::RequiresMethod)
+        .unwrap();
+    assert_eq!(required.len(), 1);
+    assert_eq!(required[0].target, "Read");
+
+    let methods = store
+        .find_method_declarations(&["Read".to_string(), "Close".to_string()])
+        .unwrap();
+    let found: Vec<(&str, &str)> = methods
+        .iter()
+        .map(|m| (m.type_name.as_str(), m.target.as_str()))
+        .collect();
+    assert_eq!(found, vec![("File", "Read"), ("File", "Close")]);
+    assert!(store.find_method_declarations(&[]).unwrap().is_empty());
+
+    store.clear_all_data().unwrap();
+    assert!(
+        store
+            .find_relations_from("Reader", RelationKind::RequiresMethod)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+// ==================== Chunk compression tests ====================
+
+fn handler_chunks(count: usize) -> Vec<CodeChunk> {
+    (0..count)
+        .map(|i| CodeChunk {
+            content: format!(
+                "pub fn handler_{i}(request: &Request) -> Result<Response> {{\n    let user = request.user()?;\n    info!(\"handling request {i}\");\n    Ok(Response::new(user.id + {i}))\n}}\n"
+            ),
+            start_line: i * 5 + 1,
+            end_line: i * 5 + 5,
+            start_byte: i * 150,
+            end_byte: i * 150 + 150,
+            symbols: vec![format!("handler_{i}")],
+        })
+        .collect()
+}
+
+#[test]
+fn test_compact_chunks_round_trip() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/handlers.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    let chunks = handler_chunks(200);
+    store.insert_chunks(file_id, &chunks).unwrap();
+
+    let report = store.compact_chunks().unwrap();
+    assert_eq!(report.chunks, 200);
+    assert_eq!(report.compressed, 200);
+    assert!(report.dictionary_bytes > 0);
+    assert!(report.stored_bytes_after < report.stored_bytes_before / 2);
+    assert!(store.chunk_codec().dictionary().is_some());
+
+    // Reads decompress transparently
+    let stored = store.get_chunks_by_file(file_id).unwrap();
+    let contents: Vec<&str> = stored.iter().map(|c| c.content.as_str()).collect();
+    let expected: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+    assert_eq!(contents, expected);
+
+    let ids: Vec<i64> = stored.iter().map(|c| c.id).collect();
+    let by_ids = store.get_chunks_by_ids(&ids[..3]).unwrap();
+    assert_eq!(by_ids[0].content, chunks[0].content);
+
+    // Compacting again decodes with the previous dictionary
+    let again = store.compact_chunks().unwrap();
+    assert_eq!(again.content_bytes, report.content_bytes);
+    assert_eq!(
+        store.get_chunks_by_file(file_id).unwrap()[199].content,
+        chunks[199].content
+    );
+}
+
+#[test]
+fn test_compress_new_chunks_when_enabled() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/handlers.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+
+    let config = SemantiqConfig::parse("[storage]\ncompress_chunks = true\n").unwrap();
+    store.apply_config(&config).unwrap();
+    assert!(store.chunk_codec().compresses());
+
+    // Without a trained dictionary, chunks are compressed on their own
+    let long_chunk = CodeChunk {
+        content: "let value = compute();\n".repeat(40),
+        ..handler_chunks(1).remove(0)
+    };
+    store
+        .insert_chunks(file_id, std::slice::from_ref(&long_chunk))
+        .unwrap();
+    let stored_type: String = store
+        .with_conn(|conn| {
+            Ok(conn.query_row("SELECT typeof(content) FROM chunks", [], |row| row.get(0))?)
+        })
+        .unwrap();
+    assert_eq!(stored_type, "blob");
+    assert_eq!(
+        store.get_chunks_by_file(file_id).unwrap()[0].content,
+        long_chunk.content
+    );
+}
+
+#[test]
+fn test_compaction_by_another_store_reloads_dictionary() {
+    let dir = std::env::temp_dir().join(format!("semantiq-compact-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("index.db");
+
+    let server = IndexStore::open(&db_path).unwrap();
+    server.set_chunk_compression(true);
+    let file_id = server
+        .insert_file("src/handlers.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    server.insert_chunks(file_id, &handler_chunks(100)).unwrap();
+
+    // A separate process compacts the same database
+    IndexStore::open(&db_path)
+        .unwrap()
+        .compact_chunks()
+        .unwrap();
+
+    // The first store picks up the new dictionary for reads and writes
+    let chunks = handler_chunks(120);
+    assert_eq!(
+        server.get_chunks_by_file(file_id).unwrap()[50].content,
+        chunks[50].content
+    );
+    let other_id = server
+        .insert_file("src/more.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    server.insert_chunks(other_id, &chunks[100..]).unwrap();
+    drop(server);
+
+    let reopened = IndexStore::open(&db_path).unwrap();
+    assert_eq!(
+        reopened.get_chunks_by_file(other_id).unwrap()[0].content,
+        chunks[100].content
+    );
+
+    drop(reopened);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn function_symbol(name: &str, line: usize) -> Symbol {
+    Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: line,
+        end_line: line,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: Some(format!("pub fn {}()", name)),
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    }
+}
+
+fn occurrences(counts: &[(&str, usize)]) -> Vec<Occurrence> {
+    counts
+        .iter()
+        .map(|&(name, count)| Occurrence {
+            name: name.to_string(),
+            count,
+            lines: Vec::new(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_get_occurrences_by_file() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_occurrences = occurrences(&[("parse", 2), ("main", 1)]);
+    let file_id = store
+        .write_file_index(&FileIndexData {
+            occurrences: &file_occurrences,
+            ..FileIndexData::new("src/main.rs", Some("rust"), "main", 4, 1000)
+        })
+        .unwrap();
+
+    let stored = store.get_occurrences_by_file(file_id).unwrap();
+    assert_eq!(stored, occurrences(&[("main", 1), ("parse", 2)]));
+    assert!(
+        store
+            .get_occurrences_by_file(file_id + 1)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_symbol_usage_and_unreferenced_symbols() {
+    let store = IndexStore::open_in_memory().unwrap();
+
+    // lib.rs defines parse, used by main.rs, and helper, only called in lib.rs
+    let lib_symbols = vec![
+        function_symbol("parse", 1),
+        function_symbol("helper", 5),
+        function_symbol("orphan", 9),
+    ];
+    let lib_occurrences = occurrences(&[("helper", 3), ("orphan", 1), ("parse", 1)]);
+    store
+        .write_file_index(&FileIndexData {
+            symbols: &lib_symbols,
+            occurrences: &lib_occurrences,
+            ..FileIndexData::new("src/lib.rs", Some("rust"), "lib", 3, 1000)
+        })
+        .unwrap();
+
+    let main_symbols = vec![function_symbol("main", 1)];
+    let main_occurrences = occurrences(&[("main", 1), ("parse", 2)]);
+    store
+        .write_file_index(&FileIndexData {
+            symbols: &main_symbols,
+            occurrences: &main_occurrences,
+            ..FileIndexData::new("src/main.rs", Some("rust"), "main", 4, 1000)
+        })
+        .unwrap();
+
+    let external_symbols = vec![function_symbol("unused_dep", 1)];
+    store
+        .write_file_index(&FileIndexData {
+            symbols: &external_symbols,
+            ..FileIndexData::new(
+                "external://cargo/dep@1.0/src/lib.rs",
+                Some("rust"),
+                "dep",
+                3,
+                1000,
+            )
+        })
+        .unwrap();
+
+    assert_eq!(
+        store.symbol_usage("parse").unwrap(),
+        SymbolUsage {
+            references: 2,
+            files: 1
+        }
+    );
+    assert_eq!(
+        store.symbol_usage("helper").unwrap(),
+        SymbolUsage {
+            references: 2,
+            files: 1
+        }
+    );
+    assert_eq!(
+        store.symbol_usage("orphan").unwrap(),
+        SymbolUsage::default()
+    );
+
+    let unreferenced: Vec<(String, usize)> = store
+        .find_unreferenced_symbols(&["function"])
+        .unwrap()
+        .into_iter()
+        .map(|u| (u.symbol.name, u.local_references))
+        .collect();
+    assert_eq!(
+        unreferenced,
+        vec![
+            ("helper".to_string(), 2),
+            ("orphan".to_string(), 0),
+            ("main".to_string(), 0),
+        ]
+    );
+    assert!(
+        store
+            .find_unreferenced_symbols(&["class"])
+            .unwrap()
+            .is_empty()
+    );
+
+    let lib_id = store.get_file_by_path("src/lib.rs").unwrap().unwrap().id;
+    assert_eq!(
+        store.find_shared_symbols(lib_id, 3).unwrap(),
+        vec![SharedSymbol {
+            file_path: "src/main.rs".to_string(),
+            name: "parse".to_string(),
+            defined_here: true,
+        }]
+    );
+    assert!(store.find_shared_symbols(lib_id, 0).unwrap().is_empty());
+
+    // Occurrences go away with their file
+    store.delete_file("src/main.rs").unwrap();
+    assert_eq!(store.symbol_usage("parse").unwrap(), SymbolUsage::default());
+}
+
+fn keywords(counts: &[(&str, usize)]) -> Vec<Keyword> {
+    counts
+        .iter()
+        .map(|&(term, count)| Keyword {
+            term: term.to_string(),
+            count,
+        })
+        .collect()
+}
+
+#[test]
+fn test_file_tags_are_ranked_by_tf_idf() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let files = [
+        (
+            "src/auth.rs",
+            keywords(&[("token", 6), ("session", 2), ("config", 2)]),
+        ),
+        ("src/db.rs", keywords(&[("query", 5), ("config", 3)])),
+        (
+            "src/http.rs",
+            keywords(&[("request", 4), ("session", 1), ("config", 1)]),
+        ),
+    ];
+    for (path, file_keywords) in &files {
+        store
+            .write_file_index(&FileIndexData {
+                keywords: file_keywords,
+                ..FileIndexData::new(path, Some("rust"), path, 3, 1000)
+            })
+            .unwrap();
+    }
+
+    let tags = store
+        .get_file_tags(&["src/auth.rs", "src/db.rs", "src/missing.rs"], 5)
+        .unwrap();
+    let auth: Vec<&str> = tags["src/auth.rs"].iter().map(|t| t.tag.as_str()).collect();
+    // config is in every file, so it is never a tag
+    assert_eq!(auth, vec!["token", "session"]);
+    assert_eq!(tags["src/db.rs"].len(), 1);
+    assert!(!tags.contains_key("src/missing.rs"));
+
+    let paths = ["src/auth.rs", "src/db.rs", "src/http.rs"];
+    let with_session = store
+        .files_with_tags(&paths, &["session".to_string()])
+        .unwrap();
+    assert_eq!(with_session.len(), 2);
+    assert!(
+        store
+            .files_with_tags(&paths, &["session".to_string(), "token".to_string()])
+            .unwrap()
+            .contains("src/auth.rs")
+    );
+    assert_eq!(store.files_with_tags(&paths, &[]).unwrap().len(), 3);
+
+    // Tags go away with their file
+    store.delete_file("src/auth.rs").unwrap();
+    assert!(
+        store
+            .files_with_tags(&paths, &["token".to_string()])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// Write a file with one embedded chunk per seed and a summary embedding
+fn write_summarized_file(store: &IndexStore, path: &str, summary_seed: f32, seeds: &[f32]) {
+    let chunks: Vec<CodeChunk> = seeds
+        .iter()
+        .enumerate()
+        .map(|(i, _)| CodeChunk {
+            content: format!("fn f{}() {{}}", i),
+            start_line: i + 1,
+            end_line: i + 1,
+            start_byte: 0,
+            end_byte: 10,
+            symbols: vec![format!("f{}", i)],
+        })
+        .collect();
+    let embeddings: Vec<Option<ChunkEmbedding>> = seeds
+        .iter()
+        .map(|s| Some(embedded(normalized_embedding(*s))))
+        .collect();
+    let summary = normalized_embedding(summary_seed);
+    store
+        .write_file_index(&FileIndexData {
+            chunks: &chunks,
+            embeddings: &embeddings,
+            summary_embedding: Some(&summary),
+            ..FileIndexData::new(path, Some("rust"), path, 3, 1000)
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_file_summaries_narrow_chunk_search() {
+    let store = IndexStore::open_in_memory().unwrap();
+    write_summarized_file(&store, "src/auth.rs", 0.5, &[0.5, 0.6]);
+    write_summarized_file(&store, "src/db.rs", 2.5, &[2.5, 2.6]);
+    write_summarized_file(&store, "src/http.rs", 4.5, &[4.5]);
+    assert_eq!(store.get_file_summary_coverage().unwrap(), (3, 3));
+    assert!(store.get_files_without_summary(10).unwrap().is_empty());
+
+    let query = normalized_embedding(2.55);
+    let files = store.search_similar_files(&query, 2).unwrap();
+    let db_id = store.get_file_by_path("src/db.rs").unwrap().unwrap().id;
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].0, db_id);
+
+    // Only the chunks of the given files are ranked
+    let chunks = store
+        .search_similar_chunks_in_files(&query, &[db_id], 10)
+        .unwrap();
+    assert_eq!(chunks.len(), 2);
+    let records = store.get_chunks_by_ids(&[chunks[0].0]).unwrap();
+    assert_eq!(records[0].file_id, db_id);
+    let everywhere = store.search_similar_chunks(&query, 1).unwrap();
+    assert_eq!(everywhere[0], chunks[0]);
+
+    // Deleting a file drops its summary
+    store.delete_file("src/db.rs").unwrap();
+    assert_eq!(store.get_file_summary_coverage().unwrap(), (2, 2));
+    assert!(
+        store
+            .search_similar_files(&query, 3)
+            .unwrap()
+            .iter()
+            .all(|(id, _)| *id != db_id)
+    );
+}
+
+#[test]
+fn test_files_without_summary_and_format_conversion() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let (symbols, chunks, imports) = journal_test_data();
+    store
+        .write_file_index(&FileIndexData {
+            symbols: &symbols,
+            chunks: &chunks,
+            imports: &imports,
+            ..FileIndexData::new("src/main.rs", Some("rust"), "fn main() {}", 12, 1000)
+        })
+        .unwrap();
+    assert_eq!(store.get_file_summary_coverage().unwrap(), (0, 1));
+
+    // The summary is rebuilt from the stored symbols and chunks
+    let missing = store.get_files_without_summary(10).unwrap();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].1, "src/main.rs\nmain\n\nfn main() {}");
+
+    store
+        .update_file_summary_embedding(missing[0].0, &normalized_embedding(1.5))
+        .unwrap();
+    assert_eq!(store.get_file_summary_coverage().unwrap(), (1, 1));
+
+    // Summaries follow the embedding format
+    store.set_embedding_format(EmbeddingFormat::Int8).unwrap();
+    let files = store
+        .search_similar_files(&normalized_embedding(1.5), 1)
+        .unwrap();
+    assert_eq!(files[0].0, missing[0].0);
+    assert!(files[0].1 < 0.05);
+}
+
+// ==================== File content tests ====================
+
+#[test]
+fn test_file_contents_are_stored_when_enabled() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let (symbols, chunks, imports) = journal_test_data();
+    let write = |store: &IndexStore, content: &str| {
+        store
+            .write_file_index(&FileIndexData {
+                symbols: &symbols,
+                chunks: &chunks,
+                imports: &imports,
+                ..FileIndexData::new("src/main.rs", Some("rust"), content, 12, 1000)
+            })
+            .unwrap()
+    };
+
+    // Disabled by default
+    write(&store, "fn main() {}");
+    assert!(!store.has_file_contents().unwrap());
+    assert_eq!(store.get_file_content("src/main.rs").unwrap(), None);
+
+    let config = SemantiqConfig::parse("[storage]\nfile_contents = true\n").unwrap();
+    store.apply_config(&config).unwrap();
+    write(&store, "fn main() {}\n");
+    assert!(store.has_file_content("src/main.rs").unwrap());
+    assert_eq!(
+        store.get_file_content("src/main.rs").unwrap().as_deref(),
+        Some("fn main() {}\n")
+    );
+    assert_eq!(store.get_file_content_paths().unwrap(), vec!["src/main.rs"]);
+
+    // A backfill leaves stored content alone
+    assert!(!store.backfill_file_content("src/main.rs", "old").unwrap());
+
+    // Deleting the file drops its content
+    store.delete_file("src/main.rs").unwrap();
+    assert!(!store.has_file_contents().unwrap());
+
+    // Rewriting a file with storage disabled drops its previous content
+    write(&store, "fn main() {}\n");
+    store.set_file_contents(false);
+    write(&store, "fn main() { run() }\n");
+    assert_eq!(store.get_file_content("src/main.rs").unwrap(), None);
+    assert!(
+        store
+            .backfill_file_content("src/main.rs", "fn main() { run() }\n")
+            .unwrap()
+    );
+    assert!(store.has_file_content("src/main.rs").unwrap());
+}
+
+#[test]
+fn test_dir_summaries_aggregate_subdirectories() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let chunk = |end_line| CodeChunk {
+        content: "code".to_string(),
+        start_line: 1,
+        end_line,
+        start_byte: 0,
+        end_byte: 4,
+        symbols: Vec::new(),
+    };
+
+    // src/lib.rs defines parse, used twice by src/main.rs; format is unused
+    let files = [
+        ("src/lib.rs", "rust", 40, vec!["parse", "format"], vec![]),
+        ("src/main.rs", "rust", 10, vec!["main"], vec![("parse", 2)]),
+        ("src/http/api.rs", "rust", 25, vec!["router"], vec![]),
+        ("web/app.ts", "typescript", 30, vec![], vec![]),
+    ];
+    for (path, language, lines, names, used) in files {
+        let symbols: Vec<Symbol> = names.iter().map(|n| function_symbol(n, 1)).collect();
+        let mut counts: Vec<(&str, usize)> = names.iter().map(|&n| (n, 1)).collect();
+        counts.extend(used);
+        let occurrences = occurrences(&counts);
+        let chunks = [chunk(lines)];
+        store
+            .write_file_index(&FileIndexData {
+                symbols: &symbols,
+                chunks: &chunks,
+                embeddings: &[None],
+                occurrences: &occurrences,
+                ..FileIndexData::new(path, Some(language), "code", 4, 1000)
+            })
+            .unwrap();
+    }
+
+    assert!(!store.dir_summaries_current().unwrap());
+    assert_eq!(store.refresh_dir_summaries().unwrap(), 4);
+    assert!(store.dir_summaries_current().unwrap());
+
+    let root = store.get_dir_summary(".").unwrap().unwrap();
+    assert_eq!(root.file_count, 4);
+    assert_eq!(root.line_count, 105);
+    assert_eq!(
+        root.languages,
+        vec![
+            LanguageShare {
+                language: "rust".to_string(),
+                file_count: 3
+            },
+            LanguageShare {
+                language: "typescript".to_string(),
+                file_count: 1
+            },
+        ]
+    );
+
+    let src = store.get_dir_summary("src").unwrap().unwrap();
+    assert_eq!(src.file_count, 3);
+    assert_eq!(src.line_count, 75);
+    assert_eq!(src.top_symbols[0].name, "parse");
+    assert_eq!(src.top_symbols[0].file_path, "src/lib.rs");
+    assert_eq!(src.top_symbols[0].references, 2);
+    assert_eq!(src.entry_files, vec!["src/lib.rs", "src/main.rs"]);
+
+    let subdirectories: Vec<String> = store
+        .get_subdirectory_summaries(".")
+        .unwrap()
+        .into_iter()
+        .map(|s| s.path)
+        .collect();
+    assert_eq!(subdirectories, vec!["src", "web"]);
+    assert!(store.get_dir_summary("docs").unwrap().is_none());
+
+    // Any write to the index makes the summaries stale
+    store.delete_file("web/app.ts").unwrap();
+    assert!(!store.dir_summaries_current().unwrap());
+}
+
+#[test]
+fn test_files_are_tagged_with_their_package() {
+    use crate::workspace::{PackageEcosystem, WorkspacePackage};
+
+    let store = IndexStore::open_in_memory().unwrap();
+    store
+        .insert_file(
+            "crates/core/src/lib.rs",
+            Some("rust"),
+            "pub fn a() {}",
+            13,
+            0,
+        )
+        .unwrap();
+
+    let packages = vec![
+        WorkspacePackage {
+            root: "crates/web".to_string(),
+            ecosystem: PackageEcosystem::Cargo,
+            name: "acme-web".to_string(),
+        },
+        WorkspacePackage {
+            root: "crates/core".to_string(),
+            ecosystem: PackageEcosystem::Cargo,
+            name: "acme-core".to_string(),
+        },
+    ];
+    let generation = store.index_generation().unwrap();
+    assert!(store.set_packages(&packages).unwrap());
+    assert!(store.index_generation().unwrap() > generation);
+
+    // Files indexed before and after the packages are recorded are tagged
+    store
+        .insert_file(
+            "crates/web/src/main.rs",
+            Some("rust"),
+            "fn main() {}",
+            12,
+            0,
+        )
+        .unwrap();
+    store
+        .insert_file("scripts/release.py", Some("python"), "print()", 7, 0)
+        .unwrap();
+    let tagged = store
+        .get_file_packages(&[
+            "crates/core/src/lib.rs",
+            "crates/web/src/main.rs",
+            "scripts/release.py",
+        ])
+        .unwrap();
+    assert_eq!(tagged.len(), 2);
+    assert_eq!(tagged["crates/core/src/lib.rs"], "acme-core");
+    assert_eq!(tagged["crates/web/src/main.rs"], "acme-web");
+    assert_eq!(
+        store
+            .get_file_by_path("crates/web/src/main.rs")
+            .unwrap()
+            .unwrap()
+            .package
+            .as_deref(),
+        Some("acme-web")
+    );
+
+    // Recording the same packages again is a no-op
+    let generation = store.index_generation().unwrap();
+    assert!(!store.set_packages(&packages).unwrap());
+    assert_eq!(store.index_generation().unwrap(), generation);
+    assert_eq!(store.get_packages().unwrap()[0].name, "acme-core");
+
+    // Removing a package untags its files
+    assert!(store.set_packages(&packages[..1]).unwrap());
+    assert!(
+        store
+            .get_file_packages(&["crates/core/src/lib.rs"])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_symbol_qualifiers_roundtrip() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+
+    let qualifiers = SymbolQualifiers {
+        is_public: true,
+        is_async: true,
+        generic_params: 2,
+        ..SymbolQualifiers::default()
+    };
+    let symbols = vec![
+        Symbol {
+            qualifiers,
+            ..function_symbol("fetch", 1)
+        },
+        function_symbol("helper", 2),
+    ];
+    store.insert_symbols(file_id, &symbols).unwrap();
+
+    let fetch = &store.find_symbol_by_name("fetch").unwrap()[0];
+    assert_eq!(fetch.qualifiers, qualifiers);
+    assert_eq!(fetch.qualifiers.names(), ["public", "async", "generic"]);
+
+    let by_file = store.get_symbols_by_file(file_id).unwrap();
+    assert_eq!(by_file[0].qualifiers, qualifiers);
+    assert_eq!(by_file[1].qualifiers, SymbolQualifiers::default());
+    let by_id = store.get_symbols_by_ids(&[fetch.id]).unwrap();
+    assert_eq!(by_id[0].qualifiers, qualifiers);
+}
+
+#[test]
+fn test_saved_queries() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let query = SavedQuery::new("find-handlers", " http handler {verb} ")
+        .with_filter("file_type", "rs")
+        .with_description("HTTP handlers of a verb");
+
+    assert!(!store.save_query(&query).unwrap());
+    let saved = store.get_saved_query("find-handlers").unwrap().unwrap();
+    assert_eq!(saved.query, "http handler {verb}");
+    assert_eq!(
+        saved.filters.get("file_type").map(String::as_str),
+        Some("rs")
+    );
+    assert_eq!(
+        saved.description.as_deref(),
+        Some("HTTP handlers of a verb")
+    );
+    assert!(saved.saved_at > 0);
+
+    // Saving again replaces the query, and it survives a full reindex
+    assert!(
+        store
+            .save_query(&SavedQuery::new("find-handlers", "handler {verb}"))
+            .unwrap()
+    );
+    store.save_query(&SavedQuery::new("auth", "login")).unwrap();
+    store.clear_all_data().unwrap();
+    let names: Vec<String> = store
+        .list_saved_queries()
+        .unwrap()
+        .into_iter()
+        .map(|q| q.name)
+        .collect();
+    assert_eq!(names, ["auth", "find-handlers"]);
+
+    assert!(store.delete_saved_query("auth").unwrap());
+    assert!(!store.delete_saved_query("auth").unwrap());
+    assert!(store.get_saved_query("auth").unwrap().is_none());
+
+    assert!(store.save_query(&SavedQuery::new("a b", "x")).is_err());
+    assert!(store.save_query(&SavedQuery::new("empty", "  ")).is_err());
+}
+
+#[test]
+fn test_saved_query_parameters() {
+    let query = SavedQuery::new("handlers", "{verb} handler in {module} {}")
+        .with_filter("package", "{module}")
+        .with_filter("symbol_kind", "function");
+    assert_eq!(query.parameters(), ["verb", "module"]);
+
+    let values: std::collections::HashMap<String, String> = [("verb", "post"), ("module", "api")]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let bound = query.bind(&values).unwrap();
+    assert_eq!(bound.query, "post handler in api {}");
+    assert_eq!(bound.filters["package"], "api");
+    assert_eq!(bound.filters["symbol_kind"], "function");
+
+    let mut missing = values.clone();
+    missing.remove("module");
+    assert_eq!(
+        query.bind(&missing).unwrap_err().to_string(),
+        "Missing value for parameter 'module' of saved query 'handlers'"
+    );
+
+    let mut unknown = values;
+    unknown.insert("lang".to_string(), "rust".to_string());
+    assert_eq!(
+        query.bind(&unknown).unwrap_err().to_string(),
+        "Unknown parameter 'lang' of saved query 'handlers' (parameters: verb, module)"
+    );
+}
+
+#[test]
+fn test_tool_usage() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let search = |category, result_count, duration_ms| ToolUsageEvent {
+        tool: "semantiq_search",
+        category: Some(category),
+        result_count,
+        duration_ms,
+    };
+    store
+        .record_tool_usage(&search("identifier", Some(3), 10))
+        .unwrap();
+    store
+        .record_tool_usage(&search("identifier", Some(0), 20))
+        .unwrap();
+    store
+        .record_tool_usage(&search("natural_language", None, 30))
+        .unwrap();
+    store
+        .record_tool_usage(&ToolUsageEvent {
+            tool: "semantiq_deps",
+            category: None,
+            result_count: Some(2),
+            duration_ms: 5,
+        })
+        .unwrap();
+
+    let tools = store.get_tool_usage(30).unwrap();
+    assert_eq!(tools.len(), 2);
+    assert_eq!(
+        tools[0],
+        ToolUsage {
+            tool: "semantiq_search".to_string(),
+            category: None,
+            calls: 3,
+            errors: 1,
+            zero_results: 1,
+            total_ms: 60,
+        }
+    );
+    assert_eq!(tools[0].zero_result_rate(), 0.5);
+    assert_eq!(tools[0].average_ms(), 20.0);
+    assert_eq!(tools[1].tool, "semantiq_deps");
+
+    let categories = store.get_query_category_usage(30).unwrap();
+    let categories: Vec<(&str, usize)> = categories
+        .iter()
+        .map(|u| (u.category.as_deref().unwrap(), u.calls))
+        .collect();
+    assert_eq!(categories, vec![("identifier", 2), ("natural_language", 1)]);
+
+    // Nothing is recorded once disabled
+    store.set_usage_stats(false);
+    store
+        .record_tool_usage(&search("identifier", Some(1), 1))
+        .unwrap();
+    assert_eq!(store.get_tool_usage(30).unwrap()[0].calls, 3);
+}
+
+#[test]
+fn test_open_base_index() {
+    use semantiq_embeddings::EmbeddingModelKind;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("base.db");
+    {
+        let built = IndexStore::open(&path).unwrap();
+        built
+            .insert_file("src/lib.rs", Some("rust"), "fn main() {}", 12, 1000)
+            .unwrap();
+        built.set_parser_version().unwrap();
+    }
+    let local = IndexStore::open_in_memory().unwrap();
+
+    let base = IndexStore::open_base(&path, &local).unwrap();
+    assert!(base.get_file_by_path("src/lib.rs").unwrap().is_some());
+    // Read-only
+    assert!(base.insert_file("src/new.rs", None, "", 0, 0).is_err());
+    drop(base);
+
+    // Indexes embedded with another model are refused
+    local
+        .set_embedding_model(EmbeddingModelKind::MultilingualMiniLm)
+        .unwrap();
+    let err = IndexStore::open_base(&path, &local).err().unwrap();
+    assert!(err.to_string().contains("embedded with"));
+
+    // So are indexes built by another version
+    let local = IndexStore::open_in_memory().unwrap();
+    IndexStore::open(&path)
+        .unwrap()
+        .with_conn(|conn| {
+            conn.execute(
+                "UPDATE metadata SET value = '999' WHERE key = 'parser_version'",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+    let err = IndexStore::open_base(&path, &local).err().unwrap();
+    assert!(err.to_string().contains("parser version"));
+
+    assert!(IndexStore::open_base(&dir.path().join("missing.db"), &local).is_err());
+}
+
+#[test]
+fn test_symbol_columns_roundtrip() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    let symbols = vec![Symbol {
+        start_column: 4,
+        end_column: 25,
+        ..function_symbol("load_settings", 4)
+    }];
+    store.insert_symbols(file_id, &symbols).unwrap();
+
+    let found = &store.find_symbol_by_name("load_settings").unwrap()[0];
+    assert_eq!((found.start_column, found.end_column), (4, 25));
+    let matched = &store.search_symbols("load_settings", 10).unwrap()[0];
+    assert_eq!(
+        (matched.symbol.start_column, matched.symbol.end_column),
+        (4, 25)
+    );
+    let by_id = &store.get_symbols_by_ids(&[found.id]).unwrap()[0];
+    assert_eq!((by_id.start_column, by_id.end_column), (4, 25));
+}
+
+#[test]
+fn test_symbol_cfg_context_roundtrip() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let file_id = store
+        .insert_file("src/lib.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    let symbols = vec![
+        Symbol {
+            cfg_context: Some("test".to_string()),
+            ..function_symbol("load_fixture", 4)
+        },
+        function_symbol("load_settings", 10),
+    ];
+    store.insert_symbols(file_id, &symbols).unwrap();
+
+    let found = &store.find_symbol_by_name("load_fixture").unwrap()[0];
+    assert_eq!(found.cfg_context.as_deref(), Some("test"));
+    let matched = &store.search_symbols("load_fixture", 10).unwrap()[0];
+    assert_eq!(matched.symbol.cfg_context.as_deref(), Some("test"));
+    let by_file = store.get_symbols_by_file(file_id).unwrap();
+    assert_eq!(by_file[1].cfg_context, None);
+}
+
+#[test]
+fn test_generated_files() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store
+        .write_file_index(&FileIndexData {
+            generated: true,
+            ..FileIndexData::new("web/app.min.js", Some("javascript"), "var a=1;", 8, 0)
+        })
+        .unwrap();
+    store
+        .insert_file("web/app.js", Some("javascript"), "let a = 1;", 10, 0)
+        .unwrap();
+
+    let paths = ["web/app.min.js", "web/app.js", "web/missing.js"];
+    let generated = store.get_generated_files(&paths).unwrap();
+    assert_eq!(
+        generated,
+        std::collections::HashSet::from(["web/app.min.js".to_string()])
+    );
+
+    let generation = store.index_generation().unwrap();
+    assert!(store.set_file_generated("web/app.js", true).unwrap());
+    assert!(store.index_generation().unwrap() > generation);
+    // Unchanged marks leave the index untouched
+    assert!(!store.set_file_generated("web/app.js", true).unwrap());
+    assert!(store.set_file_generated("web/app.min.js", false).unwrap());
+    assert_eq!(
+        store.get_generated_files(&paths).unwrap(),
+        std::collections::HashSet::from(["web/app.js".to_string()])
+    );
+}
+
+/// Write a file with everything extracted from it, as the indexers do
+fn write_parsed_file(store: &IndexStore, path: &str, content: &str) {
+    use crate::extraction::ExtractedFile;
+    use semantiq_parser::{ChunkExtractor, Language, LanguageSupport};
+
+    let language = Language::from_path(std::path::Path::new(path)).unwrap();
+    let extracted = ExtractedFile::extract(
+        &mut LanguageSupport::new().unwrap(),
+        &ChunkExtractor::new(),
+        language,
+        std::path::Path::new(path),
+        path,
+        content,
+        false,
+    )
+    .unwrap();
+    store
+        .write_file_index(
+            &FileIndexData::new(
+                path,
+                Some(language.name()),
+                content,
+                content.len() as i64,
+                0,
+            )
+            .with_extracted(&extracted),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_symbol_dependencies_and_dependents() {
+    let store = IndexStore::open_in_memory().unwrap();
+    write_parsed_file(
+        &store,
+        "src/config.rs",
+        "pub struct Config {}\n\npub fn load_config() -> Config {\n    parse_config(\"\")\n}\n\nfn parse_config(text: &str) -> Config {\n    Config {}\n}\n",
+    );
+    write_parsed_file(
+        &store,
+        "src/server.rs",
+        "use crate::config::load_config;\n\npub fn handle_request() {\n    let config = load_config();\n    respond(config);\n}\n\nfn respond(config: u8) {}\n",
+    );
+    // Defines its own load_config, and does not import config.rs
+    write_parsed_file(
+        &store,
+        "src/legacy.rs",
+        "fn load_config() {}\n\nfn reload() {\n    load_config();\n}\n",
+    );
+
+    let names = |edges: Vec<SymbolEdge>| -> Vec<(String, String, usize)> {
+        edges
+            .into_iter()
+            .map(|e| (e.file_path, e.symbol.name, e.line))
+            .collect()
+    };
+
+    assert_eq!(
+        names(
+            store
+                .get_symbol_dependencies("src/server.rs", "handle_request")
+                .unwrap()
+        ),
+        [
+            ("src/config.rs".to_string(), "load_config".to_string(), 4),
+            ("src/server.rs".to_string(), "respond".to_string(), 5),
+        ]
+    );
+    assert_eq!(
+        names(
+            store
+                .get_symbol_dependencies("src/config.rs", "load_config")
+                .unwrap()
+        ),
+        [
+            ("src/config.rs".to_string(), "Config".to_string(), 3),
+            ("src/config.rs".to_string(), "parse_config".to_string(), 4),
+        ]
+    );
+
+    assert_eq!(
+        names(
+            store
+                .get_symbol_dependents("src/config.rs", "load_config")
+                .unwrap()
+        ),
+        [("src/server.rs".to_string(), "handle_request".to_string(), 4)]
+    );
+    assert_eq!(
+        names(
+            store
+                .get_symbol_dependents("src/legacy.rs", "load_config")
+                .unwrap()
+        ),
+        [("src/legacy.rs".to_string(), "reload".to_string(), 4)]
+    );
+    assert!(
+        store
+            .get_symbol_dependents("src/config.rs", "missing")
+            .unwrap()
+            .is_empty()
+    );
+
+    // Edges go with the symbols when a file is written again
+    write_parsed_file(&store, "src/server.rs", "pub fn handle_request() {}\n");
+    assert!(
+        store
+            .get_symbol_dependents("src/config.rs", "load_config")
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_outline_files() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store
+        .write_file_index(&FileIndexData {
+            outline: true,
+            ..FileIndexData::new("src/tables.rs", Some("rust"), "pub fn lookup() {}", 18, 0)
+        })
+        .unwrap();
+    store
+        .insert_file("src/main.rs", Some("rust"), "fn main() {}", 12, 0)
+        .unwrap();
+
+    let outlines = store
+        .get_outline_files(&["src/tables.rs", "src/main.rs", "src/missing.rs"])
+        .unwrap();
+    assert_eq!(
+        outlines,
+        std::collections::HashSet::from(["src/tables.rs".to_string()])
+    );
+}
+
+#[test]
+fn test_text_masks() {
+    let store = IndexStore::open_in_memory().unwrap();
+    let mask = TextMask {
+        comments: vec![0..20, 300..301, 70_000..70_200],
+        strings: vec![25..40, 45..60],
+    };
+    store
+        .write_file_index(&FileIndexData {
+            text_mask: Some(&mask),
+            ..FileIndexData::new("src/lib.rs", Some("rust"), "fn main() {}", 12, 0)
+        })
+        .unwrap();
+    store
+        .write_file_index(&FileIndexData::new("data.json", Some("json"), "{}", 2, 0))
+        .unwrap();
+
+    assert_eq!(store.get_text_mask("src/lib.rs").unwrap(), Some(mask));
+    assert_eq!(store.get_text_mask("data.json").unwrap(), None);
+
+    // Reindexing the file replaces its ranges
+    store
+        .write_file_index(&FileIndexData::new(
+            "src/lib.rs",
+            Some("rust"),
+            "fn main() {}",
+            12,
+            0,
+        ))
+        .unwrap();
+    assert_eq!(store.get_text_mask("src/lib.rs").unwrap(), None);
+}
+
+#[test]
+fn test_enforce_size_quota() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store.set_search_tracking(true);
+
+    let mut file_ids = Vec::new();
+    for path in ["src/a.rs", "legacy/b.rs"] {
+        let file_id = store
+            .insert_file(path, Some("rust"), "fn main() {}", 12, 1000)
+            .unwrap();
+        let chunks: Vec<CodeChunk> = (0..600)
+            .map(|i| CodeChunk {
+                content: format!("fn f{}() {{}}", i),
+                start_line: i + 1,
+                end_line: i + 1,
+                start_byte: 0,
+                end_byte: 12,
+                symbols: Vec::new(),
+            })
+            .collect();
+        store.insert_chunks(file_id, &chunks).unwrap();
+        for chunk in store.get_chunks_by_file(file_id).unwrap() {
+            store
+                .update_chunk_embedding(chunk.id, &vec![0.5; EMBEDDING_DIMENSION])
+                .unwrap();
+        }
+        file_ids.push(file_id);
+    }
+    store.record_searched_dirs(["src/a.rs"]).unwrap();
+
+    let quota = QuotaConfig {
+        max_db_mb: 5,
+        ..QuotaConfig::default()
+    };
+    let report = store.enforce_size_quota(&quota).unwrap();
+    assert_eq!(report.directories, vec!["legacy".to_string()]);
+    assert_eq!(report.chunks, 600);
+    assert!(report.size_after < report.size_before);
+
+    let embedded = store
+        .chunks_with_embeddings()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(embedded.len(), 600);
+    assert!(embedded.iter().all(|chunk| chunk.file_id == file_ids[0]));
+    assert!(store.get_chunks_without_embeddings(10).unwrap().is_empty());
+    assert!(store.embeddings_evicted("legacy/c.rs").unwrap());
+    assert!(!store.embeddings_evicted("src/c.rs").unwrap());
+
+    let stats = store.get_quota_stats().unwrap();
+    assert_eq!(stats.project_files, 2);
+    assert_eq!(stats.evicted.len(), 1);
+    assert_eq!(stats.evicted[0].directory, "legacy");
+    assert_eq!(stats.evicted[0].last_searched, None);
+
+    // Without a size quota the embeddings come back on the next index pass
+    store.enforce_size_quota(&QuotaConfig::default()).unwrap();
+    assert!(store.get_quota_stats().unwrap().evicted.is_empty());
+    assert!(!store.embeddings_evicted("legacy/c.rs").unwrap());
+    assert!(store.needs_reindex("legacy/b.rs", "fn main() {}").unwrap());
+    assert!(!store.needs_reindex("src/a.rs", "fn main() {}").unwrap());
+}
+
+#[test]
+fn test_index_issues_are_replaced_with_their_file() {
+    use crate::issues::{IndexIssue, IndexIssueKind};
+
+    let store = IndexStore::open_in_memory().unwrap();
+    let issues = [
+        IndexIssue::new("src/a.rs", IndexIssueKind::Encoding, "guessed windows-1252"),
+        IndexIssue::new("src/a.rs", IndexIssueKind::ParseFailure, "Failed to parse"),
+    ];
+    store
+        .write_file_index(&FileIndexData {
+            issues: &issues,
+            ..FileIndexData::new("src/a.rs", Some("rust"), "fn a(", 5, 0)
+        })
+        .unwrap();
+    store
+        .set_index_issues(
+            "assets/blob.rs",
+            &[IndexIssue::new(
+                "assets/blob.rs",
+                IndexIssueKind::ReadError,
+                "binary file",
+            )],
+        )
+        .unwrap();
+
+    let all = store.get_index_issues(None, 10).unwrap();
+    assert_eq!(
+        all.iter()
+            .map(|i| (i.path.as_str(), i.kind.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("assets/blob.rs", "read_error"),
+            ("src/a.rs", "encoding"),
+            ("src/a.rs", "parse_failure"),
+        ]
+    );
+    let parse_failures = store
+        .get_index_issues(Some(IndexIssueKind::ParseFailure), 10)
+        .unwrap();
+    assert_eq!(parse_failures.len(), 1);
+    assert_eq!(parse_failures[0].message, "Failed to parse");
+    assert_eq!(
+        store
+            .get_file_index_issues("src/a.rs")
+            .unwrap()
+            .iter()
+            .map(|i| i.kind.as_str())
+            .collect::<Vec<_>>(),
+        ["encoding", "parse_failure"]
+    );
+    assert_eq!(
+        store
+            .count_index_issues()
+            .unwrap()
+            .get(&IndexIssueKind::Encoding),
+        Some(&1)
+    );
+
+    // Indexing the file again replaces its issues
+    store
+        .write_file_index(&FileIndexData::new(
+            "src/a.rs",
+            Some("rust"),
+            "fn a() {}",
+            9,
+            0,
+        ))
+        .unwrap();
+    assert_eq!(store.get_index_issues(None, 10).unwrap().len(), 1);
+
+    // Files gone from the project lose theirs
+    assert_eq!(
+        store
+            .retain_index_issues(|path| path != "assets/blob.rs")
+            .unwrap(),
+        1
+    );
+    assert!(store.count_index_issues().unwrap().is_empty());
+
+    store
+        .set_index_issues(
+            "src/a.rs",
+            &[IndexIssue::too_large("src/a.rs", 20 * 1024 * 1024)],
+        )
+        .unwrap();
+    store.delete_file("src/a.rs").unwrap();
+    assert!(store.get_index_issues(None, 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_last_indexed_with_prefix() {
+    let store = IndexStore::open_in_memory().unwrap();
+    store
+        .insert_file("src/a.rs", Some("rust"), "fn a() {}", 9, 0)
+        .unwrap();
+
+    let indexed_at = store
+        .get_file_by_path("src/a.rs")
+        .unwrap()
+        .unwrap()
+        .indexed_at;
+    assert_eq!(
+        store.get_last_indexed_with_prefix("src/").unwrap(),
+        Some(indexed_at)
+    );
+    assert_eq!(store.get_last_indexed_with_prefix("docs/").unwrap(), None);
+}
+
+#[test]
+fn test_translation_units_are_replaced_as_a_whole() {
+    use crate::includes::TranslationUnit;
+
+    let unit = |path: &str, target: Option<&str>| TranslationUnit {
+        path: path.to_string(),
+        include_dirs: vec!["include".to_string(), "/usr/include/openssl".to_string()],
+        defines: vec!["DEBUG".to_string()],
+        target: target.map(String::from),
+    };
+    let store = IndexStore::open_in_memory().unwrap();
+    let units = [
+        unit("src/b.c", Some("app")),
+        unit("src/a.c", Some("app")),
+        unit("tools/gen.c", None),
+    ];
+
+    assert!(store.set_translation_units(&units).unwrap());
+    // The same units again are not written
+    assert!(!store.set_translation_units(&units).unwrap());
+    assert_eq!(
+        store
+            .get_translation_units()
+            .unwrap()
+            .iter()
+            .map(|u| u.path.as_str())
+            .collect::<Vec<_>>(),
+        ["src/a.c", "src/b.c", "tools/gen.c"]
+    );
+    assert_eq!(
+        store.get_translation_unit("src/a.c").unwrap(),
+        Some(unit("src/a.c", Some("app")))
+    );
+    assert_eq!(store.get_build_targets().unwrap(), [("app".to_string(), 2)]);
+    assert_eq!(
+        store.get_target_files("app").unwrap(),
+        ["src/a.c", "src/b.c"]
+    );
+
+    assert!(
+        store
+            .set_translation_units(&[unit("src/a.c", None)])
+            .unwrap()
+    );
+    assert!(store.get_translation_unit("src/b.c").unwrap().is_none());
+    assert!(store.get_build_targets().unwrap().is_empty());
+}
@@ -0,0 +1,107 @@
+//! Comment and string ranges for IndexStore.
+//!
+//! The byte ranges of the comments and string literals of each file (see
+//! `semantiq_parser::text_mask`) let text searches leave out matches in
+//! them. Ranges are stored as LEB128 varints, each as the gap from the end
+//! of the previous range followed by its length, a few bytes per comment.
+
+use super::IndexStore;
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, OptionalExtension, params};
+use semantiq_parser::TextMask;
+use std::ops::Range;
+
+impl IndexStore {
+    /// Internal implementation for use within a transaction.
+    pub(crate) fn insert_text_mask_impl(
+        conn: &Connection,
+        file_id: i64,
+        mask: Option<&TextMask>,
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM text_masks WHERE file_id = ?1")?
+            .execute([file_id])?;
+        let Some(mask) = mask.filter(|mask| !mask.is_empty()) else {
+            return Ok(());
+        };
+
+        conn.prepare_cached(
+            "INSERT INTO text_masks (file_id, comments, strings) VALUES (?1, ?2, ?3)",
+        )?
+        .execute(params![
+            file_id,
+            encode_ranges(&mask.comments),
+            encode_ranges(&mask.strings)
+        ])?;
+        Ok(())
+    }
+
+    /// Get the comment and string ranges of an indexed file, as of its
+    /// indexed content. Files without any have none.
+    pub fn get_text_mask(&self, path: &str) -> Result<Option<TextMask>> {
+        let encoded: Option<(Vec<u8>, Vec<u8>)> = self.with_conn(|conn| {
+            Ok(conn
+                .prepare_cached(
+                    "SELECT m.comments, m.strings FROM text_masks m
+                     JOIN files f ON f.id = m.file_id
+                     WHERE f.path = ?1",
+                )?
+                .query_row([path], |row| Ok((row.get(0)?, row.get(1)?)))
+                .optional()?)
+        })?;
+
+        encoded
+            .map(|(comments, strings)| {
+                Ok::<_, anyhow::Error>(TextMask {
+                    comments: decode_ranges(&comments)?,
+                    strings: decode_ranges(&strings)?,
+                })
+            })
+            .transpose()
+            .with_context(|| format!("Corrupt comment and string ranges of {}", path))
+    }
+}
+
+fn encode_ranges(ranges: &[Range<usize>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut end = 0;
+    for range in ranges {
+        write_varint(&mut bytes, range.start.saturating_sub(end));
+        write_varint(&mut bytes, range.len());
+        end = range.end;
+    }
+    bytes
+}
+
+fn decode_ranges(mut bytes: &[u8]) -> Result<Vec<Range<usize>>> {
+    let mut ranges = Vec::new();
+    let mut end = 0;
+    while !bytes.is_empty() {
+        let start = end + read_varint(&mut bytes)?;
+        end = start + read_varint(&mut bytes)?;
+        ranges.push(start..end);
+    }
+    Ok(ranges)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<usize> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let Some((&byte, rest)) = bytes.split_first() else {
+            bail!("Truncated varint");
+        };
+        *bytes = rest;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Varint too long")
+}
@@ -0,0 +1,135 @@
+//! Translation unit operations for IndexStore.
+//!
+//! The translation units of the compilation database (see
+//! [`crate::includes`]) are recorded as a whole each time it is read, so
+//! that include lookups and build targets do not need it again.
+
+use super::IndexStore;
+use crate::includes::TranslationUnit;
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use tracing::debug;
+
+impl IndexStore {
+    /// Record the translation units of the project in place of the
+    /// previous ones.
+    ///
+    /// Returns `false`, without writing, when they are already the
+    /// recorded ones.
+    pub fn set_translation_units(&self, units: &[TranslationUnit]) -> Result<bool> {
+        let mut units = units.to_vec();
+        units.sort();
+        units.dedup_by(|a, b| a.path == b.path);
+
+        self.with_conn(|conn| {
+            if Self::get_translation_units_impl(conn)? == units {
+                return Ok(false);
+            }
+
+            conn.execute("BEGIN IMMEDIATE", [])?;
+
+            let result = (|| -> Result<()> {
+                conn.execute("DELETE FROM translation_units", [])?;
+                let mut stmt = conn.prepare_cached(
+                    "INSERT INTO translation_units (path, target, include_dirs, defines)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for unit in &units {
+                    stmt.execute(params![
+                        unit.path,
+                        unit.target,
+                        serde_json::to_string(&unit.include_dirs)?,
+                        serde_json::to_string(&unit.defines)?,
+                    ])?;
+                }
+                Ok(())
+            })();
+
+            match result {
+                Ok(()) => {
+                    conn.execute("COMMIT", [])?;
+                    debug!("Recorded {} translation units", units.len());
+                    Ok(true)
+                }
+                Err(e) => {
+                    let _ = conn.execute("ROLLBACK", []);
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    /// Get the recorded translation units, sorted by path.
+    pub fn get_translation_units(&self) -> Result<Vec<TranslationUnit>> {
+        self.with_conn(Self::get_translation_units_impl)
+    }
+
+    fn get_translation_units_impl(conn: &Connection) -> Result<Vec<TranslationUnit>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT path, target, include_dirs, defines FROM translation_units ORDER BY path",
+        )?;
+        let units = stmt
+            .query_map([], unit_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(units)
+    }
+
+    /// Get the translation unit of the source file at `path`, if it is in
+    /// the compilation database.
+    pub fn get_translation_unit(&self, path: &str) -> Result<Option<TranslationUnit>> {
+        self.with_conn(|conn| {
+            let unit = conn
+                .prepare_cached(
+                    "SELECT path, target, include_dirs, defines FROM translation_units
+                     WHERE path = ?1",
+                )?
+                .query_row([path], unit_from_row)
+                .optional()?;
+            Ok(unit)
+        })
+    }
+
+    /// Get the build targets with their number of source files, by name.
+    pub fn get_build_targets(&self) -> Result<Vec<(String, usize)>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT target, COUNT(*) FROM translation_units
+                 WHERE target IS NOT NULL GROUP BY target ORDER BY target",
+            )?;
+            let targets = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(targets)
+        })
+    }
+
+    /// Get the source files compiled by the build target `target`, by path.
+    pub fn get_target_files(&self, target: &str) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT path FROM translation_units WHERE target = ?1 ORDER BY path",
+            )?;
+            let files = stmt
+                .query_map([target], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(files)
+        })
+    }
+}
+
+fn unit_from_row(row: &Row<'_>) -> rusqlite::Result<TranslationUnit> {
+    let json_list = |index: usize| -> rusqlite::Result<Vec<String>> {
+        let json: String = row.get(index)?;
+        serde_json::from_str(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into())
+        })
+    };
+    Ok(TranslationUnit {
+        path: row.get(0)?,
+        target: row.get(1)?,
+        include_dirs: json_list(2)?,
+        defines: json_list(3)?,
+    })
+}
@@ -0,0 +1,148 @@
+//! Tool usage statistics for IndexStore.
+//!
+//! Each tool call is counted per day, tool and query category, with its
+//! errors, calls that returned no results and total duration, so that
+//! `semantiq stats --usage` shows which tools and kinds of queries are used
+//! the most and which ones come back empty. The statistics stay in the index
+//! database and are never sent anywhere; `usage.enabled = false` in
+//! `semantiq.toml` turns recording off.
+
+use super::IndexStore;
+use crate::clock::unix_now;
+use anyhow::Result;
+use rusqlite::{Row, params};
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Outcome of one tool call, recorded by [`IndexStore::record_tool_usage`]
+#[derive(Debug, Clone, Copy)]
+pub struct ToolUsageEvent<'a> {
+    pub tool: &'a str,
+    /// Category of the query, e.g. its shape for `semantiq_search`
+    pub category: Option<&'a str>,
+    /// Number of results, `None` when the call failed
+    pub result_count: Option<usize>,
+    pub duration_ms: u64,
+}
+
+/// Calls of a tool, or of one query category of a tool, over a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ToolUsage {
+    pub tool: String,
+    pub category: Option<String>,
+    pub calls: usize,
+    pub errors: usize,
+    /// Successful calls that returned no results
+    pub zero_results: usize,
+    pub total_ms: u64,
+}
+
+impl ToolUsage {
+    /// Share of the successful calls that returned no results
+    pub fn zero_result_rate(&self) -> f64 {
+        let succeeded = self.calls.saturating_sub(self.errors);
+        if succeeded == 0 {
+            return 0.0;
+        }
+        self.zero_results as f64 / succeeded as f64
+    }
+
+    /// Average duration of a call in milliseconds
+    pub fn average_ms(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.total_ms as f64 / self.calls as f64
+    }
+}
+
+fn tool_usage_from_row(row: &Row<'_>) -> rusqlite::Result<ToolUsage> {
+    let category: String = row.get(1)?;
+    Ok(ToolUsage {
+        tool: row.get(0)?,
+        category: (!category.is_empty()).then_some(category),
+        calls: row.get::<_, i64>(2)? as usize,
+        errors: row.get::<_, i64>(3)? as usize,
+        zero_results: row.get::<_, i64>(4)? as usize,
+        total_ms: row.get::<_, i64>(5)? as u64,
+    })
+}
+
+impl IndexStore {
+    /// Record the usage statistics of the tools from now on.
+    pub fn set_usage_stats(&self, enabled: bool) {
+        self.usage_stats.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether the usage statistics of the tools are recorded
+    pub fn records_usage_stats(&self) -> bool {
+        self.usage_stats.load(Ordering::Relaxed)
+    }
+
+    /// Count a tool call in today's statistics. Does nothing when usage
+    /// statistics are disabled.
+    pub fn record_tool_usage(&self, event: &ToolUsageEvent<'_>) -> Result<()> {
+        if !self.records_usage_stats() {
+            return Ok(());
+        }
+        let day = unix_now() / SECS_PER_DAY;
+        let error = event.result_count.is_none() as i64;
+        let zero_results = (event.result_count == Some(0)) as i64;
+
+        self.with_conn(|conn| {
+            conn.prepare_cached(
+                "INSERT INTO tool_usage (day, tool, category, calls, errors, zero_results, total_ms)
+                 VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6)
+                 ON CONFLICT(day, tool, category) DO UPDATE SET
+                     calls = calls + 1,
+                     errors = errors + excluded.errors,
+                     zero_results = zero_results + excluded.zero_results,
+                     total_ms = total_ms + excluded.total_ms",
+            )?
+            .execute(params![
+                day,
+                event.tool,
+                event.category.unwrap_or_default(),
+                error,
+                zero_results,
+                event.duration_ms as i64
+            ])?;
+            Ok(())
+        })
+    }
+
+    /// Get the calls of each tool over the last `days` days (including
+    /// today), most called first. `category` is always `None`.
+    pub fn get_tool_usage(&self, days: u32) -> Result<Vec<ToolUsage>> {
+        self.tool_usage_since(
+            days,
+            "SELECT tool, '', SUM(calls), SUM(errors), SUM(zero_results), SUM(total_ms)
+             FROM tool_usage WHERE day >= ?1
+             GROUP BY tool ORDER BY SUM(calls) DESC, tool",
+        )
+    }
+
+    /// Get the calls of each tool and query category over the last `days`
+    /// days, most called first. Calls without a category are left out.
+    pub fn get_query_category_usage(&self, days: u32) -> Result<Vec<ToolUsage>> {
+        self.tool_usage_since(
+            days,
+            "SELECT tool, category, SUM(calls), SUM(errors), SUM(zero_results), SUM(total_ms)
+             FROM tool_usage WHERE day >= ?1 AND category != ''
+             GROUP BY tool, category ORDER BY SUM(calls) DESC, tool, category",
+        )
+    }
+
+    fn tool_usage_since(&self, days: u32, sql: &str) -> Result<Vec<ToolUsage>> {
+        let since = unix_now() / SECS_PER_DAY - i64::from(days.max(1)) + 1;
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let usage = stmt
+                .query_map([since], tool_usage_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(usage)
+        })
+    }
+}
@@ -0,0 +1,91 @@
+//! Summary text of a file, embedded for the coarse step of semantic search
+//!
+//! A file summary lists the file path, the names and first doc comment line
+//! of its symbols, and the chunks defining the most symbols. Its embedding
+//! is stored in `files_vec`, one vector per file.
+
+/// Symbols listed in a file summary
+const MAX_SUMMARY_SYMBOLS: usize = 50;
+
+/// Chunks included in a file summary
+const MAX_SUMMARY_CHUNKS: usize = 2;
+
+/// Characters of a file summary; embedding models truncate longer inputs
+pub const MAX_SUMMARY_CHARS: usize = 1500;
+
+/// Build the summary of a file from its symbols (name and doc comment) and
+/// its chunks (content and number of symbols). `None` for a file with
+/// neither, which gets no summary embedding.
+pub fn file_summary<'a>(
+    path: &str,
+    symbols: impl IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    chunks: impl IntoIterator<Item = (&'a str, usize)>,
+) -> Option<String> {
+    let mut summary = String::from(path);
+    let mut empty = true;
+
+    for (name, doc_comment) in symbols.into_iter().take(MAX_SUMMARY_SYMBOLS) {
+        empty = false;
+        summary.push('\n');
+        summary.push_str(name);
+        if let Some(doc) =
+            doc_comment.and_then(|d| d.lines().map(str::trim).find(|line| !line.is_empty()))
+        {
+            summary.push_str(": ");
+            summary.push_str(doc);
+        }
+    }
+
+    // Chunks defining the most symbols first, in file order on ties
+    let mut chunks: Vec<(&str, usize)> = chunks.into_iter().collect();
+    chunks.sort_by_key(|&(_, symbols)| std::cmp::Reverse(symbols));
+    for (content, _) in chunks.into_iter().take(MAX_SUMMARY_CHUNKS) {
+        empty = false;
+        summary.push_str("\n\n");
+        summary.push_str(content.trim());
+    }
+
+    if empty {
+        return None;
+    }
+    if let Some((end, _)) = summary.char_indices().nth(MAX_SUMMARY_CHARS) {
+        summary.truncate(end);
+    }
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_summary() {
+        let summary = file_summary(
+            "src/auth.rs",
+            [
+                ("login", Some("\n  Check the credentials\n  of a user")),
+                ("logout", None),
+            ],
+            [("fn logout() {}", 1), ("fn login() {}\nfn check() {}", 2)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            "src/auth.rs\nlogin: Check the credentials\nlogout\n\n\
+             fn login() {}\nfn check() {}\n\nfn logout() {}"
+        );
+    }
+
+    #[test]
+    fn test_file_summary_is_truncated() {
+        let content = "é".repeat(MAX_SUMMARY_CHARS * 2);
+        let summary = file_summary("a.rs", [], [(content.as_str(), 0)]).unwrap();
+        assert_eq!(summary.chars().count(), MAX_SUMMARY_CHARS);
+    }
+
+    #[test]
+    fn test_empty_file_has_no_summary() {
+        assert_eq!(file_summary("empty.rs", [], []), None);
+    }
+}
@@ -0,0 +1,135 @@
+//! One-line titles of chunks, shown as the heading of search results
+//!
+//! The title of a chunk is built from the symbol it belongs to: the first
+//! line of its signature, followed by the first sentence of its doc comment,
+//! e.g. `pub fn load(path: &Path) -> Config — Load the configuration file.`
+//! Chunks outside of any symbol have no title.
+
+use rusqlite::{Connection, params};
+
+/// Characters of a title; longer ones are cut with an ellipsis
+pub const MAX_TITLE_CHARS: usize = 120;
+
+/// Build the title of a chunk from the symbol it belongs to
+pub fn chunk_title(
+    kind: &str,
+    name: &str,
+    signature: Option<&str>,
+    doc_comment: Option<&str>,
+) -> String {
+    let head = signature
+        .and_then(|s| s.lines().map(str::trim).find(|line| !line.is_empty()))
+        .map(|line| line.trim_end_matches(['{', ':']).trim_end())
+        .filter(|line| !line.is_empty())
+        .map_or_else(|| format!("{} {}", kind, name), str::to_string);
+
+    let mut title = match doc_comment.and_then(first_sentence) {
+        Some(sentence) => format!("{} — {}", head, sentence),
+        None => head,
+    };
+    if let Some((end, _)) = title.char_indices().nth(MAX_TITLE_CHARS - 1) {
+        title.truncate(end);
+        title.push('…');
+    }
+    title
+}
+
+/// Set the title of the chunks of a file, or of every chunk when `file_id`
+/// is `None`, from the symbol each is linked to.
+pub(crate) fn fill_chunk_titles(conn: &Connection, file_id: Option<i64>) -> rusqlite::Result<()> {
+    let mut select = conn.prepare_cached(
+        "SELECT c.id, s.kind, s.name, s.signature, s.doc_comment
+         FROM chunks c
+         LEFT JOIN symbols s ON s.id = c.symbol_id
+         WHERE ?1 IS NULL OR c.file_id = ?1",
+    )?;
+    let titles = select
+        .query_map([file_id], |row| {
+            let kind: Option<String> = row.get(1)?;
+            let name: Option<String> = row.get(2)?;
+            let signature: Option<String> = row.get(3)?;
+            let doc_comment: Option<String> = row.get(4)?;
+            let title = kind.zip(name).map(|(kind, name)| {
+                chunk_title(&kind, &name, signature.as_deref(), doc_comment.as_deref())
+            });
+            Ok((row.get::<_, i64>(0)?, title))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut update = conn.prepare_cached("UPDATE chunks SET title = ?1 WHERE id = ?2")?;
+    for (id, title) in titles {
+        update.execute(params![title, id])?;
+    }
+    Ok(())
+}
+
+/// First sentence of a doc comment, without its comment markers (`///`,
+/// `/**`, `*`, `#`, `"""`...) or the tag lines following it (`@param`)
+fn first_sentence(doc_comment: &str) -> Option<String> {
+    let mut text = String::new();
+    for line in doc_comment.lines() {
+        let line = line
+            .trim()
+            .trim_start_matches(['/', '*', '!', '#', '"', '\''])
+            .trim_end_matches(['/', '*', '"', '\''])
+            .trim();
+        if line.is_empty() {
+            // A blank line ends the first paragraph
+            if text.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if line.starts_with('@') {
+            break;
+        }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+
+    let end = text
+        .match_indices(". ")
+        .next()
+        .map_or(text.len(), |(index, _)| index + 1);
+    text.truncate(end);
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_title() {
+        assert_eq!(
+            chunk_title(
+                "function",
+                "load",
+                Some("pub fn load(path: &Path) -> Config {"),
+                Some("/// Load the configuration file. Defaults apply to\n/// missing keys.")
+            ),
+            "pub fn load(path: &Path) -> Config — Load the configuration file."
+        );
+        assert_eq!(
+            chunk_title(
+                "class",
+                "Client",
+                Some("class Client(Base):"),
+                Some("/**\n * HTTP client with retries\n *\n * @param base the base URL\n */")
+            ),
+            "class Client(Base) — HTTP client with retries"
+        );
+        assert_eq!(chunk_title("struct", "Point", None, None), "struct Point");
+    }
+
+    #[test]
+    fn test_long_titles_are_cut() {
+        let doc = format!("/// {}", "word ".repeat(50));
+        let title = chunk_title("function", "run", Some("fn run()"), Some(&doc));
+        assert_eq!(title.chars().count(), MAX_TITLE_CHARS);
+        assert!(title.starts_with("fn run() — word word"));
+        assert!(title.ends_with('…'));
+    }
+}
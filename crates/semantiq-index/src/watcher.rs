@@ -115,4 +115,50 @@ mod tests {
         let watcher = FileWatcher::new();
         assert!(watcher.is_ok());
     }
+
+    fn event_paths(events: Vec<FileEvent>) -> Vec<PathBuf> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                FileEvent::Created(path) | FileEvent::Modified(path) | FileEvent::Deleted(path) => {
+                    path
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_events_skip_excluded_paths() {
+        use notify::EventKind;
+        use notify::event::CreateKind;
+
+        let event = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/project/src/lib.rs"))
+            .add_path(PathBuf::from("/project/node_modules/pkg/index.js"));
+
+        assert_eq!(
+            event_paths(FileWatcher::convert_event(event)),
+            vec![PathBuf::from("/project/src/lib.rs")]
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_event_paths_map_to_indexed_paths() {
+        use crate::paths::relative_path;
+        use notify::EventKind;
+        use notify::event::RemoveKind;
+
+        let root = Path::new(r"C:\project");
+        let event = Event::new(EventKind::Remove(RemoveKind::File))
+            .add_path(PathBuf::from(r"C:\project\src\engine\mod.rs"))
+            .add_path(PathBuf::from(r"C:\project\node_modules\pkg\index.js"));
+
+        let paths = event_paths(FileWatcher::convert_event(event));
+        assert_eq!(paths.len(), 1);
+        assert_eq!(
+            relative_path(root, &paths[0]).as_deref(),
+            Some("src/engine/mod.rs")
+        );
+    }
 }
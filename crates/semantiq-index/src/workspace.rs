@@ -0,0 +1,637 @@
+//! Package boundaries of monorepos
+//!
+//! The manifests at the project root declare the packages of a workspace:
+//!
+//! - Rust: `[workspace] members` of `Cargo.toml`, plus its own `[package]`
+//! - TypeScript/JavaScript: `pnpm-workspace.yaml` or the `workspaces` of
+//!   `package.json`, each package being named by its own `package.json`
+//! - Go: the `use` directives of `go.work`, or the `go.mod` of the root
+//!
+//! Every indexed project file belongs to the package with the deepest root
+//! containing it (see [`owning_package`]), which searches can filter on and
+//! dependency analysis uses to tell imports within a package from imports
+//! of another one.
+
+use crate::exclusions::EXCLUDED_DIRS;
+use crate::external::is_external_path;
+use crate::paths::{relative_path, to_index_path};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Files whose changes can add, remove or rename workspace packages
+pub const MANIFEST_FILES: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pnpm-workspace.yaml",
+    "go.mod",
+    "go.work",
+];
+
+/// Maximum directory depth searched by a `**` member pattern
+const MAX_GLOB_DEPTH: usize = 8;
+
+/// Build system declaring a workspace package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageEcosystem {
+    Cargo,
+    Go,
+    Npm,
+}
+
+impl PackageEcosystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PackageEcosystem::Cargo => "cargo",
+            PackageEcosystem::Npm => "npm",
+            PackageEcosystem::Go => "go",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cargo" => Some(PackageEcosystem::Cargo),
+            "npm" => Some(PackageEcosystem::Npm),
+            "go" => Some(PackageEcosystem::Go),
+            _ => None,
+        }
+    }
+}
+
+/// A package of the project's workspace
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct WorkspacePackage {
+    /// Directory of the package relative to the project root, empty for the root
+    pub root: String,
+    pub ecosystem: PackageEcosystem,
+    /// Crate name, npm package name or Go module path
+    pub name: String,
+}
+
+impl WorkspacePackage {
+    /// Check if an indexed path is under the root of the package
+    pub fn contains(&self, path: &str) -> bool {
+        self.root.is_empty()
+            || path
+                .strip_prefix(self.root.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// Find the packages declared by the manifests at the root of a project,
+/// sorted by root.
+pub fn discover_workspace_packages(project_root: &Path) -> Vec<WorkspacePackage> {
+    let mut packages = BTreeSet::new();
+    packages.extend(discover_cargo_members(project_root));
+    packages.extend(discover_npm_members(project_root));
+    packages.extend(discover_go_modules(project_root));
+    packages.into_iter().collect()
+}
+
+/// Package owning an indexed path: the one with the deepest root containing
+/// it. External files belong to no package.
+pub fn owning_package<'a>(
+    packages: &'a [WorkspacePackage],
+    path: &str,
+) -> Option<&'a WorkspacePackage> {
+    if is_external_path(path) {
+        return None;
+    }
+    packages.iter().filter(|p| p.contains(path)).max_by(|a, b| {
+        a.root
+            .len()
+            .cmp(&b.root.len())
+            .then_with(|| b.ecosystem.cmp(&a.ecosystem))
+    })
+}
+
+/// Package an import of the file at `source_path` refers to.
+///
+/// Local imports (relative paths, `crate::`) stay in the package of the file
+/// or resolve to the package owning their target, other imports refer to a
+/// package when they start with its name. Imports of third-party and
+/// standard libraries refer to none.
+pub fn imported_package<'a>(
+    packages: &'a [WorkspacePackage],
+    source_path: &str,
+    import: &str,
+) -> Option<&'a WorkspacePackage> {
+    let first_segment = import.split("::").next().unwrap_or(import);
+    if matches!(first_segment, "crate" | "self" | "super") {
+        return owning_package(packages, source_path);
+    }
+    if import.starts_with("./") || import.starts_with("../") || import == "." || import == ".." {
+        let dir = source_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let target = resolve_relative(dir, import)?;
+        return owning_package(packages, &target);
+    }
+
+    packages.iter().find(|p| match p.ecosystem {
+        PackageEcosystem::Cargo => first_segment == p.name.replace('-', "_"),
+        PackageEcosystem::Npm | PackageEcosystem::Go => import
+            .strip_prefix(p.name.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+    })
+}
+
+/// Join a relative import to a directory, `None` when it leaves the project
+fn resolve_relative(dir: &str, import: &str) -> Option<String> {
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for component in import.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn discover_cargo_members(project_root: &Path) -> Vec<WorkspacePackage> {
+    let Some(manifest) = read_toml(&project_root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+
+    let mut packages = Vec::new();
+    if let Some(name) = cargo_package_name(&manifest) {
+        packages.push(package(
+            project_root,
+            project_root,
+            PackageEcosystem::Cargo,
+            name,
+        ));
+    }
+
+    let Some(workspace) = manifest.get("workspace") else {
+        return packages;
+    };
+    let excluded: Vec<PathBuf> = string_list(workspace.get("exclude"))
+        .iter()
+        .map(|p| project_root.join(p))
+        .collect();
+    for pattern in string_list(workspace.get("members")) {
+        for dir in expand_member_pattern(project_root, &pattern) {
+            if excluded.iter().any(|e| dir.starts_with(e)) {
+                continue;
+            }
+            let name = read_toml(&dir.join("Cargo.toml"))
+                .as_ref()
+                .and_then(cargo_package_name);
+            if let Some(name) = name {
+                packages.push(package(project_root, &dir, PackageEcosystem::Cargo, name));
+            }
+        }
+    }
+    packages
+}
+
+fn discover_npm_members(project_root: &Path) -> Vec<WorkspacePackage> {
+    let mut packages = Vec::new();
+    if let Some(name) = npm_package_name(project_root) {
+        packages.push(package(
+            project_root,
+            project_root,
+            PackageEcosystem::Npm,
+            name,
+        ));
+    }
+
+    let mut patterns = pnpm_workspace_patterns(project_root);
+    if let Some(manifest) = read_json(&project_root.join("package.json")) {
+        // `workspaces` is either a list of patterns or `{ "packages": [...] }`
+        let workspaces = manifest.get("workspaces");
+        let list = workspaces
+            .and_then(|w| w.get("packages"))
+            .or(workspaces)
+            .and_then(|w| w.as_array());
+        patterns.extend(
+            list.into_iter()
+                .flatten()
+                .filter_map(|p| p.as_str().map(String::from)),
+        );
+    }
+
+    let excluded: Vec<PathBuf> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .flat_map(|p| expand_member_pattern(project_root, p))
+        .collect();
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        for dir in expand_member_pattern(project_root, pattern) {
+            if excluded.contains(&dir) {
+                continue;
+            }
+            if let Some(name) = npm_package_name(&dir) {
+                packages.push(package(project_root, &dir, PackageEcosystem::Npm, name));
+            }
+        }
+    }
+    packages
+}
+
+fn discover_go_modules(project_root: &Path) -> Vec<WorkspacePackage> {
+    let mut dirs = vec![project_root.to_path_buf()];
+    if let Ok(work) = fs::read_to_string(project_root.join("go.work")) {
+        dirs.extend(go_work_uses(&work).iter().map(|d| project_root.join(d)));
+    }
+
+    dirs.into_iter()
+        .filter_map(|dir| {
+            let go_mod = fs::read_to_string(dir.join("go.mod")).ok()?;
+            let name = go_module_path(&go_mod)?;
+            Some(package(project_root, &dir, PackageEcosystem::Go, name))
+        })
+        .collect()
+}
+
+fn package(
+    project_root: &Path,
+    dir: &Path,
+    ecosystem: PackageEcosystem,
+    name: String,
+) -> WorkspacePackage {
+    let root = relative_path(project_root, dir).unwrap_or_else(|| to_index_path(dir));
+    WorkspacePackage {
+        root,
+        ecosystem,
+        name,
+    }
+}
+
+fn cargo_package_name(manifest: &toml::Value) -> Option<String> {
+    manifest
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+fn npm_package_name(dir: &Path) -> Option<String> {
+    read_json(&dir.join("package.json"))?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+/// Patterns of the `packages` list of `pnpm-workspace.yaml`
+fn pnpm_workspace_patterns(project_root: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(project_root.join("pnpm-workspace.yaml")) else {
+        return Vec::new();
+    };
+    match serde_yaml::from_str::<serde_yaml::Value>(&content) {
+        Ok(value) => value
+            .get("packages")
+            .and_then(|p| p.as_sequence())
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.as_str().map(String::from))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to parse pnpm-workspace.yaml: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Directories of the `use` directives of a `go.work` file, in both the
+/// `use ./dir` and the `use ( ... )` block forms
+fn go_work_uses(content: &str) -> Vec<String> {
+    let mut dirs = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if !line.is_empty() {
+                dirs.push(line.trim_matches('"').to_string());
+            }
+        } else if let Some(rest) = line
+            .strip_prefix("use")
+            .filter(|rest| rest.starts_with([' ', '\t', '(']))
+        {
+            match rest.trim() {
+                "(" => in_block = true,
+                dir => dirs.push(dir.trim_matches('"').to_string()),
+            }
+        }
+    }
+    dirs
+}
+
+/// Module path declared by a `go.mod` file
+fn go_module_path(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let path = line
+            .trim()
+            .strip_prefix("module ")?
+            .trim()
+            .trim_matches('"');
+        (!path.is_empty()).then(|| path.to_string())
+    })
+}
+
+/// Directories matching a workspace member pattern such as `crates/*`,
+/// `packages/**` or `apps/web`, sorted. `*` matches within a path component
+/// and `**` any number of directories; excluded and hidden directories are
+/// never matched by a wildcard.
+fn expand_member_pattern(project_root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![project_root.to_path_buf()];
+    for component in pattern.trim_start_matches("./").split('/') {
+        let mut next = Vec::new();
+        match component {
+            "" | "." => continue,
+            "**" => {
+                for dir in &dirs {
+                    collect_subdirectories(dir, MAX_GLOB_DEPTH, &mut next);
+                }
+                next.extend(dirs);
+            }
+            _ if component.contains('*') => {
+                for dir in &dirs {
+                    next.extend(subdirectories(dir).into_iter().filter(|d| {
+                        d.file_name()
+                            .is_some_and(|n| wildcard_match(component, &n.to_string_lossy()))
+                    }));
+                }
+            }
+            _ => next.extend(
+                dirs.iter()
+                    .map(|d| d.join(component))
+                    .filter(|d| d.is_dir()),
+            ),
+        }
+        dirs = next;
+    }
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            !name.starts_with('.') && !EXCLUDED_DIRS.contains(&name.as_str())
+        })
+        .map(|e| e.path())
+        .collect()
+}
+
+fn collect_subdirectories(dir: &Path, depth: usize, dirs: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        return;
+    }
+    for sub in subdirectories(dir) {
+        collect_subdirectories(&sub, depth - 1, dirs);
+        dirs.push(sub);
+    }
+}
+
+/// Match a name against a pattern where `*` matches any run of characters
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(name) = name.strip_prefix(prefix) else {
+        return false;
+    };
+    if !rest.contains('*') {
+        return name.len() >= rest.len() && name.ends_with(rest);
+    }
+    (0..=name.len())
+        .filter(|&i| name.is_char_boundary(i))
+        .any(|i| wildcard_match(rest, &name[i..]))
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    match toml::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Failed to parse {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Failed to parse {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn summary(packages: &[WorkspacePackage]) -> Vec<(&str, &str, &str)> {
+        packages
+            .iter()
+            .map(|p| (p.root.as_str(), p.ecosystem.as_str(), p.name.as_str()))
+            .collect()
+    }
+
+    fn pkg(root: &str, ecosystem: PackageEcosystem, name: &str) -> WorkspacePackage {
+        WorkspacePackage {
+            root: root.to_string(),
+            ecosystem,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_discover_cargo_workspace() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/old\"]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"acme-core\"\n",
+        );
+        write(
+            root,
+            "crates/web/Cargo.toml",
+            "[package]\nname = \"acme-web\"\n",
+        );
+        write(
+            root,
+            "crates/old/Cargo.toml",
+            "[package]\nname = \"acme-old\"\n",
+        );
+        write(root, "tools/cli/Cargo.toml", "[package]\nname = \"acme\"\n");
+
+        assert_eq!(
+            summary(&discover_workspace_packages(root)),
+            vec![
+                ("crates/core", "cargo", "acme-core"),
+                ("crates/web", "cargo", "acme-web"),
+                ("tools/cli", "cargo", "acme"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_pnpm_workspace() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"name": "monorepo", "private": true}"#,
+        );
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'packages/**'\n  - apps/web\n  - '!packages/internal'\n",
+        );
+        write(root, "packages/ui/package.json", r#"{"name": "@acme/ui"}"#);
+        write(
+            root,
+            "packages/utils/date/package.json",
+            r#"{"name": "@acme/date"}"#,
+        );
+        write(
+            root,
+            "packages/internal/package.json",
+            r#"{"name": "internal"}"#,
+        );
+        write(
+            root,
+            "packages/ui/node_modules/x/package.json",
+            r#"{"name": "x"}"#,
+        );
+        write(root, "apps/web/package.json", r#"{"name": "web"}"#);
+
+        assert_eq!(
+            summary(&discover_workspace_packages(root)),
+            vec![
+                ("", "npm", "monorepo"),
+                ("apps/web", "npm", "web"),
+                ("packages/ui", "npm", "@acme/ui"),
+                ("packages/utils/date", "npm", "@acme/date"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_go_workspace() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "go.work",
+            "go 1.22\n\nuse (\n\t./api // service\n\t./lib\n)\nuse ./tools\n",
+        );
+        write(
+            root,
+            "api/go.mod",
+            "module github.com/acme/api\n\ngo 1.22\n",
+        );
+        write(root, "lib/go.mod", "module github.com/acme/lib\n");
+        write(root, "tools/go.mod", "module github.com/acme/tools\n");
+
+        assert_eq!(
+            summary(&discover_workspace_packages(root)),
+            vec![
+                ("api", "go", "github.com/acme/api"),
+                ("lib", "go", "github.com/acme/lib"),
+                ("tools", "go", "github.com/acme/tools"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_owning_package_is_the_deepest() {
+        let packages = vec![
+            pkg("", PackageEcosystem::Npm, "monorepo"),
+            pkg("packages/ui", PackageEcosystem::Npm, "@acme/ui"),
+        ];
+
+        let owner = |path| owning_package(&packages, path).map(|p| p.name.as_str());
+        assert_eq!(owner("packages/ui/src/button.tsx"), Some("@acme/ui"));
+        assert_eq!(owner("packages/ui-kit/index.ts"), Some("monorepo"));
+        assert_eq!(owner("scripts/build.ts"), Some("monorepo"));
+        assert_eq!(owner("external://npm/react@18.2.0/index.d.ts"), None);
+        assert_eq!(owning_package(&packages[1..], "scripts/build.ts"), None);
+    }
+
+    #[test]
+    fn test_imported_package() {
+        let packages = vec![
+            pkg("crates/core", PackageEcosystem::Cargo, "acme-core"),
+            pkg("crates/web", PackageEcosystem::Cargo, "acme-web"),
+            pkg("packages/ui", PackageEcosystem::Npm, "@acme/ui"),
+            pkg("apps/web", PackageEcosystem::Npm, "web"),
+        ];
+
+        let imported =
+            |source, import| imported_package(&packages, source, import).map(|p| p.name.as_str());
+        assert_eq!(
+            imported("crates/web/src/lib.rs", "crate::routes"),
+            Some("acme-web")
+        );
+        assert_eq!(
+            imported("crates/web/src/lib.rs", "acme_core::store::Store"),
+            Some("acme-core")
+        );
+        assert_eq!(imported("crates/web/src/lib.rs", "serde::Serialize"), None);
+        assert_eq!(
+            imported("apps/web/src/app.tsx", "@acme/ui/button"),
+            Some("@acme/ui")
+        );
+        assert_eq!(imported("apps/web/src/app.tsx", "@acme/uikit"), None);
+        assert_eq!(imported("apps/web/src/app.tsx", "./layout"), Some("web"));
+        assert_eq!(
+            imported("apps/web/src/app.tsx", "../../../packages/ui/src/button"),
+            Some("@acme/ui")
+        );
+        assert_eq!(imported("apps/web/app.tsx", "../../../../outside"), None);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "core"));
+        assert!(wildcard_match("semantiq-*", "semantiq-index"));
+        assert!(wildcard_match("*-plugin", "eslint-plugin"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("semantiq-*", "other"));
+        assert!(!wildcard_match("a*bc", "abc-"));
+    }
+}
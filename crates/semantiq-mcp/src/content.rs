@@ -0,0 +1,455 @@
+//! Structured content of tool responses
+//!
+//! Tools answer with their response rendered in the requested format. With
+//! `[mcp] structured_content` (the default), clients that render results
+//! natively also get:
+//!
+//! - the response as JSON, when the text is markdown
+//! - each location of the response (search results, references,
+//!   definitions, implementations, components, files viewed in a session) as
+//!   an embedded resource, with a `semantiq://file/<path>#L<start>-L<end>`
+//!   URI and, for scored results, the relevance score as its `priority`
+//!   annotation
+//!
+//! The structured content is annotated for the user, so that clients can
+//! display it without passing it to the model a second time.
+
+use rmcp::model::{
+    AnnotateAble, Annotations, Content, IntoContents, RawContent, ResourceContents, Role,
+};
+use semantiq_index::EXTERNAL_PREFIX;
+use semantiq_retrieval::{
+    BatchSearchResponse, ComponentNode, ComponentTreeResponse, ConfigUsageResponse,
+    DependenciesResponse, EntrypointsResponse, ExploreResponse, ImplementationsResponse,
+    IndexIssuesResponse, OutputFormat, OverviewResponse, ReferencesResponse, RelatedFilesResponse,
+    RoutesResponse, SearchResult, SearchResults, SemanticDiffResponse, SessionSummary,
+    SymbolExplanation, ToMarkdown,
+};
+use serde::Serialize;
+use std::fmt::{self, Write};
+use std::ops::Deref;
+use tracing::warn;
+
+/// URI prefix of project files
+const FILE_URI_PREFIX: &str = "semantiq://file/";
+
+/// URI prefix of the indexed sources of external dependencies
+const EXTERNAL_URI_PREFIX: &str = "semantiq://external/";
+
+/// Response of a tool: its text, followed by structured content
+#[derive(Debug, Clone)]
+pub struct ToolOutput {
+    text: String,
+    structured: Vec<Content>,
+}
+
+impl ToolOutput {
+    pub(crate) fn new(text: String) -> Self {
+        Self {
+            text,
+            structured: Vec::new(),
+        }
+    }
+
+    /// `value` rendered in `format`, with its structured content when
+    /// `structured` is set
+    pub(crate) fn render<T>(format: OutputFormat, value: &T, structured: bool) -> Self
+    where
+        T: Serialize + ToMarkdown + ResourceLinks,
+    {
+        let mut output = Self::new(format.render(value));
+        if !structured {
+            return output;
+        }
+        if format == OutputFormat::Markdown {
+            match serde_json::to_string(value) {
+                Ok(json) => output
+                    .structured
+                    .push(for_user(RawContent::text(json), None)),
+                Err(e) => warn!("Failed to serialize tool response: {}", e),
+            }
+        }
+        output
+            .structured
+            .extend(value.resource_links().iter().map(ResourceLink::to_content));
+        output
+    }
+
+    /// Text of the response
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Structured content sent after the text
+    pub fn structured(&self) -> &[Content] {
+        &self.structured
+    }
+}
+
+impl Deref for ToolOutput {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for ToolOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl PartialEq<&str> for ToolOutput {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+impl IntoContents for ToolOutput {
+    fn into_contents(self) -> Vec<Content> {
+        std::iter::once(Content::text(self.text))
+            .chain(self.structured)
+            .collect()
+    }
+}
+
+/// Content annotated for display to the user
+fn for_user(content: RawContent, priority: Option<f32>) -> Content {
+    content.optional_annotate(Some(Annotations {
+        audience: Some(vec![Role::User]),
+        priority,
+        timestamp: None,
+    }))
+}
+
+/// Lines of a file a response points to
+pub struct ResourceLink<'a> {
+    pub file_path: &'a str,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Code or signature at these lines
+    pub text: &'a str,
+    /// Relevance score of a search result
+    pub score: Option<f32>,
+}
+
+impl ResourceLink<'_> {
+    /// `semantiq://file/src/lib.rs#L10-L42`, or
+    /// `semantiq://external/cargo/serde@1.0.200/src/lib.rs#L3` for external
+    /// dependencies
+    pub fn uri(&self) -> String {
+        let (prefix, path) = match self.file_path.strip_prefix(EXTERNAL_PREFIX) {
+            Some(rest) => (EXTERNAL_URI_PREFIX, rest),
+            None => (FILE_URI_PREFIX, self.file_path),
+        };
+        let mut uri = String::from(prefix);
+        for byte in path.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~/@+".contains(&byte) {
+                uri.push(byte as char);
+            } else {
+                let _ = write!(uri, "%{:02X}", byte);
+            }
+        }
+        if self.start_line == self.end_line {
+            let _ = write!(uri, "#L{}", self.start_line);
+        } else {
+            let _ = write!(uri, "#L{}-L{}", self.start_line, self.end_line);
+        }
+        uri
+    }
+
+    fn to_content(&self) -> Content {
+        let resource = ResourceContents::TextResourceContents {
+            uri: self.uri(),
+            mime_type: Some("text/plain".to_string()),
+            text: self.text.to_string(),
+        };
+        for_user(
+            RawContent::resource(resource),
+            self.score.map(|score| score.clamp(0.0, 1.0)),
+        )
+    }
+}
+
+impl<'a> From<&'a SearchResult> for ResourceLink<'a> {
+    fn from(result: &'a SearchResult) -> Self {
+        Self {
+            file_path: &result.file_path,
+            start_line: result.start_line,
+            end_line: result.end_line,
+            text: &result.content,
+            score: Some(result.score),
+        }
+    }
+}
+
+/// Locations of a tool response, sent as embedded resources
+pub trait ResourceLinks {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        Vec::new()
+    }
+}
+
+impl ResourceLinks for SearchResults {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.results.iter().map(ResourceLink::from).collect()
+    }
+}
+
+impl ResourceLinks for BatchSearchResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.searches
+            .iter()
+            .filter_map(|search| search.results.as_ref())
+            .flat_map(|results| &results.results)
+            .map(ResourceLink::from)
+            .collect()
+    }
+}
+
+impl ResourceLinks for ReferencesResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.definitions
+            .iter()
+            .chain(&self.usages)
+            .map(ResourceLink::from)
+            .collect()
+    }
+}
+
+impl ResourceLinks for SymbolExplanation {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.definitions
+            .iter()
+            .map(|definition| ResourceLink {
+                file_path: &definition.file_path,
+                start_line: definition.start_line,
+                end_line: definition.end_line,
+                text: definition.signature.as_deref().unwrap_or(&self.name),
+                score: None,
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for ImplementationsResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.implementations
+            .iter()
+            .map(|implementation| ResourceLink {
+                file_path: &implementation.file_path,
+                start_line: implementation.start_line,
+                end_line: implementation.end_line,
+                text: &implementation.type_name,
+                score: None,
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for EntrypointsResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.entrypoints
+            .iter()
+            .map(|entrypoint| ResourceLink {
+                file_path: &entrypoint.file_path,
+                start_line: entrypoint.start_line,
+                end_line: entrypoint.end_line,
+                text: &entrypoint.name,
+                score: None,
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for IndexIssuesResponse {
+    /// Files left out of the index (read errors, too large) are not linked
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.issues
+            .iter()
+            .filter(|issue| matches!(issue.kind.as_str(), "parse_failure" | "encoding"))
+            .map(|issue| ResourceLink {
+                file_path: &issue.path,
+                start_line: 1,
+                end_line: 1,
+                text: &issue.message,
+                score: None,
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for ExploreResponse {
+    /// Only indexed files are linked, not directories or files left out
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.indexed_at.is_some() && !entry.is_dir)
+            .map(|entry| ResourceLink {
+                file_path: &entry.path,
+                start_line: 1,
+                end_line: 1,
+                text: &entry.name,
+                score: None,
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for ConfigUsageResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.usages
+            .iter()
+            .map(|usage| ResourceLink {
+                file_path: &usage.file_path,
+                start_line: usage.line,
+                end_line: usage.line,
+                text: &usage.name,
+                score: None,
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for RoutesResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.routes
+            .iter()
+            .map(|route| match route.handler_definition {
+                Some(ref definition) => ResourceLink {
+                    file_path: &definition.file_path,
+                    start_line: definition.start_line,
+                    end_line: definition.end_line,
+                    text: route.handler.as_deref().unwrap_or(&route.path),
+                    score: None,
+                },
+                None => ResourceLink {
+                    file_path: &route.file_path,
+                    start_line: route.start_line,
+                    end_line: route.end_line,
+                    text: &route.path,
+                    score: None,
+                },
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for ComponentTreeResponse {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        fn push<'a>(links: &mut Vec<ResourceLink<'a>>, components: &'a [ComponentNode]) {
+            for component in components {
+                links.push(ResourceLink {
+                    file_path: &component.file_path,
+                    start_line: component.line,
+                    end_line: component.line,
+                    text: &component.name,
+                    score: None,
+                });
+                push(links, &component.children);
+            }
+        }
+
+        let mut links = Vec::new();
+        push(&mut links, &self.rendered_by);
+        push(&mut links, &self.renders);
+        links
+    }
+}
+
+impl ResourceLinks for SessionSummary {
+    fn resource_links(&self) -> Vec<ResourceLink<'_>> {
+        self.files
+            .iter()
+            .flat_map(|file| {
+                file.lines.iter().map(|range| ResourceLink {
+                    file_path: &file.file_path,
+                    start_line: range.start_line,
+                    end_line: range.end_line,
+                    text: &file.file_path,
+                    score: None,
+                })
+            })
+            .collect()
+    }
+}
+
+impl ResourceLinks for DependenciesResponse {}
+
+impl ResourceLinks for RelatedFilesResponse {}
+
+impl ResourceLinks for OverviewResponse {}
+
+impl ResourceLinks for SemanticDiffResponse {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_retrieval::SearchResultKind;
+
+    fn link(file_path: &str, start_line: usize, end_line: usize) -> ResourceLink<'_> {
+        ResourceLink {
+            file_path,
+            start_line,
+            end_line,
+            text: "",
+            score: None,
+        }
+    }
+
+    #[test]
+    fn test_resource_link_uri() {
+        assert_eq!(
+            link("src/lib.rs", 10, 42).uri(),
+            "semantiq://file/src/lib.rs#L10-L42"
+        );
+        assert_eq!(
+            link("docs/my notes#1.md", 3, 3).uri(),
+            "semantiq://file/docs/my%20notes%231.md#L3"
+        );
+        assert_eq!(
+            link("external://cargo/serde@1.0.200/src/de.rs", 1, 2).uri(),
+            "semantiq://external/cargo/serde@1.0.200/src/de.rs#L1-L2"
+        );
+    }
+
+    #[test]
+    fn test_render_structured_content() {
+        let result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "src/lib.rs".to_string(),
+            1,
+            3,
+            "fn main() {}".to_string(),
+            1.4,
+        );
+        let results = SearchResults::new("main".to_string(), vec![result], 5);
+
+        let output = ToolOutput::render(OutputFormat::Markdown, &results, true);
+        assert_eq!(output.text(), results.to_markdown());
+        let contents = output.into_contents();
+        assert_eq!(contents.len(), 3);
+        let json = contents[1].as_text().expect("JSON text");
+        let value: serde_json::Value = serde_json::from_str(&json.text).unwrap();
+        assert_eq!(value["results"][0]["file_path"], "src/lib.rs");
+        assert_eq!(contents[1].audience(), Some(&vec![Role::User]));
+
+        let resource = contents[2].as_resource().expect("embedded resource");
+        match &resource.resource {
+            ResourceContents::TextResourceContents { uri, text, .. } => {
+                assert_eq!(uri, "semantiq://file/src/lib.rs#L1-L3");
+                assert_eq!(text, "fn main() {}");
+            }
+            other => panic!("unexpected resource {:?}", other),
+        }
+        // Scores above 1 are clamped to the range of priorities
+        assert_eq!(contents[2].priority(), Some(1.0));
+
+        // JSON output is not repeated, and nothing is added when disabled
+        let json = ToolOutput::render(OutputFormat::Json, &results, true);
+        assert_eq!(json.structured().len(), 1);
+        let plain = ToolOutput::render(OutputFormat::Markdown, &results, false);
+        assert!(plain.structured().is_empty());
+    }
+}
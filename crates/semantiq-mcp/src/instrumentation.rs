@@ -0,0 +1,328 @@
+//! Tracing spans around tool invocations
+//!
+//! Every tool call, from an MCP client or the HTTP API, runs inside a
+//! `tool_call` span carrying the tool name and a hash of its main input, so
+//! that calls can be correlated without logging queries verbatim. The call
+//! ends with an event recording its duration and result count, or its error
+//! category when it failed. Calls made through the server are also counted
+//! in the usage statistics of the index (see `semantiq stats --usage`).
+
+use crate::tools::{ReindexOutcome, ToolError};
+use semantiq_index::{IndexStore, ToolUsageEvent};
+use semantiq_retrieval::{
+    BatchSearchResponse, ComponentTreeResponse, ConfigUsageResponse, DependenciesResponse,
+    EntrypointsResponse, ExploreResponse, ImplementationsResponse, IndexIssuesResponse,
+    OverviewResponse, ReferencesResponse, RelatedFilesResponse, RoutesResponse, SearchResults,
+    SemanticDiffResponse, SessionSummary, SymbolExplanation,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{Instrument, Span, debug, info, info_span, warn};
+
+/// Number of results reported in the closing event of a tool call
+pub(crate) trait ResultCount {
+    fn result_count(&self) -> usize;
+}
+
+impl ResultCount for SearchResults {
+    fn result_count(&self) -> usize {
+        self.results.len()
+    }
+}
+
+impl ResultCount for BatchSearchResponse {
+    fn result_count(&self) -> usize {
+        self.searches
+            .iter()
+            .filter_map(|search| search.results.as_ref())
+            .map(ResultCount::result_count)
+            .sum()
+    }
+}
+
+impl ResultCount for ReferencesResponse {
+    fn result_count(&self) -> usize {
+        self.definitions.len() + self.usages.len()
+    }
+}
+
+impl ResultCount for DependenciesResponse {
+    fn result_count(&self) -> usize {
+        self.imports.len() + self.imported_by.len()
+    }
+}
+
+impl ResultCount for SymbolExplanation {
+    fn result_count(&self) -> usize {
+        self.definitions.len()
+    }
+}
+
+impl ResultCount for ImplementationsResponse {
+    fn result_count(&self) -> usize {
+        self.implementations.len()
+    }
+}
+
+impl ResultCount for ComponentTreeResponse {
+    fn result_count(&self) -> usize {
+        self.rendered_by.len() + self.renders.len()
+    }
+}
+
+impl ResultCount for RelatedFilesResponse {
+    fn result_count(&self) -> usize {
+        self.related.len()
+    }
+}
+
+impl ResultCount for OverviewResponse {
+    fn result_count(&self) -> usize {
+        self.subdirectories.len()
+    }
+}
+
+impl ResultCount for ExploreResponse {
+    fn result_count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl ResultCount for EntrypointsResponse {
+    fn result_count(&self) -> usize {
+        self.entrypoints.len()
+    }
+}
+
+impl ResultCount for ConfigUsageResponse {
+    fn result_count(&self) -> usize {
+        self.usages.len()
+    }
+}
+
+impl ResultCount for IndexIssuesResponse {
+    fn result_count(&self) -> usize {
+        self.issues.len()
+    }
+}
+
+impl ResultCount for RoutesResponse {
+    fn result_count(&self) -> usize {
+        self.routes.len()
+    }
+}
+
+impl ResultCount for SemanticDiffResponse {
+    fn result_count(&self) -> usize {
+        self.total_changes
+    }
+}
+
+impl ResultCount for SessionSummary {
+    fn result_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+impl ResultCount for ReindexOutcome {
+    fn result_count(&self) -> usize {
+        match self {
+            ReindexOutcome::Completed(report) => report.indexed.len() + report.removed.len(),
+            ReindexOutcome::InitialIndexing(_) => 0,
+        }
+    }
+}
+
+/// Stable hash of a tool input, logged instead of the input itself
+pub(crate) fn query_hash(input: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    input.trim().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// One tool invocation, traced from creation to [`ToolCall::run`]
+pub(crate) struct ToolCall {
+    tool: &'static str,
+    span: Span,
+    started: Instant,
+    /// Store whose usage statistics count the call
+    usage: Option<Arc<IndexStore>>,
+    /// Category of the query in the usage statistics
+    category: Option<&'static str>,
+}
+
+impl ToolCall {
+    pub(crate) fn new(tool: &'static str, input: &str) -> Self {
+        let span = info_span!(
+            "tool_call",
+            tool,
+            query_hash = %query_hash(input),
+            duration_ms = Empty,
+            result_count = Empty,
+            error_category = Empty,
+        );
+        Self {
+            tool,
+            span,
+            started: Instant::now(),
+            usage: None,
+            category: None,
+        }
+    }
+
+    /// Count the call in the usage statistics of `store`
+    pub(crate) fn recorded_in(mut self, store: Arc<IndexStore>) -> Self {
+        self.usage = Some(store);
+        self
+    }
+
+    /// Category of the query, recorded in the usage statistics
+    pub(crate) fn with_category(mut self, category: &'static str) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Run the tool inside the span and record its outcome
+    pub(crate) fn run<T: ResultCount>(
+        self,
+        f: impl FnOnce() -> Result<T, ToolError>,
+    ) -> Result<T, ToolError> {
+        let result = self.span.in_scope(f);
+        self.finish(&result);
+        result
+    }
+
+    /// Async counterpart of [`ToolCall::run`]
+    pub(crate) async fn run_async<T: ResultCount>(
+        self,
+        future: impl Future<Output = Result<T, ToolError>>,
+    ) -> Result<T, ToolError> {
+        let result = future.instrument(self.span.clone()).await;
+        self.finish(&result);
+        result
+    }
+
+    fn finish<T: ResultCount>(&self, result: &Result<T, ToolError>) {
+        let duration_ms = self.started.elapsed().as_millis() as u64;
+        self.span.record("duration_ms", duration_ms);
+
+        if let Some(ref store) = self.usage {
+            let event = ToolUsageEvent {
+                tool: self.tool,
+                category: self.category,
+                result_count: result.as_ref().ok().map(ResultCount::result_count),
+                duration_ms,
+            };
+            if let Err(e) = store.record_tool_usage(&event) {
+                debug!("Failed to record usage of {}: {}", self.tool, e);
+            }
+        }
+
+        match result {
+            Ok(output) => {
+                let result_count = output.result_count();
+                self.span.record("result_count", result_count);
+                info!(parent: &self.span, duration_ms, result_count, "Tool call completed");
+            }
+            Err(e) => {
+                let error_category = e.category();
+                self.span.record("error_category", error_category);
+                match e {
+                    ToolError::InvalidParams(_) | ToolError::RateLimited { .. } => {
+                        info!(parent: &self.span, duration_ms, error_category, "Tool call rejected: {}", e)
+                    }
+                    _ => {
+                        warn!(parent: &self.span, duration_ms, error_category, "Tool call failed: {}", e)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Log output captured in memory
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture_json(f: impl FnOnce()) -> Vec<serde_json::Value> {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+
+        let output = captured.0.lock().unwrap().clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_query_hash_is_stable() {
+        assert_eq!(query_hash("load config"), query_hash(" load config "));
+        assert_ne!(query_hash("load config"), query_hash("save config"));
+        assert_eq!(query_hash("x").len(), 16);
+    }
+
+    #[test]
+    fn test_successful_call_logs_duration_and_count() {
+        let events = capture_json(|| {
+            let result = ToolCall::new("semantiq_explain", "parse").run(|| {
+                Ok(ImplementationsResponse {
+                    target: "Shape".to_string(),
+                    total_count: 0,
+                    search_time_ms: 0,
+                    implementations: Vec::new(),
+                })
+            });
+            assert!(result.is_ok());
+        });
+
+        let event = events.last().unwrap();
+        assert_eq!(event["fields"]["message"], "Tool call completed");
+        assert_eq!(event["fields"]["result_count"], 0);
+        assert!(event["fields"]["duration_ms"].is_u64());
+        assert_eq!(event["span"]["name"], "tool_call");
+        assert_eq!(event["span"]["tool"], "semantiq_explain");
+        assert_eq!(event["span"]["query_hash"], query_hash("parse"));
+    }
+
+    #[test]
+    fn test_failed_call_logs_error_category() {
+        let events = capture_json(|| {
+            let result: Result<SymbolExplanation, _> = ToolCall::new("semantiq_search", "q")
+                .run(|| Err(ToolError::Internal("boom".to_string())));
+            assert!(result.is_err());
+        });
+
+        let event = events.last().unwrap();
+        assert_eq!(event["level"], "WARN");
+        assert_eq!(event["fields"]["error_category"], "internal");
+        assert_eq!(event["fields"]["message"], "Tool call failed: boom");
+    }
+}
@@ -1,6 +1,10 @@
+mod content;
+mod instrumentation;
+mod rate_limit;
 pub mod server;
 pub mod tools;
 pub mod version_check;
 
+pub use content::ToolOutput;
 pub use server::SemantiqServer;
 pub use version_check::disable_update_check;
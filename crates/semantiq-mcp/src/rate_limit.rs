@@ -0,0 +1,234 @@
+//! Rate limiting of tool calls
+//!
+//! Each tool has a token bucket refilled at `requests_per_second` and holding
+//! at most `burst` tokens, plus a cap on the calls running at the same time.
+//! A client calling a tool in a tight loop is turned away with
+//! [`ToolError::RateLimited`] instead of starving the auto-indexer.
+
+use crate::tools::ToolError;
+use semantiq_index::LimitsConfig;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Delay suggested when a call is refused because too many are running
+const BUSY_RETRY_AFTER: Duration = Duration::from_millis(100);
+
+/// Token bucket refilled continuously
+struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(refill_per_sec: f64, burst: u32, now: Instant) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Self {
+            capacity,
+            refill_per_sec,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    /// Take a token, or return how long until one is available
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+/// Limits of one tool
+struct ToolLimiter {
+    requests_per_second: f64,
+    /// `None` when the tool is not throttled (`requests_per_second = 0`)
+    bucket: Option<Mutex<TokenBucket>>,
+    /// 0 for no cap
+    max_concurrent: usize,
+    running: AtomicUsize,
+}
+
+/// A call allowed to run; releases its concurrency slot when dropped
+pub(crate) struct ToolPermit(Option<Arc<ToolLimiter>>);
+
+impl Drop for ToolPermit {
+    fn drop(&mut self) {
+        if let Some(ref limiter) = self.0 {
+            limiter.running.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
+
+/// Rate limits of every tool, created from `[limits]` in `semantiq.toml`
+pub(crate) struct RateLimiter {
+    config: LimitsConfig,
+    tools: Mutex<HashMap<&'static str, Arc<ToolLimiter>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: &LimitsConfig) -> Self {
+        Self {
+            config: config.clone(),
+            tools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Upper bound of the `limit` parameter of the tools
+    pub(crate) fn max_results(&self) -> usize {
+        self.config.max_results.max(1)
+    }
+
+    /// Admit a call of `tool`, or refuse it with [`ToolError::RateLimited`]
+    pub(crate) fn acquire(&self, tool: &'static str) -> Result<ToolPermit, ToolError> {
+        self.acquire_at(tool, Instant::now())
+    }
+
+    fn acquire_at(&self, tool: &'static str, now: Instant) -> Result<ToolPermit, ToolError> {
+        if !self.config.enabled {
+            return Ok(ToolPermit(None));
+        }
+        let limiter = self.limiter(tool, now);
+
+        let running = limiter.running.fetch_add(1, Ordering::AcqRel);
+        // From here the permit releases the slot, including on refusal
+        let permit = ToolPermit(Some(Arc::clone(&limiter)));
+        if limiter.max_concurrent > 0 && running >= limiter.max_concurrent {
+            return Err(ToolError::RateLimited {
+                message: format!(
+                    "Rate limited: {} {} calls are already running",
+                    limiter.max_concurrent, tool
+                ),
+                retry_after_ms: BUSY_RETRY_AFTER.as_millis() as u64,
+            });
+        }
+
+        if let Some(ref bucket) = limiter.bucket {
+            let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(wait) = bucket.try_take(now) {
+                return Err(ToolError::RateLimited {
+                    message: format!(
+                        "Rate limited: {} accepts {} calls per second",
+                        tool, limiter.requests_per_second
+                    ),
+                    retry_after_ms: (wait.as_secs_f64() * 1000.0).ceil() as u64,
+                });
+            }
+        }
+
+        Ok(permit)
+    }
+
+    fn limiter(&self, tool: &'static str, now: Instant) -> Arc<ToolLimiter> {
+        let mut tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+        let limiter = tools.entry(tool).or_insert_with(|| {
+            let (requests_per_second, burst, max_concurrent) = self.config.for_tool(tool);
+            let throttled = requests_per_second.is_finite() && requests_per_second > 0.0;
+            Arc::new(ToolLimiter {
+                requests_per_second,
+                bucket: throttled
+                    .then(|| Mutex::new(TokenBucket::new(requests_per_second, burst, now))),
+                max_concurrent,
+                running: AtomicUsize::new(0),
+            })
+        });
+        Arc::clone(limiter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_index::ToolLimitsConfig;
+
+    fn limiter(requests_per_second: f64, burst: u32, max_concurrent: usize) -> RateLimiter {
+        RateLimiter::new(&LimitsConfig {
+            requests_per_second,
+            burst,
+            max_concurrent,
+            ..Default::default()
+        })
+    }
+
+    fn retry_after(result: Result<ToolPermit, ToolError>) -> u64 {
+        match result {
+            Err(ToolError::RateLimited { retry_after_ms, .. }) => retry_after_ms,
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("expected the call to be rate limited"),
+        }
+    }
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let limits = limiter(2.0, 3, 0);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limits.acquire_at("semantiq_search", start).is_ok());
+        }
+        assert_eq!(
+            retry_after(limits.acquire_at("semantiq_search", start)),
+            500
+        );
+
+        // Tools have separate buckets
+        assert!(limits.acquire_at("semantiq_deps", start).is_ok());
+
+        // One token is back after half a second
+        let later = start + Duration::from_millis(500);
+        assert!(limits.acquire_at("semantiq_search", later).is_ok());
+        assert!(limits.acquire_at("semantiq_search", later).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_calls_are_capped() {
+        let limits = limiter(0.0, 1, 2);
+        let now = Instant::now();
+
+        let first = limits.acquire_at("semantiq_search", now).unwrap();
+        let _second = limits.acquire_at("semantiq_search", now).unwrap();
+        assert_eq!(retry_after(limits.acquire_at("semantiq_search", now)), 100);
+
+        drop(first);
+        assert!(limits.acquire_at("semantiq_search", now).is_ok());
+    }
+
+    #[test]
+    fn test_tool_overrides_and_disabled_limits() {
+        let mut config = LimitsConfig {
+            requests_per_second: 1.0,
+            burst: 1,
+            ..Default::default()
+        };
+        config.tools.insert(
+            "semantiq_explain".to_string(),
+            ToolLimitsConfig {
+                burst: Some(2),
+                ..Default::default()
+            },
+        );
+        let limits = RateLimiter::new(&config);
+        let now = Instant::now();
+        assert!(limits.acquire_at("semantiq_explain", now).is_ok());
+        assert!(limits.acquire_at("semantiq_explain", now).is_ok());
+        assert!(limits.acquire_at("semantiq_explain", now).is_err());
+
+        config.enabled = false;
+        let limits = RateLimiter::new(&config);
+        for _ in 0..10 {
+            assert!(limits.acquire_at("semantiq_explain", now).is_ok());
+        }
+    }
+}
@@ -8,25 +8,199 @@ use rmcp::{
     service::{Peer, RequestContext, RoleServer},
     tool,
 };
-use semantiq_index::{AutoIndexer, IndexStore};
-use semantiq_retrieval::{RetrievalEngine, SearchOptions};
+use semantiq_index::{
+    AutoIndexer, GenerationWatcher, IndexPhase, IndexProgress, IndexStore, MigrationStep,
+    ProgressTracker, RecalibrationConfig, ReindexReport, SemantiqConfig,
+};
+use semantiq_retrieval::{
+    BatchSearchEntry, BatchSearchResponse, ComponentTreeResponse, ConfigUsageResponse, Deadline,
+    DependenciesResponse, DiffError, EntrypointsResponse, ExploreResponse, HistoryError,
+    ImplementationsResponse, IndexIssuesResponse, OutputFormat, OverviewResponse, QueryShape,
+    ReferencesResponse, RelatedFilesResponse, RetrievalEngine, RoutesResponse, SearchResults,
+    SemanticDiffResponse, SessionSummary, SymbolExplanation, ToMarkdown,
+};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
-
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::content::{ResourceLinks, ToolOutput};
+use crate::instrumentation::ToolCall;
+use crate::rate_limit::RateLimiter;
+use crate::tools::{
+    ReindexOutcome, SemantiqBatchSearch, SemantiqComponentTree, SemantiqConfigUsage, SemantiqDeps,
+    SemantiqDiff, SemantiqEntrypoints, SemantiqExplain, SemantiqExplore, SemantiqFindRefs,
+    SemantiqImplementations, SemantiqIndexIssues, SemantiqOverview, SemantiqReindex,
+    SemantiqRelated, SemantiqRoutes, SemantiqSavedSearch, SemantiqSearch, SemantiqSessionSummary,
+    ToolError,
+};
 use crate::version_check::{VersionCheckConfig, check_for_update};
 
+/// Minimum delay between two indexing progress notifications
+const PROGRESS_NOTIFY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay between two checks of the index generation
+const GENERATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum number of files changed by a single `semantiq_reindex` call
+const MAX_REINDEX_FILES: usize = 100;
+
+/// Maximum number of paths listed per section of a reindex report
+const MAX_REPORTED_PATHS: usize = 20;
+
+/// A deadline cancelled with the request: a cancelled search returns what
+/// it found. Abort the returned task once the search is done.
+fn cancellable_deadline(ct: CancellationToken) -> (Deadline, tokio::task::JoinHandle<()>) {
+    let deadline = Deadline::new();
+    if ct.is_cancelled() {
+        deadline.cancel();
+    }
+    let cancel = deadline.clone();
+    let cancellation = tokio::spawn(async move {
+        ct.cancelled().await;
+        cancel.cancel();
+    });
+    (deadline, cancellation)
+}
+
+/// Parse the `format` parameter of a tool, defaulting to markdown
+fn parse_output_format(format: Option<&str>) -> Result<OutputFormat, String> {
+    match format {
+        Some(f) => OutputFormat::parse(f)
+            .ok_or_else(|| format!("Invalid format '{}': expected 'markdown' or 'json'", f)),
+        None => Ok(OutputFormat::Markdown),
+    }
+}
+
+/// Human-readable one-line summary of indexing progress
+fn format_progress_message(progress: &IndexProgress) -> String {
+    match progress.phase {
+        IndexPhase::Idle => "Indexing not started".to_string(),
+        IndexPhase::Scanning => format!("Scanning project: {} files found", progress.scanned),
+        IndexPhase::Indexing => {
+            let mut message = format!(
+                "Indexing {}/{} files ({:.0}%)",
+                progress.processed,
+                progress.total,
+                progress.percent()
+            );
+            if let Some(eta_ms) = progress.eta_ms {
+                message.push_str(&format!(", ~{}s remaining", eta_ms.div_ceil(1000)));
+            }
+            message
+        }
+        IndexPhase::Complete => {
+            let warnings = match progress.warnings {
+                0 => String::new(),
+                n => format!(", {} warnings", n),
+            };
+            format!(
+                "Indexing complete: {} indexed, {} unchanged, {} errors{} in {:.1}s",
+                progress.indexed,
+                progress.skipped,
+                progress.errors,
+                warnings,
+                progress.elapsed_ms as f64 / 1000.0
+            )
+        }
+    }
+}
+
+/// Human-readable summary of an on-demand reindex
+fn format_reindex_report(pattern: Option<&str>, report: &ReindexReport) -> String {
+    let mut output = format!(
+        "Reindex of '{}': {} file(s) matched\n",
+        pattern.unwrap_or("."),
+        report.matched
+    );
+
+    if report.changed() == 0 && report.deferred == 0 {
+        output.push_str("\nAll matching files are up to date.\n");
+        return output;
+    }
+
+    let sections = [
+        ("Updated", &report.indexed),
+        ("Removed", &report.removed),
+        ("Failed", &report.errors),
+    ];
+    for (title, paths) in sections {
+        if paths.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("\n## {} ({})\n", title, paths.len()));
+        for path in paths.iter().take(MAX_REPORTED_PATHS) {
+            output.push_str(&format!("- {}\n", path));
+        }
+        if paths.len() > MAX_REPORTED_PATHS {
+            output.push_str(&format!(
+                "- ... and {} more\n",
+                paths.len() - MAX_REPORTED_PATHS
+            ));
+        }
+    }
+
+    output.push_str(&format!("\nUnchanged: {}\n", report.unchanged));
+    if report.deferred > 0 {
+        output.push_str(&format!(
+            "Deferred: {} changed file(s) over the limit of {}, left to the file watcher\n",
+            report.deferred, MAX_REINDEX_FILES
+        ));
+    }
+
+    output
+}
+
+/// What a server needs before it can answer requests in full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Readiness {
+    /// The index database answers queries
+    pub index_opened: bool,
+    /// The initial indexing pass is over, or the server does not index
+    pub initial_index_complete: bool,
+    /// The query embedding model was loaded, or found unavailable
+    pub embedding_model_ready: bool,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.index_opened && self.initial_index_complete && self.embedding_model_ready
+    }
+}
+
 #[derive(Clone)]
 pub struct SemantiqServer {
     engine: Arc<RetrievalEngine>,
     store: Arc<IndexStore>,
     auto_indexer: Option<Arc<Mutex<AutoIndexer>>>,
+    index_progress: ProgressTracker,
+    generation: Arc<GenerationWatcher>,
+    limits: Arc<RateLimiter>,
+    /// Follow tool responses with structured content (`[mcp]` in `semantiq.toml`)
+    structured_content: bool,
+    /// Background recalibration of search thresholds (`[recalibration]`)
+    recalibration: RecalibrationConfig,
+    /// Cancelled by [`SemantiqServer::shutdown`], stopping the auto-indexer
+    /// and the threshold recalibration
+    stopping: CancellationToken,
 }
 
 impl SemantiqServer {
     pub fn new(db_path: &Path, project_root: &str) -> Result<Self> {
+        Self::open(db_path, project_root, true)
+    }
+
+    /// Serve an existing index without watching or reindexing the project.
+    /// The index is kept up to date by running `semantiq index` separately,
+    /// so no background task outlives the server.
+    pub fn without_auto_indexer(db_path: &Path, project_root: &str) -> Result<Self> {
+        Self::open(db_path, project_root, false)
+    }
+
+    fn open(db_path: &Path, project_root: &str, auto_index: bool) -> Result<Self> {
         info!("Initializing Semantiq MCP server");
         info!("Database path: {:?}", db_path);
         info!("Project root: {}", project_root);
@@ -34,27 +208,71 @@ impl SemantiqServer {
         // Share a single IndexStore instance across all components
         let store = Arc::new(IndexStore::open(db_path)?);
 
-        // Check if parser version changed and prepare for full reindex if needed
-        let _ = store.check_and_prepare_for_reindex()?;
+        // Apply semantiq.toml (e.g. embedding quantization) before indexing starts
+        let config = match SemantiqConfig::load(Path::new(project_root)) {
+            Ok(config) => {
+                store.apply_config(&config)?;
+                config
+            }
+            Err(e) => {
+                warn!("Ignoring invalid configuration: {:#}", e);
+                SemantiqConfig::default()
+            }
+        };
+        if let Err(e) = config.register_languages(Path::new(project_root)) {
+            warn!("Custom languages or symbol kinds disabled: {:#}", e);
+        }
+
+        if auto_index {
+            // Check if parser version changed and prepare for full reindex if needed
+            let _ = store.check_and_prepare_for_reindex()?;
+        } else if store.needs_full_reindex()? {
+            // Nothing would rebuild the index if it were cleared here
+            warn!(
+                "Index {:?} was built by another parser version, run 'semantiq index --force'",
+                db_path
+            );
+        }
 
-        let engine = Arc::new(RetrievalEngine::new(Arc::clone(&store), project_root));
+        let engine = Arc::new(RetrievalEngine::from_config(
+            Arc::clone(&store),
+            project_root,
+            &config,
+        ));
 
         // Initialize auto-indexer with the same shared store
-        let auto_indexer = match AutoIndexer::new(Arc::clone(&store), PathBuf::from(project_root)) {
-            Ok(indexer) => {
+        let mut index_progress = ProgressTracker::new();
+        let auto_indexer = match auto_index.then(|| {
+            AutoIndexer::from_config(Arc::clone(&store), PathBuf::from(project_root), &config)
+        }) {
+            None => None,
+            Some(Ok(indexer)) => {
                 info!("Auto-indexing enabled");
+                // Keep our own handle: the indexer mutex is held during the initial pass
+                index_progress = indexer.progress();
                 Some(Arc::new(Mutex::new(indexer)))
             }
-            Err(e) => {
+            Some(Err(e)) => {
                 info!("Auto-indexing disabled: {}", e);
                 None
             }
         };
 
+        let generation = Arc::new(GenerationWatcher::spawn(
+            Arc::clone(&store),
+            GENERATION_POLL_INTERVAL,
+        ));
+
         Ok(Self {
             engine,
             store,
             auto_indexer,
+            index_progress,
+            generation,
+            limits: Arc::new(RateLimiter::new(&config.limits)),
+            structured_content: config.mcp.structured_content,
+            recalibration: config.recalibration,
+            stopping: CancellationToken::new(),
         })
     }
 
@@ -87,6 +305,61 @@ impl SemantiqServer {
         });
     }
 
+    /// Forward initial indexing progress to the MCP client as logging notifications.
+    /// Updates are throttled to one per second and stop once indexing completes.
+    fn spawn_progress_notifier(peer: Peer<RoleServer>, tracker: ProgressTracker) {
+        tokio::spawn(async move {
+            let mut rx = tracker.subscribe();
+            loop {
+                let progress = rx.borrow_and_update().clone();
+                if progress.phase != IndexPhase::Idle {
+                    let sent = peer
+                        .notify_logging_message(LoggingMessageNotificationParam {
+                            level: LoggingLevel::Info,
+                            logger: Some("semantiq.index".into()),
+                            data: serde_json::json!({
+                                "message": format_progress_message(&progress),
+                                "progress": progress,
+                            }),
+                        })
+                        .await;
+                    if sent.is_err() || progress.is_complete() {
+                        break;
+                    }
+                }
+
+                if rx.changed().await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(PROGRESS_NOTIFY_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Notify the MCP client each time the index generation changes, so it
+    /// knows that results it holds may be stale.
+    fn spawn_generation_notifier(peer: Peer<RoleServer>, watcher: &GenerationWatcher) {
+        let mut rx = watcher.subscribe();
+        tokio::spawn(async move {
+            while rx.changed().await.is_ok() {
+                let generation = *rx.borrow_and_update();
+                let sent = peer
+                    .notify_logging_message(LoggingMessageNotificationParam {
+                        level: LoggingLevel::Info,
+                        logger: Some("semantiq.index".into()),
+                        data: serde_json::json!({
+                            "message": format!("Index changed (generation {})", generation),
+                            "index_generation": generation,
+                        }),
+                    })
+                    .await;
+                if sent.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     pub fn store(&self) -> &Arc<IndexStore> {
         &self.store
     }
@@ -95,11 +368,139 @@ impl SemantiqServer {
         &self.engine
     }
 
+    /// Reindex files found stale at query time in the background.
+    /// No-op when auto-indexing is disabled.
+    pub fn reindex_stale_files(&self, rel_paths: Vec<String>) {
+        let Some(ref auto_indexer) = self.auto_indexer else {
+            return;
+        };
+        if rel_paths.is_empty() {
+            return;
+        }
+
+        let indexer = Arc::clone(auto_indexer);
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                let indexer = indexer.blocking_lock();
+                indexer.reindex_paths(&rel_paths)
+            })
+            .await;
+
+            match result {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("On-demand reindex failed: {}", e),
+                Err(e) => error!("On-demand reindex task panicked: {}", e),
+            }
+        });
+    }
+
+    /// Watcher of the index generation, for clients to learn when the index
+    /// changed
+    pub fn index_generation(&self) -> &GenerationWatcher {
+        &self.generation
+    }
+
+    /// Latest snapshot of the initial indexing pass
+    pub fn index_progress(&self) -> IndexProgress {
+        self.index_progress.snapshot()
+    }
+
+    /// Whether the server can answer requests in full, for readiness probes
+    pub fn readiness(&self) -> Readiness {
+        Readiness {
+            index_opened: self.store.ping().is_ok(),
+            initial_index_complete: self.auto_indexer.is_none()
+                || self.index_progress.snapshot().is_complete(),
+            embedding_model_ready: self.engine.embedding_model_ready(),
+        }
+    }
+
+    /// Stop the auto-indexer once the file events it was told about are
+    /// indexed, and checkpoint the write-ahead log of the index.
+    ///
+    /// An initial pass still running is not waited for: the files it did not
+    /// get to are indexed at the next start.
+    pub async fn shutdown(&self) {
+        self.stopping.cancel();
+
+        if let Some(ref auto_indexer) = self.auto_indexer {
+            if self.index_progress.snapshot().is_complete() {
+                // Waits for the batch being processed, if any
+                let indexer = Arc::clone(auto_indexer).lock_owned().await;
+                let flushed = tokio::task::spawn_blocking(move || indexer.process_events()).await;
+                match flushed {
+                    Ok(Ok(_)) => info!("Pending file changes indexed"),
+                    Ok(Err(e)) => error!("Failed to index pending file changes: {}", e),
+                    Err(e) => error!("Auto-indexer flush task panicked: {}", e),
+                }
+            } else {
+                info!("Initial indexing interrupted, it resumes at the next start");
+            }
+        }
+
+        match self.engine.async_store().checkpoint_wal().await {
+            Ok(()) => debug!("Index write-ahead log checkpointed"),
+            Err(e) => warn!("Failed to checkpoint the index: {}", e),
+        }
+    }
+
+    /// Load the query embedding model in the background, so that startup
+    /// does not wait for it and the first semantic search usually does not
+    pub fn start_model_preload(&self) {
+        let engine = Arc::clone(&self.engine);
+        tokio::task::spawn_blocking(move || {
+            if engine.preload_embedding_model() {
+                info!("Embedding model loaded, semantic search ready");
+            } else {
+                warn!("Embedding model unavailable, semantic search disabled");
+            }
+        });
+    }
+
+    /// Recalibrate the semantic search thresholds in the background as
+    /// searches record observations, checking every `check_interval_secs`
+    /// whether enough were recorded or the calibration expired
+    pub fn start_recalibration(&self) {
+        if !self.recalibration.enabled {
+            return;
+        }
+        let engine = Arc::clone(&self.engine);
+        let config = self.recalibration.clone();
+        let stopping = self.stopping.clone();
+
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(config.check_interval_secs.max(1)));
+            // The first tick completes at once, leave startup to indexing
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = stopping.cancelled() => break,
+                }
+
+                let engine = Arc::clone(&engine);
+                let config = config.clone();
+                match tokio::task::spawn_blocking(move || engine.recalibrate_if_due(&config)).await
+                {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => warn!("Threshold recalibration failed: {}", e),
+                    Err(e) => warn!("Threshold recalibration task panicked: {}", e),
+                }
+            }
+        });
+
+        info!("Threshold recalibration scheduled");
+    }
+
     /// Start the auto-indexing background task
     /// Performs initial indexing first, then watches for changes
     pub fn start_auto_indexer(&self) {
         if let Some(ref auto_indexer) = self.auto_indexer {
             let indexer = Arc::clone(auto_indexer);
+            let engine = Arc::clone(&self.engine);
+            let stopping = self.stopping.clone();
 
             tokio::spawn(async move {
                 // Perform initial indexing in a blocking task
@@ -120,6 +521,21 @@ impl SemantiqServer {
                         } else if result.scanned > 0 {
                             info!("Index up to date: {} files checked", result.scanned);
                         }
+
+                        // Seed semantic thresholds until live searches calibrate them
+                        if result.indexed > 0 || !engine.has_calibrated_thresholds() {
+                            let bootstrap =
+                                tokio::task::spawn_blocking(move || engine.bootstrap_thresholds())
+                                    .await;
+                            match bootstrap {
+                                Ok(Ok(true)) => {
+                                    info!("Calibrated search thresholds from the index")
+                                }
+                                Ok(Ok(false)) => {}
+                                Ok(Err(e)) => warn!("Threshold bootstrap failed: {}", e),
+                                Err(e) => warn!("Threshold bootstrap task panicked: {}", e),
+                            }
+                        }
                     }
                     Ok(Err(e)) => {
                         tracing::error!("Initial indexing failed: {}", e);
@@ -131,14 +547,32 @@ impl SemantiqServer {
 
                 // Then start watching for changes
                 let mut interval = tokio::time::interval(Duration::from_secs(2));
+                let mut migrating = true;
 
                 loop {
-                    interval.tick().await;
+                    tokio::select! {
+                        _ = interval.tick() => {}
+                        _ = stopping.cancelled() => break,
+                    }
 
                     let indexer = indexer.lock().await;
                     if let Err(e) = indexer.process_events() {
                         tracing::error!("Auto-indexer error: {}", e);
                     }
+
+                    // Re-embed the index for a new embedding model between
+                    // file events, without waiting while batches remain
+                    if migrating {
+                        match indexer.migrate_embeddings() {
+                            Ok(MigrationStep::Done) => migrating = false,
+                            Ok(MigrationStep::Paused) => {}
+                            Ok(_) => interval.reset_immediately(),
+                            Err(e) => {
+                                tracing::error!("Embedding migration stopped: {}", e);
+                                migrating = false;
+                            }
+                        }
+                    }
                 }
             });
 
@@ -147,297 +581,1393 @@ impl SemantiqServer {
     }
 }
 
-#[tool(tool_box)]
 impl SemantiqServer {
-    #[tool(
-        name = "semantiq_search",
-        description = "Search for code patterns, symbols, or text in the codebase. Returns relevant matches with file paths and line numbers. Supports filtering: min_score (0.0-1.0, default 0.35), file_type (comma-separated extensions like 'rs,ts,py'), symbol_kind (function,method,class,struct,enum,interface,trait,module,variable,constant,type)."
-    )]
-    pub async fn semantiq_search(
-        &self,
-        #[tool(param)] query: String,
-        #[tool(param)] limit: Option<usize>,
-        #[tool(param)] min_score: Option<f32>,
-        #[tool(param)] file_type: Option<String>,
-        #[tool(param)] symbol_kind: Option<String>,
-    ) -> Result<String, String> {
-        debug!(
-            query = %query,
-            limit = ?limit,
-            file_type = ?file_type,
-            symbol_kind = ?symbol_kind,
-            "semantiq_search called"
-        );
-
-        // Validate query
-        let query = query.trim();
-        if query.is_empty() {
-            return Err("Query cannot be empty".to_string());
-        }
-        if query.len() > 500 {
-            return Err("Query exceeds maximum length of 500 characters".to_string());
-        }
-
-        let limit = limit.unwrap_or(20).min(1000);
-
-        // Build SearchOptions
-        let mut options = SearchOptions::new();
-
-        if let Some(score) = min_score {
-            options = options.with_min_score(score);
-        }
+    /// Trace a tool call and count it in the usage statistics of the index
+    fn tool_call(&self, tool: &'static str, input: &str) -> ToolCall {
+        ToolCall::new(tool, input).recorded_in(Arc::clone(&self.store))
+    }
 
-        if let Some(ref ft) = file_type {
-            let types = SearchOptions::parse_csv(ft);
-            if !types.is_empty() {
-                options = options.with_file_types(types);
-            }
-        }
+    /// Tool response rendered in `format`, followed by its structured
+    /// content unless disabled
+    fn output<T>(&self, format: OutputFormat, response: &T) -> ToolOutput
+    where
+        T: serde::Serialize + ToMarkdown + ResourceLinks,
+    {
+        ToolOutput::render(format, response, self.structured_content)
+    }
 
-        if let Some(ref sk) = symbol_kind {
-            let kinds = SearchOptions::parse_csv(sk);
-            if !kinds.is_empty() {
-                options = options.with_symbol_kinds(kinds);
-            }
-        }
+    /// Run `call` (one of the `run_*` methods) off the async runtime, once a
+    /// slot of the index queue (`[limits] index_queue`) is free: tools query
+    /// SQLite synchronously and would block the threads serving the other
+    /// requests.
+    pub async fn blocking<T, F>(&self, call: F) -> Result<T, ToolError>
+    where
+        F: FnOnce(&SemantiqServer) -> Result<T, ToolError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let server = self.clone();
+        self.engine
+            .async_store()
+            .spawn_blocking(move || call(&server))
+            .await
+            .map_err(|e| {
+                error!("Tool call failed: {}", e);
+                ToolError::Internal("Tool call failed: an internal error occurred".to_string())
+            })?
+    }
 
-        match self.engine.search(query, limit, Some(options)) {
-            Ok(results) => {
-                let mut output = format!(
-                    "Found {} results for '{}' ({} ms)\n\n",
-                    results.total_count, query, results.search_time_ms
-                );
+    /// Run a search until `deadline`, scheduling files with stale results for
+    /// reindexing
+    pub fn run_search(
+        &self,
+        request: &SemantiqSearch,
+        deadline: Deadline,
+    ) -> Result<SearchResults, ToolError> {
+        self.tool_call("semantiq_search", &request.query)
+            .with_category(QueryShape::classify(&request.query).as_str())
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_search")?;
+                self.execute_search(request, deadline)
+            })
+    }
 
-                for result in &results.results {
-                    output.push_str(&format!(
-                        "📄 {}\n   Lines {}-{} | Score: {:.2}\n",
-                        result.file_path, result.start_line, result.end_line, result.score
-                    ));
-
-                    if let Some(ref symbol_name) = result.metadata.symbol_name {
-                        output.push_str(&format!(
-                            "   Symbol: {} ({})\n",
-                            symbol_name,
-                            result.metadata.symbol_kind.as_deref().unwrap_or("unknown")
+    /// Run a saved query with the parameters of the request filled in
+    pub fn run_saved_search(
+        &self,
+        request: &SemantiqSavedSearch,
+        deadline: Deadline,
+    ) -> Result<SearchResults, ToolError> {
+        self.tool_call("semantiq_saved_search", &request.name)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_saved_search")?;
+                let name = request.validated_name()?;
+
+                let saved = match self.store.get_saved_query(name) {
+                    Ok(Some(saved)) => saved,
+                    Ok(None) => {
+                        let names: Vec<String> = self
+                            .store
+                            .list_saved_queries()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|q| q.name)
+                            .collect();
+                        let available = if names.is_empty() {
+                            "No queries are saved yet (see `semantiq query save`)".to_string()
+                        } else {
+                            format!("Saved queries: {}", names.join(", "))
+                        };
+                        return Err(ToolError::InvalidParams(format!(
+                            "Unknown saved query '{}'. {}",
+                            name, available
+                        )));
+                    }
+                    Err(e) => {
+                        error!("Failed to load saved query {}: {}", name, e);
+                        return Err(ToolError::Internal(
+                            "Saved search failed: an internal error occurred".to_string(),
                         ));
                     }
+                };
 
-                    let snippet: String = result.content.chars().take(200).collect();
-                    output.push_str(&format!("   ```\n   {}\n   ```\n\n", snippet.trim()));
-                }
-
-                Ok(output)
-            }
-            Err(e) => {
-                error!("Search failed: {}", e);
-                Err("Search failed: an internal error occurred".to_string())
-            }
-        }
+                self.execute_search(&request.search_request(&saved)?, deadline)
+            })
     }
 
-    #[tool(
-        name = "semantiq_find_refs",
-        description = "Find all references to a symbol including definitions and usages. Useful for understanding how a function or class is used."
-    )]
-    pub async fn semantiq_find_refs(
+    /// Run the searches of a batch concurrently, each through the index
+    /// queue, until one deadline shared by all of them. A failed search is
+    /// reported in its entry without failing the others.
+    pub async fn run_batch_search(
         &self,
-        #[tool(param)] symbol: String,
-        #[tool(param)] limit: Option<usize>,
-    ) -> Result<String, String> {
-        debug!(symbol = %symbol, limit = ?limit, "semantiq_find_refs called");
-
-        // Validate symbol input
-        let symbol = symbol.trim().to_string();
-        if symbol.is_empty() {
-            return Err("Symbol name cannot be empty".to_string());
-        }
-        if symbol.len() > 500 {
-            return Err("Symbol name exceeds maximum length of 500 characters".to_string());
-        }
-
-        let limit = limit.unwrap_or(50).min(1000);
-
-        match self.engine.find_references(&symbol, limit) {
-            Ok(results) => {
-                let mut output = format!(
-                    "Found {} references to '{}' ({} ms)\n\n",
-                    results.total_count, symbol, results.search_time_ms
-                );
-
-                let definitions: Vec<_> = results
-                    .results
-                    .iter()
-                    .filter(|r| {
-                        r.metadata
-                            .match_type
-                            .as_ref()
-                            .map(|t| t == "definition")
-                            .unwrap_or(false)
-                    })
-                    .collect();
-
-                let usages: Vec<_> = results
-                    .results
+        request: &SemantiqBatchSearch,
+        deadline: Deadline,
+    ) -> Result<BatchSearchResponse, ToolError> {
+        let input: Vec<&str> = request.queries.iter().map(|q| q.query.as_str()).collect();
+        self.tool_call("semantiq_batch_search", &input.join("\n"))
+            .run_async(async {
+                let _permit = self.limits.acquire("semantiq_batch_search")?;
+                let queries = request.validated_queries()?;
+                let start = Instant::now();
+                let deadline = self.engine.start_deadline(&deadline);
+
+                let tasks: Vec<_> = queries
                     .iter()
-                    .filter(|r| {
-                        r.metadata
-                            .match_type
-                            .as_ref()
-                            .map(|t| t != "definition")
-                            .unwrap_or(true)
+                    .map(|query| {
+                        let server = self.clone();
+                        let query = query.clone();
+                        let deadline = deadline.clone();
+                        tokio::spawn(async move {
+                            server
+                                .blocking(move |server| server.execute_search(&query, deadline))
+                                .await
+                        })
                     })
                     .collect();
 
-                if !definitions.is_empty() {
-                    output.push_str("## Definitions\n\n");
-                    for def in &definitions {
-                        output.push_str(&format!(
-                            "📍 {}:{}\n   {}\n\n",
-                            def.file_path,
-                            def.start_line,
-                            def.content.lines().next().unwrap_or("")
-                        ));
-                    }
+                let mut searches = Vec::with_capacity(tasks.len());
+                for (query, task) in queries.iter().zip(tasks) {
+                    let outcome = task.await.unwrap_or_else(|e| {
+                        error!("Batch search task failed: {}", e);
+                        Err(ToolError::Internal(
+                            "Search failed: an internal error occurred".to_string(),
+                        ))
+                    });
+                    let (results, error) = match outcome {
+                        Ok(results) => (Some(results), None),
+                        Err(e) => (None, Some(e.to_string())),
+                    };
+                    searches.push(BatchSearchEntry {
+                        query: query.query.trim().to_string(),
+                        results,
+                        error,
+                    });
                 }
 
-                if !usages.is_empty() {
-                    output.push_str(&format!("## Usages ({} found)\n\n", usages.len()));
-                    for usage in usages.iter().take(20) {
-                        output.push_str(&format!(
-                            "📎 {}:{}\n   {}\n\n",
-                            usage.file_path,
-                            usage.start_line,
-                            usage.content.trim()
-                        ));
-                    }
+                Ok(BatchSearchResponse {
+                    error_count: searches.iter().filter(|s| s.error.is_some()).count(),
+                    search_time_ms: start.elapsed().as_millis() as u64,
+                    searches,
+                })
+            })
+            .await
+    }
 
-                    if usages.len() > 20 {
-                        output.push_str(&format!("... and {} more usages\n", usages.len() - 20));
-                    }
-                }
+    /// Run a validated search, scheduling files with stale results for
+    /// reindexing
+    fn execute_search(
+        &self,
+        request: &SemantiqSearch,
+        deadline: Deadline,
+    ) -> Result<SearchResults, ToolError> {
+        let query = request.validated_query()?;
+        let options = request.options()?.with_deadline(deadline);
+        let limit = request.effective_limit().min(self.limits.max_results());
 
-                Ok(output)
+        match self.engine.search(query, limit, Some(options)) {
+            Ok(results) => {
+                self.reindex_stale_files(results.stale_files.clone());
+                Ok(results)
             }
             Err(e) => {
-                error!("Find references failed: {}", e);
-                Err("Find references failed: an internal error occurred".to_string())
+                error!("Search failed: {}", e);
+                Err(ToolError::Internal(
+                    "Search failed: an internal error occurred".to_string(),
+                ))
             }
         }
     }
 
-    #[tool(
-        name = "semantiq_deps",
-        description = "Analyze the dependency graph for a file. Shows what the file imports and what other files import it."
-    )]
-    pub async fn semantiq_deps(&self, #[tool(param)] file_path: String) -> Result<String, String> {
-        debug!(file = %file_path, "semantiq_deps called");
-
-        // Validate file_path input
-        let file_path = file_path.trim().to_string();
-        if file_path.is_empty() {
-            return Err("File path cannot be empty".to_string());
-        }
-        if file_path.len() > 500 {
-            return Err("File path exceeds maximum length of 500 characters".to_string());
-        }
-        // Reject path traversal attempts
-        if file_path.contains("..") {
-            return Err("File path must not contain '..'".to_string());
-        }
-
-        let mut output = format!("Dependency analysis for '{}'\n\n", file_path);
-
-        match self.engine.get_dependencies(&file_path) {
-            Ok(deps) => {
-                output.push_str(&format!("## Imports ({} dependencies)\n\n", deps.len()));
-                for dep in &deps {
-                    output.push_str(&format!("→ {}", dep.target_path));
-                    if let Some(ref name) = dep.import_name {
-                        output.push_str(&format!(" (as {})", name));
+    /// Find the definitions and usages of a symbol
+    pub fn run_find_refs(
+        &self,
+        request: &SemantiqFindRefs,
+    ) -> Result<ReferencesResponse, ToolError> {
+        self.tool_call("semantiq_find_refs", &request.symbol)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_find_refs")?;
+                let symbol = request.validated_symbol()?;
+                let mut options = request.options()?;
+                options.limit = options.limit.min(self.limits.max_results());
+
+                let found = self
+                    .engine
+                    .find_references(symbol, &options)
+                    .and_then(|results| {
+                        let links = if options.cross_language && options.as_of.is_none() {
+                            self.engine.cross_language_links(symbol)?
+                        } else {
+                            Vec::new()
+                        };
+                        Ok(ReferencesResponse {
+                            links,
+                            as_of: options.as_of,
+                            ..ReferencesResponse::from(results)
+                        })
+                    });
+                found.map_err(|e| match e.downcast_ref::<HistoryError>() {
+                    Some(_) => ToolError::InvalidParams(e.to_string()),
+                    None => {
+                        error!("Find references failed: {}", e);
+                        ToolError::Internal(
+                            "Find references failed: an internal error occurred".to_string(),
+                        )
                     }
-                    output.push_str(&format!(" [{}]\n", dep.kind));
-                }
-                output.push('\n');
-            }
-            Err(e) => {
-                output.push_str(&format!("Could not analyze imports: {}\n\n", e));
-            }
-        }
+                })
+            })
+    }
 
-        match self.engine.get_dependents(&file_path) {
-            Ok(deps) => {
-                output.push_str(&format!("## Imported by ({} files)\n\n", deps.len()));
-                for dep in &deps {
-                    output.push_str(&format!("← {}\n", dep.target_path));
-                }
-            }
-            Err(e) => {
-                output.push_str(&format!("Could not analyze dependents: {}\n", e));
+    /// Get the imports and dependents of a file
+    pub fn run_deps(&self, request: &SemantiqDeps) -> Result<DependenciesResponse, ToolError> {
+        self.tool_call("semantiq_deps", &request.file_path).run(|| {
+            let _permit = self.limits.acquire("semantiq_deps")?;
+            let file_path = request.validated_path()?;
+
+            if let Some(symbol) = request.validated_symbol()? {
+                return Ok(DependenciesResponse::for_symbol(
+                    file_path,
+                    symbol,
+                    self.engine.get_symbol_dependencies(file_path, symbol),
+                    self.engine.get_symbol_dependents(file_path, symbol),
+                ));
             }
-        }
 
-        Ok(output)
+            let package = self.engine.file_package(file_path).unwrap_or_else(|e| {
+                warn!("Failed to get the package of {}: {}", file_path, e);
+                None
+            });
+            let compilation = self.engine.file_compilation(file_path).unwrap_or_else(|e| {
+                warn!("Failed to get the compilation of {}: {}", file_path, e);
+                None
+            });
+            Ok(DependenciesResponse::new(
+                file_path,
+                self.engine.get_dependencies(file_path),
+                self.engine.get_dependents(file_path),
+            )
+            .with_package(package)
+            .with_compilation(compilation))
+        })
     }
 
-    #[tool(
-        name = "semantiq_explain",
-        description = "Get a detailed explanation of a symbol including its definition, documentation, usage patterns, and related symbols."
-    )]
-    pub async fn semantiq_explain(&self, #[tool(param)] symbol: String) -> Result<String, String> {
-        debug!(symbol = %symbol, "semantiq_explain called");
-
-        // Validate symbol input
-        let symbol = symbol.trim().to_string();
-        if symbol.is_empty() {
-            return Err("Symbol name cannot be empty".to_string());
-        }
-        if symbol.len() > 500 {
-            return Err("Symbol name exceeds maximum length of 500 characters".to_string());
-        }
-
-        match self.engine.explain_symbol(&symbol) {
-            Ok(explanation) => {
-                if !explanation.found {
-                    return Ok(format!("Symbol '{}' not found in the index.", symbol));
+    /// Explain a symbol from its definitions, usages and related symbols,
+    /// or from its definitions at an earlier index generation
+    pub fn run_explain(&self, request: &SemantiqExplain) -> Result<SymbolExplanation, ToolError> {
+        self.tool_call("semantiq_explain", &request.symbol).run(|| {
+            let _permit = self.limits.acquire("semantiq_explain")?;
+            let symbol = request.validated_symbol()?;
+
+            let explained = match request.as_of {
+                Some(generation) => self.engine.explain_symbol_as_of(symbol, generation),
+                None => self.engine.explain_symbol(symbol),
+            };
+            explained.map_err(|e| match e.downcast_ref::<HistoryError>() {
+                Some(_) => ToolError::InvalidParams(e.to_string()),
+                None => {
+                    error!("Explain failed: {}", e);
+                    ToolError::Internal("Explain failed: an internal error occurred".to_string())
                 }
+            })
+        })
+    }
 
-                let mut output = format!("# Symbol: {}\n\n", explanation.name);
-
-                output.push_str(&format!(
-                    "Found {} definition(s), {} usage(s)\n\n",
-                    explanation.definitions.len(),
-                    explanation.usage_count
-                ));
+    /// List the types implementing or extending a trait, interface or base type
+    pub fn run_implementations(
+        &self,
+        request: &SemantiqImplementations,
+    ) -> Result<ImplementationsResponse, ToolError> {
+        self.tool_call("semantiq_implementations", &request.symbol)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_implementations")?;
+                let symbol = request.validated_symbol()?;
+                let limit = request.effective_limit().min(self.limits.max_results());
+
+                self.engine
+                    .find_implementations(symbol, limit)
+                    .map_err(|e| {
+                        error!("Find implementations failed: {}", e);
+                        ToolError::Internal(
+                            "Find implementations failed: an internal error occurred".to_string(),
+                        )
+                    })
+            })
+    }
 
-                for (i, def) in explanation.definitions.iter().enumerate() {
-                    output.push_str(&format!("## Definition {} ({})\n", i + 1, def.kind));
-                    output.push_str(&format!(
-                        "📄 {}:{}-{}\n\n",
-                        def.file_path, def.start_line, def.end_line
-                    ));
+    /// Build the render hierarchy of a React component
+    pub fn run_component_tree(
+        &self,
+        request: &SemantiqComponentTree,
+    ) -> Result<ComponentTreeResponse, ToolError> {
+        self.tool_call("semantiq_component_tree", &request.component)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_component_tree")?;
+                let component = request.validated_component()?;
+
+                self.engine
+                    .component_tree(component, request.effective_depth())
+                    .map_err(|e| {
+                        error!("Component tree failed: {}", e);
+                        ToolError::Internal(
+                            "Component tree failed: an internal error occurred".to_string(),
+                        )
+                    })
+            })
+    }
 
-                    if let Some(ref sig) = def.signature {
-                        output.push_str(&format!("```\n{}\n```\n\n", sig));
-                    }
+    /// Suggest the files likely to need editing together with a file
+    pub fn run_related(
+        &self,
+        request: &SemantiqRelated,
+    ) -> Result<RelatedFilesResponse, ToolError> {
+        self.tool_call("semantiq_related", &request.file_path)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_related")?;
+                let file_path = request.validated_path()?;
+                let limit = request.effective_limit().min(self.limits.max_results());
+
+                self.engine
+                    .find_related_files(file_path, limit)
+                    .map_err(|e| {
+                        error!("Find related files failed: {}", e);
+                        ToolError::Internal(
+                            "Find related files failed: an internal error occurred".to_string(),
+                        )
+                    })
+            })
+    }
 
-                    if let Some(ref doc) = def.doc_comment {
-                        output.push_str(&format!("**Documentation:**\n{}\n\n", doc));
-                    }
-                }
+    /// Map a directory: languages, lines, top symbols and entry files of the
+    /// directory and of its subdirectories
+    pub fn run_overview(&self, request: &SemantiqOverview) -> Result<OverviewResponse, ToolError> {
+        let path = request.path.as_deref().unwrap_or(".");
+        self.tool_call("semantiq_overview", path).run(|| {
+            let _permit = self.limits.acquire("semantiq_overview")?;
+            let path = request.validated_path()?;
+
+            self.engine.overview(path).map_err(|e| {
+                error!("Overview failed: {}", e);
+                ToolError::Internal("Overview failed: an internal error occurred".to_string())
+            })
+        })
+    }
 
-                if !explanation.related_symbols.is_empty() {
-                    output.push_str("## Related Symbols\n\n");
-                    for related in explanation.related_symbols.iter().take(10) {
-                        output.push_str(&format!("- {}\n", related));
-                    }
-                }
+    /// List the entries of a directory with their language, symbols,
+    /// indexing time and freshness, or why they are left out of the index
+    pub fn run_explore(&self, request: &SemantiqExplore) -> Result<ExploreResponse, ToolError> {
+        let path = request.path.as_deref().unwrap_or(".");
+        self.tool_call("semantiq_explore", path).run(|| {
+            let _permit = self.limits.acquire("semantiq_explore")?;
+            let path = request.validated_path()?;
+            let limit = request.effective_limit().min(self.limits.max_results());
+
+            self.engine
+                .explore(path, limit)
+                .map_err(|e| {
+                    error!("Explore failed: {}", e);
+                    ToolError::Internal("Explore failed: an internal error occurred".to_string())
+                })?
+                .ok_or_else(|| {
+                    ToolError::InvalidParams(format!("No directory '{}' in the project", path))
+                })
+        })
+    }
 
-                Ok(output)
+    /// List the main functions, HTTP routes, CLI subcommands and library
+    /// exports of the project or of a directory
+    pub fn run_entrypoints(
+        &self,
+        request: &SemantiqEntrypoints,
+    ) -> Result<EntrypointsResponse, ToolError> {
+        let path = request.path.as_deref().unwrap_or(".");
+        self.tool_call("semantiq_entrypoints", path).run(|| {
+            let _permit = self.limits.acquire("semantiq_entrypoints")?;
+            let kind = request.validated_kind()?;
+            let path = request.validated_path()?;
+            let limit = request.effective_limit().min(self.limits.max_results());
+
+            self.engine.entrypoints(kind, path, limit).map_err(|e| {
+                error!("Entry points failed: {}", e);
+                ToolError::Internal("Entry points failed: an internal error occurred".to_string())
+            })
+        })
+    }
+
+    /// List the HTTP routes matching a method and path, with their handlers
+    pub fn run_routes(&self, request: &SemantiqRoutes) -> Result<RoutesResponse, ToolError> {
+        let path = request.path.as_deref().unwrap_or("");
+        self.tool_call("semantiq_routes", path).run(|| {
+            let _permit = self.limits.acquire("semantiq_routes")?;
+            let (method, path) = request.validated_route()?;
+            let limit = request.effective_limit().min(self.limits.max_results());
+
+            self.engine
+                .routes(method.as_deref(), path.as_deref(), limit)
+                .map_err(|e| {
+                    error!("Routes failed: {}", e);
+                    ToolError::Internal("Routes failed: an internal error occurred".to_string())
+                })
+        })
+    }
+
+    /// List the reads and writes of an environment variable or
+    /// configuration key
+    pub fn run_config_usage(
+        &self,
+        request: &SemantiqConfigUsage,
+    ) -> Result<ConfigUsageResponse, ToolError> {
+        self.tool_call("semantiq_config_usage", &request.name)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_config_usage")?;
+                let name = request.validated_name()?;
+                let source = request.validated_source()?;
+                let access = request.validated_access()?;
+                let limit = request.effective_limit().min(self.limits.max_results());
+
+                self.engine
+                    .config_usage(name, source, access, limit)
+                    .map_err(|e| {
+                        error!("Config usage failed: {}", e);
+                        ToolError::Internal(
+                            "Config usage failed: an internal error occurred".to_string(),
+                        )
+                    })
+            })
+    }
+
+    /// List the problems met while indexing files
+    pub fn run_index_issues(
+        &self,
+        request: &SemantiqIndexIssues,
+    ) -> Result<IndexIssuesResponse, ToolError> {
+        let kind = request.kind.as_deref().unwrap_or("");
+        self.tool_call("semantiq_index_issues", kind).run(|| {
+            let _permit = self.limits.acquire("semantiq_index_issues")?;
+            let kind = request.validated_kind()?;
+            let limit = request.effective_limit().min(self.limits.max_results());
+
+            self.engine.index_issues(kind, limit).map_err(|e| {
+                error!("Index issues failed: {}", e);
+                ToolError::Internal("Index issues failed: an internal error occurred".to_string())
+            })
+        })
+    }
+
+    /// List the searches of a session and the files they surfaced
+    pub fn run_session_summary(
+        &self,
+        request: &SemantiqSessionSummary,
+    ) -> Result<SessionSummary, ToolError> {
+        self.tool_call("semantiq_session_summary", &request.session_id)
+            .run(|| {
+                let _permit = self.limits.acquire("semantiq_session_summary")?;
+                let session_id = request.validated_session_id()?;
+
+                self.engine.session_summary(session_id).ok_or_else(|| {
+                    ToolError::InvalidParams(format!(
+                        "Unknown session '{}': sessions start with the first semantiq_search given their session_id",
+                        session_id
+                    ))
+                })
+            })
+    }
+
+    pub fn run_diff(&self, request: &SemantiqDiff) -> Result<SemanticDiffResponse, ToolError> {
+        self.tool_call("semantiq_diff", &request.from).run(|| {
+            let _permit = self.limits.acquire("semantiq_diff")?;
+            let (from, to) = request.validated_revisions()?;
+
+            self.engine
+                .semantic_diff(from, to)
+                .map_err(|e| match e.downcast_ref::<DiffError>() {
+                    Some(DiffError::GitUnavailable(_)) => ToolError::Unavailable(e.to_string()),
+                    Some(_) => ToolError::InvalidParams(e.to_string()),
+                    None => {
+                        error!("Semantic diff failed: {}", e);
+                        ToolError::Internal(
+                            "Semantic diff failed: an internal error occurred".to_string(),
+                        )
+                    }
+                })
+        })
+    }
+
+    /// Reindex changed files matching the request, at most
+    /// [`MAX_REINDEX_FILES`] per call
+    pub async fn run_reindex(
+        &self,
+        request: &SemantiqReindex,
+    ) -> Result<ReindexOutcome, ToolError> {
+        self.tool_call(
+            "semantiq_reindex",
+            request.path.as_deref().unwrap_or_default(),
+        )
+        .run_async(async {
+            let _permit = self.limits.acquire("semantiq_reindex")?;
+            let pattern = request.validated_path()?.map(str::to_string);
+
+            let Some(ref auto_indexer) = self.auto_indexer else {
+                return Err(ToolError::Unavailable(
+                    "Reindexing is unavailable: auto-indexing is disabled".to_string(),
+                ));
+            };
+
+            // The indexer is busy with the initial pass, which covers every file anyway
+            let progress = self.index_progress.snapshot();
+            if matches!(progress.phase, IndexPhase::Scanning | IndexPhase::Indexing) {
+                return Ok(ReindexOutcome::InitialIndexing(progress));
             }
-            Err(e) => {
-                error!("Explain failed: {}", e);
-                Err("Explain failed: an internal error occurred".to_string())
+
+            let indexer = Arc::clone(auto_indexer);
+            let result = tokio::task::spawn_blocking(move || {
+                let indexer = indexer.blocking_lock();
+                indexer.reindex_matching(pattern.as_deref(), MAX_REINDEX_FILES)
+            })
+            .await;
+
+            match result {
+                Ok(Ok(report)) => Ok(ReindexOutcome::Completed(report)),
+                Ok(Err(e)) => {
+                    error!("Reindex failed: {}", e);
+                    Err(ToolError::Internal(format!("Reindex failed: {}", e)))
+                }
+                Err(e) => {
+                    error!("Reindex task panicked: {}", e);
+                    Err(ToolError::Internal(
+                        "Reindex failed: an internal error occurred".to_string(),
+                    ))
+                }
             }
+        })
+        .await
+    }
+}
+
+#[tool(tool_box)]
+impl SemantiqServer {
+    #[tool(
+        name = "semantiq_search",
+        description = "Search for code patterns, symbols, or text in the codebase. Returns relevant matches with file paths and line numbers. Results list the tags (salient keywords) of their files and the most common ones as facets."
+    )]
+    #[allow(clippy::too_many_arguments)] // one argument per tool parameter
+    pub async fn semantiq_search(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Code, identifiers or natural language to search for. Add 'tags:a,b' to keep only files tagged with all of the given tags",
+            length(min = 1, max = 500)
+        )]
+        query: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of results (default 20)",
+            range(min = 1, max = 1000)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "Minimum relevance score of a result (default 0.35)",
+            range(min = 0.0, max = 1.0)
+        )]
+        min_score: Option<f32>,
+        #[tool(param)]
+        #[serde(default, deserialize_with = "crate::tools::schema::deserialize_list")]
+        #[schemars(
+            description = "File extensions to search, e.g. [\"rs\", \"ts\"] or \"rs,ts\"",
+            schema_with = "crate::tools::schema::file_types_schema"
+        )]
+        file_type: Option<String>,
+        #[tool(param)]
+        #[serde(default, deserialize_with = "crate::tools::schema::deserialize_list")]
+        #[schemars(
+            description = "Kinds of symbols to keep, e.g. [\"function\", \"struct\"] or \"function,struct\"",
+            schema_with = "crate::tools::schema::symbol_kinds_schema"
+        )]
+        symbol_kind: Option<String>,
+        #[tool(param)]
+        #[serde(default, deserialize_with = "crate::tools::schema::deserialize_list")]
+        #[schemars(
+            description = "Workspace packages to search in a monorepo (Cargo workspace members, pnpm/npm workspace packages, Go modules), e.g. [\"@acme/ui\"] or \"acme-core,acme-web\"",
+            schema_with = "crate::tools::schema::packages_schema"
+        )]
+        package: Option<String>,
+        #[tool(param)]
+        #[serde(default, deserialize_with = "crate::tools::schema::deserialize_list")]
+        #[schemars(
+            description = "Keep only symbols having all of these qualifiers, e.g. [\"public\", \"async\"] or \"public,generic\". Text matches and chunks outside of any symbol are left out",
+            schema_with = "crate::tools::schema::qualifiers_schema"
+        )]
+        qualifiers: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "HTTP route, e.g. 'POST /api/users' or '/api/users/42': return only the handlers of the routes matching it (see semantiq_routes)",
+            length(min = 1, max = 500)
+        )]
+        route: Option<String>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "Files to search: 'project' (default), 'external' for the indexed sources of third-party dependencies when enabled in semantiq.toml, or 'all'",
+            schema_with = "crate::tools::schema::scope_schema"
+        )]
+        scope: Option<String>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Explain why each result matched: strategy, distance, term matches, boosts and thresholds"
+        )]
+        explain_scores: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Lines of the file to add above and below each result, to read the surrounding code without another call (default 0)",
+            range(min = 0, max = 50)
+        )]
+        context_lines: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "Expand each result to the whole function, class or other symbol enclosing it (symbols up to 200 lines)"
+        )]
+        expand_to_symbol: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Leave out symbols compiled only under a condition (#[cfg(test)], #ifdef _WIN32, //go:build linux...) and the chunks inside them. Text matches are kept"
+        )]
+        exclude_conditional: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Keep generated files (linguist-generated in .gitattributes, protobuf output, minified code...) in the results, marked as generated. Left out by default"
+        )]
+        include_generated: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Leave out text matches inside comments, found from the syntax tree of indexed files"
+        )]
+        exclude_comments: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Leave out text matches inside string literals (interpolated code is kept), found from the syntax tree of indexed files"
+        )]
+        exclude_strings: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Id of the search session of the conversation, e.g. a conversation id. Follow-up searches of a session rank results from the files and directories its recent searches surfaced higher; semantiq_session_summary lists them",
+            length(min = 1, max = 500)
+        )]
+        session_id: Option<String>,
+        ct: CancellationToken,
+    ) -> Result<ToolOutput, String> {
+        debug!(
+            query = %query,
+            limit = ?limit,
+            file_type = ?file_type,
+            symbol_kind = ?symbol_kind,
+            package = ?package,
+            qualifiers = ?qualifiers,
+            route = ?route,
+            scope = ?scope,
+            session_id = ?session_id,
+            "semantiq_search called"
+        );
+
+        let request = SemantiqSearch {
+            query,
+            limit,
+            min_score,
+            file_type,
+            symbol_kind,
+            package,
+            qualifiers,
+            route,
+            scope,
+            explain_scores,
+            context_lines,
+            expand_to_symbol,
+            exclude_conditional,
+            include_generated,
+            exclude_comments,
+            exclude_strings,
+            session_id,
+        };
+        let format = parse_output_format(format.as_deref())?;
+
+        let (deadline, cancellation) = cancellable_deadline(ct);
+        let results = self
+            .blocking(move |server| server.run_search(&request, deadline))
+            .await;
+        cancellation.abort();
+
+        Ok(self.output(format, &results?))
+    }
+
+    #[tool(
+        name = "semantiq_batch_search",
+        description = "Run several searches in one call, each with its own parameters (those of semantiq_search). The searches run concurrently under one time limit and their results are returned by query; a failed search does not fail the others."
+    )]
+    pub async fn semantiq_batch_search(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Searches to run, at most 10, e.g. [{\"query\": \"load config\"}, {\"query\": \"parse args\", \"file_type\": \"rs\"}]",
+            schema_with = "crate::tools::schema::batch_queries_schema"
+        )]
+        queries: Vec<SemantiqSearch>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+        ct: CancellationToken,
+    ) -> Result<ToolOutput, String> {
+        debug!(queries = queries.len(), "semantiq_batch_search called");
+
+        let request = SemantiqBatchSearch::new(queries);
+        let format = parse_output_format(format.as_deref())?;
+
+        let (deadline, cancellation) = cancellable_deadline(ct);
+        let response = self.run_batch_search(&request, deadline).await;
+        cancellation.abort();
+
+        Ok(self.output(format, &response?))
+    }
+
+    #[tool(
+        name = "semantiq_saved_search",
+        description = "Run a search saved by the team with `semantiq query save`, filling in its {parameters}. An unknown name lists the saved queries."
+    )]
+    pub async fn semantiq_saved_search(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Name of the saved query, e.g. 'find-handlers'",
+            length(min = 1, max = 500)
+        )]
+        name: String,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "Values of the {parameters} of the saved query, e.g. {\"verb\": \"post\"}"
+        )]
+        params: Option<HashMap<String, String>>,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of results (default: the saved limit, or 20)",
+            range(min = 1, max = 1000)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+        ct: CancellationToken,
+    ) -> Result<ToolOutput, String> {
+        debug!(name = %name, params = ?params, limit = ?limit, "semantiq_saved_search called");
+
+        let request = SemantiqSavedSearch {
+            name,
+            params: params.unwrap_or_default(),
+            limit,
+            ..Default::default()
+        };
+        let format = parse_output_format(format.as_deref())?;
+
+        let (deadline, cancellation) = cancellable_deadline(ct);
+        let results = self
+            .blocking(move |server| server.run_saved_search(&request, deadline))
+            .await;
+        cancellation.abort();
+
+        Ok(self.output(format, &results?))
+    }
+
+    #[tool(
+        name = "semantiq_find_refs",
+        description = "Find all references to a symbol including definitions and usages. Useful for understanding how a function or class is used."
+    )]
+    #[allow(clippy::too_many_arguments)] // one argument per tool parameter
+    pub async fn semantiq_find_refs(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "Name of the symbol", length(min = 1, max = 500))]
+        symbol: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of results (default 50)",
+            range(min = 1, max = 1000)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "References to return: 'definition', 'usage' or 'all' (default)",
+            schema_with = "crate::tools::schema::ref_kind_schema"
+        )]
+        kind: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Only files under this path relative to the project root, e.g. 'src/api'",
+            length(max = 500)
+        )]
+        path_prefix: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "Skip test files and directories")]
+        exclude_tests: Option<bool>,
+        #[tool(param)]
+        #[schemars(description = "Skip usages inside comments")]
+        exclude_comments: Option<bool>,
+        #[tool(param)]
+        #[schemars(description = "Skip usages inside string literals")]
+        exclude_strings: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also find the references of the symbol in other languages: the name napi-rs, wasm-bindgen or PyO3 bindings export a Rust symbol as (get_user is getUser in JavaScript), or the Rust symbol behind a bound name"
+        )]
+        cross_language: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Index generation to find the references at, to see the code as it was indexed before (the index_generation of JSON search results and index change notifications). Usages are then counted per file, without their lines, and cross-language links are only followed in the current index"
+        )]
+        as_of: Option<u64>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(
+            symbol = %symbol,
+            limit = ?limit,
+            kind = ?kind,
+            path_prefix = ?path_prefix,
+            exclude_tests = ?exclude_tests,
+            exclude_comments = ?exclude_comments,
+            exclude_strings = ?exclude_strings,
+            cross_language = ?cross_language,
+            as_of = ?as_of,
+            "semantiq_find_refs called"
+        );
+
+        let request = SemantiqFindRefs {
+            symbol,
+            limit,
+            kind,
+            path_prefix,
+            exclude_tests,
+            exclude_comments,
+            exclude_strings,
+            cross_language,
+            as_of,
+        };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_find_refs(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_deps",
+        description = "Analyze the dependency graph for a file. Shows what the file imports and what other files import it, or with `symbol`, the functions, types and other symbols one of its symbols uses and is used by."
+    )]
+    pub async fn semantiq_deps(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Path of the file relative to the project root",
+            length(min = 1, max = 500)
+        )]
+        file_path: String,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "Name of a symbol of the file, e.g. 'handle_request': list the symbols it uses and the symbols using it instead of the file's imports",
+            length(min = 1, max = 500)
+        )]
+        symbol: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(file = %file_path, symbol = ?symbol, "semantiq_deps called");
+
+        let request = SemantiqDeps { file_path, symbol };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_deps(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_explain",
+        description = "Get a detailed explanation of a symbol including its definition, documentation, how often and in how many files it is referenced, and related symbols."
+    )]
+    pub async fn semantiq_explain(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "Name of the symbol", length(min = 1, max = 500))]
+        symbol: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Index generation to explain the symbol at, to see its definitions and usage counts as they were indexed before (the index_generation of JSON search results and index change notifications)"
+        )]
+        as_of: Option<u64>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(symbol = %symbol, as_of = ?as_of, "semantiq_explain called");
+
+        let request = SemantiqExplain { symbol, as_of };
+        let format = parse_output_format(format.as_deref())?;
+
+        let explanation = self
+            .blocking(move |server| server.run_explain(&request))
+            .await?;
+        Ok(self.output(format, &explanation))
+    }
+
+    #[tool(
+        name = "semantiq_implementations",
+        description = "List the types implementing a trait or interface, or extending a base type, with their locations. Covers Rust impl blocks and supertraits, TypeScript/JavaScript and Java implements/extends, and Go types whose methods satisfy an interface (reported as inferred)."
+    )]
+    pub async fn semantiq_implementations(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Name of the trait, interface or base type",
+            length(min = 1, max = 500)
+        )]
+        symbol: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of results (default 50)",
+            range(min = 1, max = 1000)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(symbol = %symbol, limit = ?limit, "semantiq_implementations called");
+
+        let request = SemantiqImplementations { symbol, limit };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_implementations(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_component_tree",
+        description = "Show the render hierarchy of a React component from the JSX of TSX/JSX files: the components rendering it, and the tree of components it renders with the file and line of each JSX element."
+    )]
+    pub async fn semantiq_component_tree(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "Name of the component", length(min = 1, max = 500))]
+        component: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Levels of rendered components to follow (default 3)",
+            range(min = 1, max = 10)
+        )]
+        depth: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(component = %component, depth = ?depth, "semantiq_component_tree called");
+
+        let request = SemantiqComponentTree { component, depth };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_component_tree(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_related",
+        description = "Suggest the files most likely to need editing together with a file, ranked, with the reasons for each: imports in either direction, symbols one file defines and the other uses, similar file summaries, and how often both changed in the same git commits."
+    )]
+    pub async fn semantiq_related(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Path of the file relative to the project root",
+            length(min = 1, max = 500)
+        )]
+        file_path: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of suggestions (default 10)",
+            range(min = 1, max = 100)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(file = %file_path, limit = ?limit, "semantiq_related called");
+
+        let request = SemantiqRelated { file_path, limit };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_related(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_overview",
+        description = "Get a structured map of the codebase or of a directory before searching: for the directory and each subdirectory, the number of files and lines, the languages, the most referenced top-level symbols and the entry files to read first."
+    )]
+    pub async fn semantiq_overview(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Directory relative to the project root (default: the project root)",
+            length(min = 1, max = 500)
+        )]
+        path: Option<String>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(path = ?path, "semantiq_overview called");
+
+        let request = SemantiqOverview { path };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_overview(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_explore",
+        description = "List a directory of the project, subdirectories first, with what the index knows of each entry: language, number of symbols and top-level symbols, when it was indexed and whether the file changed since, and for entries left out of the index why (hidden, dependency or build directory, .gitignore, unsupported language, too large). Use it instead of ls to navigate the repository."
+    )]
+    pub async fn semantiq_explore(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Directory relative to the project root (default: the project root)",
+            length(min = 1, max = 500)
+        )]
+        path: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of entries listed (default 200)",
+            range(min = 1, max = 1000)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(path = ?path, limit = ?limit, "semantiq_explore called");
+
+        let request = SemantiqExplore { path, limit };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_explore(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_entrypoints",
+        description = "Find how the application starts and is wired together: main functions, HTTP route registrations with their handlers, CLI subcommand definitions and the exported API of library roots (lib.rs, index.ts, __init__.py), grouped by kind."
+    )]
+    pub async fn semantiq_entrypoints(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Kind of entry points to list: 'main', 'route', 'command' or 'export' (default: all)"
+        )]
+        kind: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Directory relative to the project root (default: the project root)",
+            length(min = 1, max = 500)
+        )]
+        path: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of entry points listed per kind (default 25)",
+            range(min = 1, max = 500)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(kind = ?kind, path = ?path, limit = ?limit, "semantiq_entrypoints called");
+
+        let request = SemantiqEntrypoints { kind, path, limit };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_entrypoints(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_routes",
+        description = "Find where an HTTP route is handled: the routes registered with axum, actix, Express, NestJS, Flask, FastAPI, Spring, ASP.NET, Go or Laravel matching a method and path, e.g. 'POST /api/users/42', with the definition of their handler. Path parameters (:id, {id}, <int:id>) match any segment, and routes of routers mounted under a prefix match the end of the path."
+    )]
+    pub async fn semantiq_routes(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "HTTP method, e.g. 'POST' (default: any)")]
+        method: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Request path, e.g. '/api/users/42', optionally after its method: 'POST /api/users' (default: list every route)",
+            length(min = 1, max = 500)
+        )]
+        path: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of routes listed (default 25)",
+            range(min = 1, max = 500)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(method = ?method, path = ?path, limit = ?limit, "semantiq_routes called");
+
+        let request = SemantiqRoutes {
+            method,
+            path,
+            limit,
+        };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_routes(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_config_usage",
+        description = "List where an environment variable or configuration key is read and written: std::env::var, process.env, os.environ, os.Getenv, System.getenv, getenv, ENV, $VAR in shell scripts, and the keys read or set through config/settings objects, system properties and Spring @Value placeholders. Writes come first, each site with its enclosing function and line of code."
+    )]
+    pub async fn semantiq_config_usage(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Environment variable or configuration key, e.g. 'DATABASE_URL' or 'db.url', or a glob such as 'DATABASE_*'",
+            length(min = 1, max = 500)
+        )]
+        name: String,
+        #[tool(param)]
+        #[schemars(
+            description = "'env' for environment variables or 'config' for configuration keys (default: both)"
+        )]
+        source: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "'read' or 'write' (default: both)")]
+        access: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of sites listed (default 50)",
+            range(min = 1, max = 500)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(name = %name, source = ?source, access = ?access, limit = ?limit, "semantiq_config_usage called");
+
+        let request = SemantiqConfigUsage {
+            name,
+            source,
+            access,
+            limit,
+        };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_config_usage(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_index_issues",
+        description = "List the files that could not be indexed fully: parse failures (indexed without symbols or chunks), read errors and files too large (left out of the index), and files whose encoding was guessed. Use it when a file is missing from results or found without its symbols."
+    )]
+    pub async fn semantiq_index_issues(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "'parse_failure', 'read_error', 'too_large' or 'encoding' (default: all)"
+        )]
+        kind: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of issues listed (default 50)",
+            range(min = 1, max = 500)
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(kind = ?kind, limit = ?limit, "semantiq_index_issues called");
+
+        let request = SemantiqIndexIssues { kind, limit };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_index_issues(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_session_summary",
+        description = "List what a search session has viewed so far: its searches, and the files and lines their results came from, most recent first. Sessions are started by semantiq_search calls given a session_id."
+    )]
+    pub async fn semantiq_session_summary(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Id of the session, as given to semantiq_search",
+            length(min = 1, max = 500)
+        )]
+        session_id: String,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(session_id = %session_id, "semantiq_session_summary called");
+
+        let request = SemantiqSessionSummary { session_id };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_session_summary(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_diff",
+        description = "Summarize what changed between two git revisions at the symbol level instead of as line diffs: functions, methods and types added, removed, modified, with a changed signature, or moved/renamed (matched by identical code or embedding similarity)."
+    )]
+    pub async fn semantiq_diff(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Base revision: commit, branch or tag",
+            length(min = 1, max = 500)
+        )]
+        from: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Revision to compare with the base (default HEAD)",
+            length(min = 1, max = 500)
+        )]
+        to: Option<String>,
+        #[tool(param)]
+        #[serde(default)]
+        #[schemars(
+            description = "'markdown' (default) or 'json' for structured output",
+            schema_with = "crate::tools::schema::format_schema"
+        )]
+        format: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(from = %from, to = ?to, "semantiq_diff called");
+
+        let request = SemantiqDiff { from, to };
+        let format = parse_output_format(format.as_deref())?;
+
+        let response = self
+            .blocking(move |server| server.run_diff(&request))
+            .await?;
+        Ok(self.output(format, &response))
+    }
+
+    #[tool(
+        name = "semantiq_reindex",
+        description = "Reindex files immediately instead of waiting for the file watcher. Use after files were changed outside the editor so searches see current code. Only changed files are reindexed, at most 100 per call."
+    )]
+    pub async fn semantiq_reindex(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "File path, directory or glob relative to the project root, e.g. 'src/**/*.rs' (default: the whole project)",
+            length(min = 1, max = 500)
+        )]
+        path: Option<String>,
+    ) -> Result<ToolOutput, String> {
+        debug!(path = ?path, "semantiq_reindex called");
+
+        let request = SemantiqReindex { path };
+        match self.run_reindex(&request).await? {
+            ReindexOutcome::Completed(report) => Ok(ToolOutput::new(format_reindex_report(
+                request.validated_path()?,
+                &report,
+            ))),
+            ReindexOutcome::InitialIndexing(progress) => Ok(ToolOutput::new(format!(
+                "Initial indexing in progress ({}). Changed files will be picked up when it completes.",
+                format_progress_message(&progress)
+            ))),
         }
     }
 }
@@ -449,6 +1979,7 @@ impl ServerHandler for SemantiqServer {
             protocol_version: Default::default(),
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
+                .enable_logging()
                 .build(),
             server_info: Implementation {
                 name: "semantiq".to_string(),
@@ -456,8 +1987,21 @@ impl ServerHandler for SemantiqServer {
             },
             instructions: Some(
                 "Semantiq provides semantic code understanding tools for AI assistants. \
-                Use semantiq_search to find code, semantiq_find_refs to trace symbol usage, \
-                semantiq_deps to analyze dependencies, and semantiq_explain for detailed symbol info."
+                Use semantiq_search to find code (semantiq_batch_search to run several searches at once), semantiq_find_refs to trace symbol usage, \
+                semantiq_deps to analyze dependencies, semantiq_explain for detailed symbol info, \
+                semantiq_implementations to list the implementors of a trait or interface, \
+                semantiq_component_tree to see which React components render or are rendered by a component, \
+                semantiq_related to find the files likely to change together with a file, \
+                semantiq_overview to get a map of the codebase or of a directory, \
+                semantiq_explore to list a directory with the index data of its entries and why some are left out, \
+                semantiq_entrypoints to see how the application starts (main functions, routes, CLI commands, exports), \
+                semantiq_routes to find the handler of an HTTP route such as POST /api/users, \
+                semantiq_config_usage to find where an environment variable or configuration key is read and written, \
+                semantiq_diff to review the symbol-level changes between two git revisions, \
+                semantiq_saved_search to run the searches saved by the team, \
+                semantiq_session_summary to list what the searches of a session have viewed, \
+                semantiq_index_issues to see which files failed to parse or were left out of the index, \
+                and semantiq_reindex to refresh the index after files changed."
                     .to_string(),
             ),
         }
@@ -470,6 +2014,8 @@ impl ServerHandler for SemantiqServer {
     ) -> std::result::Result<rmcp::model::InitializeResult, rmcp::Error> {
         // Now that we have a peer connection, spawn the version check
         Self::spawn_version_check(context.peer.clone());
+        Self::spawn_progress_notifier(context.peer.clone(), self.index_progress.clone());
+        Self::spawn_generation_notifier(context.peer.clone(), &self.generation);
 
         Ok(self.get_info())
     }
@@ -478,6 +2024,7 @@ impl ServerHandler for SemantiqServer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use semantiq_index::{IndexIssue, IndexIssueKind, SavedQuery};
     use std::sync::Arc;
     use tempfile::TempDir;
 
@@ -490,11 +2037,21 @@ mod tests {
         // Create the server without spawning background tasks
         let store = Arc::new(IndexStore::open(&db_path).expect("Failed to open store"));
         let engine = Arc::new(RetrievalEngine::new(Arc::clone(&store), &project_root));
+        let generation = Arc::new(GenerationWatcher::spawn(
+            Arc::clone(&store),
+            GENERATION_POLL_INTERVAL,
+        ));
 
         let server = SemantiqServer {
             engine,
             store,
             auto_indexer: None,
+            index_progress: ProgressTracker::new(),
+            generation,
+            limits: Arc::new(RateLimiter::new(&SemantiqConfig::default().limits)),
+            structured_content: true,
+            recalibration: RecalibrationConfig::default(),
+            stopping: CancellationToken::new(),
         };
 
         (server, temp_dir)
@@ -517,10 +2074,27 @@ mod tests {
 
         if let Some(lang) = lang
             && let Ok(mut support) = semantiq_parser::LanguageSupport::new()
-            && let Ok(tree) = support.parse(lang, content)
+            && let Ok(tree) = support.parse_file(lang, std::path::Path::new(path), content)
             && let Ok(symbols) = semantiq_parser::SymbolExtractor::extract(&tree, content, lang)
         {
             let _ = store.insert_symbols(file_id, &symbols);
+            if let Ok(relations) = semantiq_parser::RelationExtractor::extract(&tree, content, lang)
+            {
+                let _ = store.insert_type_relations(file_id, &relations);
+            }
+            if let Ok(entrypoints) =
+                semantiq_parser::EntrypointExtractor::extract(&tree, content, lang, path)
+            {
+                let _ = store.insert_entrypoints(file_id, &entrypoints);
+            }
+            if let Ok(config_refs) =
+                semantiq_parser::ConfigRefExtractor::extract(&tree, content, lang)
+            {
+                let _ = store.insert_config_refs(file_id, &config_refs);
+            }
+            if let Ok(bindings) = semantiq_parser::BindingExtractor::extract(&tree, content, lang) {
+                let _ = store.insert_bindings(file_id, &bindings);
+            }
         }
 
         file_id
@@ -533,7 +2107,27 @@ mod tests {
         let (server, _temp) = create_test_server();
 
         let result = server
-            .semantiq_search("".to_string(), None, None, None, None)
+            .semantiq_search(
+                "".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_err());
@@ -545,7 +2139,27 @@ mod tests {
         let (server, _temp) = create_test_server();
 
         let result = server
-            .semantiq_search("   ".to_string(), None, None, None, None)
+            .semantiq_search(
+                "   ".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_err());
@@ -558,20 +2172,94 @@ mod tests {
 
         let long_query = "a".repeat(501);
         let result = server
-            .semantiq_search(long_query, None, None, None, None)
+            .semantiq_search(
+                long_query,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("maximum length"));
     }
 
+    #[tokio::test]
+    async fn test_cancelled_search_returns_partial_results() {
+        let (server, _temp) = create_test_server();
+        index_test_file(&server.store, "lib.rs", "fn handler() {}", "rust");
+
+        let ct = CancellationToken::new();
+        ct.cancel();
+        let output = server
+            .semantiq_search(
+                "handler".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                ct,
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("stopped at its time limit"));
+    }
+
     #[tokio::test]
     async fn test_search_query_at_max_length_succeeds() {
         let (server, _temp) = create_test_server();
 
         let max_query = "a".repeat(500);
         let result = server
-            .semantiq_search(max_query, None, None, None, None)
+            .semantiq_search(
+                max_query,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         // Should not error on length validation
@@ -591,7 +2279,27 @@ mod tests {
         );
 
         let result = server
-            .semantiq_search("hello".to_string(), Some(10), None, None, None)
+            .semantiq_search(
+                "hello".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_ok());
@@ -619,6 +2327,20 @@ mod tests {
                 None,
                 Some("rs".to_string()),
                 None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
             )
             .await;
 
@@ -633,7 +2355,27 @@ mod tests {
         index_test_file(&server.store, "test.rs", "fn exact_match() {}", "rust");
 
         let result = server
-            .semantiq_search("exact_match".to_string(), Some(10), Some(0.9), None, None)
+            .semantiq_search(
+                "exact_match".to_string(),
+                Some(10),
+                Some(0.9),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_ok());
@@ -657,12 +2399,211 @@ mod tests {
                 None,
                 None,
                 Some("function".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
             )
             .await;
 
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_search_json_format() {
+        let (server, _temp) = create_test_server();
+
+        index_test_file(&server.store, "test.rs", "fn hello_world() {}", "rust");
+
+        let output = server
+            .semantiq_search(
+                "hello".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("json".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["query"], "hello");
+        assert!(json["results"].is_array());
+        assert!(json["total_count"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_search_explain_scores() {
+        let (server, temp) = create_test_server();
+
+        let content = "fn hello_world() {}";
+        std::fs::write(temp.path().join("test.rs"), content).expect("Failed to write test file");
+        index_test_file(&server.store, "test.rs", content, "rust");
+
+        let output = server
+            .semantiq_search(
+                "hello_world".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("json".to_string()),
+                Some(true),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        let explanation = &json["results"][0]["metadata"]["explanation"];
+        assert_eq!(explanation["strategy"], "symbol");
+        assert_eq!(explanation["term_matches"][0]["term"], "hello_world");
+        assert!(explanation["thresholds"]["min_score"].is_number());
+    }
+
+    #[tokio::test]
+    async fn test_search_invalid_scope_returns_error() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_search(
+                "hello".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("vendor".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid scope 'vendor': expected 'project', 'external' or 'all'"
+        );
+    }
+
+    // ==================== semantiq_batch_search tests ====================
+
+    #[tokio::test]
+    async fn test_batch_search_returns_results_by_query() {
+        let (server, temp) = create_test_server();
+
+        let content = "fn hello_world() {}\nfn parse_args() {}";
+        std::fs::write(temp.path().join("test.rs"), content).expect("Failed to write test file");
+        index_test_file(&server.store, "test.rs", content, "rust");
+
+        let output = server
+            .semantiq_batch_search(
+                vec![
+                    SemantiqSearch::new("hello").with_limit(5),
+                    SemantiqSearch::new("parse_args").with_file_type("rs"),
+                ],
+                Some("json".to_string()),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["error_count"], 0);
+        let searches = json["searches"].as_array().unwrap();
+        assert_eq!(searches.len(), 2);
+        assert_eq!(searches[0]["query"], "hello");
+        assert_eq!(searches[0]["results"]["query"], "hello");
+        assert_eq!(searches[1]["query"], "parse_args");
+        assert!(
+            searches[1]["results"]["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|r| r["file_path"] == "test.rs")
+        );
+
+        let markdown = server
+            .semantiq_batch_search(
+                vec![SemantiqSearch::new("hello")],
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(markdown.contains("Ran 1 searches"));
+        assert!(markdown.contains("## 1. hello"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_search_rejects_invalid_query_before_running() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_batch_search(
+                vec![
+                    SemantiqSearch::new("hello"),
+                    SemantiqSearch::new("world").with_scope("vendor"),
+                ],
+                None,
+                CancellationToken::new(),
+            )
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            "Query 2: Invalid scope 'vendor': expected 'project', 'external' or 'all'"
+        );
+
+        let result = server
+            .semantiq_batch_search(Vec::new(), None, CancellationToken::new())
+            .await;
+        assert_eq!(result.unwrap_err(), "Queries cannot be empty");
+    }
+
     // ==================== semantiq_find_refs tests ====================
 
     #[tokio::test]
@@ -677,7 +2618,18 @@ mod tests {
         index_test_file(&server.store, "test.rs", content, "rust");
 
         let result = server
-            .semantiq_find_refs("my_symbol".to_string(), Some(10))
+            .semantiq_find_refs(
+                "my_symbol".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await;
 
         assert!(result.is_ok(), "Expected Ok but got: {:?}", result);
@@ -697,7 +2649,18 @@ mod tests {
         index_test_file(&server.store, "lib.rs", content, "rust");
 
         let result = server
-            .semantiq_find_refs("calculate".to_string(), Some(50))
+            .semantiq_find_refs(
+                "calculate".to_string(),
+                Some(50),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await;
 
         assert!(result.is_ok(), "Expected Ok but got: {:?}", result);
@@ -711,47 +2674,340 @@ mod tests {
         let (server, _temp) = create_test_server();
 
         let result = server
-            .semantiq_find_refs("nonexistent".to_string(), None)
+            .semantiq_find_refs(
+                "nonexistent".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await;
 
         // Should use default limit of 50
         assert!(result.is_ok());
     }
 
-    // ==================== semantiq_deps tests ====================
-
     #[tokio::test]
-    async fn test_deps_returns_formatted_output() {
-        let (server, _temp) = create_test_server();
-
-        let file_id = index_test_file(&server.store, "main.rs", "use crate::utils;", "rust");
+    async fn test_find_refs_definitions_only() {
+        let (server, temp) = create_test_server();
 
-        // Add a dependency
-        server
-            .store
-            .insert_dependency(file_id, "crate::utils", Some("utils"), "local")
-            .expect("Failed to insert dependency");
+        let content = "fn compute() {}\nfn caller() { compute(); }";
+        std::fs::write(temp.path().join("lib.rs"), content).expect("Failed to write test file");
+        index_test_file(&server.store, "lib.rs", content, "rust");
 
-        let result = server.semantiq_deps("main.rs".to_string()).await;
+        let output = server
+            .semantiq_find_refs(
+                "compute".to_string(),
+                None,
+                Some("definition".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
 
-        assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.contains("Dependency analysis for 'main.rs'"));
-        assert!(output.contains("Imports"));
+        assert!(output.contains("## Definitions"));
+        assert!(!output.contains("## Usages"));
     }
 
     #[tokio::test]
-    async fn test_deps_shows_imports_section() {
-        let (server, _temp) = create_test_server();
-
-        let file_id = index_test_file(&server.store, "app.rs", "use std::io;", "rust");
-
-        server
-            .store
-            .insert_dependency(file_id, "std::io", Some("io"), "std")
-            .expect("Failed to insert dependency");
+    async fn test_find_refs_path_prefix_and_exclude_tests() {
+        let (server, temp) = create_test_server();
 
-        let result = server.semantiq_deps("app.rs".to_string()).await;
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::create_dir_all(temp.path().join("tests")).unwrap();
+        std::fs::write(temp.path().join("src/app.rs"), "fn run() { render(); }").unwrap();
+        std::fs::write(temp.path().join("tests/app.rs"), "fn check() { render(); }").unwrap();
+
+        let output = server
+            .semantiq_find_refs(
+                "render".to_string(),
+                None,
+                Some("usage".to_string()),
+                None,
+                Some(true),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("src/app.rs"));
+        assert!(!output.contains("tests/app.rs"));
+
+        let output = server
+            .semantiq_find_refs(
+                "render".to_string(),
+                None,
+                None,
+                Some("tests".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("tests/app.rs"));
+        assert!(!output.contains("src/app.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_find_refs_invalid_kind_returns_error() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_find_refs(
+                "render".to_string(),
+                None,
+                Some("callers".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid kind"));
+    }
+
+    #[tokio::test]
+    async fn test_find_refs_path_prefix_traversal_rejected() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_find_refs(
+                "render".to_string(),
+                None,
+                None,
+                Some("../etc".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_refs_json_format_splits_definitions() {
+        let (server, temp) = create_test_server();
+
+        let content = "fn compute() {}\nfn caller() { compute(); }";
+        std::fs::write(temp.path().join("lib.rs"), content).expect("Failed to write test file");
+        index_test_file(&server.store, "lib.rs", content, "rust");
+
+        let output = server
+            .semantiq_find_refs(
+                "compute".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("json".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["symbol"], "compute");
+        let definitions = json["definitions"].as_array().unwrap();
+        assert!(!definitions.is_empty());
+        assert!(
+            definitions
+                .iter()
+                .all(|d| d["metadata"]["match_type"] == "definition")
+        );
+        assert!(json["usages"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_find_refs_follows_cross_language_links() {
+        let (server, temp) = create_test_server();
+
+        std::fs::create_dir_all(temp.path().join("core/src")).unwrap();
+        std::fs::create_dir_all(temp.path().join("py")).unwrap();
+        let rust =
+            "#[pyfunction]\n#[pyo3(name = \"fetch_user\")]\nfn get_user(id: u32) -> u32 { id }\n";
+        let python = "from core import fetch_user\nprint(fetch_user(1))\n";
+        std::fs::write(temp.path().join("core/src/lib.rs"), rust).unwrap();
+        std::fs::write(temp.path().join("py/app.py"), python).unwrap();
+        index_test_file(&server.store, "core/src/lib.rs", rust, "rust");
+        index_test_file(&server.store, "py/app.py", python, "python");
+
+        let request = SemantiqFindRefs::new("get_user").following_cross_language(true);
+        let response = server.run_find_refs(&request).unwrap();
+        assert_eq!(response.links.len(), 1);
+        assert_eq!(response.links[0].name, "fetch_user");
+        assert_eq!(response.links[0].framework.as_deref(), Some("pyo3"));
+        assert!(response.usages.iter().any(|u| u.file_path == "py/app.py"));
+
+        // Not followed unless asked
+        let response = server
+            .run_find_refs(&SemantiqFindRefs::new("get_user"))
+            .unwrap();
+        assert!(response.links.is_empty());
+        assert!(response.usages.iter().all(|u| u.file_path != "py/app.py"));
+
+        // From the bound name back to Rust
+        let output = server
+            .semantiq_find_refs(
+                "fetch_user".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(true),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("🔗 get_user (pyo3) at core/src/lib.rs:3"));
+        assert!(output.contains("📍 core/src/lib.rs:3"));
+    }
+
+    #[tokio::test]
+    async fn test_find_refs_and_explain_as_of_earlier_generation() {
+        let (server, _temp) = create_test_server();
+
+        index_test_file(
+            &server.store,
+            "src/config.rs",
+            "fn load_config() {}\n",
+            "rust",
+        );
+        let before = server.store.index_generation().unwrap();
+        index_test_file(
+            &server.store,
+            "src/config.rs",
+            "fn read_config() {}\n",
+            "rust",
+        );
+
+        let response = server
+            .run_find_refs(&SemantiqFindRefs::new("load_config").as_of(before))
+            .unwrap();
+        assert_eq!(response.as_of, Some(before));
+        assert_eq!(response.definitions.len(), 1);
+        assert_eq!(response.definitions[0].start_line, 1);
+        assert!(
+            server
+                .run_find_refs(&SemantiqFindRefs::new("load_config"))
+                .unwrap()
+                .definitions
+                .is_empty()
+        );
+
+        let output = server
+            .semantiq_explain("load_config".to_string(), Some(before), None)
+            .await
+            .unwrap();
+        assert!(output.contains(&format!(
+            "Found 1 definition(s) as of index generation {}",
+            before
+        )));
+
+        let future = server.store.index_generation().unwrap() + 1;
+        let err = server
+            .run_explain(&SemantiqExplain::new("load_config").as_of(future))
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidParams(_)));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_format_returns_error() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_find_refs(
+                "compute".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("xml".to_string()),
+            )
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid format 'xml': expected 'markdown' or 'json'"
+        );
+
+        let result = server
+            .semantiq_explain("compute".to_string(), None, Some("yaml".to_string()))
+            .await;
+        assert!(result.is_err());
+    }
+
+    // ==================== semantiq_deps tests ====================
+
+    #[tokio::test]
+    async fn test_deps_returns_formatted_output() {
+        let (server, _temp) = create_test_server();
+
+        let file_id = index_test_file(&server.store, "main.rs", "use crate::utils;", "rust");
+
+        // Add a dependency
+        server
+            .store
+            .insert_dependency(file_id, "crate::utils", Some("utils"), "local")
+            .expect("Failed to insert dependency");
+
+        let result = server
+            .semantiq_deps("main.rs".to_string(), None, None)
+            .await;
+
+        assert!(result.is_ok());
+        let output = result.unwrap();
+        assert!(output.contains("Dependency analysis for 'main.rs'"));
+        assert!(output.contains("Imports"));
+    }
+
+    #[tokio::test]
+    async fn test_deps_shows_imports_section() {
+        let (server, _temp) = create_test_server();
+
+        let file_id = index_test_file(&server.store, "app.rs", "use std::io;", "rust");
+
+        server
+            .store
+            .insert_dependency(file_id, "std::io", Some("io"), "std")
+            .expect("Failed to insert dependency");
+
+        let result = server.semantiq_deps("app.rs".to_string(), None, None).await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -763,7 +3019,9 @@ mod tests {
     async fn test_deps_nonexistent_file() {
         let (server, _temp) = create_test_server();
 
-        let result = server.semantiq_deps("nonexistent.rs".to_string()).await;
+        let result = server
+            .semantiq_deps("nonexistent.rs".to_string(), None, None)
+            .await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -785,7 +3043,9 @@ mod tests {
             .expect("Failed to insert dependency");
 
         // Query reverse deps for utils.rs — should show main.rs as importer
-        let result = server.semantiq_deps("utils.rs".to_string()).await;
+        let result = server
+            .semantiq_deps("utils.rs".to_string(), None, None)
+            .await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -801,6 +3061,201 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_deps_json_format() {
+        let (server, _temp) = create_test_server();
+
+        let file_id = index_test_file(&server.store, "main.rs", "use crate::utils;", "rust");
+        server
+            .store
+            .insert_dependency(file_id, "crate::utils", Some("utils"), "local")
+            .expect("Failed to insert dependency");
+
+        let output = server
+            .semantiq_deps("main.rs".to_string(), Some("json".to_string()), None)
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["file_path"], "main.rs");
+        assert_eq!(json["imports"][0]["target_path"], "crate::utils");
+        assert_eq!(json["imports"][0]["import_name"], "utils");
+        assert!(json["imported_by"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deps_mark_cross_package_imports() {
+        let (server, _temp) = create_test_server();
+        let package = |root: &str, name: &str| semantiq_index::WorkspacePackage {
+            root: root.to_string(),
+            ecosystem: semantiq_index::PackageEcosystem::Npm,
+            name: name.to_string(),
+        };
+        server
+            .store
+            .set_packages(&[
+                package("apps/web", "web"),
+                package("packages/ui", "@acme/ui"),
+            ])
+            .unwrap();
+
+        let file_id = index_test_file(
+            &server.store,
+            "apps/web/src/app.ts",
+            "import { Button } from '@acme/ui';",
+            "typescript",
+        );
+        for target in ["@acme/ui", "./layout"] {
+            server
+                .store
+                .insert_dependency(file_id, target, None, "local")
+                .unwrap();
+        }
+
+        let output = server
+            .semantiq_deps("apps/web/src/app.ts".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("Dependency analysis for 'apps/web/src/app.ts' (package web)"));
+        assert!(output.contains("## Imports (2 dependencies, 1 cross-package)"));
+        assert!(output.contains("→ @acme/ui [local, cross-package: @acme/ui]"));
+        assert!(output.contains("→ ./layout [local]\n"));
+
+        let output = server
+            .semantiq_deps(
+                "apps/web/src/app.ts".to_string(),
+                Some("json".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["package"], "web");
+        assert_eq!(json["imports"][0]["scope"], "cross_package");
+        assert_eq!(json["imports"][1]["scope"], "intra_package");
+    }
+
+    #[tokio::test]
+    async fn test_deps_lists_imported_symbols() {
+        let (server, _temp) = create_test_server();
+
+        let file_id = index_test_file(
+            &server.store,
+            "app.ts",
+            "import { A, B as C } from './x';",
+            "typescript",
+        );
+        let dependency_id = server
+            .store
+            .insert_dependency(file_id, "./x", Some("x"), "local")
+            .expect("Failed to insert dependency");
+        server
+            .store
+            .insert_dependency_symbols(
+                dependency_id,
+                &[
+                    semantiq_parser::ImportedSymbol::new("A", None),
+                    semantiq_parser::ImportedSymbol::new("B", Some("C".to_string())),
+                ],
+            )
+            .expect("Failed to insert dependency symbols");
+
+        let output = server
+            .semantiq_deps("app.ts".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(
+            output.contains("→ ./x { A, B as C } [local]"),
+            "Expected imported symbols in output: {}",
+            output
+        );
+
+        let output = server
+            .semantiq_deps("app.ts".to_string(), Some("json".to_string()), None)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["imports"][0]["symbols"][1]["name"], "B");
+        assert_eq!(json["imports"][0]["symbols"][1]["alias"], "C");
+    }
+
+    #[tokio::test]
+    async fn test_deps_of_a_symbol() {
+        use semantiq_parser::{ImportExtractor, Language, LanguageSupport, OccurrenceExtractor};
+
+        let (server, _temp) = create_test_server();
+        let mut support = LanguageSupport::new().unwrap();
+        for (path, content) in [
+            (
+                "src/config.rs",
+                "pub fn load_config() -> u8 {\n    parse_config()\n}\n\nfn parse_config() -> u8 {\n    0\n}\n",
+            ),
+            (
+                "src/server.rs",
+                "use crate::config::load_config;\n\npub fn handle_request() {\n    load_config();\n}\n",
+            ),
+        ] {
+            let tree = support.parse(Language::Rust, content).unwrap();
+            let symbols =
+                semantiq_parser::SymbolExtractor::extract(&tree, content, Language::Rust).unwrap();
+            let imports = ImportExtractor::extract(&tree, content, Language::Rust).unwrap();
+            let occurrences = OccurrenceExtractor::extract(&tree, content, Language::Rust).unwrap();
+            server
+                .store
+                .write_file_index(&semantiq_index::FileIndexData {
+                    symbols: &symbols,
+                    imports: &imports,
+                    occurrences: &occurrences,
+                    ..semantiq_index::FileIndexData::new(
+                        path,
+                        Some("rust"),
+                        content,
+                        content.len() as i64,
+                        0,
+                    )
+                })
+                .unwrap();
+        }
+
+        let output = server
+            .semantiq_deps(
+                "src/config.rs".to_string(),
+                None,
+                Some("load_config".to_string()),
+            )
+            .await
+            .unwrap();
+        assert!(
+            output.contains("→ parse_config (function) at src/config.rs:5 [referenced at line 2]"),
+            "Expected used symbols in output: {}",
+            output
+        );
+        assert!(
+            output
+                .contains("← handle_request (function) at src/server.rs:3 [references at line 4]"),
+            "Expected using symbols in output: {}",
+            output
+        );
+
+        let output = server
+            .semantiq_deps(
+                "src/config.rs".to_string(),
+                Some("json".to_string()),
+                Some("load_config".to_string()),
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["symbol"], "load_config");
+        assert_eq!(json["used_by"][0]["name"], "handle_request");
+        assert_eq!(json["used_by"][0]["line"], 4);
+
+        let result = server
+            .semantiq_deps("src/config.rs".to_string(), None, Some("".to_string()))
+            .await;
+        assert!(result.is_err());
+    }
+
     // ==================== semantiq_explain tests ====================
 
     #[tokio::test]
@@ -814,7 +3269,9 @@ mod tests {
             "rust",
         );
 
-        let result = server.semantiq_explain("process".to_string()).await;
+        let result = server
+            .semantiq_explain("process".to_string(), None, None)
+            .await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -826,7 +3283,7 @@ mod tests {
         let (server, _temp) = create_test_server();
 
         let result = server
-            .semantiq_explain("nonexistent_symbol".to_string())
+            .semantiq_explain("nonexistent_symbol".to_string(), None, None)
             .await;
 
         assert!(result.is_ok());
@@ -841,7 +3298,9 @@ mod tests {
         index_test_file(&server.store, "a.rs", "fn shared_name() {}", "rust");
         index_test_file(&server.store, "b.rs", "fn shared_name() {}", "rust");
 
-        let result = server.semantiq_explain("shared_name".to_string()).await;
+        let result = server
+            .semantiq_explain("shared_name".to_string(), None, None)
+            .await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -853,14 +3312,647 @@ mod tests {
         );
     }
 
-    // ==================== ServerHandler tests ====================
+    // ==================== semantiq_implementations tests ====================
 
-    #[test]
-    fn test_get_info_returns_correct_name() {
+    #[tokio::test]
+    async fn test_implementations_lists_implementors() {
         let (server, _temp) = create_test_server();
-        let info = server.get_info();
 
-        assert_eq!(info.server_info.name, "semantiq");
+        index_test_file(
+            &server.store,
+            "shapes.rs",
+            "trait Shape {}\nstruct Circle;\nimpl Shape for Circle {}\nstruct Square;\nimpl Shape for Square {}",
+            "rust",
+        );
+        index_test_file(
+            &server.store,
+            "reader.go",
+            "package io\ntype Reader interface {\n\tRead() int\n}\ntype File struct{}\nfunc (f *File) Read() int { return 0 }",
+            "go",
+        );
+
+        let output = server
+            .semantiq_implementations("Shape".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("Implementations of 'Shape' (2 found"));
+        assert!(output.contains("shapes.rs:3-3 Circle (implements)"));
+
+        let output = server
+            .semantiq_implementations("Reader".to_string(), None, Some("json".to_string()))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["total_count"], 1);
+        assert_eq!(json["implementations"][0]["type_name"], "File");
+        assert_eq!(json["implementations"][0]["inferred"], true);
+    }
+
+    #[tokio::test]
+    async fn test_implementations_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_implementations(" ".to_string(), None, None)
+            .await;
+        assert_eq!(result.unwrap_err(), "Symbol name cannot be empty");
+
+        let output = server
+            .semantiq_implementations("Missing".to_string(), Some(5), None)
+            .await
+            .unwrap();
+        assert!(output.contains("No implementations of 'Missing' found"));
+    }
+
+    // ==================== semantiq_component_tree tests ====================
+
+    #[tokio::test]
+    async fn test_component_tree_lists_renders() {
+        let (server, _temp) = create_test_server();
+
+        index_test_file(
+            &server.store,
+            "src/App.tsx",
+            "export function App() {\n  return <Layout><Button label=\"ok\" /></Layout>;\n}\n",
+            "typescript",
+        );
+        index_test_file(
+            &server.store,
+            "src/Layout.tsx",
+            "export const Layout = ({ children }: Props) => <main><Header />{children}</main>;\n",
+            "typescript",
+        );
+
+        let output = server
+            .semantiq_component_tree("App".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("Component tree of 'App'"));
+        assert!(output.contains("- Layout (src/App.tsx:2)\n  - Header (src/Layout.tsx:1)\n"));
+        assert!(output.contains("- Button (src/App.tsx:2)"));
+
+        let output = server
+            .semantiq_component_tree("Layout".to_string(), Some(1), Some("json".to_string()))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(json["rendered_by"][0]["name"], "App");
+        assert_eq!(json["renders"][0]["name"], "Header");
+    }
+
+    #[tokio::test]
+    async fn test_component_tree_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_component_tree(" ".to_string(), None, None)
+            .await;
+        assert_eq!(result.unwrap_err(), "Component name cannot be empty");
+
+        let output = server
+            .semantiq_component_tree("Missing".to_string(), Some(50), None)
+            .await
+            .unwrap();
+        assert!(output.contains("No components rendering or rendered by 'Missing' found"));
+    }
+
+    // ==================== semantiq_related tests ====================
+
+    #[tokio::test]
+    async fn test_related_lists_imports_with_reasons() {
+        let (server, _temp) = create_test_server();
+        let lib_id = index_test_file(
+            &server.store,
+            "src/lib.rs",
+            "mod parser;\npub fn run() {}\n",
+            "rust",
+        );
+        index_test_file(
+            &server.store,
+            "src/parser.rs",
+            "pub fn parse() {}\n",
+            "rust",
+        );
+        server
+            .store
+            .insert_dependency(lib_id, "crate::parser", None, "local")
+            .unwrap();
+
+        let output = server
+            .semantiq_related("src/lib.rs".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("Files related to 'src/lib.rs' (1 found"));
+        assert!(output.contains("📄 src/parser.rs"));
+        assert!(output.contains("imports: imported as `crate::parser`"));
+
+        let output = server
+            .semantiq_related(
+                "./src/parser.rs".to_string(),
+                Some(5),
+                Some("json".to_string()),
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["file_path"], "src/parser.rs");
+        assert_eq!(json["related"][0]["file_path"], "src/lib.rs");
+        assert_eq!(json["related"][0]["reasons"][0]["signal"], "imported_by");
+    }
+
+    #[tokio::test]
+    async fn test_related_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server.semantiq_related(" ".to_string(), None, None).await;
+        assert_eq!(result.unwrap_err(), "File path cannot be empty");
+
+        let result = server
+            .semantiq_related("../secret.rs".to_string(), None, None)
+            .await;
+        assert_eq!(result.unwrap_err(), "File path must not contain '..'");
+
+        let output = server
+            .semantiq_related("src/missing.rs".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("No files related to 'src/missing.rs' found"));
+    }
+
+    // ==================== semantiq_overview tests ====================
+
+    #[tokio::test]
+    async fn test_overview_maps_directories() {
+        let (server, _temp) = create_test_server();
+        index_test_file(
+            &server.store,
+            "src/main.rs",
+            "fn main() { run(); }\n",
+            "rust",
+        );
+        index_test_file(
+            &server.store,
+            "src/engine/mod.rs",
+            "pub fn run() {}\n",
+            "rust",
+        );
+
+        let output = server.semantiq_overview(None, None).await.unwrap();
+        assert!(output.starts_with("Overview of '.': 2 files"));
+        assert!(output.contains("## Subdirectories (1)"));
+        assert!(output.contains("📁 src/: 2 files"));
+
+        let output = server
+            .semantiq_overview(Some("src".to_string()), Some("json".to_string()))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["path"], "src");
+        assert_eq!(json["summary"]["entry_files"][0], "src/main.rs");
+        assert_eq!(json["subdirectories"][0]["path"], "src/engine");
+    }
+
+    #[tokio::test]
+    async fn test_overview_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server.semantiq_overview(Some(" ".to_string()), None).await;
+        assert_eq!(result.unwrap_err(), "Path cannot be empty");
+
+        let result = server
+            .semantiq_overview(Some("../elsewhere".to_string()), None)
+            .await;
+        assert_eq!(result.unwrap_err(), "Path must not contain '..'");
+
+        let output = server
+            .semantiq_overview(Some("docs".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(output, "No indexed files under 'docs'.");
+    }
+
+    // ==================== semantiq_entrypoints tests ====================
+
+    #[tokio::test]
+    async fn test_entrypoints_lists_kinds() {
+        let (server, _temp) = create_test_server();
+        index_test_file(
+            &server.store,
+            "src/main.rs",
+            "fn main() { serve(); }\n",
+            "rust",
+        );
+        index_test_file(
+            &server.store,
+            "src/api.rs",
+            "#[get(\"/users\")]\nasync fn list_users() {}\n",
+            "rust",
+        );
+
+        let output = server
+            .semantiq_entrypoints(None, None, None, None)
+            .await
+            .unwrap();
+        assert!(output.starts_with("Entry points of '.' (2 found"));
+        assert!(output.contains("## Main functions (1)\n\n📄 src/main.rs:1 main\n"));
+        assert!(output.contains("📄 src/api.rs:2 GET /users → list_users\n"));
+
+        let output = server
+            .semantiq_entrypoints(
+                Some("route".to_string()),
+                Some("src".to_string()),
+                None,
+                Some("json".to_string()),
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["total_count"], 1);
+        assert_eq!(json["entrypoints"][0]["handler"], "list_users");
+    }
+
+    #[tokio::test]
+    async fn test_entrypoints_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_entrypoints(Some("test".to_string()), None, None, None)
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid kind 'test': expected one of main, route, command, export"
+        );
+
+        let result = server
+            .semantiq_entrypoints(None, Some("../elsewhere".to_string()), None, None)
+            .await;
+        assert_eq!(result.unwrap_err(), "Path must not contain '..'");
+
+        let output = server
+            .semantiq_entrypoints(Some("command".to_string()), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(output, "No command entry points found under '.'.");
+    }
+
+    // ==================== semantiq_routes tests ====================
+
+    #[tokio::test]
+    async fn test_routes_finds_handlers() {
+        let (server, temp) = create_test_server();
+        let content = "router.post('/users', createUser);\nrouter.get('/users/:id', showUser);\n\nfunction createUser(req, res) {}\nfunction showUser(req, res) {}\n";
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/users.js"), content).unwrap();
+        index_test_file(&server.store, "src/users.js", content, "javascript");
+
+        let output = server
+            .semantiq_routes(None, Some("GET /users/42".to_string()), None, None)
+            .await
+            .unwrap();
+        assert!(output.starts_with("HTTP routes matching 'GET /users/42' (1 found"));
+        assert!(output.contains("📄 src/users.js:2 GET /users/:id → showUser (src/users.js:5)\n"));
+
+        let output = server
+            .semantiq_routes(
+                Some("post".to_string()),
+                Some("/api/users".to_string()),
+                None,
+                Some("json".to_string()),
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["method"], "POST");
+        assert_eq!(json["routes"][0]["matched"], "suffix");
+        assert_eq!(json["routes"][0]["handler_definition"]["start_line"], 4);
+
+        // The route filter of semantiq_search returns the handler alone
+        let request = SemantiqSearch::new("users").with_route("POST /api/users");
+        let results = server.run_search(&request, Deadline::new()).unwrap();
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(
+            results.results[0].metadata.symbol_name.as_deref(),
+            Some("createUser")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_routes_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_routes(Some("fetch".to_string()), None, None, None)
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid method 'FETCH': expected one of GET, POST, PUT, DELETE, PATCH, HEAD, OPTIONS"
+        );
+
+        let result = server
+            .semantiq_routes(None, Some("POST /api users".to_string()), None, None)
+            .await;
+        assert!(
+            result
+                .unwrap_err()
+                .starts_with("Invalid path 'POST /api users'")
+        );
+
+        let request = SemantiqSearch::new("users").with_route(" ");
+        let result = server.run_search(&request, Deadline::new());
+        assert!(
+            matches!(result, Err(ToolError::InvalidParams(e)) if e.starts_with("Invalid route"))
+        );
+
+        let output = server
+            .semantiq_routes(None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(output, "No HTTP routes found in the index.");
+    }
+
+    // ==================== semantiq_config_usage tests ====================
+
+    #[tokio::test]
+    async fn test_config_usage_lists_reads_and_writes() {
+        let (server, temp) = create_test_server();
+        let content = "function loadConfig() {\n  const port = process.env.PORT || 3000;\n  return { port };\n}\n\nfunction setupTests() {\n  process.env.PORT = '0';\n}\n";
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/config.js"), content).unwrap();
+        index_test_file(&server.store, "src/config.js", content, "javascript");
+
+        let output = server
+            .semantiq_config_usage("PORT".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+        assert!(output.starts_with("Reads and writes of 'PORT' (2 found: 1 write, 1 read"));
+        assert!(output.contains("📄 src/config.js:7 [write] env PORT in setupTests\n"));
+        assert!(output.contains("   const port = process.env.PORT || 3000;\n"));
+
+        let output = server
+            .semantiq_config_usage(
+                "PORT".to_string(),
+                Some("env".to_string()),
+                Some("read".to_string()),
+                None,
+                Some("json".to_string()),
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["total_count"], 1);
+        assert_eq!(json["usages"][0]["symbol"], "loadConfig");
+        assert_eq!(json["usages"][0]["line"], 2);
+
+        let output = server
+            .semantiq_config_usage("port".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "No read or write of 'port' found. Indexed names resembling it: PORT"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_config_usage_validates_input() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_config_usage("DATABASE URL".to_string(), None, None, None, None)
+            .await;
+        assert!(
+            result
+                .unwrap_err()
+                .starts_with("Invalid name 'DATABASE URL'")
+        );
+
+        let result = server
+            .semantiq_config_usage(
+                "PORT".to_string(),
+                Some("file".to_string()),
+                None,
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid source 'file': expected one of env, config"
+        );
+
+        let request = SemantiqConfigUsage::new("PORT").with_access("delete");
+        let result = server.run_config_usage(&request);
+        assert_eq!(
+            result.unwrap_err(),
+            ToolError::InvalidParams(
+                "Invalid access 'delete': expected one of read, write".to_string()
+            )
+        );
+    }
+
+    // ==================== semantiq_index_issues tests ====================
+
+    #[tokio::test]
+    async fn test_index_issues_lists_files_not_fully_indexed() {
+        let (server, _temp) = create_test_server();
+
+        let output = server
+            .semantiq_index_issues(None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(output, "No indexing issues: every file was indexed.");
+
+        server
+            .store
+            .set_index_issues(
+                "assets/blob.rs",
+                &[IndexIssue::new(
+                    "assets/blob.rs",
+                    IndexIssueKind::ReadError,
+                    "binary file",
+                )],
+            )
+            .unwrap();
+        let output = server
+            .semantiq_index_issues(None, None, None)
+            .await
+            .unwrap();
+        assert!(output.starts_with("Indexing issues (1 found: 1 read_error"));
+        assert!(output.contains("📄 assets/blob.rs [read_error] binary file\n"));
+
+        let output = server
+            .semantiq_index_issues(
+                Some("parse_failure".to_string()),
+                None,
+                Some("json".to_string()),
+            )
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["total_count"], 0);
+        assert_eq!(json["counts"]["read_error"], 1);
+
+        let result = server
+            .semantiq_index_issues(Some("crash".to_string()), None, None)
+            .await;
+        assert!(result.unwrap_err().starts_with("Invalid kind 'crash'"));
+    }
+
+    // ==================== semantiq_explore tests ====================
+
+    #[tokio::test]
+    async fn test_explore_lists_directory_with_index_data() {
+        let (server, temp) = create_test_server();
+        let content = "fn retry_upload() {}";
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::create_dir_all(temp.path().join("node_modules")).unwrap();
+        std::fs::write(temp.path().join("src/upload.rs"), content).unwrap();
+        index_test_file(&server.store, "src/upload.rs", content, "rust");
+
+        let output = server.semantiq_explore(None, None, None).await.unwrap();
+        assert!(output.starts_with("Directory '.' ("));
+        assert!(output.contains("📁 node_modules/: excluded (excluded_directory)\n"));
+        assert!(output.contains("📁 src/: rust, 1 indexed files, indexed "));
+        assert!(output.contains("📄 .semantiq.db: "));
+
+        let output = server
+            .semantiq_explore(Some("src".to_string()), None, Some("json".to_string()))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["entries"][0]["path"], "src/upload.rs");
+        assert_eq!(json["entries"][0]["freshness"], "fresh");
+        assert_eq!(json["entries"][0]["top_symbols"][0], "retry_upload");
+
+        let result = server
+            .semantiq_explore(Some("docs".to_string()), None, None)
+            .await;
+        assert_eq!(result.unwrap_err(), "No directory 'docs' in the project");
+    }
+
+    // ==================== semantiq_session_summary tests ====================
+
+    #[tokio::test]
+    async fn test_session_summary_lists_searches() {
+        let (server, temp) = create_test_server();
+        let content = "fn retry_upload() { backoff(); }";
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/upload.rs"), content).unwrap();
+        index_test_file(&server.store, "src/upload.rs", content, "rust");
+
+        let results = server
+            .run_search(
+                &SemantiqSearch::new("retry_upload").with_session_id("chat-1"),
+                Deadline::new(),
+            )
+            .unwrap();
+        assert!(!results.results.is_empty());
+
+        let output = server
+            .semantiq_session_summary("chat-1".to_string(), None)
+            .await
+            .unwrap();
+        assert!(output.starts_with("Session 'chat-1': 1 searches, 1 files viewed"));
+        assert!(output.contains("1. 'retry_upload'"));
+        assert!(output.contains("📄 src/upload.rs:1-1"));
+
+        let json = server
+            .semantiq_session_summary("chat-1".to_string(), Some("json".to_string()))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["files"][0]["file_path"], "src/upload.rs");
+
+        let error = server
+            .semantiq_session_summary("chat-2".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Unknown session 'chat-2'"));
+    }
+
+    // ==================== semantiq_diff tests ====================
+
+    #[tokio::test]
+    async fn test_diff_validates_revisions() {
+        let (server, _temp) = create_test_server();
+
+        let result = server
+            .semantiq_diff("--output=/tmp/x".to_string(), None, None)
+            .await;
+        assert!(result.unwrap_err().contains("not a valid git revision"));
+
+        let result = server
+            .semantiq_diff("HEAD~1".to_string(), Some("main branch".to_string()), None)
+            .await;
+        assert!(result.unwrap_err().contains("Target revision"));
+
+        // The test project is not a git repository
+        let result = server.semantiq_diff("HEAD~1".to_string(), None, None).await;
+        assert!(result.unwrap_err().contains("not inside a git repository"));
+    }
+
+    // ==================== Reindex tests ====================
+
+    #[tokio::test]
+    async fn test_reindex_without_auto_indexer_returns_error() {
+        let (server, _temp) = create_test_server();
+
+        let result = server.semantiq_reindex(None).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("auto-indexing is disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_reindex_path_traversal_rejected() {
+        let (server, _temp) = create_test_server();
+
+        let result = server.semantiq_reindex(Some("../secret".to_string())).await;
+        assert!(result.unwrap_err().contains("'..'"));
+
+        let result = server
+            .semantiq_reindex(Some("/etc/passwd".to_string()))
+            .await;
+        assert!(result.unwrap_err().contains("relative"));
+    }
+
+    #[test]
+    fn test_format_reindex_report() {
+        let report = ReindexReport {
+            matched: 5,
+            indexed: vec!["src/lib.rs".to_string()],
+            removed: vec!["src/old.rs".to_string()],
+            unchanged: 3,
+            deferred: 2,
+            errors: vec![],
+        };
+
+        let output = format_reindex_report(Some("src"), &report);
+        assert!(output.starts_with("Reindex of 'src': 5 file(s) matched"));
+        assert!(output.contains("## Updated (1)\n- src/lib.rs"));
+        assert!(output.contains("## Removed (1)\n- src/old.rs"));
+        assert!(!output.contains("Failed"));
+        assert!(output.contains("Unchanged: 3"));
+        assert!(output.contains("Deferred: 2"));
+
+        let up_to_date = ReindexReport {
+            matched: 4,
+            unchanged: 4,
+            ..Default::default()
+        };
+        let output = format_reindex_report(None, &up_to_date);
+        assert!(output.contains("'.': 4 file(s) matched"));
+        assert!(output.contains("up to date"));
+    }
+
+    // ==================== ServerHandler tests ====================
+
+    #[test]
+    fn test_get_info_returns_correct_name() {
+        let (server, _temp) = create_test_server();
+        let info = server.get_info();
+
+        assert_eq!(info.server_info.name, "semantiq");
     }
 
     #[test]
@@ -882,6 +3974,19 @@ mod tests {
         assert!(instructions.contains("semantiq_find_refs"));
         assert!(instructions.contains("semantiq_deps"));
         assert!(instructions.contains("semantiq_explain"));
+        assert!(instructions.contains("semantiq_implementations"));
+        assert!(instructions.contains("semantiq_component_tree"));
+        assert!(instructions.contains("semantiq_entrypoints"));
+        assert!(instructions.contains("semantiq_routes"));
+        assert!(instructions.contains("semantiq_config_usage"));
+        assert!(instructions.contains("semantiq_related"));
+        assert!(instructions.contains("semantiq_overview"));
+        assert!(instructions.contains("semantiq_session_summary"));
+        assert!(instructions.contains("semantiq_index_issues"));
+        assert!(instructions.contains("semantiq_explore"));
+        assert!(instructions.contains("semantiq_batch_search"));
+        assert!(instructions.contains("semantiq_diff"));
+        assert!(instructions.contains("semantiq_reindex"));
     }
 
     #[test]
@@ -893,6 +3998,357 @@ mod tests {
         assert!(info.capabilities.tools.is_some());
     }
 
+    #[test]
+    fn test_get_info_enables_logging() {
+        let (server, _temp) = create_test_server();
+        let info = server.get_info();
+
+        // Logging is used for update and indexing progress notifications
+        assert!(info.capabilities.logging.is_some());
+    }
+
+    // ==================== Indexing progress tests ====================
+
+    #[test]
+    fn test_index_progress_reflects_tracker() {
+        let (server, _temp) = create_test_server();
+        assert_eq!(server.index_progress().phase, IndexPhase::Idle);
+
+        server.index_progress.begin_scan();
+        server.index_progress.begin_indexing(4);
+        server.index_progress.start_file("src/lib.rs");
+
+        let progress = server.index_progress();
+        assert_eq!(progress.phase, IndexPhase::Indexing);
+        assert_eq!(progress.total, 4);
+        assert_eq!(progress.current_file.as_deref(), Some("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_format_progress_message() {
+        let progress = IndexProgress {
+            phase: IndexPhase::Indexing,
+            total: 10,
+            processed: 5,
+            eta_ms: Some(2500),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_progress_message(&progress),
+            "Indexing 5/10 files (50%), ~3s remaining"
+        );
+
+        let progress = IndexProgress {
+            phase: IndexPhase::Complete,
+            indexed: 3,
+            skipped: 7,
+            elapsed_ms: 1500,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_progress_message(&progress),
+            "Indexing complete: 3 indexed, 7 unchanged, 0 errors in 1.5s"
+        );
+        assert_eq!(
+            format_progress_message(&IndexProgress {
+                warnings: 2,
+                ..progress
+            }),
+            "Indexing complete: 3 indexed, 7 unchanged, 0 errors, 2 warnings in 1.5s"
+        );
+    }
+
+    // ==================== Rate limiting tests ====================
+
+    #[tokio::test]
+    async fn test_blocking_runs_tool_calls_through_index_queue() {
+        let (server, _temp) = create_test_server();
+
+        let response = server
+            .blocking(|server| server.run_config_usage(&SemantiqConfigUsage::new("PORT")))
+            .await
+            .unwrap();
+        assert_eq!(response.total_count, 0);
+
+        // A panicking call is an internal error and releases its slot
+        let result: Result<(), ToolError> =
+            server.blocking(|_| panic!("index lookup panicked")).await;
+        assert!(matches!(result, Err(ToolError::Internal(_))));
+        assert_eq!(server.engine().async_store().in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_tool_returns_retry_after() {
+        let (mut server, _temp) = create_test_server();
+        server.limits = Arc::new(RateLimiter::new(&semantiq_index::LimitsConfig {
+            requests_per_second: 0.5,
+            burst: 2,
+            ..Default::default()
+        }));
+
+        for _ in 0..2 {
+            let result = server
+                .semantiq_explain("parse".to_string(), None, None)
+                .await;
+            assert!(result.is_ok());
+        }
+
+        let error = server
+            .semantiq_explain("parse".to_string(), None, None)
+            .await
+            .unwrap_err();
+        let error: serde_json::Value = serde_json::from_str(&error).unwrap();
+        assert_eq!(error["error"], "rate_limited");
+        assert!(error["retry_after_ms"].as_u64().unwrap() > 1000);
+        assert!(
+            error["message"]
+                .as_str()
+                .unwrap()
+                .contains("semantiq_explain")
+        );
+
+        // Other tools keep their own budget
+        assert!(
+            server
+                .semantiq_deps("src/lib.rs".to_string(), None, None)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_max_results_caps_search_limit() {
+        let (mut server, temp) = create_test_server();
+        server.limits = Arc::new(RateLimiter::new(&semantiq_index::LimitsConfig {
+            max_results: 2,
+            ..Default::default()
+        }));
+        for i in 0..5 {
+            std::fs::write(
+                temp.path().join(format!("handler_{}.rs", i)),
+                "fn handler() {}",
+            )
+            .unwrap();
+            index_test_file(
+                &server.store,
+                &format!("handler_{}.rs", i),
+                "fn handler() {}",
+                "rust",
+            );
+        }
+
+        let results = server
+            .run_search(
+                &SemantiqSearch::new("handler")
+                    .with_limit(500)
+                    .with_min_score(0.0),
+                Deadline::new(),
+            )
+            .unwrap();
+        assert_eq!(results.results.len(), 2);
+    }
+
+    #[test]
+    fn test_tool_schemas_describe_parameters() {
+        let tools = SemantiqServer::tool_box().list();
+        let schema = |name: &str| {
+            tools
+                .iter()
+                .find(|tool| tool.name == name)
+                .unwrap()
+                .schema_as_json_value()
+        };
+
+        let search = schema("semantiq_search");
+        let properties = &search["properties"];
+        assert_eq!(search["required"], serde_json::json!(["query"]));
+        assert_eq!(properties["query"]["maxLength"], 500);
+        assert_eq!(properties["min_score"]["minimum"], 0.0);
+        assert_eq!(properties["min_score"]["maximum"], 1.0);
+        assert_eq!(properties["limit"]["minimum"], 1.0);
+        assert_eq!(properties["file_type"]["anyOf"][0]["type"], "array");
+        let kinds = &properties["symbol_kind"]["anyOf"][0]["items"]["enum"];
+        assert!(kinds.as_array().unwrap().contains(&"struct".into()));
+        assert_eq!(properties["package"]["anyOf"][0]["type"], "array");
+        assert_eq!(
+            properties["qualifiers"]["anyOf"][0]["items"]["enum"],
+            serde_json::json!(["public", "async", "static", "abstract", "generic"])
+        );
+        assert_eq!(
+            properties["scope"]["enum"],
+            serde_json::json!(["project", "external", "all"])
+        );
+        assert!(properties["format"]["description"].is_string());
+
+        let refs = schema("semantiq_find_refs");
+        assert_eq!(refs["required"], serde_json::json!(["symbol"]));
+        assert_eq!(
+            refs["properties"]["kind"]["enum"],
+            serde_json::json!(["definition", "usage", "all"])
+        );
+        assert!(schema("semantiq_reindex")["required"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_out_of_range_parameters() {
+        let (server, _temp) = create_test_server();
+
+        let error = server
+            .semantiq_search(
+                "handler".to_string(),
+                None,
+                Some(1.5),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "Invalid min_score 1.5: expected a number between 0.0 and 1.0"
+        );
+
+        let error = server
+            .semantiq_search(
+                "handler".to_string(),
+                None,
+                None,
+                None,
+                Some("function,fucntion".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            error.starts_with("Invalid symbol_kind 'fucntion': expected one of function, method")
+        );
+
+        let error = server
+            .run_search(
+                &SemantiqSearch::new("handler").with_qualifiers("public,exported"),
+                Deadline::new(),
+            )
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "Invalid qualifier 'exported': expected one of public, async, static, abstract, generic"
+        );
+
+        let error = server
+            .run_search(
+                &SemantiqSearch::new("handler").with_context_lines(51),
+                Deadline::new(),
+            )
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "Invalid context_lines 51: expected at most 50");
+    }
+
+    #[tokio::test]
+    async fn test_search_expands_results_to_enclosing_symbol() {
+        let (server, temp) = create_test_server();
+        let content =
+            "fn load_config() {\n    let text = read_settings_file();\n    parse(text)\n}\n";
+        std::fs::write(temp.path().join("lib.rs"), content).unwrap();
+        index_test_file(&server.store, "lib.rs", content, "rust");
+
+        let output = server
+            .semantiq_search(
+                "read_settings_file".to_string(),
+                None,
+                Some(0.0),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(true),
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("Lines 1-4 (matched 2-2)"));
+        assert!(output.contains("fn load_config() {"));
+    }
+
+    #[tokio::test]
+    async fn test_search_and_explain_symbol_qualifiers() {
+        let (server, temp) = create_test_server();
+        let content = "pub async fn load_config() {}\nfn load_defaults() {}";
+        std::fs::write(temp.path().join("lib.rs"), content).unwrap();
+        index_test_file(&server.store, "lib.rs", content, "rust");
+
+        let output = server
+            .semantiq_search(
+                "load".to_string(),
+                None,
+                Some(0.0),
+                None,
+                None,
+                None,
+                Some("public,async".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("Symbol: load_config (public async function)"));
+        assert!(!output.contains("load_defaults"));
+
+        let output = server
+            .semantiq_explain("load_config".to_string(), None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("## Definition 1 (public async function)"));
+    }
+
     // ==================== Edge case tests ====================
 
     #[tokio::test]
@@ -901,7 +4357,27 @@ mod tests {
 
         // Should handle special regex/FTS characters gracefully
         let result = server
-            .semantiq_search("test*".to_string(), Some(10), None, None, None)
+            .semantiq_search(
+                "test*".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_ok());
@@ -912,7 +4388,27 @@ mod tests {
         let (server, _temp) = create_test_server();
 
         let result = server
-            .semantiq_search("函数".to_string(), Some(10), None, None, None)
+            .semantiq_search(
+                "函数".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
             .await;
 
         assert!(result.is_ok());
@@ -923,9 +4419,160 @@ mod tests {
         let (server, _temp) = create_test_server();
 
         let result = server
-            .semantiq_find_refs("operator+".to_string(), Some(10))
+            .semantiq_find_refs(
+                "operator+".to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await;
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_saved_search_fills_in_parameters() {
+        let (server, temp) = create_test_server();
+        let content = "fn handle_post() {}\nfn handle_get() {}";
+        std::fs::write(temp.path().join("lib.rs"), content).unwrap();
+        index_test_file(&server.store, "lib.rs", content, "rust");
+        server
+            .store
+            .save_query(
+                &SavedQuery::new("handlers", "handle_{verb}")
+                    .with_filter("min_score", "0")
+                    .with_filter("symbol_kind", "function"),
+            )
+            .unwrap();
+
+        let params = HashMap::from([("verb".to_string(), "post".to_string())]);
+        let output = server
+            .semantiq_saved_search(
+                "handlers".to_string(),
+                Some(params),
+                None,
+                Some("json".to_string()),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let results: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(results["query"], "handle_post");
+        assert_eq!(
+            results["results"][0]["metadata"]["symbol_name"],
+            "handle_post"
+        );
+
+        let missing = server
+            .semantiq_saved_search(
+                "handlers".to_string(),
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(missing.contains("Missing value for parameter 'verb'"));
+
+        let unknown = server
+            .semantiq_saved_search(
+                "handler".to_string(),
+                None,
+                None,
+                None,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(unknown.contains("Unknown saved query 'handler'. Saved queries: handlers"));
+    }
+
+    #[test]
+    fn test_tool_calls_are_counted_in_usage_stats() {
+        let (server, temp) = create_test_server();
+        let content = "fn parse_config() {}";
+        std::fs::write(temp.path().join("lib.rs"), content).unwrap();
+        index_test_file(&server.store, "lib.rs", content, "rust");
+
+        let search = SemantiqSearch::new("parse_config").with_filter("min_score", "0");
+        server
+            .run_search(&search.unwrap(), Deadline::new())
+            .unwrap();
+        server
+            .run_search(
+                &SemantiqSearch::new("nothing_like_this_xyz"),
+                Deadline::new(),
+            )
+            .unwrap();
+        assert!(
+            server
+                .run_search(&SemantiqSearch::new(""), Deadline::new())
+                .is_err()
+        );
+
+        let usage = server.store.get_tool_usage(1).unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].tool, "semantiq_search");
+        assert_eq!(usage[0].calls, 3);
+        assert_eq!(usage[0].errors, 1);
+        assert_eq!(usage[0].zero_results, 1);
+        let categories = server.store.get_query_category_usage(1).unwrap();
+        assert_eq!(categories[0].category.as_deref(), Some("identifier"));
+        assert_eq!(categories[0].calls, 2);
+
+        server.store.set_usage_stats(false);
+        server
+            .run_search(&SemantiqSearch::new("parse_config"), Deadline::new())
+            .unwrap();
+        assert_eq!(server.store.get_tool_usage(1).unwrap()[0].calls, 3);
+    }
+
+    #[test]
+    fn test_saved_search_request_applies_filters() {
+        let saved = SavedQuery::new("handlers", "handler {verb}")
+            .with_filter("file_type", "rs,ts")
+            .with_filter("limit", "5")
+            .with_filter("expand_to_symbol", "true");
+        let request = SemantiqSavedSearch::new("handlers")
+            .with_param("verb", "get")
+            .search_request(&saved)
+            .unwrap();
+        assert_eq!(request.query, "handler get");
+        assert_eq!(request.file_type.as_deref(), Some("rs,ts"));
+        assert_eq!(request.limit, Some(5));
+        assert_eq!(request.expand_to_symbol, Some(true));
+
+        let request = SemantiqSavedSearch::new("handlers")
+            .with_param("verb", "get")
+            .with_limit(50)
+            .search_request(&saved)
+            .unwrap();
+        assert_eq!(request.limit, Some(50));
+
+        let capped = SemantiqSavedSearch {
+            max_limit: Some(3),
+            ..SemantiqSavedSearch::new("handlers").with_param("verb", "get")
+        };
+        assert_eq!(capped.search_request(&saved).unwrap().limit, Some(3));
+
+        let invalid = SavedQuery::new("bad", "x").with_filter("limit", "many");
+        assert_eq!(
+            SemantiqSavedSearch::new("bad")
+                .search_request(&invalid)
+                .unwrap_err(),
+            ToolError::InvalidParams("Invalid limit 'many': expected a number".to_string())
+        );
+        let unknown = SavedQuery::new("bad", "x").with_filter("language", "rust");
+        assert!(matches!(
+            SemantiqSavedSearch::new("bad").search_request(&unknown),
+            Err(ToolError::InvalidParams(message)) if message.starts_with("Unknown filter 'language'")
+        ));
+    }
 }
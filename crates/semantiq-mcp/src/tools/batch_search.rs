@@ -0,0 +1,81 @@
+use super::{SemantiqSearch, ToolError};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_batch_search`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqBatchSearch {
+    /// Searches to run, each with the parameters of `semantiq_search`
+    pub queries: Vec<SemantiqSearch>,
+}
+
+impl SemantiqBatchSearch {
+    pub const MAX_QUERIES: usize = 10;
+
+    pub fn new(queries: Vec<SemantiqSearch>) -> Self {
+        Self { queries }
+    }
+
+    /// The searches, each checked like a call of `semantiq_search` so that
+    /// a mistake in one is reported before any of them runs
+    pub fn validated_queries(&self) -> Result<&[SemantiqSearch], ToolError> {
+        if self.queries.is_empty() {
+            return Err(ToolError::InvalidParams(
+                "Queries cannot be empty".to_string(),
+            ));
+        }
+        if self.queries.len() > Self::MAX_QUERIES {
+            return Err(ToolError::InvalidParams(format!(
+                "Too many queries: {} given, at most {} per batch",
+                self.queries.len(),
+                Self::MAX_QUERIES
+            )));
+        }
+        for (i, query) in self.queries.iter().enumerate() {
+            query
+                .validated_query()
+                .and_then(|_| query.options())
+                .map_err(|e| ToolError::InvalidParams(format!("Query {}: {}", i + 1, e)))?;
+        }
+        Ok(&self.queries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validated_queries() {
+        let batch = SemantiqBatchSearch::new(vec![
+            SemantiqSearch::new("load config"),
+            SemantiqSearch::new("parse").with_file_type("rs"),
+        ]);
+        assert_eq!(batch.validated_queries().map(<[_]>::len), Ok(2));
+
+        assert_eq!(
+            SemantiqBatchSearch::default().validated_queries().err(),
+            Some(ToolError::InvalidParams(
+                "Queries cannot be empty".to_string()
+            ))
+        );
+
+        let too_many = SemantiqBatchSearch::new(vec![SemantiqSearch::new("load"); 11]);
+        assert_eq!(
+            too_many.validated_queries().err(),
+            Some(ToolError::InvalidParams(
+                "Too many queries: 11 given, at most 10 per batch".to_string()
+            ))
+        );
+
+        let invalid = SemantiqBatchSearch::new(vec![
+            SemantiqSearch::new("load"),
+            SemantiqSearch::new("parse").with_min_score(2.0),
+        ]);
+        assert_eq!(
+            invalid.validated_queries().err(),
+            Some(ToolError::InvalidParams(
+                "Query 2: Invalid min_score 2: expected a number between 0.0 and 1.0".to_string()
+            ))
+        );
+    }
+}
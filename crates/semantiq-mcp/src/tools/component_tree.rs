@@ -0,0 +1,40 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_component_tree`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqComponentTree {
+    /// React component name
+    pub component: String,
+    /// Levels of rendered components to follow
+    pub depth: Option<usize>,
+}
+
+impl SemantiqComponentTree {
+    pub const DEFAULT_DEPTH: usize = 3;
+    pub const MAX_DEPTH: usize = 10;
+
+    pub fn new(component: &str) -> Self {
+        Self {
+            component: component.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Trimmed component name
+    pub fn validated_component(&self) -> Result<&str, ToolError> {
+        validate_text(&self.component, "Component name")
+    }
+
+    /// Requested depth, defaulted and capped to [`Self::MAX_DEPTH`]
+    pub fn effective_depth(&self) -> usize {
+        self.depth
+            .unwrap_or(Self::DEFAULT_DEPTH)
+            .clamp(1, Self::MAX_DEPTH)
+    }
+}
@@ -0,0 +1,96 @@
+use super::{ToolError, validate_text};
+use semantiq_parser::{ConfigAccess, ConfigSource};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_config_usage`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqConfigUsage {
+    /// Environment variable or configuration key, or a glob (`DATABASE_*`)
+    pub name: String,
+    /// `env` or `config`, both when `None`
+    pub source: Option<String>,
+    /// `read` or `write`, both when `None`
+    pub access: Option<String>,
+    /// Sites listed
+    pub limit: Option<usize>,
+}
+
+impl SemantiqConfigUsage {
+    pub const DEFAULT_LIMIT: usize = 50;
+    pub const MAX_LIMIT: usize = 500;
+    pub const SOURCES: &'static [&'static str] = &["env", "config"];
+    pub const ACCESSES: &'static [&'static str] = &["read", "write"];
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    pub fn with_access(mut self, access: &str) -> Self {
+        self.access = Some(access.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Trimmed name, made of the characters of variable names and
+    /// configuration keys and `*` wildcards
+    pub fn validated_name(&self) -> Result<&str, ToolError> {
+        let name = validate_text(&self.name, "Name")?;
+        if !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | ':' | '/' | '*'))
+        {
+            return Err(ToolError::InvalidParams(format!(
+                "Invalid name '{}': expected an environment variable or configuration key such as 'DATABASE_URL' or 'db.url', or a glob such as 'DATABASE_*'",
+                name
+            )));
+        }
+        Ok(name)
+    }
+
+    /// Requested source, `None` for both
+    pub fn validated_source(&self) -> Result<Option<ConfigSource>, ToolError> {
+        let Some(ref source) = self.source else {
+            return Ok(None);
+        };
+        ConfigSource::parse(source.trim()).map(Some).ok_or_else(|| {
+            ToolError::InvalidParams(format!(
+                "Invalid source '{}': expected one of {}",
+                source,
+                Self::SOURCES.join(", ")
+            ))
+        })
+    }
+
+    /// Requested access, `None` for both
+    pub fn validated_access(&self) -> Result<Option<ConfigAccess>, ToolError> {
+        let Some(ref access) = self.access else {
+            return Ok(None);
+        };
+        ConfigAccess::parse(access.trim()).map(Some).ok_or_else(|| {
+            ToolError::InvalidParams(format!(
+                "Invalid access '{}': expected one of {}",
+                access,
+                Self::ACCESSES.join(", ")
+            ))
+        })
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .clamp(1, Self::MAX_LIMIT)
+    }
+}
@@ -1,45 +1,44 @@
+use super::{ToolError, validate_text};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Parameters of `semantiq_deps`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SemantiqDeps {
     pub file_path: String,
-    pub direction: DependencyDirection,
-    pub depth: Option<usize>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "lowercase")]
-pub enum DependencyDirection {
-    /// What this file imports
-    Imports,
-    /// What imports this file
-    ImportedBy,
-    /// Both directions
-    #[default]
-    Both,
+    /// Symbol of the file to analyze instead of the whole file
+    #[serde(default)]
+    pub symbol: Option<String>,
 }
 
 impl SemantiqDeps {
     pub fn new(file_path: &str) -> Self {
         Self {
             file_path: file_path.to_string(),
-            direction: DependencyDirection::default(),
-            depth: None,
+            symbol: None,
         }
     }
 
-    pub fn imports_only(mut self) -> Self {
-        self.direction = DependencyDirection::Imports;
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        self.symbol = Some(symbol.to_string());
         self
     }
 
-    pub fn imported_by_only(mut self) -> Self {
-        self.direction = DependencyDirection::ImportedBy;
-        self
+    /// Trimmed symbol name, if one is given
+    pub fn validated_symbol(&self) -> Result<Option<&str>, ToolError> {
+        self.symbol
+            .as_deref()
+            .map(|symbol| validate_text(symbol, "Symbol name"))
+            .transpose()
     }
 
-    pub fn with_depth(mut self, depth: usize) -> Self {
-        self.depth = Some(depth);
-        self
+    /// Trimmed file path, rejecting path traversal
+    pub fn validated_path(&self) -> Result<&str, ToolError> {
+        let file_path = validate_text(&self.file_path, "File path")?;
+        if file_path.contains("..") {
+            return Err(ToolError::InvalidParams(
+                "File path must not contain '..'".to_string(),
+            ));
+        }
+        Ok(file_path)
     }
 }
@@ -0,0 +1,44 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_diff`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqDiff {
+    /// Base revision (commit, branch or tag)
+    pub from: String,
+    /// Revision compared against `from`, defaults to `HEAD`
+    pub to: Option<String>,
+}
+
+impl SemantiqDiff {
+    pub const DEFAULT_TO: &'static str = "HEAD";
+
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            to: Some(to.to_string()),
+        }
+    }
+
+    /// Trimmed revisions to compare, `to` defaulted to [`Self::DEFAULT_TO`]
+    pub fn validated_revisions(&self) -> Result<(&str, &str), ToolError> {
+        let from = validate_revision(&self.from, "Base revision")?;
+        let to = match self.to.as_deref() {
+            Some(to) if !to.trim().is_empty() => validate_revision(to, "Target revision")?,
+            _ => Self::DEFAULT_TO,
+        };
+        Ok((from, to))
+    }
+}
+
+/// Revisions are passed to git: reject anything that could be read as an option
+fn validate_revision<'a>(value: &'a str, what: &str) -> Result<&'a str, ToolError> {
+    let value = validate_text(value, what)?;
+    if value.starts_with('-') || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(ToolError::InvalidParams(format!(
+            "{} '{}' is not a valid git revision",
+            what, value
+        )));
+    }
+    Ok(value)
+}
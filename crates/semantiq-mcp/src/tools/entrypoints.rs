@@ -0,0 +1,71 @@
+use super::{ToolError, validate_text};
+use semantiq_parser::EntrypointKind;
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_entrypoints`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqEntrypoints {
+    /// `main`, `route`, `command` or `export`, all kinds when `None`
+    pub kind: Option<String>,
+    /// Directory relative to the project root
+    pub path: Option<String>,
+    /// Entry points listed per kind
+    pub limit: Option<usize>,
+}
+
+impl SemantiqEntrypoints {
+    pub const DEFAULT_LIMIT: usize = 25;
+    pub const MAX_LIMIT: usize = 500;
+    pub const KINDS: &'static [&'static str] = &["main", "route", "command", "export"];
+
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Requested kind of entry points, `None` for all
+    pub fn validated_kind(&self) -> Result<Option<EntrypointKind>, ToolError> {
+        let Some(ref kind) = self.kind else {
+            return Ok(None);
+        };
+        EntrypointKind::parse(kind.trim()).map(Some).ok_or_else(|| {
+            ToolError::InvalidParams(format!(
+                "Invalid kind '{}': expected one of {}",
+                kind,
+                Self::KINDS.join(", ")
+            ))
+        })
+    }
+
+    /// Trimmed directory path, "." for the project root, rejecting path
+    /// traversal
+    pub fn validated_path(&self) -> Result<&str, ToolError> {
+        let Some(ref path) = self.path else {
+            return Ok(".");
+        };
+        let path = validate_text(path, "Path")?;
+        if path.contains("..") {
+            return Err(ToolError::InvalidParams(
+                "Path must not contain '..'".to_string(),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .clamp(1, Self::MAX_LIMIT)
+    }
+}
@@ -0,0 +1,52 @@
+use thiserror::Error;
+
+/// Error returned by a tool call
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ToolError {
+    /// The request parameters are invalid
+    #[error("{0}")]
+    InvalidParams(String),
+    /// The tool cannot run on this server (e.g. auto-indexing is disabled)
+    #[error("{0}")]
+    Unavailable(String),
+    /// The tool failed; details are logged rather than returned
+    #[error("{0}")]
+    Internal(String),
+    /// The tool was called too often; the client should retry after a delay
+    #[error("{message}, retry after {retry_after_ms} ms")]
+    RateLimited {
+        message: String,
+        retry_after_ms: u64,
+    },
+}
+
+impl ToolError {
+    /// Category logged with failed tool calls
+    pub fn category(&self) -> &'static str {
+        match self {
+            ToolError::InvalidParams(_) => "invalid_params",
+            ToolError::Unavailable(_) => "unavailable",
+            ToolError::Internal(_) => "internal",
+            ToolError::RateLimited { .. } => "rate_limited",
+        }
+    }
+}
+
+/// Error text returned to MCP clients. Rate limiting is reported as JSON so
+/// that agents can read the delay to wait before retrying.
+impl From<ToolError> for String {
+    fn from(error: ToolError) -> Self {
+        match error {
+            ToolError::RateLimited {
+                ref message,
+                retry_after_ms,
+            } => serde_json::json!({
+                "error": error.category(),
+                "message": message,
+                "retry_after_ms": retry_after_ms,
+            })
+            .to_string(),
+            _ => error.to_string(),
+        }
+    }
+}
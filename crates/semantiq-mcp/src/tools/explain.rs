@@ -1,41 +1,29 @@
+use super::{ToolError, validate_text};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Parameters of `semantiq_explain`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SemantiqExplain {
     pub symbol: String,
-    pub include_source: bool,
-    pub include_docs: bool,
-    pub include_related: bool,
+    /// Index generation to explain the symbol at
+    pub as_of: Option<u64>,
 }
 
 impl SemantiqExplain {
     pub fn new(symbol: &str) -> Self {
         Self {
             symbol: symbol.to_string(),
-            include_source: true,
-            include_docs: true,
-            include_related: true,
+            as_of: None,
         }
     }
 
-    pub fn minimal(mut self) -> Self {
-        self.include_source = false;
-        self.include_related = false;
+    pub fn as_of(mut self, generation: u64) -> Self {
+        self.as_of = Some(generation);
         self
     }
 
-    pub fn with_source(mut self, include: bool) -> Self {
-        self.include_source = include;
-        self
-    }
-
-    pub fn with_docs(mut self, include: bool) -> Self {
-        self.include_docs = include;
-        self
-    }
-
-    pub fn with_related(mut self, include: bool) -> Self {
-        self.include_related = include;
-        self
+    /// Trimmed symbol name
+    pub fn validated_symbol(&self) -> Result<&str, ToolError> {
+        validate_text(&self.symbol, "Symbol name")
     }
 }
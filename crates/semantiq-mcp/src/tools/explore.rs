@@ -0,0 +1,81 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_explore`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqExplore {
+    /// Directory relative to the project root, the root when `None`
+    pub path: Option<String>,
+    /// Entries listed
+    pub limit: Option<usize>,
+}
+
+impl SemantiqExplore {
+    pub const DEFAULT_LIMIT: usize = 200;
+    pub const MAX_LIMIT: usize = 1000;
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Trimmed directory path, "." for the project root, rejecting path
+    /// traversal
+    pub fn validated_path(&self) -> Result<&str, ToolError> {
+        let Some(ref path) = self.path else {
+            return Ok(".");
+        };
+        let path = validate_text(path, "Path")?;
+        if path.contains("..") {
+            return Err(ToolError::InvalidParams(
+                "Path must not contain '..'".to_string(),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validated_path() {
+        assert_eq!(SemantiqExplore::default().validated_path(), Ok("."));
+        assert_eq!(
+            SemantiqExplore::default()
+                .with_path(" src/api ")
+                .validated_path(),
+            Ok("src/api")
+        );
+        assert!(
+            SemantiqExplore::default()
+                .with_path("../etc")
+                .validated_path()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_effective_limit() {
+        assert_eq!(SemantiqExplore::default().effective_limit(), 200);
+        assert_eq!(
+            SemantiqExplore::default()
+                .with_limit(100_000)
+                .effective_limit(),
+            1000
+        );
+    }
+}
@@ -1,20 +1,34 @@
+use super::{MAX_INPUT_LENGTH, ToolError, validate_text};
+use semantiq_retrieval::{FindRefsOptions, RefKind};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Parameters of `semantiq_find_refs`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SemantiqFindRefs {
     pub symbol: String,
     pub limit: Option<usize>,
-    pub include_definitions: bool,
-    pub include_usages: bool,
+    /// "definition", "usage" or "all" (default)
+    pub kind: Option<String>,
+    /// Only files under this relative path
+    pub path_prefix: Option<String>,
+    pub exclude_tests: Option<bool>,
+    /// Skip usages inside comments
+    pub exclude_comments: Option<bool>,
+    /// Skip usages inside string literals
+    pub exclude_strings: Option<bool>,
+    /// Follow the links of the symbol to other languages
+    pub cross_language: Option<bool>,
+    /// Index generation to find the references at
+    pub as_of: Option<u64>,
 }
 
 impl SemantiqFindRefs {
+    pub const MAX_LIMIT: usize = 1000;
+
     pub fn new(symbol: &str) -> Self {
         Self {
             symbol: symbol.to_string(),
-            limit: None,
-            include_definitions: true,
-            include_usages: true,
+            ..Default::default()
         }
     }
 
@@ -23,13 +37,85 @@ impl SemantiqFindRefs {
         self
     }
 
-    pub fn definitions_only(mut self) -> Self {
-        self.include_usages = false;
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    pub fn with_path_prefix(mut self, prefix: &str) -> Self {
+        self.path_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn excluding_tests(mut self, exclude: bool) -> Self {
+        self.exclude_tests = Some(exclude);
         self
     }
 
-    pub fn usages_only(mut self) -> Self {
-        self.include_definitions = false;
+    pub fn excluding_comments(mut self, exclude: bool) -> Self {
+        self.exclude_comments = Some(exclude);
         self
     }
+
+    pub fn excluding_strings(mut self, exclude: bool) -> Self {
+        self.exclude_strings = Some(exclude);
+        self
+    }
+
+    pub fn following_cross_language(mut self, follow: bool) -> Self {
+        self.cross_language = Some(follow);
+        self
+    }
+
+    pub fn as_of(mut self, generation: u64) -> Self {
+        self.as_of = Some(generation);
+        self
+    }
+
+    /// Trimmed symbol name
+    pub fn validated_symbol(&self) -> Result<&str, ToolError> {
+        validate_text(&self.symbol, "Symbol name")
+    }
+
+    /// Find-references options built from the filters
+    pub fn options(&self) -> Result<FindRefsOptions, ToolError> {
+        let limit = self
+            .limit
+            .unwrap_or(FindRefsOptions::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT);
+
+        let kind = match self.kind.as_deref() {
+            Some(k) => RefKind::parse(k).ok_or_else(|| {
+                ToolError::InvalidParams(format!(
+                    "Invalid kind '{}': expected 'definition', 'usage' or 'all'",
+                    k
+                ))
+            })?,
+            None => RefKind::All,
+        };
+
+        if let Some(ref prefix) = self.path_prefix
+            && (prefix.contains("..") || prefix.len() > MAX_INPUT_LENGTH)
+        {
+            return Err(ToolError::InvalidParams(
+                "Invalid path_prefix: must be a relative path without '..'".to_string(),
+            ));
+        }
+
+        let mut options = FindRefsOptions::new()
+            .with_limit(limit)
+            .with_kind(kind)
+            .excluding_tests(self.exclude_tests.unwrap_or(false))
+            .excluding_comments(self.exclude_comments.unwrap_or(false))
+            .excluding_strings(self.exclude_strings.unwrap_or(false))
+            .following_cross_language(self.cross_language.unwrap_or(false));
+        if let Some(ref prefix) = self.path_prefix {
+            options = options.with_path_prefix(prefix);
+        }
+        if let Some(generation) = self.as_of {
+            options = options.as_of_generation(generation);
+        }
+
+        Ok(options)
+    }
 }
@@ -0,0 +1,39 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_implementations`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqImplementations {
+    /// Trait, interface or base type name
+    pub symbol: String,
+    pub limit: Option<usize>,
+}
+
+impl SemantiqImplementations {
+    pub const DEFAULT_LIMIT: usize = 50;
+    pub const MAX_LIMIT: usize = 1000;
+
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Trimmed trait or interface name
+    pub fn validated_symbol(&self) -> Result<&str, ToolError> {
+        validate_text(&self.symbol, "Symbol name")
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT)
+    }
+}
@@ -0,0 +1,88 @@
+use super::ToolError;
+use semantiq_index::IndexIssueKind;
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_index_issues`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqIndexIssues {
+    /// `parse_failure`, `read_error`, `too_large` or `encoding`, any when
+    /// `None`
+    pub kind: Option<String>,
+    /// Issues listed
+    pub limit: Option<usize>,
+}
+
+impl SemantiqIndexIssues {
+    pub const DEFAULT_LIMIT: usize = 50;
+    pub const MAX_LIMIT: usize = 500;
+
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Requested kind, `None` for any
+    pub fn validated_kind(&self) -> Result<Option<IndexIssueKind>, ToolError> {
+        let Some(ref kind) = self.kind else {
+            return Ok(None);
+        };
+        IndexIssueKind::from_name(kind.trim())
+            .map(Some)
+            .ok_or_else(|| {
+                let kinds: Vec<&str> = IndexIssueKind::ALL.iter().map(|k| k.as_str()).collect();
+                ToolError::InvalidParams(format!(
+                    "Invalid kind '{}': expected one of {}",
+                    kind,
+                    kinds.join(", ")
+                ))
+            })
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validated_kind() {
+        assert_eq!(SemantiqIndexIssues::default().validated_kind(), Ok(None));
+        assert_eq!(
+            SemantiqIndexIssues::default()
+                .with_kind(" too_large ")
+                .validated_kind(),
+            Ok(Some(IndexIssueKind::TooLarge))
+        );
+        assert_eq!(
+            SemantiqIndexIssues::default()
+                .with_kind("crash")
+                .validated_kind(),
+            Err(ToolError::InvalidParams(
+                "Invalid kind 'crash': expected one of parse_failure, read_error, too_large, encoding"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_effective_limit() {
+        assert_eq!(SemantiqIndexIssues::default().effective_limit(), 50);
+        assert_eq!(
+            SemantiqIndexIssues::default()
+                .with_limit(10_000)
+                .effective_limit(),
+            500
+        );
+    }
+}
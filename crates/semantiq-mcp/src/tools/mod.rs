@@ -1,9 +1,91 @@
+//! Tool requests shared by the MCP server and the HTTP API
+//!
+//! Each request mirrors the parameters of one MCP tool and validates them.
+//! [`crate::SemantiqServer`] runs a request and returns the shared result
+//! types of `semantiq_retrieval`: the MCP tools render them as markdown or
+//! JSON, the HTTP API serializes them as JSON.
+
+mod batch_search;
+mod component_tree;
+mod config_usage;
 mod deps;
+mod diff;
+mod entrypoints;
+mod error;
 mod explain;
+mod explore;
 mod find_refs;
+mod implementations;
+mod index_issues;
+mod overview;
+mod reindex;
+mod related;
+mod routes;
+mod saved_search;
+pub(crate) mod schema;
 mod search;
+mod session_summary;
 
+pub use batch_search::SemantiqBatchSearch;
+pub use component_tree::SemantiqComponentTree;
+pub use config_usage::SemantiqConfigUsage;
 pub use deps::SemantiqDeps;
+pub use diff::SemantiqDiff;
+pub use entrypoints::SemantiqEntrypoints;
+pub use error::ToolError;
 pub use explain::SemantiqExplain;
+pub use explore::SemantiqExplore;
 pub use find_refs::SemantiqFindRefs;
+pub use implementations::SemantiqImplementations;
+pub use index_issues::SemantiqIndexIssues;
+pub use overview::SemantiqOverview;
+pub use reindex::{ReindexOutcome, SemantiqReindex};
+pub use related::SemantiqRelated;
+pub use routes::SemantiqRoutes;
+pub use saved_search::SemantiqSavedSearch;
 pub use search::SemantiqSearch;
+pub use session_summary::SemantiqSessionSummary;
+
+/// Maximum length of text parameters (queries, symbols, paths)
+pub const MAX_INPUT_LENGTH: usize = 500;
+
+/// Trim a required text parameter and check its length.
+/// `what` names the parameter in error messages (e.g. "Query").
+fn validate_text<'a>(value: &'a str, what: &str) -> Result<&'a str, ToolError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(ToolError::InvalidParams(format!(
+            "{} cannot be empty",
+            what
+        )));
+    }
+    if value.len() > MAX_INPUT_LENGTH {
+        return Err(ToolError::InvalidParams(format!(
+            "{} exceeds maximum length of {} characters",
+            what, MAX_INPUT_LENGTH
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_text() {
+        assert_eq!(validate_text("  load  ", "Symbol name"), Ok("load"));
+        assert_eq!(
+            validate_text(" ", "Query"),
+            Err(ToolError::InvalidParams(
+                "Query cannot be empty".to_string()
+            ))
+        );
+        assert_eq!(
+            validate_text(&"a".repeat(501), "File path"),
+            Err(ToolError::InvalidParams(
+                "File path exceeds maximum length of 500 characters".to_string()
+            ))
+        );
+    }
+}
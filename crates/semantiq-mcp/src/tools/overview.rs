@@ -0,0 +1,31 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_overview`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqOverview {
+    pub path: Option<String>,
+}
+
+impl SemantiqOverview {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: Some(path.to_string()),
+        }
+    }
+
+    /// Trimmed directory path, "." for the project root, rejecting path
+    /// traversal
+    pub fn validated_path(&self) -> Result<&str, ToolError> {
+        let Some(ref path) = self.path else {
+            return Ok(".");
+        };
+        let path = validate_text(path, "Path")?;
+        if path.contains("..") {
+            return Err(ToolError::InvalidParams(
+                "Path must not contain '..'".to_string(),
+            ));
+        }
+        Ok(path)
+    }
+}
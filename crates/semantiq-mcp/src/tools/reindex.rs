@@ -0,0 +1,59 @@
+use super::{MAX_INPUT_LENGTH, ToolError};
+use semantiq_index::{IndexProgress, ReindexReport};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Parameters of `semantiq_reindex`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqReindex {
+    /// File, directory or glob relative to the project root; defaults to the whole project
+    pub path: Option<String>,
+}
+
+impl SemantiqReindex {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: Some(path.to_string()),
+        }
+    }
+
+    /// Trimmed path pattern, `None` for the whole project.
+    /// Rejects path traversal and absolute paths.
+    pub fn validated_path(&self) -> Result<Option<&str>, ToolError> {
+        let Some(path) = self
+            .path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        else {
+            return Ok(None);
+        };
+        if path.len() > MAX_INPUT_LENGTH {
+            return Err(ToolError::InvalidParams(format!(
+                "Path exceeds maximum length of {} characters",
+                MAX_INPUT_LENGTH
+            )));
+        }
+        if path.contains("..") {
+            return Err(ToolError::InvalidParams(
+                "Path must not contain '..'".to_string(),
+            ));
+        }
+        if Path::new(path).is_absolute() {
+            return Err(ToolError::InvalidParams(
+                "Path must be relative to the project root".to_string(),
+            ));
+        }
+        Ok(Some(path))
+    }
+}
+
+/// Outcome of a reindex request
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ReindexOutcome {
+    /// Changed files were reindexed
+    Completed(ReindexReport),
+    /// The initial indexing pass is running and will pick up changed files
+    InitialIndexing(IndexProgress),
+}
@@ -0,0 +1,44 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_related`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqRelated {
+    pub file_path: String,
+    pub limit: Option<usize>,
+}
+
+impl SemantiqRelated {
+    pub const DEFAULT_LIMIT: usize = 10;
+    pub const MAX_LIMIT: usize = 100;
+
+    pub fn new(file_path: &str) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Trimmed file path, rejecting path traversal
+    pub fn validated_path(&self) -> Result<&str, ToolError> {
+        let file_path = validate_text(&self.file_path, "File path")?;
+        if file_path.contains("..") {
+            return Err(ToolError::InvalidParams(
+                "File path must not contain '..'".to_string(),
+            ));
+        }
+        Ok(file_path)
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT)
+    }
+}
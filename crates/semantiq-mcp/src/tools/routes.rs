@@ -0,0 +1,77 @@
+use super::{ToolError, validate_text};
+use semantiq_parser::HttpRoute;
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_routes`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqRoutes {
+    /// HTTP method, any when `None`
+    pub method: Option<String>,
+    /// Request path (e.g. "/api/users/42"), also accepted with its method
+    /// ("POST /api/users"), all routes when `None`
+    pub path: Option<String>,
+    /// Routes listed
+    pub limit: Option<usize>,
+}
+
+impl SemantiqRoutes {
+    pub const DEFAULT_LIMIT: usize = 25;
+    pub const MAX_LIMIT: usize = 500;
+    pub const METHODS: &'static [&'static str] =
+        &["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
+    pub fn with_method(mut self, method: &str) -> Self {
+        self.method = Some(method.to_string());
+        self
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Requested method and path, the method given with the path applying
+    /// when `method` is not set
+    pub fn validated_route(&self) -> Result<(Option<String>, Option<String>), ToolError> {
+        let route = match self.path {
+            Some(ref path) => {
+                let path = validate_text(path, "Path")?;
+                Some(HttpRoute::parse(path).ok_or_else(|| {
+                    ToolError::InvalidParams(format!(
+                        "Invalid path '{}': expected a path such as '/api/users', optionally after its method",
+                        path
+                    ))
+                })?)
+            }
+            None => None,
+        };
+
+        let method = match self.method {
+            Some(ref method) => {
+                let method = method.trim().to_uppercase();
+                if !Self::METHODS.contains(&method.as_str()) {
+                    return Err(ToolError::InvalidParams(format!(
+                        "Invalid method '{}': expected one of {}",
+                        method,
+                        Self::METHODS.join(", ")
+                    )));
+                }
+                Some(method)
+            }
+            None => route.as_ref().and_then(|r| r.method.clone()),
+        };
+        Ok((method, route.map(|r| r.path)))
+    }
+
+    /// Requested limit, defaulted and capped to [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .clamp(1, Self::MAX_LIMIT)
+    }
+}
@@ -0,0 +1,58 @@
+use super::{SemantiqSearch, ToolError, validate_text};
+use semantiq_index::SavedQuery;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Parameters of `semantiq_saved_search`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqSavedSearch {
+    pub name: String,
+    /// Values of the `{parameter}` placeholders of the saved query
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+    /// Overrides the limit saved with the query
+    pub limit: Option<usize>,
+    /// Cap on the saved or requested limit, set by the caller rather than
+    /// the client (e.g. the HTTP API's own maximum)
+    #[serde(skip)]
+    pub max_limit: Option<usize>,
+}
+
+impl SemantiqSavedSearch {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: &str) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Trimmed name of the saved query
+    pub fn validated_name(&self) -> Result<&str, ToolError> {
+        validate_text(&self.name, "Saved query name")
+    }
+
+    /// The search of `saved` with the parameters of the request filled in
+    pub fn search_request(&self, saved: &SavedQuery) -> Result<SemantiqSearch, ToolError> {
+        let bound = saved
+            .bind(&self.params)
+            .map_err(|e| ToolError::InvalidParams(e.to_string()))?;
+        let mut request = SemantiqSearch::from_saved(&bound)?;
+        if let Some(limit) = self.limit {
+            request.limit = Some(limit);
+        }
+        if let Some(max_limit) = self.max_limit {
+            request.limit = Some(request.effective_limit().min(max_limit));
+        }
+        Ok(request)
+    }
+}
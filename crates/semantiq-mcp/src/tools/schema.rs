@@ -0,0 +1,178 @@
+//! JSON Schemas of tool parameters
+//!
+//! MCP tools derive their input schema from the Rust types of their
+//! parameters. The functions below describe the parameters that are plain
+//! strings in Rust but accept a fixed set of values or a list; requests are
+//! still validated when they run, with the messages of [`super::ToolError`].
+
+use rmcp::schemars::r#gen::SchemaGenerator;
+use rmcp::schemars::schema::Schema;
+use semantiq_parser::{SymbolKind, SymbolQualifiers};
+use serde::Deserializer;
+use serde::de::{self, SeqAccess, Visitor};
+use serde_json::{Value, json};
+use std::fmt;
+
+fn schema(value: Value) -> Schema {
+    serde_json::from_value(value).expect("tool parameter schemas are valid JSON Schema")
+}
+
+/// A list of strings, also accepted as one comma-separated string
+fn list_schema(items: Value) -> Schema {
+    schema(json!({
+        "anyOf": [
+            { "type": "array", "items": items },
+            { "type": "string" },
+        ],
+    }))
+}
+
+/// `file_type`: file extensions without the dot
+pub fn file_types_schema(_: &mut SchemaGenerator) -> Schema {
+    list_schema(json!({ "type": "string" }))
+}
+
+/// `symbol_kind`: names of [`SymbolKind`]
+pub fn symbol_kinds_schema(_: &mut SchemaGenerator) -> Schema {
+    let kinds: Vec<&str> = SymbolKind::ALL.iter().map(|k| k.as_str()).collect();
+    list_schema(json!({ "type": "string", "enum": kinds }))
+}
+
+/// `package`: names of workspace packages
+pub fn packages_schema(_: &mut SchemaGenerator) -> Schema {
+    list_schema(json!({ "type": "string" }))
+}
+
+/// `qualifiers`: names of [`SymbolQualifiers`]
+pub fn qualifiers_schema(_: &mut SchemaGenerator) -> Schema {
+    list_schema(json!({ "type": "string", "enum": SymbolQualifiers::NAMES }))
+}
+
+/// `scope` of `semantiq_search`
+pub fn scope_schema(_: &mut SchemaGenerator) -> Schema {
+    schema(json!({ "type": "string", "enum": ["project", "external", "all"] }))
+}
+
+/// `kind` of `semantiq_find_refs`
+pub fn ref_kind_schema(_: &mut SchemaGenerator) -> Schema {
+    schema(json!({ "type": "string", "enum": ["definition", "usage", "all"] }))
+}
+
+/// `format` of the tools returning structured results
+pub fn format_schema(_: &mut SchemaGenerator) -> Schema {
+    schema(json!({ "type": "string", "enum": ["markdown", "json"] }))
+}
+
+/// `queries` of `semantiq_batch_search`: the parameters of `semantiq_search`
+pub fn batch_queries_schema(generator: &mut SchemaGenerator) -> Schema {
+    let value = |schema: Schema| serde_json::to_value(schema).unwrap_or_default();
+    schema(json!({
+        "type": "array",
+        "minItems": 1,
+        "maxItems": 10,
+        "items": {
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": { "type": "string", "minLength": 1, "maxLength": 500 },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 1000 },
+                "min_score": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+                "file_type": value(file_types_schema(generator)),
+                "symbol_kind": value(symbol_kinds_schema(generator)),
+                "package": value(packages_schema(generator)),
+                "qualifiers": value(qualifiers_schema(generator)),
+                "route": { "type": "string", "maxLength": 500 },
+                "scope": value(scope_schema(generator)),
+                "explain_scores": { "type": "boolean" },
+                "context_lines": { "type": "integer", "minimum": 0, "maximum": 50 },
+                "expand_to_symbol": { "type": "boolean" },
+                "exclude_conditional": { "type": "boolean" },
+                "include_generated": { "type": "boolean" },
+                "exclude_comments": { "type": "boolean" },
+                "exclude_strings": { "type": "boolean" },
+                "session_id": { "type": "string", "minLength": 1, "maxLength": 500 },
+            },
+        },
+    }))
+}
+
+/// Deserialize a list parameter given as an array of strings or as one
+/// comma-separated string, into the comma-separated form
+pub fn deserialize_list<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ListVisitor;
+
+    impl<'de> Visitor<'de> for ListVisitor {
+        type Value = Option<String>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an array of strings or a comma-separated string")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(Some(value.to_string()))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut items = Vec::new();
+            while let Some(item) = seq.next_element::<String>()? {
+                items.push(item);
+            }
+            Ok(Some(items.join(",")))
+        }
+    }
+
+    deserializer.deserialize_option(ListVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default, deserialize_with = "deserialize_list")]
+        file_type: Option<String>,
+    }
+
+    fn file_type(json: &str) -> Result<Option<String>, String> {
+        serde_json::from_str::<Params>(json)
+            .map(|p| p.file_type)
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_list_parameters_accept_arrays_and_strings() {
+        assert_eq!(
+            file_type(r#"{"file_type": ["rs", "ts"]}"#),
+            Ok(Some("rs,ts".into()))
+        );
+        assert_eq!(
+            file_type(r#"{"file_type": "rs,ts"}"#),
+            Ok(Some("rs,ts".into()))
+        );
+        assert_eq!(file_type(r#"{"file_type": null}"#), Ok(None));
+        assert_eq!(file_type("{}"), Ok(None));
+
+        let error = file_type(r#"{"file_type": 3}"#).unwrap_err();
+        assert!(error.contains("an array of strings or a comma-separated string"));
+    }
+}
@@ -1,20 +1,77 @@
+use super::schema::deserialize_list;
+use super::{ToolError, validate_text};
+use semantiq_index::SavedQuery;
+use semantiq_parser::{HttpRoute, SymbolKind, SymbolQualifiers};
+use semantiq_retrieval::{SearchOptions, SearchScope};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Parameters of `semantiq_search`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SemantiqSearch {
     pub query: String,
     pub limit: Option<usize>,
-    pub languages: Option<Vec<String>>,
-    pub file_patterns: Option<Vec<String>>,
+    pub min_score: Option<f32>,
+    /// Comma-separated file extensions (e.g. "rs,ts"), also accepted as an array
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub file_type: Option<String>,
+    /// Comma-separated symbol kinds (e.g. "function,struct"), also accepted as an array
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub symbol_kind: Option<String>,
+    /// Comma-separated workspace packages (e.g. "@acme/ui,web"), also accepted as an array
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub package: Option<String>,
+    /// Comma-separated symbol qualifiers (e.g. "public,async"), also accepted as an array
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub qualifiers: Option<String>,
+    /// HTTP route (e.g. "POST /api/users"), returning only its handlers
+    pub route: Option<String>,
+    /// "project" (default), "external" or "all"
+    pub scope: Option<String>,
+    /// Attach a score breakdown to each result
+    pub explain_scores: Option<bool>,
+    /// Lines of context added above and below each result
+    pub context_lines: Option<usize>,
+    /// Expand each result to the whole symbol enclosing it
+    pub expand_to_symbol: Option<bool>,
+    /// Leave out symbols compiled only under a `#[cfg]`, `#ifdef` or `//go:build` condition
+    pub exclude_conditional: Option<bool>,
+    /// Keep generated files (protobuf output, minified code...) in the results
+    pub include_generated: Option<bool>,
+    /// Leave out text matches inside comments
+    pub exclude_comments: Option<bool>,
+    /// Leave out text matches inside string literals
+    pub exclude_strings: Option<bool>,
+    /// Search session of the conversation, favouring the files it surfaced
+    pub session_id: Option<String>,
 }
 
 impl SemantiqSearch {
+    pub const DEFAULT_LIMIT: usize = 20;
+    pub const MAX_LIMIT: usize = 1000;
+
+    /// Parameters that can be saved with a query as filters
+    pub const FILTERS: &'static [&'static str] = &[
+        "limit",
+        "min_score",
+        "file_type",
+        "symbol_kind",
+        "package",
+        "qualifiers",
+        "route",
+        "scope",
+        "explain_scores",
+        "context_lines",
+        "expand_to_symbol",
+        "exclude_conditional",
+        "include_generated",
+        "exclude_comments",
+        "exclude_strings",
+    ];
+
     pub fn new(query: &str) -> Self {
         Self {
             query: query.to_string(),
-            limit: None,
-            languages: None,
-            file_patterns: None,
+            ..Default::default()
         }
     }
 
@@ -23,13 +80,277 @@ impl SemantiqSearch {
         self
     }
 
-    pub fn with_languages(mut self, languages: Vec<String>) -> Self {
-        self.languages = Some(languages);
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    pub fn with_file_type(mut self, file_type: &str) -> Self {
+        self.file_type = Some(file_type.to_string());
+        self
+    }
+
+    pub fn with_symbol_kind(mut self, symbol_kind: &str) -> Self {
+        self.symbol_kind = Some(symbol_kind.to_string());
+        self
+    }
+
+    pub fn with_package(mut self, package: &str) -> Self {
+        self.package = Some(package.to_string());
         self
     }
 
-    pub fn with_file_patterns(mut self, patterns: Vec<String>) -> Self {
-        self.file_patterns = Some(patterns);
+    pub fn with_qualifiers(mut self, qualifiers: &str) -> Self {
+        self.qualifiers = Some(qualifiers.to_string());
         self
     }
+
+    pub fn with_route(mut self, route: &str) -> Self {
+        self.route = Some(route.to_string());
+        self
+    }
+
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        self.scope = Some(scope.to_string());
+        self
+    }
+
+    pub fn with_explain_scores(mut self, explain: bool) -> Self {
+        self.explain_scores = Some(explain);
+        self
+    }
+
+    pub fn with_context_lines(mut self, lines: usize) -> Self {
+        self.context_lines = Some(lines);
+        self
+    }
+
+    pub fn with_expand_to_symbol(mut self, expand: bool) -> Self {
+        self.expand_to_symbol = Some(expand);
+        self
+    }
+
+    pub fn with_exclude_conditional(mut self, exclude: bool) -> Self {
+        self.exclude_conditional = Some(exclude);
+        self
+    }
+
+    pub fn with_include_generated(mut self, include: bool) -> Self {
+        self.include_generated = Some(include);
+        self
+    }
+
+    pub fn with_exclude_comments(mut self, exclude: bool) -> Self {
+        self.exclude_comments = Some(exclude);
+        self
+    }
+
+    pub fn with_exclude_strings(mut self, exclude: bool) -> Self {
+        self.exclude_strings = Some(exclude);
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: &str) -> Self {
+        self.session_id = Some(session_id.to_string());
+        self
+    }
+
+    /// Set one of [`Self::FILTERS`] from its text value
+    pub fn with_filter(mut self, name: &str, value: &str) -> Result<Self, ToolError> {
+        fn parse<T: std::str::FromStr>(
+            name: &str,
+            value: &str,
+            expected: &str,
+        ) -> Result<T, ToolError> {
+            value.trim().parse().map_err(|_| {
+                ToolError::InvalidParams(format!(
+                    "Invalid {} '{}': expected {}",
+                    name, value, expected
+                ))
+            })
+        }
+
+        let text = || Some(value.to_string());
+        match name {
+            "limit" => self.limit = Some(parse(name, value, "a number")?),
+            "min_score" => self.min_score = Some(parse(name, value, "a number")?),
+            "file_type" => self.file_type = text(),
+            "symbol_kind" => self.symbol_kind = text(),
+            "package" => self.package = text(),
+            "qualifiers" => self.qualifiers = text(),
+            "route" => self.route = text(),
+            "scope" => self.scope = text(),
+            "explain_scores" => self.explain_scores = Some(parse(name, value, "true or false")?),
+            "context_lines" => self.context_lines = Some(parse(name, value, "a number")?),
+            "expand_to_symbol" => {
+                self.expand_to_symbol = Some(parse(name, value, "true or false")?)
+            }
+            "exclude_conditional" => {
+                self.exclude_conditional = Some(parse(name, value, "true or false")?)
+            }
+            "include_generated" => {
+                self.include_generated = Some(parse(name, value, "true or false")?)
+            }
+            "exclude_comments" => {
+                self.exclude_comments = Some(parse(name, value, "true or false")?)
+            }
+            "exclude_strings" => self.exclude_strings = Some(parse(name, value, "true or false")?),
+            _ => {
+                return Err(ToolError::InvalidParams(format!(
+                    "Unknown filter '{}': expected one of {}",
+                    name,
+                    Self::FILTERS.join(", ")
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    /// The search of a saved query, its parameters already filled in
+    pub fn from_saved(saved: &SavedQuery) -> Result<Self, ToolError> {
+        saved
+            .filters
+            .iter()
+            .try_fold(Self::new(&saved.query), |request, (name, value)| {
+                request.with_filter(name, value)
+            })
+    }
+
+    /// Trimmed query text
+    pub fn validated_query(&self) -> Result<&str, ToolError> {
+        validate_text(&self.query, "Query")
+    }
+
+    /// Requested number of results, capped at [`Self::MAX_LIMIT`]
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT)
+    }
+
+    /// Search options built from the filters
+    pub fn options(&self) -> Result<SearchOptions, ToolError> {
+        let mut options = SearchOptions::new();
+
+        if let Some(score) = self.min_score {
+            if !(0.0..=1.0).contains(&score) {
+                return Err(ToolError::InvalidParams(format!(
+                    "Invalid min_score {}: expected a number between 0.0 and 1.0",
+                    score
+                )));
+            }
+            options = options.with_min_score(score);
+        }
+
+        if let Some(ref ft) = self.file_type {
+            let types = SearchOptions::parse_csv(ft);
+            if !types.is_empty() {
+                options = options.with_file_types(types);
+            }
+        }
+
+        if let Some(ref sk) = self.symbol_kind {
+            let kinds = SearchOptions::parse_csv(sk);
+            if let Some(unknown) = kinds.iter().find(|k| {
+                !SymbolKind::ALL
+                    .iter()
+                    .any(|kind| kind.as_str() == k.as_str())
+            }) {
+                let expected: Vec<&str> = SymbolKind::ALL.iter().map(|k| k.as_str()).collect();
+                return Err(ToolError::InvalidParams(format!(
+                    "Invalid symbol_kind '{}': expected one of {}",
+                    unknown,
+                    expected.join(", ")
+                )));
+            }
+            if !kinds.is_empty() {
+                options = options.with_symbol_kinds(kinds);
+            }
+        }
+
+        if let Some(ref pk) = self.package {
+            let packages = SearchOptions::parse_csv(pk);
+            if !packages.is_empty() {
+                options = options.with_packages(packages);
+            }
+        }
+
+        if let Some(ref q) = self.qualifiers {
+            let qualifiers = SearchOptions::parse_csv(q);
+            if let Some(unknown) = qualifiers
+                .iter()
+                .find(|q| !SymbolQualifiers::NAMES.contains(&q.as_str()))
+            {
+                return Err(ToolError::InvalidParams(format!(
+                    "Invalid qualifier '{}': expected one of {}",
+                    unknown,
+                    SymbolQualifiers::NAMES.join(", ")
+                )));
+            }
+            if !qualifiers.is_empty() {
+                options = options.with_qualifiers(qualifiers);
+            }
+        }
+
+        if let Some(ref r) = self.route {
+            let route = HttpRoute::parse(r).ok_or_else(|| {
+                ToolError::InvalidParams(format!(
+                    "Invalid route '{}': expected a method and path such as 'POST /api/users', or a path",
+                    r
+                ))
+            })?;
+            options = options.with_route(route);
+        }
+
+        if let Some(ref sc) = self.scope {
+            let scope = SearchScope::parse(sc).ok_or_else(|| {
+                ToolError::InvalidParams(format!(
+                    "Invalid scope '{}': expected 'project', 'external' or 'all'",
+                    sc
+                ))
+            })?;
+            options = options.with_scope(scope);
+        }
+
+        if self.explain_scores.unwrap_or(false) {
+            options = options.with_explain_scores(true);
+        }
+
+        if let Some(lines) = self.context_lines {
+            if lines > SearchOptions::MAX_CONTEXT_LINES {
+                return Err(ToolError::InvalidParams(format!(
+                    "Invalid context_lines {}: expected at most {}",
+                    lines,
+                    SearchOptions::MAX_CONTEXT_LINES
+                )));
+            }
+            options = options.with_context_lines(lines);
+        }
+
+        if self.expand_to_symbol.unwrap_or(false) {
+            options = options.with_expand_to_symbol(true);
+        }
+
+        if self.exclude_conditional.unwrap_or(false) {
+            options = options.with_exclude_conditional(true);
+        }
+
+        if self.include_generated.unwrap_or(false) {
+            options = options.with_include_generated(true);
+        }
+
+        if self.exclude_comments.unwrap_or(false) {
+            options = options.with_exclude_comments(true);
+        }
+
+        if self.exclude_strings.unwrap_or(false) {
+            options = options.with_exclude_strings(true);
+        }
+
+        if let Some(ref session_id) = self.session_id {
+            options = options.with_session(validate_text(session_id, "Session id")?);
+        }
+
+        Ok(options)
+    }
 }
@@ -0,0 +1,21 @@
+use super::{ToolError, validate_text};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of `semantiq_session_summary`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemantiqSessionSummary {
+    pub session_id: String,
+}
+
+impl SemantiqSessionSummary {
+    pub fn new(session_id: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+        }
+    }
+
+    /// Trimmed session id
+    pub fn validated_session_id(&self) -> Result<&str, ToolError> {
+        validate_text(&self.session_id, "Session id")
+    }
+}
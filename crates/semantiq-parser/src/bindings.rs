@@ -0,0 +1,436 @@
+//! Rust symbols exported to other languages through bindings.
+//!
+//! A Rust core with JavaScript or Python bindings defines each exported
+//! symbol once in Rust, under the name the other language calls it by:
+//! `#[napi]` functions become camelCase (`get_user` is `getUser` in
+//! JavaScript), `#[wasm_bindgen]` and PyO3 (`#[pyfunction]`, `#[pyclass]`,
+//! `#[pymethods]`) keep their Rust names, and `js_name = ...` or
+//! `name = ...` override either. Recording both names lets references be
+//! followed from one language to the other.
+
+use crate::language::Language;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+/// Binding generator exporting a Rust symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingFramework {
+    /// napi-rs, Node.js native modules
+    Napi,
+    /// wasm-bindgen, WebAssembly modules called from JavaScript
+    WasmBindgen,
+    /// PyO3, Python extension modules
+    Pyo3,
+}
+
+impl BindingFramework {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BindingFramework::Napi => "napi",
+            BindingFramework::WasmBindgen => "wasm_bindgen",
+            BindingFramework::Pyo3 => "pyo3",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "napi" => Some(BindingFramework::Napi),
+            "wasm_bindgen" => Some(BindingFramework::WasmBindgen),
+            "pyo3" => Some(BindingFramework::Pyo3),
+            _ => None,
+        }
+    }
+
+    /// Framework of an attribute, by the last segment of its path
+    fn of_attribute(name: &str) -> Option<Self> {
+        match name {
+            "napi" => Some(BindingFramework::Napi),
+            "wasm_bindgen" => Some(BindingFramework::WasmBindgen),
+            "pyfunction" | "pyclass" | "pymethods" | "pymodule" | "pyo3" => {
+                Some(BindingFramework::Pyo3)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A Rust symbol and the name another language calls it by
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Binding {
+    /// Name of the symbol in Rust
+    pub symbol: String,
+    /// Name of the symbol in the other language
+    pub exported_name: String,
+    pub framework: BindingFramework,
+    pub line: usize,
+}
+
+pub struct BindingExtractor;
+
+impl BindingExtractor {
+    /// Extract the symbols of a Rust file exported through napi-rs,
+    /// wasm-bindgen or PyO3. Other languages have none.
+    pub fn extract(tree: &Tree, source: &str, language: Language) -> Result<Vec<Binding>> {
+        let mut bindings = Vec::new();
+        if language == Language::Rust {
+            Self::extract_recursive(&tree.root_node(), source, None, &mut bindings);
+        }
+        Ok(bindings)
+    }
+
+    /// `exporting_impl` is the framework of the `impl` block the node is in,
+    /// when it exports its public methods
+    fn extract_recursive(
+        node: &Node,
+        source: &str,
+        exporting_impl: Option<BindingFramework>,
+        bindings: &mut Vec<Binding>,
+    ) {
+        match node.kind() {
+            "function_item" | "struct_item" | "enum_item" => {
+                Self::extract_item(node, source, exporting_impl, bindings);
+            }
+            "impl_item" => {
+                // `#[wasm_bindgen] impl` and `#[pymethods] impl` export
+                // their methods, `#[napi] impl` only those marked `#[napi]`
+                let framework = rust_attributes(node)
+                    .iter()
+                    .filter_map(|a| attribute_name(a, source))
+                    .find_map(|name| match name.as_str() {
+                        "wasm_bindgen" => Some(BindingFramework::WasmBindgen),
+                        "pymethods" => Some(BindingFramework::Pyo3),
+                        _ => None,
+                    });
+                if let Some(body) = node.child_by_field_name("body") {
+                    let mut cursor = body.walk();
+                    for child in body.named_children(&mut cursor) {
+                        Self::extract_recursive(&child, source, framework, bindings);
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            Self::extract_recursive(&child, source, None, bindings);
+        }
+    }
+
+    fn extract_item(
+        node: &Node,
+        source: &str,
+        exporting_impl: Option<BindingFramework>,
+        bindings: &mut Vec<Binding>,
+    ) {
+        let Some(symbol) = node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        else {
+            return;
+        };
+
+        let mut framework = None;
+        let mut renamed = None;
+        for attribute in rust_attributes(node) {
+            let Some(name) = attribute_name(&attribute, source) else {
+                continue;
+            };
+            let Some(attribute_framework) = BindingFramework::of_attribute(&name) else {
+                continue;
+            };
+            framework.get_or_insert(attribute_framework);
+            let key = match attribute_framework {
+                BindingFramework::Pyo3 => "name",
+                _ => "js_name",
+            };
+            if let Some(value) = attribute_argument(&attribute, source, key) {
+                renamed = Some(value);
+            }
+        }
+
+        let framework = match (framework, exporting_impl) {
+            (Some(framework), _) => framework,
+            // Methods of an exporting `impl` block, PyO3 exporting them all
+            (None, Some(BindingFramework::Pyo3)) => BindingFramework::Pyo3,
+            (None, Some(framework)) if is_public(node) => framework,
+            _ => return,
+        };
+
+        let exported_name = renamed.unwrap_or_else(|| match framework {
+            BindingFramework::Napi if node.kind() == "function_item" => camel_case(symbol),
+            _ => symbol.to_string(),
+        });
+        bindings.push(Binding {
+            symbol: symbol.to_string(),
+            exported_name,
+            framework,
+            line: node.start_position().row + 1,
+        });
+    }
+}
+
+/// Attributes of a Rust item, which precede it as siblings
+fn rust_attributes<'t>(node: &Node<'t>) -> Vec<Node<'t>> {
+    let mut attributes = Vec::new();
+    let mut current = node.prev_named_sibling();
+    while let Some(sibling) = current {
+        match sibling.kind() {
+            "attribute_item" => {
+                if let Some(attribute) = sibling.named_child(0) {
+                    attributes.push(attribute);
+                }
+            }
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        current = sibling.prev_named_sibling();
+    }
+    attributes.reverse();
+    attributes
+}
+
+/// Last segment of the path of an attribute: `napi` for `#[napi_derive::napi]`
+fn attribute_name(attribute: &Node, source: &str) -> Option<String> {
+    let path = attribute.named_child(0)?;
+    let name = match path.kind() {
+        "identifier" => path,
+        "scoped_identifier" => path.child_by_field_name("name")?,
+        _ => return None,
+    };
+    name.utf8_text(source.as_bytes()).ok().map(str::to_string)
+}
+
+/// Value of the `key = value` argument of an attribute, quoted or not:
+/// `getUser` for `#[wasm_bindgen(js_name = getUser)]`
+fn attribute_argument(attribute: &Node, source: &str, key: &str) -> Option<String> {
+    let arguments = attribute.child_by_field_name("arguments")?;
+    let text = arguments.utf8_text(source.as_bytes()).ok()?;
+    let text = text.strip_prefix('(')?.strip_suffix(')')?;
+    text.split(',').find_map(|argument| {
+        let (name, value) = argument.split_once('=')?;
+        let value = value.trim().trim_matches('"');
+        (name.trim() == key && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+fn is_public(node: &Node) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|child| child.kind() == "visibility_modifier")
+}
+
+/// Words of an identifier, split at `_`, `-` and case changes:
+/// `parseHTTPRequest` gives `parse`, `HTTP` and `Request`
+fn identifier_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let lower_to_upper = !chars[i - 1].is_uppercase() && chars[i].is_uppercase();
+            let acronym_end = chars[i - 1].is_uppercase()
+                && chars[i].is_uppercase()
+                && chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if lower_to_upper || acronym_end {
+                words.push(chars[start..i].iter().collect());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect());
+        }
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `get_user` as napi-rs exports it: `getUser`
+fn camel_case(name: &str) -> String {
+    identifier_words(&name.to_lowercase())
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            if i == 0 {
+                word.clone()
+            } else {
+                capitalize(word)
+            }
+        })
+        .collect()
+}
+
+/// Spellings of a name in the naming conventions of other languages
+/// (`get_user`, `getUser`, `GetUser`), starting with the name itself. A
+/// binding exports a symbol under one of them.
+pub fn binding_name_variants(name: &str) -> Vec<String> {
+    let words: Vec<String> = identifier_words(name)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect();
+    let mut variants = vec![name.to_string()];
+    if words.is_empty() {
+        return variants;
+    }
+    let snake = words.join("_");
+    let pascal: String = words.iter().map(|w| capitalize(w)).collect();
+    let camel = camel_case(&snake);
+    for variant in [snake, camel, pascal] {
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(content: &str) -> Vec<Binding> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(Language::Rust, content).unwrap();
+        BindingExtractor::extract(&tree, content, Language::Rust).unwrap()
+    }
+
+    fn names(bindings: &[Binding]) -> Vec<(&str, &str, &str)> {
+        bindings
+            .iter()
+            .map(|b| {
+                (
+                    b.symbol.as_str(),
+                    b.exported_name.as_str(),
+                    b.framework.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_napi_exports_functions_in_camel_case() {
+        let bindings = extract(
+            r#"
+use napi_derive::napi;
+
+#[napi]
+pub fn get_user(id: u32) -> String { String::new() }
+
+#[napi(js_name = "loadAll")]
+pub fn load_users() {}
+
+#[napi(object)]
+pub struct UserRecord { pub id: u32 }
+
+#[napi]
+impl UserRecord {
+    #[napi(constructor)]
+    pub fn new() -> Self { Self { id: 0 } }
+
+    pub fn internal_only(&self) {}
+}
+
+pub fn not_exported() {}
+"#,
+        );
+        assert_eq!(
+            names(&bindings),
+            [
+                ("get_user", "getUser", "napi"),
+                ("load_users", "loadAll", "napi"),
+                ("UserRecord", "UserRecord", "napi"),
+                ("new", "new", "napi"),
+            ]
+        );
+        assert_eq!(bindings[0].line, 5);
+    }
+
+    #[test]
+    fn test_wasm_bindgen_and_pyo3_keep_names() {
+        let bindings = extract(
+            r#"
+#[wasm_bindgen(js_name = parseConfig)]
+pub fn parse_config(text: &str) {}
+
+#[wasm_bindgen]
+pub struct Parser;
+
+#[wasm_bindgen]
+impl Parser {
+    pub fn feed(&mut self) {}
+    fn private_helper(&self) {}
+}
+
+#[pyfunction]
+#[pyo3(name = "tokenize_text")]
+fn tokenize(text: &str) {}
+
+#[pyclass(name = "Tokenizer")]
+struct RustTokenizer;
+
+#[pymethods]
+impl RustTokenizer {
+    fn encode(&self) {}
+}
+"#,
+        );
+        assert_eq!(
+            names(&bindings),
+            [
+                ("parse_config", "parseConfig", "wasm_bindgen"),
+                ("Parser", "Parser", "wasm_bindgen"),
+                ("feed", "feed", "wasm_bindgen"),
+                ("tokenize", "tokenize_text", "pyo3"),
+                ("RustTokenizer", "Tokenizer", "pyo3"),
+                ("encode", "encode", "pyo3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_languages_have_no_bindings() {
+        let mut support = LanguageSupport::new().unwrap();
+        let content = "function getUser() {}\n";
+        let tree = support.parse(Language::JavaScript, content).unwrap();
+        assert!(
+            BindingExtractor::extract(&tree, content, Language::JavaScript)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_binding_name_variants() {
+        assert_eq!(
+            binding_name_variants("get_user"),
+            ["get_user", "getUser", "GetUser"]
+        );
+        assert_eq!(
+            binding_name_variants("parseHTTPRequest"),
+            [
+                "parseHTTPRequest",
+                "parse_http_request",
+                "parseHttpRequest",
+                "ParseHttpRequest"
+            ]
+        );
+        assert_eq!(
+            binding_name_variants("Tokenizer"),
+            ["Tokenizer", "tokenizer"]
+        );
+        assert_eq!(
+            binding_name_variants("load_v2"),
+            ["load_v2", "loadV2", "LoadV2"]
+        );
+    }
+}
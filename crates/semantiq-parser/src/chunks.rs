@@ -1,10 +1,15 @@
+use crate::config_keys::{ConfigKey, is_config_language, top_level_keys};
 use crate::language::Language;
+use crate::plugins;
+use crate::symbols::SymbolExtractor;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use tree_sitter::Tree;
+use tree_sitter::{Node, Tree};
 
 const DEFAULT_CHUNK_SIZE: usize = 1500;
 const OVERLAP_LINES: usize = 3;
+/// Depth of the keys of configuration files chunked on their own
+const DEFAULT_CONFIG_KEY_DEPTH: usize = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeChunk {
@@ -18,12 +23,14 @@ pub struct CodeChunk {
 
 pub struct ChunkExtractor {
     chunk_size: usize,
+    config_key_depth: usize,
 }
 
 impl ChunkExtractor {
     pub fn new() -> Self {
         Self {
             chunk_size: DEFAULT_CHUNK_SIZE,
+            config_key_depth: DEFAULT_CONFIG_KEY_DEPTH,
         }
     }
 
@@ -32,11 +39,32 @@ impl ChunkExtractor {
         self
     }
 
+    /// Chunk the keys of JSON, YAML and TOML files down to `depth` (1 for
+    /// the top-level keys): one chunk per key, named by its path
+    pub fn with_config_key_depth(mut self, depth: usize) -> Self {
+        self.config_key_depth = depth.max(1);
+        self
+    }
+
     pub fn extract(&self, tree: &Tree, source: &str, language: Language) -> Result<Vec<CodeChunk>> {
         let lines: Vec<&str> = source.lines().collect();
         let mut chunks = Vec::new();
         let root = tree.root_node();
 
+        if language == Language::Sql {
+            let chunks = self.sql_statement_chunks(&root, source, &lines);
+            if !chunks.is_empty() {
+                return Ok(chunks);
+            }
+        }
+
+        if is_config_language(language) {
+            let chunks = self.config_key_chunks(&root, source, &lines, language);
+            if !chunks.is_empty() {
+                return Ok(chunks);
+            }
+        }
+
         // Find semantic boundaries (functions, classes, etc.)
         let boundaries = self.find_semantic_boundaries(&root, source, language);
 
@@ -191,6 +219,164 @@ impl ChunkExtractor {
             Language::Toml => matches!(kind, "table" | "array"),
             Language::Bash => matches!(kind, "function_definition" | "compound_statement"),
             Language::Elixir => matches!(kind, "call" | "anonymous_function" | "do_block"),
+            Language::Sql => kind == "statement",
+            Language::Custom(id) => plugins::get(id).is_some_and(|l| l.symbols.contains_key(kind)),
+        }
+    }
+
+    /// One chunk per schema statement (CREATE, ALTER, DROP), so that each
+    /// migration step is retrieved on its own. Other statements, such as the
+    /// INSERTs of seed data, are grouped up to the chunk size. Comments
+    /// preceding a statement belong to its chunk.
+    fn sql_statement_chunks(&self, root: &Node, source: &str, lines: &[&str]) -> Vec<CodeChunk> {
+        // (start line, end line exclusive, object name, schema statement)
+        let mut segments: Vec<(usize, usize, Option<String>, bool)> = Vec::new();
+        let mut comment_start = None;
+
+        let mut cursor = root.walk();
+        for child in root.children(&mut cursor) {
+            match child.kind() {
+                "comment" | "marginalia" => {
+                    comment_start.get_or_insert(child.start_position().row);
+                }
+                ";" => {}
+                _ => {
+                    let start = comment_start.take().unwrap_or(child.start_position().row);
+                    let body = child.named_child(0).filter(|_| child.kind() == "statement");
+                    let schema = body.is_some_and(|b| {
+                        let kind = b.kind();
+                        kind.starts_with("create_")
+                            || kind.starts_with("alter_")
+                            || kind.starts_with("drop_")
+                    });
+                    let name = body.and_then(|b| SymbolExtractor::sql_object_name(&b, source));
+                    segments.push((start, child.end_position().row + 1, name, schema));
+                }
+            }
+        }
+
+        let mut groups: Vec<(usize, usize, Vec<String>, bool)> = Vec::new();
+        for (start, end, name, schema) in segments {
+            if let Some(last) = groups.last_mut() {
+                let shares_line = start < last.1;
+                let fits = !schema && !last.3 && {
+                    let size: usize = lines[last.0..end.min(lines.len())]
+                        .iter()
+                        .map(|l| l.len() + 1)
+                        .sum();
+                    size <= self.chunk_size
+                };
+                if shares_line || fits {
+                    last.1 = last.1.max(end);
+                    last.2.extend(name);
+                    last.3 |= schema;
+                    continue;
+                }
+            }
+            groups.push((start, end, name.into_iter().collect(), schema));
+        }
+
+        groups
+            .into_iter()
+            .map(|(start, end, symbols, _)| self.create_chunk(source, lines, start, end, &symbols))
+            .collect()
+    }
+
+    /// One chunk per key of a configuration file down to the configured
+    /// depth, listing the path of the key (`services.web.environment`) as
+    /// its symbol, so that a setting is found with the path it is configured
+    /// under. Keys larger than the chunk size are split into the keys they
+    /// hold. Consecutive keys of the same parent with a plain value (`name`,
+    /// `version`...) share a chunk up to the chunk size. Comments preceding
+    /// a key belong to its chunk.
+    fn config_key_chunks(
+        &self,
+        root: &Node,
+        source: &str,
+        lines: &[&str],
+        language: Language,
+    ) -> Vec<CodeChunk> {
+        // (start line, end line exclusive, key, holds other keys)
+        let mut segments: Vec<(usize, usize, ConfigKey, bool)> = Vec::new();
+        self.collect_config_keys(
+            top_level_keys(*root, source, language),
+            source,
+            language,
+            &mut segments,
+        );
+
+        let mut groups: Vec<KeyGroup> = Vec::new();
+        for (start, end, key, nested) in &segments {
+            let (start, end, nested) = (*start, *end, *nested);
+            let parent = &key.path[..key.path.len() - 1];
+            // Comments right above the key, not already in the previous chunk
+            let floor = groups.last().map_or(0, |last| last.end);
+            let mut start = start;
+            while start > floor && {
+                let line = lines[start - 1].trim_start();
+                line.starts_with('#') || line.starts_with("//")
+            } {
+                start -= 1;
+            }
+
+            if let Some(last) = groups.last_mut() {
+                let shares_line = start < last.end;
+                let fits = !nested && !last.holds_keys && last.parent == parent && {
+                    let size: usize = lines[last.start..end.min(lines.len())]
+                        .iter()
+                        .map(|l| l.len() + 1)
+                        .sum();
+                    size <= self.chunk_size
+                };
+                if shares_line || fits {
+                    last.end = last.end.max(end);
+                    last.paths.push(key.dotted_path());
+                    last.holds_keys |= nested;
+                    continue;
+                }
+            }
+            groups.push(KeyGroup {
+                start,
+                end,
+                paths: vec![key.dotted_path()],
+                holds_keys: nested,
+                parent,
+            });
+        }
+
+        groups
+            .into_iter()
+            .map(|group| self.create_chunk(source, lines, group.start, group.end, &group.paths))
+            .collect()
+    }
+
+    fn collect_config_keys<'tree>(
+        &self,
+        keys: Vec<ConfigKey<'tree>>,
+        source: &str,
+        language: Language,
+        segments: &mut Vec<(usize, usize, ConfigKey<'tree>, bool)>,
+    ) {
+        for key in keys {
+            let nested = key.nested_keys(source, language);
+            let size = key.node.end_byte() - key.node.start_byte();
+            if !nested.is_empty()
+                && (key.path.len() < self.config_key_depth || size > self.chunk_size)
+            {
+                self.collect_config_keys(nested, source, language, segments);
+                continue;
+            }
+
+            let start = key.node.start_position().row;
+            let end = key.node.end_position();
+            // A node ending at the start of a line does not cover that line
+            let end = if end.column == 0 && end.row > start {
+                end.row
+            } else {
+                end.row + 1
+            };
+            let holds_keys = !nested.is_empty();
+            segments.push((start, end, key, holds_keys));
         }
     }
 
@@ -284,6 +470,20 @@ struct SemanticBoundary {
     end_line: usize,
 }
 
+/// Consecutive keys of a configuration file making up one chunk
+struct KeyGroup<'a> {
+    /// First line, 0-based
+    start: usize,
+    /// Line after the last one
+    end: usize,
+    /// Dotted paths of the keys
+    paths: Vec<String>,
+    /// Whether one of the keys holds other keys
+    holds_keys: bool,
+    /// Path of the parent of the first key
+    parent: &'a [String],
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -454,4 +654,135 @@ class Calculator:
         // Empty source should produce no or empty chunks
         assert!(chunks.is_empty() || chunks[0].content.is_empty());
     }
+
+    #[test]
+    fn test_sql_chunk_per_statement() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = "-- Users\nCREATE TABLE users (\n    id INTEGER PRIMARY KEY\n);\n\nALTER TABLE users ADD COLUMN email TEXT;\nINSERT INTO users (id) VALUES (1);\nINSERT INTO users (id) VALUES (2);\nCREATE INDEX idx_email ON users (email); DROP TABLE legacy;\n";
+        let tree = support.parse(Language::Sql, source).unwrap();
+        let chunks = ChunkExtractor::new()
+            .extract(&tree, source, Language::Sql)
+            .unwrap();
+
+        let ranges: Vec<(usize, usize, Vec<&str>)> = chunks
+            .iter()
+            .map(|c| {
+                (
+                    c.start_line,
+                    c.end_line,
+                    c.symbols.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (1, 4, vec!["users"]),
+                (6, 6, vec!["users"]),
+                (7, 8, vec!["users", "users"]),
+                (9, 9, vec!["users", "legacy"]),
+            ]
+        );
+        assert!(
+            chunks[0]
+                .content
+                .starts_with("-- Users\nCREATE TABLE users (")
+        );
+    }
+
+    fn chunk_ranges(chunks: &[CodeChunk]) -> Vec<(usize, usize, Vec<&str>)> {
+        chunks
+            .iter()
+            .map(|c| {
+                (
+                    c.start_line,
+                    c.end_line,
+                    c.symbols.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_chunk_per_key_path() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = "version: '3'
+name: app
+# Services of the stack
+services:
+  web:
+    image: nginx
+    environment:
+      REDIS_URL: redis://cache:6379
+  cache:
+    image: redis
+";
+        let tree = support.parse(Language::Yaml, source).unwrap();
+
+        let chunks = ChunkExtractor::new()
+            .extract(&tree, source, Language::Yaml)
+            .unwrap();
+        assert_eq!(
+            chunk_ranges(&chunks),
+            vec![(1, 2, vec!["version", "name"]), (3, 10, vec!["services"])]
+        );
+        assert!(
+            chunks[1]
+                .content
+                .starts_with("# Services of the stack\nservices:")
+        );
+
+        let chunks = ChunkExtractor::new()
+            .with_config_key_depth(3)
+            .extract(&tree, source, Language::Yaml)
+            .unwrap();
+        assert_eq!(
+            chunk_ranges(&chunks),
+            vec![
+                (1, 2, vec!["version", "name"]),
+                (6, 6, vec!["services.web.image"]),
+                (7, 8, vec!["services.web.environment"]),
+                (10, 10, vec!["services.cache.image"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_large_config_key_is_split_into_its_keys() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = "{\n  \"name\": \"app\",\n  \"scripts\": {\n    \"build\": \"tsc --project tsconfig.build.json\",\n    \"test\": \"vitest run --coverage\"\n  }\n}\n";
+        let tree = support.parse(Language::Json, source).unwrap();
+
+        let chunks = ChunkExtractor::new()
+            .with_chunk_size(60)
+            .extract(&tree, source, Language::Json)
+            .unwrap();
+        assert_eq!(
+            chunk_ranges(&chunks),
+            vec![
+                (2, 2, vec!["name"]),
+                (4, 4, vec!["scripts.build"]),
+                (5, 5, vec!["scripts.test"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toml_chunk_per_table() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = "title = \"app\"\n\n[database]\nurl = \"postgres://localhost\"\n\n[database.pool]\nsize = 4\n";
+        let tree = support.parse(Language::Toml, source).unwrap();
+
+        let chunks = ChunkExtractor::new()
+            .extract(&tree, source, Language::Toml)
+            .unwrap();
+        assert_eq!(
+            chunk_ranges(&chunks),
+            vec![
+                (1, 1, vec!["title"]),
+                (3, 5, vec!["database"]),
+                (6, 7, vec!["database.pool"]),
+            ]
+        );
+    }
 }
@@ -0,0 +1,279 @@
+use crate::language::Language;
+use tree_sitter::Node;
+
+/// Conditional compilation context of the symbol declared by `node`: the
+/// conditions under which it is compiled, outermost first, joined by `&&`.
+///
+/// Read from `#[cfg(...)]` attributes of the item and its enclosing items
+/// and `#![cfg(...)]` of enclosing modules in Rust, enclosing `#if`,
+/// `#ifdef` and `#ifndef` regions in C and C++ (as `defined(X)`,
+/// `!defined(X)` or the `#if` condition, negated in `#else` branches, include
+/// guards aside) and `//go:build` constraints in Go. `None` when the symbol
+/// is always compiled.
+pub(crate) fn cfg_context(node: &Node, source: &str, language: Language) -> Option<String> {
+    let conditions = match language {
+        Language::Rust => rust_conditions(node, source),
+        Language::C | Language::Cpp => preproc_conditions(node, source),
+        Language::Go => go_build_constraint(node, source).into_iter().collect(),
+        _ => Vec::new(),
+    };
+
+    if conditions.is_empty() {
+        None
+    } else {
+        Some(conditions.join(" && "))
+    }
+}
+
+fn text<'a>(node: &Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or("")
+}
+
+/// Whitespace of a condition collapsed to single spaces
+fn normalize(condition: &str) -> String {
+    condition.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn rust_conditions(node: &Node, source: &str) -> Vec<String> {
+    let mut levels: Vec<Vec<String>> = Vec::new();
+    let mut current = Some(*node);
+
+    while let Some(item) = current {
+        let mut level = Vec::new();
+
+        // `#![cfg(...)]` at the top of a file or an inline module
+        if matches!(item.kind(), "source_file" | "declaration_list") {
+            let mut cursor = item.walk();
+            for child in item.children(&mut cursor) {
+                if child.kind() == "inner_attribute_item" {
+                    level.extend(rust_cfg(&child, source));
+                }
+            }
+        }
+
+        // `#[cfg(...)]` attributes preceding the item
+        let mut outer = Vec::new();
+        let mut sibling = item.prev_sibling();
+        while let Some(prev) = sibling {
+            match prev.kind() {
+                "attribute_item" => outer.extend(rust_cfg(&prev, source)),
+                "line_comment" | "block_comment" => {}
+                _ => break,
+            }
+            sibling = prev.prev_sibling();
+        }
+        outer.reverse();
+        level.extend(outer);
+
+        levels.push(level);
+        current = item.parent();
+    }
+
+    levels.into_iter().rev().flatten().collect()
+}
+
+/// Condition of a `#[cfg(...)]` or `#![cfg(...)]` attribute
+fn rust_cfg(attribute_item: &Node, source: &str) -> Option<String> {
+    let mut cursor = attribute_item.walk();
+    let attribute = attribute_item
+        .children(&mut cursor)
+        .find(|child| child.kind() == "attribute")?;
+    let condition = text(&attribute, source)
+        .strip_prefix("cfg")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    Some(normalize(condition))
+}
+
+fn preproc_conditions(node: &Node, source: &str) -> Vec<String> {
+    let mut conditions = Vec::new();
+    let mut child = *node;
+
+    while let Some(parent) = child.parent() {
+        let in_alternative = parent
+            .child_by_field_name("alternative")
+            .is_some_and(|alternative| alternative.id() == child.id());
+
+        let condition = match parent.kind() {
+            "preproc_ifdef" if !is_include_guard(&parent, source) => {
+                parent.child_by_field_name("name").map(|name| {
+                    let negated = parent.child(0).is_some_and(|d| d.kind() == "#ifndef");
+                    let defined = format!("defined({})", text(&name, source));
+                    if negated != in_alternative {
+                        format!("!{}", defined)
+                    } else {
+                        defined
+                    }
+                })
+            }
+            "preproc_if" | "preproc_elif" => {
+                parent.child_by_field_name("condition").map(|condition| {
+                    let condition = normalize(text(&condition, source));
+                    if in_alternative {
+                        format!("!({})", condition)
+                    } else {
+                        condition
+                    }
+                })
+            }
+            _ => None,
+        };
+        conditions.extend(condition);
+        child = parent;
+    }
+
+    conditions.reverse();
+    conditions
+}
+
+/// `#ifndef X` directly followed by `#define X`
+fn is_include_guard(ifdef: &Node, source: &str) -> bool {
+    if ifdef.child(0).is_none_or(|d| d.kind() != "#ifndef") {
+        return false;
+    }
+    let Some(name) = ifdef.child_by_field_name("name") else {
+        return false;
+    };
+
+    let mut cursor = ifdef.walk();
+    ifdef
+        .named_children(&mut cursor)
+        .filter(|child| child.id() != name.id() && !child.kind().contains("comment"))
+        .take(1)
+        .any(|child| {
+            child.kind() == "preproc_def"
+                && child
+                    .child_by_field_name("name")
+                    .is_some_and(|defined| text(&defined, source) == text(&name, source))
+        })
+}
+
+/// Expression of the `//go:build` line of the file of `node`
+fn go_build_constraint(node: &Node, source: &str) -> Option<String> {
+    let mut root = *node;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .take_while(|child| child.kind() == "comment")
+        .find_map(|comment| {
+            text(&comment, source)
+                .strip_prefix("//go:build ")
+                .map(normalize)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+    use crate::symbols::{Symbol, SymbolExtractor};
+
+    fn extract(language: Language, source: &str) -> Vec<Symbol> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        SymbolExtractor::extract(&tree, source, language).unwrap()
+    }
+
+    fn cfg(symbols: &[Symbol], name: &str) -> Option<String> {
+        symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("no symbol {name}"))
+            .cfg_context
+            .clone()
+    }
+
+    #[test]
+    fn test_rust_cfg_context() {
+        let source = r#"
+fn always() {}
+
+#[cfg(feature = "tls")]
+/// Connect over TLS
+#[inline]
+fn connect_tls() {}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    fn unix_only() {}
+
+    fn helper() {}
+}
+
+#[cfg(any(windows,   target_os = "macos"))]
+impl Client {
+    fn open(&self) {}
+}
+"#;
+        let symbols = extract(Language::Rust, source);
+        assert_eq!(cfg(&symbols, "always"), None);
+        assert_eq!(
+            cfg(&symbols, "connect_tls").as_deref(),
+            Some(r#"feature = "tls""#)
+        );
+        assert_eq!(cfg(&symbols, "tests").as_deref(), Some("test"));
+        assert_eq!(cfg(&symbols, "helper").as_deref(), Some("test"));
+        assert_eq!(cfg(&symbols, "unix_only").as_deref(), Some("test && unix"));
+        assert_eq!(
+            cfg(&symbols, "open").as_deref(),
+            Some(r#"any(windows, target_os = "macos")"#)
+        );
+
+        let gated = extract(Language::Rust, "#![cfg(feature = \"cli\")]\nfn run() {}");
+        assert_eq!(cfg(&gated, "run").as_deref(), Some(r#"feature = "cli""#));
+    }
+
+    #[test]
+    fn test_preprocessor_context() {
+        let source = r#"
+#ifndef CONFIG_H
+#define CONFIG_H
+
+int always(void) { return 1; }
+
+#ifdef _WIN32
+int open_windows(void) { return 0; }
+#else
+int open_posix(void) { return 0; }
+#endif
+
+#if DEBUG > 1
+int trace(void) { return 0; }
+#elif DEBUG
+int log_debug(void) { return 0; }
+#endif
+
+#endif
+"#;
+        let symbols = extract(Language::Cpp, source);
+        assert_eq!(cfg(&symbols, "always"), None);
+        assert_eq!(
+            cfg(&symbols, "open_windows").as_deref(),
+            Some("defined(_WIN32)")
+        );
+        assert_eq!(
+            cfg(&symbols, "open_posix").as_deref(),
+            Some("!defined(_WIN32)")
+        );
+        assert_eq!(cfg(&symbols, "trace").as_deref(), Some("DEBUG > 1"));
+        assert_eq!(
+            cfg(&symbols, "log_debug").as_deref(),
+            Some("!(DEBUG > 1) && DEBUG")
+        );
+    }
+
+    #[test]
+    fn test_go_build_constraint() {
+        let source = "//go:build linux && amd64\n\npackage sys\n\nfunc Probe() {}\n";
+        let symbols = extract(Language::Go, source);
+        assert_eq!(cfg(&symbols, "Probe").as_deref(), Some("linux && amd64"));
+
+        let symbols = extract(Language::Go, "package sys\n\nfunc Probe() {}\n");
+        assert_eq!(cfg(&symbols, "Probe"), None);
+    }
+}
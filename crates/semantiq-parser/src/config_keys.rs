@@ -0,0 +1,215 @@
+//! Keys of configuration files and their paths.
+//!
+//! JSON, YAML and TOML files are trees of keys rather than of code symbols,
+//! and a key is known by its path from the root of the document, e.g.
+//! `services.web.environment`: it is how configuration is looked up and how
+//! people ask about it. Symbols of these files are named after their key
+//! with the path of the enclosing keys as parent, and their chunks hold one
+//! key each (see [`crate::ChunkExtractor`]).
+
+use crate::language::Language;
+use tree_sitter::Node;
+
+/// Whether files of `language` are configuration, made of keys
+pub fn is_config_language(language: Language) -> bool {
+    matches!(language, Language::Json | Language::Yaml | Language::Toml)
+}
+
+/// A key of a configuration file
+#[derive(Debug, Clone)]
+pub(crate) struct ConfigKey<'tree> {
+    /// `pair`, `block_mapping_pair`, `table` or `table_array_element`
+    pub node: Node<'tree>,
+    /// Segments of the path of the key from the root of the document
+    pub path: Vec<String>,
+}
+
+impl<'tree> ConfigKey<'tree> {
+    /// Path of the key, its segments joined with dots
+    pub fn dotted_path(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// Keys defined directly in the value of this key, in order. Keys in
+    /// lists and inline tables are part of the value rather than nested.
+    pub fn nested_keys(&self, source: &str, language: Language) -> Vec<ConfigKey<'tree>> {
+        let container = match language {
+            Language::Json => self
+                .node
+                .child_by_field_name("value")
+                .filter(|v| v.kind() == "object"),
+            Language::Yaml => self
+                .node
+                .child_by_field_name("value")
+                .filter(|v| v.kind() == "block_node")
+                .and_then(|v| v.named_child(0))
+                .filter(|v| v.kind() == "block_mapping"),
+            Language::Toml => {
+                Some(self.node).filter(|n| matches!(n.kind(), "table" | "table_array_element"))
+            }
+            _ => None,
+        };
+        container
+            .map(|container| pairs_of(container, &self.path, source, language))
+            .unwrap_or_default()
+    }
+}
+
+/// Keys at the top of each document of a configuration file, in order. TOML
+/// tables are keys named by their header (`[database.pool]`).
+pub(crate) fn top_level_keys<'tree>(
+    root: Node<'tree>,
+    source: &str,
+    language: Language,
+) -> Vec<ConfigKey<'tree>> {
+    match language {
+        Language::Json => named_children(root)
+            .filter(|n| n.kind() == "object")
+            .flat_map(|object| pairs_of(object, &[], source, language))
+            .collect(),
+        Language::Yaml => named_children(root)
+            .filter(|n| n.kind() == "document")
+            .filter_map(|document| document.named_child(0))
+            .filter(|n| n.kind() == "block_node")
+            .filter_map(|block| block.named_child(0))
+            .filter(|n| n.kind() == "block_mapping")
+            .flat_map(|mapping| pairs_of(mapping, &[], source, language))
+            .collect(),
+        Language::Toml => named_children(root)
+            .filter_map(|node| match node.kind() {
+                "pair" | "table" | "table_array_element" => Some(ConfigKey {
+                    path: key_segments(&node, source, language)?,
+                    node,
+                }),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Segments of the key a pair or TOML table is named by, unquoted: one,
+/// or several for TOML dotted keys (`a.b = 1`)
+pub(crate) fn key_segments(node: &Node, source: &str, language: Language) -> Option<Vec<String>> {
+    let key = match language {
+        Language::Json | Language::Yaml => node.child_by_field_name("key")?,
+        Language::Toml => node
+            .named_child(0)
+            .filter(|k| matches!(k.kind(), "bare_key" | "quoted_key" | "dotted_key"))?,
+        _ => return None,
+    };
+
+    let mut segments = Vec::new();
+    collect_segments(&key, source, &mut segments);
+    (!segments.is_empty()).then_some(segments)
+}
+
+fn collect_segments(key: &Node, source: &str, segments: &mut Vec<String>) {
+    if key.kind() == "dotted_key" {
+        for part in named_children(*key) {
+            collect_segments(&part, source, segments);
+        }
+    } else if let Ok(text) = key.utf8_text(source.as_bytes()) {
+        let text = text.trim().trim_matches(['"', '\'']);
+        if !text.is_empty() {
+            segments.push(text.to_string());
+        }
+    }
+}
+
+/// Pairs directly inside an object, mapping or table, under `parent`
+fn pairs_of<'tree>(
+    container: Node<'tree>,
+    parent: &[String],
+    source: &str,
+    language: Language,
+) -> Vec<ConfigKey<'tree>> {
+    named_children(container)
+        .filter(|n| matches!(n.kind(), "pair" | "block_mapping_pair"))
+        .filter_map(|node| {
+            let mut path = parent.to_vec();
+            path.extend(key_segments(&node, source, language)?);
+            Some(ConfigKey { node, path })
+        })
+        .collect()
+}
+
+fn named_children(node: Node<'_>) -> impl Iterator<Item = Node<'_>> {
+    (0..node.named_child_count()).filter_map(move |i| node.named_child(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+    use std::path::Path;
+
+    fn paths(language: Language, path: &str, source: &str, depth: usize) -> Vec<String> {
+        fn walk(
+            key: &ConfigKey,
+            source: &str,
+            language: Language,
+            depth: usize,
+            out: &mut Vec<String>,
+        ) {
+            out.push(key.dotted_path());
+            if depth > 1 {
+                for nested in key.nested_keys(source, language) {
+                    walk(&nested, source, language, depth - 1, out);
+                }
+            }
+        }
+
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support
+            .parse_file(language, Path::new(path), source)
+            .unwrap();
+        let mut out = Vec::new();
+        for key in top_level_keys(tree.root_node(), source, language) {
+            walk(&key, source, language, depth, &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn test_yaml_key_paths() {
+        let source = "services:\n  web:\n    environment:\n      REDIS_URL: redis://cache\n    ports:\n      - \"80:80\"\n\"quoted key\": 1\n---\nsecond: 2\n";
+        assert_eq!(
+            paths(Language::Yaml, "compose.yaml", source, 3),
+            [
+                "services",
+                "services.web",
+                "services.web.environment",
+                "services.web.ports",
+                "quoted key",
+                "second",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_key_paths() {
+        let source = r#"{"name": "app", "scripts": {"build": "tsc"}, "files": [{"a": 1}]}"#;
+        assert_eq!(
+            paths(Language::Json, "package.json", source, 2),
+            ["name", "scripts", "scripts.build", "files"]
+        );
+    }
+
+    #[test]
+    fn test_toml_key_paths() {
+        let source = "a.b = 1\n\"q k\" = 2\n[database.pool]\nsize = 4\nopts = { w = 1 }\n[[bin]]\nname = \"cli\"\n";
+        assert_eq!(
+            paths(Language::Toml, "app.toml", source, 2),
+            [
+                "a.b",
+                "q k",
+                "database.pool",
+                "database.pool.size",
+                "database.pool.opts",
+                "bin",
+                "bin.name",
+            ]
+        );
+    }
+}
@@ -0,0 +1,885 @@
+//! Reads and writes of environment variables and configuration keys.
+//!
+//! Environment variables are recognized by the standard accessors of each
+//! language (`std::env::var("X")`, `process.env.X`, `os.environ["X"]`,
+//! `os.Getenv("X")`, `System.getenv("X")`, `getenv("X")`, `ENV["X"]`,
+//! `$X` in shell scripts, ...). Configuration keys are the string keys read
+//! or set through a configuration object (`config.get("db.url")`,
+//! `viper.GetString("port")`, `app.config["SECRET_KEY"]`,
+//! `Configuration["Logging"]`), system properties and Spring
+//! `@Value("${db.url}")` placeholders. Only keys written as literals are
+//! recorded.
+
+use crate::language::Language;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tree_sitter::{Node, Tree};
+
+/// Functions reading or writing environment variables and system
+/// properties, by their name or the end of their qualified name (`::`, `->`
+/// and `\` read as `.`)
+const ACCESSORS: &[(&str, ConfigSource, ConfigAccess)] = &[
+    // Rust
+    ("env.var", ConfigSource::Env, ConfigAccess::Read),
+    ("env.var_os", ConfigSource::Env, ConfigAccess::Read),
+    ("dotenvy.var", ConfigSource::Env, ConfigAccess::Read),
+    ("dotenv.var", ConfigSource::Env, ConfigAccess::Read),
+    ("env.set_var", ConfigSource::Env, ConfigAccess::Write),
+    ("env.remove_var", ConfigSource::Env, ConfigAccess::Write),
+    // Python
+    ("os.getenv", ConfigSource::Env, ConfigAccess::Read),
+    ("environ.get", ConfigSource::Env, ConfigAccess::Read),
+    ("environ.setdefault", ConfigSource::Env, ConfigAccess::Write),
+    ("environ.pop", ConfigSource::Env, ConfigAccess::Write),
+    ("os.putenv", ConfigSource::Env, ConfigAccess::Write),
+    ("os.unsetenv", ConfigSource::Env, ConfigAccess::Write),
+    // Go
+    ("os.Getenv", ConfigSource::Env, ConfigAccess::Read),
+    ("os.LookupEnv", ConfigSource::Env, ConfigAccess::Read),
+    ("os.Setenv", ConfigSource::Env, ConfigAccess::Write),
+    ("os.Unsetenv", ConfigSource::Env, ConfigAccess::Write),
+    // Deno
+    ("Deno.env.get", ConfigSource::Env, ConfigAccess::Read),
+    ("Deno.env.set", ConfigSource::Env, ConfigAccess::Write),
+    ("Deno.env.delete", ConfigSource::Env, ConfigAccess::Write),
+    // Java, Kotlin, Scala
+    ("System.getenv", ConfigSource::Env, ConfigAccess::Read),
+    ("sys.env", ConfigSource::Env, ConfigAccess::Read),
+    ("sys.env.get", ConfigSource::Env, ConfigAccess::Read),
+    ("sys.env.getOrElse", ConfigSource::Env, ConfigAccess::Read),
+    (
+        "System.getProperty",
+        ConfigSource::Config,
+        ConfigAccess::Read,
+    ),
+    (
+        "System.setProperty",
+        ConfigSource::Config,
+        ConfigAccess::Write,
+    ),
+    (
+        "System.clearProperty",
+        ConfigSource::Config,
+        ConfigAccess::Write,
+    ),
+    // C#
+    (
+        "Environment.GetEnvironmentVariable",
+        ConfigSource::Env,
+        ConfigAccess::Read,
+    ),
+    (
+        "Environment.SetEnvironmentVariable",
+        ConfigSource::Env,
+        ConfigAccess::Write,
+    ),
+    // C, C++, PHP
+    ("getenv", ConfigSource::Env, ConfigAccess::Read),
+    ("secure_getenv", ConfigSource::Env, ConfigAccess::Read),
+    ("setenv", ConfigSource::Env, ConfigAccess::Write),
+    ("unsetenv", ConfigSource::Env, ConfigAccess::Write),
+    ("putenv", ConfigSource::Env, ConfigAccess::Write),
+    // Ruby
+    ("ENV.fetch", ConfigSource::Env, ConfigAccess::Read),
+    ("ENV.key?", ConfigSource::Env, ConfigAccess::Read),
+    ("ENV.store", ConfigSource::Env, ConfigAccess::Write),
+    ("ENV.delete", ConfigSource::Env, ConfigAccess::Write),
+    // Elixir
+    ("System.get_env", ConfigSource::Env, ConfigAccess::Read),
+    ("System.fetch_env", ConfigSource::Env, ConfigAccess::Read),
+    ("System.fetch_env!", ConfigSource::Env, ConfigAccess::Read),
+    ("System.put_env", ConfigSource::Env, ConfigAccess::Write),
+    ("System.delete_env", ConfigSource::Env, ConfigAccess::Write),
+];
+
+/// Objects holding the environment, whose members or subscripts are
+/// variables
+const ENV_OBJECTS: &[&str] = &[
+    "process.env",
+    "import.meta.env",
+    "Bun.env",
+    "os.environ",
+    "environ",
+    "ENV",
+    "$_ENV",
+    "sys.env",
+];
+
+/// Words naming configuration objects (`config`, `appConfig`,
+/// `settings`, `viper`, `Configuration`, `AppSettings`, ...), looked for in
+/// the last segment of receivers, lowercased
+const CONFIG_RECEIVERS: &[&str] = &[
+    "config",
+    "cfg",
+    "settings",
+    "viper",
+    "properties",
+    "environment",
+];
+
+/// Whether a variable or key is read or written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigAccess {
+    Read,
+    /// Assignment, deletion or default set
+    Write,
+}
+
+impl ConfigAccess {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigAccess::Read => "read",
+            ConfigAccess::Write => "write",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read" => Some(ConfigAccess::Read),
+            "write" => Some(ConfigAccess::Write),
+            _ => None,
+        }
+    }
+}
+
+/// Where a value is looked up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// Environment variable
+    Env,
+    /// Key of a configuration object, system property or Spring placeholder
+    Config,
+}
+
+impl ConfigSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigSource::Env => "env",
+            ConfigSource::Config => "config",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "env" => Some(ConfigSource::Env),
+            "config" => Some(ConfigSource::Config),
+            _ => None,
+        }
+    }
+}
+
+/// A read or write of an environment variable or configuration key
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ConfigRef {
+    /// Variable (`DATABASE_URL`) or key (`db.url`)
+    pub name: String,
+    pub source: ConfigSource,
+    pub access: ConfigAccess,
+    pub line: usize,
+}
+
+pub struct ConfigRefExtractor;
+
+impl ConfigRefExtractor {
+    /// Extract the environment variables and configuration keys read or
+    /// written by a file, once per name, access and line
+    pub fn extract(tree: &Tree, source: &str, language: Language) -> Result<Vec<ConfigRef>> {
+        let mut refs = Vec::new();
+        Self::extract_recursive(&tree.root_node(), source, language, &mut refs);
+
+        let mut seen = HashSet::new();
+        refs.retain(|r: &ConfigRef| seen.insert(r.clone()));
+        Ok(refs)
+    }
+
+    fn extract_recursive(node: &Node, source: &str, language: Language, refs: &mut Vec<ConfigRef>) {
+        match node.kind() {
+            "call_expression"
+            | "call"
+            | "method_invocation"
+            | "invocation_expression"
+            | "function_call_expression"
+            | "member_call_expression"
+            | "scoped_call_expression" => Self::extract_call(node, source, language, refs),
+            "macro_invocation" => Self::extract_rust_macro(node, source, refs),
+            "subscript_expression"
+            | "subscript"
+            | "element_reference"
+            | "element_access_expression"
+            | "index_expression"
+            | "indexing_expression" => Self::extract_subscript(node, source, refs),
+            "member_expression" => Self::extract_js_member(node, source, refs),
+            "variable_declarator" => Self::extract_js_destructuring(node, source, refs),
+            "annotation" if language == Language::Java => {
+                Self::extract_spring_value(node, source, refs)
+            }
+            "simple_expansion" | "expansion" if language == Language::Bash => {
+                Self::extract_shell_expansion(node, source, refs)
+            }
+            "declaration_command" if language == Language::Bash => {
+                Self::extract_shell_export(node, source, refs)
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::extract_recursive(&child, source, language, refs);
+        }
+    }
+
+    /// `env::var("X")`, `os.Getenv("X")`, `config.get("db.url")`, ...
+    fn extract_call(node: &Node, source: &str, language: Language, refs: &mut Vec<ConfigRef>) {
+        let Some((callee, arguments)) = Self::call_parts(node, source) else {
+            return;
+        };
+        let Some(name) = Self::first_string_arg(&arguments, source) else {
+            return;
+        };
+
+        let accessor = ACCESSORS
+            .iter()
+            .find(|(accessor, _, _)| is_callee(&callee, accessor))
+            .map(|(_, source, access)| (*source, *access));
+        let accessor = accessor.or_else(|| match (language, callee.as_str()) {
+            // Laravel helpers
+            (Language::Php, "env") => Some((ConfigSource::Env, ConfigAccess::Read)),
+            (Language::Php, "config") => Some((ConfigSource::Config, ConfigAccess::Read)),
+            _ => Self::config_method(&callee),
+        });
+        let Some((config_source, access)) = accessor else {
+            return;
+        };
+
+        // putenv("X=1")
+        let name = name.split('=').next().unwrap_or_default();
+        Self::push(node, name, config_source, access, refs);
+    }
+
+    /// `env!("X")` and `option_env!("X")`
+    fn extract_rust_macro(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        let Some(name) = node.child_by_field_name("macro") else {
+            return;
+        };
+        if !matches!(text(&name, source), "env" | "option_env") {
+            return;
+        }
+        let mut cursor = node.walk();
+        let Some(arguments) = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "token_tree")
+        else {
+            return;
+        };
+        if let Some(variable) = Self::first_string_arg(&arguments, source) {
+            Self::push(node, &variable, ConfigSource::Env, ConfigAccess::Read, refs);
+        }
+    }
+
+    /// `os.environ["X"]`, `ENV["X"]`, `$_ENV['X']`, `app.config["X"]`,
+    /// `Configuration["Logging"]`, written when assigned or deleted
+    fn extract_subscript(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        let Some(object) = node.named_child(0) else {
+            return;
+        };
+        let mut key = None;
+        for i in 1..node.named_child_count() {
+            if let Some(index) = node.named_child(i) {
+                key = Self::string_in(&index, source, 2);
+                if key.is_some() {
+                    break;
+                }
+            }
+        }
+        let Some(key) = key else {
+            return;
+        };
+
+        let object = normalize_callee(text(&object, source));
+        let config_source = if ENV_OBJECTS.contains(&object.as_str()) {
+            ConfigSource::Env
+        } else if is_config_receiver(&object) {
+            ConfigSource::Config
+        } else {
+            return;
+        };
+        Self::push(
+            node,
+            &key,
+            config_source,
+            Self::access_of(node, source),
+            refs,
+        );
+    }
+
+    /// `process.env.X`, `import.meta.env.X`, written when assigned or
+    /// deleted
+    fn extract_js_member(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        let (Some(object), Some(property)) = (
+            node.child_by_field_name("object"),
+            node.child_by_field_name("property"),
+        ) else {
+            return;
+        };
+        if !matches!(
+            text(&object, source),
+            "process.env" | "import.meta.env" | "Bun.env"
+        ) {
+            return;
+        }
+        let access = Self::access_of(node, source);
+        Self::push(
+            node,
+            text(&property, source),
+            ConfigSource::Env,
+            access,
+            refs,
+        );
+    }
+
+    /// `const { PORT, HOST: host } = process.env`
+    fn extract_js_destructuring(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        let (Some(pattern), Some(value)) = (
+            node.child_by_field_name("name"),
+            node.child_by_field_name("value"),
+        ) else {
+            return;
+        };
+        if pattern.kind() != "object_pattern"
+            || !matches!(text(&value, source), "process.env" | "import.meta.env")
+        {
+            return;
+        }
+        let mut cursor = pattern.walk();
+        for property in pattern.named_children(&mut cursor) {
+            let name = match property.kind() {
+                "shorthand_property_identifier_pattern" => Some(property),
+                "pair_pattern" => property.child_by_field_name("key"),
+                "object_assignment_pattern" => property.child_by_field_name("left"),
+                _ => None,
+            };
+            if let Some(name) = name {
+                let name = text(&name, source).trim_matches(|c| matches!(c, '"' | '\''));
+                Self::push(&property, name, ConfigSource::Env, ConfigAccess::Read, refs);
+            }
+        }
+    }
+
+    /// `@Value("${db.url:localhost}")`
+    fn extract_spring_value(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        let (Some(name), Some(arguments)) = (
+            node.child_by_field_name("name"),
+            node.child_by_field_name("arguments"),
+        ) else {
+            return;
+        };
+        if text(&name, source) != "Value" {
+            return;
+        }
+        let Some(value) = Self::string_in(&arguments, source, 2) else {
+            return;
+        };
+        for placeholder in value.split("${").skip(1) {
+            let key = placeholder
+                .split(['}', ':'])
+                .next()
+                .unwrap_or_default()
+                .trim();
+            Self::push(node, key, ConfigSource::Config, ConfigAccess::Read, refs);
+        }
+    }
+
+    /// `$DATABASE_URL`, `${PORT:-8080}`: uppercase shell variables, by
+    /// convention those of the environment
+    fn extract_shell_expansion(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        let mut cursor = node.walk();
+        let Some(variable) = node
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "variable_name")
+        else {
+            return;
+        };
+        let name = text(&variable, source);
+        if is_uppercase_name(name) {
+            Self::push(node, name, ConfigSource::Env, ConfigAccess::Read, refs);
+        }
+    }
+
+    /// `export DATABASE_URL=...`
+    fn extract_shell_export(node: &Node, source: &str, refs: &mut Vec<ConfigRef>) {
+        if !text(node, source).starts_with("export") {
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let variable = match child.kind() {
+                "variable_assignment" => child.child_by_field_name("name"),
+                "variable_name" => Some(child),
+                _ => None,
+            };
+            if let Some(variable) = variable {
+                let name = text(&variable, source);
+                Self::push(&child, name, ConfigSource::Env, ConfigAccess::Write, refs);
+            }
+        }
+    }
+
+    /// Normalized callee and argument list of a call
+    fn call_parts<'t>(node: &Node<'t>, source: &str) -> Option<(String, Node<'t>)> {
+        let callee = match node.kind() {
+            // Java
+            "method_invocation" => {
+                let name = text(&node.child_by_field_name("name")?, source);
+                match node.child_by_field_name("object") {
+                    Some(object) => format!("{}.{}", text(&object, source), name),
+                    None => name.to_string(),
+                }
+            }
+            // PHP
+            "member_call_expression" => format!(
+                "{}.{}",
+                text(&node.child_by_field_name("object")?, source),
+                text(&node.child_by_field_name("name")?, source)
+            ),
+            "scoped_call_expression" => format!(
+                "{}.{}",
+                text(&node.child_by_field_name("scope")?, source),
+                text(&node.child_by_field_name("name")?, source)
+            ),
+            // Ruby
+            "call" if node.child_by_field_name("method").is_some() => {
+                let method = text(&node.child_by_field_name("method")?, source);
+                match node.child_by_field_name("receiver") {
+                    Some(receiver) => format!("{}.{}", text(&receiver, source), method),
+                    None => method.to_string(),
+                }
+            }
+            _ => {
+                let function = node
+                    .child_by_field_name("function")
+                    .or_else(|| node.child_by_field_name("target"))
+                    .or_else(|| node.named_child(0))?;
+                text(&function, source).to_string()
+            }
+        };
+
+        let arguments = node.child_by_field_name("arguments").or_else(|| {
+            // Kotlin: call_suffix > value_arguments, Elixir: arguments
+            let mut cursor = node.walk();
+            let arguments = node
+                .named_children(&mut cursor)
+                .find(|c| matches!(c.kind(), "call_suffix" | "arguments"))?;
+            match arguments.kind() {
+                "call_suffix" => arguments.named_child(0),
+                _ => Some(arguments),
+            }
+        })?;
+        Some((normalize_callee(&callee), arguments))
+    }
+
+    /// Getter or setter called on a configuration object:
+    /// `config.get("db.url")`, `viper.GetString("port")`,
+    /// `settings.set_value("x")`
+    fn config_method(callee: &str) -> Option<(ConfigSource, ConfigAccess)> {
+        let (receiver, method) = callee.rsplit_once('.')?;
+        if !is_config_receiver(receiver) {
+            return None;
+        }
+        let access = if is_accessor_method(method, "get") {
+            ConfigAccess::Read
+        } else if is_accessor_method(method, "set") {
+            ConfigAccess::Write
+        } else {
+            return None;
+        };
+        Some((ConfigSource::Config, access))
+    }
+
+    /// Whether the value at `node` is assigned or deleted rather than read
+    fn access_of(node: &Node, source: &str) -> ConfigAccess {
+        let Some(parent) = node.parent() else {
+            return ConfigAccess::Read;
+        };
+        match parent.kind() {
+            "assignment_expression"
+            | "assignment"
+            | "augmented_assignment"
+            | "augmented_assignment_expression"
+            | "operator_assignment"
+            | "assignment_statement" => {
+                let left = parent
+                    .child_by_field_name("left")
+                    .or_else(|| parent.named_child(0));
+                match left {
+                    Some(left)
+                        if left.start_byte() <= node.start_byte()
+                            && node.end_byte() <= left.end_byte() =>
+                    {
+                        ConfigAccess::Write
+                    }
+                    _ => ConfigAccess::Read,
+                }
+            }
+            // delete process.env.X
+            "unary_expression" if text(&parent, source).starts_with("delete") => {
+                ConfigAccess::Write
+            }
+            // del os.environ["X"], unset($_ENV['X'])
+            "delete_statement" | "unset_statement" => ConfigAccess::Write,
+            "expression_list" => match parent.parent() {
+                Some(grand) if matches!(grand.kind(), "delete_statement") => ConfigAccess::Write,
+                Some(grand) if grand.kind() == "assignment_statement" => {
+                    Self::access_of(&parent, source)
+                }
+                _ => ConfigAccess::Read,
+            },
+            _ => ConfigAccess::Read,
+        }
+    }
+
+    /// First argument of a call, if it is a string literal
+    fn first_string_arg(arguments: &Node, source: &str) -> Option<String> {
+        let mut cursor = arguments.walk();
+        let argument = arguments
+            .named_children(&mut cursor)
+            .find(|c| c.kind() != "comment")?;
+        Self::string_in(&argument, source, 2)
+    }
+
+    /// Value of the string literal at `node`, or wrapped in it (`argument`,
+    /// `value_argument`, bracketed lists) up to `depth` levels
+    fn string_in(node: &Node, source: &str, depth: usize) -> Option<String> {
+        let kind = node.kind();
+        if kind.contains("string") && !kind.contains("content") && !kind.contains("fragment") {
+            return string_value(node, source);
+        }
+        if depth == 0 || node.named_child_count() != 1 {
+            return None;
+        }
+        Self::string_in(&node.named_child(0)?, source, depth - 1)
+    }
+
+    fn push(
+        node: &Node,
+        name: &str,
+        source: ConfigSource,
+        access: ConfigAccess,
+        refs: &mut Vec<ConfigRef>,
+    ) {
+        let valid = match source {
+            ConfigSource::Env => is_env_name(name),
+            ConfigSource::Config => is_config_key(name),
+        };
+        if !valid {
+            return;
+        }
+        refs.push(ConfigRef {
+            name: name.to_string(),
+            source,
+            access,
+            line: node.start_position().row + 1,
+        });
+    }
+}
+
+/// Callee without whitespace and generic arguments, its separators read
+/// as `.`: `std::env::var` gives `std.env.var`, `$this->config->get` gives
+/// `$this.config.get`
+fn normalize_callee(callee: &str) -> String {
+    let callee: String = callee.split_whitespace().collect();
+    let callee = callee
+        .replace("->", ".")
+        .replace("?.", ".")
+        .replace("::", ".")
+        .replace('\\', ".");
+    let mut normalized = String::with_capacity(callee.len());
+    let mut depth = 0usize;
+    for c in callee.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => normalized.push(c),
+            _ => {}
+        }
+    }
+    normalized.replace("..", ".").trim_matches('.').to_string()
+}
+
+/// Whether `callee` is `accessor` or ends with it after a `.`
+fn is_callee(callee: &str, accessor: &str) -> bool {
+    callee == accessor
+        || callee
+            .strip_suffix(accessor)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Whether the last segment of `receiver` names a configuration object
+fn is_config_receiver(receiver: &str) -> bool {
+    let last = receiver.rsplit('.').next().unwrap_or(receiver);
+    let last = last
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(['$', '@', '_'])
+        .to_lowercase();
+    CONFIG_RECEIVERS.iter().any(|word| last.contains(word))
+}
+
+/// `get`, `get_string`, `getString`, `GetString` for the `get` prefix
+fn is_accessor_method(method: &str, prefix: &str) -> bool {
+    let lower = method.to_lowercase();
+    let Some(rest) = lower.strip_prefix(prefix) else {
+        return false;
+    };
+    rest.is_empty()
+        || rest.starts_with('_')
+        || method[prefix.len()..].starts_with(|c: char| c.is_uppercase())
+}
+
+/// Value of a string literal without its quotes, `None` for literals
+/// without quotes (heredocs)
+fn string_value(node: &Node, source: &str) -> Option<String> {
+    let literal = text(node, source);
+    let value = literal.trim_matches(|c| matches!(c, '"' | '\'' | '`'));
+    if value.len() == literal.len() {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_config_key(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 200
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | ':' | '/'))
+}
+
+/// `DATABASE_URL`, `PORT`, but not `i`, `Name` or `1`
+fn is_uppercase_name(name: &str) -> bool {
+    name.len() > 1
+        && is_env_name(name)
+        && name.chars().any(|c| c.is_ascii_uppercase())
+        && !name.chars().any(|c| c.is_ascii_lowercase())
+}
+
+fn text<'s>(node: &Node, source: &'s str) -> &'s str {
+    &source[node.start_byte()..node.end_byte()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(language: Language, source: &str) -> Vec<(String, &'static str, &'static str)> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        ConfigRefExtractor::extract(&tree, source, language)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.name, r.source.as_str(), r.access.as_str()))
+            .collect()
+    }
+
+    fn refs(
+        expected: &[(&str, &'static str, &'static str)],
+    ) -> Vec<(String, &'static str, &'static str)> {
+        expected
+            .iter()
+            .map(|(name, source, access)| (name.to_string(), *source, *access))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_refs() {
+        let source = r#"
+fn main() {
+    let url = std::env::var("DATABASE_URL").unwrap();
+    let home = env::var_os("HOME");
+    let version = env!("CARGO_PKG_VERSION");
+    std::env::set_var("RUST_LOG", "debug");
+    let port: u16 = settings.get::<u16>("server.port")?;
+    let other = map.get("not_config");
+}
+"#;
+        assert_eq!(
+            extract(Language::Rust, source),
+            refs(&[
+                ("DATABASE_URL", "env", "read"),
+                ("HOME", "env", "read"),
+                ("CARGO_PKG_VERSION", "env", "read"),
+                ("RUST_LOG", "env", "write"),
+                ("server.port", "config", "read"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_js_refs() {
+        let source = r#"
+const port = process.env.PORT || 3000;
+const key = process.env["API_KEY"];
+const { HOST, DB_USER: user } = process.env;
+process.env.NODE_ENV = "production";
+delete process.env.DEBUG;
+const mode = import.meta.env.MODE;
+const ttl = config.get("cache.ttl");
+"#;
+        assert_eq!(
+            extract(Language::JavaScript, source),
+            refs(&[
+                ("PORT", "env", "read"),
+                ("API_KEY", "env", "read"),
+                ("HOST", "env", "read"),
+                ("DB_USER", "env", "read"),
+                ("NODE_ENV", "env", "write"),
+                ("DEBUG", "env", "write"),
+                ("MODE", "env", "read"),
+                ("cache.ttl", "config", "read"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_python_refs() {
+        let source = r#"
+import os
+url = os.environ["DATABASE_URL"]
+debug = os.environ.get("DEBUG", "0")
+level = os.getenv("LOG_LEVEL")
+os.environ["TZ"] = "UTC"
+del os.environ["TMP"]
+secret = app.config["SECRET_KEY"]
+name = f"{os.getenv('USER')}"
+"#;
+        assert_eq!(
+            extract(Language::Python, source),
+            refs(&[
+                ("DATABASE_URL", "env", "read"),
+                ("DEBUG", "env", "read"),
+                ("LOG_LEVEL", "env", "read"),
+                ("TZ", "env", "write"),
+                ("TMP", "env", "write"),
+                ("SECRET_KEY", "config", "read"),
+                ("USER", "env", "read"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_go_and_java_refs() {
+        let go = r#"
+package main
+func main() {
+	port := os.Getenv("PORT")
+	os.Setenv("GIN_MODE", "release")
+	timeout := viper.GetInt("http.timeout")
+}
+"#;
+        assert_eq!(
+            extract(Language::Go, go),
+            refs(&[
+                ("PORT", "env", "read"),
+                ("GIN_MODE", "env", "write"),
+                ("http.timeout", "config", "read"),
+            ])
+        );
+
+        let java = r#"
+class App {
+    @Value("${db.url:localhost}")
+    private String url;
+    void run() {
+        String home = System.getenv("JAVA_HOME");
+        String tmp = System.getProperty("java.io.tmpdir");
+        int size = env.getProperty("pool.size", Integer.class);
+        String name = environment.getProperty("app.name");
+    }
+}
+"#;
+        assert_eq!(
+            extract(Language::Java, java),
+            refs(&[
+                ("db.url", "config", "read"),
+                ("JAVA_HOME", "env", "read"),
+                ("java.io.tmpdir", "config", "read"),
+                ("app.name", "config", "read"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_other_languages() {
+        assert_eq!(
+            extract(
+                Language::CSharp,
+                "class P { void M() { var p = Environment.GetEnvironmentVariable(\"PATH\"); var l = Configuration[\"Logging\"]; } }"
+            ),
+            refs(&[("PATH", "env", "read"), ("Logging", "config", "read")])
+        );
+        assert_eq!(
+            extract(
+                Language::C,
+                "int main() { char *home = getenv(\"HOME\"); setenv(\"LANG\", \"C\", 1); }"
+            ),
+            refs(&[("HOME", "env", "read"), ("LANG", "env", "write")])
+        );
+        assert_eq!(
+            extract(
+                Language::Ruby,
+                "key = ENV[\"SECRET_KEY_BASE\"]\nENV[\"RAILS_ENV\"] = \"test\"\nport = ENV.fetch(\"PORT\", 3000)\n"
+            ),
+            refs(&[
+                ("SECRET_KEY_BASE", "env", "read"),
+                ("RAILS_ENV", "env", "write"),
+                ("PORT", "env", "read"),
+            ])
+        );
+        assert_eq!(
+            extract(
+                Language::Php,
+                "<?php $db = getenv('DB_HOST'); $key = env('APP_KEY'); $tz = $_ENV['TZ']; $name = config('app.name');"
+            ),
+            refs(&[
+                ("DB_HOST", "env", "read"),
+                ("APP_KEY", "env", "read"),
+                ("TZ", "env", "read"),
+                ("app.name", "config", "read"),
+            ])
+        );
+        assert_eq!(
+            extract(
+                Language::Bash,
+                "export APP_ENV=prod\necho \"$HOME ${PORT:-8080} $name\"\n"
+            ),
+            refs(&[
+                ("APP_ENV", "env", "write"),
+                ("HOME", "env", "read"),
+                ("PORT", "env", "read"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_kind_round_trip() {
+        for access in [ConfigAccess::Read, ConfigAccess::Write] {
+            assert_eq!(ConfigAccess::parse(access.as_str()), Some(access));
+        }
+        for source in [ConfigSource::Env, ConfigSource::Config] {
+            assert_eq!(ConfigSource::parse(source.as_str()), Some(source));
+        }
+        assert_eq!(ConfigSource::parse("other"), None);
+    }
+}
@@ -0,0 +1,1525 @@
+use crate::language::Language;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+/// HTTP methods registered by route attributes, decorators and router calls
+const HTTP_METHODS: &[&str] = &["get", "post", "put", "delete", "patch", "head", "options"];
+
+/// File names of the roots of libraries, whose public items are their API
+const LIBRARY_ROOTS: &[&str] = &[
+    "lib.rs",
+    "index.js",
+    "index.mjs",
+    "index.cjs",
+    "index.jsx",
+    "index.ts",
+    "index.tsx",
+    "__init__.py",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrypointKind {
+    /// `fn main`, `func main` in package main, `if __name__ == "__main__"`
+    Main,
+    /// HTTP route registration: `#[get("/users")]`, `app.get("/users", h)`,
+    /// `@app.route("/users")`, `@GetMapping("/users")`
+    Route,
+    /// CLI subcommand: clap `#[derive(Subcommand)]` variant, click command,
+    /// argparse subparser, cobra command, commander command
+    Command,
+    /// Public item of a library root (`lib.rs`, `index.ts`, `__init__.py`)
+    Export,
+}
+
+impl EntrypointKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntrypointKind::Main => "main",
+            EntrypointKind::Route => "route",
+            EntrypointKind::Command => "command",
+            EntrypointKind::Export => "export",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "main" => Some(EntrypointKind::Main),
+            "route" => Some(EntrypointKind::Route),
+            "command" => Some(EntrypointKind::Command),
+            "export" => Some(EntrypointKind::Export),
+            _ => None,
+        }
+    }
+}
+
+/// A place where a program is entered: its main function, an HTTP route,
+/// a CLI subcommand or an exported library item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entrypoint {
+    pub kind: EntrypointKind,
+    /// `main`, the route (`GET /users`), the command (`serve`) or the
+    /// exported name
+    pub name: String,
+    /// Function handling the route or command, or called by the main block,
+    /// when it is named
+    pub handler: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+pub struct EntrypointExtractor;
+
+impl EntrypointExtractor {
+    /// Extract the entry points of a file. Exports are only extracted from
+    /// library roots (see [`EntrypointExtractor::is_library_root`]), as
+    /// every module of a library exports items to the others.
+    pub fn extract(
+        tree: &Tree,
+        source: &str,
+        language: Language,
+        path: &str,
+    ) -> Result<Vec<Entrypoint>> {
+        let mut entrypoints = Vec::new();
+        let root = tree.root_node();
+        Self::extract_recursive(&root, source, language, &mut entrypoints);
+        if Self::is_library_root(path) {
+            Self::extract_exports(&root, source, language, &mut entrypoints);
+        }
+        Ok(entrypoints)
+    }
+
+    /// Whether `path` is the root module of a library: `lib.rs`,
+    /// `index.js`/`index.ts` or `__init__.py`
+    pub fn is_library_root(path: &str) -> bool {
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        LIBRARY_ROOTS.contains(&name)
+    }
+
+    fn extract_recursive(
+        node: &Node,
+        source: &str,
+        language: Language,
+        entrypoints: &mut Vec<Entrypoint>,
+    ) {
+        match language {
+            Language::Rust => Self::extract_rust(node, source, entrypoints),
+            Language::TypeScript | Language::JavaScript => {
+                Self::extract_js(node, source, entrypoints)
+            }
+            Language::Python => Self::extract_python(node, source, entrypoints),
+            Language::Go => Self::extract_go(node, source, entrypoints),
+            Language::Java => Self::extract_java(node, source, entrypoints),
+            Language::CSharp => Self::extract_csharp(node, source, entrypoints),
+            Language::C | Language::Cpp => Self::extract_c(node, source, entrypoints),
+            Language::Kotlin => Self::extract_kotlin(node, source, entrypoints),
+            Language::Php => Self::extract_php(node, source, entrypoints),
+            _ => return,
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::extract_recursive(&child, source, language, entrypoints);
+        }
+    }
+
+    fn extract_rust(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        match node.kind() {
+            "function_item" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = Self::text(&name, source);
+                if name == "main" && node.parent().is_some_and(|p| p.kind() == "source_file") {
+                    entrypoints.push(Self::entrypoint(node, EntrypointKind::Main, name, None));
+                    return;
+                }
+
+                // #[get("/users")], #[actix_web::post("/users")]
+                for attribute in Self::rust_attributes(node) {
+                    let Some(method) = Self::rust_attribute_name(&attribute, source) else {
+                        continue;
+                    };
+                    if method != "route" && !HTTP_METHODS.contains(&method.as_str()) {
+                        continue;
+                    }
+                    let Some(path) = Self::rust_attribute_string(&attribute, source) else {
+                        continue;
+                    };
+                    let route = if method == "route" {
+                        path
+                    } else {
+                        Self::route(&method, &path)
+                    };
+                    entrypoints.push(Self::entrypoint(
+                        node,
+                        EntrypointKind::Route,
+                        route,
+                        Some(name.clone()),
+                    ));
+                }
+            }
+            // #[derive(Subcommand)] enum Commands { Serve, IndexAll }
+            "enum_item" => {
+                let is_subcommand = Self::rust_attributes(node).iter().any(|attribute| {
+                    Self::rust_attribute_name(attribute, source).as_deref() == Some("derive")
+                        && Self::text(attribute, source)
+                            .split(|c: char| !c.is_alphanumeric())
+                            .any(|word| word == "Subcommand")
+                });
+                let Some(body) = node.child_by_field_name("body").filter(|_| is_subcommand) else {
+                    return;
+                };
+                let mut cursor = body.walk();
+                for variant in body.named_children(&mut cursor) {
+                    if variant.kind() != "enum_variant" {
+                        continue;
+                    }
+                    if let Some(name) = variant.child_by_field_name("name") {
+                        entrypoints.push(Self::entrypoint(
+                            &variant,
+                            EntrypointKind::Command,
+                            kebab_case(&Self::text(&name, source)),
+                            None,
+                        ));
+                    }
+                }
+            }
+            // Router::new().route("/users", get(list).post(create))
+            "call_expression" => {
+                let (Some(function), Some(arguments)) = (
+                    node.child_by_field_name("function"),
+                    node.child_by_field_name("arguments"),
+                ) else {
+                    return;
+                };
+                if function.kind() != "field_expression"
+                    || function
+                        .child_by_field_name("field")
+                        .is_none_or(|f| Self::text(&f, source) != "route")
+                {
+                    return;
+                }
+                let Some(path) =
+                    Self::first_string_arg(&arguments, source).filter(|path| path.starts_with('/'))
+                else {
+                    return;
+                };
+
+                let mut handlers = Vec::new();
+                if let Some(method_router) = arguments.named_child(1) {
+                    Self::rust_method_handlers(&method_router, source, &mut handlers);
+                }
+                // Located at the arguments, as the call spans the whole chain
+                // of routes before it
+                if handlers.is_empty() {
+                    entrypoints.push(Self::entrypoint(
+                        &arguments,
+                        EntrypointKind::Route,
+                        path.clone(),
+                        None,
+                    ));
+                }
+                // Chained methods are nested the other way round
+                for (method, handler) in handlers.into_iter().rev() {
+                    entrypoints.push(Self::entrypoint(
+                        &arguments,
+                        EntrypointKind::Route,
+                        Self::route(&method, &path),
+                        handler,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Methods and handlers of an axum method router: `get(list).post(create)`
+    fn rust_method_handlers(
+        node: &Node,
+        source: &str,
+        handlers: &mut Vec<(String, Option<String>)>,
+    ) {
+        if node.kind() != "call_expression" {
+            return;
+        }
+        let (Some(function), Some(arguments)) = (
+            node.child_by_field_name("function"),
+            node.child_by_field_name("arguments"),
+        ) else {
+            return;
+        };
+        let (method, inner) = match function.kind() {
+            "identifier" => (Self::text(&function, source), None),
+            "field_expression" => {
+                let Some(field) = function.child_by_field_name("field") else {
+                    return;
+                };
+                (
+                    Self::text(&field, source),
+                    function.child_by_field_name("value"),
+                )
+            }
+            _ => return,
+        };
+        if !HTTP_METHODS.contains(&method.as_str()) {
+            return;
+        }
+        let handler = arguments
+            .named_child(0)
+            .filter(|h| matches!(h.kind(), "identifier" | "scoped_identifier"))
+            .map(|h| Self::text(&h, source));
+        handlers.push((method, handler));
+        if let Some(inner) = inner {
+            Self::rust_method_handlers(&inner, source, handlers);
+        }
+    }
+
+    /// Attributes of a Rust item, which precede it as siblings
+    fn rust_attributes<'t>(node: &Node<'t>) -> Vec<Node<'t>> {
+        let mut attributes = Vec::new();
+        let mut current = node.prev_named_sibling();
+        while let Some(sibling) = current {
+            match sibling.kind() {
+                "attribute_item" => {
+                    if let Some(attribute) = sibling.named_child(0) {
+                        attributes.push(attribute);
+                    }
+                }
+                "line_comment" | "block_comment" => {}
+                _ => break,
+            }
+            current = sibling.prev_named_sibling();
+        }
+        attributes
+    }
+
+    /// Last segment of the path of an attribute: `post` for `#[actix_web::post]`
+    fn rust_attribute_name(attribute: &Node, source: &str) -> Option<String> {
+        let path = attribute.named_child(0)?;
+        match path.kind() {
+            "identifier" => Some(Self::text(&path, source)),
+            "scoped_identifier" => path
+                .child_by_field_name("name")
+                .map(|n| Self::text(&n, source)),
+            _ => None,
+        }
+    }
+
+    /// First string literal among the arguments of an attribute
+    fn rust_attribute_string(attribute: &Node, source: &str) -> Option<String> {
+        let arguments = attribute.child_by_field_name("arguments")?;
+        let mut cursor = arguments.walk();
+        arguments
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "string_literal")
+            .map(|s| Self::string_value(&s, source))
+    }
+
+    fn extract_js(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        match node.kind() {
+            "call_expression" => {
+                let (Some(function), Some(arguments)) = (
+                    node.child_by_field_name("function"),
+                    node.child_by_field_name("arguments"),
+                ) else {
+                    return;
+                };
+                if function.kind() != "member_expression" {
+                    return;
+                }
+                let Some(property) = function.child_by_field_name("property") else {
+                    return;
+                };
+                let property = Self::text(&property, source);
+                let Some(first) = Self::first_string_arg(&arguments, source) else {
+                    return;
+                };
+
+                // app.get("/users", listUsers), router.post("/users", ...)
+                if (HTTP_METHODS.contains(&property.as_str()) || property == "all")
+                    && first.starts_with('/')
+                    && arguments.named_child_count() >= 2
+                {
+                    let handler = arguments
+                        .named_child(arguments.named_child_count() - 1)
+                        .filter(|h| matches!(h.kind(), "identifier" | "member_expression"))
+                        .map(|h| Self::text(&h, source));
+                    entrypoints.push(Self::entrypoint(
+                        node,
+                        EntrypointKind::Route,
+                        Self::route(&property, &first),
+                        handler,
+                    ));
+                }
+                // program.command("serve <port>").action(serve)
+                else if property == "command"
+                    && let Some(name) = first.split_whitespace().next()
+                {
+                    entrypoints.push(Self::entrypoint(
+                        node,
+                        EntrypointKind::Command,
+                        name.to_string(),
+                        Self::js_command_action(node, source),
+                    ));
+                }
+            }
+            // NestJS: @Get(":id") find() {}
+            "decorator" => {
+                let Some(call) = node
+                    .named_child(0)
+                    .filter(|c| c.kind() == "call_expression")
+                else {
+                    return;
+                };
+                let (Some(function), Some(arguments)) = (
+                    call.child_by_field_name("function"),
+                    call.child_by_field_name("arguments"),
+                ) else {
+                    return;
+                };
+                let method = Self::text(&function, source).to_lowercase();
+                if !HTTP_METHODS.contains(&method.as_str()) && method != "all" {
+                    return;
+                }
+                let path = Self::first_string_arg(&arguments, source).unwrap_or_default();
+
+                let mut next = node.next_named_sibling();
+                while let Some(sibling) = next.filter(|s| s.kind() == "decorator") {
+                    next = sibling.next_named_sibling();
+                }
+                let Some(method_definition) = next.filter(|m| m.kind() == "method_definition")
+                else {
+                    return;
+                };
+                let prefix = Self::nest_controller_prefix(node, source).unwrap_or_default();
+                entrypoints.push(Self::entrypoint(
+                    &method_definition,
+                    EntrypointKind::Route,
+                    Self::route(&method, &join_paths(&prefix, &path)),
+                    method_definition
+                        .child_by_field_name("name")
+                        .map(|n| Self::text(&n, source)),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Handler given to `.action()` on a commander command
+    fn js_command_action(call: &Node, source: &str) -> Option<String> {
+        let member = call.parent().filter(|p| p.kind() == "member_expression")?;
+        let property = member.child_by_field_name("property")?;
+        if Self::text(&property, source) != "action" {
+            return None;
+        }
+        let action_call = member.parent().filter(|p| p.kind() == "call_expression")?;
+        action_call
+            .child_by_field_name("arguments")?
+            .named_child(0)
+            .filter(|h| matches!(h.kind(), "identifier" | "member_expression"))
+            .map(|h| Self::text(&h, source))
+    }
+
+    /// Path of the `@Controller("users")` decorator of the class enclosing a
+    /// NestJS route
+    fn nest_controller_prefix(node: &Node, source: &str) -> Option<String> {
+        let mut current = node.parent();
+        while let Some(parent) = current {
+            if matches!(parent.kind(), "class_declaration" | "class") {
+                break;
+            }
+            current = parent.parent();
+        }
+        let class = current?;
+
+        // The decorators of an exported class belong to the export statement
+        let mut holders = vec![class];
+        holders.extend(class.parent().filter(|p| p.kind() == "export_statement"));
+        for holder in holders {
+            let mut cursor = holder.walk();
+            for decorator in holder.children_by_field_name("decorator", &mut cursor) {
+                let Some(call) = decorator
+                    .named_child(0)
+                    .filter(|c| c.kind() == "call_expression")
+                else {
+                    continue;
+                };
+                if call
+                    .child_by_field_name("function")
+                    .is_some_and(|f| Self::text(&f, source) == "Controller")
+                {
+                    return call
+                        .child_by_field_name("arguments")
+                        .and_then(|a| Self::first_string_arg(&a, source));
+                }
+            }
+        }
+        None
+    }
+
+    fn extract_python(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        match node.kind() {
+            "decorated_definition" => {
+                let function = node
+                    .child_by_field_name("definition")
+                    .filter(|d| d.kind() == "function_definition")
+                    .and_then(|d| d.child_by_field_name("name"))
+                    .map(|n| Self::text(&n, source));
+
+                let mut cursor = node.walk();
+                for decorator in node.named_children(&mut cursor) {
+                    if decorator.kind() != "decorator" {
+                        continue;
+                    }
+                    // @click.command is used with or without a call
+                    let Some(expression) = decorator.named_child(0) else {
+                        continue;
+                    };
+                    let (callee, arguments) = match expression.kind() {
+                        "call" => (
+                            expression.child_by_field_name("function"),
+                            expression.child_by_field_name("arguments"),
+                        ),
+                        _ => (Some(expression), None),
+                    };
+                    let Some(attribute) = callee
+                        .filter(|c| c.kind() == "attribute")
+                        .and_then(|c| c.child_by_field_name("attribute"))
+                    else {
+                        continue;
+                    };
+                    let attribute = Self::text(&attribute, source);
+                    let first = arguments.and_then(|a| Self::first_string_arg(&a, source));
+
+                    match attribute.as_str() {
+                        // @app.get("/users"), @router.post("/users")
+                        method if HTTP_METHODS.contains(&method) => {
+                            if let Some(path) = first.filter(|p| p.starts_with('/')) {
+                                entrypoints.push(Self::entrypoint(
+                                    node,
+                                    EntrypointKind::Route,
+                                    Self::route(method, &path),
+                                    function.clone(),
+                                ));
+                            }
+                        }
+                        // @app.route("/users", methods=["GET", "POST"])
+                        "route" | "api_route" | "websocket" => {
+                            let Some(path) = first.filter(|p| p.starts_with('/')) else {
+                                continue;
+                            };
+                            let methods = arguments
+                                .map(|a| Self::python_route_methods(&a, source))
+                                .unwrap_or_default();
+                            if methods.is_empty() {
+                                entrypoints.push(Self::entrypoint(
+                                    node,
+                                    EntrypointKind::Route,
+                                    path.clone(),
+                                    function.clone(),
+                                ));
+                            }
+                            for method in methods {
+                                entrypoints.push(Self::entrypoint(
+                                    node,
+                                    EntrypointKind::Route,
+                                    Self::route(&method, &path),
+                                    function.clone(),
+                                ));
+                            }
+                        }
+                        // @click.command(), @cli.command("run"), @app.command()
+                        "command" | "group" => {
+                            // click and typer name commands after their function
+                            let Some(name) =
+                                first.or_else(|| function.as_ref().map(|f| f.replace('_', "-")))
+                            else {
+                                continue;
+                            };
+                            entrypoints.push(Self::entrypoint(
+                                node,
+                                EntrypointKind::Command,
+                                name,
+                                function.clone(),
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // subparsers.add_parser("index")
+            "call" => {
+                let (Some(function), Some(arguments)) = (
+                    node.child_by_field_name("function"),
+                    node.child_by_field_name("arguments"),
+                ) else {
+                    return;
+                };
+                if function
+                    .child_by_field_name("attribute")
+                    .is_some_and(|a| Self::text(&a, source) == "add_parser")
+                    && let Some(name) = Self::first_string_arg(&arguments, source)
+                {
+                    entrypoints.push(Self::entrypoint(node, EntrypointKind::Command, name, None));
+                }
+            }
+            // if __name__ == "__main__": main()
+            "if_statement" => {
+                let Some(condition) = node.child_by_field_name("condition") else {
+                    return;
+                };
+                if condition.kind() != "comparison_operator" {
+                    return;
+                }
+                let mut cursor = condition.walk();
+                let operands: Vec<String> = condition
+                    .named_children(&mut cursor)
+                    .map(|o| match o.kind() {
+                        "string" => Self::string_value(&o, source),
+                        _ => Self::text(&o, source),
+                    })
+                    .collect();
+                if !(operands.iter().any(|o| o == "__name__")
+                    && operands.iter().any(|o| o == "__main__"))
+                {
+                    return;
+                }
+                let handler = node
+                    .child_by_field_name("consequence")
+                    .and_then(|block| Self::first_python_call(&block, source));
+                entrypoints.push(Self::entrypoint(
+                    node,
+                    EntrypointKind::Main,
+                    "__main__".to_string(),
+                    handler,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Methods of `methods=["GET", "POST"]`, uppercased
+    fn python_route_methods(arguments: &Node, source: &str) -> Vec<String> {
+        let mut cursor = arguments.walk();
+        let Some(list) = arguments
+            .named_children(&mut cursor)
+            .filter(|a| a.kind() == "keyword_argument")
+            .find(|a| {
+                a.child_by_field_name("name")
+                    .is_some_and(|n| Self::text(&n, source) == "methods")
+            })
+            .and_then(|a| a.child_by_field_name("value"))
+        else {
+            return Vec::new();
+        };
+        let mut cursor = list.walk();
+        list.named_children(&mut cursor)
+            .filter(|m| m.kind() == "string")
+            .map(|m| Self::string_value(&m, source).to_uppercase())
+            .collect()
+    }
+
+    /// Name of the first function called by a statement of a block
+    fn first_python_call(block: &Node, source: &str) -> Option<String> {
+        let mut cursor = block.walk();
+        block.named_children(&mut cursor).find_map(|statement| {
+            let mut expression = statement.named_child(0)?;
+            // sys.exit(main())
+            while expression.kind() == "call" {
+                let function = expression.child_by_field_name("function")?;
+                let text = Self::text(&function, source);
+                if !matches!(text.as_str(), "sys.exit" | "exit" | "raise SystemExit") {
+                    return Some(text);
+                }
+                expression = expression
+                    .child_by_field_name("arguments")?
+                    .named_child(0)?;
+            }
+            None
+        })
+    }
+
+    fn extract_go(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        match node.kind() {
+            // func main() in package main
+            "function_declaration"
+                if node
+                    .child_by_field_name("name")
+                    .is_some_and(|n| Self::text(&n, source) == "main")
+                    && Self::go_package(node, source).as_deref() == Some("main") =>
+            {
+                entrypoints.push(Self::entrypoint(
+                    node,
+                    EntrypointKind::Main,
+                    "main".to_string(),
+                    None,
+                ));
+            }
+            // http.HandleFunc("/users", list), r.GET("/users", list)
+            "call_expression" => {
+                let (Some(function), Some(arguments)) = (
+                    node.child_by_field_name("function"),
+                    node.child_by_field_name("arguments"),
+                ) else {
+                    return;
+                };
+                let Some(field) = function
+                    .child_by_field_name("field")
+                    .filter(|_| function.kind() == "selector_expression")
+                else {
+                    return;
+                };
+                let field = Self::text(&field, source);
+                let is_handle = matches!(field.as_str(), "HandleFunc" | "Handle");
+                let method = field.to_lowercase();
+                if !is_handle && !HTTP_METHODS.contains(&method.as_str()) {
+                    return;
+                }
+                // Go 1.22 patterns may start with a method: "GET /users"
+                let Some(path) = Self::first_string_arg(&arguments, source)
+                    .filter(|p| p.starts_with('/') || p.contains(" /"))
+                else {
+                    return;
+                };
+                let handler = arguments
+                    .named_child(1)
+                    .filter(|h| matches!(h.kind(), "identifier" | "selector_expression"))
+                    .map(|h| Self::text(&h, source));
+                let route = if is_handle {
+                    path
+                } else {
+                    Self::route(&method, &path)
+                };
+                entrypoints.push(Self::entrypoint(
+                    node,
+                    EntrypointKind::Route,
+                    route,
+                    handler,
+                ));
+            }
+            // &cobra.Command{Use: "serve [flags]", RunE: runServe}
+            "composite_literal" => {
+                let (Some(ty), Some(body)) = (
+                    node.child_by_field_name("type"),
+                    node.child_by_field_name("body"),
+                ) else {
+                    return;
+                };
+                if Self::text(&ty, source) != "cobra.Command" {
+                    return;
+                }
+                let mut name = None;
+                let mut handler = None;
+                let mut cursor = body.walk();
+                for element in body.named_children(&mut cursor) {
+                    let (Some(key), Some(value)) = (
+                        element.child_by_field_name("key"),
+                        element.child_by_field_name("value"),
+                    ) else {
+                        continue;
+                    };
+                    let Some(value) = value.named_child(0) else {
+                        continue;
+                    };
+                    match Self::text(&key, source).as_str() {
+                        "Use" => {
+                            name = Self::string_value(&value, source)
+                                .split_whitespace()
+                                .next()
+                                .map(str::to_string);
+                        }
+                        "Run" | "RunE" => {
+                            handler = matches!(value.kind(), "identifier" | "selector_expression")
+                                .then(|| Self::text(&value, source));
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(name) = name {
+                    entrypoints.push(Self::entrypoint(
+                        node,
+                        EntrypointKind::Command,
+                        name,
+                        handler,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Package of the Go file containing `node`
+    fn go_package(node: &Node, source: &str) -> Option<String> {
+        let mut root = *node;
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        let mut cursor = root.walk();
+        let clause = root
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "package_clause")?;
+        clause.named_child(0).map(|n| Self::text(&n, source))
+    }
+
+    fn extract_java(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        if node.kind() != "method_declaration" {
+            return;
+        }
+        let Some(name) = node.child_by_field_name("name") else {
+            return;
+        };
+        let name = Self::text(&name, source);
+        let modifiers = Self::java_modifiers(node);
+
+        if name == "main"
+            && modifiers.as_ref().is_some_and(|m| {
+                Self::text(m, source)
+                    .split_whitespace()
+                    .any(|w| w == "static")
+            })
+        {
+            entrypoints.push(Self::entrypoint(node, EntrypointKind::Main, name, None));
+            return;
+        }
+
+        // @GetMapping("/users"), @RequestMapping(value = "/users", method = RequestMethod.POST)
+        let Some(modifiers) = modifiers else {
+            return;
+        };
+        let prefix = Self::java_class_prefix(node, source).unwrap_or_default();
+        let mut cursor = modifiers.walk();
+        for annotation in modifiers.named_children(&mut cursor) {
+            let Some((method, path)) = Self::spring_mapping(&annotation, source) else {
+                continue;
+            };
+            let path = join_paths(&prefix, &path);
+            let route = match method {
+                Some(method) => Self::route(&method, &path),
+                None => path,
+            };
+            entrypoints.push(Self::entrypoint(
+                node,
+                EntrypointKind::Route,
+                route,
+                Some(name.clone()),
+            ));
+        }
+    }
+
+    fn java_modifiers<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .find(|c| c.kind() == "modifiers")
+    }
+
+    /// Method and path of a Spring mapping annotation, the method being
+    /// `None` for a `@RequestMapping` without one
+    fn spring_mapping(annotation: &Node, source: &str) -> Option<(Option<String>, String)> {
+        if !matches!(annotation.kind(), "annotation" | "marker_annotation") {
+            return None;
+        }
+        let name = Self::text(&annotation.child_by_field_name("name")?, source);
+        let mut method = match name.strip_suffix("Mapping")? {
+            "Request" => None,
+            method if HTTP_METHODS.contains(&method.to_lowercase().as_str()) => {
+                Some(method.to_lowercase())
+            }
+            _ => return None,
+        };
+
+        let mut path = String::new();
+        if let Some(arguments) = annotation.child_by_field_name("arguments") {
+            let mut cursor = arguments.walk();
+            for argument in arguments.named_children(&mut cursor) {
+                match argument.kind() {
+                    "string_literal" => path = Self::string_value(&argument, source),
+                    "element_value_pair" => {
+                        let (Some(key), Some(value)) = (
+                            argument.child_by_field_name("key"),
+                            argument.child_by_field_name("value"),
+                        ) else {
+                            continue;
+                        };
+                        match Self::text(&key, source).as_str() {
+                            "value" | "path" if value.kind() == "string_literal" => {
+                                path = Self::string_value(&value, source);
+                            }
+                            // RequestMethod.POST
+                            "method" => {
+                                method = Self::text(&value, source)
+                                    .rsplit('.')
+                                    .next()
+                                    .map(str::to_lowercase);
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Some((method, path))
+    }
+
+    /// Path of the `@RequestMapping` of the class declaring a method
+    fn java_class_prefix(node: &Node, source: &str) -> Option<String> {
+        let class = node
+            .parent()
+            .filter(|p| p.kind() == "class_body")?
+            .parent()?;
+        let modifiers = Self::java_modifiers(&class)?;
+        let mut cursor = modifiers.walk();
+        modifiers
+            .named_children(&mut cursor)
+            .find_map(|annotation| Self::spring_mapping(&annotation, source))
+            .map(|(_, path)| path)
+    }
+
+    fn extract_csharp(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        if node.kind() != "method_declaration" {
+            return;
+        }
+        let Some(name) = node.child_by_field_name("name") else {
+            return;
+        };
+        let name = Self::text(&name, source);
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        if name == "Main"
+            && children
+                .iter()
+                .any(|c| c.kind() == "modifier" && Self::text(c, source) == "static")
+        {
+            entrypoints.push(Self::entrypoint(node, EntrypointKind::Main, name, None));
+            return;
+        }
+
+        // [HttpGet("/users")]
+        for list in children.iter().filter(|c| c.kind() == "attribute_list") {
+            let mut cursor = list.walk();
+            for attribute in list.named_children(&mut cursor) {
+                let Some(method) = attribute
+                    .child_by_field_name("name")
+                    .map(|n| Self::text(&n, source))
+                    .and_then(|n| n.strip_prefix("Http").map(str::to_lowercase))
+                    .filter(|m| HTTP_METHODS.contains(&m.as_str()))
+                else {
+                    continue;
+                };
+                let mut inner = attribute.walk();
+                let path = attribute
+                    .named_children(&mut inner)
+                    .find(|c| c.kind() == "attribute_argument_list")
+                    .and_then(|arguments| arguments.named_child(0))
+                    .and_then(|argument| argument.named_child(0))
+                    .filter(|value| value.kind() == "string_literal")
+                    .map(|value| Self::string_value(&value, source))
+                    .unwrap_or_default();
+                entrypoints.push(Self::entrypoint(
+                    node,
+                    EntrypointKind::Route,
+                    Self::route(&method, &path),
+                    Some(name.clone()),
+                ));
+            }
+        }
+    }
+
+    fn extract_c(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        if node.kind() != "function_definition" {
+            return;
+        }
+        if node
+            .child_by_field_name("declarator")
+            .filter(|d| d.kind() == "function_declarator")
+            .and_then(|d| d.child_by_field_name("declarator"))
+            .is_some_and(|n| Self::text(&n, source) == "main")
+        {
+            entrypoints.push(Self::entrypoint(
+                node,
+                EntrypointKind::Main,
+                "main".to_string(),
+                None,
+            ));
+        }
+    }
+
+    fn extract_kotlin(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        if node.kind() == "function_declaration"
+            && node.parent().is_some_and(|p| p.kind() == "source_file")
+            && node
+                .child_by_field_name("name")
+                .is_some_and(|n| Self::text(&n, source) == "main")
+        {
+            entrypoints.push(Self::entrypoint(
+                node,
+                EntrypointKind::Main,
+                "main".to_string(),
+                None,
+            ));
+        }
+    }
+
+    fn extract_php(node: &Node, source: &str, entrypoints: &mut Vec<Entrypoint>) {
+        // Laravel: Route::get('/users', [UserController::class, 'index'])
+        if node.kind() != "scoped_call_expression" {
+            return;
+        }
+        let (Some(scope), Some(name), Some(arguments)) = (
+            node.child_by_field_name("scope"),
+            node.child_by_field_name("name"),
+            node.child_by_field_name("arguments"),
+        ) else {
+            return;
+        };
+        let method = Self::text(&name, source).to_lowercase();
+        if Self::text(&scope, source) != "Route" || !HTTP_METHODS.contains(&method.as_str()) {
+            return;
+        }
+        if let Some(path) = Self::first_string_arg(&arguments, source) {
+            entrypoints.push(Self::entrypoint(
+                node,
+                EntrypointKind::Route,
+                Self::route(&method, &join_paths("", &path)),
+                None,
+            ));
+        }
+    }
+
+    /// Public top-level items of a library root
+    fn extract_exports(
+        root: &Node,
+        source: &str,
+        language: Language,
+        entrypoints: &mut Vec<Entrypoint>,
+    ) {
+        let mut exports = Vec::new();
+        let mut cursor = root.walk();
+        for item in root.named_children(&mut cursor) {
+            match language {
+                Language::Rust => Self::rust_exports(&item, source, &mut exports),
+                Language::TypeScript | Language::JavaScript => {
+                    Self::js_exports(&item, source, &mut exports)
+                }
+                Language::Python => Self::python_exports(&item, source, &mut exports),
+                _ => return,
+            }
+        }
+
+        // __all__ lists the API of a Python package when it is defined
+        if language == Language::Python && exports.iter().any(|(all, _, _)| *all) {
+            exports.retain(|(all, _, _)| *all);
+        }
+        for (_, node, name) in exports {
+            entrypoints.push(Self::entrypoint(&node, EntrypointKind::Export, name, None));
+        }
+    }
+
+    fn rust_exports<'t>(
+        item: &Node<'t>,
+        source: &str,
+        exports: &mut Vec<(bool, Node<'t>, String)>,
+    ) {
+        let mut cursor = item.walk();
+        let is_pub = item
+            .named_children(&mut cursor)
+            .any(|c| c.kind() == "visibility_modifier" && Self::text(&c, source) == "pub");
+        if !is_pub {
+            return;
+        }
+        let name = match item.kind() {
+            // pub use store::{IndexStore, FileIndexData};
+            "use_declaration" => item
+                .child_by_field_name("argument")
+                .map(|a| Self::text(&a, source).split_whitespace().collect()),
+            "function_item" | "struct_item" | "enum_item" | "union_item" | "trait_item"
+            | "type_item" | "const_item" | "static_item" | "mod_item" => item
+                .child_by_field_name("name")
+                .map(|n| Self::text(&n, source)),
+            _ => None,
+        };
+        if let Some(name) = name {
+            exports.push((false, *item, name));
+        }
+    }
+
+    fn js_exports<'t>(item: &Node<'t>, source: &str, exports: &mut Vec<(bool, Node<'t>, String)>) {
+        if item.kind() != "export_statement" {
+            return;
+        }
+        if let Some(declaration) = item.child_by_field_name("declaration") {
+            match declaration.kind() {
+                "lexical_declaration" | "variable_declaration" => {
+                    let mut cursor = declaration.walk();
+                    for declarator in declaration.named_children(&mut cursor) {
+                        if let Some(name) = declarator
+                            .child_by_field_name("name")
+                            .filter(|n| n.kind() == "identifier")
+                        {
+                            exports.push((false, *item, Self::text(&name, source)));
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(name) = declaration.child_by_field_name("name") {
+                        exports.push((false, *item, Self::text(&name, source)));
+                    }
+                }
+            }
+            return;
+        }
+
+        let mut cursor = item.walk();
+        let clause = item
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "export_clause");
+        match (clause, item.child_by_field_name("source")) {
+            // export { a, b as c } [from "./x"]
+            (Some(clause), _) => {
+                let mut cursor = clause.walk();
+                for specifier in clause.named_children(&mut cursor) {
+                    if let Some(name) = specifier
+                        .child_by_field_name("alias")
+                        .or_else(|| specifier.child_by_field_name("name"))
+                    {
+                        exports.push((false, *item, Self::text(&name, source)));
+                    }
+                }
+            }
+            // export * from "./x"
+            (None, Some(module)) => {
+                exports.push((
+                    false,
+                    *item,
+                    format!("* from {}", Self::string_value(&module, source)),
+                ));
+            }
+            // export default App
+            (None, None) if item.child_by_field_name("value").is_some() => {
+                exports.push((false, *item, "default".to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    /// Exports of `__init__.py`: the names of `__all__` (flagged), or else
+    /// its public definitions and the names it imports from its modules
+    fn python_exports<'t>(
+        item: &Node<'t>,
+        source: &str,
+        exports: &mut Vec<(bool, Node<'t>, String)>,
+    ) {
+        match item.kind() {
+            "expression_statement" => {
+                let Some(assignment) = item.named_child(0).filter(|a| a.kind() == "assignment")
+                else {
+                    return;
+                };
+                let (Some(left), Some(right)) = (
+                    assignment.child_by_field_name("left"),
+                    assignment.child_by_field_name("right"),
+                ) else {
+                    return;
+                };
+                if Self::text(&left, source) != "__all__"
+                    || !matches!(right.kind(), "list" | "tuple")
+                {
+                    return;
+                }
+                let mut cursor = right.walk();
+                for name in right.named_children(&mut cursor) {
+                    if name.kind() == "string" {
+                        exports.push((true, *item, Self::string_value(&name, source)));
+                    }
+                }
+            }
+            "function_definition" | "class_definition" | "decorated_definition" => {
+                let definition = if item.kind() == "decorated_definition" {
+                    item.child_by_field_name("definition")
+                } else {
+                    Some(*item)
+                };
+                if let Some(name) = definition
+                    .and_then(|d| d.child_by_field_name("name"))
+                    .map(|n| Self::text(&n, source))
+                    .filter(|n| !n.starts_with('_'))
+                {
+                    exports.push((false, *item, name));
+                }
+            }
+            // from .store import IndexStore, FileData as Data
+            "import_from_statement" => {
+                if item
+                    .child_by_field_name("module_name")
+                    .is_none_or(|m| m.kind() != "relative_import")
+                {
+                    return;
+                }
+                let mut cursor = item.walk();
+                for name in item.children_by_field_name("name", &mut cursor) {
+                    let name = match name.kind() {
+                        "aliased_import" => name.child_by_field_name("alias"),
+                        _ => Some(name),
+                    };
+                    if let Some(name) = name
+                        .map(|n| Self::text(&n, source))
+                        .filter(|n| !n.starts_with('_'))
+                    {
+                        exports.push((false, *item, name));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// First argument of a call, if it is a string literal
+    fn first_string_arg(arguments: &Node, source: &str) -> Option<String> {
+        let mut argument = arguments.named_child(0)?;
+        // PHP wraps each argument
+        if argument.kind() == "argument" {
+            argument = argument.named_child(0)?;
+        }
+        argument
+            .kind()
+            .contains("string")
+            .then(|| Self::string_value(&argument, source))
+    }
+
+    /// `GET /users`
+    fn route(method: &str, path: &str) -> String {
+        format!("{} {}", method.to_uppercase(), path)
+    }
+
+    fn entrypoint(
+        node: &Node,
+        kind: EntrypointKind,
+        name: String,
+        handler: Option<String>,
+    ) -> Entrypoint {
+        Entrypoint {
+            kind,
+            name,
+            handler,
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+        }
+    }
+
+    /// Value of a string literal, without its quotes
+    fn string_value(node: &Node, source: &str) -> String {
+        Self::text(node, source)
+            .trim_matches(|c| matches!(c, '"' | '\'' | '`'))
+            .to_string()
+    }
+
+    fn text(node: &Node, source: &str) -> String {
+        source[node.start_byte()..node.end_byte()].to_string()
+    }
+}
+
+/// Join a controller prefix and a route path: `users` and `:id` give
+/// `/users/:id`
+fn join_paths(prefix: &str, path: &str) -> String {
+    let joined: Vec<&str> = [prefix, path]
+        .iter()
+        .map(|p| p.trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .collect();
+    format!("/{}", joined.join("/"))
+}
+
+/// Name clap derives for a subcommand variant: `IndexAll` gives `index-all`
+fn kebab_case(name: &str) -> String {
+    let mut kebab = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                kebab.push('-');
+            }
+            kebab.extend(c.to_lowercase());
+        } else {
+            kebab.push(c);
+        }
+    }
+    kebab
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(
+        language: Language,
+        path: &str,
+        source: &str,
+    ) -> Vec<(EntrypointKind, String, Option<String>)> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        EntrypointExtractor::extract(&tree, source, language, path)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.kind, e.name, e.handler))
+            .collect()
+    }
+
+    fn entry(
+        kind: EntrypointKind,
+        name: &str,
+        handler: Option<&str>,
+    ) -> (EntrypointKind, String, Option<String>) {
+        (kind, name.to_string(), handler.map(str::to_string))
+    }
+
+    #[test]
+    fn test_rust_entrypoints() {
+        let source = r#"
+#[tokio::main]
+async fn main() {}
+
+#[actix_web::get("/users")]
+/// List users
+async fn list_users() {}
+
+fn router() -> Router {
+    Router::new().route("/items", get(list_items).post(create_item))
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    Serve { port: u16 },
+    IndexAll,
+}
+
+mod nested {
+    fn main() {}
+}
+"#;
+        assert_eq!(
+            extract(Language::Rust, "src/main.rs", source),
+            vec![
+                entry(EntrypointKind::Main, "main", None),
+                entry(EntrypointKind::Route, "GET /users", Some("list_users")),
+                entry(EntrypointKind::Route, "GET /items", Some("list_items")),
+                entry(EntrypointKind::Route, "POST /items", Some("create_item")),
+                entry(EntrypointKind::Command, "serve", None),
+                entry(EntrypointKind::Command, "index-all", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_exports_of_library_roots() {
+        let source = "pub use store::{IndexStore, FileData};\npub fn open() {}\npub(crate) fn internal() {}\nfn private() {}\npub mod config;\n";
+        assert_eq!(
+            extract(Language::Rust, "crates/index/src/lib.rs", source),
+            vec![
+                entry(EntrypointKind::Export, "store::{IndexStore,FileData}", None),
+                entry(EntrypointKind::Export, "open", None),
+                entry(EntrypointKind::Export, "config", None),
+            ]
+        );
+        assert!(extract(Language::Rust, "src/store.rs", source).is_empty());
+    }
+
+    #[test]
+    fn test_python_entrypoints() {
+        let source = r#"
+@app.get("/users")
+def list_users(): pass
+
+@app.route("/items", methods=["GET", "POST"])
+def items(): pass
+
+@click.command()
+def build_all(): pass
+
+@cli.command("run")
+def run_it(): pass
+
+sub.add_parser("index")
+
+if __name__ == "__main__":
+    sys.exit(main())
+"#;
+        assert_eq!(
+            extract(Language::Python, "app.py", source),
+            vec![
+                entry(EntrypointKind::Route, "GET /users", Some("list_users")),
+                entry(EntrypointKind::Route, "GET /items", Some("items")),
+                entry(EntrypointKind::Route, "POST /items", Some("items")),
+                entry(EntrypointKind::Command, "build-all", Some("build_all")),
+                entry(EntrypointKind::Command, "run", Some("run_it")),
+                entry(EntrypointKind::Command, "index", None),
+                entry(EntrypointKind::Main, "__main__", Some("main")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_exports_prefer_all() {
+        let source = "from .store import IndexStore\ndef open(): pass\n";
+        assert_eq!(
+            extract(Language::Python, "pkg/__init__.py", source),
+            vec![
+                entry(EntrypointKind::Export, "IndexStore", None),
+                entry(EntrypointKind::Export, "open", None),
+            ]
+        );
+
+        let source = "from .store import IndexStore\n__all__ = [\"open\"]\ndef open(): pass\n";
+        assert_eq!(
+            extract(Language::Python, "pkg/__init__.py", source),
+            vec![entry(EntrypointKind::Export, "open", None)]
+        );
+    }
+
+    #[test]
+    fn test_js_entrypoints() {
+        let source = r#"
+app.get("/users", listUsers);
+router.post("/users", (req, res) => {});
+app.get("env");
+cache.get("key", fallback);
+program.command("serve <port>").action(serve);
+export function createApp() {}
+export { a, b as c };
+export * from "./routes";
+"#;
+        assert_eq!(
+            extract(Language::JavaScript, "src/index.js", source),
+            vec![
+                entry(EntrypointKind::Route, "GET /users", Some("listUsers")),
+                entry(EntrypointKind::Route, "POST /users", None),
+                entry(EntrypointKind::Command, "serve", Some("serve")),
+                entry(EntrypointKind::Export, "createApp", None),
+                entry(EntrypointKind::Export, "a", None),
+                entry(EntrypointKind::Export, "c", None),
+                entry(EntrypointKind::Export, "* from ./routes", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nest_routes() {
+        let source = "@Controller('users')\nexport class UsersController {\n  @Get(':id')\n  find() {}\n  @Post()\n  create() {}\n}\n";
+        assert_eq!(
+            extract(Language::TypeScript, "src/users.controller.ts", source),
+            vec![
+                entry(EntrypointKind::Route, "GET /users/:id", Some("find")),
+                entry(EntrypointKind::Route, "POST /users", Some("create")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_go_entrypoints() {
+        let source = r#"package main
+
+func main() {
+    http.HandleFunc("/users", listUsers)
+    r.GET("/items", h.ListItems)
+    m.Get("key")
+}
+
+var serveCmd = &cobra.Command{
+    Use:  "serve [flags]",
+    RunE: runServe,
+}
+"#;
+        assert_eq!(
+            extract(Language::Go, "main.go", source),
+            vec![
+                entry(EntrypointKind::Main, "main", None),
+                entry(EntrypointKind::Route, "/users", Some("listUsers")),
+                entry(EntrypointKind::Route, "GET /items", Some("h.ListItems")),
+                entry(EntrypointKind::Command, "serve", Some("runServe")),
+            ]
+        );
+
+        // main only starts programs in package main
+        let source = "package util\n\nfunc main() {}\n";
+        assert!(extract(Language::Go, "util.go", source).is_empty());
+    }
+
+    #[test]
+    fn test_java_entrypoints() {
+        let source = r#"
+@RestController
+@RequestMapping("/api")
+class UserController {
+    public static void main(String[] args) {}
+
+    @GetMapping("/users")
+    public List<User> list() {}
+
+    @RequestMapping(value = "/users", method = RequestMethod.POST)
+    void create() {}
+}
+"#;
+        assert_eq!(
+            extract(Language::Java, "UserController.java", source),
+            vec![
+                entry(EntrypointKind::Main, "main", None),
+                entry(EntrypointKind::Route, "GET /api/users", Some("list")),
+                entry(EntrypointKind::Route, "POST /api/users", Some("create")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_languages() {
+        assert_eq!(
+            extract(
+                Language::C,
+                "main.c",
+                "int main(int argc, char **argv) { return 0; }"
+            ),
+            vec![entry(EntrypointKind::Main, "main", None)]
+        );
+        assert_eq!(
+            extract(
+                Language::CSharp,
+                "Program.cs",
+                "class P { static void Main(string[] args) {} [HttpGet(\"users\")] public IActionResult List() {} }"
+            ),
+            vec![
+                entry(EntrypointKind::Main, "Main", None),
+                entry(EntrypointKind::Route, "GET users", Some("List")),
+            ]
+        );
+        assert_eq!(
+            extract(
+                Language::Kotlin,
+                "Main.kt",
+                "fun main(args: Array<String>) {}"
+            ),
+            vec![entry(EntrypointKind::Main, "main", None)]
+        );
+        assert_eq!(
+            extract(
+                Language::Php,
+                "routes/web.php",
+                "<?php Route::get('/users', [UserController::class, 'index']);"
+            ),
+            vec![entry(EntrypointKind::Route, "GET /users", None)]
+        );
+    }
+
+    #[test]
+    fn test_kind_round_trip() {
+        for kind in [
+            EntrypointKind::Main,
+            EntrypointKind::Route,
+            EntrypointKind::Command,
+            EntrypointKind::Export,
+        ] {
+            assert_eq!(EntrypointKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(EntrypointKind::parse("other"), None);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::language::Language;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use tree_sitter::{Node, Tree};
 
 #[derive(Debug, Clone)]
@@ -7,10 +8,40 @@ pub struct Import {
     pub path: String,
     pub name: Option<String>,
     pub kind: ImportKind,
+    /// Symbols brought into scope by the import, empty when the whole module is imported
+    pub symbols: Vec<ImportedSymbol>,
     pub start_line: usize,
     pub end_line: usize,
 }
 
+/// A symbol named by an import statement.
+///
+/// Default imports are recorded as `default` and namespace or glob imports as `*`,
+/// with the local binding in `alias`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedSymbol {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl ImportedSymbol {
+    pub fn new(name: impl Into<String>, alias: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            alias,
+        }
+    }
+}
+
+impl std::fmt::Display for ImportedSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "{} as {}", self.name, alias),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImportKind {
     /// Standard library import
@@ -74,10 +105,14 @@ impl ImportExtractor {
             Language::CSharp => Self::extract_csharp_import(node, source),
             Language::Kotlin => Self::extract_kotlin_import(node, source),
             Language::Scala => Self::extract_scala_import(node, source),
-            // Markup/config languages don't have traditional imports
-            Language::Html | Language::Json | Language::Yaml | Language::Toml => None,
+            // Markup/config languages and SQL don't have traditional imports
+            Language::Html | Language::Json | Language::Yaml | Language::Toml | Language::Sql => {
+                None
+            }
             Language::Bash => Self::extract_bash_import(node, source),
             Language::Elixir => Self::extract_elixir_import(node, source),
+            // Custom languages only declare symbols
+            Language::Custom(_) => None,
         }
     }
 
@@ -96,11 +131,13 @@ impl ImportExtractor {
         let path = Self::parse_rust_use_path(text)?;
         let kind = Self::classify_rust_import(&path);
         let name = Self::extract_rust_import_name(&path);
+        let symbols = Self::parse_rust_use_symbols(&path);
 
         Some(Import {
             path,
             name,
             kind,
+            symbols,
             start_line,
             end_line,
         })
@@ -138,6 +175,67 @@ impl ImportExtractor {
         path.rsplit("::").next().map(String::from)
     }
 
+    /// Symbols named by a use path: `a::{B, c::D as E}` yields `B` and `D as E`.
+    fn parse_rust_use_symbols(path: &str) -> Vec<ImportedSymbol> {
+        let mut symbols = Vec::new();
+        Self::collect_rust_use_symbols(path, &mut symbols);
+        symbols
+    }
+
+    fn collect_rust_use_symbols(path: &str, symbols: &mut Vec<ImportedSymbol>) {
+        let path = path.trim();
+        if path.is_empty() {
+            return;
+        }
+
+        if let (Some(open), Some(close)) = (path.find('{'), path.rfind('}')) {
+            let prefix = path[..open].trim_end_matches("::");
+            let inner = &path[open + 1..close];
+
+            // Split on top-level commas only, nested groups are handled recursively
+            let mut depth = 0usize;
+            let mut start = 0;
+            for (i, c) in inner.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth = depth.saturating_sub(1),
+                    ',' if depth == 0 => {
+                        Self::collect_rust_use_item(prefix, &inner[start..i], symbols);
+                        start = i + 1;
+                    }
+                    _ => {}
+                }
+            }
+            Self::collect_rust_use_item(prefix, &inner[start..], symbols);
+            return;
+        }
+
+        let (target, alias) = match path.split_once(" as ") {
+            Some((target, alias)) => (target.trim(), Some(alias.trim().to_string())),
+            None => (path, None),
+        };
+        if let Some(name) = target.rsplit("::").next() {
+            symbols.push(ImportedSymbol::new(name, alias));
+        }
+    }
+
+    fn collect_rust_use_item(prefix: &str, item: &str, symbols: &mut Vec<ImportedSymbol>) {
+        let item = item.trim();
+        if item.is_empty() {
+            return;
+        }
+
+        // `self` inside a group names the module the group belongs to
+        if item == "self" || item.starts_with("self as ") {
+            let alias = item.strip_prefix("self as ").map(|a| a.trim().to_string());
+            let module = prefix.rsplit("::").next().unwrap_or(prefix);
+            symbols.push(ImportedSymbol::new(module, alias));
+            return;
+        }
+
+        Self::collect_rust_use_symbols(item, symbols);
+    }
+
     fn extract_ts_import(node: &Node, source: &str) -> Option<Import> {
         if node.kind() != "import_statement" {
             return None;
@@ -162,11 +260,13 @@ impl ImportExtractor {
                 };
 
                 let name = path.split('/').next_back().map(String::from);
+                let symbols = Self::extract_ts_import_symbols(node, source);
 
                 return Some(Import {
                     path,
                     name,
                     kind,
+                    symbols,
                     start_line,
                     end_line,
                 });
@@ -176,6 +276,50 @@ impl ImportExtractor {
         None
     }
 
+    /// Symbols of an import clause: default, `* as ns` and `{ A, B as C }` forms.
+    fn extract_ts_import_symbols(node: &Node, source: &str) -> Vec<ImportedSymbol> {
+        let text = |n: Node| source[n.start_byte()..n.end_byte()].to_string();
+        let mut symbols = Vec::new();
+
+        let mut cursor = node.walk();
+        let Some(clause) = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "import_clause")
+        else {
+            return symbols;
+        };
+
+        let mut clause_cursor = clause.walk();
+        for child in clause.children(&mut clause_cursor) {
+            match child.kind() {
+                "identifier" => symbols.push(ImportedSymbol::new("default", Some(text(child)))),
+                "namespace_import" => {
+                    let mut ns_cursor = child.walk();
+                    let alias = child
+                        .children(&mut ns_cursor)
+                        .find(|c| c.kind() == "identifier")
+                        .map(text);
+                    symbols.push(ImportedSymbol::new("*", alias));
+                }
+                "named_imports" => {
+                    let mut named_cursor = child.walk();
+                    for specifier in child.children(&mut named_cursor) {
+                        if specifier.kind() != "import_specifier" {
+                            continue;
+                        }
+                        if let Some(name) = specifier.child_by_field_name("name") {
+                            let alias = specifier.child_by_field_name("alias").map(text);
+                            symbols.push(ImportedSymbol::new(text(name), alias));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        symbols
+    }
+
     fn extract_python_import(node: &Node, source: &str) -> Option<Import> {
         match node.kind() {
             "import_statement" => {
@@ -194,6 +338,7 @@ impl ImportExtractor {
                             path,
                             name,
                             kind,
+                            symbols: Vec::new(),
                             start_line,
                             end_line,
                         });
@@ -216,11 +361,13 @@ impl ImportExtractor {
                             Self::classify_python_import(&path)
                         };
                         let name = path.split('.').next_back().map(String::from);
+                        let symbols = Self::extract_python_import_symbols(node, source);
 
                         return Some(Import {
                             path,
                             name,
                             kind,
+                            symbols,
                             start_line,
                             end_line,
                         });
@@ -232,6 +379,34 @@ impl ImportExtractor {
         }
     }
 
+    /// Names listed by `from module import a, b as c` (or `*`).
+    fn extract_python_import_symbols(node: &Node, source: &str) -> Vec<ImportedSymbol> {
+        let text = |n: Node| source[n.start_byte()..n.end_byte()].to_string();
+        let mut symbols = Vec::new();
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.kind() == "wildcard_import" {
+                symbols.push(ImportedSymbol::new("*", None));
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children_by_field_name("name", &mut cursor) {
+            match child.kind() {
+                "aliased_import" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        let alias = child.child_by_field_name("alias").map(text);
+                        symbols.push(ImportedSymbol::new(text(name), alias));
+                    }
+                }
+                _ => symbols.push(ImportedSymbol::new(text(child), None)),
+            }
+        }
+
+        symbols
+    }
+
     fn classify_python_import(path: &str) -> ImportKind {
         let first_segment = path.split('.').next().unwrap_or(path);
 
@@ -296,6 +471,7 @@ impl ImportExtractor {
                     path,
                     name,
                     kind,
+                    symbols: Vec::new(),
                     start_line,
                     end_line,
                 });
@@ -331,6 +507,7 @@ impl ImportExtractor {
                     path,
                     name,
                     kind,
+                    symbols: Vec::new(),
                     start_line,
                     end_line,
                 });
@@ -361,6 +538,7 @@ impl ImportExtractor {
                         path,
                         name,
                         kind: ImportKind::Local,
+                        symbols: Vec::new(),
                         start_line,
                         end_line,
                     });
@@ -374,6 +552,7 @@ impl ImportExtractor {
                         path,
                         name,
                         kind: ImportKind::Std,
+                        symbols: Vec::new(),
                         start_line,
                         end_line,
                     });
@@ -408,6 +587,7 @@ impl ImportExtractor {
             path,
             name,
             kind,
+            symbols: Vec::new(),
             start_line,
             end_line,
         })
@@ -467,6 +647,7 @@ impl ImportExtractor {
                             path,
                             name,
                             kind,
+                            symbols: Vec::new(),
                             start_line,
                             end_line,
                         });
@@ -504,6 +685,7 @@ impl ImportExtractor {
                     path,
                     name,
                     kind,
+                    symbols: Vec::new(),
                     start_line,
                     end_line,
                 });
@@ -539,6 +721,7 @@ impl ImportExtractor {
                     path,
                     name,
                     kind,
+                    symbols: Vec::new(),
                     start_line,
                     end_line,
                 });
@@ -575,6 +758,7 @@ impl ImportExtractor {
             path,
             name,
             kind,
+            symbols: Vec::new(),
             start_line,
             end_line,
         })
@@ -609,6 +793,7 @@ impl ImportExtractor {
             path,
             name,
             kind: ImportKind::Local,
+            symbols: Vec::new(),
             start_line,
             end_line,
         })
@@ -656,6 +841,7 @@ impl ImportExtractor {
             path,
             name,
             kind,
+            symbols: Vec::new(),
             start_line,
             end_line,
         })
@@ -838,6 +1024,109 @@ use std::collections::{HashMap, HashSet};
         assert_eq!(imports.len(), 1);
         // Import with braces should have no specific name
         assert!(imports[0].name.is_none());
+        assert_eq!(
+            imports[0].symbols,
+            vec![
+                ImportedSymbol::new("HashMap", None),
+                ImportedSymbol::new("HashSet", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_import_symbols_nested_and_aliased() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = r#"
+use crate::store::{self, schema::{FileRecord, DependencyRecord as Dep}, *};
+use std::io::Result as IoResult;
+"#;
+        let tree = support.parse(Language::Rust, source).unwrap();
+        let imports = ImportExtractor::extract(&tree, source, Language::Rust).unwrap();
+
+        assert_eq!(imports.len(), 2);
+        assert_eq!(
+            imports[0].symbols,
+            vec![
+                ImportedSymbol::new("store", None),
+                ImportedSymbol::new("FileRecord", None),
+                ImportedSymbol::new("DependencyRecord", Some("Dep".to_string())),
+                ImportedSymbol::new("*", None),
+            ]
+        );
+        assert_eq!(
+            imports[1].symbols,
+            vec![ImportedSymbol::new("Result", Some("IoResult".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_typescript_import_symbols() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = r#"
+import { A, B as C } from './x';
+import React, { useState } from 'react';
+import * as path from 'path';
+import './side-effect';
+"#;
+        let tree = support.parse(Language::TypeScript, source).unwrap();
+        let imports = ImportExtractor::extract(&tree, source, Language::TypeScript).unwrap();
+
+        assert_eq!(imports.len(), 4);
+        assert_eq!(
+            imports[0].symbols,
+            vec![
+                ImportedSymbol::new("A", None),
+                ImportedSymbol::new("B", Some("C".to_string()))
+            ]
+        );
+        assert_eq!(
+            imports[1].symbols,
+            vec![
+                ImportedSymbol::new("default", Some("React".to_string())),
+                ImportedSymbol::new("useState", None)
+            ]
+        );
+        assert_eq!(
+            imports[2].symbols,
+            vec![ImportedSymbol::new("*", Some("path".to_string()))]
+        );
+        assert!(imports[3].symbols.is_empty());
+    }
+
+    #[test]
+    fn test_python_import_symbols() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = r#"
+import os
+from collections import defaultdict, OrderedDict as OD
+from .models import *
+"#;
+        let tree = support.parse(Language::Python, source).unwrap();
+        let imports = ImportExtractor::extract(&tree, source, Language::Python).unwrap();
+
+        let os = imports.iter().find(|i| i.path == "os").unwrap();
+        assert!(os.symbols.is_empty());
+
+        let collections = imports.iter().find(|i| i.path == "collections").unwrap();
+        assert_eq!(
+            collections.symbols,
+            vec![
+                ImportedSymbol::new("defaultdict", None),
+                ImportedSymbol::new("OrderedDict", Some("OD".to_string()))
+            ]
+        );
+
+        let models = imports.iter().find(|i| i.path == ".models").unwrap();
+        assert_eq!(models.symbols, vec![ImportedSymbol::new("*", None)]);
+    }
+
+    #[test]
+    fn test_imported_symbol_display() {
+        assert_eq!(ImportedSymbol::new("A", None).to_string(), "A");
+        assert_eq!(
+            ImportedSymbol::new("B", Some("C".to_string())).to_string(),
+            "B as C"
+        );
     }
 
     #[test]
@@ -0,0 +1,370 @@
+use crate::language::Language;
+use crate::occurrences::OccurrenceExtractor;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tree_sitter::{Node, Tree};
+
+/// Keywords kept per file, the most frequent first
+const MAX_KEYWORDS: usize = 32;
+
+/// Shortest and longest word kept as a keyword
+const MIN_KEYWORD_LEN: usize = 3;
+const MAX_KEYWORD_LEN: usize = 32;
+
+/// English function words, language keywords and names too generic to tell
+/// files apart
+const STOPWORDS: &[&str] = &[
+    // English
+    "about",
+    "after",
+    "all",
+    "also",
+    "and",
+    "any",
+    "are",
+    "because",
+    "been",
+    "before",
+    "being",
+    "but",
+    "can",
+    "could",
+    "does",
+    "done",
+    "each",
+    "either",
+    "etc",
+    "for",
+    "from",
+    "has",
+    "have",
+    "here",
+    "how",
+    "into",
+    "its",
+    "just",
+    "may",
+    "more",
+    "most",
+    "must",
+    "not",
+    "now",
+    "one",
+    "only",
+    "other",
+    "our",
+    "over",
+    "same",
+    "should",
+    "since",
+    "some",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "them",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "too",
+    "two",
+    "under",
+    "until",
+    "use",
+    "used",
+    "uses",
+    "using",
+    "very",
+    "was",
+    "way",
+    "were",
+    "what",
+    "when",
+    "where",
+    "whether",
+    "which",
+    "while",
+    "who",
+    "why",
+    "will",
+    "with",
+    "without",
+    "would",
+    "yet",
+    "you",
+    "your",
+    // Keywords and built-in names
+    "abstract",
+    "async",
+    "await",
+    "bool",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "crate",
+    "def",
+    "default",
+    "defp",
+    "del",
+    "elif",
+    "else",
+    "end",
+    "enum",
+    "export",
+    "extends",
+    "extern",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "fmt",
+    "func",
+    "function",
+    "impl",
+    "implements",
+    "import",
+    "int",
+    "interface",
+    "let",
+    "long",
+    "loop",
+    "match",
+    "mod",
+    "module",
+    "mut",
+    "new",
+    "nil",
+    "none",
+    "null",
+    "object",
+    "option",
+    "override",
+    "package",
+    "pass",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "raise",
+    "ref",
+    "require",
+    "result",
+    "return",
+    "self",
+    "short",
+    "static",
+    "str",
+    "string",
+    "struct",
+    "super",
+    "switch",
+    "throw",
+    "throws",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "uint",
+    "undefined",
+    "unsafe",
+    "usize",
+    "var",
+    "vec",
+    "void",
+    "where",
+    "yield",
+    // Generic programming words
+    "arg",
+    "args",
+    "data",
+    "err",
+    "error",
+    "get",
+    "item",
+    "len",
+    "list",
+    "map",
+    "name",
+    "todo",
+    "fixme",
+    "tmp",
+    "val",
+    "value",
+];
+
+/// A salient word of a file, from its identifiers and comments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keyword {
+    pub term: String,
+    pub count: usize,
+}
+
+pub struct KeywordExtractor;
+
+impl KeywordExtractor {
+    /// Count the words of a file's identifiers and comments, most frequent
+    /// first (ties by term).
+    ///
+    /// Identifiers are split into words (`parseAuthToken` gives `parse`,
+    /// `auth` and `token`), words are lowercased and plurals reduced to the
+    /// singular. Stopwords, numbers and words shorter than three letters are
+    /// skipped. Data and markup files have none.
+    pub fn extract(tree: &Tree, source: &str, language: Language) -> Result<Vec<Keyword>> {
+        if matches!(
+            language,
+            Language::Html | Language::Json | Language::Yaml | Language::Toml
+        ) {
+            return Ok(Vec::new());
+        }
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        Self::collect_recursive(&tree.root_node(), source, &mut counts);
+
+        let mut keywords: Vec<Keyword> = counts
+            .into_iter()
+            .map(|(term, count)| Keyword { term, count })
+            .collect();
+        keywords.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+        keywords.truncate(MAX_KEYWORDS);
+        Ok(keywords)
+    }
+
+    fn collect_recursive(node: &Node, source: &str, counts: &mut HashMap<String, usize>) {
+        if node.kind().contains("comment") || OccurrenceExtractor::is_identifier(node) {
+            if let Ok(text) = node.utf8_text(source.as_bytes()) {
+                for word in split_words(text) {
+                    if let Some(term) = normalize_term(&word) {
+                        *counts.entry(term).or_default() += 1;
+                    }
+                }
+            }
+            return;
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::collect_recursive(&child, source, counts);
+        }
+    }
+}
+
+/// Split text into words at non-letters and camelCase boundaries
+/// (`HTTPServer` gives `HTTP` and `Server`).
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in text.split(|c: char| !c.is_alphabetic()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let lower_to_upper = chars[i - 1].is_lowercase() && chars[i].is_uppercase();
+            let acronym_end = chars[i - 1].is_uppercase()
+                && chars[i].is_uppercase()
+                && chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if lower_to_upper || acronym_end {
+                words.push(chars[start..i].iter().collect());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect());
+        }
+    }
+    words
+}
+
+/// Lowercase a word and reduce a plural to the singular, `None` for
+/// stopwords and words of the wrong length.
+pub fn normalize_term(word: &str) -> Option<String> {
+    let mut term = word.to_lowercase();
+    let length = term.chars().count();
+    if !(MIN_KEYWORD_LEN..=MAX_KEYWORD_LEN).contains(&length) {
+        return None;
+    }
+
+    if length > 4 {
+        if let Some(stem) = term.strip_suffix("ies") {
+            term = format!("{}y", stem);
+        } else if term.ends_with('s') && !term.ends_with("ss") && !term.ends_with("us") {
+            term.pop();
+        }
+    }
+
+    if STOPWORDS.contains(&term.as_str()) {
+        return None;
+    }
+    Some(term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(language: Language, source: &str) -> Vec<(String, usize)> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        KeywordExtractor::extract(&tree, source, language)
+            .unwrap()
+            .into_iter()
+            .map(|k| (k.term, k.count))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words("parseAuthToken HTTPServer user_id"),
+            vec!["parse", "Auth", "Token", "HTTP", "Server", "user", "id"]
+        );
+    }
+
+    #[test]
+    fn test_normalize_term() {
+        assert_eq!(normalize_term("Tokens").as_deref(), Some("token"));
+        assert_eq!(normalize_term("policies").as_deref(), Some("policy"));
+        assert_eq!(normalize_term("status").as_deref(), Some("status"));
+        assert_eq!(normalize_term("class"), None);
+        assert_eq!(normalize_term("id"), None);
+    }
+
+    #[test]
+    fn test_extracts_identifier_and_comment_words() {
+        let source = r#"
+/// Verify the session token of a user
+pub fn verify_session_token(token: &SessionToken) -> bool {
+    // Expired sessions are rejected
+    let session = load_session(token);
+    session.is_valid()
+}
+"#;
+        let keywords = extract(Language::Rust, source);
+
+        assert_eq!(keywords[0], ("session".to_string(), 7));
+        assert!(keywords.contains(&("token".to_string(), 5)));
+        assert!(keywords.contains(&("expired".to_string(), 1)));
+        // Words of string literals and stopwords are not keywords
+        assert!(
+            !keywords
+                .iter()
+                .any(|(term, _)| term == "the" || term == "pub")
+        );
+    }
+
+    #[test]
+    fn test_data_files_have_no_keywords() {
+        assert!(extract(Language::Json, r#"{"session": "token"}"#).is_empty());
+    }
+}
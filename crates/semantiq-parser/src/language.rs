@@ -1,7 +1,15 @@
+use crate::plugins;
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 use std::path::Path;
 
+/// Bytes read from an extensionless file when sniffing its language
+const SNIFF_LIMIT: u64 = 64 * 1024;
+
+/// Lines searched for an editor modeline at the start and at the end of a file
+const MODELINE_LINES: usize = 5;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Language {
@@ -24,11 +32,20 @@ pub enum Language {
     Toml,
     Bash,
     Elixir,
+    Sql,
+    /// Language declared in `semantiq.toml`, see [`crate::plugins`]
+    Custom(u16),
 }
 
 impl Language {
+    /// Detect the language from a file extension. Custom languages take
+    /// precedence over the built-in ones.
     pub fn from_extension(ext: &str) -> Option<Self> {
-        match ext.to_lowercase().as_str() {
+        let ext = ext.to_lowercase();
+        if let Some(language) = plugins::find(|l| l.extensions.contains(&ext.as_str())) {
+            return Some(language);
+        }
+        match ext.as_str() {
             "rs" => Some(Language::Rust),
             "ts" | "tsx" => Some(Language::TypeScript),
             "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
@@ -48,14 +65,149 @@ impl Language {
             "toml" => Some(Language::Toml),
             "sh" | "bash" | "zsh" => Some(Language::Bash),
             "ex" | "exs" => Some(Language::Elixir),
+            "sql" | "ddl" | "pgsql" => Some(Language::Sql),
             _ => None,
         }
     }
 
+    /// Detect the language from the file extension or a well-known file name.
     pub fn from_path(path: &Path) -> Option<Self> {
         path.extension()
             .and_then(|ext| ext.to_str())
             .and_then(Self::from_extension)
+            .or_else(|| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(Self::from_filename)
+            })
+    }
+
+    /// Map well-known extensionless (or misleading) file names to a language.
+    ///
+    /// There is no grammar for Make, Docker or CMake files: their recipes and
+    /// `RUN` instructions are shell commands, so they are parsed as Bash, which
+    /// is enough for chunking and embeddings.
+    pub fn from_filename(name: &str) -> Option<Self> {
+        match name {
+            "Rakefile" | "Gemfile" | "Vagrantfile" | "Podfile" | "Brewfile" | "Guardfile"
+            | "Capfile" | "Fastfile" | "Appfile" | "Berksfile" | "Dangerfile" => {
+                Some(Language::Ruby)
+            }
+            "SConstruct" | "SConscript" | "BUILD" | "BUILD.bazel" | "WORKSPACE"
+            | "WORKSPACE.bazel" | "Tiltfile" => Some(Language::Python),
+            "Jakefile" => Some(Language::JavaScript),
+            "Makefile" | "makefile" | "GNUmakefile" | "Dockerfile" | "Containerfile"
+            | "CMakeLists.txt" | "PKGBUILD" | "APKBUILD" => Some(Language::Bash),
+            _ if name.starts_with("Dockerfile.") => Some(Language::Bash),
+            _ => None,
+        }
+    }
+
+    /// Map a language or editor mode name (e.g. "python", "sh", "c++") to a language.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::builtin_from_name(name).or_else(|| plugins::find(|l| l.name == name))
+    }
+
+    /// Map a name to one of the built-in languages
+    pub(crate) fn builtin_from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" => Some(Language::Rust),
+            "typescript" | "ts" | "typescriptreact" | "tsx" => Some(Language::TypeScript),
+            "javascript" | "js" | "javascriptreact" | "jsx" | "node" => Some(Language::JavaScript),
+            "python" | "py" => Some(Language::Python),
+            "go" | "golang" => Some(Language::Go),
+            "java" => Some(Language::Java),
+            "c" => Some(Language::C),
+            "cpp" | "c++" => Some(Language::Cpp),
+            "php" => Some(Language::Php),
+            "ruby" | "rb" => Some(Language::Ruby),
+            "csharp" | "cs" | "c#" => Some(Language::CSharp),
+            "kotlin" => Some(Language::Kotlin),
+            "scala" => Some(Language::Scala),
+            "html" | "mhtml" => Some(Language::Html),
+            "json" => Some(Language::Json),
+            "yaml" => Some(Language::Yaml),
+            "toml" | "conf-toml" => Some(Language::Toml),
+            "sh" | "bash" | "zsh" | "shell-script" | "shell" => Some(Language::Bash),
+            "elixir" => Some(Language::Elixir),
+            "sql" | "pgsql" | "mysql" | "plsql" => Some(Language::Sql),
+            _ => None,
+        }
+    }
+
+    /// Detect the language from a `#!` interpreter line.
+    ///
+    /// Handles `#!/usr/bin/env [-S] interpreter` and versioned interpreters
+    /// such as `python3.12`.
+    pub fn from_shebang(first_line: &str) -> Option<Self> {
+        let command = first_line.strip_prefix("#!")?.trim();
+        let mut parts = command.split_whitespace();
+        let mut program = parts.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = parts.find(|arg| !arg.starts_with('-') && !arg.contains('='))?;
+        }
+
+        // python3.12 -> python, ruby2.7 -> ruby
+        let interpreter = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        match interpreter {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" | "ash" => Some(Language::Bash),
+            "python" | "pypy" => Some(Language::Python),
+            "node" | "nodejs" | "deno" | "bun" => Some(Language::JavaScript),
+            "ts-node" | "tsx" => Some(Language::TypeScript),
+            "ruby" | "jruby" => Some(Language::Ruby),
+            "php" => Some(Language::Php),
+            "elixir" => Some(Language::Elixir),
+            "scala" => Some(Language::Scala),
+            _ => None,
+        }
+    }
+
+    /// Detect the language from an Emacs (`-*- mode: python -*-`) or Vim
+    /// (`vim: set ft=ruby:`) modeline in the first or last lines.
+    pub fn from_modeline(content: &str) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        let head = lines.iter().take(MODELINE_LINES);
+        let tail = lines
+            .iter()
+            .skip(MODELINE_LINES)
+            .skip(lines.len().saturating_sub(2 * MODELINE_LINES));
+
+        head.chain(tail)
+            .find_map(|line| parse_emacs_modeline(line).or_else(|| parse_vim_modeline(line)))
+            .and_then(Self::from_name)
+    }
+
+    /// Detect the language from file content (shebang, then modeline).
+    pub fn from_content(content: &str) -> Option<Self> {
+        content
+            .lines()
+            .next()
+            .and_then(Self::from_shebang)
+            .or_else(|| Self::from_modeline(content))
+    }
+
+    /// Detect the language of a file on disk.
+    ///
+    /// Uses the path first; extensionless files that are not recognized by name
+    /// are sniffed for a shebang or modeline. Binary files are ignored.
+    pub fn detect(path: &Path) -> Option<Self> {
+        if let Some(language) = Self::from_path(path) {
+            return Some(language);
+        }
+        if path.extension().is_some() {
+            return None;
+        }
+
+        let mut head = Vec::new();
+        std::fs::File::open(path)
+            .ok()?
+            .take(SNIFF_LIMIT)
+            .read_to_end(&mut head)
+            .ok()?;
+        if head.contains(&0) {
+            return None;
+        }
+        Self::from_content(&String::from_utf8_lossy(&head))
     }
 
     pub fn name(&self) -> &'static str {
@@ -79,9 +231,39 @@ impl Language {
             Language::Toml => "toml",
             Language::Bash => "bash",
             Language::Elixir => "elixir",
+            Language::Sql => "sql",
+            Language::Custom(id) => plugins::get(*id).map_or("unknown", |l| l.name),
         }
     }
 
+    /// Tree-sitter grammar of the language, `None` for a custom language
+    /// that is not registered
+    pub(crate) fn grammar(&self) -> Option<tree_sitter::Language> {
+        Some(match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::Go => tree_sitter_go::LANGUAGE.into(),
+            Language::Java => tree_sitter_java::LANGUAGE.into(),
+            Language::C => tree_sitter_c::LANGUAGE.into(),
+            Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+            Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+            Language::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+            Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+            Language::Kotlin => tree_sitter_kotlin_ng::LANGUAGE.into(),
+            Language::Scala => tree_sitter_scala::LANGUAGE.into(),
+            Language::Html => tree_sitter_html::LANGUAGE.into(),
+            Language::Json => tree_sitter_json::LANGUAGE.into(),
+            Language::Yaml => tree_sitter_yaml::LANGUAGE.into(),
+            Language::Toml => tree_sitter_toml_ng::LANGUAGE.into(),
+            Language::Bash => tree_sitter_bash::LANGUAGE.into(),
+            Language::Elixir => tree_sitter_elixir::LANGUAGE.into(),
+            Language::Sql => tree_sitter_sequel::LANGUAGE.into(),
+            Language::Custom(id) => plugins::get(*id)?.grammar.clone(),
+        })
+    }
+
     pub fn file_extensions(&self) -> &'static [&'static str] {
         match self {
             Language::Rust => &["rs"],
@@ -103,12 +285,59 @@ impl Language {
             Language::Toml => &["toml"],
             Language::Bash => &["sh", "bash", "zsh"],
             Language::Elixir => &["ex", "exs"],
+            Language::Sql => &["sql", "ddl", "pgsql"],
+            Language::Custom(id) => plugins::get(*id).map_or(&[], |l| l.extensions),
         }
     }
 }
 
+/// Extract the mode from an Emacs modeline: `-*- mode: python -*-` or `-*- python -*-`
+fn parse_emacs_modeline(line: &str) -> Option<&str> {
+    let start = line.find("-*-")? + 3;
+    let end = start + line[start..].find("-*-")?;
+    let vars = line[start..end].trim();
+
+    if !vars.contains(':') {
+        return Some(vars).filter(|v| !v.is_empty());
+    }
+    vars.split(';').find_map(|var| {
+        let (key, value) = var.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then(|| value.trim())
+    })
+}
+
+/// Extract the filetype from a Vim modeline: `vim: set ft=ruby:` or `vi: filetype=sh`
+fn parse_vim_modeline(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"]
+        .iter()
+        .filter_map(|marker| {
+            let pos = line.find(marker)?;
+            // The marker must start a word (e.g. not "navi:")
+            let preceded_ok = line[..pos]
+                .chars()
+                .next_back()
+                .is_none_or(|c| c.is_whitespace());
+            preceded_ok.then_some(pos + marker.len())
+        })
+        .min()?;
+
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+                .or_else(|| option.strip_prefix("syntax="))
+        })
+        .filter(|ft| !ft.is_empty())
+}
+
 pub struct LanguageSupport {
     parsers: std::collections::HashMap<Language, tree_sitter::Parser>,
+    /// TypeScript with JSX, for `.tsx` files
+    tsx: tree_sitter::Parser,
 }
 
 impl LanguageSupport {
@@ -116,95 +345,18 @@ impl LanguageSupport {
         let mut parsers = std::collections::HashMap::new();
 
         // Initialize parsers for each language
-        Self::add_parser(
-            &mut parsers,
-            Language::Rust,
-            tree_sitter_rust::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::TypeScript,
-            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::JavaScript,
-            tree_sitter_javascript::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Python,
-            tree_sitter_python::LANGUAGE.into(),
-        )?;
-        Self::add_parser(&mut parsers, Language::Go, tree_sitter_go::LANGUAGE.into())?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Java,
-            tree_sitter_java::LANGUAGE.into(),
-        )?;
-        Self::add_parser(&mut parsers, Language::C, tree_sitter_c::LANGUAGE.into())?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Cpp,
-            tree_sitter_cpp::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Php,
-            tree_sitter_php::LANGUAGE_PHP.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Ruby,
-            tree_sitter_ruby::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::CSharp,
-            tree_sitter_c_sharp::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Kotlin,
-            tree_sitter_kotlin_ng::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Scala,
-            tree_sitter_scala::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Html,
-            tree_sitter_html::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Json,
-            tree_sitter_json::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Yaml,
-            tree_sitter_yaml::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Toml,
-            tree_sitter_toml_ng::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Bash,
-            tree_sitter_bash::LANGUAGE.into(),
-        )?;
-        Self::add_parser(
-            &mut parsers,
-            Language::Elixir,
-            tree_sitter_elixir::LANGUAGE.into(),
-        )?;
+        for &lang in Self::supported_languages() {
+            let grammar = lang
+                .grammar()
+                .ok_or_else(|| anyhow!("No grammar for language: {:?}", lang))?;
+            Self::add_parser(&mut parsers, lang, grammar)?;
+        }
+
+        let mut tsx = tree_sitter::Parser::new();
+        tsx.set_language(&tree_sitter_typescript::LANGUAGE_TSX.into())
+            .map_err(|e| anyhow!("Failed to set TSX language: {}", e))?;
 
-        Ok(Self { parsers })
+        Ok(Self { parsers, tsx })
     }
 
     fn add_parser(
@@ -221,6 +373,14 @@ impl LanguageSupport {
     }
 
     pub fn parse(&mut self, lang: Language, source: &str) -> Result<tree_sitter::Tree> {
+        // Custom languages may be registered after this was created
+        if let Language::Custom(id) = lang
+            && !self.parsers.contains_key(&lang)
+        {
+            let custom = plugins::get(id).ok_or_else(|| anyhow!("Unknown language {:?}", lang))?;
+            Self::add_parser(&mut self.parsers, lang, custom.grammar.clone())?;
+        }
+
         let parser = self
             .parsers
             .get_mut(&lang)
@@ -231,6 +391,28 @@ impl LanguageSupport {
             .ok_or_else(|| anyhow!("Failed to parse source"))
     }
 
+    /// Parse the content of the file at `path`. `.tsx` files are parsed with
+    /// the TSX grammar: the TypeScript grammar rejects JSX, while the TSX
+    /// grammar rejects the `<T>value` casts of `.ts` files.
+    pub fn parse_file(
+        &mut self,
+        lang: Language,
+        path: &Path,
+        source: &str,
+    ) -> Result<tree_sitter::Tree> {
+        let is_tsx = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("tsx"));
+        if lang != Language::TypeScript || !is_tsx {
+            return self.parse(lang, source);
+        }
+
+        self.tsx
+            .parse(source, None)
+            .ok_or_else(|| anyhow!("Failed to parse source"))
+    }
+
     pub fn supported_languages() -> &'static [Language] {
         &[
             Language::Rust,
@@ -252,6 +434,7 @@ impl LanguageSupport {
             Language::Toml,
             Language::Bash,
             Language::Elixir,
+            Language::Sql,
         ]
     }
 }
@@ -320,6 +503,10 @@ mod tests {
         // PHP
         assert_eq!(Language::from_extension("php"), Some(Language::Php));
         assert_eq!(Language::from_extension("phtml"), Some(Language::Php));
+
+        // SQL
+        assert_eq!(Language::from_extension("sql"), Some(Language::Sql));
+        assert_eq!(Language::from_extension("ddl"), Some(Language::Sql));
     }
 
     #[test]
@@ -340,6 +527,104 @@ mod tests {
         assert_eq!(Language::from_path(Path::new("noextension")), None);
     }
 
+    #[test]
+    fn test_language_from_special_filenames() {
+        assert_eq!(
+            Language::from_path(Path::new("Rakefile")),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("app/Gemfile")),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("Makefile")),
+            Some(Language::Bash)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("docker/Dockerfile.dev")),
+            Some(Language::Bash)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("CMakeLists.txt")),
+            Some(Language::Bash)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("BUILD.bazel")),
+            Some(Language::Python)
+        );
+        assert_eq!(Language::from_path(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_language_from_shebang() {
+        assert_eq!(Language::from_shebang("#!/bin/bash"), Some(Language::Bash));
+        assert_eq!(Language::from_shebang("#!/bin/sh -e"), Some(Language::Bash));
+        assert_eq!(
+            Language::from_shebang("#!/usr/bin/env python3"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::from_shebang("#!/usr/bin/python3.12 -u"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::from_shebang("#!/usr/bin/env -S node --no-warnings"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            Language::from_shebang("#!/usr/bin/env ruby"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(Language::from_shebang("#!/usr/bin/env perl"), None);
+        assert_eq!(Language::from_shebang("echo hello"), None);
+    }
+
+    #[test]
+    fn test_language_from_modeline() {
+        assert_eq!(
+            Language::from_modeline("# -*- mode: python; coding: utf-8 -*-\nimport os\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::from_modeline("# -*- ruby -*-\nputs 1\n"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::from_modeline("echo hi\n# vim: set ft=sh ts=4:\n"),
+            Some(Language::Bash)
+        );
+
+        // Vim modelines are also accepted in the last lines of the file
+        let mut content = "x = 1\n".repeat(20);
+        content.push_str("# vi: filetype=python\n");
+        assert_eq!(Language::from_modeline(&content), Some(Language::Python));
+
+        assert_eq!(Language::from_modeline("navi: ft=ruby\n"), None);
+        assert_eq!(Language::from_modeline("plain text\n"), None);
+    }
+
+    #[test]
+    fn test_language_detect_extensionless_file() {
+        let dir = std::env::temp_dir().join(format!("semantiq-detect-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let script = dir.join("deploy");
+        std::fs::write(&script, "#!/usr/bin/env bash\nset -e\n").unwrap();
+        assert_eq!(Language::detect(&script), Some(Language::Bash));
+
+        let binary = dir.join("tool");
+        std::fs::write(&binary, b"#!/bin/sh\0\x01\x02").unwrap();
+        assert_eq!(Language::detect(&binary), None);
+
+        // Files with an unknown extension are not sniffed
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, "#!/bin/sh\n").unwrap();
+        assert_eq!(Language::detect(&text), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_language_name() {
         assert_eq!(Language::Rust.name(), "rust");
@@ -385,6 +670,23 @@ mod tests {
         assert!(!tree.root_node().has_error());
     }
 
+    #[test]
+    fn test_parse_tsx() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = "const App = () => <Layout title=\"home\"><Button /></Layout>;";
+        let tree = support
+            .parse_file(Language::TypeScript, Path::new("src/App.tsx"), source)
+            .unwrap();
+        assert!(!tree.root_node().has_error());
+
+        // Casts are only valid outside of .tsx files
+        let cast = "const n = <number>value;";
+        let tree = support
+            .parse_file(Language::TypeScript, Path::new("src/util.ts"), cast)
+            .unwrap();
+        assert!(!tree.root_node().has_error());
+    }
+
     #[test]
     fn test_parse_javascript() {
         let mut support = LanguageSupport::new().unwrap();
@@ -454,10 +756,22 @@ mod tests {
         assert!(!tree.root_node().has_error());
     }
 
+    #[test]
+    fn test_parse_sql() {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support
+            .parse(
+                Language::Sql,
+                "CREATE TABLE users (id INTEGER PRIMARY KEY);\nALTER TABLE users ADD COLUMN email TEXT;",
+            )
+            .unwrap();
+        assert!(!tree.root_node().has_error());
+    }
+
     #[test]
     fn test_supported_languages() {
         let languages = LanguageSupport::supported_languages();
-        assert_eq!(languages.len(), 19);
+        assert_eq!(languages.len(), 20);
         assert!(languages.contains(&Language::Rust));
         assert!(languages.contains(&Language::TypeScript));
         assert!(languages.contains(&Language::JavaScript));
@@ -477,6 +791,7 @@ mod tests {
         assert!(languages.contains(&Language::Toml));
         assert!(languages.contains(&Language::Bash));
         assert!(languages.contains(&Language::Elixir));
+        assert!(languages.contains(&Language::Sql));
     }
 
     #[test]
@@ -1,13 +1,38 @@
+pub mod bindings;
 pub mod chunks;
+mod conditions;
+mod config_keys;
+pub mod config_refs;
+pub mod entrypoints;
 pub mod imports;
+pub mod keywords;
 pub mod language;
+pub mod occurrences;
+pub mod plugins;
+pub mod qualifiers;
+pub mod relations;
+pub mod routes;
+pub mod symbol_kinds;
 pub mod symbols;
+pub mod text_mask;
 
 /// Version du parser. Incrémenter force une réindexation complète.
 /// Incrémenter quand : ajout/modif de types de noeuds, changement logique d'extraction
-pub const PARSER_VERSION: u32 = 3; // Arrow functions classifiées comme fonctions
+pub const PARSER_VERSION: u32 = 23; // Clés de configuration nommées par leur chemin, un chunk par clé
 
+pub use bindings::{Binding, BindingExtractor, BindingFramework, binding_name_variants};
 pub use chunks::{ChunkExtractor, CodeChunk};
-pub use imports::{Import, ImportExtractor, ImportKind};
+pub use config_keys::is_config_language;
+pub use config_refs::{ConfigAccess, ConfigRef, ConfigRefExtractor, ConfigSource};
+pub use entrypoints::{Entrypoint, EntrypointExtractor, EntrypointKind};
+pub use imports::{Import, ImportExtractor, ImportKind, ImportedSymbol};
+pub use keywords::{Keyword, KeywordExtractor, split_words};
 pub use language::{Language, LanguageSupport};
+pub use occurrences::{Occurrence, OccurrenceExtractor};
+pub use plugins::{LanguagePlugin, register_languages};
+pub use qualifiers::SymbolQualifiers;
+pub use relations::{RelationExtractor, RelationKind, TypeRelation};
+pub use routes::{HttpRoute, RouteMatch};
+pub use symbol_kinds::{SymbolKindMapping, SymbolKindOverrides, register_symbol_kinds};
 pub use symbols::{Symbol, SymbolExtractor, SymbolKind};
+pub use text_mask::{TextMask, TextMaskExtractor};
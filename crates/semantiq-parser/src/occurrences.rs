@@ -0,0 +1,171 @@
+use crate::language::Language;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tree_sitter::{Node, Tree};
+
+/// Longest identifier counted; longer ones are generated code or data
+const MAX_IDENTIFIER_LEN: usize = 128;
+
+/// Number of times a name appears as an identifier in a file, definitions
+/// included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Occurrence {
+    pub name: String,
+    pub count: usize,
+    /// Lines (1-based) the name appears on, in order, each once
+    #[serde(default)]
+    pub lines: Vec<usize>,
+}
+
+pub struct OccurrenceExtractor;
+
+impl OccurrenceExtractor {
+    /// Count the identifiers of a file, sorted by name.
+    ///
+    /// Only identifier nodes are counted, so names mentioned in comments or
+    /// string literals are not. Data and markup files have none.
+    pub fn extract(tree: &Tree, source: &str, language: Language) -> Result<Vec<Occurrence>> {
+        if matches!(
+            language,
+            Language::Html | Language::Json | Language::Yaml | Language::Toml
+        ) {
+            return Ok(Vec::new());
+        }
+
+        let mut counts: HashMap<&str, (usize, Vec<usize>)> = HashMap::new();
+        Self::count_recursive(&tree.root_node(), source, &mut counts);
+
+        let mut occurrences: Vec<Occurrence> = counts
+            .into_iter()
+            .map(|(name, (count, lines))| Occurrence {
+                name: name.to_string(),
+                count,
+                lines,
+            })
+            .collect();
+        occurrences.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(occurrences)
+    }
+
+    fn count_recursive<'a>(
+        node: &Node,
+        source: &'a str,
+        counts: &mut HashMap<&'a str, (usize, Vec<usize>)>,
+    ) {
+        if Self::is_identifier(node) {
+            if let Ok(name) = node.utf8_text(source.as_bytes())
+                && !name.is_empty()
+                && name.len() <= MAX_IDENTIFIER_LEN
+            {
+                let (count, lines) = counts.entry(name).or_default();
+                *count += 1;
+                // Nodes are visited in source order
+                let line = node.start_position().row + 1;
+                if lines.last() != Some(&line) {
+                    lines.push(line);
+                }
+            }
+            return;
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::count_recursive(&child, source, counts);
+        }
+    }
+
+    /// Identifier node kinds across grammars: `identifier`, `type_identifier`,
+    /// `field_identifier`, `simple_identifier` (Kotlin), `constant` (Ruby),
+    /// `name` (PHP) and `alias` (Elixir modules).
+    pub(crate) fn is_identifier(node: &Node) -> bool {
+        if !node.is_named() || node.child_count() > 0 {
+            return false;
+        }
+        let kind = node.kind();
+        kind.ends_with("identifier") || matches!(kind, "constant" | "name" | "alias")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(language: Language, source: &str) -> Vec<(String, usize)> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        OccurrenceExtractor::extract(&tree, source, language)
+            .unwrap()
+            .into_iter()
+            .map(|o| (o.name, o.count))
+            .collect()
+    }
+
+    fn count(occurrences: &[(String, usize)], name: &str) -> usize {
+        occurrences
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or(0, |(_, c)| *c)
+    }
+
+    #[test]
+    fn test_counts_rust_identifiers() {
+        let source = r#"
+/// Uses parse_config
+pub fn load(path: &Path) -> Config {
+    let config = parse_config(path);
+    // parse_config is called once
+    println!("parse_config");
+    config.validate();
+    config
+}
+"#;
+        let occurrences = extract(Language::Rust, source);
+
+        assert_eq!(count(&occurrences, "parse_config"), 1);
+        assert_eq!(count(&occurrences, "config"), 3);
+        assert_eq!(count(&occurrences, "Config"), 1);
+        assert_eq!(count(&occurrences, "load"), 1);
+        assert_eq!(count(&occurrences, "validate"), 1);
+
+        let names: Vec<&str> = occurrences.iter().map(|(n, _)| n.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn test_counts_python_and_typescript_identifiers() {
+        let python = extract(
+            Language::Python,
+            "from app import Service\n\ndef run():\n    return Service().start()  # Service\n",
+        );
+        assert_eq!(count(&python, "Service"), 2);
+        assert_eq!(count(&python, "start"), 1);
+
+        let typescript = extract(
+            Language::TypeScript,
+            "import { Client } from './client';\nconst c: Client = new Client();\nc.connect();\n",
+        );
+        assert_eq!(count(&typescript, "Client"), 3);
+        assert_eq!(count(&typescript, "connect"), 1);
+    }
+
+    #[test]
+    fn test_occurrence_lines() {
+        let source = "fn load() {\n    parse(parse(1));\n}\n\nfn parse(n: u8) {}\n";
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(Language::Rust, source).unwrap();
+        let occurrences = OccurrenceExtractor::extract(&tree, source, Language::Rust).unwrap();
+
+        let parse = occurrences.iter().find(|o| o.name == "parse").unwrap();
+        assert_eq!(parse.count, 3);
+        assert_eq!(parse.lines, [2, 5]);
+    }
+
+    #[test]
+    fn test_data_files_have_no_occurrences() {
+        assert!(extract(Language::Json, r#"{"name": "value"}"#).is_empty());
+    }
+}
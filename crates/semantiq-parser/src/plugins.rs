@@ -0,0 +1,235 @@
+//! Custom languages parsed with compiled tree-sitter grammars
+//!
+//! Languages declared in `semantiq.toml` are loaded from a shared library
+//! (`.so`, `.dylib` or `.dll`) exporting the grammar, e.g.:
+//!
+//! ```toml
+//! [[languages]]
+//! name = "mydsl"
+//! grammar = "grammars/libtree-sitter-mydsl.so"
+//! extensions = ["dsl"]
+//!
+//! [languages.symbols]
+//! rule_definition = "function"
+//! record_declaration = "struct"
+//! ```
+//!
+//! Loaded languages are registered for the whole process and detected
+//! before the built-in ones, as [`Language::Custom`].
+
+use crate::language::Language;
+use crate::symbols::SymbolKind;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::{debug, info};
+
+/// A language declared in `[[languages]]` of `semantiq.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguagePlugin {
+    /// Language name, as stored in the index and used in filters
+    pub name: String,
+    /// Compiled grammar, relative to the project root
+    pub grammar: PathBuf,
+    /// File extensions of the language, without the dot
+    pub extensions: Vec<String>,
+    /// Function of the library returning the grammar, by default
+    /// `tree_sitter_<name>`
+    #[serde(default)]
+    pub entry_point: Option<String>,
+    /// Field of symbol nodes holding their name
+    #[serde(default = "default_name_field")]
+    pub name_field: String,
+    /// Kind of the symbol each node kind declares; these nodes also start
+    /// chunks
+    #[serde(default)]
+    pub symbols: BTreeMap<String, SymbolKind>,
+}
+
+fn default_name_field() -> String {
+    "name".to_string()
+}
+
+/// A registered custom language
+pub(crate) struct CustomLanguage {
+    pub(crate) name: &'static str,
+    pub(crate) extensions: &'static [&'static str],
+    pub(crate) grammar: tree_sitter::Language,
+    pub(crate) name_field: String,
+    pub(crate) symbols: BTreeMap<String, SymbolKind>,
+}
+
+/// Custom languages, indexed by the id of [`Language::Custom`]. Entries
+/// are leaked: they live as long as the grammar libraries they point into.
+static LANGUAGES: RwLock<Vec<&'static CustomLanguage>> = RwLock::new(Vec::new());
+
+/// Load and register the languages of `plugins`, resolving grammar paths
+/// against `project_root`. A language already registered under the same
+/// name is kept.
+pub fn register_languages(
+    plugins: &[LanguagePlugin],
+    project_root: &Path,
+) -> Result<Vec<Language>> {
+    plugins
+        .iter()
+        .map(|plugin| {
+            if let Some(language) = find(|l| l.name == plugin.name) {
+                debug!("Language {} already registered", plugin.name);
+                return Ok(language);
+            }
+            let grammar = load_grammar(plugin, &project_root.join(&plugin.grammar))
+                .with_context(|| format!("Failed to load the grammar of {}", plugin.name))?;
+            let language = register(plugin, grammar)?;
+            info!(
+                "Registered language {} for .{}",
+                plugin.name,
+                plugin.extensions.join(", .")
+            );
+            Ok(language)
+        })
+        .collect()
+}
+
+/// Load the grammar exported by a compiled tree-sitter library
+fn load_grammar(plugin: &LanguagePlugin, path: &Path) -> Result<tree_sitter::Language> {
+    let entry_point = plugin
+        .entry_point
+        .clone()
+        .unwrap_or_else(|| format!("tree_sitter_{}", plugin.name.replace('-', "_")));
+
+    // SAFETY: the library is a grammar generated by the tree-sitter CLI, whose
+    // entry point takes no arguments and returns a static TSLanguage. It is
+    // never unloaded, so the grammar outlives every parser using it.
+    let grammar = unsafe {
+        let library = libloading::Library::new(path)?;
+        let entry = *library
+            .get::<unsafe extern "C" fn() -> *const ()>(entry_point.as_bytes())
+            .with_context(|| format!("No {} function in {:?}", entry_point, path))?;
+        std::mem::forget(library);
+        tree_sitter::Language::new(tree_sitter_language::LanguageFn::from_raw(entry))
+    };
+
+    // Reject grammars generated for another ABI before they are used
+    tree_sitter::Parser::new()
+        .set_language(&grammar)
+        .map_err(|e| anyhow!("Incompatible grammar {:?}: {}", path, e))?;
+    Ok(grammar)
+}
+
+/// Register a custom language parsed with `grammar`
+pub(crate) fn register(
+    plugin: &LanguagePlugin,
+    grammar: tree_sitter::Language,
+) -> Result<Language> {
+    if plugin.name.is_empty() || Language::builtin_from_name(&plugin.name).is_some() {
+        bail!("Invalid custom language name '{}'", plugin.name);
+    }
+    if plugin.extensions.is_empty() {
+        bail!("Language {} has no file extension", plugin.name);
+    }
+
+    let extensions: Vec<&'static str> = plugin
+        .extensions
+        .iter()
+        .map(|ext| &*Box::leak(ext.trim_start_matches('.').to_lowercase().into_boxed_str()))
+        .collect();
+    let language = Box::leak(Box::new(CustomLanguage {
+        name: Box::leak(plugin.name.clone().into_boxed_str()),
+        extensions: Box::leak(extensions.into_boxed_slice()),
+        grammar,
+        name_field: plugin.name_field.clone(),
+        symbols: plugin.symbols.clone(),
+    }));
+
+    let mut languages = match LANGUAGES.write() {
+        Ok(languages) => languages,
+        Err(e) => e.into_inner(),
+    };
+    let id = u16::try_from(languages.len()).context("Too many custom languages")?;
+    languages.push(language);
+    Ok(Language::Custom(id))
+}
+
+/// The registered language with this id
+pub(crate) fn get(id: u16) -> Option<&'static CustomLanguage> {
+    let languages = match LANGUAGES.read() {
+        Ok(languages) => languages,
+        Err(e) => e.into_inner(),
+    };
+    languages.get(id as usize).copied()
+}
+
+/// The first registered language matching `predicate`
+pub(crate) fn find(predicate: impl Fn(&CustomLanguage) -> bool) -> Option<Language> {
+    let languages = match LANGUAGES.read() {
+        Ok(languages) => languages,
+        Err(e) => e.into_inner(),
+    };
+    languages
+        .iter()
+        .position(|l| predicate(l))
+        .map(|id| Language::Custom(id as u16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkExtractor, LanguageSupport, SymbolExtractor};
+
+    /// A custom language borrowing the JSON grammar
+    fn plugin(name: &str, extension: &str) -> LanguagePlugin {
+        LanguagePlugin {
+            name: name.to_string(),
+            grammar: PathBuf::from("unused.so"),
+            extensions: vec![extension.to_string()],
+            entry_point: None,
+            name_field: "key".to_string(),
+            symbols: BTreeMap::from([("pair".to_string(), SymbolKind::Constant)]),
+        }
+    }
+
+    #[test]
+    fn test_custom_language_is_detected_and_parsed() {
+        let language = register(
+            &plugin("settings-dsl", "Setting"),
+            tree_sitter_json::LANGUAGE.into(),
+        )
+        .unwrap();
+
+        assert_eq!(language.name(), "settings-dsl");
+        assert_eq!(Language::from_name("settings-dsl"), Some(language));
+        assert_eq!(
+            Language::from_path(Path::new("conf/app.setting")),
+            Some(language)
+        );
+
+        let source = "{\n  \"timeout\": 30,\n  \"retries\": 3\n}\n";
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, language).unwrap();
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["\"timeout\"", "\"retries\""]);
+        assert!(symbols.iter().all(|s| s.kind == SymbolKind::Constant));
+
+        let chunks = ChunkExtractor::new()
+            .extract(&tree, source, language)
+            .unwrap();
+        assert!(!chunks.is_empty());
+    }
+
+    #[test]
+    fn test_register_rejects_builtin_names() {
+        let error = register(&plugin("rust", "rsx"), tree_sitter_json::LANGUAGE.into());
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_register_languages_reports_missing_grammar() {
+        let error = register_languages(&[plugin("missing-dsl", "mdsl")], Path::new("/nonexistent"))
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("missing-dsl"));
+        assert_eq!(Language::from_extension("mdsl"), None);
+    }
+}
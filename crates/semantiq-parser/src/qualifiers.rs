@@ -0,0 +1,440 @@
+use crate::language::Language;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// Structured qualifiers of a symbol, read from its modifiers, decorators
+/// and naming conventions.
+///
+/// `is_public` follows each language's rules: a bare `pub` in Rust, an
+/// exported name in Go, TypeScript and JavaScript, `public` (or an interface
+/// member) in Java and C#, the absence of `private`/`protected` where members
+/// are public by default (Kotlin, Scala, PHP, Ruby, C++ structs), a name
+/// without a leading `_` in Python, `def` rather than `defp` in Elixir and
+/// external linkage in C. Qualifiers are not extracted for data formats,
+/// SQL or custom languages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SymbolQualifiers {
+    pub is_public: bool,
+    pub is_async: bool,
+    pub is_static: bool,
+    pub is_abstract: bool,
+    /// Number of generic type, const or template parameters
+    pub generic_params: usize,
+}
+
+impl SymbolQualifiers {
+    /// Names of the qualifiers, as accepted by [`Self::has`]
+    pub const NAMES: [&'static str; 5] = ["public", "async", "static", "abstract", "generic"];
+
+    /// Names of the qualifiers the symbol has, in [`Self::NAMES`] order
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(|name| self.has(name))
+            .collect()
+    }
+
+    /// Whether the symbol has the named qualifier (case-insensitive)
+    pub fn has(&self, name: &str) -> bool {
+        match name.to_lowercase().as_str() {
+            "public" => self.is_public,
+            "async" => self.is_async,
+            "static" => self.is_static,
+            "abstract" => self.is_abstract,
+            "generic" => self.generic_params > 0,
+            _ => false,
+        }
+    }
+
+    /// Qualifiers of the symbol declared by `node`, named `name`
+    pub(crate) fn extract(node: &Node, source: &str, language: Language, name: &str) -> Self {
+        let words = modifier_words(node, source);
+        let has_word = |word: &str| words.iter().any(|w| w == word);
+
+        let mut qualifiers = SymbolQualifiers {
+            is_async: has_word("async") || has_word("suspend"),
+            is_static: has_word("static"),
+            is_abstract: has_word("abstract"),
+            generic_params: generic_params(node),
+            ..Self::default()
+        };
+
+        match language {
+            Language::Rust => {
+                // `pub(crate)`, `pub(super)` and `pub(in path)` are not public API
+                qualifiers.is_public = has_word("pub")
+                    && !["crate", "super", "self", "in"].iter().any(|w| has_word(w));
+            }
+            Language::TypeScript | Language::JavaScript => {
+                let is_member = node.parent().is_some_and(|p| p.kind() == "class_body");
+                qualifiers.is_public = if is_member {
+                    !has_word("private") && !has_word("protected") && !name.starts_with('#')
+                } else {
+                    node.parent()
+                        .is_some_and(|p| p.kind() == "export_statement")
+                };
+                qualifiers.is_async |= is_async_function_variable(node);
+            }
+            Language::Python => {
+                qualifiers.is_public =
+                    !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__"));
+                for decorator in python_decorators(node, source) {
+                    match decorator.as_str() {
+                        "staticmethod" => qualifiers.is_static = true,
+                        "abstractmethod" => qualifiers.is_abstract = true,
+                        _ => {}
+                    }
+                }
+            }
+            Language::Go => {
+                qualifiers.is_public = name.chars().next().is_some_and(char::is_uppercase);
+            }
+            Language::Java | Language::CSharp => {
+                let in_interface = is_interface_member(node);
+                qualifiers.is_public = has_word("public") || in_interface;
+                // Interface methods without a default body are abstract
+                qualifiers.is_abstract |= in_interface
+                    && node.kind() == "method_declaration"
+                    && node.child_by_field_name("body").is_none()
+                    && !has_word("static");
+            }
+            Language::Kotlin | Language::Scala | Language::Php => {
+                qualifiers.is_public =
+                    !has_word("private") && !has_word("protected") && !has_word("internal");
+            }
+            Language::Ruby => {
+                qualifiers.is_static = node.kind() == "singleton_method";
+                qualifiers.is_public = qualifiers.is_static || ruby_visibility(node, source);
+            }
+            Language::C => {
+                qualifiers.is_public = !qualifiers.is_static;
+            }
+            Language::Cpp => cpp_qualifiers(node, source, &words, &mut qualifiers),
+            Language::Elixir => {
+                let target = node
+                    .child_by_field_name("target")
+                    .and_then(|t| t.utf8_text(source.as_bytes()).ok());
+                qualifiers.is_public = !matches!(
+                    target,
+                    Some("defp" | "defmacrop" | "defguardp" | "defdelegatep")
+                );
+            }
+            Language::Html
+            | Language::Json
+            | Language::Yaml
+            | Language::Toml
+            | Language::Bash
+            | Language::Sql
+            | Language::Custom(_) => return Self::default(),
+        }
+
+        qualifiers
+    }
+}
+
+/// Keywords and modifier words written on a declaration: its anonymous
+/// children (`async`, `static`, `export`, ...) and the words of its modifier
+/// nodes (`pub(crate)` gives `pub` and `crate`).
+fn modifier_words(node: &Node, source: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if !child.is_named() {
+            words.push(child.kind().to_string());
+        } else if (child.kind().contains("modifier") || child.kind() == "storage_class_specifier")
+            && let Ok(text) = child.utf8_text(source.as_bytes())
+        {
+            words.extend(
+                text.split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .filter(|w| !w.is_empty())
+                    .map(String::from),
+            );
+        }
+    }
+    words
+}
+
+/// Number of generic parameters declared by `node`
+fn generic_params(node: &Node) -> usize {
+    let mut cursor = node.walk();
+    let parameters = node
+        .child_by_field_name("type_parameters")
+        .or_else(|| {
+            node.children(&mut cursor)
+                .find(|c| matches!(c.kind(), "type_parameters" | "type_parameter_list"))
+        })
+        // Go generic types: `type Set[T comparable] ...`
+        .or_else(|| {
+            let spec = node.named_child(0).filter(|c| c.kind() == "type_spec")?;
+            spec.child_by_field_name("type_parameters")
+        })
+        // C++ templates wrap the declaration they parameterize
+        .or_else(|| {
+            let template = node
+                .parent()
+                .filter(|p| p.kind() == "template_declaration")?;
+            template.child_by_field_name("parameters")
+        });
+
+    parameters.map_or(0, |parameters| {
+        let mut cursor = parameters.walk();
+        parameters
+            .named_children(&mut cursor)
+            .filter(|c| !c.kind().contains("comment"))
+            .count()
+    })
+}
+
+/// Whether a TypeScript/JavaScript variable holds an `async` arrow function
+/// or function expression
+fn is_async_function_variable(node: &Node) -> bool {
+    if !matches!(node.kind(), "lexical_declaration" | "variable_declaration") {
+        return false;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|declarator| {
+        declarator
+            .child_by_field_name("value")
+            .filter(|v| matches!(v.kind(), "arrow_function" | "function_expression"))
+            .is_some_and(|value| {
+                let mut cursor = value.walk();
+                value.children(&mut cursor).any(|c| c.kind() == "async")
+            })
+    })
+}
+
+/// Names of the decorators of a Python definition, without their module
+/// and arguments (`@abc.abstractmethod` gives `abstractmethod`)
+fn python_decorators(node: &Node, source: &str) -> Vec<String> {
+    let Some(decorated) = node.parent().filter(|p| p.kind() == "decorated_definition") else {
+        return Vec::new();
+    };
+    let mut cursor = decorated.walk();
+    decorated
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "decorator")
+        .filter_map(|c| c.utf8_text(source.as_bytes()).ok())
+        .map(|text| {
+            let text = text.trim_start_matches('@');
+            let text = text.split('(').next().unwrap_or(text);
+            text.rsplit('.').next().unwrap_or(text).trim().to_string()
+        })
+        .collect()
+}
+
+/// Whether `node` is declared in the body of a Java or C# interface
+fn is_interface_member(node: &Node) -> bool {
+    node.parent().is_some_and(|body| {
+        body.kind() == "interface_body"
+            || body
+                .parent()
+                .is_some_and(|p| p.kind() == "interface_declaration")
+    })
+}
+
+/// Visibility of a Ruby method, set by the last bare `private`, `protected`
+/// or `public` call before it in its class body
+fn ruby_visibility(node: &Node, source: &str) -> bool {
+    let mut prev = node.prev_named_sibling();
+    while let Some(sibling) = prev {
+        if sibling.kind() == "identifier" {
+            match sibling.utf8_text(source.as_bytes()) {
+                Ok("private" | "protected") => return false,
+                Ok("public") => return true,
+                _ => {}
+            }
+        }
+        prev = sibling.prev_named_sibling();
+    }
+    true
+}
+
+/// C++ visibility, from the access specifier preceding a member (private by
+/// default in a class) or the linkage of a free function, and pure virtual
+/// functions (`virtual void f() = 0;`) as abstract
+fn cpp_qualifiers(node: &Node, source: &str, words: &[String], qualifiers: &mut SymbolQualifiers) {
+    let outer = node
+        .parent()
+        .filter(|p| p.kind() == "template_declaration")
+        .unwrap_or(*node);
+    let member_list = outer
+        .parent()
+        .filter(|p| p.kind() == "field_declaration_list");
+
+    qualifiers.is_public = match member_list {
+        Some(list) => {
+            let mut access = None;
+            let mut prev = outer.prev_sibling();
+            while let Some(sibling) = prev {
+                if sibling.kind() == "access_specifier" {
+                    access = sibling.utf8_text(source.as_bytes()).ok();
+                    break;
+                }
+                prev = sibling.prev_sibling();
+            }
+            match access {
+                Some(access) => access.trim() == "public",
+                None => list.parent().is_none_or(|p| p.kind() != "class_specifier"),
+            }
+        }
+        None => !qualifiers.is_static,
+    };
+
+    qualifiers.is_abstract = words.iter().any(|w| w == "virtual")
+        && node
+            .child_by_field_name("default_value")
+            .and_then(|v| v.utf8_text(source.as_bytes()).ok())
+            .is_some_and(|v| v.trim() == "0");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+    use crate::symbols::{Symbol, SymbolExtractor};
+
+    fn extract(language: Language, source: &str) -> Vec<Symbol> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        SymbolExtractor::extract(&tree, source, language).unwrap()
+    }
+
+    fn qualifiers(symbols: &[Symbol], name: &str) -> Vec<&'static str> {
+        symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("no symbol {name}"))
+            .qualifiers
+            .names()
+    }
+
+    #[test]
+    fn test_rust_qualifiers() {
+        let symbols = extract(
+            Language::Rust,
+            "pub async fn fetch<T, const N: usize>() {}\npub(crate) static LIMIT: i32 = 1;\nfn helper() {}",
+        );
+        assert_eq!(
+            qualifiers(&symbols, "fetch"),
+            ["public", "async", "generic"]
+        );
+        let fetch = symbols.iter().find(|s| s.name == "fetch").unwrap();
+        assert_eq!(fetch.qualifiers.generic_params, 2);
+        assert_eq!(qualifiers(&symbols, "LIMIT"), ["static"]);
+        assert!(qualifiers(&symbols, "helper").is_empty());
+    }
+
+    #[test]
+    fn test_typescript_qualifiers() {
+        let symbols = extract(
+            Language::TypeScript,
+            r#"
+export async function load<T>(id: T) {}
+function local() {}
+export const handler = async () => 1;
+class Service {
+    private static async refresh() {}
+    #secret() {}
+    run() {}
+}
+"#,
+        );
+        assert_eq!(qualifiers(&symbols, "load"), ["public", "async", "generic"]);
+        assert!(qualifiers(&symbols, "local").is_empty());
+        assert_eq!(qualifiers(&symbols, "handler"), ["public", "async"]);
+        assert_eq!(qualifiers(&symbols, "refresh"), ["async", "static"]);
+        assert!(qualifiers(&symbols, "#secret").is_empty());
+        assert_eq!(qualifiers(&symbols, "run"), ["public"]);
+    }
+
+    #[test]
+    fn test_python_qualifiers() {
+        let symbols = extract(
+            Language::Python,
+            r#"
+class Repository:
+    @staticmethod
+    def create(): pass
+
+    @abc.abstractmethod
+    async def fetch(self): pass
+
+    def _cache(self): pass
+
+    def __init__(self): pass
+"#,
+        );
+        assert_eq!(qualifiers(&symbols, "create"), ["public", "static"]);
+        assert_eq!(
+            qualifiers(&symbols, "fetch"),
+            ["public", "async", "abstract"]
+        );
+        assert!(qualifiers(&symbols, "_cache").is_empty());
+        assert_eq!(qualifiers(&symbols, "__init__"), ["public"]);
+    }
+
+    #[test]
+    fn test_go_and_java_qualifiers() {
+        let symbols = extract(
+            Language::Go,
+            "package p\nfunc Map[T any, U any](x T) {}\nfunc helper() {}",
+        );
+        assert_eq!(qualifiers(&symbols, "Map"), ["public", "generic"]);
+        assert!(qualifiers(&symbols, "helper").is_empty());
+
+        let symbols = extract(
+            Language::Java,
+            r#"
+public abstract class Shape<T> {
+    public static <U> void create() {}
+    private int sides;
+}
+interface Drawable { void draw(); }
+"#,
+        );
+        assert_eq!(
+            qualifiers(&symbols, "Shape"),
+            ["public", "abstract", "generic"]
+        );
+        assert_eq!(
+            qualifiers(&symbols, "create"),
+            ["public", "static", "generic"]
+        );
+        assert_eq!(qualifiers(&symbols, "draw"), ["public", "abstract"]);
+    }
+
+    #[test]
+    fn test_cpp_qualifiers() {
+        let symbols = extract(
+            Language::Cpp,
+            r#"
+class Widget {
+    int id;
+public:
+    static Widget create();
+    virtual void draw() = 0;
+};
+template <typename T, int N> T make() {}
+static int helper() {}
+"#,
+        );
+        assert!(qualifiers(&symbols, "id").is_empty());
+        assert_eq!(qualifiers(&symbols, "create"), ["public", "static"]);
+        assert_eq!(qualifiers(&symbols, "draw"), ["public", "abstract"]);
+        assert_eq!(qualifiers(&symbols, "make"), ["public", "generic"]);
+        assert_eq!(qualifiers(&symbols, "helper"), ["static"]);
+    }
+
+    #[test]
+    fn test_has_qualifier() {
+        let qualifiers = SymbolQualifiers {
+            is_public: true,
+            generic_params: 1,
+            ..SymbolQualifiers::default()
+        };
+        assert!(qualifiers.has("PUBLIC"));
+        assert!(qualifiers.has("generic"));
+        assert!(!qualifiers.has("async"));
+        assert!(!qualifiers.has("unknown"));
+    }
+}
@@ -0,0 +1,655 @@
+use crate::language::Language;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    /// `impl Trait for Type`, `class A implements I`
+    Implements,
+    /// Base class, super-interface, supertrait or embedded Go interface
+    Extends,
+    /// Go method declared on a receiver type, used to infer interface satisfaction
+    HasMethod,
+    /// Method required by a Go interface
+    RequiresMethod,
+    /// JSX element of a component rendered by another component
+    Renders,
+}
+
+impl RelationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationKind::Implements => "implements",
+            RelationKind::Extends => "extends",
+            RelationKind::HasMethod => "has_method",
+            RelationKind::RequiresMethod => "requires_method",
+            RelationKind::Renders => "renders",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "implements" => Some(RelationKind::Implements),
+            "extends" => Some(RelationKind::Extends),
+            "has_method" => Some(RelationKind::HasMethod),
+            "requires_method" => Some(RelationKind::RequiresMethod),
+            "renders" => Some(RelationKind::Renders),
+            _ => None,
+        }
+    }
+}
+
+/// A relationship between a type and a trait, interface, base type or method,
+/// or between two React components.
+///
+/// Names are unqualified and without type arguments: `impl fmt::Display for
+/// Wrapper<T>` is recorded as `Wrapper implements Display`, and
+/// `<ui.Button>` in `function Toolbar()` as `Toolbar renders Button`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeRelation {
+    /// The implementing, extending or receiver type, or the rendering component
+    pub type_name: String,
+    pub kind: RelationKind,
+    /// The trait, interface, base type, method or rendered component name
+    pub target: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+pub struct RelationExtractor;
+
+impl RelationExtractor {
+    pub fn extract(tree: &Tree, source: &str, language: Language) -> Result<Vec<TypeRelation>> {
+        let mut relations = Vec::new();
+        Self::extract_recursive(&tree.root_node(), source, language, &mut relations);
+        Ok(relations)
+    }
+
+    fn extract_recursive(
+        node: &Node,
+        source: &str,
+        language: Language,
+        relations: &mut Vec<TypeRelation>,
+    ) {
+        match language {
+            Language::Rust => Self::extract_rust_relations(node, source, relations),
+            Language::TypeScript | Language::JavaScript => {
+                Self::extract_ts_relations(node, source, relations)
+            }
+            Language::Java => Self::extract_java_relations(node, source, relations),
+            Language::Go => Self::extract_go_relations(node, source, relations),
+            _ => return,
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::extract_recursive(&child, source, language, relations);
+        }
+    }
+
+    fn extract_rust_relations(node: &Node, source: &str, relations: &mut Vec<TypeRelation>) {
+        match node.kind() {
+            // impl Trait for Type
+            "impl_item" => {
+                let (Some(trait_node), Some(type_node)) = (
+                    node.child_by_field_name("trait"),
+                    node.child_by_field_name("type"),
+                ) else {
+                    return;
+                };
+                if let (Some(type_name), Some(target)) = (
+                    Self::type_name(&type_node, source),
+                    Self::type_name(&trait_node, source),
+                ) {
+                    relations.push(Self::relation(
+                        node,
+                        type_name,
+                        RelationKind::Implements,
+                        target,
+                    ));
+                }
+            }
+            // trait Sub: Super + Other
+            "trait_item" => {
+                let (Some(name), Some(bounds)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("bounds"),
+                ) else {
+                    return;
+                };
+                let name = Self::text(&name, source);
+                Self::push_all(
+                    node,
+                    &name,
+                    RelationKind::Extends,
+                    &bounds,
+                    source,
+                    relations,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn extract_ts_relations(node: &Node, source: &str, relations: &mut Vec<TypeRelation>) {
+        match node.kind() {
+            "class_declaration" | "abstract_class_declaration" | "class" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = Self::text(&name, source);
+
+                let mut cursor = node.walk();
+                let Some(heritage) = node
+                    .named_children(&mut cursor)
+                    .find(|c| c.kind() == "class_heritage")
+                else {
+                    return;
+                };
+
+                let mut cursor = heritage.walk();
+                for clause in heritage.named_children(&mut cursor) {
+                    match clause.kind() {
+                        // TypeScript: class_heritage (extends_clause value: B)
+                        "extends_clause" => {
+                            if let Some(target) = clause
+                                .child_by_field_name("value")
+                                .and_then(|v| Self::type_name(&v, source))
+                            {
+                                relations.push(Self::relation(
+                                    node,
+                                    name.clone(),
+                                    RelationKind::Extends,
+                                    target,
+                                ));
+                            }
+                        }
+                        "implements_clause" => Self::push_all(
+                            node,
+                            &name,
+                            RelationKind::Implements,
+                            &clause,
+                            source,
+                            relations,
+                        ),
+                        // JavaScript: class_heritage (identifier)
+                        _ => {
+                            if let Some(target) = Self::type_name(&clause, source) {
+                                relations.push(Self::relation(
+                                    node,
+                                    name.clone(),
+                                    RelationKind::Extends,
+                                    target,
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            // <Button label="ok" />, <Layout>...</Layout>
+            "jsx_opening_element" | "jsx_self_closing_element" => {
+                let Some(target) = node
+                    .child_by_field_name("name")
+                    .and_then(|n| Self::component_name(&n, source))
+                else {
+                    return;
+                };
+                if let Some(component) = Self::enclosing_component(node, source) {
+                    relations.push(Self::relation(
+                        node,
+                        component,
+                        RelationKind::Renders,
+                        target,
+                    ));
+                }
+            }
+            "interface_declaration" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = Self::text(&name, source);
+
+                let mut cursor = node.walk();
+                for clause in node.named_children(&mut cursor) {
+                    if clause.kind() == "extends_type_clause" {
+                        Self::push_all(
+                            node,
+                            &name,
+                            RelationKind::Extends,
+                            &clause,
+                            source,
+                            relations,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn extract_java_relations(node: &Node, source: &str, relations: &mut Vec<TypeRelation>) {
+        if !matches!(
+            node.kind(),
+            "class_declaration"
+                | "interface_declaration"
+                | "enum_declaration"
+                | "record_declaration"
+        ) {
+            return;
+        }
+        let Some(name) = node.child_by_field_name("name") else {
+            return;
+        };
+        let name = Self::text(&name, source);
+
+        let mut cursor = node.walk();
+        for clause in node.named_children(&mut cursor) {
+            let kind = match clause.kind() {
+                "superclass" | "extends_interfaces" => RelationKind::Extends,
+                "super_interfaces" => RelationKind::Implements,
+                _ => continue,
+            };
+            // The types are either direct children or wrapped in a type_list
+            let mut inner = clause.walk();
+            for child in clause.named_children(&mut inner) {
+                if child.kind() == "type_list" {
+                    Self::push_all(node, &name, kind, &child, source, relations);
+                } else if let Some(target) = Self::type_name(&child, source) {
+                    relations.push(Self::relation(node, name.clone(), kind, target));
+                }
+            }
+        }
+    }
+
+    /// Go has no implements clause: record the methods of each receiver type
+    /// and the methods required by each interface, so that implementations can
+    /// be inferred from method sets across files.
+    fn extract_go_relations(node: &Node, source: &str, relations: &mut Vec<TypeRelation>) {
+        match node.kind() {
+            // type Name interface { Method(); Embedded }
+            "type_spec" => {
+                let (Some(name), Some(interface)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("type"),
+                ) else {
+                    return;
+                };
+                if interface.kind() != "interface_type" {
+                    return;
+                }
+                let name = Self::text(&name, source);
+
+                let mut cursor = interface.walk();
+                for elem in interface.named_children(&mut cursor) {
+                    match elem.kind() {
+                        "method_elem" | "method_spec" => {
+                            if let Some(method) = elem.child_by_field_name("name") {
+                                relations.push(Self::relation(
+                                    &elem,
+                                    name.clone(),
+                                    RelationKind::RequiresMethod,
+                                    Self::text(&method, source),
+                                ));
+                            }
+                        }
+                        // Embedded interface; unions and ~T constraints are not relations
+                        "type_elem" if elem.named_child_count() == 1 => {
+                            if let Some(target) = elem
+                                .named_child(0)
+                                .and_then(|t| Self::type_name(&t, source))
+                            {
+                                relations.push(Self::relation(
+                                    &elem,
+                                    name.clone(),
+                                    RelationKind::Extends,
+                                    target,
+                                ));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // func (r *Receiver) Method()
+            "method_declaration" => {
+                let (Some(receiver), Some(method)) = (
+                    node.child_by_field_name("receiver"),
+                    node.child_by_field_name("name"),
+                ) else {
+                    return;
+                };
+                let mut cursor = receiver.walk();
+                let receiver_type = receiver
+                    .named_children(&mut cursor)
+                    .find_map(|param| param.child_by_field_name("type"))
+                    .and_then(|t| Self::type_name(&t, source));
+                if let Some(receiver_type) = receiver_type {
+                    relations.push(Self::relation(
+                        node,
+                        receiver_type,
+                        RelationKind::HasMethod,
+                        Self::text(&method, source),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Component named by a JSX element: `Button` for `<Button>` and
+    /// `<ui.Button>`. Lowercase names are HTML elements (`<div>`) and
+    /// `None` is returned for them.
+    fn component_name(node: &Node, source: &str) -> Option<String> {
+        let name = match node.kind() {
+            "identifier" => Self::text(node, source),
+            "member_expression" | "nested_identifier" => {
+                let last = node.named_child(node.named_child_count().checked_sub(1)?)?;
+                return Some(Self::text(&last, source));
+            }
+            _ => return None,
+        };
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+            .then_some(name)
+    }
+
+    /// Name of the component a JSX element belongs to: the nearest enclosing
+    /// function, variable (`const Card = () => ...`) or class whose name is
+    /// capitalized, as React requires of components. Callbacks and helpers
+    /// such as `items.map(item => <Row />)` are attributed to the component
+    /// they are declared in.
+    fn enclosing_component(node: &Node, source: &str) -> Option<String> {
+        let mut current = node.parent();
+        while let Some(parent) = current {
+            if matches!(
+                parent.kind(),
+                "function_declaration"
+                    | "generator_function_declaration"
+                    | "variable_declarator"
+                    | "class_declaration"
+                    | "abstract_class_declaration"
+                    | "class"
+            ) && let Some(name) = parent.child_by_field_name("name")
+                && matches!(name.kind(), "identifier" | "type_identifier")
+            {
+                let name = Self::text(&name, source);
+                if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    return Some(name);
+                }
+            }
+            current = parent.parent();
+        }
+        None
+    }
+
+    /// Push a relation to every type named among the children of `list`.
+    fn push_all(
+        node: &Node,
+        type_name: &str,
+        kind: RelationKind,
+        list: &Node,
+        source: &str,
+        relations: &mut Vec<TypeRelation>,
+    ) {
+        let mut cursor = list.walk();
+        for child in list.named_children(&mut cursor) {
+            if let Some(target) = Self::type_name(&child, source) {
+                relations.push(Self::relation(node, type_name.to_string(), kind, target));
+            }
+        }
+    }
+
+    /// Unqualified name of a type node, without type arguments or references.
+    /// Returns `None` for nodes that do not name a type (e.g. `mixin(Base)`).
+    fn type_name(node: &Node, source: &str) -> Option<String> {
+        match node.kind() {
+            "type_identifier" | "identifier" => Some(Self::text(node, source)),
+            // a::B, ns.B, pkg.B, a.b.B
+            "scoped_type_identifier" | "nested_type_identifier" | "qualified_type" => node
+                .child_by_field_name("name")
+                .map(|n| Self::text(&n, source)),
+            "member_expression" => node
+                .child_by_field_name("property")
+                .map(|n| Self::text(&n, source)),
+            "scoped_identifier" => node
+                .child_by_field_name("name")
+                .map(|n| Self::text(&n, source)),
+            // B<T>, &B, *B
+            "generic_type" | "reference_type" | "pointer_type" => {
+                let inner = node
+                    .child_by_field_name("type")
+                    .or_else(|| node.child_by_field_name("name"))
+                    .or_else(|| node.named_child(0))?;
+                Self::type_name(&inner, source)
+            }
+            _ => None,
+        }
+    }
+
+    fn relation(
+        node: &Node,
+        type_name: String,
+        kind: RelationKind,
+        target: String,
+    ) -> TypeRelation {
+        TypeRelation {
+            type_name,
+            kind,
+            target,
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+        }
+    }
+
+    fn text(node: &Node, source: &str) -> String {
+        source[node.start_byte()..node.end_byte()].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(language: Language, source: &str) -> Vec<(String, RelationKind, String)> {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        RelationExtractor::extract(&tree, source, language)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.type_name, r.kind, r.target))
+            .collect()
+    }
+
+    fn rel(type_name: &str, kind: RelationKind, target: &str) -> (String, RelationKind, String) {
+        (type_name.to_string(), kind, target.to_string())
+    }
+
+    #[test]
+    fn test_extract_rust_relations() {
+        let source = r#"
+impl<T: Clone> fmt::Display for Wrapper<T> {}
+impl Drop for &Guard {}
+impl Config {}
+trait Shape: Debug + Clone {}
+"#;
+        assert_eq!(
+            extract(Language::Rust, source),
+            vec![
+                rel("Wrapper", RelationKind::Implements, "Display"),
+                rel("Guard", RelationKind::Implements, "Drop"),
+                rel("Shape", RelationKind::Extends, "Debug"),
+                rel("Shape", RelationKind::Extends, "Clone"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_typescript_relations() {
+        let source = r#"
+class Service extends Base<Config> implements Disposable, events.Emitter {}
+abstract class Repo extends Store {}
+interface Reader extends Closer, Source<string> {}
+"#;
+        assert_eq!(
+            extract(Language::TypeScript, source),
+            vec![
+                rel("Service", RelationKind::Extends, "Base"),
+                rel("Service", RelationKind::Implements, "Disposable"),
+                rel("Service", RelationKind::Implements, "Emitter"),
+                rel("Repo", RelationKind::Extends, "Store"),
+                rel("Reader", RelationKind::Extends, "Closer"),
+                rel("Reader", RelationKind::Extends, "Source"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_javascript_relations() {
+        let source = r#"
+class Button extends Component {}
+class Mixed extends mixin(Base) {}
+"#;
+        assert_eq!(
+            extract(Language::JavaScript, source),
+            vec![rel("Button", RelationKind::Extends, "Component")]
+        );
+    }
+
+    #[test]
+    fn test_extract_tsx_component_renders() {
+        let source = r#"
+export function Toolbar({ items }: Props) {
+    return (
+        <div className="toolbar">
+            <ui.Button label="save" />
+            {items.map(item => <ToolbarItem key={item.id} {...item} />)}
+        </div>
+    );
+}
+
+const Page = memo(() => <Layout><Toolbar items={[]} /></Layout>);
+
+class Legacy extends Component {
+    render() {
+        return <Page />;
+    }
+}
+
+const helper = () => <Orphan />;
+"#;
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support
+            .parse_file(
+                Language::TypeScript,
+                std::path::Path::new("src/Toolbar.tsx"),
+                source,
+            )
+            .unwrap();
+        let relations: Vec<_> = RelationExtractor::extract(&tree, source, Language::TypeScript)
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.kind == RelationKind::Renders)
+            .map(|r| (r.type_name, r.target, r.start_line))
+            .collect();
+        let renders =
+            |component: &str, target: &str, line| (component.to_string(), target.to_string(), line);
+        assert_eq!(
+            relations,
+            vec![
+                renders("Toolbar", "Button", 5),
+                renders("Toolbar", "ToolbarItem", 6),
+                renders("Page", "Layout", 11),
+                renders("Page", "Toolbar", 11),
+                renders("Legacy", "Page", 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_jsx_component_renders() {
+        let source = "function App() {\n  return <Router><Home /></Router>;\n}\n";
+        assert_eq!(
+            extract(Language::JavaScript, source),
+            vec![
+                rel("App", RelationKind::Renders, "Router"),
+                rel("App", RelationKind::Renders, "Home"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_java_relations() {
+        let source = r#"
+public class ArrayList<E> extends AbstractList<E> implements List<E>, RandomAccess {}
+interface Deque<E> extends Queue<E> {}
+enum Color implements Paint {}
+"#;
+        assert_eq!(
+            extract(Language::Java, source),
+            vec![
+                rel("ArrayList", RelationKind::Extends, "AbstractList"),
+                rel("ArrayList", RelationKind::Implements, "List"),
+                rel("ArrayList", RelationKind::Implements, "RandomAccess"),
+                rel("Deque", RelationKind::Extends, "Queue"),
+                rel("Color", RelationKind::Implements, "Paint"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_go_method_sets() {
+        let source = r#"
+package store
+
+type ReadCloser interface {
+    Read(p []byte) (int, error)
+    io.Closer
+}
+
+type File struct{}
+
+func (f *File) Read(p []byte) (int, error) { return 0, nil }
+func (f File) Close() error { return nil }
+func (c *Cache[K]) Close() error { return nil }
+func helper() {}
+"#;
+        assert_eq!(
+            extract(Language::Go, source),
+            vec![
+                rel("ReadCloser", RelationKind::RequiresMethod, "Read"),
+                rel("ReadCloser", RelationKind::Extends, "Closer"),
+                rel("File", RelationKind::HasMethod, "Read"),
+                rel("File", RelationKind::HasMethod, "Close"),
+                rel("Cache", RelationKind::HasMethod, "Close"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relation_kind_round_trip() {
+        for kind in [
+            RelationKind::Implements,
+            RelationKind::Extends,
+            RelationKind::HasMethod,
+            RelationKind::RequiresMethod,
+            RelationKind::Renders,
+        ] {
+            assert_eq!(RelationKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(RelationKind::parse("uses"), None);
+    }
+
+    #[test]
+    fn test_relation_line_numbers() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = "struct A;\n\nimpl Clone for A {\n    fn clone(&self) -> A { A }\n}\n";
+        let tree = support.parse(Language::Rust, source).unwrap();
+        let relations = RelationExtractor::extract(&tree, source, Language::Rust).unwrap();
+
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].start_line, 3);
+        assert_eq!(relations[0].end_line, 5);
+    }
+}
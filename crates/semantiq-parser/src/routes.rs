@@ -0,0 +1,227 @@
+//! HTTP routes of route entry points (see [`crate::entrypoints`]), and how
+//! a request such as `POST /api/users/42` is matched against them.
+//!
+//! Route paths keep the syntax of their framework: parameters are written
+//! `:id` (Express, axum 0.6), `{id}` (axum, Spring, ASP.NET, Go),
+//! `<int:id>` (Flask) or `[id]` (Next.js), and match any segment; catch-all
+//! parameters (`*`, `*rest`, `{*rest}`, `{rest...}`, `<path:rest>`) match
+//! the rest of the path.
+
+use crate::entrypoints::{Entrypoint, EntrypointKind};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Methods accepted in route texts such as `POST /api/users`
+const METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
+/// Quotes, brackets and punctuation around a route named in free text
+const DELIMITERS: &[char] = &['"', '\'', '`', '(', ')', '?', '!', ',', '.', ';', ':'];
+
+/// Method and path of an HTTP route
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HttpRoute {
+    /// Uppercase method, `None` for routes handling every method
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Path starting with `/`
+    pub path: String,
+}
+
+/// How a request path matches a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteMatch {
+    /// The whole path matches the route
+    Exact,
+    /// The path only ends with the route, which a router may mount under
+    /// the rest of the path (`app.use("/api", router)`)
+    Suffix,
+}
+
+impl HttpRoute {
+    pub fn new(method: Option<&str>, path: &str) -> Self {
+        Self {
+            method: method.map(str::to_uppercase),
+            path: normalize_path(path),
+        }
+    }
+
+    /// Parse `POST /api/users` or `/api/users` (any method). Methods are
+    /// case-insensitive.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (method, path) = match text.split_once(char::is_whitespace) {
+            Some((method, path)) if is_method(method) => (Some(method), path.trim()),
+            _ => (None, text),
+        };
+        if path.is_empty() || path.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Self::new(method, path))
+    }
+
+    /// Find a route named in free text, such as `where is POST /api/users
+    /// handled`: a method followed by a path starting with `/`.
+    pub fn find(text: &str) -> Option<Self> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        words.windows(2).find_map(|pair| {
+            let method = pair[0].trim_start_matches(DELIMITERS);
+            let path = pair[1].trim_end_matches(DELIMITERS);
+            (is_method(method) && path.starts_with('/') && path.len() > 1)
+                .then(|| Self::new(Some(method), path))
+        })
+    }
+
+    /// Route of a route entry point, whose name is `GET /users` or `/users`
+    pub fn of_entrypoint(entrypoint: &Entrypoint) -> Option<Self> {
+        if entrypoint.kind != EntrypointKind::Route {
+            return None;
+        }
+        Self::parse(&entrypoint.name)
+    }
+
+    /// Whether a request for `method` (any when `None`) and `path` is
+    /// handled by this route, and how its path matches.
+    pub fn matches(&self, method: Option<&str>, path: &str) -> Option<RouteMatch> {
+        if let (Some(expected), Some(method)) = (&self.method, method)
+            && !expected.eq_ignore_ascii_case(method)
+        {
+            return None;
+        }
+
+        let route = segments(&self.path);
+        let path = normalize_path(path);
+        let request = segments(&path);
+        if segments_match(&route, &request) {
+            return Some(RouteMatch::Exact);
+        }
+        // A router mounted under a prefix only declares the end of the path
+        if !route.is_empty()
+            && (1..request.len()).any(|start| segments_match(&route, &request[start..]))
+        {
+            return Some(RouteMatch::Suffix);
+        }
+        None
+    }
+}
+
+impl fmt::Display for HttpRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.method {
+            Some(method) => write!(f, "{} {}", method, self.path),
+            None => f.write_str(&self.path),
+        }
+    }
+}
+
+fn is_method(word: &str) -> bool {
+    METHODS.iter().any(|m| m.eq_ignore_ascii_case(word))
+}
+
+/// Path with a leading `/`, without query string, fragment or trailing `/`
+fn normalize_path(path: &str) -> String {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    format!("/{}", path.trim_matches('/'))
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+fn segments_match(route: &[&str], request: &[&str]) -> bool {
+    match (route.split_first(), request.split_first()) {
+        (Some((segment, _)), _) if is_catch_all(segment) => true,
+        (None, None) => true,
+        (Some((segment, route)), Some((part, request))) => {
+            (is_parameter(segment) || is_parameter(part) || segment == part)
+                && segments_match(route, request)
+        }
+        _ => false,
+    }
+}
+
+/// `:id`, `{id}`, `<int:id>`, `[id]`
+fn is_parameter(segment: &str) -> bool {
+    segment.starts_with(':')
+        || (segment.starts_with('{') && segment.ends_with('}'))
+        || (segment.starts_with('<') && segment.ends_with('>'))
+        || (segment.starts_with('[') && segment.ends_with(']'))
+}
+
+/// `*`, `*rest`, `{*rest}`, `{rest...}`, `<path:rest>`, `[...slug]`
+fn is_catch_all(segment: &str) -> bool {
+    segment.starts_with('*')
+        || segment.starts_with("{*")
+        || segment.ends_with("...}")
+        || segment.starts_with("<path:")
+        || segment.starts_with("[...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_find() {
+        let route = HttpRoute::parse("post /api/users/").unwrap();
+        assert_eq!(route.method.as_deref(), Some("POST"));
+        assert_eq!(route.path, "/api/users");
+        assert_eq!(route.to_string(), "POST /api/users");
+        assert_eq!(HttpRoute::parse("users").unwrap().to_string(), "/users");
+        assert_eq!(HttpRoute::parse("  "), None);
+
+        assert_eq!(
+            HttpRoute::find("where is POST /api/users handled?"),
+            Some(HttpRoute::new(Some("POST"), "/api/users"))
+        );
+        assert_eq!(
+            HttpRoute::find("who handles `GET /items/42`?"),
+            Some(HttpRoute::new(Some("GET"), "/items/42"))
+        );
+        assert_eq!(HttpRoute::find("get the / of a path"), None);
+        assert_eq!(HttpRoute::find("parse_config"), None);
+    }
+
+    #[test]
+    fn test_matches_parameters() {
+        let route = HttpRoute::new(Some("GET"), "/users/:id");
+        assert_eq!(
+            route.matches(Some("get"), "/users/42"),
+            Some(RouteMatch::Exact)
+        );
+        assert_eq!(route.matches(None, "/users/{id}"), Some(RouteMatch::Exact));
+        assert_eq!(route.matches(Some("POST"), "/users/42"), None);
+        assert_eq!(route.matches(Some("GET"), "/users"), None);
+        assert_eq!(route.matches(Some("GET"), "/users/42/posts"), None);
+
+        for path in ["/users/{id}", "/users/<int:id>", "/users/[id]"] {
+            let route = HttpRoute::new(None, path);
+            assert_eq!(
+                route.matches(Some("DELETE"), "/users/7"),
+                Some(RouteMatch::Exact)
+            );
+        }
+
+        let files = HttpRoute::new(Some("GET"), "/files/{*path}");
+        assert_eq!(
+            files.matches(Some("GET"), "/files/a/b.txt?raw=1"),
+            Some(RouteMatch::Exact)
+        );
+    }
+
+    #[test]
+    fn test_matches_mounted_routers() {
+        let route = HttpRoute::new(Some("POST"), "/users");
+        assert_eq!(
+            route.matches(Some("POST"), "/api/users"),
+            Some(RouteMatch::Suffix)
+        );
+        assert_eq!(route.matches(Some("POST"), "/api/users/1"), None);
+        // The root route only matches the root
+        assert_eq!(HttpRoute::new(None, "/").matches(None, "/api"), None);
+        assert_eq!(
+            HttpRoute::new(None, "/").matches(None, "/"),
+            Some(RouteMatch::Exact)
+        );
+    }
+}
@@ -0,0 +1,533 @@
+//! Symbol kinds of the node kinds of each language
+//!
+//! Each built-in language maps some node kinds of its grammar to the kind
+//! of symbol they declare. `[symbol_kinds.<language>]` in `semantiq.toml`
+//! overrides these mappings, e.g. for a grammar version that names its nodes
+//! differently, or to classify declarations the way a team reads them:
+//!
+//! ```toml
+//! [symbol_kinds.ruby]
+//! constant = "type"
+//! singleton_class = "class"
+//!
+//! [symbol_kinds.html]
+//! element = "none"
+//! ```
+//!
+//! A node kind mapped to `none` declares no symbol. Overrides only change
+//! symbols: chunk boundaries stay those of the language. They are
+//! registered for the whole process, like custom languages, whose symbol
+//! kinds are set in their own `[languages.symbols]` table.
+
+use crate::language::Language;
+use crate::plugins;
+use crate::symbols::SymbolKind;
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use tracing::debug;
+
+/// Override value removing the mapping of a node kind
+pub const NO_SYMBOL: &str = "none";
+
+/// Overrides of a configuration file: symbol kind (or [`NO_SYMBOL`]) by
+/// node kind, by language name
+pub type SymbolKindOverrides = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Mapping of a node kind, as applied by the parser
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolKindMapping {
+    pub node_kind: String,
+    /// Kind of the symbol the node declares, `None` for no symbol
+    pub kind: Option<SymbolKind>,
+    /// Kind of the built-in table, when the mapping is overridden
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builtin: Option<SymbolKind>,
+    pub overridden: bool,
+}
+
+/// A validated override
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Override {
+    language: Language,
+    node_kind: String,
+    kind: Option<SymbolKind>,
+}
+
+/// Registered overrides, looked up for every node of every parsed file
+static OVERRIDES: RwLock<Vec<Override>> = RwLock::new(Vec::new());
+
+const RUST: &[(&str, SymbolKind)] = &[
+    ("function_item", SymbolKind::Function),
+    ("struct_item", SymbolKind::Struct),
+    ("enum_item", SymbolKind::Enum),
+    ("trait_item", SymbolKind::Trait),
+    ("impl_item", SymbolKind::Class),
+    ("mod_item", SymbolKind::Module),
+    ("const_item", SymbolKind::Constant),
+    ("static_item", SymbolKind::Constant),
+    ("type_item", SymbolKind::Type),
+    ("use_declaration", SymbolKind::Import),
+];
+
+const TYPESCRIPT: &[(&str, SymbolKind)] = &[
+    ("function_declaration", SymbolKind::Function),
+    ("arrow_function", SymbolKind::Function),
+    ("method_definition", SymbolKind::Method),
+    ("class_declaration", SymbolKind::Class),
+    ("interface_declaration", SymbolKind::Interface),
+    ("enum_declaration", SymbolKind::Enum),
+    ("type_alias_declaration", SymbolKind::Type),
+    ("import_statement", SymbolKind::Import),
+    // variable_declaration = var, lexical_declaration = const/let
+    ("variable_declaration", SymbolKind::Variable),
+    ("lexical_declaration", SymbolKind::Variable),
+];
+
+const JAVASCRIPT: &[(&str, SymbolKind)] = &[
+    ("function_declaration", SymbolKind::Function),
+    ("arrow_function", SymbolKind::Function),
+    ("method_definition", SymbolKind::Method),
+    ("class_declaration", SymbolKind::Class),
+    ("import_statement", SymbolKind::Import),
+    ("variable_declaration", SymbolKind::Variable),
+    ("lexical_declaration", SymbolKind::Variable),
+];
+
+const PYTHON: &[(&str, SymbolKind)] = &[
+    ("function_definition", SymbolKind::Function),
+    ("class_definition", SymbolKind::Class),
+    ("import_statement", SymbolKind::Import),
+    ("import_from_statement", SymbolKind::Import),
+];
+
+const GO: &[(&str, SymbolKind)] = &[
+    ("function_declaration", SymbolKind::Function),
+    ("method_declaration", SymbolKind::Method),
+    ("type_declaration", SymbolKind::Type),
+    ("struct_type", SymbolKind::Struct),
+    ("interface_type", SymbolKind::Interface),
+    ("const_declaration", SymbolKind::Constant),
+    ("var_declaration", SymbolKind::Variable),
+    ("import_declaration", SymbolKind::Import),
+];
+
+const JAVA: &[(&str, SymbolKind)] = &[
+    ("method_declaration", SymbolKind::Method),
+    ("class_declaration", SymbolKind::Class),
+    ("interface_declaration", SymbolKind::Interface),
+    ("enum_declaration", SymbolKind::Enum),
+    ("import_declaration", SymbolKind::Import),
+    ("field_declaration", SymbolKind::Variable),
+];
+
+const C: &[(&str, SymbolKind)] = &[
+    ("function_definition", SymbolKind::Function),
+    ("struct_specifier", SymbolKind::Struct),
+    ("enum_specifier", SymbolKind::Enum),
+    ("type_definition", SymbolKind::Type),
+    ("preproc_include", SymbolKind::Import),
+];
+
+/// `template_declaration` is not mapped: it wraps the templated class or
+/// function, which is extracted with the template header included.
+const CPP: &[(&str, SymbolKind)] = &[
+    ("function_definition", SymbolKind::Function),
+    ("class_specifier", SymbolKind::Class),
+    ("struct_specifier", SymbolKind::Struct),
+    ("union_specifier", SymbolKind::Struct),
+    ("enum_specifier", SymbolKind::Enum),
+    ("namespace_definition", SymbolKind::Module),
+    ("field_declaration", SymbolKind::Variable),
+    ("type_definition", SymbolKind::Type),
+    ("alias_declaration", SymbolKind::Type),
+];
+
+const PHP: &[(&str, SymbolKind)] = &[
+    ("function_definition", SymbolKind::Function),
+    ("method_declaration", SymbolKind::Method),
+    ("class_declaration", SymbolKind::Class),
+    ("interface_declaration", SymbolKind::Interface),
+    ("trait_declaration", SymbolKind::Trait),
+    ("enum_declaration", SymbolKind::Enum),
+    ("namespace_definition", SymbolKind::Module),
+    ("const_declaration", SymbolKind::Constant),
+    ("namespace_use_declaration", SymbolKind::Import),
+];
+
+const RUBY: &[(&str, SymbolKind)] = &[
+    ("method", SymbolKind::Function),
+    ("singleton_method", SymbolKind::Function),
+    ("class", SymbolKind::Class),
+    ("module", SymbolKind::Module),
+    ("constant", SymbolKind::Constant),
+];
+
+const CSHARP: &[(&str, SymbolKind)] = &[
+    ("method_declaration", SymbolKind::Method),
+    ("local_function_statement", SymbolKind::Function),
+    ("class_declaration", SymbolKind::Class),
+    ("struct_declaration", SymbolKind::Struct),
+    ("interface_declaration", SymbolKind::Interface),
+    ("enum_declaration", SymbolKind::Enum),
+    ("namespace_declaration", SymbolKind::Module),
+    ("field_declaration", SymbolKind::Variable),
+    ("property_declaration", SymbolKind::Variable),
+    ("using_directive", SymbolKind::Import),
+];
+
+/// Interfaces are `class_declaration` nodes in this grammar
+const KOTLIN: &[(&str, SymbolKind)] = &[
+    ("function_declaration", SymbolKind::Function),
+    ("class_declaration", SymbolKind::Class),
+    ("object_declaration", SymbolKind::Class),
+    ("enum_class_body", SymbolKind::Enum),
+    ("property_declaration", SymbolKind::Variable),
+];
+
+const SCALA: &[(&str, SymbolKind)] = &[
+    ("function_definition", SymbolKind::Function),
+    ("class_definition", SymbolKind::Class),
+    ("object_definition", SymbolKind::Class),
+    ("trait_definition", SymbolKind::Trait),
+    ("enum_definition", SymbolKind::Enum),
+    ("type_definition", SymbolKind::Type),
+    ("val_definition", SymbolKind::Variable),
+    ("var_definition", SymbolKind::Variable),
+    ("import_declaration", SymbolKind::Import),
+];
+
+const HTML: &[(&str, SymbolKind)] = &[
+    ("element", SymbolKind::Variable),
+    ("script_element", SymbolKind::Module),
+    ("style_element", SymbolKind::Module),
+];
+
+const JSON: &[(&str, SymbolKind)] = &[
+    ("pair", SymbolKind::Variable),
+    ("object", SymbolKind::Struct),
+    ("array", SymbolKind::Variable),
+];
+
+const YAML: &[(&str, SymbolKind)] = &[
+    ("block_mapping_pair", SymbolKind::Variable),
+    ("block_mapping", SymbolKind::Struct),
+    ("block_sequence", SymbolKind::Variable),
+];
+
+const TOML: &[(&str, SymbolKind)] = &[
+    ("pair", SymbolKind::Variable),
+    ("table", SymbolKind::Struct),
+    ("table_array_element", SymbolKind::Struct),
+    ("array", SymbolKind::Variable),
+];
+
+const BASH: &[(&str, SymbolKind)] = &[
+    ("function_definition", SymbolKind::Function),
+    ("variable_assignment", SymbolKind::Variable),
+];
+
+const ELIXIR: &[(&str, SymbolKind)] = &[
+    ("call", SymbolKind::Function), // def, defp, defmodule
+    ("anonymous_function", SymbolKind::Function),
+    ("do_block", SymbolKind::Module),
+];
+
+const SQL: &[(&str, SymbolKind)] = &[
+    ("create_table", SymbolKind::Table),
+    ("alter_table", SymbolKind::Table),
+    ("create_view", SymbolKind::Table),
+    ("create_materialized_view", SymbolKind::Table),
+    ("column_definition", SymbolKind::Column),
+    ("create_index", SymbolKind::Index),
+    ("create_function", SymbolKind::Function),
+    ("create_type", SymbolKind::Type),
+];
+
+/// Built-in mappings of a language, empty for custom languages
+pub fn builtin(language: Language) -> &'static [(&'static str, SymbolKind)] {
+    match language {
+        Language::Rust => RUST,
+        Language::TypeScript => TYPESCRIPT,
+        Language::JavaScript => JAVASCRIPT,
+        Language::Python => PYTHON,
+        Language::Go => GO,
+        Language::Java => JAVA,
+        Language::C => C,
+        Language::Cpp => CPP,
+        Language::Php => PHP,
+        Language::Ruby => RUBY,
+        Language::CSharp => CSHARP,
+        Language::Kotlin => KOTLIN,
+        Language::Scala => SCALA,
+        Language::Html => HTML,
+        Language::Json => JSON,
+        Language::Yaml => YAML,
+        Language::Toml => TOML,
+        Language::Bash => BASH,
+        Language::Elixir => ELIXIR,
+        Language::Sql => SQL,
+        Language::Custom(_) => &[],
+    }
+}
+
+fn builtin_kind(language: Language, node_kind: &str) -> Option<SymbolKind> {
+    builtin(language)
+        .iter()
+        .find(|(kind, _)| *kind == node_kind)
+        .map(|(_, kind)| *kind)
+}
+
+/// Kind of the symbol a node of `node_kind` declares, overrides first
+pub(crate) fn symbol_kind(language: Language, node_kind: &str) -> Option<SymbolKind> {
+    if let Language::Custom(id) = language {
+        return plugins::get(id)?.symbols.get(node_kind).copied();
+    }
+
+    let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    match overrides
+        .iter()
+        .find(|o| o.language == language && o.node_kind == node_kind)
+    {
+        Some(o) => o.kind,
+        None => builtin_kind(language, node_kind),
+    }
+}
+
+/// Validate and register overrides. A node kind already overridden keeps
+/// its first registered kind.
+pub fn register_symbol_kinds(overrides: &SymbolKindOverrides) -> Result<()> {
+    let parsed = parse(overrides)?;
+
+    let mut registered = OVERRIDES.write().unwrap_or_else(|e| e.into_inner());
+    for o in parsed {
+        if registered
+            .iter()
+            .any(|r| r.language == o.language && r.node_kind == o.node_kind)
+        {
+            debug!(
+                "Symbol kind of {}.{} already overridden",
+                o.language.name(),
+                o.node_kind
+            );
+            continue;
+        }
+        registered.push(o);
+    }
+    Ok(())
+}
+
+fn parse(overrides: &SymbolKindOverrides) -> Result<Vec<Override>> {
+    let mut parsed = Vec::new();
+    for (name, mappings) in overrides {
+        let language = match Language::builtin_from_name(name) {
+            Some(language) => language,
+            None if plugins::find(|l| l.name == name).is_some() => bail!(
+                "Symbol kinds of {} are set in its [languages.symbols] table",
+                name
+            ),
+            None => bail!("Unknown language '{}' in [symbol_kinds]", name),
+        };
+
+        for (node_kind, kind) in mappings {
+            if !has_node_kind(language, node_kind) {
+                bail!(
+                    "The {} grammar has no '{}' node",
+                    language.name(),
+                    node_kind
+                );
+            }
+            let kind = match kind.as_str() {
+                NO_SYMBOL => None,
+                _ => Some(SymbolKind::from_name(kind).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown symbol kind '{}' for {}.{}, expected one of {} or {}",
+                        kind,
+                        language.name(),
+                        node_kind,
+                        SymbolKind::ALL.map(|k| k.as_str()).join(", "),
+                        NO_SYMBOL
+                    )
+                })?),
+            };
+            parsed.push(Override {
+                language,
+                node_kind: node_kind.clone(),
+                kind,
+            });
+        }
+    }
+    Ok(parsed)
+}
+
+/// Whether the grammar of `language` has named nodes of `node_kind`. `.tsx`
+/// files are parsed with the TSX grammar, so its nodes are TypeScript ones.
+fn has_node_kind(language: Language, node_kind: &str) -> bool {
+    let mut grammars: Vec<tree_sitter::Language> = language.grammar().into_iter().collect();
+    if language == Language::TypeScript {
+        grammars.push(tree_sitter_typescript::LANGUAGE_TSX.into());
+    }
+    grammars
+        .iter()
+        .any(|grammar| grammar.id_for_node_kind(node_kind, true) != 0)
+}
+
+/// Effective mappings of a language: the built-in table with the overrides
+/// merged over it, sorted by node kind
+pub fn effective(language: Language) -> Vec<SymbolKindMapping> {
+    let mut mappings: BTreeMap<String, SymbolKindMapping> = match language {
+        Language::Custom(id) => plugins::get(id)
+            .map(|custom| {
+                custom
+                    .symbols
+                    .iter()
+                    .map(|(node_kind, kind)| (node_kind.as_str(), *kind))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default(),
+        _ => builtin(language).to_vec(),
+    }
+    .into_iter()
+    .map(|(node_kind, kind)| {
+        let mapping = SymbolKindMapping {
+            node_kind: node_kind.to_string(),
+            kind: Some(kind),
+            builtin: None,
+            overridden: false,
+        };
+        (node_kind.to_string(), mapping)
+    })
+    .collect();
+
+    let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    for o in overrides.iter().filter(|o| o.language == language) {
+        mappings.insert(
+            o.node_kind.clone(),
+            SymbolKindMapping {
+                node_kind: o.node_kind.clone(),
+                kind: o.kind,
+                builtin: builtin_kind(language, &o.node_kind),
+                overridden: true,
+            },
+        );
+    }
+    mappings.into_values().collect()
+}
+
+/// Registered overrides as `language.node_kind=kind` pairs, sorted; empty
+/// without overrides. The index stores it to rebuild symbols when it changes.
+pub fn fingerprint() -> String {
+    let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    let mut pairs: Vec<String> = overrides
+        .iter()
+        .map(|o| {
+            format!(
+                "{}.{}={}",
+                o.language.name(),
+                o.node_kind,
+                o.kind.map_or(NO_SYMBOL, |k| k.as_str())
+            )
+        })
+        .collect();
+    pairs.sort();
+    pairs.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+    use crate::symbols::SymbolExtractor;
+
+    fn overrides(language: &str, pairs: &[(&str, &str)]) -> SymbolKindOverrides {
+        let mappings = pairs
+            .iter()
+            .map(|(node, kind)| (node.to_string(), kind.to_string()))
+            .collect();
+        BTreeMap::from([(language.to_string(), mappings)])
+    }
+
+    #[test]
+    fn test_builtin_node_kinds_exist() {
+        let mut missing = Vec::new();
+        for &language in LanguageSupport::supported_languages() {
+            for (node_kind, _) in builtin(language) {
+                if !has_node_kind(language, node_kind) {
+                    missing.push(format!("{}.{}", language.name(), node_kind));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "Unknown node kinds: {:?}", missing);
+    }
+
+    #[test]
+    fn test_parse_validates_overrides() {
+        let parsed = parse(&overrides("rb", &[("constant", "type"), ("class", "none")])).unwrap();
+        assert_eq!(
+            parsed,
+            [
+                Override {
+                    language: Language::Ruby,
+                    node_kind: "class".to_string(),
+                    kind: None,
+                },
+                Override {
+                    language: Language::Ruby,
+                    node_kind: "constant".to_string(),
+                    kind: Some(SymbolKind::Type),
+                },
+            ]
+        );
+
+        let error = |config| parse(&config).unwrap_err().to_string();
+        assert_eq!(
+            error(overrides("cobol", &[("paragraph", "function")])),
+            "Unknown language 'cobol' in [symbol_kinds]"
+        );
+        assert_eq!(
+            error(overrides("ruby", &[("konstant", "type")])),
+            "The ruby grammar has no 'konstant' node"
+        );
+        assert!(
+            error(overrides("ruby", &[("constant", "const")])).starts_with(
+                "Unknown symbol kind 'const' for ruby.constant, expected one of function, method,"
+            )
+        );
+        // JSX nodes belong to the TSX grammar
+        assert!(parse(&overrides("typescript", &[("jsx_element", "variable")])).is_ok());
+    }
+
+    #[test]
+    fn test_overrides_apply_to_extraction() {
+        // Bash nodes no other test overrides, the registry being process-wide
+        register_symbol_kinds(&overrides(
+            "bash",
+            &[
+                ("variable_assignment", "none"),
+                ("function_definition", "method"),
+            ],
+        ))
+        .unwrap();
+
+        let source = "NAME=world\ngreet() {\n  echo hi\n}\n";
+        let tree = LanguageSupport::new()
+            .unwrap()
+            .parse(Language::Bash, source)
+            .unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, Language::Bash).unwrap();
+        let kinds: Vec<(&str, SymbolKind)> =
+            symbols.iter().map(|s| (s.name.as_str(), s.kind)).collect();
+        assert_eq!(kinds, [("greet", SymbolKind::Method)]);
+
+        let mappings = effective(Language::Bash);
+        let assignment = mappings
+            .iter()
+            .find(|m| m.node_kind == "variable_assignment")
+            .unwrap();
+        assert_eq!(assignment.kind, None);
+        assert_eq!(assignment.builtin, Some(SymbolKind::Variable));
+        assert!(assignment.overridden);
+        assert!(fingerprint().contains("bash.variable_assignment=none"));
+    }
+}
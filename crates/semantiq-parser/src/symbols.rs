@@ -1,4 +1,9 @@
+use crate::conditions;
+use crate::config_keys::{is_config_language, key_segments};
 use crate::language::Language;
+use crate::plugins;
+use crate::qualifiers::SymbolQualifiers;
+use crate::symbol_kinds;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use tree_sitter::{Node, Tree};
@@ -18,9 +23,30 @@ pub enum SymbolKind {
     Constant,
     Type,
     Import,
+    Table,
+    Column,
+    Index,
 }
 
 impl SymbolKind {
+    pub const ALL: [SymbolKind; 15] = [
+        SymbolKind::Function,
+        SymbolKind::Method,
+        SymbolKind::Class,
+        SymbolKind::Struct,
+        SymbolKind::Enum,
+        SymbolKind::Interface,
+        SymbolKind::Trait,
+        SymbolKind::Module,
+        SymbolKind::Variable,
+        SymbolKind::Constant,
+        SymbolKind::Type,
+        SymbolKind::Import,
+        SymbolKind::Table,
+        SymbolKind::Column,
+        SymbolKind::Index,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             SymbolKind::Function => "function",
@@ -35,8 +61,16 @@ impl SymbolKind {
             SymbolKind::Constant => "constant",
             SymbolKind::Type => "type",
             SymbolKind::Import => "import",
+            SymbolKind::Table => "table",
+            SymbolKind::Column => "column",
+            SymbolKind::Index => "index",
         }
     }
+
+    /// Kind named `name`, as written by [`SymbolKind::as_str`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,9 +81,21 @@ pub struct Symbol {
     pub end_line: usize,
     pub start_byte: usize,
     pub end_byte: usize,
+    /// 0-based byte columns of the start and end of the symbol in their lines
+    #[serde(default)]
+    pub start_column: usize,
+    #[serde(default)]
+    pub end_column: usize,
     pub signature: Option<String>,
     pub doc_comment: Option<String>,
     pub parent: Option<String>,
+    /// Visibility, `async`, `static`, `abstract` and generic parameters
+    #[serde(default)]
+    pub qualifiers: SymbolQualifiers,
+    /// Conditions under which the symbol is compiled (`#[cfg(...)]`,
+    /// `#ifdef` regions, `//go:build`), e.g. `test` or `defined(_WIN32)`
+    #[serde(default)]
+    pub cfg_context: Option<String>,
 }
 
 pub struct SymbolExtractor;
@@ -72,7 +118,13 @@ impl SymbolExtractor {
         parent: Option<&str>,
     ) -> Result<()> {
         if let Some(symbol) = Self::node_to_symbol(node, source, language, parent) {
-            let parent_name = symbol.name.clone();
+            // Configuration keys are known by their path from the document root
+            let parent_name = match parent {
+                Some(parent) if is_config_language(language) => {
+                    format!("{}.{}", parent, symbol.name)
+                }
+                _ => symbol.name.clone(),
+            };
             symbols.push(symbol);
 
             // Extract children with this as parent
@@ -97,6 +149,13 @@ impl SymbolExtractor {
         language: Language,
         parent: Option<&str>,
     ) -> Option<Symbol> {
+        if language == Language::Cpp {
+            return Self::cpp_node_to_symbol(node, source, parent);
+        }
+        if language == Language::Sql {
+            return Self::sql_node_to_symbol(node, source, parent);
+        }
+
         let mut kind = Self::get_symbol_kind(node.kind(), language)?;
         let name = Self::extract_name(node, source, language)?;
 
@@ -112,9 +171,13 @@ impl SymbolExtractor {
         let end_line = node.end_position().row + 1;
         let start_byte = node.start_byte();
         let end_byte = node.end_byte();
+        let start_column = node.start_position().column;
+        let end_column = node.end_position().column;
 
         let signature = Self::extract_signature(node, source, language);
         let doc_comment = Self::extract_doc_comment(node, source);
+        let qualifiers = SymbolQualifiers::extract(node, source, language, &name);
+        let cfg_context = conditions::cfg_context(node, source, language);
 
         Some(Symbol {
             name,
@@ -123,230 +186,199 @@ impl SymbolExtractor {
             end_line,
             start_byte,
             end_byte,
+            start_column,
+            end_column,
             signature,
             doc_comment,
             parent: parent.map(String::from),
+            qualifiers,
+            cfg_context,
         })
     }
 
+    /// Kind of the symbol a node declares, from the built-in table of the
+    /// language with the overrides of `semantiq.toml` merged over it
     fn get_symbol_kind(node_kind: &str, language: Language) -> Option<SymbolKind> {
-        match language {
-            Language::Rust => Self::rust_symbol_kind(node_kind),
-            Language::TypeScript | Language::JavaScript => Self::ts_symbol_kind(node_kind),
-            Language::Python => Self::python_symbol_kind(node_kind),
-            Language::Go => Self::go_symbol_kind(node_kind),
-            Language::Java => Self::java_symbol_kind(node_kind),
-            Language::C | Language::Cpp => Self::c_symbol_kind(node_kind),
-            Language::Php => Self::php_symbol_kind(node_kind),
-            Language::Ruby => Self::ruby_symbol_kind(node_kind),
-            Language::CSharp => Self::csharp_symbol_kind(node_kind),
-            Language::Kotlin => Self::kotlin_symbol_kind(node_kind),
-            Language::Scala => Self::scala_symbol_kind(node_kind),
-            Language::Html => Self::html_symbol_kind(node_kind),
-            Language::Json => Self::json_symbol_kind(node_kind),
-            Language::Yaml => Self::yaml_symbol_kind(node_kind),
-            Language::Toml => Self::toml_symbol_kind(node_kind),
-            Language::Bash => Self::bash_symbol_kind(node_kind),
-            Language::Elixir => Self::elixir_symbol_kind(node_kind),
-        }
-    }
-
-    fn rust_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_item" => Some(SymbolKind::Function),
-            "struct_item" => Some(SymbolKind::Struct),
-            "enum_item" => Some(SymbolKind::Enum),
-            "trait_item" => Some(SymbolKind::Trait),
-            "impl_item" => Some(SymbolKind::Class),
-            "mod_item" => Some(SymbolKind::Module),
-            "const_item" => Some(SymbolKind::Constant),
-            "static_item" => Some(SymbolKind::Constant),
-            "type_item" => Some(SymbolKind::Type),
-            "use_declaration" => Some(SymbolKind::Import),
-            _ => None,
-        }
-    }
-
-    fn ts_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_declaration" | "arrow_function" => Some(SymbolKind::Function),
-            "method_definition" => Some(SymbolKind::Method),
-            "class_declaration" => Some(SymbolKind::Class),
-            "interface_declaration" => Some(SymbolKind::Interface),
-            "enum_declaration" => Some(SymbolKind::Enum),
-            "type_alias_declaration" => Some(SymbolKind::Type),
-            "import_statement" => Some(SymbolKind::Import),
-            // variable_declaration = var, lexical_declaration = const/let
-            "variable_declaration" | "lexical_declaration" => Some(SymbolKind::Variable),
-            _ => None,
-        }
+        symbol_kinds::symbol_kind(language, node_kind)
     }
 
-    fn python_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_definition" => Some(SymbolKind::Function),
-            "class_definition" => Some(SymbolKind::Class),
-            "import_statement" | "import_from_statement" => Some(SymbolKind::Import),
-            _ => None,
-        }
-    }
-
-    fn go_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_declaration" => Some(SymbolKind::Function),
-            "method_declaration" => Some(SymbolKind::Method),
-            "type_declaration" => Some(SymbolKind::Type),
-            "struct_type" => Some(SymbolKind::Struct),
-            "interface_type" => Some(SymbolKind::Interface),
-            "const_declaration" => Some(SymbolKind::Constant),
-            "var_declaration" => Some(SymbolKind::Variable),
-            "import_declaration" => Some(SymbolKind::Import),
-            _ => None,
-        }
-    }
-
-    fn java_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "method_declaration" => Some(SymbolKind::Method),
-            "class_declaration" => Some(SymbolKind::Class),
-            "interface_declaration" => Some(SymbolKind::Interface),
-            "enum_declaration" => Some(SymbolKind::Enum),
-            "import_declaration" => Some(SymbolKind::Import),
-            "field_declaration" => Some(SymbolKind::Variable),
-            _ => None,
-        }
-    }
-
-    fn c_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_definition" => Some(SymbolKind::Function),
-            "struct_specifier" => Some(SymbolKind::Struct),
-            "enum_specifier" => Some(SymbolKind::Enum),
-            "type_definition" => Some(SymbolKind::Type),
-            "preproc_include" => Some(SymbolKind::Import),
-            _ => None,
-        }
-    }
-
-    fn php_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_definition" => Some(SymbolKind::Function),
-            "method_declaration" => Some(SymbolKind::Method),
-            "class_declaration" => Some(SymbolKind::Class),
-            "interface_declaration" => Some(SymbolKind::Interface),
-            "trait_declaration" => Some(SymbolKind::Trait),
-            "enum_declaration" => Some(SymbolKind::Enum),
-            "namespace_definition" => Some(SymbolKind::Module),
-            "const_declaration" => Some(SymbolKind::Constant),
-            "namespace_use_declaration" => Some(SymbolKind::Import),
-            _ => None,
-        }
-    }
+    /// Build a SQL symbol. Statements are named after the object they create
+    /// or change, without its schema: `ALTER TABLE public.users ...` is a
+    /// `table` symbol named `users`, so a table is defined by its CREATE TABLE
+    /// and every later migration altering it. Columns and indexes belong to
+    /// their table.
+    fn sql_node_to_symbol(node: &Node, source: &str, parent: Option<&str>) -> Option<Symbol> {
+        let source_bytes = source.as_bytes();
+        let kind = Self::get_symbol_kind(node.kind(), Language::Sql)?;
+
+        let (name, parent) = match kind {
+            SymbolKind::Column => (
+                node.child_by_field_name("name")?
+                    .utf8_text(source_bytes)
+                    .ok()?
+                    .to_string(),
+                parent.map(String::from),
+            ),
+            SymbolKind::Index => {
+                // CREATE INDEX name ON table (...); unnamed indexes are skipped
+                let mut cursor = node.walk();
+                let name = node
+                    .children(&mut cursor)
+                    .find(|c| c.kind() == "identifier")?
+                    .utf8_text(source_bytes)
+                    .ok()?
+                    .to_string();
+                (name, Self::sql_object_name(node, source))
+            }
+            _ => (
+                Self::sql_object_name(node, source)?,
+                parent.map(String::from),
+            ),
+        };
 
-    fn ruby_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "method" => Some(SymbolKind::Function),
-            "singleton_method" => Some(SymbolKind::Function),
-            "class" => Some(SymbolKind::Class),
-            "module" => Some(SymbolKind::Module),
-            "constant" => Some(SymbolKind::Constant),
-            _ => None,
-        }
-    }
+        // Comments precede the enclosing statement rather than the node itself
+        let statement = node.parent().filter(|p| p.kind() == "statement");
 
-    fn csharp_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "method_declaration" => Some(SymbolKind::Method),
-            "local_function_statement" => Some(SymbolKind::Function),
-            "class_declaration" => Some(SymbolKind::Class),
-            "struct_declaration" => Some(SymbolKind::Struct),
-            "interface_declaration" => Some(SymbolKind::Interface),
-            "enum_declaration" => Some(SymbolKind::Enum),
-            "namespace_declaration" => Some(SymbolKind::Module),
-            "field_declaration" => Some(SymbolKind::Variable),
-            "property_declaration" => Some(SymbolKind::Variable),
-            "using_directive" => Some(SymbolKind::Import),
-            _ => None,
-        }
-    }
-
-    fn kotlin_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_declaration" => Some(SymbolKind::Function),
-            "class_declaration" => Some(SymbolKind::Class),
-            "object_declaration" => Some(SymbolKind::Class),
-            "interface_declaration" => Some(SymbolKind::Interface),
-            "enum_class_body" => Some(SymbolKind::Enum),
-            "property_declaration" => Some(SymbolKind::Variable),
-            "import_header" => Some(SymbolKind::Import),
-            _ => None,
-        }
+        Some(Symbol {
+            name,
+            kind,
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            start_column: node.start_position().column,
+            end_column: node.end_position().column,
+            signature: Self::extract_signature(node, source, Language::Sql),
+            doc_comment: Self::extract_doc_comment(&statement.unwrap_or(*node), source),
+            parent,
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
+        })
     }
 
-    fn scala_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_definition" => Some(SymbolKind::Function),
-            "class_definition" => Some(SymbolKind::Class),
-            "object_definition" => Some(SymbolKind::Class),
-            "trait_definition" => Some(SymbolKind::Trait),
-            "enum_definition" => Some(SymbolKind::Enum),
-            "type_definition" => Some(SymbolKind::Type),
-            "val_definition" | "var_definition" => Some(SymbolKind::Variable),
-            "import_declaration" => Some(SymbolKind::Import),
-            _ => None,
-        }
+    /// Name of the table, view or function a SQL statement refers to
+    pub(crate) fn sql_object_name(node: &Node, source: &str) -> Option<String> {
+        let mut cursor = node.walk();
+        let reference = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "object_reference")?;
+        reference
+            .child_by_field_name("name")?
+            .utf8_text(source.as_bytes())
+            .ok()
+            .map(String::from)
     }
 
-    fn html_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "element" => Some(SymbolKind::Variable),
-            "script_element" => Some(SymbolKind::Module),
-            "style_element" => Some(SymbolKind::Module),
-            _ => None,
-        }
-    }
+    /// Build a C++ symbol, resolving declarator names, member functions and
+    /// out-of-class `Class::method` definitions.
+    fn cpp_node_to_symbol(node: &Node, source: &str, parent: Option<&str>) -> Option<Symbol> {
+        let source_bytes = source.as_bytes();
+        let mut kind = Self::get_symbol_kind(node.kind(), Language::Cpp)?;
+        let mut parent = parent.map(String::from);
+
+        // Templated declarations are wrapped in a template_declaration node
+        let template = node.parent().filter(|p| p.kind() == "template_declaration");
+        let outer = template.unwrap_or(*node);
+        let in_class_body = outer
+            .parent()
+            .is_some_and(|p| p.kind() == "field_declaration_list");
+
+        let name = match node.kind() {
+            "function_definition" | "field_declaration" => {
+                let declarator = node.child_by_field_name("declarator")?;
+                let (is_function, name_node) = Self::cpp_declarator_name(declarator)?;
+                let full_name = name_node.utf8_text(source_bytes).ok()?;
+
+                // Member function declared or defined inside a class body
+                let is_member_function = match node.kind() {
+                    "field_declaration" => is_function,
+                    _ => in_class_body,
+                };
+                if is_member_function {
+                    kind = SymbolKind::Method;
+                }
 
-    fn json_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "pair" => Some(SymbolKind::Variable),
-            "object" => Some(SymbolKind::Struct),
-            "array" => Some(SymbolKind::Variable),
-            _ => None,
-        }
-    }
+                match full_name.rsplit_once("::") {
+                    // Out-of-class definition: `void Class::method() { ... }`
+                    Some((qualifier, name)) if name_node.kind() == "qualified_identifier" => {
+                        if node.kind() == "function_definition" {
+                            kind = SymbolKind::Method;
+                        }
+                        parent = Some(qualifier.to_string());
+                        name.to_string()
+                    }
+                    _ => full_name.to_string(),
+                }
+            }
+            "type_definition" => {
+                let declarator = node.child_by_field_name("declarator")?;
+                let (_, name_node) = Self::cpp_declarator_name(declarator)?;
+                name_node.utf8_text(source_bytes).ok()?.to_string()
+            }
+            "class_specifier" | "struct_specifier" | "union_specifier" | "enum_specifier" => {
+                // Skip forward declarations and elaborated type specifiers
+                node.child_by_field_name("body")?;
+                let name_node = node.child_by_field_name("name")?;
+                name_node.utf8_text(source_bytes).ok()?.to_string()
+            }
+            _ => {
+                let name_node = node.child_by_field_name("name")?;
+                name_node.utf8_text(source_bytes).ok()?.to_string()
+            }
+        };
 
-    fn yaml_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "block_mapping_pair" => Some(SymbolKind::Variable),
-            "block_mapping" => Some(SymbolKind::Struct),
-            "block_sequence" => Some(SymbolKind::Variable),
-            _ => None,
+        let mut signature = Self::extract_signature(node, source, Language::Cpp);
+        if let Some(template) = template
+            && let Some(params) = template.child_by_field_name("parameters")
+            && let Ok(params) = params.utf8_text(source_bytes)
+        {
+            signature = signature.map(|sig| format!("template {} {}", params, sig));
         }
-    }
 
-    fn toml_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "pair" => Some(SymbolKind::Variable),
-            "table" => Some(SymbolKind::Struct),
-            "array" => Some(SymbolKind::Variable),
-            _ => None,
-        }
-    }
+        let qualifiers = SymbolQualifiers::extract(node, source, Language::Cpp, &name);
 
-    fn bash_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "function_definition" => Some(SymbolKind::Function),
-            "variable_assignment" => Some(SymbolKind::Variable),
-            _ => None,
-        }
+        Some(Symbol {
+            name,
+            kind,
+            start_line: outer.start_position().row + 1,
+            end_line: outer.end_position().row + 1,
+            start_byte: outer.start_byte(),
+            end_byte: outer.end_byte(),
+            start_column: outer.start_position().column,
+            end_column: outer.end_position().column,
+            signature,
+            doc_comment: Self::extract_doc_comment(&outer, source),
+            parent,
+            qualifiers,
+            cfg_context: conditions::cfg_context(&outer, source, Language::Cpp),
+        })
     }
 
-    fn elixir_symbol_kind(node_kind: &str) -> Option<SymbolKind> {
-        match node_kind {
-            "call" => Some(SymbolKind::Function), // def, defp, defmodule
-            "anonymous_function" => Some(SymbolKind::Function),
-            "do_block" => Some(SymbolKind::Module),
-            _ => None,
+    /// Unwrap a C++ declarator (function, pointer, reference, ...) down to the
+    /// node holding the declared name. Returns whether a function declarator
+    /// was encountered along the way.
+    fn cpp_declarator_name<'a>(declarator: Node<'a>) -> Option<(bool, Node<'a>)> {
+        let mut current = declarator;
+        let mut is_function = false;
+
+        loop {
+            match current.kind() {
+                "function_declarator" => {
+                    is_function = true;
+                    current = current.child_by_field_name("declarator")?;
+                }
+                "pointer_declarator"
+                | "reference_declarator"
+                | "array_declarator"
+                | "parenthesized_declarator"
+                | "init_declarator"
+                | "attributed_declarator" => {
+                    current = current.child_by_field_name("declarator").or_else(|| {
+                        current.named_child(current.named_child_count().checked_sub(1)?)
+                    })?;
+                }
+                _ => return Some((is_function, current)),
+            }
         }
     }
 
@@ -366,6 +398,10 @@ impl SymbolExtractor {
     }
 
     fn extract_name(node: &Node, source: &str, language: Language) -> Option<String> {
+        if is_config_language(language) {
+            return key_segments(node, source, language).map(|segments| segments.join("."));
+        }
+
         // Try to find the identifier child
         let name_field = match language {
             Language::Rust => "name",
@@ -385,6 +421,8 @@ impl SymbolExtractor {
             Language::Toml => "key",
             Language::Bash => "name",
             Language::Elixir => "name",
+            Language::Sql => "name",
+            Language::Custom(id) => plugins::get(id).map_or("name", |l| l.name_field.as_str()),
         };
 
         let source_bytes = source.as_bytes();
@@ -482,6 +520,7 @@ impl SymbolExtractor {
 mod tests {
     use super::*;
     use crate::language::LanguageSupport;
+    use std::path::Path;
 
     #[test]
     fn test_extract_rust_symbols() {
@@ -516,6 +555,11 @@ impl User {
                 .iter()
                 .any(|s| s.name == "User" && s.kind == SymbolKind::Struct)
         );
+
+        // Columns are byte offsets in the start and end lines
+        let new = symbols.iter().find(|s| s.name == "new").unwrap();
+        assert_eq!((new.start_column, new.end_column), (4, 5));
+        assert!(source[new.start_byte..].starts_with("fn new"));
     }
 
     #[test]
@@ -735,6 +779,153 @@ int main() {
         );
     }
 
+    #[test]
+    fn test_extract_sql_symbols() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = r#"
+-- Registered users
+CREATE TABLE public.users (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_users_email ON users (email);
+
+ALTER TABLE users ADD COLUMN name VARCHAR(255);
+
+CREATE VIEW active_users AS SELECT * FROM users;
+
+INSERT INTO users (email) VALUES ('admin@example.com');
+"#;
+        let tree = support.parse(Language::Sql, source).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, Language::Sql).unwrap();
+        let found: Vec<(&str, SymbolKind, Option<&str>, usize)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.parent.as_deref(), s.start_line))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                ("users", SymbolKind::Table, None, 3),
+                ("id", SymbolKind::Column, Some("users"), 4),
+                ("email", SymbolKind::Column, Some("users"), 5),
+                ("idx_users_email", SymbolKind::Index, Some("users"), 8),
+                ("users", SymbolKind::Table, None, 10),
+                ("name", SymbolKind::Column, Some("users"), 10),
+                ("active_users", SymbolKind::Table, None, 12),
+            ]
+        );
+        assert_eq!(
+            symbols[0].doc_comment.as_deref(),
+            Some("-- Registered users")
+        );
+        assert_eq!(
+            symbols[4].signature.as_deref(),
+            Some("ALTER TABLE users ADD COLUMN name VARCHAR(255)")
+        );
+    }
+
+    #[test]
+    fn test_extract_cpp_symbols() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = r#"
+#include <vector>
+
+namespace geometry {
+
+/// A 2D shape
+class Shape {
+public:
+    virtual double area() const = 0;
+    int id;
+
+    void describe() {
+        return;
+    }
+};
+
+template <typename T>
+struct Box {
+    T value;
+};
+
+enum class Color { Red, Green };
+
+double Shape::perimeter() const {
+    return 0.0;
+}
+
+int helper(int a) {
+    return a;
+}
+
+}
+"#;
+        let tree = support.parse(Language::Cpp, source).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, Language::Cpp).unwrap();
+        let find = |name: &str| symbols.iter().find(|s| s.name == name);
+
+        let namespace = find("geometry").expect("namespace not found");
+        assert_eq!(namespace.kind, SymbolKind::Module);
+
+        let class = find("Shape").expect("class not found");
+        assert_eq!(class.kind, SymbolKind::Class);
+        assert_eq!(class.parent.as_deref(), Some("geometry"));
+        assert!(class.doc_comment.as_deref().unwrap().contains("A 2D shape"));
+
+        let area = find("area").expect("method declaration not found");
+        assert_eq!(area.kind, SymbolKind::Method);
+        assert_eq!(area.parent.as_deref(), Some("Shape"));
+
+        let id = find("id").expect("field not found");
+        assert_eq!(id.kind, SymbolKind::Variable);
+        assert_eq!(id.parent.as_deref(), Some("Shape"));
+
+        let describe = find("describe").expect("inline method not found");
+        assert_eq!(describe.kind, SymbolKind::Method);
+        assert_eq!(describe.parent.as_deref(), Some("Shape"));
+
+        let boxed = find("Box").expect("template struct not found");
+        assert_eq!(boxed.kind, SymbolKind::Struct);
+        assert!(
+            boxed
+                .signature
+                .as_deref()
+                .unwrap()
+                .starts_with("template <typename T>")
+        );
+
+        assert_eq!(find("Color").unwrap().kind, SymbolKind::Enum);
+
+        let perimeter = find("perimeter").expect("out-of-class method not found");
+        assert_eq!(perimeter.kind, SymbolKind::Method);
+        assert_eq!(perimeter.parent.as_deref(), Some("Shape"));
+
+        let helper = find("helper").expect("free function not found");
+        assert_eq!(helper.kind, SymbolKind::Function);
+        assert_eq!(helper.parent.as_deref(), Some("geometry"));
+    }
+
+    #[test]
+    fn test_cpp_skips_forward_declarations() {
+        let mut support = LanguageSupport::new().unwrap();
+        let source = r#"
+class Forward;
+
+class Widget {
+    Widget* next;
+};
+"#;
+        let tree = support.parse(Language::Cpp, source).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, Language::Cpp).unwrap();
+
+        assert!(!symbols.iter().any(|s| s.name == "Forward"));
+        let next = symbols.iter().find(|s| s.name == "next").unwrap();
+        assert_eq!(next.kind, SymbolKind::Variable);
+        assert_eq!(next.parent.as_deref(), Some("Widget"));
+    }
+
     #[test]
     fn test_symbol_kind_as_str() {
         assert_eq!(SymbolKind::Function.as_str(), "function");
@@ -749,6 +940,9 @@ int main() {
         assert_eq!(SymbolKind::Constant.as_str(), "constant");
         assert_eq!(SymbolKind::Type.as_str(), "type");
         assert_eq!(SymbolKind::Import.as_str(), "import");
+        assert_eq!(SymbolKind::Table.as_str(), "table");
+        assert_eq!(SymbolKind::Column.as_str(), "column");
+        assert_eq!(SymbolKind::Index.as_str(), "index");
     }
 
     #[test]
@@ -799,6 +993,35 @@ impl Calculator {
         assert!(add_func.is_some());
     }
 
+    #[test]
+    fn test_config_keys_have_their_path_as_parent() {
+        let mut support = LanguageSupport::new().unwrap();
+        let yaml = "services:\n  web:\n    environment:\n      REDIS_URL: redis://cache\n";
+        let tree = support
+            .parse_file(Language::Yaml, Path::new("compose.yaml"), yaml)
+            .unwrap();
+        let symbols = SymbolExtractor::extract(&tree, yaml, Language::Yaml).unwrap();
+        let redis = symbols.iter().find(|s| s.name == "REDIS_URL").unwrap();
+        assert_eq!(redis.parent.as_deref(), Some("services.web.environment"));
+
+        let json = r#"{"scripts": {"build": "tsc"}}"#;
+        let tree = support
+            .parse_file(Language::Json, Path::new("package.json"), json)
+            .unwrap();
+        let symbols = SymbolExtractor::extract(&tree, json, Language::Json).unwrap();
+        let build = symbols.iter().find(|s| s.name == "build").unwrap();
+        assert_eq!(build.parent.as_deref(), Some("scripts"));
+
+        let toml = "[database.pool]\nsize = 4\n";
+        let tree = support
+            .parse_file(Language::Toml, Path::new("app.toml"), toml)
+            .unwrap();
+        let symbols = SymbolExtractor::extract(&tree, toml, Language::Toml).unwrap();
+        assert!(symbols.iter().any(|s| s.name == "database.pool"));
+        let size = symbols.iter().find(|s| s.name == "size").unwrap();
+        assert_eq!(size.parent.as_deref(), Some("database.pool"));
+    }
+
     #[test]
     fn test_extract_typescript_arrow_functions() {
         let mut support = LanguageSupport::new().unwrap();
@@ -0,0 +1,186 @@
+use crate::language::Language;
+use anyhow::Result;
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+/// Byte ranges of the comments and string literals of a file, for text
+/// searches leaving them out.
+///
+/// The code interpolated in strings (`${user.name}`, `f"{user}"`,
+/// `"#{user}"`) is not masked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextMask {
+    /// Comments, doc comments included, sorted
+    pub comments: Vec<Range<usize>>,
+    /// String and character literals, sorted
+    pub strings: Vec<Range<usize>>,
+}
+
+impl TextMask {
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty() && self.strings.is_empty()
+    }
+
+    /// Whether the byte at `offset` is in a comment
+    pub fn in_comment(&self, offset: usize) -> bool {
+        contains(&self.comments, offset)
+    }
+
+    /// Whether the byte at `offset` is in a string literal
+    pub fn in_string(&self, offset: usize) -> bool {
+        contains(&self.strings, offset)
+    }
+}
+
+fn contains(ranges: &[Range<usize>], offset: usize) -> bool {
+    let index = ranges.partition_point(|range| range.end <= offset);
+    ranges.get(index).is_some_and(|range| range.start <= offset)
+}
+
+/// Append a range, merged with the previous one when they touch
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+    match ranges.last_mut() {
+        Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+        _ => ranges.push(range),
+    }
+}
+
+pub struct TextMaskExtractor;
+
+impl TextMaskExtractor {
+    /// Find the comments and string literals of a file. Data and markup
+    /// files have none: their strings are their content.
+    pub fn extract(tree: &Tree, language: Language) -> Result<TextMask> {
+        let mut mask = TextMask::default();
+        if matches!(
+            language,
+            Language::Html | Language::Json | Language::Yaml | Language::Toml
+        ) {
+            return Ok(mask);
+        }
+
+        Self::mask_recursive(&tree.root_node(), &mut mask);
+        Ok(mask)
+    }
+
+    fn mask_recursive(node: &Node, mask: &mut TextMask) {
+        if Self::is_comment(node) {
+            push_range(&mut mask.comments, node.byte_range());
+            return;
+        }
+
+        let mut cursor = node.walk();
+        if Self::is_string(node) {
+            // Interpolated code splits the string
+            let mut start = node.start_byte();
+            for child in node.children(&mut cursor) {
+                if Self::is_interpolation(&child) {
+                    push_range(&mut mask.strings, start..child.start_byte());
+                    Self::mask_recursive(&child, mask);
+                    start = child.end_byte();
+                }
+            }
+            push_range(&mut mask.strings, start..node.end_byte());
+            return;
+        }
+
+        for child in node.children(&mut cursor) {
+            Self::mask_recursive(&child, mask);
+        }
+    }
+
+    /// Comment node kinds across grammars: `comment`, `line_comment`,
+    /// `block_comment`...
+    fn is_comment(node: &Node) -> bool {
+        node.is_named() && node.kind().contains("comment")
+    }
+
+    /// String literal node kinds across grammars: `string`,
+    /// `string_literal`, `raw_string_literal`, `template_string`,
+    /// `interpreted_string_literal` (Go), heredocs and character literals.
+    /// Anonymous nodes are left out, e.g. the `string` type of TypeScript.
+    fn is_string(node: &Node) -> bool {
+        if !node.is_named() {
+            return false;
+        }
+        let kind = node.kind();
+        kind.contains("string")
+            || matches!(kind, "heredoc_body" | "char_literal" | "character_literal")
+    }
+
+    /// Code interpolated in a string: `interpolation` (Python, Ruby, C#,
+    /// Scala), `template_substitution` (TypeScript), `interpolated_expression`
+    /// (Kotlin), `expansion` and `command_substitution` (Bash)
+    fn is_interpolation(node: &Node) -> bool {
+        let kind = node.kind();
+        kind.contains("interpolat") || kind.contains("substitution") || kind.contains("expansion")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageSupport;
+
+    fn extract(language: Language, source: &str) -> TextMask {
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(language, source).unwrap();
+        TextMaskExtractor::extract(&tree, language).unwrap()
+    }
+
+    fn texts<'a>(source: &'a str, ranges: &[Range<usize>]) -> Vec<&'a str> {
+        ranges.iter().map(|range| &source[range.clone()]).collect()
+    }
+
+    #[test]
+    fn test_masks_rust_comments_and_strings() {
+        let source = r#"/// Loads the config
+fn load() -> Config {
+    // parse_config is called once
+    let name = "parse_config";
+    let c = 'x';
+    parse_config(name) /* inline */
+}
+"#;
+        let mask = extract(Language::Rust, source);
+
+        assert_eq!(
+            texts(source, &mask.comments),
+            [
+                "/// Loads the config\n",
+                "// parse_config is called once",
+                "/* inline */"
+            ]
+        );
+        assert_eq!(texts(source, &mask.strings), ["\"parse_config\"", "'x'"]);
+
+        let call = source.rfind("parse_config(").unwrap();
+        assert!(!mask.in_comment(call) && !mask.in_string(call));
+        assert!(mask.in_string(source.find("\"parse_config\"").unwrap() + 1));
+        assert!(mask.in_comment(source.find("parse_config is").unwrap()));
+    }
+
+    #[test]
+    fn test_interpolated_code_is_not_masked() {
+        let source = "const greeting = `Hello ${user.name}!`; // user\nlet t: string = 'user';\n";
+        let mask = extract(Language::TypeScript, source);
+
+        assert_eq!(texts(source, &mask.strings), ["`Hello ", "!`", "'user'"]);
+        assert_eq!(texts(source, &mask.comments), ["// user"]);
+        assert!(!mask.in_string(source.find("user.name").unwrap()));
+        // The `string` type is code
+        assert!(!mask.in_string(source.find("string").unwrap()));
+
+        let python = "print(f\"{user} logged in\")  # user\n";
+        let mask = extract(Language::Python, python);
+        assert_eq!(texts(python, &mask.strings), ["f\"", " logged in\""]);
+    }
+
+    #[test]
+    fn test_data_files_have_no_mask() {
+        assert!(extract(Language::Json, r#"{"name": "value"}"#).is_empty());
+    }
+}
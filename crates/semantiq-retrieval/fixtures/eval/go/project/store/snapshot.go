@@ -0,0 +1,33 @@
+package store
+
+import (
+	"encoding/json"
+	"os"
+)
+
+// SaveSnapshot writes every entry of the store to a JSON file
+func (s *Store) SaveSnapshot(path string) error {
+	s.mu.RLock()
+	data, err := json.Marshal(s.entries)
+	s.mu.RUnlock()
+	if err != nil {
+		return err
+	}
+	return os.WriteFile(path, data, 0o644)
+}
+
+// LoadSnapshot replaces the entries of the store with those of a JSON file
+func (s *Store) LoadSnapshot(path string) error {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return err
+	}
+	entries := make(map[string]Entry)
+	if err := json.Unmarshal(data, &entries); err != nil {
+		return err
+	}
+	s.mu.Lock()
+	s.entries = entries
+	s.mu.Unlock()
+	return nil
+}
@@ -0,0 +1,59 @@
+package store
+
+import (
+	"errors"
+	"sync"
+	"time"
+)
+
+// ErrNotFound is returned for keys missing from the store
+var ErrNotFound = errors.New("key not found")
+
+// Entry is a stored value and its expiry
+type Entry struct {
+	Value     []byte
+	ExpiresAt time.Time
+}
+
+// Store is an in-memory key-value store safe for concurrent use
+type Store struct {
+	mu      sync.RWMutex
+	entries map[string]Entry
+}
+
+// NewStore creates an empty store
+func NewStore() *Store {
+	return &Store{entries: make(map[string]Entry)}
+}
+
+// Get returns the value of key, or ErrNotFound when missing or expired
+func (s *Store) Get(key string) ([]byte, error) {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+	entry, ok := s.entries[key]
+	if !ok || (!entry.ExpiresAt.IsZero() && time.Now().After(entry.ExpiresAt)) {
+		return nil, ErrNotFound
+	}
+	return entry.Value, nil
+}
+
+// SetWithTTL stores value under key for ttl
+func (s *Store) SetWithTTL(key string, value []byte, ttl time.Duration) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.entries[key] = Entry{Value: value, ExpiresAt: time.Now().Add(ttl)}
+}
+
+// PurgeExpired deletes every expired entry and returns how many were removed
+func (s *Store) PurgeExpired() int {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	removed := 0
+	for key, entry := range s.entries {
+		if !entry.ExpiresAt.IsZero() && time.Now().After(entry.ExpiresAt) {
+			delete(s.entries, key)
+			removed++
+		}
+	}
+	return removed
+}
@@ -0,0 +1,44 @@
+"""Shopping cart and checkout."""
+
+from dataclasses import dataclass, field
+
+from .pricing import apply_discount
+
+
+@dataclass
+class CartItem:
+    sku: str
+    quantity: int
+    unit_price: float
+
+
+@dataclass
+class ShoppingCart:
+    """Items a customer is about to buy."""
+
+    items: list = field(default_factory=list)
+
+    def add_item(self, sku: str, quantity: int, unit_price: float) -> None:
+        """Add `quantity` units of a product to the cart."""
+        for item in self.items:
+            if item.sku == sku:
+                item.quantity += quantity
+                return
+        self.items.append(CartItem(sku, quantity, unit_price))
+
+    def remove_item(self, sku: str) -> None:
+        """Remove a product from the cart."""
+        self.items = [item for item in self.items if item.sku != sku]
+
+    def total(self, coupon: str | None = None) -> float:
+        """Total price of the cart, after the coupon discount."""
+        subtotal = sum(item.quantity * item.unit_price for item in self.items)
+        return apply_discount(subtotal, coupon)
+
+
+def checkout(cart: ShoppingCart, payment_token: str) -> str:
+    """Charge the customer and return the order id."""
+    if not cart.items:
+        raise ValueError("cannot checkout an empty cart")
+    amount = cart.total()
+    return f"order-{payment_token[:8]}-{int(amount * 100)}"
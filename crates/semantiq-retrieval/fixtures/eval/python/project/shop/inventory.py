@@ -0,0 +1,21 @@
+"""Stock levels of the warehouse."""
+
+
+class OutOfStock(Exception):
+    """Raised when a product has fewer units than requested."""
+
+
+class Inventory:
+    def __init__(self):
+        self.stock = {}
+
+    def restock(self, sku: str, quantity: int) -> None:
+        """Receive new units of a product."""
+        self.stock[sku] = self.stock.get(sku, 0) + quantity
+
+    def reserve(self, sku: str, quantity: int) -> None:
+        """Set aside units for an order, raising OutOfStock when short."""
+        available = self.stock.get(sku, 0)
+        if available < quantity:
+            raise OutOfStock(sku)
+        self.stock[sku] = available - quantity
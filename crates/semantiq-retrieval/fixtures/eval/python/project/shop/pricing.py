@@ -0,0 +1,21 @@
+"""Prices, coupons and taxes."""
+
+COUPONS = {"WELCOME10": 0.10, "SUMMER25": 0.25}
+
+VAT_RATES = {"FR": 0.20, "DE": 0.19, "US": 0.0}
+
+
+def apply_discount(amount: float, coupon: str | None) -> float:
+    """Apply the percentage discount of a coupon code."""
+    rate = COUPONS.get(coupon or "", 0.0)
+    return round(amount * (1 - rate), 2)
+
+
+def compute_vat(amount: float, country: str) -> float:
+    """Value added tax owed on `amount` in `country`."""
+    return round(amount * VAT_RATES.get(country, 0.0), 2)
+
+
+def format_price(amount: float, currency: str = "EUR") -> str:
+    """Format an amount for display, e.g. `12.50 EUR`."""
+    return f"{amount:.2f} {currency}"
@@ -0,0 +1,45 @@
+use std::time::{Duration, SystemTime};
+
+/// Signed token identifying a logged-in user
+pub struct SessionToken {
+    pub user_id: u64,
+    pub expires_at: SystemTime,
+    signature: Vec<u8>,
+}
+
+impl SessionToken {
+    /// Issue a token for `user_id`, valid for `ttl`
+    pub fn issue(user_id: u64, ttl: Duration, key: &[u8]) -> Self {
+        let expires_at = SystemTime::now() + ttl;
+        let signature = sign(user_id, key);
+        Self {
+            user_id,
+            expires_at,
+            signature,
+        }
+    }
+
+    /// Whether the token has expired
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() > self.expires_at
+    }
+}
+
+/// Check the signature and expiry of a session token
+pub fn verify_token(token: &SessionToken, key: &[u8]) -> bool {
+    !token.is_expired() && token.signature == sign(token.user_id, key)
+}
+
+/// Hash a password with a per-user salt
+pub fn hash_password(password: &str, salt: &str) -> u64 {
+    let mut hash: u64 = 14695981039346656037;
+    for byte in salt.bytes().chain(password.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(1099511628211);
+    }
+    hash
+}
+
+fn sign(user_id: u64, key: &[u8]) -> Vec<u8> {
+    key.iter().map(|b| b ^ (user_id as u8)).collect()
+}
@@ -0,0 +1,47 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Cache keeping the `capacity` most recently used entries
+pub struct LruCache<V> {
+    capacity: usize,
+    entries: HashMap<String, V>,
+    order: VecDeque<String>,
+}
+
+impl<V> LruCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Get an entry, marking it as recently used
+    pub fn get(&mut self, key: &str) -> Option<&V> {
+        if self.entries.contains_key(key) {
+            self.touch(key);
+        }
+        self.entries.get(key)
+    }
+
+    /// Insert an entry, evicting the least recently used one when full
+    pub fn insert(&mut self, key: &str, value: V) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(key) {
+            self.evict_oldest();
+        }
+        self.entries.insert(key.to_string(), value);
+        self.touch(key);
+    }
+
+    fn touch(&mut self, key: &str) {
+        self.order.retain(|k| k != key);
+        self.order.push_back(key.to_string());
+    }
+
+    /// Remove the least recently used entry
+    fn evict_oldest(&mut self) {
+        if let Some(oldest) = self.order.pop_front() {
+            self.entries.remove(&oldest);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Settings of the server, read from a TOML file
+pub struct Config {
+    pub port: u16,
+    pub database_url: String,
+    pub features: HashMap<String, bool>,
+}
+
+/// Read and parse the configuration file at `path`
+pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
+    let text = fs::read_to_string(path).map_err(ConfigError::Io)?;
+    parse_config(&text)
+}
+
+/// Parse `key = value` lines into a configuration
+pub fn parse_config(text: &str) -> Result<Config, ConfigError> {
+    let mut values = HashMap::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let (key, value) = line.split_once('=').ok_or(ConfigError::Syntax)?;
+        values.insert(key.trim().to_string(), value.trim().to_string());
+    }
+    Ok(Config {
+        port: values
+            .get("port")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(8080),
+        database_url: values.remove("database_url").unwrap_or_default(),
+        features: HashMap::new(),
+    })
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Syntax,
+}
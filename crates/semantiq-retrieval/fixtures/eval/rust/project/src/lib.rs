@@ -0,0 +1,3 @@
+pub mod auth;
+pub mod cache;
+pub mod config;
@@ -0,0 +1,29 @@
+type Listener<T> = (payload: T) => void;
+
+/** Publish/subscribe bus of typed events */
+export class EventEmitter<Events extends Record<string, unknown>> {
+  private listeners = new Map<keyof Events, Listener<any>[]>();
+
+  /** Register a listener, returning a function removing it */
+  subscribe<K extends keyof Events>(event: K, listener: Listener<Events[K]>): () => void {
+    const listeners = this.listeners.get(event) ?? [];
+    listeners.push(listener);
+    this.listeners.set(event, listeners);
+    return () => this.unsubscribe(event, listener);
+  }
+
+  unsubscribe<K extends keyof Events>(event: K, listener: Listener<Events[K]>): void {
+    const listeners = this.listeners.get(event) ?? [];
+    this.listeners.set(
+      event,
+      listeners.filter((l) => l !== listener),
+    );
+  }
+
+  /** Call every listener of `event` with `payload` */
+  emit<K extends keyof Events>(event: K, payload: Events[K]): void {
+    for (const listener of this.listeners.get(event) ?? []) {
+      listener(payload);
+    }
+  }
+}
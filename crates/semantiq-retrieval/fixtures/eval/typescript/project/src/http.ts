@@ -0,0 +1,40 @@
+export interface RequestOptions {
+  method?: "GET" | "POST" | "PUT" | "DELETE";
+  headers?: Record<string, string>;
+  body?: unknown;
+  retries?: number;
+}
+
+export class HttpError extends Error {
+  constructor(public status: number, message: string) {
+    super(message);
+  }
+}
+
+/** Send a JSON request, retrying failed attempts with exponential backoff */
+export async function fetchJson<T>(url: string, options: RequestOptions = {}): Promise<T> {
+  const retries = options.retries ?? 2;
+  for (let attempt = 0; ; attempt++) {
+    const response = await fetch(url, {
+      method: options.method ?? "GET",
+      headers: { "Content-Type": "application/json", ...options.headers },
+      body: options.body === undefined ? undefined : JSON.stringify(options.body),
+    });
+    if (response.ok) {
+      return (await response.json()) as T;
+    }
+    if (attempt >= retries) {
+      throw new HttpError(response.status, await response.text());
+    }
+    await sleep(backoffDelay(attempt));
+  }
+}
+
+/** Delay before the next retry, doubling at each attempt */
+export function backoffDelay(attempt: number): number {
+  return Math.min(100 * 2 ** attempt, 5000);
+}
+
+function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
@@ -0,0 +1,41 @@
+import { fetchJson } from "./http";
+
+export interface User {
+  id: string;
+  email: string;
+  displayName: string;
+}
+
+/** Users of the API, cached after the first fetch */
+export class UserRepository {
+  private cache = new Map<string, User>();
+
+  constructor(private baseUrl: string) {}
+
+  async findById(id: string): Promise<User> {
+    const cached = this.cache.get(id);
+    if (cached) {
+      return cached;
+    }
+    const user = await fetchJson<User>(`${this.baseUrl}/users/${id}`);
+    this.cache.set(id, user);
+    return user;
+  }
+
+  async updateEmail(id: string, email: string): Promise<User> {
+    if (!isValidEmail(email)) {
+      throw new Error(`invalid email: ${email}`);
+    }
+    const user = await fetchJson<User>(`${this.baseUrl}/users/${id}`, {
+      method: "PUT",
+      body: { email },
+    });
+    this.cache.set(id, user);
+    return user;
+  }
+}
+
+/** Loose check of the shape of an email address */
+export function isValidEmail(email: string): boolean {
+  return /^[^@\s]+@[^@\s]+\.[^@\s]+$/.test(email);
+}
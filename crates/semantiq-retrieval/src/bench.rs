@@ -0,0 +1,370 @@
+//! Retrieval quality and latency benchmarks.
+//!
+//! A suite is a YAML file of queries and the results they should find:
+//!
+//! ```yaml
+//! k: 5
+//! cases:
+//!   - query: "load the project configuration"
+//!     expected:
+//!       - file: crates/semantiq-index/src/config.rs
+//!         symbol: load
+//!   - query: "RetrievalEngine"
+//!     expected:
+//!       - symbol: RetrievalEngine
+//! ```
+//!
+//! A result is relevant when it matches any expected entry: the file path
+//! (relative to the project root) and the symbol name must both match when
+//! given. Every query goes through the full search pipeline, and the report
+//! gives precision@k, MRR and latency percentiles, overall and per search
+//! strategy (the shape of the query). Reports are saved as JSON and used as
+//! baselines, failing a run whose quality dropped.
+
+use crate::engine::RetrievalEngine;
+use crate::results::SearchResult;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Instant;
+
+/// Number of results scored per query when the suite does not set it
+pub const DEFAULT_BENCH_K: usize = 10;
+
+/// Queries and their expected results
+#[derive(Debug, Clone, Deserialize)]
+pub struct BenchSuite {
+    /// Number of results scored per query
+    #[serde(default = "default_k")]
+    pub k: usize,
+    pub cases: Vec<BenchCase>,
+}
+
+fn default_k() -> usize {
+    DEFAULT_BENCH_K
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BenchCase {
+    pub query: String,
+    pub expected: Vec<ExpectedResult>,
+}
+
+/// A result a query should find
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExpectedResult {
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+impl ExpectedResult {
+    pub(crate) fn matches(&self, result: &SearchResult) -> bool {
+        let file_matches = self
+            .file
+            .as_deref()
+            .is_none_or(|file| result.file_path == file.trim_start_matches("./"));
+        let symbol_matches = self
+            .symbol
+            .as_deref()
+            .is_none_or(|symbol| result.metadata.symbol_name.as_deref() == Some(symbol));
+        file_matches && symbol_matches
+    }
+}
+
+impl BenchSuite {
+    /// Load a suite from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read benchmark suite {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("Invalid benchmark suite {:?}", path))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let suite: Self = serde_yaml::from_str(content)?;
+        if suite.k == 0 {
+            bail!("k must be at least 1");
+        }
+        if suite.cases.is_empty() {
+            bail!("The suite has no cases");
+        }
+        for case in &suite.cases {
+            if case.query.trim().is_empty() {
+                bail!("A case has an empty query");
+            }
+            if case.expected.is_empty()
+                || case
+                    .expected
+                    .iter()
+                    .any(|e| e.file.is_none() && e.symbol.is_none())
+            {
+                bail!(
+                    "Case '{}' needs expected results with a file or a symbol",
+                    case.query
+                );
+            }
+        }
+        Ok(suite)
+    }
+}
+
+/// Quality and latency of a set of queries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchMetrics {
+    pub queries: usize,
+    /// Mean share of relevant results in the top k
+    pub precision_at_k: f64,
+    /// Mean reciprocal rank of the first relevant result, 0 when none is in
+    /// the top k
+    pub mrr: f64,
+    pub latency_p50_ms: f64,
+    pub latency_p90_ms: f64,
+    pub latency_p99_ms: f64,
+}
+
+impl BenchMetrics {
+    fn from_cases<'a>(cases: impl Iterator<Item = &'a CaseResult>, k: usize) -> Self {
+        let cases: Vec<&CaseResult> = cases.collect();
+        if cases.is_empty() {
+            return Self::default();
+        }
+        let count = cases.len() as f64;
+        let mut latencies: Vec<f64> = cases.iter().map(|c| c.latency_ms).collect();
+        latencies.sort_by(|a, b| a.total_cmp(b));
+        Self {
+            queries: cases.len(),
+            precision_at_k: cases
+                .iter()
+                .map(|c| c.relevant as f64 / k as f64)
+                .sum::<f64>()
+                / count,
+            mrr: cases
+                .iter()
+                .map(|c| c.first_relevant_rank.map_or(0.0, |rank| 1.0 / rank as f64))
+                .sum::<f64>()
+                / count,
+            latency_p50_ms: percentile(&latencies, 50.0),
+            latency_p90_ms: percentile(&latencies, 90.0),
+            latency_p99_ms: percentile(&latencies, 99.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Outcome of one query of the suite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub query: String,
+    /// Shape of the query, which picks the strategy weights
+    pub strategy: String,
+    /// Rank (from 1) of the first relevant result in the top k
+    pub first_relevant_rank: Option<usize>,
+    /// Relevant results in the top k
+    pub relevant: usize,
+    pub latency_ms: f64,
+}
+
+/// Results of a benchmark run, also saved as a baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub k: usize,
+    pub overall: BenchMetrics,
+    pub strategies: BTreeMap<String, BenchMetrics>,
+    pub cases: Vec<CaseResult>,
+}
+
+/// Run every query of `suite` through the search pipeline of `engine`
+pub fn run(engine: &RetrievalEngine, suite: &BenchSuite) -> Result<BenchReport> {
+    let mut cases = Vec::with_capacity(suite.cases.len());
+    for case in &suite.cases {
+        let start = Instant::now();
+        let results = engine
+            .search(&case.query, suite.k, None)
+            .with_context(|| format!("Search failed for '{}'", case.query))?;
+        let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let relevant: Vec<bool> = results
+            .results
+            .iter()
+            .take(suite.k)
+            .map(|r| case.expected.iter().any(|e| e.matches(r)))
+            .collect();
+        cases.push(CaseResult {
+            query: case.query.clone(),
+            strategy: results
+                .strategy
+                .map_or("unknown", |s| s.shape.as_str())
+                .to_string(),
+            first_relevant_rank: relevant.iter().position(|r| *r).map(|i| i + 1),
+            relevant: relevant.iter().filter(|r| **r).count(),
+            latency_ms,
+        });
+    }
+    Ok(BenchReport::new(suite.k, cases))
+}
+
+impl BenchReport {
+    pub fn new(k: usize, cases: Vec<CaseResult>) -> Self {
+        let mut strategies: BTreeMap<String, Vec<&CaseResult>> = BTreeMap::new();
+        for case in &cases {
+            strategies
+                .entry(case.strategy.clone())
+                .or_default()
+                .push(case);
+        }
+        Self {
+            k,
+            overall: BenchMetrics::from_cases(cases.iter(), k),
+            strategies: strategies
+                .into_iter()
+                .map(|(strategy, cases)| (strategy, BenchMetrics::from_cases(cases.into_iter(), k)))
+                .collect(),
+            cases,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read benchmark baseline {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid benchmark baseline {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write benchmark baseline {:?}", path))
+    }
+
+    /// Precision@k and MRR drops of more than `tolerance` against
+    /// `baseline`, overall and for the strategies both runs measured.
+    /// Latency is too noisy across machines to fail a run on.
+    pub fn regressions(&self, baseline: &BenchReport, tolerance: f64) -> Vec<String> {
+        let mut regressions = Vec::new();
+        let mut compare = |scope: &str, current: &BenchMetrics, base: &BenchMetrics| {
+            for (metric, now, before) in [
+                ("precision@k", current.precision_at_k, base.precision_at_k),
+                ("MRR", current.mrr, base.mrr),
+            ] {
+                if before - now > tolerance {
+                    regressions.push(format!("{} {}: {:.3} -> {:.3}", scope, metric, before, now));
+                }
+            }
+        };
+        compare("overall", &self.overall, &baseline.overall);
+        for (strategy, metrics) in &self.strategies {
+            if let Some(base) = baseline.strategies.get(strategy) {
+                compare(strategy, metrics, base);
+            }
+        }
+        regressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::SearchResultKind;
+
+    fn case(strategy: &str, rank: Option<usize>, relevant: usize, latency_ms: f64) -> CaseResult {
+        CaseResult {
+            query: "q".to_string(),
+            strategy: strategy.to_string(),
+            first_relevant_rank: rank,
+            relevant,
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn test_parse_suite() {
+        let suite = BenchSuite::parse(
+            "cases:\n  - query: parse config\n    expected:\n      - file: ./src/config.rs\n        symbol: load\n",
+        )
+        .unwrap();
+        assert_eq!(suite.k, DEFAULT_BENCH_K);
+        assert_eq!(suite.cases[0].expected[0].symbol.as_deref(), Some("load"));
+
+        let empty = "cases:\n  - query: parse config\n    expected:\n      - {}\n";
+        assert!(BenchSuite::parse(empty).is_err());
+        assert!(BenchSuite::parse("k: 0\ncases: []\n").is_err());
+    }
+
+    #[test]
+    fn test_expected_result_matches_file_and_symbol() {
+        let mut result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "src/config.rs".to_string(),
+            1,
+            3,
+            String::new(),
+            0.9,
+        );
+        result.metadata.symbol_name = Some("load".to_string());
+
+        let expected = |file: Option<&str>, symbol: Option<&str>| ExpectedResult {
+            file: file.map(str::to_string),
+            symbol: symbol.map(str::to_string),
+        };
+        assert!(expected(Some("./src/config.rs"), None).matches(&result));
+        assert!(expected(None, Some("load")).matches(&result));
+        assert!(expected(Some("src/config.rs"), Some("load")).matches(&result));
+        assert!(!expected(Some("src/config.rs"), Some("save")).matches(&result));
+        assert!(!expected(Some("config.rs"), None).matches(&result));
+    }
+
+    #[test]
+    fn test_report_metrics() {
+        let report = BenchReport::new(
+            4,
+            vec![
+                case("identifier", Some(1), 2, 10.0),
+                case("identifier", Some(2), 1, 30.0),
+                case("natural_language", None, 0, 20.0),
+            ],
+        );
+
+        let identifier = &report.strategies["identifier"];
+        assert_eq!(identifier.queries, 2);
+        assert!((identifier.precision_at_k - 0.375).abs() < 1e-9);
+        assert!((identifier.mrr - 0.75).abs() < 1e-9);
+
+        assert_eq!(report.overall.queries, 3);
+        assert!((report.overall.mrr - 0.5).abs() < 1e-9);
+        assert_eq!(report.overall.latency_p50_ms, 20.0);
+        assert_eq!(report.overall.latency_p99_ms, 30.0);
+    }
+
+    #[test]
+    fn test_regressions_against_baseline() {
+        let baseline = BenchReport::new(
+            2,
+            vec![
+                case("identifier", Some(1), 1, 10.0),
+                case("keywords", Some(1), 1, 10.0),
+            ],
+        );
+        let current = BenchReport::new(
+            2,
+            vec![
+                case("identifier", Some(1), 1, 50.0),
+                case("keywords", Some(2), 1, 10.0),
+            ],
+        );
+
+        assert!(baseline.regressions(&baseline, 0.0).is_empty());
+        let regressions = current.regressions(&baseline, 0.01);
+        assert_eq!(regressions.len(), 2);
+        assert!(regressions[0].starts_with("overall MRR"));
+        assert!(regressions[1].starts_with("keywords MRR"));
+    }
+}
@@ -0,0 +1,688 @@
+//! LRU cache of search results, and database cache of query embeddings.
+//!
+//! Entries are keyed by the normalized query, limit and search options, and
+//! are only valid for the index generation they were computed at: any write
+//! to the index bumps the generation and invalidates the whole cache. Results
+//! can optionally be persisted in the database to survive restarts.
+//!
+//! References found by `find_references` are cached the same way, but outlive
+//! a change of generation as long as the files they were found in, and those
+//! using the symbol, were not indexed again: writes elsewhere in the index
+//! leave them valid.
+//!
+//! Query embeddings do not depend on the index, only on the embedding model,
+//! so they are kept in the database across index changes and restarts until
+//! they expire.
+
+use crate::query::{FindRefsOptions, SearchOptions, SearchStrategy};
+use crate::results::SearchResult;
+use anyhow::Result;
+use semantiq_embeddings::EmbeddingModel;
+use semantiq_index::{IndexStore, QueryCacheConfig, resolve_path};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+/// Ranked search results, before freshness verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedSearch {
+    pub results: Vec<SearchResult>,
+    pub strategy: Option<SearchStrategy>,
+}
+
+struct CacheEntry {
+    search: CachedSearch,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Index generation the entries were computed at
+    generation: u64,
+    entries: HashMap<String, CacheEntry>,
+    /// Monotonic counter recording entry use, for LRU eviction
+    tick: u64,
+}
+
+impl CacheState {
+    /// Drop every entry if the index moved on since they were cached.
+    fn sync_generation(&mut self, generation: u64) {
+        if self.generation != generation {
+            if !self.entries.is_empty() {
+                debug!(
+                    "Index generation {} -> {}, dropping {} cached queries",
+                    self.generation,
+                    generation,
+                    self.entries.len()
+                );
+            }
+            self.entries.clear();
+            self.generation = generation;
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// In-memory LRU cache of search results with optional database persistence.
+pub struct QueryCache {
+    capacity: usize,
+    persist: bool,
+    state: Mutex<CacheState>,
+}
+
+impl QueryCache {
+    pub fn new(config: &QueryCacheConfig) -> Self {
+        Self {
+            capacity: config.capacity.max(1),
+            persist: config.persist,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Build the cache key of a search.
+    ///
+    /// Whitespace in the query is collapsed so that trivially different
+    /// spellings of the same search share an entry.
+    pub fn key(query: &str, limit: usize, options: &SearchOptions) -> String {
+        let query = normalize_query(query);
+        let sorted = |values: &Option<Vec<String>>| {
+            values.as_ref().map(|v| {
+                let mut v = v.clone();
+                v.sort();
+                v
+            })
+        };
+        format!(
+            "{}\u{1f}{}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}\u{1f}{}\u{1f}{:?}\u{1f}{}\u{1f}{}\u{1f}{}\u{1f}{}\u{1f}{:?}",
+            query,
+            limit,
+            options.min_score,
+            sorted(&options.file_types),
+            sorted(&options.symbol_kinds),
+            sorted(&options.packages),
+            sorted(&options.qualifiers),
+            options.scope,
+            options.explain_scores,
+            options.dedup,
+            options.exclude_conditional,
+            options.include_generated,
+            options.exclude_comments,
+            options.exclude_strings,
+            options.route
+        )
+    }
+
+    /// Look up a search at the current index generation.
+    pub(crate) fn get(
+        &self,
+        store: &IndexStore,
+        key: &str,
+        generation: u64,
+    ) -> Option<CachedSearch> {
+        let mut state = self.state.lock().ok()?;
+        state.sync_generation(generation);
+
+        let tick = state.next_tick();
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.last_used = tick;
+            return Some(entry.search.clone());
+        }
+
+        if !self.persist {
+            return None;
+        }
+        let payload = match store.get_cached_query(key, generation) {
+            Ok(payload) => payload?,
+            Err(e) => {
+                warn!("Failed to read persisted query cache: {}", e);
+                return None;
+            }
+        };
+        let search: CachedSearch = serde_json::from_str(&payload).ok()?;
+        self.insert(&mut state, key, search.clone());
+        Some(search)
+    }
+
+    /// Store a search computed at `generation`.
+    pub(crate) fn put(&self, store: &IndexStore, key: &str, generation: u64, search: CachedSearch) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.sync_generation(generation);
+
+        if self.persist {
+            let result = serde_json::to_string(&search)
+                .map_err(anyhow::Error::from)
+                .and_then(|payload| {
+                    store.put_cached_query(key, generation, &payload, self.capacity)
+                });
+            if let Err(e) = result {
+                warn!("Failed to persist query cache entry: {}", e);
+            }
+        }
+
+        self.insert(&mut state, key, search);
+    }
+
+    /// Drop every cached search, including persisted ones.
+    pub fn clear(&self, store: &IndexStore) {
+        if let Ok(mut state) = self.state.lock() {
+            state.entries.clear();
+        }
+        if self.persist
+            && let Err(e) = store.clear_query_cache()
+        {
+            warn!("Failed to clear persisted query cache: {}", e);
+        }
+    }
+
+    /// Number of searches cached in memory.
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|s| s.entries.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&self, state: &mut CacheState, key: &str, search: CachedSearch) {
+        if !state.entries.contains_key(key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+
+        let last_used = state.next_tick();
+        state
+            .entries
+            .insert(key.to_string(), CacheEntry { search, last_used });
+    }
+}
+
+struct RefsEntry {
+    /// Index generation the references were last checked at
+    generation: u64,
+    results: Vec<SearchResult>,
+    /// Content hash of the files with a reference or an occurrence of the
+    /// symbol, by path
+    files: BTreeMap<String, String>,
+    /// Size and modification time on disk of the files with a reference
+    on_disk: Vec<Option<(u64, SystemTime)>>,
+    last_used: u64,
+}
+
+/// In-memory LRU cache of the references of symbols.
+pub struct RefsCache {
+    capacity: usize,
+    state: Mutex<RefsState>,
+}
+
+#[derive(Default)]
+struct RefsState {
+    entries: HashMap<String, RefsEntry>,
+    /// Monotonic counter recording entry use, for LRU eviction
+    tick: u64,
+}
+
+impl RefsCache {
+    pub fn new(config: &QueryCacheConfig) -> Self {
+        Self {
+            capacity: config.capacity.max(1),
+            state: Mutex::new(RefsState::default()),
+        }
+    }
+
+    /// Build the cache key of a lookup of the references of `symbol`.
+    pub fn key(symbol: &str, options: &FindRefsOptions) -> String {
+        format!(
+            "{}\u{1f}{}\u{1f}{:?}\u{1f}{:?}\u{1f}{}\u{1f}{}\u{1f}{}",
+            symbol,
+            options.limit,
+            options.kind,
+            options.path_prefix,
+            options.exclude_tests,
+            options.exclude_comments,
+            options.exclude_strings
+        )
+    }
+
+    /// Look up the references of `symbol` at the current index generation,
+    /// in the project at `root`. An entry of an earlier generation is kept
+    /// when the files of its references and of the occurrences of the
+    /// symbol are unchanged. Files of references changed on disk since
+    /// drop the entry, their lines are read again.
+    pub(crate) fn get(
+        &self,
+        store: &IndexStore,
+        root: &Path,
+        key: &str,
+        symbol: &str,
+        generation: u64,
+    ) -> Option<Vec<SearchResult>> {
+        let mut state = self.state.lock().ok()?;
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(key)?;
+
+        if disk_stamps(root, &entry.results) != entry.on_disk {
+            debug!("Files of the references of '{}' changed on disk", symbol);
+            state.entries.remove(key);
+            return None;
+        }
+        if entry.generation != generation {
+            let paths: Vec<&str> = entry.files.keys().map(String::as_str).collect();
+            match store.get_name_file_hashes(symbol, &paths) {
+                Ok(files) if files == entry.files => entry.generation = generation,
+                Ok(_) => {
+                    debug!("Files of the references of '{}' changed", symbol);
+                    state.entries.remove(key);
+                    return None;
+                }
+                Err(e) => {
+                    warn!("Failed to check cached references: {}", e);
+                    return None;
+                }
+            }
+        }
+        entry.last_used = tick;
+        Some(entry.results.clone())
+    }
+
+    /// Store the references of `symbol` found at `generation` in the
+    /// project at `root`.
+    pub(crate) fn put(
+        &self,
+        store: &IndexStore,
+        root: &Path,
+        key: &str,
+        symbol: &str,
+        generation: u64,
+        results: Vec<SearchResult>,
+    ) {
+        let paths: Vec<&str> = results.iter().map(|r| r.file_path.as_str()).collect();
+        let files = match store.get_name_file_hashes(symbol, &paths) {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("Failed to cache references: {}", e);
+                return;
+            }
+        };
+        let on_disk = disk_stamps(root, &results);
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        if !state.entries.contains_key(key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.tick += 1;
+        let last_used = state.tick;
+        state.entries.insert(
+            key.to_string(),
+            RefsEntry {
+                generation,
+                results,
+                files,
+                on_disk,
+                last_used,
+            },
+        );
+    }
+
+    /// Drop every cached lookup.
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.entries.clear();
+        }
+    }
+
+    /// Number of lookups cached.
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|s| s.entries.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Cache of query embeddings in the database, shared by the processes
+/// using the index.
+#[derive(Clone)]
+pub(crate) struct QueryEmbeddingCache {
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl QueryEmbeddingCache {
+    /// `None` when `query_embeddings` is disabled
+    pub(crate) fn new(config: &QueryCacheConfig) -> Option<Self> {
+        config.query_embeddings.then(|| Self {
+            capacity: config.query_embeddings_capacity.max(1),
+            ttl: Duration::from_secs(config.query_embeddings_ttl_days * 24 * 60 * 60),
+        })
+    }
+
+    /// Embed `query` with `model` (named `model_name`), unless its embedding
+    /// is cached.
+    pub(crate) fn embed(
+        &self,
+        store: &IndexStore,
+        model_name: &str,
+        model: &dyn EmbeddingModel,
+        query: &str,
+    ) -> Result<Vec<f32>> {
+        if model.is_placeholder() {
+            return model.embed(query);
+        }
+
+        let query = normalize_query(query);
+        match store.get_query_embedding(model_name, &query, self.ttl) {
+            Ok(Some(embedding)) => {
+                debug!("Query embedding cache hit for '{}'", query);
+                return Ok(embedding);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read cached query embedding: {}", e),
+        }
+
+        let embedding = model.embed(&query)?;
+        if let Err(e) =
+            store.put_query_embedding(model_name, &query, &embedding, self.ttl, self.capacity)
+        {
+            warn!("Failed to cache query embedding: {}", e);
+        }
+        Ok(embedding)
+    }
+}
+
+/// Size and modification time of the file of each result in the project
+/// at `root`, `None` for a file that cannot be read
+fn disk_stamps(root: &Path, results: &[SearchResult]) -> Vec<Option<(u64, SystemTime)>> {
+    results
+        .iter()
+        .map(|result| {
+            let metadata = fs::metadata(resolve_path(root, &result.file_path)).ok()?;
+            Some((metadata.len(), metadata.modified().ok()?))
+        })
+        .collect()
+}
+
+/// Collapse whitespace, so that trivially different spellings of the same
+/// query share cache entries
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{DedupPolicy, SearchScope};
+
+    fn cached(path: &str) -> CachedSearch {
+        CachedSearch {
+            results: vec![SearchResult::new(
+                crate::results::SearchResultKind::Symbol,
+                path.to_string(),
+                1,
+                1,
+                "fn main() {}".to_string(),
+                0.9,
+            )],
+            strategy: None,
+        }
+    }
+
+    fn cache(capacity: usize, persist: bool) -> QueryCache {
+        QueryCache::new(&QueryCacheConfig {
+            enabled: true,
+            capacity,
+            persist,
+            ..QueryCacheConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_key_normalizes_query_and_options() {
+        let options = SearchOptions::new().with_file_types(vec!["ts".into(), "rs".into()]);
+        let reordered = SearchOptions::new().with_file_types(vec!["rs".into(), "ts".into()]);
+        assert_eq!(
+            QueryCache::key("  parse   config ", 20, &options),
+            QueryCache::key("parse config", 20, &reordered)
+        );
+        assert_ne!(
+            QueryCache::key("parse config", 20, &options),
+            QueryCache::key("parse config", 10, &options)
+        );
+        assert_ne!(
+            QueryCache::key("parse config", 20, &SearchOptions::new()),
+            QueryCache::key(
+                "parse config",
+                20,
+                &SearchOptions::new().with_scope(SearchScope::All)
+            )
+        );
+        assert_ne!(
+            QueryCache::key("parse config", 20, &SearchOptions::new()),
+            QueryCache::key(
+                "parse config",
+                20,
+                &SearchOptions::new().with_explain_scores(true)
+            )
+        );
+        assert_ne!(
+            QueryCache::key("parse config", 20, &SearchOptions::new()),
+            QueryCache::key(
+                "parse config",
+                20,
+                &SearchOptions::new().with_dedup(DedupPolicy::Exact)
+            )
+        );
+    }
+
+    #[test]
+    fn test_generation_change_invalidates_entries() {
+        let store = IndexStore::open_in_memory().unwrap();
+        let cache = cache(8, false);
+        cache.put(&store, "q", 1, cached("a.rs"));
+        assert!(cache.get(&store, "q", 1).is_some());
+
+        assert!(cache.get(&store, "q", 2).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_entry_is_evicted() {
+        let store = IndexStore::open_in_memory().unwrap();
+        let cache = cache(2, false);
+        cache.put(&store, "a", 1, cached("a.rs"));
+        cache.put(&store, "b", 1, cached("b.rs"));
+        // Touch "a" so that "b" becomes the least recently used
+        assert!(cache.get(&store, "a", 1).is_some());
+        cache.put(&store, "c", 1, cached("c.rs"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&store, "a", 1).is_some());
+        assert!(cache.get(&store, "b", 1).is_none());
+        assert!(cache.get(&store, "c", 1).is_some());
+    }
+
+    #[test]
+    fn test_persisted_entries_survive_a_new_cache() {
+        let store = IndexStore::open_in_memory().unwrap();
+        cache(8, true).put(&store, "q", 5, cached("a.rs"));
+
+        let restarted = cache(8, true);
+        let hit = restarted.get(&store, "q", 5).expect("persisted entry");
+        assert_eq!(hit.results[0].file_path, "a.rs");
+        assert!(restarted.get(&store, "q", 6).is_none());
+    }
+
+    /// Index `path` with `content`, using `name` if given
+    fn write_file(store: &IndexStore, path: &str, content: &str, name: Option<&str>) {
+        let occurrences: Vec<semantiq_parser::Occurrence> = name
+            .map(|name| semantiq_parser::Occurrence {
+                name: name.to_string(),
+                count: 1,
+                lines: vec![1],
+            })
+            .into_iter()
+            .collect();
+        store
+            .write_file_index(&semantiq_index::FileIndexData {
+                occurrences: &occurrences,
+                ..semantiq_index::FileIndexData::new(
+                    path,
+                    Some("rust"),
+                    content,
+                    content.len() as i64,
+                    0,
+                )
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_refs_survive_changes_to_other_files() {
+        let store = IndexStore::open_in_memory().unwrap();
+        write_file(&store, "a.rs", "fn main() { parse() }", Some("parse"));
+        write_file(&store, "b.rs", "fn other() {}", None);
+        let cache = RefsCache::new(&QueryCacheConfig::default());
+        let key = RefsCache::key("parse", &FindRefsOptions::default());
+        let generation = store.index_generation().unwrap();
+        cache.put(
+            &store,
+            Path::new("/nonexistent"),
+            &key,
+            "parse",
+            generation,
+            cached("a.rs").results,
+        );
+
+        // A file without the symbol changes
+        write_file(&store, "b.rs", "fn other() { 1 }", None);
+        let generation = store.index_generation().unwrap();
+        let hit = cache
+            .get(&store, Path::new("/nonexistent"), &key, "parse", generation)
+            .expect("kept");
+        assert_eq!(hit[0].file_path, "a.rs");
+
+        // Another file starts using the symbol
+        write_file(&store, "b.rs", "fn other() { parse() }", Some("parse"));
+        let generation = store.index_generation().unwrap();
+        assert!(
+            cache
+                .get(&store, Path::new("/nonexistent"), &key, "parse", generation)
+                .is_none()
+        );
+        assert!(cache.is_empty());
+
+        // The file of a reference is indexed again
+        cache.put(
+            &store,
+            Path::new("/nonexistent"),
+            &key,
+            "parse",
+            generation,
+            cached("a.rs").results,
+        );
+        write_file(&store, "a.rs", "fn main() {}", None);
+        let generation = store.index_generation().unwrap();
+        assert!(
+            cache
+                .get(&store, Path::new("/nonexistent"), &key, "parse", generation)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_refs_key_follows_options() {
+        let options = FindRefsOptions::default();
+        assert_ne!(
+            RefsCache::key("parse", &options),
+            RefsCache::key("parser", &options)
+        );
+        assert_ne!(
+            RefsCache::key("parse", &options),
+            RefsCache::key(
+                "parse",
+                &FindRefsOptions {
+                    exclude_tests: true,
+                    ..FindRefsOptions::default()
+                }
+            )
+        );
+    }
+
+    /// Model counting the texts it embeds
+    #[derive(Default)]
+    struct CountingModel {
+        embedded: std::sync::atomic::AtomicUsize,
+    }
+
+    impl EmbeddingModel for CountingModel {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.embedded
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(vec![text.len() as f32, 1.0])
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            texts.iter().map(|t| self.embed(t)).collect()
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_query_embeddings_are_reused_across_caches() {
+        let store = IndexStore::open_in_memory().unwrap();
+        let model = CountingModel::default();
+        let config = QueryCacheConfig::default();
+        let cache = QueryEmbeddingCache::new(&config).unwrap();
+
+        let embedding = cache
+            .embed(&store, "minilm", &model, "parse config")
+            .unwrap();
+        let restarted = QueryEmbeddingCache::new(&config).unwrap();
+        let cached = restarted
+            .embed(&store, "minilm", &model, " parse  config ")
+            .unwrap();
+        assert_eq!(cached, embedding);
+        assert_eq!(model.embedded.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        // Embeddings of another model are not shared
+        cache
+            .embed(&store, "multilingual-minilm", &model, "parse config")
+            .unwrap();
+        assert_eq!(model.embedded.load(std::sync::atomic::Ordering::Relaxed), 2);
+
+        let disabled = QueryCacheConfig {
+            query_embeddings: false,
+            ..QueryCacheConfig::default()
+        };
+        assert!(QueryEmbeddingCache::new(&disabled).is_none());
+    }
+}
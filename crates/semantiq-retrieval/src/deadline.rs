@@ -0,0 +1,109 @@
+//! Time budget and cancellation of searches.
+//!
+//! A search checks its deadline between strategies and while walking files
+//! or ranking chunks file by file, and returns the results found so far once
+//! the deadline passes or the caller cancels it. A single vector scan of the
+//! whole index is not interrupted.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// When a search gives up: after a timeout, or when cancelled.
+///
+/// Clones share the cancellation flag, so the caller keeps a clone to
+/// [`cancel`](Deadline::cancel) a search running elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct Deadline {
+    /// Time budget, `None` for the engine default (`search.timeout_ms`)
+    timeout: Option<Duration>,
+    /// Instant the budget runs out, set when the search starts
+    at: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+    /// The budget is counting, see [`Deadline::start`]
+    started: bool,
+}
+
+impl Deadline {
+    /// Deadline with the engine's default timeout
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deadline with a given timeout, `Duration::ZERO` for none
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop the search as soon as it next checks its deadline
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Start counting the timeout, or `default_timeout` when none was given.
+    /// A deadline already started keeps its instant, so that searches run
+    /// together share one budget.
+    pub(crate) fn start(&self, default_timeout: Duration) -> Self {
+        if self.started {
+            return self.clone();
+        }
+        let timeout = self.timeout.unwrap_or(default_timeout);
+        Self {
+            timeout: Some(timeout),
+            at: (!timeout.is_zero()).then(|| Instant::now() + timeout),
+            cancelled: Arc::clone(&self.cancelled),
+            started: true,
+        }
+    }
+
+    /// The search was cancelled or ran out of time
+    pub fn expired(&self) -> bool {
+        self.is_cancelled() || self.at.is_some_and(|at| Instant::now() >= at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_expires_after_timeout() {
+        let deadline = Deadline::new().with_timeout(Duration::from_millis(1));
+        assert!(!deadline.expired());
+
+        let started = deadline.start(Duration::from_secs(60));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(started.expired());
+    }
+
+    #[test]
+    fn test_zero_timeout_never_expires() {
+        let started = Deadline::new().start(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(!started.expired());
+    }
+
+    #[test]
+    fn test_started_deadline_keeps_its_instant() {
+        let started = Deadline::new()
+            .with_timeout(Duration::from_millis(1))
+            .start(Duration::from_secs(60));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(started.start(Duration::from_secs(60)).expired());
+    }
+
+    #[test]
+    fn test_cancel_reaches_started_deadline() {
+        let deadline = Deadline::new();
+        let started = deadline.start(Duration::from_secs(60));
+        assert!(!started.expired());
+
+        deadline.cancel();
+        assert!(started.expired());
+    }
+}
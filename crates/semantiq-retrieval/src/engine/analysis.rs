@@ -1,32 +1,118 @@
 //! Code analysis functionality for RetrievalEngine.
 
 use super::RetrievalEngine;
-use crate::query::{Query, SearchOptions};
+use super::search::symbol_span;
+use crate::cache::RefsCache;
+use crate::query::{FindRefsOptions, Query, SearchOptions};
 use crate::results::{SearchResult, SearchResultKind, SearchResultMetadata, SearchResults};
 use anyhow::Result;
+use semantiq_index::workspace::{WorkspacePackage, imported_package, owning_package};
+use semantiq_index::{SymbolEdge, chunk_title, is_c_family, is_c_source, normalize_path};
+use semantiq_parser::{ImportedSymbol, RelationKind, SymbolQualifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::time::Instant;
-use tracing::info;
+use tracing::{debug, info};
 
 /// Information about a dependency relationship.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyInfo {
     pub target_path: String,
     pub import_name: Option<String>,
     pub kind: String,
+    /// Symbols named by the import statement, empty for whole-module imports
+    #[serde(default)]
+    pub symbols: Vec<ImportedSymbol>,
+    /// Workspace package of the imported code (of the importing file for
+    /// dependents), unknown for third-party libraries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// Whether the dependency stays within the package of the analyzed file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<DependencyScope>,
+    /// Indexed file a C or C++ include resolves to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_path: Option<String>,
+    /// Header through which a dependent uses a C or C++ source, paired
+    /// with it, rather than including the analyzed file itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+/// A symbol used by, or using, the analyzed one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDependency {
+    pub name: String,
+    pub kind: String,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Type or module the symbol belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// First line referencing the used symbol, in the file of the using one
+    pub line: usize,
+    /// Lines referencing the used symbol
+    pub references: usize,
+}
+
+impl From<SymbolEdge> for SymbolDependency {
+    fn from(edge: SymbolEdge) -> Self {
+        Self {
+            name: edge.symbol.name,
+            kind: edge.symbol.kind,
+            file_path: edge.file_path,
+            start_line: edge.symbol.start_line.max(0) as usize,
+            end_line: edge.symbol.end_line.max(0) as usize,
+            parent: edge.symbol.parent,
+            line: edge.line,
+            references: edge.count,
+        }
+    }
+}
+
+/// Package boundary crossed by a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyScope {
+    /// Both files belong to the same workspace package
+    IntraPackage,
+    /// The files belong to different workspace packages
+    CrossPackage,
+}
+
+impl DependencyScope {
+    fn between(a: Option<&WorkspacePackage>, b: Option<&WorkspacePackage>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) if a == b => Some(DependencyScope::IntraPackage),
+            (Some(_), Some(_)) => Some(DependencyScope::CrossPackage),
+            _ => None,
+        }
+    }
 }
 
 /// Explanation of a symbol including definitions and usages.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolExplanation {
     pub name: String,
     pub found: bool,
     pub definitions: Vec<SymbolDefinition>,
+    /// Occurrences of the name outside its definitions
     pub usage_count: usize,
+    /// Files with at least one of these occurrences
+    #[serde(default)]
+    pub usage_files: usize,
     pub related_symbols: Vec<String>,
+    /// Index generation the symbol is explained at, `None` for the current
+    /// index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<u64>,
 }
 
 /// Definition location and metadata for a symbol.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolDefinition {
     pub file_path: String,
     pub kind: String,
@@ -34,23 +120,88 @@ pub struct SymbolDefinition {
     pub end_line: usize,
     pub signature: Option<String>,
     pub doc_comment: Option<String>,
+    /// Visibility, `async`, `static`, `abstract` and generic parameters
+    #[serde(default)]
+    pub qualifiers: SymbolQualifiers,
+    /// Conditions under which the definition is compiled, e.g. `test`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg_context: Option<String>,
 }
 
 impl RetrievalEngine {
-    /// Find all references to a symbol (definitions + usages).
-    pub fn find_references(&self, symbol_name: &str, limit: usize) -> Result<SearchResults> {
-        info!(symbol = %symbol_name, limit = limit, "Finding references");
+    /// Find references to a symbol (definitions and/or usages), filtered by `options`.
+    ///
+    /// Usages start with the JSX elements rendering the symbol as a React
+    /// component, followed by the text matches. With
+    /// `options.cross_language`, the references of the symbols linked to it
+    /// in other languages (see [`RetrievalEngine::cross_language_links`])
+    /// follow. With `options.as_of`, the references are those the index had
+    /// at that generation: definitions with the lines they had, and one
+    /// usage per file counting its references, their lines not being kept.
+    pub fn find_references(
+        &self,
+        symbol_name: &str,
+        options: &FindRefsOptions,
+    ) -> Result<SearchResults> {
+        let limit = options.limit;
+        info!(
+            symbol = %symbol_name,
+            limit = limit,
+            kind = ?options.kind,
+            path_prefix = ?options.path_prefix,
+            exclude_tests = options.exclude_tests,
+            exclude_comments = options.exclude_comments,
+            exclude_strings = options.exclude_strings,
+            cross_language = options.cross_language,
+            "Finding references"
+        );
+        if let Some(generation) = options.as_of {
+            return self.find_references_as_of(symbol_name, options, generation);
+        }
         let start = Instant::now();
+
+        // Serve repeated lookups from the cache while their files are unchanged
+        let generation = self.store.index_generation()?;
+        let cache = self
+            .refs_cache
+            .as_ref()
+            .filter(|_| !options.cross_language)
+            .map(|cache| (cache, RefsCache::key(symbol_name, options)));
+        if let Some((cache, key)) = &cache
+            && let Some(hit) = cache.get(
+                &self.store,
+                Path::new(&self.root_path),
+                key,
+                symbol_name,
+                generation,
+            )
+        {
+            debug!(symbol = %symbol_name, "References cache hit");
+            let mut results = SearchResults::new(
+                symbol_name.to_string(),
+                hit,
+                start.elapsed().as_millis() as u64,
+            );
+            results.cached = true;
+            return Ok(results);
+        }
+
         let mut results = Vec::new();
 
         // Find symbol definitions
-        let symbols = self.store.find_symbol_by_name(symbol_name)?;
+        let symbols = if options.kind.includes_definitions() {
+            self.store.find_symbol_by_name(symbol_name)?
+        } else {
+            Vec::new()
+        };
 
         for symbol in &symbols {
-            if let Some(file) = self
-                .store
-                .get_file_by_path(&self.get_file_path(symbol.file_id)?)?
-            {
+            let file_path = self.get_file_path(symbol.file_id)?;
+            if !options.accepts_path(&file_path) {
+                continue;
+            }
+
+            if let Some(file) = self.store.get_file_by_path(&file_path)? {
                 let content = self.read_file_lines(
                     &file.path,
                     symbol.start_line as usize,
@@ -71,31 +222,119 @@ impl RetrievalEngine {
                         symbol_kind: Some(symbol.kind.clone()),
                         match_type: Some("definition".to_string()),
                         context: symbol.signature.clone(),
-                    }),
+                        title: Some(chunk_title(
+                            &symbol.kind,
+                            &symbol.name,
+                            symbol.signature.as_deref(),
+                            symbol.doc_comment.as_deref(),
+                        )),
+                        signature: symbol.signature.clone(),
+                        parent: symbol.parent.clone(),
+                        qualifiers: Some(symbol.qualifiers),
+                        cfg_context: symbol.cfg_context.clone(),
+                        explanation: None,
+                        ..SearchResultMetadata::default()
+                    })
+                    .with_span(symbol_span(symbol)),
                 );
             }
         }
 
-        // Find usages via text search
-        let usage_results =
-            self.search_text(&Query::new(symbol_name), limit, &SearchOptions::default())?;
+        if options.kind.includes_usages() {
+            // JSX elements rendering the symbol as a component
+            for relation in self
+                .store
+                .find_relations_to(symbol_name, RelationKind::Renders)?
+            {
+                if !options.accepts_path(&relation.file_path) {
+                    continue;
+                }
+                let start_line = relation.start_line as usize;
+                let end_line = relation.end_line as usize;
+                let content = self.read_file_lines(&relation.file_path, start_line, end_line)?;
+                results.push(
+                    SearchResult::new(
+                        SearchResultKind::Reference,
+                        relation.file_path,
+                        start_line,
+                        end_line,
+                        content,
+                        1.0,
+                    )
+                    .with_metadata(SearchResultMetadata {
+                        symbol_name: Some(symbol_name.to_string()),
+                        match_type: Some("render".to_string()),
+                        parent: Some(relation.type_name),
+                        ..SearchResultMetadata::default()
+                    }),
+                );
+            }
+
+            // Find usages via text search. Path filters are applied afterwards, so
+            // over-fetch to leave room for filtered-out matches.
+            let fetch_limit = if options.path_prefix.is_some() || options.exclude_tests {
+                limit.saturating_mul(4)
+            } else {
+                limit
+            };
+            let usage_results = self.search_text(
+                &Query::new(symbol_name),
+                fetch_limit,
+                &SearchOptions::default()
+                    .with_exclude_comments(options.exclude_comments)
+                    .with_exclude_strings(options.exclude_strings),
+            )?;
+
+            // Deduplicate: track seen (file_path, start_line) pairs from symbol definitions
+            let mut seen = std::collections::HashSet::new();
+            for r in &results {
+                seen.insert((r.file_path.clone(), r.start_line));
+            }
 
-        // Deduplicate: track seen (file_path, start_line) pairs from symbol definitions
-        let mut seen = std::collections::HashSet::new();
-        for r in &results {
-            seen.insert((r.file_path.clone(), r.start_line));
+            for mut result in usage_results {
+                if !options.accepts_path(&result.file_path) {
+                    continue;
+                }
+                let key = (result.file_path.clone(), result.start_line);
+                if seen.insert(key) {
+                    result.kind = SearchResultKind::Reference;
+                    result.metadata.match_type = Some("usage".to_string());
+                    results.push(result);
+                }
+            }
         }
 
-        for mut result in usage_results {
-            let key = (result.file_path.clone(), result.start_line);
-            if seen.insert(key) {
-                result.kind = SearchResultKind::Reference;
-                result.metadata.match_type = Some("usage".to_string());
-                results.push(result);
+        if options.cross_language && results.len() < limit {
+            let linked_options = options.clone().following_cross_language(false);
+            let mut seen: HashSet<(String, usize)> = results
+                .iter()
+                .map(|r| (r.file_path.clone(), r.start_line))
+                .collect();
+            let mut followed = HashSet::from([symbol_name.to_string()]);
+            for link in self.cross_language_links(symbol_name)? {
+                if !followed.insert(link.name.clone()) {
+                    continue;
+                }
+                let linked = self.find_references(&link.name, &linked_options)?;
+                for result in linked.results {
+                    if seen.insert((result.file_path.clone(), result.start_line)) {
+                        results.push(result);
+                    }
+                }
             }
         }
 
         results.truncate(limit);
+        if let Some((cache, key)) = &cache {
+            cache.put(
+                &self.store,
+                Path::new(&self.root_path),
+                key,
+                symbol_name,
+                generation,
+                results.clone(),
+            );
+        }
 
         let search_time = start.elapsed().as_millis() as u64;
         Ok(SearchResults::new(
@@ -111,12 +350,23 @@ impl RetrievalEngine {
 
         if let Some(file) = self.store.get_file_by_path(file_path)? {
             let records = self.store.get_dependencies(file.id)?;
+            let packages = self.store.get_packages()?;
+            let own_package = owning_package(&packages, &file.path);
 
             for record in records {
+                let package = imported_package(&packages, &file.path, &record.target_path);
+                let resolved_path = is_c_family(&file.path)
+                    .then(|| self.resolve_include(&file.path, &record))
+                    .flatten();
                 deps.push(DependencyInfo {
+                    resolved_path,
+                    via: None,
                     target_path: record.target_path,
                     import_name: record.import_name,
                     kind: record.kind,
+                    symbols: record.symbols,
+                    package: package.map(|p| p.name.clone()),
+                    scope: DependencyScope::between(own_package, package),
                 });
             }
         }
@@ -124,28 +374,92 @@ impl RetrievalEngine {
         Ok(deps)
     }
 
-    /// Get dependents for a file (what imports it).
+    /// Get dependents for a file (what imports it). The dependents of a C
+    /// or C++ source are those of its headers.
     pub fn get_dependents(&self, file_path: &str) -> Result<Vec<DependencyInfo>> {
         let mut deps = Vec::new();
 
-        let records = self.store.get_dependents(file_path)?;
+        let file_path = normalize_path(file_path);
+        let records = self.store.get_dependents(&file_path)?;
+        let packages = self.store.get_packages()?;
+        let own_package = owning_package(&packages, &file_path);
 
+        let mut dependents = Vec::new();
         for record in records {
             let source_path = self.get_file_path(record.source_file_id)?;
+            if is_c_family(&file_path) && !self.may_include(&source_path, &record, &file_path) {
+                continue;
+            }
+            dependents.push((source_path, None, record));
+        }
+        if is_c_source(&file_path) {
+            let mut seen: HashSet<String> = dependents.iter().map(|(p, _, _)| p.clone()).collect();
+            for (source_path, header, record) in self.paired_header_dependents(&file_path)? {
+                if seen.insert(source_path.clone()) {
+                    dependents.push((source_path, Some(header), record));
+                }
+            }
+        }
+
+        for (source_path, via, record) in dependents {
+            let package = owning_package(&packages, &source_path);
             deps.push(DependencyInfo {
+                package: package.map(|p| p.name.clone()),
+                scope: DependencyScope::between(own_package, package),
                 target_path: source_path,
                 import_name: record.import_name,
                 kind: record.kind,
+                symbols: record.symbols,
+                resolved_path: None,
+                via,
             });
         }
 
         Ok(deps)
     }
 
+    /// Get the symbols used by the symbol `symbol` of a file (by calls,
+    /// type annotations or other references), resolved through the imports
+    /// of the file.
+    pub fn get_symbol_dependencies(
+        &self,
+        file_path: &str,
+        symbol: &str,
+    ) -> Result<Vec<SymbolDependency>> {
+        Ok(self
+            .store
+            .get_symbol_dependencies(file_path, symbol)?
+            .into_iter()
+            .map(SymbolDependency::from)
+            .collect())
+    }
+
+    /// Get the symbols using the symbol `symbol` of a file.
+    pub fn get_symbol_dependents(
+        &self,
+        file_path: &str,
+        symbol: &str,
+    ) -> Result<Vec<SymbolDependency>> {
+        Ok(self
+            .store
+            .get_symbol_dependents(file_path, symbol)?
+            .into_iter()
+            .map(SymbolDependency::from)
+            .collect())
+    }
+
+    /// Get the workspace package owning an indexed file.
+    pub fn file_package(&self, file_path: &str) -> Result<Option<String>> {
+        Ok(self
+            .store
+            .get_file_by_path(file_path)?
+            .and_then(|file| file.package))
+    }
+
     /// Get detailed explanation of a symbol.
     pub fn explain_symbol(&self, symbol_name: &str) -> Result<SymbolExplanation> {
         info!(symbol = %symbol_name, "Explaining symbol");
-        let symbols = self.store.find_symbol_by_name(symbol_name)?;
+        let mut symbols = self.store.find_symbol_by_name(symbol_name)?;
 
         if symbols.is_empty() {
             return Ok(SymbolExplanation {
@@ -153,7 +467,31 @@ impl RetrievalEngine {
                 found: false,
                 definitions: Vec::new(),
                 usage_count: 0,
+                usage_files: 0,
                 related_symbols: Vec::new(),
+                as_of: None,
+            });
+        }
+
+        // A SQL table is defined by its CREATE TABLE and altered by later
+        // migrations: list the CREATE first, then the ALTERs in file order.
+        if symbols.iter().any(|s| s.kind == "table") {
+            let mut paths = HashMap::new();
+            for symbol in &symbols {
+                if let Entry::Vacant(e) = paths.entry(symbol.file_id) {
+                    e.insert(self.get_file_path(symbol.file_id)?);
+                }
+            }
+            symbols.sort_by_cached_key(|s| {
+                let alters = s
+                    .signature
+                    .as_deref()
+                    .is_some_and(|sig| sig.trim_start().to_ascii_uppercase().starts_with("ALTER"));
+                (
+                    s.kind != "table" || alters,
+                    paths[&s.file_id].clone(),
+                    s.start_line,
+                )
             });
         }
 
@@ -175,6 +513,8 @@ impl RetrievalEngine {
                 end_line: symbol.end_line as usize,
                 signature: symbol.signature.clone(),
                 doc_comment: symbol.doc_comment.clone(),
+                qualifiers: symbol.qualifiers,
+                cfg_context: symbol.cfg_context.clone(),
             });
 
             // Find related symbols in the same file (only query each file once)
@@ -188,29 +528,17 @@ impl RetrievalEngine {
             }
         }
 
-        // Count usages via FTS5 (much faster than reading files from disk)
-        // Falls back to text search if FTS5 returns no results
-        let usage_count = match self.store.search_symbols(symbol_name, 100) {
-            Ok(fts_results) => {
-                // FTS5 returns symbol definitions; add a conservative estimate
-                // for text usages beyond definitions
-                let definition_count = definitions.len();
-                fts_results.len().saturating_sub(definition_count)
-            }
-            Err(_) => {
-                // Fallback: use text search (slower but more accurate)
-                let usage_results =
-                    self.search_text(&Query::new(symbol_name), 100, &SearchOptions::default())?;
-                usage_results.len()
-            }
-        };
+        // Counted from the identifier occurrences stored at index time
+        let usage = self.store.symbol_usage(symbol_name)?;
 
         Ok(SymbolExplanation {
             name: symbol_name.to_string(),
             found: true,
             definitions,
-            usage_count,
+            usage_count: usage.references,
+            usage_files: usage.files,
             related_symbols: related_symbols.into_iter().collect(),
+            as_of: None,
         })
     }
 }
@@ -0,0 +1,106 @@
+//! Search of a read-only base index along with the local index.
+//!
+//! A base index (see `IndexStore::open_base`) is built elsewhere, e.g.
+//! nightly in CI, and covers the whole project while the local index is
+//! still being built. Its results are merged with the local ones. Files the
+//! local index covers shadow the base index: their base results, indexed
+//! from an older revision, are left out.
+
+use super::RetrievalEngine;
+use crate::cache::CachedSearch;
+use crate::query::{Query, SearchOptions};
+use crate::results::SearchResult;
+use anyhow::Result;
+use semantiq_index::{BaseIndexConfig, IndexStore};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+impl RetrievalEngine {
+    /// Attach the base index of the configuration (`[base]` in
+    /// `semantiq.toml`), if one is configured. A base index that is missing
+    /// or was built by another version is left out with a warning.
+    pub fn with_base_config(self, config: &BaseIndexConfig) -> Self {
+        let Some(path) = config.resolve(Path::new(&self.root_path)) else {
+            return self;
+        };
+        match IndexStore::open_base(&path, &self.store) {
+            Ok(base) => {
+                info!("Searching base index {:?}", path);
+                self.with_base_index(Arc::new(base))
+            }
+            Err(e) => {
+                warn!("Base index disabled: {:#}", e);
+                self
+            }
+        }
+    }
+
+    /// Also search the read-only `base` index (`[base]` in `semantiq.toml`).
+    /// Its results are merged with the local ones, except for the files the
+    /// local index covers.
+    pub fn with_base_index(mut self, base: Arc<IndexStore>) -> Self {
+        let mut engine = Self::with_options(base, &self.root_path, false)
+            .with_search_config(&self.search_config)
+            .with_git_config(&self.git_config);
+        // Both indexes are embedded with the same model, queries are
+        // embedded once
+        engine.include_paths = Arc::clone(&self.include_paths);
+        engine.query_model = Arc::clone(&self.query_model);
+        engine.model_loading = Arc::clone(&self.model_loading);
+        engine.query_embedding_cache = self.query_embedding_cache.clone();
+        // The base index is read-only and its results do not change
+        engine.query_cache = None;
+        engine.refs_cache = None;
+        engine.local_store = Some(Arc::clone(&self.store));
+        self.base = Some(Box::new(engine));
+        self
+    }
+
+    /// The read-only base index searched along with this one, if any
+    pub fn base_index(&self) -> Option<&IndexStore> {
+        self.base.as_ref().map(|base| base.store.as_ref())
+    }
+
+    /// Results of the base index for `query`, verified, expanded and with
+    /// their snippets, leaving out files of the local index. Also returns
+    /// the files of the results that changed since the base index was built.
+    pub(crate) fn search_base(
+        &self,
+        query: &Query,
+        limit: usize,
+        opts: &SearchOptions,
+    ) -> Result<(Vec<SearchResult>, Vec<String>)> {
+        let Some(ref base) = self.base else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+        // Query embeddings are shared, which needs the same model
+        if base.store.embedding_model()? != self.store.embedding_model()? {
+            debug!("Base index skipped: the local index switched to another embedding model");
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let CachedSearch {
+            results: mut base_results,
+            ..
+        } = base.rank_results(query, limit, opts)?;
+
+        let mut shadowed: HashMap<String, bool> = HashMap::new();
+        for result in &base_results {
+            if !shadowed.contains_key(&result.file_path) {
+                let local = self.store.get_file_by_path(&result.file_path)?.is_some();
+                shadowed.insert(result.file_path.clone(), local);
+            }
+        }
+        base_results.retain(|r| !shadowed[&r.file_path]);
+
+        let stale_files = base.verify_freshness(&mut base_results);
+        base.expand_results(&mut base_results, opts);
+        base.attach_snippets(&mut base_results, query);
+        for result in &mut base_results {
+            result.from_base = true;
+        }
+        Ok((base_results, stale_files))
+    }
+}
@@ -0,0 +1,132 @@
+//! Component hierarchy of React codebases for RetrievalEngine.
+//!
+//! The parser records each JSX element naming a component (`<Button />`) as
+//! a `renders` relation from the enclosing component. The tree of a
+//! component follows these relations down, and lists the components
+//! rendering it.
+
+use super::RetrievalEngine;
+use crate::results::ComponentTreeResponse;
+use anyhow::Result;
+use semantiq_index::TypeRelationRecord;
+use semantiq_parser::RelationKind;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Instant;
+use tracing::info;
+
+/// Maximum number of components listed in a tree, across all levels.
+const MAX_TREE_NODES: usize = 200;
+
+/// A component in a render hierarchy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentNode {
+    pub name: String,
+    /// File and line of the first JSX element rendering the component
+    pub file_path: String,
+    pub line: usize,
+    /// Components it renders, down to the requested depth
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ComponentNode>,
+    /// Already an ancestor in the tree (recursive rendering): its children
+    /// are not listed again
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recursive: bool,
+}
+
+impl ComponentNode {
+    fn new(name: String, record: &TypeRelationRecord) -> Self {
+        Self {
+            name,
+            file_path: record.file_path.clone(),
+            line: record.start_line as usize,
+            children: Vec::new(),
+            recursive: false,
+        }
+    }
+}
+
+/// Components left to list in a tree
+struct TreeBudget {
+    remaining: usize,
+    truncated: bool,
+}
+
+impl RetrievalEngine {
+    /// Build the render hierarchy of a component: the components rendering
+    /// it, and the components it renders, recursively down to `depth` levels.
+    ///
+    /// Components are matched by name, like implementations: components
+    /// sharing a name in different files are merged.
+    pub fn component_tree(&self, name: &str, depth: usize) -> Result<ComponentTreeResponse> {
+        info!(component = %name, depth = depth, "Building component tree");
+        let start = Instant::now();
+
+        let mut seen = HashSet::new();
+        let rendered_by = self
+            .store
+            .find_relations_to(name, RelationKind::Renders)?
+            .into_iter()
+            .filter(|r| seen.insert(r.type_name.clone()))
+            .map(|r| ComponentNode::new(r.type_name.clone(), &r))
+            .collect();
+
+        let mut budget = TreeBudget {
+            remaining: MAX_TREE_NODES,
+            truncated: false,
+        };
+        let mut ancestors = vec![name.to_string()];
+        let renders = self.rendered_components(name, depth, &mut ancestors, &mut budget)?;
+
+        Ok(ComponentTreeResponse {
+            component: name.to_string(),
+            depth,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            rendered_by,
+            renders,
+            truncated: budget.truncated,
+        })
+    }
+
+    /// Components rendered by `name`, in the order they first appear, with
+    /// their own children down to `depth` levels.
+    fn rendered_components(
+        &self,
+        name: &str,
+        depth: usize,
+        ancestors: &mut Vec<String>,
+        budget: &mut TreeBudget,
+    ) -> Result<Vec<ComponentNode>> {
+        if depth == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut seen = HashSet::new();
+        let mut components = Vec::new();
+        for record in self
+            .store
+            .find_relations_from(name, RelationKind::Renders)?
+        {
+            if !seen.insert(record.target.clone()) {
+                continue;
+            }
+            if budget.remaining == 0 {
+                budget.truncated = true;
+                break;
+            }
+            budget.remaining -= 1;
+
+            let mut node = ComponentNode::new(record.target.clone(), &record);
+            if ancestors.contains(&record.target) {
+                node.recursive = true;
+            } else {
+                ancestors.push(record.target.clone());
+                node.children =
+                    self.rendered_components(&record.target, depth - 1, ancestors, budget)?;
+                ancestors.pop();
+            }
+            components.push(node);
+        }
+        Ok(components)
+    }
+}
@@ -0,0 +1,160 @@
+//! Environment variable and configuration key usage for RetrievalEngine.
+//!
+//! The parser records the environment variables and configuration keys each
+//! file reads or writes (see `semantiq_parser::config_refs`). Listing the
+//! sites of one name, writes first and with the function making each
+//! access, answers "where does `DATABASE_URL` come from and who uses it"
+//! without grepping every spelling of an environment lookup.
+
+use super::RetrievalEngine;
+use crate::results::ConfigUsageResponse;
+use anyhow::Result;
+use semantiq_index::SymbolRecord;
+use semantiq_parser::{ConfigAccess, ConfigSource};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Symbol kinds holding a value rather than code, only reported as the
+/// symbol making an access when no other symbol contains it
+const VALUE_KINDS: &[&str] = &["variable", "constant"];
+
+/// Names suggested when none matches the one requested
+const MAX_SIMILAR_NAMES: usize = 10;
+
+/// A read or write of an environment variable or configuration key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigUsage {
+    pub name: String,
+    /// `env` or `config`
+    pub source: String,
+    /// `read` or `write`
+    pub access: String,
+    pub file_path: String,
+    pub line: usize,
+    /// Innermost symbol containing the line, when there is one, functions
+    /// and types before variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// The line, trimmed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl RetrievalEngine {
+    /// List the reads and writes of the environment variables or
+    /// configuration keys matching `name`, a name or a glob (`DATABASE_*`),
+    /// of the given source and access or of any.
+    ///
+    /// Writes come first for each name. At most `limit` sites are listed;
+    /// `total_count`, `read_count` and `write_count` have the numbers found.
+    /// When nothing matches, indexed names resembling `name` are suggested.
+    pub fn config_usage(
+        &self,
+        name: &str,
+        source: Option<ConfigSource>,
+        access: Option<ConfigAccess>,
+        limit: usize,
+    ) -> Result<ConfigUsageResponse> {
+        let start = Instant::now();
+        info!(
+            name = %name,
+            source = ?source,
+            access = ?access,
+            limit = limit,
+            "Listing config usage"
+        );
+
+        let records = self.store.get_config_refs(name, source, access)?;
+        let total_count = records.len();
+        let write_count = records
+            .iter()
+            .filter(|r| r.access == ConfigAccess::Write.as_str())
+            .count();
+
+        let mut symbols: HashMap<i64, Vec<SymbolRecord>> = HashMap::new();
+        let mut usages = Vec::new();
+        for record in records.into_iter().take(limit) {
+            let line = record.line as usize;
+            let file_symbols = symbols.entry(record.file_id).or_insert_with(|| {
+                self.store
+                    .get_symbols_by_file(record.file_id)
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to load symbols of {}: {}", record.file_path, e);
+                        Vec::new()
+                    })
+            });
+            // The function reading `const port = process.env.PORT` rather
+            // than `port`, unless the variable is all there is
+            let symbol = file_symbols
+                .iter()
+                .filter(|s| s.start_line as usize <= line && line <= s.end_line as usize)
+                .min_by_key(|s| {
+                    (
+                        VALUE_KINDS.contains(&s.kind.as_str()),
+                        s.end_line - s.start_line,
+                    )
+                })
+                .map(|s| s.name.clone());
+            let code = self
+                .read_file_lines(&record.file_path, line, line)
+                .ok()
+                .map(|code| code.trim().to_string())
+                .filter(|code| !code.is_empty());
+
+            usages.push(ConfigUsage {
+                name: record.name,
+                source: record.source,
+                access: record.access,
+                file_path: record.file_path,
+                line,
+                symbol,
+                code,
+            });
+        }
+
+        let similar = if total_count == 0 {
+            self.similar_config_names(name, source)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(ConfigUsageResponse {
+            name: name.to_string(),
+            source: source.map(|s| s.as_str().to_string()),
+            access: access.map(|a| a.as_str().to_string()),
+            total_count,
+            read_count: total_count - write_count,
+            write_count,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            usages,
+            similar,
+        })
+    }
+
+    /// Indexed names equal to `name` but for case, or containing it or
+    /// contained in it (`DATABASE_URL` for `database_url` or `DATABASE`)
+    fn similar_config_names(
+        &self,
+        name: &str,
+        source: Option<ConfigSource>,
+    ) -> Result<Vec<String>> {
+        let wanted = name.trim_matches('*').to_lowercase();
+        if wanted.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut similar: Vec<String> = self
+            .store
+            .get_config_names(source)?
+            .into_iter()
+            .filter(|candidate| {
+                let candidate = candidate.to_lowercase();
+                candidate.contains(&wanted) || wanted.contains(&candidate)
+            })
+            .collect();
+        similar.sort_by_key(|candidate| !candidate.eq_ignore_ascii_case(&wanted));
+        similar.truncate(MAX_SIMILAR_NAMES);
+        Ok(similar)
+    }
+}
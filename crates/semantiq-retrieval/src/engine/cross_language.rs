@@ -0,0 +1,108 @@
+//! Cross-language links for RetrievalEngine.
+//!
+//! In a polyglot repository the same logical symbol exists once per
+//! language: `get_user` in the Rust core is `getUser` in its TypeScript
+//! bindings. Links come from the binding attributes the parser records
+//! (napi-rs, wasm-bindgen, PyO3, see `semantiq_parser::bindings`) and, for
+//! bindings generated without them, from definitions of the same name in
+//! another naming convention when one side is a binding file (a `.d.ts`
+//! declaration, a `.pyi` stub, a `pkg/` or `bindings/` directory).
+
+use super::RetrievalEngine;
+use anyhow::Result;
+use semantiq_parser::{Language, binding_name_variants};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Endings of the files declaring the symbols a native module exports
+const BINDING_FILE_SUFFIXES: &[&str] = &[".d.ts", ".d.mts", ".d.cts", ".pyi", "_bg.js"];
+
+/// Directories holding generated bindings or the glue code of a native
+/// module
+const BINDING_DIRS: &[&str] = &["pkg", "bindings", "binding", "napi", "ffi", "native"];
+
+/// A symbol standing for another one in another language
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossLanguageLink {
+    /// Name of the linked symbol, which may be spelled like the requested one
+    pub name: String,
+    /// `napi`, `wasm_bindgen` or `pyo3` for links declared by a binding
+    /// attribute, `None` for links inferred from names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    /// The binding attribute, or the definition of the linked symbol
+    pub file_path: String,
+    pub line: usize,
+}
+
+/// Whether a file declares or wraps the symbols of a native module
+pub(crate) fn is_binding_file(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    BINDING_FILE_SUFFIXES
+        .iter()
+        .any(|suffix| path.ends_with(suffix))
+        || path
+            .split('/')
+            .rev()
+            .skip(1)
+            .any(|dir| BINDING_DIRS.contains(&dir))
+}
+
+fn language_of(path: &str) -> Option<Language> {
+    Language::from_path(Path::new(path))
+}
+
+impl RetrievalEngine {
+    /// Symbols linked to `symbol_name` in other languages: the other end of
+    /// the bindings exporting it or exported as it, then the definitions in
+    /// binding files of the same name in another naming convention.
+    pub fn cross_language_links(&self, symbol_name: &str) -> Result<Vec<CrossLanguageLink>> {
+        let mut links: Vec<CrossLanguageLink> = Vec::new();
+
+        for binding in self.store.get_bindings(&[symbol_name.to_string()])? {
+            let name = if binding.symbol == symbol_name {
+                binding.exported_name
+            } else {
+                binding.symbol
+            };
+            links.push(CrossLanguageLink {
+                name,
+                framework: Some(binding.framework),
+                file_path: binding.file_path,
+                line: binding.line as usize,
+            });
+        }
+
+        let mut own_languages = HashSet::new();
+        let mut own_binding_file = false;
+        for symbol in self.store.find_symbol_by_name(symbol_name)? {
+            let path = self.get_file_path(symbol.file_id)?;
+            own_binding_file |= is_binding_file(&path);
+            own_languages.insert(language_of(&path));
+        }
+
+        let linked: HashSet<String> = links.iter().map(|l| l.name.clone()).collect();
+        for variant in binding_name_variants(symbol_name).into_iter().skip(1) {
+            if linked.contains(&variant) {
+                continue;
+            }
+            for symbol in self.store.find_symbol_by_name(&variant)? {
+                let path = self.get_file_path(symbol.file_id)?;
+                if own_languages.contains(&language_of(&path))
+                    || !(own_binding_file || is_binding_file(&path))
+                {
+                    continue;
+                }
+                links.push(CrossLanguageLink {
+                    name: variant.clone(),
+                    framework: None,
+                    file_path: path,
+                    line: symbol.start_line as usize,
+                });
+            }
+        }
+
+        Ok(links)
+    }
+}
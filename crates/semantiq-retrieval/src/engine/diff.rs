@@ -0,0 +1,748 @@
+//! Symbol-level diff between two git revisions for RetrievalEngine.
+
+use super::RetrievalEngine;
+use crate::results::SemanticDiffResponse;
+use anyhow::{Result, bail};
+use semantiq_index::{MAX_FILE_SIZE, should_exclude_path};
+use semantiq_parser::{Language, LanguageSupport, Symbol, SymbolExtractor, SymbolKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::time::Instant;
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Maximum number of changed files analyzed
+const MAX_DIFF_FILES: usize = 500;
+
+/// Minimum cosine similarity between the bodies of a removed and an added
+/// symbol to report them as the same code moved
+const MOVE_SIMILARITY: f32 = 0.9;
+
+/// Symbols with a shorter body (e.g. `fn new() {}`) are too generic to be
+/// matched as moved code
+const MIN_MOVED_BODY_CHARS: usize = 32;
+
+/// Maximum number of removed or added symbols compared by embedding
+const MAX_MOVE_CANDIDATES: usize = 256;
+
+/// Characters of a symbol body embedded for move detection
+const MAX_EMBEDDED_CHARS: usize = 2000;
+
+/// Errors caused by the requested revisions rather than by the engine
+#[derive(Debug, Error)]
+pub enum DiffError {
+    #[error("Invalid revision '{0}'")]
+    InvalidRevision(String),
+    #[error("Unknown revision '{0}'")]
+    UnknownRevision(String),
+    #[error("{0} is not inside a git repository")]
+    NotARepository(String),
+    #[error("git is not available: {0}")]
+    GitUnavailable(String),
+}
+
+/// How a symbol changed between two revisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Body changed, signature unchanged
+    Modified,
+    SignatureChanged,
+    /// Same code in another file or under another parent, possibly renamed
+    Moved,
+    /// Same code under another name in the same place
+    Renamed,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+            ChangeKind::SignatureChanged => "signature_changed",
+            ChangeKind::Moved => "moved",
+            ChangeKind::Renamed => "renamed",
+        }
+    }
+}
+
+/// A symbol added, removed or changed between two revisions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolChange {
+    pub change: ChangeKind,
+    pub name: String,
+    /// Symbol kind (`function`, `struct`, ...)
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Location in the new revision, or in the old one for removed symbols
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Previous file of moved and renamed symbols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_file_path: Option<String>,
+    /// Previous name of renamed (or moved and renamed) symbols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_start_line: Option<usize>,
+    /// Signature before a signature change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Similarity of the old and new bodies of moved and renamed symbols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+}
+
+/// Symbol changes of one file changed between two revisions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// Path relative to the project root, in the new revision unless deleted
+    pub path: String,
+    /// Path in the old revision of a renamed file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// `added`, `deleted`, `modified` or `renamed`
+    pub status: String,
+    /// Language the file was parsed as, `None` when it could not be analyzed
+    /// (unsupported language, binary or too large)
+    pub language: Option<String>,
+    pub changes: Vec<SymbolChange>,
+}
+
+/// A path listed by `git diff --name-status`
+#[derive(Debug, PartialEq)]
+struct ChangedPath {
+    status: &'static str,
+    old_path: Option<String>,
+    path: String,
+}
+
+impl ChangedPath {
+    /// Path of the file in the old revision, if it existed
+    fn old_path_at_base(&self) -> Option<&str> {
+        match self.status {
+            "added" => None,
+            _ => Some(self.old_path.as_deref().unwrap_or(&self.path)),
+        }
+    }
+
+    /// Path of the file in the new revision, if it still exists
+    fn path_at_target(&self) -> Option<&str> {
+        (self.status != "deleted").then_some(self.path.as_str())
+    }
+}
+
+/// A symbol of one version of a file
+struct SymbolVersion {
+    symbol: Symbol,
+    /// Whitespace-normalized source of the symbol, without nested symbols
+    body: String,
+}
+
+impl SymbolVersion {
+    fn key(&self) -> (Option<&str>, &str, SymbolKind) {
+        (
+            self.symbol.parent.as_deref(),
+            &self.symbol.name,
+            self.symbol.kind,
+        )
+    }
+
+    fn signature(&self) -> Option<String> {
+        self.symbol.signature.as_deref().map(normalize_whitespace)
+    }
+}
+
+/// A removed or added symbol waiting for move detection
+struct Unmatched {
+    file: usize,
+    version: SymbolVersion,
+}
+
+impl RetrievalEngine {
+    /// Compare two git revisions symbol by symbol.
+    ///
+    /// Files changed between `from` and `to` (limited to the project root)
+    /// are parsed at both revisions. Symbols are matched by parent, name and
+    /// kind and reported as added, removed, modified or with a changed
+    /// signature. Removed and added symbols with the same body, or whose
+    /// embeddings are at least [`MOVE_SIMILARITY`] similar, are reported as
+    /// moved (or renamed) instead.
+    pub fn semantic_diff(&self, from: &str, to: &str) -> Result<SemanticDiffResponse> {
+        info!(from = %from, to = %to, "Computing semantic diff");
+        let start = Instant::now();
+        let root = Path::new(&self.root_path);
+
+        ensure_repository(root)?;
+        let from_commit = resolve_revision(root, from)?;
+        let to_commit = resolve_revision(root, to)?;
+
+        let output = git_stdout(
+            root,
+            &[
+                "diff",
+                "--name-status",
+                "-z",
+                "-M",
+                "--relative",
+                "--no-ext-diff",
+                &from_commit,
+                &to_commit,
+                "--",
+            ],
+        )?;
+        let mut paths = parse_name_status(&output);
+        paths.retain(|p| !should_exclude_path(Path::new(&p.path)));
+        let skipped_files = paths.len().saturating_sub(MAX_DIFF_FILES);
+        paths.truncate(MAX_DIFF_FILES);
+
+        let mut support = LanguageSupport::new()?;
+        let mut files = Vec::with_capacity(paths.len());
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+
+        for (index, changed) in paths.iter().enumerate() {
+            let old_source = match changed.old_path_at_base() {
+                Some(path) => read_at_revision(root, &from_commit, path)?,
+                None => None,
+            };
+            let new_source = match changed.path_at_target() {
+                Some(path) => read_at_revision(root, &to_commit, path)?,
+                None => None,
+            };
+            let language = Language::from_path(Path::new(&changed.path)).or_else(|| {
+                new_source
+                    .as_deref()
+                    .or(old_source.as_deref())
+                    .and_then(Language::from_content)
+            });
+
+            let mut file = FileDiff {
+                path: changed.path.clone(),
+                old_path: changed.old_path.clone(),
+                status: changed.status.to_string(),
+                language: None,
+                changes: Vec::new(),
+            };
+
+            let versions = language.and_then(|language| {
+                let old = parse_version(&mut support, language, old_source.as_deref(), changed)?;
+                let new = parse_version(&mut support, language, new_source.as_deref(), changed)?;
+                Some((language, old, new))
+            });
+            if let Some((language, old, new)) = versions {
+                file.language = Some(language.name().to_string());
+                let (changes, file_removed, file_added) = diff_symbols(&changed.path, old, new);
+                file.changes = changes;
+                removed.extend(file_removed.into_iter().map(|version| Unmatched {
+                    file: index,
+                    version,
+                }));
+                added.extend(file_added.into_iter().map(|version| Unmatched {
+                    file: index,
+                    version,
+                }));
+            }
+            files.push(file);
+        }
+
+        self.match_moves(&paths, &mut files, removed, added);
+
+        for file in &mut files {
+            file.changes.sort_by_key(|c| (c.start_line, c.end_line));
+        }
+        let total_changes = files.iter().map(|f| f.changes.len()).sum();
+
+        Ok(SemanticDiffResponse {
+            from: from.trim().to_string(),
+            to: to.trim().to_string(),
+            files,
+            total_changes,
+            skipped_files,
+            search_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Pair removed and added symbols holding the same code, first by
+    /// identical body, then by embedding similarity. Pairs become moves or
+    /// renames, the rest additions and removals.
+    fn match_moves(
+        &self,
+        paths: &[ChangedPath],
+        files: &mut [FileDiff],
+        removed: Vec<Unmatched>,
+        added: Vec<Unmatched>,
+    ) {
+        let mut removed: Vec<Option<Unmatched>> = removed.into_iter().map(Some).collect();
+        let mut added: Vec<Option<Unmatched>> = added.into_iter().map(Some).collect();
+        let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+
+        let mut by_body: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (i, r) in removed.iter().enumerate() {
+            let body = &r.as_ref().expect("unmatched").version.body;
+            if body.len() >= MIN_MOVED_BODY_CHARS {
+                by_body.entry(body).or_default().push_back(i);
+            }
+        }
+        for (j, a) in added.iter().enumerate() {
+            let version = &a.as_ref().expect("unmatched").version;
+            let candidates = by_body.get_mut(version.body.as_str());
+            if let Some(i) = candidates.and_then(|c| {
+                let position = c.iter().position(|&i| {
+                    let old = &removed[i].as_ref().expect("unmatched").version;
+                    compatible_kinds(old.symbol.kind, version.symbol.kind)
+                })?;
+                c.remove(position)
+            }) {
+                pairs.push((i, j, 1.0));
+            }
+        }
+
+        let paired_removed: Vec<usize> = pairs.iter().map(|p| p.0).collect();
+        let paired_added: Vec<usize> = pairs.iter().map(|p| p.1).collect();
+        let remaining_removed: Vec<usize> = (0..removed.len())
+            .filter(|i| !paired_removed.contains(i))
+            .filter(|&i| {
+                removed[i].as_ref().expect("unmatched").version.body.len() >= MIN_MOVED_BODY_CHARS
+            })
+            .take(MAX_MOVE_CANDIDATES)
+            .collect();
+        let remaining_added: Vec<usize> = (0..added.len())
+            .filter(|j| !paired_added.contains(j))
+            .filter(|&j| {
+                added[j].as_ref().expect("unmatched").version.body.len() >= MIN_MOVED_BODY_CHARS
+            })
+            .take(MAX_MOVE_CANDIDATES)
+            .collect();
+
+        if !remaining_removed.is_empty()
+            && !remaining_added.is_empty()
+            && let Some(model) = self.embedding_model()
+        {
+            let texts = |items: &[Option<Unmatched>], indices: &[usize]| -> Vec<String> {
+                indices
+                    .iter()
+                    .map(|&i| {
+                        let body = &items[i].as_ref().expect("unmatched").version.body;
+                        body.chars().take(MAX_EMBEDDED_CHARS).collect()
+                    })
+                    .collect()
+            };
+            let embeddings = model
+                .embed_batch(&texts(&removed, &remaining_removed))
+                .and_then(|old| Ok((old, model.embed_batch(&texts(&added, &remaining_added))?)));
+
+            match embeddings {
+                Ok((old_embeddings, new_embeddings)) => {
+                    let mut candidates = Vec::new();
+                    for (a, &i) in remaining_removed.iter().enumerate() {
+                        let old = &removed[i].as_ref().expect("unmatched").version;
+                        for (b, &j) in remaining_added.iter().enumerate() {
+                            let new = &added[j].as_ref().expect("unmatched").version;
+                            if !compatible_kinds(old.symbol.kind, new.symbol.kind) {
+                                continue;
+                            }
+                            let similarity =
+                                cosine_similarity(&old_embeddings[a], &new_embeddings[b]);
+                            if similarity >= MOVE_SIMILARITY {
+                                candidates.push((i, j, similarity));
+                            }
+                        }
+                    }
+                    candidates.sort_by(|x, y| y.2.total_cmp(&x.2));
+
+                    let mut used_removed = vec![false; removed.len()];
+                    let mut used_added = vec![false; added.len()];
+                    for (i, j, similarity) in candidates {
+                        if !used_removed[i] && !used_added[j] {
+                            used_removed[i] = true;
+                            used_added[j] = true;
+                            pairs.push((i, j, similarity));
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to embed symbols for move detection: {}", e),
+            }
+        }
+
+        for (i, j, similarity) in pairs {
+            let (Some(old), Some(new)) = (removed[i].take(), added[j].take()) else {
+                continue;
+            };
+            let old_path = paths[old.file]
+                .old_path_at_base()
+                .unwrap_or(&paths[old.file].path);
+            let same_place =
+                old.file == new.file && old.version.symbol.parent == new.version.symbol.parent;
+            let renamed = old.version.symbol.name != new.version.symbol.name;
+
+            let mut change = symbol_change(
+                if same_place {
+                    ChangeKind::Renamed
+                } else {
+                    ChangeKind::Moved
+                },
+                &paths[new.file].path,
+                &new.version.symbol,
+            );
+            if !same_place {
+                change.old_file_path = Some(old_path.to_string());
+            }
+            if renamed {
+                change.old_name = Some(old.version.symbol.name.clone());
+            }
+            change.old_start_line = Some(old.version.symbol.start_line);
+            change.similarity = Some(similarity);
+            files[new.file].changes.push(change);
+        }
+
+        for old in removed.into_iter().flatten() {
+            let path = paths[old.file]
+                .old_path_at_base()
+                .unwrap_or(&paths[old.file].path);
+            files[old.file].changes.push(symbol_change(
+                ChangeKind::Removed,
+                path,
+                &old.version.symbol,
+            ));
+        }
+        for new in added.into_iter().flatten() {
+            files[new.file].changes.push(symbol_change(
+                ChangeKind::Added,
+                &paths[new.file].path,
+                &new.version.symbol,
+            ));
+        }
+    }
+}
+
+/// Functions and methods can move into or out of a type
+fn compatible_kinds(old: SymbolKind, new: SymbolKind) -> bool {
+    use SymbolKind::{Function, Method};
+    old == new || matches!((old, new), (Function, Method) | (Method, Function))
+}
+
+fn symbol_change(change: ChangeKind, path: &str, symbol: &Symbol) -> SymbolChange {
+    SymbolChange {
+        change,
+        name: symbol.name.clone(),
+        kind: symbol.kind.as_str().to_string(),
+        parent: symbol.parent.clone(),
+        file_path: path.to_string(),
+        start_line: symbol.start_line,
+        end_line: symbol.end_line,
+        old_file_path: None,
+        old_name: None,
+        old_start_line: None,
+        old_signature: None,
+        signature: None,
+        similarity: None,
+    }
+}
+
+/// Match the symbols of two versions of a file by parent, name and kind.
+///
+/// Returns the changes of matched symbols, then the symbols only found in
+/// the old version and those only found in the new one.
+fn diff_symbols(
+    path: &str,
+    old: Vec<SymbolVersion>,
+    new: Vec<SymbolVersion>,
+) -> (Vec<SymbolChange>, Vec<SymbolVersion>, Vec<SymbolVersion>) {
+    let mut changes = Vec::new();
+    let mut added = Vec::new();
+
+    let mut old_by_key: HashMap<(Option<String>, String, SymbolKind), VecDeque<SymbolVersion>> =
+        HashMap::new();
+    for version in old {
+        let (parent, name, kind) = version.key();
+        let key = (parent.map(str::to_string), name.to_string(), kind);
+        old_by_key.entry(key).or_default().push_back(version);
+    }
+
+    for version in new {
+        let (parent, name, kind) = version.key();
+        let key = (parent.map(str::to_string), name.to_string(), kind);
+        let Some(previous) = old_by_key.get_mut(&key).and_then(VecDeque::pop_front) else {
+            added.push(version);
+            continue;
+        };
+
+        let (old_signature, signature) = (previous.signature(), version.signature());
+        if old_signature != signature {
+            let mut change = symbol_change(ChangeKind::SignatureChanged, path, &version.symbol);
+            change.old_signature = previous.symbol.signature.clone();
+            change.signature = version.symbol.signature.clone();
+            change.old_start_line = Some(previous.symbol.start_line);
+            changes.push(change);
+        } else if previous.body != version.body {
+            let mut change = symbol_change(ChangeKind::Modified, path, &version.symbol);
+            change.old_start_line = Some(previous.symbol.start_line);
+            changes.push(change);
+        }
+    }
+
+    let mut removed: Vec<SymbolVersion> = old_by_key.into_values().flatten().collect();
+    removed.sort_by_key(|v| v.symbol.start_byte);
+    (changes, removed, added)
+}
+
+/// Parse one version of a changed file. A missing version (added or
+/// deleted file) has no symbols; `None` when the file cannot be analyzed.
+fn parse_version(
+    support: &mut LanguageSupport,
+    language: Language,
+    source: Option<&str>,
+    changed: &ChangedPath,
+) -> Option<Vec<SymbolVersion>> {
+    let Some(source) = source else {
+        return Some(Vec::new());
+    };
+    if source.len() as u64 > MAX_FILE_SIZE || source.contains('\0') {
+        return None;
+    }
+
+    let symbols = support
+        .parse_file(language, Path::new(&changed.path), source)
+        .and_then(|tree| SymbolExtractor::extract(&tree, source, language));
+    match symbols {
+        Ok(symbols) => Some(symbol_versions(source, symbols)),
+        Err(e) => {
+            warn!("Failed to parse {} for diff: {}", changed.path, e);
+            None
+        }
+    }
+}
+
+/// Attach to each symbol its own body: its source without the symbols
+/// nested in it, so that changing a method does not modify its class.
+fn symbol_versions(source: &str, symbols: Vec<Symbol>) -> Vec<SymbolVersion> {
+    let ranges: Vec<(usize, usize)> = symbols
+        .iter()
+        .filter(|s| s.kind != SymbolKind::Import)
+        .map(|s| (s.start_byte, s.end_byte))
+        .collect();
+
+    symbols
+        .into_iter()
+        .filter(|s| s.kind != SymbolKind::Import)
+        .map(|symbol| {
+            let (start, end) = (symbol.start_byte, symbol.end_byte);
+            let mut nested: Vec<(usize, usize)> = ranges
+                .iter()
+                .copied()
+                .filter(|&(s, e)| s >= start && e <= end && (s, e) != (start, end))
+                .collect();
+            nested.sort_unstable();
+
+            let mut own = String::new();
+            let mut cursor = start;
+            for (s, e) in nested {
+                if s >= cursor {
+                    own.push_str(source.get(cursor..s).unwrap_or_default());
+                }
+                cursor = cursor.max(e);
+            }
+            own.push_str(source.get(cursor..end).unwrap_or_default());
+
+            SymbolVersion {
+                body: normalize_whitespace(&own),
+                symbol,
+            }
+        })
+        .collect()
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Parse the NUL-separated output of `git diff --name-status -z`.
+fn parse_name_status(output: &[u8]) -> Vec<ChangedPath> {
+    let text = String::from_utf8_lossy(output);
+    let mut fields = text.split('\0').filter(|f| !f.is_empty());
+    let mut paths = Vec::new();
+
+    while let Some(status) = fields.next() {
+        let changed = match status.chars().next() {
+            Some('R') => {
+                let (Some(old), Some(new)) = (fields.next(), fields.next()) else {
+                    break;
+                };
+                ChangedPath {
+                    status: "renamed",
+                    old_path: Some(old.to_string()),
+                    path: new.to_string(),
+                }
+            }
+            Some('C') => {
+                let (Some(_), Some(new)) = (fields.next(), fields.next()) else {
+                    break;
+                };
+                ChangedPath {
+                    status: "added",
+                    old_path: None,
+                    path: new.to_string(),
+                }
+            }
+            Some(code) => {
+                let Some(path) = fields.next() else {
+                    break;
+                };
+                let status = match code {
+                    'A' => "added",
+                    'D' => "deleted",
+                    'M' | 'T' => "modified",
+                    _ => continue,
+                };
+                ChangedPath {
+                    status,
+                    old_path: None,
+                    path: path.to_string(),
+                }
+            }
+            None => break,
+        };
+        paths.push(changed);
+    }
+    paths
+}
+
+pub(super) fn git(root: &Path, args: &[&str]) -> Result<Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()
+        .map_err(|e| DiffError::GitUnavailable(e.to_string()).into())
+}
+
+fn git_stdout(root: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = git(root, args)?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn ensure_repository(root: &Path) -> Result<()> {
+    let output = git(root, &["rev-parse", "--is-inside-work-tree"])?;
+    if !output.status.success() {
+        return Err(DiffError::NotARepository(root.display().to_string()).into());
+    }
+    Ok(())
+}
+
+/// Resolve a revision to a commit id. Revisions looking like options are
+/// rejected so that they cannot be passed to git as flags.
+fn resolve_revision(root: &Path, revision: &str) -> Result<String> {
+    let revision = revision.trim();
+    if revision.is_empty()
+        || revision.starts_with('-')
+        || revision
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(DiffError::InvalidRevision(revision.to_string()).into());
+    }
+
+    let output = git(
+        root,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+            &format!("{}^{{commit}}", revision),
+        ],
+    )?;
+    if !output.status.success() {
+        return Err(DiffError::UnknownRevision(revision.to_string()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Content of a file at a commit, `None` if it is not valid UTF-8.
+fn read_at_revision(root: &Path, commit: &str, path: &str) -> Result<Option<String>> {
+    let content = git_stdout(root, &["show", &format!("{}:./{}", commit, path)])?;
+    Ok(String::from_utf8(content).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_status() {
+        let output = b"M\0src/lib.rs\0A\0src/new.rs\0D\0old.py\0R087\0a.ts\0b.ts\0";
+        assert_eq!(
+            parse_name_status(output),
+            vec![
+                ChangedPath {
+                    status: "modified",
+                    old_path: None,
+                    path: "src/lib.rs".to_string()
+                },
+                ChangedPath {
+                    status: "added",
+                    old_path: None,
+                    path: "src/new.rs".to_string()
+                },
+                ChangedPath {
+                    status: "deleted",
+                    old_path: None,
+                    path: "old.py".to_string()
+                },
+                ChangedPath {
+                    status: "renamed",
+                    old_path: Some("a.ts".to_string()),
+                    path: "b.ts".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_symbols_are_excluded_from_own_body() {
+        let source = "impl Parser {\n    fn parse(&self) -> u32 {\n        1\n    }\n}\n";
+        let mut support = LanguageSupport::new().unwrap();
+        let tree = support.parse(Language::Rust, source).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, source, Language::Rust).unwrap();
+        let versions = symbol_versions(source, symbols);
+
+        let method = versions
+            .iter()
+            .find(|v| v.symbol.name == "parse")
+            .expect("method");
+        assert_eq!(method.body, "fn parse(&self) -> u32 { 1 }");
+        for version in versions.iter().filter(|v| v.symbol.name != "parse") {
+            assert!(!version.body.contains("fn parse"));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Entry points of a project for RetrievalEngine.
+//!
+//! The parser records where programs are entered: main functions, HTTP
+//! route registrations, CLI subcommand definitions and the public items of
+//! library roots. Listed together by kind, they show how the application is
+//! wired, before reading any of its code.
+
+use super::RetrievalEngine;
+use crate::results::EntrypointsResponse;
+use anyhow::Result;
+use semantiq_index::{EntrypointRecord, normalize_path};
+use semantiq_parser::EntrypointKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tracing::info;
+
+/// A main function, HTTP route, CLI subcommand or library export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntrypointLocation {
+    /// `main`, `route`, `command` or `export`
+    pub kind: String,
+    /// `main`, the route (`GET /users`), the command (`serve`) or the
+    /// exported name
+    pub name: String,
+    /// Function handling the route or command, when it is named
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl From<EntrypointRecord> for EntrypointLocation {
+    fn from(record: EntrypointRecord) -> Self {
+        Self {
+            kind: record.kind,
+            name: record.name,
+            handler: record.handler,
+            file_path: record.file_path,
+            start_line: record.start_line as usize,
+            end_line: record.end_line as usize,
+        }
+    }
+}
+
+impl RetrievalEngine {
+    /// List the entry points of the files under `path` ("." for the whole
+    /// project), of one kind or of all of them: main functions first, then
+    /// CLI subcommands, HTTP routes and library exports.
+    ///
+    /// At most `limit` entry points are listed per kind, so that a library
+    /// exporting hundreds of items does not hide the main function of its
+    /// binary; `counts` has the number found of each kind.
+    pub fn entrypoints(
+        &self,
+        kind: Option<EntrypointKind>,
+        path: &str,
+        limit: usize,
+    ) -> Result<EntrypointsResponse> {
+        let start = Instant::now();
+        let path = match normalize_path(path) {
+            path if path.is_empty() => ".".to_string(),
+            path => path,
+        };
+        info!(path = %path, kind = ?kind, limit = limit, "Listing entry points");
+
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut entrypoints = Vec::new();
+        for record in self.store.get_entrypoints(kind, Some(&path))? {
+            let count = counts.entry(record.kind.clone()).or_default();
+            *count += 1;
+            if *count <= limit {
+                entrypoints.push(EntrypointLocation::from(record));
+            }
+        }
+
+        Ok(EntrypointsResponse {
+            path,
+            kind: kind.map(|k| k.as_str().to_string()),
+            total_count: counts.values().sum(),
+            counts,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            entrypoints,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+//! Expansion of search results beyond the matched lines.
+//!
+//! Agents reading a result usually need the code around it. With
+//! `SearchOptions::context_lines` or `expand_to_symbol`, the content of each
+//! result is grown to the surrounding lines or to the whole symbol enclosing
+//! it, read from the indexed file once per file. The matched lines are kept
+//! in `matched_lines`.
+
+use super::RetrievalEngine;
+use crate::query::SearchOptions;
+use crate::results::{LineRange, SearchResult};
+use semantiq_index::SymbolRecord;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Longest symbol a result is expanded to, in lines. Results inside longer
+/// symbols only get their context lines.
+const MAX_SYMBOL_LINES: usize = 200;
+
+/// Indexed content and symbols of a file with results
+struct ExpansionSource {
+    lines: Vec<String>,
+    symbols: Vec<SymbolRecord>,
+}
+
+impl RetrievalEngine {
+    /// Expand the content of results as requested by `options`. Stale
+    /// results are left as they are: their line ranges may have shifted.
+    pub(crate) fn expand_results(&self, results: &mut [SearchResult], options: &SearchOptions) {
+        if !options.expands_results() {
+            return;
+        }
+
+        let mut sources: HashMap<String, Option<ExpansionSource>> = HashMap::new();
+        for result in results.iter_mut().filter(|r| !r.stale) {
+            let source = sources
+                .entry(result.file_path.clone())
+                .or_insert_with(|| self.expansion_source(&result.file_path, options));
+            let Some(source) = source else {
+                continue;
+            };
+
+            let (mut start, mut end) = (result.start_line, result.end_line);
+            if options.expand_to_symbol
+                && let Some(symbol) = innermost_symbol(&source.symbols, start, end)
+            {
+                start = symbol.start_line as usize;
+                end = symbol.end_line as usize;
+            }
+            start = start.saturating_sub(options.context_lines).max(1);
+            end = (end + options.context_lines).min(source.lines.len());
+
+            if start > end || (start, end) == (result.start_line, result.end_line) {
+                continue;
+            }
+            result.matched_lines = Some(LineRange {
+                start_line: result.start_line,
+                end_line: result.end_line,
+            });
+            result.content = source.lines[start - 1..end].join("\n");
+            result.start_line = start;
+            result.end_line = end;
+        }
+    }
+
+    /// Read the indexed content of a file, and its symbols when results are
+    /// expanded to them. `None` if the file cannot be read.
+    fn expansion_source(
+        &self,
+        file_path: &str,
+        options: &SearchOptions,
+    ) -> Option<ExpansionSource> {
+        let content = match self.read_indexed_file(file_path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Failed to read {} to expand results: {}", file_path, e);
+                return None;
+            }
+        };
+
+        let symbols = if options.expand_to_symbol {
+            let records = match self.store.get_file_by_path(file_path) {
+                Ok(Some(file)) => self.store.get_symbols_by_file(file.id),
+                Ok(None) => Ok(Vec::new()),
+                Err(e) => Err(e),
+            };
+            records.unwrap_or_else(|e| {
+                warn!("Failed to load symbols of {}: {}", file_path, e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        Some(ExpansionSource {
+            lines: content.lines().map(str::to_string).collect(),
+            symbols,
+        })
+    }
+}
+
+/// Smallest symbol covering lines `start..=end`, if it is at most
+/// [`MAX_SYMBOL_LINES`] long
+fn innermost_symbol(symbols: &[SymbolRecord], start: usize, end: usize) -> Option<&SymbolRecord> {
+    symbols
+        .iter()
+        .filter(|s| s.start_line as usize <= start && s.end_line as usize >= end)
+        .min_by_key(|s| s.end_line - s.start_line)
+        .filter(|s| ((s.end_line - s.start_line) as usize) < MAX_SYMBOL_LINES)
+}
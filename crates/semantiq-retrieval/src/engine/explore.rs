@@ -0,0 +1,305 @@
+//! Directory listings for RetrievalEngine.
+//!
+//! A listing annotates the entries of a directory on disk with what the index
+//! knows of them: their language and symbols, when they were indexed and
+//! whether that still matches the disk, or why the indexer leaves them out
+//! (hidden, dependency or build directory, `.gitignore`, unsupported
+//! language, size). Navigating through it keeps these visible where a plain
+//! `ls` would not.
+
+use super::RetrievalEngine;
+use super::freshness::FileFreshness;
+use crate::results::{ExploreResponse, Freshness};
+use anyhow::Result;
+use ignore::WalkBuilder;
+use semantiq_index::{
+    EXCLUDED_DIRS, MAX_OUTLINE_FILE_SIZE, normalize_path, resolve_path, should_exclude_path,
+    unix_now,
+};
+use semantiq_parser::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use tracing::info;
+
+/// Top symbols listed per entry
+const MAX_ENTRY_SYMBOLS: usize = 5;
+
+/// Why the indexer leaves an entry out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    /// The name starts with '.'
+    Hidden,
+    /// A dependency or build output directory (`node_modules`, `target`...)
+    ExcludedDirectory,
+    /// The directory listed is itself hidden or excluded
+    InExcludedDirectory,
+    /// Matched by `.gitignore`, `.ignore` or `.git/info/exclude`
+    Gitignored,
+    /// No supported language is detected for the file
+    UnsupportedLanguage,
+    /// Larger than even an outline of the file is indexed for
+    TooLarge,
+}
+
+impl ExclusionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExclusionReason::Hidden => "hidden",
+            ExclusionReason::ExcludedDirectory => "excluded_directory",
+            ExclusionReason::InExcludedDirectory => "in_excluded_directory",
+            ExclusionReason::Gitignored => "gitignored",
+            ExclusionReason::UnsupportedLanguage => "unsupported_language",
+            ExclusionReason::TooLarge => "too_large",
+        }
+    }
+}
+
+/// A file or subdirectory of a listed directory, with its index data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExploreEntry {
+    pub name: String,
+    /// Path relative to the project root
+    pub path: String,
+    pub is_dir: bool,
+    /// Language of a file, indexed or detected; most frequent language of
+    /// the indexed files under a directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Size in bytes of a file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Symbols of an indexed file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol_count: Option<usize>,
+    /// Indexed files under a directory, subdirectories included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed_file_count: Option<usize>,
+    /// Unix time the file, or the most recently indexed file under the
+    /// directory, was indexed at; `None` when not indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<i64>,
+    /// Whether the index of a file matches it on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<Freshness>,
+    /// Top-level symbols of a file in order of appearance, most referenced
+    /// top-level symbols under a directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_symbols: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
+    /// Indexed as an outline of its top-level symbols, being large
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outline: bool,
+    /// Why the indexer leaves the entry out, `None` when it does not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded: Option<ExclusionReason>,
+    /// Issues met by the last indexing of a file (see
+    /// `semantiq_index_issues`), as `kind: message`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<String>,
+}
+
+impl RetrievalEngine {
+    /// List the directory `path` relative to the project root ("" or "."
+    /// for the root), subdirectories first then by name, with the index
+    /// data of each entry. At most `limit` entries are listed;
+    /// `entry_count` has the number in the directory. `None` when `path` is
+    /// not a directory of the project.
+    pub fn explore(&self, path: &str, limit: usize) -> Result<Option<ExploreResponse>> {
+        let start = Instant::now();
+        let path = normalize_path(path);
+        if path.split('/').any(|part| part == "..") {
+            return Ok(None);
+        }
+        let dir = resolve_path(Path::new(&self.root_path), &path);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        info!(path = %path, limit = limit, "Exploring directory");
+
+        if !self.store.dir_summaries_current()? {
+            let count = self.store.refresh_dir_summaries()?;
+            info!(directories = count, "Rebuilt directory summaries");
+        }
+
+        let mut children: Vec<(OsString, bool)> = fs::read_dir(&dir)?
+            .flatten()
+            .map(|entry| (entry.file_name(), entry.path().is_dir()))
+            .collect();
+        children.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let entry_count = children.len();
+        children.truncate(limit);
+
+        let inherited = (!path.is_empty() && should_exclude_path(Path::new(&path)))
+            .then_some(ExclusionReason::InExcludedDirectory);
+        let walked = walked_names(&dir);
+
+        let mut entries = Vec::with_capacity(children.len());
+        for (name, is_dir) in children {
+            let name = name.to_string_lossy().into_owned();
+            let rel_path = match path.as_str() {
+                "" => name.clone(),
+                dir => format!("{}/{}", dir, name),
+            };
+            let excluded = inherited.or_else(|| {
+                if name.starts_with('.') {
+                    Some(ExclusionReason::Hidden)
+                } else if is_dir && EXCLUDED_DIRS.contains(&name.as_str()) {
+                    Some(ExclusionReason::ExcludedDirectory)
+                } else if !walked.contains(&name) {
+                    Some(ExclusionReason::Gitignored)
+                } else {
+                    None
+                }
+            });
+            let entry = if is_dir {
+                self.explore_directory(name, rel_path, excluded)?
+            } else {
+                self.explore_file(name, rel_path, &dir, excluded)?
+            };
+            entries.push(entry);
+        }
+
+        Ok(Some(ExploreResponse {
+            path: if path.is_empty() {
+                ".".to_string()
+            } else {
+                path
+            },
+            entry_count,
+            listed_at: unix_now(),
+            search_time_ms: start.elapsed().as_millis() as u64,
+            entries,
+        }))
+    }
+
+    fn explore_directory(
+        &self,
+        name: String,
+        path: String,
+        excluded: Option<ExclusionReason>,
+    ) -> Result<ExploreEntry> {
+        let summary = self.store.get_dir_summary(&path)?;
+        let indexed_at = self
+            .store
+            .get_last_indexed_with_prefix(&format!("{}/", path))?;
+
+        Ok(ExploreEntry {
+            name,
+            is_dir: true,
+            language: summary
+                .as_ref()
+                .and_then(|s| s.languages.first())
+                .map(|l| l.language.clone()),
+            size: None,
+            symbol_count: None,
+            indexed_file_count: Some(summary.as_ref().map_or(0, |s| s.file_count)),
+            indexed_at,
+            freshness: None,
+            top_symbols: summary
+                .map(|s| {
+                    s.top_symbols
+                        .into_iter()
+                        .take(MAX_ENTRY_SYMBOLS)
+                        .map(|symbol| symbol.name)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            generated: false,
+            outline: false,
+            excluded,
+            issues: Vec::new(),
+            path,
+        })
+    }
+
+    fn explore_file(
+        &self,
+        name: String,
+        path: String,
+        dir: &Path,
+        excluded: Option<ExclusionReason>,
+    ) -> Result<ExploreEntry> {
+        let full_path = dir.join(&name);
+        let size = fs::metadata(&full_path).ok().map(|m| m.len());
+        let detected = Language::detect(&full_path);
+        let excluded = excluded.or_else(|| {
+            if detected.is_none() {
+                Some(ExclusionReason::UnsupportedLanguage)
+            } else if size.is_some_and(|size| size > MAX_OUTLINE_FILE_SIZE) {
+                Some(ExclusionReason::TooLarge)
+            } else {
+                None
+            }
+        });
+
+        let record = self.store.get_file_by_path(&path)?;
+        let (symbol_count, top_symbols, freshness) = match record {
+            Some(ref record) => {
+                let symbols = self.store.get_symbols_by_file(record.id)?;
+                let top_symbols = symbols
+                    .iter()
+                    .filter(|s| s.parent.is_none())
+                    .take(MAX_ENTRY_SYMBOLS)
+                    .map(|s| s.name.clone())
+                    .collect();
+                let freshness = match self.check_file_freshness(&path, Some(record)) {
+                    FileFreshness::Fresh => Freshness::Fresh,
+                    FileFreshness::Stale(_) => Freshness::Stale,
+                    FileFreshness::Missing | FileFreshness::Indexed => Freshness::Unknown,
+                };
+                (Some(symbols.len()), top_symbols, Some(freshness))
+            }
+            None => (None, Vec::new(), None),
+        };
+        let generated = !self.store.get_generated_files(&[&path])?.is_empty();
+        let outline = !self.store.get_outline_files(&[&path])?.is_empty();
+        let issues = self
+            .store
+            .get_file_index_issues(&path)?
+            .into_iter()
+            .map(|issue| format!("{}: {}", issue.kind, issue.message))
+            .collect();
+
+        Ok(ExploreEntry {
+            name,
+            is_dir: false,
+            language: record
+                .as_ref()
+                .and_then(|r| r.language.clone())
+                .or_else(|| detected.map(|l| l.name().to_string())),
+            size,
+            symbol_count,
+            indexed_file_count: None,
+            indexed_at: record.map(|r| r.indexed_at),
+            freshness,
+            top_symbols,
+            generated,
+            outline,
+            excluded,
+            issues,
+            path,
+        })
+    }
+}
+
+/// Names of the entries of `dir` that `.gitignore` and the other ignore
+/// files leave to the indexer, read the way its walk reads them
+fn walked_names(dir: &Path) -> HashSet<String> {
+    WalkBuilder::new(dir)
+        .max_depth(Some(1))
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect()
+}
@@ -0,0 +1,167 @@
+//! Staleness checks for search results.
+//!
+//! Between a file edit and the completion of its reindex, indexed line ranges
+//! may no longer match the file on disk. Results are verified against the
+//! current file before being returned: stale results are flagged and their
+//! content refreshed, and results from deleted files are dropped unless the
+//! index stores their content. Every result gets the indexing time and hash
+//! of its file, and its [`Freshness`], for clients to decide whether to trust
+//! it.
+
+use super::RetrievalEngine;
+use crate::results::{Freshness, SearchResult, SearchResultKind};
+use semantiq_index::{FileRecord, is_external_path, read_source, resolve_path};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tracing::debug;
+
+/// State of an indexed file compared to its current content on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FileFreshness {
+    /// The index matches the file on disk
+    Fresh,
+    /// The file changed since it was indexed; holds the current content
+    Stale(String),
+    /// The file no longer exists (or cannot be read) on disk
+    Missing,
+    /// The file is not on disk but its indexed content is stored, e.g. an
+    /// index copied from another machine
+    Indexed,
+}
+
+impl RetrievalEngine {
+    /// Verify results against the files on disk.
+    ///
+    /// Results whose file changed since indexing are marked `stale` and get
+    /// their content re-read from the same line range. Results from files that
+    /// no longer exist are removed, unless their content is stored in the
+    /// index. Every result gets the `indexed_at`, `content_hash` and
+    /// `freshness` metadata of its file. Returns the paths of files that need
+    /// to be reindexed, sorted.
+    pub(crate) fn verify_freshness(&self, results: &mut Vec<SearchResult>) -> Vec<String> {
+        let mut records: HashMap<String, Option<FileRecord>> = HashMap::new();
+        let mut freshness: HashMap<String, FileFreshness> = HashMap::new();
+        let text_from_disk = !self.serves_stored_contents();
+
+        for result in results.iter() {
+            if !records.contains_key(&result.file_path) {
+                let record = self
+                    .store
+                    .get_file_by_path(&result.file_path)
+                    .ok()
+                    .flatten();
+                records.insert(result.file_path.clone(), record);
+            }
+
+            // Text matches read from disk at query time are always current,
+            // and external dependency sources are immutable for a given version
+            if (result.kind == SearchResultKind::TextMatch && text_from_disk)
+                || is_external_path(&result.file_path)
+                || freshness.contains_key(&result.file_path)
+            {
+                continue;
+            }
+            let state =
+                self.check_file_freshness(&result.file_path, records[&result.file_path].as_ref());
+            freshness.insert(result.file_path.clone(), state);
+        }
+
+        results.retain(|r| freshness.get(&r.file_path) != Some(&FileFreshness::Missing));
+
+        for result in results.iter_mut() {
+            if let Some(FileFreshness::Stale(content)) = freshness.get(&result.file_path) {
+                result.stale = true;
+                if let Some(refreshed) = slice_lines(content, result.start_line, result.end_line) {
+                    result.content = refreshed;
+                }
+            }
+
+            let record = records[&result.file_path].as_ref();
+            result.metadata.indexed_at = record.map(|r| r.indexed_at);
+            result.metadata.content_hash = record.map(|r| r.hash.clone());
+            result.metadata.freshness = Some(match freshness.get(&result.file_path) {
+                _ if result.stale => Freshness::Stale,
+                // Not compared: read at query time, or immutable
+                None if record.is_some() || result.kind == SearchResultKind::TextMatch => {
+                    Freshness::Fresh
+                }
+                Some(FileFreshness::Fresh) if record.is_some() => Freshness::Fresh,
+                _ => Freshness::Unknown,
+            });
+        }
+
+        let mut stale_files: Vec<String> = freshness
+            .into_iter()
+            .filter(|(_, state)| !matches!(state, FileFreshness::Fresh | FileFreshness::Indexed))
+            .map(|(path, _)| path)
+            .collect();
+        stale_files.sort();
+
+        if !stale_files.is_empty() {
+            debug!(files = ?stale_files, "Search returned results from stale files");
+        }
+
+        stale_files
+    }
+
+    /// Compare the indexed version of a file, `record`, with its current
+    /// content.
+    ///
+    /// Uses the recorded size and mtime as a fast path and only hashes the
+    /// content when they differ.
+    pub(crate) fn check_file_freshness(
+        &self,
+        file_path: &str,
+        record: Option<&FileRecord>,
+    ) -> FileFreshness {
+        let root = Path::new(&self.root_path);
+        let full_path = resolve_path(root, file_path);
+
+        let metadata = match fs::metadata(&full_path) {
+            Ok(m) if m.is_file() => m,
+            _ if self.store.has_file_content(file_path).unwrap_or(false) => {
+                return FileFreshness::Indexed;
+            }
+            _ => return FileFreshness::Missing,
+        };
+
+        let Some(record) = record else {
+            // Not indexed (or unreadable index): nothing to compare against
+            return FileFreshness::Fresh;
+        };
+
+        let last_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        if metadata.len() as i64 == record.size && last_modified == record.last_modified {
+            return FileFreshness::Fresh;
+        }
+
+        let content = match read_source(&full_path) {
+            Ok(source) => source.content,
+            Err(_) => return FileFreshness::Missing,
+        };
+
+        match self.store.needs_reindex(file_path, &content) {
+            Ok(true) => FileFreshness::Stale(content),
+            _ => FileFreshness::Fresh,
+        }
+    }
+}
+
+/// Extract the 1-based inclusive line range from content, if it still exists.
+pub(crate) fn slice_lines(content: &str, start: usize, end: usize) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start_idx = start.saturating_sub(1);
+    if start_idx >= lines.len() {
+        return None;
+    }
+    let end_idx = end.clamp(start_idx + 1, lines.len());
+    Some(lines[start_idx..end_idx].join("\n"))
+}
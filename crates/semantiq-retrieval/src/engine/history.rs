@@ -0,0 +1,228 @@
+//! Queries at an earlier index generation for RetrievalEngine.
+//!
+//! The index keeps every version of every symbol, with the lines it had,
+//! and the number of occurrences of each name in each file, with the
+//! generations they were valid at (see `semantiq_index::store::history`).
+//! The definitions and usages of a symbol can thus be listed as they were
+//! before a refactoring or on the branch indexed at the time. The lines of
+//! usages are not kept: a usage at an earlier generation is the number of
+//! references in a file.
+
+use super::RetrievalEngine;
+use super::analysis::{SymbolDefinition, SymbolExplanation};
+use crate::query::FindRefsOptions;
+use crate::results::{SearchResult, SearchResultKind, SearchResultMetadata, SearchResults};
+use anyhow::Result;
+use semantiq_index::{SymbolVersionRecord, chunk_title};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use thiserror::Error;
+use tracing::info;
+
+/// Definitions listed by [`RetrievalEngine::explain_symbol_as_of`], like
+/// [`RetrievalEngine::explain_symbol`]
+const MAX_DEFINITIONS: usize = 20;
+
+/// Errors caused by the requested generation rather than by the engine
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("Index generation {requested} does not exist yet (current generation {current})")]
+    FutureGeneration { requested: u64, current: u64 },
+    #[error(
+        "Index generation {requested} is no longer kept in the history (oldest generation {oldest}, see [history] max_generations)"
+    )]
+    PrunedGeneration { requested: u64, oldest: u64 },
+}
+
+impl RetrievalEngine {
+    /// Current index generation, the latest one `as_of` queries accept.
+    pub fn index_generation(&self) -> Result<u64> {
+        self.store.index_generation()
+    }
+
+    fn check_generation(&self, generation: u64) -> Result<()> {
+        let current = self.store.index_generation()?;
+        if generation > current {
+            return Err(HistoryError::FutureGeneration {
+                requested: generation,
+                current,
+            }
+            .into());
+        }
+        let oldest = self.store.oldest_history_generation()?;
+        if generation < oldest {
+            return Err(HistoryError::PrunedGeneration {
+                requested: generation,
+                oldest,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// References to a symbol at index generation `generation`, filtered
+    /// by `options` (see [`RetrievalEngine::find_references`]): its
+    /// definitions, whose content is the signature they had, followed by
+    /// one usage per file holding the number of references in that file.
+    /// Comments and strings are not told apart, nor are cross-language links
+    /// followed.
+    pub(crate) fn find_references_as_of(
+        &self,
+        symbol_name: &str,
+        options: &FindRefsOptions,
+        generation: u64,
+    ) -> Result<SearchResults> {
+        info!(symbol = %symbol_name, generation, "Finding references at an earlier generation");
+        let start = Instant::now();
+        self.check_generation(generation)?;
+
+        let versions = self.store.get_symbol_versions(symbol_name, generation)?;
+        let usages = self.usages_as_of(symbol_name, generation, &versions)?;
+        let mut results = Vec::new();
+        if options.kind.includes_definitions() {
+            results.extend(
+                versions
+                    .into_iter()
+                    .filter(|v| options.accepts_path(&v.file_path))
+                    .map(definition_result),
+            );
+        }
+        if options.kind.includes_usages() {
+            results.extend(
+                usages
+                    .into_iter()
+                    .filter(|(path, _)| options.accepts_path(path))
+                    .map(|(path, count)| usage_result(symbol_name, path, count)),
+            );
+        }
+        results.truncate(options.limit);
+
+        Ok(SearchResults::new(
+            symbol_name.to_string(),
+            results,
+            start.elapsed().as_millis() as u64,
+        ))
+    }
+
+    /// Number of references to `symbol_name` in each file at `generation`,
+    /// by path: its occurrences less its definitions `versions`
+    fn usages_as_of(
+        &self,
+        symbol_name: &str,
+        generation: u64,
+        versions: &[SymbolVersionRecord],
+    ) -> Result<Vec<(String, usize)>> {
+        let mut definitions: HashMap<&str, usize> = HashMap::new();
+        for version in versions {
+            *definitions.entry(version.file_path.as_str()).or_default() += 1;
+        }
+        Ok(self
+            .store
+            .get_occurrences_as_of(symbol_name, generation)?
+            .into_iter()
+            .filter_map(|(path, count)| {
+                let defined = definitions.get(path.as_str()).copied().unwrap_or(0);
+                let references = count.saturating_sub(defined);
+                (references > 0).then_some((path, references))
+            })
+            .collect())
+    }
+
+    /// Explain a symbol as it was at index generation `generation`: its
+    /// definitions, the symbols of their files and how often it was
+    /// referenced then.
+    pub fn explain_symbol_as_of(
+        &self,
+        symbol_name: &str,
+        generation: u64,
+    ) -> Result<SymbolExplanation> {
+        info!(symbol = %symbol_name, generation, "Explaining symbol at an earlier generation");
+        self.check_generation(generation)?;
+
+        let versions = self.store.get_symbol_versions(symbol_name, generation)?;
+        let usages = self.usages_as_of(symbol_name, generation, &versions)?;
+        let mut definitions = Vec::new();
+        let mut related_symbols = HashSet::new();
+        let mut seen_paths = HashSet::new();
+        for version in versions.iter().take(MAX_DEFINITIONS) {
+            if seen_paths.insert(version.file_path.as_str()) {
+                for other in self
+                    .store
+                    .get_file_symbol_versions(&version.file_path, generation)?
+                {
+                    if other.name != symbol_name {
+                        related_symbols.insert(other.name);
+                    }
+                }
+            }
+            definitions.push(SymbolDefinition {
+                file_path: version.file_path.clone(),
+                kind: version.kind.clone(),
+                start_line: version.start_line as usize,
+                end_line: version.end_line as usize,
+                signature: version.signature.clone(),
+                doc_comment: version.doc_comment.clone(),
+                qualifiers: Default::default(),
+                cfg_context: None,
+            });
+        }
+
+        let mut related_symbols: Vec<String> = related_symbols.into_iter().collect();
+        related_symbols.sort();
+        Ok(SymbolExplanation {
+            name: symbol_name.to_string(),
+            found: !definitions.is_empty(),
+            definitions,
+            usage_count: usages.iter().map(|(_, count)| count).sum(),
+            usage_files: usages.len(),
+            related_symbols,
+            as_of: Some(generation),
+        })
+    }
+}
+
+fn definition_result(version: SymbolVersionRecord) -> SearchResult {
+    let content = version
+        .signature
+        .clone()
+        .unwrap_or_else(|| version.name.clone());
+    SearchResult::new(
+        SearchResultKind::Symbol,
+        version.file_path,
+        version.start_line as usize,
+        version.end_line as usize,
+        content,
+        1.0,
+    )
+    .with_metadata(SearchResultMetadata {
+        title: Some(chunk_title(
+            &version.kind,
+            &version.name,
+            version.signature.as_deref(),
+            version.doc_comment.as_deref(),
+        )),
+        symbol_name: Some(version.name),
+        symbol_kind: Some(version.kind),
+        match_type: Some("definition".to_string()),
+        context: version.signature.clone(),
+        signature: version.signature,
+        parent: version.parent,
+        ..SearchResultMetadata::default()
+    })
+}
+
+/// The `count` references of a file at an earlier generation, whose lines
+/// are not kept: line 0 stands for the whole file
+fn usage_result(symbol_name: &str, path: String, count: usize) -> SearchResult {
+    let content = match count {
+        1 => "1 reference".to_string(),
+        n => format!("{} references", n),
+    };
+    SearchResult::new(SearchResultKind::Reference, path, 0, 0, content, 1.0).with_metadata(
+        SearchResultMetadata {
+            symbol_name: Some(symbol_name.to_string()),
+            match_type: Some("usage".to_string()),
+            ..SearchResultMetadata::default()
+        },
+    )
+}
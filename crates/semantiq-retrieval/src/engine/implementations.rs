@@ -0,0 +1,139 @@
+//! Implementations of traits and interfaces for RetrievalEngine.
+
+use super::RetrievalEngine;
+use crate::results::ImplementationsResponse;
+use anyhow::Result;
+use semantiq_index::TypeRelationRecord;
+use semantiq_parser::RelationKind;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+use tracing::info;
+
+/// Maximum depth of embedded interfaces followed when collecting the
+/// methods required by a Go interface.
+const MAX_EMBEDDING_DEPTH: usize = 5;
+
+/// A type implementing or extending a trait, interface or base type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Implementation {
+    pub type_name: String,
+    /// `implements` or `extends`
+    pub relation: String,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Inferred from the type's method set (Go) rather than declared
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inferred: bool,
+}
+
+impl Implementation {
+    fn declared(record: TypeRelationRecord) -> Self {
+        Self {
+            type_name: record.type_name,
+            relation: record.kind,
+            file_path: record.file_path,
+            start_line: record.start_line as usize,
+            end_line: record.end_line as usize,
+            inferred: false,
+        }
+    }
+}
+
+impl RetrievalEngine {
+    /// Find the types implementing or extending a trait, interface or base type.
+    ///
+    /// Declared relations (`impl Trait for Type`, `implements`, `extends`) are
+    /// listed first. Go types are then matched by method set: a type whose
+    /// methods cover every method of the interface is reported as an inferred
+    /// implementation, located at its first matching method.
+    pub fn find_implementations(
+        &self,
+        name: &str,
+        limit: usize,
+    ) -> Result<ImplementationsResponse> {
+        info!(target = %name, limit = limit, "Finding implementations");
+        let start = Instant::now();
+
+        let mut implementations: Vec<Implementation> = self
+            .store
+            .find_relations_to(name, RelationKind::Implements)?
+            .into_iter()
+            .chain(self.store.find_relations_to(name, RelationKind::Extends)?)
+            .map(Implementation::declared)
+            .collect();
+        implementations.extend(self.infer_go_implementations(name)?);
+
+        let mut seen = HashSet::new();
+        implementations.retain(|i| seen.insert((i.type_name.clone(), i.file_path.clone())));
+        let total_count = implementations.len();
+        implementations.truncate(limit);
+
+        Ok(ImplementationsResponse {
+            target: name.to_string(),
+            total_count,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            implementations,
+        })
+    }
+
+    /// Go types whose method set covers the methods required by `interface`,
+    /// including those of embedded interfaces found in the index.
+    fn infer_go_implementations(&self, interface: &str) -> Result<Vec<Implementation>> {
+        let mut required = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![interface.to_string()];
+
+        for _ in 0..MAX_EMBEDDING_DEPTH {
+            let mut next = Vec::new();
+            for name in pending.drain(..) {
+                if !visited.insert(name.clone()) {
+                    continue;
+                }
+                for record in self
+                    .store
+                    .find_relations_from(&name, RelationKind::RequiresMethod)?
+                {
+                    required.insert(record.target);
+                }
+                for record in self
+                    .store
+                    .find_relations_from(&name, RelationKind::Extends)?
+                {
+                    if record.language.as_deref() == Some("go") {
+                        next.push(record.target);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            pending = next;
+        }
+
+        if required.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let methods: Vec<String> = required.iter().cloned().collect();
+        let mut method_sets: BTreeMap<String, (HashSet<String>, TypeRelationRecord)> =
+            BTreeMap::new();
+        for record in self.store.find_method_declarations(&methods)? {
+            let entry = method_sets
+                .entry(record.type_name.clone())
+                .or_insert_with(|| (HashSet::new(), record.clone()));
+            entry.0.insert(record.target);
+        }
+
+        Ok(method_sets
+            .into_values()
+            .filter(|(methods, _)| methods.len() == required.len())
+            .map(|(_, first)| Implementation {
+                relation: RelationKind::Implements.as_str().to_string(),
+                inferred: true,
+                ..Implementation::declared(first)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,105 @@
+//! Dependencies of C and C++ files through their includes.
+//!
+//! Dependents are found by the name of the included file, which two headers
+//! of the same name in different directories share: those whose include
+//! resolves to another file are left out. A source file is never included,
+//! its dependents are the files including the headers paired with it (see
+//! `semantiq_index::includes`).
+//!
+//! Includes are looked up in the include directories of the translation
+//! unit of the including file when the compilation database has one.
+
+use super::RetrievalEngine;
+use anyhow::Result;
+use semantiq_index::{DependencyRecord, normalize_path, paired_files};
+use serde::{Deserialize, Serialize};
+
+/// How a C or C++ source is compiled, from the compilation database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Compilation {
+    /// Build target compiling the file, if the build system names it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Source files of the build target, including this one
+    #[serde(default)]
+    pub target_files: usize,
+    /// Macros defined on the command line, `NAME` or `NAME=value`
+    pub defines: Vec<String>,
+    /// Include directories in search order, relative to the project root or
+    /// absolute outside of it
+    pub include_dirs: Vec<String>,
+}
+
+impl RetrievalEngine {
+    /// Indexed file the include `record` of the file at `including`
+    /// designates, if it is found
+    pub(crate) fn resolve_include(
+        &self,
+        including: &str,
+        record: &DependencyRecord,
+    ) -> Option<String> {
+        let unit = self.store.get_translation_unit(including).ok().flatten();
+        let paths = unit.map(|unit| self.include_paths.for_unit(&unit));
+        paths.as_ref().unwrap_or(&self.include_paths).resolve(
+            including,
+            &record.target_path,
+            record.kind == "local",
+            |path| self.is_indexed(path),
+        )
+    }
+
+    /// Whether the include `record` of the file at `including` may include
+    /// `target`: it does unless it resolves to another file
+    pub(crate) fn may_include(
+        &self,
+        including: &str,
+        record: &DependencyRecord,
+        target: &str,
+    ) -> bool {
+        self.resolve_include(including, record)
+            .is_none_or(|resolved| resolved == target)
+    }
+
+    /// Files including the headers paired with the C or C++ source at
+    /// `source`, with the header each includes and its include
+    pub(crate) fn paired_header_dependents(
+        &self,
+        source: &str,
+    ) -> Result<Vec<(String, String, DependencyRecord)>> {
+        let mut dependents = Vec::new();
+        for header in paired_files(source, |path| self.is_indexed(path)) {
+            for record in self.store.get_dependents(&header)? {
+                let including = self.get_file_path(record.source_file_id)?;
+                if including != source && self.may_include(&including, &record, &header) {
+                    dependents.push((including, header.clone(), record));
+                }
+            }
+        }
+        Ok(dependents)
+    }
+
+    /// How the C or C++ source at `file_path` is compiled, `None` if it is
+    /// not in the compilation database
+    pub fn file_compilation(&self, file_path: &str) -> Result<Option<Compilation>> {
+        let Some(unit) = self
+            .store
+            .get_translation_unit(&normalize_path(file_path))?
+        else {
+            return Ok(None);
+        };
+        let target_files = match unit.target {
+            Some(ref target) => self.store.get_target_files(target)?.len(),
+            None => 0,
+        };
+        Ok(Some(Compilation {
+            target: unit.target,
+            target_files,
+            defines: unit.defines,
+            include_dirs: unit.include_dirs,
+        }))
+    }
+
+    fn is_indexed(&self, path: &str) -> bool {
+        matches!(self.store.get_file_by_path(path), Ok(Some(_)))
+    }
+}
@@ -0,0 +1,45 @@
+//! Indexing issues for RetrievalEngine.
+//!
+//! Files that failed to parse, could not be read, are too large or had their
+//! encoding guessed are indexed as well as possible or left out (see
+//! `semantiq_index::issues`). Listing them explains why a file is missing
+//! from results, or found without its symbols.
+
+use super::RetrievalEngine;
+use crate::results::IndexIssuesResponse;
+use anyhow::Result;
+use semantiq_index::IndexIssueKind;
+use std::time::Instant;
+use tracing::info;
+
+impl RetrievalEngine {
+    /// List the issues met by the last indexing of each file, of the given
+    /// kind or of any, by path. At most `limit` are listed; `total_count`
+    /// and `counts` have the numbers recorded.
+    pub fn index_issues(
+        &self,
+        kind: Option<IndexIssueKind>,
+        limit: usize,
+    ) -> Result<IndexIssuesResponse> {
+        let start = Instant::now();
+        info!(kind = ?kind, limit = limit, "Listing index issues");
+
+        let counts = self.store.count_index_issues()?;
+        let total_count = match kind {
+            Some(kind) => counts.get(&kind).copied().unwrap_or(0),
+            None => counts.values().sum(),
+        };
+        let issues = self.store.get_index_issues(kind, limit)?;
+
+        Ok(IndexIssuesResponse {
+            kind: kind.map(|k| k.as_str().to_string()),
+            total_count,
+            counts: counts
+                .into_iter()
+                .map(|(kind, count)| (kind.as_str().to_string(), count))
+                .collect(),
+            search_time_ms: start.elapsed().as_millis() as u64,
+            issues,
+        })
+    }
+}
@@ -4,41 +4,123 @@
 //! strategies (semantic, symbol, text) into a unified search interface.
 
 mod analysis;
+mod base;
+mod components;
+mod config_usage;
+mod cross_language;
+mod diff;
+mod entrypoints;
+mod expand;
+mod explore;
+mod freshness;
+mod history;
+mod implementations;
+mod includes;
+mod issues;
+mod overview;
+mod related;
+mod routes;
 mod search;
 mod threshold;
+mod usage;
 
+use crate::cache::{QueryCache, QueryEmbeddingCache, RefsCache};
+use crate::results::SessionSummary;
+use crate::session::SearchSessions;
 use crate::threshold::{CollectorConfig, DistanceCollector, ThresholdConfig};
-use semantiq_embeddings::{EmbeddingModel, create_embedding_model};
-use semantiq_index::IndexStore;
-use std::path::PathBuf;
+use anyhow::Result;
+use semantiq_embeddings::{EmbeddingModel, EmbeddingModelKind, create_embedding_model_for};
+use semantiq_index::{
+    AsyncIndexStore, GitConfig, IncludePaths, IncludesConfig, IndexStore, QueryCacheConfig,
+    SearchConfig, SemantiqConfig,
+};
+use std::path::Path;
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Instant;
 use tracing::debug;
 
 // Re-export types
-pub use analysis::{DependencyInfo, SymbolDefinition, SymbolExplanation};
+pub use analysis::{
+    DependencyInfo, DependencyScope, SymbolDefinition, SymbolDependency, SymbolExplanation,
+};
+pub use components::ComponentNode;
+pub use config_usage::ConfigUsage;
+pub use cross_language::CrossLanguageLink;
+pub use diff::{ChangeKind, DiffError, FileDiff, SymbolChange};
+pub use entrypoints::EntrypointLocation;
+pub use explore::{ExclusionReason, ExploreEntry};
+pub use history::HistoryError;
+pub use implementations::Implementation;
+pub use includes::Compilation;
+pub use related::{RelatedFile, RelatedReason, RelatedSignal};
+pub use routes::{HandlerDefinition, RouteLocation};
+pub use usage::{LanguageCaveat, UnusedSymbol, UsageConfidence};
 
-/// Cached list of walkable file paths with a TTL to avoid re-walking the
-/// directory tree on every `search_text()` call within the same session.
-pub(crate) struct FileListCache {
-    paths: Vec<PathBuf>,
-    created_at: Instant,
+/// Load an embedding model for queries, `None` if it is not available.
+fn load_query_model(kind: EmbeddingModelKind) -> Option<Arc<dyn EmbeddingModel>> {
+    match create_embedding_model_for(kind) {
+        Ok(model) => {
+            debug!(
+                "Embedding model {} loaded (dim={})",
+                kind,
+                model.dimension()
+            );
+            Some(model)
+        }
+        Err(e) => {
+            debug!("Failed to load embedding model: {}", e);
+            None
+        }
+    }
 }
 
-/// TTL for the file list cache (30 seconds).
-const FILE_LIST_CACHE_TTL_SECS: u64 = 30;
+/// Embedding model queries are embedded with, loaded on first use
+pub(crate) struct QueryModel {
+    /// Model of the index the model is (to be) loaded for
+    kind: EmbeddingModelKind,
+    /// `None` until loaded, then `Some(None)` if it is not available
+    model: Option<Option<Arc<dyn EmbeddingModel>>>,
+}
 
 /// The main search and retrieval engine.
 pub struct RetrievalEngine {
     pub(crate) store: Arc<IndexStore>,
+    /// The store, reached from async code through a bounded queue of
+    /// blocking threads
+    pub(crate) async_store: AsyncIndexStore,
     pub(crate) root_path: String,
-    pub(crate) embedding_model: Option<Box<dyn EmbeddingModel>>,
+    /// Model queries are embedded with, loaded by the first semantic search
+    /// so that the engine is usable for other lookups right away
+    pub(crate) query_model: Arc<RwLock<QueryModel>>,
+    /// Held while the query model loads, so that it is loaded only once
+    pub(crate) model_loading: Arc<Mutex<()>>,
     /// Adaptive threshold configuration (loaded from calibration).
     pub(crate) threshold_config: Arc<RwLock<ThresholdConfig>>,
     /// Distance collector for ML calibration (optional).
     pub(crate) distance_collector: Option<DistanceCollector>,
-    /// Cached file list for text search to avoid re-walking the tree.
-    pub(crate) file_list_cache: Mutex<Option<FileListCache>>,
+    /// Cache of search results, invalidated when the index changes.
+    pub(crate) query_cache: Option<QueryCache>,
+    /// Cache of the references of symbols, invalidated when their files
+    /// change.
+    pub(crate) refs_cache: Option<RefsCache>,
+    /// Cache of query embeddings, kept in the database across restarts.
+    pub(crate) query_embedding_cache: Option<QueryEmbeddingCache>,
+    /// Semantic search strategy (`[search]` in `semantiq.toml`).
+    pub(crate) search_config: SearchConfig,
+    /// Use of the git history (`[git]` in `semantiq.toml`).
+    pub(crate) git_config: GitConfig,
+    /// Directories C and C++ includes are looked up in (`[includes]` in
+    /// `semantiq.toml` and the compilation database)
+    pub(crate) include_paths: Arc<IncludePaths>,
+    /// Whether semantic searches go through file summaries first, with the
+    /// index generation it was decided at.
+    pub(crate) coarse_search: Mutex<Option<(u64, bool)>>,
+    /// Engine over the read-only base index searched along with this one
+    pub(crate) base: Option<Box<RetrievalEngine>>,
+    /// Set on the engine over a base index: the local index, which shadows
+    /// it and caches the query embeddings
+    pub(crate) local_store: Option<Arc<IndexStore>>,
+    /// Search sessions of the conversations of the clients
+    pub(crate) sessions: SearchSessions,
 }
 
 impl RetrievalEngine {
@@ -52,17 +134,9 @@ impl RetrievalEngine {
     /// When `enable_collection` is true, distance observations are collected
     /// during semantic search for later ML calibration.
     pub fn with_options(store: Arc<IndexStore>, root_path: &str, enable_collection: bool) -> Self {
-        // Try to load embedding model
-        let embedding_model = match create_embedding_model(None) {
-            Ok(model) => {
-                debug!("Embedding model loaded (dim={})", model.dimension());
-                Some(model)
-            }
-            Err(e) => {
-                debug!("Failed to load embedding model: {}", e);
-                None
-            }
-        };
+        // Queries are embedded with the model of the index, so that query
+        // vectors live in the same space as the stored ones
+        let model_kind = store.embedding_model().unwrap_or_default();
 
         // Load calibrated thresholds from database
         let threshold_config = Self::load_thresholds_from_store(&store);
@@ -89,15 +163,222 @@ impl RetrievalEngine {
         };
 
         Self {
+            async_store: AsyncIndexStore::new(Arc::clone(&store)),
             store,
             root_path: root_path.to_string(),
-            embedding_model,
+            query_model: Arc::new(RwLock::new(QueryModel {
+                kind: model_kind,
+                model: None,
+            })),
+            model_loading: Arc::new(Mutex::new(())),
             threshold_config: Arc::new(RwLock::new(threshold_config)),
             distance_collector,
-            file_list_cache: Mutex::new(None),
+            query_cache: Some(QueryCache::new(&QueryCacheConfig::default())),
+            refs_cache: Some(RefsCache::new(&QueryCacheConfig::default())),
+            query_embedding_cache: QueryEmbeddingCache::new(&QueryCacheConfig::default()),
+            search_config: SearchConfig::default(),
+            git_config: GitConfig::default(),
+            include_paths: Arc::default(),
+            coarse_search: Mutex::new(None),
+            base: None,
+            local_store: None,
+            sessions: SearchSessions::default(),
         }
     }
 
+    /// Create a new RetrievalEngine configured by the `semantiq.toml` of the
+    /// project: caches, search strategy, git history, C and C++ includes,
+    /// base index and index queue.
+    pub fn from_config(store: Arc<IndexStore>, root_path: &str, config: &SemantiqConfig) -> Self {
+        Self::new(store, root_path)
+            .with_query_cache(&config.cache)
+            .with_search_config(&config.search)
+            .with_git_config(&config.git)
+            .with_includes_config(&config.includes)
+            .with_base_config(&config.base)
+            .with_index_queue(config.limits.index_queue)
+    }
+
+    /// Configure the search result and query embedding caches (`[cache]` in
+    /// `semantiq.toml`).
+    pub fn with_query_cache(mut self, config: &QueryCacheConfig) -> Self {
+        self.query_cache = config.enabled.then(|| QueryCache::new(config));
+        self.refs_cache = config.enabled.then(|| RefsCache::new(config));
+        self.query_embedding_cache = QueryEmbeddingCache::new(config);
+        if let Some(base) = &mut self.base {
+            base.query_embedding_cache = self.query_embedding_cache.clone();
+        }
+        self
+    }
+
+    /// Configure the semantic search strategy (`[search]` in `semantiq.toml`).
+    pub fn with_search_config(mut self, config: &SearchConfig) -> Self {
+        self.search_config = config.clone();
+        self.coarse_search = Mutex::new(None);
+        self.base = self
+            .base
+            .map(|base| Box::new(base.with_search_config(config)));
+        self
+    }
+
+    /// Configure the use of the git history (`[git]` in `semantiq.toml`).
+    pub fn with_git_config(mut self, config: &GitConfig) -> Self {
+        self.git_config = config.clone();
+        self.base = self.base.map(|base| Box::new(base.with_git_config(config)));
+        self
+    }
+
+    /// Configure the lookup of C and C++ includes (`[includes]` in
+    /// `semantiq.toml`), reading the compilation database of the project.
+    pub fn with_includes_config(mut self, config: &IncludesConfig) -> Self {
+        self.include_paths = Arc::new(IncludePaths::load(Path::new(&self.root_path), config));
+        if let Some(base) = &mut self.base {
+            base.include_paths = Arc::clone(&self.include_paths);
+        }
+        self
+    }
+
+    /// Run at most `size` index operations at the same time from async code
+    /// (`[limits] index_queue` in `semantiq.toml`).
+    pub fn with_index_queue(mut self, size: usize) -> Self {
+        self.async_store = AsyncIndexStore::new(Arc::clone(&self.store)).with_queue_size(size);
+        self
+    }
+
+    /// The store, for async callers. Its queue is the one of
+    /// [`RetrievalEngine::run_blocking`].
+    pub fn async_store(&self) -> &AsyncIndexStore {
+        &self.async_store
+    }
+
+    /// Run `work` on the engine off the async runtime, once a slot of the
+    /// index queue is free. Searches and lookups query SQLite synchronously
+    /// and must not block the threads serving other requests.
+    pub async fn run_blocking<T, F>(self: &Arc<Self>, work: F) -> Result<T>
+    where
+        F: FnOnce(&RetrievalEngine) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let engine = Arc::clone(self);
+        self.async_store
+            .spawn_blocking(move || work(&engine))
+            .await?
+    }
+
+    /// Model queries are embedded with, `None` if it could not be loaded.
+    /// Loaded on the first call, and reloaded when the index switched to
+    /// another embedding model.
+    pub(crate) fn embedding_model(&self) -> Option<Arc<dyn EmbeddingModel>> {
+        let index_kind = self.store.embedding_model().ok();
+        if let Some(model) = self.loaded_model(index_kind) {
+            return model;
+        }
+
+        // Searches arriving while the model loads wait for it
+        let _loading = match self.model_loading.lock() {
+            Ok(guard) => guard,
+            Err(e) => e.into_inner(),
+        };
+        if let Some(model) = self.loaded_model(index_kind) {
+            return model;
+        }
+
+        let (loaded_kind, loaded) = match self.query_model.read() {
+            Ok(query_model) => (query_model.kind, query_model.model.is_some()),
+            Err(e) => (e.get_ref().kind, e.get_ref().model.is_some()),
+        };
+        let kind = index_kind.unwrap_or(loaded_kind);
+        if loaded {
+            debug!(
+                "Index switched from embedding model {} to {}",
+                loaded_kind, kind
+            );
+        }
+
+        let model = load_query_model(kind);
+        let mut query_model = match self.query_model.write() {
+            Ok(loaded) => loaded,
+            Err(e) => e.into_inner(),
+        };
+        *query_model = QueryModel {
+            kind,
+            model: Some(model.clone()),
+        };
+        model
+    }
+
+    /// The loaded query model, unless it is still to be loaded for the
+    /// model of the index
+    fn loaded_model(
+        &self,
+        index_kind: Option<EmbeddingModelKind>,
+    ) -> Option<Option<Arc<dyn EmbeddingModel>>> {
+        let query_model = match self.query_model.read() {
+            Ok(loaded) => loaded,
+            Err(e) => e.into_inner(),
+        };
+        if index_kind.is_some_and(|kind| kind != query_model.kind) {
+            return None;
+        }
+        query_model.model.clone()
+    }
+
+    /// Embed a search query with `model`, the model of the index, in the
+    /// query prompt of the index, through the query embedding cache when
+    /// enabled
+    pub(crate) fn embed_query(&self, model: &dyn EmbeddingModel, query: &str) -> Result<Vec<f32>> {
+        let store = self.local_store.as_ref().unwrap_or(&self.store);
+        let query = store.query_text(query);
+        let Some(ref cache) = self.query_embedding_cache else {
+            return model.embed(&query);
+        };
+        let kind = match self.query_model.read() {
+            Ok(query_model) => query_model.kind,
+            Err(e) => e.get_ref().kind,
+        };
+        cache.embed(store, kind.as_str(), model, &query)
+    }
+
+    /// The query embedding model, unless it is a placeholder returning
+    /// meaningless embeddings (built without ONNX, or the model failed to
+    /// download), in which case chunks are ranked with BM25 instead
+    pub(crate) fn semantic_model(&self) -> Option<Arc<dyn EmbeddingModel>> {
+        self.embedding_model()
+            .filter(|model| !model.is_placeholder())
+    }
+
+    /// Load the query embedding model now rather than on the first semantic
+    /// search. Returns whether it is available.
+    pub fn preload_embedding_model(&self) -> bool {
+        self.embedding_model().is_some()
+    }
+
+    /// Whether the query embedding model was loaded (or found unavailable),
+    /// so that semantic search does not wait for it
+    pub fn embedding_model_ready(&self) -> bool {
+        let query_model = match self.query_model.read() {
+            Ok(loaded) => loaded,
+            Err(e) => e.into_inner(),
+        };
+        query_model.model.is_some()
+    }
+
+    /// Get the search result cache (if enabled).
+    pub fn query_cache(&self) -> Option<&QueryCache> {
+        self.query_cache.as_ref()
+    }
+
+    /// The cache of the references of symbols, if enabled.
+    pub fn refs_cache(&self) -> Option<&RefsCache> {
+        self.refs_cache.as_ref()
+    }
+
+    /// Searches and files viewed in a search session, `None` if no search
+    /// was made in it (or it was forgotten).
+    pub fn session_summary(&self, session_id: &str) -> Option<SessionSummary> {
+        self.sessions.summary(session_id)
+    }
+
     /// Get the current threshold configuration.
     pub fn threshold_config(&self) -> Arc<RwLock<ThresholdConfig>> {
         Arc::clone(&self.threshold_config)
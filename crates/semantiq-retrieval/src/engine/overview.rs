@@ -0,0 +1,46 @@
+//! Directory overviews for RetrievalEngine.
+//!
+//! Overviews read the directory summaries of the index (see
+//! `semantiq_index::DirectorySummary`), rebuilding them first when the index
+//! changed since they were computed.
+
+use super::RetrievalEngine;
+use crate::results::OverviewResponse;
+use anyhow::Result;
+use semantiq_index::normalize_path;
+use std::time::Instant;
+use tracing::info;
+
+/// Subdirectories listed in an overview
+const MAX_SUBDIRECTORIES: usize = 50;
+
+impl RetrievalEngine {
+    /// Structured map of the directory `path` relative to the project root
+    /// ("" or "." for the root): languages, lines, top symbols and entry
+    /// files of the directory and of each of its subdirectories.
+    pub fn overview(&self, path: &str) -> Result<OverviewResponse> {
+        let start = Instant::now();
+        let path = match normalize_path(path) {
+            path if path.is_empty() => ".".to_string(),
+            path => path,
+        };
+
+        if !self.store.dir_summaries_current()? {
+            let count = self.store.refresh_dir_summaries()?;
+            info!(directories = count, "Rebuilt directory summaries");
+        }
+
+        let summary = self.store.get_dir_summary(&path)?;
+        let mut subdirectories = self.store.get_subdirectory_summaries(&path)?;
+        let subdirectory_count = subdirectories.len();
+        subdirectories.truncate(MAX_SUBDIRECTORIES);
+
+        Ok(OverviewResponse {
+            path,
+            summary,
+            subdirectory_count,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            subdirectories,
+        })
+    }
+}
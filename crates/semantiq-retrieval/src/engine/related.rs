@@ -0,0 +1,445 @@
+//! Files likely to be edited together with a given file, for RetrievalEngine.
+//!
+//! Several signals are merged, each adding to the score of a suggested file:
+//! import edges in either direction, symbols defined in one file and used in
+//! the other, similar file summaries, and how often both files changed in the
+//! same commits when the git history is available.
+
+use super::RetrievalEngine;
+use super::diff::git;
+use crate::results::RelatedFilesResponse;
+use anyhow::Result;
+use semantiq_index::{FileRecord, is_external_path, normalize_path};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+use tracing::{debug, info};
+
+/// Score of an import edge between the two files, in either direction
+const IMPORT_SCORE: f32 = 1.0;
+
+/// Score of each shared symbol, up to [`MAX_SHARED_SYMBOLS_SCORE`]
+const SHARED_SYMBOL_SCORE: f32 = 0.25;
+const MAX_SHARED_SYMBOLS_SCORE: f32 = 1.0;
+
+/// Names defined in more files are too common to relate two files
+const MAX_SYMBOL_DEFINITIONS: usize = 3;
+
+/// Shared symbols named in a reason
+const MAX_LISTED_SYMBOLS: usize = 5;
+
+/// Files compared by summary, and the similarity from which they count
+const SIMILAR_FILES: usize = 20;
+const MIN_SUMMARY_SIMILARITY: f32 = 0.5;
+
+/// Score of a summary similarity of 1
+const SIMILARITY_SCORE: f32 = 0.75;
+
+/// Score of files changed in every commit of the file
+const CO_CHANGE_SCORE: f32 = 1.5;
+
+/// Commits a file must share with the given one to be suggested
+const MIN_CO_CHANGES: usize = 2;
+
+/// Commits touching more files (formatting, renames, dependency bumps) say
+/// little about which files belong together
+const MAX_COMMIT_FILES: usize = 50;
+
+/// Indexed files checked as the target of each import
+const MAX_IMPORT_CANDIDATES: usize = 10;
+
+/// Signal behind a related file suggestion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelatedSignal {
+    /// The file imports the suggested file
+    Imports,
+    /// The suggested file imports the file
+    ImportedBy,
+    /// One file defines symbols the other uses
+    SharedSymbols,
+    /// Both files have similar summary embeddings
+    SimilarSummary,
+    /// Both files changed in the same commits
+    CoChange,
+}
+
+impl RelatedSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelatedSignal::Imports => "imports",
+            RelatedSignal::ImportedBy => "imported_by",
+            RelatedSignal::SharedSymbols => "shared_symbols",
+            RelatedSignal::SimilarSummary => "similar_summary",
+            RelatedSignal::CoChange => "co_change",
+        }
+    }
+}
+
+/// Why a file is suggested, and how much it adds to its score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedReason {
+    pub signal: RelatedSignal,
+    pub detail: String,
+    pub score: f32,
+}
+
+/// A file likely to need editing together with the given one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedFile {
+    pub file_path: String,
+    /// Sum of the scores of the reasons
+    pub score: f32,
+    pub reasons: Vec<RelatedReason>,
+}
+
+/// Reasons collected for each suggested file, one per signal
+#[derive(Default)]
+struct Suggestions {
+    reasons: BTreeMap<String, Vec<RelatedReason>>,
+}
+
+impl Suggestions {
+    fn add(&mut self, file_path: &str, signal: RelatedSignal, detail: String, score: f32) {
+        let reasons = self.reasons.entry(file_path.to_string()).or_default();
+        if !reasons.iter().any(|r| r.signal == signal) {
+            reasons.push(RelatedReason {
+                signal,
+                detail,
+                score,
+            });
+        }
+    }
+
+    /// Suggested files, highest score first
+    fn ranked(self) -> Vec<RelatedFile> {
+        let mut files: Vec<RelatedFile> = self
+            .reasons
+            .into_iter()
+            .map(|(file_path, mut reasons)| {
+                reasons.sort_by(|a, b| b.score.total_cmp(&a.score));
+                RelatedFile {
+                    file_path,
+                    score: reasons.iter().map(|r| r.score).sum(),
+                    reasons,
+                }
+            })
+            .collect();
+        files.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        files
+    }
+}
+
+impl RetrievalEngine {
+    /// Suggest the files most likely to need editing together with
+    /// `file_path`, each with the reasons it is suggested for.
+    ///
+    /// The git history is read when `[git] enabled` is set and the project is
+    /// a git repository; otherwise suggestions come from the index alone.
+    pub fn find_related_files(
+        &self,
+        file_path: &str,
+        limit: usize,
+    ) -> Result<RelatedFilesResponse> {
+        let file_path = normalize_path(file_path);
+        info!(file = %file_path, limit = limit, "Finding related files");
+        let start = Instant::now();
+
+        let mut suggestions = Suggestions::default();
+        if let Some(file) = self.store.get_file_by_path(&file_path)? {
+            self.add_import_edges(&file, &mut suggestions)?;
+            self.add_shared_symbols(&file, &mut suggestions)?;
+            self.add_similar_summaries(&file, &mut suggestions)?;
+        }
+
+        let history_commits = if self.git_config.enabled {
+            self.add_co_changes(&file_path, &mut suggestions)?
+        } else {
+            None
+        };
+
+        suggestions.reasons.remove(&file_path);
+        suggestions
+            .reasons
+            .retain(|path, _| !is_external_path(path));
+        let mut related = suggestions.ranked();
+        let total_count = related.len();
+        related.truncate(limit);
+
+        Ok(RelatedFilesResponse {
+            file_path,
+            total_count,
+            history_commits,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            related,
+        })
+    }
+
+    /// Files imported by `file` and files importing it.
+    ///
+    /// Imports are resolved the way `semantiq_deps` lists dependents: an
+    /// indexed file named like the last segment of an import is its target
+    /// if it lists the import among its dependents.
+    fn add_import_edges(&self, file: &FileRecord, suggestions: &mut Suggestions) -> Result<()> {
+        for dependent in self.store.get_dependents(&file.path)? {
+            let path = self.get_file_path(dependent.source_file_id)?;
+            suggestions.add(
+                &path,
+                RelatedSignal::ImportedBy,
+                format!("imports it as `{}`", dependent.target_path),
+                IMPORT_SCORE,
+            );
+        }
+
+        let dependencies = self.store.get_dependencies(file.id)?;
+        if dependencies.is_empty() {
+            return Ok(());
+        }
+        let mut by_stem: HashMap<String, Vec<String>> = HashMap::new();
+        for path in self.store.get_all_file_paths()? {
+            if let Some(stem) = Path::new(&path).file_stem().and_then(|s| s.to_str()) {
+                by_stem.entry(stem.to_lowercase()).or_default().push(path);
+            }
+        }
+
+        for dependency in dependencies {
+            let Some(stem) = import_stem(&dependency.target_path) else {
+                continue;
+            };
+            let candidates = by_stem.get(&stem).map(Vec::as_slice).unwrap_or_default();
+            for path in candidates.iter().take(MAX_IMPORT_CANDIDATES) {
+                let imported = self
+                    .store
+                    .get_dependents(path)?
+                    .iter()
+                    .any(|d| d.id == dependency.id);
+                if imported {
+                    suggestions.add(
+                        path,
+                        RelatedSignal::Imports,
+                        format!("imported as `{}`", dependency.target_path),
+                        IMPORT_SCORE,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Files defining symbols `file` uses, and files using symbols it defines.
+    fn add_shared_symbols(&self, file: &FileRecord, suggestions: &mut Suggestions) -> Result<()> {
+        let mut by_file: BTreeMap<String, (Vec<String>, Vec<String>)> = BTreeMap::new();
+        for shared in self
+            .store
+            .find_shared_symbols(file.id, MAX_SYMBOL_DEFINITIONS)?
+        {
+            let (defined_here, used_here) = by_file.entry(shared.file_path).or_default();
+            if shared.defined_here {
+                defined_here.push(shared.name);
+            } else {
+                used_here.push(shared.name);
+            }
+        }
+
+        for (path, (defined_here, used_here)) in by_file {
+            let count = defined_here.len() + used_here.len();
+            let mut parts = Vec::new();
+            if !used_here.is_empty() {
+                parts.push(format!("defines {}", list_symbols(&used_here)));
+            }
+            if !defined_here.is_empty() {
+                parts.push(format!("uses {}", list_symbols(&defined_here)));
+            }
+            suggestions.add(
+                &path,
+                RelatedSignal::SharedSymbols,
+                parts.join("; "),
+                (count as f32 * SHARED_SYMBOL_SCORE).min(MAX_SHARED_SYMBOLS_SCORE),
+            );
+        }
+        Ok(())
+    }
+
+    /// Files whose summary embedding is close to the summary of `file`.
+    fn add_similar_summaries(
+        &self,
+        file: &FileRecord,
+        suggestions: &mut Suggestions,
+    ) -> Result<()> {
+        let Some(embedding) = self.store.get_file_summary_embedding(file.id)? else {
+            return Ok(());
+        };
+
+        for (file_id, distance) in self
+            .store
+            .search_similar_files(&embedding, SIMILAR_FILES + 1)?
+        {
+            // L2 distance between normalized embeddings
+            let similarity = 1.0 - distance * distance / 2.0;
+            if file_id == file.id || similarity < MIN_SUMMARY_SIMILARITY {
+                continue;
+            }
+            let path = self.get_file_path(file_id)?;
+            suggestions.add(
+                &path,
+                RelatedSignal::SimilarSummary,
+                format!("similar summary ({:.2})", similarity),
+                similarity * SIMILARITY_SCORE,
+            );
+        }
+        Ok(())
+    }
+
+    /// Indexed files that changed in the same commits as `file_path`.
+    /// Returns the number of commits of the file read from the history,
+    /// `None` if it could not be read.
+    fn add_co_changes(
+        &self,
+        file_path: &str,
+        suggestions: &mut Suggestions,
+    ) -> Result<Option<usize>> {
+        let Some((commits, co_changes)) = self.read_co_changes(file_path) else {
+            return Ok(None);
+        };
+
+        for (path, together) in co_changes {
+            if together < MIN_CO_CHANGES || self.store.get_file_by_path(&path)?.is_none() {
+                continue;
+            }
+            suggestions.add(
+                &path,
+                RelatedSignal::CoChange,
+                format!("changed together in {} of {} commits", together, commits),
+                together as f32 / commits as f32 * CO_CHANGE_SCORE,
+            );
+        }
+        Ok(Some(commits))
+    }
+
+    /// Read the recent commits of `file_path` from git: their number, and how
+    /// many of them changed each other file.
+    fn read_co_changes(&self, file_path: &str) -> Option<(usize, HashMap<String, usize>)> {
+        let max_count = format!("--max-count={}", self.git_config.history_commits);
+        let output = git(
+            Path::new(&self.root_path),
+            &[
+                "-c",
+                "core.quotePath=false",
+                "--literal-pathspecs",
+                "log",
+                "--no-merges",
+                "--relative",
+                "--full-diff",
+                "--name-only",
+                "--format=%x1e",
+                &max_count,
+                "--",
+                file_path,
+            ],
+        );
+        match output {
+            Ok(output) if output.status.success() => {
+                Some(parse_co_changes(&output.stdout, file_path))
+            }
+            Ok(output) => {
+                debug!(
+                    "Git history unavailable: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                None
+            }
+            Err(e) => {
+                debug!("Git history unavailable: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Lowercase name of the file an import path most likely points to, e.g.
+/// `search` for `crate::engine::search` or `./search.js`.
+fn import_stem(import: &str) -> Option<String> {
+    let last = import
+        .rsplit(['/', '\\', ':'])
+        .find(|segment| !segment.is_empty())?;
+    let stem = Path::new(last).file_stem()?.to_str()?;
+    (!stem.is_empty() && stem != "." && stem != "..").then(|| stem.to_lowercase())
+}
+
+/// Count, in the output of `git log --name-only --format=%x1e`, the commits
+/// and how many of them changed each file other than `file_path`.
+fn parse_co_changes(log: &[u8], file_path: &str) -> (usize, HashMap<String, usize>) {
+    let log = String::from_utf8_lossy(log);
+    let mut commits = 0;
+    let mut co_changes: HashMap<String, usize> = HashMap::new();
+    for commit in log.split('\x1e') {
+        let files: HashSet<&str> = commit
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        commits += 1;
+        if files.len() > MAX_COMMIT_FILES {
+            continue;
+        }
+        for path in files {
+            if path != file_path {
+                *co_changes.entry(path.to_string()).or_default() += 1;
+            }
+        }
+    }
+    (commits, co_changes)
+}
+
+/// Names of shared symbols, the first [`MAX_LISTED_SYMBOLS`] of them
+fn list_symbols(names: &[String]) -> String {
+    let mut listed: Vec<String> = names
+        .iter()
+        .take(MAX_LISTED_SYMBOLS)
+        .map(|name| format!("`{}`", name))
+        .collect();
+    if names.len() > MAX_LISTED_SYMBOLS {
+        listed.push(format!("{} more", names.len() - MAX_LISTED_SYMBOLS));
+    }
+    listed.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_stem() {
+        assert_eq!(
+            import_stem("crate::engine::search").as_deref(),
+            Some("search")
+        );
+        assert_eq!(import_stem("./utils/Format.js").as_deref(), Some("format"));
+        assert_eq!(import_stem("react").as_deref(), Some("react"));
+        assert_eq!(import_stem("../"), None);
+    }
+
+    #[test]
+    fn test_parse_co_changes() {
+        let many: String = (0..60).map(|i| format!("f{}.rs\n", i)).collect();
+        let log = format!(
+            "\x1e\n\nsrc/a.rs\nsrc/b.rs\n\x1e\n\nsrc/a.rs\nsrc/b.rs\nsrc/c.rs\n\x1e\n\nsrc/a.rs\n{}",
+            many
+        );
+        let (commits, co_changes) = parse_co_changes(log.as_bytes(), "src/a.rs");
+        assert_eq!(commits, 3);
+        assert_eq!(co_changes.get("src/b.rs"), Some(&2));
+        assert_eq!(co_changes.get("src/c.rs"), Some(&1));
+        // Bulk commits are counted but do not relate files
+        assert_eq!(co_changes.get("f0.rs"), None);
+        assert_eq!(co_changes.get("src/a.rs"), None);
+    }
+}
@@ -0,0 +1,288 @@
+//! HTTP routes of a project for RetrievalEngine.
+//!
+//! Routes are recorded with the method and path their framework registers
+//! (see `semantiq_parser::routes`). A request such as `POST /api/users/42`
+//! is matched against them segment by segment, path parameters matching any
+//! segment, and the function handling each matching route is looked up
+//! among the indexed symbols, so that "where is POST /api/users handled"
+//! leads to the handler rather than to every mention of `/api/users`.
+
+use super::RetrievalEngine;
+use crate::query::SearchOptions;
+use crate::results::{RoutesResponse, SearchResult, SearchResultKind, SearchResultMetadata};
+use anyhow::Result;
+use semantiq_index::{RouteRecord, SymbolRecord, chunk_title};
+use semantiq_parser::{HttpRoute, RouteMatch};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Score of the handlers of routes matching the whole path of the `route`
+/// search filter
+const EXACT_ROUTE_SCORE: f32 = 1.0;
+
+/// Score of the handlers of routes only matching the end of the path, which
+/// may be mounted under its beginning
+const SUFFIX_ROUTE_SCORE: f32 = 0.9;
+
+/// Symbol kinds handlers are looked up among, before any other kind
+const HANDLER_KINDS: &[&str] = &["function", "method"];
+
+/// An HTTP route, where it is registered and the function handling it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteLocation {
+    /// Uppercase method, `None` for routes handling every method
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub path: String,
+    /// Function handling the route, when it is named
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    /// File and lines registering the route
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Definition of the handler, when it is indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler_definition: Option<HandlerDefinition>,
+    /// How the requested path matches the route, `None` when every route is
+    /// listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched: Option<RouteMatch>,
+}
+
+impl RouteLocation {
+    /// `POST /api/users`, or the path alone for routes of every method
+    pub fn route(&self) -> String {
+        HttpRoute {
+            method: self.method.clone(),
+            path: self.path.clone(),
+        }
+        .to_string()
+    }
+}
+
+/// Where the function handling a route is defined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandlerDefinition {
+    pub file_path: String,
+    pub kind: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl RetrievalEngine {
+    /// List the HTTP routes handling `method` (any when `None`) and
+    /// matching `path` (all routes when `None`), with their handlers.
+    ///
+    /// Routes matching the whole path come before those only matching its
+    /// end, which a router may mount under a prefix. At most `limit` routes
+    /// are listed; `total_count` has the number found.
+    pub fn routes(
+        &self,
+        method: Option<&str>,
+        path: Option<&str>,
+        limit: usize,
+    ) -> Result<RoutesResponse> {
+        let start = Instant::now();
+        let method = method.map(str::to_uppercase);
+        info!(method = ?method, path = ?path, limit = limit, "Listing routes");
+
+        let matching = self.matching_routes(method.as_deref(), path)?;
+        let total_count = matching.len();
+        let routes = matching
+            .into_iter()
+            .take(limit)
+            .map(|(record, matched)| {
+                let handler_definition = match self.handler_definition(&record)? {
+                    Some(symbol) => Some(HandlerDefinition {
+                        file_path: self.get_file_path(symbol.file_id)?,
+                        kind: symbol.kind,
+                        start_line: symbol.start_line as usize,
+                        end_line: symbol.end_line as usize,
+                        signature: symbol.signature,
+                    }),
+                    None => None,
+                };
+                Ok(RouteLocation {
+                    method: record.method,
+                    path: record.path,
+                    handler: record.handler,
+                    file_path: record.file_path,
+                    start_line: record.start_line as usize,
+                    end_line: record.end_line as usize,
+                    handler_definition,
+                    matched,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RoutesResponse {
+            method,
+            path: path.map(str::to_string),
+            total_count,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            routes,
+        })
+    }
+
+    /// Search results for the `route` filter: the handlers of the routes
+    /// matching it, or the lines registering the routes whose handler is not
+    /// indexed. Routes only matching the end of the path are left out when
+    /// some match the whole path.
+    pub(crate) fn search_routes(
+        &self,
+        route: &HttpRoute,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let matching = self.matching_routes(route.method.as_deref(), Some(&route.path))?;
+        let exact = matching
+            .iter()
+            .any(|(_, matched)| *matched == Some(RouteMatch::Exact));
+
+        let mut results = Vec::new();
+        for (record, matched) in matching {
+            if exact && matched != Some(RouteMatch::Exact) {
+                continue;
+            }
+            if !options.scope.accepts_path(&record.file_path) {
+                continue;
+            }
+            let score = match matched {
+                Some(RouteMatch::Exact) => EXACT_ROUTE_SCORE,
+                _ => SUFFIX_ROUTE_SCORE,
+            };
+            let handled = HttpRoute {
+                method: record.method.clone(),
+                path: record.path.clone(),
+            }
+            .to_string();
+
+            let result = match self.handler_definition(&record)? {
+                Some(symbol) => self.handler_result(symbol, &handled, score)?,
+                None => {
+                    let start = record.start_line as usize;
+                    let end = record.end_line as usize;
+                    let content = self
+                        .read_file_lines(&record.file_path, start, end)
+                        .unwrap_or_else(|e| {
+                            warn!("Failed to read route {}: {}", handled, e);
+                            handled.clone()
+                        });
+                    SearchResult::new(
+                        SearchResultKind::TextMatch,
+                        record.file_path,
+                        start,
+                        end,
+                        content,
+                        score,
+                    )
+                    .with_metadata(SearchResultMetadata {
+                        match_type: Some("route".to_string()),
+                        route: Some(handled),
+                        ..SearchResultMetadata::default()
+                    })
+                }
+            };
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Routes handling `method` and matching `path`, with how they match,
+    /// whole matches first
+    fn matching_routes(
+        &self,
+        method: Option<&str>,
+        path: Option<&str>,
+    ) -> Result<Vec<(RouteRecord, Option<RouteMatch>)>> {
+        let records = self.store.get_routes(method)?;
+        let Some(path) = path else {
+            return Ok(records.into_iter().map(|r| (r, None)).collect());
+        };
+
+        let mut matching: Vec<(RouteRecord, Option<RouteMatch>)> = records
+            .into_iter()
+            .filter_map(|record| {
+                let route = HttpRoute {
+                    method: record.method.clone(),
+                    path: record.path.clone(),
+                };
+                let matched = route.matches(method, path)?;
+                Some((record, Some(matched)))
+            })
+            .collect();
+        matching.sort_by_key(|(_, matched)| *matched);
+        Ok(matching)
+    }
+
+    /// Symbol defining the handler of a route: a function or method of its
+    /// name in the file registering the route, else anywhere in the
+    /// project. Qualified handlers (`h.ListItems`, `users::create`) are
+    /// looked up by their last segment.
+    fn handler_definition(&self, record: &RouteRecord) -> Result<Option<SymbolRecord>> {
+        let Some(ref handler) = record.handler else {
+            return Ok(None);
+        };
+        let name = handler
+            .rsplit(['.', ':', '@'])
+            .next()
+            .unwrap_or(handler)
+            .trim();
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        let mut symbols = self.store.find_symbol_by_name(name)?;
+        symbols.sort_by_key(|symbol| {
+            (
+                symbol.file_id != record.file_id,
+                !HANDLER_KINDS.contains(&symbol.kind.as_str()),
+            )
+        });
+        Ok(symbols.into_iter().next())
+    }
+
+    fn handler_result(
+        &self,
+        symbol: SymbolRecord,
+        route: &str,
+        score: f32,
+    ) -> Result<SearchResult> {
+        let file_path = self.get_file_path(symbol.file_id)?;
+        let span = super::search::symbol_span(&symbol);
+        let content = symbol
+            .signature
+            .clone()
+            .unwrap_or_else(|| symbol.name.clone());
+        Ok(SearchResult::new(
+            SearchResultKind::Symbol,
+            file_path,
+            symbol.start_line as usize,
+            symbol.end_line as usize,
+            content,
+            score,
+        )
+        .with_metadata(SearchResultMetadata {
+            title: Some(chunk_title(
+                &symbol.kind,
+                &symbol.name,
+                symbol.signature.as_deref(),
+                symbol.doc_comment.as_deref(),
+            )),
+            symbol_name: Some(symbol.name),
+            symbol_kind: Some(symbol.kind),
+            match_type: Some("route".to_string()),
+            route: Some(route.to_string()),
+            context: symbol.doc_comment,
+            signature: symbol.signature,
+            parent: symbol.parent,
+            qualifiers: Some(symbol.qualifiers),
+            cfg_context: symbol.cfg_context,
+            ..SearchResultMetadata::default()
+        })
+        .with_span(span))
+    }
+}
@@ -1,21 +1,112 @@
 //! Search functionality for RetrievalEngine.
 
 use super::RetrievalEngine;
-use crate::query::{Query, SearchOptions};
-use crate::results::{SearchResult, SearchResultKind, SearchResultMetadata, SearchResults};
-use crate::text_searcher::TextSearcher;
+use crate::cache::{CachedSearch, QueryCache};
+use crate::deadline::Deadline;
+use crate::query::{QualifiedName, Query, QueryShape, SearchOptions, SearchScope, SearchStrategy};
+use crate::results::{
+    EnclosingSymbol, FilteredMatches, NearMiss, ScoreBoost, ScoreExplanation, ScoreThresholds,
+    SearchDiagnostics, SearchResult, SearchResultKind, SearchResultMetadata, SearchResults,
+    SnippetBuilder, Span, TagFacet, TermMatch, dedup_results,
+};
+use crate::text_searcher::{TextMatch, TextSearcher};
 use anyhow::Result;
 use ignore::WalkBuilder;
-use semantiq_index::should_exclude_entry;
+use semantiq_index::{
+    ChunkRecord, SymbolField, SymbolRecord, chunk_title, normalize_path, read_source,
+    relative_path, resolve_path, should_exclude_entry,
+};
+use semantiq_parser::{SymbolQualifiers, TextMask};
 use std::fs;
-use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
 /// Maximum limit for search results to prevent excessive memory usage.
 const MAX_SEARCH_LIMIT: usize = 1000;
 
+/// Number of below-threshold matches reported for an empty search
+const MAX_NEAR_MISSES: usize = 3;
+
+/// Number of alternative queries suggested for an empty search
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Symbol names compared with each query term for spelling suggestions
+const MAX_SUGGESTION_CANDIDATES: usize = 2000;
+
+/// Tags reported for each file with results
+const TAGS_PER_FILE: usize = 5;
+
+/// Tag facets reported with search results
+const MAX_FACETS: usize = 10;
+
+/// Share of the files with a summary embedding from which semantic search
+/// goes through file summaries first
+const COARSE_SEARCH_COVERAGE: f64 = 0.9;
+
+/// Files whose chunks are ranked between two deadline checks of the coarse
+/// semantic search
+const COARSE_SEARCH_BATCH: usize = 25;
+
+/// Score of the most relevant chunk of a BM25 search, the others scoring in
+/// proportion of their relevance. BM25 relevance depends on the size of the
+/// index, so it is only meaningful relative to the other candidates.
+const BM25_TOP_SCORE: f32 = 0.8;
+
+/// Largest boost of a symbol whose doc comment holds every word of a
+/// natural-language query
+const DOCS_MATCH_BOOST: f32 = 0.4;
+
+/// Boost of a symbol in the module or parent of a qualified query such as
+/// `auth::login`
+const QUALIFIER_MATCH_BOOST: f32 = 1.2;
+
+/// Factor of a symbol named like a qualified query but outside of its
+/// module or parent, ranking it below the symbols in scope
+const QUALIFIER_MISMATCH_FACTOR: f32 = 0.7;
+
+/// Share of `words` found in a doc comment. Words are compared by their
+/// first letters, so that "uploads" is found in "upload" and "retried" in
+/// "retry".
+fn doc_coverage(doc: &str, words: &[String]) -> f32 {
+    let doc_words: Vec<String> = doc
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 1)
+        .map(str::to_lowercase)
+        .collect();
+    let found = words
+        .iter()
+        .filter(|word| doc_words.iter().any(|d| same_stem(d, word)))
+        .count();
+    found as f32 / words.len() as f32
+}
+
+/// Whether two lowercase words differ only by an inflection: equal, or
+/// sharing at least four first letters and all but the last three.
+fn same_stem(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    let shared = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    let longest = a.chars().count().max(b.chars().count());
+    shared >= 4 && shared + 3 >= longest
+}
+
+/// Whether the extension of a file passes the file type filter
+fn accepts_file_extension(path: &Path, options: &SearchOptions) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| options.accepts_extension(ext))
+}
+
 impl RetrievalEngine {
+    /// Start the time budget of `deadline` now, with the engine default
+    /// (`search.timeout_ms`) unless it has its own: searches given the
+    /// returned deadline share one budget however late they start.
+    pub fn start_deadline(&self, deadline: &Deadline) -> Deadline {
+        deadline.start(Duration::from_millis(self.search_config.timeout_ms))
+    }
+
     /// Perform a multi-strategy search combining semantic, symbol, and text search.
     pub fn search(
         &self,
@@ -25,7 +116,8 @@ impl RetrievalEngine {
     ) -> Result<SearchResults> {
         let start = Instant::now();
         let query = Query::new(query_text);
-        let opts = options.unwrap_or_default();
+        let mut opts = options.unwrap_or_default();
+        opts.deadline = self.start_deadline(&opts.deadline);
 
         // Cap limit to prevent excessive memory usage
         let safe_limit = limit.min(MAX_SEARCH_LIMIT);
@@ -38,21 +130,562 @@ impl RetrievalEngine {
             );
         }
 
+        // Serve repeated searches from the cache while the index is unchanged
+        let generation = self.store.index_generation()?;
+        let cache = self.query_cache.as_ref().map(|cache| {
+            (
+                cache,
+                QueryCache::key(query_text, safe_limit, &opts),
+                generation,
+            )
+        });
+        let hit = cache
+            .as_ref()
+            .and_then(|(cache, key, generation)| cache.get(&self.store, key, *generation));
+        let cached = hit.is_some();
+
+        let mut truncated = false;
+        let ranked = match hit {
+            Some(hit) => {
+                debug!(query = %query_text, "Query cache hit");
+                hit
+            }
+            None => {
+                let ranked = self.rank_results(&query, safe_limit, &opts)?;
+                // Partial results are not cached
+                truncated = opts.deadline.expired();
+                if let Some((cache, key, generation)) = &cache
+                    && !truncated
+                {
+                    cache.put(&self.store, key, *generation, ranked.clone());
+                }
+                ranked
+            }
+        };
+        if truncated {
+            warn!(
+                query = %query_text,
+                "Search stopped at its deadline, returning partial results"
+            );
+        }
+        let CachedSearch {
+            results: mut all_results,
+            strategy,
+        } = ranked;
+
+        // Flag results from files changed since indexing, drop deleted files
+        let mut stale_files = self.verify_freshness(&mut all_results);
+        self.expand_results(&mut all_results, &opts);
+        self.attach_snippets(&mut all_results, &query);
+
+        if self.base.is_some() && !opts.deadline.expired() {
+            match self.search_base(&query, safe_limit, &opts) {
+                Ok((base_results, base_stale)) => {
+                    all_results.extend(base_results);
+                    all_results.sort_by(|a, b| {
+                        b.score
+                            .partial_cmp(&a.score)
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    // Local text matches may cover base results
+                    all_results = dedup_results(all_results, opts.dedup);
+                    all_results.truncate(safe_limit);
+                    stale_files.extend(base_stale);
+                    stale_files.sort_unstable();
+                    stale_files.dedup();
+                }
+                Err(e) => warn!("Failed to search the base index: {}", e),
+            }
+        }
+
+        // Follow-up searches of a session favour the files it already surfaced
+        if let Some(ref session) = opts.session {
+            self.sessions.boost(session, &mut all_results);
+        }
+
+        let search_time = start.elapsed().as_millis() as u64;
+        info!(
+            query = %query_text,
+            results = all_results.len(),
+            time_ms = search_time,
+            "Search completed"
+        );
+        let mut results = SearchResults::new(query_text.to_string(), all_results, search_time);
+        results.stale_files = stale_files;
+        results.strategy = strategy;
+        results.cached = cached;
+        results.index_generation = generation;
+        results.truncated_by_timeout = truncated;
+        if let Err(e) = self.tag_results(&mut results, &query) {
+            warn!("Failed to load file tags: {}", e);
+        }
+        // Directories searched recently keep their embeddings under a size quota
+        if let Err(e) = self.store.record_searched_dirs(
+            results
+                .results
+                .iter()
+                .filter(|r| !r.from_base)
+                .map(|r| r.file_path.as_str()),
+        ) {
+            warn!("Failed to record searched directories: {}", e);
+        }
+        if let Some(ref session) = opts.session {
+            self.sessions.record(session, query_text, &results.results);
+        }
+
+        if results.is_empty() && !truncated {
+            match self.diagnose_empty_search(&query, safe_limit, &opts) {
+                Ok(diagnostics) => results.diagnostics = Some(diagnostics),
+                Err(e) => warn!("Failed to diagnose empty search: {}", e),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Attach the tags of the files with results, and the tags most of them
+    /// share as facets. Tags the query already filters on are not facets.
+    fn tag_results(&self, results: &mut SearchResults, query: &Query) -> Result<()> {
+        let mut paths: Vec<&str> = results
+            .results
+            .iter()
+            .map(|r| r.file_path.as_str())
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+
+        let file_tags = self.store.get_file_tags(&paths, TAGS_PER_FILE)?;
+        let mut counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        for tags in file_tags.values() {
+            for tag in tags {
+                if !query.filters.tags.contains(&tag.tag) {
+                    *counts.entry(tag.tag.as_str()).or_default() += 1;
+                }
+            }
+        }
+        let mut facets: Vec<TagFacet> = counts
+            .into_iter()
+            .map(|(tag, count)| TagFacet {
+                tag: tag.to_string(),
+                count,
+            })
+            .collect();
+        facets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        facets.truncate(MAX_FACETS);
+
+        results.facets = facets;
+        results.file_tags = file_tags
+            .into_iter()
+            .map(|(path, tags)| (path, tags.into_iter().map(|t| t.tag).collect()))
+            .collect();
+        Ok(())
+    }
+
+    /// Attach to each result its snippet around the query terms, headed by
+    /// the signature of the enclosing symbol when there is one
+    pub(crate) fn attach_snippets(&self, results: &mut [SearchResult], query: &Query) {
+        let builder = SnippetBuilder::new(self.search_config.snippet_tokens, query.all_terms());
+        // Expanded results were asked for in full
+        let full_builder = SnippetBuilder::new(0, query.all_terms());
+        let mut symbols: std::collections::HashMap<String, Vec<SymbolRecord>> =
+            std::collections::HashMap::new();
+        for result in results.iter_mut() {
+            if !symbols.contains_key(&result.file_path) {
+                let records = match self.store.get_file_by_path(&result.file_path) {
+                    Ok(Some(file)) => self.store.get_symbols_by_file(file.id),
+                    Ok(None) => Ok(Vec::new()),
+                    Err(e) => Err(e),
+                };
+                let records = records.unwrap_or_else(|e| {
+                    warn!("Failed to load symbols of {}: {}", result.file_path, e);
+                    Vec::new()
+                });
+                symbols.insert(result.file_path.clone(), records);
+            }
+            let enclosing: Vec<EnclosingSymbol<'_>> = symbols[&result.file_path]
+                .iter()
+                .filter_map(|s| {
+                    Some(EnclosingSymbol {
+                        start_line: s.start_line as usize,
+                        end_line: s.end_line as usize,
+                        signature: s.signature.as_deref()?,
+                    })
+                })
+                .collect();
+            let builder = match result.matched_lines {
+                Some(_) => &full_builder,
+                None => &builder,
+            };
+            result.snippet = Some(builder.build(&result.content, result.start_line, &enclosing));
+        }
+    }
+
+    /// Explain why a search found nothing: index and embedding coverage,
+    /// matches excluded by the filters, the closest matches below the score
+    /// thresholds, and the query with misspelled or partial terms replaced
+    /// by indexed symbol names.
+    fn diagnose_empty_search(
+        &self,
+        query: &Query,
+        limit: usize,
+        opts: &SearchOptions,
+    ) -> Result<SearchDiagnostics> {
+        let (embedded_chunks, total_chunks) = self.store.get_embedding_coverage()?;
+        // Only queries searched semantically load the embedding model
+        let uses_semantic = SearchStrategy::for_query(query).uses_semantic();
+        let model_available = uses_semantic && self.semantic_model().is_some();
+        let semantic_search = model_available && embedded_chunks > 0;
+        let mut diagnostics = SearchDiagnostics {
+            embedded_chunks,
+            total_chunks,
+            semantic_search,
+            ..Default::default()
+        };
+
+        if total_chunks == 0 {
+            diagnostics.hints.push(
+                "The index is empty: run `semantiq index` or wait for the initial indexing to finish"
+                    .to_string(),
+            );
+        } else if uses_semantic && !model_available {
+            diagnostics.hints.push(
+                "No embedding model is loaded, so chunks were ranked by keyword relevance (BM25) rather than meaning"
+                    .to_string(),
+            );
+        } else if embedded_chunks == 0 {
+            diagnostics.hints.push(
+                "No chunk has an embedding yet, so semantic search cannot match anything until indexing completes"
+                    .to_string(),
+            );
+        }
+
+        // Rerun the search without the filters to count what they excluded
+        if opts.file_types.is_some() || opts.symbol_kinds.is_some() {
+            let unfiltered = SearchOptions {
+                file_types: None,
+                symbol_kinds: None,
+                explain_scores: false,
+                ..opts.clone()
+            };
+            let results = self.rank_results(query, limit, &unfiltered)?.results;
+            diagnostics.filtered_out = filtered_matches(&results, opts);
+            for filtered in &diagnostics.filtered_out {
+                diagnostics.hints.push(match filtered.filter.as_str() {
+                    "file_type" => format!(
+                        "{} match(es) in .{} files were excluded by the file_type filter",
+                        filtered.count, filtered.value
+                    ),
+                    _ => format!(
+                        "{} {} symbol(s) were excluded by the symbol_kind filter",
+                        filtered.count, filtered.value
+                    ),
+                });
+            }
+        }
+
+        if !query.filters.tags.is_empty() {
+            diagnostics.hints.push(format!(
+                "Only files tagged {} were searched: remove `tags:` to search every file",
+                query.filters.tags.join(", ")
+            ));
+        }
+
+        if let Some(ref packages) = opts.packages {
+            let known = self.store.get_packages()?;
+            let unknown: Vec<&str> = packages
+                .iter()
+                .filter(|p| !known.iter().any(|k| k.name.eq_ignore_ascii_case(p)))
+                .map(String::as_str)
+                .collect();
+            if known.is_empty() {
+                diagnostics.hints.push(
+                    "No workspace package was found in the project: remove the package filter"
+                        .to_string(),
+                );
+            } else if !unknown.is_empty() {
+                let mut names: Vec<&str> = known.iter().map(|k| k.name.as_str()).collect();
+                names.sort_unstable();
+                names.dedup();
+                diagnostics.hints.push(format!(
+                    "Unknown package(s) {}: the workspace packages are {}",
+                    unknown.join(", "),
+                    names.join(", ")
+                ));
+            } else {
+                diagnostics.hints.push(format!(
+                    "Only files of package(s) {} were searched: remove the package filter to search every package",
+                    packages.join(", ")
+                ));
+            }
+        }
+
+        if let Some(ref qualifiers) = opts.qualifiers {
+            let unknown: Vec<&str> = qualifiers
+                .iter()
+                .filter(|q| !SymbolQualifiers::NAMES.contains(&q.to_lowercase().as_str()))
+                .map(String::as_str)
+                .collect();
+            diagnostics.hints.push(if unknown.is_empty() {
+                format!(
+                    "Only symbols that are {} were searched: remove the qualifiers filter to include other symbols and text matches",
+                    qualifiers.join(", ")
+                )
+            } else {
+                format!(
+                    "Unknown qualifier(s) {}: the qualifiers are {}",
+                    unknown.join(", "),
+                    SymbolQualifiers::NAMES.join(", ")
+                )
+            });
+        }
+
+        if let Some(ref route) = opts.route {
+            diagnostics.hints.push(format!(
+                "No indexed HTTP route matches {}: list the routes with semantiq_routes, or remove the route filter",
+                route
+            ));
+        }
+
+        diagnostics.near_misses = self.near_misses(query, opts)?;
+        if let Some(closest) = diagnostics.near_misses.first() {
+            diagnostics.hints.push(format!(
+                "The closest match ({}:{}, {}) scored {:.2}, below the threshold of {:.2}{}",
+                closest.file_path,
+                closest.start_line,
+                closest.strategy,
+                closest.score,
+                closest.threshold,
+                if closest.strategy == "semantic" {
+                    ""
+                } else {
+                    ": lower min_score to include it"
+                }
+            ));
+        }
+
+        diagnostics.suggestions = self.query_suggestions(query)?;
+        if total_chunks > 0 && diagnostics.hints.is_empty() && diagnostics.suggestions.is_empty() {
+            diagnostics
+                .hints
+                .push("No indexed code is close to any term of the query".to_string());
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Best matches rejected by the minimum score or the semantic thresholds
+    fn near_misses(&self, query: &Query, opts: &SearchOptions) -> Result<Vec<NearMiss>> {
+        let min_score = opts.effective_min_score();
+        let relaxed = SearchOptions {
+            min_score: Some(0.0),
+            explain_scores: false,
+            ..opts.clone()
+        };
+
+        let mut near_misses: Vec<NearMiss> = self
+            .rank_results(query, MAX_NEAR_MISSES, &relaxed)?
+            .results
+            .into_iter()
+            .filter(|r| r.score < min_score)
+            .map(|r| NearMiss {
+                strategy: match r.kind {
+                    SearchResultKind::SemanticMatch => "semantic",
+                    SearchResultKind::Symbol => "symbol",
+                    _ => "text",
+                }
+                .to_string(),
+                file_path: r.file_path,
+                start_line: r.start_line,
+                end_line: r.end_line,
+                score: r.score,
+                threshold: min_score,
+            })
+            .collect();
+
+        // Chunks the adaptive distance thresholds kept out of semantic search
+        if SearchStrategy::for_query(query).uses_semantic()
+            && let Some(model) = self.embedding_model()
+        {
+            let embedding = self.embed_query(model.as_ref(), &query.text)?;
+            let similar =
+                self.find_similar_chunks(&embedding, MAX_NEAR_MISSES * 4, &opts.deadline)?;
+            let language = self.detect_dominant_language(&similar);
+            let (max_distance, min_similarity) = self.get_thresholds(language.as_deref());
+            let threshold = min_similarity
+                .max(1.0 / (1.0 + max_distance))
+                .max(min_score);
+
+            let distances: std::collections::HashMap<i64, f32> = similar.iter().copied().collect();
+            let ids: Vec<i64> = similar.iter().map(|(id, _)| *id).collect();
+            for chunk in self.store.get_chunks_by_ids(&ids)? {
+                let score = 1.0 / (1.0 + distances[&chunk.id]);
+                if score >= threshold {
+                    continue;
+                }
+                let Some(file_path) = self.store.get_chunk_file_path(chunk.file_id)? else {
+                    continue;
+                };
+                let accepted = opts.scope.accepts_path(&file_path)
+                    && Path::new(&file_path)
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .is_none_or(|ext| opts.accepts_extension(ext))
+                    && (query.filters.tags.is_empty()
+                        || !self
+                            .store
+                            .files_with_tags(&[&file_path], &query.filters.tags)?
+                            .is_empty())
+                    && (opts.packages.is_none()
+                        || opts.accepts_package(
+                            self.store
+                                .get_file_packages(&[&file_path])?
+                                .get(&file_path)
+                                .map(String::as_str),
+                        ))
+                    && (opts.include_generated
+                        || self.store.get_generated_files(&[&file_path])?.is_empty());
+                if accepted {
+                    near_misses.push(NearMiss {
+                        file_path,
+                        start_line: chunk.start_line as usize,
+                        end_line: chunk.end_line as usize,
+                        strategy: "semantic".to_string(),
+                        score,
+                        threshold,
+                    });
+                }
+            }
+        }
+
+        near_misses.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut seen = std::collections::HashSet::new();
+        near_misses.retain(|m| seen.insert((m.file_path.clone(), m.start_line)));
+        near_misses.truncate(MAX_NEAR_MISSES);
+        Ok(near_misses)
+    }
+
+    /// The query with a misspelled or partial term replaced by a close
+    /// indexed symbol name, e.g. `confgi loader` -> `config loader`
+    fn query_suggestions(&self, query: &Query) -> Result<Vec<String>> {
+        let mut candidates: Vec<(usize, String)> = Vec::new();
+
+        for term in query.text.split_whitespace() {
+            let word = term.trim_matches(|c: char| !c.is_alphanumeric() && c != '_');
+            let length = word.chars().count();
+            if length < 3 {
+                continue;
+            }
+            let lower = word.to_lowercase();
+            // Typos rarely hit the first letters of longer words
+            let prefix: String = lower
+                .chars()
+                .take(if length >= 5 { 2 } else { 1 })
+                .collect();
+            let budget = if length <= 4 { 1 } else { 2 };
+
+            for name in self
+                .store
+                .symbol_names_with_prefix(&prefix, MAX_SUGGESTION_CANDIDATES)?
+            {
+                let name_lower = name.to_lowercase();
+                if name_lower == lower {
+                    continue;
+                }
+                let distance = edit_distance(&lower, &name_lower);
+                if distance <= budget || name_lower.starts_with(&lower) {
+                    candidates.push((distance, query.text.replacen(word, &name, 1)));
+                }
+            }
+        }
+
+        candidates.sort();
+        let mut suggestions: Vec<String> = Vec::new();
+        for (_, suggestion) in candidates {
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+        Ok(suggestions)
+    }
+
+    /// Run the search strategies selected for the query and rank their merged
+    /// results. Strategies not started by the deadline are skipped.
+    pub(crate) fn rank_results(
+        &self,
+        query: &Query,
+        safe_limit: usize,
+        opts: &SearchOptions,
+    ) -> Result<CachedSearch> {
+        // Weight (or skip) strategies according to the shape of the query
+        let strategy = SearchStrategy::for_query(query);
+        debug!(
+            shape = strategy.shape.as_str(),
+            "Search strategy: {}",
+            strategy.describe()
+        );
+
+        // The tag, package, qualifier and conditional filters drop results
+        // afterwards, so fetch more candidates
+        let tags = &query.filters.tags;
+        let fetch_limit = if tags.is_empty()
+            && opts.packages.is_none()
+            && opts.qualifiers.is_none()
+            && !opts.exclude_conditional
+        {
+            safe_limit
+        } else {
+            (safe_limit * 4).min(MAX_SEARCH_LIMIT)
+        };
+
         let mut all_results = Vec::new();
+        let deadline = &opts.deadline;
+
+        // 0. Handlers of the HTTP routes of the `route` filter, which
+        // replaces the other strategies, or of a route named by the query
+        let route_filter = opts.route.is_some();
+        if let Some(route) = opts.route.as_ref().or(query.route.as_ref()) {
+            all_results.extend(self.search_routes(route, opts)?);
+        }
 
         // 1. Semantic search (vector similarity) - highest priority
-        if self.embedding_model.is_some() {
-            let semantic_results = self.search_semantic(query_text, safe_limit, &opts)?;
+        // Without a real embedding model, chunks are ranked by keyword
+        // relevance (BM25)
+        if !route_filter && strategy.uses_semantic() && !deadline.expired() {
+            let mut semantic_results = if self.semantic_model().is_some() {
+                self.search_semantic(&query.text, fetch_limit, opts)?
+            } else {
+                self.search_bm25(query, fetch_limit, opts)?
+            };
+            apply_weight(&mut semantic_results, strategy.semantic_weight);
             all_results.extend(semantic_results);
         }
 
         // 2. Symbol search (FTS) - prioritize symbol matches
-        let symbol_results = self.search_symbols(&query, safe_limit, &opts)?;
-        all_results.extend(symbol_results);
+        if !route_filter && strategy.uses_symbols() && !deadline.expired() {
+            let mut symbol_results = self.search_symbols(query, fetch_limit, opts)?;
+            apply_weight(&mut symbol_results, strategy.symbol_weight);
+            all_results.extend(symbol_results);
+        }
 
-        // 3. Text search (grep-like) - only if we need more results
-        if all_results.len() < safe_limit {
-            let text_results = self.search_text(&query, safe_limit - all_results.len(), &opts)?;
+        // 3. Text search (grep-like) - only if we need more results.
+        // It reads the project tree, so it does not cover external dependencies,
+        // and is left to the local index when searching a base index.
+        if !route_filter
+            && strategy.uses_text()
+            && self.local_store.is_none()
+            && all_results.len() < fetch_limit
+            && opts.scope.includes_project()
+            && !deadline.expired()
+        {
+            let mut text_results =
+                self.search_text(query, fetch_limit - all_results.len(), opts)?;
+            apply_weight(&mut text_results, strategy.text_weight);
             all_results.extend(text_results);
         }
 
@@ -63,34 +696,146 @@ impl RetrievalEngine {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        // Remove duplicates based on file_path + start_line + end_line
-        // Using start_line + end_line is more reliable than content.len() which
-        // could collide for different content of the same length
-        let mut seen = std::collections::HashSet::new();
-        all_results.retain(|r| {
-            let key = format!("{}:{}:{}", r.file_path, r.start_line, r.end_line);
-            seen.insert(key)
-        });
+        // Remove results covering the same code, keeping the best-scored
+        // (or with `MergeOverlapping`, the richest) one
+        let mut all_results = dedup_results(all_results, opts.dedup);
 
         // Filter by minimum score
         let min_score = opts.effective_min_score();
         all_results.retain(|r| r.score >= min_score);
+        for result in &mut all_results {
+            if let Some(ref mut explanation) = result.metadata.explanation {
+                explanation.thresholds.min_score = min_score;
+            }
+        }
+
+        // Keep only files with every tag of the `tags:` filter
+        if !tags.is_empty() {
+            let mut paths: Vec<&str> = all_results.iter().map(|r| r.file_path.as_str()).collect();
+            paths.sort_unstable();
+            paths.dedup();
+            let tagged = self.store.files_with_tags(&paths, tags)?;
+            all_results.retain(|r| tagged.contains(&r.file_path));
+        }
+
+        // Keep only files of the packages of the `package` filter
+        if opts.packages.is_some() {
+            let mut paths: Vec<&str> = all_results.iter().map(|r| r.file_path.as_str()).collect();
+            paths.sort_unstable();
+            paths.dedup();
+            let packages = self.store.get_file_packages(&paths)?;
+            all_results
+                .retain(|r| opts.accepts_package(packages.get(&r.file_path).map(String::as_str)));
+        }
+
+        // Leave out generated files, or mark them when they are searched too,
+        // and mark the files of which only an outline is indexed
+        let mut paths: Vec<&str> = all_results.iter().map(|r| r.file_path.as_str()).collect();
+        paths.sort_unstable();
+        paths.dedup();
+        let generated = self.store.get_generated_files(&paths)?;
+        let outlines = self.store.get_outline_files(&paths)?;
+        if opts.include_generated {
+            for result in &mut all_results {
+                result.metadata.generated = generated.contains(&result.file_path);
+            }
+        } else if !generated.is_empty() {
+            all_results.retain(|r| !generated.contains(&r.file_path));
+        }
+        for result in &mut all_results {
+            result.metadata.outline = outlines.contains(&result.file_path);
+        }
+
+        // Keep only symbols with every qualifier of the `qualifiers` filter
+        if opts.qualifiers.is_some() {
+            all_results.retain(|r| opts.accepts_qualifiers(r.metadata.qualifiers.as_ref()));
+        }
+
+        // Drop symbols compiled only under some configuration
+        if opts.exclude_conditional {
+            all_results.retain(|r| r.metadata.cfg_context.is_none());
+        }
 
         // Limit results
         all_results.truncate(safe_limit);
 
-        let search_time = start.elapsed().as_millis() as u64;
-        info!(
-            query = %query_text,
-            results = all_results.len(),
-            time_ms = search_time,
-            "Search completed"
+        Ok(CachedSearch {
+            results: all_results,
+            strategy: Some(strategy),
+        })
+    }
+
+    /// Chunks closest to the query embedding. On large indexes whose files
+    /// have summary embeddings, only the chunks of the `coarse_files` files
+    /// with the closest summaries are compared with the query, a batch of
+    /// files at a time until the deadline.
+    fn find_similar_chunks(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        deadline: &Deadline,
+    ) -> Result<Vec<(i64, f32)>> {
+        if !self.use_coarse_search()? {
+            return self.store.search_similar_chunks(query_embedding, limit);
+        }
+
+        let files = self
+            .store
+            .search_similar_files(query_embedding, self.search_config.coarse_files)?;
+        let file_ids: Vec<i64> = files.into_iter().map(|(id, _)| id).collect();
+        debug!("Coarse search kept {} files", file_ids.len());
+
+        let mut similar = Vec::new();
+        for batch in file_ids.chunks(COARSE_SEARCH_BATCH) {
+            if deadline.expired() {
+                debug!("Coarse search stopped at the deadline");
+                break;
+            }
+            similar.extend(self.store.search_similar_chunks_in_files(
+                query_embedding,
+                batch,
+                limit,
+            )?);
+        }
+        similar.sort_by(|a, b| a.1.total_cmp(&b.1));
+        similar.truncate(limit);
+        Ok(similar)
+    }
+
+    /// Whether semantic searches go through file summaries first: the index
+    /// has at least `coarse_min_chunks` chunks and nearly all of its files
+    /// have a summary embedding. Decided again when the index changes.
+    pub(crate) fn use_coarse_search(&self) -> Result<bool> {
+        let min_chunks = self.search_config.coarse_min_chunks;
+        if min_chunks == 0 || self.search_config.coarse_files == 0 {
+            return Ok(false);
+        }
+
+        let generation = self.store.index_generation()?;
+        let mut decided = self
+            .coarse_search
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Coarse search lock poisoned: {}", e))?;
+        if let Some((at, coarse)) = *decided
+            && at == generation
+        {
+            return Ok(coarse);
+        }
+
+        let chunk_count = self.store.get_stats()?.chunk_count;
+        let (summarized, files) = self.store.get_file_summary_coverage()?;
+        let coarse = chunk_count >= min_chunks
+            && files > 0
+            && summarized as f64 >= files as f64 * COARSE_SEARCH_COVERAGE;
+        debug!(
+            "Coarse search {}: {} chunks, {}/{} files summarized",
+            if coarse { "enabled" } else { "disabled" },
+            chunk_count,
+            summarized,
+            files
         );
-        Ok(SearchResults::new(
-            query_text.to_string(),
-            all_results,
-            search_time,
-        ))
+        *decided = Some((generation, coarse));
+        Ok(coarse)
     }
 
     /// Perform semantic (vector similarity) search.
@@ -100,18 +845,25 @@ impl RetrievalEngine {
         limit: usize,
         options: &SearchOptions,
     ) -> Result<Vec<SearchResult>> {
-        let model = match &self.embedding_model {
+        let model = match self.embedding_model() {
             Some(m) => m,
             None => return Ok(Vec::new()),
         };
 
         // Generate query embedding
-        let query_embedding = model.embed(query_text)?;
+        let query_embedding = self.embed_query(model.as_ref(), query_text)?;
 
-        // Use sqlite-vec's efficient vector search
-        let similar_chunks = self
-            .store
-            .search_similar_chunks(&query_embedding, limit * 2)?;
+        // Use sqlite-vec's efficient vector search. Scope filtering happens
+        // afterwards, so fetch more candidates when part of the index is excluded.
+        let candidates = match options.scope {
+            SearchScope::All => limit * 2,
+            _ => limit * 4,
+        };
+        if options.deadline.expired() {
+            return Ok(Vec::new());
+        }
+        let similar_chunks =
+            self.find_similar_chunks(&query_embedding, candidates, &options.deadline)?;
 
         if similar_chunks.is_empty() {
             debug!("No similar chunks found via vector search");
@@ -154,6 +906,15 @@ impl RetrievalEngine {
         let chunk_ids: Vec<i64> = filtered_results.iter().map(|(id, _)| *id).collect();
         let chunks = self.store.get_chunks_by_ids(&chunk_ids)?;
 
+        // Symbols the chunks belong to, for their kind, signature and parent
+        let symbol_ids: Vec<i64> = chunks.iter().filter_map(|c| c.symbol_id).collect();
+        let owners: std::collections::HashMap<i64, SymbolRecord> = self
+            .store
+            .get_symbols_by_ids(&symbol_ids)?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect();
+
         // Create a map from chunk_id to distance for scoring
         let distance_map: std::collections::HashMap<i64, f32> =
             filtered_results.into_iter().collect();
@@ -163,13 +924,18 @@ impl RetrievalEngine {
             .into_iter()
             .filter_map(|chunk| {
                 let distance = *distance_map.get(&chunk.id)?;
-                let score = 1.0 / (1.0 + distance);
+                let similarity = 1.0 / (1.0 + distance);
 
-                if score < min_similarity {
+                if similarity < min_similarity {
                     return None;
                 }
+                // Duplicated and boilerplate chunks rank below unique code
+                let score = similarity * chunk.distinctiveness;
 
                 let file_path = self.store.get_chunk_file_path(chunk.file_id).ok()??;
+                if !options.scope.accepts_path(&file_path) {
+                    return None;
+                }
 
                 if let Some(ext) = Path::new(&file_path).extension().and_then(|e| e.to_str())
                     && !options.accepts_extension(ext)
@@ -177,6 +943,27 @@ impl RetrievalEngine {
                     return None;
                 }
 
+                let explanation = options.explain_scores.then(|| {
+                    let boosts = if chunk.distinctiveness < 1.0 {
+                        vec![ScoreBoost {
+                            name: "distinctiveness".to_string(),
+                            factor: chunk.distinctiveness,
+                        }]
+                    } else {
+                        Vec::new()
+                    };
+                    ScoreExplanation {
+                        distance: Some(distance),
+                        boosts,
+                        thresholds: ScoreThresholds {
+                            max_distance: Some(max_distance),
+                            min_similarity: Some(min_similarity),
+                            ..ScoreThresholds::default()
+                        },
+                        ..ScoreExplanation::new("semantic", similarity)
+                    }
+                });
+
                 Some(
                     SearchResult::new(
                         SearchResultKind::SemanticMatch,
@@ -186,12 +973,13 @@ impl RetrievalEngine {
                         chunk.content.clone(),
                         score,
                     )
-                    .with_metadata(SearchResultMetadata {
-                        symbol_name: chunk.symbols.first().cloned(),
-                        symbol_kind: None,
-                        match_type: Some("semantic".to_string()),
-                        context: None,
-                    }),
+                    .with_metadata(chunk_metadata(
+                        &chunk,
+                        chunk.symbol_id.and_then(|id| owners.get(&id)),
+                        "semantic",
+                        explanation,
+                    ))
+                    .with_span(chunk_span(&chunk)),
                 )
             })
             .take(limit)
@@ -204,6 +992,96 @@ impl RetrievalEngine {
         Ok(results)
     }
 
+    /// Rank chunks by keyword relevance with BM25, in place of
+    /// [`Self::search_semantic`] when no embedding model is available.
+    pub(crate) fn search_bm25(
+        &self,
+        query: &Query,
+        limit: usize,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        if options.deadline.expired() {
+            return Ok(Vec::new());
+        }
+
+        // Scope filtering happens afterwards, as for semantic search
+        let candidates = match options.scope {
+            SearchScope::All => limit * 2,
+            _ => limit * 4,
+        };
+        let ranked = self
+            .store
+            .search_chunks_bm25(&query.all_terms(), candidates)?;
+        if ranked.is_empty() {
+            return Ok(Vec::new());
+        }
+        debug!("BM25 search returned {} candidate chunks", ranked.len());
+
+        let top_relevance = ranked[0].1.max(f32::EPSILON);
+        let chunk_ids: Vec<i64> = ranked.iter().map(|(id, _)| *id).collect();
+        let relevance: std::collections::HashMap<i64, f32> = ranked.into_iter().collect();
+        let chunks = self.store.get_chunks_by_ids(&chunk_ids)?;
+
+        let symbol_ids: Vec<i64> = chunks.iter().filter_map(|c| c.symbol_id).collect();
+        let owners: std::collections::HashMap<i64, SymbolRecord> = self
+            .store
+            .get_symbols_by_ids(&symbol_ids)?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect();
+
+        let mut results: Vec<SearchResult> = chunks
+            .into_iter()
+            .filter_map(|chunk| {
+                let relevance = *relevance.get(&chunk.id)?;
+                let score = BM25_TOP_SCORE * (relevance / top_relevance).min(1.0);
+
+                let file_path = self.store.get_chunk_file_path(chunk.file_id).ok()??;
+                if !options.scope.accepts_path(&file_path) {
+                    return None;
+                }
+
+                if let Some(ext) = Path::new(&file_path).extension().and_then(|e| e.to_str())
+                    && !options.accepts_extension(ext)
+                {
+                    return None;
+                }
+
+                let explanation = options
+                    .explain_scores
+                    .then(|| ScoreExplanation::new("bm25", score));
+
+                Some(
+                    SearchResult::new(
+                        SearchResultKind::TextMatch,
+                        file_path,
+                        chunk.start_line as usize,
+                        chunk.end_line as usize,
+                        chunk.content.clone(),
+                        score,
+                    )
+                    .with_metadata(chunk_metadata(
+                        &chunk,
+                        chunk.symbol_id.and_then(|id| owners.get(&id)),
+                        "bm25",
+                        explanation,
+                    ))
+                    .with_span(chunk_span(&chunk)),
+                )
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(limit);
+
+        debug!("Found {} BM25 matches after filtering", results.len());
+        Ok(results)
+    }
+
     /// Collect distance observations for ML calibration.
     pub(crate) fn collect_distance_observations(&self, query: &str, results: &[(i64, f32)]) {
         let collector = match &self.distance_collector {
@@ -251,6 +1129,11 @@ impl RetrievalEngine {
     }
 
     /// Search symbols using FTS5 full-text search.
+    ///
+    /// Natural-language queries also search the doc comments for their
+    /// words, and symbols whose documentation holds more of them rank higher.
+    /// Qualified queries such as `auth::login` or `UserService.create` rank
+    /// the symbols in that module or parent above the others of that name.
     pub(crate) fn search_symbols(
         &self,
         query: &Query,
@@ -258,209 +1141,341 @@ impl RetrievalEngine {
         options: &SearchOptions,
     ) -> Result<Vec<SearchResult>> {
         let mut results = Vec::new();
+        let doc_words = if query.shape == QueryShape::NaturalLanguage {
+            query.content_words()
+        } else {
+            Vec::new()
+        };
 
         for term in query.all_terms() {
-            let symbols = self.store.search_symbols(term, limit)?;
+            if options.deadline.expired() {
+                break;
+            }
+            // A qualified name is scored by its bare name
+            let name_term = match query.qualified {
+                Some(ref qualified) if term == query.text => qualified.name.as_str(),
+                _ => term,
+            };
+            for found in self.store.search_symbols(term, limit)? {
+                results.extend(self.symbol_result(
+                    found.symbol,
+                    found.matched_in,
+                    name_term,
+                    &doc_words,
+                    query.qualified.as_ref(),
+                    options,
+                )?);
+            }
+        }
 
-            for symbol in symbols {
-                if !options.accepts_symbol_kind(&symbol.kind) {
-                    continue;
-                }
+        if !doc_words.is_empty() && !options.deadline.expired() {
+            let words: Vec<&str> = doc_words.iter().map(String::as_str).collect();
+            for symbol in self.store.search_symbol_docs(&words, limit)? {
+                results.extend(self.symbol_result(
+                    symbol,
+                    SymbolField::Docs,
+                    &query.text,
+                    &doc_words,
+                    None,
+                    options,
+                )?);
+            }
+        }
 
-                let file_path = self.get_file_path(symbol.file_id)?;
+        Ok(results)
+    }
 
-                if let Some(ext) = Path::new(&file_path).extension().and_then(|e| e.to_str())
-                    && !options.accepts_extension(ext)
-                {
-                    continue;
-                }
+    /// Score a symbol matched by `term`, or `None` when the options filter
+    /// it out. Matches in the documentation are boosted by the share of
+    /// `doc_words` it holds, and symbols by whether they are in the scope of
+    /// a `qualified` name.
+    fn symbol_result(
+        &self,
+        symbol: SymbolRecord,
+        matched_in: SymbolField,
+        term: &str,
+        doc_words: &[String],
+        qualified: Option<&QualifiedName>,
+        options: &SearchOptions,
+    ) -> Result<Option<SearchResult>> {
+        if !options.accepts_symbol_kind(&symbol.kind) {
+            return Ok(None);
+        }
 
-                let content = symbol
-                    .signature
-                    .clone()
-                    .unwrap_or_else(|| symbol.name.clone());
-
-                // Improved scoring algorithm
-                let name_lower = symbol.name.to_lowercase();
-                let term_lower = term.to_lowercase();
-
-                let mut score = if name_lower == term_lower {
-                    1.0
-                } else if name_lower.starts_with(&term_lower) {
-                    0.85
-                } else if name_lower.contains(&term_lower) {
-                    0.7
-                } else {
-                    0.5
-                };
+        let file_path = self.get_file_path(symbol.file_id)?;
+        if !options.scope.accepts_path(&file_path) {
+            return Ok(None);
+        }
 
-                // Boost score based on symbol kind
-                let kind_boost = match symbol.kind.as_str() {
-                    "function" | "method" => 1.15,
-                    "class" | "struct" | "trait" | "interface" => 1.1,
-                    "enum" | "type" => 1.05,
-                    "module" => 1.0,
-                    "constant" => 0.95,
-                    "variable" => 0.9,
-                    _ => 1.0,
-                };
-                score *= kind_boost;
+        if let Some(ext) = Path::new(&file_path).extension().and_then(|e| e.to_str())
+            && !options.accepts_extension(ext)
+        {
+            return Ok(None);
+        }
 
-                // Slight boost for shorter names
-                let length_factor = 1.0 + (1.0 / (symbol.name.len() as f32 + 5.0));
-                score *= length_factor;
+        let content = symbol
+            .signature
+            .clone()
+            .unwrap_or_else(|| symbol.name.clone());
+
+        // Improved scoring algorithm
+        let name_lower = symbol.name.to_lowercase();
+        let term_lower = term.to_lowercase();
+
+        let (base_score, match_kind) = if name_lower == term_lower {
+            (1.0, "exact")
+        } else if name_lower.starts_with(&term_lower) {
+            (0.85, "prefix")
+        } else if name_lower.contains(&term_lower) {
+            (0.7, "contains")
+        } else if matched_in == SymbolField::Docs {
+            (0.5, "docs")
+        } else {
+            (0.5, "fts")
+        };
+        let mut score = base_score;
+
+        // Boost score based on symbol kind
+        let kind_boost = match symbol.kind.as_str() {
+            "function" | "method" => 1.15,
+            "class" | "struct" | "trait" | "interface" | "table" => 1.1,
+            "enum" | "type" => 1.05,
+            "module" => 1.0,
+            "constant" | "column" | "index" => 0.95,
+            "variable" => 0.9,
+            _ => 1.0,
+        };
+        score *= kind_boost;
 
-                score = score.min(1.0);
+        // Slight boost for shorter names
+        let length_factor = 1.0 + (1.0 / (symbol.name.len() as f32 + 5.0));
+        score *= length_factor;
 
-                results.push(
-                    SearchResult::new(
-                        SearchResultKind::Symbol,
-                        file_path,
-                        symbol.start_line as usize,
-                        symbol.end_line as usize,
-                        content,
-                        score,
-                    )
-                    .with_metadata(SearchResultMetadata {
-                        symbol_name: Some(symbol.name),
-                        symbol_kind: Some(symbol.kind.clone()),
-                        match_type: Some("symbol".to_string()),
-                        context: symbol.doc_comment,
-                    }),
-                );
+        // Documentation answering a natural-language query
+        let docs_boost = match (matched_in, symbol.doc_comment.as_deref()) {
+            (SymbolField::Docs, Some(doc)) if !doc_words.is_empty() => {
+                1.0 + DOCS_MATCH_BOOST * doc_coverage(doc, doc_words)
             }
-        }
+            _ => 1.0,
+        };
+        score *= docs_boost;
 
-        Ok(results)
+        // Module or parent of a qualified name
+        let qualifier_factor = match qualified {
+            Some(qualified) if qualified.matches_scope(symbol.parent.as_deref(), &file_path) => {
+                QUALIFIER_MATCH_BOOST
+            }
+            Some(_) => QUALIFIER_MISMATCH_FACTOR,
+            None => 1.0,
+        };
+        score *= qualifier_factor;
+
+        let capped = score > 1.0;
+        score = score.min(1.0);
+
+        let explanation = options.explain_scores.then(|| {
+            let start = name_lower.find(&term_lower);
+            let mut boosts = vec![
+                ScoreBoost {
+                    name: format!("kind:{}", symbol.kind),
+                    factor: kind_boost,
+                },
+                ScoreBoost {
+                    name: "length".to_string(),
+                    factor: length_factor,
+                },
+            ];
+            if docs_boost > 1.0 {
+                boosts.push(ScoreBoost {
+                    name: "docs".to_string(),
+                    factor: docs_boost,
+                });
+            }
+            if qualified.is_some() {
+                boosts.push(ScoreBoost {
+                    name: "qualifier".to_string(),
+                    factor: qualifier_factor,
+                });
+            }
+            ScoreExplanation {
+                term_matches: vec![TermMatch {
+                    term: term.to_string(),
+                    match_kind: Some(match_kind.to_string()),
+                    start,
+                    end: start.map(|s| s + term_lower.len()),
+                }],
+                boosts,
+                capped,
+                ..ScoreExplanation::new("symbol", base_score)
+            }
+        });
+
+        let span = symbol_span(&symbol);
+        Ok(Some(
+            SearchResult::new(
+                SearchResultKind::Symbol,
+                file_path,
+                symbol.start_line as usize,
+                symbol.end_line as usize,
+                content,
+                score,
+            )
+            .with_metadata(SearchResultMetadata {
+                title: Some(chunk_title(
+                    &symbol.kind,
+                    &symbol.name,
+                    symbol.signature.as_deref(),
+                    symbol.doc_comment.as_deref(),
+                )),
+                symbol_name: Some(symbol.name),
+                symbol_kind: Some(symbol.kind.clone()),
+                match_type: Some("symbol".to_string()),
+                match_in: Some(matched_in),
+                context: symbol.doc_comment,
+                signature: symbol.signature,
+                parent: symbol.parent,
+                qualifiers: Some(symbol.qualifiers),
+                cfg_context: symbol.cfg_context,
+                explanation,
+                ..SearchResultMetadata::default()
+            })
+            .with_span(span),
+        ))
     }
 
     /// Search text content using grep-like matching.
     ///
-    /// Uses a cached file list (with TTL) to avoid re-walking the directory
-    /// tree on every call within the same session.
+    /// Searches the file content stored in the index when there is some (see
+    /// [`Self::serves_stored_contents`]), otherwise the project files, walked
+    /// and searched in parallel until `limit` matches are found. Matches in
+    /// comments or strings are left out as `options` asks, using the ranges
+    /// recorded when the file was indexed: files changed since keep all
+    /// their matches.
     pub(crate) fn search_text(
         &self,
         query: &Query,
         limit: usize,
         options: &SearchOptions,
     ) -> Result<Vec<SearchResult>> {
-        let mut results = Vec::new();
         let root = Path::new(&self.root_path);
+        let searcher = TextSearcher::new(true);
+        let terms = query.all_terms();
+        let stored = self.serves_stored_contents();
+        // Over-fetch to leave room for masked matches
+        let fetch_limit = if options.masks_text() {
+            limit.saturating_mul(4)
+        } else {
+            limit
+        };
 
-        if !root.exists() {
-            return Ok(results);
-        }
-
-        let file_paths = self.get_cached_file_list(root)?;
-
-        for path in &file_paths {
-            if results.len() >= limit {
-                break;
+        let mut files: Vec<(String, Vec<(String, TextMatch)>)> = if stored {
+            let mut files = Vec::new();
+            let mut found = 0;
+            for rel_path in self.store.get_file_content_paths()? {
+                if found >= fetch_limit || options.deadline.expired() {
+                    break;
+                }
+                if !accepts_file_extension(Path::new(&rel_path), options) {
+                    continue;
+                }
+                if let Some(content) = self.store.get_file_content(&rel_path).ok().flatten() {
+                    let matches = searcher.search_terms(&content, &terms);
+                    found += matches.len();
+                    files.push((rel_path, matches));
+                }
             }
+            files
+        } else if root.exists() {
+            let filter_options = options.clone();
+            let walker = WalkBuilder::new(root)
+                .hidden(true)
+                .git_ignore(true)
+                .filter_entry(move |entry| {
+                    let name = entry.file_name().to_string_lossy();
+                    if should_exclude_entry(&name) {
+                        return false;
+                    }
+                    entry.file_type().is_some_and(|t| t.is_dir())
+                        || accepts_file_extension(entry.path(), &filter_options)
+                })
+                .build_parallel();
+            searcher
+                .search_walk(walker, &terms, fetch_limit, &options.deadline)?
+                .into_iter()
+                .filter_map(|file| Some((relative_path(root, &file.path)?, file.matches)))
+                .collect()
+        } else {
+            return Ok(Vec::new());
+        };
 
-            let accepted = path
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| options.accepts_extension(ext))
-                .unwrap_or(false);
-
-            if !accepted {
-                continue;
+        if options.masks_text() {
+            for (rel_path, matches) in &mut files {
+                let Some(mask) = self.indexed_text_mask(rel_path, stored) else {
+                    continue;
+                };
+                matches.retain(|(term, text_match)| {
+                    has_unmasked_occurrence(text_match, term, &mask, options)
+                });
             }
+        }
 
-            if let Ok(content) = fs::read_to_string(path) {
-                let matches = self.find_text_matches(&content, query);
-
-                for (line_num, line_content, score) in matches {
-                    let rel_path = path
-                        .strip_prefix(root)
-                        .unwrap_or(path)
-                        .to_string_lossy()
-                        .to_string();
-
-                    results.push(SearchResult::new(
-                        SearchResultKind::TextMatch,
-                        rel_path,
-                        line_num,
-                        line_num,
-                        line_content,
-                        score,
-                    ));
-
-                    if results.len() >= limit {
-                        break;
-                    }
+        let mut results = Vec::new();
+        for (rel_path, matches) in files {
+            for (term, text_match) in matches {
+                if results.len() >= limit {
+                    return Ok(results);
                 }
+                let span = text_match_span(&text_match);
+                let mut result = SearchResult::new(
+                    SearchResultKind::TextMatch,
+                    rel_path.clone(),
+                    text_match.line_number,
+                    text_match.line_number,
+                    text_match.line_content,
+                    text_match.score,
+                )
+                .with_span(span);
+                if options.explain_scores {
+                    result.metadata.explanation = Some(ScoreExplanation {
+                        term_matches: vec![TermMatch {
+                            term,
+                            match_kind: None,
+                            start: Some(text_match.match_start),
+                            end: Some(text_match.match_end),
+                        }],
+                        ..ScoreExplanation::new("text", text_match.score)
+                    });
+                }
+                results.push(result);
             }
         }
 
         Ok(results)
     }
 
-    /// Get the cached file list, rebuilding it if the cache has expired.
-    fn get_cached_file_list(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        use super::{FILE_LIST_CACHE_TTL_SECS, FileListCache};
-        use std::time::Duration;
-
-        let mut cache = self
-            .file_list_cache
-            .lock()
-            .map_err(|e| anyhow::anyhow!("File list cache lock poisoned: {}", e))?;
-
-        if let Some(ref cached) = *cache
-            && cached.created_at.elapsed() < Duration::from_secs(FILE_LIST_CACHE_TTL_SECS)
-        {
-            return Ok(cached.paths.clone());
-        }
-
-        // Rebuild the file list
-        let walker = WalkBuilder::new(root)
-            .hidden(true)
-            .git_ignore(true)
-            .filter_entry(|entry| {
-                let name = entry.file_name().to_string_lossy();
-                !should_exclude_entry(&name)
-            })
-            .build();
-
-        let paths: Vec<PathBuf> = walker
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_file())
-            .map(|e| e.into_path())
-            .collect();
-
-        *cache = Some(FileListCache {
-            paths: paths.clone(),
-            created_at: std::time::Instant::now(),
-        });
-
-        Ok(paths)
-    }
-
-    /// Find text matches in content.
-    pub(crate) fn find_text_matches(
-        &self,
-        content: &str,
-        query: &Query,
-    ) -> Vec<(usize, String, f32)> {
-        let searcher = TextSearcher::new(true);
-        let terms = query.all_terms();
-        let mut matches = Vec::new();
-        let mut seen_lines = std::collections::HashSet::new();
-
-        for term in &terms {
-            if let Ok(results) = searcher.search(content, term) {
-                for result in results {
-                    if seen_lines.insert(result.line_number) {
-                        matches.push((result.line_number, result.line_content, result.score));
-                    }
-                }
+    /// Comment and string ranges of a file, `None` when it has none or when
+    /// the project file searched (`stored` false) differs from the indexed
+    /// content the ranges are offsets in
+    fn indexed_text_mask(&self, rel_path: &str, stored: bool) -> Option<TextMask> {
+        if !stored {
+            let content = fs::read_to_string(Path::new(&self.root_path).join(rel_path)).ok()?;
+            if self.store.needs_reindex(rel_path, &content).unwrap_or(true) {
+                return None;
             }
         }
+        self.store.get_text_mask(rel_path).unwrap_or_else(|e| {
+            warn!("Failed to read comment and string ranges: {}", e);
+            None
+        })
+    }
 
-        matches.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
-
-        matches
+    /// Whether text searches and line reads use the file content stored in
+    /// the index (`storage.file_contents`) rather than the project files
+    pub(crate) fn serves_stored_contents(&self) -> bool {
+        self.store.has_file_contents().unwrap_or_else(|e| {
+            warn!("Failed to check for stored file contents: {}", e);
+            false
+        })
     }
 
     /// Get file path from file ID.
@@ -470,7 +1485,8 @@ impl RetrievalEngine {
             .ok_or_else(|| anyhow::anyhow!("File not found with id: {}", file_id))
     }
 
-    /// Read specific lines from a file.
+    /// Read specific lines from a file, from its content stored in the index
+    /// if there is one, which matches the indexed line numbers.
     ///
     /// Validates that the resolved path stays within `root_path` to prevent
     /// path traversal attacks via `..` sequences.
@@ -480,8 +1496,33 @@ impl RetrievalEngine {
         start: usize,
         end: usize,
     ) -> Result<String> {
+        let content = self.read_indexed_file(file_path)?;
+        let lines: Vec<&str> = content.lines().collect();
+
+        let start_idx = start.saturating_sub(1).min(lines.len());
+        let end_idx = end.min(lines.len());
+
+        if start_idx >= end_idx {
+            return Ok(String::new());
+        }
+
+        Ok(lines[start_idx..end_idx].join("\n"))
+    }
+
+    /// Read a file, from its content stored in the index if there is one,
+    /// otherwise from disk (see [`Self::read_file_lines`])
+    pub(crate) fn read_indexed_file(&self, file_path: &str) -> Result<String> {
+        let file_path = &normalize_path(file_path);
+        match self.store.get_file_content(file_path)? {
+            Some(content) => Ok(content),
+            None => self.read_project_file(file_path),
+        }
+    }
+
+    /// Read a file of the project from disk.
+    fn read_project_file(&self, file_path: &str) -> Result<String> {
         let root = Path::new(&self.root_path);
-        let full_path = root.join(file_path);
+        let full_path = resolve_path(root, file_path);
 
         // Canonicalize to resolve symlinks and .. components, then verify
         // the resolved path is still within the project root.
@@ -496,16 +1537,152 @@ impl RetrievalEngine {
             ));
         }
 
-        let content = fs::read_to_string(&canonical_path)?;
-        let lines: Vec<&str> = content.lines().collect();
+        Ok(read_source(&canonical_path)?.content)
+    }
+}
 
-        let start_idx = start.saturating_sub(1).min(lines.len());
-        let end_idx = end.min(lines.len());
+/// Span of a symbol, from the definition's first to last byte
+pub(crate) fn symbol_span(symbol: &SymbolRecord) -> Span {
+    Span {
+        start_byte: symbol.start_byte.max(0) as usize,
+        end_byte: symbol.end_byte.max(0) as usize,
+        start_column: symbol.start_column.max(0) as usize,
+        end_column: symbol.end_column.max(0) as usize,
+    }
+}
 
-        if start_idx >= end_idx {
-            return Ok(String::new());
+/// Span of a chunk, which holds whole lines: it starts at the first column
+/// and ends after its last line
+fn chunk_span(chunk: &ChunkRecord) -> Span {
+    let last_line_start = chunk.content.rfind('\n').map_or(0, |i| i + 1);
+    Span {
+        start_byte: chunk.start_byte.max(0) as usize,
+        end_byte: chunk.end_byte.max(0) as usize,
+        start_column: 0,
+        end_column: chunk.content.len() - last_line_start,
+    }
+}
+
+/// Span of a text match, on a single line
+fn text_match_span(text_match: &TextMatch) -> Span {
+    let len = text_match.match_end - text_match.match_start;
+    Span {
+        start_byte: text_match.start_byte,
+        end_byte: text_match.start_byte + len,
+        start_column: text_match.column,
+        end_column: text_match.column + len,
+    }
+}
+
+/// Whether the line of a text match has an occurrence of its term outside
+/// the comments and strings left out by `options`
+fn has_unmasked_occurrence(
+    text_match: &TextMatch,
+    term: &str,
+    mask: &TextMask,
+    options: &SearchOptions,
+) -> bool {
+    let line_start = text_match.start_byte - text_match.match_start;
+    text_match
+        .line_content
+        .to_lowercase()
+        .match_indices(&term.to_lowercase())
+        .any(|(index, _)| {
+            let offset = line_start + index;
+            !(options.exclude_comments && mask.in_comment(offset)
+                || options.exclude_strings && mask.in_string(offset))
+        })
+}
+
+/// Metadata of a chunk result: the symbol the chunk belongs to, if any,
+/// otherwise the first symbol it mentions
+fn chunk_metadata(
+    chunk: &ChunkRecord,
+    owner: Option<&SymbolRecord>,
+    match_type: &str,
+    explanation: Option<ScoreExplanation>,
+) -> SearchResultMetadata {
+    match owner {
+        Some(owner) => SearchResultMetadata {
+            symbol_name: Some(owner.name.clone()),
+            symbol_kind: Some(owner.kind.clone()),
+            match_type: Some(match_type.to_string()),
+            context: owner.doc_comment.clone(),
+            title: chunk.title.clone(),
+            signature: owner.signature.clone(),
+            parent: owner.parent.clone(),
+            qualifiers: Some(owner.qualifiers),
+            cfg_context: owner.cfg_context.clone(),
+            explanation,
+            ..SearchResultMetadata::default()
+        },
+        None => SearchResultMetadata {
+            symbol_name: chunk.symbols.first().cloned(),
+            title: chunk.title.clone(),
+            match_type: Some(match_type.to_string()),
+            explanation,
+            ..SearchResultMetadata::default()
+        },
+    }
+}
+
+/// Count the results rejected by the `file_type` or `symbol_kind` filter of
+/// `opts`, per extension and symbol kind, most frequent first
+fn filtered_matches(results: &[SearchResult], opts: &SearchOptions) -> Vec<FilteredMatches> {
+    let mut counts: std::collections::BTreeMap<(&str, String), usize> =
+        std::collections::BTreeMap::new();
+    for result in results {
+        if let Some(ext) = Path::new(&result.file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            && !opts.accepts_extension(ext)
+        {
+            *counts.entry(("file_type", ext.to_lowercase())).or_default() += 1;
+        } else if let Some(ref kind) = result.metadata.symbol_kind
+            && !opts.accepts_symbol_kind(kind)
+        {
+            *counts.entry(("symbol_kind", kind.clone())).or_default() += 1;
         }
+    }
 
-        Ok(lines[start_idx..end_idx].join("\n"))
+    let mut filtered: Vec<FilteredMatches> = counts
+        .into_iter()
+        .map(|((filter, value), count)| FilteredMatches {
+            filter: filter.to_string(),
+            value,
+            count,
+        })
+        .collect();
+    filtered.sort_by_key(|f| std::cmp::Reverse(f.count));
+    filtered
+}
+
+/// Levenshtein distance between two strings, in characters
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Scale the scores of a strategy's results by its weight for the current query
+fn apply_weight(results: &mut [SearchResult], weight: f32) {
+    if weight != 1.0 {
+        for result in results {
+            result.score *= weight;
+            if let Some(ref mut explanation) = result.metadata.explanation {
+                explanation.strategy_weight = weight;
+            }
+        }
     }
 }
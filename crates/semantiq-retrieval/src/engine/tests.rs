@@ -71,6 +71,11 @@ fn test_dependency_info_struct() {
         target_path: "src/utils.rs".to_string(),
         import_name: Some("utils".to_string()),
         kind: "local".to_string(),
+        symbols: Vec::new(),
+        package: None,
+        scope: None,
+        resolved_path: None,
+        via: None,
     };
 
     assert_eq!(dep.target_path, "src/utils.rs");
@@ -87,6 +92,8 @@ fn test_symbol_definition_struct() {
         end_line: 20,
         signature: Some("fn process_data()".to_string()),
         doc_comment: Some("/// Process data".to_string()),
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
     };
 
     assert_eq!(def.file_path, "src/lib.rs");
@@ -102,7 +109,9 @@ fn test_symbol_explanation_not_found() {
         found: false,
         definitions: Vec::new(),
         usage_count: 0,
+        usage_files: 0,
         related_symbols: Vec::new(),
+        as_of: None,
     };
 
     assert!(!explanation.found);
@@ -122,9 +131,13 @@ fn test_symbol_explanation_found() {
             end_line: 20,
             signature: Some("fn process_data()".to_string()),
             doc_comment: None,
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
         }],
         usage_count: 5,
+        usage_files: 2,
         related_symbols: vec!["helper".to_string(), "utils".to_string()],
+        as_of: None,
     };
 
     assert!(explanation.found);
@@ -132,3 +145,2451 @@ fn test_symbol_explanation_found() {
     assert_eq!(explanation.usage_count, 5);
     assert_eq!(explanation.related_symbols.len(), 2);
 }
+
+// ==================== Freshness tests ====================
+
+use crate::results::{Freshness, LineRange, SearchResult, SearchResultKind};
+use freshness::{FileFreshness, slice_lines};
+use semantiq_index::FileRecord;
+
+/// Create an engine over a temporary project with `src/lib.rs` indexed.
+fn engine_with_indexed_file(content: &str) -> (RetrievalEngine, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), content).unwrap();
+
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    store
+        .insert_file("src/lib.rs", Some("rust"), content, content.len() as i64, 0)
+        .unwrap();
+
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+    (engine, dir)
+}
+
+fn file_record(engine: &RetrievalEngine) -> Option<FileRecord> {
+    engine.store.get_file_by_path("src/lib.rs").unwrap()
+}
+
+fn symbol_result(file_path: &str, start_line: usize, end_line: usize) -> SearchResult {
+    SearchResult::new(
+        SearchResultKind::Symbol,
+        file_path.to_string(),
+        start_line,
+        end_line,
+        "fn old() {}".to_string(),
+        0.9,
+    )
+}
+
+#[test]
+fn test_slice_lines() {
+    let content = "a\nb\nc\nd";
+    assert_eq!(slice_lines(content, 2, 3), Some("b\nc".to_string()));
+    assert_eq!(slice_lines(content, 3, 10), Some("c\nd".to_string()));
+    assert_eq!(slice_lines(content, 5, 6), None);
+}
+
+#[test]
+fn test_unchanged_file_is_fresh() {
+    let (engine, _dir) = engine_with_indexed_file("fn old() {}\n");
+
+    assert_eq!(
+        engine.check_file_freshness("src/lib.rs", file_record(&engine).as_ref()),
+        FileFreshness::Fresh
+    );
+
+    let mut results = vec![symbol_result("src/lib.rs", 1, 1)];
+    let stale_files = engine.verify_freshness(&mut results);
+
+    assert!(stale_files.is_empty());
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].stale);
+
+    let record = file_record(&engine).unwrap();
+    let metadata = &results[0].metadata;
+    assert_eq!(metadata.freshness, Some(Freshness::Fresh));
+    assert_eq!(metadata.indexed_at, Some(record.indexed_at));
+    assert_eq!(metadata.content_hash, Some(record.hash));
+}
+
+#[test]
+fn test_modified_file_marks_results_stale() {
+    let (engine, dir) = engine_with_indexed_file("fn old() {}\n");
+    std::fs::write(
+        dir.path().join("src/lib.rs"),
+        "fn new_name() {}\nfn other() {}\n",
+    )
+    .unwrap();
+
+    let mut results = vec![symbol_result("src/lib.rs", 1, 1)];
+    let stale_files = engine.verify_freshness(&mut results);
+
+    assert_eq!(stale_files, vec!["src/lib.rs".to_string()]);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].stale);
+    assert_eq!(results[0].content, "fn new_name() {}");
+    assert_eq!(results[0].metadata.freshness, Some(Freshness::Stale));
+    assert!(results[0].metadata.content_hash.is_some());
+}
+
+#[test]
+fn test_unindexed_file_freshness_is_unknown() {
+    let (engine, dir) = engine_with_indexed_file("fn old() {}\n");
+    std::fs::write(dir.path().join("src/new.rs"), "fn new() {}\n").unwrap();
+
+    let mut results = vec![symbol_result("src/new.rs", 1, 1)];
+    engine.verify_freshness(&mut results);
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].metadata.freshness, Some(Freshness::Unknown));
+    assert!(results[0].metadata.indexed_at.is_none());
+    assert!(results[0].metadata.content_hash.is_none());
+}
+
+#[test]
+fn test_deleted_file_results_are_dropped() {
+    let (engine, dir) = engine_with_indexed_file("fn old() {}\n");
+    std::fs::remove_file(dir.path().join("src/lib.rs")).unwrap();
+
+    let mut results = vec![symbol_result("src/lib.rs", 1, 1)];
+    let stale_files = engine.verify_freshness(&mut results);
+
+    assert!(results.is_empty());
+    assert_eq!(stale_files, vec!["src/lib.rs".to_string()]);
+}
+
+#[test]
+fn test_text_matches_skip_freshness_check() {
+    let (engine, _dir) = engine_with_indexed_file("fn old() {}\n");
+
+    let mut results = vec![SearchResult::new(
+        SearchResultKind::TextMatch,
+        "src/missing.rs".to_string(),
+        1,
+        1,
+        "fn old() {}".to_string(),
+        0.5,
+    )];
+    let stale_files = engine.verify_freshness(&mut results);
+
+    assert!(stale_files.is_empty());
+    assert_eq!(results.len(), 1);
+}
+
+#[test]
+fn test_external_results_skip_freshness_check() {
+    let (engine, _dir) = engine_with_indexed_file("fn old() {}\n");
+
+    let mut results = vec![symbol_result(
+        "external://cargo/serde@1.0.0/src/lib.rs",
+        1,
+        1,
+    )];
+    let stale_files = engine.verify_freshness(&mut results);
+
+    assert!(stale_files.is_empty());
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].stale);
+}
+
+#[test]
+fn test_stored_contents_serve_deleted_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    store.set_file_contents(true);
+    let content = "fn old() {}\nfn load_settings() {}\n";
+    store
+        .write_file_index(&FileIndexData::new(
+            "src/lib.rs",
+            Some("rust"),
+            content,
+            content.len() as i64,
+            0,
+        ))
+        .unwrap();
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+
+    // The file was never on disk: text search and reads use the stored content
+    let query = Query::new("load_settings");
+    let matches = engine
+        .search_text(&query, 10, &SearchOptions::default())
+        .unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].start_line, 2);
+    assert_eq!(
+        engine.read_file_lines("src/lib.rs", 2, 2).unwrap(),
+        "fn load_settings() {}"
+    );
+
+    assert_eq!(
+        engine.check_file_freshness("src/lib.rs", file_record(&engine).as_ref()),
+        FileFreshness::Indexed
+    );
+    let mut results = vec![symbol_result("src/lib.rs", 1, 1)];
+    let stale_files = engine.verify_freshness(&mut results);
+    assert!(stale_files.is_empty());
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].stale);
+    // Nothing on disk to compare the index with
+    assert_eq!(results[0].metadata.freshness, Some(Freshness::Unknown));
+}
+
+// ==================== Search scope tests ====================
+
+use crate::query::{Query, SearchOptions, SearchScope};
+use semantiq_parser::{Symbol, SymbolKind, SymbolQualifiers};
+
+#[test]
+fn test_search_scope_filters_external_symbols() {
+    let content = "fn parse_config() {}\n";
+    let (engine, _dir) = engine_with_indexed_file(content);
+    let project_id = engine
+        .store
+        .get_file_by_path("src/lib.rs")
+        .unwrap()
+        .unwrap()
+        .id;
+    let external_id = engine
+        .store
+        .insert_file(
+            "external://cargo/config@1.0.0/src/lib.rs",
+            Some("rust"),
+            content,
+            content.len() as i64,
+            0,
+        )
+        .unwrap();
+
+    let symbol = Symbol {
+        name: "parse_config".to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 20,
+        start_column: 0,
+        end_column: 0,
+        signature: Some("fn parse_config()".to_string()),
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    for file_id in [project_id, external_id] {
+        engine
+            .store
+            .insert_symbols(file_id, std::slice::from_ref(&symbol))
+            .unwrap();
+    }
+
+    let paths = |scope: SearchScope| -> Vec<String> {
+        let options = SearchOptions::new().with_scope(scope);
+        let mut paths: Vec<String> = engine
+            .search("parse_config", 10, Some(options))
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|r| r.file_path)
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    };
+
+    assert_eq!(paths(SearchScope::Project), vec!["src/lib.rs"]);
+    assert_eq!(
+        paths(SearchScope::External),
+        vec!["external://cargo/config@1.0.0/src/lib.rs"]
+    );
+    assert_eq!(paths(SearchScope::All).len(), 2);
+}
+
+// ==================== Search strategy tests ====================
+
+use crate::query::QueryShape;
+
+#[test]
+fn test_search_records_strategy() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+
+    let results = engine.search("parse_config", 10, None).unwrap();
+    let strategy = results.strategy.expect("strategy should be recorded");
+    assert_eq!(strategy.shape, QueryShape::Identifier);
+    assert!(strategy.uses_symbols());
+
+    let results = engine.search("src/lib.rs", 10, None).unwrap();
+    let strategy = results.strategy.expect("strategy should be recorded");
+    assert_eq!(strategy.shape, QueryShape::Path);
+    assert!(!strategy.uses_semantic());
+}
+
+#[test]
+fn test_embedding_model_loads_on_first_semantic_search() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+    assert!(!engine.embedding_model_ready());
+
+    // Path queries are not searched semantically
+    engine.search("src/lib.rs", 10, None).unwrap();
+    assert!(!engine.embedding_model_ready());
+
+    engine.search("parse_config", 10, None).unwrap();
+    assert!(engine.embedding_model_ready());
+}
+
+#[test]
+fn test_search_attaches_snippets() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+
+    let results = engine.search("parse_config", 10, None).unwrap();
+    assert!(!results.is_empty());
+    for result in &results.results {
+        let snippet = result.snippet.as_ref().expect("snippet should be attached");
+        let highlight = snippet.highlights[0];
+        assert_eq!(
+            &snippet.text[highlight.start..highlight.end],
+            "parse_config"
+        );
+    }
+}
+
+#[test]
+fn test_bench_scores_expected_results() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+    let suite = crate::BenchSuite::parse(
+        "k: 3\ncases:\n  - query: parse_config\n    expected:\n      - file: src/lib.rs\n  - query: parse_config\n    expected:\n      - file: src/main.rs\n",
+    )
+    .unwrap();
+
+    let report = crate::bench::run(&engine, &suite).unwrap();
+    assert_eq!(report.cases[0].first_relevant_rank, Some(1));
+    assert_eq!(report.cases[1].first_relevant_rank, None);
+    assert_eq!(report.overall.mrr, 0.5);
+    assert_eq!(report.strategies["identifier"].queries, 2);
+}
+
+#[test]
+fn test_search_explains_scores_on_request() {
+    let content = "fn parse_config() {}\n";
+    let (engine, _dir) = engine_with_indexed_file(content);
+    let file_id = engine
+        .store
+        .get_file_by_path("src/lib.rs")
+        .unwrap()
+        .unwrap()
+        .id;
+    let symbol = Symbol {
+        name: "parse_config".to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 20,
+        start_column: 0,
+        end_column: 0,
+        signature: Some("fn parse_config()".to_string()),
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    engine.store.insert_symbols(file_id, &[symbol]).unwrap();
+
+    let results = engine.search("parse_config", 10, None).unwrap();
+    assert!(
+        results
+            .results
+            .iter()
+            .all(|r| r.metadata.explanation.is_none())
+    );
+
+    let options = SearchOptions::new().with_explain_scores(true);
+    let results = engine.search("parse_config", 10, Some(options)).unwrap();
+    let symbol_result = results
+        .results
+        .iter()
+        .find(|r| r.kind == SearchResultKind::Symbol)
+        .expect("symbol result");
+    let explanation = symbol_result
+        .metadata
+        .explanation
+        .as_ref()
+        .expect("explanation requested");
+    assert_eq!(explanation.strategy, "symbol");
+    assert_eq!(explanation.term_matches[0].term, "parse_config");
+    assert_eq!(
+        explanation.term_matches[0].match_kind.as_deref(),
+        Some("exact")
+    );
+    assert!(explanation.boosts.iter().any(|b| b.name == "kind:function"));
+    assert!(explanation.thresholds.min_score > 0.0);
+    assert!(explanation.summary().starts_with("symbol "));
+}
+
+// ==================== Query cache tests ====================
+
+#[test]
+fn test_repeated_search_is_served_from_cache() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+
+    assert!(!engine.search("parse_config", 10, None).unwrap().cached);
+    assert!(engine.search(" parse_config ", 10, None).unwrap().cached);
+
+    // Any index write invalidates the cache
+    engine
+        .store
+        .insert_file("src/other.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    assert!(!engine.search("parse_config", 10, None).unwrap().cached);
+}
+
+#[test]
+fn test_disabled_query_cache() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+    let engine = engine.with_query_cache(&semantiq_index::QueryCacheConfig {
+        enabled: false,
+        ..Default::default()
+    });
+
+    assert!(engine.query_cache().is_none());
+    engine.search("parse_config", 10, None).unwrap();
+    assert!(!engine.search("parse_config", 10, None).unwrap().cached);
+}
+
+#[test]
+fn test_cancelled_search_returns_partial_results() {
+    let (engine, _dir) = engine_with_indexed_file("fn parse_config() {}\n");
+
+    let deadline = crate::Deadline::new();
+    deadline.cancel();
+    let options = SearchOptions::new().with_deadline(deadline);
+    let results = engine.search("parse_config", 10, Some(options)).unwrap();
+    assert!(results.truncated_by_timeout);
+    assert!(results.is_empty());
+    // Nor are they diagnosed or cached
+    assert!(results.diagnostics.is_none());
+    let results = engine.search("parse_config", 10, None).unwrap();
+    assert!(!results.cached);
+    assert!(!results.truncated_by_timeout);
+
+    // A zero timeout disables the limit
+    let options = SearchOptions::new()
+        .with_deadline(crate::Deadline::new().with_timeout(std::time::Duration::ZERO));
+    assert!(
+        !engine
+            .search("parse_config", 10, Some(options))
+            .unwrap()
+            .truncated_by_timeout
+    );
+}
+
+// ==================== Implementations tests ====================
+
+use semantiq_parser::{Language, LanguageSupport, RelationExtractor, TextMaskExtractor};
+
+/// Index `files` with their type relations and contents, without symbols
+/// or chunks.
+fn engine_with_relations(files: &[(&str, Language, &str)]) -> RetrievalEngine {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let mut support = LanguageSupport::new().unwrap();
+    for (path, language, content) in files {
+        let tree = support
+            .parse_file(*language, std::path::Path::new(path), content)
+            .unwrap();
+        let relations = RelationExtractor::extract(&tree, content, *language).unwrap();
+        let file_id = store
+            .insert_file(path, Some(language.name()), content, 0, 0)
+            .unwrap();
+        store.insert_type_relations(file_id, &relations).unwrap();
+        store.backfill_file_content(path, content).unwrap();
+    }
+    RetrievalEngine::with_options(store, "/nonexistent", false)
+}
+
+#[test]
+fn test_find_declared_implementations() {
+    let engine = engine_with_relations(&[
+        (
+            "src/shape.rs",
+            Language::Rust,
+            "impl Shape for Circle {}\nimpl Shape for Square {}\ntrait Solid: Shape {}\n",
+        ),
+        (
+            "web/shape.ts",
+            Language::TypeScript,
+            "class Triangle implements Shape {}\n",
+        ),
+    ]);
+
+    let response = engine.find_implementations("Shape", 50).unwrap();
+    assert_eq!(response.target, "Shape");
+    assert_eq!(response.total_count, 4);
+    let found: Vec<(&str, &str, &str)> = response
+        .implementations
+        .iter()
+        .map(|i| {
+            (
+                i.type_name.as_str(),
+                i.relation.as_str(),
+                i.file_path.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("Circle", "implements", "src/shape.rs"),
+            ("Square", "implements", "src/shape.rs"),
+            ("Triangle", "implements", "web/shape.ts"),
+            ("Solid", "extends", "src/shape.rs"),
+        ]
+    );
+    assert!(response.implementations.iter().all(|i| !i.inferred));
+
+    let limited = engine.find_implementations("Shape", 1).unwrap();
+    assert_eq!(limited.total_count, 4);
+    assert_eq!(limited.implementations.len(), 1);
+}
+
+#[test]
+fn test_infer_go_implementations_from_method_sets() {
+    let engine = engine_with_relations(&[
+        (
+            "io/io.go",
+            Language::Go,
+            "package io\ntype Closer interface { Close() error }\ntype ReadCloser interface {\n    Read(p []byte) (int, error)\n    Closer\n}\n",
+        ),
+        (
+            "store/file.go",
+            Language::Go,
+            "package store\ntype File struct{}\nfunc (f *File) Read(p []byte) (int, error) { return 0, nil }\nfunc (f *File) Close() error { return nil }\nfunc (r *Reader) Read(p []byte) (int, error) { return 0, nil }\n",
+        ),
+    ]);
+
+    let response = engine.find_implementations("ReadCloser", 50).unwrap();
+    assert_eq!(response.implementations.len(), 1);
+    let file = &response.implementations[0];
+    assert_eq!(file.type_name, "File");
+    assert_eq!(file.relation, "implements");
+    assert_eq!(file.file_path, "store/file.go");
+    assert_eq!(file.start_line, 3);
+    assert!(file.inferred);
+
+    // Closer is extended by ReadCloser and implemented by File; Reader lacks Close
+    let closers = engine.find_implementations("Closer", 50).unwrap();
+    let names: Vec<&str> = closers
+        .implementations
+        .iter()
+        .map(|i| i.type_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["ReadCloser", "File"]);
+
+    assert!(
+        engine
+            .find_implementations("Unknown", 50)
+            .unwrap()
+            .implementations
+            .is_empty()
+    );
+}
+
+// ==================== Component tree tests ====================
+
+use crate::query::{FindRefsOptions, RefKind};
+
+fn component_engine() -> RetrievalEngine {
+    engine_with_relations(&[
+        (
+            "src/App.tsx",
+            Language::TypeScript,
+            "export function App() {\n  return <Layout><Dashboard /></Layout>;\n}\n",
+        ),
+        (
+            "src/Dashboard.tsx",
+            Language::TypeScript,
+            "const Dashboard = () => (\n  <div>\n    <Chart />\n    <ui.Button label=\"refresh\" />\n  </div>\n);\n",
+        ),
+        (
+            "src/Chart.jsx",
+            Language::JavaScript,
+            "function Chart() {\n  return <Dashboard />;\n}\n",
+        ),
+        (
+            "src/Settings.tsx",
+            Language::TypeScript,
+            "function Settings() {\n  return <Button label=\"save\" />;\n}\n",
+        ),
+    ])
+}
+
+#[test]
+fn test_component_tree() {
+    let engine = component_engine();
+
+    let tree = engine.component_tree("Dashboard", 3).unwrap();
+    let parents: Vec<(&str, &str, usize)> = tree
+        .rendered_by
+        .iter()
+        .map(|c| (c.name.as_str(), c.file_path.as_str(), c.line))
+        .collect();
+    assert_eq!(
+        parents,
+        vec![("App", "src/App.tsx", 2), ("Chart", "src/Chart.jsx", 2)]
+    );
+
+    let children: Vec<&str> = tree.renders.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(children, vec!["Chart", "Button"]);
+    // Chart renders Dashboard back: listed once, without its children
+    let chart = &tree.renders[0];
+    assert_eq!(chart.children.len(), 1);
+    assert_eq!(chart.children[0].name, "Dashboard");
+    assert!(chart.children[0].recursive);
+    assert!(chart.children[0].children.is_empty());
+    assert!(!tree.truncated);
+
+    let shallow = engine.component_tree("App", 1).unwrap();
+    let children: Vec<&str> = shallow.renders.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(children, vec!["Layout", "Dashboard"]);
+    assert!(shallow.renders.iter().all(|c| c.children.is_empty()));
+    assert!(shallow.rendered_by.is_empty());
+}
+
+#[test]
+fn test_find_references_includes_jsx_renders() {
+    let engine = component_engine();
+    let options = FindRefsOptions::new().with_kind(RefKind::Usage);
+
+    let results = engine.find_references("Button", &options).unwrap();
+    let renders: Vec<(&str, usize, Option<&str>)> = results
+        .results
+        .iter()
+        .filter(|r| r.metadata.match_type.as_deref() == Some("render"))
+        .map(|r| {
+            (
+                r.file_path.as_str(),
+                r.start_line,
+                r.metadata.parent.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        renders,
+        vec![
+            ("src/Dashboard.tsx", 4, Some("Dashboard")),
+            ("src/Settings.tsx", 2, Some("Settings")),
+        ]
+    );
+    assert!(results.results[0].content.contains("<ui.Button"));
+}
+
+#[test]
+fn test_find_references_excludes_comments_and_strings() {
+    let dir = tempfile::Builder::new()
+        .prefix("project")
+        .tempdir()
+        .unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    let content = "/* load_config reads the file */\nfn main() {\n    let name = \"load_config\"; // load_config\n    log(\"load_config\"); load_config(name);\n}\n";
+    std::fs::write(dir.path().join("src/main.rs"), content).unwrap();
+
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let mut support = LanguageSupport::new().unwrap();
+    let tree = support.parse(Language::Rust, content).unwrap();
+    let mask = TextMaskExtractor::extract(&tree, Language::Rust).unwrap();
+    store
+        .write_file_index(&FileIndexData {
+            text_mask: Some(&mask),
+            ..FileIndexData::new("src/main.rs", Some("rust"), content, 0, 0)
+        })
+        .unwrap();
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+
+    let usage_lines = |options: FindRefsOptions| -> Vec<usize> {
+        let mut lines: Vec<usize> = engine
+            .find_references("load_config", &options.with_kind(RefKind::Usage))
+            .unwrap()
+            .results
+            .iter()
+            .map(|r| r.start_line)
+            .collect();
+        lines.sort();
+        lines
+    };
+    assert_eq!(usage_lines(FindRefsOptions::new()), [1, 3, 4]);
+    assert_eq!(
+        usage_lines(FindRefsOptions::new().excluding_comments(true)),
+        [3, 4]
+    );
+    assert_eq!(
+        usage_lines(
+            FindRefsOptions::new()
+                .excluding_comments(true)
+                .excluding_strings(true)
+        ),
+        [4]
+    );
+
+    // The ranges are offsets in the indexed content: a file changed since
+    // keeps all its matches
+    std::fs::write(
+        dir.path().join("src/main.rs"),
+        format!("/* load_config */\n{}", content),
+    )
+    .unwrap();
+    assert_eq!(
+        usage_lines(FindRefsOptions::new().excluding_comments(true)),
+        [1, 2, 4, 5]
+    );
+}
+
+// ==================== Semantic diff tests ====================
+
+use std::process::Command;
+
+fn run_git(dir: &std::path::Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "-c",
+            "commit.gpgsign=false",
+        ])
+        .args(args)
+        .output()
+        .expect("git is required for diff tests")
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+fn commit_files(dir: &std::path::Path, files: &[(&str, &str)], message: &str) {
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    run_git(dir, &["add", "-A"]);
+    run_git(dir, &["commit", "-q", "-m", message]);
+}
+
+#[test]
+fn test_semantic_diff_reports_symbol_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    run_git(dir.path(), &["init", "-q"]);
+    commit_files(
+        dir.path(),
+        &[
+            (
+                "src/lib.rs",
+                "pub fn parse(input: &str) -> usize {\n    input.len()\n}\n\npub fn render(value: usize) -> String {\n    format!(\"value: {}\", value)\n}\n\npub fn sum_even(items: &[u32]) -> u32 {\n    items.iter().copied().filter(|i| i % 2 == 0).sum()\n}\n\nfn obsolete() {}\n",
+            ),
+            ("notes.txt", "first\n"),
+        ],
+        "first",
+    );
+    std::fs::remove_file(dir.path().join("notes.txt")).unwrap();
+    commit_files(
+        dir.path(),
+        &[
+            (
+                "src/lib.rs",
+                "pub fn parse(input: &str, strict: bool) -> usize {\n    if strict { input.trim().len() } else { input.len() }\n}\n\npub fn render(value: usize) -> String {\n    format!(\"value = {}\", value)\n}\n\npub fn added() -> bool {\n    true\n}\n",
+            ),
+            (
+                "src/util.rs",
+                "pub fn sum_even(items: &[u32]) -> u32 {\n    items.iter().copied().filter(|i| i % 2 == 0).sum()\n}\n",
+            ),
+        ],
+        "second",
+    );
+
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+    let diff = engine.semantic_diff("HEAD~1", "HEAD").unwrap();
+
+    let changes: Vec<(&str, &str, &str)> = diff
+        .files
+        .iter()
+        .flat_map(|f| f.changes.iter())
+        .map(|c| (c.file_path.as_str(), c.name.as_str(), c.change.as_str()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("src/lib.rs", "parse", "signature_changed"),
+            ("src/lib.rs", "render", "modified"),
+            ("src/lib.rs", "added", "added"),
+            ("src/lib.rs", "obsolete", "removed"),
+            ("src/util.rs", "sum_even", "moved"),
+        ]
+    );
+    assert_eq!(diff.total_changes, 5);
+
+    let moved = &diff.files[2].changes[0];
+    assert_eq!(moved.old_file_path.as_deref(), Some("src/lib.rs"));
+    assert_eq!(moved.old_start_line, Some(9));
+    assert_eq!(moved.similarity, Some(1.0));
+
+    let notes = diff.files.iter().find(|f| f.path == "notes.txt").unwrap();
+    assert_eq!(notes.status, "deleted");
+    assert_eq!(notes.language, None);
+}
+
+#[test]
+fn test_semantic_diff_rejects_bad_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+
+    let err = engine.semantic_diff("HEAD", "HEAD").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<DiffError>(),
+        Some(DiffError::NotARepository(_))
+    ));
+
+    run_git(dir.path(), &["init", "-q"]);
+    commit_files(dir.path(), &[("a.rs", "fn a() {}\n")], "first");
+    for (revision, expected) in [("--output=/tmp/x", "invalid"), ("nope", "unknown")] {
+        let err = engine.semantic_diff(revision, "HEAD").unwrap_err();
+        match err.downcast_ref::<DiffError>() {
+            Some(DiffError::InvalidRevision(_)) => assert_eq!(expected, "invalid"),
+            Some(DiffError::UnknownRevision(_)) => assert_eq!(expected, "unknown"),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
+
+// ==================== Related files tests ====================
+
+use semantiq_index::GitConfig;
+use semantiq_parser::Occurrence;
+
+#[test]
+fn test_related_files_merge_co_changes_and_shared_symbols() {
+    let dir = tempfile::tempdir().unwrap();
+    run_git(dir.path(), &["init", "-q"]);
+    let files = [
+        ("src/config.rs", "pub fn parse_config() {}\n"),
+        ("src/config_test.rs", "fn check() {}\n"),
+        ("src/main.rs", "fn main() { parse_config(); }\n"),
+    ];
+    commit_files(dir.path(), &files[..2], "first");
+    commit_files(
+        dir.path(),
+        &[
+            ("src/config.rs", "pub fn parse_config() { }\n"),
+            ("src/config_test.rs", "fn check() { }\n"),
+            ("src/main.rs", files[2].1),
+        ],
+        "second",
+    );
+    commit_files(dir.path(), &[("src/config.rs", files[0].1)], "third");
+
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let symbols = [Symbol {
+        name: "parse_config".to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: None,
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    }];
+    let config_occurrences = [Occurrence {
+        name: "parse_config".to_string(),
+        count: 1,
+        lines: Vec::new(),
+    }];
+    let main_occurrences = [Occurrence {
+        name: "parse_config".to_string(),
+        count: 1,
+        lines: Vec::new(),
+    }];
+    for (path, content) in files {
+        let (symbols, occurrences): (&[Symbol], &[Occurrence]) = match path {
+            "src/config.rs" => (&symbols, &config_occurrences),
+            "src/main.rs" => (&[], &main_occurrences),
+            _ => (&[], &[]),
+        };
+        store
+            .write_file_index(&FileIndexData {
+                symbols,
+                occurrences,
+                ..FileIndexData::new(path, Some("rust"), content, content.len() as i64, 0)
+            })
+            .unwrap();
+    }
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+
+    let response = engine.find_related_files("src/config.rs", 10).unwrap();
+    assert_eq!(response.history_commits, Some(3));
+    let related: Vec<(&str, Vec<RelatedSignal>)> = response
+        .related
+        .iter()
+        .map(|f| {
+            (
+                f.file_path.as_str(),
+                f.reasons.iter().map(|r| r.signal).collect(),
+            )
+        })
+        .collect();
+    // main.rs changed with config.rs only once
+    assert_eq!(
+        related,
+        vec![
+            ("src/config_test.rs", vec![RelatedSignal::CoChange]),
+            ("src/main.rs", vec![RelatedSignal::SharedSymbols]),
+        ]
+    );
+    assert_eq!(
+        response.related[0].reasons[0].detail,
+        "changed together in 2 of 3 commits"
+    );
+    assert_eq!(response.related[1].reasons[0].detail, "uses `parse_config`");
+
+    let engine = engine.with_git_config(&GitConfig {
+        enabled: false,
+        ..GitConfig::default()
+    });
+    let response = engine.find_related_files("src/config.rs", 10).unwrap();
+    assert_eq!(response.history_commits, None);
+    assert_eq!(response.total_count, 1);
+    assert_eq!(response.related[0].file_path, "src/main.rs");
+}
+
+// ==================== Overview tests ====================
+
+use crate::results::ToMarkdown;
+
+#[test]
+fn test_overview_follows_index_changes() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    for path in ["src/main.rs", "src/http/api.rs"] {
+        store
+            .write_file_index(&FileIndexData::new(path, Some("rust"), "", 0, 0))
+            .unwrap();
+    }
+    let engine = RetrievalEngine::with_options(Arc::clone(&store), "/nonexistent", false);
+
+    let root = engine.overview("").unwrap();
+    assert_eq!(root.path, ".");
+    assert_eq!(root.summary.unwrap().file_count, 2);
+    assert_eq!(root.subdirectory_count, 1);
+    assert_eq!(root.subdirectories[0].path, "src");
+    assert_eq!(root.subdirectories[0].entry_files, vec!["src/main.rs"]);
+
+    // Summaries are rebuilt once the index changed
+    store
+        .write_file_index(&FileIndexData::new(
+            "src/http/routes.rs",
+            Some("rust"),
+            "",
+            0,
+            0,
+        ))
+        .unwrap();
+    let http = engine.overview("./src/http/").unwrap();
+    assert_eq!(http.path, "src/http");
+    assert_eq!(http.summary.unwrap().file_count, 2);
+    assert!(http.subdirectories.is_empty());
+
+    let missing = engine.overview("docs").unwrap();
+    assert!(missing.summary.is_none());
+    assert_eq!(missing.to_markdown(), "No indexed files under 'docs'.");
+}
+
+#[test]
+fn test_explore_annotates_entries_with_index_data() {
+    let (engine, dir) = engine_with_indexed_file("fn old() {}\n");
+    let root = dir.path();
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+    std::fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+    std::fs::write(root.join(".gitignore"), "secrets.rs\n").unwrap();
+    std::fs::write(root.join("secrets.rs"), "fn key() {}\n").unwrap();
+    std::fs::write(root.join("logo.png"), [0u8; 4]).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "fn new_name() {}\n").unwrap();
+
+    let listing = engine.explore("", 100).unwrap().unwrap();
+    assert_eq!(listing.path, ".");
+    assert_eq!(
+        listing
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.excluded))
+            .collect::<Vec<_>>(),
+        [
+            (".git", Some(ExclusionReason::Hidden)),
+            ("node_modules", Some(ExclusionReason::ExcludedDirectory)),
+            ("src", None),
+            (".gitignore", Some(ExclusionReason::Hidden)),
+            ("logo.png", Some(ExclusionReason::UnsupportedLanguage)),
+            ("secrets.rs", Some(ExclusionReason::Gitignored)),
+        ]
+    );
+    let src = &listing.entries[2];
+    assert_eq!(src.indexed_file_count, Some(1));
+    assert_eq!(src.language.as_deref(), Some("rust"));
+    assert_eq!(src.indexed_at, file_record(&engine).map(|r| r.indexed_at));
+
+    // Files changed since they were indexed are stale
+    let src = engine.explore("./src/", 100).unwrap().unwrap();
+    assert_eq!(src.path, "src");
+    let lib = &src.entries[0];
+    assert_eq!(lib.path, "src/lib.rs");
+    assert_eq!(lib.symbol_count, Some(0));
+    assert_eq!(lib.freshness, Some(Freshness::Stale));
+
+    // Everything in an excluded directory is excluded
+    let modules = engine.explore("node_modules", 100).unwrap().unwrap();
+    assert_eq!(
+        modules.entries[0].excluded,
+        Some(ExclusionReason::InExcludedDirectory)
+    );
+
+    let limited = engine.explore("", 2).unwrap().unwrap();
+    assert_eq!(limited.entry_count, 6);
+    assert_eq!(limited.entries.len(), 2);
+
+    assert!(engine.explore("docs", 100).unwrap().is_none());
+    assert!(engine.explore("src/lib.rs", 100).unwrap().is_none());
+}
+
+// ==================== SQL explain tests ====================
+
+use semantiq_parser::SymbolExtractor;
+
+#[test]
+fn test_explain_table_lists_create_before_alters() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let mut support = LanguageSupport::new().unwrap();
+    let migrations = [
+        (
+            "migrations/002_add_email.sql",
+            "ALTER TABLE users_table ADD COLUMN email TEXT;\n",
+        ),
+        (
+            "migrations/001_create_users.sql",
+            "CREATE TABLE users_table (\n    id INTEGER PRIMARY KEY\n);\n",
+        ),
+        (
+            "migrations/003_add_name.sql",
+            "-- Display name\nALTER TABLE users_table ADD COLUMN name TEXT;\n",
+        ),
+    ];
+    for (path, content) in migrations {
+        let tree = support.parse(Language::Sql, content).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, content, Language::Sql).unwrap();
+        let file_id = store.insert_file(path, Some("sql"), content, 0, 0).unwrap();
+        store.insert_symbols(file_id, &symbols).unwrap();
+    }
+    let engine = RetrievalEngine::with_options(store, "/nonexistent", false);
+
+    let explanation = engine.explain_symbol("users_table").unwrap();
+    assert!(explanation.found);
+    let definitions: Vec<(&str, &str)> = explanation
+        .definitions
+        .iter()
+        .map(|d| (d.file_path.as_str(), d.signature.as_deref().unwrap_or("")))
+        .collect();
+    assert_eq!(
+        definitions,
+        vec![
+            (
+                "migrations/001_create_users.sql",
+                "CREATE TABLE users_table ("
+            ),
+            (
+                "migrations/002_add_email.sql",
+                "ALTER TABLE users_table ADD COLUMN email TEXT"
+            ),
+            (
+                "migrations/003_add_name.sql",
+                "ALTER TABLE users_table ADD COLUMN name TEXT"
+            ),
+        ]
+    );
+    assert!(explanation.definitions.iter().all(|d| d.kind == "table"));
+}
+
+// ==================== Empty search diagnostics tests ====================
+
+use crate::results::NearMiss;
+use semantiq_parser::ChunkExtractor;
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(search::edit_distance("config", "config"), 0);
+    assert_eq!(search::edit_distance("confgi", "config"), 2);
+    assert_eq!(search::edit_distance("parse", "parser"), 1);
+    assert_eq!(search::edit_distance("", "abc"), 3);
+    assert_eq!(search::edit_distance("créer", "creer"), 1);
+}
+
+/// Write `files` to a temporary project and index everything extracted
+/// from them, as the indexers do, without embeddings.
+fn engine_with_files(files: &[(&str, &str)]) -> (RetrievalEngine, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let mut support = LanguageSupport::new().unwrap();
+    let chunk_extractor = ChunkExtractor::new();
+    for (path, content) in files {
+        let full_path = dir.path().join(path);
+        std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+        std::fs::write(&full_path, content).unwrap();
+
+        let language = Language::from_path(&full_path).unwrap();
+        let extracted = ExtractedFile::extract(
+            &mut support,
+            &chunk_extractor,
+            language,
+            &full_path,
+            path,
+            content,
+            false,
+        )
+        .unwrap();
+        store
+            .write_file_index(
+                &FileIndexData::new(
+                    path,
+                    Some(language.name()),
+                    content,
+                    content.len() as i64,
+                    0,
+                )
+                .with_extracted(&extracted),
+            )
+            .unwrap();
+    }
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+    (engine, dir)
+}
+
+/// `src/lib.rs` and `web/app.ts`, a few functions each
+const SYMBOL_FILES: [(&str, &str); 2] = [
+    (
+        "src/lib.rs",
+        "fn load_config() {}\nfn parse_settings() {}\n",
+    ),
+    ("web/app.ts", "function renderDashboard() {}\n"),
+];
+
+#[test]
+fn test_results_have_no_diagnostics() {
+    let (engine, _dir) = engine_with_files(&SYMBOL_FILES);
+
+    let results = engine.search("load_config", 10, None).unwrap();
+    assert!(!results.is_empty());
+    assert!(results.diagnostics.is_none());
+}
+
+#[test]
+fn test_empty_search_reports_filtered_matches() {
+    let (engine, _dir) = engine_with_files(&SYMBOL_FILES);
+
+    let options = SearchOptions::new().with_file_types(vec!["py".to_string()]);
+    let results = engine.search("renderDashboard", 10, Some(options)).unwrap();
+    assert!(results.is_empty());
+
+    let diagnostics = results.diagnostics.unwrap();
+    assert_eq!(diagnostics.filtered_out.len(), 1);
+    assert_eq!(diagnostics.filtered_out[0].filter, "file_type");
+    assert_eq!(diagnostics.filtered_out[0].value, "ts");
+    assert!(
+        diagnostics
+            .hints
+            .iter()
+            .any(|h| h.contains(".ts files were excluded by the file_type filter"))
+    );
+    // Without embeddings (or without a model) semantic search cannot run
+    assert!(!diagnostics.semantic_search);
+    assert_eq!(diagnostics.embedded_chunks, 0);
+    assert!(diagnostics.hints.iter().any(|h| {
+        h.contains("No embedding model is loaded") || h.contains("No chunk has an embedding yet")
+    }));
+}
+
+#[test]
+fn test_empty_search_suggests_symbol_names() {
+    let (engine, _dir) = engine_with_files(&SYMBOL_FILES);
+
+    // Misspelled in both words, or BM25 would match the `config` keyword
+    let results = engine.search("lod_confg", 10, None).unwrap();
+    assert!(results.is_empty());
+    let diagnostics = results.diagnostics.unwrap();
+    assert_eq!(diagnostics.suggestions, vec!["load_config"]);
+
+    // Each term is corrected on its own; partial names are completed
+    let results = engine.search("lod_confg par", 10, None).unwrap();
+    let diagnostics = results.diagnostics.unwrap();
+    assert_eq!(
+        diagnostics.suggestions,
+        vec!["load_config par", "lod_confg parse_settings"]
+    );
+}
+
+#[test]
+fn test_empty_search_reports_near_misses() {
+    let (engine, _dir) = engine_with_files(&SYMBOL_FILES);
+
+    let options = SearchOptions::new().with_min_score(1.0);
+    let results = engine
+        .search("how are settings parsed", 10, Some(options))
+        .unwrap();
+    assert!(results.is_empty());
+
+    let diagnostics = results.diagnostics.unwrap();
+    let closest: &NearMiss = &diagnostics.near_misses[0];
+    assert_eq!(closest.file_path, "src/lib.rs");
+    assert_eq!(closest.threshold, 1.0);
+    assert!(closest.score < 1.0);
+    assert!(
+        diagnostics
+            .hints
+            .iter()
+            .any(|h| h.contains("lower min_score to include it"))
+    );
+}
+
+// ==================== Usage and unused symbol tests ====================
+
+use semantiq_index::{ExtractedFile, FileIndexData, SymbolRecord, UnreferencedSymbol};
+use usage::{UsageConfidence, is_exported, language_caveat};
+
+fn unreferenced(name: &str, signature: &str, parent: Option<&str>) -> UnreferencedSymbol {
+    UnreferencedSymbol {
+        symbol: SymbolRecord {
+            id: 1,
+            file_id: 1,
+            name: name.to_string(),
+            kind: "function".to_string(),
+            start_line: 1,
+            end_line: 1,
+            start_byte: 0,
+            end_byte: 0,
+            start_column: 0,
+            end_column: 0,
+            signature: Some(signature.to_string()),
+            doc_comment: None,
+            parent: parent.map(str::to_string),
+            qualifiers: SymbolQualifiers::default(),
+            cfg_context: None,
+        },
+        file_path: "src/lib".to_string(),
+        language: None,
+        local_references: 0,
+    }
+}
+
+#[test]
+fn test_is_exported_per_language() {
+    let exported = |name, signature, language, line| {
+        is_exported(&unreferenced(name, signature, None), language, line)
+    };
+
+    assert!(exported("load", "pub fn load()", "rust", None));
+    assert!(exported("load", "pub(crate) fn load()", "rust", None));
+    assert!(!exported("load", "fn load()", "rust", None));
+
+    assert!(exported("Load", "func Load()", "go", None));
+    assert!(!exported("load", "func load()", "go", None));
+
+    assert!(exported("load", "public void load() {", "java", None));
+    assert!(!exported("load", "private void load() {", "java", None));
+    assert!(!exported("handle", "@GetMapping(\"/\")", "java", None));
+
+    assert!(exported(
+        "load",
+        "function load() {",
+        "typescript",
+        Some("export function load() {")
+    ));
+    assert!(!exported(
+        "load",
+        "function load() {",
+        "typescript",
+        Some("function load() {")
+    ));
+    assert!(!is_exported(
+        &unreferenced("reset", "private reset() {", Some("Store")),
+        "typescript",
+        None
+    ));
+
+    assert!(exported("load", "def load(self):", "python", None));
+    assert!(!exported("_load", "def _load(self):", "python", None));
+
+    assert!(!exported("load", "static int load(void)", "c", None));
+    assert!(!exported("load", "defp load(x) do", "elixir", None));
+}
+
+#[test]
+fn test_language_caveats() {
+    assert_eq!(language_caveat("rust").unwrap().0, UsageConfidence::High);
+    assert_eq!(language_caveat("java").unwrap().0, UsageConfidence::Medium);
+    assert_eq!(language_caveat("python").unwrap().0, UsageConfidence::Low);
+    assert!(language_caveat("sql").is_none());
+}
+
+#[test]
+fn test_find_unused_symbols_and_usage_counts() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "src/config.rs",
+            "pub fn load() -> u32 { parse_value() }\n\
+                 pub fn parse_value() -> u32 { 1 }\n\
+                 pub fn orphan() {}\n\
+                 fn private_helper() {}\n",
+        ),
+        (
+            "src/main.rs",
+            "fn main() { config::load(); config::load(); }\n",
+        ),
+        ("src/server.rs", "pub fn start() { config::load(); }\n"),
+        ("tests/config_test.rs", "pub fn fixture() {}\n"),
+    ]);
+
+    let explanation = engine.explain_symbol("load").unwrap();
+    assert_eq!(explanation.usage_count, 3);
+    assert_eq!(explanation.usage_files, 2);
+
+    let unused = engine.find_unused_symbols(None, false, 10).unwrap();
+    let names: Vec<(&str, usize)> = unused
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.local_references))
+        .collect();
+    assert_eq!(names, vec![("parse_value", 1), ("orphan", 0), ("start", 0)]);
+    assert_eq!(unused.total_count, 3);
+    assert_eq!(unused.caveats.len(), 1);
+    assert_eq!(unused.caveats[0].language, "rust");
+    assert!(
+        unused
+            .symbols
+            .iter()
+            .all(|s| s.confidence == UsageConfidence::High)
+    );
+
+    let with_tests = engine.find_unused_symbols(None, true, 10).unwrap();
+    assert!(with_tests.symbols.iter().any(|s| s.name == "fixture"));
+
+    let limited = engine
+        .find_unused_symbols(Some("src/config.rs"), false, 1)
+        .unwrap();
+    assert_eq!(limited.symbols.len(), 1);
+    assert_eq!(limited.total_count, 2);
+}
+
+// ==================== Tag tests ====================
+
+#[test]
+fn test_search_reports_tags_and_filters_by_tag() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "src/auth.rs",
+            "// Check the session token\nfn check_session_token(token: &Token) {}\n",
+        ),
+        (
+            "src/db.rs",
+            "// Check a database query\nfn check_query(query: &Query) {}\n",
+        ),
+        (
+            "src/http.rs",
+            "// Check an HTTP request\nfn check_request(request: &Request) {}\n",
+        ),
+    ]);
+
+    let results = engine.search("check", 10, None).unwrap();
+    let mut paths: Vec<&str> = results
+        .results
+        .iter()
+        .map(|r| r.file_path.as_str())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    assert_eq!(paths, vec!["src/auth.rs", "src/db.rs", "src/http.rs"]);
+    assert_eq!(results.file_tags["src/auth.rs"][0], "token");
+    assert!(
+        results
+            .facets
+            .iter()
+            .any(|f| f.tag == "query" && f.count == 1)
+    );
+    // check is in every file, so it tells none apart
+    assert!(!results.facets.iter().any(|f| f.tag == "check"));
+
+    let results = engine.search("check tags:tokens", 10, None).unwrap();
+    assert!(!results.is_empty());
+    assert!(results.results.iter().all(|r| r.file_path == "src/auth.rs"));
+    assert!(!results.facets.iter().any(|f| f.tag == "token"));
+
+    let results = engine.search("check tags:token,query", 10, None).unwrap();
+    assert!(results.is_empty());
+    let diagnostics = results.diagnostics.unwrap();
+    assert!(diagnostics.hints.iter().any(|h| h.contains("tags:")));
+}
+
+// ==================== Package tests ====================
+
+use semantiq_index::{PackageEcosystem, WorkspacePackage};
+
+fn cargo_package(root: &str, name: &str) -> WorkspacePackage {
+    WorkspacePackage {
+        root: root.to_string(),
+        ecosystem: PackageEcosystem::Cargo,
+        name: name.to_string(),
+    }
+}
+
+#[test]
+fn test_search_filters_by_package() {
+    let (engine, _dir) = engine_with_files(&[
+        ("crates/core/src/lib.rs", "fn check_config() {}\n"),
+        ("crates/web/src/lib.rs", "fn check_request() {}\n"),
+    ]);
+    engine
+        .store
+        .set_packages(&[
+            cargo_package("crates/core", "acme-core"),
+            cargo_package("crates/web", "acme-web"),
+        ])
+        .unwrap();
+
+    let options = SearchOptions::new().with_packages(vec!["acme-web".to_string()]);
+    let results = engine.search("check", 10, Some(options)).unwrap();
+    assert!(!results.is_empty());
+    assert!(
+        results
+            .results
+            .iter()
+            .all(|r| r.file_path == "crates/web/src/lib.rs")
+    );
+
+    let options = SearchOptions::new().with_packages(vec!["acme-cli".to_string()]);
+    let results = engine.search("check", 10, Some(options)).unwrap();
+    assert!(results.is_empty());
+    let diagnostics = results.diagnostics.unwrap();
+    assert!(diagnostics.hints.iter().any(|h| {
+        h.contains("Unknown package(s) acme-cli: the workspace packages are acme-core, acme-web")
+    }));
+}
+
+#[test]
+fn test_dependencies_distinguish_package_boundaries() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    store
+        .set_packages(&[
+            cargo_package("crates/core", "acme-core"),
+            cargo_package("crates/web", "acme-web"),
+        ])
+        .unwrap();
+    let web = store
+        .insert_file("crates/web/src/lib.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    store
+        .insert_file("crates/core/src/store.rs", Some("rust"), "", 0, 0)
+        .unwrap();
+    for (target, kind) in [
+        ("crate::routes", "local"),
+        ("acme_core::store", "external"),
+        ("serde::Serialize", "external"),
+    ] {
+        store.insert_dependency(web, target, None, kind).unwrap();
+    }
+    let engine = RetrievalEngine::with_options(store, "/nonexistent", false);
+
+    let imports = engine.get_dependencies("crates/web/src/lib.rs").unwrap();
+    let scopes: Vec<(&str, Option<&str>, Option<DependencyScope>)> = imports
+        .iter()
+        .map(|d| (d.target_path.as_str(), d.package.as_deref(), d.scope))
+        .collect();
+    assert_eq!(
+        scopes,
+        vec![
+            (
+                "crate::routes",
+                Some("acme-web"),
+                Some(DependencyScope::IntraPackage)
+            ),
+            (
+                "acme_core::store",
+                Some("acme-core"),
+                Some(DependencyScope::CrossPackage)
+            ),
+            ("serde::Serialize", None, None),
+        ]
+    );
+
+    let dependents = engine.get_dependents("crates/core/src/store.rs").unwrap();
+    let web_dependent = dependents
+        .iter()
+        .find(|d| d.target_path == "crates/web/src/lib.rs")
+        .unwrap();
+    assert_eq!(web_dependent.package.as_deref(), Some("acme-web"));
+    assert_eq!(web_dependent.scope, Some(DependencyScope::CrossPackage));
+
+    assert_eq!(
+        engine.file_package("crates/core/src/store.rs").unwrap(),
+        Some("acme-core".to_string())
+    );
+}
+
+use semantiq_parser::CodeChunk;
+
+/// Write a file with one chunk, with or without a summary embedding
+fn write_chunked_file(store: &IndexStore, path: &str, summarized: bool) {
+    let chunks = vec![CodeChunk {
+        content: "fn handler() {}".to_string(),
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 15,
+        symbols: vec!["handler".to_string()],
+    }];
+    let summary = vec![0.05; 384];
+    store
+        .write_file_index(&FileIndexData {
+            chunks: &chunks,
+            summary_embedding: summarized.then_some(summary.as_slice()),
+            ..FileIndexData::new(path, Some("rust"), path, 3, 0)
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_coarse_search_needs_large_summarized_index() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    for i in 0..10 {
+        write_chunked_file(&store, &format!("src/f{}.rs", i), true);
+    }
+    let config = |coarse_min_chunks| SearchConfig {
+        coarse_min_chunks,
+        coarse_files: 5,
+        ..SearchConfig::default()
+    };
+
+    let engine = RetrievalEngine::with_options(Arc::clone(&store), "/tmp", false);
+    assert!(!engine.use_coarse_search().unwrap());
+    let engine = engine.with_search_config(&config(10));
+    assert!(engine.use_coarse_search().unwrap());
+
+    // Two files without summary bring coverage under 90%
+    write_chunked_file(&store, "src/new1.rs", false);
+    write_chunked_file(&store, "src/new2.rs", false);
+    assert!(!engine.use_coarse_search().unwrap());
+
+    let engine = engine.with_search_config(&config(0));
+    assert!(!engine.use_coarse_search().unwrap());
+}
+
+#[test]
+fn test_search_filters_by_qualifiers() {
+    let (engine, _dir) = engine_with_files(&[(
+        "src/session.rs",
+        "pub async fn check_session() {}\nfn check_token() {}\npub fn check_user<T>(user: T) {}\n",
+    )]);
+
+    let names = |qualifiers: &[&str]| {
+        let options = SearchOptions::new()
+            .with_qualifiers(qualifiers.iter().map(|q| q.to_string()).collect());
+        let mut names: Vec<String> = engine
+            .search("check", 10, Some(options))
+            .unwrap()
+            .results
+            .into_iter()
+            .filter_map(|r| r.metadata.symbol_name)
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&["public"]), ["check_session", "check_user"]);
+    assert_eq!(names(&["public", "async"]), ["check_session"]);
+    assert_eq!(names(&["generic"]), ["check_user"]);
+
+    let options = SearchOptions::new().with_qualifiers(vec!["abstract".to_string()]);
+    let results = engine.search("check", 10, Some(options)).unwrap();
+    assert!(results.is_empty());
+    let diagnostics = results.diagnostics.unwrap();
+    assert!(
+        diagnostics
+            .hints
+            .iter()
+            .any(|h| h.contains("Only symbols that are abstract were searched"))
+    );
+}
+
+#[test]
+fn test_search_excludes_conditional_symbols() {
+    let (engine, _dir) = engine_with_files(&[(
+        "src/session.rs",
+        "fn check_session() {}\n\n#[cfg(test)]\nmod tests {\n    fn check_fixture() {}\n}\n\n#[cfg(windows)]\nfn check_registry() {}\n",
+    )]);
+
+    let symbols = |options: SearchOptions| {
+        let mut symbols: Vec<(String, Option<String>)> = engine
+            .search("check", 10, Some(options))
+            .unwrap()
+            .results
+            .into_iter()
+            .filter(|r| r.kind == SearchResultKind::Symbol)
+            .filter_map(|r| Some((r.metadata.symbol_name?, r.metadata.cfg_context)))
+            .collect();
+        symbols.sort();
+        symbols
+    };
+    assert_eq!(
+        symbols(SearchOptions::new()),
+        [
+            ("check_fixture".to_string(), Some("test".to_string())),
+            ("check_registry".to_string(), Some("windows".to_string())),
+            ("check_session".to_string(), None),
+        ]
+    );
+    assert_eq!(
+        symbols(SearchOptions::new().with_exclude_conditional(true)),
+        [("check_session".to_string(), None)]
+    );
+}
+
+#[test]
+fn test_search_leaves_out_generated_files() {
+    let (engine, _dir) = engine_with_files(&[
+        ("src/session.rs", "fn check_session() {}\n"),
+        ("src/api/session.pb.rs", "fn check_session_message() {}\n"),
+    ]);
+    assert!(
+        engine
+            .store
+            .set_file_generated("src/api/session.pb.rs", true)
+            .unwrap()
+    );
+
+    let files = |options: SearchOptions| {
+        let mut files: Vec<(String, bool)> = engine
+            .search("check_session", 10, Some(options))
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|r| (r.file_path, r.metadata.generated))
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    };
+    assert_eq!(
+        files(SearchOptions::new()),
+        [("src/session.rs".to_string(), false)]
+    );
+    assert_eq!(
+        files(SearchOptions::new().with_include_generated(true)),
+        [
+            ("src/api/session.pb.rs".to_string(), true),
+            ("src/session.rs".to_string(), false),
+        ]
+    );
+}
+
+#[test]
+fn test_search_without_model_ranks_chunks_with_bm25() {
+    let (engine, _dir) = engine_with_files(&SYMBOL_FILES);
+    // Tests are built without ONNX: the model is a placeholder
+    assert!(engine.semantic_model().is_none());
+
+    let options = SearchOptions::new().with_explain_scores(true);
+    let results = engine
+        .search("how are settings parsed", 10, Some(options))
+        .unwrap();
+    let bm25 = results
+        .results
+        .iter()
+        .find(|r| r.metadata.match_type.as_deref() == Some("bm25"))
+        .expect("a chunk ranked by BM25");
+    assert_eq!(bm25.file_path, "src/lib.rs");
+    assert!(bm25.score > 0.0 && bm25.score < 1.0);
+    let explanation = bm25.metadata.explanation.as_ref().unwrap();
+    assert_eq!(explanation.strategy, "bm25");
+}
+
+#[test]
+fn test_search_expands_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let content = "use std::fs;\n\nfn load_config() {\n    let path = \"config.toml\";\n    let text = fs::read_to_string(path);\n    parse(text)\n}\n\nfn other() {}\n";
+    std::fs::write(dir.path().join("lib.rs"), content).unwrap();
+    let mut support = LanguageSupport::new().unwrap();
+    let tree = support.parse(Language::Rust, content).unwrap();
+    let symbols = SymbolExtractor::extract(&tree, content, Language::Rust).unwrap();
+    let file_id = store
+        .insert_file("lib.rs", Some("rust"), content, 0, 0)
+        .unwrap();
+    store.insert_symbols(file_id, &symbols).unwrap();
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+
+    let text_match = |options: SearchOptions| {
+        engine
+            .search("read_to_string", 10, Some(options.with_min_score(0.0)))
+            .unwrap()
+            .results
+            .into_iter()
+            .find(|r| r.kind == SearchResultKind::TextMatch)
+            .unwrap()
+    };
+
+    let plain = text_match(SearchOptions::new());
+    assert_eq!((plain.start_line, plain.end_line), (5, 5));
+    assert!(plain.matched_lines.is_none());
+
+    let context = text_match(SearchOptions::new().with_context_lines(1));
+    assert_eq!((context.start_line, context.end_line), (4, 6));
+    assert_eq!(
+        context.matched_lines,
+        Some(LineRange {
+            start_line: 5,
+            end_line: 5
+        })
+    );
+    assert!(context.content.starts_with("    let path"));
+
+    let symbol = text_match(SearchOptions::new().with_expand_to_symbol(true));
+    assert_eq!((symbol.start_line, symbol.end_line), (3, 7));
+    assert!(symbol.content.starts_with("fn load_config() {"));
+    assert!(symbol.content.ends_with('}'));
+    // The snippet shows the whole expanded content
+    assert_eq!(symbol.snippet.unwrap().text, symbol.content);
+
+    // Context lines are added around the symbol, within the file
+    let both = text_match(
+        SearchOptions::new()
+            .with_expand_to_symbol(true)
+            .with_context_lines(SearchOptions::MAX_CONTEXT_LINES + 10),
+    );
+    assert_eq!((both.start_line, both.end_line), (1, 9));
+}
+
+// ==================== Doc comment search tests ====================
+
+use semantiq_index::SymbolField;
+
+#[test]
+fn test_natural_language_queries_match_doc_comments() {
+    let (engine, _dir) = engine_with_indexed_file("fn send_chunk() {}\nfn upload_file() {}\n");
+    let file_id = engine
+        .store
+        .get_file_by_path("src/lib.rs")
+        .unwrap()
+        .unwrap()
+        .id;
+    let symbol = |name: &str, line: usize, doc: &str| Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: line,
+        end_line: line,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: Some(format!("fn {}()", name)),
+        doc_comment: Some(doc.to_string()),
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    engine
+        .store
+        .insert_symbols(
+            file_id,
+            &[
+                symbol("send_chunk", 1, "Retry failed uploads with a backoff"),
+                symbol("upload_file", 2, "Upload a file in one request"),
+            ],
+        )
+        .unwrap();
+
+    let options = SearchOptions::new().with_explain_scores(true);
+    let query = Query::new("how are failed uploads retried");
+    assert_eq!(query.shape, QueryShape::NaturalLanguage);
+    let results = engine.search_symbols(&query, 10, &options).unwrap();
+
+    let send_chunk = results
+        .iter()
+        .filter(|r| r.metadata.symbol_name.as_deref() == Some("send_chunk"))
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .expect("the doc comment should match");
+    assert_eq!(send_chunk.metadata.match_in, Some(SymbolField::Docs));
+    let boosts = &send_chunk.metadata.explanation.as_ref().unwrap().boosts;
+    let docs = boosts.iter().find(|b| b.name == "docs").unwrap();
+    assert!((docs.factor - 1.4).abs() < 1e-6);
+
+    // Identifiers are matched on the name, without the docs boost
+    let results = engine
+        .search_symbols(&Query::new("upload_file"), 10, &options)
+        .unwrap();
+    let upload = &results[0];
+    assert_eq!(upload.metadata.match_in, Some(SymbolField::Name));
+    assert!(
+        !upload
+            .metadata
+            .explanation
+            .as_ref()
+            .unwrap()
+            .boosts
+            .iter()
+            .any(|b| b.name == "docs")
+    );
+}
+
+// ==================== Qualified symbol search tests ====================
+
+#[test]
+fn test_qualified_queries_rank_symbols_in_scope_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let symbol = |name: &str, parent: Option<&str>| Symbol {
+        name: name.to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 0,
+        start_column: 0,
+        end_column: 0,
+        signature: Some(format!("fn {}()", name)),
+        doc_comment: None,
+        parent: parent.map(String::from),
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+    for (path, name, parent) in [
+        ("src/admin.rs", "login", None),
+        ("src/auth/session.rs", "login", None),
+        ("src/orders.ts", "create", Some("OrderService")),
+        ("src/users.ts", "create", Some("UserService")),
+    ] {
+        let file_id = store.insert_file(path, Some("rust"), "", 0, 0).unwrap();
+        store
+            .insert_symbols(file_id, &[symbol(name, parent)])
+            .unwrap();
+    }
+    let engine = RetrievalEngine::with_options(store, dir.path().to_str().unwrap(), false);
+    let options = SearchOptions::new().with_explain_scores(true);
+
+    let ranked = |text: &str| -> Vec<(String, f32)> {
+        let mut results = engine
+            .search_symbols(&Query::new(text), 10, &options)
+            .unwrap();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results
+            .into_iter()
+            .map(|r| (r.file_path, r.score))
+            .collect()
+    };
+
+    let login = ranked("auth::login");
+    assert_eq!(login[0].0, "src/auth/session.rs");
+    let other = login
+        .iter()
+        .find(|(path, _)| path == "src/admin.rs")
+        .unwrap();
+    assert!(other.1 < login[0].1);
+
+    let create = ranked("UserService.create");
+    assert_eq!(create[0].0, "src/users.ts");
+    assert!(create.iter().any(|(path, _)| path == "src/orders.ts"));
+
+    // Unqualified names rank both the same
+    let plain = ranked("login");
+    assert_eq!(plain[0].1, plain[1].1);
+}
+
+// ==================== Base index tests ====================
+
+#[test]
+fn test_base_index_results_are_merged_and_shadowed() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    let files = [
+        ("src/config.rs", "fn load_settings() {}\n"),
+        ("src/server.rs", "fn load_settings() {}\n"),
+    ];
+    for (path, content) in files {
+        std::fs::write(root.join(path), content).unwrap();
+    }
+    let symbol = Symbol {
+        name: "load_settings".to_string(),
+        kind: SymbolKind::Function,
+        start_line: 1,
+        end_line: 1,
+        start_byte: 0,
+        end_byte: 21,
+        start_column: 0,
+        end_column: 0,
+        signature: Some("fn load_settings()".to_string()),
+        doc_comment: None,
+        parent: None,
+        qualifiers: SymbolQualifiers::default(),
+        cfg_context: None,
+    };
+
+    // The base index covers both files, the local index only the server
+    let base_path = dir.path().join("base.db");
+    {
+        let built = IndexStore::open(&base_path).unwrap();
+        for (path, content) in files {
+            let file_id = built
+                .insert_file(path, Some("rust"), content, content.len() as i64, 0)
+                .unwrap();
+            built
+                .insert_symbols(file_id, std::slice::from_ref(&symbol))
+                .unwrap();
+        }
+        built.set_parser_version().unwrap();
+    }
+    let local = Arc::new(IndexStore::open_in_memory().unwrap());
+    let (path, content) = files[1];
+    let file_id = local
+        .insert_file(path, Some("rust"), content, content.len() as i64, 0)
+        .unwrap();
+    local
+        .insert_symbols(file_id, std::slice::from_ref(&symbol))
+        .unwrap();
+
+    let base = IndexStore::open_base(&base_path, &local).unwrap();
+    let engine = RetrievalEngine::with_options(local, root.to_str().unwrap(), false)
+        .with_base_index(Arc::new(base));
+    assert!(engine.base_index().is_some());
+
+    let results = engine.search("load_settings", 10, None).unwrap();
+    let symbols: Vec<(&str, bool)> = results
+        .results
+        .iter()
+        .filter(|r| r.kind == SearchResultKind::Symbol)
+        .map(|r| (r.file_path.as_str(), r.from_base))
+        .collect();
+    assert!(symbols.contains(&("src/config.rs", true)));
+    assert!(symbols.contains(&("src/server.rs", false)));
+    // Shadowed by the local index
+    assert!(!symbols.contains(&("src/server.rs", true)));
+}
+
+// ==================== Span tests ====================
+
+#[test]
+fn test_references_have_byte_spans() {
+    let content = "struct Config;\n\nimpl Config {\n    fn load_settings() {}\n}\n\nfn main() {\n    Config::load_settings();\n}\n";
+    let (engine, _dir) = engine_with_indexed_file(content);
+    let file_id = file_record(&engine).unwrap().id;
+    let mut support = LanguageSupport::new().unwrap();
+    let tree = support.parse(Language::Rust, content).unwrap();
+    let symbols = SymbolExtractor::extract(&tree, content, Language::Rust).unwrap();
+    engine.store.insert_symbols(file_id, &symbols).unwrap();
+
+    let refs = engine
+        .find_references("load_settings", &FindRefsOptions::new())
+        .unwrap();
+    let definition = refs
+        .results
+        .iter()
+        .find(|r| r.kind == SearchResultKind::Symbol)
+        .unwrap();
+    let span = definition.span.expect("definition span");
+    assert_eq!(span.start_column, 4);
+    assert_eq!(
+        &content[span.start_byte..span.end_byte],
+        "fn load_settings() {}"
+    );
+    assert_eq!(span.end_column, 25);
+
+    let usage = refs
+        .results
+        .iter()
+        .find(|r| r.kind == SearchResultKind::Reference)
+        .unwrap();
+    assert_eq!(usage.start_line, 8);
+    let span = usage.span.expect("usage span");
+    assert_eq!(&content[span.start_byte..span.end_byte], "load_settings");
+    assert_eq!((span.start_column, span.end_column), (12, 25));
+}
+
+// ==================== Recalibration tests ====================
+
+fn observe(engine: &RetrievalEngine, count: usize, timestamp: i64) {
+    // Observations are unique by query
+    let first = engine
+        .store
+        .get_observation_counts()
+        .unwrap()
+        .get("rust")
+        .copied()
+        .unwrap_or(0);
+    let batch: Vec<(String, f32, u64, i64)> = (0..count)
+        .map(|i| {
+            let distance = 0.4 + (i % 50) as f32 * 0.01;
+            ("rust".to_string(), distance, (first + i) as u64, timestamp)
+        })
+        .collect();
+    engine
+        .store
+        .insert_distance_observations_batch(&batch)
+        .unwrap();
+}
+
+#[test]
+fn test_recalibrate_when_observations_grow() {
+    let (engine, _dir) = engine_with_indexed_file("fn main() {}\n");
+    let config = semantiq_index::RecalibrationConfig::default();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    // Too few observations, and expired ones are dropped
+    observe(&engine, 40, now);
+    observe(&engine, 100, 0);
+    assert!(!engine.recalibrate_if_due(&config).unwrap());
+    assert_eq!(engine.store.get_observation_counts().unwrap()["rust"], 40);
+
+    observe(&engine, 160, now);
+    assert!(engine.recalibrate_if_due(&config).unwrap());
+    assert!(engine.has_calibrated_thresholds());
+    let samples = engine.threshold_config.read().unwrap().global.sample_count;
+    assert_eq!(samples, 200);
+
+    // Not due again until `observation_delta` more observations
+    observe(&engine, 100, now);
+    assert!(!engine.recalibrate_if_due(&config).unwrap());
+    observe(&engine, config.observation_delta, now);
+    assert!(engine.recalibrate_if_due(&config).unwrap());
+    let samples = engine.threshold_config.read().unwrap().global.sample_count;
+    assert_eq!(samples, 800);
+}
+
+// ==================== Entry point tests ====================
+
+use semantiq_parser::EntrypointKind;
+
+#[test]
+fn test_entrypoints_by_kind_and_directory() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "src/main.rs",
+            "fn main() {}\n\n#[derive(Subcommand)]\nenum Commands {\n    Serve,\n    Index,\n}\n",
+        ),
+        (
+            "src/api.rs",
+            "#[get(\"/users\")]\nasync fn list_users() {}\n\n#[post(\"/users\")]\nasync fn create_user() {}\n",
+        ),
+        ("src/lib.rs", "pub mod api;\npub fn run() {}\n"),
+        ("tools/gen.rs", "fn main() {}\n"),
+    ]);
+
+    let all = engine.entrypoints(None, ".", 10).unwrap();
+    assert_eq!(all.total_count, 8);
+    let listed: Vec<(&str, &str, &str)> = all
+        .entrypoints
+        .iter()
+        .map(|e| (e.kind.as_str(), e.name.as_str(), e.file_path.as_str()))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            ("main", "main", "src/main.rs"),
+            ("main", "main", "tools/gen.rs"),
+            ("command", "serve", "src/main.rs"),
+            ("command", "index", "src/main.rs"),
+            ("route", "GET /users", "src/api.rs"),
+            ("route", "POST /users", "src/api.rs"),
+            ("export", "api", "src/lib.rs"),
+            ("export", "run", "src/lib.rs"),
+        ]
+    );
+    assert_eq!(all.entrypoints[4].handler.as_deref(), Some("list_users"));
+
+    // One kind, under a directory
+    let mains = engine
+        .entrypoints(Some(EntrypointKind::Main), "src/", 10)
+        .unwrap();
+    assert_eq!(mains.path, "src");
+    assert_eq!(mains.total_count, 1);
+    assert_eq!(mains.entrypoints[0].file_path, "src/main.rs");
+
+    // The limit applies to each kind, with the counts of all of them
+    let limited = engine.entrypoints(None, ".", 1).unwrap();
+    let kinds: Vec<&str> = limited
+        .entrypoints
+        .iter()
+        .map(|e| e.kind.as_str())
+        .collect();
+    assert_eq!(kinds, ["main", "command", "route", "export"]);
+    assert_eq!(limited.counts["main"], 2);
+    assert_eq!(limited.total_count, 8);
+}
+
+// ==================== Route tests ====================
+
+use semantiq_parser::{HttpRoute, RouteMatch};
+
+#[test]
+fn test_routes_match_parameters_and_handlers() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "src/users.js",
+            "const { createUser } = require('./handlers');\n\nrouter.get('/users/:id', showUser);\nrouter.post('/users', createUser);\n\nfunction showUser(req, res) {}\n",
+        ),
+        (
+            "src/handlers.js",
+            "function createUser(req, res) {\n  res.send('created');\n}\n",
+        ),
+        ("src/health.js", "app.get('/api/health', health);\n"),
+    ]);
+
+    let all = engine.routes(None, None, 10).unwrap();
+    assert_eq!(all.total_count, 3);
+    let routes: Vec<String> = all.routes.iter().map(|r| r.route()).collect();
+    assert_eq!(routes, ["GET /api/health", "POST /users", "GET /users/:id"]);
+    assert!(all.routes.iter().all(|r| r.matched.is_none()));
+
+    // Parameters match any segment; the handler is found in its own file
+    let shown = engine.routes(Some("get"), Some("/users/42"), 10).unwrap();
+    assert_eq!(shown.method.as_deref(), Some("GET"));
+    assert_eq!(shown.total_count, 1);
+    let definition = shown.routes[0].handler_definition.as_ref().unwrap();
+    assert_eq!(definition.file_path, "src/users.js");
+    assert_eq!(definition.start_line, 6);
+
+    // A router mounted under /api only declares the end of the path
+    let created = engine.routes(Some("POST"), Some("/api/users"), 10).unwrap();
+    assert_eq!(created.routes.len(), 1);
+    assert_eq!(created.routes[0].matched, Some(RouteMatch::Suffix));
+    let definition = created.routes[0].handler_definition.as_ref().unwrap();
+    assert_eq!(definition.file_path, "src/handlers.js");
+
+    // Unindexed handlers are left without a definition
+    let health = engine.routes(None, Some("/api/health"), 10).unwrap();
+    assert_eq!(health.routes[0].matched, Some(RouteMatch::Exact));
+    assert!(health.routes[0].handler_definition.is_none());
+
+    assert_eq!(
+        engine
+            .routes(Some("DELETE"), Some("/users/1"), 10)
+            .unwrap()
+            .total_count,
+        0
+    );
+}
+
+#[test]
+fn test_search_route_filter() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "src/users.js",
+            "router.post('/users', createUser);\nrouter.get('/users', listUsers);\n\nfunction createUser(req, res) {}\nfunction listUsers(req, res) {}\n",
+        ),
+        (
+            "src/client.js",
+            "fetch('/api/users', { method: 'POST' });\n",
+        ),
+    ]);
+
+    // The filter only returns the handlers of the matching routes
+    let options = SearchOptions::new().with_route(HttpRoute::new(Some("POST"), "/api/users"));
+    let results = engine.search("users", 10, Some(options)).unwrap();
+    assert_eq!(results.results.len(), 1);
+    let handler = &results.results[0];
+    assert_eq!(handler.metadata.symbol_name.as_deref(), Some("createUser"));
+    assert_eq!(handler.metadata.route.as_deref(), Some("POST /users"));
+    assert_eq!(handler.start_line, 4);
+
+    let missing = SearchOptions::new().with_route(HttpRoute::new(Some("PUT"), "/users"));
+    let results = engine.search("users", 10, Some(missing)).unwrap();
+    assert!(results.is_empty());
+    let hints = results.diagnostics.unwrap().hints;
+    assert!(
+        hints
+            .iter()
+            .any(|h| h.contains("No indexed HTTP route matches PUT /users"))
+    );
+
+    // A route named by the query ranks its handler first
+    let results = engine
+        .search("where is POST /api/users handled", 10, None)
+        .unwrap();
+    assert_eq!(
+        results.results[0].metadata.symbol_name.as_deref(),
+        Some("createUser")
+    );
+}
+
+// ==================== Config usage tests ====================
+
+use semantiq_parser::{ConfigAccess, ConfigSource};
+
+#[test]
+fn test_config_usage_lists_writes_first_with_enclosing_symbols() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "src/db.py",
+            "import os\n\ndef connect():\n    url = os.environ[\"DATABASE_URL\"]\n    return url\n",
+        ),
+        (
+            "src/setup.py",
+            "import os\n\ndef configure():\n    os.environ[\"DATABASE_URL\"] = \"sqlite://\"\n    debug = os.getenv(\"DEBUG\")\n",
+        ),
+    ]);
+
+    let usage = engine.config_usage("DATABASE_URL", None, None, 10).unwrap();
+    assert_eq!((usage.write_count, usage.read_count), (1, 1));
+    let write = &usage.usages[0];
+    assert_eq!(write.access, "write");
+    assert_eq!(write.file_path, "src/setup.py");
+    assert_eq!(write.symbol.as_deref(), Some("configure"));
+    assert_eq!(
+        write.code.as_deref(),
+        Some("os.environ[\"DATABASE_URL\"] = \"sqlite://\"")
+    );
+    assert_eq!(usage.usages[1].symbol.as_deref(), Some("connect"));
+
+    let reads = engine
+        .config_usage("*", Some(ConfigSource::Env), Some(ConfigAccess::Read), 1)
+        .unwrap();
+    assert_eq!(reads.total_count, 2);
+    assert_eq!(reads.usages.len(), 1);
+    assert_eq!(reads.usages[0].name, "DATABASE_URL");
+
+    // Unknown names suggest the indexed ones resembling them
+    let missing = engine.config_usage("database", None, None, 10).unwrap();
+    assert_eq!(missing.total_count, 0);
+    assert_eq!(missing.similar, ["DATABASE_URL"]);
+}
+
+// ==================== Blocking queue tests ====================
+
+#[tokio::test]
+async fn test_run_blocking_goes_through_index_queue() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    store
+        .insert_file("src/lib.rs", Some("rust"), "fn main() {}", 12, 0)
+        .unwrap();
+    let engine =
+        Arc::new(RetrievalEngine::with_options(store, "/nonexistent", false).with_index_queue(1));
+    assert_eq!(engine.async_store().queue_size(), 1);
+
+    let stats = engine
+        .run_blocking(|engine| engine.store.get_stats())
+        .await
+        .unwrap();
+    assert_eq!(stats.file_count, 1);
+    assert_eq!(
+        engine.async_store().get_stats().await.unwrap().file_count,
+        1
+    );
+
+    let failed = engine
+        .run_blocking(|_| -> Result<()> { anyhow::bail!("lookup failed") })
+        .await;
+    assert_eq!(failed.unwrap_err().to_string(), "lookup failed");
+    assert_eq!(engine.async_store().in_flight(), 0);
+}
+
+// ==================== Cross-language link tests ====================
+
+#[test]
+fn test_cross_language_links_from_bindings_and_binding_files() {
+    let (engine, _dir) = engine_with_files(&[
+        (
+            "core/src/lib.rs",
+            "#[wasm_bindgen(js_name = parseConfig)]\npub fn parse_config() {}\n\npub fn get_user() {}\n",
+        ),
+        (
+            "bindings/index.js",
+            "export function getUser(id) { return native.getUser(id); }\n",
+        ),
+        (
+            "web/session.js",
+            "export function parseConfig() {}\nexport function GetUser() {}\n",
+        ),
+    ]);
+
+    // Declared by the binding attribute, both ways
+    let links = engine.cross_language_links("parse_config").unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].name, "parseConfig");
+    assert_eq!(links[0].framework.as_deref(), Some("wasm_bindgen"));
+    assert_eq!(
+        (links[0].file_path.as_str(), links[0].line),
+        ("core/src/lib.rs", 2)
+    );
+    let links = engine.cross_language_links("parseConfig").unwrap();
+    assert_eq!(links[0].name, "parse_config");
+
+    // Inferred from the binding file, not from `GetUser` elsewhere
+    let links = engine.cross_language_links("get_user").unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].name, "getUser");
+    assert_eq!(links[0].framework, None);
+    assert_eq!(links[0].file_path, "bindings/index.js");
+
+    assert!(engine.cross_language_links("unknown").unwrap().is_empty());
+}
+
+// ==================== Index history tests ====================
+
+#[test]
+fn test_find_references_and_explain_as_of_generation() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let mut support = LanguageSupport::new().unwrap();
+    let chunk_extractor = ChunkExtractor::new();
+    let mut write = |path: &str, content: &str| {
+        let extracted = ExtractedFile::extract(
+            &mut support,
+            &chunk_extractor,
+            Language::Rust,
+            Path::new(path),
+            path,
+            content,
+            false,
+        )
+        .unwrap();
+        store
+            .write_file_index(
+                &FileIndexData::new(path, Some("rust"), content, 0, 0).with_extracted(&extracted),
+            )
+            .unwrap();
+        store.index_generation().unwrap()
+    };
+    write("src/lib.rs", "pub fn load() {}\n\npub fn save() {}\n");
+    let before = write("src/main.rs", "fn main() {\n    load();\n    load();\n}\n");
+    write(
+        "src/lib.rs",
+        "pub fn save() {}\n\npub fn load_config(path: &str) {}\n",
+    );
+    let after = write("src/main.rs", "fn main() {\n    load_config(\"x\");\n}\n");
+    let engine = RetrievalEngine::with_options(Arc::clone(&store), "/nonexistent", false);
+
+    let refs = engine
+        .find_references("load", &FindRefsOptions::new().as_of_generation(before))
+        .unwrap();
+    assert_eq!(refs.results.len(), 2);
+    assert_eq!(refs.results[0].start_line, 1);
+    assert_eq!(refs.results[0].content, "pub fn load() {}");
+    // Usages are counted per file, their lines not being kept
+    assert_eq!(refs.results[1].file_path, "src/main.rs");
+    assert_eq!(refs.results[1].start_line, 0);
+    assert_eq!(refs.results[1].content, "2 references");
+    assert!(
+        engine
+            .find_references("load", &FindRefsOptions::new().as_of_generation(after))
+            .unwrap()
+            .results
+            .is_empty()
+    );
+
+    let explanation = engine.explain_symbol_as_of("load", before).unwrap();
+    assert!(explanation.found);
+    assert_eq!(explanation.as_of, Some(before));
+    assert_eq!(
+        explanation.definitions[0].signature.as_deref(),
+        Some("pub fn load() {}")
+    );
+    assert_eq!((explanation.usage_count, explanation.usage_files), (2, 1));
+    assert_eq!(explanation.related_symbols, ["save"]);
+    assert!(!engine.explain_symbol_as_of("load", after).unwrap().found);
+
+    // `save` only moved: one version, with the lines of each generation
+    let explanation = engine.explain_symbol_as_of("save", before).unwrap();
+    assert_eq!(explanation.definitions[0].start_line, 3);
+    assert_eq!(explanation.related_symbols, ["load"]);
+    let explanation = engine.explain_symbol_as_of("save", after).unwrap();
+    assert_eq!(explanation.definitions[0].start_line, 1);
+    assert_eq!(explanation.related_symbols, ["load_config"]);
+
+    let future = engine.explain_symbol_as_of("save", after + 1).unwrap_err();
+    assert!(matches!(
+        future.downcast_ref::<HistoryError>(),
+        Some(HistoryError::FutureGeneration { .. })
+    ));
+
+    store.set_symbol_history_retention(Some(1)).unwrap();
+    let pruned = engine.explain_symbol_as_of("save", before).unwrap_err();
+    assert!(matches!(
+        pruned.downcast_ref::<HistoryError>(),
+        Some(HistoryError::PrunedGeneration { .. })
+    ));
+}
+
+// ==================== C/C++ include tests ====================
+
+use semantiq_index::IncludesConfig;
+
+#[test]
+fn test_c_dependents_follow_includes_and_header_pairs() {
+    let store = Arc::new(IndexStore::open_in_memory().unwrap());
+    let files: &[(&str, &[(&str, &str)])] = &[
+        ("include/net/socket.h", &[]),
+        ("src/net/socket.c", &[("net/socket.h", "local")]),
+        (
+            "src/main.c",
+            &[("net/socket.h", "local"), ("stdio.h", "std")],
+        ),
+        ("src/legacy/socket.h", &[]),
+        ("src/legacy/old.c", &[("socket.h", "local")]),
+    ];
+    for (path, includes) in files {
+        let file_id = store.insert_file(path, Some("c"), "", 0, 0).unwrap();
+        for (include, kind) in *includes {
+            store
+                .insert_dependency(file_id, include, None, kind)
+                .unwrap();
+        }
+    }
+    let engine = RetrievalEngine::with_options(store, "/nonexistent", false).with_includes_config(
+        &IncludesConfig {
+            paths: vec!["include".into()],
+            compile_commands: None,
+        },
+    );
+
+    let imports = engine.get_dependencies("src/main.c").unwrap();
+    assert_eq!(
+        imports[0].resolved_path.as_deref(),
+        Some("include/net/socket.h")
+    );
+    assert_eq!(imports[1].resolved_path, None);
+
+    // src/legacy/old.c includes the other socket.h
+    let dependents = |path: &str| -> Vec<(String, Option<String>)> {
+        engine
+            .get_dependents(path)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.target_path, d.via))
+            .collect()
+    };
+    assert_eq!(
+        dependents("include/net/socket.h"),
+        [
+            ("src/net/socket.c".to_string(), None),
+            ("src/main.c".to_string(), None),
+        ]
+    );
+    assert_eq!(
+        dependents("src/net/socket.c"),
+        [(
+            "src/main.c".to_string(),
+            Some("include/net/socket.h".to_string())
+        )]
+    );
+    assert_eq!(
+        dependents("src/legacy/socket.h"),
+        [("src/legacy/old.c".to_string(), None)]
+    );
+}
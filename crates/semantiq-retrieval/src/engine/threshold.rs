@@ -2,12 +2,16 @@
 
 use super::RetrievalEngine;
 use crate::threshold::{
-    CalibrationConfig, Confidence, LanguageThresholds, ThresholdCalibrator, ThresholdConfig,
+    BOOTSTRAP_SAMPLE_SIZE, CalibrationConfig, Confidence, LanguageThresholds, ThresholdCalibrator,
+    ThresholdConfig, bootstrap_thresholds, save_calibrations,
 };
 use anyhow::Result;
-use semantiq_index::{CalibrationData, IndexStore};
+use semantiq_index::{IndexStore, RecalibrationConfig, unix_now};
 use tracing::{debug, info, warn};
 
+/// Observations of a language needed to calibrate its thresholds
+const AUTO_CALIBRATION_MIN_SAMPLES: usize = 50;
+
 impl RetrievalEngine {
     /// Default minimum similarity threshold for semantic search results.
     pub const SEMANTIC_MIN_SIMILARITY: f32 = 0.3;
@@ -61,6 +65,10 @@ impl RetrievalEngine {
         if let Ok(mut config) = self.threshold_config.write() {
             *config = new_config;
         }
+        // Cached scores were filtered with the previous thresholds
+        if let Some(cache) = &self.query_cache {
+            cache.clear(&self.store);
+        }
     }
 
     /// Get thresholds for a specific language using the fallback cascade.
@@ -116,49 +124,13 @@ impl RetrievalEngine {
         );
 
         let calibrator = ThresholdCalibrator::with_config(CalibrationConfig {
-            min_samples: 50,
+            min_samples: AUTO_CALIBRATION_MIN_SAMPLES,
             ..Default::default()
         });
 
         let config = calibrator.calibrate_all(&all_observations);
 
-        // Save per-language calibrations
-        for (language, thresholds) in &config.per_language {
-            if thresholds.sample_count >= 50 {
-                let stats = thresholds.stats.as_ref();
-                let data = CalibrationData {
-                    language: language.clone(),
-                    max_distance: thresholds.max_distance,
-                    min_similarity: thresholds.min_similarity,
-                    confidence: thresholds.confidence.to_string(),
-                    sample_count: thresholds.sample_count,
-                    p50_distance: stats.map(|s| s.p50),
-                    p90_distance: stats.map(|s| s.p90),
-                    p95_distance: stats.map(|s| s.p95),
-                    mean_distance: stats.map(|s| s.mean),
-                    std_distance: stats.map(|s| s.std_dev),
-                };
-                self.store.save_calibration(&data)?;
-            }
-        }
-
-        // Save global calibration
-        if config.global.sample_count >= 50 {
-            let stats = config.global.stats.as_ref();
-            let data = CalibrationData {
-                language: "_global".to_string(),
-                max_distance: config.global.max_distance,
-                min_similarity: config.global.min_similarity,
-                confidence: config.global.confidence.to_string(),
-                sample_count: config.global.sample_count,
-                p50_distance: stats.map(|s| s.p50),
-                p90_distance: stats.map(|s| s.p90),
-                p95_distance: stats.map(|s| s.p95),
-                mean_distance: stats.map(|s| s.mean),
-                std_distance: stats.map(|s| s.std_dev),
-            };
-            self.store.save_calibration(&data)?;
-        }
+        save_calibrations(&self.store, &config, AUTO_CALIBRATION_MIN_SAMPLES)?;
 
         self.reload_thresholds();
 
@@ -171,6 +143,96 @@ impl RetrievalEngine {
         Ok(true)
     }
 
+    /// Calibrate the thresholds again from the observed distances once
+    /// `observation_delta` observations were recorded beyond the samples of
+    /// the last calibration, or once it is older than `max_age_days`.
+    /// Observations older than `max_age_days` are dropped first. The new
+    /// thresholds replace the previous ones at once, both being logged.
+    /// Returns whether thresholds were recalibrated.
+    pub fn recalibrate_if_due(&self, recalibration: &RecalibrationConfig) -> Result<bool> {
+        self.flush_observations()?;
+        if let Some(max_age_secs) = recalibration.max_age_secs() {
+            let dropped = self.store.cleanup_old_observations(max_age_secs)?;
+            if dropped > 0 {
+                info!("Dropped {} expired distance observations", dropped);
+            }
+        }
+
+        let observations: usize = self.store.get_observation_counts()?.values().sum();
+        if observations < AUTO_CALIBRATION_MIN_SAMPLES {
+            return Ok(false);
+        }
+
+        let before = match self.threshold_config.read() {
+            Ok(config) => config.clone(),
+            Err(_) => return Ok(false),
+        };
+        let now = unix_now();
+        let due = match before.calibrated_at {
+            None => true,
+            Some(calibrated_at) => {
+                observations.saturating_sub(before.global.sample_count)
+                    >= recalibration.observation_delta
+                    || recalibration
+                        .max_age_secs()
+                        .is_some_and(|max_age| now - calibrated_at >= max_age)
+            }
+        };
+        if !due {
+            debug!(
+                observations,
+                calibrated_samples = before.global.sample_count,
+                "Threshold recalibration not due"
+            );
+            return Ok(false);
+        }
+
+        info!(observations, "Recalibrating search thresholds");
+        if !self.auto_calibrate()? {
+            return Ok(false);
+        }
+
+        let after = match self.threshold_config.read() {
+            Ok(config) => config.clone(),
+            Err(_) => return Ok(true),
+        };
+        log_threshold_change("_global", Some(&before.global), Some(&after.global));
+        let mut languages: Vec<&String> = before
+            .per_language
+            .keys()
+            .chain(after.per_language.keys())
+            .collect();
+        languages.sort();
+        languages.dedup();
+        for language in languages {
+            log_threshold_change(
+                language,
+                before.per_language.get(language),
+                after.per_language.get(language),
+            );
+        }
+
+        Ok(true)
+    }
+
+    /// Seed thresholds from the contents of the index (see [`bootstrap_thresholds`])
+    /// and reload them. Returns whether thresholds were calibrated.
+    pub fn bootstrap_thresholds(&self) -> Result<bool> {
+        let calibrated = bootstrap_thresholds(&self.store, BOOTSTRAP_SAMPLE_SIZE)?.is_some();
+        if calibrated {
+            self.reload_thresholds();
+        }
+        Ok(calibrated)
+    }
+
+    /// Whether calibrated thresholds (live or bootstrapped) are loaded.
+    pub fn has_calibrated_thresholds(&self) -> bool {
+        self.threshold_config
+            .read()
+            .map(|config| config.is_calibrated())
+            .unwrap_or(false)
+    }
+
     /// Check if auto-calibration should be triggered and perform it if needed.
     pub(crate) fn maybe_auto_calibrate(&self) {
         let collector = match &self.distance_collector {
@@ -202,3 +264,21 @@ impl RetrievalEngine {
         self.maybe_auto_calibrate();
     }
 }
+
+/// Log the thresholds of a language before and after a recalibration
+fn log_threshold_change(
+    language: &str,
+    before: Option<&LanguageThresholds>,
+    after: Option<&LanguageThresholds>,
+) {
+    info!(
+        language,
+        before_max_distance = before.map(|t| t.max_distance),
+        after_max_distance = after.map(|t| t.max_distance),
+        before_min_similarity = before.map(|t| t.min_similarity),
+        after_min_similarity = after.map(|t| t.min_similarity),
+        before_samples = before.map(|t| t.sample_count),
+        after_samples = after.map(|t| t.sample_count),
+        "Recalibrated search thresholds"
+    );
+}
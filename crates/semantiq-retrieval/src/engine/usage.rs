@@ -0,0 +1,227 @@
+//! Symbol usage statistics and dead-code candidates for RetrievalEngine.
+
+use super::RetrievalEngine;
+use crate::query::FindRefsOptions;
+use crate::results::UnusedSymbolsResponse;
+use anyhow::Result;
+use semantiq_index::UnreferencedSymbol;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tracing::info;
+
+/// Symbol kinds checked for references. Variables, modules and imports are
+/// left out: they are mostly used where they are declared.
+const ANALYZED_KINDS: &[&str] = &[
+    "function",
+    "method",
+    "class",
+    "struct",
+    "enum",
+    "interface",
+    "trait",
+    "type",
+    "constant",
+];
+
+/// Names called by the runtime rather than by project code
+const ENTRY_POINTS: &[&str] = &["main", "init"];
+
+/// How much an unused candidate of a language can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageConfidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl UsageConfidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageConfidence::Low => "low",
+            UsageConfidence::Medium => "medium",
+            UsageConfidence::High => "high",
+        }
+    }
+}
+
+/// An exported symbol whose name no other file references
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnusedSymbol {
+    pub name: String,
+    pub kind: String,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub signature: Option<String>,
+    pub language: String,
+    pub confidence: UsageConfidence,
+    /// References in its own file: the symbol is used but need not be exported
+    pub local_references: usize,
+}
+
+/// Why candidates of a language may still be used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageCaveat {
+    pub language: String,
+    pub confidence: UsageConfidence,
+    pub caveat: String,
+}
+
+/// Confidence and caveat of the analysis for a language, `None` for
+/// languages whose symbols are not analyzed (SQL, data files).
+pub(crate) fn language_caveat(language: &str) -> Option<(UsageConfidence, &'static str)> {
+    let caveat = match language {
+        "rust" => (
+            UsageConfidence::High,
+            "Names used only inside macro invocations are counted, names built by macros are not",
+        ),
+        "go" => (
+            UsageConfidence::High,
+            "Methods satisfying an interface may only be called through it",
+        ),
+        "java" | "csharp" | "kotlin" | "scala" => (
+            UsageConfidence::Medium,
+            "Reflection and dependency injection use symbols without naming them; annotated declarations are skipped",
+        ),
+        "typescript" => (
+            UsageConfidence::Medium,
+            "Re-exports count as references, and dynamic property access is not tracked",
+        ),
+        "c" | "cpp" => (
+            UsageConfidence::Medium,
+            "Names built by macros are not tracked, and a declaration in a header counts as a definition",
+        ),
+        "javascript" | "python" | "ruby" | "php" | "elixir" | "bash" => (
+            UsageConfidence::Low,
+            "Dynamic dispatch, metaprogramming and lookups by string are not tracked",
+        ),
+        _ => return None,
+    };
+    Some(caveat)
+}
+
+/// Whether a symbol is visible outside its file, from the language's
+/// visibility rules. `line` is the source line the symbol starts on.
+pub(crate) fn is_exported(
+    candidate: &UnreferencedSymbol,
+    language: &str,
+    line: Option<&str>,
+) -> bool {
+    let symbol = &candidate.symbol;
+    let signature = symbol.signature.as_deref().unwrap_or("").trim_start();
+    let private = signature.starts_with("private") || signature.contains(" private ");
+
+    match language {
+        "rust" => signature.starts_with("pub"),
+        "go" => symbol.name.starts_with(|c: char| c.is_ascii_uppercase()),
+        "java" | "csharp" | "kotlin" | "scala" => {
+            // Annotations and attributes usually mean a framework calls it
+            !private && !signature.starts_with('@') && !signature.starts_with('[')
+        }
+        "typescript" | "javascript" => {
+            if symbol.parent.is_some() {
+                !private && !symbol.name.starts_with('#')
+            } else {
+                line.is_some_and(|l| l.trim_start().starts_with("export"))
+            }
+        }
+        "python" => !symbol.name.starts_with('_'),
+        "c" | "cpp" => !signature.starts_with("static"),
+        "php" => !private,
+        "elixir" => !signature.starts_with("defp"),
+        _ => true,
+    }
+}
+
+impl RetrievalEngine {
+    /// Find exported symbols whose name is referenced in no other file.
+    ///
+    /// References are identifier occurrences matched by name, so a symbol
+    /// sharing its name with a used one is never reported. Each candidate
+    /// carries the confidence of its language, and the response lists the
+    /// caveats of every language involved.
+    pub fn find_unused_symbols(
+        &self,
+        path_prefix: Option<&str>,
+        include_tests: bool,
+        limit: usize,
+    ) -> Result<UnusedSymbolsResponse> {
+        info!(path_prefix = ?path_prefix, include_tests, limit, "Finding unused symbols");
+        let start = Instant::now();
+
+        let options = FindRefsOptions::new()
+            .with_path_prefix(path_prefix.unwrap_or(""))
+            .excluding_tests(!include_tests);
+
+        let mut symbols = Vec::new();
+        let mut caveats = BTreeMap::new();
+        // Candidates come ordered by file: keep the lines of the current one
+        let mut file_lines: Option<(String, Vec<String>)> = None;
+
+        for candidate in self.store.find_unreferenced_symbols(ANALYZED_KINDS)? {
+            if !options.accepts_path(&candidate.file_path)
+                || ENTRY_POINTS.contains(&candidate.symbol.name.as_str())
+            {
+                continue;
+            }
+            let language = candidate.language.clone().unwrap_or_default();
+            let Some((confidence, caveat)) = language_caveat(&language) else {
+                continue;
+            };
+
+            // TypeScript and JavaScript exports are on the line, not the node
+            let line = if matches!(language.as_str(), "typescript" | "javascript")
+                && candidate.symbol.parent.is_none()
+            {
+                if file_lines.as_ref().map(|(path, _)| path) != Some(&candidate.file_path) {
+                    let content = self
+                        .read_file_lines(&candidate.file_path, 1, usize::MAX)
+                        .unwrap_or_default();
+                    file_lines = Some((
+                        candidate.file_path.clone(),
+                        content.lines().map(str::to_string).collect(),
+                    ));
+                }
+                file_lines.as_ref().and_then(|(_, lines)| {
+                    lines
+                        .get((candidate.symbol.start_line as usize).saturating_sub(1))
+                        .map(String::as_str)
+                })
+            } else {
+                None
+            };
+            if !is_exported(&candidate, &language, line) {
+                continue;
+            }
+
+            caveats.entry(language.clone()).or_insert(LanguageCaveat {
+                language: language.clone(),
+                confidence,
+                caveat: caveat.to_string(),
+            });
+            symbols.push(UnusedSymbol {
+                name: candidate.symbol.name,
+                kind: candidate.symbol.kind,
+                file_path: candidate.file_path,
+                start_line: candidate.symbol.start_line as usize,
+                end_line: candidate.symbol.end_line as usize,
+                signature: candidate.symbol.signature,
+                language,
+                confidence,
+                local_references: candidate.local_references,
+            });
+        }
+
+        let total_count = symbols.len();
+        symbols.truncate(limit);
+
+        Ok(UnusedSymbolsResponse {
+            total_count,
+            search_time_ms: start.elapsed().as_millis() as u64,
+            symbols,
+            caveats: caveats.into_values().collect(),
+        })
+    }
+}
@@ -0,0 +1,309 @@
+//! Retrieval evaluation against golden queries.
+//!
+//! Each directory of `fixtures/eval` holds a small sample project in one
+//! language (`project/`) and the results its queries must find
+//! (`golden.yaml`):
+//!
+//! ```yaml
+//! language: rust
+//! k: 5
+//! min_recall: 0.8
+//! min_precision: 0.5
+//! cases:
+//!   - query: "load the configuration file"
+//!     expected:
+//!       - file: src/config.rs
+//!         symbol: load_config
+//! ```
+//!
+//! Cases are written like those of a [`crate::bench`] suite. The project is
+//! indexed by the same indexer as `semantiq serve`, into an in-memory index,
+//! and every query goes through the full search pipeline. Recall is the
+//! share of expected results found in the top k, precision the share of the
+//! top k results that were expected; both are averaged over the cases and
+//! checked against the minimums of the golden set by `cargo test`, so that a
+//! parser or ranking change that degrades retrieval fails the build.
+//!
+//! Tests are built without the ONNX model, so golden sets are tuned for
+//! symbol, text and BM25 search: a case that only semantic search can answer
+//! belongs in a benchmark suite instead.
+
+use crate::bench::BenchCase;
+use crate::engine::RetrievalEngine;
+use anyhow::{Context, Result, bail};
+use semantiq_index::{AutoIndexer, IndexStore};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Number of results scored per query when the golden set does not set it
+pub const DEFAULT_EVAL_K: usize = 5;
+
+/// Golden queries of a sample project and the quality they must reach
+#[derive(Debug, Clone, Deserialize)]
+pub struct GoldenSet {
+    pub language: String,
+    /// Number of results scored per query
+    #[serde(default = "default_k")]
+    pub k: usize,
+    /// Mean recall below which the evaluation fails
+    #[serde(default)]
+    pub min_recall: f64,
+    /// Mean precision below which the evaluation fails
+    #[serde(default)]
+    pub min_precision: f64,
+    pub cases: Vec<BenchCase>,
+}
+
+fn default_k() -> usize {
+    DEFAULT_EVAL_K
+}
+
+impl GoldenSet {
+    /// Load a golden set from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read golden set {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("Invalid golden set {:?}", path))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let golden: Self = serde_yaml::from_str(content)?;
+        if golden.k == 0 {
+            bail!("k must be at least 1");
+        }
+        for (name, value) in [
+            ("min_recall", golden.min_recall),
+            ("min_precision", golden.min_precision),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                bail!("{} must be between 0 and 1, got {}", name, value);
+            }
+        }
+        if golden.cases.is_empty() {
+            bail!("The golden set has no cases");
+        }
+        for case in &golden.cases {
+            if case.expected.is_empty()
+                || case
+                    .expected
+                    .iter()
+                    .any(|e| e.file.is_none() && e.symbol.is_none())
+            {
+                bail!(
+                    "Case '{}' needs expected results with a file or a symbol",
+                    case.query
+                );
+            }
+        }
+        Ok(golden)
+    }
+}
+
+/// Outcome of one golden query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalCase {
+    pub query: String,
+    /// Share of the expected results found in the top k
+    pub recall: f64,
+    /// Share of the top k results that were expected, 1 when there are none
+    pub precision: f64,
+    /// Expected results missing from the top k, as `file#symbol`
+    pub missed: Vec<String>,
+}
+
+/// Quality of the search pipeline on a golden set
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalReport {
+    pub language: String,
+    pub k: usize,
+    pub recall: f64,
+    pub precision: f64,
+    pub min_recall: f64,
+    pub min_precision: f64,
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalReport {
+    fn new(golden: &GoldenSet, cases: Vec<EvalCase>) -> Self {
+        let mean = |metric: fn(&EvalCase) -> f64| {
+            cases.iter().map(metric).sum::<f64>() / cases.len().max(1) as f64
+        };
+        Self {
+            language: golden.language.clone(),
+            k: golden.k,
+            recall: mean(|c| c.recall),
+            precision: mean(|c| c.precision),
+            min_recall: golden.min_recall,
+            min_precision: golden.min_precision,
+            cases,
+        }
+    }
+
+    /// Metrics below the minimums of the golden set, with the queries that
+    /// missed expected results
+    pub fn failures(&self) -> Vec<String> {
+        let mut failures = Vec::new();
+        for (metric, value, min) in [
+            ("recall", self.recall, self.min_recall),
+            ("precision", self.precision, self.min_precision),
+        ] {
+            if value < min {
+                failures.push(format!(
+                    "{}: {}@{} is {:.3}, below {:.3}",
+                    self.language, metric, self.k, value, min
+                ));
+            }
+        }
+        if !failures.is_empty() {
+            for case in self.cases.iter().filter(|c| !c.missed.is_empty()) {
+                failures.push(format!(
+                    "{}: '{}' missed {}",
+                    self.language,
+                    case.query,
+                    case.missed.join(", ")
+                ));
+            }
+        }
+        failures
+    }
+}
+
+/// Run every golden query through the search pipeline of `engine`
+pub fn evaluate(engine: &RetrievalEngine, golden: &GoldenSet) -> Result<EvalReport> {
+    let mut cases = Vec::with_capacity(golden.cases.len());
+    for case in &golden.cases {
+        let results = engine
+            .search(&case.query, golden.k, None)
+            .with_context(|| format!("Search failed for '{}'", case.query))?;
+        let top: Vec<_> = results.results.iter().take(golden.k).collect();
+
+        let missed: Vec<String> = case
+            .expected
+            .iter()
+            .filter(|e| !top.iter().any(|r| e.matches(r)))
+            .map(|e| {
+                format!(
+                    "{}#{}",
+                    e.file.as_deref().unwrap_or("*"),
+                    e.symbol.as_deref().unwrap_or("*")
+                )
+            })
+            .collect();
+        let relevant = top
+            .iter()
+            .filter(|r| case.expected.iter().any(|e| e.matches(r)))
+            .count();
+
+        cases.push(EvalCase {
+            query: case.query.clone(),
+            recall: 1.0 - missed.len() as f64 / case.expected.len() as f64,
+            precision: if top.is_empty() {
+                1.0
+            } else {
+                relevant as f64 / top.len() as f64
+            },
+            missed,
+        });
+    }
+    Ok(EvalReport::new(golden, cases))
+}
+
+/// Directory of the sample projects shipped with this crate
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/eval")
+}
+
+/// Index the sample project of `fixture` (a directory holding `project/`
+/// and `golden.yaml`) and evaluate its golden set
+pub fn evaluate_fixture(fixture: &Path) -> Result<EvalReport> {
+    let golden = GoldenSet::load(&fixture.join("golden.yaml"))?;
+    let project = fixture.join("project");
+
+    let store = Arc::new(IndexStore::open_in_memory()?);
+    let indexed = AutoIndexer::new(Arc::clone(&store), project.clone())?.initial_index()?;
+    if indexed.errors > 0 {
+        bail!("{} files of {:?} failed to index", indexed.errors, project);
+    }
+
+    let root = project.to_string_lossy();
+    let engine = RetrievalEngine::with_options(store, &root, false);
+    evaluate(&engine, &golden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_golden_set() {
+        let golden = GoldenSet::parse(
+            "language: rust\nmin_recall: 0.8\ncases:\n  - query: load config\n    expected:\n      - symbol: load_config\n",
+        )
+        .unwrap();
+        assert_eq!(golden.k, DEFAULT_EVAL_K);
+        assert_eq!(golden.min_precision, 0.0);
+
+        assert!(GoldenSet::parse("language: rust\ncases: []\n").is_err());
+        assert!(
+            GoldenSet::parse(
+                "language: rust\nmin_recall: 80\ncases:\n  - query: q\n    expected:\n      - symbol: s\n"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_report_failures() {
+        let golden = GoldenSet::parse(
+            "language: go\nmin_recall: 0.75\nmin_precision: 0.2\ncases:\n  - query: q\n    expected:\n      - symbol: s\n",
+        )
+        .unwrap();
+        let case = |query: &str, recall: f64, missed: &[&str]| EvalCase {
+            query: query.to_string(),
+            recall,
+            precision: 0.5,
+            missed: missed.iter().map(|m| m.to_string()).collect(),
+        };
+
+        let passing = EvalReport::new(
+            &golden,
+            vec![case("a", 1.0, &[]), case("b", 0.5, &["*#Open"])],
+        );
+        assert_eq!(passing.recall, 0.75);
+        assert!(passing.failures().is_empty());
+
+        let failing = EvalReport::new(&golden, vec![case("a", 0.5, &["db.go#Open"])]);
+        assert_eq!(
+            failing.failures(),
+            [
+                "go: recall@5 is 0.500, below 0.750",
+                "go: 'a' missed db.go#Open"
+            ]
+        );
+    }
+
+    /// The golden queries of every sample project
+    #[test]
+    fn test_golden_queries() {
+        let mut fixtures: Vec<PathBuf> = std::fs::read_dir(fixtures_dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_dir())
+            .collect();
+        fixtures.sort();
+        assert!(!fixtures.is_empty());
+
+        let mut failures = Vec::new();
+        for fixture in &fixtures {
+            let report = evaluate_fixture(fixture)
+                .unwrap_or_else(|e| panic!("Evaluation of {:?} failed: {:#}", fixture, e));
+            failures.extend(report.failures());
+        }
+        assert!(
+            failures.is_empty(),
+            "Retrieval quality dropped:\n{}",
+            failures.join("\n")
+        );
+    }
+}
@@ -1,15 +1,44 @@
+pub mod bench;
+pub mod cache;
+pub mod deadline;
 pub mod engine;
+pub mod eval;
+pub mod normalize;
 pub mod query;
 pub mod results;
+pub mod session;
 pub mod text_searcher;
 pub mod threshold;
 
-pub use engine::{DependencyInfo, RetrievalEngine, SymbolDefinition, SymbolExplanation};
-pub use query::{Query, QueryExpander, SearchOptions};
-pub use results::{SearchResult, SearchResultKind};
+pub use bench::{BenchReport, BenchSuite};
+pub use cache::{QueryCache, RefsCache};
+pub use deadline::Deadline;
+pub use engine::{
+    ChangeKind, Compilation, ComponentNode, ConfigUsage, CrossLanguageLink, DependencyInfo,
+    DependencyScope, DiffError, EntrypointLocation, ExclusionReason, ExploreEntry, FileDiff,
+    HandlerDefinition, HistoryError, Implementation, LanguageCaveat, RelatedFile, RelatedReason,
+    RelatedSignal, RetrievalEngine, RouteLocation, SymbolChange, SymbolDefinition,
+    SymbolDependency, SymbolExplanation, UnusedSymbol, UsageConfidence,
+};
+pub use eval::{EvalReport, GoldenSet};
+pub use query::{
+    DedupPolicy, FindRefsOptions, QualifiedName, Query, QueryExpander, QueryShape, RefKind,
+    SearchOptions, SearchScope, SearchStrategy,
+};
+pub use results::{
+    BatchSearchEntry, BatchSearchResponse, ComponentTreeResponse, ConfigUsageResponse,
+    DependenciesResponse, EnclosingSymbol, EntrypointsResponse, ExploreResponse, FilteredMatches,
+    Freshness, Highlight, ImplementationsResponse, IndexIssuesResponse, LineRange, NearMiss,
+    OutputFormat, OverviewResponse, ReferencesResponse, RelatedFilesResponse, RoutesResponse,
+    ScoreBoost, ScoreExplanation, ScoreThresholds, SearchDiagnostics, SearchResult,
+    SearchResultKind, SearchResultMetadata, SearchResults, SemanticDiffResponse, SessionQuery,
+    SessionSummary, Snippet, SnippetBuilder, Span, TagFacet, TermMatch, ToMarkdown,
+    UnusedSymbolsResponse, ViewedFile,
+};
+pub use session::SearchSessions;
 pub use text_searcher::TextSearcher;
 pub use threshold::{
-    CalibrationConfig, CalibrationResult, CollectorConfig, Confidence, DistanceCollector,
-    DistanceObservation, DistanceStats, LanguageThresholds, ThresholdCalibrator, ThresholdConfig,
-    format_calibration_summary,
+    BOOTSTRAP_SAMPLE_SIZE, CalibrationConfig, CalibrationResult, CollectorConfig, Confidence,
+    DistanceCollector, DistanceObservation, DistanceStats, LanguageThresholds, ThresholdCalibrator,
+    ThresholdConfig, bootstrap_thresholds, format_calibration_summary,
 };
@@ -0,0 +1,127 @@
+//! Normalization of non-English query text.
+//!
+//! Identifiers are almost always ASCII even when comments are not, so a
+//! French query such as "créer l'utilisateur" cannot match `creer_utilisateur`
+//! lexically. These helpers normalize typography and transliterate accented
+//! Latin letters so that symbol and text search can find such identifiers.
+
+/// Normalize the typography of a query.
+///
+/// Curly quotes and apostrophes become their ASCII counterparts, non-breaking
+/// and other Unicode spaces become plain spaces, and runs of whitespace collapse.
+pub fn normalize_query(text: &str) -> String {
+    let mapped: String = text
+        .chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201B}' | '\u{02BC}' | '\u{2032}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{00AB}' | '\u{00BB}' => '"',
+            '\u{2010}' | '\u{2011}' | '\u{2013}' | '\u{2014}' => '-',
+            c if c.is_whitespace() => ' ',
+            c => c,
+        })
+        .collect();
+    mapped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Transliterate accented Latin letters to ASCII (`é` → `e`, `ç` → `c`,
+/// `œ` → `oe`, `ß` → `ss`). Other characters are kept as is.
+pub fn transliterate(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match fold_char(c) {
+            Some(folded) => output.push_str(folded),
+            None => output.push(c),
+        }
+    }
+    output
+}
+
+/// Strip an elided article or pronoun from a word (`l'utilisateur` → `utilisateur`,
+/// `d'accès` → `accès`), as found in French and Italian.
+pub fn strip_elision(word: &str) -> &str {
+    const ELISIONS: &[&str] = &[
+        "l'", "d'", "j'", "m'", "n'", "s'", "t'", "c'", "qu'", "jusqu'", "lorsqu'", "puisqu'",
+        "all'", "dell'", "nell'", "un'",
+    ];
+
+    let lower = word.to_lowercase();
+    ELISIONS
+        .iter()
+        .find(|prefix| lower.starts_with(*prefix) && lower.len() > prefix.len())
+        .and_then(|prefix| word.get(prefix.len()..))
+        .unwrap_or(word)
+}
+
+fn fold_char(c: char) -> Option<&'static str> {
+    let folded = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ď' | 'đ' => "d",
+        'Ď' | 'Đ' => "D",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ł' | 'ĺ' | 'ļ' | 'ľ' => "l",
+        'Ł' | 'Ĺ' | 'Ļ' | 'Ľ' => "L",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ŕ' | 'ř' => "r",
+        'Ŕ' | 'Ř' => "R",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ß' => "ss",
+        'ţ' | 'ť' => "t",
+        'Ţ' | 'Ť' => "T",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' | 'Ÿ' => "Y",
+        'ź' | 'ż' | 'ž' => "z",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("  où est créé\u{00A0}l\u{2019}utilisateur\t?"),
+            "où est créé l'utilisateur ?"
+        );
+        assert_eq!(normalize_query("«\u{202F}test\u{202F}»"), "\" test \"");
+    }
+
+    #[test]
+    fn test_transliterate() {
+        assert_eq!(transliterate("créer_utilisateur"), "creer_utilisateur");
+        assert_eq!(transliterate("Événement façade"), "Evenement facade");
+        assert_eq!(transliterate("cœur Straße"), "coeur Strasse");
+        assert_eq!(transliterate("getUser"), "getUser");
+        assert_eq!(transliterate("日本語"), "日本語");
+    }
+
+    #[test]
+    fn test_strip_elision() {
+        assert_eq!(strip_elision("l'utilisateur"), "utilisateur");
+        assert_eq!(strip_elision("D'accès"), "accès");
+        assert_eq!(strip_elision("qu'il"), "il");
+        assert_eq!(strip_elision("l'"), "l'");
+        assert_eq!(strip_elision("user"), "user");
+    }
+}
@@ -1,3 +1,8 @@
+use crate::deadline::Deadline;
+use crate::normalize::{normalize_query, strip_elision, transliterate};
+use semantiq_index::is_external_path;
+use semantiq_parser::keywords::normalize_term;
+use semantiq_parser::{HttpRoute, Language, SymbolQualifiers};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -5,6 +10,15 @@ pub struct Query {
     pub text: String,
     pub expanded_terms: Vec<String>,
     pub filters: QueryFilters,
+    /// Detected shape of the query text, used to pick search strategies
+    #[serde(default)]
+    pub shape: QueryShape,
+    /// Module or parent qualifying a symbol name, e.g. `auth::login`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified: Option<QualifiedName>,
+    /// HTTP route named by the query, e.g. `where is POST /api/users handled`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<HttpRoute>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -13,17 +27,41 @@ pub struct QueryFilters {
     pub file_patterns: Vec<String>,
     pub symbol_kinds: Vec<String>,
     pub include_tests: bool,
+    /// Keep only results from files with all these tags (`tags:` in the query)
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Query {
+    /// Parse a query, taking `tags:a,b` (or `tag:a`) tokens out of its text
+    /// as a tag filter. A query made only of tags searches for the tags.
     pub fn new(text: &str) -> Self {
+        let (text, tags) = split_tag_filter(text);
+        let text = if text.trim().is_empty() {
+            normalize_query(&tags.join(" "))
+        } else {
+            normalize_query(&text)
+        };
         let expander = QueryExpander::new();
-        let expanded_terms = expander.expand(text);
+        let mut expanded_terms = expander.expand(&text);
+        let qualified = QualifiedName::parse(&text);
+        // Symbols are indexed by their bare name
+        if let Some(ref qualified) = qualified
+            && !expanded_terms.contains(&qualified.name)
+        {
+            expanded_terms.insert(0, qualified.name.clone());
+        }
 
         Self {
-            text: text.to_string(),
+            shape: QueryShape::classify(&text),
+            qualified,
+            route: HttpRoute::find(&text),
+            text,
             expanded_terms,
-            filters: QueryFilters::default(),
+            filters: QueryFilters {
+                tags,
+                ..QueryFilters::default()
+            },
         }
     }
 
@@ -37,6 +75,270 @@ impl Query {
         terms.extend(self.expanded_terms.iter().map(|s| s.as_str()));
         terms
     }
+
+    /// Lowercased words of the query text, without stopwords, e.g. to match
+    /// documentation
+    pub fn content_words(&self) -> Vec<String> {
+        let mut words: Vec<String> = Vec::new();
+        for word in self
+            .text
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+        {
+            if word.chars().count() > 1
+                && !QueryShape::STOPWORDS.contains(&word.as_str())
+                && !words.contains(&word)
+            {
+                words.push(word);
+            }
+        }
+        words
+    }
+}
+
+/// A symbol name qualified by its module or parent symbol, e.g.
+/// `auth::login` or `UserService.create`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualifiedName {
+    /// Segments of the qualifier, e.g. `["auth"]` for `auth::login`
+    pub qualifier: Vec<String>,
+    pub name: String,
+}
+
+impl QualifiedName {
+    /// Parse a single identifier qualified with `::` or `.`. File paths such
+    /// as `config.toml` are not qualified names.
+    pub fn parse(text: &str) -> Option<Self> {
+        let token = text.trim().trim_end_matches("()");
+        if token.contains(char::is_whitespace) || QueryShape::is_path_like(token) {
+            return None;
+        }
+        let separator = if token.contains("::") { "::" } else { "." };
+        let mut segments: Vec<String> = token.split(separator).map(String::from).collect();
+        let valid = segments.len() >= 2
+            && segments.iter().all(|segment| {
+                segment.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+                    && segment
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+            });
+        if !valid {
+            return None;
+        }
+        let name = segments.pop()?;
+        Some(Self {
+            qualifier: segments,
+            name,
+        })
+    }
+
+    /// Whether a symbol with this parent, defined in this file, is in the
+    /// qualifier's scope: the qualifier segments appear in order, ignoring
+    /// case, among the directories and name of the file followed by the
+    /// parent, e.g. `auth::login` in `src/auth/session.rs`.
+    pub fn matches_scope(&self, parent: Option<&str>, file_path: &str) -> bool {
+        let path = file_path
+            .rsplit_once('.')
+            .filter(|(_, ext)| !ext.contains('/'))
+            .map_or(file_path, |(stem, _)| stem);
+        let mut scope = path.split(['/', '\\']).chain(
+            parent
+                .into_iter()
+                .flat_map(|p| p.split("::").flat_map(|s| s.split('.'))),
+        );
+        self.qualifier
+            .iter()
+            .all(|segment| scope.any(|s| s.eq_ignore_ascii_case(segment)))
+    }
+}
+
+/// Split `tags:` and `tag:` tokens off a query, returning the rest of the
+/// text and the tags, normalized like the keywords files are tagged with.
+fn split_tag_filter(text: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let lower = word.to_lowercase();
+        let Some(list) = lower
+            .strip_prefix("tags:")
+            .or_else(|| lower.strip_prefix("tag:"))
+        else {
+            words.push(word);
+            continue;
+        };
+        for tag in list.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let tag = normalize_term(tag).unwrap_or_else(|| tag.to_string());
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+    (words.join(" "), tags)
+}
+
+/// Shape of a query, deciding which search strategies are favored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryShape {
+    /// A code identifier such as `getUserById`, `parse_config` or `Store::open`
+    Identifier,
+    /// A sentence or question such as "where do we retry failed uploads"
+    NaturalLanguage,
+    /// A file or directory path such as `src/auth/login.ts`
+    Path,
+    /// Plain words that fit none of the above; all strategies are weighted equally
+    #[default]
+    Keywords,
+}
+
+impl QueryShape {
+    /// Words that mark a multi-word query as natural language
+    const STOPWORDS: &'static [&'static str] = &[
+        "a", "an", "the", "where", "what", "when", "why", "how", "which", "who", "is", "are", "do",
+        "does", "did", "we", "i", "to", "of", "in", "for", "on", "with", "that", "this", "it",
+        "and", "or", "from", "by", "be", "can", "should", "find", "show", "get",
+        // French
+        "le", "la", "les", "un", "une", "des", "du", "de", "où", "comment", "quel", "quelle",
+        "quels", "pourquoi", "quand", "est", "sont", "pour", "dans", "avec", "sur", "qui", "que",
+        "et", "ou", "on", "nous",
+    ];
+
+    /// Non-code extensions recognized in path-like queries
+    const PATH_EXTENSIONS: &'static [&'static str] = &[
+        "json", "toml", "yaml", "yml", "md", "lock", "txt", "xml", "html", "css",
+    ];
+
+    /// Classify a query from its text
+    pub fn classify(text: &str) -> Self {
+        let text = text.trim();
+        let words: Vec<&str> = text.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => QueryShape::Keywords,
+            [token] if Self::is_path_like(token) => QueryShape::Path,
+            [token] if Self::is_identifier_like(token) => QueryShape::Identifier,
+            [_] => QueryShape::Keywords,
+            _ if text.ends_with('?') || words.len() >= 4 => QueryShape::NaturalLanguage,
+            _ if words
+                .iter()
+                .any(|w| Self::STOPWORDS.contains(&w.to_lowercase().as_str())) =>
+            {
+                QueryShape::NaturalLanguage
+            }
+            _ => QueryShape::Keywords,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryShape::Identifier => "identifier",
+            QueryShape::NaturalLanguage => "natural_language",
+            QueryShape::Path => "path",
+            QueryShape::Keywords => "keywords",
+        }
+    }
+
+    /// A single token with a path separator, or ending in a file extension
+    fn is_path_like(token: &str) -> bool {
+        if token.contains("::") {
+            return false;
+        }
+        if token.contains('/') || token.contains('\\') {
+            return token.chars().any(|c| c.is_alphanumeric());
+        }
+        match token.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => {
+                let ext = ext.to_lowercase();
+                Language::from_extension(&ext).is_some()
+                    || Self::PATH_EXTENSIONS.contains(&ext.as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// A single token using code naming conventions (mixed case, `_`, `::`, `.`, `$`, `()`)
+    fn is_identifier_like(token: &str) -> bool {
+        let is_call = token.ends_with("()");
+        let token = token.trim_end_matches("()");
+        let valid_chars = token
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | ':' | '.'));
+        if !valid_chars || !token.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$') {
+            return false;
+        }
+
+        let has_separator = token.contains(['_', '$', '.']) || token.contains("::");
+        let has_inner_upper = token.chars().skip(1).any(|c| c.is_uppercase());
+        let has_lower = token.chars().any(|c| c.is_lowercase());
+        let has_digit = token.chars().any(|c| c.is_ascii_digit());
+
+        let starts_upper = token.starts_with(char::is_uppercase);
+
+        is_call
+            || has_separator
+            || (has_inner_upper && has_lower)
+            || (starts_upper && has_lower)
+            || has_digit
+    }
+}
+
+/// Weights applied to the scores of each search strategy for a query.
+///
+/// A weight of zero skips the strategy entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SearchStrategy {
+    pub shape: QueryShape,
+    pub semantic_weight: f32,
+    pub symbol_weight: f32,
+    pub text_weight: f32,
+}
+
+impl SearchStrategy {
+    /// Strategy weights for a query shape
+    pub fn for_shape(shape: QueryShape) -> Self {
+        let (semantic_weight, symbol_weight, text_weight) = match shape {
+            // Symbol names match exactly; embeddings of a bare identifier are noisy
+            QueryShape::Identifier => (0.75, 1.0, 0.9),
+            // Meaning matters more than literal matches of the whole sentence
+            QueryShape::NaturalLanguage => (1.0, 0.8, 0.6),
+            // Paths are found literally (e.g. in imports), not semantically
+            QueryShape::Path => (0.0, 0.8, 1.0),
+            QueryShape::Keywords => (1.0, 1.0, 1.0),
+        };
+        Self {
+            shape,
+            semantic_weight,
+            symbol_weight,
+            text_weight,
+        }
+    }
+
+    /// Strategy weights for a query
+    pub fn for_query(query: &Query) -> Self {
+        Self::for_shape(query.shape)
+    }
+
+    pub fn uses_semantic(&self) -> bool {
+        self.semantic_weight > 0.0
+    }
+
+    pub fn uses_symbols(&self) -> bool {
+        self.symbol_weight > 0.0
+    }
+
+    pub fn uses_text(&self) -> bool {
+        self.text_weight > 0.0
+    }
+
+    /// Short human-readable description of the strategy
+    pub fn describe(&self) -> &'static str {
+        match self.shape {
+            QueryShape::Identifier => "identifier query, symbol search prioritized",
+            QueryShape::NaturalLanguage => "natural-language query, semantic search prioritized",
+            QueryShape::Path => "path query, text search prioritized, semantic search skipped",
+            QueryShape::Keywords => "keyword query, all strategies weighted equally",
+        }
+    }
 }
 
 pub struct QueryExpander;
@@ -53,10 +355,24 @@ impl QueryExpander {
     pub fn expand(&self, text: &str) -> Vec<String> {
         let mut expanded = Vec::new();
 
+        // Accent-free spelling of the whole query, to match ASCII identifiers and text
+        let folded = transliterate(text);
+        if folded != text {
+            expanded.push(folded);
+        }
+
         // Split on whitespace and process each term (limit to prevent amplification)
         for term in text.split_whitespace().take(Self::MAX_TERMS) {
             // Add case variations
             expanded.extend(self.case_variations(term));
+
+            // Add the transliterated term without elided article
+            // (e.g. "l'exécution" -> "execution") and its case variations
+            let ascii = transliterate(strip_elision(term));
+            if ascii != term {
+                expanded.extend(self.case_variations(&ascii));
+                expanded.push(ascii);
+            }
         }
 
         // Remove duplicates while preserving order
@@ -195,6 +511,66 @@ impl Default for QueryExpander {
     }
 }
 
+/// Which part of the index a search covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Project files only
+    #[default]
+    Project,
+    /// Indexed sources of external dependencies only
+    External,
+    /// Project files and external dependencies
+    All,
+}
+
+impl SearchScope {
+    /// Parse a scope name (`project`, `external` or `all`)
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "project" | "" => Some(SearchScope::Project),
+            "external" | "deps" | "dependencies" => Some(SearchScope::External),
+            "all" => Some(SearchScope::All),
+            _ => None,
+        }
+    }
+
+    /// Check if an indexed file path is covered by this scope
+    pub fn accepts_path(&self, path: &str) -> bool {
+        match self {
+            SearchScope::Project => !is_external_path(path),
+            SearchScope::External => is_external_path(path),
+            SearchScope::All => true,
+        }
+    }
+
+    pub fn includes_project(&self) -> bool {
+        matches!(self, SearchScope::Project | SearchScope::All)
+    }
+}
+
+/// How a search removes results covering the same code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DedupPolicy {
+    /// Drop results with the same file and line range only
+    Exact,
+    /// Merge overlapping results of different kinds into the richest one (a
+    /// symbol definition over a semantic chunk over a text match), recording
+    /// the other kinds in its `also_matched_via` metadata
+    #[default]
+    MergeOverlapping,
+}
+
+impl DedupPolicy {
+    /// Parse a policy name (`exact` or `merge`)
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "exact" => Some(DedupPolicy::Exact),
+            "merge" | "merge_overlapping" | "" => Some(DedupPolicy::MergeOverlapping),
+            _ => None,
+        }
+    }
+}
+
 /// Options for filtering and configuring search behavior
 #[derive(Debug, Clone, Default)]
 pub struct SearchOptions {
@@ -204,12 +580,47 @@ pub struct SearchOptions {
     pub file_types: Option<Vec<String>>,
     /// Symbol kinds to include (e.g., ["function", "class"]). If set, only these symbol types are returned.
     pub symbol_kinds: Option<Vec<String>>,
+    /// Workspace packages to include (e.g., ["@acme/ui"]). If set, only files of these packages are searched.
+    pub packages: Option<Vec<String>>,
+    /// Symbol qualifiers required (e.g., ["public", "async"]). If set, only symbols having all of them are returned.
+    pub qualifiers: Option<Vec<String>>,
+    /// Project files, external dependencies or both (default: project only)
+    pub scope: SearchScope,
+    /// Attach a score breakdown to each result's metadata
+    pub explain_scores: bool,
+    /// Timeout and cancellation of the search
+    pub deadline: Deadline,
+    /// How results covering the same code are deduplicated
+    pub dedup: DedupPolicy,
+    /// Lines of the file added above and below the content of each result
+    pub context_lines: usize,
+    /// Expand the content of each result to the whole symbol enclosing it
+    pub expand_to_symbol: bool,
+    /// Leave out symbols, and chunks of symbols, compiled only under a
+    /// `#[cfg(...)]`, `#ifdef` or `//go:build` condition
+    pub exclude_conditional: bool,
+    /// Search generated files too (protobuf output, minified bundles...),
+    /// left out by default
+    pub include_generated: bool,
+    /// Leave out text matches inside comments
+    pub exclude_comments: bool,
+    /// Leave out text matches inside string literals
+    pub exclude_strings: bool,
+    /// Search session of the conversation, whose recently surfaced files and
+    /// directories are boosted (see [`crate::SearchSessions`])
+    pub session: Option<String>,
+    /// HTTP route (e.g. `POST /api/users`). If set, only the handlers of the
+    /// routes matching it are returned.
+    pub route: Option<HttpRoute>,
 }
 
 impl SearchOptions {
     /// Default minimum score threshold
     pub const DEFAULT_MIN_SCORE: f32 = 0.35;
 
+    /// Maximum context lines added on each side of a result
+    pub const MAX_CONTEXT_LINES: usize = 50;
+
     /// Extensions excluded by default when no file_types filter is set
     pub const EXCLUDED_EXTENSIONS: &'static [&'static str] = &[
         "json",
@@ -243,6 +654,9 @@ impl SearchOptions {
         "variable",
         "constant",
         "type",
+        "table",
+        "column",
+        "index",
     ];
 
     /// Create new SearchOptions with default values
@@ -268,6 +682,102 @@ impl SearchOptions {
         self
     }
 
+    /// Create SearchOptions with workspace package filter
+    pub fn with_packages(mut self, packages: Vec<String>) -> Self {
+        self.packages = Some(packages);
+        self
+    }
+
+    /// Create SearchOptions with symbol qualifier filter
+    pub fn with_qualifiers(mut self, qualifiers: Vec<String>) -> Self {
+        self.qualifiers = Some(qualifiers);
+        self
+    }
+
+    /// Create SearchOptions covering the given scope
+    pub fn with_scope(mut self, scope: SearchScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Create SearchOptions attaching a score breakdown to each result
+    pub fn with_explain_scores(mut self, explain_scores: bool) -> Self {
+        self.explain_scores = explain_scores;
+        self
+    }
+
+    /// Create SearchOptions stopping the search at the given deadline
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Create SearchOptions deduplicating results with the given policy
+    pub fn with_dedup(mut self, dedup: DedupPolicy) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Create SearchOptions adding `lines` lines of context around each
+    /// result, capped at [`Self::MAX_CONTEXT_LINES`]
+    pub fn with_context_lines(mut self, lines: usize) -> Self {
+        self.context_lines = lines.min(Self::MAX_CONTEXT_LINES);
+        self
+    }
+
+    /// Create SearchOptions expanding each result to its enclosing symbol
+    pub fn with_expand_to_symbol(mut self, expand: bool) -> Self {
+        self.expand_to_symbol = expand;
+        self
+    }
+
+    /// Create SearchOptions leaving out conditionally compiled symbols
+    pub fn with_exclude_conditional(mut self, exclude: bool) -> Self {
+        self.exclude_conditional = exclude;
+        self
+    }
+
+    /// Create SearchOptions searching generated files too
+    pub fn with_include_generated(mut self, include: bool) -> Self {
+        self.include_generated = include;
+        self
+    }
+
+    /// Create SearchOptions leaving out text matches inside comments
+    pub fn with_exclude_comments(mut self, exclude: bool) -> Self {
+        self.exclude_comments = exclude;
+        self
+    }
+
+    /// Create SearchOptions leaving out text matches inside string literals
+    pub fn with_exclude_strings(mut self, exclude: bool) -> Self {
+        self.exclude_strings = exclude;
+        self
+    }
+
+    /// Create SearchOptions belonging to a search session
+    pub fn with_session(mut self, session: &str) -> Self {
+        self.session = Some(session.to_string());
+        self
+    }
+
+    /// Create SearchOptions with HTTP route filter
+    pub fn with_route(mut self, route: HttpRoute) -> Self {
+        self.route = Some(route);
+        self
+    }
+
+    /// Whether text matches are filtered by the comment and string ranges
+    /// of their file
+    pub fn masks_text(&self) -> bool {
+        self.exclude_comments || self.exclude_strings
+    }
+
+    /// Whether the content of results is expanded beyond the matched lines
+    pub fn expands_results(&self) -> bool {
+        self.context_lines > 0 || self.expand_to_symbol
+    }
+
     /// Get the effective minimum score (uses default if not set)
     pub fn effective_min_score(&self) -> f32 {
         self.min_score.unwrap_or(Self::DEFAULT_MIN_SCORE)
@@ -286,6 +796,28 @@ impl SearchOptions {
         }
     }
 
+    /// Check if a file of the given workspace package is accepted by these options
+    pub fn accepts_package(&self, package: Option<&str>) -> bool {
+        match (&self.packages, package) {
+            (None, _) => true,
+            (Some(packages), Some(package)) => {
+                packages.iter().any(|p| p.eq_ignore_ascii_case(package))
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Check if a symbol with the given qualifiers is accepted by these
+    /// options. Results that are not symbols (`None`) are rejected by a
+    /// qualifier filter.
+    pub fn accepts_qualifiers(&self, qualifiers: Option<&SymbolQualifiers>) -> bool {
+        match (&self.qualifiers, qualifiers) {
+            (None, _) => true,
+            (Some(required), Some(qualifiers)) => required.iter().all(|q| qualifiers.has(q)),
+            (Some(_), None) => false,
+        }
+    }
+
     /// Check if a symbol kind is accepted by these options
     pub fn accepts_symbol_kind(&self, kind: &str) -> bool {
         if let Some(ref symbol_kinds) = self.symbol_kinds {
@@ -309,6 +841,190 @@ impl SearchOptions {
     }
 }
 
+/// Which kind of references `find_references` should return
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RefKind {
+    /// Symbol definitions only
+    Definition,
+    /// Usages (text matches) only
+    Usage,
+    /// Both definitions and usages
+    #[default]
+    All,
+}
+
+impl RefKind {
+    /// Parse a kind name (`definition`, `usage` or `all`, plural forms accepted)
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "definition" | "definitions" | "def" => Some(RefKind::Definition),
+            "usage" | "usages" | "reference" | "references" => Some(RefKind::Usage),
+            "all" | "" => Some(RefKind::All),
+            _ => None,
+        }
+    }
+
+    pub fn includes_definitions(&self) -> bool {
+        matches!(self, RefKind::Definition | RefKind::All)
+    }
+
+    pub fn includes_usages(&self) -> bool {
+        matches!(self, RefKind::Usage | RefKind::All)
+    }
+}
+
+/// Options for filtering `find_references` results
+#[derive(Debug, Clone)]
+pub struct FindRefsOptions {
+    /// Maximum number of results
+    pub limit: usize,
+    /// Definitions, usages or both
+    pub kind: RefKind,
+    /// Only include files whose relative path starts with this prefix
+    pub path_prefix: Option<String>,
+    /// Exclude test files and directories
+    pub exclude_tests: bool,
+    /// Leave out usages inside comments
+    pub exclude_comments: bool,
+    /// Leave out usages inside string literals
+    pub exclude_strings: bool,
+    /// Also find the references of the symbols linked to this one in other
+    /// languages (napi-rs, wasm-bindgen and PyO3 bindings)
+    pub cross_language: bool,
+    /// Index generation to find the references at, from the symbol and
+    /// occurrence history; usages are then counted per file
+    pub as_of: Option<u64>,
+}
+
+impl Default for FindRefsOptions {
+    fn default() -> Self {
+        Self {
+            limit: Self::DEFAULT_LIMIT,
+            kind: RefKind::All,
+            path_prefix: None,
+            exclude_tests: false,
+            exclude_comments: false,
+            exclude_strings: false,
+            cross_language: false,
+            as_of: None,
+        }
+    }
+}
+
+impl FindRefsOptions {
+    /// Default maximum number of results
+    pub const DEFAULT_LIMIT: usize = 50;
+
+    /// Directory names that hold test code
+    const TEST_DIRS: &'static [&'static str] =
+        &["test", "tests", "__tests__", "spec", "specs", "testdata"];
+
+    /// Create new FindRefsOptions with default values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create FindRefsOptions with a result limit
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Create FindRefsOptions with a reference kind filter
+    pub fn with_kind(mut self, kind: RefKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Create FindRefsOptions with a path prefix filter, a directory or file
+    /// relative to the project root
+    pub fn with_path_prefix(mut self, prefix: &str) -> Self {
+        let prefix = prefix.trim().trim_start_matches("./").trim_end_matches('/');
+        self.path_prefix = (!prefix.is_empty()).then(|| prefix.to_string());
+        self
+    }
+
+    /// Create FindRefsOptions excluding test files
+    pub fn excluding_tests(mut self, exclude: bool) -> Self {
+        self.exclude_tests = exclude;
+        self
+    }
+
+    /// Create FindRefsOptions excluding usages inside comments
+    pub fn excluding_comments(mut self, exclude: bool) -> Self {
+        self.exclude_comments = exclude;
+        self
+    }
+
+    /// Create FindRefsOptions excluding usages inside string literals
+    pub fn excluding_strings(mut self, exclude: bool) -> Self {
+        self.exclude_strings = exclude;
+        self
+    }
+
+    /// Create FindRefsOptions following cross-language links
+    pub fn following_cross_language(mut self, follow: bool) -> Self {
+        self.cross_language = follow;
+        self
+    }
+
+    /// Create FindRefsOptions finding the references at an earlier index
+    /// generation
+    pub fn as_of_generation(mut self, generation: u64) -> Self {
+        self.as_of = Some(generation);
+        self
+    }
+
+    /// Check if a relative file path is accepted by these options. The path
+    /// prefix matches whole path components, so `src/api` does not accept
+    /// `src/api_v2/x.rs`. Indexed external dependencies are never reported
+    /// as references.
+    pub fn accepts_path(&self, path: &str) -> bool {
+        if is_external_path(path) {
+            return false;
+        }
+        if let Some(ref prefix) = self.path_prefix
+            && path != prefix
+            && !path
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        {
+            return false;
+        }
+        !(self.exclude_tests && Self::is_test_path(path))
+    }
+
+    /// Heuristic detection of test files from their path
+    pub fn is_test_path(path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        let mut components = path.split('/').peekable();
+
+        while let Some(component) = components.next() {
+            let is_file_name = components.peek().is_none();
+            if !is_file_name {
+                if Self::TEST_DIRS.contains(&component) {
+                    return true;
+                }
+                continue;
+            }
+
+            let stem = component.split('.').next().unwrap_or(component);
+            return stem == "test"
+                || stem == "tests"
+                || stem.starts_with("test_")
+                || stem.ends_with("_test")
+                || stem.ends_with("_tests")
+                || stem.ends_with("_spec")
+                || stem.ends_with("Test")
+                || stem.ends_with("Tests")
+                || component.contains(".test.")
+                || component.contains(".spec.");
+        }
+
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -390,6 +1106,7 @@ mod tests {
             file_patterns: vec!["*.rs".to_string()],
             symbol_kinds: vec!["function".to_string()],
             include_tests: true,
+            tags: Vec::new(),
         };
 
         let query = Query::new("test").with_filters(filters);
@@ -397,6 +1114,65 @@ mod tests {
         assert!(query.filters.include_tests);
     }
 
+    #[test]
+    fn test_query_tag_filter() {
+        let query = Query::new("verify tags:Authentication,sessions token");
+        assert_eq!(query.text, "verify token");
+        assert_eq!(query.filters.tags, vec!["authentication", "session"]);
+
+        let query = Query::new("tag:token tag:token");
+        assert_eq!(query.text, "token");
+        assert_eq!(query.filters.tags, vec!["token"]);
+
+        assert!(Query::new("parse_config").filters.tags.is_empty());
+    }
+
+    #[test]
+    fn test_qualified_name_parse() {
+        let parse = |text| QualifiedName::parse(text).map(|q| (q.qualifier, q.name));
+        assert_eq!(
+            parse("auth::login"),
+            Some((vec!["auth".to_string()], "login".to_string()))
+        );
+        assert_eq!(
+            parse("app::auth::login()"),
+            Some((
+                vec!["app".to_string(), "auth".to_string()],
+                "login".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("UserService.create"),
+            Some((vec!["UserService".to_string()], "create".to_string()))
+        );
+        assert_eq!(parse("login"), None);
+        assert_eq!(parse("config.toml"), None);
+        assert_eq!(parse("src/auth.rs"), None);
+        assert_eq!(parse("auth::"), None);
+        assert_eq!(parse("auth login"), None);
+
+        let query = Query::new("auth::login");
+        assert_eq!(query.qualified.unwrap().name, "login");
+        assert!(query.expanded_terms.contains(&"login".to_string()));
+    }
+
+    #[test]
+    fn test_qualified_name_matches_scope() {
+        let login = QualifiedName::parse("auth::login").unwrap();
+        assert!(login.matches_scope(None, "src/auth/session.rs"));
+        assert!(login.matches_scope(None, "lib/Auth.ts"));
+        assert!(!login.matches_scope(None, "src/admin.rs"));
+        assert!(!login.matches_scope(None, "src/authorization.rs"));
+
+        let create = QualifiedName::parse("UserService.create").unwrap();
+        assert!(create.matches_scope(Some("UserService"), "src/users.ts"));
+        assert!(!create.matches_scope(Some("OrderService"), "src/users.ts"));
+
+        let nested = QualifiedName::parse("app::auth::login").unwrap();
+        assert!(nested.matches_scope(None, "app/src/auth.rs"));
+        assert!(!nested.matches_scope(None, "auth/app.rs"));
+    }
+
     #[test]
     fn test_query_all_terms() {
         let query = Query::new("get_user");
@@ -571,6 +1347,22 @@ mod tests {
         assert!(!options.accepts_symbol_kind("variable"));
     }
 
+    #[test]
+    fn test_accepts_qualifiers() {
+        let public_async = SymbolQualifiers {
+            is_public: true,
+            is_async: true,
+            ..SymbolQualifiers::default()
+        };
+        assert!(SearchOptions::default().accepts_qualifiers(None));
+
+        let options =
+            SearchOptions::new().with_qualifiers(vec!["public".to_string(), "async".to_string()]);
+        assert!(options.accepts_qualifiers(Some(&public_async)));
+        assert!(!options.accepts_qualifiers(Some(&SymbolQualifiers::default())));
+        assert!(!options.accepts_qualifiers(None)); // not a symbol
+    }
+
     #[test]
     fn test_parse_csv() {
         let result = SearchOptions::parse_csv("rs, ts, py");
@@ -599,4 +1391,209 @@ mod tests {
         assert!(options.accepts_symbol_kind("function"));
         assert!(!options.accepts_symbol_kind("class"));
     }
+
+    // FindRefsOptions tests
+
+    #[test]
+    fn test_ref_kind_parse() {
+        assert_eq!(RefKind::parse("definition"), Some(RefKind::Definition));
+        assert_eq!(RefKind::parse("Usages"), Some(RefKind::Usage));
+        assert_eq!(RefKind::parse("all"), Some(RefKind::All));
+        assert_eq!(RefKind::parse("bogus"), None);
+
+        assert!(RefKind::All.includes_definitions() && RefKind::All.includes_usages());
+        assert!(!RefKind::Definition.includes_usages());
+        assert!(!RefKind::Usage.includes_definitions());
+    }
+
+    #[test]
+    fn test_find_refs_options_default() {
+        let options = FindRefsOptions::default();
+        assert_eq!(options.limit, FindRefsOptions::DEFAULT_LIMIT);
+        assert_eq!(options.kind, RefKind::All);
+        assert!(options.accepts_path("tests/integration.rs"));
+    }
+
+    #[test]
+    fn test_find_refs_options_path_prefix() {
+        let options = FindRefsOptions::new().with_path_prefix("./src/");
+        assert_eq!(options.path_prefix.as_deref(), Some("src"));
+        assert!(options.accepts_path("src/lib.rs"));
+        assert!(!options.accepts_path("benches/lib.rs"));
+
+        let options = FindRefsOptions::new().with_path_prefix("src/lib.rs");
+        assert!(options.accepts_path("src/lib.rs"));
+
+        let options = FindRefsOptions::new().with_path_prefix("  ");
+        assert!(options.path_prefix.is_none());
+    }
+
+    #[test]
+    fn test_find_refs_options_path_prefix_sibling_directory() {
+        let options = FindRefsOptions::new().with_path_prefix("src/api");
+        assert!(options.accepts_path("src/api/handlers.rs"));
+        assert!(!options.accepts_path("src/api_v2/x.rs"));
+        assert!(!options.accepts_path("src/api.rs"));
+    }
+
+    #[test]
+    fn test_find_refs_options_skip_external() {
+        let options = FindRefsOptions::new();
+        assert!(!options.accepts_path("external://cargo/serde@1.0.0/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_search_scope() {
+        assert_eq!(SearchScope::parse("External"), Some(SearchScope::External));
+        assert_eq!(SearchScope::parse("all"), Some(SearchScope::All));
+        assert_eq!(SearchScope::parse("project"), Some(SearchScope::Project));
+        assert_eq!(SearchScope::parse("vendor"), None);
+        assert_eq!(DedupPolicy::parse("Exact"), Some(DedupPolicy::Exact));
+        assert_eq!(
+            DedupPolicy::parse("merge"),
+            Some(DedupPolicy::MergeOverlapping)
+        );
+        assert_eq!(DedupPolicy::parse("none"), None);
+
+        let external = "external://npm/typed@1.0.0/index.d.ts";
+        assert_eq!(SearchOptions::default().scope, SearchScope::Project);
+        assert!(SearchScope::Project.accepts_path("src/lib.rs"));
+        assert!(!SearchScope::Project.accepts_path(external));
+        assert!(SearchScope::External.accepts_path(external));
+        assert!(!SearchScope::External.accepts_path("src/lib.rs"));
+        assert!(SearchScope::All.accepts_path(external));
+    }
+
+    #[test]
+    fn test_find_refs_options_exclude_tests() {
+        let options = FindRefsOptions::new().excluding_tests(true);
+        assert!(options.accepts_path("src/lib.rs"));
+        assert!(!options.accepts_path("tests/api.rs"));
+        assert!(!options.accepts_path("src/store/tests.rs"));
+        assert!(!options.accepts_path("pkg/handler_test.go"));
+        assert!(!options.accepts_path("app/test_models.py"));
+        assert!(!options.accepts_path("web/button.spec.ts"));
+        assert!(!options.accepts_path("src/UserServiceTest.java"));
+        assert!(options.accepts_path("src/contest.rs"));
+    }
+
+    // Query classification tests
+
+    #[test]
+    fn test_classify_identifiers() {
+        for query in [
+            "getUserById",
+            "parse_config",
+            "UserService",
+            "MAX_RETRIES",
+            "Store::open",
+            "self.store",
+            "$scope",
+            "sha256",
+            "open()",
+        ] {
+            assert_eq!(
+                QueryShape::classify(query),
+                QueryShape::Identifier,
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_natural_language() {
+        for query in [
+            "where do we retry failed uploads",
+            "how is auth handled",
+            "retry failed uploads?",
+            "the upload queue",
+            "database connection pool setup",
+            "où est créé l'utilisateur",
+            "gestion des erreurs",
+        ] {
+            assert_eq!(
+                QueryShape::classify(query),
+                QueryShape::NaturalLanguage,
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_normalizes_non_english_text() {
+        let query = Query::new("créer  l\u{2019}utilisateur");
+        assert_eq!(query.text, "créer l'utilisateur");
+        // The accent-free spelling and its words are searched too
+        assert!(
+            query
+                .expanded_terms
+                .contains(&"creer l'utilisateur".to_string())
+        );
+        assert!(query.expanded_terms.contains(&"creer".to_string()));
+        assert!(query.expanded_terms.contains(&"utilisateur".to_string()));
+
+        let query = Query::new("créerUtilisateur");
+        assert!(
+            query
+                .expanded_terms
+                .contains(&"creerUtilisateur".to_string())
+        );
+        assert!(
+            query
+                .expanded_terms
+                .contains(&"creer_utilisateur".to_string())
+        );
+
+        // ASCII queries are unchanged
+        assert!(Query::new("parse").expanded_terms.is_empty());
+    }
+
+    #[test]
+    fn test_classify_paths() {
+        for query in [
+            "src/auth/login.ts",
+            "crates/semantiq-index",
+            "main.rs",
+            "Cargo.toml",
+            "src\\lib.rs",
+        ] {
+            assert_eq!(QueryShape::classify(query), QueryShape::Path, "{query}");
+        }
+    }
+
+    #[test]
+    fn test_classify_keywords() {
+        for query in ["retry", "upload handler", "HTTP", ""] {
+            assert_eq!(QueryShape::classify(query), QueryShape::Keywords, "{query}");
+        }
+        assert_eq!(Query::new("getUserById").shape, QueryShape::Identifier);
+    }
+
+    #[test]
+    fn test_strategy_for_shape() {
+        let path = SearchStrategy::for_shape(QueryShape::Path);
+        assert!(!path.uses_semantic());
+        assert!(path.uses_text() && path.uses_symbols());
+
+        let identifier = SearchStrategy::for_shape(QueryShape::Identifier);
+        assert!(identifier.symbol_weight > identifier.semantic_weight);
+
+        let natural = SearchStrategy::for_shape(QueryShape::NaturalLanguage);
+        assert!(natural.semantic_weight > natural.symbol_weight);
+        assert!(natural.semantic_weight > natural.text_weight);
+
+        let keywords = SearchStrategy::for_shape(QueryShape::Keywords);
+        assert_eq!(
+            (
+                keywords.semantic_weight,
+                keywords.symbol_weight,
+                keywords.text_weight
+            ),
+            (1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            serde_json::to_value(natural).unwrap()["shape"],
+            "natural_language"
+        );
+    }
 }
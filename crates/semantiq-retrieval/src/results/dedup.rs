@@ -0,0 +1,173 @@
+//! Deduplication of search results covering the same code.
+
+use super::{SearchResult, SearchResultKind};
+use crate::query::DedupPolicy;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Remove the results covering the same code according to `policy`.
+///
+/// `results` are sorted best first and so are the results returned. With
+/// [`DedupPolicy::Exact`] the first of the results with the same file and
+/// line range is kept. With [`DedupPolicy::MergeOverlapping`] results of
+/// different kinds whose line ranges overlap are merged into the one of the
+/// richest kind, which takes the best score of the results merged into it.
+pub(crate) fn dedup_results(results: Vec<SearchResult>, policy: DedupPolicy) -> Vec<SearchResult> {
+    match policy {
+        DedupPolicy::Exact => {
+            let mut seen = HashSet::new();
+            results
+                .into_iter()
+                .filter(|r| seen.insert((r.file_path.clone(), r.start_line, r.end_line)))
+                .collect()
+        }
+        DedupPolicy::MergeOverlapping => merge_overlapping(results),
+    }
+}
+
+fn merge_overlapping(mut results: Vec<SearchResult>) -> Vec<SearchResult> {
+    // Richest kinds first, so a result is only ever merged into a richer one
+    // or into one with the same range
+    results.sort_by(|a, b| richness(b.kind).cmp(&richness(a.kind)).then(by_score(a, b)));
+
+    let mut kept: Vec<SearchResult> = Vec::new();
+    for result in results {
+        let target = kept.iter_mut().find(|k| {
+            k.file_path == result.file_path
+                && k.start_line <= result.end_line
+                && result.start_line <= k.end_line
+                && (k.kind != result.kind
+                    || (k.start_line == result.start_line && k.end_line == result.end_line))
+        });
+        match target {
+            Some(target) => absorb(target, result),
+            None => kept.push(result),
+        }
+    }
+
+    kept.sort_by(by_score);
+    kept
+}
+
+/// Merge `other` into `target`
+fn absorb(target: &mut SearchResult, other: SearchResult) {
+    let kinds = std::iter::once(other.kind).chain(other.metadata.also_matched_via);
+    for kind in kinds {
+        if kind != target.kind && !target.metadata.also_matched_via.contains(&kind) {
+            target.metadata.also_matched_via.push(kind);
+        }
+    }
+
+    if other.score > target.score {
+        target.score = other.score;
+        if other.metadata.explanation.is_some() {
+            target.metadata.explanation = other.metadata.explanation;
+        }
+    }
+}
+
+/// How much a result of this kind tells about the code it covers
+fn richness(kind: SearchResultKind) -> u8 {
+    match kind {
+        SearchResultKind::Symbol => 2,
+        SearchResultKind::SemanticMatch => 1,
+        SearchResultKind::TextMatch | SearchResultKind::Reference => 0,
+    }
+}
+
+fn by_score(a: &SearchResult, b: &SearchResult) -> Ordering {
+    b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        kind: SearchResultKind,
+        path: &str,
+        lines: (usize, usize),
+        score: f32,
+    ) -> SearchResult {
+        SearchResult::new(
+            kind,
+            path.to_string(),
+            lines.0,
+            lines.1,
+            String::new(),
+            score,
+        )
+    }
+
+    fn summary(results: &[SearchResult]) -> Vec<(SearchResultKind, usize, f32)> {
+        results
+            .iter()
+            .map(|r| (r.kind, r.start_line, r.score))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_keeps_richest_kind() {
+        let results = vec![
+            result(SearchResultKind::TextMatch, "src/a.rs", (12, 12), 0.9),
+            result(SearchResultKind::SemanticMatch, "src/a.rs", (10, 25), 0.7),
+            result(SearchResultKind::Symbol, "src/a.rs", (10, 30), 0.6),
+            result(SearchResultKind::TextMatch, "src/a.rs", (40, 40), 0.5),
+            result(SearchResultKind::TextMatch, "src/b.rs", (12, 12), 0.4),
+        ];
+
+        let merged = dedup_results(results, DedupPolicy::MergeOverlapping);
+
+        assert_eq!(
+            summary(&merged),
+            vec![
+                (SearchResultKind::Symbol, 10, 0.9),
+                (SearchResultKind::TextMatch, 40, 0.5),
+                (SearchResultKind::TextMatch, 12, 0.4),
+            ]
+        );
+        assert_eq!(
+            merged[0].metadata.also_matched_via,
+            vec![SearchResultKind::SemanticMatch, SearchResultKind::TextMatch]
+        );
+        assert!(merged[1].metadata.also_matched_via.is_empty());
+    }
+
+    #[test]
+    fn test_merge_keeps_overlapping_results_of_the_same_kind() {
+        let results = vec![
+            result(SearchResultKind::Symbol, "src/a.rs", (5, 8), 0.8),
+            result(SearchResultKind::Symbol, "src/a.rs", (1, 20), 0.6),
+            result(SearchResultKind::Symbol, "src/a.rs", (5, 8), 0.5),
+        ];
+
+        let merged = dedup_results(results, DedupPolicy::MergeOverlapping);
+
+        assert_eq!(
+            summary(&merged),
+            vec![
+                (SearchResultKind::Symbol, 5, 0.8),
+                (SearchResultKind::Symbol, 1, 0.6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exact_drops_identical_ranges_only() {
+        let results = vec![
+            result(SearchResultKind::TextMatch, "src/a.rs", (10, 10), 0.9),
+            result(SearchResultKind::Symbol, "src/a.rs", (10, 30), 0.6),
+            result(SearchResultKind::SemanticMatch, "src/a.rs", (10, 30), 0.5),
+        ];
+
+        let deduped = dedup_results(results, DedupPolicy::Exact);
+
+        assert_eq!(
+            summary(&deduped),
+            vec![
+                (SearchResultKind::TextMatch, 10, 0.9),
+                (SearchResultKind::Symbol, 10, 0.6),
+            ]
+        );
+    }
+}
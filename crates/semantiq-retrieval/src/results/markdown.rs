@@ -0,0 +1,2183 @@
+//! Markdown rendering of results, as returned by the MCP tools.
+
+use super::{
+    BatchSearchResponse, ComponentTreeResponse, ConfigUsageResponse, DependenciesResponse,
+    EntrypointsResponse, ExploreResponse, Freshness, ImplementationsResponse, IndexIssuesResponse,
+    OverviewResponse, ReferencesResponse, RelatedFilesResponse, RoutesResponse, SearchDiagnostics,
+    SearchResult, SearchResults, SemanticDiffResponse, SessionSummary, UnusedSymbolsResponse,
+};
+use crate::engine::{
+    ChangeKind, Compilation, ComponentNode, DependencyInfo, DependencyScope, ExploreEntry,
+    SymbolChange, SymbolDependency, SymbolExplanation,
+};
+use semantiq_index::DirectorySummary;
+use semantiq_parser::{ImportedSymbol, RouteMatch};
+
+/// Maximum number of characters shown of a search result without a snippet
+const SNIPPET_CHARS: usize = 200;
+
+/// Maximum number of usages listed in a references response
+const MAX_LISTED_USAGES: usize = 20;
+
+/// Maximum number of related symbols listed in an explanation
+const MAX_RELATED_SYMBOLS: usize = 10;
+
+/// Maximum number of top symbols listed per subdirectory in an overview
+const MAX_SUBDIRECTORY_SYMBOLS: usize = 5;
+
+/// Render a response as human-readable markdown
+pub trait ToMarkdown {
+    fn to_markdown(&self) -> String;
+}
+
+impl ToMarkdown for SearchResults {
+    fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "Found {} results for '{}' ({} ms{})\n",
+            self.total_count,
+            self.query,
+            self.search_time_ms,
+            if self.cached { ", cached" } else { "" }
+        );
+        if let Some(ref strategy) = self.strategy {
+            output.push_str(&format!("Strategy: {}\n", strategy.describe()));
+        }
+        if self.truncated_by_timeout {
+            output.push_str("⚠️ The search stopped at its time limit: results may be incomplete\n");
+        }
+        if !self.facets.is_empty() {
+            let facets: Vec<String> = self
+                .facets
+                .iter()
+                .map(|f| format!("{} ({})", f.tag, f.count))
+                .collect();
+            output.push_str(&format!(
+                "Tags: {} (narrow with tags:name)\n",
+                facets.join(", ")
+            ));
+        }
+        output.push('\n');
+
+        for result in &self.results {
+            output.push_str(&format!(
+                "📄 {}{}\n   Lines {}-{}{} | Score: {:.2}{}{}{}{}\n",
+                result.file_path,
+                match result.metadata.title {
+                    Some(ref title) => format!(" — {}", title),
+                    None => String::new(),
+                },
+                result.start_line,
+                result.end_line,
+                match result.matched_lines {
+                    Some(ref matched) => {
+                        format!(" (matched {}-{})", matched.start_line, matched.end_line)
+                    }
+                    None => String::new(),
+                },
+                result.score,
+                if result.stale {
+                    " | ⚠️ stale (file changed, reindexing)"
+                } else {
+                    ""
+                },
+                if result.from_base {
+                    " | base index"
+                } else {
+                    ""
+                },
+                if result.metadata.generated {
+                    " | generated"
+                } else {
+                    ""
+                },
+                if result.metadata.outline {
+                    " | outline only"
+                } else {
+                    ""
+                }
+            ));
+
+            if let Some(tags) = self.file_tags.get(&result.file_path) {
+                output.push_str(&format!("   Tags: {}\n", tags.join(", ")));
+            }
+
+            if let Some(ref symbol_name) = result.metadata.symbol_name {
+                let mut kind = result
+                    .metadata
+                    .qualifiers
+                    .map(|q| q.names())
+                    .unwrap_or_default();
+                kind.push(result.metadata.symbol_kind.as_deref().unwrap_or("unknown"));
+                output.push_str(&format!("   Symbol: {} ({})", symbol_name, kind.join(" ")));
+                if let Some(ref parent) = result.metadata.parent {
+                    output.push_str(&format!(" in {}", parent));
+                }
+                if let Some(ref cfg) = result.metadata.cfg_context {
+                    output.push_str(&format!(" [cfg: {}]", cfg));
+                }
+                output.push('\n');
+            }
+
+            if let Some(ref route) = result.metadata.route {
+                output.push_str(&format!("   Route: {}\n", route));
+            }
+
+            if !result.metadata.also_matched_via.is_empty() {
+                let kinds: Vec<&str> = result
+                    .metadata
+                    .also_matched_via
+                    .iter()
+                    .map(|kind| kind.as_str())
+                    .collect();
+                output.push_str(&format!("   Also matched via: {}\n", kinds.join(", ")));
+            }
+
+            if let Some(ref explanation) = result.metadata.explanation {
+                output.push_str(&format!("   Why: {}", explanation.summary()));
+                let terms: Vec<String> = explanation
+                    .term_matches
+                    .iter()
+                    .map(|m| match (m.start, m.end) {
+                        (Some(start), Some(end)) => format!("'{}' at {}..{}", m.term, start, end),
+                        _ => format!("'{}'", m.term),
+                    })
+                    .collect();
+                if !terms.is_empty() {
+                    output.push_str(&format!(" | terms: {}", terms.join(", ")));
+                }
+                output.push_str(&format!(
+                    " | min score {:.2}\n",
+                    explanation.thresholds.min_score
+                ));
+            }
+
+            let snippet: String = match result.snippet {
+                Some(ref snippet) => {
+                    if let Some(ref header) = snippet.header {
+                        output.push_str(&format!("   In: `{}`\n", header));
+                    }
+                    snippet.text.clone()
+                }
+                // The title stands for the chunk
+                None if result.metadata.title.is_some() => {
+                    output.push('\n');
+                    continue;
+                }
+                None => result.content.chars().take(SNIPPET_CHARS).collect(),
+            };
+            output.push_str(&format!("   ```\n   {}\n   ```\n\n", snippet.trim()));
+        }
+
+        if let Some(ref diagnostics) = self.diagnostics {
+            output.push_str(&diagnostics.to_markdown());
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for SearchDiagnostics {
+    fn to_markdown(&self) -> String {
+        let mut output = String::from("## Why no results?\n\n");
+        for hint in &self.hints {
+            output.push_str(&format!("- {}\n", hint));
+        }
+        output.push_str(&format!(
+            "- Index: {} chunks, {} with embeddings{}\n",
+            self.total_chunks,
+            self.embedded_chunks,
+            if self.semantic_search {
+                ""
+            } else {
+                " (semantic search not used)"
+            }
+        ));
+
+        if !self.near_misses.is_empty() {
+            output.push_str("\n### Closest matches below the threshold\n\n");
+            for near_miss in &self.near_misses {
+                output.push_str(&format!(
+                    "- {}:{}-{} ({}) | Score: {:.2} < {:.2}\n",
+                    near_miss.file_path,
+                    near_miss.start_line,
+                    near_miss.end_line,
+                    near_miss.strategy,
+                    near_miss.score,
+                    near_miss.threshold
+                ));
+            }
+        }
+
+        if !self.suggestions.is_empty() {
+            let suggestions: Vec<String> = self
+                .suggestions
+                .iter()
+                .map(|s| format!("'{}'", s))
+                .collect();
+            output.push_str(&format!("\nDid you mean: {}?\n", suggestions.join(", ")));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for BatchSearchResponse {
+    fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "Ran {} searches ({} ms{})\n",
+            self.searches.len(),
+            self.search_time_ms,
+            match self.error_count {
+                0 => String::new(),
+                n => format!(", {} failed", n),
+            }
+        );
+        for (i, search) in self.searches.iter().enumerate() {
+            output.push_str(&format!("\n## {}. {}\n\n", i + 1, search.query));
+            match (&search.results, &search.error) {
+                (Some(results), _) => output.push_str(&results.to_markdown()),
+                (None, Some(error)) => output.push_str(&format!("❌ {}\n", error)),
+                (None, None) => {}
+            }
+        }
+        output
+    }
+}
+
+impl ToMarkdown for ReferencesResponse {
+    fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "Found {} references to '{}' ({} ms{})\n\n",
+            self.total_count,
+            self.symbol,
+            self.search_time_ms,
+            if self.cached { ", cached" } else { "" }
+        );
+
+        if let Some(generation) = self.as_of {
+            output.push_str(&format!(
+                "As of index generation {}: usages are counted per file, their lines are not kept in the history\n\n",
+                generation
+            ));
+        }
+
+        if !self.links.is_empty() {
+            output.push_str("## Linked in other languages\n\n");
+            for link in &self.links {
+                let framework = link
+                    .framework
+                    .as_deref()
+                    .map(|f| format!(" ({})", f))
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "🔗 {}{} at {}:{}\n",
+                    link.name, framework, link.file_path, link.line
+                ));
+            }
+            output.push('\n');
+        }
+
+        if !self.definitions.is_empty() {
+            output.push_str("## Definitions\n\n");
+            for def in &self.definitions {
+                output.push_str(&format!(
+                    "📍 {}\n   {}\n\n",
+                    location(def),
+                    def.content.lines().next().unwrap_or("")
+                ));
+            }
+        }
+
+        if !self.usages.is_empty() {
+            output.push_str(&format!("## Usages ({} found)\n\n", self.usages.len()));
+            for usage in self.usages.iter().take(MAX_LISTED_USAGES) {
+                output.push_str(&format!(
+                    "📎 {}\n   {}\n\n",
+                    location(usage),
+                    usage.content.trim()
+                ));
+            }
+
+            if self.usages.len() > MAX_LISTED_USAGES {
+                output.push_str(&format!(
+                    "... and {} more usages\n",
+                    self.usages.len() - MAX_LISTED_USAGES
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for DependenciesResponse {
+    fn to_markdown(&self) -> String {
+        if let Some(ref symbol) = self.symbol {
+            return self.symbol_markdown(symbol);
+        }
+
+        let mut output = format!("Dependency analysis for '{}'", self.file_path);
+        if let Some(ref package) = self.package {
+            output.push_str(&format!(" (package {})", package));
+        }
+        output.push('\n');
+        if let Some(ref compilation) = self.compilation {
+            output.push_str(&format_compilation(compilation));
+        }
+        output.push('\n');
+
+        match self.imports_error {
+            None => {
+                output.push_str(&format!(
+                    "## Imports ({} dependencies{})\n\n",
+                    self.imports.len(),
+                    cross_package_count(&self.imports)
+                ));
+                for dep in &self.imports {
+                    output.push_str(&format!("→ {}", dep.target_path));
+                    if let Some(ref resolved) = dep.resolved_path {
+                        output.push_str(&format!(" ({})", resolved));
+                    }
+                    if !dep.symbols.is_empty() {
+                        output.push_str(&format!(" {}", format_imported_symbols(&dep.symbols)));
+                    } else if let Some(ref name) = dep.import_name {
+                        output.push_str(&format!(" (as {})", name));
+                    }
+                    match (dep.scope, &dep.package) {
+                        (Some(DependencyScope::CrossPackage), Some(package)) => output
+                            .push_str(&format!(" [{}, cross-package: {}]\n", dep.kind, package)),
+                        _ => output.push_str(&format!(" [{}]\n", dep.kind)),
+                    }
+                }
+                output.push('\n');
+            }
+            Some(ref e) => {
+                output.push_str(&format!("Could not analyze imports: {}\n\n", e));
+            }
+        }
+
+        match self.imported_by_error {
+            None => {
+                output.push_str(&format!(
+                    "## Imported by ({} files{})\n\n",
+                    self.imported_by.len(),
+                    cross_package_count(&self.imported_by)
+                ));
+                for dep in &self.imported_by {
+                    output.push_str(&format!("← {}", dep.target_path));
+                    if let Some(ref header) = dep.via {
+                        output.push_str(&format!(" (via {})", header));
+                    }
+                    if !dep.symbols.is_empty() {
+                        output.push_str(&format!(" {}", format_imported_symbols(&dep.symbols)));
+                    }
+                    if let (Some(DependencyScope::CrossPackage), Some(package)) =
+                        (dep.scope, &dep.package)
+                    {
+                        output.push_str(&format!(" [cross-package: {}]", package));
+                    }
+                    output.push('\n');
+                }
+            }
+            Some(ref e) => {
+                output.push_str(&format!("Could not analyze dependents: {}\n", e));
+            }
+        }
+
+        output
+    }
+}
+
+impl DependenciesResponse {
+    fn symbol_markdown(&self, symbol: &str) -> String {
+        let mut output = format!(
+            "Dependency analysis for '{}' in '{}'\n\n",
+            symbol, self.file_path
+        );
+
+        match self.imports_error {
+            None => {
+                output.push_str(&format!("## Uses ({} symbols)\n\n", self.uses.len()));
+                for dep in &self.uses {
+                    output.push_str(&format!(
+                        "→ {} [referenced at line {}]\n",
+                        format_symbol_dependency(dep),
+                        dep.line
+                    ));
+                }
+                output.push('\n');
+            }
+            Some(ref e) => {
+                output.push_str(&format!("Could not analyze the symbols used: {}\n\n", e));
+            }
+        }
+
+        match self.imported_by_error {
+            None => {
+                output.push_str(&format!("## Used by ({} symbols)\n\n", self.used_by.len()));
+                for dep in &self.used_by {
+                    output.push_str(&format!(
+                        "← {} [references at line {}]\n",
+                        format_symbol_dependency(dep),
+                        dep.line
+                    ));
+                }
+            }
+            Some(ref e) => {
+                output.push_str(&format!("Could not analyze the symbols using it: {}\n", e));
+            }
+        }
+
+        output
+    }
+}
+
+/// `name (kind) in Parent at path:line`
+fn format_symbol_dependency(dep: &SymbolDependency) -> String {
+    let mut text = format!("{} ({})", dep.name, dep.kind);
+    if let Some(ref parent) = dep.parent {
+        text.push_str(&format!(" in {}", parent));
+    }
+    text.push_str(&format!(" at {}:{}", dep.file_path, dep.start_line));
+    text
+}
+
+/// `path:line`, or `path:line:column` with a 1-based column when the result
+/// has a span
+fn location(result: &SearchResult) -> String {
+    if result.start_line == 0 {
+        // A whole file, for usages at an earlier index generation
+        return result.file_path.clone();
+    }
+    match result.span {
+        Some(span) => format!(
+            "{}:{}:{}",
+            result.file_path,
+            result.start_line,
+            span.start_column + 1
+        ),
+        None => format!("{}:{}", result.file_path, result.start_line),
+    }
+}
+
+/// `", N cross-package"` when some of the dependencies cross a package boundary
+fn cross_package_count(deps: &[DependencyInfo]) -> String {
+    let count = deps
+        .iter()
+        .filter(|d| d.scope == Some(DependencyScope::CrossPackage))
+        .count();
+    if count == 0 {
+        String::new()
+    } else {
+        format!(", {} cross-package", count)
+    }
+}
+
+impl ToMarkdown for ImplementationsResponse {
+    fn to_markdown(&self) -> String {
+        if self.implementations.is_empty() {
+            return format!(
+                "No implementations of '{}' found in the index.",
+                self.target
+            );
+        }
+
+        let mut output = format!(
+            "Implementations of '{}' ({} found, {} ms)\n\n",
+            self.target, self.total_count, self.search_time_ms
+        );
+        for implementation in &self.implementations {
+            output.push_str(&format!(
+                "📄 {}:{}-{} {} ({}{})\n",
+                implementation.file_path,
+                implementation.start_line,
+                implementation.end_line,
+                implementation.type_name,
+                implementation.relation,
+                if implementation.inferred {
+                    ", inferred from methods"
+                } else {
+                    ""
+                }
+            ));
+        }
+        if self.total_count > self.implementations.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.total_count - self.implementations.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for EntrypointsResponse {
+    fn to_markdown(&self) -> String {
+        if self.entrypoints.is_empty() {
+            return format!(
+                "No {}entry points found under '{}'.",
+                self.kind
+                    .as_deref()
+                    .map(|k| format!("{} ", k))
+                    .unwrap_or_default(),
+                self.path
+            );
+        }
+
+        let mut output = format!(
+            "Entry points of '{}' ({} found, {} ms)\n",
+            self.path, self.total_count, self.search_time_ms
+        );
+        let mut current_kind = None;
+        for entrypoint in &self.entrypoints {
+            if current_kind != Some(entrypoint.kind.as_str()) {
+                current_kind = Some(entrypoint.kind.as_str());
+                let heading = match entrypoint.kind.as_str() {
+                    "main" => "Main functions",
+                    "command" => "CLI commands",
+                    "route" => "HTTP routes",
+                    "export" => "Library exports",
+                    other => other,
+                };
+                let count = self.counts.get(&entrypoint.kind).copied().unwrap_or(0);
+                output.push_str(&format!("\n## {} ({})\n\n", heading, count));
+            }
+            output.push_str(&format!(
+                "📄 {}:{} {}",
+                entrypoint.file_path, entrypoint.start_line, entrypoint.name
+            ));
+            if let Some(ref handler) = entrypoint.handler {
+                output.push_str(&format!(" → {}", handler));
+            }
+            output.push('\n');
+        }
+        if self.total_count > self.entrypoints.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.total_count - self.entrypoints.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for RoutesResponse {
+    fn to_markdown(&self) -> String {
+        let requested = match (&self.method, &self.path) {
+            (Some(method), Some(path)) => Some(format!("{} {}", method, path)),
+            (None, Some(path)) => Some(path.clone()),
+            (Some(method), None) => Some(method.clone()),
+            (None, None) => None,
+        };
+        if self.routes.is_empty() {
+            return match requested {
+                Some(requested) => format!("No HTTP route matches '{}'.", requested),
+                None => "No HTTP routes found in the index.".to_string(),
+            };
+        }
+
+        let mut output = match requested {
+            Some(ref requested) => format!(
+                "HTTP routes matching '{}' ({} found, {} ms)\n\n",
+                requested, self.total_count, self.search_time_ms
+            ),
+            None => format!(
+                "HTTP routes ({} found, {} ms)\n\n",
+                self.total_count, self.search_time_ms
+            ),
+        };
+        for route in &self.routes {
+            output.push_str(&format!(
+                "📄 {}:{} {}",
+                route.file_path,
+                route.start_line,
+                route.route()
+            ));
+            if let Some(ref handler) = route.handler {
+                output.push_str(&format!(" → {}", handler));
+            }
+            if let Some(ref definition) = route.handler_definition {
+                output.push_str(&format!(
+                    " ({}:{})",
+                    definition.file_path, definition.start_line
+                ));
+            }
+            if route.matched == Some(RouteMatch::Suffix) {
+                output.push_str(" [matches the end of the path: mounted under a prefix?]");
+            }
+            output.push('\n');
+        }
+        if self.total_count > self.routes.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.total_count - self.routes.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for ConfigUsageResponse {
+    fn to_markdown(&self) -> String {
+        if self.usages.is_empty() {
+            let mut output = format!("No read or write of '{}' found.", self.name);
+            if !self.similar.is_empty() {
+                output.push_str(&format!(
+                    " Indexed names resembling it: {}",
+                    self.similar.join(", ")
+                ));
+            }
+            return output;
+        }
+
+        let mut output = format!(
+            "Reads and writes of '{}' ({} found: {} write{}, {} read{}, {} ms)\n\n",
+            self.name,
+            self.total_count,
+            self.write_count,
+            if self.write_count == 1 { "" } else { "s" },
+            self.read_count,
+            if self.read_count == 1 { "" } else { "s" },
+            self.search_time_ms
+        );
+        for usage in &self.usages {
+            output.push_str(&format!(
+                "📄 {}:{} [{}] {} {}",
+                usage.file_path, usage.line, usage.access, usage.source, usage.name
+            ));
+            if let Some(ref symbol) = usage.symbol {
+                output.push_str(&format!(" in {}", symbol));
+            }
+            output.push('\n');
+            if let Some(ref code) = usage.code {
+                output.push_str(&format!("   {}\n", code));
+            }
+        }
+        if self.total_count > self.usages.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.total_count - self.usages.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for IndexIssuesResponse {
+    fn to_markdown(&self) -> String {
+        if self.issues.is_empty() {
+            return match self.kind {
+                Some(ref kind) => format!("No indexing issue of kind '{}'.", kind),
+                None => "No indexing issues: every file was indexed.".to_string(),
+            };
+        }
+
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect();
+        let mut output = format!(
+            "Indexing issues ({} found: {}, {} ms)\n\n",
+            self.total_count,
+            counts.join(", "),
+            self.search_time_ms
+        );
+        for issue in &self.issues {
+            output.push_str(&format!(
+                "📄 {} [{}] {}\n",
+                issue.path, issue.kind, issue.message
+            ));
+        }
+        if self.total_count > self.issues.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.total_count - self.issues.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for ComponentTreeResponse {
+    fn to_markdown(&self) -> String {
+        if self.rendered_by.is_empty() && self.renders.is_empty() {
+            return format!(
+                "No components rendering or rendered by '{}' found in the index.",
+                self.component
+            );
+        }
+
+        let mut output = format!(
+            "Component tree of '{}' ({} ms)\n",
+            self.component, self.search_time_ms
+        );
+        if !self.rendered_by.is_empty() {
+            output.push_str("\nRendered by:\n");
+            for parent in &self.rendered_by {
+                output.push_str(&format!(
+                    "📄 {}:{} {}\n",
+                    parent.file_path, parent.line, parent.name
+                ));
+            }
+        }
+        if !self.renders.is_empty() {
+            output.push_str(&format!("\nRenders ({} levels):\n", self.depth));
+            push_component_tree(&mut output, &self.renders, 0);
+        }
+        if self.truncated {
+            output.push_str("... tree truncated\n");
+        }
+        output
+    }
+}
+
+fn push_component_tree(output: &mut String, components: &[ComponentNode], level: usize) {
+    for component in components {
+        output.push_str(&format!(
+            "{}- {} ({}:{}{})\n",
+            "  ".repeat(level),
+            component.name,
+            component.file_path,
+            component.line,
+            if component.recursive {
+                ", recursive"
+            } else {
+                ""
+            }
+        ));
+        push_component_tree(output, &component.children, level + 1);
+    }
+}
+
+impl ToMarkdown for RelatedFilesResponse {
+    fn to_markdown(&self) -> String {
+        let history = match self.history_commits {
+            Some(commits) => format!("{} commits of git history", commits),
+            None => "no git history".to_string(),
+        };
+        if self.related.is_empty() {
+            return format!(
+                "No files related to '{}' found ({}).",
+                self.file_path, history
+            );
+        }
+
+        let mut output = format!(
+            "Files related to '{}' ({} found, {}, {} ms)\n\n",
+            self.file_path, self.total_count, history, self.search_time_ms
+        );
+        for file in &self.related {
+            output.push_str(&format!(
+                "📄 {} (score {:.2})\n",
+                file.file_path, file.score
+            ));
+            for reason in &file.reasons {
+                output.push_str(&format!(
+                    "   - {}: {}\n",
+                    reason.signal.as_str(),
+                    reason.detail
+                ));
+            }
+        }
+        if self.total_count > self.related.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.total_count - self.related.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for OverviewResponse {
+    fn to_markdown(&self) -> String {
+        let Some(ref summary) = self.summary else {
+            return format!("No indexed files under '{}'.", self.path);
+        };
+
+        let mut output = format!(
+            "Overview of '{}': {}\n",
+            self.path,
+            format_directory_totals(summary)
+        );
+
+        if !summary.entry_files.is_empty() {
+            output.push_str("\n## Entry files\n\n");
+            for path in &summary.entry_files {
+                output.push_str(&format!("- {}\n", path));
+            }
+        }
+
+        if !summary.top_symbols.is_empty() {
+            output.push_str("\n## Top symbols\n\n");
+            for symbol in &summary.top_symbols {
+                output.push_str(&format!(
+                    "- {} ({}) in {}, {} references\n",
+                    symbol.name, symbol.kind, symbol.file_path, symbol.references
+                ));
+            }
+        }
+
+        if !self.subdirectories.is_empty() {
+            output.push_str(&format!(
+                "\n## Subdirectories ({})\n\n",
+                self.subdirectory_count
+            ));
+            for directory in &self.subdirectories {
+                output.push_str(&format!(
+                    "📁 {}/: {}\n",
+                    directory.path,
+                    format_directory_totals(directory)
+                ));
+                if !directory.entry_files.is_empty() {
+                    output.push_str(&format!(
+                        "   entry files: {}\n",
+                        directory.entry_files.join(", ")
+                    ));
+                }
+                if !directory.top_symbols.is_empty() {
+                    let names: Vec<&str> = directory
+                        .top_symbols
+                        .iter()
+                        .take(MAX_SUBDIRECTORY_SYMBOLS)
+                        .map(|s| s.name.as_str())
+                        .collect();
+                    output.push_str(&format!("   top symbols: {}\n", names.join(", ")));
+                }
+            }
+            if self.subdirectory_count > self.subdirectories.len() {
+                output.push_str(&format!(
+                    "... and {} more\n",
+                    self.subdirectory_count - self.subdirectories.len()
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+/// "12 files, 3400 lines (rust 10, toml 2)"
+fn format_directory_totals(summary: &DirectorySummary) -> String {
+    let languages: Vec<String> = summary
+        .languages
+        .iter()
+        .map(|l| format!("{} {}", l.language, l.file_count))
+        .collect();
+    format!(
+        "{} files, {} lines ({})",
+        summary.file_count,
+        summary.line_count,
+        languages.join(", ")
+    )
+}
+
+impl ToMarkdown for ExploreResponse {
+    fn to_markdown(&self) -> String {
+        if self.entries.is_empty() {
+            return format!("Directory '{}' is empty.", self.path);
+        }
+
+        let mut output = format!(
+            "Directory '{}' ({} entries, {} ms)\n\n",
+            self.path, self.entry_count, self.search_time_ms
+        );
+        for entry in &self.entries {
+            let (icon, slash) = if entry.is_dir {
+                ("📁", "/")
+            } else {
+                ("📄", "")
+            };
+            output.push_str(&format!(
+                "{} {}{}: {}\n",
+                icon,
+                entry.name,
+                slash,
+                format_explore_entry(entry, self.listed_at)
+            ));
+            if !entry.top_symbols.is_empty() {
+                output.push_str(&format!(
+                    "   top symbols: {}\n",
+                    entry.top_symbols.join(", ")
+                ));
+            }
+            for issue in &entry.issues {
+                output.push_str(&format!("   issue: {}\n", issue));
+            }
+        }
+        if self.entry_count > self.entries.len() {
+            output.push_str(&format!(
+                "... and {} more\n",
+                self.entry_count - self.entries.len()
+            ));
+        }
+
+        output
+    }
+}
+
+/// Language, counts, indexing time and exclusion of an entry of a listing
+/// made at `listed_at`
+fn format_explore_entry(entry: &ExploreEntry, listed_at: i64) -> String {
+    let mut parts = Vec::new();
+    if let Some(ref language) = entry.language {
+        parts.push(language.clone());
+    }
+    if let Some(count) = entry.indexed_file_count.filter(|&count| count > 0) {
+        parts.push(format!("{} indexed files", count));
+    }
+    if let Some(count) = entry.symbol_count {
+        parts.push(format!("{} symbols", count));
+    }
+    match entry.indexed_at {
+        Some(indexed_at) => parts.push(format!(
+            "indexed {} ago",
+            format_age(listed_at.saturating_sub(indexed_at))
+        )),
+        None if entry.excluded.is_none() => parts.push("not indexed".to_string()),
+        None => {}
+    }
+    if entry.freshness == Some(Freshness::Stale) {
+        parts.push("stale".to_string());
+    }
+    if entry.generated {
+        parts.push("generated".to_string());
+    }
+    if entry.outline {
+        parts.push("outline".to_string());
+    }
+    if let Some(reason) = entry.excluded {
+        parts.push(format!("excluded ({})", reason.as_str()));
+    }
+    parts.join(", ")
+}
+
+/// Duration in seconds, in its largest whole unit
+fn format_age(seconds: i64) -> String {
+    match seconds {
+        ..60 => format!("{}s", seconds.max(0)),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+impl ToMarkdown for SessionSummary {
+    fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "Session '{}': {} searches, {} files viewed\n",
+            self.session_id,
+            self.search_count,
+            self.files.len()
+        );
+
+        if !self.queries.is_empty() {
+            output.push_str("\n## Searches\n\n");
+            let forgotten = self.search_count - self.queries.len();
+            if forgotten > 0 {
+                output.push_str(&format!("... {} earlier searches\n", forgotten));
+            }
+            for (index, query) in self.queries.iter().enumerate() {
+                output.push_str(&format!(
+                    "{}. '{}' ({} results)\n",
+                    forgotten + index + 1,
+                    query.query,
+                    query.result_count
+                ));
+            }
+        }
+
+        if !self.files.is_empty() {
+            output.push_str("\n## Files viewed\n\n");
+            for file in &self.files {
+                let lines: Vec<String> = file
+                    .lines
+                    .iter()
+                    .map(|range| format!("{}-{}", range.start_line, range.end_line))
+                    .collect();
+                output.push_str(&format!(
+                    "📄 {}:{} ({} searches, best score {:.2})\n",
+                    file.file_path,
+                    lines.join(","),
+                    file.views,
+                    file.best_score
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for UnusedSymbolsResponse {
+    fn to_markdown(&self) -> String {
+        if self.symbols.is_empty() {
+            return "No unused exported symbols found in the index.".to_string();
+        }
+
+        let mut output = format!(
+            "# Unused symbols ({} found, {} ms)\n",
+            self.total_count, self.search_time_ms
+        );
+        let mut current_file = None;
+        for symbol in &self.symbols {
+            if current_file != Some(&symbol.file_path) {
+                output.push_str(&format!("\n## {}\n", symbol.file_path));
+                current_file = Some(&symbol.file_path);
+            }
+            let usage = match symbol.local_references {
+                0 => "never referenced".to_string(),
+                n => format!("{} reference(s) in its own file only", n),
+            };
+            output.push_str(&format!(
+                "- `{}` ({}, line {}): {}, {} confidence\n",
+                symbol.name,
+                symbol.kind,
+                symbol.start_line,
+                usage,
+                symbol.confidence.as_str()
+            ));
+        }
+        if self.total_count > self.symbols.len() {
+            output.push_str(&format!(
+                "\n... and {} more\n",
+                self.total_count - self.symbols.len()
+            ));
+        }
+
+        output.push_str("\n## Caveats\n");
+        for caveat in &self.caveats {
+            output.push_str(&format!(
+                "- {} ({} confidence): {}\n",
+                caveat.language,
+                caveat.confidence.as_str(),
+                caveat.caveat
+            ));
+        }
+
+        output
+    }
+}
+
+impl ToMarkdown for SemanticDiffResponse {
+    fn to_markdown(&self) -> String {
+        if self.files.is_empty() {
+            return format!("No changes between {} and {}.", self.from, self.to);
+        }
+
+        let mut output = format!(
+            "Semantic diff {}..{}: {} symbol change(s) in {} file(s) ({} ms)\n\n",
+            self.from,
+            self.to,
+            self.total_changes,
+            self.files.len(),
+            self.search_time_ms
+        );
+        for file in &self.files {
+            let renamed_from = file
+                .old_path
+                .as_ref()
+                .map(|old| format!(" from {}", old))
+                .unwrap_or_default();
+            match file.language {
+                Some(ref language) => output.push_str(&format!(
+                    "📄 {} ({}{}, {})\n",
+                    file.path, file.status, renamed_from, language
+                )),
+                None => {
+                    output.push_str(&format!(
+                        "📄 {} ({}{}, not analyzed)\n",
+                        file.path, file.status, renamed_from
+                    ));
+                    continue;
+                }
+            }
+
+            if file.changes.is_empty() {
+                output.push_str("   No symbol changes\n");
+            }
+            for change in &file.changes {
+                output.push_str(&format_symbol_change(change));
+            }
+        }
+        if self.skipped_files > 0 {
+            output.push_str(&format!(
+                "\n... and {} more changed files not analyzed\n",
+                self.skipped_files
+            ));
+        }
+
+        output
+    }
+}
+
+/// Render one symbol change as a line of a semantic diff, followed by the
+/// old and new signatures when they changed.
+fn format_symbol_change(change: &SymbolChange) -> String {
+    let marker = match change.change {
+        ChangeKind::Added => "+",
+        ChangeKind::Removed => "-",
+        ChangeKind::Modified | ChangeKind::SignatureChanged => "~",
+        ChangeKind::Moved | ChangeKind::Renamed => ">",
+    };
+    let name = match change.parent {
+        Some(ref parent) => format!("{}::{}", parent, change.name),
+        None => change.name.clone(),
+    };
+
+    let mut line = format!(
+        "   {} {} ({}) :{}-{} {}",
+        marker,
+        name,
+        change.kind,
+        change.start_line,
+        change.end_line,
+        change.change.as_str().replace('_', " ")
+    );
+    if let Some(ref old_name) = change.old_name {
+        line.push_str(&format!(" from '{}'", old_name));
+    }
+    if let Some(ref old_file) = change.old_file_path {
+        line.push_str(&format!(
+            " from {}:{}",
+            old_file,
+            change.old_start_line.unwrap_or_default()
+        ));
+    }
+    if let Some(similarity) = change.similarity {
+        line.push_str(&format!(" (similarity {:.2})", similarity));
+    }
+    line.push('\n');
+
+    if change.change == ChangeKind::SignatureChanged {
+        if let Some(ref old) = change.old_signature {
+            line.push_str(&format!("       - {}\n", old.trim()));
+        }
+        if let Some(ref new) = change.signature {
+            line.push_str(&format!("       + {}\n", new.trim()));
+        }
+    }
+    line
+}
+
+/// Render the build target and defines of a C or C++ source, one line each
+fn format_compilation(compilation: &Compilation) -> String {
+    let mut output = String::new();
+    if let Some(ref target) = compilation.target {
+        output.push_str(&format!(
+            "Build target: {} ({} files)\n",
+            target, compilation.target_files
+        ));
+    }
+    if !compilation.defines.is_empty() {
+        output.push_str(&format!("Defines: {}\n", compilation.defines.join(", ")));
+    }
+    output
+}
+
+/// Render imported symbols as `{ A, B as C }`.
+fn format_imported_symbols(symbols: &[ImportedSymbol]) -> String {
+    let names: Vec<String> = symbols.iter().map(ToString::to_string).collect();
+    format!("{{ {} }}", names.join(", "))
+}
+
+impl ToMarkdown for SymbolExplanation {
+    fn to_markdown(&self) -> String {
+        if !self.found {
+            return match self.as_of {
+                Some(generation) => format!(
+                    "Symbol '{}' not found at index generation {}.",
+                    self.name, generation
+                ),
+                None => format!("Symbol '{}' not found in the index.", self.name),
+            };
+        }
+
+        let mut output = format!("# Symbol: {}\n\n", self.name);
+
+        match self.as_of {
+            Some(generation) => output.push_str(&format!(
+                "Found {} definition(s) as of index generation {}, referenced {} time(s) across {} file(s)\n\n",
+                self.definitions.len(),
+                generation,
+                self.usage_count,
+                self.usage_files
+            )),
+            None => output.push_str(&format!(
+                "Found {} definition(s), referenced {} time(s) across {} file(s)\n\n",
+                self.definitions.len(),
+                self.usage_count,
+                self.usage_files
+            )),
+        }
+
+        for (i, def) in self.definitions.iter().enumerate() {
+            let qualifiers = def.qualifiers.names();
+            if qualifiers.is_empty() {
+                output.push_str(&format!("## Definition {} ({})\n", i + 1, def.kind));
+            } else {
+                output.push_str(&format!(
+                    "## Definition {} ({} {})\n",
+                    i + 1,
+                    qualifiers.join(" "),
+                    def.kind
+                ));
+            }
+
+            output.push_str(&format!(
+                "📄 {}:{}-{}\n\n",
+                def.file_path, def.start_line, def.end_line
+            ));
+
+            if let Some(ref cfg) = def.cfg_context {
+                output.push_str(&format!("Compiled only when: `{}`\n\n", cfg));
+            }
+
+            if let Some(ref sig) = def.signature {
+                output.push_str(&format!("```\n{}\n```\n\n", sig));
+            }
+
+            if let Some(ref doc) = def.doc_comment {
+                output.push_str(&format!("**Documentation:**\n{}\n\n", doc));
+            }
+        }
+
+        if !self.related_symbols.is_empty() {
+            output.push_str("## Related Symbols\n\n");
+            for related in self.related_symbols.iter().take(MAX_RELATED_SYMBOLS) {
+                output.push_str(&format!("- {}\n", related));
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        ConfigUsage, CrossLanguageLink, DependencyInfo, EntrypointLocation, ExclusionReason,
+        ExploreEntry, FileDiff, HandlerDefinition, Implementation, LanguageCaveat, RouteLocation,
+        SymbolDefinition, SymbolDependency, UnusedSymbol, UsageConfidence,
+    };
+    use crate::results::{
+        BatchSearchEntry, LineRange, NearMiss, ScoreBoost, ScoreExplanation, SearchResult,
+        SearchResultKind, SearchResultMetadata, SessionQuery, TagFacet, TermMatch, ViewedFile,
+    };
+    use semantiq_index::IndexIssueRecord;
+    use semantiq_parser::SymbolQualifiers;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_search_results_markdown() {
+        let mut result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "src/lib.rs".to_string(),
+            3,
+            5,
+            "fn parse() {}".to_string(),
+            0.9,
+        )
+        .with_metadata(SearchResultMetadata {
+            symbol_name: Some("parse".to_string()),
+            symbol_kind: Some("function".to_string()),
+            ..Default::default()
+        });
+        result.stale = true;
+
+        let mut results = SearchResults::new("parse".to_string(), vec![result], 12);
+        results.file_tags.insert(
+            "src/lib.rs".to_string(),
+            vec!["parser".to_string(), "token".to_string()],
+        );
+        results.facets.push(TagFacet {
+            tag: "parser".to_string(),
+            count: 1,
+        });
+
+        let output = results.to_markdown();
+        assert!(output.starts_with("Found 1 results for 'parse' (12 ms)"));
+        assert!(output.contains("Tags: parser (1) (narrow with tags:name)"));
+        assert!(output.contains("📄 src/lib.rs\n   Lines 3-5 | Score: 0.90 | ⚠️ stale"));
+        assert!(output.contains("   Tags: parser, token\n   Symbol: parse (function)"));
+        assert!(output.contains("```\n   fn parse() {}\n   ```"));
+    }
+
+    #[test]
+    fn test_search_results_markdown_titles_results() {
+        let result = SearchResult::new(
+            SearchResultKind::SemanticMatch,
+            "src/config.rs".to_string(),
+            10,
+            40,
+            "pub fn load(path: &Path) -> Config {\n    let text = read(path);".to_string(),
+            0.8,
+        )
+        .with_metadata(SearchResultMetadata {
+            title: Some("pub fn load(path: &Path) -> Config — Load the configuration.".to_string()),
+            ..Default::default()
+        });
+
+        let output = SearchResults::new("load config".to_string(), vec![result], 3).to_markdown();
+        assert!(output.contains(
+            "📄 src/config.rs — pub fn load(path: &Path) -> Config — Load the configuration.\n   Lines 10-40"
+        ));
+        // The title stands for the raw chunk content
+        assert!(!output.contains("```"));
+    }
+
+    #[test]
+    fn test_search_results_markdown_shows_score_explanation() {
+        let mut explanation = ScoreExplanation::new("symbol", 0.85);
+        explanation.term_matches.push(TermMatch {
+            term: "parse".to_string(),
+            match_kind: Some("prefix".to_string()),
+            start: Some(0),
+            end: Some(5),
+        });
+        explanation.boosts.push(ScoreBoost {
+            name: "kind:function".to_string(),
+            factor: 1.15,
+        });
+        explanation.thresholds.min_score = 0.35;
+
+        let result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "src/lib.rs".to_string(),
+            3,
+            5,
+            "fn parse_all() {}".to_string(),
+            0.98,
+        )
+        .with_metadata(SearchResultMetadata {
+            explanation: Some(explanation),
+            ..Default::default()
+        });
+
+        let output = SearchResults::new("parse".to_string(), vec![result], 1).to_markdown();
+        assert!(output.contains("Why: symbol 0.85 × kind:function 1.15"));
+        assert!(output.contains("terms: 'parse' at 0..5"));
+        assert!(output.contains("min score 0.35"));
+    }
+
+    #[test]
+    fn test_empty_search_markdown_shows_diagnostics() {
+        let mut results = SearchResults::new("confgi".to_string(), Vec::new(), 3);
+        results.diagnostics = Some(SearchDiagnostics {
+            embedded_chunks: 0,
+            total_chunks: 12,
+            semantic_search: false,
+            near_misses: vec![NearMiss {
+                file_path: "src/config.rs".to_string(),
+                start_line: 4,
+                end_line: 4,
+                strategy: "text".to_string(),
+                score: 0.21,
+                threshold: 0.35,
+            }],
+            suggestions: vec!["config".to_string()],
+            hints: vec!["No chunk has an embedding yet".to_string()],
+            ..Default::default()
+        });
+
+        let output = results.to_markdown();
+        assert!(output.starts_with("Found 0 results for 'confgi'"));
+        assert!(output.contains("## Why no results?\n\n- No chunk has an embedding yet\n"));
+        assert!(
+            output.contains("- Index: 12 chunks, 0 with embeddings (semantic search not used)")
+        );
+        assert!(output.contains("- src/config.rs:4-4 (text) | Score: 0.21 < 0.35"));
+        assert!(output.contains("Did you mean: 'config'?"));
+    }
+
+    #[test]
+    fn test_batch_search_markdown() {
+        let results = SearchResults::new(
+            "load config".to_string(),
+            vec![SearchResult::new(
+                SearchResultKind::Symbol,
+                "src/config.rs".to_string(),
+                3,
+                9,
+                "fn load_config()".to_string(),
+                0.9,
+            )],
+            4,
+        );
+        let response = BatchSearchResponse {
+            error_count: 1,
+            search_time_ms: 6,
+            searches: vec![
+                BatchSearchEntry {
+                    query: "load config".to_string(),
+                    results: Some(results.clone()),
+                    error: None,
+                },
+                BatchSearchEntry {
+                    query: "parse args".to_string(),
+                    results: None,
+                    error: Some("Search failed: an internal error occurred".to_string()),
+                },
+            ],
+        };
+        assert_eq!(
+            response.to_markdown(),
+            format!(
+                "Ran 2 searches (6 ms, 1 failed)\n\n## 1. load config\n\n{}\n## 2. parse args\n\n❌ Search failed: an internal error occurred\n",
+                results.to_markdown()
+            )
+        );
+    }
+
+    #[test]
+    fn test_references_markdown_lists_cross_language_links() {
+        let response = ReferencesResponse {
+            symbol: "get_user".to_string(),
+            total_count: 0,
+            search_time_ms: 2,
+            definitions: vec![],
+            usages: vec![],
+            as_of: None,
+            cached: false,
+            links: vec![
+                CrossLanguageLink {
+                    name: "getUser".to_string(),
+                    framework: Some("napi".to_string()),
+                    file_path: "core/src/lib.rs".to_string(),
+                    line: 12,
+                },
+                CrossLanguageLink {
+                    name: "GetUser".to_string(),
+                    framework: None,
+                    file_path: "bindings/go/user.go".to_string(),
+                    line: 4,
+                },
+            ],
+        };
+
+        assert_eq!(
+            response.to_markdown(),
+            "Found 0 references to 'get_user' (2 ms)\n\n\
+             ## Linked in other languages\n\n\
+             🔗 getUser (napi) at core/src/lib.rs:12\n\
+             🔗 GetUser at bindings/go/user.go:4\n\n"
+        );
+    }
+
+    #[test]
+    fn test_references_markdown_truncates_usages() {
+        let usages = (0..25)
+            .map(|i| {
+                SearchResult::new(
+                    SearchResultKind::Reference,
+                    format!("f{}.rs", i),
+                    i,
+                    i,
+                    "run();".to_string(),
+                    0.5,
+                )
+            })
+            .collect();
+        let response = ReferencesResponse {
+            symbol: "run".to_string(),
+            total_count: 25,
+            search_time_ms: 1,
+            definitions: vec![],
+            usages,
+            links: vec![],
+            as_of: None,
+            cached: true,
+        };
+
+        let output = response.to_markdown();
+        assert!(output.starts_with("Found 25 references to 'run' (1 ms, cached)\n"));
+        assert!(!output.contains("## Definitions"));
+        assert!(!output.contains("## Linked in other languages"));
+        assert!(output.contains("## Usages (25 found)"));
+        assert!(output.contains("... and 5 more usages"));
+    }
+
+    #[test]
+    fn test_dependencies_markdown() {
+        let response = DependenciesResponse {
+            file_path: "src/main.rs".to_string(),
+            package: None,
+            compilation: Some(Compilation {
+                target: Some("app".to_string()),
+                target_files: 12,
+                defines: vec!["DEBUG".to_string(), "VERSION=2".to_string()],
+                include_dirs: vec!["include".to_string()],
+            }),
+            imports: vec![DependencyInfo {
+                target_path: "crate::config".to_string(),
+                import_name: Some("Config".to_string()),
+                kind: "local".to_string(),
+                symbols: Vec::new(),
+                package: None,
+                scope: None,
+                resolved_path: None,
+                via: None,
+            }],
+            imported_by: vec![],
+            symbol: None,
+            uses: Vec::new(),
+            used_by: Vec::new(),
+            imports_error: None,
+            imported_by_error: Some("boom".to_string()),
+        };
+
+        let output = response.to_markdown();
+        assert!(output.starts_with(
+            "Dependency analysis for 'src/main.rs'\nBuild target: app (12 files)\nDefines: DEBUG, VERSION=2\n\n"
+        ));
+        assert!(output.contains("## Imports (1 dependencies)"));
+        assert!(output.contains("→ crate::config (as Config) [local]"));
+        assert!(output.contains("Could not analyze dependents: boom"));
+    }
+
+    #[test]
+    fn test_dependencies_markdown_lists_imported_symbols() {
+        let response = DependenciesResponse {
+            file_path: "src/app.ts".to_string(),
+            package: None,
+            compilation: None,
+            imports: vec![DependencyInfo {
+                target_path: "./x".to_string(),
+                import_name: Some("x".to_string()),
+                kind: "local".to_string(),
+                symbols: vec![
+                    ImportedSymbol::new("A", None),
+                    ImportedSymbol::new("B", Some("C".to_string())),
+                ],
+                package: None,
+                scope: None,
+                resolved_path: None,
+                via: None,
+            }],
+            imported_by: vec![DependencyInfo {
+                target_path: "src/main.ts".to_string(),
+                import_name: Some("app".to_string()),
+                kind: "local".to_string(),
+                symbols: vec![ImportedSymbol::new("start", None)],
+                package: None,
+                scope: None,
+                resolved_path: None,
+                via: None,
+            }],
+            symbol: None,
+            uses: Vec::new(),
+            used_by: Vec::new(),
+            imports_error: None,
+            imported_by_error: None,
+        };
+
+        let output = response.to_markdown();
+        assert!(output.contains("→ ./x { A, B as C } [local]"));
+        assert!(output.contains("← src/main.ts { start }"));
+    }
+
+    #[test]
+    fn test_symbol_dependencies_markdown() {
+        let dependency = |name: &str, parent: Option<&str>, line| SymbolDependency {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            file_path: "src/server.rs".to_string(),
+            start_line: 10,
+            end_line: 20,
+            parent: parent.map(str::to_string),
+            line,
+            references: 1,
+        };
+        let response = DependenciesResponse::for_symbol(
+            "src/server.rs",
+            "handle",
+            Ok(vec![dependency("respond", Some("Server"), 12)]),
+            Err(anyhow::anyhow!("boom")),
+        );
+
+        let output = response.to_markdown();
+        assert!(output.starts_with("Dependency analysis for 'handle' in 'src/server.rs'"));
+        assert!(output.contains("## Uses (1 symbols)"));
+        assert!(output.contains(
+            "→ respond (function) in Server at src/server.rs:10 [referenced at line 12]"
+        ));
+        assert!(output.contains("Could not analyze the symbols using it: boom"));
+        assert!(!output.contains("## Imports"));
+    }
+
+    #[test]
+    fn test_unused_symbols_markdown() {
+        let unused = |name: &str, line, local_references| UnusedSymbol {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            file_path: "src/config.rs".to_string(),
+            start_line: line,
+            end_line: line,
+            signature: None,
+            language: "rust".to_string(),
+            confidence: UsageConfidence::High,
+            local_references,
+        };
+        let response = UnusedSymbolsResponse {
+            total_count: 3,
+            search_time_ms: 4,
+            symbols: vec![unused("parse_value", 2, 1), unused("orphan", 3, 0)],
+            caveats: vec![LanguageCaveat {
+                language: "rust".to_string(),
+                confidence: UsageConfidence::High,
+                caveat: "Macros".to_string(),
+            }],
+        };
+
+        let output = response.to_markdown();
+        assert!(output.starts_with("# Unused symbols (3 found, 4 ms)\n\n## src/config.rs\n"));
+        assert!(output.contains(
+            "- `parse_value` (function, line 2): 1 reference(s) in its own file only, high confidence\n"
+        ));
+        assert!(
+            output.contains("- `orphan` (function, line 3): never referenced, high confidence\n")
+        );
+        assert!(output.contains("\n... and 1 more\n"));
+        assert!(output.ends_with("## Caveats\n- rust (high confidence): Macros\n"));
+
+        let empty = UnusedSymbolsResponse {
+            total_count: 0,
+            search_time_ms: 0,
+            symbols: vec![],
+            caveats: vec![],
+        };
+        assert_eq!(
+            empty.to_markdown(),
+            "No unused exported symbols found in the index."
+        );
+    }
+
+    #[test]
+    fn test_implementations_markdown() {
+        let response = ImplementationsResponse {
+            target: "Shape".to_string(),
+            total_count: 3,
+            search_time_ms: 2,
+            implementations: vec![
+                Implementation {
+                    type_name: "Circle".to_string(),
+                    relation: "implements".to_string(),
+                    file_path: "src/circle.rs".to_string(),
+                    start_line: 4,
+                    end_line: 9,
+                    inferred: false,
+                },
+                Implementation {
+                    type_name: "Square".to_string(),
+                    relation: "implements".to_string(),
+                    file_path: "shapes/square.go".to_string(),
+                    start_line: 12,
+                    end_line: 12,
+                    inferred: true,
+                },
+            ],
+        };
+
+        let output = response.to_markdown();
+        assert!(output.starts_with("Implementations of 'Shape' (3 found, 2 ms)\n\n"));
+        assert!(output.contains("📄 src/circle.rs:4-9 Circle (implements)\n"));
+        assert!(
+            output
+                .contains("📄 shapes/square.go:12-12 Square (implements, inferred from methods)\n")
+        );
+        assert!(output.ends_with("... and 1 more\n"));
+
+        let empty = ImplementationsResponse {
+            target: "Nope".to_string(),
+            total_count: 0,
+            search_time_ms: 0,
+            implementations: vec![],
+        };
+        assert_eq!(
+            empty.to_markdown(),
+            "No implementations of 'Nope' found in the index."
+        );
+    }
+
+    #[test]
+    fn test_entrypoints_markdown() {
+        let location =
+            |kind: &str, name: &str, handler: Option<&str>, file_path: &str| EntrypointLocation {
+                kind: kind.to_string(),
+                name: name.to_string(),
+                handler: handler.map(str::to_string),
+                file_path: file_path.to_string(),
+                start_line: 3,
+                end_line: 8,
+            };
+        let response = EntrypointsResponse {
+            path: ".".to_string(),
+            kind: None,
+            total_count: 4,
+            counts: BTreeMap::from([("main".to_string(), 1), ("route".to_string(), 3)]),
+            search_time_ms: 1,
+            entrypoints: vec![
+                location("main", "main", None, "src/main.rs"),
+                location("route", "GET /users", Some("list_users"), "src/api.rs"),
+                location("route", "POST /users", Some("create_user"), "src/api.rs"),
+            ],
+        };
+
+        let output = response.to_markdown();
+        assert!(output.starts_with("Entry points of '.' (4 found, 1 ms)\n"));
+        assert!(output.contains("\n## Main functions (1)\n\n📄 src/main.rs:3 main\n"));
+        assert!(output.contains(
+            "\n## HTTP routes (3)\n\n📄 src/api.rs:3 GET /users → list_users\n📄 src/api.rs:3 POST /users → create_user\n"
+        ));
+        assert!(output.ends_with("... and 1 more\n"));
+
+        let empty = EntrypointsResponse {
+            path: "docs".to_string(),
+            kind: Some("route".to_string()),
+            total_count: 0,
+            counts: BTreeMap::new(),
+            search_time_ms: 0,
+            entrypoints: vec![],
+        };
+        assert_eq!(
+            empty.to_markdown(),
+            "No route entry points found under 'docs'."
+        );
+    }
+
+    #[test]
+    fn test_routes_markdown() {
+        let response = RoutesResponse {
+            method: Some("POST".to_string()),
+            path: Some("/api/users".to_string()),
+            total_count: 2,
+            search_time_ms: 1,
+            routes: vec![RouteLocation {
+                method: Some("POST".to_string()),
+                path: "/users".to_string(),
+                handler: Some("createUser".to_string()),
+                file_path: "src/users.js".to_string(),
+                start_line: 4,
+                end_line: 4,
+                handler_definition: Some(HandlerDefinition {
+                    file_path: "src/handlers.js".to_string(),
+                    kind: "function".to_string(),
+                    start_line: 1,
+                    end_line: 3,
+                    signature: None,
+                }),
+                matched: Some(RouteMatch::Suffix),
+            }],
+        };
+        assert_eq!(
+            response.to_markdown(),
+            "HTTP routes matching 'POST /api/users' (2 found, 1 ms)\n\n📄 src/users.js:4 POST /users → createUser (src/handlers.js:1) [matches the end of the path: mounted under a prefix?]\n... and 1 more\n"
+        );
+
+        let empty = RoutesResponse {
+            method: None,
+            path: Some("/health".to_string()),
+            total_count: 0,
+            search_time_ms: 0,
+            routes: vec![],
+        };
+        assert_eq!(empty.to_markdown(), "No HTTP route matches '/health'.");
+    }
+
+    #[test]
+    fn test_config_usage_markdown() {
+        let response = ConfigUsageResponse {
+            name: "DATABASE_URL".to_string(),
+            source: None,
+            access: None,
+            total_count: 3,
+            read_count: 2,
+            write_count: 1,
+            search_time_ms: 2,
+            usages: vec![
+                ConfigUsage {
+                    name: "DATABASE_URL".to_string(),
+                    source: "env".to_string(),
+                    access: "write".to_string(),
+                    file_path: "tests/setup.rs".to_string(),
+                    line: 5,
+                    symbol: Some("setup".to_string()),
+                    code: Some("std::env::set_var(\"DATABASE_URL\", url);".to_string()),
+                },
+                ConfigUsage {
+                    name: "DATABASE_URL".to_string(),
+                    source: "env".to_string(),
+                    access: "read".to_string(),
+                    file_path: "src/db.rs".to_string(),
+                    line: 12,
+                    symbol: None,
+                    code: None,
+                },
+            ],
+            similar: vec![],
+        };
+        assert_eq!(
+            response.to_markdown(),
+            "Reads and writes of 'DATABASE_URL' (3 found: 1 write, 2 reads, 2 ms)\n\n📄 tests/setup.rs:5 [write] env DATABASE_URL in setup\n   std::env::set_var(\"DATABASE_URL\", url);\n📄 src/db.rs:12 [read] env DATABASE_URL\n... and 1 more\n"
+        );
+
+        let empty = ConfigUsageResponse {
+            name: "database_url".to_string(),
+            total_count: 0,
+            read_count: 0,
+            write_count: 0,
+            usages: vec![],
+            similar: vec!["DATABASE_URL".to_string()],
+            ..response
+        };
+        assert_eq!(
+            empty.to_markdown(),
+            "No read or write of 'database_url' found. Indexed names resembling it: DATABASE_URL"
+        );
+    }
+
+    #[test]
+    fn test_index_issues_markdown() {
+        let response = IndexIssuesResponse {
+            kind: None,
+            total_count: 3,
+            counts: BTreeMap::from([
+                ("parse_failure".to_string(), 2),
+                ("read_error".to_string(), 1),
+            ]),
+            search_time_ms: 1,
+            issues: vec![IndexIssueRecord {
+                path: "src/broken.rs".to_string(),
+                kind: "parse_failure".to_string(),
+                message: "Failed to parse file".to_string(),
+                recorded_at: 0,
+            }],
+        };
+        assert_eq!(
+            response.to_markdown(),
+            "Indexing issues (3 found: 2 parse_failure, 1 read_error, 1 ms)\n\n\
+             📄 src/broken.rs [parse_failure] Failed to parse file\n\
+             ... and 2 more\n"
+        );
+
+        let empty = IndexIssuesResponse {
+            kind: Some("too_large".to_string()),
+            total_count: 0,
+            issues: vec![],
+            ..response
+        };
+        assert_eq!(
+            empty.to_markdown(),
+            "No indexing issue of kind 'too_large'."
+        );
+    }
+
+    #[test]
+    fn test_explore_markdown() {
+        let entry = |name: &str, is_dir: bool| ExploreEntry {
+            name: name.to_string(),
+            path: format!("src/{}", name),
+            is_dir,
+            language: None,
+            size: None,
+            symbol_count: None,
+            indexed_file_count: None,
+            indexed_at: None,
+            freshness: None,
+            top_symbols: vec![],
+            generated: false,
+            outline: false,
+            excluded: None,
+            issues: vec![],
+        };
+        let response = ExploreResponse {
+            path: "src".to_string(),
+            entry_count: 4,
+            listed_at: 1_700_007_200,
+            search_time_ms: 2,
+            entries: vec![
+                ExploreEntry {
+                    language: Some("rust".to_string()),
+                    indexed_file_count: Some(8),
+                    indexed_at: Some(1_700_006_900),
+                    top_symbols: vec!["Router".to_string(), "serve".to_string()],
+                    ..entry("api", true)
+                },
+                ExploreEntry {
+                    indexed_file_count: Some(0),
+                    excluded: Some(ExclusionReason::ExcludedDirectory),
+                    ..entry("node_modules", true)
+                },
+                ExploreEntry {
+                    language: Some("rust".to_string()),
+                    symbol_count: Some(12),
+                    indexed_at: Some(1_700_000_000),
+                    freshness: Some(Freshness::Stale),
+                    issues: vec!["encoding: guessed windows-1252".to_string()],
+                    ..entry("main.rs", false)
+                },
+            ],
+        };
+        assert_eq!(
+            response.to_markdown(),
+            "Directory 'src' (4 entries, 2 ms)\n\n\
+             📁 api/: rust, 8 indexed files, indexed 5m ago\n\
+             \x20  top symbols: Router, serve\n\
+             📁 node_modules/: excluded (excluded_directory)\n\
+             📄 main.rs: rust, 12 symbols, indexed 2h ago, stale\n\
+             \x20  issue: encoding: guessed windows-1252\n\
+             ... and 1 more\n"
+        );
+
+        let empty = ExploreResponse {
+            entry_count: 0,
+            entries: vec![],
+            ..response
+        };
+        assert_eq!(empty.to_markdown(), "Directory 'src' is empty.");
+    }
+
+    #[test]
+    fn test_session_summary_markdown() {
+        let summary = SessionSummary {
+            session_id: "chat-1".to_string(),
+            started_at: 1_700_000_000,
+            search_count: 3,
+            queries: vec![
+                SessionQuery {
+                    query: "upload retry".to_string(),
+                    result_count: 4,
+                    searched_at: 1_700_000_010,
+                },
+                SessionQuery {
+                    query: "backoff".to_string(),
+                    result_count: 1,
+                    searched_at: 1_700_000_020,
+                },
+            ],
+            files: vec![ViewedFile {
+                file_path: "src/upload.rs".to_string(),
+                views: 2,
+                best_score: 0.84,
+                lines: vec![
+                    LineRange {
+                        start_line: 10,
+                        end_line: 20,
+                    },
+                    LineRange {
+                        start_line: 42,
+                        end_line: 48,
+                    },
+                ],
+                last_viewed_at: 1_700_000_020,
+            }],
+        };
+
+        let output = summary.to_markdown();
+        assert!(output.starts_with("Session 'chat-1': 3 searches, 1 files viewed\n"));
+        assert!(output.contains(
+            "... 1 earlier searches\n2. 'upload retry' (4 results)\n3. 'backoff' (1 results)\n"
+        ));
+        assert!(output.contains("📄 src/upload.rs:10-20,42-48 (2 searches, best score 0.84)\n"));
+    }
+
+    #[test]
+    fn test_component_tree_markdown() {
+        let node = |name: &str, file_path: &str, line, children| ComponentNode {
+            name: name.to_string(),
+            file_path: file_path.to_string(),
+            line,
+            children,
+            recursive: false,
+        };
+        let response = ComponentTreeResponse {
+            component: "App".to_string(),
+            depth: 3,
+            search_time_ms: 1,
+            rendered_by: vec![node("Root", "src/main.tsx", 5, vec![])],
+            renders: vec![node(
+                "Layout",
+                "src/App.tsx",
+                10,
+                vec![
+                    node("Header", "src/Layout.tsx", 4, vec![]),
+                    ComponentNode {
+                        recursive: true,
+                        ..node("App", "src/Layout.tsx", 8, vec![])
+                    },
+                ],
+            )],
+            truncated: false,
+        };
+
+        assert_eq!(
+            response.to_markdown(),
+            "Component tree of 'App' (1 ms)\n\
+             \nRendered by:\n\
+             📄 src/main.tsx:5 Root\n\
+             \nRenders (3 levels):\n\
+             - Layout (src/App.tsx:10)\n  \
+             - Header (src/Layout.tsx:4)\n  \
+             - App (src/Layout.tsx:8, recursive)\n"
+        );
+
+        let empty = ComponentTreeResponse {
+            rendered_by: vec![],
+            renders: vec![],
+            ..response
+        };
+        assert_eq!(
+            empty.to_markdown(),
+            "No components rendering or rendered by 'App' found in the index."
+        );
+    }
+
+    #[test]
+    fn test_semantic_diff_markdown() {
+        let change = |change, name: &str, line| SymbolChange {
+            change,
+            name: name.to_string(),
+            kind: "function".to_string(),
+            parent: None,
+            file_path: "src/lib.rs".to_string(),
+            start_line: line,
+            end_line: line + 2,
+            old_file_path: None,
+            old_name: None,
+            old_start_line: None,
+            old_signature: None,
+            signature: None,
+            similarity: None,
+        };
+        let response = SemanticDiffResponse {
+            from: "v1".to_string(),
+            to: "v2".to_string(),
+            files: vec![
+                FileDiff {
+                    path: "src/lib.rs".to_string(),
+                    old_path: None,
+                    status: "modified".to_string(),
+                    language: Some("rust".to_string()),
+                    changes: vec![
+                        SymbolChange {
+                            old_signature: Some("fn parse(input: &str) {".to_string()),
+                            signature: Some("fn parse(input: &str, strict: bool) {".to_string()),
+                            ..change(ChangeKind::SignatureChanged, "parse", 1)
+                        },
+                        SymbolChange {
+                            file_path: "src/util.rs".to_string(),
+                            old_file_path: Some("src/old.rs".to_string()),
+                            old_start_line: Some(7),
+                            similarity: Some(0.97),
+                            ..change(ChangeKind::Moved, "sum", 5)
+                        },
+                        change(ChangeKind::Removed, "obsolete", 9),
+                    ],
+                },
+                FileDiff {
+                    path: "notes.txt".to_string(),
+                    old_path: None,
+                    status: "added".to_string(),
+                    language: None,
+                    changes: vec![],
+                },
+            ],
+            total_changes: 3,
+            skipped_files: 0,
+            search_time_ms: 4,
+        };
+
+        let output = response.to_markdown();
+        assert!(output.starts_with(
+            "Semantic diff v1..v2: 3 symbol change(s) in 2 file(s) (4 ms)\n\n📄 src/lib.rs (modified, rust)\n"
+        ));
+        assert!(output.contains(
+            "   ~ parse (function) :1-3 signature changed\n       - fn parse(input: &str) {\n       + fn parse(input: &str, strict: bool) {\n"
+        ));
+        assert!(
+            output.contains("   > sum (function) :5-7 moved from src/old.rs:7 (similarity 0.97)\n")
+        );
+        assert!(output.contains("   - obsolete (function) :9-11 removed\n"));
+        assert!(output.ends_with("📄 notes.txt (added, not analyzed)\n"));
+
+        let empty = SemanticDiffResponse {
+            files: vec![],
+            total_changes: 0,
+            ..response
+        };
+        assert_eq!(empty.to_markdown(), "No changes between v1 and v2.");
+    }
+
+    #[test]
+    fn test_explanation_markdown() {
+        let explanation = SymbolExplanation {
+            name: "Config".to_string(),
+            found: true,
+            definitions: vec![SymbolDefinition {
+                file_path: "src/config.rs".to_string(),
+                kind: "struct".to_string(),
+                start_line: 1,
+                end_line: 4,
+                signature: Some("pub struct Config".to_string()),
+                doc_comment: Some("Settings".to_string()),
+                qualifiers: SymbolQualifiers {
+                    is_public: true,
+                    ..SymbolQualifiers::default()
+                },
+                cfg_context: Some(r#"feature = "serde""#.to_string()),
+            }],
+            usage_count: 2,
+            usage_files: 1,
+            related_symbols: vec!["load".to_string()],
+            as_of: None,
+        };
+
+        let output = explanation.to_markdown();
+        assert!(output.starts_with("# Symbol: Config"));
+        assert!(output.contains("Found 1 definition(s), referenced 2 time(s) across 1 file(s)"));
+        assert!(output.contains("## Definition 1 (public struct)"));
+        assert!(output.contains("📄 src/config.rs:1-4"));
+        assert!(output.contains(r#"Compiled only when: `feature = "serde"`"#));
+        assert!(output.contains("**Documentation:**\nSettings"));
+        assert!(output.contains("- load"));
+
+        // At an earlier generation
+        let earlier = SymbolExplanation {
+            as_of: Some(7),
+            ..explanation
+        };
+        assert!(earlier.to_markdown().contains(
+            "Found 1 definition(s) as of index generation 7, referenced 2 time(s) across 1 file(s)\n"
+        ));
+
+        let missing = SymbolExplanation {
+            name: "Nope".to_string(),
+            found: false,
+            definitions: vec![],
+            usage_count: 0,
+            usage_files: 0,
+            related_symbols: vec![],
+            as_of: None,
+        };
+        assert_eq!(
+            missing.to_markdown(),
+            "Symbol 'Nope' not found in the index."
+        );
+        let missing = SymbolExplanation {
+            as_of: Some(7),
+            ..missing
+        };
+        assert_eq!(
+            missing.to_markdown(),
+            "Symbol 'Nope' not found at index generation 7."
+        );
+    }
+}
@@ -0,0 +1,1112 @@
+//! Search and analysis results, and the response types shared by the MCP
+//! tools, the HTTP API and the CLI.
+//!
+//! Every response type serializes to JSON with serde and renders to the
+//! markdown used by the MCP tools through [`ToMarkdown`].
+
+mod dedup;
+mod markdown;
+mod snippet;
+
+use crate::engine::{
+    Compilation, ComponentNode, ConfigUsage, CrossLanguageLink, DependencyInfo, EntrypointLocation,
+    ExploreEntry, FileDiff, Implementation, LanguageCaveat, RelatedFile, RouteLocation,
+    SymbolDependency, UnusedSymbol,
+};
+use crate::query::SearchStrategy;
+use semantiq_index::{DirectorySummary, IndexIssueRecord, SymbolField};
+use semantiq_parser::SymbolQualifiers;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub(crate) use dedup::dedup_results;
+pub use markdown::ToMarkdown;
+pub use snippet::{EnclosingSymbol, Highlight, Snippet, SnippetBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchResultKind {
+    Symbol,
+    TextMatch,
+    SemanticMatch,
+    Reference,
+}
+
+impl SearchResultKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchResultKind::Symbol => "symbol",
+            SearchResultKind::TextMatch => "text_match",
+            SearchResultKind::SemanticMatch => "semantic_match",
+            SearchResultKind::Reference => "reference",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub kind: SearchResultKind,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub content: String,
+    pub score: f32,
+    pub metadata: SearchResultMetadata,
+    /// The file changed since it was indexed; `content` was re-read from disk
+    /// but line ranges may have shifted
+    #[serde(default)]
+    pub stale: bool,
+    /// Part of `content` around the query terms, set by searches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+    /// Lines that matched, when `content` was expanded with context lines or
+    /// to the enclosing symbol (see `SearchOptions::context_lines`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_lines: Option<LineRange>,
+    /// Found in the base index, for a file the local index does not cover yet
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub from_base: bool,
+    /// Exact location of the match in the file: the symbol, chunk or text
+    /// that matched, whatever lines `content` was expanded to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+/// An inclusive range of 1-based lines of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// A range of bytes of a file, for precise edits. Columns are 0-based byte
+/// offsets in the start and end lines, as in tree-sitter; the end is
+/// exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_column: usize,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResultMetadata {
+    pub symbol_name: Option<String>,
+    pub symbol_kind: Option<String>,
+    pub match_type: Option<String>,
+    pub context: Option<String>,
+    /// One-line heading of the result: the signature of its symbol and the
+    /// first sentence of its doc comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Part of the symbol the query matched (symbol matches)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_in: Option<SymbolField>,
+    /// Signature of the symbol, or of the symbol a chunk belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Name of the symbol enclosing that symbol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Visibility, `async`, `static`, `abstract` and generic parameters of the symbol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualifiers: Option<SymbolQualifiers>,
+    /// Conditions under which the symbol is compiled, e.g. `test` or
+    /// `defined(_WIN32)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg_context: Option<String>,
+    /// HTTP route handled, e.g. `POST /api/users`, for results of the route
+    /// filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// The file is generated, set when `SearchOptions::include_generated`
+    /// is enabled
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
+    /// The file is too large to chunk and only its top-level symbols are
+    /// indexed (see `semantiq_index::outline`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outline: bool,
+    /// How the score was computed, set when `SearchOptions::explain_scores` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+    /// Kinds of the overlapping results merged into this one (see
+    /// [`crate::DedupPolicy::MergeOverlapping`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_matched_via: Vec<SearchResultKind>,
+    /// When the file was indexed, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<i64>,
+    /// Hash of the file content that was indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Whether the result matches the file on disk, set by searches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<Freshness>,
+}
+
+/// Whether a search result matches the current content of its file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    /// The file is unchanged since it was indexed (or was read at query time)
+    Fresh,
+    /// The file changed since it was indexed and is being reindexed
+    Stale,
+    /// The file is not indexed or not on disk, so it cannot be compared
+    Unknown,
+}
+
+impl Freshness {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Freshness::Fresh => "fresh",
+            Freshness::Stale => "stale",
+            Freshness::Unknown => "unknown",
+        }
+    }
+}
+
+/// Breakdown of a result's score: `base_score × boosts` (capped at 1.0),
+/// then `× strategy_weight`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Search strategy that produced the result (`semantic`, `bm25`, `symbol` or `text`)
+    pub strategy: String,
+    /// Score before boosts and weighting
+    pub base_score: f32,
+    /// Vector distance between the query and chunk embeddings (semantic matches)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
+    /// Query terms found in the result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub term_matches: Vec<TermMatch>,
+    /// Multiplicative boosts applied to the base score, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boosts: Vec<ScoreBoost>,
+    /// The boosted score exceeded 1.0 and was capped
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub capped: bool,
+    /// Weight of the strategy for the shape of the query
+    pub strategy_weight: f32,
+    /// Thresholds the result had to pass
+    pub thresholds: ScoreThresholds,
+}
+
+impl ScoreExplanation {
+    pub fn new(strategy: &str, base_score: f32) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            base_score,
+            strategy_weight: 1.0,
+            ..Self::default()
+        }
+    }
+
+    /// One-line summary, e.g. `symbol 0.85 × kind 1.15 × length 1.08 (capped) × weight 1.20`
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} {:.2}", self.strategy, self.base_score);
+        if let Some(distance) = self.distance {
+            summary.push_str(&format!(" (distance {:.3})", distance));
+        }
+        for boost in &self.boosts {
+            summary.push_str(&format!(" × {} {:.2}", boost.name, boost.factor));
+        }
+        if self.capped {
+            summary.push_str(" (capped)");
+        }
+        if self.strategy_weight != 1.0 {
+            summary.push_str(&format!(" × weight {:.2}", self.strategy_weight));
+        }
+        summary
+    }
+}
+
+/// A query term found in a result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermMatch {
+    pub term: String,
+    /// How the term matched (`exact`, `prefix`, `contains` or `fts` for symbol names)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_kind: Option<String>,
+    /// Byte offset of the match in the symbol name or line, if it occurs literally
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+}
+
+/// A multiplicative factor applied to a score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBoost {
+    pub name: String,
+    pub factor: f32,
+}
+
+/// Thresholds applied while ranking a result.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreThresholds {
+    /// Minimum final score
+    pub min_score: f32,
+    /// Maximum embedding distance (semantic matches, adaptive per language)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_distance: Option<f32>,
+    /// Minimum similarity (semantic matches, adaptive per language)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_similarity: Option<f32>,
+}
+
+impl SearchResult {
+    pub fn new(
+        kind: SearchResultKind,
+        file_path: String,
+        start_line: usize,
+        end_line: usize,
+        content: String,
+        score: f32,
+    ) -> Self {
+        Self {
+            kind,
+            file_path,
+            start_line,
+            end_line,
+            content,
+            score,
+            metadata: SearchResultMetadata::default(),
+            stale: false,
+            snippet: None,
+            matched_lines: None,
+            from_base: false,
+            span: None,
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: SearchResultMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn location(&self) -> String {
+        if self.start_line == self.end_line {
+            format!("{}:{}", self.file_path, self.start_line)
+        } else {
+            format!("{}:{}-{}", self.file_path, self.start_line, self.end_line)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub total_count: usize,
+    pub search_time_ms: u64,
+    /// Files whose results were stale or deleted and that should be reindexed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale_files: Vec<String>,
+    /// Strategy weights chosen from the shape of the query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<SearchStrategy>,
+    /// Results were served from the query cache
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+    /// Index generation the results were found at; results of a previous
+    /// generation may be stale
+    #[serde(default)]
+    pub index_generation: u64,
+    /// The search ran out of time or was cancelled, and only holds the
+    /// results found until then
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated_by_timeout: bool,
+    /// Why nothing was found, attached when there are no results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<SearchDiagnostics>,
+    /// Best keywords of each file with results, best first
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_tags: BTreeMap<String, Vec<String>>,
+    /// Most common tags among the files with results, to narrow the search
+    /// with `tags:`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub facets: Vec<TagFacet>,
+}
+
+/// Results of several searches run together under one deadline, from
+/// `semantiq_batch_search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSearchResponse {
+    /// Number of searches that failed
+    pub error_count: usize,
+    pub search_time_ms: u64,
+    /// One entry per query, in the order of the request
+    pub searches: Vec<BatchSearchEntry>,
+}
+
+/// Outcome of one search of a batch, keyed by its query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSearchEntry {
+    pub query: String,
+    /// Results, `None` when the search failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<SearchResults>,
+    /// Why the search failed; the other searches of the batch still ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A tag and the number of files with results it is a tag of
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagFacet {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Hints on why a search returned no results
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchDiagnostics {
+    /// Chunks with an embedding, out of `total_chunks`
+    pub embedded_chunks: usize,
+    pub total_chunks: usize,
+    /// Semantic search ran for this query
+    pub semantic_search: bool,
+    /// Matches excluded by the `file_type` and `symbol_kind` filters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filtered_out: Vec<FilteredMatches>,
+    /// Best matches scored below the thresholds, best first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub near_misses: Vec<NearMiss>,
+    /// Queries with misspelled or partial terms replaced by indexed symbol names
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+    /// Actionable explanations, one sentence each
+    pub hints: Vec<String>,
+}
+
+/// Matches excluded by one filter value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilteredMatches {
+    /// `file_type` or `symbol_kind`
+    pub filter: String,
+    /// Extension or symbol kind of the excluded matches
+    pub value: String,
+    pub count: usize,
+}
+
+/// A match rejected by a score threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearMiss {
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// `semantic`, `symbol` or `text`
+    pub strategy: String,
+    pub score: f32,
+    /// Score the match needed
+    pub threshold: f32,
+}
+
+impl SearchResults {
+    pub fn new(query: String, results: Vec<SearchResult>, search_time_ms: u64) -> Self {
+        let total_count = results.len();
+        Self {
+            query,
+            results,
+            total_count,
+            search_time_ms,
+            stale_files: Vec::new(),
+            strategy: None,
+            cached: false,
+            index_generation: 0,
+            truncated_by_timeout: false,
+            diagnostics: None,
+            file_tags: BTreeMap::new(),
+            facets: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    pub fn top(&self, n: usize) -> Vec<&SearchResult> {
+        self.results.iter().take(n).collect()
+    }
+
+    pub fn merge(&mut self, other: SearchResults) {
+        self.results.extend(other.results);
+        self.results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.total_count = self.results.len();
+    }
+}
+
+/// Output format requested by a client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable markdown (default for MCP tools)
+    #[default]
+    Markdown,
+    /// Structured JSON of the response type
+    Json,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "markdown" | "md" => Some(OutputFormat::Markdown),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Render a response in this format
+    pub fn render<T: Serialize + ToMarkdown>(&self, value: &T) -> String {
+        match self {
+            OutputFormat::Markdown => value.to_markdown(),
+            OutputFormat::Json => serde_json::to_string_pretty(value)
+                .unwrap_or_else(|e| format!("{{\"error\": \"serialization failed: {}\"}}", e)),
+        }
+    }
+}
+
+/// References to a symbol, split into definitions and usages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferencesResponse {
+    pub symbol: String,
+    pub total_count: usize,
+    pub search_time_ms: u64,
+    pub definitions: Vec<SearchResult>,
+    pub usages: Vec<SearchResult>,
+    /// Symbols linked to this one in other languages, whose references
+    /// are included, when they were followed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<CrossLanguageLink>,
+    /// Index generation the references were found at, `None` for the
+    /// current index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<u64>,
+    /// References were served from the references cache
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+impl From<SearchResults> for ReferencesResponse {
+    fn from(results: SearchResults) -> Self {
+        let (definitions, usages) = results
+            .results
+            .into_iter()
+            .partition(|r| r.metadata.match_type.as_deref() == Some("definition"));
+
+        Self {
+            symbol: results.query,
+            total_count: results.total_count,
+            search_time_ms: results.search_time_ms,
+            definitions,
+            usages,
+            links: Vec::new(),
+            as_of: None,
+            cached: results.cached,
+        }
+    }
+}
+
+/// Types implementing or extending a trait, interface or base type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImplementationsResponse {
+    pub target: String,
+    /// Number of implementations found, before the limit was applied
+    pub total_count: usize,
+    pub search_time_ms: u64,
+    pub implementations: Vec<Implementation>,
+}
+
+/// Render hierarchy of a React component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentTreeResponse {
+    pub component: String,
+    /// Levels of rendered components followed
+    pub depth: usize,
+    pub search_time_ms: u64,
+    /// Components rendering the component
+    pub rendered_by: Vec<ComponentNode>,
+    /// Components rendered by the component, as a tree
+    pub renders: Vec<ComponentNode>,
+    /// The tree was cut at its maximum size
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Files likely to need editing together with a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedFilesResponse {
+    pub file_path: String,
+    /// Number of related files found, before the limit was applied
+    pub total_count: usize,
+    /// Commits of the file read from the git history, `None` when the
+    /// history was not available
+    #[serde(default)]
+    pub history_commits: Option<usize>,
+    pub search_time_ms: u64,
+    pub related: Vec<RelatedFile>,
+}
+
+/// Map of a directory for orientation, from `semantiq_overview`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverviewResponse {
+    /// Directory relative to the project root ("." for the root)
+    pub path: String,
+    /// Summary of the directory, `None` when no indexed file is under it
+    pub summary: Option<DirectorySummary>,
+    /// Number of subdirectories, before the limit was applied
+    pub subdirectory_count: usize,
+    pub search_time_ms: u64,
+    /// Summaries of the direct subdirectories, most files first
+    pub subdirectories: Vec<DirectorySummary>,
+}
+
+/// Main functions, HTTP routes, CLI subcommands and library exports, from
+/// `semantiq_entrypoints`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntrypointsResponse {
+    /// Directory relative to the project root ("." for the root)
+    pub path: String,
+    /// Kind of entry points requested, `None` for all
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Number of entry points found, before the limit was applied
+    pub total_count: usize,
+    /// Number of entry points found of each kind
+    pub counts: BTreeMap<String, usize>,
+    pub search_time_ms: u64,
+    /// Entry points ordered by kind, file and line
+    pub entrypoints: Vec<EntrypointLocation>,
+}
+
+/// HTTP routes and their handlers, from `semantiq_routes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutesResponse {
+    /// Method requested, `None` for any
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Path requested, `None` for all routes
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Number of routes found, before the limit was applied
+    pub total_count: usize,
+    pub search_time_ms: u64,
+    /// Routes matching the whole path first, then by path and method
+    pub routes: Vec<RouteLocation>,
+}
+
+/// Reads and writes of environment variables and configuration keys, from
+/// `semantiq_config_usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigUsageResponse {
+    /// Name or glob requested
+    pub name: String,
+    /// Source requested (`env` or `config`), `None` for any
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Access requested (`read` or `write`), `None` for any
+    #[serde(default)]
+    pub access: Option<String>,
+    /// Number of sites found, before the limit was applied
+    pub total_count: usize,
+    pub read_count: usize,
+    pub write_count: usize,
+    pub search_time_ms: u64,
+    /// Writes first for each name, then by file and line
+    pub usages: Vec<ConfigUsage>,
+    /// Indexed names resembling the one requested, when none matches it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub similar: Vec<String>,
+}
+
+/// Problems met while indexing files, from `semantiq_index_issues`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexIssuesResponse {
+    /// Kind requested, `None` for any
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Number of issues of the kind requested, before the limit was applied
+    pub total_count: usize,
+    /// Number of issues of each kind recorded
+    pub counts: BTreeMap<String, usize>,
+    pub search_time_ms: u64,
+    /// By path, then kind
+    pub issues: Vec<IndexIssueRecord>,
+}
+
+/// Entries of a directory with their index data, from `semantiq_explore`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExploreResponse {
+    /// Directory relative to the project root ("." for the root)
+    pub path: String,
+    /// Number of entries in the directory, before the limit was applied
+    pub entry_count: usize,
+    /// Unix time of the listing, to tell how long ago entries were indexed
+    pub listed_at: i64,
+    pub search_time_ms: u64,
+    /// Subdirectories first, then by name
+    pub entries: Vec<ExploreEntry>,
+}
+
+/// Queries and files of a search session, from `semantiq_session_summary`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    /// Unix time of the first search of the session
+    pub started_at: i64,
+    /// Number of searches of the session, before older ones were forgotten
+    pub search_count: usize,
+    /// Most recent searches, oldest first
+    pub queries: Vec<SessionQuery>,
+    /// Files surfaced by the searches, most recently viewed first
+    pub files: Vec<ViewedFile>,
+}
+
+/// A search of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionQuery {
+    pub query: String,
+    pub result_count: usize,
+    /// Unix time of the search
+    pub searched_at: i64,
+}
+
+/// A file surfaced by the searches of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewedFile {
+    pub file_path: String,
+    /// Number of searches returning results from the file
+    pub views: usize,
+    /// Highest score of a result from the file
+    pub best_score: f32,
+    /// Lines of the results from the file, merged when they overlap
+    pub lines: Vec<LineRange>,
+    /// Unix time of the last search returning results from the file
+    pub last_viewed_at: i64,
+}
+
+/// Exported symbols that no other file references, from `semantiq analyze unused`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusedSymbolsResponse {
+    /// Number of candidates found, before the limit was applied
+    pub total_count: usize,
+    pub search_time_ms: u64,
+    pub symbols: Vec<UnusedSymbol>,
+    /// Confidence and caveats of each language with candidates
+    pub caveats: Vec<LanguageCaveat>,
+}
+
+/// Symbol-level changes between two git revisions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticDiffResponse {
+    pub from: String,
+    pub to: String,
+    pub files: Vec<FileDiff>,
+    /// Number of symbol changes across all files
+    pub total_changes: usize,
+    /// Changed files left out because the diff touched too many files
+    #[serde(default)]
+    pub skipped_files: usize,
+    pub search_time_ms: u64,
+}
+
+/// Imports of a file and the files importing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependenciesResponse {
+    pub file_path: String,
+    /// Workspace package owning the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// How the file is compiled, for C and C++ sources of the compilation
+    /// database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compilation: Option<Compilation>,
+    pub imports: Vec<DependencyInfo>,
+    pub imported_by: Vec<DependencyInfo>,
+    /// Symbol of the file analyzed instead of its imports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Symbols used by `symbol`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uses: Vec<SymbolDependency>,
+    /// Symbols using `symbol`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub used_by: Vec<SymbolDependency>,
+    /// Set when imports (or the symbols used) could not be analyzed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imports_error: Option<String>,
+    /// Set when dependents (or the symbols using it) could not be analyzed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_by_error: Option<String>,
+}
+
+impl DependenciesResponse {
+    /// Build a response from the results of the import and dependent lookups
+    pub fn new(
+        file_path: &str,
+        imports: anyhow::Result<Vec<DependencyInfo>>,
+        imported_by: anyhow::Result<Vec<DependencyInfo>>,
+    ) -> Self {
+        let (imports, imports_error) = match imports {
+            Ok(deps) => (deps, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        let (imported_by, imported_by_error) = match imported_by {
+            Ok(deps) => (deps, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+
+        Self {
+            file_path: file_path.to_string(),
+            package: None,
+            compilation: None,
+            imports,
+            imported_by,
+            symbol: None,
+            uses: Vec::new(),
+            used_by: Vec::new(),
+            imports_error,
+            imported_by_error,
+        }
+    }
+
+    /// Build a response from the symbols used by and using `symbol`
+    pub fn for_symbol(
+        file_path: &str,
+        symbol: &str,
+        uses: anyhow::Result<Vec<SymbolDependency>>,
+        used_by: anyhow::Result<Vec<SymbolDependency>>,
+    ) -> Self {
+        let mut response = Self::new(file_path, Ok(Vec::new()), Ok(Vec::new()));
+        response.symbol = Some(symbol.to_string());
+        match uses {
+            Ok(uses) => response.uses = uses,
+            Err(e) => response.imports_error = Some(e.to_string()),
+        }
+        match used_by {
+            Ok(used_by) => response.used_by = used_by,
+            Err(e) => response.imported_by_error = Some(e.to_string()),
+        }
+        response
+    }
+
+    /// Set the workspace package owning the file
+    pub fn with_package(mut self, package: Option<String>) -> Self {
+        self.package = package;
+        self
+    }
+
+    /// Set how the file is compiled
+    pub fn with_compilation(mut self, compilation: Option<Compilation>) -> Self {
+        self.compilation = compilation;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_result_new() {
+        let result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "test.rs".to_string(),
+            10,
+            20,
+            "fn test() {}".to_string(),
+            0.9,
+        );
+
+        assert_eq!(result.file_path, "test.rs");
+        assert_eq!(result.start_line, 10);
+        assert_eq!(result.end_line, 20);
+        assert_eq!(result.score, 0.9);
+        assert_eq!(result.kind, SearchResultKind::Symbol);
+    }
+
+    #[test]
+    fn test_search_result_location_single_line() {
+        let result = SearchResult::new(
+            SearchResultKind::TextMatch,
+            "src/main.rs".to_string(),
+            42,
+            42,
+            "let x = 1;".to_string(),
+            0.5,
+        );
+
+        assert_eq!(result.location(), "src/main.rs:42");
+    }
+
+    #[test]
+    fn test_search_result_location_multi_line() {
+        let result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "src/lib.rs".to_string(),
+            10,
+            25,
+            "fn foo() { ... }".to_string(),
+            0.8,
+        );
+
+        assert_eq!(result.location(), "src/lib.rs:10-25");
+    }
+
+    #[test]
+    fn test_search_result_with_metadata() {
+        let result = SearchResult::new(
+            SearchResultKind::Symbol,
+            "test.rs".to_string(),
+            1,
+            5,
+            "fn hello()".to_string(),
+            1.0,
+        )
+        .with_metadata(SearchResultMetadata {
+            symbol_name: Some("hello".to_string()),
+            symbol_kind: Some("function".to_string()),
+            match_type: Some("definition".to_string()),
+            context: Some("/// A greeting function".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(result.metadata.symbol_name, Some("hello".to_string()));
+        assert_eq!(result.metadata.symbol_kind, Some("function".to_string()));
+    }
+
+    #[test]
+    fn test_search_results_new() {
+        let results = vec![
+            SearchResult::new(
+                SearchResultKind::Symbol,
+                "a.rs".to_string(),
+                1,
+                1,
+                "fn a()".to_string(),
+                0.9,
+            ),
+            SearchResult::new(
+                SearchResultKind::TextMatch,
+                "b.rs".to_string(),
+                2,
+                2,
+                "let b".to_string(),
+                0.5,
+            ),
+        ];
+
+        let search_results = SearchResults::new("test".to_string(), results, 100);
+
+        assert_eq!(search_results.query, "test");
+        assert_eq!(search_results.total_count, 2);
+        assert_eq!(search_results.search_time_ms, 100);
+    }
+
+    #[test]
+    fn test_search_results_is_empty() {
+        let empty = SearchResults::new("test".to_string(), vec![], 10);
+        assert!(empty.is_empty());
+
+        let non_empty = SearchResults::new(
+            "test".to_string(),
+            vec![SearchResult::new(
+                SearchResultKind::Symbol,
+                "a.rs".to_string(),
+                1,
+                1,
+                "fn a()".to_string(),
+                0.9,
+            )],
+            10,
+        );
+        assert!(!non_empty.is_empty());
+    }
+
+    #[test]
+    fn test_search_results_top() {
+        let results = vec![
+            SearchResult::new(
+                SearchResultKind::Symbol,
+                "a.rs".to_string(),
+                1,
+                1,
+                "fn a()".to_string(),
+                0.9,
+            ),
+            SearchResult::new(
+                SearchResultKind::Symbol,
+                "b.rs".to_string(),
+                2,
+                2,
+                "fn b()".to_string(),
+                0.8,
+            ),
+            SearchResult::new(
+                SearchResultKind::Symbol,
+                "c.rs".to_string(),
+                3,
+                3,
+                "fn c()".to_string(),
+                0.7,
+            ),
+        ];
+
+        let search_results = SearchResults::new("test".to_string(), results, 50);
+        let top2 = search_results.top(2);
+
+        assert_eq!(top2.len(), 2);
+        assert_eq!(top2[0].file_path, "a.rs");
+        assert_eq!(top2[1].file_path, "b.rs");
+    }
+
+    #[test]
+    fn test_search_results_merge() {
+        let results1 = vec![SearchResult::new(
+            SearchResultKind::Symbol,
+            "a.rs".to_string(),
+            1,
+            1,
+            "fn a()".to_string(),
+            0.9,
+        )];
+        let results2 = vec![SearchResult::new(
+            SearchResultKind::Symbol,
+            "b.rs".to_string(),
+            2,
+            2,
+            "fn b()".to_string(),
+            0.95,
+        )];
+
+        let mut search_results1 = SearchResults::new("test".to_string(), results1, 50);
+        let search_results2 = SearchResults::new("test".to_string(), results2, 30);
+
+        search_results1.merge(search_results2);
+
+        assert_eq!(search_results1.total_count, 2);
+        // After merge, results should be sorted by score (highest first)
+        assert_eq!(search_results1.results[0].file_path, "b.rs"); // 0.95
+        assert_eq!(search_results1.results[1].file_path, "a.rs"); // 0.9
+    }
+
+    #[test]
+    fn test_search_result_kind_serialization() {
+        assert_eq!(SearchResultKind::Symbol, SearchResultKind::Symbol);
+        assert_eq!(SearchResultKind::TextMatch, SearchResultKind::TextMatch);
+        assert_eq!(
+            SearchResultKind::SemanticMatch,
+            SearchResultKind::SemanticMatch
+        );
+        assert_eq!(SearchResultKind::Reference, SearchResultKind::Reference);
+    }
+
+    #[test]
+    fn test_output_format_parse() {
+        assert_eq!(OutputFormat::parse("json"), Some(OutputFormat::Json));
+        assert_eq!(
+            OutputFormat::parse(" Markdown "),
+            Some(OutputFormat::Markdown)
+        );
+        assert_eq!(OutputFormat::parse("md"), Some(OutputFormat::Markdown));
+        assert_eq!(OutputFormat::parse("xml"), None);
+        assert_eq!(OutputFormat::default(), OutputFormat::Markdown);
+    }
+
+    #[test]
+    fn test_references_response_splits_definitions() {
+        let definition = SearchResult::new(
+            SearchResultKind::Symbol,
+            "a.rs".to_string(),
+            1,
+            3,
+            "fn run() {}".to_string(),
+            1.0,
+        )
+        .with_metadata(SearchResultMetadata {
+            match_type: Some("definition".to_string()),
+            ..Default::default()
+        });
+        let usage = SearchResult::new(
+            SearchResultKind::Reference,
+            "b.rs".to_string(),
+            7,
+            7,
+            "run();".to_string(),
+            0.8,
+        );
+
+        let response: ReferencesResponse =
+            SearchResults::new("run".to_string(), vec![usage, definition], 5).into();
+
+        assert_eq!(response.symbol, "run");
+        assert_eq!(response.total_count, 2);
+        assert_eq!(response.definitions.len(), 1);
+        assert_eq!(response.definitions[0].file_path, "a.rs");
+        assert_eq!(response.usages.len(), 1);
+        assert_eq!(response.usages[0].file_path, "b.rs");
+    }
+
+    #[test]
+    fn test_render_json() {
+        let results = SearchResults::new(
+            "query".to_string(),
+            vec![SearchResult::new(
+                SearchResultKind::SemanticMatch,
+                "a.rs".to_string(),
+                1,
+                2,
+                "fn a()".to_string(),
+                0.75,
+            )],
+            3,
+        );
+
+        let json = OutputFormat::Json.render(&results);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["query"], "query");
+        assert_eq!(value["results"][0]["kind"], "semantic_match");
+        assert_eq!(value["results"][0]["file_path"], "a.rs");
+        assert!(value.get("stale_files").is_none());
+    }
+
+    #[test]
+    fn test_dependencies_response_keeps_errors() {
+        let response = DependenciesResponse::new(
+            "src/lib.rs",
+            Ok(vec![DependencyInfo {
+                target_path: "std::fs".to_string(),
+                import_name: None,
+                kind: "std".to_string(),
+                symbols: Vec::new(),
+                package: None,
+                scope: None,
+                resolved_path: None,
+                via: None,
+            }]),
+            Err(anyhow::anyhow!("database locked")),
+        );
+
+        assert_eq!(response.imports.len(), 1);
+        assert!(response.imported_by.is_empty());
+        assert_eq!(
+            response.imported_by_error.as_deref(),
+            Some("database locked")
+        );
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json.get("imports_error").is_none());
+    }
+}
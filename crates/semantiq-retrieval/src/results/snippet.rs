@@ -0,0 +1,328 @@
+//! Snippets of search results, centered on the query terms
+//!
+//! A snippet is the part of a result's content around the line matching the
+//! most query terms, grown a line above and a line below at a time within a
+//! token budget. When the snippet starts below the signature of the symbol
+//! enclosing it, the signature is given as a header. The byte ranges of the
+//! query terms in the snippet text are listed for clients to highlight.
+
+use serde::{Deserialize, Serialize};
+
+/// Rough number of characters per token of source code
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Shortest query term highlighted
+const MIN_TERM_LEN: usize = 3;
+
+/// The part of a result shown to the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    /// Signature of the symbol enclosing the snippet, when it starts above it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    pub text: String,
+    /// Lines of the file the snippet covers
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Byte ranges of the query terms in `text`, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<Highlight>,
+    /// Part of the result content was left out
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Byte range of a query term in a snippet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A symbol of the result's file, whose signature may head a snippet
+#[derive(Debug, Clone, Copy)]
+pub struct EnclosingSymbol<'a> {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub signature: &'a str,
+}
+
+/// Builds the snippets of the results of one query
+#[derive(Debug, Clone)]
+pub struct SnippetBuilder {
+    /// Characters of a snippet and its header, `usize::MAX` for no limit
+    max_chars: usize,
+    /// Lowercased query words
+    terms: Vec<String>,
+}
+
+impl SnippetBuilder {
+    /// Snippets of about `max_tokens` tokens (0 for the whole content)
+    /// highlighting the words of `terms`.
+    pub fn new<'a>(max_tokens: usize, terms: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut words: Vec<String> = Vec::new();
+        for term in terms {
+            for word in term.split_whitespace() {
+                let word = word
+                    .trim_matches(|c: char| !c.is_alphanumeric() && c != '_')
+                    .to_lowercase();
+                if word.chars().count() >= MIN_TERM_LEN && !words.contains(&word) {
+                    words.push(word);
+                }
+            }
+        }
+        Self {
+            max_chars: match max_tokens {
+                0 => usize::MAX,
+                tokens => tokens.saturating_mul(CHARS_PER_TOKEN),
+            },
+            terms: words,
+        }
+    }
+
+    /// Build the snippet of `content`, whose first line is line `start_line`
+    /// of its file. `symbols` are symbols of the file with a signature; the
+    /// innermost one starting above the best line heads the snippet.
+    pub fn build(
+        &self,
+        content: &str,
+        start_line: usize,
+        symbols: &[EnclosingSymbol<'_>],
+    ) -> Snippet {
+        let lines: Vec<&str> = content.lines().collect();
+        if lines.is_empty() {
+            return Snippet {
+                header: None,
+                text: String::new(),
+                start_line,
+                end_line: start_line,
+                highlights: Vec::new(),
+                truncated: false,
+            };
+        }
+
+        // Line matching the most distinct terms, the first one on ties
+        let anchor = lines
+            .iter()
+            .enumerate()
+            .max_by_key(|&(i, line)| (self.count_terms(line), std::cmp::Reverse(i)))
+            .map_or(0, |(i, _)| i);
+        let anchor_line = start_line + anchor;
+
+        let enclosing = symbols
+            .iter()
+            .filter(|s| s.start_line < anchor_line && anchor_line <= s.end_line)
+            .max_by_key(|s| s.start_line);
+        let header = enclosing.and_then(|s| {
+            s.signature
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(|line| clip(line, 0, self.max_chars / 2).to_string())
+        });
+        let budget = self
+            .max_chars
+            .saturating_sub(header.as_ref().map_or(0, |h| h.chars().count() + 1));
+
+        // Grow the window a line above, then a line below, while it fits
+        let cost = |i: usize| lines[i].chars().count() + 1;
+        let (mut first, mut last) = (anchor, anchor);
+        let mut used = cost(anchor);
+        loop {
+            let mut grew = false;
+            if first > 0 && used + cost(first - 1) <= budget {
+                first -= 1;
+                used += cost(first);
+                grew = true;
+            }
+            if last + 1 < lines.len() && used + cost(last + 1) <= budget {
+                last += 1;
+                used += cost(last);
+                grew = true;
+            }
+            if !grew {
+                break;
+            }
+        }
+        while first < anchor && lines[first].trim().is_empty() {
+            first += 1;
+        }
+        while last > anchor && lines[last].trim().is_empty() {
+            last -= 1;
+        }
+
+        // A line longer than the budget is cut around its first term
+        let mut clipped = false;
+        let mut window: Vec<&str> = lines[first..=last].to_vec();
+        if used > budget {
+            let line = lines[anchor];
+            let around = self.first_term(line).unwrap_or(0);
+            window = vec![clip(line, around, budget.max(1))];
+            clipped = true;
+        }
+
+        let text = window.join("\n");
+        let header =
+            header.filter(|_| enclosing.is_some_and(|s| s.start_line < start_line + first));
+        Snippet {
+            header,
+            highlights: self.highlights(&text),
+            text,
+            start_line: start_line + first,
+            end_line: start_line + last,
+            truncated: clipped || first > 0 || last + 1 < lines.len(),
+        }
+    }
+
+    /// Number of distinct terms found in a line
+    fn count_terms(&self, line: &str) -> usize {
+        let line = line.to_lowercase();
+        self.terms
+            .iter()
+            .filter(|term| line.contains(term.as_str()))
+            .count()
+    }
+
+    /// Byte offset of the first term found in a line
+    fn first_term(&self, line: &str) -> Option<usize> {
+        let lower = line.to_ascii_lowercase();
+        self.terms
+            .iter()
+            .filter_map(|term| lower.find(term.as_str()))
+            .min()
+    }
+
+    /// Byte ranges of the terms in a text, overlapping ranges merged
+    fn highlights(&self, text: &str) -> Vec<Highlight> {
+        // ASCII lowercasing keeps byte offsets
+        let lower = text.to_ascii_lowercase();
+        let mut ranges: Vec<Highlight> = self
+            .terms
+            .iter()
+            .flat_map(|term| {
+                lower
+                    .match_indices(term.as_str())
+                    .map(|(start, m)| Highlight {
+                        start,
+                        end: start + m.len(),
+                    })
+            })
+            .collect();
+        ranges.sort_by_key(|h| (h.start, h.end));
+
+        let mut merged: Vec<Highlight> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+/// At most `max_chars` characters of a line, around byte offset `around`
+fn clip(line: &str, around: usize, max_chars: usize) -> &str {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    if chars.len() <= max_chars {
+        return line;
+    }
+    // Start a quarter of the budget before the term, so it is shown with
+    // what precedes it
+    let term_char = chars.partition_point(|&(offset, _)| offset < around);
+    let start = term_char
+        .saturating_sub(max_chars / 4)
+        .min(chars.len() - max_chars);
+    let end = chars.get(start + max_chars).map_or(line.len(), |&(o, _)| o);
+    &line[chars[start].0..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(range: std::ops::RangeInclusive<usize>) -> String {
+        range
+            .map(|i| format!("    let value_{i} = compute({i});"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_snippet_centers_on_matching_line() {
+        let content = format!(
+            "{}\n    let token = parse_auth_token(header);\n{}",
+            lines(1..=20),
+            lines(22..=40)
+        );
+        let builder = SnippetBuilder::new(40, ["auth token"]);
+        let snippet = builder.build(&content, 100, &[]);
+
+        assert!(snippet.truncated);
+        assert!(snippet.start_line < 120 && snippet.end_line > 120);
+        assert!(snippet.text.chars().count() <= 160);
+        assert!(snippet.text.contains("parse_auth_token(header)"));
+
+        let highlighted: Vec<&str> = snippet
+            .highlights
+            .iter()
+            .map(|h| &snippet.text[h.start..h.end])
+            .collect();
+        assert_eq!(highlighted, vec!["token", "auth", "token"]);
+    }
+
+    #[test]
+    fn test_snippet_header_is_enclosing_signature() {
+        let content = format!("{}\n    check_session(user);", lines(1..=30));
+        let symbols = [
+            EnclosingSymbol {
+                start_line: 1,
+                end_line: 200,
+                signature: "impl Auth",
+            },
+            EnclosingSymbol {
+                start_line: 9,
+                end_line: 50,
+                signature: "pub fn login(user: &User) -> bool",
+            },
+            EnclosingSymbol {
+                start_line: 45,
+                end_line: 46,
+                signature: "fn unrelated()",
+            },
+        ];
+        let builder = SnippetBuilder::new(30, ["session"]);
+        let snippet = builder.build(&content, 10, &symbols);
+
+        assert_eq!(snippet.end_line, 40);
+        assert_eq!(
+            snippet.header.as_deref(),
+            Some("pub fn login(user: &User) -> bool")
+        );
+        assert!(snippet.text.chars().count() + 34 <= 120);
+
+        // No header when the signature is part of the snippet
+        let snippet = SnippetBuilder::new(0, ["session"]).build(&content, 9, &symbols);
+        assert_eq!(snippet.header, None);
+        assert!(!snippet.truncated);
+    }
+
+    #[test]
+    fn test_long_line_is_clipped_around_term() {
+        let content = format!("{}needle{}", "é".repeat(300), "x".repeat(300));
+        let snippet = SnippetBuilder::new(10, ["needle"]).build(&content, 1, &[]);
+
+        assert!(snippet.truncated);
+        assert_eq!(snippet.text.chars().count(), 40);
+        let highlight = snippet.highlights[0];
+        assert_eq!(&snippet.text[highlight.start..highlight.end], "needle");
+    }
+
+    #[test]
+    fn test_snippet_without_match_starts_at_first_line() {
+        let snippet = SnippetBuilder::new(10, ["missing"]).build(&lines(1..=10), 5, &[]);
+        assert_eq!(snippet.start_line, 5);
+        assert!(snippet.text.starts_with("    let value_1"));
+        assert!(snippet.highlights.is_empty());
+    }
+}
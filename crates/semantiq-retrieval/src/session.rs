@@ -0,0 +1,400 @@
+//! Search sessions of a conversation.
+//!
+//! A client passes the same session id with each search of a conversation.
+//! The session remembers its recent queries and the files their results came
+//! from, so that follow-up searches favour the same neighborhood: results
+//! from a file the last searches surfaced are boosted, and to a lesser extent
+//! results from the same directories. Sessions live in memory only, the
+//! least recently used ones being forgotten beyond the capacity.
+//!
+//! Boosts are applied to ranked results after the query cache, so cached
+//! searches are shared across sessions.
+
+use crate::results::{
+    LineRange, ScoreBoost, SearchResult, SessionQuery, SessionSummary, ViewedFile,
+};
+use semantiq_index::unix_now;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Boost of results from a file surfaced by the recent searches of the session
+pub const SESSION_FILE_BOOST: f32 = 1.2;
+
+/// Boost of results from a directory of the files surfaced by the recent
+/// searches of the session
+pub const SESSION_DIRECTORY_BOOST: f32 = 1.1;
+
+/// Searches of a session whose results make up its neighborhood
+const RECENT_SEARCHES: usize = 5;
+
+/// Searches remembered per session
+const MAX_SESSION_QUERIES: usize = 100;
+
+/// Files remembered per session, the least recently viewed being forgotten
+const MAX_SESSION_FILES: usize = 500;
+
+/// Line ranges remembered per file
+const MAX_FILE_RANGES: usize = 20;
+
+/// Directory of a path relative to the project root, "" at the root
+fn directory(path: &str) -> &str {
+    Path::new(path)
+        .parent()
+        .and_then(Path::to_str)
+        .unwrap_or_default()
+}
+
+struct RecordedQuery {
+    query: SessionQuery,
+    /// Files the results came from
+    files: Vec<String>,
+}
+
+struct Session {
+    started_at: i64,
+    search_count: usize,
+    queries: VecDeque<RecordedQuery>,
+    files: HashMap<String, ViewedFile>,
+    last_used: u64,
+}
+
+impl Session {
+    /// Files of the recent searches, and their directories
+    fn neighborhood(&self) -> (HashSet<&str>, HashSet<&str>) {
+        let files: HashSet<&str> = self
+            .queries
+            .iter()
+            .rev()
+            .take(RECENT_SEARCHES)
+            .flat_map(|recorded| recorded.files.iter().map(String::as_str))
+            .collect();
+        let directories = files.iter().map(|path| directory(path)).collect();
+        (files, directories)
+    }
+
+    fn record(&mut self, query: &str, results: &[SearchResult], now: i64) {
+        let mut files: Vec<String> = Vec::new();
+        for result in results {
+            let path = &result.file_path;
+            if !files.contains(path) {
+                files.push(path.clone());
+            }
+
+            let viewed = self
+                .files
+                .entry(path.clone())
+                .or_insert_with(|| ViewedFile {
+                    file_path: path.clone(),
+                    views: 0,
+                    best_score: 0.0,
+                    lines: Vec::new(),
+                    last_viewed_at: now,
+                });
+            viewed.best_score = viewed.best_score.max(result.score);
+            viewed.last_viewed_at = now;
+            add_range(
+                &mut viewed.lines,
+                LineRange {
+                    start_line: result.start_line,
+                    end_line: result.end_line,
+                },
+            );
+        }
+        for path in &files {
+            if let Some(viewed) = self.files.get_mut(path) {
+                viewed.views += 1;
+            }
+        }
+
+        if self.files.len() > MAX_SESSION_FILES {
+            let mut viewed: Vec<(i64, String)> = self
+                .files
+                .values()
+                .map(|file| (file.last_viewed_at, file.file_path.clone()))
+                .collect();
+            viewed.sort();
+            for (_, path) in viewed
+                .into_iter()
+                .take(self.files.len() - MAX_SESSION_FILES)
+            {
+                self.files.remove(&path);
+            }
+        }
+
+        self.search_count += 1;
+        if self.queries.len() == MAX_SESSION_QUERIES {
+            self.queries.pop_front();
+        }
+        self.queries.push_back(RecordedQuery {
+            query: SessionQuery {
+                query: query.to_string(),
+                result_count: results.len(),
+                searched_at: now,
+            },
+            files,
+        });
+    }
+}
+
+/// Add a line range to sorted, disjoint ranges, merging those it overlaps
+/// or touches. Ranges beyond [`MAX_FILE_RANGES`] are dropped.
+fn add_range(ranges: &mut Vec<LineRange>, range: LineRange) {
+    let mut merged = range;
+    ranges.retain(|other| {
+        let disjoint =
+            other.end_line + 1 < merged.start_line || merged.end_line + 1 < other.start_line;
+        if !disjoint {
+            merged.start_line = merged.start_line.min(other.start_line);
+            merged.end_line = merged.end_line.max(other.end_line);
+        }
+        disjoint
+    });
+    let index = ranges.partition_point(|other| other.start_line < merged.start_line);
+    if ranges.len() < MAX_FILE_RANGES {
+        ranges.insert(index, merged);
+    }
+}
+
+#[derive(Default)]
+struct SessionState {
+    sessions: HashMap<String, Session>,
+    /// Monotonic counter recording session use, for LRU eviction
+    tick: u64,
+}
+
+/// In-memory search sessions, keyed by the session id of the client
+pub struct SearchSessions {
+    capacity: usize,
+    state: Mutex<SessionState>,
+}
+
+impl Default for SearchSessions {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl SearchSessions {
+    /// Sessions remembered by default
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(SessionState::default()),
+        }
+    }
+
+    /// Boost the results near the files surfaced by the recent searches of
+    /// a session, and sort them again by score
+    pub fn boost(&self, session_id: &str, results: &mut [SearchResult]) {
+        let Ok(state) = self.state.lock() else {
+            return;
+        };
+        let Some(session) = state.sessions.get(session_id) else {
+            return;
+        };
+        let (files, directories) = session.neighborhood();
+        if files.is_empty() {
+            return;
+        }
+
+        for result in results.iter_mut() {
+            let factor = if files.contains(result.file_path.as_str()) {
+                SESSION_FILE_BOOST
+            } else if directories.contains(directory(&result.file_path)) {
+                SESSION_DIRECTORY_BOOST
+            } else {
+                continue;
+            };
+            let boosted = result.score * factor;
+            result.score = boosted.min(1.0);
+            if let Some(ref mut explanation) = result.metadata.explanation {
+                explanation.boosts.push(ScoreBoost {
+                    name: "session".to_string(),
+                    factor,
+                });
+                explanation.capped |= boosted > 1.0;
+            }
+        }
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// Record a search of a session and the files of its results, starting
+    /// the session on its first search
+    pub fn record(&self, session_id: &str, query: &str, results: &[SearchResult]) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.tick += 1;
+        let tick = state.tick;
+
+        if !state.sessions.contains_key(session_id) && state.sessions.len() >= self.capacity {
+            let oldest = state
+                .sessions
+                .iter()
+                .min_by_key(|(_, session)| session.last_used)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                state.sessions.remove(&oldest);
+            }
+        }
+
+        let now = unix_now();
+        let session = state
+            .sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| Session {
+                started_at: now,
+                search_count: 0,
+                queries: VecDeque::new(),
+                files: HashMap::new(),
+                last_used: tick,
+            });
+        session.last_used = tick;
+        session.record(query, results, now);
+    }
+
+    /// Searches and viewed files of a session, `None` if it is unknown
+    pub fn summary(&self, session_id: &str) -> Option<SessionSummary> {
+        let state = self.state.lock().ok()?;
+        let session = state.sessions.get(session_id)?;
+
+        let mut files: Vec<ViewedFile> = session.files.values().cloned().collect();
+        files.sort_by(|a, b| {
+            b.last_viewed_at
+                .cmp(&a.last_viewed_at)
+                .then_with(|| b.views.cmp(&a.views))
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        Some(SessionSummary {
+            session_id: session_id.to_string(),
+            started_at: session.started_at,
+            search_count: session.search_count,
+            queries: session
+                .queries
+                .iter()
+                .map(|recorded| recorded.query.clone())
+                .collect(),
+            files,
+        })
+    }
+
+    /// Number of sessions remembered
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|s| s.sessions.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::SearchResultKind;
+
+    fn result(path: &str, start_line: usize, end_line: usize, score: f32) -> SearchResult {
+        SearchResult::new(
+            SearchResultKind::SemanticMatch,
+            path.to_string(),
+            start_line,
+            end_line,
+            String::new(),
+            score,
+        )
+    }
+
+    #[test]
+    fn test_session_boosts_neighborhood() {
+        let sessions = SearchSessions::default();
+        sessions.record(
+            "s1",
+            "upload retry",
+            &[result("src/upload/retry.rs", 10, 20, 0.8)],
+        );
+
+        let mut results = vec![
+            result("src/other.rs", 1, 5, 0.7),
+            result("src/upload/queue.rs", 1, 5, 0.65),
+            result("src/upload/retry.rs", 30, 40, 0.6),
+        ];
+        sessions.boost("s1", &mut results);
+        let order: Vec<&str> = results.iter().map(|r| r.file_path.as_str()).collect();
+        assert_eq!(
+            order,
+            vec!["src/upload/retry.rs", "src/upload/queue.rs", "src/other.rs"]
+        );
+        assert!((results[0].score - 0.72).abs() < 1e-6);
+        assert!((results[1].score - 0.715).abs() < 1e-6);
+        assert_eq!(results[2].score, 0.7);
+
+        // Other sessions are not affected
+        let mut results = vec![result("src/upload/retry.rs", 1, 5, 0.5)];
+        sessions.boost("s2", &mut results);
+        assert_eq!(results[0].score, 0.5);
+    }
+
+    #[test]
+    fn test_session_summary() {
+        let sessions = SearchSessions::default();
+        assert!(sessions.summary("s1").is_none());
+
+        sessions.record(
+            "s1",
+            "config",
+            &[
+                result("src/config.rs", 1, 10, 0.9),
+                result("src/config.rs", 8, 15, 0.6),
+                result("src/main.rs", 3, 4, 0.5),
+            ],
+        );
+        sessions.record("s1", "load config", &[result("src/config.rs", 40, 50, 0.7)]);
+
+        let summary = sessions.summary("s1").unwrap();
+        assert_eq!(summary.search_count, 2);
+        let queries: Vec<&str> = summary.queries.iter().map(|q| q.query.as_str()).collect();
+        assert_eq!(queries, vec!["config", "load config"]);
+        assert_eq!(summary.queries[0].result_count, 3);
+
+        let config = &summary.files[0];
+        assert_eq!(config.file_path, "src/config.rs");
+        assert_eq!(config.views, 2);
+        assert_eq!(config.best_score, 0.9);
+        assert_eq!(
+            config.lines,
+            vec![
+                LineRange {
+                    start_line: 1,
+                    end_line: 15
+                },
+                LineRange {
+                    start_line: 40,
+                    end_line: 50
+                }
+            ]
+        );
+        assert_eq!(summary.files[1].file_path, "src/main.rs");
+        assert_eq!(summary.files[1].views, 1);
+    }
+
+    #[test]
+    fn test_least_recently_used_session_is_forgotten() {
+        let sessions = SearchSessions::new(2);
+        sessions.record("a", "one", &[]);
+        sessions.record("b", "two", &[]);
+        sessions.record("a", "three", &[]);
+        sessions.record("c", "four", &[]);
+
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.summary("a").is_some());
+        assert!(sessions.summary("b").is_none());
+        assert!(sessions.summary("c").is_some());
+    }
+}
@@ -1,18 +1,40 @@
+use crate::deadline::Deadline;
 use anyhow::Result;
-use grep_regex::RegexMatcherBuilder;
-use grep_searcher::{Searcher, Sink, SinkMatch};
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{BinaryDetection, MmapChoice, Searcher, SearcherBuilder, Sink, SinkMatch};
+use ignore::{WalkParallel, WalkState};
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Files from this size on are searched through a memory map instead of
+/// being read in chunks
+const MMAP_MIN_BYTES: u64 = 256 * 1024;
 
 /// A text match result from ripgrep-based search
 #[derive(Debug, Clone)]
 pub struct TextMatch {
     pub line_number: usize,
     pub line_content: String,
+    /// Byte range of the match in `line_content`, which is trimmed
     pub match_start: usize,
     pub match_end: usize,
+    /// Byte offset of the match in the searched content or file
+    pub start_byte: usize,
+    /// 0-based byte column of the match in its line, indentation included
+    pub column: usize,
     pub score: f32,
 }
 
+/// Text matches of one file, each with the query term it matched, best
+/// first
+#[derive(Debug, Clone)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub matches: Vec<(String, TextMatch)>,
+}
+
 /// Text searcher using ripgrep's grep-* crates
 pub struct TextSearcher {
     case_insensitive: bool,
@@ -26,12 +48,7 @@ impl TextSearcher {
     /// Search for a pattern in the given content
     /// Returns matches with line numbers and scores
     pub fn search(&self, content: &str, pattern: &str) -> Result<Vec<TextMatch>> {
-        // Escape user input to prevent ReDoS attacks
-        let escaped = regex::escape(pattern);
-        let matcher = RegexMatcherBuilder::new()
-            .case_insensitive(self.case_insensitive)
-            .word(false)
-            .build(&escaped)?;
+        let matcher = self.literal_matcher(pattern)?;
 
         let mut matches = Vec::new();
         let mut sink = MatchSink::new(&mut matches, pattern);
@@ -42,6 +59,120 @@ impl TextSearcher {
         Ok(matches)
     }
 
+    /// Search for several terms in the given content. A line matching
+    /// several terms is reported once, for the first of them; matches are
+    /// sorted by score.
+    pub fn search_terms(&self, content: &str, terms: &[&str]) -> Vec<(String, TextMatch)> {
+        let Ok(matcher) = self.terms_matcher(terms) else {
+            return Vec::new();
+        };
+        let terms = Terms::new(terms, self.case_insensitive);
+
+        let mut matches = Vec::new();
+        let mut sink = TermsSink::new(&mut matches, &terms);
+        if Searcher::new()
+            .search_slice(&matcher, content.as_bytes(), &mut sink)
+            .is_err()
+        {
+            return Vec::new();
+        }
+
+        sort_by_score(&mut matches);
+        matches
+    }
+
+    /// Search for several terms in the files of a walk, like
+    /// [`Self::search_terms`], on the walker's threads. The terms are
+    /// searched with one matcher, so each file is read once; each thread
+    /// has its own copy. Files of at least 256 KiB are memory-mapped, and binary
+    /// files are skipped. The walk stops once `limit` matches are found or
+    /// the deadline expires, so which files are searched then depends on
+    /// thread scheduling.
+    ///
+    /// Returns the files with matches, sorted by path.
+    pub fn search_walk(
+        &self,
+        walker: WalkParallel,
+        terms: &[&str],
+        limit: usize,
+        deadline: &Deadline,
+    ) -> Result<Vec<FileMatches>> {
+        let matcher = self.terms_matcher(terms)?;
+        let terms = Terms::new(terms, self.case_insensitive);
+        let found = AtomicUsize::new(0);
+        let files = Mutex::new(Vec::new());
+
+        walker.run(|| {
+            let matcher = matcher.clone();
+            let terms = &terms;
+            let mut reader = file_searcher(MmapChoice::never());
+            // SAFETY: a file truncated by another process while it is mapped
+            // makes reading the map fail with SIGBUS. ripgrep takes the same
+            // risk for large files, where maps are faster than reads.
+            let mut mapper = file_searcher(unsafe { MmapChoice::auto() });
+            let (found, files) = (&found, &files);
+
+            Box::new(move |entry| {
+                if found.load(Ordering::Relaxed) >= limit || deadline.expired() {
+                    return WalkState::Quit;
+                }
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    return WalkState::Continue;
+                }
+
+                let large = entry
+                    .metadata()
+                    .is_ok_and(|metadata| metadata.len() >= MMAP_MIN_BYTES);
+                let searcher = if large { &mut mapper } else { &mut reader };
+                let matches = search_file(searcher, &matcher, terms, entry.path());
+                if !matches.is_empty() {
+                    found.fetch_add(matches.len(), Ordering::Relaxed);
+                    if let Ok(mut files) = files.lock() {
+                        files.push(FileMatches {
+                            path: entry.into_path(),
+                            matches,
+                        });
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+
+        let mut files = files
+            .into_inner()
+            .map_err(|e| anyhow::anyhow!("Text search results lock poisoned: {}", e))?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Matcher of a literal pattern
+    fn literal_matcher(&self, pattern: &str) -> Result<RegexMatcher> {
+        // Escape user input to prevent ReDoS attacks
+        let escaped = regex::escape(pattern);
+        let matcher = RegexMatcherBuilder::new()
+            .case_insensitive(self.case_insensitive)
+            .word(false)
+            .build(&escaped)?;
+        Ok(matcher)
+    }
+
+    /// Matcher of any of several literal terms
+    fn terms_matcher(&self, terms: &[&str]) -> Result<RegexMatcher> {
+        let alternation = terms
+            .iter()
+            .map(|term| regex::escape(term))
+            .collect::<Vec<_>>()
+            .join("|");
+        let matcher = RegexMatcherBuilder::new()
+            .case_insensitive(self.case_insensitive)
+            .word(false)
+            .build(&alternation)?;
+        Ok(matcher)
+    }
+
     /// Search with word boundary matching for better precision
     pub fn search_word(&self, content: &str, pattern: &str) -> Result<Vec<TextMatch>> {
         // Build regex matcher with word boundaries
@@ -90,6 +221,42 @@ impl Default for TextSearcher {
     }
 }
 
+/// Searcher of files, with line numbers, that stops at binary data
+fn file_searcher(mmap: MmapChoice) -> Searcher {
+    SearcherBuilder::new()
+        .line_number(true)
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .memory_map(mmap)
+        .build()
+}
+
+/// Search a file for the terms at once, like
+/// [`TextSearcher::search_terms`]. Unreadable and binary files have no
+/// matches.
+fn search_file(
+    searcher: &mut Searcher,
+    matcher: &RegexMatcher,
+    terms: &Terms,
+    path: &Path,
+) -> Vec<(String, TextMatch)> {
+    let mut matches = Vec::new();
+    let mut sink = TermsSink::new(&mut matches, terms);
+    if searcher.search_path(matcher, path, &mut sink).is_err() || sink.binary {
+        return Vec::new();
+    }
+
+    sort_by_score(&mut matches);
+    matches
+}
+
+fn sort_by_score(matches: &mut [(String, TextMatch)]) {
+    matches.sort_by(|a, b| {
+        b.1.score
+            .partial_cmp(&a.1.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
 /// Sink implementation to collect matches
 struct MatchSink<'a> {
     matches: &'a mut Vec<TextMatch>,
@@ -106,79 +273,158 @@ impl<'a> MatchSink<'a> {
             pattern,
         }
     }
+}
 
-    fn calculate_score(&self, line: &str, match_start: usize) -> f32 {
-        let line_trimmed = line.trim();
+impl Sink for MatchSink<'_> {
+    type Error = io::Error;
 
-        // Use eq_ignore_ascii_case for the common ASCII path, avoiding allocation.
-        // Fall back to full Unicode lowercase only when ASCII comparison fails.
-        let is_exact_line = if line_trimmed.is_ascii() && self.pattern.is_ascii() {
-            line_trimmed.eq_ignore_ascii_case(self.pattern)
-        } else {
-            line_trimmed.to_lowercase() == self.pattern_lower
-        };
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        let line = String::from_utf8_lossy(mat.bytes());
+        if let Some(text_match) = line_match(mat, &line, self.pattern, &self.pattern_lower) {
+            self.matches.push(text_match);
+        }
+        Ok(true)
+    }
+}
 
-        // Base score
-        let mut score = if is_exact_line {
-            0.9 // Exact line match
-        } else if match_start == 0
-            || line
-                .chars()
-                .nth(match_start.saturating_sub(1))
-                .map(|c| !c.is_alphanumeric())
-                .unwrap_or(true)
-        {
-            0.7 // Word boundary match
-        } else {
-            0.5 // Substring match
-        };
+/// Terms searched at once, in the order a line matching several of them
+/// is attributed
+struct Terms {
+    /// Each term with its lowercase form
+    terms: Vec<(String, String)>,
+    case_insensitive: bool,
+}
 
-        // Position bonus (earlier matches are better)
-        let position_factor = 1.0 - (match_start as f32 / (line.len() as f32 + 10.0)) * 0.2;
-        score *= position_factor;
+impl Terms {
+    fn new(terms: &[&str], case_insensitive: bool) -> Self {
+        Self {
+            terms: terms
+                .iter()
+                .map(|term| (term.to_string(), term.to_lowercase()))
+                .collect(),
+            case_insensitive,
+        }
+    }
 
-        score.min(1.0)
+    /// First term found in a line, with its lowercase form
+    fn first_in(&self, line: &str) -> Option<(&str, &str)> {
+        let line_lower = self.case_insensitive.then(|| line.to_lowercase());
+        self.terms
+            .iter()
+            .find(|(term, term_lower)| match line_lower {
+                Some(ref line_lower) => line_lower.contains(term_lower.as_str()),
+                None => line.contains(term.as_str()),
+            })
+            .map(|(term, term_lower)| (term.as_str(), term_lower.as_str()))
     }
 }
 
-impl Sink for MatchSink<'_> {
-    type Error = io::Error;
+/// Sink collecting the matches of a search for several terms, each line
+/// attributed to the first term it contains
+struct TermsSink<'a> {
+    matches: &'a mut Vec<(String, TextMatch)>,
+    terms: &'a Terms,
+    /// The search stopped at binary data
+    binary: bool,
+}
 
-    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
-        let line_number = mat.line_number().unwrap_or(0) as usize;
-        let line_bytes = mat.bytes();
+impl<'a> TermsSink<'a> {
+    fn new(matches: &'a mut Vec<(String, TextMatch)>, terms: &'a Terms) -> Self {
+        Self {
+            matches,
+            terms,
+            binary: false,
+        }
+    }
+}
 
-        // Convert bytes to string, handling potential UTF-8 issues
-        let line_content = String::from_utf8_lossy(line_bytes).trim().to_string();
+impl Sink for TermsSink<'_> {
+    type Error = io::Error;
 
-        // Skip empty lines and comments
-        if line_content.is_empty()
-            || line_content.starts_with("//")
-            || line_content.starts_with('#')
-        {
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        let line = String::from_utf8_lossy(mat.bytes());
+        let Some((term, term_lower)) = self.terms.first_in(&line) else {
             return Ok(true);
+        };
+        if let Some(text_match) = line_match(mat, &line, term, term_lower) {
+            self.matches.push((term.to_string(), text_match));
         }
+        Ok(true)
+    }
+
+    fn binary_data(&mut self, _searcher: &Searcher, _offset: u64) -> Result<bool, Self::Error> {
+        self.binary = true;
+        Ok(false)
+    }
+}
 
-        // Find match position within line for scoring
-        let match_start = line_content
-            .to_lowercase()
-            .find(&self.pattern_lower)
-            .unwrap_or(0);
+/// Match of `pattern` in a matched line, `None` for empty and comment lines
+fn line_match(
+    mat: &SinkMatch<'_>,
+    line: &str,
+    pattern: &str,
+    pattern_lower: &str,
+) -> Option<TextMatch> {
+    let line_number = mat.line_number().unwrap_or(0) as usize;
+    let indent = line.len() - line.trim_start().len();
+    let line_content = line.trim().to_string();
+
+    // Skip empty lines and comments
+    if line_content.is_empty() || line_content.starts_with("//") || line_content.starts_with('#') {
+        return None;
+    }
 
-        let match_end = match_start + self.pattern.len();
+    // Find match position within line for scoring
+    let match_start = line_content.to_lowercase().find(pattern_lower).unwrap_or(0);
 
-        let score = self.calculate_score(&line_content, match_start);
+    let match_end = match_start + pattern.len();
+    let column = indent + match_start;
+    let start_byte = mat.absolute_byte_offset() as usize + column;
 
-        self.matches.push(TextMatch {
-            line_number,
-            line_content,
-            match_start,
-            match_end,
-            score,
-        });
+    let score = calculate_score(&line_content, match_start, pattern, pattern_lower);
 
-        Ok(true)
-    }
+    Some(TextMatch {
+        line_number,
+        line_content,
+        match_start,
+        match_end,
+        start_byte,
+        column,
+        score,
+    })
+}
+
+fn calculate_score(line: &str, match_start: usize, pattern: &str, pattern_lower: &str) -> f32 {
+    let line_trimmed = line.trim();
+
+    // Use eq_ignore_ascii_case for the common ASCII path, avoiding allocation.
+    // Fall back to full Unicode lowercase only when ASCII comparison fails.
+    let is_exact_line = if line_trimmed.is_ascii() && pattern.is_ascii() {
+        line_trimmed.eq_ignore_ascii_case(pattern)
+    } else {
+        line_trimmed.to_lowercase() == pattern_lower
+    };
+
+    // Base score
+    let mut score = if is_exact_line {
+        0.9 // Exact line match
+    } else if match_start == 0
+        || line
+            .chars()
+            .nth(match_start.saturating_sub(1))
+            .map(|c| !c.is_alphanumeric())
+            .unwrap_or(true)
+    {
+        0.7 // Word boundary match
+    } else {
+        0.5 // Substring match
+    };
+
+    // Position bonus (earlier matches are better)
+    let position_factor = 1.0 - (match_start as f32 / (line.len() as f32 + 10.0)) * 0.2;
+    score *= position_factor;
+
+    score.min(1.0)
 }
 
 #[cfg(test)]
@@ -197,6 +443,19 @@ mod tests {
         assert!(matches[0].line_content.contains("main"));
     }
 
+    #[test]
+    fn test_match_position() {
+        let searcher = TextSearcher::new(true);
+        let content = "fn main() {\n    println!(\"Hello\");\n}";
+
+        let matches = searcher.search(content, "hello").unwrap();
+
+        assert_eq!(matches[0].match_start, 10);
+        assert_eq!(matches[0].column, 14);
+        assert_eq!(matches[0].start_byte, 26);
+        assert_eq!(&content[26..31], "Hello");
+    }
+
     #[test]
     fn test_case_insensitive() {
         let searcher = TextSearcher::new(true);
@@ -283,4 +542,115 @@ mod tests {
         let result = searcher.search_regex(content, &pattern);
         assert!(result.is_ok());
     }
+
+    fn walk(root: &Path) -> WalkParallel {
+        ignore::WalkBuilder::new(root).build_parallel()
+    }
+
+    #[test]
+    fn test_search_terms_reports_each_line_once() {
+        let searcher = TextSearcher::new(true);
+        let content = "let config = load();
+parse(config);
+load_all();";
+
+        let matches = searcher.search_terms(content, &["config", "load"]);
+
+        let lines: Vec<(usize, &str)> = matches
+            .iter()
+            .map(|(term, m)| (m.line_number, term.as_str()))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.contains(&(1, "config")));
+        assert!(lines.contains(&(3, "load")));
+    }
+
+    #[test]
+    fn test_search_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "fn parse_config() {}
+",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/other.rs"),
+            "fn main() {}
+",
+        )
+        .unwrap();
+        // Large enough to be memory-mapped
+        let large = format!(
+            "{}fn parse_config_large() {{}}\n",
+            "let x = 1;\n".repeat(30_000)
+        );
+        assert!(large.len() as u64 >= MMAP_MIN_BYTES);
+        std::fs::write(root.join("large.rs"), large).unwrap();
+        std::fs::write(root.join("data.bin"), b"parse_config\x00\x01").unwrap();
+
+        let searcher = TextSearcher::new(true);
+        let files = searcher
+            .search_walk(walk(root), &["parse_config"], 100, &Deadline::new())
+            .unwrap();
+
+        let paths: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, vec![root.join("large.rs"), root.join("src/lib.rs")]);
+        assert_eq!(files[0].matches[0].1.line_number, 30_001);
+        assert_eq!(files[1].matches[0].0, "parse_config");
+    }
+
+    #[test]
+    fn test_search_walk_several_terms() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "fn load_config() {}\nfn Config() {}\nfn save() {}\n",
+        )
+        .unwrap();
+
+        let searcher = TextSearcher::new(false);
+        let files = searcher
+            .search_walk(
+                walk(dir.path()),
+                &["config", "Config"],
+                100,
+                &Deadline::new(),
+            )
+            .unwrap();
+
+        // Each line once, for the first term it contains with the case of
+        // the search
+        let mut lines: Vec<(usize, &str)> = files[0]
+            .matches
+            .iter()
+            .map(|(term, m)| (m.line_number, term.as_str()))
+            .collect();
+        lines.sort();
+        assert_eq!(lines, vec![(1, "config"), (2, "Config")]);
+    }
+
+    #[test]
+    fn test_search_walk_stops_at_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..20 {
+            std::fs::write(dir.path().join(format!("{}.rs", i)), "fn main() {}\n").unwrap();
+        }
+        let searcher = TextSearcher::new(true);
+
+        let files = searcher
+            .search_walk(walk(dir.path()), &["main"], 3, &Deadline::new())
+            .unwrap();
+        // Threads already searching a file when the limit is reached finish it
+        assert!(files.len() >= 3 && files.len() < 20);
+
+        let deadline = Deadline::new();
+        deadline.cancel();
+        let files = searcher
+            .search_walk(walk(dir.path()), &["main"], 100, &deadline)
+            .unwrap();
+        assert!(files.is_empty());
+    }
 }
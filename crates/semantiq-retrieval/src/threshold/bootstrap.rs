@@ -0,0 +1,238 @@
+//! Bootstrap calibration from the contents of the index.
+//!
+//! Live calibration needs hundreds of search observations, so a fresh index
+//! would otherwise use the fixed default thresholds for a long time. Instead,
+//! random chunks are used as pseudo-queries against the vector index and the
+//! distances to their nearest neighbours seed the thresholds right away.
+
+use super::calibrator::{CalibrationConfig, ThresholdCalibrator};
+use super::collector::CollectorConfig;
+use super::config::{Confidence, ThresholdConfig};
+use anyhow::Result;
+use semantiq_index::{CalibrationData, IndexStore};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+/// Number of chunks sampled as pseudo-queries.
+pub const BOOTSTRAP_SAMPLE_SIZE: usize = 200;
+
+/// Nearest neighbours whose distances are recorded for each pseudo-query.
+const BOOTSTRAP_NEIGHBORS: usize = 10;
+
+/// Minimum distances per language (and globally) to calibrate.
+const BOOTSTRAP_MIN_SAMPLES: usize = 50;
+
+/// Calibrate thresholds from `sample_size` random chunks and save them.
+///
+/// Does nothing once enough live observations exist, since live calibration
+/// then owns the thresholds. Bootstrap thresholds are capped at medium
+/// confidence and are overwritten when live calibration runs. Returns the
+/// saved configuration, or `None` if the index has too few embedded chunks.
+pub fn bootstrap_thresholds(
+    store: &IndexStore,
+    sample_size: usize,
+) -> Result<Option<ThresholdConfig>> {
+    let live_observations: usize = store.get_observation_counts()?.values().sum();
+    if live_observations >= CollectorConfig::default().bootstrap_threshold {
+        debug!(
+            observations = live_observations,
+            "Skipping bootstrap calibration, live observations are available"
+        );
+        return Ok(None);
+    }
+
+    let distances = sample_neighbor_distances(store, sample_size)?;
+    let total: usize = distances.values().map(|d| d.len()).sum();
+    if total < BOOTSTRAP_MIN_SAMPLES {
+        debug!(
+            distances = total,
+            "Not enough embedded chunks for bootstrap calibration"
+        );
+        return Ok(None);
+    }
+
+    let calibrator = ThresholdCalibrator::with_config(CalibrationConfig {
+        min_samples: BOOTSTRAP_MIN_SAMPLES,
+        ..Default::default()
+    });
+    let mut config = calibrator.calibrate_all(&distances);
+    for thresholds in config
+        .per_language
+        .values_mut()
+        .chain(std::iter::once(&mut config.global))
+    {
+        if thresholds.confidence == Confidence::High {
+            thresholds.confidence = Confidence::Medium;
+        }
+    }
+
+    save_calibrations(store, &config, BOOTSTRAP_MIN_SAMPLES)?;
+
+    info!(
+        distances = total,
+        languages = config.per_language.len(),
+        max_distance = config.global.max_distance,
+        "Bootstrapped thresholds from index contents"
+    );
+
+    Ok(Some(config))
+}
+
+/// Distances from sampled chunks to their nearest neighbours, grouped by
+/// the language of the neighbour as live observations are.
+fn sample_neighbor_distances(
+    store: &IndexStore,
+    sample_size: usize,
+) -> Result<HashMap<String, Vec<f32>>> {
+    let mut distances: HashMap<String, Vec<f32>> = HashMap::new();
+    let mut languages: HashMap<i64, Option<String>> = HashMap::new();
+
+    for (chunk_id, embedding) in store.sample_chunk_embeddings(sample_size)? {
+        let neighbors = store.search_similar_chunks(&embedding, BOOTSTRAP_NEIGHBORS + 1)?;
+        for (neighbor_id, distance) in neighbors
+            .into_iter()
+            .filter(|(id, _)| *id != chunk_id)
+            .take(BOOTSTRAP_NEIGHBORS)
+        {
+            let language = match languages.get(&neighbor_id) {
+                Some(language) => language.clone(),
+                None => {
+                    let language = store.get_chunk_language(neighbor_id)?;
+                    languages.insert(neighbor_id, language.clone());
+                    language
+                }
+            };
+            if let Some(language) = language {
+                distances.entry(language).or_default().push(distance);
+            }
+        }
+    }
+
+    Ok(distances)
+}
+
+/// Save calibrated thresholds that have at least `min_samples` samples.
+pub(crate) fn save_calibrations(
+    store: &IndexStore,
+    config: &ThresholdConfig,
+    min_samples: usize,
+) -> Result<()> {
+    let entries = config
+        .per_language
+        .iter()
+        .map(|(language, thresholds)| (language.as_str(), thresholds))
+        .chain(std::iter::once(("_global", &config.global)));
+
+    for (language, thresholds) in entries {
+        if thresholds.sample_count < min_samples {
+            continue;
+        }
+        let stats = thresholds.stats.as_ref();
+        store.save_calibration(&CalibrationData {
+            language: language.to_string(),
+            max_distance: thresholds.max_distance,
+            min_similarity: thresholds.min_similarity,
+            confidence: thresholds.confidence.to_string(),
+            sample_count: thresholds.sample_count,
+            p50_distance: stats.map(|s| s.p50),
+            p90_distance: stats.map(|s| s.p90),
+            p95_distance: stats.map(|s| s.p95),
+            mean_distance: stats.map(|s| s.mean),
+            std_distance: stats.map(|s| s.std_dev),
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_index::schema::EMBEDDING_DIMENSION;
+    use semantiq_parser::CodeChunk;
+
+    /// Index `count` chunks whose embeddings spread along a few axes.
+    fn store_with_embedded_chunks(count: usize) -> IndexStore {
+        let store = IndexStore::open_in_memory().unwrap();
+        let file_id = store
+            .insert_file("src/lib.rs", Some("rust"), "", 0, 0)
+            .unwrap();
+        let chunks: Vec<CodeChunk> = (0..count)
+            .map(|i| CodeChunk {
+                content: format!("fn f{}() {{}}", i),
+                start_line: i + 1,
+                end_line: i + 1,
+                start_byte: 0,
+                end_byte: 0,
+                symbols: vec![format!("f{}", i)],
+            })
+            .collect();
+        store.insert_chunks(file_id, &chunks).unwrap();
+
+        for (i, chunk) in store
+            .get_chunks_by_file(file_id)
+            .unwrap()
+            .iter()
+            .enumerate()
+        {
+            let mut embedding = vec![0.0f32; EMBEDDING_DIMENSION];
+            embedding[i % 8] = 1.0;
+            embedding[8 + i % 5] = 0.2 * (i % 3) as f32;
+            store.update_chunk_embedding(chunk.id, &embedding).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_bootstrap_seeds_usable_thresholds() {
+        let store = store_with_embedded_chunks(40);
+
+        let config = bootstrap_thresholds(&store, 40)
+            .unwrap()
+            .expect("calibrated");
+        let rust = config.get_thresholds("rust").expect("rust calibrated");
+        assert_eq!(rust.sample_count, 40 * BOOTSTRAP_NEIGHBORS);
+        assert_eq!(rust.confidence, Confidence::Low);
+        assert!(config.global.stats.is_some());
+
+        let saved = store.load_calibration("_global").unwrap().expect("saved");
+        assert_eq!(saved.sample_count, config.global.sample_count);
+        assert!(store.load_calibration("rust").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_bootstrap_caps_confidence() {
+        let store = store_with_embedded_chunks(210);
+
+        let config = bootstrap_thresholds(&store, 210)
+            .unwrap()
+            .expect("calibrated");
+        assert_eq!(config.global.sample_count, 2100);
+        assert_eq!(
+            Confidence::from_count(config.global.sample_count),
+            Confidence::High
+        );
+        assert_eq!(config.global.confidence, Confidence::Medium);
+        assert!(config.global.should_use());
+    }
+
+    #[test]
+    fn test_bootstrap_needs_embedded_chunks() {
+        let store = store_with_embedded_chunks(3);
+        assert!(bootstrap_thresholds(&store, 10).unwrap().is_none());
+        assert!(store.load_all_calibrations().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bootstrap_skipped_with_live_observations() {
+        let store = store_with_embedded_chunks(40);
+        let observations: Vec<(String, f32, u64, i64)> = (0..500)
+            .map(|i| ("rust".to_string(), 0.8, i as u64, 0))
+            .collect();
+        store
+            .insert_distance_observations_batch(&observations)
+            .unwrap();
+
+        assert!(bootstrap_thresholds(&store, 40).unwrap().is_none());
+    }
+}
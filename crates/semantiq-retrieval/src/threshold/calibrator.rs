@@ -2,6 +2,7 @@
 
 use super::config::{Confidence, LanguageThresholds, MIN_SAMPLES_FOR_CALIBRATION, ThresholdConfig};
 use super::stats::DistanceStats;
+use semantiq_index::unix_now;
 use tracing::{debug, info};
 
 /// Result of a calibration operation.
@@ -230,12 +231,7 @@ impl ThresholdCalibrator {
         }
 
         // Set calibration timestamp
-        config.calibrated_at = Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0),
-        );
+        config.calibrated_at = Some(unix_now());
 
         config
     }
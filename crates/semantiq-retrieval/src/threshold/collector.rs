@@ -1,11 +1,11 @@
 //! Distance observation collection during search.
 
+use semantiq_index::unix_now;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{info, warn};
 
 /// A single distance observation recorded during search.
@@ -24,10 +24,7 @@ pub struct DistanceObservation {
 impl DistanceObservation {
     /// Create a new observation.
     pub fn new(language: String, distance: f32, query_hash: u64) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs() as i64)
-            .unwrap_or(0);
+        let timestamp = unix_now();
 
         Self {
             language,
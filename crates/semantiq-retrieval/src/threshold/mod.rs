@@ -3,11 +3,14 @@
 //! This module provides ML-based adaptive thresholds that automatically calibrate
 //! based on observed distance distributions per programming language.
 
+mod bootstrap;
 mod calibrator;
 mod collector;
 mod config;
 mod stats;
 
+pub(crate) use bootstrap::save_calibrations;
+pub use bootstrap::{BOOTSTRAP_SAMPLE_SIZE, bootstrap_thresholds};
 pub use calibrator::{
     CalibrationConfig, CalibrationResult, ThresholdCalibrator, format_calibration_summary,
 };
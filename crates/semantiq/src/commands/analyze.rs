@@ -0,0 +1,131 @@
+//! Analyses of the index
+
+use anyhow::Result;
+use semantiq_retrieval::UnusedSymbolsResponse;
+use std::path::PathBuf;
+
+use super::common::{open_engine, parse_output_format};
+
+/// List exported symbols that no other file references
+pub async fn analyze_unused(
+    path: Option<&str>,
+    include_tests: bool,
+    limit: usize,
+    database: Option<PathBuf>,
+    format: Option<&str>,
+) -> Result<()> {
+    let format = parse_output_format(format)?;
+
+    let engine = open_engine(database)?;
+    let response = engine.find_unused_symbols(path, include_tests, limit)?;
+
+    match format {
+        Some(format) => println!("{}", format.render(&response)),
+        None => print!("{}", format_unused(&response)),
+    }
+
+    Ok(())
+}
+
+/// Plain terminal output: candidates grouped by file, then the caveats of
+/// each language.
+fn format_unused(response: &UnusedSymbolsResponse) -> String {
+    if response.symbols.is_empty() {
+        return "No unused exported symbols found\n".to_string();
+    }
+
+    let mut output = format!(
+        "{} exported symbol(s) not referenced outside their file\n",
+        response.total_count
+    );
+
+    let mut current_file = None;
+    for symbol in &response.symbols {
+        if current_file != Some(&symbol.file_path) {
+            output.push_str(&format!("\n📄 {}\n", symbol.file_path));
+            current_file = Some(&symbol.file_path);
+        }
+        let usage = match symbol.local_references {
+            0 => "unused".to_string(),
+            n => format!("{} local reference(s)", n),
+        };
+        output.push_str(&format!(
+            "   {:>5}  {} {} ({}, {} confidence)\n",
+            symbol.start_line,
+            symbol.kind,
+            symbol.name,
+            usage,
+            symbol.confidence.as_str()
+        ));
+    }
+    if response.total_count > response.symbols.len() {
+        output.push_str(&format!(
+            "\n... and {} more (raise --limit to list them)\n",
+            response.total_count - response.symbols.len()
+        ));
+    }
+
+    output.push_str("\nCaveats:\n");
+    for caveat in &response.caveats {
+        output.push_str(&format!(
+            "   {} ({} confidence): {}\n",
+            caveat.language,
+            caveat.confidence.as_str(),
+            caveat.caveat
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_retrieval::{LanguageCaveat, UnusedSymbol, UsageConfidence};
+
+    #[test]
+    fn test_format_unused() {
+        let symbol = |name: &str, file: &str, local_references| UnusedSymbol {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            file_path: file.to_string(),
+            start_line: 12,
+            end_line: 14,
+            signature: None,
+            language: "python".to_string(),
+            confidence: UsageConfidence::Low,
+            local_references,
+        };
+        let response = UnusedSymbolsResponse {
+            total_count: 3,
+            search_time_ms: 1,
+            symbols: vec![
+                symbol("load", "app/config.py", 0),
+                symbol("save", "app/store.py", 2),
+            ],
+            caveats: vec![LanguageCaveat {
+                language: "python".to_string(),
+                confidence: UsageConfidence::Low,
+                caveat: "Dynamic dispatch".to_string(),
+            }],
+        };
+
+        let output = format_unused(&response);
+        assert!(output.starts_with("3 exported symbol(s) not referenced outside their file\n"));
+        assert!(
+            output
+                .contains("\n📄 app/config.py\n      12  function load (unused, low confidence)\n")
+        );
+        assert!(output.contains("function save (2 local reference(s), low confidence)\n"));
+        assert!(output.contains("... and 1 more"));
+        assert!(output.ends_with("Caveats:\n   python (low confidence): Dynamic dispatch\n"));
+
+        let empty = UnusedSymbolsResponse {
+            total_count: 0,
+            search_time_ms: 0,
+            symbols: vec![],
+            caveats: vec![],
+        };
+        assert_eq!(format_unused(&empty), "No unused exported symbols found\n");
+    }
+}
@@ -0,0 +1,132 @@
+//! Benchmark retrieval quality and latency on a suite of queries
+
+use anyhow::{Result, bail};
+use semantiq_index::QueryCacheConfig;
+use semantiq_retrieval::bench::{self, BenchMetrics};
+use semantiq_retrieval::{BenchReport, BenchSuite};
+use std::path::{Path, PathBuf};
+
+use super::common::open_engine;
+
+/// Options of the `bench` command
+pub struct BenchOptions {
+    /// Results scored per query, overriding the suite's `k`
+    pub top_k: Option<usize>,
+    /// Report of an earlier run to compare against
+    pub baseline: Option<PathBuf>,
+    /// Where to save the report of this run
+    pub save_baseline: Option<PathBuf>,
+    /// Largest precision@k or MRR drop accepted against the baseline
+    pub tolerance: f64,
+}
+
+pub async fn bench(suite: &Path, database: Option<PathBuf>, options: BenchOptions) -> Result<()> {
+    let mut suite = BenchSuite::load(suite)?;
+    if let Some(k) = options.top_k {
+        if k == 0 {
+            bail!("k must be at least 1");
+        }
+        suite.k = k;
+    }
+    let baseline = options
+        .baseline
+        .as_deref()
+        .map(BenchReport::load)
+        .transpose()?;
+    if let Some(ref baseline) = baseline
+        && baseline.k != suite.k
+    {
+        bail!(
+            "The baseline was measured at k={}, this run at k={}",
+            baseline.k,
+            suite.k
+        );
+    }
+
+    // Every query must run the whole pipeline to measure its latency
+    let engine = open_engine(database)?.with_query_cache(&QueryCacheConfig {
+        enabled: false,
+        ..QueryCacheConfig::default()
+    });
+    let report = bench::run(&engine, &suite)?;
+
+    println!(
+        "Benchmark of {} queries (k = {})\n",
+        report.overall.queries, report.k
+    );
+    println!(
+        "{:<18} {:>7} {:>8} {:>7} {:>10} {:>10} {:>10}",
+        "Strategy", "Queries", "P@k", "MRR", "p50 (ms)", "p90 (ms)", "p99 (ms)"
+    );
+    print_metrics("overall", &report.overall);
+    for (strategy, metrics) in &report.strategies {
+        print_metrics(strategy, metrics);
+    }
+
+    let missed: Vec<&str> = report
+        .cases
+        .iter()
+        .filter(|c| c.first_relevant_rank.is_none())
+        .map(|c| c.query.as_str())
+        .collect();
+    if !missed.is_empty() {
+        println!("\nNo expected result in the top {}:", report.k);
+        for query in missed {
+            println!("  - {}", query);
+        }
+    }
+
+    if let Some(ref path) = options.save_baseline {
+        report.save(path)?;
+        println!("\nSaved baseline to {:?}", path);
+    }
+
+    if let Some(baseline) = baseline {
+        println!("\nAgainst baseline:");
+        print_delta("overall", &report.overall, &baseline.overall);
+        for (strategy, metrics) in &report.strategies {
+            if let Some(base) = baseline.strategies.get(strategy) {
+                print_delta(strategy, metrics, base);
+            }
+        }
+
+        let regressions = report.regressions(&baseline, options.tolerance);
+        if !regressions.is_empty() {
+            println!("\nRegressions (tolerance {}):", options.tolerance);
+            for regression in &regressions {
+                println!("  - {}", regression);
+            }
+            bail!(
+                "{} metrics regressed against the baseline",
+                regressions.len()
+            );
+        }
+        println!("\nNo regression against the baseline");
+    }
+
+    Ok(())
+}
+
+fn print_metrics(name: &str, metrics: &BenchMetrics) {
+    println!(
+        "{:<18} {:>7} {:>8.3} {:>7.3} {:>10.1} {:>10.1} {:>10.1}",
+        name,
+        metrics.queries,
+        metrics.precision_at_k,
+        metrics.mrr,
+        metrics.latency_p50_ms,
+        metrics.latency_p90_ms,
+        metrics.latency_p99_ms
+    );
+}
+
+fn print_delta(name: &str, current: &BenchMetrics, baseline: &BenchMetrics) {
+    println!(
+        "  {:<18} P@k {:+.3}  MRR {:+.3}  p50 {:+.1} ms  p90 {:+.1} ms",
+        name,
+        current.precision_at_k - baseline.precision_at_k,
+        current.mrr - baseline.mrr,
+        current.latency_p50_ms - baseline.latency_p50_ms,
+        current.latency_p90_ms - baseline.latency_p90_ms
+    );
+}
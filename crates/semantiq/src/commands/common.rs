@@ -1,7 +1,10 @@
 //! Common utilities and constants for CLI commands
 
 use anyhow::{Context, Result};
+use semantiq_index::{IndexStore, SemantiqConfig};
+use semantiq_retrieval::{OutputFormat, RetrievalEngine};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Default database filename
 pub const DEFAULT_DB_NAME: &str = ".semantiq.db";
@@ -28,6 +31,49 @@ pub fn resolve_db_path(database: Option<PathBuf>, project_root: &Path) -> PathBu
     database.unwrap_or_else(|| project_root.join(DEFAULT_DB_NAME))
 }
 
+/// Opens the index of the current directory.
+/// Fails if the database does not exist yet.
+pub fn open_store(database: Option<PathBuf>) -> Result<Arc<IndexStore>> {
+    let cwd = std::env::current_dir()?;
+    let db_path = resolve_db_path(database, &cwd);
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found: {:?}. Run 'semantiq index' first.",
+            db_path
+        );
+    }
+
+    Ok(Arc::new(IndexStore::open(&db_path)?))
+}
+
+/// Opens a retrieval engine over the index of the current directory.
+/// Fails if the database does not exist yet.
+pub fn open_engine(database: Option<PathBuf>) -> Result<RetrievalEngine> {
+    engine_over(open_store(database)?)
+}
+
+/// Creates a retrieval engine over an opened index of the current directory.
+pub fn engine_over(store: Arc<IndexStore>) -> Result<RetrievalEngine> {
+    let cwd = std::env::current_dir()?;
+    let cwd_str = cwd
+        .to_str()
+        .context("Current directory path contains invalid UTF-8")?;
+    let config = SemantiqConfig::load(&cwd)?;
+    config.register_languages(&cwd)?;
+    Ok(RetrievalEngine::from_config(store, cwd_str, &config))
+}
+
+/// Parses the `--format` option. `None` means plain terminal output.
+pub fn parse_output_format(format: Option<&str>) -> Result<Option<OutputFormat>> {
+    format
+        .map(|f| {
+            OutputFormat::parse(f)
+                .with_context(|| format!("Invalid format '{}'. Valid formats: json, markdown", f))
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,4 +110,14 @@ mod tests {
         let result = resolve_db_path(None, project);
         assert_eq!(result, PathBuf::from("/project/.semantiq.db"));
     }
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!(parse_output_format(None).unwrap(), None);
+        assert_eq!(
+            parse_output_format(Some("json")).unwrap(),
+            Some(OutputFormat::Json)
+        );
+        assert!(parse_output_format(Some("xml")).is_err());
+    }
 }
@@ -0,0 +1,66 @@
+//! Compress chunk content with a zstd dictionary trained on the index
+
+use anyhow::{Context, Result};
+use semantiq_index::{IndexLockAttempt, IndexStore};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::common::resolve_db_path;
+
+const LOCK_OWNER: &str = "semantiq compact";
+
+/// How long to wait for an indexing process to release the index
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn compact(database: Option<PathBuf>) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let db_path = resolve_db_path(database, &cwd);
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found: {:?}. Run 'semantiq index' first.",
+            db_path
+        );
+    }
+
+    let store = IndexStore::open(&db_path)?;
+    let _lock = match store.lock_index(LOCK_OWNER, LOCK_TIMEOUT)? {
+        IndexLockAttempt::Acquired(guard) => guard,
+        IndexLockAttempt::Held(holder) => {
+            anyhow::bail!(
+                "The index is locked by {}, try again once it is released",
+                holder
+            )
+        }
+    };
+
+    let size_before = store.get_database_size()?;
+    let report = store.compact_chunks()?;
+    let size_after = store.get_database_size()?;
+
+    println!(
+        "Compressed {} of {} chunks: {:.1} MB of content stored in {:.1} MB",
+        report.compressed,
+        report.chunks,
+        report.content_bytes as f64 / (1024.0 * 1024.0),
+        report.stored_bytes_after as f64 / (1024.0 * 1024.0)
+    );
+    if report.dictionary_bytes > 0 {
+        println!(
+            "Trained a {:.1} KB dictionary on the indexed chunks",
+            report.dictionary_bytes as f64 / 1024.0
+        );
+    } else {
+        println!("Too few chunks to train a dictionary, compressed without one");
+    }
+    println!(
+        "Database size: {:.1} MB -> {:.1} MB",
+        size_before as f64 / (1024.0 * 1024.0),
+        size_after as f64 / (1024.0 * 1024.0)
+    );
+    println!(
+        "Set `compress_chunks = true` under [storage] in semantiq.toml to also compress newly indexed chunks."
+    );
+
+    Ok(())
+}
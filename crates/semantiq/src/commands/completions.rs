@@ -0,0 +1,16 @@
+//! Shell completion scripts
+
+use anyhow::Result;
+use clap::Command;
+use clap_complete::{Shell, generate};
+use std::io::{self, Write};
+
+/// Print the completion script of `command` for `shell`
+pub fn completions(command: &mut Command, shell: Shell) -> Result<()> {
+    // Generated in memory: clap_complete panics when stdout is closed early
+    let mut script = Vec::new();
+    let name = command.get_name().to_string();
+    generate(shell, command, name, &mut script);
+    io::stdout().write_all(&script)?;
+    Ok(())
+}
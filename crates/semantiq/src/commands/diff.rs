@@ -0,0 +1,162 @@
+//! Symbol-level diff between two git revisions
+
+use anyhow::{Context, Result};
+use semantiq_index::IndexStore;
+use semantiq_retrieval::{ChangeKind, RetrievalEngine, SemanticDiffResponse};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::common::{open_engine, parse_output_format, resolve_db_path};
+
+pub async fn diff(
+    from: &str,
+    to: &str,
+    database: Option<PathBuf>,
+    format: Option<&str>,
+) -> Result<()> {
+    let format = parse_output_format(format)?;
+
+    // The index is only used to pick the embedding model for moved code;
+    // diffing works in projects that were never indexed
+    let cwd = std::env::current_dir()?;
+    let engine = if resolve_db_path(database.clone(), &cwd).exists() {
+        open_engine(database)?
+    } else {
+        let store = Arc::new(IndexStore::open_in_memory()?);
+        let cwd_str = cwd
+            .to_str()
+            .context("Current directory path contains invalid UTF-8")?;
+        RetrievalEngine::with_options(store, cwd_str, false)
+    };
+    let response = engine.semantic_diff(from, to)?;
+
+    match format {
+        Some(format) => println!("{}", format.render(&response)),
+        None => print!("{}", format_diff(&response)),
+    }
+
+    Ok(())
+}
+
+/// Plain terminal output: one line per changed symbol, grouped by file
+fn format_diff(response: &SemanticDiffResponse) -> String {
+    if response.files.is_empty() {
+        return format!("No changes between {} and {}\n", response.from, response.to);
+    }
+
+    let mut output = format!(
+        "{} symbol change(s) in {} file(s) between {} and {}\n",
+        response.total_changes,
+        response.files.len(),
+        response.from,
+        response.to
+    );
+
+    for file in &response.files {
+        output.push_str(&format!("\n📄 {} ({})\n", file.path, file.status));
+        if file.language.is_none() {
+            output.push_str("   not analyzed\n");
+            continue;
+        }
+
+        for change in &file.changes {
+            let detail = match change.change {
+                ChangeKind::Moved => format!(
+                    " ← {}:{}",
+                    change.old_file_path.as_deref().unwrap_or_default(),
+                    change.old_start_line.unwrap_or_default()
+                ),
+                ChangeKind::Renamed => {
+                    format!(" ← {}", change.old_name.as_deref().unwrap_or_default())
+                }
+                _ => String::new(),
+            };
+            output.push_str(&format!(
+                "   {:<17} {} {} :{}{}\n",
+                change.change.as_str(),
+                change.kind,
+                change.name,
+                change.start_line,
+                detail
+            ));
+            if let (Some(old), Some(new)) = (&change.old_signature, &change.signature) {
+                output.push_str(&format!("      - {}\n      + {}\n", old.trim(), new.trim()));
+            }
+        }
+    }
+
+    if response.skipped_files > 0 {
+        output.push_str(&format!(
+            "\n{} more changed file(s) not analyzed\n",
+            response.skipped_files
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_retrieval::{FileDiff, SymbolChange};
+
+    #[test]
+    fn test_format_diff() {
+        let change = |change, name: &str| SymbolChange {
+            change,
+            name: name.to_string(),
+            kind: "function".to_string(),
+            parent: None,
+            file_path: "src/util.rs".to_string(),
+            start_line: 3,
+            end_line: 5,
+            old_file_path: None,
+            old_name: None,
+            old_start_line: None,
+            old_signature: None,
+            signature: None,
+            similarity: None,
+        };
+        let response = SemanticDiffResponse {
+            from: "main".to_string(),
+            to: "HEAD".to_string(),
+            files: vec![FileDiff {
+                path: "src/util.rs".to_string(),
+                old_path: None,
+                status: "added".to_string(),
+                language: Some("rust".to_string()),
+                changes: vec![
+                    SymbolChange {
+                        old_file_path: Some("src/lib.rs".to_string()),
+                        old_start_line: Some(12),
+                        similarity: Some(1.0),
+                        ..change(ChangeKind::Moved, "sum_even")
+                    },
+                    SymbolChange {
+                        old_signature: Some("fn parse(input: &str)".to_string()),
+                        signature: Some("fn parse(input: &str, strict: bool)".to_string()),
+                        ..change(ChangeKind::SignatureChanged, "parse")
+                    },
+                ],
+            }],
+            total_changes: 2,
+            skipped_files: 0,
+            search_time_ms: 3,
+        };
+
+        let output = format_diff(&response);
+        assert!(output.starts_with("2 symbol change(s) in 1 file(s) between main and HEAD\n"));
+        assert!(output.contains("📄 src/util.rs (added)\n"));
+        assert!(output.contains("   moved             function sum_even :3 ← src/lib.rs:12\n"));
+        assert!(output.contains(
+            "   signature_changed function parse :3\n      - fn parse(input: &str)\n      + fn parse(input: &str, strict: bool)\n"
+        ));
+
+        let empty = SemanticDiffResponse {
+            files: vec![],
+            total_changes: 0,
+            ..response
+        };
+        assert_eq!(format_diff(&empty), "No changes between main and HEAD\n");
+    }
+}
@@ -0,0 +1,159 @@
+//! Explain a symbol
+
+use anyhow::Result;
+use semantiq_retrieval::SymbolExplanation;
+use std::path::PathBuf;
+
+use super::common::{open_engine, parse_output_format};
+
+pub async fn explain(
+    symbol: &str,
+    database: Option<PathBuf>,
+    as_of: Option<u64>,
+    format: Option<&str>,
+) -> Result<()> {
+    let symbol = symbol.trim();
+    if symbol.is_empty() {
+        anyhow::bail!("Symbol name cannot be empty");
+    }
+    let format = parse_output_format(format)?;
+
+    let engine = open_engine(database)?;
+    let explanation = match as_of {
+        Some(generation) => engine.explain_symbol_as_of(symbol, generation)?,
+        None => engine.explain_symbol(symbol)?,
+    };
+
+    match format {
+        Some(format) => println!("{}", format.render(&explanation)),
+        None => print!("{}", format_explanation(&explanation)),
+    }
+
+    Ok(())
+}
+
+/// Plain terminal output: each definition with its signature and docs,
+/// followed by the usage counts and related symbols.
+fn format_explanation(explanation: &SymbolExplanation) -> String {
+    if !explanation.found {
+        return match explanation.as_of {
+            Some(generation) => format!(
+                "Symbol '{}' not found at index generation {}\n",
+                explanation.name, generation
+            ),
+            None => format!("Symbol '{}' not found in the index\n", explanation.name),
+        };
+    }
+
+    let mut output = match explanation.as_of {
+        Some(generation) => format!(
+            "Symbol: {}\nFound {} definition(s) as of index generation {}, referenced {} time(s) across {} file(s)\n",
+            explanation.name,
+            explanation.definitions.len(),
+            generation,
+            explanation.usage_count,
+            explanation.usage_files
+        ),
+        None => format!(
+            "Symbol: {}\nFound {} definition(s), referenced {} time(s) across {} file(s)\n",
+            explanation.name,
+            explanation.definitions.len(),
+            explanation.usage_count,
+            explanation.usage_files
+        ),
+    };
+
+    for def in &explanation.definitions {
+        let mut kind = def.qualifiers.names();
+        kind.push(&def.kind);
+        output.push_str(&format!(
+            "\n📄 {}:{}-{} ({})\n",
+            def.file_path,
+            def.start_line,
+            def.end_line,
+            kind.join(" ")
+        ));
+        if let Some(ref signature) = def.signature {
+            output.push_str(&format!("   {}\n", signature.trim()));
+        }
+        if let Some(ref cfg) = def.cfg_context {
+            output.push_str(&format!("   cfg: {}\n", cfg));
+        }
+        if let Some(ref doc) = def.doc_comment {
+            for line in doc.lines() {
+                output.push_str(&format!("   │ {}\n", line.trim()));
+            }
+        }
+    }
+
+    if !explanation.related_symbols.is_empty() {
+        output.push_str(&format!(
+            "\nRelated: {}\n",
+            explanation.related_symbols.join(", ")
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_parser::SymbolQualifiers;
+    use semantiq_retrieval::SymbolDefinition;
+
+    #[test]
+    fn test_format_explanation() {
+        let explanation = SymbolExplanation {
+            name: "Config".to_string(),
+            found: true,
+            definitions: vec![SymbolDefinition {
+                file_path: "src/config.rs".to_string(),
+                kind: "struct".to_string(),
+                start_line: 1,
+                end_line: 4,
+                signature: Some("pub struct Config".to_string()),
+                doc_comment: Some("Runtime settings\nLoaded from semantiq.toml".to_string()),
+                qualifiers: SymbolQualifiers {
+                    is_public: true,
+                    ..SymbolQualifiers::default()
+                },
+                cfg_context: None,
+            }],
+            usage_count: 3,
+            usage_files: 2,
+            related_symbols: vec!["load".to_string(), "save".to_string()],
+            as_of: None,
+        };
+
+        let output = format_explanation(&explanation);
+        assert!(output.starts_with(
+            "Symbol: Config\nFound 1 definition(s), referenced 3 time(s) across 2 file(s)\n"
+        ));
+        assert!(output.contains("📄 src/config.rs:1-4 (public struct)\n   pub struct Config\n"));
+        assert!(output.contains("   │ Loaded from semantiq.toml\n"));
+        assert!(output.ends_with("Related: load, save\n"));
+
+        let missing = SymbolExplanation {
+            name: "Nope".to_string(),
+            found: false,
+            definitions: vec![],
+            usage_count: 0,
+            usage_files: 0,
+            related_symbols: vec![],
+            as_of: None,
+        };
+        assert_eq!(
+            format_explanation(&missing),
+            "Symbol 'Nope' not found in the index\n"
+        );
+
+        let earlier = SymbolExplanation {
+            as_of: Some(4),
+            ..explanation
+        };
+        assert!(format_explanation(&earlier).starts_with(
+            "Symbol: Config\nFound 1 definition(s) as of index generation 4, referenced"
+        ));
+    }
+}
@@ -0,0 +1,519 @@
+//! Export the index for other tools
+
+use anyhow::{Context, Result};
+use semantiq_index::{
+    FileRecord, IndexStore, SymbolRecord, WorkspacePackage, is_external_path, read_source,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::common::resolve_db_path;
+use crate::scip;
+
+/// Default output file of `--format scip`
+const DEFAULT_SCIP_OUTPUT: &str = "index.scip";
+
+/// Deepest chain of parents kept in a symbol moniker
+const MAX_PARENT_DEPTH: usize = 8;
+
+pub async fn export(
+    database: Option<PathBuf>,
+    format: &str,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    if format != "scip" {
+        anyhow::bail!("Unknown export format '{}'. Valid formats: scip", format);
+    }
+
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let db_path = resolve_db_path(database, &cwd);
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found: {:?}. Run 'semantiq index' first.",
+            db_path
+        );
+    }
+
+    let store = IndexStore::open(&db_path)?;
+    let index = build_scip_index(&store, &cwd)?;
+
+    let output = output.unwrap_or_else(|| PathBuf::from(DEFAULT_SCIP_OUTPUT));
+    std::fs::write(&output, index.to_bytes())
+        .with_context(|| format!("Failed to write {:?}", output))?;
+
+    let symbols: usize = index.documents.iter().map(|d| d.symbols.len()).sum();
+    let occurrences: usize = index.documents.iter().map(|d| d.occurrences.len()).sum();
+    println!(
+        "Exported {} documents, {} symbols and {} occurrences to {}",
+        index.documents.len(),
+        symbols,
+        occurrences,
+        output.display()
+    );
+
+    Ok(())
+}
+
+/// An indexed file with the monikers of its symbols
+struct ExportedFile {
+    record: FileRecord,
+    content: String,
+    lines: LineIndex,
+    symbols: Vec<SymbolRecord>,
+    monikers: Vec<String>,
+    /// Index of the symbol enclosing each symbol, if any
+    parents: Vec<Option<usize>>,
+}
+
+/// Build a SCIP index of the project files of the index.
+///
+/// Identifier occurrences are only recorded as counts per file, so
+/// references are located by scanning the text of each file for the names
+/// it uses, which resolve to the definition of the same file or to the only
+/// definition of the project. Ambiguous names are left out.
+pub fn build_scip_index(store: &IndexStore, project_root: &Path) -> Result<scip::Index> {
+    let packages = store.get_packages()?;
+
+    let mut files = Vec::new();
+    for path in store.get_all_file_paths()? {
+        if is_external_path(&path) {
+            continue;
+        }
+        let Some(record) = store.get_file_by_path(&path)? else {
+            continue;
+        };
+        let content = match store.get_file_content(&path)? {
+            Some(content) => content,
+            None => match read_source(&project_root.join(&path)) {
+                Ok(source) => source.content,
+                Err(e) => {
+                    tracing::warn!("Skipping {} in the export: {}", path, e);
+                    continue;
+                }
+            },
+        };
+        let mut symbols = store.get_symbols_by_file(record.id)?;
+        symbols.retain(|symbol| is_definition(symbol, record.language.as_deref()));
+        let parents = symbols
+            .iter()
+            .map(|symbol| parent_index(&symbols, symbol))
+            .collect::<Vec<_>>();
+        let package = record
+            .package
+            .as_deref()
+            .and_then(|name| find_package(&packages, name, &path));
+        let monikers = (0..symbols.len())
+            .map(|i| moniker(&path, package, &symbols, &parents, i))
+            .collect();
+
+        files.push(ExportedFile {
+            lines: LineIndex::new(&content),
+            record,
+            content,
+            symbols,
+            monikers,
+            parents,
+        });
+    }
+
+    let mut definitions: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for (f, file) in files.iter().enumerate() {
+        for (s, symbol) in file.symbols.iter().enumerate() {
+            definitions
+                .entry(symbol.name.as_str())
+                .or_default()
+                .push((f, s));
+        }
+    }
+    let resolve = |name: &str, f: usize| -> Option<&str> {
+        let candidates = definitions.get(name)?;
+        let (file, symbol) = candidates
+            .iter()
+            .find(|(file, _)| *file == f)
+            .or_else(|| (candidates.len() == 1).then(|| &candidates[0]))?;
+        Some(files[*file].monikers[*symbol].as_str())
+    };
+
+    let mut documents = Vec::new();
+    for (f, file) in files.iter().enumerate() {
+        let mut document = scip::Document {
+            relative_path: file.record.path.clone(),
+            language: file.record.language.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+
+        let mut definition_ranges = Vec::new();
+        for (s, symbol) in file.symbols.iter().enumerate() {
+            let moniker = &file.monikers[s];
+            let start = (symbol.start_byte.max(0) as usize).min(file.content.len());
+            let end = (symbol.end_byte.max(0) as usize).clamp(start, file.content.len());
+            let name_start = file
+                .content
+                .get(start..end)
+                .and_then(|text| find_word(text, &symbol.name).next())
+                .map(|offset| start + offset)
+                .unwrap_or(start);
+            let name_end = (name_start + symbol.name.len()).min(file.content.len());
+            let range = file.lines.range(&file.content, name_start, name_end);
+            definition_ranges.push(range);
+
+            document.occurrences.push(scip::Occurrence {
+                range,
+                symbol: moniker.clone(),
+                symbol_roles: scip::ROLE_DEFINITION,
+                enclosing_range: Some(file.lines.range(&file.content, start, end)),
+            });
+
+            let mut documentation = Vec::new();
+            if let Some(ref signature) = symbol.signature {
+                documentation.push(format!("```{}\n{}\n```", document.language, signature));
+            }
+            if let Some(ref doc) = symbol.doc_comment {
+                documentation.push(doc.clone());
+            }
+            document.symbols.push(scip::SymbolInformation {
+                symbol: moniker.clone(),
+                documentation,
+                relationships: Vec::new(),
+                kind: scip::symbol_kind(&symbol.kind),
+                display_name: symbol.name.clone(),
+                enclosing_symbol: file.parents[s].map(|p| file.monikers[p].clone()),
+            });
+        }
+
+        for relation in store.get_type_relations(file.record.id)? {
+            if !matches!(relation.kind.as_str(), "implements" | "extends") {
+                continue;
+            }
+            let (Some(s), Some(target)) = (
+                file.symbols.iter().position(|symbol| {
+                    symbol.name == relation.type_name
+                        && matches!(
+                            scip::Descriptor::for_symbol(&symbol.kind, &symbol.name),
+                            scip::Descriptor::Type(_)
+                        )
+                }),
+                resolve(&relation.target, f),
+            ) else {
+                continue;
+            };
+            let information = &mut document.symbols[s];
+            if information.symbol != target
+                && !information.relationships.iter().any(|r| r.symbol == target)
+            {
+                information.relationships.push(scip::Relationship {
+                    symbol: target.to_string(),
+                    is_implementation: true,
+                });
+            }
+        }
+
+        for occurrence in store.get_occurrences_by_file(file.record.id)? {
+            let Some(target) = resolve(&occurrence.name, f) else {
+                continue;
+            };
+            for offset in find_word(&file.content, &occurrence.name) {
+                let range = file
+                    .lines
+                    .range(&file.content, offset, offset + occurrence.name.len());
+                if definition_ranges.contains(&range) {
+                    continue;
+                }
+                document.occurrences.push(scip::Occurrence {
+                    range,
+                    symbol: target.to_string(),
+                    symbol_roles: 0,
+                    enclosing_range: None,
+                });
+            }
+        }
+
+        document
+            .occurrences
+            .sort_by_key(|occurrence| occurrence.range);
+        documents.push(document);
+    }
+
+    Ok(scip::Index {
+        metadata: scip::Metadata {
+            tool_name: "semantiq".to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            arguments: vec![
+                "export".to_string(),
+                "--format".to_string(),
+                "scip".to_string(),
+            ],
+            project_root: format!("file://{}", project_root.display()),
+        },
+        documents,
+    })
+}
+
+/// Whether a symbol is exported as a definition. Imports are not, nor are
+/// Rust `impl` blocks, which are indexed as classes named after their type
+/// or trait.
+fn is_definition(symbol: &SymbolRecord, language: Option<&str>) -> bool {
+    match symbol.kind.as_str() {
+        "import" => false,
+        "class" => language != Some("rust"),
+        _ => true,
+    }
+}
+
+/// The workspace package of the given name owning `path`
+fn find_package<'a>(
+    packages: &'a [WorkspacePackage],
+    name: &str,
+    path: &str,
+) -> Option<&'a WorkspacePackage> {
+    packages
+        .iter()
+        .filter(|package| package.name == name && package.contains(path))
+        .max_by_key(|package| package.root.len())
+}
+
+/// Index of the symbol named by `symbol.parent`: the innermost one of that
+/// name enclosing it, else the first one of that name in the file (e.g. the
+/// struct of a Rust `impl` block)
+fn parent_index(symbols: &[SymbolRecord], symbol: &SymbolRecord) -> Option<usize> {
+    let parent = symbol.parent.as_deref()?;
+    let named = || {
+        symbols
+            .iter()
+            .enumerate()
+            .filter(move |(_, s)| s.name == parent && s.id != symbol.id)
+    };
+    named()
+        .filter(|(_, s)| s.start_byte <= symbol.start_byte && s.end_byte >= symbol.end_byte)
+        .min_by_key(|(_, s)| s.end_byte - s.start_byte)
+        .or_else(|| named().next())
+        .map(|(i, _)| i)
+}
+
+/// Moniker of the symbol `i` of the file at `path`
+fn moniker(
+    path: &str,
+    package: Option<&WorkspacePackage>,
+    symbols: &[SymbolRecord],
+    parents: &[Option<usize>],
+    i: usize,
+) -> String {
+    let module_path = match package {
+        Some(package) if !package.root.is_empty() => path
+            .strip_prefix(package.root.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(path),
+        _ => path,
+    };
+    let mut descriptors = scip::module_descriptors(module_path);
+
+    let mut chain = vec![i];
+    while let Some(parent) = parents[*chain.last().unwrap_or(&i)]
+        && chain.len() <= MAX_PARENT_DEPTH
+        && !chain.contains(&parent)
+    {
+        chain.push(parent);
+    }
+    // A parent defined in another file, e.g. the struct of a Rust `impl`
+    // block, is kept as a type
+    let outermost = *chain.last().unwrap_or(&i);
+    if parents[outermost].is_none()
+        && let Some(ref parent) = symbols[outermost].parent
+    {
+        descriptors.push(scip::Descriptor::Type(parent.clone()));
+    }
+    descriptors.extend(
+        chain
+            .iter()
+            .rev()
+            .map(|&s| scip::Descriptor::for_symbol(&symbols[s].kind, &symbols[s].name)),
+    );
+
+    let package = package.map(|package| scip::Package {
+        manager: package.ecosystem.as_str(),
+        name: &package.name,
+    });
+    scip::symbol(package.as_ref(), &descriptors)
+}
+
+/// Byte offsets of the whole-word matches of `word` in `text`
+fn find_word<'a>(text: &'a str, word: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word)
+        .filter(move |&(offset, _)| {
+            !word.is_empty()
+                && !text[..offset].chars().next_back().is_some_and(is_word)
+                && !text[offset + word.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(is_word)
+        })
+        .map(|(offset, _)| offset)
+}
+
+/// Byte offsets of the line starts of a text
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { starts }
+    }
+
+    fn position(&self, offset: usize) -> (i32, i32) {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        (line as i32, (offset - self.starts[line]) as i32)
+    }
+
+    /// Range of the bytes `start..end` of `text`. A trailing newline is left
+    /// out, so that ranges of whole lines end on their last line.
+    fn range(&self, text: &str, start: usize, end: usize) -> scip::Range {
+        let end = if end > start && text.as_bytes().get(end - 1) == Some(&b'\n') {
+            end - 1
+        } else {
+            end
+        };
+        let (start_line, start_character) = self.position(start);
+        let (end_line, end_character) = self.position(end);
+        scip::Range {
+            start_line,
+            start_character,
+            end_line,
+            end_character,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_index::{FileIndexData, PackageEcosystem};
+    use semantiq_parser::{
+        Language, LanguageSupport, OccurrenceExtractor, RelationExtractor, SymbolExtractor,
+    };
+
+    fn index_file(store: &IndexStore, path: &str, content: &str) {
+        let mut parser = LanguageSupport::new().unwrap();
+        let tree = parser.parse(Language::Rust, content).unwrap();
+        let symbols = SymbolExtractor::extract(&tree, content, Language::Rust).unwrap();
+        let relations = RelationExtractor::extract(&tree, content, Language::Rust).unwrap();
+        let occurrences = OccurrenceExtractor::extract(&tree, content, Language::Rust).unwrap();
+        store
+            .write_file_index(&FileIndexData {
+                symbols: &symbols,
+                relations: &relations,
+                occurrences: &occurrences,
+                ..FileIndexData::new(path, Some("rust"), content, content.len() as i64, 0)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_build_scip_index() {
+        let store = IndexStore::open_in_memory().unwrap();
+        store.set_file_contents(true);
+        index_file(
+            &store,
+            "crates/shapes/src/shape.rs",
+            "pub trait Shape {\n    fn area(&self) -> f64;\n}\n",
+        );
+        index_file(
+            &store,
+            "crates/shapes/src/square.rs",
+            "/// A square\npub struct Square {\n    side: f64,\n}\n\n\
+             impl Shape for Square {\n    fn area(&self) -> f64 {\n        self.side * self.side\n    }\n}\n",
+        );
+        store
+            .set_packages(&[WorkspacePackage {
+                root: "crates/shapes".to_string(),
+                ecosystem: PackageEcosystem::Cargo,
+                name: "shapes".to_string(),
+            }])
+            .unwrap();
+
+        let index = build_scip_index(&store, Path::new("/project")).unwrap();
+        assert_eq!(index.metadata.project_root, "file:///project");
+        assert_eq!(index.documents.len(), 2);
+
+        let square = index
+            .documents
+            .iter()
+            .find(|d| d.relative_path == "crates/shapes/src/square.rs")
+            .unwrap();
+        let square_symbol = "semantiq cargo shapes . src/square/Square#";
+        let shape_symbol = "semantiq cargo shapes . src/shape/Shape#";
+
+        let info = square
+            .symbols
+            .iter()
+            .find(|s| s.symbol == square_symbol)
+            .unwrap();
+        assert_eq!(info.display_name, "Square");
+        assert_eq!(info.kind, scip::symbol_kind("struct"));
+        assert!(info.documentation.iter().any(|d| d.contains("A square")));
+        assert_eq!(
+            info.relationships,
+            vec![scip::Relationship {
+                symbol: shape_symbol.to_string(),
+                is_implementation: true,
+            }]
+        );
+
+        let definition = square
+            .occurrences
+            .iter()
+            .find(|o| o.symbol == square_symbol && o.symbol_roles == scip::ROLE_DEFINITION)
+            .unwrap();
+        assert_eq!(
+            definition.range,
+            scip::Range {
+                start_line: 1,
+                start_character: 11,
+                end_line: 1,
+                end_character: 17,
+            }
+        );
+        assert_eq!(definition.enclosing_range.unwrap().end_line, 3);
+
+        // `impl Shape for Square` references both types
+        let references = square
+            .occurrences
+            .iter()
+            .filter(|o| o.symbol_roles == 0 && o.range.start_line == 5)
+            .map(|o| o.symbol.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(references, vec![shape_symbol, square_symbol]);
+
+        // The trait of the `impl` block is defined in another file
+        assert!(square.symbols.iter().any(|s| s.symbol
+            == "semantiq cargo shapes . src/square/Shape#area()."
+            && s.enclosing_symbol.is_none()));
+
+        let bytes = index.to_bytes();
+        assert!(!bytes.is_empty());
+    }
+
+    #[test]
+    fn test_line_index() {
+        let text = "ab\ncd\n";
+        let lines = LineIndex::new(text);
+        assert_eq!(lines.position(0), (0, 0));
+        assert_eq!(lines.position(4), (1, 1));
+        let range = lines.range(text, 0, text.len());
+        assert_eq!((range.end_line, range.end_character), (1, 2));
+        assert_eq!(find_word("a ab b_a a", "a").collect::<Vec<_>>(), vec![0, 9]);
+    }
+
+    #[tokio::test]
+    async fn test_export_rejects_unknown_format() {
+        let err = export(None, "lsif", None).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown export format 'lsif'. Valid formats: scip"
+        );
+    }
+}
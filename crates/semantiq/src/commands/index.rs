@@ -2,19 +2,100 @@
 
 use anyhow::Result;
 use ignore::WalkBuilder;
-use semantiq_embeddings::create_embedding_model;
-use semantiq_index::{IndexStore, MAX_FILE_SIZE, should_exclude_entry};
-use semantiq_parser::{
-    ChunkExtractor, ImportExtractor, Language, LanguageSupport, SymbolExtractor,
+use semantiq_embeddings::{ChunkEmbedding, create_embedding_model_for};
+use semantiq_index::priority::IndexQueue;
+use semantiq_index::{
+    EXTERNAL_PREFIX, EmbeddingMigrator, ExtractedFile, FileIndexData, FileOutcome,
+    GeneratedDetector, IncludeResolver, IndexIssue, IndexIssueKind, IndexLockAttempt,
+    IndexLockGuard, IndexReport, IndexStore, MAX_OUTLINE_FILE_SIZE, MIGRATION_BATCH_SIZE,
+    MigrationStep, ProgressTracker, SemantiqConfig, external, file_summary, is_outline_size,
+    read_source, relative_path, resolve_path, should_exclude_entry, workspace,
 };
+use semantiq_parser::{Language, LanguageSupport};
+use semantiq_retrieval::{BOOTSTRAP_SAMPLE_SIZE, bootstrap_thresholds};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{Instant, UNIX_EPOCH};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 
 use super::common::{resolve_db_path, resolve_project_root};
+use super::progress::ProgressBar;
 
-pub async fn index(path: &Path, database: Option<PathBuf>, force: bool) -> Result<()> {
+/// Owner recorded in the index lock while this command writes
+const LOCK_OWNER: &str = "semantiq index";
+
+/// Files written to the index per transaction
+const WRITE_BATCH_SIZE: usize = 32;
+
+/// A file parsed and embedded, waiting to be written with its batch
+struct ParsedFile {
+    rel_path: String,
+    language: &'static str,
+    encoding: Option<&'static str>,
+    generated: bool,
+    outline: bool,
+    content: String,
+    size: i64,
+    last_modified: i64,
+    extracted: ExtractedFile,
+    embeddings: Vec<Option<ChunkEmbedding>>,
+    summary_embedding: Option<Vec<f32>>,
+    issues: Vec<IndexIssue>,
+}
+
+impl ParsedFile {
+    fn data(&self) -> FileIndexData<'_> {
+        FileIndexData {
+            encoding: self.encoding,
+            generated: self.generated,
+            outline: self.outline,
+            embeddings: &self.embeddings,
+            issues: &self.issues,
+            summary_embedding: self.summary_embedding.as_deref(),
+            ..FileIndexData::new(
+                &self.rel_path,
+                Some(self.language),
+                &self.content,
+                self.size,
+                self.last_modified,
+            )
+        }
+        .with_extracted(&self.extracted)
+    }
+}
+
+/// Write the parsed files of a batch in one transaction and empty it
+fn write_batch(store: &IndexStore, batch: &mut Vec<ParsedFile>) -> Result<()> {
+    let files: Vec<FileIndexData<'_>> = batch.iter().map(ParsedFile::data).collect();
+    store.write_file_indexes(&files)?;
+    batch.clear();
+    Ok(())
+}
+
+/// How to handle an index lock held by another process
+pub struct LockOptions {
+    /// How long to wait for the lock before giving up
+    pub timeout: Duration,
+    /// Take the lock over instead of waiting
+    pub steal: bool,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            steal: false,
+        }
+    }
+}
+
+pub async fn index(
+    path: &Path,
+    database: Option<PathBuf>,
+    force: bool,
+    lock_options: LockOptions,
+) -> Result<()> {
     let project_root = resolve_project_root(path)?;
     let db_path = resolve_db_path(database, &project_root);
 
@@ -22,19 +103,52 @@ pub async fn index(path: &Path, database: Option<PathBuf>, force: bool) -> Resul
     info!("Database: {:?}", db_path);
 
     let start = Instant::now();
+    let config = SemantiqConfig::load(&project_root)?;
+    config.register_languages(&project_root)?;
     let store = IndexStore::open(&db_path)?;
+    let lock = acquire_lock(&store, &lock_options)?;
+    store.apply_config(&config)?;
 
     // Check if parser version changed and prepare for full reindex if needed
     let needs_full_reindex = store.check_and_prepare_for_reindex()?;
     let force = force || needs_full_reindex;
 
+    // Files left in the indexing journal by an interrupted run are indexed again
+    let recovered = store.recover_interrupted_files()?;
+    if !recovered.is_empty() {
+        info!(
+            "Recovering {} files from an interrupted index run",
+            recovered.len()
+        );
+    }
+
+    // Files are tagged with the workspace package owning them as they are indexed
+    let packages = workspace::discover_workspace_packages(&project_root);
+    if store.set_packages(&packages)? {
+        info!("Found {} workspace packages", packages.len());
+    }
+
+    // C and C++ includes are classified by the include directories of the
+    // compilation database
+    let include_resolver = IncludeResolver::load(&project_root, &config.includes);
+    let units = include_resolver.units();
+    if store.set_translation_units(&units)? {
+        info!("Found {} translation units", units.len());
+    }
+
     let mut language_support = LanguageSupport::new()?;
-    let chunk_extractor = ChunkExtractor::new();
+    let chunk_extractor = config.chunking.chunk_extractor();
 
-    // Initialize embedding model
-    let embedding_model = match create_embedding_model(None) {
+    // Initialize the model of the index, which stays the previous one while
+    // the chunks are re-embedded for a new model
+    let model_kind = store.embedding_model()?;
+    let embedding_model = match create_embedding_model_for(model_kind) {
         Ok(model) => {
-            info!("Embedding model loaded (dim={})", model.dimension());
+            info!(
+                "Embedding model {} loaded (dim={})",
+                model_kind,
+                model.dimension()
+            );
             Some(model)
         }
         Err(e) => {
@@ -50,6 +164,15 @@ pub async fn index(path: &Path, database: Option<PathBuf>, force: bool) -> Resul
     let mut symbol_count = 0;
     let mut chunk_count = 0;
     let mut dep_count = 0;
+    let mut warning_count = 0;
+    let mut report = IndexReport::default();
+
+    // Generated files are indexed but left out of searches by default
+    let generated = GeneratedDetector::load(&project_root);
+
+    let progress = ProgressTracker::new();
+    let mut progress_bar = ProgressBar::stderr();
+    progress.begin_scan();
 
     // Walk the directory, excluding hidden dirs and dependency folders
     let walker = WalkBuilder::new(&project_root)
@@ -61,38 +184,87 @@ pub async fn index(path: &Path, database: Option<PathBuf>, force: bool) -> Resul
         })
         .build();
 
+    // Collect candidate files first so progress can report totals and ETA
+    let mut candidates = Vec::new();
     for entry in walker.filter_map(|e| e.ok()) {
         let path = entry.path();
 
         if !path.is_file() {
             continue;
         }
+        progress.file_scanned();
 
         // Check if this is a supported language
-        let language = match Language::from_path(path) {
-            Some(lang) => lang,
-            None => continue,
+        if let Some(language) = Language::detect(path) {
+            candidates.push((entry.into_path(), language));
+        }
+    }
+
+    // Past `quota.max_files`, the files of lowest priority are left out
+    let mut over_quota: HashSet<PathBuf> = HashSet::new();
+    if config.quota.max_files > 0 {
+        let paths = candidates.iter().map(|(path, _)| path.clone()).collect();
+        let mut queue = IndexQueue::new(&project_root, paths, &config.quota);
+        over_quota.extend(queue.limit(config.quota.max_files));
+    }
+    if !over_quota.is_empty() {
+        info!(
+            "Leaving out {} files of lowest priority (quota.max_files = {})",
+            over_quota.len(),
+            config.quota.max_files
+        );
+        for path in &over_quota {
+            if let Some(rel_path) = relative_path(&project_root, path) {
+                store.delete_file(&rel_path)?;
+            }
+        }
+        candidates.retain(|(path, _)| !over_quota.contains(path));
+    }
+    store.set_quota_skipped_files(over_quota.len())?;
+
+    progress.begin_indexing(candidates.len());
+
+    let mut batch: Vec<ParsedFile> = Vec::with_capacity(WRITE_BATCH_SIZE);
+    for (path_buf, language) in candidates {
+        if !lock.refresh()? {
+            anyhow::bail!("The index lock was taken over by another process, stopping");
+        }
+        let path = path_buf.as_path();
+
+        let Some(rel_path) = relative_path(&project_root, path) else {
+            continue;
         };
 
-        // Get relative path
-        let rel_path = path
-            .strip_prefix(&project_root)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .to_string();
+        progress.start_file(&rel_path);
+        if let Some(ref mut bar) = progress_bar {
+            bar.update(&progress.snapshot());
+        }
 
-        // Read file content
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
+        // Read file content, decoding it to UTF-8 if needed
+        let source = match read_source(path) {
+            Ok(source) => source,
             Err(e) => {
-                debug!("Skipping {}: {}", rel_path, e);
+                warn!("Skipping {}: {}", rel_path, e);
+                let issues = [IndexIssue::new(&rel_path, IndexIssueKind::ReadError, e)];
+                store.set_index_issues(&rel_path, &issues)?;
+                report.extend(issues);
+                progress.finish_file(FileOutcome::Skipped);
                 continue;
             }
         };
+        let content = source.content;
+        let is_generated = generated.is_generated(&rel_path, &content);
 
         // Check if we need to reindex
         if !force && !store.needs_reindex(&rel_path, &content)? {
+            // `.gitattributes` may have changed since the file was indexed
+            store.set_file_generated(&rel_path, is_generated)?;
+            // Files indexed before `storage.file_contents` was enabled
+            if store.stores_file_contents() {
+                store.backfill_file_content(&rel_path, &content)?;
+            }
             debug!("Skipping {} (unchanged)", rel_path);
+            progress.finish_file(FileOutcome::Skipped);
             continue;
         }
 
@@ -106,91 +278,255 @@ pub async fn index(path: &Path, database: Option<PathBuf>, force: bool) -> Resul
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0i64);
 
-        // Skip large files
-        if size > MAX_FILE_SIZE as i64 {
-            debug!("Skipping {} (too large: {} bytes)", rel_path, size);
+        // Skip files too large even for an outline of their top-level symbols
+        if size > MAX_OUTLINE_FILE_SIZE as i64 {
+            warn!("Skipping {}: too large ({} bytes)", rel_path, size);
+            let issues = [IndexIssue::too_large(&rel_path, metadata.len())];
+            store.set_index_issues(&rel_path, &issues)?;
+            report.extend(issues);
+            progress.finish_file(FileOutcome::Skipped);
             continue;
         }
+        let outline = is_outline_size(metadata.len());
+        if outline {
+            info!(
+                "Indexing the outline of {} ({} bytes, too large to chunk)",
+                rel_path, size
+            );
+        }
 
-        // Insert file record
-        let file_id = store.insert_file(
+        let mut issues = Vec::new();
+        if let Some(ref warning) = source.warning {
+            warn!("{}: {}", rel_path, warning);
+            warning_count += 1;
+            progress.file_warning();
+            issues.push(IndexIssue::new(
+                &rel_path,
+                IndexIssueKind::Encoding,
+                warning,
+            ));
+        }
+
+        let mut extracted = match ExtractedFile::extract(
+            &mut language_support,
+            &chunk_extractor,
+            language,
+            path,
             &rel_path,
-            Some(language.name()),
             &content,
+            outline,
+        ) {
+            Ok(extracted) => extracted,
+            // The file is indexed without its symbols and the pass goes on
+            Err(e) => {
+                warn!("Failed to parse {}: {}", rel_path, e);
+                issues.push(IndexIssue::new(&rel_path, IndexIssueKind::ParseFailure, e));
+                ExtractedFile::default()
+            }
+        };
+        include_resolver.classify(&rel_path, &mut extracted.imports);
+
+        // Generate embeddings for chunks, except in the directories evicted
+        // by `quota.max_db_mb`
+        let evicted = store.embeddings_evicted(&rel_path)?;
+        let embeddings: Vec<Option<ChunkEmbedding>> = match embedding_model {
+            Some(ref model) if !evicted => extracted
+                .chunks
+                .iter()
+                .map(
+                    |chunk| match model.embed_chunk(&store.embedding_text(&chunk.content)) {
+                        Ok(embedding) => Some(embedding),
+                        Err(e) => {
+                            debug!(
+                                "Failed to generate embedding for chunk at {}:{}: {}",
+                                rel_path, chunk.start_line, e
+                            );
+                            None
+                        }
+                    },
+                )
+                .collect(),
+            _ => Vec::new(),
+        };
+        let summary_embedding = embedding_model
+            .as_ref()
+            .filter(|_| !outline)
+            .and_then(|model| {
+                let summary = file_summary(
+                    &rel_path,
+                    extracted
+                        .symbols
+                        .iter()
+                        .map(|s| (s.name.as_str(), s.doc_comment.as_deref())),
+                    extracted
+                        .chunks
+                        .iter()
+                        .map(|c| (c.content.as_str(), c.symbols.len())),
+                )?;
+                model
+                    .embed(&store.passage_text(&summary))
+                    .map_err(|e| {
+                        debug!(
+                            "Failed to generate summary embedding for {}: {}",
+                            rel_path, e
+                        )
+                    })
+                    .ok()
+            });
+
+        symbol_count += extracted.symbols.len();
+        chunk_count += extracted.chunks.len();
+        dep_count += extracted.imports.len();
+
+        debug!(
+            "Indexed {}: {} symbols, {} chunks, {} deps",
+            rel_path,
+            extracted.symbols.len(),
+            extracted.chunks.len(),
+            extracted.imports.len()
+        );
+
+        // Files are written with everything extracted from them, a batch
+        // of files per transaction
+        batch.push(ParsedFile {
+            rel_path,
+            language: language.name(),
+            encoding: source.encoding,
+            generated: is_generated,
+            outline,
+            content,
             size,
             last_modified,
+            extracted,
+            embeddings,
+            summary_embedding,
+            issues: issues.clone(),
+        });
+        report.extend(issues);
+        if batch.len() >= WRITE_BATCH_SIZE {
+            write_batch(&store, &mut batch)?;
+        }
+
+        file_count += 1;
+        progress.finish_file(FileOutcome::Indexed);
+
+        match progress_bar {
+            Some(ref mut bar) => bar.update(&progress.snapshot()),
+            // Progress update every 100 files when not attached to a terminal
+            None if file_count % 100 == 0 => info!("Indexed {} files...", file_count),
+            None => {}
+        }
+    }
+
+    write_batch(&store, &mut batch)?;
+    // Issues of files removed from the project since they were met
+    store.retain_index_issues(|path| resolve_path(&project_root, path).is_file())?;
+
+    progress.complete();
+    if let Some(ref mut bar) = progress_bar {
+        bar.finish();
+    }
+
+    // Sources of declared dependencies, opt-in through semantiq.toml
+    if config.external.enabled {
+        info!("Indexing external dependencies...");
+        let packages = external::discover_packages(&project_root);
+        let result = external::index_external_packages(
+            &store,
+            &packages,
+            &mut language_support,
+            &chunk_extractor,
+            embedding_model.as_deref(),
         )?;
+        info!(
+            "  External packages: {} indexed, {} up to date ({} files)",
+            result.packages_indexed, result.packages_up_to_date, result.files_indexed
+        );
+    } else {
+        let removed = store.delete_files_with_prefix(EXTERNAL_PREFIX)?;
+        if removed > 0 {
+            info!("Removed {} external dependency files", removed);
+        }
+    }
 
-        // Parse and extract symbols
-        match language_support.parse(language, &content) {
-            Ok(tree) => {
-                // Extract symbols
-                let symbols = SymbolExtractor::extract(&tree, &content, language)?;
-                store.insert_symbols(file_id, &symbols)?;
-                symbol_count += symbols.len();
-
-                // Extract chunks
-                let chunks = chunk_extractor.extract(&tree, &content, language)?;
-                store.insert_chunks(file_id, &chunks)?;
-                chunk_count += chunks.len();
-
-                // Generate embeddings for chunks
-                if let Some(ref model) = embedding_model {
-                    let stored_chunks = store.get_chunks_by_file(file_id)?;
-                    for chunk in stored_chunks {
-                        match model.embed(&chunk.content) {
-                            Ok(embedding) => {
-                                if let Err(e) = store.update_chunk_embedding(chunk.id, &embedding) {
-                                    warn!(
-                                        "Failed to store embedding for chunk {}: {}",
-                                        chunk.id, e
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                debug!(
-                                    "Failed to generate embedding for chunk {}: {}",
-                                    chunk.id, e
-                                );
-                            }
-                        }
+    // A new embedding model re-embeds the chunks, then searches switch to it
+    let migrated = match EmbeddingMigrator::for_store(&store, config.embeddings.switch_coverage) {
+        Ok(Some(migrator)) => {
+            let mut reported = 0;
+            migrator.run(&store, MIGRATION_BATCH_SIZE, |step| match step {
+                MigrationStep::Migrating(progress) => {
+                    let percent = (progress.coverage() * 100.0) as usize;
+                    if percent >= reported + 10 {
+                        reported = percent - percent % 10;
+                        info!("Re-embedded {}% of the chunks...", reported);
                     }
                 }
+                MigrationStep::Switched(model) => info!("Switched to embedding model {}", model),
+                _ => {}
+            })?;
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            warn!(
+                "Could not load the new embedding model: {}. Searches keep using {}.",
+                e, model_kind
+            );
+            false
+        }
+    };
 
-                // Extract imports and store as dependencies
-                let imports = ImportExtractor::extract(&tree, &content, language)?;
-                store.delete_dependencies(file_id)?;
-                for import in &imports {
-                    store.insert_dependency(
-                        file_id,
-                        &import.path,
-                        import.name.as_deref(),
-                        import.kind.as_str(),
-                    )?;
-                }
-                dep_count += imports.len();
-
-                debug!(
-                    "Indexed {}: {} symbols, {} chunks, {} deps",
-                    rel_path,
-                    symbols.len(),
-                    chunks.len(),
-                    imports.len()
-                );
-            }
-            Err(e) => {
-                warn!("Failed to parse {}: {}", rel_path, e);
-            }
+    // Seed semantic search thresholds until live searches calibrate them
+    if file_count > 0 || migrated {
+        match bootstrap_thresholds(&store, BOOTSTRAP_SAMPLE_SIZE) {
+            Ok(Some(thresholds)) => info!(
+                "Calibrated search thresholds from the index (max distance {:.3})",
+                thresholds.global.max_distance
+            ),
+            Ok(None) => {}
+            Err(e) => warn!("Threshold bootstrap failed: {}", e),
         }
+    }
 
-        file_count += 1;
+    // Per-directory summaries for semantiq_overview
+    match store.refresh_dir_summaries() {
+        Ok(count) => info!("Summarized {} directories", count),
+        Err(e) => warn!("Directory summaries failed: {}", e),
+    }
 
-        // Progress update every 100 files
-        if file_count % 100 == 0 {
-            info!("Indexed {} files...", file_count);
+    // Weights of duplicated and boilerplate chunks in semantic search
+    match store.refresh_chunk_distinctiveness() {
+        Ok(count) => info!("Weighted down {} boilerplate or duplicated chunks", count),
+        Err(e) => warn!("Chunk distinctiveness failed: {}", e),
+    }
+
+    // With compression enabled, the first run trains the chunk dictionary
+    if config.storage.compress_chunks
+        && file_count > 0
+        && store.chunk_codec().dictionary().is_none()
+    {
+        match store.compact_chunks() {
+            Ok(report) => info!(
+                "Compressed {} chunks ({} -> {} bytes)",
+                report.compressed, report.content_bytes, report.stored_bytes_after
+            ),
+            Err(e) => warn!("Chunk compaction failed: {}", e),
         }
     }
 
+    // Past `quota.max_db_mb`, the least recently searched directories lose
+    // their embeddings
+    match store.enforce_size_quota(&config.quota) {
+        Ok(report) if !report.directories.is_empty() => info!(
+            "Dropped the embeddings of {} chunks in {} directories to fit quota.max_db_mb",
+            report.chunks,
+            report.directories.len()
+        ),
+        Ok(_) => {}
+        Err(e) => warn!("Index size quota failed: {}", e),
+    }
+
+    drop(lock);
     let elapsed = start.elapsed();
 
     info!("Indexing complete!");
@@ -198,7 +534,43 @@ pub async fn index(path: &Path, database: Option<PathBuf>, force: bool) -> Resul
     info!("  Symbols: {}", symbol_count);
     info!("  Chunks: {}", chunk_count);
     info!("  Dependencies: {}", dep_count);
+    if warning_count > 0 {
+        info!("  Warnings: {} (see the log for the files)", warning_count);
+    }
+    if !report.is_empty() {
+        warn!("  Issues: {} (see `semantiq stats`)", report);
+    }
     info!("  Time: {:.2}s", elapsed.as_secs_f64());
 
     Ok(())
 }
+
+/// Take the index lock, waiting for or stealing it from another process
+fn acquire_lock<'a>(store: &'a IndexStore, options: &LockOptions) -> Result<IndexLockGuard<'a>> {
+    if options.steal {
+        let (guard, _previous) = store.steal_index_lock(LOCK_OWNER)?;
+        return Ok(guard);
+    }
+
+    let attempt = match store.try_lock_index(LOCK_OWNER)? {
+        IndexLockAttempt::Held(holder) if !options.timeout.is_zero() => {
+            info!(
+                "Index is locked by {}, waiting up to {}s",
+                holder,
+                options.timeout.as_secs()
+            );
+            store.lock_index(LOCK_OWNER, options.timeout)?
+        }
+        attempt => attempt,
+    };
+
+    match attempt {
+        IndexLockAttempt::Acquired(guard) => Ok(guard),
+        IndexLockAttempt::Held(holder) => anyhow::bail!(
+            "The index is locked by {}. A running `semantiq serve` keeps the index up to date \
+             on its own; wait for it to finish, raise --lock-timeout, or pass --steal-lock if \
+             the holder is stuck.",
+            holder
+        ),
+    }
+}
@@ -5,7 +5,7 @@ use std::fs;
 use std::path::Path;
 
 use super::common::resolve_project_root;
-use super::index::index;
+use super::index::{LockOptions, index};
 
 pub async fn init(path: &Path) -> Result<()> {
     let project_root = resolve_project_root(path)?;
@@ -121,7 +121,7 @@ The index updates automatically when files change. No manual reindexing needed.
 
     // 5. Index the project
     println!("\nIndexing project...");
-    index(path, None, false).await?;
+    index(path, None, false, LockOptions::default()).await?;
 
     println!("\n✓ Semantiq initialized successfully!");
     println!("\nNext steps:");
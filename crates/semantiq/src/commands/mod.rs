@@ -1,18 +1,44 @@
 //! CLI command implementations for Semantiq
 
+mod analyze;
+mod bench;
 mod calibrate;
 mod common;
+mod compact;
+mod completions;
+mod diff;
+mod explain;
+mod export;
 mod index;
 mod init;
 mod init_cursor;
+mod progress;
+mod quantize;
+mod query;
+mod refs;
 mod search;
 mod serve;
 mod stats;
+mod symbol_kinds;
+mod tui;
 
+pub use analyze::analyze_unused;
+pub use bench::{BenchOptions, bench};
 pub use calibrate::calibrate;
-pub use index::index;
+pub use common::DEFAULT_DB_NAME;
+pub use compact::compact;
+pub use completions::completions;
+pub use diff::diff;
+pub use explain::explain;
+pub use export::export;
+pub use index::{LockOptions, index};
 pub use init::init;
 pub use init_cursor::init_cursor;
-pub use search::search;
+pub use quantize::quantize;
+pub use query::{query_delete, query_list, query_run, query_save};
+pub use refs::{RefsFilters, refs};
+pub use search::{SearchFilters, search};
 pub use serve::serve;
 pub use stats::stats;
+pub use symbol_kinds::symbol_kinds;
+pub use tui::tui;
@@ -0,0 +1,154 @@
+//! Terminal progress bar for the `index` command
+
+use semantiq_index::{IndexPhase, IndexProgress};
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Width of the bar itself, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Maximum length of the current file path shown after the bar
+const MAX_PATH_LEN: usize = 40;
+
+/// Minimum delay between two redraws
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Single-line progress bar redrawn in place on stderr
+pub struct ProgressBar {
+    last_draw: Option<Instant>,
+}
+
+impl ProgressBar {
+    /// Create a progress bar if stderr is an interactive terminal
+    pub fn stderr() -> Option<Self> {
+        std::io::stderr()
+            .is_terminal()
+            .then_some(Self { last_draw: None })
+    }
+
+    /// Redraw the bar, throttled to avoid flooding the terminal
+    pub fn update(&mut self, progress: &IndexProgress) {
+        if self
+            .last_draw
+            .is_some_and(|t| t.elapsed() < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{}", render_progress_line(progress));
+        let _ = stderr.flush();
+    }
+
+    /// Clear the bar so the final summary starts on a clean line
+    pub fn finish(&mut self) {
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+    }
+}
+
+/// Render a progress snapshot as a single line of text
+pub fn render_progress_line(progress: &IndexProgress) -> String {
+    if progress.phase == IndexPhase::Scanning {
+        return format!("Scanning... {} files found", progress.scanned);
+    }
+
+    let filled = ((progress.percent() / 100.0) * BAR_WIDTH as f32).round() as usize;
+    let filled = filled.min(BAR_WIDTH);
+
+    let mut line = format!(
+        "[{}{}] {:>3.0}% {}/{} files",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        progress.percent(),
+        progress.processed,
+        progress.total
+    );
+
+    if let Some(eta_ms) = progress.eta_ms
+        && !progress.is_complete()
+    {
+        line.push_str(&format!(" | ETA {}", format_duration(eta_ms)));
+    }
+
+    if let Some(ref file) = progress.current_file {
+        line.push_str(" | ");
+        line.push_str(&truncate_path(file, MAX_PATH_LEN));
+    }
+
+    line
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms.div_ceil(1000);
+    if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Keep the end of long paths, which carries the file name
+fn truncate_path(path: &str, max_len: usize) -> String {
+    let char_count = path.chars().count();
+    if char_count <= max_len {
+        return path.to_string();
+    }
+    let tail: String = path.chars().skip(char_count - (max_len - 3)).collect();
+    format!("...{}", tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_progress_line() {
+        let progress = IndexProgress {
+            phase: IndexPhase::Indexing,
+            total: 4,
+            processed: 2,
+            current_file: Some("src/main.rs".to_string()),
+            eta_ms: Some(65_000),
+            ..Default::default()
+        };
+
+        let line = render_progress_line(&progress);
+        assert!(line.starts_with(&format!("[{}{}]", "#".repeat(15), "-".repeat(15))));
+        assert!(line.contains(" 50% 2/4 files"));
+        assert!(line.contains("ETA 1m05s"));
+        assert!(line.ends_with("| src/main.rs"));
+    }
+
+    #[test]
+    fn test_render_scanning_line() {
+        let progress = IndexProgress {
+            phase: IndexPhase::Scanning,
+            scanned: 12,
+            ..Default::default()
+        };
+        assert_eq!(
+            render_progress_line(&progress),
+            "Scanning... 12 files found"
+        );
+    }
+
+    #[test]
+    fn test_truncate_path_keeps_file_name() {
+        assert_eq!(truncate_path("src/lib.rs", 40), "src/lib.rs");
+
+        let truncated = truncate_path("crates/very/deeply/nested/module/file.rs", 20);
+        assert_eq!(truncated.chars().count(), 20);
+        assert!(truncated.starts_with("..."));
+        assert!(truncated.ends_with("file.rs"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(1500), "2s");
+        assert_eq!(format_duration(125_000), "2m05s");
+    }
+}
@@ -0,0 +1,48 @@
+//! Convert stored embeddings between float32 and int8
+
+use anyhow::{Context, Result};
+use semantiq_index::{EmbeddingFormat, IndexStore};
+use std::path::PathBuf;
+
+use super::common::resolve_db_path;
+
+pub async fn quantize(database: Option<PathBuf>, format: &str) -> Result<()> {
+    let format = EmbeddingFormat::parse(format)
+        .with_context(|| format!("Invalid format '{}'. Valid formats: int8, float32", format))?;
+
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let db_path = resolve_db_path(database, &cwd);
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found: {:?}. Run 'semantiq index' first.",
+            db_path
+        );
+    }
+
+    let store = IndexStore::open(&db_path)?;
+    let current = store.embedding_format();
+
+    if current == format {
+        println!("Embeddings are already stored as {}", format.as_str());
+        return Ok(());
+    }
+
+    let size_before = store.get_database_size()?;
+    let converted = store.set_embedding_format(format)?;
+    let size_after = store.get_database_size()?;
+
+    println!(
+        "Converted {} embeddings from {} to {}",
+        converted,
+        current.as_str(),
+        format.as_str()
+    );
+    println!(
+        "Database size: {:.1} MB -> {:.1} MB",
+        size_before as f64 / (1024.0 * 1024.0),
+        size_after as f64 / (1024.0 * 1024.0)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+//! Save, list and run named searches
+
+use anyhow::{Context, Result};
+use semantiq_index::{IndexStore, SavedQuery};
+use semantiq_mcp::tools::{SemantiqSavedSearch, SemantiqSearch};
+use std::path::PathBuf;
+
+use super::common::{engine_over, open_store, parse_output_format};
+use super::search::print_results;
+
+pub async fn query_save(
+    name: &str,
+    query: &str,
+    filters: &[String],
+    description: Option<&str>,
+    database: Option<PathBuf>,
+) -> Result<()> {
+    let mut saved = SavedQuery::new(name, query);
+    for (filter, value) in parse_assignments(filters, "filter")? {
+        saved = saved.with_filter(&filter, &value);
+    }
+    if let Some(description) = description {
+        saved = saved.with_description(description);
+    }
+    validate_filters(&saved)?;
+
+    let store = open_store(database)?;
+    let replaced = store.save_query(&saved)?;
+
+    let parameters = saved.parameters();
+    println!(
+        "{} query '{}'{}",
+        if replaced { "Replaced saved" } else { "Saved" },
+        saved.name,
+        if parameters.is_empty() {
+            String::new()
+        } else {
+            format!(" (parameters: {})", parameters.join(", "))
+        }
+    );
+    Ok(())
+}
+
+pub async fn query_list(database: Option<PathBuf>) -> Result<()> {
+    let store = open_store(database)?;
+    print!("{}", format_saved_queries(&store.list_saved_queries()?));
+    Ok(())
+}
+
+pub async fn query_run(
+    name: &str,
+    params: &[String],
+    limit: Option<usize>,
+    database: Option<PathBuf>,
+    format: Option<&str>,
+) -> Result<()> {
+    let format = parse_output_format(format)?;
+    let store = open_store(database)?;
+    let saved = find_saved_query(&store, name)?;
+
+    let mut request = SemantiqSavedSearch::new(name);
+    for (param, value) in parse_assignments(params, "parameter")? {
+        request = request.with_param(&param, &value);
+    }
+    if let Some(limit) = limit {
+        request = request.with_limit(limit);
+    }
+    let search = request.search_request(&saved)?;
+    let query = search.validated_query()?;
+    let options = search.options()?;
+
+    let engine = engine_over(store)?;
+    let results = engine.search(query, search.effective_limit(), Some(options))?;
+
+    // Flush distance observations for ML calibration
+    if let Err(e) = engine.flush_observations() {
+        tracing::debug!("Failed to flush observations: {}", e);
+    }
+
+    print_results(query, &results, format);
+    Ok(())
+}
+
+pub async fn query_delete(name: &str, database: Option<PathBuf>) -> Result<()> {
+    let store = open_store(database)?;
+    if !store.delete_saved_query(name)? {
+        anyhow::bail!("No saved query named '{}'", name);
+    }
+    println!("Deleted saved query '{}'", name);
+    Ok(())
+}
+
+fn find_saved_query(store: &IndexStore, name: &str) -> Result<SavedQuery> {
+    if let Some(saved) = store.get_saved_query(name)? {
+        return Ok(saved);
+    }
+    let names: Vec<String> = store
+        .list_saved_queries()?
+        .into_iter()
+        .map(|q| q.name)
+        .collect();
+    if names.is_empty() {
+        anyhow::bail!(
+            "Unknown saved query '{}'. Save one with 'semantiq query save'",
+            name
+        );
+    }
+    anyhow::bail!(
+        "Unknown saved query '{}'. Saved queries: {}",
+        name,
+        names.join(", ")
+    )
+}
+
+/// Parse `name=value` arguments; `what` names them in error messages
+fn parse_assignments(assignments: &[String], what: &str) -> Result<Vec<(String, String)>> {
+    assignments
+        .iter()
+        .map(|assignment| {
+            let (name, value) = assignment
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .with_context(|| {
+                    format!("Invalid {} '{}': expected name=value", what, assignment)
+                })?;
+            Ok((name.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Check the filters of a query before saving it. Filters holding
+/// parameters are checked once filled in, when the query is run.
+fn validate_filters(saved: &SavedQuery) -> Result<()> {
+    for (name, value) in &saved.filters {
+        if value.contains('{') {
+            if !SemantiqSearch::FILTERS.contains(&name.as_str()) {
+                anyhow::bail!(
+                    "Unknown filter '{}': expected one of {}",
+                    name,
+                    SemantiqSearch::FILTERS.join(", ")
+                );
+            }
+            continue;
+        }
+        SemantiqSearch::new(&saved.query)
+            .with_filter(name, value)?
+            .options()?;
+    }
+    Ok(())
+}
+
+/// Plain terminal output: one line per query with its filters, then its
+/// description
+fn format_saved_queries(queries: &[SavedQuery]) -> String {
+    if queries.is_empty() {
+        return "No saved queries. Save one with 'semantiq query save <NAME> <QUERY>'\n"
+            .to_string();
+    }
+
+    let mut output = format!("{} saved queries\n", queries.len());
+    for query in queries {
+        output.push_str(&format!("\n{}: {}", query.name, query.query));
+        if !query.filters.is_empty() {
+            let filters: Vec<String> = query
+                .filters
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            output.push_str(&format!(" [{}]", filters.join(", ")));
+        }
+        output.push('\n');
+        if let Some(ref description) = query.description {
+            output.push_str(&format!("   {}\n", description));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assignments() {
+        let parsed = parse_assignments(
+            &["file_type=rs,ts".to_string(), "verb = a=b".to_string()],
+            "filter",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                ("file_type".to_string(), "rs,ts".to_string()),
+                ("verb".to_string(), " a=b".to_string())
+            ]
+        );
+
+        let err = parse_assignments(&["rs".to_string()], "filter").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid filter 'rs': expected name=value");
+        assert!(parse_assignments(&["=rs".to_string()], "parameter").is_err());
+    }
+
+    #[test]
+    fn test_validate_filters() {
+        let valid = SavedQuery::new("q", "handler")
+            .with_filter("file_type", "rs")
+            .with_filter("symbol_kind", "{kind}");
+        assert!(validate_filters(&valid).is_ok());
+
+        let invalid = SavedQuery::new("q", "handler").with_filter("symbol_kind", "fn");
+        assert!(
+            validate_filters(&invalid)
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid symbol_kind 'fn'")
+        );
+
+        let unknown = SavedQuery::new("q", "handler").with_filter("lang", "{lang}");
+        assert!(
+            validate_filters(&unknown)
+                .unwrap_err()
+                .to_string()
+                .starts_with("Unknown filter 'lang'")
+        );
+    }
+
+    #[test]
+    fn test_format_saved_queries() {
+        let queries = vec![
+            SavedQuery::new("find-handlers", "http handler {verb}")
+                .with_filter("file_type", "rs")
+                .with_description("HTTP handlers of a verb"),
+            SavedQuery::new("auth", "login flow"),
+        ];
+        assert_eq!(
+            format_saved_queries(&queries),
+            "2 saved queries\n\
+             \nfind-handlers: http handler {verb} [file_type=rs]\n   HTTP handlers of a verb\n\
+             \nauth: login flow\n"
+        );
+        assert!(format_saved_queries(&[]).starts_with("No saved queries"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Find references to a symbol
+
+use anyhow::{Context, Result};
+use semantiq_retrieval::{FindRefsOptions, RefKind, ReferencesResponse, SearchResult};
+use std::path::PathBuf;
+
+use super::common::{open_engine, parse_output_format};
+
+/// Result filters of the `refs` command
+pub struct RefsFilters {
+    pub kind: Option<String>,
+    pub path_prefix: Option<String>,
+    pub exclude_tests: bool,
+    pub exclude_comments: bool,
+    pub exclude_strings: bool,
+    pub cross_language: bool,
+    /// Index generation to find the definitions at
+    pub as_of: Option<u64>,
+}
+
+pub async fn refs(
+    symbol: &str,
+    database: Option<PathBuf>,
+    limit: usize,
+    filters: RefsFilters,
+    format: Option<&str>,
+) -> Result<()> {
+    let symbol = symbol.trim();
+    if symbol.is_empty() {
+        anyhow::bail!("Symbol name cannot be empty");
+    }
+    let format = parse_output_format(format)?;
+    let kind = filters
+        .kind
+        .as_deref()
+        .map(|k| {
+            RefKind::parse(k).with_context(|| {
+                format!("Invalid kind '{}'. Valid kinds: definition, usage, all", k)
+            })
+        })
+        .transpose()?;
+
+    let engine = open_engine(database)?;
+
+    let mut options = FindRefsOptions::new()
+        .with_limit(limit)
+        .excluding_tests(filters.exclude_tests)
+        .excluding_comments(filters.exclude_comments)
+        .excluding_strings(filters.exclude_strings)
+        .following_cross_language(filters.cross_language);
+    if let Some(kind) = kind {
+        options = options.with_kind(kind);
+    }
+    if let Some(ref prefix) = filters.path_prefix {
+        options = options.with_path_prefix(prefix);
+    }
+    if let Some(generation) = filters.as_of {
+        options = options.as_of_generation(generation);
+    }
+
+    let mut response = ReferencesResponse::from(engine.find_references(symbol, &options)?);
+    if filters.cross_language && filters.as_of.is_none() {
+        response.links = engine.cross_language_links(symbol)?;
+    }
+    response.as_of = filters.as_of;
+
+    match format {
+        Some(format) => println!("{}", format.render(&response)),
+        None => print!("{}", format_references(&response)),
+    }
+
+    Ok(())
+}
+
+/// Plain terminal output: cross-language links, definitions, then one line
+/// per usage.
+fn format_references(response: &ReferencesResponse) -> String {
+    let mut output = format!(
+        "References to '{}' ({} ms)\nFound {} references\n",
+        response.symbol, response.search_time_ms, response.total_count
+    );
+    if let Some(generation) = response.as_of {
+        output.push_str(&format!(
+            "As of index generation {} (usages counted per file)\n",
+            generation
+        ));
+    }
+
+    if !response.links.is_empty() {
+        output.push_str(&format!(
+            "\nLinked in other languages ({}):\n",
+            response.links.len()
+        ));
+        for link in &response.links {
+            let framework = link
+                .framework
+                .as_deref()
+                .map(|f| format!(" ({})", f))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "🔗 {}{}  {}:{}\n",
+                link.name, framework, link.file_path, link.line
+            ));
+        }
+    }
+
+    if !response.definitions.is_empty() {
+        output.push_str(&format!(
+            "\nDefinitions ({}):\n",
+            response.definitions.len()
+        ));
+        for def in &response.definitions {
+            output.push_str(&format!(
+                "📄 {}:{}-{}{}\n",
+                def.file_path,
+                def.start_line,
+                def.end_line,
+                kind_suffix(def)
+            ));
+            let signature = def
+                .metadata
+                .context
+                .as_deref()
+                .unwrap_or_else(|| def.content.lines().next().unwrap_or(""));
+            output.push_str(&format!("   {}\n", signature.trim()));
+        }
+    }
+
+    if !response.usages.is_empty() {
+        output.push_str(&format!("\nUsages ({}):\n", response.usages.len()));
+        for usage in &response.usages {
+            let line: String = usage.content.trim().chars().take(100).collect();
+            // Line 0 for the per-file counts of an earlier generation
+            let location = match usage.start_line {
+                0 => usage.file_path.clone(),
+                start_line => format!("{}:{}", usage.file_path, start_line),
+            };
+            output.push_str(&format!("📄 {}  {}\n", location, line));
+        }
+    }
+
+    output
+}
+
+fn kind_suffix(result: &SearchResult) -> String {
+    result
+        .metadata
+        .symbol_kind
+        .as_deref()
+        .map(|kind| format!(" ({})", kind))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_retrieval::{CrossLanguageLink, SearchResultKind, SearchResultMetadata};
+
+    #[test]
+    fn test_format_references() {
+        let definition = SearchResult::new(
+            SearchResultKind::Symbol,
+            "src/config.rs".to_string(),
+            3,
+            8,
+            "pub fn load() -> Config {\n}".to_string(),
+            1.0,
+        )
+        .with_metadata(SearchResultMetadata {
+            symbol_kind: Some("function".to_string()),
+            context: Some("pub fn load() -> Config".to_string()),
+            ..Default::default()
+        });
+        let usage = SearchResult::new(
+            SearchResultKind::Reference,
+            "src/main.rs".to_string(),
+            12,
+            12,
+            "    let config = load();".to_string(),
+            0.8,
+        );
+        let response = ReferencesResponse {
+            symbol: "load".to_string(),
+            total_count: 2,
+            search_time_ms: 4,
+            definitions: vec![definition],
+            usages: vec![usage],
+            as_of: None,
+            cached: false,
+            links: vec![CrossLanguageLink {
+                name: "loadConfig".to_string(),
+                framework: Some("napi".to_string()),
+                file_path: "src/config.rs".to_string(),
+                line: 3,
+            }],
+        };
+
+        let output = format_references(&response);
+        assert!(output.starts_with("References to 'load' (4 ms)\nFound 2 references\n"));
+        assert!(output.contains(
+            "Definitions (1):\n📄 src/config.rs:3-8 (function)\n   pub fn load() -> Config\n"
+        ));
+        assert!(
+            output.contains(
+                "Linked in other languages (1):\n🔗 loadConfig (napi)  src/config.rs:3\n"
+            )
+        );
+        assert!(output.contains("Usages (1):\n📄 src/main.rs:12  let config = load();\n"));
+    }
+}
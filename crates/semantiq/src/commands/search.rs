@@ -1,54 +1,84 @@
 //! Search the index (for testing)
 
 use anyhow::{Context, Result};
-use semantiq_index::IndexStore;
-use semantiq_retrieval::SearchOptions;
+use semantiq_retrieval::{OutputFormat, SearchOptions, SearchResults, SearchScope};
 use std::path::PathBuf;
-use std::sync::Arc;
 
-use super::common::resolve_db_path;
+use super::common::{open_engine, parse_output_format};
+
+/// Result filters of the `search` command
+pub struct SearchFilters {
+    pub min_score: Option<f32>,
+    pub file_type: Option<String>,
+    pub symbol_kind: Option<String>,
+    pub scope: Option<String>,
+    /// Attach a score breakdown to each result
+    pub explain_scores: bool,
+    /// Lines of context added around each result
+    pub context_lines: usize,
+    /// Expand each result to its enclosing symbol
+    pub expand_to_symbol: bool,
+    /// Leave out conditionally compiled symbols
+    pub exclude_conditional: bool,
+    /// Keep generated files in the results
+    pub include_generated: bool,
+    /// Leave out text matches inside comments
+    pub exclude_comments: bool,
+    /// Leave out text matches inside string literals
+    pub exclude_strings: bool,
+}
 
 pub async fn search(
     query: &str,
     database: Option<PathBuf>,
     limit: usize,
-    min_score: Option<f32>,
-    file_type: Option<String>,
-    symbol_kind: Option<String>,
+    filters: SearchFilters,
+    format: Option<&str>,
 ) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let db_path = resolve_db_path(database, &cwd);
+    let format = parse_output_format(format)?;
+    let scope = filters
+        .scope
+        .as_deref()
+        .map(|s| {
+            SearchScope::parse(s).with_context(|| {
+                format!(
+                    "Invalid scope '{}'. Valid scopes: project, external, all",
+                    s
+                )
+            })
+        })
+        .transpose()?;
 
-    if !db_path.exists() {
-        anyhow::bail!(
-            "Database not found: {:?}. Run 'semantiq index' first.",
-            db_path
-        );
-    }
-
-    let store = Arc::new(IndexStore::open(&db_path)?);
-    let cwd_str = cwd
-        .to_str()
-        .context("Current directory path contains invalid UTF-8")?;
-    let engine = semantiq_retrieval::RetrievalEngine::new(store, cwd_str);
+    let engine = open_engine(database)?;
 
     // Build SearchOptions
     let mut options = SearchOptions::new();
-    if let Some(score) = min_score {
+    if let Some(score) = filters.min_score {
         options = options.with_min_score(score);
     }
-    if let Some(ref ft) = file_type {
+    if let Some(ref ft) = filters.file_type {
         let types = SearchOptions::parse_csv(ft);
         if !types.is_empty() {
             options = options.with_file_types(types);
         }
     }
-    if let Some(ref sk) = symbol_kind {
+    if let Some(ref sk) = filters.symbol_kind {
         let kinds = SearchOptions::parse_csv(sk);
         if !kinds.is_empty() {
             options = options.with_symbol_kinds(kinds);
         }
     }
+    if let Some(scope) = scope {
+        options = options.with_scope(scope);
+    }
+    options = options
+        .with_explain_scores(filters.explain_scores)
+        .with_context_lines(filters.context_lines)
+        .with_expand_to_symbol(filters.expand_to_symbol)
+        .with_exclude_conditional(filters.exclude_conditional)
+        .with_include_generated(filters.include_generated)
+        .with_exclude_comments(filters.exclude_comments)
+        .with_exclude_strings(filters.exclude_strings);
 
     let results = engine.search(query, limit, Some(options))?;
 
@@ -57,6 +87,17 @@ pub async fn search(
         tracing::debug!("Failed to flush observations: {}", e);
     }
 
+    print_results(query, &results, format);
+    Ok(())
+}
+
+/// Print search results, as plain text unless a format is given
+pub(super) fn print_results(query: &str, results: &SearchResults, format: Option<OutputFormat>) {
+    if let Some(format) = format {
+        println!("{}", format.render(results));
+        return;
+    }
+
     println!(
         "Search results for '{}' ({} ms)",
         query, results.search_time_ms
@@ -69,6 +110,10 @@ pub async fn search(
             result.file_path, result.start_line, result.end_line, result.score
         );
 
+        if let Some(ref title) = result.metadata.title {
+            println!("   {}", title);
+        }
+
         if let Some(ref name) = result.metadata.symbol_name {
             println!(
                 "   Symbol: {} ({})",
@@ -77,10 +122,45 @@ pub async fn search(
             );
         }
 
-        let snippet: String = result.content.chars().take(100).collect();
-        println!("   {}", snippet.trim());
+        if let Some(ref cfg) = result.metadata.cfg_context {
+            println!("   Cfg: {}", cfg);
+        }
+
+        if result.metadata.generated {
+            println!("   Generated file");
+        }
+
+        if result.metadata.outline {
+            println!("   Outline only: file too large to index in full");
+        }
+
+        if let Some(ref matched) = result.matched_lines {
+            println!(
+                "   Matched: lines {}-{}",
+                matched.start_line, matched.end_line
+            );
+        }
+
+        if let Some(ref explanation) = result.metadata.explanation {
+            println!("   Why: {}", explanation.summary());
+        }
+
+        match result.snippet {
+            Some(ref snippet) => {
+                if let Some(ref header) = snippet.header {
+                    println!("   In: {}", header);
+                }
+                for line in snippet.text.trim().lines() {
+                    println!("   {}", line);
+                }
+            }
+            // The title stands for the chunk
+            None if result.metadata.title.is_some() => {}
+            None => {
+                let snippet: String = result.content.chars().take(100).collect();
+                println!("   {}", snippet.trim());
+            }
+        }
         println!();
     }
-
-    Ok(())
 }
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use tracing::info;
 
 use super::common::resolve_db_path;
+use crate::http::{ProjectRegistry, ProjectsConfig};
 
 pub async fn serve(
     project: Option<PathBuf>,
@@ -14,12 +15,27 @@ pub async fn serve(
     no_update_check: bool,
     http_port: Option<u16>,
     cors_origin: Option<String>,
+    projects: Option<PathBuf>,
 ) -> Result<()> {
     // Disable update check if flag is set (thread-safe, no unsafe needed)
     if no_update_check {
         disable_update_check();
     }
 
+    if let Some(projects) = projects {
+        // Multi-project HTTP mode: projects are opened on their first request
+        let port = http_port.context("--projects requires --http-port")?;
+        let config = ProjectsConfig::load(&projects)?;
+        info!("Starting Semantiq HTTP API server");
+        info!(
+            "Serving {} projects from {:?}",
+            config.projects.len(),
+            projects
+        );
+
+        return crate::http::serve_projects(ProjectRegistry::new(config), port, cors_origin).await;
+    }
+
     let project_root = match project {
         Some(p) => p,
         None => std::env::current_dir().context("Failed to get current directory")?,
@@ -32,8 +48,10 @@ pub async fn serve(
         .context("Project root path contains invalid UTF-8")?;
     let server = SemantiqServer::new(&db_path, project_root_str)?;
 
-    // Start auto-indexer in background
+    // Start auto-indexer, query model loading and threshold recalibration in background
     server.start_auto_indexer();
+    server.start_model_preload();
+    server.start_recalibration();
 
     if let Some(port) = http_port {
         // HTTP API mode
@@ -1,12 +1,30 @@
 //! Show index statistics
 
 use anyhow::{Context, Result};
-use semantiq_index::IndexStore;
+use semantiq_index::{
+    IndexIssueKind, IndexIssueRecord, IndexStore, QuotaConfig, QuotaStats, SemantiqConfig,
+    ToolUsage, unix_now,
+};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use super::common::resolve_db_path;
 
-pub async fn stats(database: Option<PathBuf>) -> Result<()> {
+/// Number of files listed in the "Largest files" section
+const LARGEST_FILES_LIMIT: usize = 10;
+
+/// Number of directories listed with their dropped embeddings
+const EVICTED_DIRS_LIMIT: usize = 10;
+
+/// Number of indexing issues listed
+const ISSUES_LIMIT: usize = 10;
+
+pub async fn stats(
+    database: Option<PathBuf>,
+    detailed: bool,
+    usage: bool,
+    days: u32,
+) -> Result<()> {
     let cwd = std::env::current_dir().context("Failed to get current directory")?;
     let db_path = resolve_db_path(database, &cwd);
 
@@ -18,6 +36,26 @@ pub async fn stats(database: Option<PathBuf>) -> Result<()> {
     }
 
     let store = IndexStore::open(&db_path)?;
+    if usage {
+        println!("Semantiq Usage Statistics");
+        println!("=========================");
+        println!("Database: {:?}", db_path);
+        if let Ok(config) = SemantiqConfig::load(&cwd)
+            && !config.usage.enabled
+        {
+            println!("Recording is disabled (usage.enabled in semantiq.toml)");
+        }
+        print!(
+            "{}",
+            format_usage(
+                &store.get_tool_usage(days)?,
+                &store.get_query_category_usage(days)?,
+                days
+            )
+        );
+        return Ok(());
+    }
+
     let stats = store.get_stats()?;
 
     println!("Semantiq Index Statistics");
@@ -29,6 +67,56 @@ pub async fn stats(database: Option<PathBuf>) -> Result<()> {
     println!("  Symbols: {}", stats.symbol_count);
     println!("  Chunks: {}", stats.chunk_count);
     println!("  Dependencies: {}", stats.dependency_count);
+    println!("  Generation: {}", stats.generation);
+    let units = store.get_translation_units()?;
+    if !units.is_empty() {
+        println!(
+            "  Translation units: {} ({} build targets)",
+            units.len(),
+            store.get_build_targets()?.len()
+        );
+    }
+    if let Some(migration) = store.embedding_migration()? {
+        // The same model after a change of `embeddings.preprocess`
+        let models = if migration.from == migration.to {
+            migration.to.to_string()
+        } else {
+            format!("{} -> {}", migration.from, migration.to)
+        };
+        println!(
+            "  Re-embedding: {} ({:.1}%, {}/{} chunks)",
+            models,
+            migration.coverage() * 100.0,
+            migration.embedded,
+            migration.total
+        );
+    }
+
+    let quota = SemantiqConfig::load(&cwd)
+        .map(|config| config.quota)
+        .unwrap_or_default();
+    print!(
+        "{}",
+        format_quota(
+            &quota,
+            &store.get_quota_stats()?,
+            store.get_database_size()?,
+            detailed,
+            unix_now()
+        )
+    );
+
+    print!(
+        "{}",
+        format_issues(
+            &store.count_index_issues()?,
+            &store.get_index_issues(None, ISSUES_LIMIT)?
+        )
+    );
+
+    if detailed {
+        print_detailed(&store)?;
+    }
 
     // Show ML calibration info
     let observation_counts = store.get_observation_counts().unwrap_or_default();
@@ -79,6 +167,13 @@ pub async fn stats(database: Option<PathBuf>) -> Result<()> {
             "    {}: max_dist={:.3}, min_sim={:.3} ({})",
             label, cal.max_distance, cal.min_similarity, cal.confidence
         );
+        if detailed {
+            println!(
+                "      samples: {}, calibrated {}",
+                cal.sample_count,
+                format_age(cal.calibrated_at, unix_now())
+            );
+        }
     }
 
     if calibrations.is_empty() {
@@ -87,3 +182,357 @@ pub async fn stats(database: Option<PathBuf>) -> Result<()> {
 
     Ok(())
 }
+
+/// Print the `--detailed` sections: languages, embeddings, storage, largest
+/// files and directories
+fn print_detailed(store: &IndexStore) -> Result<()> {
+    let languages = store.get_language_stats()?;
+    let (embedded, total_chunks) = store.get_embedding_coverage()?;
+    let db_size = store.get_database_size()?;
+    let largest = store.get_largest_files(LARGEST_FILES_LIMIT)?;
+    let directories = store.get_directory_stats(1)?;
+    let now = unix_now();
+
+    println!();
+    println!("Languages:");
+    if languages.is_empty() {
+        println!("  (none)");
+    }
+    for lang in &languages {
+        println!(
+            "  {}: {} files, {} symbols, {} chunks ({:.1}% embedded)",
+            lang.language,
+            lang.file_count,
+            lang.symbol_count,
+            lang.chunk_count,
+            lang.embedding_coverage()
+        );
+    }
+
+    println!();
+    println!("Embeddings:");
+    let coverage = if total_chunks == 0 {
+        0.0
+    } else {
+        embedded as f32 / total_chunks as f32 * 100.0
+    };
+    println!(
+        "  Coverage: {:.1}% ({}/{} chunks)",
+        coverage, embedded, total_chunks
+    );
+    let (windowed, truncated) = store.get_token_fit_stats()?;
+    println!(
+        "  Over the token limit: {} chunks embedded in windows, {} truncated",
+        windowed, truncated
+    );
+
+    println!();
+    println!("Storage:");
+    println!("  Database size: {}", format_bytes(db_size));
+    println!("  Embedding model: {}", store.embedding_model()?);
+    println!("  Embedding format: {}", store.embedding_format().as_str());
+
+    println!();
+    println!("Largest files:");
+    if largest.is_empty() {
+        println!("  (none)");
+    }
+    for file in &largest {
+        println!(
+            "  {} ({})",
+            file.path,
+            format_bytes(file.size.max(0) as u64)
+        );
+    }
+
+    println!();
+    println!("Directories:");
+    if directories.is_empty() {
+        println!("  (none)");
+    }
+    for dir in &directories {
+        println!(
+            "  {}: {} files, {}, last indexed {}",
+            dir.directory,
+            dir.file_count,
+            format_bytes(dir.total_size.max(0) as u64),
+            format_age(dir.last_indexed_at, now)
+        );
+    }
+
+    let targets = store.get_build_targets()?;
+    if !targets.is_empty() {
+        println!();
+        println!("Build targets:");
+        for (target, files) in &targets {
+            println!("  {}: {} files", target, files);
+        }
+    }
+
+    Ok(())
+}
+
+/// The quota section: files and database size against `[quota]`, and the
+/// directories whose embeddings were dropped (listed with `--detailed`).
+/// Empty when no quota applies.
+fn format_quota(
+    quota: &QuotaConfig,
+    stats: &QuotaStats,
+    db_size: u64,
+    detailed: bool,
+    now: i64,
+) -> String {
+    let max_db_bytes = quota.max_db_bytes();
+    if quota.max_files == 0
+        && max_db_bytes.is_none()
+        && stats.skipped_files == 0
+        && stats.evicted.is_empty()
+    {
+        return String::new();
+    }
+
+    let mut output = String::from("\nQuota:\n");
+    if quota.max_files > 0 {
+        output.push_str(&format!(
+            "  Files: {} of {}",
+            stats.project_files, quota.max_files
+        ));
+        if stats.skipped_files > 0 {
+            output.push_str(&format!(
+                " ({} of lowest priority left out)",
+                stats.skipped_files
+            ));
+        }
+        output.push('\n');
+    }
+    if let Some(max_db_bytes) = max_db_bytes {
+        output.push_str(&format!(
+            "  Database: {} of {}\n",
+            format_bytes(db_size),
+            format_bytes(max_db_bytes)
+        ));
+    }
+    if !stats.evicted.is_empty() {
+        let chunks: usize = stats.evicted.iter().map(|dir| dir.chunks).sum();
+        output.push_str(&format!(
+            "  Embeddings dropped: {} directories, {} chunks (least recently searched first)\n",
+            stats.evicted.len(),
+            chunks
+        ));
+    }
+    if detailed {
+        for dir in stats.evicted.iter().take(EVICTED_DIRS_LIMIT) {
+            output.push_str(&format!(
+                "    {}: {} chunks, dropped {}, last searched {}\n",
+                dir.directory,
+                dir.chunks,
+                format_age(dir.evicted_at, now),
+                dir.last_searched
+                    .map_or_else(|| "never".to_string(), |at| format_age(at, now))
+            ));
+        }
+    }
+    output
+}
+
+/// The issues section: the problems met by the last indexing of each file
+/// by kind, and the first of them. Empty without issues.
+fn format_issues(counts: &BTreeMap<IndexIssueKind, usize>, issues: &[IndexIssueRecord]) -> String {
+    if counts.is_empty() {
+        return String::new();
+    }
+
+    let total: usize = counts.values().sum();
+    let kinds: Vec<String> = counts
+        .iter()
+        .map(|(kind, count)| kind.describe(*count))
+        .collect();
+    let mut output = format!("\nIndexing issues: {}\n", kinds.join(", "));
+    for issue in issues {
+        output.push_str(&format!(
+            "  {} [{}]: {}\n",
+            issue.path, issue.kind, issue.message
+        ));
+    }
+    if total > issues.len() {
+        output.push_str(&format!(
+            "  ... and {} more (semantiq_index_issues lists them all)\n",
+            total - issues.len()
+        ));
+    }
+    output
+}
+
+/// The `--usage` report: calls per tool, then per query category, most
+/// called first
+fn format_usage(tools: &[ToolUsage], categories: &[ToolUsage], days: u32) -> String {
+    let mut output = format!(
+        "\nTool calls over the last {} day{}:\n",
+        days,
+        if days == 1 { "" } else { "s" }
+    );
+    if tools.is_empty() {
+        output.push_str("  (none recorded)\n");
+    }
+    for usage in tools {
+        output.push_str(&format!(
+            "  {}: {} calls, {:.1}% without results, {} errors, avg {:.0} ms\n",
+            usage.tool,
+            usage.calls,
+            usage.zero_result_rate() * 100.0,
+            usage.errors,
+            usage.average_ms()
+        ));
+    }
+
+    if !categories.is_empty() {
+        output.push_str("\nQueries by category:\n");
+    }
+    for usage in categories {
+        output.push_str(&format!(
+            "  {} ({}): {} calls, {:.1}% without results\n",
+            usage.category.as_deref().unwrap_or_default(),
+            usage.tool,
+            usage.calls,
+            usage.zero_result_rate() * 100.0
+        ));
+    }
+    output
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Describe a Unix timestamp relative to `now` (e.g. "5m ago")
+fn format_age(timestamp: i64, now: i64) -> String {
+    let secs = (now - timestamp).max(0);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_index::EvictedDirectory;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 + 512 * 1024), "5.5 MB");
+    }
+
+    #[test]
+    fn test_format_issues() {
+        assert_eq!(format_issues(&BTreeMap::new(), &[]), "");
+
+        let counts = BTreeMap::from([
+            (IndexIssueKind::ParseFailure, 2),
+            (IndexIssueKind::TooLarge, 1),
+        ]);
+        let issue = IndexIssueRecord {
+            path: "src/broken.rs".to_string(),
+            kind: "parse_failure".to_string(),
+            message: "Failed to parse file".to_string(),
+            recorded_at: 0,
+        };
+        assert_eq!(
+            format_issues(&counts, &[issue]),
+            "\nIndexing issues: 2 parse failures, 1 file too large\n\
+             \x20 src/broken.rs [parse_failure]: Failed to parse file\n\
+             \x20 ... and 2 more (semantiq_index_issues lists them all)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_usage() {
+        let search = ToolUsage {
+            tool: "semantiq_search".to_string(),
+            calls: 8,
+            errors: 0,
+            zero_results: 2,
+            total_ms: 120,
+            ..Default::default()
+        };
+        let identifiers = ToolUsage {
+            category: Some("identifier".to_string()),
+            calls: 4,
+            zero_results: 1,
+            ..search.clone()
+        };
+        assert_eq!(
+            format_usage(&[search], &[identifiers], 30),
+            "\nTool calls over the last 30 days:\n\
+             \x20 semantiq_search: 8 calls, 25.0% without results, 0 errors, avg 15 ms\n\
+             \nQueries by category:\n\
+             \x20 identifier (semantiq_search): 4 calls, 25.0% without results\n"
+        );
+        assert_eq!(
+            format_usage(&[], &[], 1),
+            "\nTool calls over the last 1 day:\n  (none recorded)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_quota() {
+        let now = 10 * 86400;
+        let stats = QuotaStats {
+            project_files: 800,
+            skipped_files: 12,
+            evicted: vec![EvictedDirectory {
+                directory: "legacy/billing".to_string(),
+                evicted_at: now - 2 * 86400,
+                chunks: 300,
+                last_searched: None,
+            }],
+        };
+        let quota = QuotaConfig {
+            max_files: 800,
+            max_db_mb: 2,
+            ..QuotaConfig::default()
+        };
+        assert_eq!(
+            format_quota(&quota, &stats, 1024 * 1024, true, now),
+            "\nQuota:\n\
+             \x20 Files: 800 of 800 (12 of lowest priority left out)\n\
+             \x20 Database: 1.0 MB of 2.0 MB\n\
+             \x20 Embeddings dropped: 1 directories, 300 chunks (least recently searched first)\n\
+             \x20   legacy/billing: 300 chunks, dropped 2d ago, last searched never\n"
+        );
+        assert_eq!(
+            format_quota(
+                &QuotaConfig::default(),
+                &QuotaStats::default(),
+                0,
+                true,
+                now
+            ),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(1000, 1030), "just now");
+        assert_eq!(format_age(1000, 1000 + 300), "5m ago");
+        assert_eq!(format_age(1000, 1000 + 7200), "2h ago");
+        assert_eq!(format_age(1000, 1000 + 3 * 86400), "3d ago");
+        assert_eq!(format_age(2000, 1000), "just now");
+    }
+}
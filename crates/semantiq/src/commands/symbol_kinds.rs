@@ -0,0 +1,136 @@
+//! Print the symbol kinds of the node kinds of each language
+
+use anyhow::{Context, Result, bail};
+use semantiq_index::SemantiqConfig;
+use semantiq_parser::{Language, LanguageSupport, SymbolKindMapping, symbol_kinds};
+use serde::Serialize;
+
+/// Effective mappings of a language
+#[derive(Debug, Serialize)]
+struct LanguageSymbolKinds {
+    language: &'static str,
+    mappings: Vec<SymbolKindMapping>,
+}
+
+pub async fn symbol_kinds(language: Option<&str>, format: Option<&str>) -> Result<()> {
+    let json = match format {
+        None => false,
+        Some("json") => true,
+        Some(other) => bail!("Unknown format '{}', expected json", other),
+    };
+
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let config = SemantiqConfig::load(&cwd)?;
+    config.register_languages(&cwd)?;
+
+    let languages: Vec<Language> = match language {
+        Some(name) => {
+            vec![
+                Language::from_name(name)
+                    .with_context(|| format!("Unknown language '{}'", name))?,
+            ]
+        }
+        None => LanguageSupport::supported_languages()
+            .iter()
+            .copied()
+            .chain(
+                config
+                    .languages
+                    .iter()
+                    .filter_map(|plugin| Language::from_name(&plugin.name)),
+            )
+            .collect(),
+    };
+    let tables: Vec<LanguageSymbolKinds> = languages
+        .into_iter()
+        .map(|language| LanguageSymbolKinds {
+            language: language.name(),
+            mappings: symbol_kinds::effective(language),
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tables)?);
+    } else {
+        print!("{}", format_tables(&tables));
+    }
+    Ok(())
+}
+
+/// Plain terminal output: the node kinds of each language with their
+/// symbol kind, overrides followed by what they replace
+fn format_tables(tables: &[LanguageSymbolKinds]) -> String {
+    let mut output = String::new();
+    for (i, table) in tables.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        output.push_str(&format!("{}\n", table.language));
+        if table.mappings.is_empty() {
+            output.push_str("  (no symbols)\n");
+        }
+
+        let width = table
+            .mappings
+            .iter()
+            .map(|m| m.node_kind.len())
+            .max()
+            .unwrap_or(0);
+        for mapping in &table.mappings {
+            let kind = mapping.kind.map_or(symbol_kinds::NO_SYMBOL, |k| k.as_str());
+            let note = match (mapping.overridden, mapping.builtin) {
+                (false, _) => String::new(),
+                (true, Some(builtin)) => format!("  (overrides {})", builtin.as_str()),
+                (true, None) => "  (added)".to_string(),
+            };
+            output.push_str(&format!(
+                "  {:width$}  {}{}\n",
+                mapping.node_kind,
+                kind,
+                note,
+                width = width
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantiq_parser::SymbolKind;
+
+    #[test]
+    fn test_format_tables() {
+        let mapping = |node_kind: &str, kind, builtin, overridden| SymbolKindMapping {
+            node_kind: node_kind.to_string(),
+            kind,
+            builtin,
+            overridden,
+        };
+        let tables = [
+            LanguageSymbolKinds {
+                language: "ruby",
+                mappings: vec![
+                    mapping("class", Some(SymbolKind::Class), None, false),
+                    mapping(
+                        "constant",
+                        Some(SymbolKind::Type),
+                        Some(SymbolKind::Constant),
+                        true,
+                    ),
+                    mapping("singleton_class", None, None, true),
+                ],
+            },
+            LanguageSymbolKinds {
+                language: "mydsl",
+                mappings: Vec::new(),
+            },
+        ];
+
+        assert_eq!(
+            format_tables(&tables),
+            "ruby\n  class            class\n  constant         type  (overrides constant)\n  singleton_class  none  (added)\n\nmydsl\n  (no symbols)\n"
+        );
+    }
+}
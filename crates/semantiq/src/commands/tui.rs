@@ -0,0 +1,528 @@
+//! Interactive search in the terminal
+//!
+//! Results are searched as the query is typed, once typing pauses, and the
+//! selected result is previewed next to the list. Enter opens it in
+//! `$VISUAL` or `$EDITOR` at its line, then returns to the search.
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use semantiq_index::EXTERNAL_PREFIX;
+use semantiq_retrieval::{RetrievalEngine, SearchOptions, SearchResult, SearchScope};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use super::common::open_engine;
+
+/// Pause in typing after which the query is searched
+const SEARCH_DELAY: Duration = Duration::from_millis(150);
+
+/// How long to wait for a key before checking for a due search
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Kinds of symbols the results are restricted to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum KindFilter {
+    #[default]
+    All,
+    Functions,
+    Types,
+}
+
+impl KindFilter {
+    fn next(self) -> Self {
+        match self {
+            KindFilter::All => KindFilter::Functions,
+            KindFilter::Functions => KindFilter::Types,
+            KindFilter::Types => KindFilter::All,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            KindFilter::All => "all",
+            KindFilter::Functions => "functions",
+            KindFilter::Types => "types",
+        }
+    }
+
+    fn symbol_kinds(self) -> Option<Vec<String>> {
+        let kinds: &[&str] = match self {
+            KindFilter::All => return None,
+            KindFilter::Functions => &["function", "method"],
+            KindFilter::Types => &["class", "struct", "trait", "interface", "enum", "type"],
+        };
+        Some(kinds.iter().map(|k| k.to_string()).collect())
+    }
+}
+
+fn next_scope(scope: SearchScope) -> SearchScope {
+    match scope {
+        SearchScope::Project => SearchScope::All,
+        SearchScope::All => SearchScope::External,
+        SearchScope::External => SearchScope::Project,
+    }
+}
+
+fn scope_name(scope: SearchScope) -> &'static str {
+    match scope {
+        SearchScope::Project => "project",
+        SearchScope::External => "external",
+        SearchScope::All => "all",
+    }
+}
+
+/// What the event loop does after a key
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Open,
+    Quit,
+}
+
+/// State of the interface
+#[derive(Default)]
+struct App {
+    query: String,
+    results: Vec<SearchResult>,
+    selected: usize,
+    scope: SearchScope,
+    kinds: KindFilter,
+    expand_to_symbol: bool,
+    /// Summary of the last search, or of an error
+    status: String,
+    /// When the query or filters last changed, until they are searched
+    changed_at: Option<Instant>,
+}
+
+impl App {
+    fn new(query: String) -> Self {
+        let changed_at = (!query.is_empty()).then(Instant::now);
+        Self {
+            query,
+            status: "Type to search".to_string(),
+            changed_at,
+            ..Self::default()
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if ctrl => return Action::Quit,
+            KeyCode::Enter if !self.results.is_empty() => return Action::Open,
+            KeyCode::Up => self.select_previous(),
+            KeyCode::Char('p') if ctrl => self.select_previous(),
+            KeyCode::Down => self.select_next(),
+            KeyCode::Char('n') if ctrl => self.select_next(),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.changed();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.changed();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.changed();
+            }
+            KeyCode::F(2) => {
+                self.scope = next_scope(self.scope);
+                self.changed();
+            }
+            KeyCode::F(3) => {
+                self.kinds = self.kinds.next();
+                self.changed();
+            }
+            KeyCode::F(4) => {
+                self.expand_to_symbol = !self.expand_to_symbol;
+                self.changed();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn changed(&mut self) {
+        self.changed_at = Some(Instant::now());
+    }
+
+    fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn select_next(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+    }
+
+    fn selected_result(&self) -> Option<&SearchResult> {
+        self.results.get(self.selected)
+    }
+
+    /// Whether typing paused long enough for the query to be searched
+    fn search_due(&self) -> bool {
+        self.changed_at
+            .is_some_and(|changed| changed.elapsed() >= SEARCH_DELAY)
+    }
+
+    fn options(&self) -> SearchOptions {
+        let mut options = SearchOptions::new()
+            .with_scope(self.scope)
+            .with_expand_to_symbol(self.expand_to_symbol);
+        if let Some(kinds) = self.kinds.symbol_kinds() {
+            options = options.with_symbol_kinds(kinds);
+        }
+        options
+    }
+
+    fn search(&mut self, engine: &RetrievalEngine, limit: usize) {
+        self.changed_at = None;
+        self.selected = 0;
+        if self.query.trim().is_empty() {
+            self.results.clear();
+            self.status = "Type to search".to_string();
+            return;
+        }
+        match engine.search(&self.query, limit, Some(self.options())) {
+            Ok(results) => {
+                self.status = format!(
+                    "{} results ({} ms)",
+                    results.results.len(),
+                    results.search_time_ms
+                );
+                self.results = results.results;
+            }
+            Err(e) => {
+                self.results.clear();
+                self.status = format!("Search failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Search the index interactively, starting with `query`
+pub async fn tui(query: Option<String>, database: Option<PathBuf>, limit: usize) -> Result<()> {
+    let engine = open_engine(database)?;
+    let root = std::env::current_dir()?;
+    let mut app = App::new(query.unwrap_or_default());
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app, &engine, &root, limit);
+    ratatui::restore();
+
+    if let Err(e) = engine.flush_observations() {
+        tracing::debug!("Failed to flush observations: {}", e);
+    }
+    result
+}
+
+fn run(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    engine: &RetrievalEngine,
+    root: &Path,
+    limit: usize,
+) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+
+        if event::poll(POLL_INTERVAL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match app.handle_key(key) {
+                Action::Quit => return Ok(()),
+                Action::Open => {
+                    if let Some(result) = app.selected_result() {
+                        let result = result.clone();
+                        ratatui::restore();
+                        let opened = open_in_editor(root, &result);
+                        *terminal = ratatui::init();
+                        if let Err(e) = opened {
+                            app.status = format!("{:#}", e);
+                        }
+                    }
+                }
+                Action::None => {}
+            }
+        }
+
+        if app.search_due() {
+            app.search(engine, limit);
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [input, body, footer] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+    let [list, preview] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .areas(body);
+
+    let query = Paragraph::new(app.query.as_str())
+        .block(Block::default().borders(Borders::ALL).title(" Search "));
+    frame.render_widget(query, input);
+    frame.set_cursor_position((input.x + 1 + app.query.chars().count() as u16, input.y + 1));
+
+    let items: Vec<ListItem> = app.results.iter().map(result_item).collect();
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    let results = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Results "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(results, list, &mut state);
+
+    let (title, text) = match app.selected_result() {
+        Some(result) => (
+            format!(" {}:{} ", result.file_path, result.start_line),
+            preview_text(result),
+        ),
+        None => (" Preview ".to_string(), Text::default()),
+    };
+    let preview_pane =
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(preview_pane, preview);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let footer_text = vec![
+        Line::from(format!(
+            "{} | scope: {} | kinds: {} | expand: {}",
+            app.status,
+            scope_name(app.scope),
+            app.kinds.as_str(),
+            if app.expand_to_symbol { "on" } else { "off" }
+        )),
+        Line::styled(
+            "↑↓ select  Enter open  F2 scope  F3 kinds  F4 expand  Ctrl-U clear  Esc quit",
+            dim,
+        ),
+    ];
+    frame.render_widget(Paragraph::new(footer_text), footer);
+}
+
+/// `path:line  symbol` line of a result in the list
+fn result_item(result: &SearchResult) -> ListItem<'_> {
+    let mut spans = vec![Span::raw(format!(
+        "{}:{}",
+        result.file_path, result.start_line
+    ))];
+    if let Some(ref name) = result.metadata.symbol_name {
+        spans.push(Span::styled(
+            format!("  {}", name),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+/// Numbered lines of a result, the lines that matched in bold
+fn preview_text(result: &SearchResult) -> Text<'_> {
+    let (matched_start, matched_end) = match result.matched_lines {
+        Some(matched) => (matched.start_line, matched.end_line),
+        None => (result.start_line, result.end_line),
+    };
+    let number_style = Style::default().fg(Color::DarkGray);
+
+    let mut lines = Vec::new();
+    if let Some(ref signature) = result.metadata.signature {
+        lines.push(Line::styled(
+            signature.as_str(),
+            Style::default().fg(Color::Cyan),
+        ));
+        lines.push(Line::default());
+    }
+    for (i, line) in result.content.lines().enumerate() {
+        let number = result.start_line + i;
+        let style = if (matched_start..=matched_end).contains(&number) {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:>5} ", number), number_style),
+            Span::styled(line, style),
+        ]));
+    }
+    Text::from(lines)
+}
+
+/// Open a result in the user's editor, waiting for it to exit
+fn open_in_editor(root: &Path, result: &SearchResult) -> Result<()> {
+    if result.file_path.starts_with(EXTERNAL_PREFIX) {
+        anyhow::bail!("Sources of external dependencies cannot be opened");
+    }
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    let column = result.span.map(|span| span.start_column + 1);
+    let mut command = editor_command(
+        &editor,
+        &root.join(&result.file_path),
+        result.start_line,
+        column,
+    )?;
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+/// Command opening `path` at `line` (and 1-based `column` where the editor
+/// takes one) in `editor`, which may hold arguments (`code --wait`)
+fn editor_command(
+    editor: &str,
+    path: &Path,
+    line: usize,
+    column: Option<usize>,
+) -> Result<Command> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("$EDITOR is empty")?;
+    let mut command = Command::new(program);
+    command.args(words);
+
+    let name = Path::new(program)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    let position = match column {
+        Some(column) => format!("{}:{}:{}", path.display(), line, column),
+        None => format!("{}:{}", path.display(), line),
+    };
+    match name {
+        "code" | "code-insiders" | "codium" | "cursor" => {
+            command.arg("-g").arg(position);
+        }
+        "subl" | "zed" | "hx" | "helix" => {
+            command.arg(position);
+        }
+        _ => {
+            command.arg(format!("+{}", line)).arg(path);
+        }
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use semantiq_retrieval::SearchResultKind;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn result(file_path: &str, line: usize) -> SearchResult {
+        SearchResult::new(
+            SearchResultKind::Symbol,
+            file_path.to_string(),
+            line,
+            line + 1,
+            "fn load_settings() {\n}".to_string(),
+            0.9,
+        )
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_editor_command() {
+        let path = Path::new("/project/src/lib.rs");
+
+        let vim = editor_command("vim", path, 12, Some(5)).unwrap();
+        assert_eq!(vim.get_program(), "vim");
+        assert_eq!(args(&vim), ["+12", "/project/src/lib.rs"]);
+
+        let code = editor_command("/usr/bin/code --wait", path, 12, Some(5)).unwrap();
+        assert_eq!(args(&code), ["--wait", "-g", "/project/src/lib.rs:12:5"]);
+
+        let zed = editor_command("zed", path, 12, None).unwrap();
+        assert_eq!(args(&zed), ["/project/src/lib.rs:12"]);
+
+        assert!(editor_command("  ", path, 1, None).is_err());
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut app = App::new(String::new());
+        assert!(!app.search_due());
+
+        app.handle_key(key(KeyCode::Char('l')));
+        app.handle_key(key(KeyCode::Char('o')));
+        app.handle_key(key(KeyCode::Backspace));
+        assert_eq!(app.query, "l");
+        assert!(app.changed_at.is_some());
+
+        // Nothing to open yet
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::None);
+
+        app.results = vec![result("src/a.rs", 1), result("src/b.rs", 3)];
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(app.selected, 1);
+        app.handle_key(key(KeyCode::Up));
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::Open);
+
+        app.handle_key(key(KeyCode::F(2)));
+        app.handle_key(key(KeyCode::F(3)));
+        app.handle_key(key(KeyCode::F(4)));
+        assert_eq!(app.scope, SearchScope::All);
+        assert_eq!(app.kinds, KindFilter::Functions);
+        assert!(app.expand_to_symbol);
+
+        let ctrl_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        app.handle_key(ctrl_u);
+        assert!(app.query.is_empty());
+        assert_eq!(app.handle_key(key(KeyCode::Esc)), Action::Quit);
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = App::new("load".to_string());
+        app.results = vec![result("src/config.rs", 3)];
+        app.status = "1 results (2 ms)".to_string();
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("src/config.rs:3"));
+        assert!(screen.contains("3 fn load_settings()"));
+        assert!(screen.contains("scope: project | kinds: all | expand: off"));
+    }
+}
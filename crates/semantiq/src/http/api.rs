@@ -0,0 +1,370 @@
+//! `/api` endpoints mirroring the MCP tools
+//!
+//! Requests are the MCP tool parameters from `semantiq_mcp::tools` and
+//! responses are the JSON output of the tools, so both layers stay in sync.
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+};
+use futures_util::stream::{self, Stream};
+use semantiq_mcp::SemantiqServer;
+use semantiq_mcp::tools::{
+    ReindexOutcome, SemantiqBatchSearch, SemantiqComponentTree, SemantiqConfigUsage, SemantiqDeps,
+    SemantiqDiff, SemantiqEntrypoints, SemantiqExplain, SemantiqExplore, SemantiqFindRefs,
+    SemantiqImplementations, SemantiqIndexIssues, SemantiqOverview, SemantiqReindex,
+    SemantiqRelated, SemantiqRoutes, SemantiqSavedSearch, SemantiqSearch, SemantiqSessionSummary,
+    ToolError,
+};
+use semantiq_retrieval::{
+    BatchSearchResponse, ComponentTreeResponse, ConfigUsageResponse, Deadline,
+    DependenciesResponse, EntrypointsResponse, ExploreResponse, ImplementationsResponse,
+    IndexIssuesResponse, OverviewResponse, ReferencesResponse, RelatedFilesResponse,
+    RoutesResponse, SearchResults, SemanticDiffResponse, SessionSummary, SymbolExplanation,
+};
+use std::sync::Arc;
+use tracing::debug;
+
+use super::openapi::openapi_document;
+use super::routes::X_CACHE;
+use super::types::{ErrorResponse, IndexProgressResponse};
+
+type AppState = Arc<SemantiqServer>;
+
+type ApiResult<T> = Result<T, (StatusCode, HeaderMap, Json<ErrorResponse>)>;
+
+/// Maximum number of results per request, lower than for MCP clients
+const MAX_API_LIMIT: usize = 100;
+
+/// Routes of the API, relative to `/api`
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/events", get(events))
+        .route("/index/progress", get(index_progress))
+        .route("/search", post(search))
+        .route("/batch-search", post(batch_search))
+        .route("/saved-search", post(saved_search))
+        .route("/refs", post(refs))
+        .route("/deps", post(deps))
+        .route("/explain", post(explain))
+        .route("/implementations", post(implementations))
+        .route("/component-tree", post(component_tree))
+        .route("/related", post(related))
+        .route("/overview", post(overview))
+        .route("/explore", post(explore))
+        .route("/entrypoints", post(entrypoints))
+        .route("/routes", post(routes))
+        .route("/config-usage", post(config_usage))
+        .route("/index-issues", post(index_issues))
+        .route("/session-summary", post(session_summary))
+        .route("/diff", post(diff))
+        .route("/reindex", post(reindex))
+}
+
+/// Map a tool error to its HTTP status and error code. Rate-limited calls
+/// get a `Retry-After` header, in whole seconds.
+fn tool_error(error: ToolError) -> (StatusCode, HeaderMap, Json<ErrorResponse>) {
+    let mut headers = HeaderMap::new();
+    let (status, code) = match error {
+        ToolError::InvalidParams(_) => (StatusCode::BAD_REQUEST, "INVALID_PARAMS"),
+        ToolError::Unavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, "UNAVAILABLE"),
+        ToolError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+        ToolError::RateLimited { retry_after_ms, .. } => {
+            let seconds = retry_after_ms.div_ceil(1000).max(1);
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+            (StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED")
+        }
+    };
+    (
+        status,
+        headers,
+        Json(ErrorResponse {
+            error: error.to_string(),
+            code: code.to_string(),
+        }),
+    )
+}
+
+async fn openapi() -> Json<serde_json::Value> {
+    Json(openapi_document())
+}
+
+/// Server-sent events: an `index_generation` event with the current index
+/// generation, then one each time the index changes
+async fn events(
+    State(server): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let mut rx = server.index_generation().subscribe();
+    rx.mark_changed();
+    let events = stream::unfold(rx, |mut rx| async move {
+        rx.changed().await.ok()?;
+        let generation = *rx.borrow_and_update();
+        let event = Event::default()
+            .event("index_generation")
+            .json_data(serde_json::json!({ "index_generation": generation }));
+        Some((event, rx))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Progress of the initial indexing pass of the auto-indexer
+async fn index_progress(State(server): State<AppState>) -> Json<IndexProgressResponse> {
+    let progress = server.index_progress();
+
+    Json(IndexProgressResponse {
+        phase: progress.phase.as_str().to_string(),
+        scanned: progress.scanned,
+        total: progress.total,
+        processed: progress.processed,
+        remaining: progress.remaining(),
+        indexed: progress.indexed,
+        skipped: progress.skipped,
+        errors: progress.errors,
+        warnings: progress.warnings,
+        percent: progress.percent(),
+        current_file: progress.current_file,
+        elapsed_ms: progress.elapsed_ms,
+        eta_ms: progress.eta_ms,
+    })
+}
+
+async fn search(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqSearch>,
+) -> ApiResult<([(HeaderName, &'static str); 1], Json<SearchResults>)> {
+    debug!(query = %req.query, "API search request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    let results = server
+        .blocking(move |server| server.run_search(&req, Deadline::new()))
+        .await
+        .map_err(tool_error)?;
+    let cache_status = if results.cached { "hit" } else { "miss" };
+    Ok(([(X_CACHE, cache_status)], Json(results)))
+}
+
+async fn batch_search(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqBatchSearch>,
+) -> ApiResult<Json<BatchSearchResponse>> {
+    debug!(queries = req.queries.len(), "API batch search request");
+    for query in &mut req.queries {
+        query.limit = Some(query.effective_limit().min(MAX_API_LIMIT));
+    }
+
+    server
+        .run_batch_search(&req, Deadline::new())
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn saved_search(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqSavedSearch>,
+) -> ApiResult<Json<SearchResults>> {
+    debug!(name = %req.name, "API saved search request");
+    req.max_limit = Some(MAX_API_LIMIT);
+
+    server
+        .blocking(move |server| server.run_saved_search(&req, Deadline::new()))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn refs(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqFindRefs>,
+) -> ApiResult<Json<ReferencesResponse>> {
+    debug!(symbol = %req.symbol, "API refs request");
+    req.limit = req.limit.map(|limit| limit.min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_find_refs(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn deps(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqDeps>,
+) -> ApiResult<Json<DependenciesResponse>> {
+    debug!(file_path = %req.file_path, "API deps request");
+    server
+        .blocking(move |server| server.run_deps(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn explain(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqExplain>,
+) -> ApiResult<Json<SymbolExplanation>> {
+    debug!(symbol = %req.symbol, "API explain request");
+    server
+        .blocking(move |server| server.run_explain(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn implementations(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqImplementations>,
+) -> ApiResult<Json<ImplementationsResponse>> {
+    debug!(symbol = %req.symbol, "API implementations request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_implementations(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn component_tree(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqComponentTree>,
+) -> ApiResult<Json<ComponentTreeResponse>> {
+    debug!(component = %req.component, "API component tree request");
+    server
+        .blocking(move |server| server.run_component_tree(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn related(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqRelated>,
+) -> ApiResult<Json<RelatedFilesResponse>> {
+    debug!(file_path = %req.file_path, "API related request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_related(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn overview(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqOverview>,
+) -> ApiResult<Json<OverviewResponse>> {
+    debug!(path = ?req.path, "API overview request");
+    server
+        .blocking(move |server| server.run_overview(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn explore(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqExplore>,
+) -> ApiResult<Json<ExploreResponse>> {
+    debug!(path = ?req.path, "API explore request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_explore(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn entrypoints(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqEntrypoints>,
+) -> ApiResult<Json<EntrypointsResponse>> {
+    debug!(kind = ?req.kind, path = ?req.path, "API entrypoints request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_entrypoints(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn routes(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqRoutes>,
+) -> ApiResult<Json<RoutesResponse>> {
+    debug!(method = ?req.method, path = ?req.path, "API routes request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_routes(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn config_usage(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqConfigUsage>,
+) -> ApiResult<Json<ConfigUsageResponse>> {
+    debug!(name = %req.name, source = ?req.source, access = ?req.access, "API config usage request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_config_usage(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn index_issues(
+    State(server): State<AppState>,
+    Json(mut req): Json<SemantiqIndexIssues>,
+) -> ApiResult<Json<IndexIssuesResponse>> {
+    debug!(kind = ?req.kind, "API index issues request");
+    req.limit = Some(req.effective_limit().min(MAX_API_LIMIT));
+
+    server
+        .blocking(move |server| server.run_index_issues(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn session_summary(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqSessionSummary>,
+) -> ApiResult<Json<SessionSummary>> {
+    debug!(session_id = %req.session_id, "API session summary request");
+    server
+        .blocking(move |server| server.run_session_summary(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn diff(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqDiff>,
+) -> ApiResult<Json<SemanticDiffResponse>> {
+    debug!(from = %req.from, to = ?req.to, "API diff request");
+    server
+        .blocking(move |server| server.run_diff(&req))
+        .await
+        .map(Json)
+        .map_err(tool_error)
+}
+
+async fn reindex(
+    State(server): State<AppState>,
+    Json(req): Json<SemantiqReindex>,
+) -> ApiResult<Json<ReindexOutcome>> {
+    debug!(path = ?req.path, "API reindex request");
+    server.run_reindex(&req).await.map(Json).map_err(tool_error)
+}
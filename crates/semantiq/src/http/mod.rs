@@ -1,12 +1,22 @@
 //! HTTP API server for Semantiq demo
 //!
 //! Exposes the MCP tools via HTTP REST endpoints for the interactive demo.
+//! The `/api` endpoints take the same parameters and return the same JSON as
+//! the MCP tools, and are described by an OpenAPI document at
+//! `/api/openapi.json`. With a projects file, one process serves several
+//! indexed projects under `/api/projects/{name}`. `/healthz` and `/readyz`
+//! serve liveness and readiness probes, and the server shuts down gracefully
+//! on SIGTERM.
 
+mod api;
+mod openapi;
+mod projects;
 mod routes;
 #[cfg(test)]
 mod tests;
 mod types;
 
+pub use projects::{ProjectRegistry, ProjectsConfig, create_projects_router};
 pub use routes::create_router;
 
 use anyhow::Result;
@@ -26,14 +36,41 @@ const MAX_BODY_SIZE: usize = 1024 * 1024;
 /// Maximum concurrent requests. Prevents resource exhaustion from abuse.
 const MAX_CONCURRENT_REQUESTS: usize = 50;
 
-/// Start the HTTP API server
+/// Start the HTTP API server, until SIGTERM or Ctrl-C
 pub async fn serve_http(
     server: SemantiqServer,
     port: u16,
     cors_origin: Option<String>,
 ) -> Result<()> {
     let server = Arc::new(server);
+    serve_router(create_router(Arc::clone(&server)), port, cors_origin).await?;
+    server.shutdown().await;
+    Ok(())
+}
+
+/// Start the HTTP API server for every project of the registry, until
+/// SIGTERM or Ctrl-C
+pub async fn serve_projects(
+    registry: ProjectRegistry,
+    port: u16,
+    cors_origin: Option<String>,
+) -> Result<()> {
+    let registry = Arc::new(registry);
+    let memory_monitor = registry.spawn_memory_monitor();
+    serve_router(
+        create_projects_router(Arc::clone(&registry)),
+        port,
+        cors_origin,
+    )
+    .await?;
+    if let Some(memory_monitor) = memory_monitor {
+        memory_monitor.abort();
+    }
+    registry.shutdown().await;
+    Ok(())
+}
 
+async fn serve_router(router: Router, port: u16, cors_origin: Option<String>) -> Result<()> {
     // Build CORS layer
     let cors = if let Some(origin) = cors_origin {
         CorsLayer::new()
@@ -45,7 +82,7 @@ pub async fn serve_http(
         CorsLayer::very_permissive()
     };
 
-    let app: Router = create_router(server)
+    let app = router
         .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
         .layer(ConcurrencyLimitLayer::new(MAX_CONCURRENT_REQUESTS))
         .layer(TraceLayer::new_for_http())
@@ -55,7 +92,42 @@ pub async fn serve_http(
     info!("Starting HTTP API server on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
 
     Ok(())
 }
+
+/// Resolve on SIGTERM (sent by Docker and Kubernetes to stop a container)
+/// or Ctrl-C. The server then stops accepting connections and waits for
+/// the requests in flight.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutting down HTTP API server");
+}
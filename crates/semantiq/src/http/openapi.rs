@@ -0,0 +1,1061 @@
+//! OpenAPI 3 description of the `/api` endpoints
+//!
+//! Schemas follow the request types of `semantiq_mcp::tools` and the JSON
+//! output of the MCP tools. Keep them in sync when a tool parameter changes.
+
+use semantiq_parser::{SymbolKind, SymbolQualifiers};
+use serde_json::{Value, json};
+
+/// OpenAPI document served at `/api/openapi.json`
+pub fn openapi_document() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Semantiq API",
+            "description": "Semantic code search over an indexed project. Each endpoint mirrors an MCP tool: same parameters, same JSON output.",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/api/search": operation(
+                "search",
+                "Search code (semantiq_search)",
+                "SearchRequest",
+                "SearchResults",
+            ),
+            "/api/batch-search": operation(
+                "batchSearch",
+                "Run several searches concurrently under one time limit (semantiq_batch_search)",
+                "BatchSearchRequest",
+                "BatchSearchResponse",
+            ),
+            "/api/saved-search": operation(
+                "savedSearch",
+                "Run a saved query with its parameters filled in (semantiq_saved_search)",
+                "SavedSearchRequest",
+                "SearchResults",
+            ),
+            "/api/refs": operation(
+                "findRefs",
+                "Find definitions and usages of a symbol (semantiq_find_refs)",
+                "FindRefsRequest",
+                "ReferencesResponse",
+            ),
+            "/api/deps": operation(
+                "deps",
+                "Imports and dependents of a file, or symbols used by and using one of its symbols (semantiq_deps)",
+                "DepsRequest",
+                "DependenciesResponse",
+            ),
+            "/api/explain": operation(
+                "explain",
+                "Explain a symbol (semantiq_explain)",
+                "ExplainRequest",
+                "SymbolExplanation",
+            ),
+            "/api/implementations": operation(
+                "implementations",
+                "Types implementing a trait or interface, or extending a base type (semantiq_implementations)",
+                "ImplementationsRequest",
+                "ImplementationsResponse",
+            ),
+            "/api/component-tree": operation(
+                "componentTree",
+                "Render hierarchy of a React component (semantiq_component_tree)",
+                "ComponentTreeRequest",
+                "ComponentTreeResponse",
+            ),
+            "/api/related": operation(
+                "related",
+                "Files likely to need editing together with a file (semantiq_related)",
+                "RelatedRequest",
+                "RelatedFilesResponse",
+            ),
+            "/api/overview": operation(
+                "overview",
+                "Map of the codebase or of a directory (semantiq_overview)",
+                "OverviewRequest",
+                "OverviewResponse",
+            ),
+            "/api/explore": operation(
+                "explore",
+                "Entries of a directory with their index data, or why they are left out (semantiq_explore)",
+                "ExploreRequest",
+                "ExploreResponse",
+            ),
+            "/api/entrypoints": operation(
+                "entrypoints",
+                "Main functions, HTTP routes, CLI commands and library exports (semantiq_entrypoints)",
+                "EntrypointsRequest",
+                "EntrypointsResponse",
+            ),
+            "/api/routes": operation(
+                "routes",
+                "HTTP routes matching a method and path, with their handlers (semantiq_routes)",
+                "RoutesRequest",
+                "RoutesResponse",
+            ),
+            "/api/config-usage": operation(
+                "configUsage",
+                "Reads and writes of an environment variable or configuration key (semantiq_config_usage)",
+                "ConfigUsageRequest",
+                "ConfigUsageResponse",
+            ),
+            "/api/index-issues": operation(
+                "indexIssues",
+                "Files that failed to parse, could not be read, were too large or had their encoding guessed (semantiq_index_issues)",
+                "IndexIssuesRequest",
+                "IndexIssuesResponse",
+            ),
+            "/api/session-summary": operation(
+                "sessionSummary",
+                "Searches of a session and the files they surfaced (semantiq_session_summary)",
+                "SessionSummaryRequest",
+                "SessionSummary",
+            ),
+            "/api/diff": operation(
+                "diff",
+                "Symbol-level changes between two git revisions (semantiq_diff)",
+                "DiffRequest",
+                "SemanticDiffResponse",
+            ),
+            "/api/reindex": operation(
+                "reindex",
+                "Reindex changed files now (semantiq_reindex)",
+                "ReindexRequest",
+                "ReindexOutcome",
+            ),
+            "/api/events": events_operation(),
+            "/api/index/progress": index_progress_operation(),
+        },
+        "components": {
+            "schemas": schemas(),
+        },
+    })
+}
+
+/// A POST operation taking `request` and returning `response` as JSON
+fn operation(id: &str, summary: &str, request: &str, response: &str) -> Value {
+    json!({
+        "post": {
+            "operationId": id,
+            "summary": summary,
+            "requestBody": {
+                "required": true,
+                "content": { "application/json": { "schema": schema_ref(request) } },
+            },
+            "responses": {
+                "200": {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": schema_ref(response) } },
+                },
+                "400": error_response("Invalid parameters (code INVALID_PARAMS)"),
+                "429": error_response("Tool called too often (code RATE_LIMITED); retry after the delay in the Retry-After header"),
+                "500": error_response("Internal error (code INTERNAL_ERROR)"),
+                "503": error_response("Unavailable on this server (code UNAVAILABLE)"),
+            },
+        },
+    })
+}
+
+/// The server-sent event stream of index changes
+fn events_operation() -> Value {
+    json!({
+        "get": {
+            "operationId": "events",
+            "summary": "Stream of index changes, as server-sent events",
+            "description": "Sends an `index_generation` event with the current index generation on connection, then one each time the index changes. Results obtained at an older generation may be stale.",
+            "responses": {
+                "200": {
+                    "description": "Event stream; the data of each event is an IndexGenerationEvent",
+                    "content": { "text/event-stream": { "schema": schema_ref("IndexGenerationEvent") } },
+                },
+            },
+        },
+    })
+}
+
+/// The progress of the initial indexing pass
+fn index_progress_operation() -> Value {
+    json!({
+        "get": {
+            "operationId": "indexProgress",
+            "summary": "Progress of the initial indexing pass",
+            "responses": {
+                "200": {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": schema_ref("IndexProgressResponse") } },
+                },
+            },
+        },
+    })
+}
+
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema_ref("ErrorResponse") } },
+    })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let nullable_string = json!({ "type": "string", "nullable": true });
+    let integer = json!({ "type": "integer", "minimum": 0 });
+    let string_list = json!({ "type": "array", "items": { "type": "string" } });
+    let symbol_kinds: Vec<&str> = SymbolKind::ALL.iter().map(|k| k.as_str()).collect();
+    let related_file = json!({
+        "type": "object",
+        "properties": {
+            "file_path": string,
+            "score": { "type": "number" },
+            "reasons": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "signal": { "type": "string", "enum": ["imports", "imported_by", "shared_symbols", "similar_summary", "co_change"] },
+                        "detail": string,
+                        "score": { "type": "number" },
+                    },
+                },
+            },
+        },
+    });
+
+    json!({
+        "SearchRequest": {
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": { "type": "string", "maxLength": 500, "description": "Search text; 'tags:a,b' keeps only files tagged a and b" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 20 },
+                "min_score": { "type": "number", "minimum": 0, "maximum": 1 },
+                "file_type": { "anyOf": [string_list, string], "description": "File extensions, e.g. ['rs', 'ts'] or 'rs,ts'" },
+                "symbol_kind": { "anyOf": [{ "type": "array", "items": { "type": "string", "enum": symbol_kinds } }, string], "description": "Symbol kinds, e.g. ['function', 'struct'] or 'function,struct'" },
+                "package": { "anyOf": [string_list, string], "description": "Workspace packages, e.g. ['@acme/ui'] or 'acme-core,acme-web'" },
+                "qualifiers": { "anyOf": [{ "type": "array", "items": { "type": "string", "enum": SymbolQualifiers::NAMES } }, string], "description": "Qualifiers every symbol must have, e.g. ['public', 'async'] or 'public,generic'" },
+                "route": { "type": "string", "maxLength": 500, "description": "HTTP route, e.g. 'POST /api/users'; only the handlers of the routes matching it are returned" },
+                "scope": { "type": "string", "enum": ["project", "external", "all"], "default": "project" },
+                "explain_scores": { "type": "boolean", "default": false },
+                "context_lines": { "type": "integer", "minimum": 0, "maximum": 50, "default": 0, "description": "Lines added above and below the content of each result" },
+                "expand_to_symbol": { "type": "boolean", "default": false, "description": "Expand the content of each result to the symbol enclosing it (up to 200 lines)" },
+                "exclude_conditional": { "type": "boolean", "default": false, "description": "Leave out symbols compiled only under #[cfg], #ifdef or //go:build conditions, and their chunks" },
+                "include_generated": { "type": "boolean", "default": false, "description": "Keep generated files (linguist-generated, protobuf output, minified code) in the results" },
+                "exclude_comments": { "type": "boolean", "default": false, "description": "Leave out text matches inside comments" },
+                "exclude_strings": { "type": "boolean", "default": false, "description": "Leave out text matches inside string literals" },
+                "session_id": { "type": "string", "maxLength": 500, "description": "Search session of the conversation; results from the files and directories its recent searches surfaced are boosted" },
+            },
+        },
+        "FindRefsRequest": {
+            "type": "object",
+            "required": ["symbol"],
+            "properties": {
+                "symbol": { "type": "string", "maxLength": 500 },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 50 },
+                "kind": { "type": "string", "enum": ["definition", "usage", "all"], "default": "all" },
+                "path_prefix": { "type": "string", "description": "Only files under this relative path" },
+                "exclude_tests": { "type": "boolean", "default": false },
+                "exclude_comments": { "type": "boolean", "default": false, "description": "Leave out usages inside comments" },
+                "exclude_strings": { "type": "boolean", "default": false, "description": "Leave out usages inside string literals" },
+                "cross_language": { "type": "boolean", "default": false, "description": "Also find the references of the symbols linked to this one in other languages by napi-rs, wasm-bindgen or PyO3 bindings" },
+                "as_of": { "type": "integer", "minimum": 0, "description": "Index generation to find the references at; usages are then counted per file, with start_line 0, and cross-language links are only followed in the current index" },
+            },
+        },
+        "DepsRequest": {
+            "type": "object",
+            "required": ["file_path"],
+            "properties": {
+                "file_path": { "type": "string", "maxLength": 500 },
+                "symbol": { "type": "string", "maxLength": 500, "description": "Symbol of the file whose dependencies to analyze instead of the file's" },
+            },
+        },
+        "ExplainRequest": {
+            "type": "object",
+            "required": ["symbol"],
+            "properties": {
+                "symbol": { "type": "string", "maxLength": 500 },
+                "as_of": { "type": "integer", "minimum": 0, "description": "Index generation to explain the symbol at" },
+            },
+        },
+        "ImplementationsRequest": {
+            "type": "object",
+            "required": ["symbol"],
+            "properties": {
+                "symbol": { "type": "string", "maxLength": 500, "description": "Trait, interface or base type name" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 50 },
+            },
+        },
+        "ComponentTreeRequest": {
+            "type": "object",
+            "required": ["component"],
+            "properties": {
+                "component": { "type": "string", "maxLength": 500, "description": "React component name" },
+                "depth": { "type": "integer", "minimum": 1, "maximum": 10, "default": 3 },
+            },
+        },
+        "BatchSearchRequest": {
+            "type": "object",
+            "required": ["queries"],
+            "properties": {
+                "queries": { "type": "array", "minItems": 1, "maxItems": 10, "items": schema_ref("SearchRequest") },
+            },
+        },
+        "SavedSearchRequest": {
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string", "maxLength": 500, "description": "Name of a query saved with `semantiq query save`" },
+                "params": { "type": "object", "additionalProperties": string, "description": "Values of the {parameters} of the saved query" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "description": "Overrides the saved limit" },
+            },
+        },
+        "RelatedRequest": {
+            "type": "object",
+            "required": ["file_path"],
+            "properties": {
+                "file_path": { "type": "string", "maxLength": 500 },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 10 },
+            },
+        },
+        "OverviewRequest": {
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "maxLength": 500, "description": "Directory relative to the project root (default: the project root)" },
+            },
+        },
+        "ExploreRequest": {
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "maxLength": 500, "description": "Directory relative to the project root (default: the project root)" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 100, "description": "Maximum number of entries listed" },
+            },
+        },
+        "EntrypointsRequest": {
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "enum": ["main", "route", "command", "export"], "description": "Kind of entry points to list (default: all)" },
+                "path": { "type": "string", "maxLength": 500, "description": "Directory relative to the project root (default: the project root)" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 25, "description": "Maximum number of entry points listed per kind" },
+            },
+        },
+        "RoutesRequest": {
+            "type": "object",
+            "properties": {
+                "method": { "type": "string", "enum": ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"], "description": "HTTP method (default: any)" },
+                "path": { "type": "string", "maxLength": 500, "description": "Request path, e.g. '/api/users/42', optionally after its method (default: every route)" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 25, "description": "Maximum number of routes listed" },
+            },
+        },
+        "ConfigUsageRequest": {
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string", "maxLength": 500, "description": "Environment variable or configuration key, e.g. DATABASE_URL or db.url, or a glob such as DATABASE_*" },
+                "source": { "type": "string", "enum": ["env", "config"], "description": "Environment variables or configuration keys (default: both)" },
+                "access": { "type": "string", "enum": ["read", "write"], "description": "Reads or writes (default: both)" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 50, "description": "Maximum number of sites listed" },
+            },
+        },
+        "IndexIssuesRequest": {
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "enum": ["parse_failure", "read_error", "too_large", "encoding"], "description": "Kind of issue (default: all)" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 50, "description": "Maximum number of issues listed" },
+            },
+        },
+        "SessionSummaryRequest": {
+            "type": "object",
+            "required": ["session_id"],
+            "properties": {
+                "session_id": { "type": "string", "maxLength": 500, "description": "Id of the session, as given to /api/search" },
+            },
+        },
+        "DiffRequest": {
+            "type": "object",
+            "required": ["from"],
+            "properties": {
+                "from": { "type": "string", "maxLength": 500, "description": "Base revision (commit, branch or tag)" },
+                "to": { "type": "string", "maxLength": 500, "default": "HEAD" },
+            },
+        },
+        "ReindexRequest": {
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "File, directory or glob relative to the project root; defaults to the whole project" },
+            },
+        },
+        "SearchResult": {
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "enum": ["symbol", "text_match", "semantic_match", "reference"] },
+                "file_path": string,
+                "start_line": integer,
+                "end_line": integer,
+                "content": string,
+                "score": { "type": "number" },
+                "stale": { "type": "boolean" },
+                "snippet": schema_ref("Snippet"),
+                "span": {
+                    "type": "object",
+                    "description": "Exact location of the matched symbol, chunk or text in the file; columns are 0-based byte offsets in their line, ends are exclusive",
+                    "properties": {
+                        "start_byte": integer,
+                        "end_byte": integer,
+                        "start_column": integer,
+                        "end_column": integer,
+                    },
+                },
+                "matched_lines": {
+                    "type": "object",
+                    "description": "Lines that matched, when the content was expanded with context_lines or expand_to_symbol",
+                    "properties": { "start_line": integer, "end_line": integer },
+                },
+                "metadata": {
+                    "type": "object",
+                    "properties": {
+                        "symbol_name": nullable_string,
+                        "symbol_kind": nullable_string,
+                        "match_type": nullable_string,
+                        "context": nullable_string,
+                        "title": { "type": "string", "description": "One-line heading of the result: the signature of its symbol and the first sentence of its doc comment" },
+                        "signature": { "type": "string", "description": "Signature of the symbol, or of the symbol the chunk belongs to" },
+                        "parent": { "type": "string", "description": "Name of the symbol enclosing that symbol" },
+                        "qualifiers": schema_ref("SymbolQualifiers"),
+                        "cfg_context": { "type": "string", "description": "Conditions under which the symbol is compiled, e.g. 'test' or 'defined(_WIN32)'" },
+                        "generated": { "type": "boolean", "description": "Whether the file is generated, only with include_generated" },
+                        "outline": { "type": "boolean", "description": "Whether the file is too large to chunk, only its top-level symbols being indexed" },
+                        "explanation": { "type": "object", "description": "Score breakdown, when explain_scores is set" },
+                        "indexed_at": { "type": "integer", "description": "When the file was indexed, in seconds since the Unix epoch" },
+                        "content_hash": { "type": "string", "description": "Hash of the indexed file content" },
+                        "freshness": { "type": "string", "enum": ["fresh", "stale", "unknown"], "description": "Whether the result matches the file on disk: unknown when the file is not indexed or not on disk" },
+                    },
+                },
+            },
+        },
+        "Snippet": {
+            "type": "object",
+            "description": "Part of the content around the query terms",
+            "properties": {
+                "header": { "type": "string", "description": "Signature of the enclosing symbol, when the snippet starts below it" },
+                "text": string,
+                "start_line": integer,
+                "end_line": integer,
+                "highlights": {
+                    "type": "array",
+                    "description": "Byte ranges of the query terms in text",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "start": integer,
+                            "end": integer,
+                        },
+                    },
+                },
+                "truncated": { "type": "boolean" },
+            },
+        },
+        "BatchSearchResponse": {
+            "type": "object",
+            "properties": {
+                "error_count": integer,
+                "search_time_ms": integer,
+                "searches": {
+                    "type": "array",
+                    "description": "One entry per query, in the order of the request",
+                    "items": {
+                        "type": "object",
+                        "required": ["query"],
+                        "properties": {
+                            "query": string,
+                            "results": schema_ref("SearchResults"),
+                            "error": { "type": "string", "description": "Why the search failed; the other searches still ran" },
+                        },
+                    },
+                },
+            },
+        },
+        "SearchResults": {
+            "type": "object",
+            "properties": {
+                "query": string,
+                "results": { "type": "array", "items": schema_ref("SearchResult") },
+                "total_count": integer,
+                "search_time_ms": integer,
+                "stale_files": string_list,
+                "strategy": { "type": "object", "description": "Strategy weights chosen from the shape of the query" },
+                "cached": { "type": "boolean" },
+                "index_generation": { "type": "integer", "minimum": 0, "description": "Index generation the results were found at" },
+                "diagnostics": schema_ref("SearchDiagnostics"),
+                "file_tags": {
+                    "type": "object",
+                    "description": "Best keywords of each file with results, by path",
+                    "additionalProperties": string_list,
+                },
+                "facets": {
+                    "type": "array",
+                    "description": "Most common tags among the files with results",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "tag": string,
+                            "count": integer,
+                        },
+                    },
+                },
+            },
+        },
+        "SearchDiagnostics": {
+            "type": "object",
+            "description": "Hints on why a search returned no results, present only then",
+            "properties": {
+                "embedded_chunks": integer,
+                "total_chunks": integer,
+                "semantic_search": { "type": "boolean" },
+                "filtered_out": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "filter": { "type": "string", "enum": ["file_type", "symbol_kind"] },
+                            "value": string,
+                            "count": integer,
+                        },
+                    },
+                },
+                "near_misses": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "file_path": string,
+                            "start_line": integer,
+                            "end_line": integer,
+                            "strategy": { "type": "string", "enum": ["semantic", "symbol", "text"] },
+                            "score": { "type": "number" },
+                            "threshold": { "type": "number" },
+                        },
+                    },
+                },
+                "suggestions": string_list,
+                "hints": string_list,
+            },
+        },
+        "ReferencesResponse": {
+            "type": "object",
+            "properties": {
+                "symbol": string,
+                "total_count": integer,
+                "search_time_ms": integer,
+                "definitions": { "type": "array", "items": schema_ref("SearchResult") },
+                "usages": { "type": "array", "items": schema_ref("SearchResult") },
+                "links": {
+                    "type": "array",
+                    "description": "Symbols linked to this one in other languages, with cross_language",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": string,
+                            "framework": { "type": "string", "enum": ["napi", "wasm_bindgen", "pyo3"], "description": "Absent for links inferred from names in binding files" },
+                            "file_path": string,
+                            "line": integer,
+                        },
+                    },
+                },
+                "as_of": { "type": "integer", "description": "Index generation the references were found at, with as_of" },
+                "cached": { "type": "boolean" },
+            },
+        },
+        "DependencyInfo": {
+            "type": "object",
+            "properties": {
+                "target_path": string,
+                "import_name": nullable_string,
+                "kind": string,
+                "symbols": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "name": string, "alias": nullable_string },
+                    },
+                },
+                "package": { "type": "string", "description": "Workspace package of the imported file, or of the importing file for dependents" },
+                "scope": { "type": "string", "enum": ["intra_package", "cross_package"] },
+                "resolved_path": { "type": "string", "description": "Indexed file a C or C++ include resolves to" },
+                "via": { "type": "string", "description": "Header through which a dependent uses a C or C++ source" },
+            },
+        },
+        "DependenciesResponse": {
+            "type": "object",
+            "properties": {
+                "file_path": string,
+                "package": { "type": "string", "description": "Workspace package owning the file" },
+                "compilation": {
+                    "type": "object",
+                    "description": "How a C or C++ source of the compilation database is compiled",
+                    "properties": {
+                        "target": { "type": "string", "description": "Build target compiling the file" },
+                        "target_files": integer,
+                        "defines": string_list,
+                        "include_dirs": string_list,
+                    },
+                },
+                "imports": { "type": "array", "items": schema_ref("DependencyInfo") },
+                "imported_by": { "type": "array", "items": schema_ref("DependencyInfo") },
+                "imports_error": string,
+                "imported_by_error": string,
+                "symbol": string,
+                "uses": { "type": "array", "items": schema_ref("SymbolDependency") },
+                "used_by": { "type": "array", "items": schema_ref("SymbolDependency") },
+            },
+        },
+        "SymbolDependency": {
+            "type": "object",
+            "properties": {
+                "name": string,
+                "kind": string,
+                "file_path": string,
+                "start_line": integer,
+                "end_line": integer,
+                "parent": string,
+                "line": { "type": "integer", "description": "First line referencing the used symbol, in the file of the using one" },
+                "references": { "type": "integer", "description": "Lines referencing the used symbol" },
+            },
+        },
+        "SymbolQualifiers": {
+            "type": "object",
+            "properties": {
+                "is_public": { "type": "boolean" },
+                "is_async": { "type": "boolean" },
+                "is_static": { "type": "boolean" },
+                "is_abstract": { "type": "boolean" },
+                "generic_params": integer,
+            },
+        },
+        "SymbolExplanation": {
+            "type": "object",
+            "properties": {
+                "name": string,
+                "found": { "type": "boolean" },
+                "definitions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "file_path": string,
+                            "kind": string,
+                            "start_line": integer,
+                            "end_line": integer,
+                            "signature": nullable_string,
+                            "doc_comment": nullable_string,
+                            "qualifiers": schema_ref("SymbolQualifiers"),
+                            "cfg_context": { "type": "string", "description": "Conditions under which the definition is compiled" },
+                        },
+                    },
+                },
+                "usage_count": integer,
+                "usage_files": integer,
+                "related_symbols": string_list,
+                "as_of": { "type": "integer", "description": "Index generation the symbol was explained at, with as_of" },
+            },
+        },
+        "ImplementationsResponse": {
+            "type": "object",
+            "properties": {
+                "target": string,
+                "total_count": integer,
+                "search_time_ms": integer,
+                "implementations": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "type_name": string,
+                            "relation": { "type": "string", "enum": ["implements", "extends"] },
+                            "file_path": string,
+                            "start_line": integer,
+                            "end_line": integer,
+                            "inferred": { "type": "boolean", "description": "Matched by method set (Go) rather than declared" },
+                        },
+                    },
+                },
+            },
+        },
+        "ComponentTreeResponse": {
+            "type": "object",
+            "properties": {
+                "component": string,
+                "depth": integer,
+                "search_time_ms": integer,
+                "rendered_by": { "type": "array", "items": schema_ref("ComponentNode") },
+                "renders": { "type": "array", "items": schema_ref("ComponentNode") },
+                "truncated": { "type": "boolean", "description": "The tree was cut at its maximum size" },
+            },
+        },
+        "ComponentNode": {
+            "type": "object",
+            "properties": {
+                "name": string,
+                "file_path": string,
+                "line": integer,
+                "children": { "type": "array", "items": schema_ref("ComponentNode") },
+                "recursive": { "type": "boolean", "description": "Already an ancestor in the tree; its children are not repeated" },
+            },
+        },
+        "OverviewResponse": {
+            "type": "object",
+            "properties": {
+                "path": string,
+                "summary": {
+                    "allOf": [schema_ref("DirectorySummary")],
+                    "nullable": true,
+                    "description": "Null when no indexed file is under the directory",
+                },
+                "subdirectory_count": integer,
+                "search_time_ms": integer,
+                "subdirectories": { "type": "array", "items": schema_ref("DirectorySummary") },
+            },
+        },
+        "ExploreResponse": {
+            "type": "object",
+            "properties": {
+                "path": string,
+                "entry_count": { "type": "integer", "description": "Number of entries in the directory, before the limit was applied" },
+                "listed_at": { "type": "integer", "description": "Unix time of the listing" },
+                "search_time_ms": integer,
+                "entries": {
+                    "type": "array",
+                    "description": "Subdirectories first, then by name",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": string,
+                            "path": string,
+                            "is_dir": { "type": "boolean" },
+                            "language": { "type": "string", "description": "Language of a file; most frequent language of the indexed files under a directory" },
+                            "size": { "type": "integer", "description": "Size in bytes of a file" },
+                            "symbol_count": { "type": "integer", "description": "Symbols of an indexed file" },
+                            "indexed_file_count": { "type": "integer", "description": "Indexed files under a directory" },
+                            "indexed_at": { "type": "integer", "description": "Unix time the file, or the latest file under the directory, was indexed" },
+                            "freshness": { "type": "string", "enum": ["fresh", "stale", "unknown"], "description": "Whether the index of a file matches it on disk" },
+                            "top_symbols": string_list,
+                            "generated": { "type": "boolean" },
+                            "outline": { "type": "boolean", "description": "Indexed as an outline of its top-level symbols" },
+                            "excluded": {
+                                "type": "string",
+                                "enum": ["hidden", "excluded_directory", "in_excluded_directory", "gitignored", "unsupported_language", "too_large"],
+                                "description": "Why the indexer leaves the entry out",
+                            },
+                            "issues": string_list,
+                        },
+                    },
+                },
+            },
+        },
+        "EntrypointsResponse": {
+            "type": "object",
+            "properties": {
+                "path": string,
+                "kind": { "type": "string", "nullable": true, "description": "Kind requested, null for all" },
+                "total_count": integer,
+                "counts": { "type": "object", "additionalProperties": integer, "description": "Number of entry points found of each kind" },
+                "search_time_ms": integer,
+                "entrypoints": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "type": "string", "enum": ["main", "route", "command", "export"] },
+                            "name": { "type": "string", "description": "main, the route (GET /users), the command or the exported name" },
+                            "handler": { "type": "string", "description": "Function handling the route or command, when named" },
+                            "file_path": string,
+                            "start_line": integer,
+                            "end_line": integer,
+                        },
+                    },
+                },
+            },
+        },
+        "RoutesResponse": {
+            "type": "object",
+            "properties": {
+                "method": { "type": "string", "nullable": true, "description": "Method requested, null for any" },
+                "path": { "type": "string", "nullable": true, "description": "Path requested, null for every route" },
+                "total_count": integer,
+                "search_time_ms": integer,
+                "routes": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "method": { "type": "string", "description": "Method of the route, absent for every method" },
+                            "path": { "type": "string", "description": "Path as registered, e.g. /users/:id" },
+                            "handler": { "type": "string", "description": "Function handling the route, when named" },
+                            "file_path": string,
+                            "start_line": integer,
+                            "end_line": integer,
+                            "handler_definition": {
+                                "type": "object",
+                                "description": "Definition of the handler, when indexed",
+                                "properties": {
+                                    "file_path": string,
+                                    "kind": { "type": "string" },
+                                    "start_line": integer,
+                                    "end_line": integer,
+                                    "signature": { "type": "string" },
+                                },
+                            },
+                            "matched": { "type": "string", "enum": ["exact", "suffix"], "description": "suffix when only the end of the path matches, the route being mounted under a prefix" },
+                        },
+                    },
+                },
+            },
+        },
+        "ConfigUsageResponse": {
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "description": "Name or glob requested" },
+                "source": { "type": "string", "nullable": true, "description": "Source requested, null for both" },
+                "access": { "type": "string", "nullable": true, "description": "Access requested, null for both" },
+                "total_count": integer,
+                "read_count": integer,
+                "write_count": integer,
+                "search_time_ms": integer,
+                "usages": {
+                    "type": "array",
+                    "description": "Writes first for each name, then by file and line",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": string,
+                            "source": { "type": "string", "enum": ["env", "config"] },
+                            "access": { "type": "string", "enum": ["read", "write"] },
+                            "file_path": string,
+                            "line": integer,
+                            "symbol": { "type": "string", "description": "Innermost symbol containing the line" },
+                            "code": { "type": "string", "description": "The line, trimmed" },
+                        },
+                    },
+                },
+                "similar": string_list,
+            },
+        },
+        "IndexIssuesResponse": {
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "nullable": true, "description": "Kind requested, null for all" },
+                "total_count": integer,
+                "counts": {
+                    "type": "object",
+                    "description": "Number of issues of each kind in the index",
+                    "additionalProperties": integer,
+                },
+                "search_time_ms": integer,
+                "issues": {
+                    "type": "array",
+                    "description": "By path, then kind",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": string,
+                            "kind": { "type": "string", "enum": ["parse_failure", "read_error", "too_large", "encoding"] },
+                            "message": string,
+                            "recorded_at": { "type": "integer", "description": "Unix time the issue was met" },
+                        },
+                    },
+                },
+            },
+        },
+        "DirectorySummary": {
+            "type": "object",
+            "properties": {
+                "path": string,
+                "file_count": integer,
+                "line_count": integer,
+                "languages": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "language": string, "file_count": integer },
+                    },
+                },
+                "top_symbols": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": string,
+                            "kind": string,
+                            "file_path": string,
+                            "references": integer,
+                        },
+                    },
+                },
+                "entry_files": string_list,
+            },
+        },
+        "SessionSummary": {
+            "type": "object",
+            "properties": {
+                "session_id": string,
+                "started_at": { "type": "integer", "description": "Unix time of the first search of the session" },
+                "search_count": integer,
+                "queries": {
+                    "type": "array",
+                    "description": "Most recent searches, oldest first",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "query": string,
+                            "result_count": integer,
+                            "searched_at": { "type": "integer", "description": "Unix time" },
+                        },
+                    },
+                },
+                "files": {
+                    "type": "array",
+                    "description": "Files surfaced by the searches, most recently viewed first",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "file_path": string,
+                            "views": { "type": "integer", "minimum": 0, "description": "Searches returning results from the file" },
+                            "best_score": { "type": "number" },
+                            "lines": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": { "start_line": integer, "end_line": integer },
+                                },
+                            },
+                            "last_viewed_at": { "type": "integer", "description": "Unix time" },
+                        },
+                    },
+                },
+            },
+        },
+        "RelatedFilesResponse": {
+            "type": "object",
+            "properties": {
+                "file_path": string,
+                "total_count": integer,
+                "history_commits": { "type": "integer", "nullable": true, "description": "Commits of the file read from the git history, null when it was not available" },
+                "search_time_ms": integer,
+                "related": { "type": "array", "items": related_file },
+            },
+        },
+        "SymbolChange": {
+            "type": "object",
+            "properties": {
+                "change": { "type": "string", "enum": ["added", "removed", "modified", "signature_changed", "moved", "renamed"] },
+                "name": string,
+                "kind": string,
+                "parent": string,
+                "file_path": string,
+                "start_line": integer,
+                "end_line": integer,
+                "old_file_path": { "type": "string", "description": "Previous file of moved symbols" },
+                "old_name": { "type": "string", "description": "Previous name of renamed symbols" },
+                "old_start_line": integer,
+                "old_signature": string,
+                "signature": string,
+                "similarity": { "type": "number", "description": "Similarity of the old and new bodies of moved and renamed symbols" },
+            },
+        },
+        "SemanticDiffResponse": {
+            "type": "object",
+            "properties": {
+                "from": string,
+                "to": string,
+                "files": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": string,
+                            "old_path": { "type": "string", "description": "Path before a rename" },
+                            "status": { "type": "string", "enum": ["added", "deleted", "modified", "renamed"] },
+                            "language": { "type": "string", "nullable": true, "description": "Null when the file could not be analyzed" },
+                            "changes": { "type": "array", "items": schema_ref("SymbolChange") },
+                        },
+                    },
+                },
+                "total_changes": integer,
+                "skipped_files": integer,
+                "search_time_ms": integer,
+            },
+        },
+        "IndexGenerationEvent": {
+            "type": "object",
+            "properties": {
+                "index_generation": { "type": "integer", "minimum": 0, "description": "Bumped by every write to the index" },
+            },
+        },
+        "IndexProgressResponse": {
+            "type": "object",
+            "required": ["phase", "scanned", "total", "processed", "remaining", "indexed", "skipped", "errors", "warnings", "percent", "elapsed_ms"],
+            "properties": {
+                "phase": { "type": "string", "enum": ["idle", "scanning", "indexing", "complete"] },
+                "scanned": { "type": "integer", "minimum": 0, "description": "Files seen while walking the project, unsupported ones included" },
+                "total": { "type": "integer", "minimum": 0, "description": "Supported files checked during this pass" },
+                "processed": integer,
+                "remaining": integer,
+                "indexed": integer,
+                "skipped": { "type": "integer", "minimum": 0, "description": "Files unchanged since they were last indexed" },
+                "errors": integer,
+                "warnings": integer,
+                "percent": { "type": "number", "minimum": 0, "maximum": 100 },
+                "current_file": nullable_string,
+                "elapsed_ms": integer,
+                "eta_ms": { "type": "integer", "minimum": 0, "nullable": true },
+            },
+        },
+        "ReindexOutcome": {
+            "description": "The reindex report, or the progress of the initial pass that will pick up changed files",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["status"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["completed"] },
+                        "matched": integer,
+                        "indexed": string_list,
+                        "removed": string_list,
+                        "unchanged": integer,
+                        "deferred": integer,
+                        "errors": string_list,
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["status"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["initial_indexing"] },
+                        "phase": { "type": "string", "enum": ["idle", "scanning", "indexing", "complete"] },
+                        "scanned": integer,
+                        "total": integer,
+                        "processed": integer,
+                        "indexed": integer,
+                        "skipped": integer,
+                        "errors": integer,
+                        "warnings": integer,
+                        "current_file": nullable_string,
+                        "elapsed_ms": integer,
+                        "eta_ms": { "type": "integer", "nullable": true },
+                    },
+                },
+            ],
+        },
+        "ErrorResponse": {
+            "type": "object",
+            "required": ["error", "code"],
+            "properties": {
+                "error": string,
+                "code": { "type": "string", "enum": ["INVALID_PARAMS", "UNAVAILABLE", "INTERNAL_ERROR", "RATE_LIMITED"] },
+            },
+        },
+    })
+}
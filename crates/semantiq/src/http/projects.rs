@@ -0,0 +1,430 @@
+//! Several indexed projects served by one HTTP process
+//!
+//! Projects are declared in a TOML file and opened on their first request,
+//! each with its own index store, retrieval engine and rate limits. Their
+//! `/api` endpoints are served under `/api/projects/{name}`. The least
+//! recently used projects are closed when more than `max_loaded` are open,
+//! and one at a time while the process uses more memory than
+//! `memory_limit_mb`, checked periodically so that the memory of a closed
+//! project is released before the next one is considered.
+
+use anyhow::{Context, Result, bail};
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{Path as UrlPath, Request, State},
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::{any, get},
+};
+use semantiq_mcp::SemantiqServer;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+
+use super::api;
+use super::routes::health;
+use super::types::{ErrorResponse, ProjectInfo, ProjectsResponse};
+use crate::commands::DEFAULT_DB_NAME;
+
+/// Interval between two checks of the resident memory against
+/// `memory_limit_mb`
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Projects served by `semantiq serve --projects`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProjectsConfig {
+    /// Maximum number of projects open at the same time
+    pub max_loaded: usize,
+    /// Close projects, one per check, while the resident memory of the
+    /// process exceeds this
+    pub memory_limit_mb: Option<u64>,
+    pub projects: BTreeMap<String, ProjectConfig>,
+}
+
+impl Default for ProjectsConfig {
+    fn default() -> Self {
+        Self {
+            max_loaded: 8,
+            memory_limit_mb: None,
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectConfig {
+    /// Project root; relative paths are resolved against the projects file
+    pub root: PathBuf,
+    /// Index database (default: .semantiq.db in the project root)
+    pub database: Option<PathBuf>,
+}
+
+impl ProjectsConfig {
+    /// Load a projects file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&content, base).with_context(|| format!("Invalid projects file {:?}", path))
+    }
+
+    /// Parse a projects file, resolving relative paths against `base`
+    pub fn parse(content: &str, base: &Path) -> Result<Self> {
+        let mut config: Self = toml::from_str(content)?;
+        if config.projects.is_empty() {
+            bail!("No project declared, add a [projects.<name>] section");
+        }
+
+        for (name, project) in &mut config.projects {
+            let valid = name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+            if !valid || name.starts_with('.') {
+                bail!(
+                    "Invalid project name '{}': use letters, digits, '-', '_' and '.'",
+                    name
+                );
+            }
+            project.root = base.join(&project.root);
+            project.database = Some(match project.database.take() {
+                Some(database) => base.join(database),
+                None => project.root.join(DEFAULT_DB_NAME),
+            });
+        }
+        Ok(config)
+    }
+}
+
+/// Why a project could not be served
+#[derive(Debug)]
+pub enum ProjectError {
+    NotFound(String),
+    Unavailable(anyhow::Error),
+}
+
+impl IntoResponse for ProjectError {
+    fn into_response(self) -> Response {
+        let (status, code, error) = match self {
+            ProjectError::NotFound(name) => (
+                StatusCode::NOT_FOUND,
+                "NOT_FOUND",
+                format!("Unknown project '{}'", name),
+            ),
+            ProjectError::Unavailable(e) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "UNAVAILABLE",
+                format!("{:#}", e),
+            ),
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error,
+                code: code.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+struct LoadedProject {
+    server: Arc<SemantiqServer>,
+    last_used: Instant,
+}
+
+/// Open projects, loaded on demand and evicted least recently used first
+pub struct ProjectRegistry {
+    config: ProjectsConfig,
+    loaded: Mutex<HashMap<String, LoadedProject>>,
+    /// Held while a project is opened, so concurrent requests open it once
+    opening: tokio::sync::Mutex<()>,
+}
+
+impl ProjectRegistry {
+    pub fn new(config: ProjectsConfig) -> Self {
+        Self {
+            config,
+            loaded: Mutex::new(HashMap::new()),
+            opening: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn loaded(&self) -> std::sync::MutexGuard<'_, HashMap<String, LoadedProject>> {
+        self.loaded.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Declared projects, sorted by name
+    pub fn projects(&self) -> Vec<ProjectInfo> {
+        let loaded = self.loaded();
+        self.config
+            .projects
+            .iter()
+            .map(|(name, project)| ProjectInfo {
+                name: name.clone(),
+                root: project.root.display().to_string(),
+                loaded: loaded.contains_key(name),
+            })
+            .collect()
+    }
+
+    /// Server of a project, opening it if needed
+    pub async fn get(&self, name: &str) -> Result<Arc<SemantiqServer>, ProjectError> {
+        if let Some(server) = self.touch(name) {
+            return Ok(server);
+        }
+        let project = self
+            .config
+            .projects
+            .get(name)
+            .ok_or_else(|| ProjectError::NotFound(name.to_string()))?;
+
+        let _opening = self.opening.lock().await;
+        // Another request may have opened it while we waited
+        if let Some(server) = self.touch(name) {
+            return Ok(server);
+        }
+
+        let server = Arc::new(open_project(name, project).await?);
+        self.loaded().insert(
+            name.to_string(),
+            LoadedProject {
+                server: Arc::clone(&server),
+                last_used: Instant::now(),
+            },
+        );
+        self.evict(name);
+        Ok(server)
+    }
+
+    fn touch(&self, name: &str) -> Option<Arc<SemantiqServer>> {
+        let mut loaded = self.loaded();
+        let project = loaded.get_mut(name)?;
+        project.last_used = Instant::now();
+        Some(Arc::clone(&project.server))
+    }
+
+    /// Shut down the open projects, checkpointing their indexes
+    pub async fn shutdown(&self) {
+        let servers: Vec<Arc<SemantiqServer>> = self
+            .loaded()
+            .values()
+            .map(|project| Arc::clone(&project.server))
+            .collect();
+        for server in servers {
+            server.shutdown().await;
+        }
+    }
+
+    /// Close the least recently used projects, except `keep`, while more
+    /// than `max_loaded` are open. Requests still running keep their project
+    /// alive.
+    fn evict(&self, keep: &str) {
+        let mut loaded = self.loaded();
+        let max_loaded = self.config.max_loaded.max(1);
+
+        while loaded.len() > max_loaded {
+            let Some(oldest) = least_recently_used(&loaded, keep) else {
+                break;
+            };
+            loaded.remove(&oldest);
+            info!("Closed project '{}' ({} projects open)", oldest, max_loaded);
+        }
+    }
+
+    /// Check the resident memory every [`MEMORY_CHECK_INTERVAL`] while the
+    /// registry is alive, `None` when no `memory_limit_mb` is set
+    pub fn spawn_memory_monitor(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        self.config.memory_limit_mb?;
+        let registry = Arc::downgrade(self);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(MEMORY_CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(registry) = registry.upgrade() else {
+                    break;
+                };
+                registry.check_memory();
+            }
+        }))
+    }
+
+    /// Close the least recently used project when the resident memory
+    /// exceeds `memory_limit_mb`, returning its name. One project is closed
+    /// per check, so its memory is released before the next check, and the
+    /// most recently used project is never closed.
+    pub fn check_memory(&self) -> Option<String> {
+        let limit = self.config.memory_limit_mb?;
+        let resident = resident_memory_mb()?;
+        if resident <= limit {
+            return None;
+        }
+
+        let mut loaded = self.loaded();
+        let newest = loaded
+            .iter()
+            .max_by_key(|(_, project)| project.last_used)
+            .map(|(name, _)| name.clone())?;
+        let oldest = least_recently_used(&loaded, &newest)?;
+        loaded.remove(&oldest);
+        info!(
+            "Closed project '{}' to free memory ({} MB resident, limit {} MB)",
+            oldest, resident, limit
+        );
+        Some(oldest)
+    }
+}
+
+/// Name of the least recently used project other than `keep`
+fn least_recently_used(loaded: &HashMap<String, LoadedProject>, keep: &str) -> Option<String> {
+    loaded
+        .iter()
+        .filter(|(name, _)| name.as_str() != keep)
+        .min_by_key(|(_, project)| project.last_used)
+        .map(|(name, _)| name.clone())
+}
+
+async fn open_project(name: &str, project: &ProjectConfig) -> Result<SemantiqServer, ProjectError> {
+    let root = project.root.clone();
+    let database = project
+        .database
+        .clone()
+        .unwrap_or_else(|| root.join(DEFAULT_DB_NAME));
+    if !database.exists() {
+        return Err(ProjectError::Unavailable(anyhow::anyhow!(
+            "Project '{}' is not indexed: {:?} not found. Run 'semantiq index' first.",
+            name,
+            database
+        )));
+    }
+
+    info!("Opening project '{}' ({:?})", name, root);
+    tokio::task::spawn_blocking(move || {
+        let root = root
+            .to_str()
+            .context("Project root path contains invalid UTF-8")?;
+        SemantiqServer::without_auto_indexer(&database, root)
+    })
+    .await
+    .map_err(|e| ProjectError::Unavailable(e.into()))?
+    .map_err(|e| {
+        warn!("Failed to open project '{}': {:#}", name, e);
+        ProjectError::Unavailable(e)
+    })
+}
+
+/// Resident memory of the process, where the OS reports it
+fn resident_memory_mb() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb / 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Create the router serving every project of the registry
+pub fn create_projects_router(registry: Arc<ProjectRegistry>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/healthz", get(health))
+        // Projects are opened on their first request, which answers 503
+        // itself when the project cannot be served
+        .route("/readyz", get(health))
+        .route("/api/projects", get(list_projects))
+        .route("/api/projects/{name}/{*path}", any(project_api))
+        .with_state(registry)
+}
+
+async fn list_projects(State(registry): State<Arc<ProjectRegistry>>) -> Json<ProjectsResponse> {
+    Json(ProjectsResponse {
+        projects: registry.projects(),
+    })
+}
+
+/// Forward `/api/projects/{name}/{path}` to `/api/{path}` of the project
+async fn project_api(
+    State(registry): State<Arc<ProjectRegistry>>,
+    UrlPath((name, path)): UrlPath<(String, String)>,
+    mut request: Request<Body>,
+) -> Response {
+    debug!(project = %name, path = %path, "Project API request");
+    let server = match registry.get(&name).await {
+        Ok(server) => server,
+        Err(e) => return e.into_response(),
+    };
+
+    let uri = match request.uri().query() {
+        Some(query) => format!("/{}?{}", path, query),
+        None => format!("/{}", path),
+    };
+    match uri.parse::<Uri>() {
+        Ok(uri) => *request.uri_mut() = uri,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    }
+
+    match api::router().with_state(server).oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolves_paths() {
+        let config = ProjectsConfig::parse(
+            r#"
+max_loaded = 2
+
+[projects.backend]
+root = "repos/backend"
+
+[projects.web]
+root = "/srv/web"
+database = "indexes/web.db"
+"#,
+            Path::new("/etc/semantiq"),
+        )
+        .unwrap();
+
+        assert_eq!(config.max_loaded, 2);
+        let backend = &config.projects["backend"];
+        assert_eq!(backend.root, Path::new("/etc/semantiq/repos/backend"));
+        assert_eq!(
+            backend.database.as_deref(),
+            Some(Path::new("/etc/semantiq/repos/backend/.semantiq.db"))
+        );
+        let web = &config.projects["web"];
+        assert_eq!(web.root, Path::new("/srv/web"));
+        assert_eq!(
+            web.database.as_deref(),
+            Some(Path::new("/etc/semantiq/indexes/web.db"))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_names_and_empty_files() {
+        let base = Path::new(".");
+        assert!(ProjectsConfig::parse("[projects.\"a/b\"]\nroot = \"x\"\n", base).is_err());
+        assert!(ProjectsConfig::parse("[projects.\"..\"]\nroot = \"x\"\n", base).is_err());
+        assert!(ProjectsConfig::parse("max_loaded = 1\n", base).is_err());
+    }
+}
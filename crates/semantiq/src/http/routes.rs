@@ -3,28 +3,38 @@
 use axum::{
     Json, Router,
     extract::State,
-    http::StatusCode,
+    http::{HeaderName, StatusCode},
     routing::{get, post},
 };
 use semantiq_mcp::SemantiqServer;
-use semantiq_retrieval::SearchOptions;
+use semantiq_mcp::server::Readiness;
+use semantiq_retrieval::{
+    DependenciesResponse, FindRefsOptions, RefKind, ReferencesResponse, SearchOptions, SearchScope,
+};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, error};
 
+use super::api;
 use super::types::*;
 
 type AppState = Arc<SemantiqServer>;
 
+/// Response header telling whether search results came from the query cache
+pub(super) const X_CACHE: HeaderName = HeaderName::from_static("x-cache");
+
 /// Create the router with all API endpoints
 pub fn create_router(server: AppState) -> Router {
     Router::new()
         .route("/health", get(health))
+        .route("/healthz", get(health))
+        .route("/readyz", get(readiness))
         .route("/stats", get(stats))
         .route("/search", post(search))
         .route("/find-refs", post(find_refs))
         .route("/deps", post(deps))
         .route("/explain", post(explain))
+        .nest("/api", api::router())
         .with_state(server)
 }
 
@@ -32,24 +42,44 @@ pub fn create_router(server: AppState) -> Router {
 // Health & Stats
 // ============================================
 
-async fn health() -> Json<HealthResponse> {
+pub(super) async fn health() -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
     })
 }
 
+/// `200` once the server can answer requests in full, `503` before
+async fn readiness(State(server): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    readiness_response(server.readiness())
+}
+
+pub(super) fn readiness_response(checks: Readiness) -> (StatusCode, Json<ReadinessResponse>) {
+    let (status, text) = if checks.is_ready() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            status: text.to_string(),
+            checks,
+        }),
+    )
+}
+
 async fn stats(
     State(server): State<AppState>,
 ) -> Result<Json<StatsResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let store = server.store();
-
-    match store.get_stats() {
+    match server.engine().async_store().get_stats().await {
         Ok(stats) => Ok(Json(StatsResponse {
             indexed_files: stats.file_count,
             indexed_symbols: stats.symbol_count,
             indexed_chunks: stats.chunk_count,
             indexed_dependencies: stats.dependency_count,
+            semantic_search_ready: server.engine().embedding_model_ready(),
+            index_generation: stats.generation,
         })),
         Err(e) => {
             error!("Failed to get stats: {}", e);
@@ -71,7 +101,10 @@ async fn stats(
 async fn search(
     State(server): State<AppState>,
     Json(req): Json<SearchRequest>,
-) -> Result<Json<SearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<
+    ([(HeaderName, &'static str); 1], Json<SearchResponse>),
+    (StatusCode, Json<ErrorResponse>),
+> {
     let start = Instant::now();
 
     // Validate query
@@ -118,33 +151,57 @@ async fn search(
         }
     }
 
+    if let Some(ref sc) = req.scope {
+        let scope = SearchScope::parse(sc).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Scope must be one of: project, external, all".to_string(),
+                    code: "INVALID_SCOPE".to_string(),
+                }),
+            )
+        })?;
+        options = options.with_scope(scope);
+    }
+
+    if req.explain_scores.unwrap_or(false) {
+        options = options.with_explain_scores(true);
+    }
+
+    options = options
+        .with_exclude_comments(req.exclude_comments.unwrap_or(false))
+        .with_exclude_strings(req.exclude_strings.unwrap_or(false));
+
     debug!(query = %query, limit = %limit, "HTTP search request");
 
-    match server.engine().search(query, limit, Some(options)) {
+    let owned_query = query.to_string();
+    let found = server
+        .engine()
+        .run_blocking(move |engine| engine.search(&owned_query, limit, Some(options)))
+        .await;
+    match found {
         Ok(results) => {
             let search_time_ms = start.elapsed().as_millis() as u64;
+            server.reindex_stale_files(results.stale_files);
 
+            let cache_status = if results.cached { "hit" } else { "miss" };
+            let file_tags = results.file_tags;
             let response = SearchResponse {
                 total_count: results.total_count,
                 search_time_ms,
+                index_generation: results.index_generation,
                 results: results
                     .results
                     .into_iter()
-                    .map(|r| SearchResult {
-                        file_path: r.file_path,
-                        start_line: r.start_line as u32,
-                        end_line: r.end_line as u32,
-                        score: r.score,
-                        content: r.content,
-                        metadata: SearchMetadata {
-                            symbol_name: r.metadata.symbol_name,
-                            symbol_kind: r.metadata.symbol_kind,
-                        },
+                    .map(|r| {
+                        let tags = file_tags.get(&r.file_path).cloned().unwrap_or_default();
+                        SearchResult { tags, ..r.into() }
                     })
                     .collect(),
+                facets: results.facets,
             };
 
-            Ok(Json(response))
+            Ok(([(X_CACHE, cache_status)], Json(response)))
         }
         Err(e) => {
             error!("Search failed: {}", e);
@@ -190,47 +247,67 @@ async fn find_refs(
         ));
     }
 
-    let limit = req.limit.unwrap_or(50).min(100);
+    let limit = req.limit.unwrap_or(FindRefsOptions::DEFAULT_LIMIT).min(100);
+
+    let kind = match req.kind.as_deref() {
+        Some(k) => RefKind::parse(k).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Kind must be one of: definition, usage, all".to_string(),
+                    code: "INVALID_KIND".to_string(),
+                }),
+            )
+        })?,
+        None => RefKind::All,
+    };
+
+    if let Some(ref prefix) = req.path_prefix
+        && (prefix.contains("..") || prefix.len() > 500)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Path prefix must be a relative path without '..'".to_string(),
+                code: "INVALID_PATH".to_string(),
+            }),
+        ));
+    }
+
+    let mut options = FindRefsOptions::new()
+        .with_limit(limit)
+        .with_kind(kind)
+        .excluding_tests(req.exclude_tests.unwrap_or(false))
+        .excluding_comments(req.exclude_comments.unwrap_or(false))
+        .excluding_strings(req.exclude_strings.unwrap_or(false))
+        .following_cross_language(req.cross_language.unwrap_or(false));
+    if let Some(ref prefix) = req.path_prefix {
+        options = options.with_path_prefix(prefix);
+    }
 
-    debug!(symbol = %symbol, limit = %limit, "HTTP find_refs request");
+    debug!(symbol = %symbol, limit = %limit, kind = ?kind, "HTTP find_refs request");
 
-    match server.engine().find_references(symbol, limit) {
+    let owned_symbol = symbol.to_string();
+    let found = server
+        .engine()
+        .run_blocking(move |engine| engine.find_references(&owned_symbol, &options))
+        .await;
+    match found {
         Ok(results) => {
             let search_time_ms = start.elapsed().as_millis() as u64;
 
-            let mut definitions: Vec<Reference> = Vec::new();
-            let mut references: Vec<Reference> = Vec::new();
-
-            for r in results.results {
-                let is_definition = r.metadata.match_type.as_deref() == Some("definition");
-
-                if is_definition {
-                    definitions.push(Reference {
-                        file_path: r.file_path,
-                        line: r.start_line as u32,
-                        column: None,
-                        usage_type: "definition".to_string(),
-                        context: Some(r.content.lines().next().unwrap_or("").to_string()),
-                    });
-                } else {
-                    let usage_type = r.metadata.match_type.unwrap_or_else(|| "usage".to_string());
-                    let context = r.content.trim().to_string();
-                    references.push(Reference {
-                        file_path: r.file_path,
-                        line: r.start_line as u32,
-                        column: None,
-                        usage_type,
-                        context: Some(context),
-                    });
-                }
-            }
+            let refs = ReferencesResponse::from(results);
 
             let response = FindRefsResponse {
                 symbol: symbol.to_string(),
-                total_count: results.total_count,
+                total_count: refs.total_count,
                 search_time_ms,
-                definitions,
-                references,
+                definitions: refs
+                    .definitions
+                    .into_iter()
+                    .map(Reference::definition)
+                    .collect(),
+                references: refs.usages.into_iter().map(Reference::usage).collect(),
             };
 
             Ok(Json(response))
@@ -290,42 +367,44 @@ async fn deps(
 
     debug!(file_path = %file_path, "HTTP deps request");
 
-    let imports = match server.engine().get_dependencies(file_path) {
-        Ok(deps) => deps
-            .into_iter()
-            .map(|d| Dependency {
-                path: d.target_path,
-                symbols: d.import_name.map(|n| vec![n]),
-                kind: d.kind,
-            })
-            .collect(),
-        Err(e) => {
-            debug!("Could not get imports: {}", e);
-            vec![]
-        }
-    };
-
-    let imported_by = match server.engine().get_dependents(file_path) {
-        Ok(deps) => deps
-            .into_iter()
-            .map(|d| Dependency {
-                path: d.target_path,
-                symbols: None,
-                kind: "import".to_string(),
-            })
-            .collect(),
-        Err(e) => {
-            debug!("Could not get dependents: {}", e);
-            vec![]
-        }
-    };
+    let owned_path = file_path.to_string();
+    let (imports, dependents) = server
+        .engine()
+        .run_blocking(move |engine| {
+            Ok((
+                engine.get_dependencies(&owned_path),
+                engine.get_dependents(&owned_path),
+            ))
+        })
+        .await
+        .map_err(|e| {
+            error!("Deps failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "Failed to get dependencies".to_string(),
+                    code: "DEPS_ERROR".to_string(),
+                }),
+            )
+        })?;
+    let deps = DependenciesResponse::new(file_path, imports, dependents);
+    if let Some(ref e) = deps.imports_error {
+        debug!("Could not get imports: {}", e);
+    }
+    if let Some(ref e) = deps.imported_by_error {
+        debug!("Could not get dependents: {}", e);
+    }
 
     let search_time_ms = start.elapsed().as_millis() as u64;
 
     Ok(Json(DepsResponse {
         file_path: file_path.to_string(),
-        imports,
-        imported_by,
+        imports: deps.imports.into_iter().map(Dependency::import).collect(),
+        imported_by: deps
+            .imported_by
+            .into_iter()
+            .map(Dependency::dependent)
+            .collect(),
         search_time_ms,
     }))
 }
@@ -363,7 +442,12 @@ async fn explain(
 
     debug!(symbol = %symbol, "HTTP explain request");
 
-    match server.engine().explain_symbol(symbol) {
+    let owned_symbol = symbol.to_string();
+    let found = server
+        .engine()
+        .run_blocking(move |engine| engine.explain_symbol(&owned_symbol))
+        .await;
+    match found {
         Ok(explanation) => {
             let search_time_ms = start.elapsed().as_millis() as u64;
 
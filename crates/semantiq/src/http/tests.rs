@@ -5,12 +5,18 @@ use semantiq_mcp::SemantiqServer;
 use std::sync::Arc;
 use tower::ServiceExt;
 
-use crate::http::create_router;
 use crate::http::types::*;
+use crate::http::{ProjectRegistry, ProjectsConfig, create_projects_router, create_router};
 
 /// Create a test router with an in-memory database
 fn test_router() -> axum::Router {
+    test_router_with_config("")
+}
+
+/// Create a test router for a project with this `semantiq.toml`
+fn test_router_with_config(config: &str) -> axum::Router {
     let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("semantiq.toml"), config).unwrap();
     let db_path = dir.path().join("test.db");
     let server = SemantiqServer::new(&db_path, dir.path().to_str().unwrap()).unwrap();
     // Leak the tempdir so it stays alive for the duration of the test
@@ -49,6 +55,76 @@ async fn test_health_returns_ok() {
     assert!(!health.version.is_empty());
 }
 
+#[tokio::test]
+async fn test_healthz_returns_ok() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_readyz_waits_for_initial_index_and_model() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(Request::get("/readyz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    // The auto-indexer and the model preload are not started
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = response_body(response).await;
+    let readiness: ReadinessResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(readiness.status, "not_ready");
+    assert!(readiness.checks.index_opened);
+    assert!(!readiness.checks.initial_index_complete);
+    assert!(!readiness.checks.embedding_model_ready);
+}
+
+#[tokio::test]
+async fn test_readyz_once_indexed() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("lib.rs"), "pub fn ready() {}\n").unwrap();
+    let server =
+        SemantiqServer::new(&dir.path().join("test.db"), dir.path().to_str().unwrap()).unwrap();
+    server.start_auto_indexer();
+    server.start_model_preload();
+
+    let server = Arc::new(server);
+    let app = create_router(Arc::clone(&server));
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+    while !server.readiness().is_ready() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "Not ready: {:?}",
+            server.readiness()
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    let response = app
+        .oneshot(Request::get("/readyz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response_body(response).await;
+    let readiness: ReadinessResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(readiness.status, "ready");
+
+    server.shutdown().await;
+    assert_eq!(
+        std::fs::metadata(dir.path().join("test.db-wal"))
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
 // ============================================
 // Stats endpoint
 // ============================================
@@ -68,6 +144,37 @@ async fn test_stats_returns_ok() {
     let stats: StatsResponse = serde_json::from_slice(&body).unwrap();
     assert_eq!(stats.indexed_files, 0);
     assert_eq!(stats.indexed_symbols, 0);
+    // The embedding model loads on the first semantic search
+    assert!(!stats.semantic_search_ready);
+}
+
+// ============================================
+// Index progress endpoint
+// ============================================
+
+#[tokio::test]
+async fn test_index_progress_returns_ok() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(
+            Request::get("/api/index/progress")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let progress: IndexProgressResponse = serde_json::from_slice(&body).unwrap();
+    assert!(
+        ["idle", "scanning", "indexing", "complete"].contains(&progress.phase.as_str()),
+        "unexpected phase: {}",
+        progress.phase
+    );
+    assert_eq!(progress.remaining, progress.total - progress.processed);
 }
 
 // ============================================
@@ -139,6 +246,92 @@ async fn test_search_valid_query_empty_index() {
     assert!(search.results.is_empty());
 }
 
+#[tokio::test]
+async fn test_search_repeated_query_hits_cache() {
+    let app = test_router();
+    let request = || {
+        Request::post("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"query": "test function"}"#))
+            .unwrap()
+    };
+
+    let first = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(first.headers()["x-cache"], "miss");
+
+    let second = app.oneshot(request()).await.unwrap();
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(second.headers()["x-cache"], "hit");
+}
+
+#[tokio::test]
+async fn test_search_returns_tags_and_facets() {
+    let dir = tempfile::tempdir().unwrap();
+    let server =
+        SemantiqServer::new(&dir.path().join("test.db"), dir.path().to_str().unwrap()).unwrap();
+    let files = [
+        (
+            "auth.rs",
+            "fn check_token() {}\n",
+            [("token", 3), ("session", 2), ("check", 1)],
+        ),
+        (
+            "db.rs",
+            "fn check_query() {}\n",
+            [("query", 2), ("database", 1), ("check", 1)],
+        ),
+    ];
+    for (path, content, keywords) in files {
+        std::fs::write(dir.path().join(path), content).unwrap();
+        let keywords: Vec<semantiq_parser::Keyword> = keywords
+            .iter()
+            .map(|&(term, count)| semantiq_parser::Keyword {
+                term: term.to_string(),
+                count,
+            })
+            .collect();
+        server
+            .store()
+            .write_file_index(&semantiq_index::FileIndexData {
+                keywords: &keywords,
+                ..semantiq_index::FileIndexData::new(
+                    path,
+                    Some("rust"),
+                    content,
+                    content.len() as i64,
+                    0,
+                )
+            })
+            .unwrap();
+    }
+    let app = create_router(Arc::new(server));
+
+    let response = app
+        .oneshot(
+            Request::post("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"query": "check tags:token"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let search: SearchResponse = serde_json::from_slice(&body).unwrap();
+    assert!(!search.results.is_empty());
+    assert!(search.results.iter().all(|r| r.file_path == "auth.rs"));
+    assert_eq!(search.results[0].tags, vec!["token", "session"]);
+    // The filtered tag is not offered again as a facet
+    let facets: Vec<(&str, usize)> = search
+        .facets
+        .iter()
+        .map(|f| (f.tag.as_str(), f.count))
+        .collect();
+    assert_eq!(facets, vec![("session", 1)]);
+}
+
 #[tokio::test]
 async fn test_search_missing_body() {
     let app = test_router();
@@ -227,6 +420,50 @@ async fn test_find_refs_valid_symbol_empty_index() {
     assert!(refs.references.is_empty());
 }
 
+#[tokio::test]
+async fn test_find_refs_invalid_kind() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(
+            Request::post("/find-refs")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"symbol": "test_fn", "kind": "callers"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "INVALID_KIND");
+}
+
+#[tokio::test]
+async fn test_find_refs_path_prefix_traversal() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(
+            Request::post("/find-refs")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"symbol": "test_fn", "path_prefix": "../secrets"}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "INVALID_PATH");
+}
+
 // ============================================
 // Deps validation
 // ============================================
@@ -343,6 +580,496 @@ async fn test_explain_valid_symbol_empty_index() {
     assert_eq!(explain.kind, "unknown");
 }
 
+// ============================================
+// /api endpoints
+// ============================================
+
+fn post_json(uri: &str, body: &'static str) -> Request<Body> {
+    Request::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_api_openapi_lists_every_endpoint() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/api/openapi.json")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+
+    let paths = spec["paths"].as_object().unwrap();
+    assert_eq!(paths.len(), 20);
+    for (path, item) in paths {
+        if item["get"].is_object() {
+            let response = app
+                .clone()
+                .oneshot(Request::get(path.as_str()).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", path);
+            continue;
+        }
+
+        // Every documented schema exists and every documented route is served
+        let request = item["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"]
+            .as_str()
+            .unwrap();
+        let name = request.trim_start_matches("#/components/schemas/");
+        assert!(spec["components"]["schemas"][name].is_object(), "{}", name);
+
+        let response = app.clone().oneshot(post_json(path, "{}")).await.unwrap();
+        assert_ne!(response.status(), StatusCode::NOT_FOUND, "{}", path);
+        assert_ne!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED,
+            "{}",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_api_search_empty_query() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/search", r#"{"query": "  "}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "INVALID_PARAMS");
+    assert_eq!(error.error, "Query cannot be empty");
+}
+
+#[tokio::test]
+async fn test_api_search_returns_tool_output() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json(
+            "/api/search",
+            r#"{"query": "parse config", "limit": 5000}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-cache"], "miss");
+
+    let body = response_body(response).await;
+    let results: semantiq_retrieval::SearchResults = serde_json::from_slice(&body).unwrap();
+    assert_eq!(results.query, "parse config");
+    assert!(results.results.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_events_start_with_current_generation() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(Request::get("/api/events").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    // The stream stays open, so read its first event only
+    let frame = response.into_body().frame().await.unwrap().unwrap();
+    let event = String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap();
+    let data = event
+        .strip_prefix("event: index_generation\ndata: ")
+        .and_then(|rest| rest.strip_suffix("\n\n"))
+        .unwrap();
+    let data: serde_json::Value = serde_json::from_str(data).unwrap();
+    assert!(data["index_generation"].is_u64());
+}
+
+#[tokio::test]
+async fn test_api_search_accepts_filter_arrays() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json(
+            "/api/search",
+            r#"{"query": "parse", "file_type": ["rs", "ts"], "symbol_kind": ["function"]}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(post_json(
+            "/api/search",
+            r#"{"query": "parse", "symbol_kind": "fn"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error.error.starts_with("Invalid symbol_kind 'fn'"));
+}
+
+#[tokio::test]
+async fn test_api_saved_search_unknown_name() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json(
+            "/api/saved-search",
+            r#"{"name": "find-handlers", "params": {"verb": "get"}}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "INVALID_PARAMS");
+    assert!(
+        error
+            .error
+            .starts_with("Unknown saved query 'find-handlers'")
+    );
+}
+
+#[tokio::test]
+async fn test_api_refs_invalid_kind() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json(
+            "/api/refs",
+            r#"{"symbol": "load", "kind": "caller"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        error.error,
+        "Invalid kind 'caller': expected 'definition', 'usage' or 'all'"
+    );
+}
+
+#[tokio::test]
+async fn test_api_refs_empty_index() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json(
+            "/api/refs",
+            r#"{"symbol": "load", "kind": "definition", "exclude_tests": true}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let refs: semantiq_retrieval::ReferencesResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(refs.symbol, "load");
+    assert_eq!(refs.total_count, 0);
+}
+
+#[tokio::test]
+async fn test_api_deps_path_traversal() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/deps", r#"{"file_path": "../etc/passwd"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_api_deps_empty_index() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/deps", r#"{"file_path": "src/main.rs"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let deps: semantiq_retrieval::DependenciesResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(deps.file_path, "src/main.rs");
+    assert!(deps.imports.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_explain_empty_index() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/explain", r#"{"symbol": "MyStruct"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let explanation: semantiq_retrieval::SymbolExplanation = serde_json::from_slice(&body).unwrap();
+    assert_eq!(explanation.name, "MyStruct");
+    assert!(!explanation.found);
+}
+
+#[tokio::test]
+async fn test_api_implementations_empty_index() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/implementations", r#"{"symbol": "Shape"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::ImplementationsResponse =
+        serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.target, "Shape");
+    assert_eq!(response.total_count, 0);
+}
+
+#[tokio::test]
+async fn test_api_component_tree_empty_index() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json(
+            "/api/component-tree",
+            r#"{"component": "App", "depth": 2}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::ComponentTreeResponse =
+        serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.component, "App");
+    assert_eq!(response.depth, 2);
+    assert!(response.renders.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_related_empty_index() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/related", r#"{"file_path": "src/lib.rs"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::RelatedFilesResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.file_path, "src/lib.rs");
+    assert!(response.related.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_entrypoints_invalid_kind() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/api/entrypoints", r#"{"kind": "test"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(post_json("/api/entrypoints", r#"{"kind": "route"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::EntrypointsResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.kind.as_deref(), Some("route"));
+    assert!(response.entrypoints.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_routes_invalid_method() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/api/routes", r#"{"method": "fetch"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(post_json("/api/routes", r#"{"path": "POST /api/users"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::RoutesResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.method.as_deref(), Some("POST"));
+    assert_eq!(response.path.as_deref(), Some("/api/users"));
+    assert!(response.routes.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_batch_search() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/api/batch-search", r#"{"queries": []}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(post_json(
+            "/api/batch-search",
+            r#"{"queries": [{"query": "hello"}, {"query": "world", "file_type": ["rs"]}]}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::BatchSearchResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.error_count, 0);
+    let queries: Vec<&str> = response.searches.iter().map(|s| s.query.as_str()).collect();
+    assert_eq!(queries, ["hello", "world"]);
+    assert!(response.searches.iter().all(|s| s.results.is_some()));
+}
+
+#[tokio::test]
+async fn test_api_index_issues() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/api/index-issues", r#"{"kind": "crash"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(post_json("/api/index-issues", r#"{"kind": "too_large"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::IndexIssuesResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.kind.as_deref(), Some("too_large"));
+    assert_eq!(response.total_count, 0);
+    assert!(response.issues.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_explore() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/api/explore", r#"{"path": "docs"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(post_json("/api/explore", r#"{"limit": 500}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::ExploreResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.path, ".");
+    assert!(
+        response
+            .entries
+            .iter()
+            .any(|entry| entry.path == "semantiq.toml" && !entry.is_dir)
+    );
+}
+
+#[tokio::test]
+async fn test_api_config_usage_invalid_source() {
+    let app = test_router();
+
+    let response = app
+        .clone()
+        .oneshot(post_json(
+            "/api/config-usage",
+            r#"{"name": "PORT", "source": "file"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(post_json("/api/config-usage", r#"{"name": "DATABASE_*"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_body(response).await;
+    let response: semantiq_retrieval::ConfigUsageResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.name, "DATABASE_*");
+    assert_eq!(response.total_count, 0);
+    assert!(response.usages.is_empty());
+}
+
+#[tokio::test]
+async fn test_api_diff_rejects_option_revision() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/diff", r#"{"from": "--output=/tmp/x"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "INVALID_PARAMS");
+}
+
+#[tokio::test]
+async fn test_api_reindex_rejects_absolute_path() {
+    let app = test_router();
+
+    let response = app
+        .oneshot(post_json("/api/reindex", r#"{"path": "/etc"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.error, "Path must be relative to the project root");
+}
+
 // ============================================
 // 404 for unknown routes
 // ============================================
@@ -374,3 +1101,206 @@ async fn test_search_get_method_not_allowed() {
 
     assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
 }
+
+#[tokio::test]
+async fn test_api_rate_limited_call_returns_429() {
+    let app = test_router_with_config(
+        "[limits.tools.semantiq_explain]\nrequests_per_second = 0.1\nburst = 1\n",
+    );
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/api/explain", r#"{"symbol": "parse"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(post_json("/api/explain", r#"{"symbol": "parse"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "10");
+
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "RATE_LIMITED");
+    assert!(error.error.contains("semantiq_explain"));
+}
+
+// ============================================
+// Projects
+// ============================================
+
+/// Create a projects registry over empty indexes, one per `(name, semantiq.toml)`
+fn projects_registry(projects: &[(&str, &str)], extra: &str) -> Arc<ProjectRegistry> {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = extra.to_string();
+    for (name, semantiq_toml) in projects {
+        let root = dir.path().join(name);
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("semantiq.toml"), semantiq_toml).unwrap();
+        semantiq_index::IndexStore::open(&root.join(".semantiq.db")).unwrap();
+        config.push_str(&format!("\n[projects.{}]\nroot = \"{}\"\n", name, name));
+    }
+    let config = ProjectsConfig::parse(&config, dir.path()).unwrap();
+    std::mem::forget(dir);
+    Arc::new(ProjectRegistry::new(config))
+}
+
+/// Create a projects router over empty indexes, one per `(name, semantiq.toml)`
+fn projects_router(projects: &[(&str, &str)], extra: &str) -> axum::Router {
+    create_projects_router(projects_registry(projects, extra))
+}
+
+async fn loaded_projects(app: &axum::Router) -> Vec<String> {
+    let response = app
+        .clone()
+        .oneshot(Request::get("/api/projects").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response_body(response).await;
+    let projects: ProjectsResponse = serde_json::from_slice(&body).unwrap();
+    projects
+        .projects
+        .into_iter()
+        .filter(|p| p.loaded)
+        .map(|p| p.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_projects_are_loaded_on_first_request() {
+    let app = projects_router(&[("alpha", ""), ("beta", "")], "");
+    assert!(loaded_projects(&app).await.is_empty());
+
+    let response = app
+        .clone()
+        .oneshot(post_json(
+            "/api/projects/alpha/search",
+            r#"{"query": "parse"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response_body(response).await;
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["query"], "parse");
+
+    assert_eq!(loaded_projects(&app).await, vec!["alpha"]);
+}
+
+#[tokio::test]
+async fn test_projects_unknown_or_unindexed_project() {
+    let app = projects_router(&[("alpha", "")], "[projects.gamma]\nroot = \"gamma\"\n");
+
+    let response = app
+        .clone()
+        .oneshot(post_json(
+            "/api/projects/missing/search",
+            r#"{"query": "parse"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "NOT_FOUND");
+
+    let response = app
+        .oneshot(post_json(
+            "/api/projects/gamma/search",
+            r#"{"query": "parse"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = response_body(response).await;
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error.error.contains("semantiq index"));
+}
+
+#[tokio::test]
+async fn test_projects_keep_their_own_configuration() {
+    let app = projects_router(
+        &[
+            (
+                "alpha",
+                "[limits.tools.semantiq_explain]\nrequests_per_second = 0.1\nburst = 1\n",
+            ),
+            ("beta", ""),
+        ],
+        "",
+    );
+
+    for (project, status) in [
+        ("alpha", StatusCode::OK),
+        ("alpha", StatusCode::TOO_MANY_REQUESTS),
+        ("beta", StatusCode::OK),
+        ("beta", StatusCode::OK),
+    ] {
+        let uri = format!("/api/projects/{}/explain", project);
+        let response = app
+            .clone()
+            .oneshot(post_json(&uri, r#"{"symbol": "parse"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status, "{}", project);
+    }
+}
+
+#[tokio::test]
+async fn test_projects_evicts_least_recently_used() {
+    let app = projects_router(
+        &[("alpha", ""), ("beta", ""), ("gamma", "")],
+        "max_loaded = 2\n",
+    );
+
+    for project in ["alpha", "beta", "alpha", "gamma"] {
+        let uri = format!("/api/projects/{}/explain", project);
+        let response = app
+            .clone()
+            .oneshot(post_json(&uri, r#"{"symbol": "parse"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let mut loaded = loaded_projects(&app).await;
+    loaded.sort();
+    assert_eq!(loaded, vec!["alpha", "gamma"]);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_projects_evicted_over_memory_limit() {
+    let registry = projects_registry(
+        &[("alpha", ""), ("beta", ""), ("gamma", "")],
+        "memory_limit_mb = 0\n",
+    );
+    let app = create_projects_router(Arc::clone(&registry));
+
+    for project in ["alpha", "beta", "gamma"] {
+        let uri = format!("/api/projects/{}/explain", project);
+        let response = app
+            .clone()
+            .oneshot(post_json(&uri, r#"{"symbol": "parse"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    // Opening projects does not check the memory
+    assert_eq!(loaded_projects(&app).await.len(), 3);
+
+    // One project per check, least recently used first
+    assert_eq!(registry.check_memory().as_deref(), Some("alpha"));
+    let mut loaded = loaded_projects(&app).await;
+    loaded.sort();
+    assert_eq!(loaded, vec!["beta", "gamma"]);
+    assert_eq!(registry.check_memory().as_deref(), Some("beta"));
+
+    // The project used last is kept even though the limit cannot be met
+    assert_eq!(registry.check_memory(), None);
+    assert_eq!(loaded_projects(&app).await, vec!["gamma"]);
+}
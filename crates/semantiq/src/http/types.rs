@@ -1,5 +1,10 @@
 //! HTTP API request and response types
+//!
+//! The wire format is kept stable for API clients; responses are converted
+//! from the shared result types of `semantiq_retrieval::results`.
 
+use semantiq_mcp::server::Readiness;
+use semantiq_retrieval::{DependencyInfo, Freshness, ScoreExplanation, Snippet, Span, TagFacet};
 use serde::{Deserialize, Serialize};
 
 // ============================================
@@ -13,6 +18,14 @@ pub struct SearchRequest {
     pub min_score: Option<f32>,
     pub file_type: Option<String>,
     pub symbol_kind: Option<String>,
+    /// "project" (default), "external" or "all"
+    pub scope: Option<String>,
+    /// Attach a score breakdown to each result
+    pub explain_scores: Option<bool>,
+    /// Leave out text matches inside comments
+    pub exclude_comments: Option<bool>,
+    /// Leave out text matches inside string literals
+    pub exclude_strings: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,6 +35,18 @@ pub struct SearchResult {
     pub end_line: u32,
     pub score: f32,
     pub content: String,
+    /// The file changed since it was indexed and is being reindexed
+    #[serde(default)]
+    pub stale: bool,
+    /// Best keywords of the file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Part of `content` around the query terms, with highlighted terms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+    /// Byte offsets and 0-based columns of the match in the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
     pub metadata: SearchMetadata,
 }
 
@@ -29,6 +54,55 @@ pub struct SearchResult {
 pub struct SearchMetadata {
     pub symbol_name: Option<String>,
     pub symbol_kind: Option<String>,
+    /// One-line heading of the result: the signature of its symbol and the
+    /// first sentence of its doc comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Signature of the symbol, or of the symbol a chunk belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Name of the symbol enclosing that symbol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Why the result matched, when `explain_scores` was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+    /// When the file was indexed, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<i64>,
+    /// Hash of the indexed file content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Whether the result matches the file on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<Freshness>,
+}
+
+impl From<semantiq_retrieval::SearchResult> for SearchResult {
+    fn from(r: semantiq_retrieval::SearchResult) -> Self {
+        Self {
+            file_path: r.file_path,
+            start_line: r.start_line as u32,
+            end_line: r.end_line as u32,
+            score: r.score,
+            content: r.content,
+            stale: r.stale,
+            tags: Vec::new(),
+            snippet: r.snippet,
+            span: r.span,
+            metadata: SearchMetadata {
+                symbol_name: r.metadata.symbol_name,
+                symbol_kind: r.metadata.symbol_kind,
+                title: r.metadata.title,
+                signature: r.metadata.signature,
+                parent: r.metadata.parent,
+                explanation: r.metadata.explanation,
+                indexed_at: r.metadata.indexed_at,
+                content_hash: r.metadata.content_hash,
+                freshness: r.metadata.freshness,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,6 +110,12 @@ pub struct SearchResponse {
     pub results: Vec<SearchResult>,
     pub total_count: usize,
     pub search_time_ms: u64,
+    /// Index generation the results were found at
+    #[serde(default)]
+    pub index_generation: u64,
+    /// Most common tags among the files with results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub facets: Vec<TagFacet>,
 }
 
 // ============================================
@@ -46,17 +126,49 @@ pub struct SearchResponse {
 pub struct FindRefsRequest {
     pub symbol: String,
     pub limit: Option<usize>,
+    /// "definition", "usage" or "all" (default)
+    pub kind: Option<String>,
+    pub path_prefix: Option<String>,
+    pub exclude_tests: Option<bool>,
+    pub exclude_comments: Option<bool>,
+    pub exclude_strings: Option<bool>,
+    pub cross_language: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Reference {
     pub file_path: String,
     pub line: u32,
+    /// 1-based, like `line`
     pub column: Option<u32>,
     pub usage_type: String,
     pub context: Option<String>,
 }
 
+impl Reference {
+    /// Build a reference from a definition result, keeping its first line as context
+    pub fn definition(r: semantiq_retrieval::SearchResult) -> Self {
+        Self {
+            file_path: r.file_path,
+            line: r.start_line as u32,
+            column: r.span.map(|span| span.start_column as u32 + 1),
+            usage_type: "definition".to_string(),
+            context: Some(r.content.lines().next().unwrap_or("").to_string()),
+        }
+    }
+
+    /// Build a reference from a usage result
+    pub fn usage(r: semantiq_retrieval::SearchResult) -> Self {
+        Self {
+            file_path: r.file_path,
+            line: r.start_line as u32,
+            column: r.span.map(|span| span.start_column as u32 + 1),
+            usage_type: r.metadata.match_type.unwrap_or_else(|| "usage".to_string()),
+            context: Some(r.content.trim().to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FindRefsResponse {
     pub symbol: String,
@@ -82,6 +194,35 @@ pub struct Dependency {
     pub kind: String,
 }
 
+impl Dependency {
+    /// Build an entry of the `imports` list
+    pub fn import(d: DependencyInfo) -> Self {
+        let symbols = if d.symbols.is_empty() {
+            d.import_name.map(|n| vec![n])
+        } else {
+            Some(d.symbols.iter().map(ToString::to_string).collect())
+        };
+
+        Self {
+            path: d.target_path,
+            symbols,
+            kind: d.kind,
+        }
+    }
+
+    /// Build an entry of the `imported_by` list
+    pub fn dependent(d: DependencyInfo) -> Self {
+        let symbols =
+            (!d.symbols.is_empty()).then(|| d.symbols.iter().map(ToString::to_string).collect());
+
+        Self {
+            path: d.target_path,
+            symbols,
+            kind: "import".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DepsResponse {
     pub file_path: String,
@@ -126,6 +267,34 @@ pub struct StatsResponse {
     pub indexed_symbols: usize,
     pub indexed_chunks: usize,
     pub indexed_dependencies: usize,
+    /// The embedding model is loaded, so semantic search runs without
+    /// waiting for it
+    #[serde(default)]
+    pub semantic_search_ready: bool,
+    /// Bumped by every write to the index, see `GET /api/events`
+    #[serde(default)]
+    pub index_generation: u64,
+}
+
+// ============================================
+// Index progress
+// ============================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexProgressResponse {
+    pub phase: String,
+    pub scanned: usize,
+    pub total: usize,
+    pub processed: usize,
+    pub remaining: usize,
+    pub indexed: usize,
+    pub skipped: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub percent: f32,
+    pub current_file: Option<String>,
+    pub elapsed_ms: u64,
+    pub eta_ms: Option<u64>,
 }
 
 // ============================================
@@ -138,6 +307,31 @@ pub struct HealthResponse {
     pub version: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadinessResponse {
+    /// "ready" or "not_ready"
+    pub status: String,
+    #[serde(flatten)]
+    pub checks: Readiness,
+}
+
+// ============================================
+// Projects
+// ============================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectsResponse {
+    pub projects: Vec<ProjectInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub root: String,
+    /// The index is open in this process
+    pub loaded: bool,
+}
+
 // ============================================
 // Error
 // ============================================
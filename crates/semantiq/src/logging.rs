@@ -0,0 +1,218 @@
+//! Logging setup: stderr output plus an optional rotating log file
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+/// Format of log lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable text
+    Text,
+    /// JSON lines
+    Json,
+}
+
+/// How and where logs are written
+pub struct LogOptions {
+    pub verbose: bool,
+    /// Write logs to stderr; off while a terminal UI owns the screen
+    pub stderr: bool,
+    /// JSON lines instead of human-readable text
+    pub json: bool,
+    /// Also write logs to this file, rotated by size
+    pub file: Option<PathBuf>,
+    /// Size in bytes at which the log file is rotated
+    pub max_file_size: u64,
+    /// Number of rotated files kept next to the current one
+    pub max_files: usize,
+}
+
+/// Install the global subscriber
+pub fn init(options: &LogOptions) -> Result<()> {
+    // Filter out verbose ONNX Runtime logs
+    let filter = if options.verbose {
+        EnvFilter::new("debug")
+    } else {
+        EnvFilter::new("info,ort=warn")
+    };
+
+    let mut layers = Vec::new();
+    if options.stderr {
+        layers.push(format_layer(io::stderr, options.json, true));
+    }
+    if let Some(ref path) = options.file {
+        let writer = RotatingFile::open(path, options.max_file_size, options.max_files)?;
+        layers.push(format_layer(writer, options.json, false));
+    }
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter)
+        .init();
+    Ok(())
+}
+
+fn format_layer<W>(writer: W, json: bool, ansi: bool) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+    if json {
+        layer.json().boxed()
+    } else {
+        layer.boxed()
+    }
+}
+
+/// Log file rotated once it reaches `max_size` bytes: `semantiq.log` is
+/// renamed to `semantiq.log.1`, older files shift up to `max_files` and the
+/// oldest is deleted.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    state: Mutex<FileState>,
+}
+
+struct FileState {
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_size: u64, max_files: usize) -> Result<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory {:?}", parent))?;
+        }
+        let file =
+            open_append(path).with_context(|| format!("Failed to open log file {:?}", path))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            state: Mutex::new(FileState { file, size }),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self, state: &mut FileState) -> io::Result<()> {
+        state.file.flush()?;
+        if self.max_files == 0 {
+            state.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            state.file = open_append(&self.path)?;
+        }
+        state.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Writer for one log event, holding the file lock while it is written
+pub struct RotatingFileWriter<'a> {
+    file: &'a RotatingFile,
+    state: MutexGuard<'a, FileState>,
+}
+
+impl Write for RotatingFileWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.state.size > 0 && self.state.size + buf.len() as u64 > self.file.max_size {
+            self.file.rotate(&mut self.state)?;
+        }
+        let written = self.state.file.write(buf)?;
+        self.state.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state.file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFile {
+    type Writer = RotatingFileWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RotatingFileWriter {
+            file: self,
+            state: self.state.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_line(file: &RotatingFile, line: &str) {
+        file.make_writer().write_all(line.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_max_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("logs").join("semantiq.log");
+        let file = RotatingFile::open(&path, 20, 2).unwrap();
+
+        for i in 0..4 {
+            write_line(&file, &format!("event number {}\n", i));
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "event number 3\n");
+        assert_eq!(
+            fs::read_to_string(file.rotated_path(1)).unwrap(),
+            "event number 2\n"
+        );
+        assert_eq!(
+            fs::read_to_string(file.rotated_path(2)).unwrap(),
+            "event number 1\n"
+        );
+        assert!(!file.rotated_path(3).exists());
+    }
+
+    #[test]
+    fn test_appends_to_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("semantiq.log");
+        fs::write(&path, "previous run\n").unwrap();
+
+        let file = RotatingFile::open(&path, 1024, 3).unwrap();
+        write_line(&file, "next run\n");
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "previous run\nnext run\n"
+        );
+    }
+}
@@ -1,11 +1,20 @@
+// The OpenAPI schemas in `http::openapi` are one `json!` literal
+#![recursion_limit = "256"]
+
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use std::path::PathBuf;
-use tracing_subscriber::EnvFilter;
+use std::time::Duration;
 
 mod commands;
 mod http;
+mod logging;
+mod scip;
+mod settings;
 
+/// Options can also be set by `SEMANTIQ_*` environment variables or in the
+/// `[cli]` table of `semantiq.toml` (see [`settings`])
 #[derive(Parser)]
 #[command(name = "semantiq")]
 #[command(author, version, about = "Semantic code understanding for AI tools")]
@@ -18,6 +27,22 @@ struct Cli {
     #[arg(long, global = true)]
     json: bool,
 
+    /// Log format: text or json (default: json for 'serve', text otherwise)
+    #[arg(long, global = true, value_enum)]
+    log_format: Option<logging::LogFormat>,
+
+    /// Also write logs to this file, rotated by size
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Size in MB at which the log file is rotated
+    #[arg(long, global = true, default_value = "10")]
+    log_max_size: u64,
+
+    /// Number of rotated log files to keep
+    #[arg(long, global = true, default_value = "5")]
+    log_max_files: usize,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -59,6 +84,10 @@ enum Commands {
         /// CORS allowed origin for HTTP API (e.g., "https://example.com")
         #[arg(long)]
         cors_origin: Option<String>,
+
+        /// Serve the projects declared in this TOML file over HTTP
+        #[arg(long, conflicts_with_all = ["project", "database"])]
+        projects: Option<PathBuf>,
     },
 
     /// Index a project directory
@@ -74,6 +103,14 @@ enum Commands {
         /// Force full reindex (ignore cache)
         #[arg(short, long)]
         force: bool,
+
+        /// Seconds to wait while another process holds the index lock
+        #[arg(long, default_value = "30")]
+        lock_timeout: u64,
+
+        /// Take the index lock over even if another process holds it
+        #[arg(long)]
+        steal_lock: bool,
     },
 
     /// Show index statistics
@@ -81,6 +118,51 @@ enum Commands {
         /// Path to the database file
         #[arg(short, long)]
         database: Option<PathBuf>,
+
+        /// Show per-language, per-directory and storage breakdowns
+        #[arg(long)]
+        detailed: bool,
+
+        /// Show how often each tool and kind of query was used instead
+        #[arg(long)]
+        usage: bool,
+
+        /// Days covered by --usage, including today
+        #[arg(long, default_value = "30", requires = "usage")]
+        days: u32,
+    },
+
+    /// Convert stored embeddings to another format (shrinks the database with int8)
+    Quantize {
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Target format: int8 or float32
+        #[arg(long, default_value = "int8")]
+        format: String,
+    },
+
+    /// Compress stored chunk content with zstd (shrinks the database)
+    Compact {
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+    },
+
+    /// Export symbols and references for other tools (Sourcegraph, editors)
+    Export {
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Export format: scip
+        #[arg(long, default_value = "scip")]
+        format: String,
+
+        /// Output file (default: index.scip)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Search the index (for testing)
@@ -107,6 +189,197 @@ enum Commands {
         /// Symbol kinds to include (comma-separated, e.g., "function,class")
         #[arg(long)]
         symbol_kind: Option<String>,
+
+        /// Search scope: project, external or all (default: project)
+        #[arg(long)]
+        scope: Option<String>,
+
+        /// Output format: json or markdown (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Explain why each result matched (strategy, distance, terms, boosts)
+        #[arg(long)]
+        explain: bool,
+
+        /// Lines of context to show above and below each result (max 50)
+        #[arg(short = 'C', long, default_value = "0")]
+        context_lines: usize,
+
+        /// Show the whole symbol enclosing each result
+        #[arg(long)]
+        expand_to_symbol: bool,
+
+        /// Leave out symbols compiled only under #[cfg], #ifdef or //go:build
+        #[arg(long)]
+        exclude_conditional: bool,
+
+        /// Keep generated files (linguist-generated, protobuf output, minified code)
+        #[arg(long)]
+        include_generated: bool,
+
+        /// Leave out text matches inside comments
+        #[arg(long)]
+        exclude_comments: bool,
+
+        /// Leave out text matches inside string literals
+        #[arg(long)]
+        exclude_strings: bool,
+    },
+
+    /// Search the index interactively in the terminal
+    Tui {
+        /// Query to start with
+        query: Option<String>,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Maximum results
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Print a shell completion script (e.g. `semantiq completions zsh > _semantiq`)
+    Completions {
+        /// Shell to complete in
+        shell: Shell,
+    },
+
+    /// Benchmark retrieval quality and latency on a YAML suite of queries
+    Bench {
+        /// YAML file of queries and their expected results
+        suite: PathBuf,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Results scored per query (default: the suite's k, or 10)
+        #[arg(short = 'k', long)]
+        top_k: Option<usize>,
+
+        /// Compare against a report saved with --save-baseline
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+
+        /// Save the report of this run as a baseline (JSON)
+        #[arg(long)]
+        save_baseline: Option<PathBuf>,
+
+        /// Largest precision@k or MRR drop accepted against the baseline
+        #[arg(long, default_value = "0.02")]
+        tolerance: f64,
+    },
+
+    /// Find definitions and usages of a symbol
+    Refs {
+        /// Symbol name
+        symbol: String,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Maximum results
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+
+        /// Reference kind: definition, usage or all (default: all)
+        #[arg(long)]
+        kind: Option<String>,
+
+        /// Only include files under this relative path (e.g., "src/api")
+        #[arg(long)]
+        path_prefix: Option<String>,
+
+        /// Skip test files and directories
+        #[arg(long)]
+        exclude_tests: bool,
+
+        /// Skip usages inside comments
+        #[arg(long)]
+        exclude_comments: bool,
+
+        /// Skip usages inside string literals
+        #[arg(long)]
+        exclude_strings: bool,
+
+        /// Also find the references of the symbol in other languages, through
+        /// napi-rs, wasm-bindgen and PyO3 bindings (`get_user` and `getUser`)
+        #[arg(long)]
+        cross_language: bool,
+
+        /// Find the references at an earlier index generation (see
+        /// `semantiq stats`); usages are counted per file, without their
+        /// lines
+        #[arg(long)]
+        as_of: Option<u64>,
+
+        /// Output format: json or markdown (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Explain a symbol: definitions, documentation, usages and related symbols
+    Explain {
+        /// Symbol name
+        symbol: String,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Explain the symbol at an earlier index generation (see
+        /// `semantiq stats`)
+        #[arg(long)]
+        as_of: Option<u64>,
+
+        /// Output format: json or markdown (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Show what changed between two git revisions at the symbol level
+    Diff {
+        /// Base revision (commit, branch or tag)
+        from: String,
+
+        /// Revision compared against the base
+        #[arg(default_value = "HEAD")]
+        to: String,
+
+        /// Path to the database file (used to pick the embedding model)
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Output format: json or markdown (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Show the symbol kind of each node kind, with the overrides of semantiq.toml
+    SymbolKinds {
+        /// Show only this language (e.g., "ruby", "typescript")
+        #[arg(short, long)]
+        language: Option<String>,
+
+        /// Output format: json (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Save, list and run named searches
+    Query {
+        #[command(subcommand)]
+        command: QueryCommand,
+    },
+
+    /// Analyze the index
+    Analyze {
+        #[command(subcommand)]
+        command: AnalyzeCommand,
     },
 
     /// Calibrate semantic search thresholds using ML
@@ -129,32 +402,112 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum QueryCommand {
+    /// Save a search under a name; {placeholders} in the query or filters become parameters
+    Save {
+        /// Name of the query, e.g. "find-handlers"
+        name: String,
+
+        /// Search query, e.g. "http handler {verb}"
+        query: String,
+
+        /// Search filter saved with the query, e.g. file_type=rs (repeatable)
+        #[arg(long = "filter", value_name = "NAME=VALUE")]
+        filters: Vec<String>,
+
+        /// What the query finds, shown by `semantiq query list`
+        #[arg(long)]
+        description: Option<String>,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+    },
+
+    /// List the saved queries
+    List {
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+    },
+
+    /// Run a saved query
+    Run {
+        /// Name of the query
+        name: String,
+
+        /// Value of a parameter, e.g. verb=post (repeatable)
+        #[arg(short, long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+
+        /// Maximum results (default: the saved limit, or 20)
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Output format: json or markdown (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Delete a saved query
+    Delete {
+        /// Name of the query
+        name: String,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AnalyzeCommand {
+    /// List exported symbols that no other file references (dead-code candidates)
+    Unused {
+        /// Only report symbols in files under this path
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Also report symbols defined in test files
+        #[arg(long)]
+        include_tests: bool,
+
+        /// Maximum number of symbols listed
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+
+        /// Path to the database file
+        #[arg(short, long)]
+        database: Option<PathBuf>,
+
+        /// Output format: json or markdown (default: plain text)
+        #[arg(long)]
+        format: Option<String>,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-
-    // Setup logging - filter out verbose ONNX Runtime logs
-    let filter = if cli.verbose {
-        EnvFilter::new("debug")
-    } else {
-        EnvFilter::new("info,ort=warn")
-    };
+    let cli: Cli = settings::parse()?;
 
     // Use JSON logging by default for serve command (MCP server)
-    let use_json = cli.json || matches!(cli.command, Commands::Serve { .. });
-
-    if use_json {
-        tracing_subscriber::fmt()
-            .with_env_filter(filter)
-            .with_writer(std::io::stderr)
-            .json()
-            .init();
-    } else {
-        tracing_subscriber::fmt()
-            .with_env_filter(filter)
-            .with_writer(std::io::stderr)
-            .init();
-    }
+    let json = match cli.log_format {
+        Some(format) => format == logging::LogFormat::Json,
+        None => cli.json || matches!(cli.command, Commands::Serve { .. }),
+    };
+    logging::init(&logging::LogOptions {
+        verbose: cli.verbose,
+        stderr: !matches!(cli.command, Commands::Tui { .. }),
+        json,
+        file: cli.log_file.clone(),
+        max_file_size: cli.log_max_size.max(1) * 1024 * 1024,
+        max_files: cli.log_max_files,
+    })?;
 
     match cli.command {
         Commands::Init { path } => commands::init(&path).await,
@@ -165,13 +518,44 @@ async fn main() -> Result<()> {
             no_update_check,
             http_port,
             cors_origin,
-        } => commands::serve(project, database, no_update_check, http_port, cors_origin).await,
+            projects,
+        } => {
+            commands::serve(
+                project,
+                database,
+                no_update_check,
+                http_port,
+                cors_origin,
+                projects,
+            )
+            .await
+        }
         Commands::Index {
             path,
             database,
             force,
-        } => commands::index(&path, database, force).await,
-        Commands::Stats { database } => commands::stats(database).await,
+            lock_timeout,
+            steal_lock,
+        } => {
+            let lock = commands::LockOptions {
+                timeout: Duration::from_secs(lock_timeout),
+                steal: steal_lock,
+            };
+            commands::index(&path, database, force, lock).await
+        }
+        Commands::Stats {
+            database,
+            detailed,
+            usage,
+            days,
+        } => commands::stats(database, detailed, usage, days).await,
+        Commands::Quantize { database, format } => commands::quantize(database, &format).await,
+        Commands::Compact { database } => commands::compact(database).await,
+        Commands::Export {
+            database,
+            format,
+            output,
+        } => commands::export(database, &format, output).await,
         Commands::Search {
             query,
             database,
@@ -179,7 +563,134 @@ async fn main() -> Result<()> {
             min_score,
             file_type,
             symbol_kind,
-        } => commands::search(&query, database, limit, min_score, file_type, symbol_kind).await,
+            scope,
+            format,
+            explain,
+            context_lines,
+            expand_to_symbol,
+            exclude_conditional,
+            include_generated,
+            exclude_comments,
+            exclude_strings,
+        } => {
+            let filters = commands::SearchFilters {
+                min_score,
+                file_type,
+                symbol_kind,
+                scope,
+                explain_scores: explain,
+                context_lines,
+                expand_to_symbol,
+                exclude_conditional,
+                include_generated,
+                exclude_comments,
+                exclude_strings,
+            };
+            commands::search(&query, database, limit, filters, format.as_deref()).await
+        }
+        Commands::Tui {
+            query,
+            database,
+            limit,
+        } => commands::tui(query, database, limit).await,
+        Commands::Completions { shell } => commands::completions(&mut Cli::command(), shell),
+        Commands::Bench {
+            suite,
+            database,
+            top_k,
+            baseline,
+            save_baseline,
+            tolerance,
+        } => {
+            let options = commands::BenchOptions {
+                top_k,
+                baseline,
+                save_baseline,
+                tolerance,
+            };
+            commands::bench(&suite, database, options).await
+        }
+        Commands::Refs {
+            symbol,
+            database,
+            limit,
+            kind,
+            path_prefix,
+            exclude_tests,
+            exclude_comments,
+            exclude_strings,
+            cross_language,
+            as_of,
+            format,
+        } => {
+            let filters = commands::RefsFilters {
+                kind,
+                path_prefix,
+                exclude_tests,
+                exclude_comments,
+                exclude_strings,
+                cross_language,
+                as_of,
+            };
+            commands::refs(&symbol, database, limit, filters, format.as_deref()).await
+        }
+        Commands::Explain {
+            symbol,
+            database,
+            as_of,
+            format,
+        } => commands::explain(&symbol, database, as_of, format.as_deref()).await,
+        Commands::Diff {
+            from,
+            to,
+            database,
+            format,
+        } => commands::diff(&from, &to, database, format.as_deref()).await,
+        Commands::SymbolKinds { language, format } => {
+            commands::symbol_kinds(language.as_deref(), format.as_deref()).await
+        }
+        Commands::Query { command } => match command {
+            QueryCommand::Save {
+                name,
+                query,
+                filters,
+                description,
+                database,
+            } => {
+                commands::query_save(&name, &query, &filters, description.as_deref(), database)
+                    .await
+            }
+            QueryCommand::List { database } => commands::query_list(database).await,
+            QueryCommand::Run {
+                name,
+                params,
+                limit,
+                database,
+                format,
+            } => commands::query_run(&name, &params, limit, database, format.as_deref()).await,
+            QueryCommand::Delete { name, database } => {
+                commands::query_delete(&name, database).await
+            }
+        },
+        Commands::Analyze {
+            command:
+                AnalyzeCommand::Unused {
+                    path,
+                    include_tests,
+                    limit,
+                    database,
+                    format,
+                },
+        } => {
+            commands::analyze_unused(
+                path.as_deref(),
+                include_tests,
+                limit,
+                database,
+                format.as_deref(),
+            )
+            .await
+        }
         Commands::Calibrate {
             database,
             language,
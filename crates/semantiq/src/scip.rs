@@ -0,0 +1,481 @@
+//! SCIP index encoding, for `semantiq export --format scip`.
+//!
+//! [SCIP](https://github.com/sourcegraph/scip) indexes are protobuf messages
+//! of the `scip.proto` schema. Only the messages and fields Semantiq fills
+//! are modeled here, and they are written with the protobuf wire format
+//! directly rather than through generated code.
+//!
+//! Symbols are named with SCIP monikers: the `semantiq` scheme, the package
+//! manager and name of the workspace package owning the file (`.` outside of
+//! any package), then a namespace descriptor per directory and file of the
+//! module path, and the symbol with its parents, e.g.
+//! `semantiq cargo semantiq-index . src/store/files/IndexStore#open().`.
+
+/// `SymbolRole.Definition`: the occurrence defines the symbol
+pub const ROLE_DEFINITION: i32 = 0x1;
+
+/// `TextEncoding.UTF8` of the metadata
+const TEXT_ENCODING_UTF8: i32 = 1;
+
+/// `PositionEncoding.UTF8CodeUnitOffsetFromLineStart` of documents:
+/// characters of ranges are byte offsets in their line
+const POSITION_ENCODING_UTF8: i32 = 1;
+
+/// Scheme of the symbols exported by Semantiq
+const SCHEME: &str = "semantiq";
+
+/// A SCIP index: one document per indexed file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Index {
+    pub metadata: Metadata,
+    pub documents: Vec<Document>,
+}
+
+/// Tool that produced the index and project it covers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub tool_name: String,
+    pub tool_version: String,
+    pub arguments: Vec<String>,
+    /// URI of the project root, e.g. `file:///home/me/project`
+    pub project_root: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    /// Path relative to the project root, with `/` separators
+    pub relative_path: String,
+    pub language: String,
+    pub occurrences: Vec<Occurrence>,
+    pub symbols: Vec<SymbolInformation>,
+}
+
+/// A definition of or reference to a symbol in a document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Occurrence {
+    pub range: Range,
+    pub symbol: String,
+    /// Bitset of `ROLE_*`, 0 for a reference
+    pub symbol_roles: i32,
+    /// Range of the whole definition, e.g. a function with its body
+    pub enclosing_range: Option<Range>,
+}
+
+/// Zero-based range of a document, characters being byte offsets in the line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Range {
+    pub start_line: i32,
+    pub start_character: i32,
+    pub end_line: i32,
+    pub end_character: i32,
+}
+
+impl Range {
+    /// The packed form of `scip.proto`: three elements when the range is on
+    /// one line, four otherwise
+    fn packed(&self) -> Vec<i32> {
+        if self.start_line == self.end_line {
+            vec![self.start_line, self.start_character, self.end_character]
+        } else {
+            vec![
+                self.start_line,
+                self.start_character,
+                self.end_line,
+                self.end_character,
+            ]
+        }
+    }
+}
+
+/// What is known of a symbol defined in a document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolInformation {
+    pub symbol: String,
+    /// Markdown paragraphs: the signature, then the doc comment
+    pub documentation: Vec<String>,
+    pub relationships: Vec<Relationship>,
+    /// `SymbolInformation.Kind` (see [`symbol_kind`])
+    pub kind: i32,
+    pub display_name: String,
+    pub enclosing_symbol: Option<String>,
+}
+
+/// A type a symbol implements or extends
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Relationship {
+    pub symbol: String,
+    pub is_implementation: bool,
+}
+
+/// Workspace package a symbol belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package<'a> {
+    /// `cargo`, `npm` or `go`
+    pub manager: &'a str,
+    pub name: &'a str,
+}
+
+/// A part of a symbol moniker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Descriptor {
+    /// A directory, file or module: `name/`
+    Namespace(String),
+    /// A class, struct, enum, interface or other type: `Name#`
+    Type(String),
+    /// A function or method: `name().`
+    Method(String),
+    /// A variable, constant or other value: `name.`
+    Term(String),
+}
+
+impl Descriptor {
+    /// The descriptor of a symbol of the given Semantiq kind
+    pub fn for_symbol(kind: &str, name: &str) -> Self {
+        let name = name.to_string();
+        match kind {
+            "function" | "method" => Descriptor::Method(name),
+            "class" | "struct" | "enum" | "interface" | "trait" | "type" | "table" => {
+                Descriptor::Type(name)
+            }
+            "module" => Descriptor::Namespace(name),
+            _ => Descriptor::Term(name),
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        let (name, suffix) = match self {
+            Descriptor::Namespace(name) => (name, "/"),
+            Descriptor::Type(name) => (name, "#"),
+            Descriptor::Method(name) => (name, "()."),
+            Descriptor::Term(name) => (name, "."),
+        };
+        out.push_str(&escape_name(name));
+        out.push_str(suffix);
+    }
+}
+
+/// Descriptors of the module path of a file: its directories and its name
+/// without extension (`src/store/files.rs` gives `src/store/files/`)
+pub fn module_descriptors(path: &str) -> Vec<Descriptor> {
+    let mut parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    if let Some(file) = parts.last_mut()
+        && let Some((stem, _)) = file.rsplit_once('.')
+        && !stem.is_empty()
+    {
+        *file = stem;
+    }
+    parts
+        .into_iter()
+        .map(|part| Descriptor::Namespace(part.to_string()))
+        .collect()
+}
+
+/// The moniker of a symbol of `package` (or of no package) with the given
+/// descriptors
+pub fn symbol(package: Option<&Package<'_>>, descriptors: &[Descriptor]) -> String {
+    let (manager, name) = match package {
+        Some(package) => (package.manager, package.name),
+        None => (".", "."),
+    };
+    let mut out = format!(
+        "{} {} {} . ",
+        SCHEME,
+        escape_package_part(manager),
+        escape_package_part(name)
+    );
+    for descriptor in descriptors {
+        descriptor.write(&mut out);
+    }
+    out
+}
+
+/// `SymbolInformation.Kind` of a symbol of the given Semantiq kind
+pub fn symbol_kind(kind: &str) -> i32 {
+    match kind {
+        "class" => 7,
+        "constant" => 8,
+        "enum" => 11,
+        "function" => 17,
+        "interface" => 21,
+        "method" => 26,
+        "module" => 29,
+        "struct" => 49,
+        "trait" => 53,
+        "type" => 54,
+        "variable" => 61,
+        _ => 0,
+    }
+}
+
+/// Descriptor names other than `[A-Za-z0-9_+$-]+` are escaped in backticks
+fn escape_name(name: &str) -> String {
+    let simple = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-' | '$'));
+    if simple {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+/// Spaces of package parts are doubled, empty parts are `.`
+fn escape_package_part(part: &str) -> String {
+    if part.is_empty() {
+        ".".to_string()
+    } else {
+        part.replace(' ', "  ")
+    }
+}
+
+impl Index {
+    /// Encode the index as a `scip.Index` protobuf message
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.message(1, |w| self.metadata.encode(w));
+        for document in &self.documents {
+            writer.message(2, |w| document.encode(w));
+        }
+        writer.buf
+    }
+}
+
+impl Metadata {
+    fn encode(&self, w: &mut Writer) {
+        w.message(2, |w| {
+            w.string(1, &self.tool_name);
+            w.string(2, &self.tool_version);
+            for argument in &self.arguments {
+                w.string(3, argument);
+            }
+        });
+        w.string(3, &self.project_root);
+        w.int32(4, TEXT_ENCODING_UTF8);
+    }
+}
+
+impl Document {
+    fn encode(&self, w: &mut Writer) {
+        w.string(1, &self.relative_path);
+        for occurrence in &self.occurrences {
+            w.message(2, |w| occurrence.encode(w));
+        }
+        for symbol in &self.symbols {
+            w.message(3, |w| symbol.encode(w));
+        }
+        w.string(4, &self.language);
+        w.int32(6, POSITION_ENCODING_UTF8);
+    }
+}
+
+impl Occurrence {
+    fn encode(&self, w: &mut Writer) {
+        w.packed_int32(1, &self.range.packed());
+        w.string(2, &self.symbol);
+        w.int32(3, self.symbol_roles);
+        if let Some(ref range) = self.enclosing_range {
+            w.packed_int32(7, &range.packed());
+        }
+    }
+}
+
+impl SymbolInformation {
+    fn encode(&self, w: &mut Writer) {
+        w.string(1, &self.symbol);
+        for paragraph in &self.documentation {
+            w.string(3, paragraph);
+        }
+        for relationship in &self.relationships {
+            w.message(4, |w| {
+                w.string(1, &relationship.symbol);
+                w.bool(3, relationship.is_implementation);
+            });
+        }
+        w.int32(5, self.kind);
+        w.string(6, &self.display_name);
+        if let Some(ref enclosing) = self.enclosing_symbol {
+            w.string(8, enclosing);
+        }
+    }
+}
+
+/// Protobuf wire format writer. Fields with default values are skipped, as
+/// proto3 encoders do.
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    const VARINT: u64 = 0;
+    const LENGTH_DELIMITED: u64 = 2;
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u64) {
+        self.varint(((field as u64) << 3) | wire_type);
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.key(field, Self::LENGTH_DELIMITED);
+        self.varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+    }
+
+    fn int32(&mut self, field: u32, value: i32) {
+        if value != 0 {
+            self.key(field, Self::VARINT);
+            // Negative values are sign-extended to 64 bits
+            self.varint(value as i64 as u64);
+        }
+    }
+
+    fn bool(&mut self, field: u32, value: bool) {
+        if value {
+            self.key(field, Self::VARINT);
+            self.varint(1);
+        }
+    }
+
+    fn packed_int32(&mut self, field: u32, values: &[i32]) {
+        if values.is_empty() {
+            return;
+        }
+        let mut packed = Writer::default();
+        for &value in values {
+            packed.varint(value as i64 as u64);
+        }
+        self.bytes(field, &packed.buf);
+    }
+
+    fn message(&mut self, field: u32, encode: impl FnOnce(&mut Writer)) {
+        let mut nested = Writer::default();
+        encode(&mut nested);
+        self.bytes(field, &nested.buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_encoding() {
+        let mut w = Writer::default();
+        w.int32(1, 150);
+        assert_eq!(w.buf, vec![0x08, 0x96, 0x01]);
+
+        let mut w = Writer::default();
+        w.int32(1, -1);
+        assert_eq!(w.buf.len(), 11);
+        assert_eq!(w.buf[1..10], [0xff; 9]);
+        assert_eq!(w.buf[10], 0x01);
+
+        // Default values are not written
+        let mut w = Writer::default();
+        w.int32(1, 0);
+        w.string(2, "");
+        w.bool(3, false);
+        w.packed_int32(4, &[]);
+        assert!(w.buf.is_empty());
+    }
+
+    #[test]
+    fn test_range_packing() {
+        let one_line = Range {
+            start_line: 2,
+            start_character: 4,
+            end_line: 2,
+            end_character: 9,
+        };
+        assert_eq!(one_line.packed(), vec![2, 4, 9]);
+
+        let mut w = Writer::default();
+        w.packed_int32(1, &one_line.packed());
+        assert_eq!(w.buf, vec![0x0a, 0x03, 2, 4, 9]);
+
+        let lines = Range {
+            end_line: 5,
+            ..one_line
+        };
+        assert_eq!(lines.packed(), vec![2, 4, 5, 9]);
+    }
+
+    #[test]
+    fn test_symbol_monikers() {
+        let package = Package {
+            manager: "npm",
+            name: "@acme/ui",
+        };
+        let mut descriptors = module_descriptors("src/components/button.tsx");
+        descriptors.push(Descriptor::for_symbol("class", "Button"));
+        descriptors.push(Descriptor::for_symbol("method", "render"));
+        assert_eq!(
+            symbol(Some(&package), &descriptors),
+            "semantiq npm @acme/ui . src/components/button/Button#render()."
+        );
+
+        let descriptors = vec![
+            Descriptor::Namespace("lib".to_string()),
+            Descriptor::for_symbol("constant", "MAX SIZE"),
+            Descriptor::for_symbol("module", "a`b"),
+        ];
+        assert_eq!(
+            symbol(None, &descriptors),
+            "semantiq . . . lib/`MAX SIZE`.`a``b`/"
+        );
+
+        assert_eq!(
+            module_descriptors("Makefile"),
+            vec![Descriptor::Namespace("Makefile".to_string())]
+        );
+        assert_eq!(
+            module_descriptors(".github/ci.yml"),
+            vec![
+                Descriptor::Namespace(".github".to_string()),
+                Descriptor::Namespace("ci".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_encoding() {
+        let index = Index {
+            metadata: Metadata {
+                tool_name: "semantiq".to_string(),
+                ..Default::default()
+            },
+            documents: vec![Document {
+                relative_path: "a.rs".to_string(),
+                ..Default::default()
+            }],
+        };
+        let bytes = index.to_bytes();
+        // Metadata: tool_info { name }, then the text encoding
+        assert_eq!(
+            bytes[..16],
+            [
+                0x0a, 0x0e, 0x12, 0x0a, 0x0a, 0x08, b's', b'e', b'm', b'a', b'n', b't', b'i', b'q',
+                0x20, 0x01
+            ]
+        );
+        // Document: relative_path, then the position encoding
+        assert_eq!(
+            bytes[16..],
+            [0x12, 0x08, 0x0a, 0x04, b'a', b'.', b'r', b's', 0x30, 0x01]
+        );
+    }
+}
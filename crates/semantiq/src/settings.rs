@@ -0,0 +1,244 @@
+//! Layered CLI options
+//!
+//! Every option of the CLI can also be set by an environment variable or in
+//! the `[cli]` table of `semantiq.toml`, for containers where flags are
+//! awkward to pass. An option takes its value from, in order:
+//!
+//! 1. the command-line flag
+//! 2. the environment variable: `SEMANTIQ_` and the setting name in upper
+//!    case (`SEMANTIQ_HTTP_PORT=8080`)
+//! 3. the `[cli]` table of `semantiq.toml` in the current directory, or of
+//!    the file named by `SEMANTIQ_CONFIG` (`http_port = 8080`)
+//! 4. the default of the option
+//!
+//! Setting names are the long option names in snake case. The global
+//! options and those of `serve` keep their name (`http_port`, `cors_origin`,
+//! `log_format`), `--database` is `db` for every command and the project
+//! root is `project` (`serve --project`, and the path of `index`, `init` and
+//! `init-cursor`). The options of other commands are prefixed with the
+//! command (`index_force`, `search_limit`, `query_run_limit`). Other
+//! positional arguments have no setting.
+
+use anyhow::{Context, Result, bail};
+use clap::{Arg, ArgAction, Command, CommandFactory, FromArgMatches};
+use semantiq_index::CONFIG_FILE_NAME;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the file holding the `[cli]` table
+pub const CONFIG_ENV: &str = "SEMANTIQ_CONFIG";
+
+/// Prefix of the environment variables of the options
+const ENV_PREFIX: &str = "SEMANTIQ_";
+
+/// Table of the configuration file holding option values
+const CLI_TABLE: &str = "cli";
+
+/// Option values of the configuration file, by setting name
+pub type FileSettings = BTreeMap<String, String>;
+
+/// Parse the command line, with environment variables and the
+/// configuration file as fallbacks. Exits on invalid arguments, like
+/// [`clap::Parser::parse`].
+pub fn parse<T: CommandFactory + FromArgMatches>() -> Result<T> {
+    let path = match std::env::var_os(CONFIG_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(PathBuf::from(CONFIG_FILE_NAME)).filter(|path| path.is_file()),
+    };
+    let command = match path {
+        Some(path) => layered(T::command(), &load_file_settings(&path)?)
+            .with_context(|| format!("Invalid configuration in {:?}", path))?,
+        None => layered(T::command(), &FileSettings::new())?,
+    };
+
+    let matches = command.get_matches();
+    Ok(T::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()))
+}
+
+/// Read the `[cli]` table of a configuration file
+pub fn load_file_settings(path: &Path) -> Result<FileSettings> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    parse_file_settings(&content).with_context(|| format!("Invalid configuration in {:?}", path))
+}
+
+fn parse_file_settings(content: &str) -> Result<FileSettings> {
+    let mut table: toml::Table = toml::from_str(content)?;
+    let Some(cli) = table.remove(CLI_TABLE) else {
+        return Ok(FileSettings::new());
+    };
+    let toml::Value::Table(cli) = cli else {
+        bail!("[{}] must be a table", CLI_TABLE);
+    };
+
+    cli.into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                toml::Value::String(s) => s,
+                toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                    value.to_string()
+                }
+                _ => bail!(
+                    "{}.{} must be a string, a number or a boolean",
+                    CLI_TABLE,
+                    name
+                ),
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// `command` reading each option from its environment variable, then from
+/// `file`, when the flag is not given
+pub fn layered(command: Command, file: &FileSettings) -> Result<Command> {
+    let mut names = BTreeSet::new();
+    let command = layer_command(command, &[], file, &mut names);
+
+    if let Some(unknown) = file.keys().find(|name| !names.contains(name.as_str())) {
+        bail!("Unknown setting '{}' in [{}]", unknown, CLI_TABLE);
+    }
+    Ok(command)
+}
+
+fn layer_command(
+    mut command: Command,
+    path: &[&str],
+    file: &FileSettings,
+    names: &mut BTreeSet<String>,
+) -> Command {
+    let settings: Vec<(String, String)> = command
+        .get_arguments()
+        .filter_map(|arg| Some((arg.get_id().to_string(), setting_name(arg, path)?)))
+        .collect();
+    for (id, name) in settings {
+        command = command.mut_arg(id, |arg| {
+            let arg = arg.env(format!("{}{}", ENV_PREFIX, name.to_uppercase()));
+            match file.get(&name) {
+                Some(value) => arg.default_value(value.clone()),
+                None => arg,
+            }
+        });
+        names.insert(name);
+    }
+
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        let mut sub_path = path.to_vec();
+        sub_path.push(&name);
+        command = command.mut_subcommand(&name, |sub| layer_command(sub, &sub_path, file, names));
+    }
+    command
+}
+
+/// Setting of an argument of the command at `path`, `None` for positional
+/// arguments other than the project root
+fn setting_name(arg: &Arg, path: &[&str]) -> Option<String> {
+    if matches!(arg.get_action(), ArgAction::Help | ArgAction::Version) {
+        return None;
+    }
+    let id = arg.get_id().as_str();
+    match (path, id) {
+        (_, "database") => Some("db".to_string()),
+        (["serve"], "project") | (["index" | "init" | "init-cursor"], "path") => {
+            Some("project".to_string())
+        }
+        _ if arg.is_positional() => None,
+        ([] | ["serve"], id) => Some(id.to_string()),
+        (path, id) => Some(format!("{}_{}", path.join("_").replace('-', "_"), id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cli, Commands};
+
+    fn env_of(command: &Command, path: &[&str], id: &str) -> Option<String> {
+        let mut command = command;
+        for name in path {
+            command = command.find_subcommand(name).unwrap();
+        }
+        command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id)
+            .and_then(|arg| arg.get_env())
+            .map(|env| env.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_environment_variable_names() {
+        let command = layered(Cli::command(), &FileSettings::new()).unwrap();
+        let env = |path: &[&str], id| env_of(&command, path, id);
+
+        assert_eq!(env(&[], "log_file").as_deref(), Some("SEMANTIQ_LOG_FILE"));
+        assert_eq!(env(&["serve"], "database").as_deref(), Some("SEMANTIQ_DB"));
+        assert_eq!(
+            env(&["serve"], "project").as_deref(),
+            Some("SEMANTIQ_PROJECT")
+        );
+        assert_eq!(env(&["index"], "path").as_deref(), Some("SEMANTIQ_PROJECT"));
+        assert_eq!(
+            env(&["serve"], "http_port").as_deref(),
+            Some("SEMANTIQ_HTTP_PORT")
+        );
+        assert_eq!(
+            env(&["index"], "lock_timeout").as_deref(),
+            Some("SEMANTIQ_INDEX_LOCK_TIMEOUT")
+        );
+        assert_eq!(
+            env(&["query", "run"], "limit").as_deref(),
+            Some("SEMANTIQ_QUERY_RUN_LIMIT")
+        );
+        assert_eq!(env(&["search"], "query"), None);
+    }
+
+    #[test]
+    fn test_file_settings_are_fallbacks() {
+        let file = parse_file_settings(
+            "[embeddings]\nmodel = \"minilm\"\n\n[cli]\nhttp_port = 9000\ncors_origin = \"https://example.com\"\nno_update_check = true\n",
+        )
+        .unwrap();
+        let parse = |args: &[&str]| {
+            let matches = layered(Cli::command(), &file)
+                .unwrap()
+                .try_get_matches_from(args)
+                .unwrap();
+            Cli::from_arg_matches(&matches).unwrap()
+        };
+
+        let Commands::Serve {
+            http_port,
+            cors_origin,
+            no_update_check,
+            ..
+        } = parse(&["semantiq", "serve"]).command
+        else {
+            panic!("expected serve");
+        };
+        assert_eq!(http_port, Some(9000));
+        assert_eq!(cors_origin.as_deref(), Some("https://example.com"));
+        assert!(no_update_check);
+
+        // Flags win
+        let Commands::Serve { http_port, .. } =
+            parse(&["semantiq", "serve", "--http-port", "8080"]).command
+        else {
+            panic!("expected serve");
+        };
+        assert_eq!(http_port, Some(8080));
+    }
+
+    #[test]
+    fn test_invalid_file_settings() {
+        assert!(parse_file_settings("cli = 1\n").is_err());
+        assert!(parse_file_settings("[cli]\nhttp_port = [1]\n").is_err());
+
+        let file = parse_file_settings("[cli]\nhttp_prot = 8080\n").unwrap();
+        let error = layered(Cli::command(), &file).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown setting 'http_prot' in [cli]");
+    }
+}